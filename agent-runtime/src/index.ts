@@ -6,8 +6,13 @@
 
 import { createInterface } from "readline";
 import { OpenAIProvider } from "./providers/openai.js";
+import { OpenAICompatibleProvider } from "./providers/openai-compatible.js";
 import { AnthropicProvider } from "./providers/anthropic.js";
 import { OllamaProvider } from "./providers/ollama.js";
+import { InstrumentedProvider } from "./providers/telemetry.js";
+import type { RequestTelemetry } from "./providers/telemetry.js";
+import { RecipeEngine } from "./recipes/engine.js";
+import type { RecipeStep } from "./recipes/types.js";
 import type {
   Message,
   ChatOptions,
@@ -27,6 +32,19 @@ const logger = {
 let providers: Map<string, BaseProvider> = new Map();
 let activeProvider: string | null = null;
 
+// Conversation of the latest chat request, for screening its tool results
+let activeConversationId: string | null = null;
+
+// Method of the request being handled, for telemetry
+let activeMethod = "";
+
+// Requests sent to the host, awaiting its reply
+const hostRequests: Map<
+  string,
+  { resolve: (result: any) => void; reject: (error: Error) => void }
+> = new Map();
+let hostRequestCount = 0;
+
 // JSON-RPC interface
 const rl = createInterface({
   input: process.stdin,
@@ -38,6 +56,10 @@ const rl = createInterface({
 rl.on("line", async (line: string) => {
   try {
     const message = JSON.parse(line.trim());
+    if (message.method === undefined && hostRequests.has(message.id)) {
+      handleHostReply(message);
+      return;
+    }
     await handleRequest(message);
   } catch (error) {
     logger.error("Parse error", error);
@@ -47,9 +69,10 @@ rl.on("line", async (line: string) => {
 
 // Route requests to handlers
 async function handleRequest(message: any) {
-  const { method, params, id } = message;
+  const { method, params, id, requestId } = message;
 
-  logger.debug(`Received: ${method}`);
+  logger.debug(requestId ? `Received: ${method} (request ${requestId})` : `Received: ${method}`);
+  activeMethod = method;
 
   try {
     let result: any;
@@ -63,6 +86,10 @@ async function handleRequest(message: any) {
         result = await handleChat(params);
         break;
 
+      case "compare_chat":
+        result = await handleCompareChat(params);
+        break;
+
       case "tool_call":
         result = await handleToolCall(params);
         break;
@@ -158,18 +185,35 @@ async function handleConfigureProviders(params: any) {
   return { status: "configured" };
 }
 
-// Create provider instance
-function createProvider(config: ProviderConfig) {
+// Create provider instance, reporting telemetry for each request
+function createProvider(config: ProviderConfig): BaseProvider {
+  let provider: BaseProvider;
   switch (config.type) {
     case "openai":
-      return new OpenAIProvider(config);
+      provider = new OpenAIProvider(config);
+      break;
+    case "openai-compatible":
+      provider = new OpenAICompatibleProvider(config);
+      break;
     case "anthropic":
-      return new AnthropicProvider(config);
+      provider = new AnthropicProvider(config);
+      break;
     case "ollama":
-      return new OllamaProvider(config);
+      provider = new OllamaProvider(config);
+      break;
     default:
       throw new Error(`Unknown provider type: ${config.type}`);
   }
+  return new InstrumentedProvider(provider, config, recordTelemetry);
+}
+
+// Report a provider request to the host; telemetry never fails the request
+function recordTelemetry(telemetry: RequestTelemetry) {
+  requestHost("record_telemetry", {
+    ...telemetry,
+    method: activeMethod,
+    conversationId: activeMethod === "chat" ? activeConversationId : null,
+  }).catch((e) => logger.error("Failed to record telemetry", e));
 }
 
 // Get active provider
@@ -192,23 +236,155 @@ export { getActiveProvider };
 // Handle chat request
 async function handleChat(params: any) {
   const { messages, options } = params;
+  activeConversationId = params.conversationId ?? null;
 
-  const provider = getActiveProvider();
+  // A provider chosen for the request (or its workspace) replaces the active one
+  const providerName = options?.provider && providers.has(options.provider) ? options.provider : activeProvider;
+  const provider = providerName === activeProvider ? getActiveProvider() : providers.get(providerName)!;
   const response: ChatResponse = await provider.chat(messages, options);
 
   return {
     content: response.content,
     metadata: {
-      provider: activeProvider,
+      provider: providerName,
       timestamp: new Date().toISOString(),
       usage: response.usage,
     },
   };
 }
 
+// Send the same messages to several providers concurrently; one failing
+// provider does not fail the others
+async function handleCompareChat(params: any) {
+  const { messages, options } = params;
+  const names: string[] = params.providers ?? [];
+  activeConversationId = params.conversationId ?? null;
+
+  const answers = await Promise.all(
+    names.map(async (name) => {
+      const provider = providers.get(name);
+      if (!provider) {
+        return [name, { content: "", latencyMs: 0, error: `Provider not configured: ${name}` }] as const;
+      }
+
+      // A workspace model only applies to the workspace's provider
+      const providerOptions = {
+        ...options,
+        provider: name,
+        model: name === options?.provider ? options?.model : undefined,
+      };
+      const started = Date.now();
+      try {
+        const response: ChatResponse = await provider.chat(messages, providerOptions);
+        return [name, { content: response.content, latencyMs: Date.now() - started, usage: response.usage }] as const;
+      } catch (error: any) {
+        return [name, { content: "", latencyMs: Date.now() - started, error: error?.message || String(error) }] as const;
+      }
+    }),
+  );
+
+  return { results: Object.fromEntries(answers) };
+}
+
+// Send a request to the host and wait for its reply
+function requestHost(method: string, params: any): Promise<any> {
+  const id = `host-${++hostRequestCount}`;
+  return new Promise((resolve, reject) => {
+    hostRequests.set(id, { resolve, reject });
+    console.log(JSON.stringify({ jsonrpc: "2.0", method, params, id }));
+  });
+}
+
+// Settle a pending host request
+function handleHostReply(message: any) {
+  const pending = hostRequests.get(message.id)!;
+  hostRequests.delete(message.id);
+
+  if (message.error) {
+    pending.reject(new Error(message.error.message));
+  } else {
+    pending.resolve(message.result);
+  }
+}
+
 // Handle tool call
 async function handleToolCall(params: any) {
   const { tool, args } = params;
+  const conversationId = params.conversationId ?? activeConversationId;
+  const startedAt = new Date().toISOString();
+
+  // The host applies the tool's permission policy, asking the user if needed
+  const authorization = await requestHost("authorize_tool", { tool, args, conversationId });
+  if (!authorization?.allowed) {
+    const reason = authorization?.reason ?? "denied";
+    await recordAction(tool, args, conversationId, startedAt, "denied", reason);
+    throw new Error(`Tool '${tool}' is not permitted: ${reason}`);
+  }
+
+  let screened;
+  try {
+    // The host's guardrails redact secrets and prompt injections in the result
+    const { result } = await runTool(tool, args);
+    screened = await requestHost("screen_tool_result", { tool, result, conversationId });
+  } catch (error) {
+    await recordAction(tool, args, conversationId, startedAt, "error", String(error));
+    throw error;
+  }
+  if (screened?.blocked) {
+    await recordAction(tool, args, conversationId, startedAt, "blocked", screened.reason);
+    throw new Error(`Result of tool '${tool}' withheld: ${screened.reason}`);
+  }
+  await recordAction(tool, args, conversationId, startedAt, "success");
+  return { result: screened.result };
+}
+
+// Add a tool call to the host's audit trail; auditing never fails the call
+async function recordAction(
+  tool: string,
+  args: any,
+  conversationId: string | null,
+  startedAt: string,
+  status: "success" | "error" | "denied" | "blocked",
+  error?: string
+) {
+  try {
+    await requestHost("record_action", { tool, args, conversationId, startedAt, status, error });
+  } catch (e) {
+    logger.error(`Failed to record action '${tool}'`, e);
+  }
+}
+
+// Run a permitted tool
+async function runTool(tool: string, args: any) {
+  // Shell commands run on the host, inside a permitted folder
+  if (tool === "execute_shell_command") {
+    return { result: await requestHost("execute_shell_command", args) };
+  }
+
+  // Code search runs on the host, inside a permitted folder
+  if (tool === "search_in_folder") {
+    return { result: await requestHost("search_in_folder", args) };
+  }
+
+  // Document text is extracted on the host, inside a permitted folder
+  if (tool === "extract_document_text") {
+    return { result: await requestHost("extract_document_text", args) };
+  }
+
+  // Images are recognized by Tesseract on the host, inside a permitted folder
+  if (tool === "ocr_image") {
+    return { result: await requestHost("ocr_image", args) };
+  }
+
+  // Calendar events come from the host's synced local copy
+  if (tool === "list_calendar_events") {
+    return { result: await requestHost("list_calendar_events", args) };
+  }
+
+  // Plugin tools run in the plugin's sandbox on the host
+  if (tool.startsWith("plugin_")) {
+    return { result: await requestHost("call_plugin_tool", { tool, args }) };
+  }
 
   // Simple tool execution simulation
   return {
@@ -254,6 +430,129 @@ async function handleGetTools(params: any) {
           required: ["path"],
         },
       },
+      {
+        name: "execute_shell_command",
+        description:
+          "Run a program with arguments in a permitted folder. No shell features (pipes, globs, variables) are available.",
+        inputSchema: {
+          type: "object",
+          properties: {
+            command: { type: "string", description: "Program to run" },
+            args: {
+              type: "array",
+              items: { type: "string" },
+              description: "Program arguments",
+            },
+            workingDir: {
+              type: "string",
+              description: "Folder to run the program in",
+            },
+            timeoutSecs: {
+              type: "number",
+              description: "Seconds before the program is killed",
+            },
+          },
+          required: ["command", "workingDir"],
+        },
+      },
+      {
+        name: "search_in_folder",
+        description:
+          "Search the files of a permitted folder with a regex. Returns matching lines with their path, line number and column. Respects .gitignore and skips binary files.",
+        inputSchema: {
+          type: "object",
+          properties: {
+            path: { type: "string", description: "Folder to search" },
+            pattern: { type: "string", description: "Regex to search for" },
+            options: {
+              type: "object",
+              properties: {
+                literal: {
+                  type: "boolean",
+                  description: "Treat the pattern as plain text",
+                },
+                caseInsensitive: { type: "boolean" },
+                include: {
+                  type: "array",
+                  items: { type: "string" },
+                  description: "Only search files matching these globs, e.g. *.rs",
+                },
+                exclude: {
+                  type: "array",
+                  items: { type: "string" },
+                  description: "Skip files and folders matching these globs",
+                },
+                maxResults: { type: "number" },
+                contextLines: {
+                  type: "number",
+                  description: "Lines of context around each match",
+                },
+              },
+            },
+          },
+          required: ["path", "pattern"],
+        },
+      },
+      {
+        name: "extract_document_text",
+        description:
+          "Extract the text of a PDF, DOCX or XLSX file in a permitted folder. Returns the text of each page, or the cell rows of each sheet.",
+        inputSchema: {
+          type: "object",
+          properties: {
+            path: { type: "string", description: "Absolute path of the document" },
+          },
+          required: ["path"],
+        },
+      },
+      {
+        name: "ocr_image",
+        description:
+          "Recognize the text in a PNG, JPEG, TIFF, BMP, GIF or WebP image in a permitted folder, such as a scan or screenshot.",
+        inputSchema: {
+          type: "object",
+          properties: {
+            path: { type: "string", description: "Absolute path of the image" },
+            language: {
+              type: "string",
+              description: "Tesseract language codes such as eng or eng+kor; defaults to the OCR settings",
+            },
+          },
+          required: ["path"],
+        },
+      },
+      {
+        name: "database_schema",
+        description:
+          "Describe the tables, columns, keys and indexes of a connected database. Call this before writing SQL.",
+        inputSchema: {
+          type: "object",
+          properties: {
+            connection: { type: "string", description: "Database connection name" },
+            tables: {
+              type: "array",
+              items: { type: "string" },
+              description: "Only describe these tables",
+            },
+          },
+          required: ["connection"],
+        },
+      },
+      {
+        name: "list_calendar_events",
+        description:
+          "List the user's calendar events in a time range, from the locally synced copy of their calendars. Works offline.",
+        inputSchema: {
+          type: "object",
+          properties: {
+            range: {
+              type: "string",
+              description:
+                "today, tomorrow, week, month, a day (YYYY-MM-DD) or a span of days (YYYY-MM-DD..YYYY-MM-DD). Defaults to the next 7 days",
+            },
+          },
+        },
+      },
     ],
   };
 }
@@ -266,7 +565,7 @@ async function handleShutdown() {
 
 // Execute a skill
 async function handleExecuteSkill(params: any) {
-  const { skillId, input, variables } = params;
+  const { skillId, input, variables, outputSchema } = params;
 
   logger.info(`Executing skill: ${skillId}`);
 
@@ -280,7 +579,14 @@ ${input || "No specific input provided."}
 
 ${variables ? `Variables: ${JSON.stringify(variables)}` : ""}
 
-Execute this skill and provide the result.`;
+Execute this skill and provide the result.${
+      outputSchema
+        ? `
+
+Respond with only a JSON value matching this JSON Schema, without any other text:
+${JSON.stringify(outputSchema)}`
+        : ""
+    }`;
 
     const response: ChatResponse = await provider.chat([
       { role: "user", content: skillPrompt },
@@ -307,22 +613,37 @@ Execute this skill and provide the result.`;
 // Execute a recipe
 async function handleExecuteRecipe(params: any) {
   const { recipeId, variables } = params;
+  const steps: RecipeStep[] = params.steps ?? [];
 
   logger.info(`Executing recipe: ${recipeId}`);
 
-  // For now, return a placeholder response
-  // In a full implementation, this would:
-  // 1. Load the recipe from the database
-  // 2. Execute each step using the RecipeEngine
-  // 3. Return the aggregated results
+  // Tool calls go through the host's permission policy like chat tool calls,
+  // and file steps are checked against the host's folder permissions
+  const engine = new RecipeEngine(
+    activeProvider ? getActiveProvider() : null,
+    {
+      callTool: async (tool: string, args: any) =>
+        (await handleToolCall({ tool, args })).result,
+    },
+    {
+      readFile: (path) => requestHost("read_file", { path }),
+      writeFile: (path, content, append) =>
+        requestHost("write_file", { path, content, append }),
+    }
+  );
+  const result = await engine.execute(
+    { id: recipeId, name: recipeId, version: "1.0.0", steps },
+    variables || {}
+  );
 
   return {
-    success: true,
-    result: `Recipe '${recipeId}' executed with variables: ${JSON.stringify(variables || {})}`,
+    success: result.success,
+    result: JSON.stringify(result.results),
+    error: result.error,
     metadata: {
       recipeId,
       timestamp: new Date().toISOString(),
-      steps: 0,
+      steps: steps.length,
     },
   };
 }
@@ -411,7 +732,7 @@ function initializeDefaultProvider() {
   };
 
   try {
-    const provider = new OllamaProvider(ollamaConfig);
+    const provider = createProvider(ollamaConfig);
     providers.set("ollama", provider);
     activeProvider = "ollama";
     logger.info("Auto-initialized Ollama provider as default");
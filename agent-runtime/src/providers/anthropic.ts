@@ -42,8 +42,12 @@ export class AnthropicProvider extends BaseProvider {
         'anthropic-dangerous-direct-browser-access': 'true',
       },
       body: JSON.stringify({
-        model: this.config.model,
+        model: options?.model || this.config.model,
+        // The Messages API requires max_tokens
         max_tokens: options?.maxTokens || 4096,
+        temperature: options?.temperature ?? undefined,
+        top_p: options?.topP ?? undefined,
+        stop_sequences: options?.stop ?? undefined,
         system: systemMessage?.content,
         messages: chatMessages.map(m => ({
           role: m.role === 'user' ? 'user' : 'assistant',
@@ -85,8 +89,12 @@ export class AnthropicProvider extends BaseProvider {
         'anthropic-dangerous-direct-browser-access': 'true',
       },
       body: JSON.stringify({
-        model: this.config.model,
+        model: options?.model || this.config.model,
+        // The Messages API requires max_tokens
         max_tokens: options?.maxTokens || 4096,
+        temperature: options?.temperature ?? undefined,
+        top_p: options?.topP ?? undefined,
+        stop_sequences: options?.stop ?? undefined,
         system: systemMessage?.content,
         messages: chatMessages.map(m => ({
           role: m.role === 'user' ? 'user' : 'assistant',
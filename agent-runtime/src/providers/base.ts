@@ -15,9 +15,17 @@ export interface ProviderConfig {
   enabled: boolean;
 }
 
+/** Generation parameters left unset use the provider's own defaults */
 export interface ChatOptions {
+  /** Overrides the model configured for the provider */
+  model?: string;
   maxTokens?: number;
   temperature?: number;
+  topP?: number;
+  /** Sequences that end the answer when generated */
+  stop?: string[];
+  /** Makes sampling repeatable where the provider supports it */
+  seed?: number;
   stream?: boolean;
 }
 
@@ -43,15 +43,18 @@ export class OllamaProvider extends BaseProvider {
         'Content-Type': 'application/json',
       },
       body: JSON.stringify({
-        model: this.config.model,
+        model: options?.model || this.config.model,
         messages: messages.map(m => ({
           role: m.role,
           content: m.content,
         })),
         stream: false,
         options: {
-          num_predict: options?.maxTokens || 4096,
-          temperature: options?.temperature ?? 0.7,
+          num_predict: options?.maxTokens ?? undefined,
+          temperature: options?.temperature ?? undefined,
+          top_p: options?.topP ?? undefined,
+          stop: options?.stop ?? undefined,
+          seed: options?.seed ?? undefined,
         },
       }),
     });
@@ -79,15 +82,18 @@ export class OllamaProvider extends BaseProvider {
         'Content-Type': 'application/json',
       },
       body: JSON.stringify({
-        model: this.config.model,
+        model: options?.model || this.config.model,
         messages: messages.map(m => ({
           role: m.role,
           content: m.content,
         })),
         stream: true,
         options: {
-          num_predict: options?.maxTokens || 4096,
-          temperature: options?.temperature ?? 0.7,
+          num_predict: options?.maxTokens ?? undefined,
+          temperature: options?.temperature ?? undefined,
+          top_p: options?.topP ?? undefined,
+          stop: options?.stop ?? undefined,
+          seed: options?.seed ?? undefined,
         },
       }),
     });
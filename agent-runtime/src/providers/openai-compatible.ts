@@ -0,0 +1,39 @@
+/**
+ * OpenAI-compatible Provider - LM Studio, vLLM, llama.cpp and other servers
+ * speaking the OpenAI chat completions API
+ */
+
+import { ProviderConfig } from './base.js';
+import { OpenAIProvider } from './openai.js';
+import { logger } from '../utils/logger.js';
+
+export class OpenAICompatibleProvider extends OpenAIProvider {
+  constructor(config: ProviderConfig) {
+    super(config);
+    // There is no public default endpoint; the server's URL must be given
+    this.baseUrl = (config.baseUrl || '').replace(/\/+$/, '');
+    logger.info('OpenAI-compatible provider initialized', { baseUrl: this.baseUrl, model: config.model });
+  }
+
+  protected get label(): string {
+    return 'OpenAI-compatible';
+  }
+
+  protected assertConfigured(): void {
+    if (!this.baseUrl) {
+      throw new Error('OpenAI-compatible base URL not configured');
+    }
+    if (!this.config.model) {
+      throw new Error('OpenAI-compatible model not configured');
+    }
+  }
+
+  /** Local servers mostly run without a key; none is sent when unset */
+  protected requestHeaders(): Record<string, string> {
+    const headers: Record<string, string> = { 'Content-Type': 'application/json' };
+    if (this.apiKey) {
+      headers['Authorization'] = `Bearer ${this.apiKey}`;
+    }
+    return headers;
+  }
+}
@@ -25,8 +25,8 @@ interface OpenAIStreamChoice {
 }
 
 export class OpenAIProvider extends BaseProvider {
-  private apiKey: string;
-  private baseUrl: string;
+  protected apiKey: string;
+  protected baseUrl: string;
 
   constructor(config: ProviderConfig) {
     super(config);
@@ -35,33 +35,50 @@ export class OpenAIProvider extends BaseProvider {
     logger.info('OpenAI provider initialized', { model: config.model });
   }
 
-  async chat(messages: Message[], options?: ChatOptions): Promise<ChatResponse> {
+  /** Name used in error messages */
+  protected get label(): string {
+    return 'OpenAI';
+  }
+
+  /** Throws when the provider cannot send requests */
+  protected assertConfigured(): void {
     if (!this.apiKey) {
       throw new Error('OpenAI API key not configured');
     }
+  }
 
-    logger.debug('OpenAI chat', { messageCount: messages.length, model: this.config.model });
+  protected requestHeaders(): Record<string, string> {
+    return {
+      'Content-Type': 'application/json',
+      'Authorization': `Bearer ${this.apiKey}`,
+    };
+  }
+
+  async chat(messages: Message[], options?: ChatOptions): Promise<ChatResponse> {
+    this.assertConfigured();
+
+    logger.debug(`${this.label} chat`, { messageCount: messages.length, model: this.config.model });
 
     const response = await fetch(`${this.baseUrl}/chat/completions`, {
       method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'Authorization': `Bearer ${this.apiKey}`,
-      },
+      headers: this.requestHeaders(),
       body: JSON.stringify({
-        model: this.config.model,
+        model: options?.model || this.config.model,
         messages: messages.map(m => ({
           role: m.role,
           content: m.content,
         })),
-        max_tokens: options?.maxTokens || 4096,
-        temperature: options?.temperature ?? 0.7,
+        max_tokens: options?.maxTokens ?? undefined,
+        temperature: options?.temperature ?? undefined,
+        top_p: options?.topP ?? undefined,
+        stop: options?.stop ?? undefined,
+        seed: options?.seed ?? undefined,
       }),
     });
 
     if (!response.ok) {
       const error = await response.text();
-      throw new Error(`OpenAI API error: ${error}`);
+      throw new Error(`${this.label} API error: ${error}`);
     }
 
     const data = await response.json() as OpenAIResponse;
@@ -78,31 +95,29 @@ export class OpenAIProvider extends BaseProvider {
   }
 
   async *chatStream(messages: Message[], options?: ChatOptions): AsyncIterable<string> {
-    if (!this.apiKey) {
-      throw new Error('OpenAI API key not configured');
-    }
+    this.assertConfigured();
 
     const response = await fetch(`${this.baseUrl}/chat/completions`, {
       method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'Authorization': `Bearer ${this.apiKey}`,
-      },
+      headers: this.requestHeaders(),
       body: JSON.stringify({
-        model: this.config.model,
+        model: options?.model || this.config.model,
         messages: messages.map(m => ({
           role: m.role,
           content: m.content,
         })),
-        max_tokens: options?.maxTokens || 4096,
-        temperature: options?.temperature ?? 0.7,
+        max_tokens: options?.maxTokens ?? undefined,
+        temperature: options?.temperature ?? undefined,
+        top_p: options?.topP ?? undefined,
+        stop: options?.stop ?? undefined,
+        seed: options?.seed ?? undefined,
         stream: true,
       }),
     });
 
     if (!response.ok) {
       const error = await response.text();
-      throw new Error(`OpenAI API error: ${error}`);
+      throw new Error(`${this.label} API error: ${error}`);
     }
 
     const reader = response.body?.getReader();
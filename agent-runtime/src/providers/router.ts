@@ -5,6 +5,7 @@
 import { logger } from '../utils/logger.js';
 import { BaseProvider, ProviderConfig, Message } from './base.js';
 import { OpenAIProvider } from './openai.js';
+import { OpenAICompatibleProvider } from './openai-compatible.js';
 import { AnthropicProvider } from './anthropic.js';
 import { OllamaProvider } from './ollama.js';
 
@@ -22,6 +23,9 @@ export class ProviderRouter {
         case 'openai':
           provider = new OpenAIProvider(config);
           break;
+        case 'openai-compatible':
+          provider = new OpenAICompatibleProvider(config);
+          break;
         case 'anthropic':
           provider = new AnthropicProvider(config);
           break;
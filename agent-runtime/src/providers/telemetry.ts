@@ -0,0 +1,75 @@
+/**
+ * Provider Telemetry
+ *
+ * Wraps a provider so every request, streamed or not, reports its latency,
+ * token usage and outcome.
+ */
+
+import { BaseProvider, Message, ChatOptions, ChatResponse, ProviderConfig } from './base.js';
+
+export interface RequestTelemetry {
+  provider: string;
+  model: string;
+  latencyMs: number;
+  promptTokens?: number;
+  completionTokens?: number;
+  retries: number;
+  streamed: boolean;
+  success: boolean;
+  error?: string;
+}
+
+export type TelemetrySink = (telemetry: RequestTelemetry) => void;
+
+export class InstrumentedProvider extends BaseProvider {
+  constructor(
+    private inner: BaseProvider,
+    config: ProviderConfig,
+    private sink: TelemetrySink,
+  ) {
+    super(config);
+  }
+
+  private report(
+    started: number,
+    options: ChatOptions | undefined,
+    streamed: boolean,
+    outcome: { response?: ChatResponse; error?: unknown },
+  ) {
+    const { response, error } = outcome;
+    this.sink({
+      provider: this.config.type,
+      model: options?.model || this.config.model,
+      latencyMs: Date.now() - started,
+      promptTokens: response?.usage?.promptTokens,
+      completionTokens: response?.usage?.completionTokens,
+      retries: 0,
+      streamed,
+      success: error === undefined,
+      error: error === undefined ? undefined : error instanceof Error ? error.message : String(error),
+    });
+  }
+
+  async chat(messages: Message[], options?: ChatOptions): Promise<ChatResponse> {
+    const started = Date.now();
+    try {
+      const response = await this.inner.chat(messages, options);
+      this.report(started, options, false, { response });
+      return response;
+    } catch (error) {
+      this.report(started, options, false, { error });
+      throw error;
+    }
+  }
+
+  async *chatStream(messages: Message[], options?: ChatOptions): AsyncIterable<string> {
+    const started = Date.now();
+    try {
+      yield* this.inner.chatStream(messages, options);
+      this.report(started, options, true, {});
+    } catch (error) {
+      this.report(started, options, true, { error });
+      throw error;
+    }
+  }
+}
@@ -0,0 +1,147 @@
+/**
+ * Recipe Engine Tests
+ */
+
+import { describe, it, expect, afterEach, jest } from "@jest/globals";
+import { RecipeEngine } from "./engine.js";
+import type { Recipe, RecipeHost, RecipeStep } from "./types.js";
+
+function recipe(steps: RecipeStep[], variables?: Record<string, unknown>): Recipe {
+  return { id: "test", name: "Test", version: "1.0.0", steps, variables };
+}
+
+function memoryHost(files: Record<string, string>): RecipeHost {
+  return {
+    readFile: async (path) => {
+      if (!(path in files)) throw new Error(`Path is not permitted: ${path}`);
+      return { path, content: files[path] };
+    },
+    writeFile: async (path, content, append) => {
+      files[path] = append ? (files[path] ?? "") + content : content;
+      return { path, bytes: content.length };
+    },
+  };
+}
+
+describe("RecipeEngine", () => {
+  const originalFetch = globalThis.fetch;
+
+  afterEach(() => {
+    globalThis.fetch = originalFetch;
+  });
+
+  describe("http_request steps", () => {
+    it("should template the request and branch on the response", async () => {
+      const fetchMock = jest.fn(async (_url: any, _init?: any) =>
+        new Response(JSON.stringify({ id: 42, state: "open" }), {
+          status: 201,
+          headers: { "content-type": "application/json" },
+        })
+      );
+      globalThis.fetch = fetchMock as unknown as typeof fetch;
+
+      const files: Record<string, string> = {};
+      const engine = new RecipeEngine(null, null, memoryHost(files));
+      const result = await engine.execute(
+        recipe(
+          [
+            {
+              id: "create",
+              type: "http_request",
+              name: "Create issue",
+              url: "https://api.example.com/repos/${repo}/issues",
+              headers: { Authorization: "Bearer ${token}" },
+              body: { title: "Report for ${repo}" },
+              outputVariable: "response",
+            },
+            {
+              id: "check",
+              type: "condition",
+              name: "Created",
+              condition: "${response.status} >= 200",
+              thenSteps: [
+                {
+                  id: "save",
+                  type: "file_write",
+                  name: "Save id",
+                  path: "/notes/issues.txt",
+                  content: "${response.body.id}\n",
+                  append: true,
+                },
+              ],
+            },
+          ],
+          { repo: "app", token: "secret" }
+        )
+      );
+
+      expect(result.success).toBe(true);
+      const [url, init] = fetchMock.mock.calls[0];
+      expect(url).toBe("https://api.example.com/repos/app/issues");
+      expect(init?.method).toBe("POST");
+      expect(init?.headers).toEqual({
+        Authorization: "Bearer secret",
+        "Content-Type": "application/json",
+      });
+      expect(JSON.parse(init?.body)).toEqual({ title: "Report for app" });
+      expect(files["/notes/issues.txt"]).toBe("42\n");
+    });
+
+    it("should fail on error statuses unless the step continues", async () => {
+      globalThis.fetch = (async () =>
+        new Response("Not found", { status: 404 })) as unknown as typeof fetch;
+      const step: RecipeStep = {
+        id: "get",
+        type: "http_request",
+        name: "Fetch",
+        url: "https://api.example.com/missing",
+      };
+      const engine = new RecipeEngine(null, null);
+
+      const failed = await engine.execute(recipe([step]));
+      expect(failed.success).toBe(false);
+      expect(failed.error).toContain("404");
+
+      const continued = await engine.execute(recipe([{ ...step, onError: "continue" }]));
+      expect(continued.success).toBe(true);
+
+      const local = await engine.execute(recipe([{ ...step, url: "file:///etc/passwd" }]));
+      expect(local.success).toBe(false);
+    });
+  });
+
+  describe("file and wait steps", () => {
+    it("should read files through the host", async () => {
+      const engine = new RecipeEngine(null, null, memoryHost({ "/notes/todo.md": "ship it" }));
+      const result = await engine.execute(
+        recipe([
+          { id: "wait", type: "wait", name: "Pause", durationMs: 1 },
+          {
+            id: "read",
+            type: "file_read",
+            name: "Read",
+            path: "/notes/${file}",
+            outputVariable: "todo",
+          },
+        ], { file: "todo.md" })
+      );
+      expect(result.success).toBe(true);
+      expect(result.results.todo).toBe("ship it");
+
+      const denied = await engine.execute(
+        recipe([{ id: "read", type: "file_read", name: "Read", path: "/etc/hosts" }])
+      );
+      expect(denied.error).toContain("not permitted");
+
+      const noHost = await new RecipeEngine(null, null).execute(
+        recipe([{ id: "read", type: "file_read", name: "Read", path: "/notes/todo.md" }])
+      );
+      expect(noHost.success).toBe(false);
+
+      const tooLong = await engine.execute(
+        recipe([{ id: "wait", type: "wait", name: "Pause", durationMs: 10 * 60 * 1000 }])
+      );
+      expect(tooLong.success).toBe(false);
+    });
+  });
+});
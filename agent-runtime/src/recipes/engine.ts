@@ -3,7 +3,9 @@
  */
 
 import type {
+  HttpStepResult,
   Recipe,
+  RecipeHost,
   RecipeStep,
   RecipeExecutionContext,
   RecipeResult,
@@ -14,10 +16,12 @@ import { logger } from '../utils/logger.js';
 export class RecipeEngine {
   private provider: any;
   private mcpClient: any;
+  private host?: RecipeHost;
 
-  constructor(provider: any, mcpClient: any) {
+  constructor(provider: any, mcpClient: any, host?: RecipeHost) {
     this.provider = provider;
     this.mcpClient = mcpClient;
+    this.host = host;
   }
 
   async execute(
@@ -89,6 +93,18 @@ export class RecipeEngine {
         case 'parallel':
           result = await this.executeParallelStep(step, context);
           break;
+        case 'http_request':
+          result = await this.executeHttpStep(step, context);
+          break;
+        case 'file_read':
+          result = await this.executeFileReadStep(step, context);
+          break;
+        case 'file_write':
+          result = await this.executeFileWriteStep(step, context);
+          break;
+        case 'wait':
+          await this.executeWaitStep(step);
+          return; // No output variable for wait
         default:
           throw new Error(`Unknown step type: ${step.type}`);
       }
@@ -236,27 +252,155 @@ export class RecipeEngine {
     return await Promise.all(promises);
   }
 
+  private async executeHttpStep(
+    step: RecipeStep,
+    context: RecipeExecutionContext
+  ): Promise<HttpStepResult> {
+    if (!step.url) {
+      throw new Error(`HTTP step ${step.name} has no url`);
+    }
+
+    const url = this.substituteVariables(step.url, context);
+    if (!/^https?:\/\//i.test(url)) {
+      throw new Error(`HTTP step ${step.name} needs an http(s) url, got: ${url}`);
+    }
+    const method = step.method ?? (step.body === undefined ? 'GET' : 'POST');
+    const headers: Record<string, string> = {};
+    for (const [key, value] of Object.entries(step.headers ?? {})) {
+      headers[key] = this.substituteVariables(value, context);
+    }
+
+    let body: string | undefined;
+    if (typeof step.body === 'string') {
+      body = this.substituteVariables(step.body, context);
+    } else if (step.body !== undefined) {
+      body = JSON.stringify(this.substituteDeep(step.body, context));
+      if (!Object.keys(headers).some((h) => h.toLowerCase() === 'content-type')) {
+        headers['Content-Type'] = 'application/json';
+      }
+    }
+
+    const response = await fetch(url, {
+      method,
+      headers,
+      body,
+      signal: AbortSignal.timeout(RECIPE_LIMITS.maxStepTimeout),
+    });
+    const text = await response.text();
+    let parsed: unknown = text;
+    if (response.headers.get('content-type')?.includes('json')) {
+      try {
+        parsed = JSON.parse(text);
+      } catch {
+        // Keep the raw text of a malformed JSON body
+      }
+    }
+
+    // Error statuses fail the step so `onError` applies
+    if (!response.ok) {
+      throw new Error(`${method} ${url} failed with status ${response.status}: ${text.slice(0, 200)}`);
+    }
+
+    return {
+      status: response.status,
+      headers: Object.fromEntries(response.headers.entries()),
+      body: parsed,
+    };
+  }
+
+  private async executeFileReadStep(
+    step: RecipeStep,
+    context: RecipeExecutionContext
+  ): Promise<string> {
+    if (!step.path) {
+      throw new Error(`File read step ${step.name} has no path`);
+    }
+    if (!this.host) {
+      throw new Error(`File steps need a host to check folder permissions`);
+    }
+
+    const { content } = await this.host.readFile(this.substituteVariables(step.path, context));
+    return content;
+  }
+
+  private async executeFileWriteStep(
+    step: RecipeStep,
+    context: RecipeExecutionContext
+  ): Promise<{ path: string; bytes: number }> {
+    if (!step.path || step.content === undefined) {
+      throw new Error(`File write step ${step.name} needs a path and content`);
+    }
+    if (!this.host) {
+      throw new Error(`File steps need a host to check folder permissions`);
+    }
+
+    return await this.host.writeFile(
+      this.substituteVariables(step.path, context),
+      this.substituteVariables(step.content, context),
+      step.append ?? false
+    );
+  }
+
+  private async executeWaitStep(step: RecipeStep): Promise<void> {
+    const duration = step.durationMs ?? 0;
+    if (!Number.isFinite(duration) || duration < 0 || duration > RECIPE_LIMITS.maxStepTimeout) {
+      throw new Error(
+        `Wait step ${step.name} must wait between 0 and ${RECIPE_LIMITS.maxStepTimeout} ms`
+      );
+    }
+    await new Promise((resolve) => setTimeout(resolve, duration));
+  }
+
   private substituteVariables(
     template: string,
     context: RecipeExecutionContext
   ): string {
-    return template.replace(/\$\{(\w+)\}/g, (_, key) => {
+    return template.replace(/\$\{([\w.]+)\}/g, (_, key) => {
       const value = this.resolveVariable(key, context);
-      return String(value);
+      return typeof value === 'object' && value !== null
+        ? JSON.stringify(value)
+        : String(value);
     });
   }
 
+  /** Substitute variables in every string of a JSON value */
+  private substituteDeep(value: unknown, context: RecipeExecutionContext): unknown {
+    if (typeof value === 'string') {
+      return this.substituteVariables(value, context);
+    }
+    if (Array.isArray(value)) {
+      return value.map((item) => this.substituteDeep(item, context));
+    }
+    if (typeof value === 'object' && value !== null) {
+      return Object.fromEntries(
+        Object.entries(value).map(([key, item]) => [key, this.substituteDeep(item, context)])
+      );
+    }
+    return value;
+  }
+
+  /** Resolve a variable, following dotted paths into objects such as `response.body.id` */
   private resolveVariable(
     key: string,
     context: RecipeExecutionContext
   ): unknown {
-    if (key in context.variables) {
-      return context.variables[key];
+    const [name, ...path] = key.split('.');
+    let value: unknown;
+    if (name in context.variables) {
+      value = context.variables[name];
+    } else if (context.stepResults.has(name)) {
+      value = context.stepResults.get(name);
+    } else {
+      throw new Error(`Variable not found: ${key}`);
     }
-    if (context.stepResults.has(key)) {
-      return context.stepResults.get(key);
+
+    for (const segment of path) {
+      if (typeof value !== 'object' || value === null || !(segment in value)) {
+        throw new Error(`Variable not found: ${key}`);
+      }
+      value = (value as Record<string, unknown>)[segment];
     }
-    throw new Error(`Variable not found: ${key}`);
+    return value;
   }
 
   private evaluateCondition(
@@ -264,15 +408,33 @@ export class RecipeEngine {
     context: RecipeExecutionContext
   ): boolean {
     // Simple condition evaluation
-    // Supports: ${variable} == "value", ${variable} != "value"
-    const equalMatch = condition.match(/\$\{(\w+)\}\s*==\s*"([^"]+)"/);
+    // Supports: ${variable} == "value", ${variable} != "value", and numeric
+    // comparisons such as ${response.status} >= 400
+    const numericMatch = condition.match(/\$\{([\w.]+)\}\s*(>=|<=|>|<)\s*(-?\d+(?:\.\d+)?)/);
+    if (numericMatch) {
+      const [, varName, operator, bound] = numericMatch;
+      const actual = Number(this.resolveVariable(varName, context));
+      const expected = Number(bound);
+      switch (operator) {
+        case '>=':
+          return actual >= expected;
+        case '<=':
+          return actual <= expected;
+        case '>':
+          return actual > expected;
+        default:
+          return actual < expected;
+      }
+    }
+
+    const equalMatch = condition.match(/\$\{([\w.]+)\}\s*==\s*"([^"]+)"/);
     if (equalMatch) {
       const [, varName, expectedValue] = equalMatch;
       const actualValue = this.resolveVariable(varName, context);
       return String(actualValue) === expectedValue;
     }
 
-    const notEqualMatch = condition.match(/\$\{(\w+)\}\s*!=\s*"([^"]+)"/);
+    const notEqualMatch = condition.match(/\$\{([\w.]+)\}\s*!=\s*"([^"]+)"/);
     if (notEqualMatch) {
       const [, varName, expectedValue] = notEqualMatch;
       const actualValue = this.resolveVariable(varName, context);
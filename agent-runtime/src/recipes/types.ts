@@ -13,7 +13,16 @@ export interface Recipe {
 
 export interface RecipeStep {
   id: string;
-  type: 'prompt' | 'tool_call' | 'condition' | 'loop' | 'parallel';
+  type:
+    | 'prompt'
+    | 'tool_call'
+    | 'condition'
+    | 'loop'
+    | 'parallel'
+    | 'http_request'
+    | 'file_read'
+    | 'file_write'
+    | 'wait';
   name: string;
   description?: string;
 
@@ -36,6 +45,20 @@ export interface RecipeStep {
   // For 'parallel' type
   parallelSteps?: RecipeStep[];
 
+  // For 'http_request' type; url, header values and body are templated
+  method?: 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE' | 'HEAD';
+  url?: string;
+  headers?: Record<string, string>;
+  body?: string | Record<string, unknown>;
+
+  // For 'file_read' and 'file_write' types; the host checks folder permissions
+  path?: string;
+  content?: string;
+  append?: boolean;
+
+  // For 'wait' type
+  durationMs?: number;
+
   // Common
   outputVariable?: string;
   onError?: 'continue' | 'abort' | 'retry';
@@ -50,6 +73,20 @@ export interface RecipeExecutionContext {
   depth: number;
 }
 
+/** Host operations for steps with side effects outside the runtime */
+export interface RecipeHost {
+  readFile(path: string): Promise<{ path: string; content: string }>;
+  writeFile(path: string, content: string, append: boolean): Promise<{ path: string; bytes: number }>;
+}
+
+/** Result of an 'http_request' step */
+export interface HttpStepResult {
+  status: number;
+  headers: Record<string, string>;
+  /** Parsed when the response is JSON */
+  body: unknown;
+}
+
 export interface RecipeResult {
   success: boolean;
   results: Record<string, unknown>;
@@ -2,6 +2,7 @@
 //!
 //! Commands for multimodal processing, context management, and agent orchestration.
 
+use crate::error::AppError;
 use tauri::State;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -48,49 +49,51 @@ impl Default for AgentState {
 
 /// Process multimodal input
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_multimodal_process(
     state: State<'_, Arc<AgentState>>,
     input_type: String,
     text: Option<String>,
     image_data: Option<Vec<u8>>,
     image_format: Option<String>,
-) -> Result<MultimodalResult, String> {
+) -> Result<MultimodalResult, AppError> {
     let processor = state.multimodal_processor.lock().await;
 
     let input = match input_type.as_str() {
         "text" => InputType::Text(text.unwrap_or_default()),
         "image" => {
-            let data = image_data.ok_or("Image data required")?;
+            let data = image_data.ok_or_else(|| AppError::invalid_input("Image data required"))?;
             let format = match image_format.as_deref() {
                 Some("png") => ImageFormat::Png,
                 Some("jpeg") | Some("jpg") => ImageFormat::Jpeg,
                 Some("gif") => ImageFormat::Gif,
                 Some("webp") => ImageFormat::WebP,
                 Some("bmp") => ImageFormat::Bmp,
-                _ => return Err("Invalid image format".to_string()),
+                _ => return Err(AppError::invalid_input("Invalid image format")),
             };
             InputType::Image { data, format }
         }
         "mixed" => {
-            let txt = text.ok_or("Text required for mixed input")?;
+            let txt = text.ok_or_else(|| AppError::invalid_input("Text required for mixed input"))?;
             InputType::Mixed {
                 text: txt,
                 images: vec![],
             }
         }
-        _ => return Err("Invalid input type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid input type")),
     };
 
-    processor.process(&input)
+    Ok(processor.process(&input)?)
 }
 
 /// Analyze image data
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_analyze_image(
     state: State<'_, Arc<AgentState>>,
     image_data: Vec<u8>,
     format: String,
-) -> Result<ImageAnalysis, String> {
+) -> Result<ImageAnalysis, AppError> {
     let processor = state.multimodal_processor.lock().await;
     let image_format = match format.as_str() {
         "png" => ImageFormat::Png,
@@ -98,15 +101,13 @@ pub async fn agent_analyze_image(
         "gif" => ImageFormat::Gif,
         "webp" => ImageFormat::WebP,
         "bmp" => ImageFormat::Bmp,
-        _ => return Err("Invalid image format".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid image format")),
     };
 
-    processor.process_image(&image_data, &image_format)
-        .map_err(|e| e.to_string())
-        .and_then(|r| match r {
-            MultimodalResult::Image(analysis) => Ok(analysis),
-            _ => Err("Expected image analysis result".to_string()),
-        })
+    match processor.process_image(&image_data, &image_format)? {
+        MultimodalResult::Image(analysis) => Ok(analysis),
+        _ => Err(AppError::Internal("Expected image analysis result".to_string())),
+    }
 }
 
 // ============================================================================
@@ -115,13 +116,14 @@ pub async fn agent_analyze_image(
 
 /// Add a message to context
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_add_message(
     state: State<'_, Arc<AgentState>>,
     role: String,
     content: String,
     priority: Option<String>,
     token_count: Option<usize>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut manager = state.context_manager.lock().await;
 
     let role = match role.as_str() {
@@ -129,7 +131,7 @@ pub async fn agent_context_add_message(
         "user" => MessageRole::User,
         "assistant" => MessageRole::Assistant,
         "tool" => MessageRole::Tool,
-        _ => return Err("Invalid role".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid role")),
     };
 
     let priority = match priority.as_deref() {
@@ -155,9 +157,10 @@ pub async fn agent_context_add_message(
 
 /// Get all messages in context
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_get_messages(
     state: State<'_, Arc<AgentState>>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let manager = state.context_manager.lock().await;
     let messages = manager.get_messages_owned();
 
@@ -177,9 +180,10 @@ pub async fn agent_context_get_messages(
 
 /// Clear context
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_clear(
     state: State<'_, Arc<AgentState>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut manager = state.context_manager.lock().await;
     manager.clear();
     Ok(())
@@ -187,27 +191,30 @@ pub async fn agent_context_clear(
 
 /// Get context token count
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_token_count(
     state: State<'_, Arc<AgentState>>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let manager = state.context_manager.lock().await;
     Ok(manager.token_count())
 }
 
 /// Check if context is near limit
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_is_near_limit(
     state: State<'_, Arc<AgentState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let manager = state.context_manager.lock().await;
     Ok(manager.is_near_limit())
 }
 
 /// Compress context
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_compress(
     state: State<'_, Arc<AgentState>>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let mut manager = state.context_manager.lock().await;
     let result = manager.compress();
     Ok(serde_json::json!({
@@ -220,12 +227,13 @@ pub async fn agent_context_compress(
 
 /// Set compression strategy
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_context_set_strategy(
     state: State<'_, Arc<AgentState>>,
     strategy: String,
     min_tokens: Option<usize>,
     target_ratio: Option<f32>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let _manager = state.context_manager.lock().await;
 
     let compression_strategy = match strategy.as_str() {
@@ -233,7 +241,7 @@ pub async fn agent_context_set_strategy(
         "summarize" => CompressionStrategy::Summarize,
         "priority_only" => CompressionStrategy::PriorityOnly,
         "hybrid" => CompressionStrategy::Hybrid,
-        _ => return Err("Invalid compression strategy".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid compression strategy")),
     };
 
     let _config = CompressorConfig {
@@ -247,12 +255,51 @@ pub async fn agent_context_set_strategy(
     Ok(())
 }
 
+/// Keep a conversation's pinned messages in context through compression
+///
+/// Replaces the retained set with the conversation's pins; returns how many
+/// messages are retained.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn agent_context_retain_pinned(
+    state: State<'_, Arc<AgentState>>,
+    db: State<'_, crate::db::DbState>,
+    session: State<'_, std::sync::Mutex<crate::security::SensitiveSession>>,
+    credentials: State<'_, std::sync::Mutex<crate::security::CredentialManager>>,
+    conversation_id: String,
+) -> Result<usize, AppError> {
+    let pinned = crate::conversations::pins::load_pinned(&db, &session, &credentials, &conversation_id)?;
+
+    let retained: Vec<Message> = pinned
+        .into_iter()
+        .map(|pin| Message {
+            role: match pin.role.as_str() {
+                "system" => MessageRole::System,
+                "assistant" => MessageRole::Assistant,
+                _ => MessageRole::User,
+            },
+            token_count: pin.content.split_whitespace().count(),
+            content: pin.content,
+            priority: MessagePriority::Critical,
+            timestamp: chrono::DateTime::parse_from_rfc3339(&pin.created_at)
+                .map(|t| t.timestamp())
+                .unwrap_or_default(),
+        })
+        .collect();
+    let count = retained.len();
+
+    let mut manager = state.context_manager.lock().await;
+    manager.set_retained(retained);
+    Ok(count)
+}
+
 // ============================================================================
 // Orchestrator Commands
 // ============================================================================
 
 /// Add a task to the orchestrator queue
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_orchestrator_add_task(
     state: State<'_, Arc<AgentState>>,
     id: String,
@@ -261,7 +308,7 @@ pub async fn agent_orchestrator_add_task(
     data: Option<serde_json::Value>,
     priority: Option<String>,
     timeout_seconds: Option<u64>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let orchestrator = state.orchestrator.lock().await;
 
     let agent_type = match agent_type.as_str() {
@@ -302,9 +349,10 @@ pub async fn agent_orchestrator_add_task(
 
 /// Execute all pending tasks
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_orchestrator_execute_all(
     state: State<'_, Arc<AgentState>>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let orchestrator = state.orchestrator.lock().await;
     let result = orchestrator.execute_all().await;
     Ok(serde_json::json!({
@@ -317,18 +365,20 @@ pub async fn agent_orchestrator_execute_all(
 
 /// Get orchestrator queue length
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_orchestrator_queue_length(
     state: State<'_, Arc<AgentState>>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let orchestrator = state.orchestrator.lock().await;
     Ok(orchestrator.queue_length().await)
 }
 
 /// Clear completed results
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_orchestrator_clear_completed(
     state: State<'_, Arc<AgentState>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let orchestrator = state.orchestrator.lock().await;
     orchestrator.clear_completed().await;
     Ok(())
@@ -235,6 +235,8 @@ pub trait MemoryStore: Send + Sync {
 pub struct ContextManager {
     /// Short-term memory (recent messages)
     short_term: VecDeque<Message>,
+    /// Pinned messages, kept through every compression
+    retained: Vec<Message>,
     /// Long-term memory store
     long_term: Option<Arc<dyn MemoryStore>>,
     /// Context compressor
@@ -253,6 +255,7 @@ impl ContextManager {
     ) -> Self {
         Self {
             short_term: VecDeque::new(),
+            retained: Vec::new(),
             long_term,
             compressor: ContextCompressor::with_defaults(),
             max_tokens,
@@ -276,25 +279,45 @@ impl ContextManager {
         }
     }
     
-    /// Get all messages in context
+    /// Replace the retained set, such as with a conversation's pinned messages
+    pub fn set_retained(&mut self, messages: Vec<Message>) {
+        self.retained = messages;
+        self.current_tokens = self.retained_tokens() + self.short_term.iter().map(|m| m.token_count).sum::<usize>();
+
+        if self.current_tokens > self.max_tokens {
+            self.compress();
+        }
+    }
+
+    fn retained_tokens(&self) -> usize {
+        self.retained.iter().map(|m| m.token_count).sum()
+    }
+    
+    /// Get all messages in context, retained messages first
     pub fn get_messages(&self) -> Vec<&Message> {
-        self.short_term.iter().collect()
+        self.retained.iter().chain(self.short_term.iter()).collect()
     }
     
     /// Get messages owned (for processing)
     pub fn get_messages_owned(&self) -> Vec<Message> {
-        self.short_term.iter().cloned().collect()
+        self.retained.iter().chain(self.short_term.iter()).cloned().collect()
     }
     
-    /// Compress context to fit within limits
+    /// Compress context to fit within limits; retained messages are never
+    /// removed, so only the budget left after them is compressed into
     pub fn compress(&mut self) -> CompressionResult {
+        let retained_tokens = self.retained_tokens();
         let mut messages: Vec<Message> = self.short_term.drain(..).collect();
-        let result = self.compressor.compress(&mut messages, self.max_tokens);
+        let mut result = self
+            .compressor
+            .compress(&mut messages, self.max_tokens.saturating_sub(retained_tokens));
         
         self.short_term.clear();
         for msg in messages {
             self.short_term.push_back(msg);
         }
+        result.original_tokens += retained_tokens;
+        result.compressed_tokens += retained_tokens;
         self.current_tokens = result.compressed_tokens;
         
         result
@@ -303,6 +326,7 @@ impl ContextManager {
     /// Clear all context
     pub fn clear(&mut self) {
         self.short_term.clear();
+        self.retained.clear();
         self.current_tokens = 0;
     }
     
@@ -397,6 +421,22 @@ mod tests {
         assert!(MessagePriority::Normal > MessagePriority::Low);
     }
 
+    #[test]
+    fn test_retained_messages_survive_compression() {
+        let mut manager = ContextManager::new(None, 2000);
+        manager.set_retained(vec![make_message("pinned decision about the schema", MessagePriority::Critical)]);
+        let filler = "word ".repeat(300);
+        for _ in 0..10 {
+            manager.add_message(make_message(filler.trim(), MessagePriority::Low));
+        }
+
+        let messages = manager.get_messages();
+        assert_eq!(messages[0].content, "pinned decision about the schema");
+        assert!(messages.len() < 11);
+        assert!(manager.token_count() <= 2000);
+        assert_eq!(manager.token_count(), messages.iter().map(|m| m.token_count).sum::<usize>());
+    }
+
     #[test]
     fn test_is_near_limit() {
         let mut manager = ContextManager::new(None, 100);
@@ -4,10 +4,12 @@
 //! - Multimodal input processing (text, image)
 //! - Context management and compression
 //! - Sub-agent orchestration
+//! - Multi-agent task decomposition
 
 pub mod multimodal;
 pub mod context;
 pub mod orchestrator;
+pub mod pipeline;
 pub mod commands;
 
 pub use multimodal::{MultimodalProcessor, InputType, ImageAnalysis};
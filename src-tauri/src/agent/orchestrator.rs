@@ -2,12 +2,17 @@
 //!
 //! Provides task distribution, parallel execution, and result aggregation
 //! for coordinating multiple specialized agents.
+//!
+//! Sub-agents stored in the `sub_agents` table are run by `SubAgentExecutor`:
+//! each task gets its own agent runtime session configured with the agent's
+//! system prompt and tools, at most a few run at once, and progress is
+//! emitted on `sub-agent://progress`.
 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 
 /// Agent type for specialization
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -311,6 +316,274 @@ impl AgentOrchestrator {
     }
 }
 
+// ============================================================================
+// Sidecar-backed sub-agent execution
+// ============================================================================
+
+/// Event carrying sub-agent progress
+pub const PROGRESS_EVENT: &str = "sub-agent://progress";
+
+/// Sub-agent tasks run at once by default
+pub const DEFAULT_SUB_AGENT_CONCURRENCY: usize = 3;
+
+/// Task time limit when the agent config does not set one
+const DEFAULT_SUB_AGENT_TIMEOUT_SECS: u64 = 600;
+
+/// Per-agent settings stored as JSON in `sub_agents.config`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SubAgentConfig {
+    /// Provider to use instead of the active one
+    pub provider: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u32>,
+    pub timeout_seconds: Option<u64>,
+}
+
+/// A sub-agent definition loaded from the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubAgentProfile {
+    pub id: String,
+    pub name: String,
+    pub role: String,
+    pub system_prompt: Option<String>,
+    pub tools: Vec<String>,
+    pub config: SubAgentConfig,
+}
+
+impl SubAgentProfile {
+    /// Load a sub-agent by id
+    pub fn load(conn: &rusqlite::Connection, id: &str) -> Result<Self, String> {
+        conn.query_row(
+            "SELECT id, name, role, system_prompt, tools, config FROM sub_agents WHERE id = ?1",
+            [id],
+            |row| {
+                let tools: String = row.get(4)?;
+                let config: Option<String> = row.get(5)?;
+                Ok(Self {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    role: row.get(2)?,
+                    system_prompt: row.get(3)?,
+                    // Malformed JSON falls back to defaults rather than blocking the agent
+                    tools: serde_json::from_str(&tools).unwrap_or_default(),
+                    config: config
+                        .and_then(|c| serde_json::from_str(&c).ok())
+                        .unwrap_or_default(),
+                })
+            },
+        )
+        .map_err(|e| format!("Sub-agent {} not found: {}", id, e))
+    }
+
+    /// System prompt for the agent's session
+    fn session_prompt(&self) -> String {
+        let mut prompt = match self.system_prompt.as_deref().filter(|p| !p.trim().is_empty()) {
+            Some(p) => p.to_string(),
+            None => format!("You are {}, a {} agent.", self.name, self.role),
+        };
+        if !self.tools.is_empty() {
+            prompt.push_str(&format!("\n\nTools available to you: {}.", self.tools.join(", ")));
+        }
+        prompt
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_seconds.unwrap_or(DEFAULT_SUB_AGENT_TIMEOUT_SECS))
+    }
+}
+
+/// Progress update emitted on `PROGRESS_EVENT`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubAgentProgress {
+    pub agent_id: String,
+    /// queued, running, completed or failed
+    pub status: String,
+    pub message: Option<String>,
+    pub timestamp: String,
+}
+
+/// Run one task in a fresh agent runtime session (blocking)
+fn run_session(
+    profile: &SubAgentProfile,
+    task: &str,
+    provider_config: Option<serde_json::Value>,
+) -> Result<String, String> {
+    let mut session = crate::sidecar::SidecarProcess::start()?;
+    if let Some(config) = provider_config {
+        session.call("configure_providers", config)?;
+    }
+
+    let result = session.call(
+        "chat",
+        serde_json::json!({
+            "messages": [
+                { "role": "system", "content": profile.session_prompt() },
+                { "role": "user", "content": task },
+            ],
+            "options": {
+                "provider": profile.config.provider,
+                "temperature": profile.config.temperature,
+                "maxTokens": profile.config.max_tokens,
+                "tools": profile.tools,
+            },
+        }),
+    )?;
+
+    Ok(result
+        .get("content")
+        .and_then(|c| c.as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Store a task outcome on the sub-agent row
+pub fn record_sub_agent_result(
+    conn: &rusqlite::Connection,
+    agent_id: &str,
+    outcome: &Result<String, String>,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let (status, result, error) = match outcome {
+        Ok(output) => ("completed", Some(output.as_str()), None),
+        Err(e) => ("failed", None, Some(e.as_str())),
+    };
+    conn.execute(
+        "UPDATE sub_agents SET status = ?1, result = ?2, error = ?3, completed_at = ?4 WHERE id = ?5",
+        rusqlite::params![status, result, error, now, agent_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Runs sub-agent tasks in their own agent runtime sessions
+pub struct SubAgentExecutor {
+    semaphore: Arc<Semaphore>,
+    running: Arc<StdMutex<HashMap<String, tokio::task::AbortHandle>>>,
+}
+
+impl SubAgentExecutor {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            running: Arc::new(StdMutex::new(HashMap::new())),
+        }
+    }
+
+    /// Run a task for a sub-agent, waiting for a free slot first
+    ///
+    /// The session is killed if it outlives the agent's timeout.
+    pub async fn run(
+        &self,
+        profile: SubAgentProfile,
+        task: String,
+        provider_config: Option<serde_json::Value>,
+    ) -> Result<String, String> {
+        let _permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let timeout = profile.timeout();
+        let session = tokio::task::spawn_blocking(move || run_session(&profile, &task, provider_config));
+        match tokio::time::timeout(timeout, session).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(format!("Sub-agent task failed: {}", e)),
+            Err(_) => Err(format!("Sub-agent timed out after {} s", timeout.as_secs())),
+        }
+    }
+
+    /// Start an assigned task in the background, recording the outcome in
+    /// `sub_agents` and emitting progress events
+    pub fn spawn(&self, app: tauri::AppHandle, agent_id: String, task: String) {
+        use tauri::{Emitter, Manager};
+
+        let emit = {
+            let app = app.clone();
+            let agent_id = agent_id.clone();
+            move |status: &str, message: Option<String>| {
+                let progress = SubAgentProgress {
+                    agent_id: agent_id.clone(),
+                    status: status.to_string(),
+                    message,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                };
+                if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+                    tracing::warn!("Failed to emit sub-agent progress: {}", e);
+                }
+            }
+        };
+
+        let executor = Self {
+            semaphore: self.semaphore.clone(),
+            running: self.running.clone(),
+        };
+        let task_agent_id = agent_id.clone();
+
+        emit("queued", None);
+        let handle = tokio::spawn(async move {
+            let profile = {
+                let db = app.state::<crate::db::DbState>();
+                let conn = db.conn.lock().map_err(|e| e.to_string());
+                conn.and_then(|conn| SubAgentProfile::load(&conn, &task_agent_id))
+            };
+            let provider_config = app
+                .state::<StdMutex<crate::sidecar::SidecarState>>()
+                .lock()
+                .ok()
+                .and_then(|state| state.provider_config());
+
+            emit("running", None);
+            let outcome = match profile {
+                Ok(profile) => executor.run(profile, task, provider_config).await,
+                Err(e) => Err(e),
+            };
+
+            let db = app.state::<crate::db::DbState>();
+            let recorded = db
+                .conn
+                .lock()
+                .map_err(|e| e.to_string())
+                .and_then(|conn| record_sub_agent_result(&conn, &task_agent_id, &outcome));
+            if let Err(e) = recorded {
+                tracing::error!("Failed to record sub-agent {} result: {}", task_agent_id, e);
+            }
+
+            match &outcome {
+                Ok(output) => emit("completed", Some(output.clone())),
+                Err(e) => emit("failed", Some(e.clone())),
+            }
+            if let Ok(mut running) = executor.running.lock() {
+                running.remove(&task_agent_id);
+            }
+        });
+
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(agent_id, handle.abort_handle());
+        }
+    }
+
+    /// Stop a sub-agent's running task, returning false if none was running
+    pub fn cancel(&self, agent_id: &str) -> bool {
+        let handle = self.running.lock().ok().and_then(|mut r| r.remove(agent_id));
+        match handle {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for SubAgentExecutor {
+    fn default() -> Self {
+        Self::new(DEFAULT_SUB_AGENT_CONCURRENCY)
+    }
+}
+
 /// Mock sub-agent for testing
 pub struct MockSubAgent {
     agent_type: AgentType,
@@ -342,6 +615,7 @@ impl SubAgent for MockSubAgent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::test_support::test_conn;
 
     #[test]
     fn test_task_queue_priority() {
@@ -404,6 +678,31 @@ mod tests {
         assert_eq!(aggregated.failed, 1);
     }
 
+    #[test]
+    fn test_sub_agent_profile_load() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO sub_agents (id, name, role, system_prompt, tools, config)
+             VALUES ('a1', 'Reviewer', 'code_reviewer', '', '[\"read_file\"]', '{\"timeoutSeconds\": 30}')",
+            [],
+        )
+        .unwrap();
+
+        let profile = SubAgentProfile::load(&conn, "a1").unwrap();
+        assert_eq!(profile.tools, vec!["read_file"]);
+        assert_eq!(profile.timeout(), Duration::from_secs(30));
+        assert!(profile.session_prompt().starts_with("You are Reviewer"));
+        assert!(profile.session_prompt().ends_with("Tools available to you: read_file."));
+
+        record_sub_agent_result(&conn, "a1", &Err("boom".to_string())).unwrap();
+        let (status, error): (String, String) = conn
+            .query_row("SELECT status, error FROM sub_agents WHERE id = 'a1'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((status.as_str(), error.as_str()), ("failed", "boom"));
+    }
+
     #[tokio::test]
     async fn test_orchestrator_single_task() {
         let mut orchestrator = AgentOrchestrator::new(2);
@@ -0,0 +1,578 @@
+//! Multi-Agent Task Decomposition
+//!
+//! `orchestrate_task` hands a goal to a planner agent, which splits it into
+//! subtasks for the roles of the selected sub-agents. Subtasks run through the
+//! `SubAgentExecutor` as soon as the subtasks they depend on have finished,
+//! receiving those results as context. The planner then combines everything
+//! into one answer. Each run, including its plan and every subtask outcome,
+//! is stored in `orchestration_runs`.
+
+use super::orchestrator::{SubAgentConfig, SubAgentExecutor, SubAgentProfile};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Event carrying the latest state of a run
+pub const RUN_EVENT: &str = "orchestration://run";
+
+/// Subtasks accepted from a single plan
+const MAX_PLAN_STEPS: usize = 12;
+
+/// Runs returned when the caller does not set a limit
+const DEFAULT_RUN_LIMIT: u32 = 50;
+
+/// Role of the sub-agent that plans and aggregates, if one is selected
+const PLANNER_ROLE: &str = "planner";
+
+/// One subtask in a run's plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationStep {
+    pub index: usize,
+    pub role: String,
+    pub task: String,
+    /// Indexes of steps whose results this step needs
+    #[serde(default)]
+    pub depends_on: Vec<usize>,
+    pub agent_id: Option<String>,
+    /// pending, running, completed, failed or skipped
+    pub status: String,
+    pub result: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A recorded orchestration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationRun {
+    pub id: String,
+    pub goal: String,
+    pub agent_ids: Vec<String>,
+    /// planning, running, completed or failed
+    pub status: String,
+    pub plan: Vec<OrchestrationStep>,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// Subtask as written by the planner
+#[derive(Debug, Deserialize)]
+struct PlannedStep {
+    role: String,
+    task: String,
+    #[serde(default, alias = "dependsOn")]
+    depends_on: Vec<usize>,
+}
+
+/// Normalize a role name for matching, e.g. "Code Reviewer" -> "code_reviewer"
+fn normalize_role(role: &str) -> String {
+    role.trim()
+        .to_lowercase()
+        .replace([' ', '-'], "_")
+}
+
+fn planning_prompt(goal: &str, roles: &[String]) -> String {
+    format!(
+        "Break the goal below into subtasks for a team of agents.\n\
+         Available roles: {}.\n\
+         Reply with a JSON array only. Each item is an object with \"role\" (one of the \
+         available roles), \"task\" (a self-contained instruction) and \"depends_on\" \
+         (zero-based indexes of earlier items whose results the task needs). \
+         Use at most {} items.\n\nGoal: {}",
+        roles.join(", "),
+        MAX_PLAN_STEPS,
+        goal
+    )
+}
+
+fn aggregation_prompt(goal: &str, steps: &[OrchestrationStep]) -> String {
+    let mut prompt = format!(
+        "Combine the work of your team into a final answer to the goal.\n\nGoal: {}\n",
+        goal
+    );
+    for step in steps {
+        let outcome = match (&step.result, &step.error) {
+            (Some(result), _) => result.as_str(),
+            (None, Some(error)) => error.as_str(),
+            (None, None) => "not run",
+        };
+        prompt.push_str(&format!(
+            "\n## Step {} ({}, {})\nTask: {}\nOutcome: {}\n",
+            step.index, step.role, step.status, step.task, outcome
+        ));
+    }
+    prompt
+}
+
+/// Task text for a step, with the results of the steps it depends on
+fn step_prompt(step: &OrchestrationStep, plan: &[OrchestrationStep]) -> String {
+    let mut prompt = step.task.clone();
+    for dependency in &step.depends_on {
+        if let Some(result) = plan.get(*dependency).and_then(|d| d.result.as_deref()) {
+            prompt.push_str(&format!("\n\nResult of step {}:\n{}", dependency, result));
+        }
+    }
+    prompt
+}
+
+/// Parse the planner's reply into steps
+///
+/// Tolerates text or code fences around the JSON array. Dependencies must
+/// point at earlier steps so the plan cannot contain cycles.
+fn parse_plan(raw: &str) -> Result<Vec<OrchestrationStep>, String> {
+    let start = raw.find('[');
+    let end = raw.rfind(']');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &raw[start..=end],
+        _ => return Err("Planner did not return a JSON array of subtasks".to_string()),
+    };
+    let planned: Vec<PlannedStep> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid plan from planner: {}", e))?;
+    if planned.is_empty() {
+        return Err("Planner returned an empty plan".to_string());
+    }
+
+    Ok(planned
+        .into_iter()
+        .take(MAX_PLAN_STEPS)
+        .enumerate()
+        .map(|(index, step)| OrchestrationStep {
+            index,
+            role: step.role,
+            task: step.task,
+            depends_on: step.depends_on.into_iter().filter(|d| *d < index).collect(),
+            agent_id: None,
+            status: "pending".to_string(),
+            result: None,
+            error: None,
+        })
+        .collect())
+}
+
+/// Assign each step to a sub-agent with the step's role, spreading steps
+/// across agents that share a role
+fn assign_agents(plan: &mut [OrchestrationStep], agents: &[SubAgentProfile]) {
+    let mut by_role: HashMap<String, Vec<&SubAgentProfile>> = HashMap::new();
+    for agent in agents {
+        by_role.entry(normalize_role(&agent.role)).or_default().push(agent);
+    }
+
+    let mut next: HashMap<String, usize> = HashMap::new();
+    for step in plan.iter_mut() {
+        let role = normalize_role(&step.role);
+        match by_role.get(&role) {
+            Some(candidates) => {
+                let turn = next.entry(role).or_insert(0);
+                step.agent_id = Some(candidates[*turn % candidates.len()].id.clone());
+                *turn += 1;
+            }
+            None => {
+                step.status = "failed".to_string();
+                step.error = Some(format!("No selected sub-agent has the role '{}'", step.role));
+            }
+        }
+    }
+}
+
+/// Built-in planner used when no selected agent has the planner role
+fn default_planner() -> SubAgentProfile {
+    SubAgentProfile {
+        id: "planner".to_string(),
+        name: "Planner".to_string(),
+        role: PLANNER_ROLE.to_string(),
+        system_prompt: Some(
+            "You are a planner coordinating a team of specialized agents. \
+             You split goals into focused subtasks and merge their results."
+                .to_string(),
+        ),
+        tools: Vec::new(),
+        config: SubAgentConfig::default(),
+    }
+}
+
+fn save_run(conn: &Connection, run: &OrchestrationRun) -> Result<(), String> {
+    let agent_ids = serde_json::to_string(&run.agent_ids).map_err(|e| e.to_string())?;
+    let plan = serde_json::to_string(&run.plan).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO orchestration_runs (id, goal, agent_ids, status, plan, result, error, created_at, completed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+            status = excluded.status, plan = excluded.plan, result = excluded.result,
+            error = excluded.error, completed_at = excluded.completed_at",
+        params![
+            run.id,
+            run.goal,
+            agent_ids,
+            run.status,
+            plan,
+            run.result,
+            run.error,
+            run.created_at,
+            run.completed_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn row_to_run(row: &rusqlite::Row) -> rusqlite::Result<OrchestrationRun> {
+    let agent_ids: String = row.get(2)?;
+    let plan: String = row.get(4)?;
+    Ok(OrchestrationRun {
+        id: row.get(0)?,
+        goal: row.get(1)?,
+        agent_ids: serde_json::from_str(&agent_ids).unwrap_or_default(),
+        status: row.get(3)?,
+        plan: serde_json::from_str(&plan).unwrap_or_default(),
+        result: row.get(5)?,
+        error: row.get(6)?,
+        created_at: row.get(7)?,
+        completed_at: row.get(8)?,
+    })
+}
+
+const RUN_COLUMNS: &str = "id, goal, agent_ids, status, plan, result, error, created_at, completed_at";
+
+/// Load a run by id
+pub fn load_run(conn: &Connection, id: &str) -> Result<Option<OrchestrationRun>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM orchestration_runs WHERE id = ?1", RUN_COLUMNS),
+        [id],
+        row_to_run,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Most recent runs, newest first
+pub fn list_runs(conn: &Connection, limit: Option<u32>) -> Result<Vec<OrchestrationRun>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM orchestration_runs ORDER BY created_at DESC LIMIT ?1",
+            RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let runs = stmt
+        .query_map([limit.unwrap_or(DEFAULT_RUN_LIMIT)], row_to_run)
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(runs)
+}
+
+/// Persists and broadcasts run state as it changes
+struct RunRecorder {
+    app: tauri::AppHandle,
+}
+
+impl RunRecorder {
+    fn update(&self, run: &OrchestrationRun) {
+        use tauri::{Emitter, Manager};
+
+        let db = self.app.state::<crate::db::DbState>();
+        let saved = db
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| save_run(&conn, run));
+        if let Err(e) = saved {
+            tracing::error!("Failed to save orchestration run {}: {}", run.id, e);
+        }
+        if let Err(e) = self.app.emit(RUN_EVENT, run) {
+            tracing::warn!("Failed to emit orchestration run: {}", e);
+        }
+    }
+
+    fn finish(&self, run: &mut OrchestrationRun, outcome: Result<String, String>) {
+        match outcome {
+            Ok(result) => {
+                run.status = "completed".to_string();
+                run.result = Some(result);
+            }
+            Err(e) => {
+                run.status = "failed".to_string();
+                run.error = Some(e);
+            }
+        }
+        run.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        self.update(run);
+    }
+}
+
+/// Run every step of the plan, starting each one once its dependencies are done
+async fn execute_plan(
+    executor: &SubAgentExecutor,
+    recorder: &RunRecorder,
+    run: &mut OrchestrationRun,
+    agents: &HashMap<String, SubAgentProfile>,
+    provider_config: Option<serde_json::Value>,
+) {
+    loop {
+        // A step whose dependency did not complete can never run
+        let blocked: Vec<usize> = run
+            .plan
+            .iter()
+            .filter(|step| step.status == "pending")
+            .filter(|step| {
+                step.depends_on.iter().any(|d| {
+                    matches!(run.plan[*d].status.as_str(), "failed" | "skipped")
+                })
+            })
+            .map(|step| step.index)
+            .collect();
+        for index in blocked {
+            run.plan[index].status = "skipped".to_string();
+            run.plan[index].error = Some("A step it depends on did not complete".to_string());
+        }
+
+        let ready: Vec<usize> = run
+            .plan
+            .iter()
+            .filter(|step| step.status == "pending")
+            .filter(|step| step.depends_on.iter().all(|d| run.plan[*d].status == "completed"))
+            .map(|step| step.index)
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+
+        let mut batch = Vec::new();
+        for index in &ready {
+            let step = &run.plan[*index];
+            let prompt = step_prompt(step, &run.plan);
+            let profile = step.agent_id.as_ref().and_then(|id| agents.get(id)).cloned();
+            batch.push((*index, profile, prompt));
+        }
+        for index in &ready {
+            run.plan[*index].status = "running".to_string();
+        }
+        recorder.update(run);
+
+        let outcomes = futures_util::future::join_all(batch.into_iter().map(|(index, profile, prompt)| {
+            let provider_config = provider_config.clone();
+            async move {
+                let outcome = match profile {
+                    Some(profile) => executor.run(profile, prompt, provider_config).await,
+                    None => Err("Sub-agent is not available".to_string()),
+                };
+                (index, outcome)
+            }
+        }))
+        .await;
+
+        for (index, outcome) in outcomes {
+            let step = &mut run.plan[index];
+            match outcome {
+                Ok(result) => {
+                    step.status = "completed".to_string();
+                    step.result = Some(result);
+                }
+                Err(e) => {
+                    step.status = "failed".to_string();
+                    step.error = Some(e);
+                }
+            }
+        }
+        recorder.update(run);
+    }
+}
+
+/// Decompose a goal into subtasks, run them on the selected sub-agents and
+/// combine their results
+///
+/// A selected agent with the `planner` role plans and aggregates; otherwise a
+/// built-in planner is used. Progress is emitted on `orchestration://run` and
+/// the finished run can be inspected with `get_orchestration_run`.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn orchestrate_task(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::db::DbState>,
+    executor: tauri::State<'_, SubAgentExecutor>,
+    goal: String,
+    agent_ids: Vec<String>,
+) -> Result<OrchestrationRun, AppError> {
+    use tauri::Manager;
+
+    if goal.trim().is_empty() {
+        return Err(AppError::invalid_input("Goal cannot be empty"));
+    }
+    if agent_ids.is_empty() {
+        return Err(AppError::invalid_input("Select at least one sub-agent"));
+    }
+
+    let profiles = {
+        let conn = db.conn.lock()?;
+        agent_ids
+            .iter()
+            .map(|id| SubAgentProfile::load(&conn, id))
+            .collect::<Result<Vec<_>, String>>()?
+    };
+    let provider_config = app
+        .state::<std::sync::Mutex<crate::sidecar::SidecarState>>()
+        .lock()?
+        .provider_config();
+
+    let (planners, workers): (Vec<_>, Vec<_>) = profiles
+        .into_iter()
+        .partition(|p| normalize_role(&p.role) == PLANNER_ROLE);
+    if workers.is_empty() {
+        return Err(AppError::invalid_input("Select at least one sub-agent that is not a planner"));
+    }
+    let planner = planners.into_iter().next().unwrap_or_else(default_planner);
+
+    let mut run = OrchestrationRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        goal: goal.clone(),
+        agent_ids,
+        status: "planning".to_string(),
+        plan: Vec::new(),
+        result: None,
+        error: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        completed_at: None,
+    };
+    let recorder = RunRecorder { app };
+    recorder.update(&run);
+
+    let mut roles: Vec<String> = workers.iter().map(|w| normalize_role(&w.role)).collect();
+    roles.sort();
+    roles.dedup();
+    let plan = executor
+        .run(planner.clone(), planning_prompt(&goal, &roles), provider_config.clone())
+        .await
+        .and_then(|reply| parse_plan(&reply));
+    run.plan = match plan {
+        Ok(plan) => plan,
+        Err(e) => {
+            recorder.finish(&mut run, Err(e));
+            return Ok(run);
+        }
+    };
+    assign_agents(&mut run.plan, &workers);
+    run.status = "running".to_string();
+    recorder.update(&run);
+
+    let agents: HashMap<String, SubAgentProfile> =
+        workers.into_iter().map(|w| (w.id.clone(), w)).collect();
+    execute_plan(&executor, &recorder, &mut run, &agents, provider_config.clone()).await;
+
+    let outcome = if run.plan.iter().any(|step| step.status == "completed") {
+        executor
+            .run(planner, aggregation_prompt(&goal, &run.plan), provider_config)
+            .await
+            .map_err(|e| format!("Failed to aggregate results: {}", e))
+    } else {
+        Err("No subtask completed".to_string())
+    };
+    recorder.finish(&mut run, outcome);
+
+    Ok(run)
+}
+
+/// Get an orchestration run with its plan and step results
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_orchestration_run(
+    db: tauri::State<'_, crate::db::DbState>,
+    id: String,
+) -> Result<Option<OrchestrationRun>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(load_run(&conn, &id)?)
+}
+
+/// List recent orchestration runs
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_orchestration_runs(
+    db: tauri::State<'_, crate::db::DbState>,
+    limit: Option<u32>,
+) -> Result<Vec<OrchestrationRun>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list_runs(&conn, limit)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn profile(id: &str, role: &str) -> SubAgentProfile {
+        SubAgentProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            role: role.to_string(),
+            system_prompt: None,
+            tools: Vec::new(),
+            config: SubAgentConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_plan() {
+        let raw = "Here is the plan:\n```json\n[\n  {\"role\": \"researcher\", \"task\": \"Find sources\"},\n  {\"role\": \"Writer\", \"task\": \"Draft\", \"depends_on\": [0, 1, 5]}\n]\n```";
+        let plan = parse_plan(raw).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[1].role, "Writer");
+        // Self and forward references are dropped
+        assert_eq!(plan[1].depends_on, vec![0]);
+        assert!(parse_plan("I can't help with that").is_err());
+        assert!(parse_plan("[]").is_err());
+    }
+
+    #[test]
+    fn test_assign_agents_by_role() {
+        let mut plan = parse_plan(
+            r#"[{"role": "writer", "task": "a"}, {"role": "Writer", "task": "b"},
+                {"role": "writer", "task": "c"}, {"role": "designer", "task": "d"}]"#,
+        )
+        .unwrap();
+        assign_agents(&mut plan, &[profile("w1", "writer"), profile("w2", "writer")]);
+
+        let assigned: Vec<Option<&str>> = plan.iter().map(|s| s.agent_id.as_deref()).collect();
+        assert_eq!(assigned, vec![Some("w1"), Some("w2"), Some("w1"), None]);
+        assert_eq!(plan[3].status, "failed");
+    }
+
+    #[test]
+    fn test_step_prompt_includes_dependencies() {
+        let mut plan = parse_plan(
+            r#"[{"role": "researcher", "task": "Find sources"},
+                {"role": "writer", "task": "Draft", "depends_on": [0]}]"#,
+        )
+        .unwrap();
+        plan[0].result = Some("Three papers".to_string());
+        assert_eq!(step_prompt(&plan[1], &plan), "Draft\n\nResult of step 0:\nThree papers");
+    }
+
+    #[test]
+    fn test_save_and_load_run() {
+        let conn = test_conn();
+
+        let mut run = OrchestrationRun {
+            id: "run-1".to_string(),
+            goal: "Write a report".to_string(),
+            agent_ids: vec!["w1".to_string()],
+            status: "planning".to_string(),
+            plan: Vec::new(),
+            result: None,
+            error: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            completed_at: None,
+        };
+        save_run(&conn, &run).unwrap();
+
+        run.plan = parse_plan(r#"[{"role": "writer", "task": "Draft"}]"#).unwrap();
+        run.status = "completed".to_string();
+        run.result = Some("Done".to_string());
+        save_run(&conn, &run).unwrap();
+
+        let loaded = load_run(&conn, "run-1").unwrap().unwrap();
+        assert_eq!(loaded.status, "completed");
+        assert_eq!(loaded.plan.len(), 1);
+        assert_eq!(loaded.agent_ids, vec!["w1"]);
+        assert_eq!(list_runs(&conn, None).unwrap().len(), 1);
+        assert!(load_run(&conn, "missing").unwrap().is_none());
+    }
+}
@@ -0,0 +1,386 @@
+// Usage analytics - Chart-ready usage series built from existing tables
+//
+// Counts are materialized per day into `analytics_daily`. Each metric keeps a
+// watermark, the first day not yet final: a refresh recomputes only the days
+// from the watermark onwards and then moves it to today, so earlier days are
+// read from the aggregates even after their source rows are pruned.
+
+use crate::error::AppError;
+use crate::plugins::PluginExecutor;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Watermark of a metric that was never aggregated; sorts before any date
+const NEVER: &str = "0000-00-00";
+
+/// Materialized metrics and the queries counting them per day and dimension.
+/// `?1` is the first day to count; comparing the timestamps with a bare date
+/// includes that whole day.
+const METRICS: &[(&str, &str)] = &[
+    (
+        "messages",
+        "SELECT substr(created_at, 1, 10), role, COUNT(*) FROM messages
+         WHERE created_at >= ?1 GROUP BY 1, 2",
+    ),
+    (
+        "skill_runs",
+        "SELECT substr(e.started_at, 1, 10), e.status, COUNT(*) FROM job_executions e
+         JOIN cron_jobs j ON j.id = e.job_id
+         WHERE j.job_type = 'skill' AND e.started_at >= ?1 GROUP BY 1, 2",
+    ),
+    (
+        "recipe_runs",
+        "SELECT substr(started_at, 1, 10), status, COUNT(*) FROM recipe_executions
+         WHERE started_at >= ?1 GROUP BY 1, 2",
+    ),
+    (
+        "job_runs",
+        "SELECT substr(started_at, 1, 10), status, COUNT(*) FROM job_executions
+         WHERE started_at >= ?1 GROUP BY 1, 2",
+    ),
+];
+
+/// Message roles reported as separate series
+const MESSAGE_ROLES: [&str; 3] = ["user", "assistant", "system"];
+
+/// Time range ending today
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnalyticsPeriod {
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[serde(rename = "90d")]
+    Quarter,
+    #[serde(rename = "365d")]
+    Year,
+}
+
+impl AnalyticsPeriod {
+    pub(crate) fn days(&self) -> i64 {
+        match self {
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Quarter => 90,
+            Self::Year => 365,
+        }
+    }
+}
+
+/// Width of a chart bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// First day of the bucket holding `day`; weeks start on Monday
+    fn bucket(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => day,
+            Self::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+            Self::Month => day.with_day(1).unwrap_or(day),
+        }
+    }
+}
+
+/// Counts per bucket, aligned with the report's labels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountSeries {
+    pub name: String,
+    pub values: Vec<u64>,
+}
+
+/// Totals over the whole period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsTotals {
+    pub messages: u64,
+    pub skill_runs: u64,
+    pub recipe_runs: u64,
+    pub job_runs: u64,
+    /// Completed share of finished job runs, None without finished runs
+    pub job_success_rate: Option<f64>,
+}
+
+/// Current resource usage of a running plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginUsage {
+    pub plugin_id: String,
+    pub memory_mb: f64,
+    pub cpu_percent: f64,
+    pub execution_time_ms: u64,
+}
+
+/// Usage series for a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsReport {
+    pub period: AnalyticsPeriod,
+    pub granularity: Granularity,
+    pub from: String,
+    pub to: String,
+    /// First day of each bucket
+    pub labels: Vec<String>,
+    /// Messages per bucket, one series per role
+    pub messages: Vec<CountSeries>,
+    pub skill_runs: Vec<u64>,
+    pub recipe_runs: Vec<u64>,
+    pub job_runs: Vec<u64>,
+    /// Completed share of finished job runs per bucket
+    pub job_success_rate: Vec<Option<f64>>,
+    pub totals: AnalyticsTotals,
+    pub plugins: Vec<PluginUsage>,
+}
+
+/// Bring the daily aggregates up to date
+pub fn refresh(conn: &mut Connection, today: NaiveDate) -> Result<(), String> {
+    let today = today.to_string();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (metric, query) in METRICS {
+        let since: String = tx
+            .query_row("SELECT day FROM analytics_watermarks WHERE metric = ?1", [metric], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_else(|| NEVER.to_string());
+
+        tx.execute("DELETE FROM analytics_daily WHERE metric = ?1 AND day >= ?2", params![metric, since])
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            &format!(
+                "INSERT INTO analytics_daily (metric, day, dimension, value)
+                 SELECT ?2, * FROM ({})",
+                query
+            ),
+            params![since, metric],
+        )
+        .map_err(|e| format!("Failed to aggregate {}: {}", metric, e))?;
+        tx.execute(
+            "INSERT INTO analytics_watermarks (metric, day) VALUES (?1, ?2)
+             ON CONFLICT(metric) DO UPDATE SET day = excluded.day",
+            params![metric, today],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Build the report for a period ending `today` from the daily aggregates
+pub fn report(
+    conn: &Connection,
+    period: AnalyticsPeriod,
+    granularity: Granularity,
+    today: NaiveDate,
+) -> Result<AnalyticsReport, String> {
+    let from = today - Duration::days(period.days() - 1);
+
+    let mut labels = Vec::new();
+    let mut index = HashMap::new();
+    for day in from.iter_days().take_while(|day| *day <= today) {
+        let bucket = granularity.bucket(day);
+        if labels.last() != Some(&bucket) {
+            labels.push(bucket);
+        }
+        index.insert(day.to_string(), labels.len() - 1);
+    }
+
+    let mut counts: HashMap<(String, String), Vec<u64>> = HashMap::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT metric, day, dimension, value FROM analytics_daily
+             WHERE day >= ?1 AND day <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![from.to_string(), today.to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?))
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (metric, day, dimension, value) = row.map_err(|e| e.to_string())?;
+        if let Some(&i) = index.get(&day) {
+            counts.entry((metric, dimension)).or_insert_with(|| vec![0; labels.len()])[i] += value.max(0) as u64;
+        }
+    }
+
+    let series = |metric: &str, dimension: Option<&str>| -> Vec<u64> {
+        let mut values = vec![0; labels.len()];
+        for ((m, d), counts) in &counts {
+            if m == metric && dimension.is_none_or(|dimension| d == dimension) {
+                values.iter_mut().zip(counts).for_each(|(v, c)| *v += c);
+            }
+        }
+        values
+    };
+    let rate = |completed: u64, failed: u64| (completed + failed > 0).then(|| completed as f64 / (completed + failed) as f64);
+
+    let messages: Vec<CountSeries> = MESSAGE_ROLES
+        .iter()
+        .map(|role| CountSeries { name: role.to_string(), values: series("messages", Some(role)) })
+        .collect();
+    let completed = series("job_runs", Some("completed"));
+    let failed: Vec<u64> = series("job_runs", Some("failed"))
+        .iter()
+        .zip(series("job_runs", Some("cancelled")))
+        .map(|(f, c)| f + c)
+        .collect();
+    let (skill_runs, recipe_runs, job_runs) = (series("skill_runs", None), series("recipe_runs", None), series("job_runs", None));
+
+    let totals = AnalyticsTotals {
+        messages: messages.iter().flat_map(|s| &s.values).sum(),
+        skill_runs: skill_runs.iter().sum(),
+        recipe_runs: recipe_runs.iter().sum(),
+        job_runs: job_runs.iter().sum(),
+        job_success_rate: rate(completed.iter().sum(), failed.iter().sum()),
+    };
+
+    Ok(AnalyticsReport {
+        period,
+        granularity,
+        from: from.to_string(),
+        to: today.to_string(),
+        labels: labels.iter().map(|d| d.to_string()).collect(),
+        job_success_rate: completed.iter().zip(&failed).map(|(c, f)| rate(*c, *f)).collect(),
+        messages,
+        skill_runs,
+        recipe_runs,
+        job_runs,
+        totals,
+        plugins: Vec::new(),
+    })
+}
+
+/// Resource usage of the running plugins
+fn plugin_usage(executor: &PluginExecutor) -> Vec<PluginUsage> {
+    let mut usage: Vec<PluginUsage> = executor
+        .list_running()
+        .into_iter()
+        .filter_map(|id| {
+            executor.get_resource_usage(&id).map(|usage| PluginUsage {
+                plugin_id: id,
+                memory_mb: usage.memory_mb,
+                cpu_percent: usage.cpu_percent,
+                execution_time_ms: usage.execution_time_ms,
+            })
+        })
+        .collect();
+    usage.sort_by(|a, b| a.plugin_id.cmp(&b.plugin_id));
+    usage
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Usage series for the dashboard
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_analytics(
+    db: tauri::State<'_, crate::db::DbState>,
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    period: AnalyticsPeriod,
+    granularity: Granularity,
+) -> Result<AnalyticsReport, AppError> {
+    let today = Utc::now().date_naive();
+    let mut report = {
+        let mut conn = db.conn.lock()?;
+        refresh(&mut conn, today)?;
+        report(&conn, period, granularity, today)?
+    };
+    let executor = executor.lock()?;
+    report.plugins = plugin_usage(&executor);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES ('c1', 'Chat');
+             INSERT INTO cron_jobs (id, name, schedule, job_type) VALUES ('j1', 'Digest', '0 * * * *', 'skill');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn add_message(conn: &Connection, id: &str, role: &str, at: &str) {
+        conn.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES (?1, 'c1', ?2, '', ?3)",
+            params![id, role, at],
+        )
+        .unwrap();
+    }
+
+    fn add_run(conn: &Connection, id: &str, status: &str, at: &str) {
+        conn.execute(
+            "INSERT INTO job_executions (id, job_id, status, started_at) VALUES (?1, 'j1', ?2, ?3)",
+            params![id, status, at],
+        )
+        .unwrap();
+    }
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_report_buckets_and_rates() {
+        let mut conn = setup();
+        add_message(&conn, "m1", "user", "2026-03-02 10:00:00");
+        add_message(&conn, "m2", "assistant", "2026-03-02T10:00:05+00:00");
+        add_message(&conn, "m3", "user", "2026-03-09T08:00:00Z");
+        add_message(&conn, "old", "user", "2026-01-01T08:00:00Z");
+        add_run(&conn, "r1", "completed", "2026-03-03T00:00:00Z");
+        add_run(&conn, "r2", "failed", "2026-03-03T01:00:00Z");
+        add_run(&conn, "r3", "running", "2026-03-10T01:00:00Z");
+
+        let today = day("2026-03-10");
+        refresh(&mut conn, today).unwrap();
+
+        let daily = report(&conn, AnalyticsPeriod::Week, Granularity::Day, today).unwrap();
+        assert_eq!(daily.labels.len(), 7);
+        assert_eq!(daily.from, "2026-03-04");
+        assert_eq!(daily.totals.messages, 1);
+
+        let weekly = report(&conn, AnalyticsPeriod::Month, Granularity::Week, today).unwrap();
+        assert_eq!(weekly.labels.last().unwrap(), "2026-03-09");
+        let n = weekly.labels.len();
+        assert_eq!(weekly.messages[0].values[n - 2..], [1, 1]);
+        assert_eq!(weekly.messages[1].values[n - 2..], [1, 0]);
+        assert_eq!(weekly.skill_runs[n - 2..], [2, 1]);
+        assert_eq!(weekly.job_success_rate[n - 2..], [Some(0.5), None]);
+        assert_eq!(weekly.totals.messages, 3);
+        assert_eq!(weekly.totals.job_success_rate, Some(0.5));
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let mut conn = setup();
+        add_run(&conn, "r1", "completed", "2026-03-01T00:00:00Z");
+        add_run(&conn, "r2", "running", "2026-03-02T00:00:00Z");
+        refresh(&mut conn, day("2026-03-02")).unwrap();
+
+        // Today's counts are recomputed; final days survive pruning
+        conn.execute("UPDATE job_executions SET status = 'completed' WHERE id = 'r2'", []).unwrap();
+        conn.execute("DELETE FROM job_executions WHERE id = 'r1'", []).unwrap();
+        add_run(&conn, "r3", "failed", "2026-03-03T00:00:00Z");
+        refresh(&mut conn, day("2026-03-03")).unwrap();
+        refresh(&mut conn, day("2026-03-03")).unwrap();
+
+        let report = report(&conn, AnalyticsPeriod::Week, Granularity::Day, day("2026-03-03")).unwrap();
+        assert_eq!(report.job_runs[4..], [1, 1, 1]);
+        assert_eq!(report.job_success_rate[4..], [Some(1.0), Some(1.0), Some(0.0)]);
+    }
+}
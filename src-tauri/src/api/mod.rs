@@ -0,0 +1,313 @@
+//! REST API Module
+//!
+//! An optional local HTTP API for scripting the assistant from shell scripts
+//! or other apps. Endpoints mirror the Tauri commands for chat, skill and
+//! recipe execution and job management:
+//! - Off by default; bound to localhost unless remote access is allowed
+//! - Bearer token auth; only a hash of the token is stored
+//! - A per-minute request limit
+
+pub mod routes;
+
+use crate::db::{settings, DbState};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+/// Settings holding the configuration and the token hash
+const CONFIG_KEY: &str = "rest_api";
+const TOKEN_HASH_KEY: &str = "rest_api_token_hash";
+
+const DEFAULT_PORT: u16 = 8787;
+const DEFAULT_RATE_LIMIT: u32 = 60;
+const MAX_RATE_LIMIT: u32 = 10_000;
+
+/// Prefix making tokens recognizable in scripts and secret scanners
+const TOKEN_PREFIX: &str = "aia_";
+
+/// REST API configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on all interfaces instead of localhost only
+    pub allow_remote: bool,
+    /// Requests allowed per minute
+    pub rate_limit_per_minute: u32,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            allow_remote: false,
+            rate_limit_per_minute: DEFAULT_RATE_LIMIT,
+        }
+    }
+}
+
+impl ApiConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.port < 1024 {
+            return Err(AppError::invalid_input("Port must be 1024 or higher"));
+        }
+        if !(1..=MAX_RATE_LIMIT).contains(&self.rate_limit_per_minute) {
+            return Err(AppError::invalid_input(format!(
+                "Rate limit must be between 1 and {} requests per minute",
+                MAX_RATE_LIMIT
+            )));
+        }
+        Ok(())
+    }
+
+    fn address(&self) -> SocketAddr {
+        let ip = if self.allow_remote { IpAddr::V4(Ipv4Addr::UNSPECIFIED) } else { IpAddr::V4(Ipv4Addr::LOCALHOST) };
+        SocketAddr::new(ip, self.port)
+    }
+}
+
+/// Whether the API is serving, and where
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    pub has_token: bool,
+}
+
+pub fn load_config(conn: &rusqlite::Connection) -> Result<ApiConfig, String> {
+    match settings::get(conn, CONFIG_KEY)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid REST API settings: {}", e)),
+        None => Ok(ApiConfig::default()),
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Create a new token, replacing the previous one; only its hash is stored
+pub fn regenerate_token(conn: &rusqlite::Connection) -> Result<String, String> {
+    let token = format!(
+        "{}{}{}",
+        TOKEN_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    settings::set(conn, TOKEN_HASH_KEY, &hash_token(&token))?;
+    Ok(token)
+}
+
+/// Check a presented token against the stored hash
+pub fn verify_token(token_hash: &str, presented: &str) -> bool {
+    // Comparing hashes keeps timing independent of how much of the token matched
+    !token_hash.is_empty() && hash_token(presented) == token_hash
+}
+
+/// Fixed-window request limiter shared by all requests
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    /// Start of the current window and requests counted in it
+    state: Mutex<Option<(Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    fn new(limit: u32, window: Duration) -> Self {
+        Self { limit, window, state: Mutex::new(None) }
+    }
+
+    /// Count a request; when over the limit, the time until the window resets
+    pub fn check(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (started, count) = state.get_or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            *started = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            return Err(self.window.saturating_sub(now.duration_since(*started)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+struct RunningServer {
+    address: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// Handle of the running API server, managed as app state
+#[derive(Default)]
+pub struct ApiServerState {
+    running: tokio::sync::Mutex<Option<RunningServer>>,
+}
+
+impl ApiServerState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Stop the server, if running, and start it again when enabled
+pub async fn restart(app: &AppHandle) -> Result<ApiStatus, AppError> {
+    let (config, token_hash) = {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock()?;
+        (load_config(&conn)?, settings::get(&conn, TOKEN_HASH_KEY)?)
+    };
+
+    let state = app.state::<ApiServerState>();
+    let mut running = state.running.lock().await;
+    if let Some(server) = running.take() {
+        let _ = server.shutdown.send(());
+        tracing::info!("REST API stopped on {}", server.address);
+    }
+
+    if config.enabled {
+        let token_hash = token_hash
+            .ok_or_else(|| AppError::invalid_input("Generate an API token before enabling the REST API"))?;
+        let address = config.address();
+        let listener = tokio::net::TcpListener::bind(address)
+            .await
+            .map_err(|e| AppError::invalid_input(format!("Failed to listen on {}: {}", address, e)))?;
+        let router = routes::router(routes::ApiContext::new(app.clone(), token_hash, config.rate_limit_per_minute));
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        tauri::async_runtime::spawn(async move {
+            let serve = axum::serve(listener, router).with_graceful_shutdown(async {
+                let _ = stopped.await;
+            });
+            if let Err(e) = serve.await {
+                tracing::error!("REST API server failed: {}", e);
+            }
+        });
+        tracing::info!("REST API listening on {}", address);
+        *running = Some(RunningServer { address, shutdown });
+    }
+    drop(running);
+
+    status(app).await
+}
+
+async fn status(app: &AppHandle) -> Result<ApiStatus, AppError> {
+    let has_token = {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock()?;
+        settings::get(&conn, TOKEN_HASH_KEY)?.is_some()
+    };
+    let state = app.state::<ApiServerState>();
+    let running = state.running.lock().await;
+    Ok(ApiStatus {
+        running: running.is_some(),
+        address: running.as_ref().map(|s| s.address.to_string()),
+        has_token,
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_api_config(db: tauri::State<'_, DbState>) -> Result<ApiConfig, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(load_config(&conn)?)
+}
+
+/// Save the configuration and start, stop or rebind the server to match
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn set_api_config(app: AppHandle, config: ApiConfig) -> Result<ApiStatus, AppError> {
+    config.validate()?;
+    {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock()?;
+        let json = serde_json::to_string(&config)?;
+        settings::set(&conn, CONFIG_KEY, &json)?;
+    }
+    restart(&app).await
+}
+
+/// Create a new API token; it is only shown this once
+///
+/// The running server switches to the new token right away.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn regenerate_api_token(app: AppHandle) -> Result<String, AppError> {
+    let token = {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock()?;
+        regenerate_token(&conn)?
+    };
+    if status(&app).await?.running {
+        restart(&app).await?;
+    }
+    Ok(token)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn get_api_status(app: AppHandle) -> Result<ApiStatus, AppError> {
+    status(&app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_token_round_trip() {
+        let conn = test_conn();
+
+        let token = regenerate_token(&conn).unwrap();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        let hash = settings::get(&conn, TOKEN_HASH_KEY).unwrap().unwrap();
+        assert_ne!(hash, token);
+        assert!(verify_token(&hash, &token));
+        assert!(!verify_token(&hash, "aia_wrong"));
+        assert!(!verify_token("", ""));
+
+        let replaced = regenerate_token(&conn).unwrap();
+        let hash = settings::get(&conn, TOKEN_HASH_KEY).unwrap().unwrap();
+        assert!(!verify_token(&hash, &token));
+        assert!(verify_token(&hash, &replaced));
+    }
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(limiter.check(start).is_ok());
+        assert!(limiter.check(start).is_ok());
+        let retry = limiter.check(start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(retry, Duration::from_secs(40));
+        assert!(limiter.check(start + Duration::from_secs(60)).is_ok());
+    }
+
+    #[test]
+    fn test_config_defaults_to_localhost() {
+        let config = ApiConfig::default();
+        assert!(!config.enabled);
+        assert!(config.address().ip().is_loopback());
+        assert!(ApiConfig { allow_remote: true, ..config.clone() }.address().ip().is_unspecified());
+
+        assert!(config.validate().is_ok());
+        let err = ApiConfig { port: 80, ..config.clone() }.validate().unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidInput);
+        assert!(ApiConfig { rate_limit_per_minute: 0, ..config }.validate().is_err());
+    }
+}
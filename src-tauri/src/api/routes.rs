@@ -0,0 +1,317 @@
+//! REST API routes
+//!
+//! Every route under `/api/v1` except `/health` needs the bearer token and
+//! counts against the rate limit. Handlers call the matching Tauri commands
+//! so scripts see the same behavior as the app.
+
+use super::{verify_token, RateLimiter};
+use crate::db;
+use crate::error::{AppError, ErrorKind};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+/// Shared state of the API handlers
+#[derive(Clone)]
+pub struct ApiContext {
+    app: AppHandle,
+    gate: Gate,
+}
+
+impl ApiContext {
+    pub fn new(app: AppHandle, token_hash: String, rate_limit_per_minute: u32) -> Self {
+        Self {
+            app,
+            gate: Gate::new(token_hash, rate_limit_per_minute),
+        }
+    }
+}
+
+/// Token and request limit checked by `guard`
+#[derive(Clone)]
+struct Gate {
+    token_hash: Arc<String>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl Gate {
+    fn new(token_hash: String, rate_limit_per_minute: u32) -> Self {
+        Self {
+            token_hash: Arc::new(token_hash),
+            limiter: Arc::new(RateLimiter::per_minute(rate_limit_per_minute)),
+        }
+    }
+}
+
+/// Error response with a JSON `{ "error": ... }` body
+struct ApiError(StatusCode, String);
+
+/// HTTP status for a kind of command error
+fn status(kind: ErrorKind) -> StatusCode {
+    match kind {
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::InvalidInput => StatusCode::BAD_REQUEST,
+        ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+        ErrorKind::Busy | ErrorKind::Sidecar => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorKind::Network => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(error: AppError) -> Self {
+        ApiError(status(error.kind()), error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+pub fn router(context: ApiContext) -> Router {
+    let api = Router::new()
+        .route("/chat", post(chat))
+        .route("/skills/{id}/execute", post(execute_skill))
+        .route("/recipes/{id}/execute", post(execute_recipe))
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/{id}", axum::routing::patch(update_job))
+        .route("/jobs/{id}/run", post(run_job))
+        .route("/jobs/executions", get(list_executions));
+
+    Router::new()
+        .nest("/api/v1", secured(api, context.gate.clone()))
+        .with_state(context)
+}
+
+/// Put `api` behind the token and rate limit, and add `/health`
+fn secured<S: Clone + Send + Sync + 'static>(api: Router<S>, gate: Gate) -> Router<S> {
+    api.route_layer(middleware::from_fn_with_state(gate, guard))
+        // Added after the layer, so it needs no token
+        .route("/health", get(health))
+}
+
+/// Check the bearer token, then the rate limit
+async fn guard(State(gate): State<Gate>, request: Request, next: Next) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !token.is_some_and(|t| verify_token(&gate.token_hash, t.trim())) {
+        return ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid API token".to_string()).into_response();
+    }
+
+    if let Err(retry_after) = gate.limiter.check(Instant::now()) {
+        let mut response =
+            ApiError(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded".to_string()).into_response();
+        let seconds = retry_after.as_secs().max(1).to_string();
+        if let Ok(value) = HeaderValue::from_str(&seconds) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+        return response;
+    }
+
+    next.run(request).await
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatBody {
+    messages: Vec<crate::Message>,
+    provider: Option<String>,
+    /// Conversation whose guardrail override applies
+    conversation_id: Option<String>,
+    /// Generation parameters; the conversation's defaults fill unset ones
+    params: Option<crate::conversations::generation::GenerationParams>,
+}
+
+async fn chat(State(context): State<ApiContext>, Json(body): Json<ChatBody>) -> ApiResult<crate::ChatResponse> {
+    let app = &context.app;
+    let response = crate::sidecar::agent_chat(app.state(), app.state(), body.messages, body.provider, body.conversation_id, body.params)
+        .await?;
+    Ok(Json(response))
+}
+
+#[derive(Deserialize)]
+struct SkillBody {
+    /// Text, or JSON for skills with an input schema
+    input: Value,
+    /// Defaults to the skill's stored prompt
+    prompt: Option<String>,
+}
+
+async fn execute_skill(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<SkillBody>,
+) -> ApiResult<Value> {
+    let app = &context.app;
+    let prompt = match body.prompt {
+        Some(prompt) => prompt,
+        None => db::get_skill(app.state(), id.clone())?.prompt,
+    };
+    let input = match body.input {
+        Value::String(input) => input,
+        input => input.to_string(),
+    };
+    let result = crate::sidecar::execute_skill(app.state(), app.state(), id, prompt, input).await?;
+    Ok(Json(json!({ "result": result })))
+}
+
+#[derive(Deserialize, Default)]
+struct RecipeBody {
+    /// Defaults to the recipe's stored steps
+    steps: Option<Vec<Value>>,
+    variables: Option<Value>,
+}
+
+async fn execute_recipe(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    body: Option<Json<RecipeBody>>,
+) -> ApiResult<Value> {
+    let app = &context.app;
+    let body = body.map(|Json(body)| body).unwrap_or_default();
+    let steps = match body.steps {
+        Some(steps) => steps,
+        None => {
+            let recipe = db::get_recipe(app.state(), id.clone())?;
+            serde_json::from_str(&recipe.steps)
+                .map_err(|e| AppError::Internal(format!("Invalid recipe steps: {}", e)))?
+        }
+    };
+    let result = crate::sidecar::execute_recipe(app.state(), id, steps, body.variables).await?;
+    Ok(Json(json!({ "result": result })))
+}
+
+async fn list_jobs(State(context): State<ApiContext>) -> ApiResult<Vec<db::CronJob>> {
+    Ok(Json(db::list_cron_jobs(context.app.state())?))
+}
+
+#[derive(Deserialize)]
+struct JobUpdate {
+    enabled: bool,
+}
+
+/// Enable or disable a job
+async fn update_job(
+    State(context): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<JobUpdate>,
+) -> ApiResult<Value> {
+    let enabled = i32::from(body.enabled);
+    db::update_cron_job(context.app.state(), id.clone(), None, None, None, Some(enabled), None, None)?;
+    Ok(Json(json!({ "id": id, "enabled": body.enabled })))
+}
+
+async fn run_job(State(context): State<ApiContext>, Path(id): Path<String>) -> ApiResult<Value> {
+    let app = context.app.clone();
+    // The job runs synchronously, so keep it off the server's worker threads
+    let execution_id = tokio::task::spawn_blocking(move || {
+        db::run_cron_job_now(app.clone(), app.state(), app.state(), app.state(), id)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(Json(json!({ "executionId": execution_id })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecutionQuery {
+    job_id: Option<String>,
+}
+
+async fn list_executions(
+    State(context): State<ApiContext>,
+    Query(query): Query<ExecutionQuery>,
+) -> ApiResult<Vec<db::JobExecution>> {
+    Ok(Json(db::list_job_executions(context.app.state(), query.job_id)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "aia_test";
+
+    /// The real guard and `/health` in front of a stub route
+    fn test_router(rate_limit_per_minute: u32) -> Router {
+        let gate = Gate::new(super::super::hash_token(TOKEN), rate_limit_per_minute);
+        let api = Router::new().route("/jobs", get(|| async { Json(json!([])) }));
+        Router::new().nest("/api/v1", secured(api, gate))
+    }
+
+    async fn send(router: &Router, path: &str, token: Option<&str>) -> Response {
+        let mut request = axum::http::Request::builder().uri(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_token_is_required() {
+        let router = test_router(60);
+        assert_eq!(send(&router, "/api/v1/jobs", None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(&router, "/api/v1/jobs", Some("aia_wrong")).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(&router, "/api/v1/jobs", Some(TOKEN)).await.status(), StatusCode::OK);
+
+        let health = send(&router, "/api/v1/health", None).await;
+        assert_eq!(health.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(health.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_sets_retry_after() {
+        let router = test_router(2);
+        // Rejected tokens do not count against the limit
+        send(&router, "/api/v1/jobs", None).await;
+        assert_eq!(send(&router, "/api/v1/jobs", Some(TOKEN)).await.status(), StatusCode::OK);
+        assert_eq!(send(&router, "/api/v1/jobs", Some(TOKEN)).await.status(), StatusCode::OK);
+
+        let limited = send(&router, "/api/v1/jobs", Some(TOKEN)).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = limited.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=60).contains(&retry_after));
+        assert_eq!(send(&router, "/api/v1/health", None).await.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_invalid_api_config_is_bad_request() {
+        let config = super::super::ApiConfig { port: 80, ..Default::default() };
+        assert_eq!(ApiError::from(config.validate().unwrap_err()).0, StatusCode::BAD_REQUEST);
+        let config = super::super::ApiConfig { rate_limit_per_minute: 0, ..Default::default() };
+        assert_eq!(ApiError::from(config.validate().unwrap_err()).0, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_status_follows_error_kind() {
+        let response = |error: AppError| ApiError::from(error).0;
+        assert_eq!(response(AppError::not_found("Job with ID j1 not found")), StatusCode::NOT_FOUND);
+        assert_eq!(response(rusqlite::Error::QueryReturnedNoRows.into()), StatusCode::NOT_FOUND);
+        assert_eq!(response(AppError::invalid_input("Schedule is empty")), StatusCode::BAD_REQUEST);
+        assert_eq!(response(AppError::permission_denied("Conversation is locked")), StatusCode::FORBIDDEN);
+
+        // The message no longer decides the status
+        assert_eq!(response(AppError::Internal("Model not found in cache".to_string())), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,700 @@
+//! Template Bundles
+//!
+//! A bundle is a zip archive with the `.aipack` extension that packages
+//! templates, skills and recipes together. `manifest.json` lists each item
+//! with its kind, schema version and dependencies; each item is stored as
+//! its own JSON file under `items/`.
+//!
+//! A recipe depends on the skills, templates and recipes whose ids appear as
+//! `skillId`, `templateId` or `recipeId` values anywhere in its steps.
+//! Exporting pulls these dependencies into the bundle, and importing writes
+//! them before the items that use them, rewriting the references when an
+//! item is renamed or mapped onto an existing one.
+
+use crate::collaboration::template_io::validate_template;
+use crate::collaboration::Template;
+use crate::db::{skill_schema, Recipe, Skill};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// File extension of bundles
+pub const BUNDLE_EXTENSION: &str = "aipack";
+
+/// Bundle format written by this version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Item schema version written by this version, for every item kind
+const ITEM_SCHEMA_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Largest manifest or item file read from a bundle
+const MAX_ENTRY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Kind of bundled item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleItemKind {
+    Template,
+    Skill,
+    Recipe,
+}
+
+impl BundleItemKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Template => "template",
+            Self::Skill => "skill",
+            Self::Recipe => "recipe",
+        }
+    }
+
+    /// Import order, so dependencies are written first
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Template => 0,
+            Self::Skill => 1,
+            Self::Recipe => 2,
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Template => "templates",
+            Self::Skill => "skills",
+            Self::Recipe => "recipes",
+        }
+    }
+}
+
+/// Reference to a template, skill or recipe
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BundleItemRef {
+    pub kind: BundleItemKind,
+    pub id: String,
+}
+
+/// Item listed in a bundle manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub kind: BundleItemKind,
+    pub id: String,
+    pub name: String,
+    pub schema_version: u32,
+    /// File holding the item within the bundle
+    pub path: String,
+    #[serde(default)]
+    pub dependencies: Vec<BundleItemRef>,
+}
+
+/// Bundle manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub items: Vec<ManifestEntry>,
+}
+
+/// What to do with an item that already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleConflictPolicy {
+    /// Keep the existing item
+    Skip,
+    /// Replace the existing item, keeping its id
+    Overwrite,
+    /// Import under a new id and name
+    Rename,
+}
+
+/// Outcome of importing one item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemImportStatus {
+    Imported,
+    Overwritten,
+    Renamed,
+    Skipped,
+    Failed,
+}
+
+/// Per-item import result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemImportResult {
+    pub kind: BundleItemKind,
+    pub id: String,
+    pub name: String,
+    pub status: ItemImportStatus,
+    /// Id the item was stored under, when it differs from the bundled id
+    pub new_id: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Bundle import summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportResult {
+    pub format_version: u32,
+    pub items: Vec<ItemImportResult>,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Template, skill or recipe with its data
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum BundleItem {
+    Template(Template),
+    Skill(Skill),
+    Recipe(Recipe),
+}
+
+impl BundleItem {
+    fn kind(&self) -> BundleItemKind {
+        match self {
+            Self::Template(_) => BundleItemKind::Template,
+            Self::Skill(_) => BundleItemKind::Skill,
+            Self::Recipe(_) => BundleItemKind::Recipe,
+        }
+    }
+
+    fn id(&self) -> &str {
+        match self {
+            Self::Template(t) => &t.id,
+            Self::Skill(s) => &s.id,
+            Self::Recipe(r) => &r.id,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::Template(t) => &t.name,
+            Self::Skill(s) => &s.name,
+            Self::Recipe(r) => &r.name,
+        }
+    }
+
+    fn set_identity(&mut self, id: String, name: String) {
+        match self {
+            Self::Template(t) => (t.id, t.name) = (id, name),
+            Self::Skill(s) => (s.id, s.name) = (id, name),
+            Self::Recipe(r) => (r.id, r.name) = (id, name),
+        }
+    }
+
+    fn parse(kind: BundleItemKind, data: &str) -> Result<Self, String> {
+        let item = match kind {
+            BundleItemKind::Template => serde_json::from_str(data).map(Self::Template),
+            BundleItemKind::Skill => serde_json::from_str(data).map(Self::Skill),
+            BundleItemKind::Recipe => serde_json::from_str(data).map(Self::Recipe),
+        };
+        item.map_err(|e| format!("Invalid {} data: {}", kind.as_str(), e))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Template(t) => validate_template(t),
+            Self::Skill(s) if s.id.is_empty() || s.name.is_empty() => Err("Skill id and name cannot be empty".to_string()),
+            Self::Skill(s) => {
+                serde_json::from_str::<Vec<serde_json::Value>>(&s.tools)
+                    .map_err(|e| format!("Invalid skill tools: {}", e))?;
+                skill_schema::normalize("Input schema", s.input_schema.clone())?;
+                skill_schema::normalize("Output schema", s.output_schema.clone())?;
+                Ok(())
+            }
+            Self::Recipe(r) if r.id.is_empty() || r.name.is_empty() => Err("Recipe id and name cannot be empty".to_string()),
+            Self::Recipe(r) => serde_json::from_str::<Vec<serde_json::Value>>(&r.steps)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid recipe steps: {}", e)),
+        }
+    }
+}
+
+// ============================================================================
+// Dependencies
+// ============================================================================
+
+/// Kind referenced by a key in recipe steps
+fn reference_kind(key: &str) -> Option<BundleItemKind> {
+    match key {
+        "skillId" | "skill_id" => Some(BundleItemKind::Skill),
+        "templateId" | "template_id" => Some(BundleItemKind::Template),
+        "recipeId" | "recipe_id" => Some(BundleItemKind::Recipe),
+        _ => None,
+    }
+}
+
+/// Visit every reference in a JSON value, letting `visit` replace the id
+fn visit_references(value: &mut serde_json::Value, visit: &mut dyn FnMut(BundleItemKind, &mut String)) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match (reference_kind(key), value) {
+                    (Some(kind), serde_json::Value::String(id)) => visit(kind, id),
+                    (_, value) => visit_references(value, visit),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(|v| visit_references(v, visit)),
+        _ => {}
+    }
+}
+
+fn dependencies(item: &BundleItem) -> Vec<BundleItemRef> {
+    let BundleItem::Recipe(recipe) = item else { return Vec::new() };
+    let Ok(mut steps) = serde_json::from_str::<serde_json::Value>(&recipe.steps) else { return Vec::new() };
+
+    let mut found = Vec::new();
+    visit_references(&mut steps, &mut |kind, id| {
+        let reference = BundleItemRef { kind, id: id.clone() };
+        if !found.contains(&reference) {
+            found.push(reference);
+        }
+    });
+    found
+}
+
+/// Rewrite a recipe's references to items stored under different ids
+fn rewrite_references(item: &mut BundleItem, ids: &HashMap<BundleItemRef, String>) -> Result<(), String> {
+    let BundleItem::Recipe(recipe) = item else { return Ok(()) };
+    let mut steps: serde_json::Value = serde_json::from_str(&recipe.steps).map_err(|e| e.to_string())?;
+    visit_references(&mut steps, &mut |kind, id| {
+        if let Some(new_id) = ids.get(&BundleItemRef { kind, id: id.clone() }) {
+            *id = new_id.clone();
+        }
+    });
+    recipe.steps = steps.to_string();
+    Ok(())
+}
+
+// ============================================================================
+// Database
+// ============================================================================
+
+fn load_item(conn: &Connection, reference: &BundleItemRef) -> Result<Option<BundleItem>, String> {
+    let id = &reference.id;
+    let item = match reference.kind {
+        BundleItemKind::Template => conn
+            .query_row(
+                "SELECT id, name, category, content, visibility, version, created_at, updated_at
+                 FROM templates WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok(BundleItem::Template(Template {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        category: row.get(2)?,
+                        content: row.get(3)?,
+                        visibility: row.get(4)?,
+                        version: row.get(5)?,
+                        created_at: row.get(6)?,
+                        updated_at: row.get(7)?,
+                    }))
+                },
+            )
+            .optional(),
+        BundleItemKind::Skill => conn
+            .query_row(
+                "SELECT id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at
+                 FROM skills WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok(BundleItem::Skill(Skill {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        description: row.get(2)?,
+                        prompt: row.get(3)?,
+                        tools: row.get(4)?,
+                        input_schema: row.get(5)?,
+                        output_schema: row.get(6)?,
+                        created_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                    }))
+                },
+            )
+            .optional(),
+        BundleItemKind::Recipe => conn
+            .query_row(
+                "SELECT id, name, description, version, steps, variables, is_builtin, created_at, updated_at, is_draft
+                 FROM recipes WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok(BundleItem::Recipe(Recipe {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        description: row.get(2)?,
+                        version: row.get(3)?,
+                        steps: row.get(4)?,
+                        variables: row.get(5)?,
+                        is_builtin: row.get::<_, i32>(6)? != 0,
+                        is_draft: row.get(9)?,
+                        created_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                    }))
+                },
+            )
+            .optional(),
+    };
+    item.map_err(|e| e.to_string())
+}
+
+fn exists(conn: &Connection, kind: BundleItemKind, column: &str, value: &str) -> Result<bool, String> {
+    let sql = format!("SELECT COUNT(*) FROM {} WHERE {} = ?1", kind.table(), column);
+    conn.query_row(&sql, [value], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Id of the stored item an incoming item collides with: the same id, or
+/// for skills and recipes, whose names are unique, the same name
+fn find_existing(conn: &Connection, item: &BundleItem) -> Result<Option<String>, String> {
+    let kind = item.kind();
+    if exists(conn, kind, "id", item.id())? {
+        return Ok(Some(item.id().to_string()));
+    }
+    if kind == BundleItemKind::Template {
+        return Ok(None);
+    }
+    let sql = format!("SELECT id FROM {} WHERE name = ?1", kind.table());
+    conn.query_row(&sql, [item.name()], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Name not yet used by another item of the same kind
+fn unique_name(conn: &Connection, kind: BundleItemKind, name: &str) -> Result<String, String> {
+    let mut candidate = format!("{} (imported)", name);
+    let mut n = 2;
+    while exists(conn, kind, "name", &candidate)? {
+        candidate = format!("{} (imported {})", name, n);
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+/// Insert an item, or update the stored item with the same id
+fn save_item(conn: &Connection, item: &BundleItem) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let result = match item {
+        BundleItem::Template(t) => conn.execute(
+            "INSERT INTO templates (id, name, category, content, visibility, version, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+             name = ?2, category = ?3, content = ?4, visibility = ?5, version = ?6, updated_at = ?7",
+            params![t.id, t.name, t.category, t.content, t.visibility, t.version, now],
+        ),
+        BundleItem::Skill(s) => conn.execute(
+            "INSERT INTO skills (id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+             ON CONFLICT(id) DO UPDATE SET
+             name = ?2, description = ?3, prompt = ?4, tools = ?5,
+             input_schema = ?6, output_schema = ?7, updated_at = ?8",
+            params![s.id, s.name, s.description, s.prompt, s.tools, s.input_schema, s.output_schema, now],
+        ),
+        BundleItem::Recipe(r) => conn.execute(
+            "INSERT INTO recipes (id, name, description, version, steps, variables, is_builtin, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+             name = ?2, description = ?3, version = ?4, steps = ?5, variables = ?6, updated_at = ?7",
+            params![r.id, r.name, r.description, r.version, r.steps, r.variables, now],
+        ),
+    };
+    result.map(|_| ()).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Export / Import
+// ============================================================================
+
+/// Write the given items and everything they depend on to a bundle
+pub fn write_bundle(conn: &Connection, items: &[BundleItemRef], path: &Path) -> Result<BundleManifest, String> {
+    let mut pending: Vec<BundleItemRef> = items.to_vec();
+    let mut seen = HashSet::new();
+    let mut collected = Vec::new();
+
+    while let Some(reference) = pending.pop() {
+        if !seen.insert(reference.clone()) {
+            continue;
+        }
+        let item = load_item(conn, &reference)?
+            .ok_or_else(|| format!("{} not found: {}", reference.kind.as_str(), reference.id))?;
+        let dependencies = dependencies(&item);
+        pending.extend(dependencies.iter().cloned());
+        collected.push((item, dependencies));
+    }
+    collected.sort_by_key(|(item, _)| item.kind().rank());
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let zip_error = |e: &dyn std::fmt::Display| format!("Failed to write bundle: {}", e);
+
+    let mut entries = Vec::new();
+    for (n, (item, dependencies)) in collected.iter().enumerate() {
+        let entry = ManifestEntry {
+            kind: item.kind(),
+            id: item.id().to_string(),
+            name: item.name().to_string(),
+            schema_version: ITEM_SCHEMA_VERSION,
+            path: format!("items/{}-{}.json", item.kind().as_str(), n + 1),
+            dependencies: dependencies.clone(),
+        };
+        let data = serde_json::to_vec_pretty(item).map_err(|e| e.to_string())?;
+        zip.start_file(entry.path.as_str(), options).map_err(|e| zip_error(&e))?;
+        zip.write_all(&data).map_err(|e| zip_error(&e))?;
+        entries.push(entry);
+    }
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        items: entries,
+    };
+    zip.start_file(MANIFEST_FILE, options).map_err(|e| zip_error(&e))?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?)
+        .map_err(|e| zip_error(&e))?;
+    zip.finish().map_err(|e| zip_error(&e))?;
+
+    Ok(manifest)
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<String, String> {
+    let file = archive.by_name(name).map_err(|e| format!("Missing {}: {}", name, e))?;
+    if file.size() > MAX_ENTRY_BYTES {
+        return Err(format!("{} exceeds the {} byte limit", name, MAX_ENTRY_BYTES));
+    }
+    let mut data = String::new();
+    file.take(MAX_ENTRY_BYTES)
+        .read_to_string(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(data)
+}
+
+/// Read and check a bundle's manifest
+fn read_manifest(archive: &mut zip::ZipArchive<std::fs::File>) -> Result<BundleManifest, String> {
+    let manifest: BundleManifest = serde_json::from_str(&read_entry(archive, MANIFEST_FILE)?)
+        .map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+    if manifest.format_version == 0 || manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "Bundle format version {} is not supported (supported: {})",
+            manifest.format_version, BUNDLE_FORMAT_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Import one bundled item, recording where it was stored in `ids`
+fn import_item(
+    conn: &Connection,
+    archive: &mut zip::ZipArchive<std::fs::File>,
+    entry: &ManifestEntry,
+    policy: BundleConflictPolicy,
+    ids: &mut HashMap<BundleItemRef, String>,
+) -> Result<(ItemImportStatus, Option<String>), String> {
+    if entry.schema_version == 0 || entry.schema_version > ITEM_SCHEMA_VERSION {
+        return Err(format!("Schema version {} is not supported", entry.schema_version));
+    }
+    let mut item = BundleItem::parse(entry.kind, &read_entry(archive, &entry.path)?)?;
+    if item.kind() != entry.kind || item.id() != entry.id {
+        return Err("Item data does not match the manifest".to_string());
+    }
+    item.validate()?;
+
+    for dependency in &entry.dependencies {
+        if !ids.contains_key(dependency) && !exists(conn, dependency.kind, "id", &dependency.id)? {
+            return Err(format!("Missing dependency {} {}", dependency.kind.as_str(), dependency.id));
+        }
+    }
+    rewrite_references(&mut item, ids)?;
+
+    let reference = BundleItemRef { kind: entry.kind, id: entry.id.clone() };
+    let status = match (find_existing(conn, &item)?, policy) {
+        (None, _) => ItemImportStatus::Imported,
+        (Some(existing), BundleConflictPolicy::Skip) => {
+            ids.insert(reference, existing);
+            return Ok((ItemImportStatus::Skipped, Some("Already exists".to_string())));
+        }
+        (Some(existing), BundleConflictPolicy::Overwrite) => {
+            let name = item.name().to_string();
+            item.set_identity(existing, name);
+            ItemImportStatus::Overwritten
+        }
+        (Some(_), BundleConflictPolicy::Rename) => {
+            let name = unique_name(conn, entry.kind, item.name())?;
+            item.set_identity(uuid::Uuid::new_v4().to_string(), name);
+            ItemImportStatus::Renamed
+        }
+    };
+
+    save_item(conn, &item)?;
+    ids.insert(reference, item.id().to_string());
+    Ok((status, None))
+}
+
+/// Import every item of a bundle
+///
+/// Items are imported independently: one that fails validation, or whose
+/// dependencies are neither in the bundle nor already stored, is reported
+/// as failed without stopping the import.
+pub fn read_bundle(conn: &mut Connection, path: &Path, policy: BundleConflictPolicy) -> Result<BundleImportResult, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid bundle: {}", e))?;
+    let mut manifest = read_manifest(&mut archive)?;
+    manifest.items.sort_by_key(|entry| entry.kind.rank());
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut ids = HashMap::new();
+    let mut items = Vec::new();
+    for entry in &manifest.items {
+        let (status, message) = import_item(&tx, &mut archive, entry, policy, &mut ids)
+            .unwrap_or_else(|e| (ItemImportStatus::Failed, Some(e)));
+        let stored_id = ids.get(&BundleItemRef { kind: entry.kind, id: entry.id.clone() });
+        items.push(ItemImportResult {
+            kind: entry.kind,
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            new_id: stored_id.filter(|id| **id != entry.id).cloned(),
+            status,
+            message,
+        });
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let count = |status: &[ItemImportStatus]| items.iter().filter(|i| status.contains(&i.status)).count();
+    Ok(BundleImportResult {
+        format_version: manifest.format_version,
+        imported: count(&[ItemImportStatus::Imported, ItemImportStatus::Overwritten, ItemImportStatus::Renamed]),
+        skipped: count(&[ItemImportStatus::Skipped]),
+        failed: count(&[ItemImportStatus::Failed]),
+        items,
+    })
+}
+
+// ============================================================================
+// Tauri Commands - Bundles
+// ============================================================================
+
+/// Export templates, skills and recipes with their dependencies to a bundle
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn export_bundle(
+    items: Vec<BundleItemRef>,
+    path: String,
+    db: tauri::State<'_, crate::db::DbState>,
+) -> Result<BundleManifest, AppError> {
+    if items.is_empty() {
+        return Err(AppError::invalid_input("No items to export"));
+    }
+    let mut path = PathBuf::from(path);
+    if path.extension().is_none() {
+        path.set_extension(BUNDLE_EXTENSION);
+    }
+
+    let conn = db.conn.lock()?;
+    Ok(write_bundle(&conn, &items, &path)?)
+}
+
+/// Import a bundle, resolving existing items with `conflict_policy`
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn import_bundle(
+    path: String,
+    conflict_policy: BundleConflictPolicy,
+    db: tauri::State<'_, crate::db::DbState>,
+) -> Result<BundleImportResult, AppError> {
+    let mut conn = db.conn.lock()?;
+    Ok(read_bundle(&mut conn, Path::new(&path), conflict_policy)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn seed(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO templates (id, name, category, content) VALUES ('tpl-1', 'Summary', 'writing', 'Summarize {{text}}')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO skills (id, name, description, prompt, tools) VALUES ('skill-1', 'Summarizer', 'Summarizes', 'Be brief', '[]')",
+            [],
+        )
+        .unwrap();
+        let steps = r#"[{"id":"s1","type":"tool_call","name":"Run","args":{"skillId":"skill-1","templateId":"tpl-1"}}]"#;
+        conn.execute(
+            "INSERT INTO recipes (id, name, steps) VALUES ('recipe-1', 'Daily digest', ?1)",
+            [steps],
+        )
+        .unwrap();
+    }
+
+    fn recipe_steps(conn: &Connection, name: &str) -> serde_json::Value {
+        let steps: String = conn
+            .query_row("SELECT steps FROM recipes WHERE name = ?1", [name], |row| row.get(0))
+            .unwrap();
+        serde_json::from_str(&steps).unwrap()
+    }
+
+    #[test]
+    fn test_export_includes_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("digest.aipack");
+        let conn = test_conn();
+        seed(&conn);
+
+        let recipe = BundleItemRef { kind: BundleItemKind::Recipe, id: "recipe-1".to_string() };
+        let manifest = write_bundle(&conn, &[recipe], &path).unwrap();
+        let kinds: Vec<_> = manifest.items.iter().map(|i| (i.kind, i.id.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (BundleItemKind::Template, "tpl-1"),
+                (BundleItemKind::Skill, "skill-1"),
+                (BundleItemKind::Recipe, "recipe-1"),
+            ]
+        );
+        assert_eq!(manifest.items[2].dependencies.len(), 2);
+
+        let mut fresh = test_conn();
+        let result = read_bundle(&mut fresh, &path, BundleConflictPolicy::Skip).unwrap();
+        assert_eq!((result.imported, result.skipped, result.failed), (3, 0, 0));
+        assert_eq!(recipe_steps(&fresh, "Daily digest")[0]["args"]["skillId"], "skill-1");
+
+        let missing = BundleItemRef { kind: BundleItemKind::Skill, id: "nope".to_string() };
+        assert!(write_bundle(&conn, &[missing], &path).is_err());
+    }
+
+    #[test]
+    fn test_import_conflict_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("digest.aipack");
+        let mut conn = test_conn();
+        seed(&conn);
+        let recipe = BundleItemRef { kind: BundleItemKind::Recipe, id: "recipe-1".to_string() };
+        write_bundle(&conn, &[recipe], &path).unwrap();
+
+        let result = read_bundle(&mut conn, &path, BundleConflictPolicy::Skip).unwrap();
+        assert_eq!((result.imported, result.skipped), (0, 3));
+
+        let result = read_bundle(&mut conn, &path, BundleConflictPolicy::Overwrite).unwrap();
+        assert!(result.items.iter().all(|i| i.status == ItemImportStatus::Overwritten && i.new_id.is_none()));
+
+        let result = read_bundle(&mut conn, &path, BundleConflictPolicy::Rename).unwrap();
+        assert!(result.items.iter().all(|i| i.status == ItemImportStatus::Renamed));
+        let skill_id = result.items[1].new_id.clone().unwrap();
+        let steps = recipe_steps(&conn, "Daily digest (imported)");
+        assert_eq!(steps[0]["args"]["skillId"], skill_id.as_str());
+        assert_eq!(steps[0]["args"]["templateId"], result.items[0].new_id.clone().unwrap().as_str());
+
+        let skills: i64 = conn.query_row("SELECT COUNT(*) FROM skills WHERE name LIKE 'Summarizer%'", [], |row| row.get(0)).unwrap();
+        assert_eq!(skills, 2);
+    }
+}
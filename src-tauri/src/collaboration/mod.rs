@@ -6,8 +6,11 @@ pub mod templates;
 pub mod export_mod;
 pub mod template_io;
 pub mod template_commands;
+pub mod template_diff;
+pub mod bundle;
 pub mod marketplace;
 
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -79,15 +82,14 @@ impl Default for ExportOptions {
 
 /// List all workflows
 #[tauri::command]
-pub fn list_workflows(db: State<'_, crate::db::DbState>) -> Result<Vec<SharedWorkflow>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_workflows(db: State<'_, crate::db::DbState>) -> Result<Vec<SharedWorkflow>, AppError> {
+    let conn = db.conn.lock()?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, description, steps, owner_id, visibility, created_at, updated_at
+    let mut stmt = conn.prepare(
+        "SELECT id, name, description, steps, owner_id, visibility, created_at, updated_at
              FROM shared_workflows ORDER BY created_at DESC",
-        )
-        .map_err(|e| e.to_string())?;
+    )?;
 
     let workflows = stmt
         .query_map([], |row| {
@@ -101,44 +103,45 @@ pub fn list_workflows(db: State<'_, crate::db::DbState>) -> Result<Vec<SharedWor
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(workflows)
 }
 
 /// Get a single workflow by ID
 #[tauri::command]
-pub fn get_workflow(db: State<'_, crate::db::DbState>, id: String) -> Result<SharedWorkflow, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_workflow(
+    db: State<'_, crate::db::DbState>,
+    id: String,
+) -> Result<SharedWorkflow, AppError> {
+    let conn = db.conn.lock()?;
 
-    let workflow = conn
-        .query_row(
-            "SELECT id, name, description, steps, owner_id, visibility, created_at, updated_at
+    let workflow = conn.query_row(
+        "SELECT id, name, description, steps, owner_id, visibility, created_at, updated_at
              FROM shared_workflows WHERE id = ?1",
-            [&id],
-            |row| {
-                Ok(SharedWorkflow {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    steps: row.get(3)?,
-                    owner_id: row.get(4)?,
-                    visibility: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            },
-        )
-        .map_err(|e| e.to_string())?;
+        [&id],
+        |row| {
+            Ok(SharedWorkflow {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                steps: row.get(3)?,
+                owner_id: row.get(4)?,
+                visibility: row.get(5)?,
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        },
+    )?;
 
     Ok(workflow)
 }
 
 /// Create a new workflow
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_workflow(
     db: State<'_, crate::db::DbState>,
     id: String,
@@ -147,8 +150,8 @@ pub fn create_workflow(
     steps: String,
     owner_id: Option<String>,
     visibility: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -165,14 +168,14 @@ pub fn create_workflow(
             &now,
             &now,
         ],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 /// Update an existing workflow
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn update_workflow(
     db: State<'_, crate::db::DbState>,
     id: String,
@@ -181,8 +184,8 @@ pub fn update_workflow(
     steps: Option<String>,
     owner_id: Option<String>,
     visibility: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -216,21 +219,23 @@ pub fn update_workflow(
     }
 
     params.push(id.clone());
-    let sql = format!("UPDATE shared_workflows SET {} WHERE id = ?", updates.join(", "));
+    let sql = format!(
+        "UPDATE shared_workflows SET {} WHERE id = ?",
+        updates.join(", ")
+    );
 
-    conn.execute(&sql, rusqlite::params_from_iter(params.iter()))
-        .map_err(|e| e.to_string())?;
+    conn.execute(&sql, rusqlite::params_from_iter(params.iter()))?;
 
     Ok(())
 }
 
 /// Delete a workflow
 #[tauri::command]
-pub fn delete_workflow(db: State<'_, crate::db::DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_workflow(db: State<'_, crate::db::DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM shared_workflows WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM shared_workflows WHERE id = ?1", [&id])?;
 
     Ok(())
 }
@@ -7,7 +7,10 @@ use crate::collaboration::template_io::{
     export_template_to_json, export_templates_to_json,
     import_templates_from_json, validate_template, ConflictResolution, ImportResult,
 };
+use crate::collaboration::template_diff::{diff_templates, merge_templates, TemplateDiff, TemplateMerge};
 use crate::collaboration::Template;
+use crate::error::AppError;
+use rusqlite::OptionalExtension;
 use tauri::State;
 
 // ============================================================================
@@ -16,11 +19,12 @@ use tauri::State;
 
 /// Export a single template to JSON format
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn export_template(
     id: String,
     db: State<'_, crate::db::DbState>,
-) -> Result<Vec<u8>, String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<Vec<u8>, AppError> {
+    let conn = db.conn.lock()?;
 
     // Get template from database
     let template = conn
@@ -41,26 +45,27 @@ pub async fn export_template(
                 })
             },
         )
-        .map_err(|e| format!("Template not found: {}", e))?;
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Template not found: {}", id)))?;
 
     // Export to JSON
-    export_template_to_json(&template)
+    Ok(export_template_to_json(&template)?)
 }
 
 /// Export all templates to JSON format
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn export_all_templates(
     db: State<'_, crate::db::DbState>,
-) -> Result<Vec<u8>, String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<Vec<u8>, AppError> {
+    let conn = db.conn.lock()?;
 
     // Get all templates
     let mut stmt = conn
         .prepare(
             "SELECT id, name, category, content, visibility, version, created_at, updated_at
              FROM templates ORDER BY category, name",
-        )
-        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        )?;
 
     let templates = stmt
         .query_map([], |row| {
@@ -74,37 +79,36 @@ pub async fn export_all_templates(
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
-        })
-        .map_err(|e| format!("Failed to fetch templates: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to parse templates: {}", e))?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Export to JSON
-    export_templates_to_json(&templates)
+    Ok(export_templates_to_json(&templates)?)
 }
 
 /// Import a single template from JSON format
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn import_template(
     data: Vec<u8>,
     resolution: String, // "skip", "overwrite", "rename", "version"
     db: State<'_, crate::db::DbState>,
-) -> Result<Template, String> {
+) -> Result<Template, AppError> {
     let resolution = match resolution.as_str() {
         "skip" => ConflictResolution::Skip,
         "overwrite" => ConflictResolution::Overwrite,
         "rename" => ConflictResolution::Rename,
         "version" => ConflictResolution::Version,
-        _ => return Err("Invalid resolution strategy".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid resolution strategy")),
     };
 
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+    let conn = db.conn.lock()?;
 
     // Import from JSON
-    let (imported, result) = import_templates_from_json(&data, resolution)?;
+    let (imported, result) = import_templates_from_json(&data, resolution).map_err(AppError::invalid_input)?;
 
     if !result.success {
-        return Err(format!("Import failed: {} errors", result.error_count));
+        return Err(AppError::invalid_input(format!("Import failed: {} errors", result.error_count)));
     }
 
     // Save to database
@@ -126,34 +130,34 @@ pub async fn import_template(
                 &now,
                 &now,
             ],
-        )
-        .map_err(|e| format!("Failed to save template: {}", e))?;
+        )?;
 
         Ok(template.clone())
     } else {
-        Err("No templates imported".to_string())
+        Err(AppError::invalid_input("No templates imported"))
     }
 }
 
 /// Import multiple templates from JSON format
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn import_templates(
     data: Vec<u8>,
     resolution: String,
     db: State<'_, crate::db::DbState>,
-) -> Result<ImportResult, String> {
+) -> Result<ImportResult, AppError> {
     let resolution = match resolution.as_str() {
         "skip" => ConflictResolution::Skip,
         "overwrite" => ConflictResolution::Overwrite,
         "rename" => ConflictResolution::Rename,
         "version" => ConflictResolution::Version,
-        _ => return Err("Invalid resolution strategy".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid resolution strategy")),
     };
 
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+    let conn = db.conn.lock()?;
 
     // Import from JSON
-    let (imported, result) = import_templates_from_json(&data, resolution)?;
+    let (imported, result) = import_templates_from_json(&data, resolution).map_err(AppError::invalid_input)?;
 
     // Save all to database
     let now = chrono::Utc::now().to_rfc3339();
@@ -174,8 +178,7 @@ pub async fn import_templates(
                 &template.created_at,
                 &now,
             ],
-        )
-        .map_err(|e| format!("Failed to save template {}: {}", template.id, e))?;
+        )?;
     }
 
     Ok(result)
@@ -183,17 +186,13 @@ pub async fn import_templates(
 
 /// Validate a template structure without importing
 #[tauri::command]
-pub async fn validate_template_data(data: serde_json::Value) -> Result<bool, String> {
-    // Convert to JSON bytes for validation
-    let json_bytes = serde_json::to_vec(&data)
-        .map_err(|e| format!("Failed to serialize: {}", e))?;
-
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn validate_template_data(data: serde_json::Value) -> Result<bool, AppError> {
     // Try to parse as Template
-    let template: Template = serde_json::from_slice(&json_bytes)
-        .map_err(|e| format!("Invalid template format: {}", e))?;
+    let template: Template = serde_json::from_value(data)?;
 
     // Validate
-    validate_template(&template)?;
+    validate_template(&template).map_err(AppError::invalid_input)?;
     Ok(true)
 }
 
@@ -214,11 +213,12 @@ pub struct TemplateVersion {
 
 /// Get version history for a template
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn get_template_versions(
     id: String,
     db: State<'_, crate::db::DbState>,
-) -> Result<Vec<TemplateVersion>, String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<Vec<TemplateVersion>, AppError> {
+    let conn = db.conn.lock()?;
 
     // Check if table exists (for backward compatibility)
     let table_exists: bool = conn
@@ -239,8 +239,7 @@ pub async fn get_template_versions(
                  FROM template_versions
                  WHERE template_id = ?1
                  ORDER BY version DESC",
-        )
-        .map_err(|e| format!("Failed to query versions: {}", e))?;
+        )?;
 
     let versions = stmt
         .query_map([&id], |row| {
@@ -252,22 +251,21 @@ pub async fn get_template_versions(
                 notes: row.get(4)?,
                 created_at: row.get(5)?,
             })
-        })
-        .map_err(|e| format!("Failed to parse versions: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect versions: {}", e))?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(versions)
 }
 
 /// Create a new version of a template
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn create_template_version(
     id: String,
     notes: String,
     db: State<'_, crate::db::DbState>,
-) -> Result<i64, String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<i64, AppError> {
+    let conn = db.conn.lock()?;
 
     // Ensure table exists
     conn.execute(
@@ -281,8 +279,7 @@ pub async fn create_template_version(
             UNIQUE(template_id, version)
         )",
         [],
-    )
-    .map_err(|e| format!("Failed to create template_versions table: {}", e))?;
+    )?;
 
     // Get current template
     let template: Template = conn
@@ -303,7 +300,8 @@ pub async fn create_template_version(
                 })
             },
         )
-        .map_err(|e| format!("Template not found: {}", e))?;
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Template not found: {}", id)))?;
 
     // Get next version number
     let version: i32 = conn
@@ -311,12 +309,10 @@ pub async fn create_template_version(
             "SELECT COALESCE(MAX(version), 0) + 1 FROM template_versions WHERE template_id = ?1",
             [&id],
             |row| row.get(0),
-        )
-        .map_err(|e| format!("Failed to get next version: {}", e))?;
+        )?;
 
     // Serialize template
-    let content = serde_json::to_string(&template)
-        .map_err(|e| format!("Failed to serialize template: {}", e))?;
+    let content = serde_json::to_string(&template)?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -324,20 +320,20 @@ pub async fn create_template_version(
         "INSERT INTO template_versions (template_id, version, content, notes, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5)",
         [&id, &version.to_string(), &content, &notes, &now],
-    )
-    .map_err(|e| format!("Failed to create version: {}", e))?;
+    )?;
 
     Ok(conn.last_insert_rowid())
 }
 
 /// Rollback a template to a specific version
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn rollback_template(
     id: String,
     version_id: i64,
     db: State<'_, crate::db::DbState>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     // Get version content
     let (_template_id, content): (String, String) = conn
@@ -346,11 +342,11 @@ pub async fn rollback_template(
             [version_id],
             |row| Ok((row.get(0)?, row.get(1)?)),
         )
-        .map_err(|e| format!("Version not found: {}", e))?;
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Version not found: {}", version_id)))?;
 
     // Parse template
-    let template: Template = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse template: {}", e))?;
+    let template: Template = serde_json::from_str(&content)?;
 
     // Update main template
     let now = chrono::Utc::now().to_rfc3339();
@@ -358,25 +354,76 @@ pub async fn rollback_template(
     conn.execute(
         "UPDATE templates SET name = ?1, category = ?2, content = ?3, visibility = ?4, updated_at = ?5 WHERE id = ?6",
         [&template.name, &template.category, &template.content, &template.visibility, &now, &id],
-    )
-    .map_err(|e| format!("Failed to rollback template: {}", e))?;
+    )?;
 
     Ok(())
 }
 
+/// Load a saved version of a template
+fn load_template_version(conn: &rusqlite::Connection, id: &str, version: i32) -> Result<Template, AppError> {
+    let content: String = conn
+        .query_row(
+            "SELECT content FROM template_versions WHERE template_id = ?1 AND version = ?2",
+            rusqlite::params![id, version],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Version {} of template {} not found", version, id)))?;
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Compare two versions of a template line by line
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn diff_template_versions(
+    id: String,
+    v1: i32,
+    v2: i32,
+    db: State<'_, crate::db::DbState>,
+) -> Result<TemplateDiff, AppError> {
+    let conn = db.conn.lock()?;
+
+    let from = load_template_version(&conn, &id, v1)?;
+    let to = load_template_version(&conn, &id, v2)?;
+    Ok(diff_templates(&id, (v1, &from), (v2, &to)))
+}
+
+/// Three-way merge of two versions that diverged from `base`
+///
+/// The merge is returned without being saved; conflicting content is left
+/// between conflict markers.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn merge_template_versions(
+    id: String,
+    base: i32,
+    mine: i32,
+    theirs: i32,
+    db: State<'_, crate::db::DbState>,
+) -> Result<TemplateMerge, AppError> {
+    let conn = db.conn.lock()?;
+
+    let base_template = load_template_version(&conn, &id, base)?;
+    let mine_template = load_template_version(&conn, &id, mine)?;
+    let theirs_template = load_template_version(&conn, &id, theirs)?;
+    Ok(merge_templates((base, &base_template), (mine, &mine_template), (theirs, &theirs_template)))
+}
+
 // ============================================================================
 // Tauri Commands - Team Sharing
 // ============================================================================
 
 /// Share a template to a team
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn share_template_to_team(
     id: String,
     team_id: String,
     permissions: serde_json::Value, // JSON: {read: bool, write: bool, execute: bool}
     db: State<'_, crate::db::DbState>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     // Verify template exists
     conn
@@ -385,11 +432,11 @@ pub async fn share_template_to_team(
             [&id],
             |_| Ok(()),
         )
-        .map_err(|_| format!("Template not found: {}", id))?;
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Template not found: {}", id)))?;
 
     // Parse permissions
-    let permissions_json = serde_json::to_string(&permissions)
-        .map_err(|e| format!("Invalid permissions: {}", e))?;
+    let permissions_json = serde_json::to_string(&permissions)?;
 
     // Ensure table exists
     conn.execute(
@@ -403,8 +450,7 @@ pub async fn share_template_to_team(
             UNIQUE(template_id, team_id)
         )",
         [],
-    )
-    .map_err(|e| format!("Failed to create template_shares table: {}", e))?;
+    )?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -412,19 +458,19 @@ pub async fn share_template_to_team(
         "INSERT OR REPLACE INTO template_shares (template_id, team_id, permissions, shared_by, shared_at)
              VALUES (?1, ?2, ?3, 'system', ?4)",
         [&id, &team_id, &permissions_json, &now],
-    )
-    .map_err(|e| format!("Failed to share template: {}", e))?;
+    )?;
 
     Ok(())
 }
 
 /// Get templates shared to a team
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn get_team_templates(
     team_id: String,
     db: State<'_, crate::db::DbState>,
-) -> Result<Vec<Template>, String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<Vec<Template>, AppError> {
+    let conn = db.conn.lock()?;
 
     // Ensure table exists
     let table_exists: bool = conn
@@ -446,8 +492,7 @@ pub async fn get_team_templates(
                  INNER JOIN template_shares s ON t.id = s.template_id
                  WHERE s.team_id = ?1
                  ORDER BY t.name",
-        )
-        .map_err(|e| format!("Failed to query team templates: {}", e))?;
+        )?;
 
     let templates = stmt
         .query_map([&team_id], |row| {
@@ -461,28 +506,26 @@ pub async fn get_team_templates(
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
-        })
-        .map_err(|e| format!("Failed to parse templates: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect templates: {}", e))?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(templates)
 }
 
 /// Revoke template access from a team
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn revoke_template_access(
     id: String,
     team_id: String,
     db: State<'_, crate::db::DbState>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| format!("DB lock failed: {}", e))?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     conn.execute(
         "DELETE FROM template_shares WHERE template_id = ?1 AND team_id = ?2",
         [&id, &team_id],
-    )
-    .map_err(|e| format!("Failed to revoke access: {}", e))?;
+    )?;
 
     Ok(())
 }
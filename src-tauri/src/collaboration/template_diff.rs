@@ -0,0 +1,279 @@
+//! Template version diffing and merging
+//!
+//! Saved versions hold the whole template as JSON. The `content` of two
+//! versions is compared line by line, while name, category and visibility
+//! are compared as whole values. A three-way merge combines two versions
+//! that both diverged from a common base; overlapping content edits are left
+//! in the merged text between conflict markers for the user to resolve.
+
+use crate::collaboration::Template;
+use serde::{Deserialize, Serialize};
+
+/// Kind of a diff line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Line of a content diff; line numbers are 1-based
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// Line number in the older version, unless inserted
+    pub old_line: Option<usize>,
+    /// Line number in the newer version, unless deleted
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+/// Changed template field other than `content`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Differences between two versions of a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateDiff {
+    pub template_id: String,
+    pub from_version: i32,
+    pub to_version: i32,
+    pub lines: Vec<DiffLine>,
+    pub fields: Vec<FieldChange>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Result of a three-way merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateMerge {
+    /// Merged template; its content contains conflict markers if `conflicts > 0`
+    pub template: Template,
+    /// Conflicting content regions
+    pub conflicts: usize,
+    /// Fields changed differently on both sides; the `mine` value was kept
+    pub conflicting_fields: Vec<String>,
+}
+
+fn strip_newline(line: &str) -> String {
+    line.strip_suffix('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .unwrap_or(line)
+        .to_string()
+}
+
+/// Line diff of two texts, including unchanged lines
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let patch = diffy::create_patch(old, new);
+    let mut lines = Vec::new();
+    let (mut old_at, mut new_at) = (0, 0);
+
+    let equal_until = |lines: &mut Vec<DiffLine>, old_at: &mut usize, new_at: &mut usize, until: usize| {
+        while *old_at < until.min(old_lines.len()) {
+            lines.push(DiffLine {
+                kind: DiffLineKind::Equal,
+                old_line: Some(*old_at + 1),
+                new_line: Some(*new_at + 1),
+                text: old_lines[*old_at].to_string(),
+            });
+            *old_at += 1;
+            *new_at += 1;
+        }
+    };
+
+    for hunk in patch.hunks() {
+        // Hunk ranges are 1-based, except for an empty range
+        let range = hunk.old_range();
+        let start = if range.is_empty() { range.start() } else { range.start() - 1 };
+        equal_until(&mut lines, &mut old_at, &mut new_at, start);
+
+        for line in hunk.lines() {
+            let (kind, text) = match line {
+                diffy::Line::Context(text) => (DiffLineKind::Equal, text),
+                diffy::Line::Delete(text) => (DiffLineKind::Delete, text),
+                diffy::Line::Insert(text) => (DiffLineKind::Insert, text),
+            };
+            let old_line = (kind != DiffLineKind::Insert).then(|| {
+                old_at += 1;
+                old_at
+            });
+            let new_line = (kind != DiffLineKind::Delete).then(|| {
+                new_at += 1;
+                new_at
+            });
+            lines.push(DiffLine { kind, old_line, new_line, text: strip_newline(text) });
+        }
+    }
+    equal_until(&mut lines, &mut old_at, &mut new_at, usize::MAX);
+    lines
+}
+
+/// Name, category and visibility of a template
+fn fields(template: &Template) -> [(&'static str, &str); 3] {
+    [
+        ("name", &template.name),
+        ("category", &template.category),
+        ("visibility", &template.visibility),
+    ]
+}
+
+/// Diff two versions of a template
+pub fn diff_templates(template_id: &str, from: (i32, &Template), to: (i32, &Template)) -> TemplateDiff {
+    let lines = diff_lines(&from.1.content, &to.1.content);
+    let fields = fields(from.1)
+        .into_iter()
+        .zip(fields(to.1))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange {
+            field: field.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+        .collect();
+
+    TemplateDiff {
+        template_id: template_id.to_string(),
+        from_version: from.0,
+        to_version: to.0,
+        insertions: lines.iter().filter(|l| l.kind == DiffLineKind::Insert).count(),
+        deletions: lines.iter().filter(|l| l.kind == DiffLineKind::Delete).count(),
+        lines,
+        fields,
+    }
+}
+
+/// Three-way merge of a field; None when both sides changed it differently
+fn merge_field(base: &str, mine: &str, theirs: &str) -> Option<String> {
+    if mine == theirs || theirs == base {
+        Some(mine.to_string())
+    } else if mine == base {
+        Some(theirs.to_string())
+    } else {
+        None
+    }
+}
+
+/// Label diffy's conflict markers with the merged version numbers
+fn label_markers(text: &str, base: i32, mine: i32, theirs: i32) -> String {
+    text.split_inclusive('\n')
+        .map(|line| match line.trim_end() {
+            "<<<<<<< ours" => format!("<<<<<<< mine (v{})\n", mine),
+            "||||||| original" => format!("||||||| base (v{})\n", base),
+            ">>>>>>> theirs" => format!(">>>>>>> theirs (v{})\n", theirs),
+            _ => line.to_string(),
+        })
+        .collect()
+}
+
+/// Merge two versions that diverged from `base`
+///
+/// Returns the merged template with `mine`'s id, version and timestamps.
+/// Conflicts are written in diff3 style, with the base text between the two
+/// sides.
+pub fn merge_templates(base: (i32, &Template), mine: (i32, &Template), theirs: (i32, &Template)) -> TemplateMerge {
+    let mut merged = mine.1.clone();
+    let mut conflicting_fields = Vec::new();
+
+    let values: Vec<_> = fields(base.1)
+        .into_iter()
+        .zip(fields(mine.1))
+        .zip(fields(theirs.1))
+        .map(|(((field, base), (_, mine)), (_, theirs))| (field, merge_field(base, mine, theirs)))
+        .collect();
+    for (field, value) in values {
+        let Some(value) = value else {
+            conflicting_fields.push(field.to_string());
+            continue;
+        };
+        match field {
+            "name" => merged.name = value,
+            "category" => merged.category = value,
+            _ => merged.visibility = value,
+        }
+    }
+
+    let (content, conflicts) = match diffy::merge(&base.1.content, &mine.1.content, &theirs.1.content) {
+        Ok(content) => (content, 0),
+        Err(content) => {
+            let conflicts = content.lines().filter(|l| l.starts_with("<<<<<<< ")).count();
+            (label_markers(&content, base.0, mine.0, theirs.0), conflicts)
+        }
+    };
+    merged.content = content;
+
+    TemplateMerge { template: merged, conflicts, conflicting_fields }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str, content: &str) -> Template {
+        Template {
+            id: "t1".to_string(),
+            name: name.to_string(),
+            category: "writing".to_string(),
+            content: content.to_string(),
+            visibility: "private".to_string(),
+            version: "1.0.0".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_templates() {
+        let old = template("Email", "Hello {{name}}\nThanks\nBye\n");
+        let new = template("Email reply", "Hello {{name}}\nThank you\nBye\nP.S.\n");
+        let diff = diff_templates("t1", (1, &old), (2, &new));
+
+        let lines: Vec<_> = diff
+            .lines
+            .iter()
+            .map(|l| (l.kind.clone(), l.old_line, l.new_line, l.text.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (DiffLineKind::Equal, Some(1), Some(1), "Hello {{name}}"),
+                (DiffLineKind::Delete, Some(2), None, "Thanks"),
+                (DiffLineKind::Insert, None, Some(2), "Thank you"),
+                (DiffLineKind::Equal, Some(3), Some(3), "Bye"),
+                (DiffLineKind::Insert, None, Some(4), "P.S."),
+            ]
+        );
+        assert_eq!((diff.insertions, diff.deletions), (2, 1));
+        assert_eq!(diff.fields.len(), 1);
+        assert_eq!(diff.fields[0].new, "Email reply");
+
+        let long: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        let edited = long.replace("line 15\n", "line fifteen\n");
+        let lines = diff_lines(&long, &edited);
+        assert_eq!(lines.len(), 21);
+        assert_eq!(lines[14].kind, DiffLineKind::Delete);
+        assert_eq!((lines[20].old_line, lines[20].new_line), (Some(20), Some(20)));
+    }
+
+    #[test]
+    fn test_merge_templates() {
+        let base = template("Email", "a\nb\nc\n");
+        let mine = template("Email v2", "a\nB\nc\n");
+        let theirs = template("Email", "a\nb\nc\nd\n");
+        let merged = merge_templates((1, &base), (2, &mine), (3, &theirs));
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.template.content, "a\nB\nc\nd\n");
+        assert_eq!(merged.template.name, "Email v2");
+
+        let theirs = template("Email v3", "a\nbee\nc\n");
+        let merged = merge_templates((1, &base), (2, &mine), (3, &theirs));
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(merged.conflicting_fields, vec!["name".to_string()]);
+        assert!(merged.template.content.contains("<<<<<<< mine (v2)\nB\n||||||| base (v1)\nb\n=======\nbee\n>>>>>>> theirs (v3)\n"));
+    }
+}
@@ -0,0 +1,301 @@
+// Command tracing - Request ids, timings and the slow command history
+//
+// Every Tauri command runs in a span with the `command` target, opened by
+// `#[tracing::instrument(target = "command", skip_all, err(Debug))]` on the
+// command function. `CommandLayer` gives each of these spans a request id,
+// logs when the command starts and finishes with its duration and the kind
+// of error it failed with, and keeps the request id of the command running
+// on the current thread so sidecar calls can forward it to the agent
+// runtime. Finished commands go into a `CommandHistory` of the last
+// `HISTORY_SIZE`, which `get_slow_commands` filters by duration.
+
+use chrono::{DateTime, Utc};
+use crate::error::{AppError, ErrorKind};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Target of command spans and of the events logged for them
+pub const TARGET: &str = "command";
+
+/// Finished commands kept for `get_slow_commands`
+const HISTORY_SIZE: usize = 500;
+
+/// Commands taking longer are logged at INFO instead of DEBUG
+const SLOW_COMMAND: Duration = Duration::from_secs(1);
+
+thread_local! {
+    /// Request ids of the command spans entered on this thread, innermost last
+    static CURRENT: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Request id of the command running on this thread, if any
+pub fn current_request_id() -> Option<String> {
+    CURRENT.with(|ids| ids.borrow().last().cloned())
+}
+
+/// Finished command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandRecord {
+    pub request_id: String,
+    pub command: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// Set when the command returned an error
+    pub error_kind: Option<ErrorKind>,
+}
+
+/// Recently finished commands, shared by the layer and the app state
+#[derive(Clone, Default)]
+pub struct CommandHistory {
+    records: Arc<Mutex<VecDeque<CommandRecord>>>,
+}
+
+impl CommandHistory {
+    fn push(&self, record: CommandRecord) {
+        let Ok(mut records) = self.records.lock() else {
+            return;
+        };
+        if records.len() == HISTORY_SIZE {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Commands that took at least `threshold_ms`, newest first
+    pub fn slow(&self, threshold_ms: u64) -> Result<Vec<CommandRecord>, String> {
+        let records = self.records.lock().map_err(|e| e.to_string())?;
+        Ok(records.iter().rev().filter(|r| r.duration_ms >= threshold_ms).cloned().collect())
+    }
+}
+
+/// Command in flight, kept in the extensions of its span
+struct Call {
+    request_id: String,
+    started_at: DateTime<Utc>,
+    started: Instant,
+    error_kind: Option<ErrorKind>,
+}
+
+/// Error of an `err(Debug)` event; `AppError` prints as its JSON
+#[derive(Deserialize)]
+struct ReportedError {
+    kind: ErrorKind,
+}
+
+/// Reads the `error` field of an event
+#[derive(Default)]
+struct ErrorVisitor {
+    error: Option<String>,
+}
+
+impl Visit for ErrorVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "error" {
+            self.error = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Layer tracing the spans of Tauri commands
+pub struct CommandLayer {
+    history: CommandHistory,
+}
+
+impl CommandLayer {
+    pub fn new(history: CommandHistory) -> Self {
+        Self { history }
+    }
+}
+
+impl<S> Layer<S> for CommandLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().target() != TARGET {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        // A command called by another one keeps the caller's request id
+        let request_id = current_request_id().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        tracing::debug!(target: TARGET, command = span.name(), request_id = %request_id, "Command started");
+        span.extensions_mut().insert(Call {
+            request_id,
+            started_at: Utc::now(),
+            started: Instant::now(),
+            error_kind: None,
+        });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let request_id = span.extensions().get::<Call>().map(|call| call.request_id.clone());
+        if let Some(request_id) = request_id {
+            CURRENT.with(|ids| ids.borrow_mut().push(request_id));
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let traced = span.extensions().get::<Call>().is_some();
+        if traced {
+            CURRENT.with(|ids| ids.borrow_mut().pop());
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() != TARGET {
+            return;
+        }
+        let mut visitor = ErrorVisitor::default();
+        event.record(&mut visitor);
+        let Some(error) = visitor.error else {
+            return;
+        };
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        // Commands still returning a plain `String` fail as internal errors
+        let kind = serde_json::from_str::<ReportedError>(&error).map(|e| e.kind).unwrap_or(ErrorKind::Internal);
+        let mut extensions = span.extensions_mut();
+        if let Some(call) = extensions.get_mut::<Call>() {
+            call.error_kind = Some(kind);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(call) = span.extensions_mut().remove::<Call>() else {
+            return;
+        };
+        let duration = call.started.elapsed();
+        let duration_ms = duration.as_millis() as u64;
+        let command = span.name();
+        match call.error_kind {
+            Some(kind) => tracing::warn!(
+                target: TARGET, command, request_id = %call.request_id, duration_ms, error_kind = ?kind,
+                "Command failed"
+            ),
+            None if duration >= SLOW_COMMAND => tracing::info!(
+                target: TARGET, command, request_id = %call.request_id, duration_ms,
+                "Command finished"
+            ),
+            None => tracing::debug!(
+                target: TARGET, command, request_id = %call.request_id, duration_ms,
+                "Command finished"
+            ),
+        }
+
+        self.history.push(CommandRecord {
+            request_id: call.request_id,
+            command: command.to_string(),
+            started_at: call.started_at.to_rfc3339(),
+            duration_ms,
+            error_kind: call.error_kind,
+        });
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Recent commands that took at least `threshold_ms`, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_slow_commands(
+    history: tauri::State<'_, CommandHistory>,
+    threshold_ms: u64,
+) -> Result<Vec<CommandRecord>, AppError> {
+    Ok(history.slow(threshold_ms)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[tracing::instrument(target = "command", skip_all, err(Debug))]
+    fn load_item(found: bool) -> Result<Option<String>, AppError> {
+        if !found {
+            return Err(AppError::not_found("No such item"));
+        }
+        Ok(current_request_id())
+    }
+
+    #[tracing::instrument(target = "command", skip_all, err(Debug))]
+    fn legacy_command() -> Result<(), String> {
+        Err("boom".to_string())
+    }
+
+    #[tracing::instrument(target = "command", skip_all)]
+    fn nested_command() -> (Option<String>, Option<String>) {
+        let outer = current_request_id();
+        let inner = load_item(true).unwrap();
+        (outer, inner)
+    }
+
+    #[test]
+    fn test_layer_records_commands() {
+        let history = CommandHistory::default();
+        let subscriber = tracing_subscriber::registry().with(CommandLayer::new(history.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(load_item(true).unwrap().is_some());
+            assert!(load_item(false).is_err());
+            assert!(legacy_command().is_err());
+            let (outer, inner) = nested_command();
+            assert!(outer.is_some());
+            assert_eq!(outer, inner);
+        });
+        assert_eq!(current_request_id(), None);
+
+        let records = history.slow(0).unwrap();
+        let commands: Vec<_> = records.iter().map(|r| r.command.as_str()).collect();
+        assert_eq!(commands, vec!["nested_command", "load_item", "legacy_command", "load_item", "load_item"]);
+        assert_eq!(records[0].request_id, records[1].request_id);
+        assert_ne!(records[3].request_id, records[4].request_id);
+        assert_eq!(records[2].error_kind, Some(ErrorKind::Internal));
+        assert_eq!(records[3].error_kind, Some(ErrorKind::NotFound));
+        assert_eq!(records[4].error_kind, None);
+    }
+
+    #[test]
+    fn test_history_keeps_recent_slow_commands() {
+        let history = CommandHistory::default();
+        for i in 0..HISTORY_SIZE as u64 + 10 {
+            history.push(CommandRecord {
+                request_id: i.to_string(),
+                command: "list_conversations".to_string(),
+                started_at: Utc::now().to_rfc3339(),
+                duration_ms: i % 100,
+                error_kind: None,
+            });
+        }
+        assert_eq!(history.slow(0).unwrap().len(), HISTORY_SIZE);
+
+        let slow = history.slow(95).unwrap();
+        assert_eq!(slow.len(), 25);
+        assert!(slow.iter().all(|r| r.duration_ms >= 95));
+        assert_eq!(slow[0].request_id, "499");
+        assert_eq!(slow.last().unwrap().request_id, "95");
+    }
+}
@@ -0,0 +1,307 @@
+//! Response Comparison
+//!
+//! `compare_chat` sends one prompt to several providers at once. Their
+//! answers are stored as a single assistant message: its content is the
+//! selected answer, which later turns, search and exports see, and its
+//! metadata keeps every answer with its latency and token usage under
+//! `comparison`, so another one can be picked later. The first pick is
+//! the fastest answer without an error.
+//!
+//! In sensitive conversations the answers in the metadata are encrypted
+//! with the conversation key, like the message content.
+
+use crate::db::{columns, conversation_key, DbState};
+use crate::error::AppError;
+use crate::security::field_encryption::{decrypt_field, encrypt_field};
+use crate::security::{CredentialManager, SensitiveSession};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Most providers one prompt is sent to
+pub const MAX_COMPARED_PROVIDERS: usize = 6;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// One provider's answer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ComparedResponse {
+    pub content: String,
+    pub latency_ms: u64,
+    pub usage: Option<TokenUsage>,
+    pub error: Option<String>,
+}
+
+/// Answers of a comparison, by provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    /// Stored message; None without a conversation or when every provider failed
+    pub message_id: Option<String>,
+    pub results: BTreeMap<String, ComparedResponse>,
+    pub selected: Option<String>,
+}
+
+/// Trimmed, de-duplicated provider names, in the given order
+pub fn normalize_providers(providers: Vec<String>) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in providers.into_iter().map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return Err("Select at least one provider to compare".to_string());
+    }
+    if names.len() > MAX_COMPARED_PROVIDERS {
+        return Err(format!("At most {} providers can be compared at once", MAX_COMPARED_PROVIDERS));
+    }
+    Ok(names)
+}
+
+/// Answers in an agent runtime `compare_chat` result; providers it did not
+/// answer for are reported as failed
+pub fn parse_results(result: &Value, providers: &[String]) -> BTreeMap<String, ComparedResponse> {
+    providers
+        .iter()
+        .map(|name| {
+            let response = result["results"]
+                .get(name)
+                .and_then(|r| serde_json::from_value(r.clone()).ok())
+                .unwrap_or_else(|| ComparedResponse {
+                    error: Some("No response from the agent runtime".to_string()),
+                    ..Default::default()
+                });
+            (name.clone(), response)
+        })
+        .collect()
+}
+
+/// The fastest answer without an error
+pub fn default_selection(results: &BTreeMap<String, ComparedResponse>) -> Option<String> {
+    results
+        .iter()
+        .filter(|(_, r)| r.error.is_none())
+        .min_by_key(|(_, r)| r.latency_ms)
+        .map(|(name, _)| name.clone())
+}
+
+fn encrypt(content: &str, key: Option<&[u8; 32]>) -> Result<String, String> {
+    match key {
+        Some(key) => encrypt_field(content, key).map_err(|e| e.to_string()),
+        None => Ok(content.to_string()),
+    }
+}
+
+fn decrypt(content: &str, key: Option<&[u8; 32]>) -> Result<String, String> {
+    match key {
+        Some(key) => decrypt_field(content, key).map_err(|e| e.to_string()),
+        None => Ok(content.to_string()),
+    }
+}
+
+fn metadata(results: &BTreeMap<String, ComparedResponse>, selected: &str, key: Option<&[u8; 32]>) -> Result<String, String> {
+    let mut stored = results.clone();
+    for response in stored.values_mut() {
+        response.content = encrypt(&response.content, key)?;
+    }
+    Ok(json!({ "provider": selected, "comparison": { "results": stored, "selected": selected } }).to_string())
+}
+
+/// Store the answers as an assistant message of `conversation_id`; `key` is
+/// the conversation key of a sensitive conversation
+pub fn store(
+    conn: &Connection,
+    key: Option<&[u8; 32]>,
+    conversation_id: &str,
+    results: BTreeMap<String, ComparedResponse>,
+) -> Result<Comparison, String> {
+    let Some(selected) = default_selection(&results) else {
+        return Ok(Comparison { message_id: None, results, selected: None });
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at, is_encrypted)
+         VALUES (?1, ?2, 'assistant', ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            id,
+            conversation_id,
+            encrypt(&results[&selected].content, key)?,
+            columns::seal(&metadata(&results, &selected, key)?)?,
+            now,
+            key.is_some()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("UPDATE conversations SET updated_at = ?1 WHERE id = ?2", [&now, conversation_id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(Comparison { message_id: Some(id), results, selected: Some(selected) })
+}
+
+fn message_conversation(conn: &Connection, message_id: &str) -> Result<String, String> {
+    conn.query_row("SELECT conversation_id FROM messages WHERE id = ?1", [message_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Message not found: {}", message_id))
+}
+
+/// The stored comparison of a message, decrypted with `key`
+pub fn load(conn: &Connection, key: Option<&[u8; 32]>, message_id: &str) -> Result<Comparison, String> {
+    let metadata: Option<String> = conn
+        .query_row("SELECT metadata FROM messages WHERE id = ?1", [message_id], |row| columns::get_optional(row, 0))
+        .map_err(|e| e.to_string())?;
+    let metadata: Value = serde_json::from_str(&metadata.unwrap_or_default()).unwrap_or_default();
+    let comparison = &metadata["comparison"];
+    let mut results: BTreeMap<String, ComparedResponse> = serde_json::from_value(comparison["results"].clone())
+        .map_err(|_| format!("Message {} is not a comparison", message_id))?;
+    for response in results.values_mut() {
+        response.content = decrypt(&response.content, key)?;
+    }
+
+    Ok(Comparison {
+        message_id: Some(message_id.to_string()),
+        results,
+        selected: comparison["selected"].as_str().map(String::from),
+    })
+}
+
+/// Make `provider`'s answer the content of a comparison message
+pub fn select(conn: &Connection, key: Option<&[u8; 32]>, message_id: &str, provider: &str) -> Result<Comparison, String> {
+    let mut comparison = load(conn, key, message_id)?;
+    let response = comparison
+        .results
+        .get(provider)
+        .ok_or_else(|| format!("No answer from {} in this comparison", provider))?;
+    if let Some(error) = &response.error {
+        return Err(format!("{} failed: {}", provider, error));
+    }
+
+    conn.execute(
+        "UPDATE messages SET content = ?1, metadata = ?2 WHERE id = ?3",
+        [
+            &encrypt(&response.content, key)?,
+            &columns::seal(&metadata(&comparison.results, provider, key)?)?,
+            message_id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    comparison.selected = Some(provider.to_string());
+    Ok(comparison)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_message_comparison(
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    message_id: String,
+) -> Result<Comparison, AppError> {
+    let conn = db.read()?;
+    let conversation_id = message_conversation(&conn, &message_id)?;
+    let key = conversation_key(&conn, &session, &credentials, &conversation_id)?;
+    Ok(load(&conn, key.as_ref(), &message_id)?)
+}
+
+/// Pick the answer of a comparison to keep as the message content
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn select_compared_response(
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    message_id: String,
+    provider: String,
+) -> Result<Comparison, AppError> {
+    let conn = db.conn.lock()?;
+    let conversation_id = message_conversation(&conn, &message_id)?;
+    let key = conversation_key(&conn, &session, &credentials, &conversation_id)?;
+    Ok(select(&conn, key.as_ref(), &message_id, &provider)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Compare')", []).unwrap();
+        conn
+    }
+
+    fn response(content: &str, latency_ms: u64, error: Option<&str>) -> ComparedResponse {
+        ComparedResponse {
+            content: content.to_string(),
+            latency_ms,
+            usage: Some(TokenUsage { prompt_tokens: 12, completion_tokens: 40 }),
+            error: error.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_select_defaults() {
+        let providers = normalize_providers(vec![" openai".into(), "anthropic".into(), "openai".into(), "ollama".into()]).unwrap();
+        assert_eq!(providers, vec!["openai", "anthropic", "ollama"]);
+        assert!(normalize_providers(vec![" ".into()]).is_err());
+        assert!(normalize_providers((0..7).map(|i| format!("p{}", i)).collect()).is_err());
+
+        let result = json!({ "results": {
+            "openai": { "content": "A", "latencyMs": 900, "usage": { "promptTokens": 10, "completionTokens": 5 } },
+            "anthropic": { "content": "", "latencyMs": 100, "error": "rate limited" },
+        }});
+        let results = parse_results(&result, &providers);
+        assert_eq!(results["openai"].usage, Some(TokenUsage { prompt_tokens: 10, completion_tokens: 5 }));
+        assert!(results["ollama"].error.is_some());
+        // The faster answer failed, so the slower one is picked
+        assert_eq!(default_selection(&results).as_deref(), Some("openai"));
+    }
+
+    #[test]
+    fn test_store_and_select() {
+        let conn = setup();
+        let key = [7u8; 32];
+        let results = BTreeMap::from([
+            ("anthropic".to_string(), response("Careful answer", 1800, None)),
+            ("ollama".to_string(), response("Quick answer", 400, None)),
+            ("openai".to_string(), response("", 50, Some("timeout"))),
+        ]);
+
+        let stored = store(&conn, Some(&key), "c1", results).unwrap();
+        let id = stored.message_id.unwrap();
+        assert_eq!(stored.selected.as_deref(), Some("ollama"));
+        let content: String = conn.query_row("SELECT content FROM messages WHERE id = ?1", [&id], |r| r.get(0)).unwrap();
+        assert_eq!(decrypt_field(&content, &key).unwrap(), "Quick answer");
+
+        let chosen = select(&conn, Some(&key), &id, "anthropic").unwrap();
+        assert_eq!(chosen.results["anthropic"].content, "Careful answer");
+        let (content, metadata): (String, String) = conn
+            .query_row("SELECT content, metadata FROM messages WHERE id = ?1", [&id], |r| Ok((r.get(0)?, columns::get(r, 1)?)))
+            .unwrap();
+        assert_eq!(decrypt_field(&content, &key).unwrap(), "Careful answer");
+        assert!(!metadata.contains("Careful answer"));
+        assert!(metadata.contains(r#""provider":"anthropic""#));
+        assert_eq!(load(&conn, Some(&key), &id).unwrap().selected.as_deref(), Some("anthropic"));
+
+        assert!(select(&conn, Some(&key), &id, "openai").unwrap_err().contains("timeout"));
+        assert!(select(&conn, Some(&key), &id, "gemini").is_err());
+
+        let failed = BTreeMap::from([("openai".to_string(), response("", 10, Some("down")))]);
+        assert_eq!(store(&conn, None, "c1", failed).unwrap().message_id, None);
+    }
+}
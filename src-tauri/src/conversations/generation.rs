@@ -0,0 +1,235 @@
+//! Generation Parameters
+//!
+//! Temperature, top P, max tokens, stop sequences and seed of a chat
+//! request. A conversation may keep defaults for them; a request's own
+//! parameters win field by field, then the conversation's, then the
+//! workspace temperature, and whatever is still unset is left to the
+//! provider. Before a request is sent the parameters are checked against
+//! what its provider accepts: Anthropic takes no seed and a temperature of
+//! at most 1, OpenAI and Gemini limit the number of stop sequences.
+
+use crate::db::DbState;
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+const MAX_STOP_LENGTH: usize = 100;
+/// Stop sequences accepted when the provider sets no lower limit
+const MAX_STOP_SEQUENCES: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GenerationParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub stop: Option<Vec<String>>,
+    pub seed: Option<u64>,
+}
+
+/// What a provider type accepts
+struct Limits {
+    max_temperature: f32,
+    max_stop_sequences: usize,
+    seed: bool,
+}
+
+fn limits(provider_type: Option<&str>) -> Limits {
+    match provider_type {
+        Some("anthropic") => Limits { max_temperature: 1.0, max_stop_sequences: MAX_STOP_SEQUENCES, seed: false },
+        Some("openai") => Limits { max_temperature: 2.0, max_stop_sequences: 4, seed: true },
+        Some("gemini") => Limits { max_temperature: 2.0, max_stop_sequences: 5, seed: true },
+        _ => Limits { max_temperature: 2.0, max_stop_sequences: MAX_STOP_SEQUENCES, seed: true },
+    }
+}
+
+impl GenerationParams {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fill unset fields from `fallback`
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            temperature: self.temperature.or(fallback.temperature),
+            top_p: self.top_p.or(fallback.top_p),
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+            stop: self.stop.or(fallback.stop),
+            seed: self.seed.or(fallback.seed),
+        }
+    }
+
+    /// Check the parameters against a provider type; only the limits every
+    /// provider shares when the type is unknown
+    pub fn validate(&self, provider_type: Option<&str>) -> Result<(), AppError> {
+        let limits = limits(provider_type);
+        let name = provider_type.unwrap_or("the provider");
+
+        if let Some(t) = self.temperature {
+            if !(0.0..=limits.max_temperature).contains(&t) {
+                return Err(AppError::invalid_input(format!("Temperature for {} must be between 0 and {}", name, limits.max_temperature)));
+            }
+        }
+        if self.top_p.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+            return Err(AppError::invalid_input("Top P must be between 0 and 1"));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(AppError::invalid_input("Max tokens must be positive"));
+        }
+        if let Some(stop) = &self.stop {
+            if stop.len() > limits.max_stop_sequences {
+                return Err(AppError::invalid_input(format!("{} accepts at most {} stop sequences", name, limits.max_stop_sequences)));
+            }
+            if stop.iter().any(|s| s.is_empty() || s.chars().count() > MAX_STOP_LENGTH) {
+                return Err(AppError::invalid_input(format!("Stop sequences must be 1 to {} characters", MAX_STOP_LENGTH)));
+            }
+        }
+        if self.seed.is_some() && !limits.seed {
+            return Err(AppError::invalid_input(format!("{} does not support a seed", name)));
+        }
+        Ok(())
+    }
+
+    /// Write the set parameters into sidecar chat options
+    pub fn write_options(&self, options: &mut serde_json::Value) {
+        let fields = [
+            ("temperature", self.temperature.map(serde_json::Value::from)),
+            ("topP", self.top_p.map(serde_json::Value::from)),
+            ("maxTokens", self.max_tokens.map(serde_json::Value::from)),
+            ("stop", self.stop.clone().map(serde_json::Value::from)),
+            ("seed", self.seed.map(serde_json::Value::from)),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                options[key] = value;
+            }
+        }
+    }
+}
+
+/// Defaults kept for a conversation
+pub fn conversation_defaults(conn: &Connection, conversation_id: &str) -> Result<GenerationParams, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT params FROM conversation_generation_params WHERE conversation_id = ?1",
+            [conversation_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(stored.and_then(|p| serde_json::from_str(&p).ok()).unwrap_or_default())
+}
+
+/// Replace a conversation's defaults; empty parameters remove them. Only
+/// the shared limits are checked, since each message may pick a provider.
+pub fn set_conversation_defaults(
+    conn: &Connection,
+    conversation_id: &str,
+    params: GenerationParams,
+) -> Result<GenerationParams, AppError> {
+    let params = GenerationParams {
+        stop: params.stop.filter(|s| !s.is_empty()),
+        ..params
+    };
+    params.validate(None)?;
+
+    if params.is_empty() {
+        conn.execute("DELETE FROM conversation_generation_params WHERE conversation_id = ?1", [conversation_id])?;
+        return Ok(params);
+    }
+    let exists: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM conversations WHERE id = ?1)", [conversation_id], |row| row.get(0))?;
+    if !exists {
+        return Err(AppError::not_found(format!("Conversation not found: {}", conversation_id)));
+    }
+
+    conn.execute(
+        "INSERT INTO conversation_generation_params (conversation_id, params, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(conversation_id) DO UPDATE SET params = excluded.params, updated_at = excluded.updated_at",
+        params![
+            conversation_id,
+            serde_json::to_string(&params)?,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(params)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_conversation_generation_params(
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+) -> Result<GenerationParams, AppError> {
+    let conn = db.read()?;
+    Ok(conversation_defaults(&conn, &conversation_id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_conversation_generation_params(
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+    params: GenerationParams,
+) -> Result<GenerationParams, AppError> {
+    let conn = db.conn.lock()?;
+    set_conversation_defaults(&conn, &conversation_id, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_validate_per_provider() {
+        let params = |json: serde_json::Value| serde_json::from_value::<GenerationParams>(json).unwrap();
+
+        let warm = params(serde_json::json!({ "temperature": 1.5 }));
+        assert!(warm.validate(Some("openai")).is_ok());
+        assert!(warm.validate(Some("anthropic")).unwrap_err().to_string().contains("between 0 and 1"));
+
+        let seeded = params(serde_json::json!({ "seed": 42 }));
+        assert!(seeded.validate(Some("ollama")).is_ok());
+        assert!(seeded.validate(Some("anthropic")).unwrap_err().to_string().contains("seed"));
+
+        let stops = params(serde_json::json!({ "stop": ["a", "b", "c", "d", "e"] }));
+        assert!(stops.validate(Some("openai")).unwrap_err().to_string().contains("at most 4"));
+        assert!(stops.validate(Some("gemini")).is_ok());
+        assert!(params(serde_json::json!({ "stop": [""] })).validate(None).is_err());
+        assert!(params(serde_json::json!({ "topP": 1.2 })).validate(None).is_err());
+        assert!(params(serde_json::json!({ "maxTokens": 0 })).validate(None).is_err());
+
+        // Request values win field by field, and only set fields reach the options
+        let merged = params(serde_json::json!({ "temperature": 0.2 }))
+            .or(params(serde_json::json!({ "temperature": 0.9, "stop": ["END"] })));
+        let mut options = serde_json::json!({ "provider": "openai", "temperature": 0.5 });
+        merged.write_options(&mut options);
+        assert_eq!(options, serde_json::json!({ "provider": "openai", "temperature": 0.2f32, "stop": ["END"] }));
+    }
+
+    #[test]
+    fn test_conversation_defaults() {
+        let conn = test_conn();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Params')", []).unwrap();
+
+        assert!(conversation_defaults(&conn, "c1").unwrap().is_empty());
+        let saved = GenerationParams { max_tokens: Some(512), stop: Some(vec![]), seed: Some(7), ..Default::default() };
+        let saved = set_conversation_defaults(&conn, "c1", saved).unwrap();
+        assert_eq!(saved.stop, None);
+        assert_eq!(conversation_defaults(&conn, "c1").unwrap(), saved);
+
+        let bad = GenerationParams { temperature: Some(3.0), ..Default::default() };
+        let err = set_conversation_defaults(&conn, "c1", bad).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidInput);
+        let err = set_conversation_defaults(&conn, "missing", saved).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::NotFound);
+
+        set_conversation_defaults(&conn, "c1", GenerationParams::default()).unwrap();
+        assert!(conversation_defaults(&conn, "c1").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,671 @@
+//! Chat history imported from other tools
+//!
+//! Reads conversations from a ChatGPT export (`conversations.json`), a
+//! Claude data export (`conversations.json`), or Markdown transcripts, and
+//! stores them as regular conversations. The JSON exports may be given as
+//! the file, the unzipped export folder or the export zip itself.
+//!
+//! Every imported message records where it came from in its metadata
+//! (`importedFrom`). Imported conversations are remembered by source and
+//! external id in `imported_conversations`, so importing the same export
+//! again skips what is already there; a conversation deleted since is
+//! imported again.
+
+use crate::db::columns;
+use chrono::{DateTime, Duration, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// Export format to import from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Chatgpt,
+    Claude,
+    Markdown,
+}
+
+impl ImportFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "chatgpt" | "openai" => Some(Self::Chatgpt),
+            "claude" | "anthropic" => Some(Self::Claude),
+            "markdown" | "md" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chatgpt => "chatgpt",
+            Self::Claude => "claude",
+            Self::Markdown => "markdown",
+        }
+    }
+
+    /// Provider recorded on imported assistant messages
+    fn provider(&self) -> Option<&'static str> {
+        match self {
+            Self::Chatgpt => Some("openai"),
+            Self::Claude => Some("anthropic"),
+            Self::Markdown => None,
+        }
+    }
+}
+
+/// Conversation read from an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalConversation {
+    pub external_id: String,
+    pub title: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub messages: Vec<ExternalMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalMessage {
+    pub external_id: Option<String>,
+    /// `user`, `assistant` or `system`
+    pub role: &'static str,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+/// Outcome for one conversation of the export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedItem {
+    pub external_id: String,
+    pub title: String,
+    pub status: ImportStatus,
+    pub conversation_id: Option<String>,
+    pub messages: usize,
+    pub reason: Option<String>,
+}
+
+/// Import summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Messages stored across the imported conversations
+    pub messages: usize,
+    pub items: Vec<ImportedItem>,
+}
+
+// ============================================================================
+// Reading exports
+// ============================================================================
+
+/// Conversations of the export at `path`
+pub fn read_export(format: ImportFormat, path: &Path) -> Result<Vec<ExternalConversation>, String> {
+    match format {
+        ImportFormat::Chatgpt => parse_chatgpt(&read_export_json(path)?),
+        ImportFormat::Claude => parse_claude(&read_export_json(path)?),
+        ImportFormat::Markdown => read_markdown(path),
+    }
+}
+
+/// `conversations.json` of an export file, folder or zip
+fn read_export_json(path: &Path) -> Result<Value, String> {
+    let text = if path.is_dir() {
+        let file = path.join("conversations.json");
+        std::fs::read_to_string(&file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?
+    } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid export archive: {}", e))?;
+        let name = archive
+            .file_names()
+            .filter(|name| name.rsplit('/').next() == Some("conversations.json"))
+            .min_by_key(|name| name.len())
+            .map(String::from)
+            .ok_or("The export archive has no conversations.json")?;
+        let mut text = String::new();
+        archive
+            .by_name(&name)
+            .map_err(|e| e.to_string())?
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        text
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    };
+    serde_json::from_str(&text).map_err(|e| format!("Invalid export: {}", e))
+}
+
+fn conversations_array(export: &Value) -> Result<&Vec<Value>, String> {
+    export.as_array().ok_or_else(|| "Expected a list of conversations".to_string())
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(String::from)
+}
+
+fn epoch_time(value: &Value) -> Option<DateTime<Utc>> {
+    let seconds = value.as_f64()?;
+    DateTime::from_timestamp_millis((seconds * 1000.0) as i64)
+}
+
+fn iso_time(value: &Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// ChatGPT `conversations.json`: each conversation is a tree of message
+/// nodes; the branch ending at `current_node` is the one the user saw
+pub fn parse_chatgpt(export: &Value) -> Result<Vec<ExternalConversation>, String> {
+    let mut conversations = Vec::new();
+    for conversation in conversations_array(export)? {
+        let Some(external_id) = str_field(conversation, "conversation_id").or_else(|| str_field(conversation, "id")) else {
+            continue;
+        };
+        let mapping = conversation.get("mapping").and_then(|m| m.as_object());
+        let mut branch = Vec::new();
+        if let Some(mapping) = mapping {
+            let mut node_id = str_field(conversation, "current_node");
+            while let Some(node) = node_id.as_deref().and_then(|id| mapping.get(id)) {
+                branch.push(node);
+                node_id = str_field(node, "parent");
+                if branch.len() > mapping.len() {
+                    break;
+                }
+            }
+            branch.reverse();
+        }
+
+        let messages = branch
+            .into_iter()
+            .filter_map(|node| node.get("message").filter(|m| !m.is_null()))
+            .filter_map(|message| {
+                let hidden = message
+                    .pointer("/metadata/is_visually_hidden_from_conversation")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let role = match message.pointer("/author/role").and_then(|v| v.as_str())? {
+                    "user" => "user",
+                    "assistant" => "assistant",
+                    "system" => "system",
+                    _ => return None,
+                };
+                let content = message.get("content")?;
+                let text = match content.get("content_type").and_then(|v| v.as_str())? {
+                    "text" | "multimodal_text" => content
+                        .get("parts")?
+                        .as_array()?
+                        .iter()
+                        .filter_map(|part| part.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    _ => return None,
+                };
+                if hidden || text.trim().is_empty() {
+                    return None;
+                }
+                Some(ExternalMessage {
+                    external_id: str_field(message, "id"),
+                    role,
+                    content: text,
+                    created_at: message.get("create_time").and_then(epoch_time),
+                    model: message.pointer("/metadata/model_slug").and_then(|v| v.as_str()).map(String::from),
+                })
+            })
+            .collect();
+
+        conversations.push(ExternalConversation {
+            external_id,
+            title: str_field(conversation, "title").unwrap_or_else(|| "Untitled".to_string()),
+            created_at: conversation.get("create_time").and_then(epoch_time),
+            messages,
+        });
+    }
+    Ok(conversations)
+}
+
+/// Claude data export `conversations.json`
+pub fn parse_claude(export: &Value) -> Result<Vec<ExternalConversation>, String> {
+    let mut conversations = Vec::new();
+    for conversation in conversations_array(export)? {
+        let Some(external_id) = str_field(conversation, "uuid") else {
+            continue;
+        };
+        let messages = conversation
+            .get("chat_messages")
+            .and_then(|m| m.as_array())
+            .map(|messages| messages.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|message| {
+                let role = match message.get("sender").and_then(|v| v.as_str())? {
+                    "human" => "user",
+                    "assistant" => "assistant",
+                    _ => return None,
+                };
+                let mut text = str_field(message, "text").unwrap_or_else(|| {
+                    message
+                        .get("content")
+                        .and_then(|c| c.as_array())
+                        .map(|blocks| {
+                            blocks
+                                .iter()
+                                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                                .collect::<Vec<_>>()
+                                .join("\n\n")
+                        })
+                        .unwrap_or_default()
+                });
+                let attachments = ["attachments", "files"]
+                    .iter()
+                    .filter_map(|key| message.get(*key).and_then(|a| a.as_array()))
+                    .flatten()
+                    .filter_map(|file| str_field(file, "file_name"));
+                for name in attachments {
+                    text.push_str(&format!("\n\n[Attachment: {}]", name));
+                }
+                if text.trim().is_empty() {
+                    return None;
+                }
+                Some(ExternalMessage {
+                    external_id: str_field(message, "uuid"),
+                    role,
+                    content: text.trim().to_string(),
+                    created_at: message.get("created_at").and_then(iso_time),
+                    model: None,
+                })
+            })
+            .collect();
+
+        conversations.push(ExternalConversation {
+            external_id,
+            title: str_field(conversation, "name").unwrap_or_else(|| "Untitled".to_string()),
+            created_at: conversation.get("created_at").and_then(iso_time),
+            messages,
+        });
+    }
+    Ok(conversations)
+}
+
+/// A Markdown transcript, or every transcript in a folder
+fn read_markdown(path: &Path) -> Result<Vec<ExternalConversation>, String> {
+    let files = if path.is_dir() {
+        let mut files: Vec<_> = std::fs::read_dir(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+            })
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    files
+        .iter()
+        .map(|file| {
+            let text = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            let name = file.file_stem().and_then(|s| s.to_str()).unwrap_or("Untitled");
+            Ok(parse_markdown(name, &text))
+        })
+        .collect()
+}
+
+/// Role named by a speaker label such as "User" or "ChatGPT"
+fn speaker_role(label: &str) -> Option<&'static str> {
+    match label.trim().trim_end_matches(':').trim().to_lowercase().as_str() {
+        "user" | "you" | "human" | "me" | "prompt" => Some("user"),
+        "assistant" | "ai" | "chatgpt" | "gpt" | "claude" | "bot" | "model" | "response" => Some("assistant"),
+        "system" => Some("system"),
+        _ => None,
+    }
+}
+
+/// Speaker and rest of a line that starts a message: `## User`, or
+/// `**User:** text`
+fn speaker_line(line: &str) -> Option<(&'static str, &str)> {
+    let trimmed = line.trim();
+    if trimmed.starts_with("##") {
+        return speaker_role(trimmed.trim_start_matches('#')).map(|role| (role, ""));
+    }
+    let rest = trimmed.strip_prefix("**")?;
+    let (label, text) = rest.split_once("**")?;
+    let role = speaker_role(label)?;
+    Some((role, text.trim_start_matches(':').trim()))
+}
+
+/// Transcript with a message per speaker heading or label; its `# ` heading,
+/// or else `name`, is the title. The external id is the text's hash.
+pub fn parse_markdown(name: &str, text: &str) -> ExternalConversation {
+    let mut title = None;
+    let mut messages: Vec<ExternalMessage> = Vec::new();
+    for line in text.lines() {
+        if let Some((role, rest)) = speaker_line(line) {
+            messages.push(ExternalMessage {
+                external_id: None,
+                role,
+                content: rest.to_string(),
+                created_at: None,
+                model: None,
+            });
+        } else if let Some(message) = messages.last_mut() {
+            message.content.push('\n');
+            message.content.push_str(line);
+        } else if let Some(heading) = line.strip_prefix("# ") {
+            title.get_or_insert_with(|| heading.trim().to_string());
+        }
+    }
+    for message in &mut messages {
+        message.content = message.content.trim().to_string();
+    }
+    messages.retain(|m| !m.content.is_empty());
+
+    ExternalConversation {
+        external_id: format!("{:x}", Sha256::digest(text.as_bytes())),
+        title: title.unwrap_or_else(|| name.to_string()),
+        created_at: None,
+        messages,
+    }
+}
+
+// ============================================================================
+// Storing
+// ============================================================================
+
+/// Conversation an earlier import stored for this external id, if it still exists
+fn existing_import(conn: &Connection, format: ImportFormat, external_id: &str) -> Result<Option<String>, String> {
+    let conversation_id: Option<String> = conn
+        .query_row(
+            "SELECT i.conversation_id FROM imported_conversations i
+             JOIN conversations c ON c.id = i.conversation_id
+             WHERE i.source = ?1 AND i.external_id = ?2",
+            params![format.as_str(), external_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(conversation_id)
+}
+
+/// Store one conversation; returns its id
+fn store(conn: &Connection, format: ImportFormat, conversation: &ExternalConversation) -> Result<String, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let first = conversation
+        .created_at
+        .or_else(|| conversation.messages.iter().find_map(|m| m.created_at))
+        .unwrap_or_else(Utc::now);
+
+    // Keep the export's order even where timestamps are missing or equal
+    let mut previous = first - Duration::milliseconds(1);
+    let times: Vec<_> = conversation
+        .messages
+        .iter()
+        .map(|message| {
+            previous = message
+                .created_at
+                .filter(|t| *t > previous)
+                .unwrap_or(previous + Duration::milliseconds(1));
+            previous
+        })
+        .collect();
+    let last = times.last().copied().unwrap_or(first);
+
+    conn.execute(
+        "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
+        params![id, conversation.title, first.to_rfc3339(), last.to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    for (message, created_at) in conversation.messages.iter().zip(times) {
+
+        let mut metadata = json!({
+            "importedFrom": {
+                "source": format.as_str(),
+                "conversationId": conversation.external_id,
+                "messageId": message.external_id,
+            }
+        });
+        if message.role == "assistant" {
+            if let Some(provider) = format.provider() {
+                metadata["provider"] = json!(provider);
+            }
+            if let Some(model) = &message.model {
+                metadata["model"] = json!(model);
+            }
+        }
+        conn.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                id,
+                message.role,
+                message.content,
+                columns::seal(&metadata.to_string())?,
+                created_at.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO imported_conversations (source, external_id, conversation_id) VALUES (?1, ?2, ?3)",
+        params![format.as_str(), conversation.external_id, id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Store the conversations that were not imported before
+pub fn import(
+    conn: &mut Connection,
+    format: ImportFormat,
+    conversations: &[ExternalConversation],
+) -> Result<ImportReport, String> {
+    let mut tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut items = Vec::new();
+    for conversation in conversations {
+        let mut item = ImportedItem {
+            external_id: conversation.external_id.clone(),
+            title: conversation.title.clone(),
+            status: ImportStatus::Skipped,
+            conversation_id: None,
+            messages: conversation.messages.len(),
+            reason: None,
+        };
+
+        if conversation.messages.is_empty() {
+            item.reason = Some("No messages".to_string());
+        } else if let Some(existing) = existing_import(&tx, format, &conversation.external_id)? {
+            item.conversation_id = Some(existing);
+            item.reason = Some("Already imported".to_string());
+        } else {
+            // A failed conversation leaves nothing behind
+            let savepoint = tx.savepoint().map_err(|e| e.to_string())?;
+            match store(&savepoint, format, conversation) {
+                Ok(id) => {
+                    savepoint.commit().map_err(|e| e.to_string())?;
+                    item.status = ImportStatus::Imported;
+                    item.conversation_id = Some(id);
+                }
+                Err(e) => {
+                    item.status = ImportStatus::Failed;
+                    item.reason = Some(e);
+                }
+            }
+        }
+        items.push(item);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let count = |status| items.iter().filter(|i| i.status == status).count();
+    Ok(ImportReport {
+        format,
+        imported: count(ImportStatus::Imported),
+        skipped: count(ImportStatus::Skipped),
+        failed: count(ImportStatus::Failed),
+        messages: items.iter().filter(|i| i.status == ImportStatus::Imported).map(|i| i.messages).sum(),
+        items,
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Import conversations exported from ChatGPT (`chatgpt`), Claude (`claude`)
+/// or Markdown transcripts (`markdown`)
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn import_external_history(
+    db: tauri::State<'_, crate::db::DbState>,
+    format: String,
+    path: String,
+) -> Result<ImportReport, AppError> {
+    let format = ImportFormat::from_str(&format)
+        .ok_or_else(|| AppError::invalid_input(format!("Unknown import format: {} (expected chatgpt, claude or markdown)", format)))?;
+    let db_path = db.path();
+    let report = tauri::async_runtime::spawn_blocking(move || -> Result<ImportReport, String> {
+        let conversations = read_export(format, Path::new(&path))?;
+        let mut conn = crate::scheduler::history::open(Path::new(&db_path))?;
+        let report = import(&mut conn, format, &conversations)?;
+        tracing::info!(
+            "Imported {} conversations from {} ({} skipped, {} failed)",
+            report.imported,
+            format.as_str(),
+            report.skipped,
+            report.failed
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn chatgpt_export() -> Value {
+        json!([{
+            "id": "gpt-1",
+            "title": "Rust lifetimes",
+            "create_time": 1767225600.0,
+            "current_node": "n4",
+            "mapping": {
+                "root": { "id": "root", "message": null, "parent": null, "children": ["n1"] },
+                "n1": { "id": "n1", "parent": "root", "children": ["n2"], "message": {
+                    "id": "m1", "author": { "role": "system" }, "create_time": null,
+                    "content": { "content_type": "text", "parts": [""] },
+                    "metadata": { "is_visually_hidden_from_conversation": true } } },
+                "n2": { "id": "n2", "parent": "n1", "children": ["n3", "n3b"], "message": {
+                    "id": "m2", "author": { "role": "user" }, "create_time": 1767225601.5,
+                    "content": { "content_type": "text", "parts": ["What is 'a?"] } } },
+                "n3b": { "id": "n3b", "parent": "n2", "children": [], "message": {
+                    "id": "m3b", "author": { "role": "assistant" }, "create_time": 1767225602.0,
+                    "content": { "content_type": "text", "parts": ["Abandoned branch"] } } },
+                "n3": { "id": "n3", "parent": "n2", "children": ["n4"], "message": {
+                    "id": "m3", "author": { "role": "tool" }, "create_time": 1767225603.0,
+                    "content": { "content_type": "text", "parts": ["search results"] } } },
+                "n4": { "id": "n4", "parent": "n3", "children": [], "message": {
+                    "id": "m4", "author": { "role": "assistant" }, "create_time": 1767225604.0,
+                    "content": { "content_type": "text", "parts": ["A lifetime parameter."] },
+                    "metadata": { "model_slug": "gpt-4o" } } }
+            }
+        }])
+    }
+
+    #[test]
+    fn test_parse_exports() {
+        let chatgpt = parse_chatgpt(&chatgpt_export()).unwrap();
+        assert_eq!(chatgpt[0].external_id, "gpt-1");
+        let contents: Vec<_> = chatgpt[0].messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(contents, [("user", "What is 'a?"), ("assistant", "A lifetime parameter.")]);
+        assert_eq!(chatgpt[0].messages[1].model.as_deref(), Some("gpt-4o"));
+
+        let claude = parse_claude(&json!([{
+            "uuid": "c-1",
+            "name": "",
+            "created_at": "2026-01-01T00:00:00Z",
+            "chat_messages": [
+                { "uuid": "u1", "sender": "human", "text": "Summarize this",
+                  "created_at": "2026-01-01T00:00:01Z", "attachments": [{ "file_name": "notes.txt" }] },
+                { "uuid": "u2", "sender": "assistant", "text": "",
+                  "content": [{ "type": "text", "text": "Here is the summary." }] }
+            ]
+        }]))
+        .unwrap();
+        assert_eq!(claude[0].title, "Untitled");
+        assert_eq!(claude[0].messages[0].content, "Summarize this\n\n[Attachment: notes.txt]");
+        assert_eq!(claude[0].messages[1].content, "Here is the summary.");
+
+        let markdown = parse_markdown(
+            "notes",
+            "# Trip planning\n\n## User\nWhere should I go?\n\n## Assistant\nTry Busan.\n\n- beaches\n**You:** Thanks",
+        );
+        assert_eq!(markdown.title, "Trip planning");
+        let contents: Vec<_> = markdown.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [("user", "Where should I go?"), ("assistant", "Try Busan.\n\n- beaches"), ("user", "Thanks")]
+        );
+        assert!(parse_markdown("empty", "Just some notes").messages.is_empty());
+    }
+
+    #[test]
+    fn test_import_deduplicates() {
+        let mut conn = test_conn();
+        let mut conversations = parse_chatgpt(&chatgpt_export()).unwrap();
+        conversations.push(ExternalConversation {
+            external_id: "empty".to_string(),
+            title: "Empty".to_string(),
+            created_at: None,
+            messages: Vec::new(),
+        });
+
+        let first = import(&mut conn, ImportFormat::Chatgpt, &conversations).unwrap();
+        assert_eq!((first.imported, first.skipped, first.failed, first.messages), (1, 1, 0, 2));
+        let id = first.items[0].conversation_id.clone().unwrap();
+
+        let (role, metadata): (String, String) = conn
+            .query_row(
+                "SELECT role, metadata FROM messages WHERE conversation_id = ?1 ORDER BY created_at DESC LIMIT 1",
+                [&id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        let metadata: Value = serde_json::from_str(&columns::open(&metadata).unwrap()).unwrap();
+        assert_eq!(role, "assistant");
+        assert_eq!(metadata["provider"], "openai");
+        assert_eq!(metadata["importedFrom"]["messageId"], "m4");
+
+        let again = import(&mut conn, ImportFormat::Chatgpt, &conversations).unwrap();
+        assert_eq!((again.imported, again.skipped), (0, 2));
+        assert_eq!(again.items[0].reason.as_deref(), Some("Already imported"));
+
+        // Deleted conversations come back on the next import
+        conn.execute("DELETE FROM messages WHERE conversation_id = ?1", [&id]).unwrap();
+        conn.execute("DELETE FROM conversations WHERE id = ?1", [&id]).unwrap();
+        let restored = import(&mut conn, ImportFormat::Chatgpt, &conversations).unwrap();
+        assert_eq!(restored.imported, 1);
+    }
+}
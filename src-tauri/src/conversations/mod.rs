@@ -0,0 +1,40 @@
+//! Conversations Module
+//!
+//! Organization of saved conversations beyond the history list:
+//! - Tags, and smart folders saving filters over tags, dates and provider
+//! - Pinned messages, kept in the agent's context, and bookmarks
+//! - Redacted bundles shared outside the app
+//! - Embedding-based suggestions of similar past conversations
+//! - Extraction of reusable recipes from conversations
+//! - Import of chat history exported from other tools
+//! - Answers of several providers to one prompt, kept side by side
+//! - Per-conversation system prompts with their earlier versions
+//! - Per-conversation generation parameter defaults
+
+pub mod compare;
+pub mod generation;
+pub mod import;
+pub mod pins;
+pub mod recipe;
+pub mod share;
+pub mod similar;
+pub mod system_prompt;
+pub mod tags;
+
+use crate::db::Conversation;
+
+/// Columns read by `conversation_from_row`, for queries aliasing
+/// `conversations` as `c`
+pub(crate) const CONVERSATION_COLUMNS: &str =
+    "c.id, c.title, c.created_at, c.updated_at, c.is_sensitive, c.workspace_id";
+
+pub(crate) fn conversation_from_row(row: &rusqlite::Row) -> rusqlite::Result<Conversation> {
+    Ok(Conversation {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+        is_sensitive: row.get(4)?,
+        workspace_id: row.get(5)?,
+    })
+}
@@ -0,0 +1,294 @@
+//! Pinned Messages and Bookmarks
+//!
+//! Pinned messages stay in the agent's context when it is compressed:
+//! `agent_context_retain_pinned` loads a conversation's pins into the context
+//! manager's retained set. Bookmarks are labelled positions in a
+//! conversation for jumping back to a message later.
+
+use crate::db::{conversation_key, DbState};
+use crate::error::AppError;
+use crate::security::field_encryption::decrypt_field;
+use crate::security::{CredentialManager, SensitiveSession};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// A pinned message with its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedMessage {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub role: String,
+    pub content: String,
+    pub note: Option<String>,
+    /// When the message was sent
+    pub created_at: String,
+    pub pinned_at: String,
+}
+
+/// A labelled position in a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub id: String,
+    pub conversation_id: String,
+    pub message_id: String,
+    pub label: String,
+    pub created_at: String,
+}
+
+fn message_conversation(conn: &Connection, message_id: &str) -> Result<String, AppError> {
+    conn.query_row("SELECT conversation_id FROM messages WHERE id = ?1", [message_id], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Message not found: {}", message_id)))
+}
+
+fn note_text(note: Option<&str>) -> Option<&str> {
+    note.map(str::trim).filter(|n| !n.is_empty())
+}
+
+/// Pin a message, or update the note of a pinned one
+pub fn pin(conn: &Connection, message_id: &str, note: Option<&str>) -> Result<(), AppError> {
+    let conversation_id = message_conversation(conn, message_id)?;
+    conn.execute(
+        "INSERT INTO pinned_messages (message_id, conversation_id, note, pinned_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(message_id) DO UPDATE SET note = excluded.note",
+        rusqlite::params![message_id, conversation_id, note_text(note), chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Unpin a message; returns whether it was pinned
+pub fn unpin(conn: &Connection, message_id: &str) -> Result<bool, String> {
+    let removed = conn
+        .execute("DELETE FROM pinned_messages WHERE message_id = ?1", [message_id])
+        .map_err(|e| e.to_string())?;
+    Ok(removed > 0)
+}
+
+/// Pinned messages of a conversation in conversation order; `key` decrypts
+/// the messages of a sensitive conversation
+pub fn pinned(conn: &Connection, conversation_id: &str, key: Option<&[u8; 32]>) -> Result<Vec<PinnedMessage>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.message_id, p.conversation_id, m.role, m.content, p.note, m.created_at, p.pinned_at, m.is_encrypted
+             FROM pinned_messages p JOIN messages m ON m.id = p.message_id
+             WHERE p.conversation_id = ?1 ORDER BY m.created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([conversation_id], |row| {
+            Ok((
+                PinnedMessage {
+                    message_id: row.get(0)?,
+                    conversation_id: row.get(1)?,
+                    role: row.get(2)?,
+                    content: row.get(3)?,
+                    note: row.get(4)?,
+                    created_at: row.get(5)?,
+                    pinned_at: row.get(6)?,
+                },
+                row.get::<_, bool>(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut messages = Vec::with_capacity(rows.len());
+    for (mut message, encrypted) in rows {
+        if encrypted {
+            let key = key.ok_or("Encrypted message in non-sensitive conversation")?;
+            message.content = decrypt_field(&message.content, key).map_err(|e| e.to_string())?;
+        }
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// Pinned messages of a conversation, decrypted when it is sensitive and
+/// unlocked
+pub fn load_pinned(
+    db: &DbState,
+    session: &Mutex<SensitiveSession>,
+    credentials: &Mutex<CredentialManager>,
+    conversation_id: &str,
+) -> Result<Vec<PinnedMessage>, AppError> {
+    let conn = db.conn.lock()?;
+    let key = conversation_key(&conn, session, credentials, conversation_id)?;
+    Ok(pinned(&conn, conversation_id, key.as_ref())?)
+}
+
+pub fn add_bookmark(conn: &Connection, message_id: &str, label: &str) -> Result<Bookmark, AppError> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(AppError::invalid_input("Bookmark label is required"));
+    }
+    let bookmark = Bookmark {
+        id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: message_conversation(conn, message_id)?,
+        message_id: message_id.to_string(),
+        label: label.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    conn.execute(
+        "INSERT INTO conversation_bookmarks (id, conversation_id, message_id, label, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        [&bookmark.id, &bookmark.conversation_id, &bookmark.message_id, &bookmark.label, &bookmark.created_at],
+    )?;
+    Ok(bookmark)
+}
+
+pub fn delete_bookmark(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM conversation_bookmarks WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Bookmarks of a conversation in conversation order
+pub fn bookmarks(conn: &Connection, conversation_id: &str) -> Result<Vec<Bookmark>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.conversation_id, b.message_id, b.label, b.created_at
+             FROM conversation_bookmarks b JOIN messages m ON m.id = b.message_id
+             WHERE b.conversation_id = ?1 ORDER BY m.created_at ASC, b.created_at ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let bookmarks = stmt
+        .query_map([conversation_id], |row| {
+            Ok(Bookmark {
+                id: row.get(0)?,
+                conversation_id: row.get(1)?,
+                message_id: row.get(2)?,
+                label: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    bookmarks
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn pin_message(db: tauri::State<'_, DbState>, message_id: String, note: Option<String>) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    pin(&conn, &message_id, note.as_deref())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn unpin_message(db: tauri::State<'_, DbState>, message_id: String) -> Result<bool, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(unpin(&conn, &message_id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_pinned(
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    conversation_id: String,
+) -> Result<Vec<PinnedMessage>, AppError> {
+    load_pinned(&db, &session, &credentials, &conversation_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn add_conversation_bookmark(
+    db: tauri::State<'_, DbState>,
+    message_id: String,
+    label: String,
+) -> Result<Bookmark, AppError> {
+    let conn = db.conn.lock()?;
+    add_bookmark(&conn, &message_id, &label)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_conversation_bookmark(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(delete_bookmark(&conn, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_conversation_bookmarks(
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+) -> Result<Vec<Bookmark>, AppError> {
+    let conn = db.read()?;
+    Ok(bookmarks(&conn, &conversation_id)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::field_encryption::encrypt_field;
+    use crate::db::test_support::test_conn;
+    use crate::error::ErrorKind;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Plans')", []).unwrap();
+        for (id, content, created_at) in [
+            ("m1", "Use Postgres for the API", "2026-05-01T10:00:00+00:00"),
+            ("m2", "Sounds good", "2026-05-01T10:01:00+00:00"),
+            ("m3", "Deadline is June 3rd", "2026-05-01T10:02:00+00:00"),
+        ] {
+            conn.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES (?1, 'c1', 'user', ?2, ?3)",
+                [id, content, created_at],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn test_pins() {
+        let conn = setup();
+        pin(&conn, "m3", None).unwrap();
+        pin(&conn, "m1", Some("decision")).unwrap();
+        pin(&conn, "m1", Some("  database choice ")).unwrap();
+        assert_eq!(pin(&conn, "missing", None).unwrap_err().kind(), ErrorKind::NotFound);
+
+        let pins = pinned(&conn, "c1", None).unwrap();
+        let ids: Vec<&str> = pins.iter().map(|p| p.message_id.as_str()).collect();
+        assert_eq!(ids, vec!["m1", "m3"]);
+        assert_eq!(pins[0].note.as_deref(), Some("database choice"));
+        assert_eq!(pins[1].content, "Deadline is June 3rd");
+
+        assert!(unpin(&conn, "m3").unwrap());
+        assert!(!unpin(&conn, "m3").unwrap());
+        assert_eq!(pinned(&conn, "c1", None).unwrap().len(), 1);
+
+        // Encrypted content is only readable with the key
+        let key = [7u8; 32];
+        let secret = encrypt_field("Sensitive plan", &key).unwrap();
+        conn.execute("UPDATE messages SET content = ?1, is_encrypted = 1 WHERE id = 'm1'", [&secret])
+            .unwrap();
+        assert!(pinned(&conn, "c1", None).is_err());
+        assert_eq!(pinned(&conn, "c1", Some(&key)).unwrap()[0].content, "Sensitive plan");
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let conn = setup();
+        let late = add_bookmark(&conn, "m3", "Deadline").unwrap();
+        add_bookmark(&conn, "m1", " Start ").unwrap();
+        assert_eq!(add_bookmark(&conn, "m2", "  ").unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(add_bookmark(&conn, "missing", "Nowhere").unwrap_err().kind(), ErrorKind::NotFound);
+
+        let labels: Vec<String> = bookmarks(&conn, "c1").unwrap().into_iter().map(|b| b.label).collect();
+        assert_eq!(labels, vec!["Start", "Deadline"]);
+        delete_bookmark(&conn, &late.id).unwrap();
+        assert_eq!(bookmarks(&conn, "c1").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,344 @@
+//! Conversation to Recipe
+//!
+//! `extract_recipe_from_conversation` turns an ad-hoc chat that worked into
+//! a reusable automation. The transcript goes to the agent runtime with a
+//! prompt asking for the plan as JSON matching `plan_schema`; the reply is
+//! validated like a skill's structured output and saved as a draft recipe,
+//! with the values that varied in the chat lifted into `${variables}`. The
+//! draft is opened in the recipe editor and stops being a draft once the
+//! user saves it.
+
+use crate::db::{skill_schema, DbState, Recipe};
+use crate::error::AppError;
+use crate::sidecar::SidecarState;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Longest transcript sent; older messages are dropped first
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+
+/// Most steps in an extracted recipe
+const MAX_STEPS: usize = 50;
+
+/// Recipe extracted from a conversation, before it is stored
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecipePlan {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Variable names and their default values
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    pub steps: Vec<PlannedStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedStep {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub args: Option<Value>,
+    #[serde(default)]
+    pub output_variable: Option<String>,
+}
+
+/// Shape the model must return
+pub fn plan_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1, "maxLength": 80 },
+            "description": { "type": "string" },
+            "variables": {
+                "type": "object",
+                "additionalProperties": { "type": "string" }
+            },
+            "steps": {
+                "type": "array",
+                "minItems": 1,
+                "maxItems": MAX_STEPS,
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "minLength": 1 },
+                        "type": { "enum": ["prompt", "tool_call"] },
+                        "prompt": { "type": "string" },
+                        "tool": { "type": "string" },
+                        "args": { "type": "object" },
+                        "outputVariable": { "type": "string" }
+                    },
+                    "required": ["name", "type"]
+                }
+            }
+        },
+        "required": ["name", "steps"]
+    })
+}
+
+/// Title and transcript of a conversation, newest messages kept when it is
+/// too long; sensitive conversations are never sent
+fn transcript(conn: &Connection, conversation_id: &str) -> Result<(String, String), String> {
+    let (title, sensitive): (String, bool) = conn
+        .query_row(
+            "SELECT title, is_sensitive FROM conversations WHERE id = ?1",
+            [conversation_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+    if sensitive {
+        return Err("Sensitive conversations cannot be turned into recipes".to_string());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT role, content FROM messages WHERE conversation_id = ?1 AND role != 'system'
+             ORDER BY created_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let messages = stmt
+        .query_map([conversation_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if messages.is_empty() {
+        return Err("The conversation has no messages".to_string());
+    }
+
+    let mut kept = Vec::new();
+    let mut size = 0;
+    for (role, content) in messages {
+        let entry = format!("[{}]\n{}", role, content);
+        if size + entry.len() > MAX_TRANSCRIPT_CHARS && !kept.is_empty() {
+            break;
+        }
+        size += entry.len();
+        kept.push(entry);
+    }
+    kept.reverse();
+    Ok((title, kept.join("\n\n")))
+}
+
+fn extraction_prompt(title: &str, transcript: &str) -> String {
+    format!(
+        "The conversation below, titled \"{}\", accomplished a task. Turn it into a reusable recipe \
+that repeats the same work for other inputs.
+
+- Use \"prompt\" steps for work done by the assistant and \"tool_call\" steps, with \"tool\" and \
+\"args\", for tools it called.
+- Replace values specific to this conversation (file paths, names, dates, topics) with variables \
+written as ${{name}}, and list each variable with the value used here as its default.
+- Set \"outputVariable\" on a step whose result a later step uses as ${{name}}.
+- Keep the steps as few as the task allows.
+
+Respond with only a JSON value matching this JSON Schema, without any other text:
+{}
+
+Conversation:
+{}",
+        title,
+        plan_schema(),
+        transcript
+    )
+}
+
+/// Parse and validate the model's reply
+pub fn parse_plan(reply: &str) -> Result<RecipePlan, String> {
+    let value = skill_schema::extract_json(reply).ok_or("The model did not return a recipe")?;
+    skill_schema::validate(&plan_schema(), &value).map_err(|e| format!("Invalid recipe: {}", e))?;
+    let plan: RecipePlan = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    for step in &plan.steps {
+        match step.kind.as_str() {
+            "prompt" if step.prompt.as_deref().is_none_or(|p| p.trim().is_empty()) => {
+                return Err(format!("Step '{}' has no prompt", step.name));
+            }
+            "tool_call" if step.tool.as_deref().is_none_or(|t| t.trim().is_empty()) => {
+                return Err(format!("Step '{}' has no tool", step.name));
+            }
+            _ => {}
+        }
+    }
+    Ok(plan)
+}
+
+/// Recipe steps in the format the recipe engine runs
+fn steps_json(plan: &RecipePlan) -> Value {
+    let steps: Vec<Value> = plan
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let mut value = json!({
+                "id": format!("step-{}", i + 1),
+                "type": step.kind,
+                "name": step.name,
+            });
+            let fields = [
+                ("prompt", step.prompt.clone().map(Value::String)),
+                ("tool", step.tool.clone().map(Value::String)),
+                ("args", step.args.clone()),
+                ("outputVariable", step.output_variable.clone().map(Value::String)),
+            ];
+            for (key, field) in fields {
+                if let Some(field) = field {
+                    value[key] = field;
+                }
+            }
+            value
+        })
+        .collect();
+    Value::Array(steps)
+}
+
+/// Store a plan as a draft recipe, renaming it when the name is taken
+pub fn save_draft(conn: &Connection, plan: &RecipePlan) -> Result<Recipe, String> {
+    let base = plan.name.trim();
+    let mut name = base.to_string();
+    let mut n = 2;
+    while conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM recipes WHERE name = ?1)", [&name], |row| row.get::<_, bool>(0))
+        .map_err(|e| e.to_string())?
+    {
+        name = format!("{} ({})", base, n);
+        n += 1;
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let recipe = Recipe {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        description: Some(plan.description.trim().to_string()).filter(|d| !d.is_empty()),
+        version: "1.0.0".to_string(),
+        steps: steps_json(plan).to_string(),
+        variables: Some(json!(plan.variables).to_string()).filter(|_| !plan.variables.is_empty()),
+        is_builtin: false,
+        is_draft: true,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    conn.execute(
+        "INSERT INTO recipes (id, name, description, version, steps, variables, is_builtin, is_draft, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, 1, ?7, ?8)",
+        params![
+            recipe.id,
+            recipe.name,
+            recipe.description,
+            recipe.version,
+            recipe.steps,
+            recipe.variables,
+            recipe.created_at,
+            recipe.updated_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(recipe)
+}
+
+/// Extract a draft recipe from a conversation with the agent runtime
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn extract_recipe_from_conversation(
+    state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+) -> Result<Recipe, AppError> {
+    let prompt = {
+        let conn = db.conn.lock()?;
+        let (title, transcript) = transcript(&conn, &conversation_id)?;
+        extraction_prompt(&title, &transcript)
+    };
+
+    let response = {
+        let state = state.lock()?;
+        state.call("execute_prompt", json!({ "prompt": prompt }))?
+    };
+    if response.get("success").and_then(|s| s.as_bool()) == Some(false) {
+        let error = response.get("error").and_then(|e| e.as_str()).unwrap_or("Recipe extraction failed");
+        return Err(AppError::sidecar(error.to_string()));
+    }
+    let reply = response.get("result").and_then(|r| r.as_str()).unwrap_or("");
+    let plan = parse_plan(reply)?;
+
+    let conn = db.conn.lock()?;
+    Ok(save_draft(&conn, &plan)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES ('c1', 'Weekly report');
+             INSERT INTO conversations (id, title, is_sensitive) VALUES ('c2', 'Private', 1);
+             INSERT INTO messages (id, conversation_id, role, content, created_at)
+             VALUES ('m1', 'c1', 'user', 'Summarize sales.csv for week 12', '2026-01-01T00:00:00Z'),
+                    ('m2', 'c1', 'assistant', 'Sales grew 4%.', '2026-01-01T00:00:01Z');",
+        )
+        .unwrap();
+        conn
+    }
+
+    const REPLY: &str = r#"Here is the recipe:
+```json
+{
+  "name": "Weekly report",
+  "description": "Summarize a sales file",
+  "variables": { "file": "sales.csv", "week": "12" },
+  "steps": [
+    { "name": "Read", "type": "tool_call", "tool": "read_file", "args": { "path": "${file}" }, "outputVariable": "data" },
+    { "name": "Summarize", "type": "prompt", "prompt": "Summarize ${data} for week ${week}" }
+  ]
+}
+```"#;
+
+    #[test]
+    fn test_transcript() {
+        let conn = setup();
+        let (title, text) = transcript(&conn, "c1").unwrap();
+        assert_eq!(title, "Weekly report");
+        assert_eq!(text, "[user]\nSummarize sales.csv for week 12\n\n[assistant]\nSales grew 4%.");
+        assert!(extraction_prompt(&title, &text).contains("${name}"));
+
+        assert!(transcript(&conn, "c2").unwrap_err().contains("Sensitive"));
+        assert!(transcript(&conn, "missing").is_err());
+    }
+
+    #[test]
+    fn test_plan_to_draft() {
+        let conn = setup();
+        assert!(parse_plan("I can't help with that").is_err());
+        assert!(parse_plan(r#"{"name": "Empty", "steps": []}"#).is_err());
+        assert!(parse_plan(r#"{"name": "No prompt", "steps": [{"name": "A", "type": "prompt"}]}"#).is_err());
+
+        let plan = parse_plan(REPLY).unwrap();
+        let recipe = save_draft(&conn, &plan).unwrap();
+        assert!(recipe.is_draft);
+        let steps: Value = serde_json::from_str(&recipe.steps).unwrap();
+        assert_eq!(steps[0]["id"], "step-1");
+        assert_eq!(steps[0]["args"]["path"], "${file}");
+        assert_eq!(steps[0]["outputVariable"], "data");
+        assert!(steps[1].get("tool").is_none());
+        let variables: Value = serde_json::from_str(recipe.variables.as_deref().unwrap()).unwrap();
+        assert_eq!(variables["week"], "12");
+
+        // A second extraction gets a free name
+        assert_eq!(save_draft(&conn, &plan).unwrap().name, "Weekly report (2)");
+        let drafts: i64 = conn
+            .query_row("SELECT COUNT(*) FROM recipes WHERE is_draft = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(drafts, 2);
+    }
+}
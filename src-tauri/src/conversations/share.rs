@@ -0,0 +1,722 @@
+//! Conversation Sharing
+//!
+//! `share_conversation` renders a conversation as a standalone HTML page or
+//! JSON bundle meant for people outside the app. Secrets found by the
+//! guardrail rules are always redacted; file paths, provider and model names
+//! and user-supplied regexes are redacted on request, and message metadata
+//! is never included. The bundle can be uploaded to a cloud storage with an
+//! expiry, after which `purge_expired_shares` deletes the object.
+//!
+//! Every share is recorded in `conversation_shares` (what was shared, with
+//! which redactions, where and when), and the record outlives the
+//! conversation.
+
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::integration::cloud::{s3::S3Manager, storage, CloudStorageConfig};
+use crate::security::guardrails::{self, DetectionKind};
+use crate::security::CredentialManager;
+use regex::Regex;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+
+/// Prefix of uploaded bundles inside the storage bucket
+const OBJECT_PREFIX: &str = "shares";
+
+/// Longest allowed upload lifetime
+const MAX_EXPIRY_HOURS: u32 = 24 * 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareFormat {
+    Html,
+    Json,
+}
+
+impl ShareFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// What to remove from the shared text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShareRedaction {
+    /// Absolute and home-relative file paths
+    pub strip_paths: bool,
+    /// Provider, model and tool names
+    pub strip_api_names: bool,
+    /// Additional regexes; each match is replaced
+    pub patterns: Vec<String>,
+}
+
+/// Where to upload the bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareUpload {
+    /// Storage config; a missing secret is read from the keychain
+    pub storage: CloudStorageConfig,
+    /// Hours until the uploaded bundle is deleted; kept until revoked if unset
+    pub expires_in_hours: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareOptions {
+    pub format: ShareFormat,
+    #[serde(default)]
+    pub redaction: ShareRedaction,
+    #[serde(default = "default_true")]
+    pub include_timestamps: bool,
+    #[serde(default)]
+    pub upload: Option<ShareUpload>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Number of replacements made, by redaction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionCounts {
+    pub secrets: usize,
+    pub paths: usize,
+    pub api_names: usize,
+    pub patterns: usize,
+}
+
+/// A message as it appears in a shared bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedMessage {
+    pub role: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+/// The JSON bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareBundle {
+    pub version: String,
+    pub shared_at: String,
+    pub title: String,
+    pub messages: Vec<SharedMessage>,
+    pub redactions: RedactionCounts,
+}
+
+/// Record of a share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationShare {
+    pub id: String,
+    pub conversation_id: String,
+    /// Title as shared, after redaction
+    pub title: String,
+    pub format: ShareFormat,
+    pub redaction: ShareRedaction,
+    pub redactions: RedactionCounts,
+    pub message_count: usize,
+    /// Name of the storage the bundle was uploaded to
+    pub storage_name: Option<String>,
+    pub object_key: Option<String>,
+    pub url: Option<String>,
+    pub expires_at: Option<String>,
+    /// When the uploaded bundle was deleted, by expiry or revocation
+    pub removed_at: Option<String>,
+    pub created_at: String,
+}
+
+/// A share and the rendered bundle, for saving locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareResult {
+    pub share: ConversationShare,
+    pub content: String,
+}
+
+// ============================================================================
+// Redaction
+// ============================================================================
+
+fn path_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"(?:\b[A-Za-z]:\\|\\\\|~/|(?:^|[\s("'`=])/)[^\s"'`<>|:*?]+"#)
+            .expect("path pattern is valid")
+    })
+}
+
+fn api_name_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:anthropic|openai|ollama|openrouter|claude-[a-z0-9.\-]+|gpt-[a-z0-9.\-]+|o[134](?:-[a-z0-9]+)+|gemini-[a-z0-9.\-]+|llama[0-9.:\-]*[a-z0-9]*|mistral[a-z0-9.:\-]*)\b",
+        )
+        .expect("API name pattern is valid")
+    })
+}
+
+/// Compiled redaction for one share
+pub struct Redactor {
+    strip_paths: bool,
+    strip_api_names: bool,
+    patterns: Vec<Regex>,
+    pub counts: RedactionCounts,
+}
+
+impl Redactor {
+    pub fn new(redaction: &ShareRedaction) -> Result<Self, String> {
+        let patterns = redaction
+            .patterns
+            .iter()
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| Regex::new(p).map_err(|e| format!("Invalid redaction pattern '{}': {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            strip_paths: redaction.strip_paths,
+            strip_api_names: redaction.strip_api_names,
+            patterns,
+            counts: RedactionCounts::default(),
+        })
+    }
+
+    /// Redact `text`, adding to the counts
+    pub fn redact(&mut self, text: &str) -> String {
+        let mut text = self.redact_secrets(text);
+        for pattern in &self.patterns {
+            text = replace(pattern, &text, "[redacted]", &mut self.counts.patterns);
+        }
+        if self.strip_paths {
+            text = replace_paths(&text, &mut self.counts.paths);
+        }
+        if self.strip_api_names {
+            text = replace(api_name_pattern(), &text, "[api]", &mut self.counts.api_names);
+        }
+        text
+    }
+
+    fn redact_secrets(&mut self, text: &str) -> String {
+        let mut redacted = String::with_capacity(text.len());
+        let mut last = 0;
+        for detection in guardrails::scan(text) {
+            if detection.kind != DetectionKind::Secret {
+                continue;
+            }
+            redacted.push_str(&text[last..detection.start]);
+            redacted.push_str("[secret]");
+            last = detection.end;
+            self.counts.secrets += 1;
+        }
+        redacted.push_str(&text[last..]);
+        redacted
+    }
+}
+
+fn replace(pattern: &Regex, text: &str, placeholder: &str, count: &mut usize) -> String {
+    let matches = pattern.find_iter(text).filter(|m| !m.is_empty()).count();
+    if matches == 0 {
+        return text.to_string();
+    }
+    *count += matches;
+    pattern.replace_all(text, placeholder).into_owned()
+}
+
+/// Replace paths, keeping the character that introduced a Unix path
+fn replace_paths(text: &str, count: &mut usize) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut last = 0;
+    for found in path_pattern().find_iter(text) {
+        let matched = found.as_str();
+        let lead = matched
+            .chars()
+            .next()
+            .filter(|c| c.is_whitespace() || "(\"'`=".contains(*c))
+            .map(char::len_utf8)
+            .unwrap_or(0);
+        redacted.push_str(&text[last..found.start() + lead]);
+        redacted.push_str("[path]");
+        last = found.end();
+        *count += 1;
+    }
+    redacted.push_str(&text[last..]);
+    redacted
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_html(bundle: &ShareBundle) -> String {
+    let mut output = String::new();
+    output.push_str("<!DOCTYPE html>\n<html>\n<head>\n");
+    output.push_str("<meta charset=\"UTF-8\">\n");
+    output.push_str("<meta name=\"robots\" content=\"noindex\">\n");
+    output.push_str(&format!("<title>{}</title>\n", escape_html(&bundle.title)));
+    output.push_str("<style>\n");
+    output.push_str("body { font-family: -apple-system, sans-serif; max-width: 800px; margin: 0 auto; padding: 20px; }\n");
+    output.push_str(".message { margin: 16px 0; padding: 12px; border-radius: 8px; white-space: pre-wrap; }\n");
+    output.push_str(".user { background: #e3f2fd; }\n");
+    output.push_str(".assistant { background: #f5f5f5; }\n");
+    output.push_str(".system { background: #fff8e1; }\n");
+    output.push_str("time, footer { color: #777; font-size: 0.85em; }\n");
+    output.push_str("</style>\n</head>\n<body>\n");
+    output.push_str(&format!("<h1>{}</h1>\n", escape_html(&bundle.title)));
+
+    for message in &bundle.messages {
+        let role = match message.role.as_str() {
+            "user" | "assistant" | "system" => message.role.as_str(),
+            _ => "system",
+        };
+        output.push_str(&format!("<div class=\"message {}\"><strong>{}</strong>", role, role));
+        if let Some(created_at) = &message.created_at {
+            output.push_str(&format!(" <time>{}</time>", escape_html(created_at)));
+        }
+        output.push_str(&format!("\n<div>{}</div></div>\n", escape_html(&message.content)));
+    }
+
+    output.push_str(&format!("<footer>Shared {}</footer>\n", escape_html(&bundle.shared_at)));
+    output.push_str("</body>\n</html>");
+    output
+}
+
+/// Redacted bundle of a conversation; sensitive conversations cannot be
+/// shared
+pub fn build_bundle(conn: &Connection, conversation_id: &str, options: &ShareOptions) -> Result<ShareBundle, String> {
+    let (title, sensitive): (String, bool) = conn
+        .query_row(
+            "SELECT title, is_sensitive FROM conversations WHERE id = ?1",
+            [conversation_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+    if sensitive {
+        return Err("Sensitive conversations cannot be shared".to_string());
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT role, content, created_at FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([conversation_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut redactor = Redactor::new(&options.redaction)?;
+    let title = redactor.redact(&title);
+    let messages = rows
+        .into_iter()
+        .map(|(role, content, created_at)| SharedMessage {
+            role,
+            content: redactor.redact(&content),
+            created_at: options.include_timestamps.then_some(created_at),
+        })
+        .collect();
+
+    Ok(ShareBundle {
+        version: "1.0".to_string(),
+        shared_at: chrono::Utc::now().to_rfc3339(),
+        title,
+        messages,
+        redactions: redactor.counts,
+    })
+}
+
+pub fn render(bundle: &ShareBundle, format: ShareFormat) -> Result<String, String> {
+    match format {
+        ShareFormat::Html => Ok(render_html(bundle)),
+        ShareFormat::Json => serde_json::to_string_pretty(bundle).map_err(|e| e.to_string()),
+    }
+}
+
+// ============================================================================
+// Records
+// ============================================================================
+
+const SHARE_COLUMNS: &str = "id, conversation_id, title, format, redaction, redactions, message_count, storage, \
+                             object_key, url, expires_at, removed_at, created_at";
+
+fn share_from_row(row: &rusqlite::Row) -> rusqlite::Result<ConversationShare> {
+    let format: String = row.get(3)?;
+    let redaction: String = row.get(4)?;
+    let redactions: String = row.get(5)?;
+    let storage: Option<String> = row.get(7)?;
+    Ok(ConversationShare {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        title: row.get(2)?,
+        format: if format == "json" { ShareFormat::Json } else { ShareFormat::Html },
+        redaction: serde_json::from_str(&redaction).unwrap_or_default(),
+        redactions: serde_json::from_str(&redactions).unwrap_or_default(),
+        message_count: row.get::<_, i64>(6)? as usize,
+        storage_name: storage
+            .and_then(|json| serde_json::from_str::<CloudStorageConfig>(&json).ok())
+            .map(|config| config.name),
+        object_key: row.get(8)?,
+        url: row.get(9)?,
+        expires_at: row.get(10)?,
+        removed_at: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+/// Record a share; the storage config is kept without its secret so the
+/// object can be deleted later
+pub fn record(conn: &Connection, share: &ConversationShare, storage: Option<&CloudStorageConfig>) -> Result<(), String> {
+    let storage = storage
+        .map(|config| {
+            let mut stored = config.clone();
+            stored.secret_access_key = None;
+            serde_json::to_string(&stored)
+        })
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO conversation_shares (id, conversation_id, title, format, redaction, redactions, message_count,
+             storage, object_key, url, expires_at, removed_at, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        rusqlite::params![
+            share.id,
+            share.conversation_id,
+            share.title,
+            share.format.as_str(),
+            serde_json::to_string(&share.redaction).map_err(|e| e.to_string())?,
+            serde_json::to_string(&share.redactions).map_err(|e| e.to_string())?,
+            share.message_count as i64,
+            storage,
+            share.object_key,
+            share.url,
+            share.expires_at,
+            share.removed_at,
+            share.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Shares, newest first; those of one conversation when given
+pub fn list(conn: &Connection, conversation_id: Option<&str>) -> Result<Vec<ConversationShare>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM conversation_shares WHERE ?1 IS NULL OR conversation_id = ?1 ORDER BY created_at DESC",
+            SHARE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let shares = stmt
+        .query_map([conversation_id], share_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    shares
+}
+
+/// Uploaded bundles still in storage: expired ones only, or the given share
+fn uploaded(conn: &Connection, id: Option<&str>, now: &str) -> Result<Vec<(String, CloudStorageConfig, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, storage, object_key FROM conversation_shares
+             WHERE removed_at IS NULL AND object_key IS NOT NULL AND storage IS NOT NULL
+               AND (id = ?1 OR (?1 IS NULL AND expires_at IS NOT NULL AND expires_at <= ?2))",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![id, now], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    rows.into_iter()
+        .map(|(id, storage, key)| {
+            let config = serde_json::from_str(&storage).map_err(|e| format!("Invalid share storage: {}", e))?;
+            Ok((id, config, key))
+        })
+        .collect()
+}
+
+fn mark_removed(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE conversation_shares SET removed_at = ?2 WHERE id = ?1",
+        [id, &chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Delete uploaded bundles from storage and mark their shares removed
+async fn remove_uploads(
+    db: &DbState,
+    s3: &S3Manager,
+    credentials: &Mutex<CredentialManager>,
+    uploads: Vec<(String, CloudStorageConfig, String)>,
+) -> Result<usize, String> {
+    let mut removed = 0;
+    for (id, config, key) in uploads {
+        let config = {
+            let credentials = credentials.lock().map_err(|e| e.to_string())?;
+            storage::resolve_credentials(config, &credentials)
+        };
+        let storage = storage::open_storage(&config, s3).await?;
+        storage.delete(&key).await?;
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        mark_removed(&conn, &id)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Delete bundles whose expiry has passed; returns how many were deleted
+pub async fn purge_expired(db: &DbState, s3: &S3Manager, credentials: &Mutex<CredentialManager>) -> Result<usize, String> {
+    let uploads = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        uploaded(&conn, None, &chrono::Utc::now().to_rfc3339())?
+    };
+    remove_uploads(db, s3, credentials, uploads).await
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Render a redacted bundle of a conversation, upload it when requested and
+/// record the share
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn share_conversation(
+    db: tauri::State<'_, DbState>,
+    s3: tauri::State<'_, S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+    options: ShareOptions,
+) -> Result<ShareResult, AppError> {
+    if let Some(hours) = options.upload.as_ref().and_then(|u| u.expires_in_hours) {
+        if hours == 0 || hours > MAX_EXPIRY_HOURS {
+            return Err(AppError::invalid_input(format!("Share expiry must be between 1 and {} hours", MAX_EXPIRY_HOURS)));
+        }
+    }
+
+    let bundle = {
+        let conn = db.conn.lock()?;
+        build_bundle(&conn, &id, &options)?
+    };
+    let content = render(&bundle, options.format)?;
+
+    let now = chrono::Utc::now();
+    let mut share = ConversationShare {
+        id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: id,
+        title: bundle.title,
+        format: options.format,
+        redaction: options.redaction,
+        redactions: bundle.redactions,
+        message_count: bundle.messages.len(),
+        storage_name: None,
+        object_key: None,
+        url: None,
+        expires_at: None,
+        removed_at: None,
+        created_at: now.to_rfc3339(),
+    };
+
+    if let Some(upload) = &options.upload {
+        let config = {
+            let credentials = credentials.lock()?;
+            storage::resolve_credentials(upload.storage.clone(), &credentials)
+        };
+        let key = format!("{}/{}.{}", OBJECT_PREFIX, share.id, options.format.as_str());
+        let storage = storage::open_storage(&config, &s3).await?;
+        storage.put(&key, content.clone().into_bytes()).await?;
+
+        share.storage_name = Some(config.name.clone());
+        share.url = Some(format!("{}/{}", config.endpoint().trim_end_matches('/'), key));
+        share.object_key = Some(key);
+        share.expires_at = upload
+            .expires_in_hours
+            .map(|hours| (now + chrono::Duration::hours(hours as i64)).to_rfc3339());
+    }
+
+    let conn = db.conn.lock()?;
+    record(&conn, &share, options.upload.as_ref().map(|u| &u.storage))?;
+    tracing::info!("Shared conversation {} as {}", share.conversation_id, share.id);
+    Ok(ShareResult { share, content })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_conversation_shares(
+    db: tauri::State<'_, DbState>,
+    conversation_id: Option<String>,
+) -> Result<Vec<ConversationShare>, AppError> {
+    let conn = db.read()?;
+    Ok(list(&conn, conversation_id.as_deref())?)
+}
+
+/// Delete a share's uploaded bundle now; the record is kept
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn revoke_conversation_share(
+    db: tauri::State<'_, DbState>,
+    s3: tauri::State<'_, S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+) -> Result<(), AppError> {
+    let uploads = {
+        let conn = db.conn.lock()?;
+        uploaded(&conn, Some(&id), &chrono::Utc::now().to_rfc3339())?
+    };
+    if uploads.is_empty() {
+        return Err(AppError::not_found(format!("No uploaded bundle for share {}", id)));
+    }
+    remove_uploads(&db, &s3, &credentials, uploads).await?;
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn purge_expired_shares(
+    db: tauri::State<'_, DbState>,
+    s3: tauri::State<'_, S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+) -> Result<usize, AppError> {
+    Ok(purge_expired(&db, &s3, &credentials).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn options(format: ShareFormat, redaction: ShareRedaction) -> ShareOptions {
+        ShareOptions {
+            format,
+            redaction,
+            include_timestamps: false,
+            upload: None,
+        }
+    }
+
+    #[test]
+    fn test_redaction() {
+        let mut redactor = Redactor::new(&ShareRedaction {
+            strip_paths: true,
+            strip_api_names: true,
+            patterns: vec![r"ACME-\d+".to_string()],
+        })
+        .unwrap();
+        let text = "Ask claude-3-5-sonnet about /home/kim/work/report.md and ACME-42 with key sk-ant-REDACTED";
+        assert_eq!(
+            redactor.redact(text),
+            "Ask [api] about [path] and [redacted] with key [secret]"
+        );
+        assert_eq!(redactor.redact(r"Saved to C:\Users\kim\notes.txt"), "Saved to [path]");
+        assert_eq!(redactor.redact("1/2 of the budget"), "1/2 of the budget");
+        assert_eq!(
+            redactor.counts,
+            RedactionCounts { secrets: 1, paths: 2, api_names: 1, patterns: 1 }
+        );
+
+        // Without options only secrets are removed
+        let mut redactor = Redactor::new(&ShareRedaction::default()).unwrap();
+        assert_eq!(redactor.redact("gpt-4o read ~/notes.txt"), "gpt-4o read ~/notes.txt");
+        assert!(Redactor::new(&ShareRedaction { patterns: vec!["(".to_string()], ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_bundle_and_records() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES ('c1', 'Fix <script> in /srv/app');
+             INSERT INTO conversations (id, title, is_sensitive) VALUES ('c2', 'Private', 1);
+             INSERT INTO messages (id, conversation_id, role, content, metadata, created_at)
+                 VALUES ('m1', 'c1', 'user', 'Why does /srv/app/main.rs fail?', '{\"provider\":\"openai\"}', '2026-05-01T10:00:00+00:00');
+             INSERT INTO messages (id, conversation_id, role, content, created_at)
+                 VALUES ('m2', 'c1', 'assistant', 'Use a & b', '2026-05-01T10:01:00+00:00');",
+        )
+        .unwrap();
+
+        let redaction = ShareRedaction { strip_paths: true, ..Default::default() };
+        let bundle = build_bundle(&conn, "c1", &options(ShareFormat::Html, redaction.clone())).unwrap();
+        assert_eq!(bundle.title, "Fix <script> in [path]");
+        assert_eq!(bundle.messages[0].content, "Why does [path] fail?");
+        assert!(bundle.messages[0].created_at.is_none());
+        assert_eq!(bundle.redactions.paths, 2);
+
+        let html = render(&bundle, ShareFormat::Html).unwrap();
+        assert!(html.contains("<title>Fix &lt;script&gt; in [path]</title>"));
+        assert!(html.contains("Use a &amp; b"));
+        let json = render(&bundle, ShareFormat::Json).unwrap();
+        assert!(!json.contains("openai"));
+        assert!(build_bundle(&conn, "c2", &options(ShareFormat::Json, ShareRedaction::default())).is_err());
+
+        let share = ConversationShare {
+            id: "s1".to_string(),
+            conversation_id: "c1".to_string(),
+            title: bundle.title.clone(),
+            format: ShareFormat::Html,
+            redaction,
+            redactions: bundle.redactions.clone(),
+            message_count: 2,
+            storage_name: None,
+            object_key: Some("shares/s1.html".to_string()),
+            url: None,
+            expires_at: Some("2026-05-02T10:00:00+00:00".to_string()),
+            removed_at: None,
+            created_at: "2026-05-01T10:00:00+00:00".to_string(),
+        };
+        let storage = CloudStorageConfig {
+            name: "public".to_string(),
+            provider: crate::integration::cloud::CloudProvider::AwsS3,
+            bucket: "shares".to_string(),
+            region: None,
+            access_key_id: Some("AKID".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            endpoint_url: None,
+        };
+        record(&conn, &share, Some(&storage)).unwrap();
+
+        let shares = list(&conn, Some("c1")).unwrap();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].storage_name.as_deref(), Some("public"));
+        assert_eq!(shares[0].redactions.paths, 2);
+        let stored: String = conn
+            .query_row("SELECT storage FROM conversation_shares WHERE id = 's1'", [], |row| row.get(0))
+            .unwrap();
+        assert!(!stored.contains("\"secret\""));
+
+        // Expired uploads are found until marked removed
+        assert!(uploaded(&conn, None, "2026-05-01T12:00:00+00:00").unwrap().is_empty());
+        assert_eq!(uploaded(&conn, None, "2026-05-03T00:00:00+00:00").unwrap().len(), 1);
+        assert_eq!(uploaded(&conn, Some("s1"), "2026-05-01T12:00:00+00:00").unwrap().len(), 1);
+        mark_removed(&conn, "s1").unwrap();
+        assert!(uploaded(&conn, None, "2026-05-03T00:00:00+00:00").unwrap().is_empty());
+        assert!(list(&conn, None).unwrap()[0].removed_at.is_some());
+    }
+}
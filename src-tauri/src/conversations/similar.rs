@@ -0,0 +1,447 @@
+//! Similar Conversations
+//!
+//! Each conversation is embedded from its title and the opening user
+//! messages, standing in for a summary. The embedding is computed locally by
+//! feature hashing: words and character trigrams are hashed into a fixed
+//! number of signed buckets and the vector is L2-normalized, so cosine
+//! similarity measures shared vocabulary, including in Korean, without a
+//! model download or an API key.
+//!
+//! The `embed_conversations` system job keeps `conversation_embeddings` up to
+//! date; `find_similar_conversations` ranks past conversations against a
+//! conversation or a draft question so the UI can surface related threads.
+//! Sensitive conversations are never stored in the index; while one is
+//! unlocked it is embedded in memory at query time, so it can be suggested
+//! without leaving anything derived from its plaintext on disk.
+
+use super::{conversation_from_row, CONVERSATION_COLUMNS};
+use crate::db::{Conversation, DbState};
+use crate::error::AppError;
+use crate::security::field_encryption::decrypt_field;
+use crate::security::{CredentialManager, SensitiveSession};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Identifies the embedding function; rows from another one are recomputed
+pub const EMBEDDING_MODEL: &str = "hashed-ngrams-v1";
+
+/// Dimensions of an embedding
+pub const EMBEDDING_DIM: usize = 512;
+
+/// User messages embedded along with the title
+const SUMMARY_MESSAGES: usize = 3;
+
+/// Longest text embedded per conversation
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// Results less similar than this are not suggestions
+const MIN_SCORE: f32 = 0.15;
+
+const DEFAULT_TOP_K: usize = 5;
+const MAX_TOP_K: usize = 50;
+
+/// Words too common to say anything about a conversation
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "do", "does", "for", "from", "how", "i", "in",
+    "is", "it", "me", "my", "of", "on", "or", "that", "the", "this", "to", "was", "what", "with", "you",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarConversation {
+    pub conversation: Conversation,
+    /// Cosine similarity, between 0 and 1
+    pub score: f32,
+}
+
+/// 64-bit FNV-1a, stable across builds unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn add_feature(vector: &mut [f32], feature: &str, weight: f32) {
+    let hash = fnv1a(feature.as_bytes());
+    let bucket = (hash % EMBEDDING_DIM as u64) as usize;
+    let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+    vector[bucket] += sign * weight;
+}
+
+/// Embed text as an L2-normalized vector; all zeros when it has no words
+pub fn embed(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; EMBEDDING_DIM];
+    let lower = text.to_lowercase();
+    for word in lower.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        if STOPWORDS.contains(&word) {
+            continue;
+        }
+        add_feature(&mut vector, &format!("w:{}", word), 1.0);
+
+        // Trigrams match inflections and compounds the whole word misses
+        let chars: Vec<char> = format!(" {} ", word).chars().collect();
+        for trigram in chars.windows(3) {
+            add_feature(&mut vector, &format!("t:{}", trigram.iter().collect::<String>()), 0.5);
+        }
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// Cosine similarity of two normalized vectors
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+pub(crate) fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub(crate) fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Title and opening user messages of a conversation; None for sensitive
+/// conversations that are not unlocked in `session`
+fn conversation_text(conn: &Connection, session: &SensitiveSession, conversation_id: &str) -> Result<Option<String>, String> {
+    let conversation: Option<(String, bool)> = conn
+        .query_row(
+            "SELECT title, is_sensitive FROM conversations WHERE id = ?1",
+            [conversation_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let key = match conversation {
+        None => return Ok(None),
+        Some((_, false)) => None,
+        Some((_, true)) => match session.unlocked_key(conversation_id) {
+            Some(key) => Some(key),
+            None => return Ok(None),
+        },
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT content, is_encrypted FROM messages WHERE conversation_id = ?1 AND role = 'user'
+             ORDER BY created_at ASC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let messages = stmt
+        .query_map(rusqlite::params![conversation_id, SUMMARY_MESSAGES as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut text = conversation.map(|(title, _)| title).unwrap_or_default();
+    for (content, encrypted) in messages {
+        let content = match (encrypted, key.as_ref()) {
+            (false, _) => content,
+            (true, Some(key)) => decrypt_field(&content, key).map_err(|e| e.to_string())?,
+            (true, None) => continue,
+        };
+        text.push('\n');
+        text.push_str(&content);
+    }
+    Ok(Some(text.chars().take(MAX_TEXT_CHARS).collect()))
+}
+
+/// Embed new and changed conversations that are not sensitive; returns how
+/// many were embedded
+pub fn index(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM conversation_embeddings
+         WHERE conversation_id IN (SELECT id FROM conversations WHERE is_sensitive = 1)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let existing: HashMap<String, (String, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT conversation_id, source_hash, model FROM conversation_embeddings")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+    let ids: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM conversations WHERE is_sensitive = 0")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    let locked = SensitiveSession::new();
+    let mut embedded = 0;
+    for id in ids {
+        let Some(text) = conversation_text(conn, &locked, &id)? else {
+            continue;
+        };
+        let hash = format!("{:016x}", fnv1a(text.as_bytes()));
+        if existing.get(&id).is_some_and(|(h, model)| *h == hash && model == EMBEDDING_MODEL) {
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO conversation_embeddings (conversation_id, embedding, source_hash, model, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(conversation_id) DO UPDATE SET embedding = excluded.embedding,
+                 source_hash = excluded.source_hash, model = excluded.model, updated_at = excluded.updated_at",
+            rusqlite::params![id, to_blob(&embed(&text)), hash, EMBEDDING_MODEL, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| e.to_string())?;
+        embedded += 1;
+    }
+    Ok(embedded)
+}
+
+/// Run `index` against a database file, for the system job
+pub fn index_database(db_path: &Path) -> Result<String, String> {
+    let conn = crate::scheduler::history::open(db_path)?;
+    let embedded = index(&conn)?;
+    Ok(format!("Embedded {} conversations", embedded))
+}
+
+/// Unlocked sensitive conversations, embedded in memory
+fn unlocked_candidates(
+    conn: &Connection,
+    session: &SensitiveSession,
+    conversation_id: Option<&str>,
+) -> Result<Vec<(Conversation, Vec<f32>)>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM conversations c WHERE c.is_sensitive = 1", CONVERSATION_COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let conversations = stmt
+        .query_map([], conversation_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut candidates = Vec::new();
+    for conversation in conversations {
+        if Some(conversation.id.as_str()) == conversation_id || !session.is_unlocked(&conversation.id) {
+            continue;
+        }
+        if let Some(text) = conversation_text(conn, session, &conversation.id)? {
+            candidates.push((conversation, embed(&text)));
+        }
+    }
+    Ok(candidates)
+}
+
+/// Conversations most similar to `conversation_id` or to `text`, best first
+///
+/// Sensitive conversations take part only while unlocked in `session`.
+pub fn find_similar(
+    conn: &Connection,
+    session: &SensitiveSession,
+    conversation_id: Option<&str>,
+    text: Option<&str>,
+    top_k: usize,
+) -> Result<Vec<SimilarConversation>, String> {
+    let query = match (conversation_id, text.map(str::trim).filter(|t| !t.is_empty())) {
+        (Some(id), None) => {
+            let stored: Option<Vec<u8>> = conn
+                .query_row(
+                    "SELECT embedding FROM conversation_embeddings WHERE conversation_id = ?1 AND model = ?2",
+                    [id, EMBEDDING_MODEL],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            match stored {
+                Some(blob) => from_blob(&blob),
+                None => {
+                    let text = conversation_text(conn, session, id)?
+                        .ok_or_else(|| format!("Conversation not found or locked: {}", id))?;
+                    embed(&text)
+                }
+            }
+        }
+        (None, Some(text)) => embed(text),
+        _ => return Err("Give either a conversation id or a text".to_string()),
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, e.embedding FROM conversation_embeddings e JOIN conversations c ON c.id = e.conversation_id
+             WHERE c.is_sensitive = 0 AND e.model = ?1 AND (?2 IS NULL OR c.id != ?2)",
+            CONVERSATION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut similar = stmt
+        .query_map(rusqlite::params![EMBEDDING_MODEL, conversation_id], |row| {
+            let conversation = conversation_from_row(row)?;
+            let embedding: Vec<u8> = row.get(6)?;
+            Ok(SimilarConversation {
+                conversation,
+                score: cosine(&query, &from_blob(&embedding)),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (conversation, embedding) in unlocked_candidates(conn, session, conversation_id)? {
+        similar.push(SimilarConversation { conversation, score: cosine(&query, &embedding) });
+    }
+
+    similar.retain(|s| s.score >= MIN_SCORE);
+    similar.sort_by(|a, b| b.score.total_cmp(&a.score));
+    similar.truncate(top_k.clamp(1, MAX_TOP_K));
+    Ok(similar)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Past conversations related to a conversation (`id`) or a draft question
+/// (`text`)
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn find_similar_conversations(
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: Option<String>,
+    text: Option<String>,
+    top_k: Option<usize>,
+) -> Result<Vec<SimilarConversation>, AppError> {
+    let conn = db.conn.lock()?;
+    if let Some(id) = id.as_deref() {
+        // A locked sensitive conversation is refused rather than reported missing
+        crate::db::conversation_key(&conn, &session, &credentials, id)?;
+    }
+    let session = session.lock()?.clone();
+    Ok(find_similar(&conn, &session, id.as_deref(), text.as_deref(), top_k.unwrap_or(DEFAULT_TOP_K))?)
+}
+
+/// Embed new and changed conversations now instead of waiting for the job
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn index_conversation_embeddings(db: tauri::State<'_, DbState>) -> Result<usize, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(index(&conn)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_embedding_similarity() {
+        let question = embed("How do I configure the Postgres connection pool?");
+        let related = embed("Postgres connection pooling configuration");
+        let unrelated = embed("Banana bread recipe with walnuts");
+        assert!((cosine(&question, &question) - 1.0).abs() < 1e-5);
+        assert!(cosine(&question, &related) > 0.4);
+        assert!(cosine(&question, &unrelated) < MIN_SCORE);
+
+        // Trigrams relate Korean text sharing words with different endings
+        let korean = embed("데이터베이스 연결 설정 방법");
+        assert!(cosine(&korean, &embed("데이터베이스 연결을 설정하려면")) > 0.4);
+
+        assert!(embed("the and of").iter().all(|v| *v == 0.0));
+        assert_eq!(from_blob(&to_blob(&related)), related);
+    }
+
+    #[test]
+    fn test_index_and_find() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES
+                 ('c1', 'Postgres connection pool'), ('c2', 'Banana bread'), ('c3', 'Rust async runtime');
+             INSERT INTO conversations (id, title, is_sensitive) VALUES ('c4', 'Postgres passwords', 1);
+             INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES
+                 ('m1', 'c1', 'user', 'How big should the Postgres pool be?', '2026-05-01T10:00:00+00:00'),
+                 ('m2', 'c3', 'user', 'Tokio or async-std for a web server?', '2026-05-01T10:00:00+00:00');",
+        )
+        .unwrap();
+
+        let locked = SensitiveSession::new();
+        assert_eq!(index(&conn).unwrap(), 3);
+        assert_eq!(index(&conn).unwrap(), 0);
+        conn.execute("UPDATE conversations SET title = 'Sourdough bread' WHERE id = 'c2'", [])
+            .unwrap();
+        assert_eq!(index(&conn).unwrap(), 1);
+
+        let similar = find_similar(&conn, &locked, None, Some("Sizing a Postgres connection pool"), 5).unwrap();
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].conversation.id, "c1");
+
+        // The conversation itself and sensitive ones are never suggested
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c5', 'Postgres pool tuning')", [])
+            .unwrap();
+        index(&conn).unwrap();
+        let ids: Vec<String> = find_similar(&conn, &locked, Some("c1"), None, 5)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.conversation.id)
+            .collect();
+        assert_eq!(ids, vec!["c5"]);
+        assert!(find_similar(&conn, &locked, Some("c4"), None, 5).is_err());
+        assert!(find_similar(&conn, &locked, None, Some("  "), 5).is_err());
+    }
+
+    #[test]
+    fn test_unlocked_sensitive_conversations_are_searched_in_memory() {
+        let conn = test_conn();
+        let key = crate::security::encryption::derive_key("similar", b"salt");
+        let secret = crate::security::field_encryption::encrypt_field("Rotating Postgres pool credentials", &key).unwrap();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES ('c1', 'Banana bread');
+             INSERT INTO conversations (id, title, is_sensitive) VALUES ('c2', 'Private', 1);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, created_at, is_encrypted)
+             VALUES ('m1', 'c2', 'user', ?1, '2026-05-01T10:00:00+00:00', 1)",
+            [&secret],
+        )
+        .unwrap();
+        index(&conn).unwrap();
+
+        let mut session = SensitiveSession::with_key(key);
+        let search = |session: &SensitiveSession| -> Vec<String> {
+            find_similar(&conn, session, None, Some("Postgres pool credentials"), 5)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.conversation.id)
+                .collect()
+        };
+        assert!(search(&session).is_empty());
+        assert!(find_similar(&conn, &session, Some("c2"), None, 5).is_err());
+
+        session.unlock("c2");
+        assert_eq!(search(&session), vec!["c2"]);
+        assert!(find_similar(&conn, &session, Some("c2"), None, 5).is_ok());
+
+        // Nothing derived from the plaintext is stored
+        let stored: i64 = conn
+            .query_row("SELECT COUNT(*) FROM conversation_embeddings WHERE conversation_id = 'c2'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, 0);
+    }
+}
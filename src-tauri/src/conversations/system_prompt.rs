@@ -0,0 +1,200 @@
+//! Conversation System Prompts
+//!
+//! A conversation may carry its own system prompt. Every change is kept as
+//! a new version, so earlier prompts can be reviewed and restored; the
+//! newest version is the active one, and a version without a prompt clears
+//! it. The sidecar request builder puts the active prompt first in every
+//! chat of the conversation, in place of system messages sent with the
+//! request and of the workspace prompt.
+
+use crate::db::DbState;
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+const MAX_PROMPT_LENGTH: usize = 32_000;
+
+/// One version of a conversation's system prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemPromptVersion {
+    pub conversation_id: String,
+    pub version: i64,
+    /// None when this version cleared the prompt
+    pub prompt: Option<String>,
+    pub created_at: String,
+}
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<SystemPromptVersion> {
+    Ok(SystemPromptVersion {
+        conversation_id: row.get(0)?,
+        version: row.get(1)?,
+        prompt: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+fn latest(conn: &Connection, conversation_id: &str) -> Result<Option<SystemPromptVersion>, String> {
+    conn.query_row(
+        "SELECT conversation_id, version, prompt, created_at FROM conversation_system_prompts
+         WHERE conversation_id = ?1 ORDER BY version DESC LIMIT 1",
+        [conversation_id],
+        from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// The prompt in effect for a conversation
+pub fn active(conn: &Connection, conversation_id: &str) -> Result<Option<String>, String> {
+    Ok(latest(conn, conversation_id)?.and_then(|v| v.prompt))
+}
+
+/// Store a new version; setting the active prompt again adds none
+pub fn set(conn: &Connection, conversation_id: &str, prompt: Option<&str>) -> Result<SystemPromptVersion, String> {
+    let prompt = prompt.map(str::trim).filter(|p| !p.is_empty());
+    if prompt.is_some_and(|p| p.chars().count() > MAX_PROMPT_LENGTH) {
+        return Err(format!("System prompts are at most {} characters", MAX_PROMPT_LENGTH));
+    }
+    let exists: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM conversations WHERE id = ?1)", [conversation_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Conversation not found: {}", conversation_id));
+    }
+
+    let current = latest(conn, conversation_id)?;
+    if let Some(current) = &current {
+        if current.prompt.as_deref() == prompt {
+            return Ok(current.clone());
+        }
+    } else if prompt.is_none() {
+        return Err("The conversation has no system prompt to clear".to_string());
+    }
+
+    let version = SystemPromptVersion {
+        conversation_id: conversation_id.to_string(),
+        version: current.map_or(1, |c| c.version + 1),
+        prompt: prompt.map(String::from),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    conn.execute(
+        "INSERT INTO conversation_system_prompts (id, conversation_id, version, prompt, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            uuid::Uuid::new_v4().to_string(),
+            version.conversation_id,
+            version.version,
+            version.prompt,
+            version.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(version)
+}
+
+/// Every version, newest first
+pub fn history(conn: &Connection, conversation_id: &str) -> Result<Vec<SystemPromptVersion>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT conversation_id, version, prompt, created_at FROM conversation_system_prompts
+             WHERE conversation_id = ?1 ORDER BY version DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let versions = stmt
+        .query_map([conversation_id], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(versions)
+}
+
+/// Make the active prompt the only system message, first in `messages`;
+/// returns whether there was one
+pub fn inject(conn: &Connection, conversation_id: &str, messages: &mut Vec<crate::Message>) -> Result<bool, String> {
+    let Some(prompt) = active(conn, conversation_id)? else {
+        return Ok(false);
+    };
+    messages.retain(|m| m.role != "system");
+    messages.insert(0, crate::Message { role: "system".to_string(), content: prompt });
+    Ok(true)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Set or, with no prompt, clear a conversation's system prompt
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_conversation_system_prompt(
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+    prompt: Option<String>,
+) -> Result<SystemPromptVersion, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(set(&conn, &conversation_id, prompt.as_deref())?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_system_prompt_history(
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+) -> Result<Vec<SystemPromptVersion>, AppError> {
+    let conn = db.read()?;
+    Ok(history(&conn, &conversation_id)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Prompts')", []).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_versions() {
+        let conn = setup();
+        assert!(set(&conn, "c1", None).unwrap_err().contains("no system prompt"));
+        assert!(set(&conn, "missing", Some("Be brief")).is_err());
+
+        assert_eq!(set(&conn, "c1", Some(" Be brief ")).unwrap().version, 1);
+        // Same prompt again adds no version
+        assert_eq!(set(&conn, "c1", Some("Be brief")).unwrap().version, 1);
+        assert_eq!(set(&conn, "c1", Some("Answer in Korean")).unwrap().version, 2);
+        assert_eq!(active(&conn, "c1").unwrap().as_deref(), Some("Answer in Korean"));
+
+        let cleared = set(&conn, "c1", Some("  ")).unwrap();
+        assert_eq!((cleared.version, cleared.prompt), (3, None));
+        assert_eq!(active(&conn, "c1").unwrap(), None);
+
+        let versions: Vec<_> = history(&conn, "c1").unwrap().into_iter().map(|v| (v.version, v.prompt)).collect();
+        assert_eq!(
+            versions,
+            vec![(3, None), (2, Some("Answer in Korean".to_string())), (1, Some("Be brief".to_string()))]
+        );
+
+        conn.execute("DELETE FROM conversations WHERE id = 'c1'", []).unwrap();
+        assert!(history(&conn, "c1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_inject_replaces_system_messages() {
+        let conn = setup();
+        let message = |role: &str, content: &str| crate::Message { role: role.to_string(), content: content.to_string() };
+        let mut messages = vec![message("system", "Client prompt"), message("user", "Hi")];
+
+        assert!(!inject(&conn, "c1", &mut messages).unwrap());
+        assert_eq!(messages[0].content, "Client prompt");
+
+        set(&conn, "c1", Some("Conversation prompt")).unwrap();
+        assert!(inject(&conn, "c1", &mut messages).unwrap());
+        let roles: Vec<_> = messages.iter().map(|m| (m.role.as_str(), m.content.as_str())).collect();
+        assert_eq!(roles, vec![("system", "Conversation prompt"), ("user", "Hi")]);
+    }
+}
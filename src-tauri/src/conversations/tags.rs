@@ -0,0 +1,503 @@
+//! Conversation Tags and Smart Folders
+//!
+//! Free-form tags on conversations, compared without regard to case, and
+//! smart folders: named filters combining tags, a last-activity date range,
+//! the provider that answered and a title search. A smart folder stores only
+//! its filter, so its contents are always current.
+
+use super::{conversation_from_row, CONVERSATION_COLUMNS};
+use crate::db::{columns, Conversation, DbState};
+use chrono::NaiveDate;
+use crate::error::AppError;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const MAX_TAG_LENGTH: usize = 50;
+
+/// Trim a tag and collapse its inner whitespace
+pub fn normalize_tag(tag: &str) -> Result<String, AppError> {
+    let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ");
+    if tag.is_empty() {
+        return Err(AppError::invalid_input("Tag is required"));
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(AppError::invalid_input(format!("Tags are limited to {} characters", MAX_TAG_LENGTH)));
+    }
+    Ok(tag)
+}
+
+fn ensure_conversation(conn: &Connection, conversation_id: &str) -> Result<(), AppError> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM conversations WHERE id = ?1)",
+        [conversation_id],
+        |row| row.get(0),
+    )?;
+    if exists {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("Conversation not found: {}", conversation_id)))
+    }
+}
+
+/// Tag a conversation; returns the tag as stored. Tagging twice is a no-op.
+pub fn add(conn: &Connection, conversation_id: &str, tag: &str) -> Result<String, AppError> {
+    let tag = normalize_tag(tag)?;
+    ensure_conversation(conn, conversation_id)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO conversation_tags (conversation_id, tag) VALUES (?1, ?2)",
+        [conversation_id, &tag],
+    )?;
+    Ok(tag)
+}
+
+/// Remove a tag from a conversation; returns whether it was tagged
+pub fn remove(conn: &Connection, conversation_id: &str, tag: &str) -> Result<bool, AppError> {
+    let tag = normalize_tag(tag)?;
+    let removed = conn.execute(
+        "DELETE FROM conversation_tags WHERE conversation_id = ?1 AND tag = ?2",
+        [conversation_id, &tag],
+    )?;
+    Ok(removed > 0)
+}
+
+/// A tag and how many conversations carry it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub conversation_count: i64,
+}
+
+/// Every tag in use, or only the tags of one conversation
+pub fn list(conn: &Connection, conversation_id: Option<&str>) -> Result<Vec<TagCount>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT tag, COUNT(*) FROM conversation_tags
+             WHERE ?1 IS NULL OR tag IN (SELECT tag FROM conversation_tags WHERE conversation_id = ?1)
+             GROUP BY tag ORDER BY tag",
+        )
+        .map_err(|e| e.to_string())?;
+    let tags = stmt
+        .query_map([conversation_id], |row| {
+            Ok(TagCount {
+                tag: row.get(0)?,
+                conversation_count: row.get(1)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    tags
+}
+
+/// Conversation filter, as saved by smart folders; empty fields match all
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConversationFilter {
+    /// Conversations must carry every one of these tags
+    pub tags: Vec<String>,
+    /// First day of last activity, inclusive (`YYYY-MM-DD`, UTC)
+    pub updated_from: Option<String>,
+    /// Last day of last activity, inclusive (`YYYY-MM-DD`, UTC)
+    pub updated_to: Option<String>,
+    /// Provider that answered at least one message, from message metadata
+    pub provider: Option<String>,
+    /// Text the title contains, ignoring case
+    pub title: Option<String>,
+}
+
+impl ConversationFilter {
+    /// Validate the filter, normalizing tags and dropping blank fields
+    pub fn normalized(self) -> Result<Self, AppError> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in &self.tags {
+            let tag = normalize_tag(tag)?;
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                tags.push(tag);
+            }
+        }
+        let text = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let date = |value: Option<String>| -> Result<Option<NaiveDate>, AppError> {
+            text(value)
+                .map(|v| {
+                    NaiveDate::parse_from_str(&v, "%Y-%m-%d")
+                        .map_err(|_| AppError::invalid_input(format!("Invalid date '{}'; use YYYY-MM-DD", v)))
+                })
+                .transpose()
+        };
+        let updated_from = date(self.updated_from)?;
+        let updated_to = date(self.updated_to)?;
+        if let (Some(from), Some(to)) = (updated_from, updated_to) {
+            if from > to {
+                return Err(AppError::invalid_input("The date range ends before it starts"));
+            }
+        }
+        let iso = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+        Ok(Self {
+            tags,
+            updated_from: updated_from.map(iso),
+            updated_to: updated_to.map(iso),
+            provider: text(self.provider),
+            title: text(self.title),
+        })
+    }
+}
+
+/// Conversations with a message answered by `provider`; message metadata is
+/// encrypted, so it is matched here rather than in SQL
+fn provider_conversations(conn: &Connection, provider: &str) -> Result<BTreeSet<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT conversation_id, metadata FROM messages WHERE metadata IS NOT NULL AND metadata != ''")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut ids = BTreeSet::new();
+    for row in rows {
+        let (conversation_id, metadata) = row.map_err(|e| e.to_string())?;
+        // Metadata is optional free-form text and need not be JSON
+        let answered_by = serde_json::from_str::<serde_json::Value>(&columns::open(&metadata)?)
+            .ok()
+            .and_then(|m| m.get("provider").and_then(|p| p.as_str()).map(|p| p.eq_ignore_ascii_case(provider)))
+            .unwrap_or(false);
+        if answered_by {
+            ids.insert(conversation_id);
+        }
+    }
+    Ok(ids)
+}
+
+/// Conversations matching a filter, most recently active first
+pub fn filter(conn: &Connection, filter: &ConversationFilter) -> Result<Vec<Conversation>, AppError> {
+    let filter = filter.clone().normalized()?;
+    let mut clauses = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+    let mut param = |value: String| {
+        params.push(value);
+        params.len()
+    };
+
+    for tag in filter.tags {
+        clauses.push(format!(
+            "EXISTS (SELECT 1 FROM conversation_tags t WHERE t.conversation_id = c.id AND t.tag = ?{})",
+            param(tag)
+        ));
+    }
+    if let Some(from) = filter.updated_from {
+        clauses.push(format!("substr(c.updated_at, 1, 10) >= ?{}", param(from)));
+    }
+    if let Some(to) = filter.updated_to {
+        clauses.push(format!("substr(c.updated_at, 1, 10) <= ?{}", param(to)));
+    }
+    if let Some(provider) = filter.provider {
+        let ids = serde_json::to_string(&provider_conversations(conn, &provider)?)?;
+        clauses.push(format!("c.id IN (SELECT value FROM json_each(?{}))", param(ids)));
+    }
+    if let Some(title) = filter.title {
+        let pattern = format!("%{}%", title.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        clauses.push(format!("c.title LIKE ?{} ESCAPE '\\'", param(pattern)));
+    }
+
+    let condition = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM conversations c {} ORDER BY c.updated_at DESC",
+        CONVERSATION_COLUMNS, condition
+    ))?;
+    let conversations = stmt
+        .query_map(rusqlite::params_from_iter(params.iter()), conversation_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(conversations)
+}
+
+/// Saved, named conversation filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartFolder {
+    pub id: String,
+    pub name: String,
+    pub filter: ConversationFilter,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn folder_from_row(row: &rusqlite::Row) -> rusqlite::Result<SmartFolder> {
+    let filter: String = row.get(2)?;
+    Ok(SmartFolder {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        filter: serde_json::from_str(&filter).unwrap_or_default(),
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+pub fn list_folders(conn: &Connection) -> Result<Vec<SmartFolder>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, filter, created_at, updated_at FROM smart_folders ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let folders = stmt
+        .query_map([], folder_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    folders
+}
+
+pub fn get_folder(conn: &Connection, id: &str) -> Result<SmartFolder, AppError> {
+    conn.query_row(
+        "SELECT id, name, filter, created_at, updated_at FROM smart_folders WHERE id = ?1",
+        [id],
+        folder_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found(format!("Smart folder not found: {}", id)))
+}
+
+/// Create a smart folder, or update the one with `id`
+pub fn save_folder(
+    conn: &Connection,
+    id: Option<&str>,
+    name: &str,
+    filter: ConversationFilter,
+) -> Result<SmartFolder, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("Smart folder name is required"));
+    }
+    let filter = serde_json::to_string(&filter.normalized()?)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let duplicate = |e: rusqlite::Error| match e {
+        rusqlite::Error::SqliteFailure(ref err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+            AppError::invalid_input(format!("A smart folder named '{}' already exists", name))
+        }
+        e => AppError::from(e),
+    };
+
+    let id = match id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE smart_folders SET name = ?1, filter = ?2, updated_at = ?3 WHERE id = ?4",
+                    [name, &filter, &now, id],
+                )
+                .map_err(duplicate)?;
+            if updated == 0 {
+                return Err(AppError::not_found(format!("Smart folder not found: {}", id)));
+            }
+            id.to_string()
+        }
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO smart_folders (id, name, filter, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+                [&id, name, &filter, &now],
+            )
+            .map_err(duplicate)?;
+            id
+        }
+    };
+    get_folder(conn, &id)
+}
+
+pub fn delete_folder(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM smart_folders WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn add_tag(db: tauri::State<'_, DbState>, conversation_id: String, tag: String) -> Result<String, AppError> {
+    let conn = db.conn.lock()?;
+    add(&conn, &conversation_id, &tag)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn remove_tag(db: tauri::State<'_, DbState>, conversation_id: String, tag: String) -> Result<bool, AppError> {
+    let conn = db.conn.lock()?;
+    remove(&conn, &conversation_id, &tag)
+}
+
+/// Every tag with its conversation count, or only one conversation's tags
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_tags(db: tauri::State<'_, DbState>, conversation_id: Option<String>) -> Result<Vec<TagCount>, AppError> {
+    let conn = db.read()?;
+    Ok(list(&conn, conversation_id.as_deref())?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_conversations_by_tag(db: tauri::State<'_, DbState>, tag: String) -> Result<Vec<Conversation>, AppError> {
+    let conn = db.read()?;
+    let filter_by_tag = ConversationFilter {
+        tags: vec![tag],
+        ..ConversationFilter::default()
+    };
+    filter(&conn, &filter_by_tag)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn filter_conversations(
+    db: tauri::State<'_, DbState>,
+    filter: ConversationFilter,
+) -> Result<Vec<Conversation>, AppError> {
+    let conn = db.conn.lock()?;
+    self::filter(&conn, &filter)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_smart_folders(db: tauri::State<'_, DbState>) -> Result<Vec<SmartFolder>, AppError> {
+    let conn = db.read()?;
+    Ok(list_folders(&conn)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn save_smart_folder(
+    db: tauri::State<'_, DbState>,
+    id: Option<String>,
+    name: String,
+    filter: ConversationFilter,
+) -> Result<SmartFolder, AppError> {
+    let conn = db.conn.lock()?;
+    save_folder(&conn, id.as_deref(), &name, filter)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_smart_folder(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(delete_folder(&conn, &id)?)
+}
+
+/// Conversations currently matching a smart folder's filter
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_smart_folder_conversations(
+    db: tauri::State<'_, DbState>,
+    id: String,
+) -> Result<Vec<Conversation>, AppError> {
+    let conn = db.read()?;
+    let folder = get_folder(&conn, &id)?;
+    filter(&conn, &folder.filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+    use crate::error::ErrorKind;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        for (id, title, updated_at, provider) in [
+            ("c1", "Quarterly budget", "2026-03-02T10:00:00+00:00", Some("anthropic")),
+            ("c2", "Trip to Busan", "2026-03-15T08:30:00+00:00", Some("openai")),
+            ("c3", "100% coverage plan", "2026-04-01T23:59:00+00:00", None),
+        ] {
+            conn.execute(
+                "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+                [id, title, updated_at],
+            )
+            .unwrap();
+            let metadata = provider.map(|p| format!(r#"{{"provider":"{}"}}"#, p)).unwrap_or_default();
+            conn.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, metadata) VALUES (?1, ?2, 'assistant', 'Hi', ?3)",
+                [&format!("m-{}", id), id, &metadata],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn ids(conversations: Vec<Conversation>) -> Vec<String> {
+        conversations.into_iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn test_tags() {
+        let conn = setup();
+        assert_eq!(add(&conn, "c1", "  Work   finance ").unwrap(), "Work finance");
+        add(&conn, "c1", "work FINANCE").unwrap();
+        add(&conn, "c2", "work finance").unwrap();
+        add(&conn, "c2", "travel").unwrap();
+        assert_eq!(add(&conn, "missing", "travel").unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(add(&conn, "c1", "   ").unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        let tags = list(&conn, None).unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                TagCount { tag: "travel".to_string(), conversation_count: 1 },
+                TagCount { tag: "Work finance".to_string(), conversation_count: 2 },
+            ]
+        );
+        assert_eq!(list(&conn, Some("c1")).unwrap().len(), 1);
+
+        let by_tag = |tag: &str| {
+            let tag_filter = ConversationFilter { tags: vec![tag.to_string()], ..Default::default() };
+            ids(filter(&conn, &tag_filter).unwrap())
+        };
+        assert_eq!(by_tag("WORK FINANCE"), vec!["c2", "c1"]);
+        assert!(remove(&conn, "c2", "Work Finance").unwrap());
+        assert!(!remove(&conn, "c2", "Work Finance").unwrap());
+        assert_eq!(by_tag("work finance"), vec!["c1"]);
+    }
+
+    #[test]
+    fn test_filters_and_smart_folders() {
+        let conn = setup();
+        add(&conn, "c1", "work").unwrap();
+        add(&conn, "c3", "work").unwrap();
+
+        let march = ConversationFilter {
+            updated_from: Some("2026-03-01".to_string()),
+            updated_to: Some("2026-03-31".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter(&conn, &march).unwrap()), vec!["c2", "c1"]);
+        let openai = ConversationFilter { provider: Some("OpenAI".to_string()), ..Default::default() };
+        assert_eq!(ids(filter(&conn, &openai).unwrap()), vec!["c2"]);
+        let percent = ConversationFilter { title: Some("100%".to_string()), ..Default::default() };
+        assert_eq!(ids(filter(&conn, &percent).unwrap()), vec!["c3"]);
+        let reversed = ConversationFilter {
+            updated_from: Some("2026-04-01".to_string()),
+            updated_to: Some("2026-03-01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter(&conn, &reversed).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        let folder = save_folder(
+            &conn,
+            None,
+            "March work",
+            ConversationFilter { tags: vec!["Work".to_string()], ..march },
+        )
+        .unwrap();
+        assert_eq!(ids(filter(&conn, &get_folder(&conn, &folder.id).unwrap().filter).unwrap()), vec!["c1"]);
+        assert!(save_folder(&conn, None, "march WORK", ConversationFilter::default())
+            .unwrap_err()
+            .to_string()
+            .contains("already exists"));
+
+        let renamed = save_folder(&conn, Some(&folder.id), "Work", ConversationFilter::default()).unwrap();
+        assert_eq!(renamed.filter, ConversationFilter::default());
+        assert_eq!(list_folders(&conn).unwrap().len(), 1);
+        delete_folder(&conn, &folder.id).unwrap();
+        assert_eq!(get_folder(&conn, &folder.id).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,351 @@
+//! Database backups and restore
+//!
+//! Backups are online copies taken with `VACUUM INTO`. The copy is written to
+//! a `.partial` file, checked with `PRAGMA integrity_check` and only then
+//! renamed, so a crash never leaves a truncated file that looks like a
+//! backup. Automatic backups go to the `backups` folder next to the database
+//! and are rotated down to the `backup_retention` setting by the daily
+//! `backup_database` system task.
+//!
+//! Restoring verifies the source first, saves the current database as a
+//! `pre-restore` backup, then copies the source into the live connection
+//! with SQLite's backup API and re-runs migrations, so an older backup is
+//! brought up to the current schema.
+
+use chrono::{DateTime, Utc};
+use crate::error::AppError;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Automatic backups kept when no retention is set
+pub const DEFAULT_RETENTION: u32 = 7;
+
+/// Setting holding the number of automatic backups kept
+const RETENTION_KEY: &str = "backup_retention";
+
+/// Backup folder, relative to the database's folder
+const BACKUP_DIR: &str = "backups";
+
+/// Name prefixes of automatic and pre-restore backups
+const AUTO_PREFIX: &str = "assistant-";
+const PRE_RESTORE_PREFIX: &str = "pre-restore-";
+
+/// Backup file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+/// Result of a restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub restored_from: String,
+    /// Copy of the database as it was before the restore
+    pub safety_backup: BackupInfo,
+    /// Schema version of the restored backup, before migrations ran
+    pub schema_version: i32,
+}
+
+/// Folder holding the automatic backups of the database at `db_path`
+pub fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path.parent().unwrap_or(Path::new(".")).join(BACKUP_DIR)
+}
+
+fn info(path: &Path) -> Result<BackupInfo, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let created_at = metadata
+        .modified()
+        .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+        .unwrap_or_default();
+    Ok(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        size_bytes: metadata.len(),
+        created_at,
+    })
+}
+
+/// Unused path in `dir` for a backup named after the current time
+fn timestamped_path(dir: &Path, prefix: &str) -> PathBuf {
+    let stamp = Utc::now().format("%Y%m%d-%H%M%S");
+    let mut path = dir.join(format!("{}{}.db", prefix, stamp));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}.db", prefix, stamp, n));
+        n += 1;
+    }
+    path
+}
+
+/// Open a database file read-only and check its integrity; returns its
+/// schema version
+pub fn verify(path: &Path) -> Result<i32, String> {
+    if !path.is_file() {
+        return Err(format!("Backup not found: {}", path.display()));
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+    let result: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Not a valid database: {}", e))?;
+    if result != "ok" {
+        return Err(format!("Backup failed the integrity check: {}", result));
+    }
+    conn.query_row("SELECT MAX(version) FROM schema_migrations", [], |row| row.get::<_, Option<i32>>(0))
+        .map_err(|_| "Not an app database backup".to_string())?
+        .ok_or_else(|| "Not an app database backup".to_string())
+}
+
+/// Copy the database to `dest`, replacing any existing file
+pub fn create(conn: &Connection, dest: &Path) -> Result<BackupInfo, String> {
+    if let Some(dir) = dest.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    // Left over from an interrupted backup
+    let _ = std::fs::remove_file(&partial);
+
+    let written = conn
+        .execute("VACUUM INTO ?1", [partial.to_string_lossy()])
+        .map_err(|e| format!("Backup failed: {}", e))
+        .and_then(|_| verify(&partial));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, dest).map_err(|e| format!("Failed to save backup: {}", e))?;
+    info(dest)
+}
+
+/// Back up to `dest`, or to a new automatic backup when None
+pub fn backup(conn: &Connection, db_path: &Path, dest: Option<&Path>) -> Result<BackupInfo, String> {
+    match dest {
+        Some(dest) => create(conn, dest),
+        None => create(conn, &timestamped_path(&backup_dir(db_path), AUTO_PREFIX)),
+    }
+}
+
+/// Automatic and pre-restore backups of the database at `db_path`, newest first
+pub fn list(db_path: &Path) -> Result<Vec<BackupInfo>, String> {
+    let mut backups = backup_files(&backup_dir(db_path), &[AUTO_PREFIX, PRE_RESTORE_PREFIX])?;
+    backups.reverse();
+    backups.iter().map(|path| info(path)).collect()
+}
+
+/// Backup files in `dir` whose names start with one of `prefixes`, oldest first
+fn backup_files(dir: &Path, prefixes: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read backup folder: {}", e)),
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name().and_then(|n| n.to_str()).is_some_and(|name| {
+                name.ends_with(".db") && prefixes.iter().any(|prefix| name.starts_with(prefix))
+            })
+        })
+        .map(|path| (std::fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH), path))
+        .collect();
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Delete all but the newest `keep` automatic backups; returns the number deleted
+pub fn rotate(db_path: &Path, keep: u32) -> Result<usize, String> {
+    let files = backup_files(&backup_dir(db_path), &[AUTO_PREFIX])?;
+    let excess = files.len().saturating_sub(keep.max(1) as usize);
+    for path in &files[..excess] {
+        std::fs::remove_file(path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+    }
+    Ok(excess)
+}
+
+/// Automatic backups kept
+pub fn retention(conn: &Connection) -> Result<u32, String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM scheduler_settings WHERE key = ?1", [RETENTION_KEY], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(value.and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_RETENTION))
+}
+
+pub fn set_retention(conn: &Connection, keep: u32) -> Result<(), String> {
+    if keep == 0 {
+        return Err("Retention must keep at least one backup".to_string());
+    }
+    conn.execute(
+        "INSERT INTO scheduler_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        rusqlite::params![RETENTION_KEY, keep.to_string()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Take an automatic backup and rotate old ones (system task)
+pub fn run_scheduled(conn: &Connection, db_path: &Path, keep: Option<u32>) -> Result<(BackupInfo, usize), String> {
+    let created = backup(conn, db_path, None)?;
+    let keep = match keep {
+        Some(keep) => keep,
+        None => retention(conn)?,
+    };
+    Ok((created, rotate(db_path, keep)?))
+}
+
+/// Replace the live database with the backup at `source`
+pub fn restore(conn: &mut Connection, db_path: &Path, source: &Path) -> Result<RestoreResult, String> {
+    let schema_version = verify(source)?;
+    let live_version: i32 = conn
+        .query_row("SELECT MAX(version) FROM schema_migrations", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if schema_version > live_version {
+        return Err(format!(
+            "Backup is from a newer version of the app (schema v{}, this app supports v{})",
+            schema_version, live_version
+        ));
+    }
+
+    let safety_backup = create(conn, &timestamped_path(&backup_dir(db_path), PRE_RESTORE_PREFIX))?;
+
+    let source_conn = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+    rusqlite::backup::Backup::new(&source_conn, conn)
+        .and_then(|copy| copy.run_to_completion(256, Duration::ZERO, None))
+        .map_err(|e| format!("Restore failed: {}", e))?;
+    super::schema::run_migrations(conn).map_err(|e| format!("Failed to migrate restored database: {}", e))?;
+
+    tracing::info!("Database restored from {}", source.display());
+    Ok(RestoreResult {
+        restored_from: source.to_string_lossy().to_string(),
+        safety_backup,
+        schema_version,
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Back up the database to `path`, or to a new automatic backup
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn backup_database(db: tauri::State<'_, super::DbState>, path: Option<String>) -> Result<BackupInfo, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(backup(&conn, Path::new(&db.path()), path.as_deref().map(Path::new))?)
+}
+
+/// Restore the database from a backup file
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn restore_database(db: tauri::State<'_, super::DbState>, path: String) -> Result<RestoreResult, AppError> {
+    let mut conn = db.conn.lock()?;
+    Ok(restore(&mut conn, Path::new(&db.path()), Path::new(&path))?)
+}
+
+/// Automatic and pre-restore backups, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_backups(db: tauri::State<'_, super::DbState>) -> Result<Vec<BackupInfo>, AppError> {
+    Ok(list(Path::new(&db.path()))?)
+}
+
+/// Automatic backups kept
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_backup_retention(db: tauri::State<'_, super::DbState>) -> Result<u32, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(retention(&conn)?)
+}
+
+/// Set the automatic backups kept
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_backup_retention(db: tauri::State<'_, super::DbState>, keep: u32) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(set_retention(&conn, keep)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(dir: &Path) -> (Connection, PathBuf) {
+        let db_path = dir.join("assistant.db");
+        // WAL mode, as the app opens it
+        let conn = super::super::open_database(&db_path).unwrap();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Before')", []).unwrap();
+        (conn, db_path)
+    }
+
+    fn title(conn: &Connection) -> String {
+        conn.query_row("SELECT title FROM conversations WHERE id = 'c1'", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut conn, db_path) = setup(dir.path());
+
+        let backup = backup(&conn, &db_path, None).unwrap();
+        assert!(backup.path.contains("backups"));
+        assert!(verify(Path::new(&backup.path)).unwrap() > 0);
+
+        conn.execute("UPDATE conversations SET title = 'After'", []).unwrap();
+        let restored = restore(&mut conn, &db_path, Path::new(&backup.path)).unwrap();
+        assert_eq!(title(&conn), "Before");
+
+        let safety = Connection::open(&restored.safety_backup.path).unwrap();
+        assert_eq!(title(&safety), "After");
+        assert_eq!(list(&db_path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_restore_rejects_invalid_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut conn, db_path) = setup(dir.path());
+
+        let garbage = dir.path().join("garbage.db");
+        std::fs::write(&garbage, vec![7u8; 8192]).unwrap();
+        assert!(restore(&mut conn, &db_path, &garbage).is_err());
+        assert!(restore(&mut conn, &db_path, &dir.path().join("missing.db")).is_err());
+
+        let newer = dir.path().join("newer.db");
+        create(&conn, &newer).unwrap();
+        Connection::open(&newer)
+            .unwrap()
+            .execute("INSERT INTO schema_migrations (version) VALUES (9999)", [])
+            .unwrap();
+        assert!(restore(&mut conn, &db_path, &newer).unwrap_err().contains("newer version"));
+
+        // Nothing was touched
+        assert_eq!(title(&conn), "Before");
+        assert!(list(&db_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rotate_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let (conn, db_path) = setup(dir.path());
+        set_retention(&conn, 2).unwrap();
+
+        let mut created = Vec::new();
+        for _ in 0..3 {
+            created.push(run_scheduled(&conn, &db_path, None).unwrap().0.path);
+            // Keep modification times apart
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let left: Vec<String> = list(&db_path).unwrap().into_iter().map(|b| b.path).collect();
+        assert_eq!(left, vec![created[2].clone(), created[1].clone()]);
+        assert_eq!(rotate(&db_path, 1).unwrap(), 1);
+    }
+}
@@ -0,0 +1,49 @@
+//! Encrypted columns
+//!
+//! Columns that may hold secrets are stored encrypted with the column key
+//! from `security::encryption`: message metadata, which can carry tool
+//! output, and integration settings such as calendar URLs with embedded
+//! tokens. Writers seal values with [`seal`]; readers use [`get`] and
+//! [`get_optional`] instead of `row.get`. Rows written before encryption
+//! are read as plaintext until `security::migration` encrypts them.
+
+use crate::security::encryption::{decrypt_column, encrypt_column};
+use rusqlite::types::Type;
+use rusqlite::Row;
+
+/// Encrypted `(table, column)` pairs
+pub const ENCRYPTED_COLUMNS: &[(&str, &str)] = &[
+    ("messages", "metadata"),
+    ("calendar_sources", "url"),
+    ("calendar_sources", "username"),
+];
+
+/// Value to store in an encrypted column
+pub fn seal(value: &str) -> Result<String, String> {
+    encrypt_column(value).map_err(|e| e.to_string())
+}
+
+pub fn seal_optional(value: Option<&str>) -> Result<Option<String>, String> {
+    value.map(seal).transpose()
+}
+
+/// Plaintext of a stored value
+pub fn open(value: &str) -> Result<String, String> {
+    decrypt_column(value).map_err(|e| e.to_string())
+}
+
+/// Read and decrypt an encrypted column
+pub fn get(row: &Row, index: usize) -> rusqlite::Result<String> {
+    let value: String = row.get(index)?;
+    decrypt_column(&value).map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+/// Read and decrypt a nullable encrypted column
+pub fn get_optional(row: &Row, index: usize) -> rusqlite::Result<Option<String>> {
+    let value: Option<String> = row.get(index)?;
+    value
+        .map(|value| {
+            decrypt_column(&value).map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+        })
+        .transpose()
+}
@@ -0,0 +1,515 @@
+//! Export of all user data
+//!
+//! `export_all_data` writes a zip archive of everything the user created in
+//! the app. Each file holds JSON Lines, one record per line, and is written
+//! row by row straight from the database, so large histories are never held
+//! in memory. `manifest.json` lists every file with its record count and
+//! fields:
+//!
+//! - `conversations.jsonl`: a conversation per line with its tags and
+//!   messages (`id`, `role`, `content`, `metadata`, `createdAt`). Messages of
+//!   sensitive conversations are encrypted with the conversation's key; they
+//!   are exported as plaintext only while the conversation is unlocked, and
+//!   otherwise with `content` null and `encrypted` true.
+//! - `skills.jsonl`, `recipes.jsonl`, `templates.jsonl`: rows of those
+//!   tables, keyed by column name.
+//! - `settings.jsonl`: `key`, `value` and `updatedAt` of each app setting;
+//!   values that are JSON are exported as JSON. Settings and fields whose
+//!   names mark them as secrets (tokens, passwords, keys) are left out.
+//!   `scheduler_settings.jsonl` and `voice_settings.jsonl` hold those tables.
+//! - `permissions/`: folder permissions, tool policies and shell binary rules.
+//! - `jobs/`: scheduled jobs, their executions and skipped runs.
+//!
+//! Progress is emitted as `export://progress` events while the archive is
+//! written.
+
+use crate::db::columns;
+use crate::error::AppError;
+use crate::security::encryption::is_encrypted_column;
+use crate::security::field_encryption::decrypt_field;
+use crate::security::SensitiveSession;
+use chrono::Utc;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::Emitter;
+
+/// Archive format written by this version
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Event carrying export progress
+pub const PROGRESS_EVENT: &str = "export://progress";
+
+/// Records written between progress updates
+const PROGRESS_INTERVAL: usize = 500;
+
+/// Substrings of setting and field names holding secrets
+const SECRET_MARKERS: &[&str] = &["token", "secret", "password", "api_key", "apikey", "credential", "private_key"];
+
+/// Table exported as rows keyed by column name
+struct TableFile {
+    path: &'static str,
+    table: &'static str,
+    description: &'static str,
+}
+
+const TABLE_FILES: &[TableFile] = &[
+    TableFile { path: "skills.jsonl", table: "skills", description: "Skills" },
+    TableFile { path: "recipes.jsonl", table: "recipes", description: "Recipes" },
+    TableFile { path: "templates.jsonl", table: "templates", description: "Prompt templates" },
+    TableFile { path: "scheduler_settings.jsonl", table: "scheduler_settings", description: "Scheduler settings" },
+    TableFile { path: "voice_settings.jsonl", table: "voice_settings", description: "Voice settings" },
+    TableFile {
+        path: "permissions/folder_permissions.jsonl",
+        table: "folder_permissions",
+        description: "Folders the assistant may read or write",
+    },
+    TableFile {
+        path: "permissions/tool_policies.jsonl",
+        table: "tool_policies",
+        description: "Allow, ask or deny decision per tool",
+    },
+    TableFile {
+        path: "permissions/shell_binary_rules.jsonl",
+        table: "shell_binary_rules",
+        description: "Allowed and denied shell commands",
+    },
+    TableFile { path: "jobs/cron_jobs.jsonl", table: "cron_jobs", description: "Scheduled jobs" },
+    TableFile { path: "jobs/job_executions.jsonl", table: "job_executions", description: "Job execution history" },
+    TableFile {
+        path: "jobs/job_skipped_runs.jsonl",
+        table: "job_skipped_runs",
+        description: "Runs skipped while a job was snoozed",
+    },
+];
+
+const CONVERSATIONS_FILE: &str = "conversations.jsonl";
+const SETTINGS_FILE: &str = "settings.jsonl";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// File listed in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedFile {
+    pub path: String,
+    pub description: String,
+    /// Fields of each record
+    pub fields: Vec<String>,
+    pub records: usize,
+}
+
+/// `manifest.json` of an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    pub files: Vec<ExportedFile>,
+}
+
+/// Created export archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataExport {
+    pub path: String,
+    pub size_bytes: u64,
+    pub manifest: ExportManifest,
+}
+
+/// Progress of an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    /// File being written
+    pub file: String,
+    /// Position of the file, starting at 1
+    pub file_index: usize,
+    pub total_files: usize,
+    /// Records written to the file so far
+    pub records: usize,
+    /// Whether the file is complete
+    pub done: bool,
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// Remove secret fields at any depth
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !is_secret(key));
+            map.values_mut().for_each(redact);
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// JSON of stored text, or the text itself
+fn json_or_text(text: String) -> Value {
+    serde_json::from_str(&text).unwrap_or(Value::String(text))
+}
+
+fn column_value(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null | ValueRef::Blob(_) => Value::Null,
+        ValueRef::Integer(i) => json!(i),
+        ValueRef::Real(f) => json!(f),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+    }
+}
+
+/// Writes the archive's files and reports progress
+struct ArchiveWriter<'a> {
+    zip: zip::ZipWriter<std::fs::File>,
+    options: zip::write::SimpleFileOptions,
+    total_files: usize,
+    files: Vec<ExportedFile>,
+    progress: &'a mut dyn FnMut(&ExportProgress),
+}
+
+impl ArchiveWriter<'_> {
+    fn zip_error(e: impl std::fmt::Display) -> String {
+        format!("Failed to write export archive: {}", e)
+    }
+
+    fn start(&mut self, path: &str, description: &str, fields: Vec<String>) -> Result<(), String> {
+        self.zip.start_file(path, self.options).map_err(Self::zip_error)?;
+        self.files.push(ExportedFile {
+            path: path.to_string(),
+            description: description.to_string(),
+            fields,
+            records: 0,
+        });
+        self.report(false);
+        Ok(())
+    }
+
+    fn record(&mut self, record: &Value) -> Result<(), String> {
+        serde_json::to_writer(&mut self.zip, record).map_err(Self::zip_error)?;
+        self.zip.write_all(b"\n").map_err(Self::zip_error)?;
+        let file = self.files.last_mut().ok_or("No export file started")?;
+        file.records += 1;
+        if file.records % PROGRESS_INTERVAL == 0 {
+            self.report(false);
+        }
+        Ok(())
+    }
+
+    fn finish_file(&mut self) {
+        self.report(true);
+    }
+
+    fn report(&mut self, done: bool) {
+        if let Some(file) = self.files.last() {
+            (self.progress)(&ExportProgress {
+                file: file.path.clone(),
+                file_index: self.files.len(),
+                total_files: self.total_files,
+                records: file.records,
+                done,
+            });
+        }
+    }
+}
+
+fn write_table(writer: &mut ArchiveWriter, conn: &Connection, file: &TableFile) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} ORDER BY rowid", file.table))
+        .map_err(|e| format!("Failed to read {}: {}", file.table, e))?;
+    let fields: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    writer.start(file.path, file.description, fields.clone())?;
+
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut record = Map::new();
+        for (i, field) in fields.iter().enumerate() {
+            record.insert(field.clone(), column_value(row.get_ref(i).map_err(|e| e.to_string())?));
+        }
+        writer.record(&Value::Object(record))?;
+    }
+    writer.finish_file();
+    Ok(())
+}
+
+fn write_settings(writer: &mut ArchiveWriter, conn: &Connection) -> Result<(), String> {
+    writer.start(SETTINGS_FILE, "App settings, without secrets", vec!["key".into(), "value".into(), "updatedAt".into()])?;
+    let mut stmt = conn
+        .prepare("SELECT key, value, updated_at FROM app_settings ORDER BY key")
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let key: String = row.get(0).map_err(|e| e.to_string())?;
+        let value: String = row.get(1).map_err(|e| e.to_string())?;
+        if is_secret(&key) || is_encrypted_column(&value) {
+            continue;
+        }
+        let mut value = json_or_text(value);
+        redact(&mut value);
+        let updated_at: Option<String> = row.get(2).map_err(|e| e.to_string())?;
+        writer.record(&json!({ "key": key, "value": value, "updatedAt": updated_at }))?;
+    }
+    writer.finish_file();
+    Ok(())
+}
+
+/// Message as exported; encrypted content is decrypted with `key`, the key
+/// of an unlocked conversation, and left out without one
+fn message_record(row: &Row, key: Option<&[u8; 32]>) -> rusqlite::Result<Value> {
+    let stored: bool = row.get(5)?;
+    let content: Option<String> = match (stored, key) {
+        (false, _) => row.get(2)?,
+        (true, Some(key)) => Some(
+            decrypt_field(&row.get::<_, String>(2)?, key)
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e)))?,
+        ),
+        (true, None) => None,
+    };
+    let encrypted = stored && content.is_none();
+    Ok(json!({
+        "id": row.get::<_, String>(0)?,
+        "role": row.get::<_, String>(1)?,
+        "content": content,
+        "metadata": columns::get_optional(row, 3)?.map(json_or_text),
+        "createdAt": row.get::<_, String>(4)?,
+        "encrypted": encrypted,
+    }))
+}
+
+fn write_conversations(writer: &mut ArchiveWriter, conn: &Connection, session: &SensitiveSession) -> Result<(), String> {
+    let fields = ["id", "title", "createdAt", "updatedAt", "isSensitive", "workspaceId", "tags", "messages"];
+    writer.start(
+        CONVERSATIONS_FILE,
+        "Conversations with their tags and messages",
+        fields.iter().map(|f| f.to_string()).collect(),
+    )?;
+
+    let mut conversations = conn
+        .prepare(
+            "SELECT id, title, created_at, updated_at, is_sensitive, workspace_id
+             FROM conversations ORDER BY created_at, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut tags = conn
+        .prepare("SELECT tag FROM conversation_tags WHERE conversation_id = ?1 ORDER BY tag")
+        .map_err(|e| e.to_string())?;
+    let mut messages = conn
+        .prepare(
+            "SELECT id, role, content, metadata, created_at, is_encrypted
+             FROM messages WHERE conversation_id = ?1 ORDER BY created_at, rowid",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = conversations.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let id: String = row.get(0).map_err(|e| e.to_string())?;
+        let key = session.unlocked_key(&id);
+        let conversation_tags = tags
+            .query_map([&id], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| e.to_string())?;
+        let conversation_messages = messages
+            .query_map([&id], |row| message_record(row, key.as_ref()))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read messages of {}: {}", id, e))?;
+
+        let record = json!({
+            "id": id,
+            "title": row.get::<_, String>(1).map_err(|e| e.to_string())?,
+            "createdAt": row.get::<_, String>(2).map_err(|e| e.to_string())?,
+            "updatedAt": row.get::<_, String>(3).map_err(|e| e.to_string())?,
+            "isSensitive": row.get::<_, bool>(4).map_err(|e| e.to_string())?,
+            "workspaceId": row.get::<_, Option<String>>(5).map_err(|e| e.to_string())?,
+            "tags": conversation_tags,
+            "messages": conversation_messages,
+        });
+        writer.record(&record)?;
+    }
+    writer.finish_file();
+    Ok(())
+}
+
+/// Write every export file into a zip archive at `path`
+///
+/// Sensitive conversations unlocked in `session` are exported in plaintext.
+pub fn export_all(
+    conn: &Connection,
+    session: &SensitiveSession,
+    path: &Path,
+    progress: &mut dyn FnMut(&ExportProgress),
+) -> Result<DataExport, String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut writer = ArchiveWriter {
+        zip: zip::ZipWriter::new(file),
+        options: zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+        total_files: TABLE_FILES.len() + 2,
+        files: Vec::new(),
+        progress,
+    };
+
+    write_conversations(&mut writer, conn, session)?;
+    write_settings(&mut writer, conn)?;
+    for file in TABLE_FILES {
+        write_table(&mut writer, conn, file)?;
+    }
+
+    let manifest = ExportManifest {
+        format_version: EXPORT_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: Utc::now().to_rfc3339(),
+        files: std::mem::take(&mut writer.files),
+    };
+    let data = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    writer.zip.start_file(MANIFEST_FILE, writer.options).map_err(ArchiveWriter::zip_error)?;
+    writer.zip.write_all(&data).map_err(ArchiveWriter::zip_error)?;
+    writer.zip.finish().map_err(ArchiveWriter::zip_error)?;
+
+    let size_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Ok(DataExport { path: path.to_string_lossy().to_string(), size_bytes, manifest })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Export all user data to a zip archive at `path`
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn export_all_data(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::db::DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    path: String,
+) -> Result<DataExport, AppError> {
+    let db_path = db.path();
+    // Unlocking loads the key, so a copy can decrypt whatever is unlocked now
+    let session = session.lock()?.clone();
+    let export = tauri::async_runtime::spawn_blocking(move || -> Result<DataExport, String> {
+        let conn = crate::scheduler::history::open(Path::new(&db_path))?;
+        let export = export_all(&conn, &session, Path::new(&path), &mut |progress| {
+            if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+                tracing::warn!("Failed to emit export progress: {}", e);
+            }
+        })?;
+        tracing::info!("Exported all data to {} ({} bytes)", export.path, export.size_bytes);
+        Ok(export)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use crate::db::test_support::test_conn;
+
+    fn read_file(path: &Path, name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        let mut text = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            r#"
+            INSERT INTO conversations (id, title, created_at, updated_at) VALUES ('c1', 'Plans', '2026-01-01T00:00:00Z', '2026-01-01T00:01:00Z');
+            INSERT INTO messages (id, conversation_id, role, content, metadata, created_at)
+                VALUES ('m1', 'c1', 'user', 'Hello', NULL, '2026-01-01T00:00:00Z'),
+                       ('m2', 'c1', 'assistant', 'Hi!', '{"model":"gpt-4o"}', '2026-01-01T00:01:00Z');
+            INSERT INTO conversation_tags (conversation_id, tag) VALUES ('c1', 'travel');
+            INSERT INTO skills (id, name, description, prompt) VALUES ('s1', 'Summarize', 'Summaries', 'Summarize this');
+            INSERT INTO app_settings (key, value) VALUES ('theme', 'dark'),
+                ('rest_api_token_hash', 'abc'),
+                ('rest_api', '{"port":8787,"token":"secret-value"}');
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_export_writes_every_file() {
+        let conn = setup();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        let mut updates = Vec::new();
+        let export = export_all(&conn, &SensitiveSession::new(), &path, &mut |p| updates.push(p.clone())).unwrap();
+
+        assert_eq!(export.manifest.files.len(), TABLE_FILES.len() + 2);
+        let manifest: ExportManifest = serde_json::from_str(&read_file(&path, MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest.format_version, EXPORT_FORMAT_VERSION);
+        let skills = manifest.files.iter().find(|f| f.path == "skills.jsonl").unwrap();
+        assert_eq!(skills.records, 1);
+        assert!(skills.fields.contains(&"prompt".to_string()));
+
+        let conversations = read_file(&path, CONVERSATIONS_FILE);
+        let conversation: Value = serde_json::from_str(conversations.lines().next().unwrap()).unwrap();
+        assert_eq!(conversation["tags"], json!(["travel"]));
+        assert_eq!(conversation["messages"][1]["content"], "Hi!");
+        assert_eq!(conversation["messages"][1]["metadata"]["model"], "gpt-4o");
+
+        let done: Vec<_> = updates.iter().filter(|p| p.done).map(|p| p.file_index).collect();
+        assert_eq!(done, (1..=TABLE_FILES.len() + 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_settings_exclude_secrets() {
+        let conn = setup();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        export_all(&conn, &SensitiveSession::new(), &path, &mut |_| {}).unwrap();
+
+        let settings = read_file(&path, SETTINGS_FILE);
+        assert!(!settings.contains("secret-value"));
+        assert!(!settings.contains("rest_api_token_hash"));
+        let records: Vec<Value> = settings.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let rest_api = records.iter().find(|r| r["key"] == "rest_api").unwrap();
+        assert_eq!(rest_api["value"], json!({ "port": 8787 }));
+        assert!(records.iter().any(|r| r["key"] == "theme" && r["value"] == "dark"));
+    }
+
+    #[test]
+    fn test_sensitive_messages_exported_only_while_unlocked() {
+        let conn = test_conn();
+        let key = crate::security::encryption::derive_key("export", b"salt");
+        let secret = crate::security::field_encryption::encrypt_field("Vault code 1234", &key).unwrap();
+        conn.execute("INSERT INTO conversations (id, title, is_sensitive) VALUES ('c2', 'Private', 1)", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, created_at, is_encrypted)
+             VALUES ('m1', 'c2', 'user', ?1, '2026-01-01T00:00:00Z', 1)",
+            [&secret],
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        let mut session = SensitiveSession::with_key(key);
+        let message = |session: &SensitiveSession| -> Value {
+            export_all(&conn, session, &path, &mut |_| {}).unwrap();
+            let conversations = read_file(&path, CONVERSATIONS_FILE);
+            let conversation: Value = serde_json::from_str(conversations.lines().next().unwrap()).unwrap();
+            conversation["messages"][0].clone()
+        };
+
+        let locked = message(&session);
+        assert_eq!(locked["content"], Value::Null);
+        assert_eq!(locked["encrypted"], true);
+
+        session.unlock("c2");
+        let unlocked = message(&session);
+        assert_eq!(unlocked["content"], "Vault code 1234");
+        assert_eq!(unlocked["encrypted"], false);
+    }
+}
@@ -0,0 +1,316 @@
+//! Database health checks
+//!
+//! The weekly `integrity_check` system task runs `PRAGMA integrity_check`
+//! and `PRAGMA foreign_key_check`, refreshes the index statistics with
+//! `ANALYZE` and counts the free pages left behind by deletes. Reports are
+//! kept in `db_health_reports`, the newest [`KEEP_REPORTS`] of them. A
+//! report finding corruption, foreign key violations or a file that is
+//! mostly free pages fails the job, so a `notify_channel` param posts it,
+//! and is emitted on [`HEALTH_EVENT`]. `get_db_health` returns the latest
+//! report with the current size of the database, WAL and shared-memory
+//! files.
+
+use chrono::{DateTime, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Event carrying a `HealthReport` that found problems
+pub const HEALTH_EVENT: &str = "db://health";
+
+/// Reports kept
+pub const KEEP_REPORTS: i64 = 30;
+
+/// Problems listed by each check at most
+const MAX_PROBLEMS: usize = 100;
+
+/// Share of free pages above which the file counts as bloated
+const BLOAT_RATIO: f64 = 0.25;
+
+/// Free space below which bloat is not reported
+const MIN_BLOAT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Row whose parent key is missing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+}
+
+/// Statistics `ANALYZE` gathered for an index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStats {
+    pub table: String,
+    pub index: String,
+    pub rows: u64,
+    /// Average rows per distinct value of the leading columns, from
+    /// `sqlite_stat1`
+    pub rows_per_key: Vec<u64>,
+}
+
+/// Outcome of one health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub id: String,
+    pub checked_at: String,
+    /// Messages of `PRAGMA integrity_check`; empty when it passed
+    pub integrity_errors: Vec<String>,
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+    pub indexes: Vec<IndexStats>,
+    pub page_size: u64,
+    pub page_count: u64,
+    pub free_pages: u64,
+    /// Why the database is not healthy; empty when it is
+    pub problems: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// One line for the job output
+    pub fn summary(&self) -> String {
+        let free_mb = (self.free_pages * self.page_size) as f64 / (1024.0 * 1024.0);
+        let state = if self.healthy() { "Database is healthy".to_string() } else { self.problems.join("; ") };
+        format!("{} ({} pages, {:.1} MB free, {} indexes analyzed)", state, self.page_count, free_mb, self.indexes.len())
+    }
+}
+
+/// Current size of the database files, in bytes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSizes {
+    pub database: u64,
+    pub wal: u64,
+    pub shm: u64,
+}
+
+/// Latest report with the current file sizes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbHealth {
+    /// None until the first check ran
+    pub report: Option<HealthReport>,
+    pub files: FileSizes,
+}
+
+fn pragma_u64(conn: &Connection, pragma: &str) -> Result<u64, String> {
+    conn.query_row(&format!("PRAGMA {}", pragma), [], |row| row.get::<_, i64>(0))
+        .map(|v| v.max(0) as u64)
+        .map_err(|e| e.to_string())
+}
+
+fn integrity_errors(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA integrity_check({})", MAX_PROBLEMS))
+        .map_err(|e| e.to_string())?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(messages.into_iter().filter(|m| m != "ok").collect())
+}
+
+fn foreign_key_violations(conn: &Connection) -> Result<Vec<ForeignKeyViolation>, String> {
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check").map_err(|e| e.to_string())?;
+    let violations = stmt
+        .query_map([], |row| {
+            Ok(ForeignKeyViolation { table: row.get(0)?, rowid: row.get(1)?, parent: row.get(2)? })
+        })
+        .map_err(|e| e.to_string())?
+        .take(MAX_PROBLEMS)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(violations)
+}
+
+/// Refresh and read the index statistics
+fn index_stats(conn: &Connection) -> Result<Vec<IndexStats>, String> {
+    conn.execute_batch("ANALYZE").map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT tbl, idx, stat FROM sqlite_stat1 WHERE idx IS NOT NULL ORDER BY tbl, idx")
+        .map_err(|e| e.to_string())?;
+    let stats = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(stats
+        .into_iter()
+        .map(|(table, index, stat)| {
+            // "rows per-column..." followed by optional flags such as "unordered"
+            let mut numbers = stat.split_whitespace().map_while(|n| n.parse::<u64>().ok());
+            IndexStats { table, index, rows: numbers.next().unwrap_or(0), rows_per_key: numbers.collect() }
+        })
+        .collect())
+}
+
+/// Run every check on the database
+pub fn check(conn: &Connection, now: DateTime<Utc>) -> Result<HealthReport, String> {
+    let integrity_errors = integrity_errors(conn)?;
+    let foreign_key_violations = foreign_key_violations(conn)?;
+    let indexes = index_stats(conn)?;
+    let page_size = pragma_u64(conn, "page_size")?;
+    let page_count = pragma_u64(conn, "page_count")?;
+    let free_pages = pragma_u64(conn, "freelist_count")?;
+
+    let mut problems = Vec::new();
+    if !integrity_errors.is_empty() {
+        problems.push(format!("Integrity check failed: {}", integrity_errors[0]));
+    }
+    if !foreign_key_violations.is_empty() {
+        let tables: std::collections::BTreeSet<_> = foreign_key_violations.iter().map(|v| v.table.as_str()).collect();
+        problems.push(format!(
+            "{} rows with missing parents in {}",
+            foreign_key_violations.len(),
+            tables.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    let free_bytes = free_pages * page_size;
+    if page_count > 0 && free_bytes >= MIN_BLOAT_BYTES && free_pages as f64 / page_count as f64 > BLOAT_RATIO {
+        problems.push(format!(
+            "{:.0}% of the file is free pages; vacuum the database to reclaim {:.1} MB",
+            free_pages as f64 / page_count as f64 * 100.0,
+            free_bytes as f64 / (1024.0 * 1024.0)
+        ));
+    }
+
+    Ok(HealthReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        checked_at: now.to_rfc3339(),
+        integrity_errors,
+        foreign_key_violations,
+        indexes,
+        page_size,
+        page_count,
+        free_pages,
+        problems,
+    })
+}
+
+/// Store a report, dropping all but the newest [`KEEP_REPORTS`]
+pub fn save(conn: &Connection, report: &HealthReport) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO db_health_reports (id, checked_at, healthy, report) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            report.id,
+            report.checked_at,
+            report.healthy(),
+            serde_json::to_string(report).map_err(|e| e.to_string())?
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM db_health_reports WHERE id NOT IN
+            (SELECT id FROM db_health_reports ORDER BY checked_at DESC LIMIT ?1)",
+        [KEEP_REPORTS],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Check the database and store the report
+pub fn run(conn: &Connection, now: DateTime<Utc>) -> Result<HealthReport, String> {
+    let report = check(conn, now)?;
+    save(conn, &report)?;
+    Ok(report)
+}
+
+pub fn latest(conn: &Connection) -> Result<Option<HealthReport>, String> {
+    let report: Option<String> = conn
+        .query_row("SELECT report FROM db_health_reports ORDER BY checked_at DESC LIMIT 1", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    report
+        .map(|r| serde_json::from_str(&r).map_err(|e| format!("Invalid health report: {}", e)))
+        .transpose()
+}
+
+/// Sizes of the database at `db_path` and its WAL and shared-memory files;
+/// missing files count as empty
+pub fn file_sizes(db_path: &Path) -> FileSizes {
+    let size = |suffix: &str| {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+    };
+    FileSizes { database: size(""), wal: size("-wal"), shm: size("-shm") }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Latest health report and the current file sizes
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_db_health(db: tauri::State<'_, super::DbState>) -> Result<DbHealth, AppError> {
+    let report = {
+        let conn = db.read()?;
+        latest(&conn)?
+    };
+    Ok(DbHealth { report, files: file_sizes(Path::new(&db.path())) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_healthy_database_and_report_history() {
+        let conn = test_conn();
+        assert!(latest(&conn).unwrap().is_none());
+
+        let now = Utc::now();
+        let report = run(&conn, now).unwrap();
+        assert!(report.healthy(), "{:?}", report.problems);
+        assert!(report.integrity_errors.is_empty());
+        assert!(report.page_count > 0);
+        assert!(report.indexes.iter().any(|i| i.table == "cron_jobs"));
+        assert_eq!(latest(&conn).unwrap().unwrap().id, report.id);
+
+        for day in 1..=KEEP_REPORTS {
+            run(&conn, now + chrono::Duration::days(day)).unwrap();
+        }
+        let kept: i64 = conn.query_row("SELECT COUNT(*) FROM db_health_reports", [], |row| row.get(0)).unwrap();
+        assert_eq!(kept, KEEP_REPORTS);
+    }
+
+    #[test]
+    fn test_detects_violations_and_bloat() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("assistant.db");
+        let conn = Connection::open(&db_path).unwrap();
+        super::super::schema::run_migrations(&conn).unwrap();
+
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             INSERT INTO messages (id, conversation_id, role, content) VALUES ('m1', 'gone', 'user', 'Hi');
+             CREATE TABLE filler (data BLOB);",
+        )
+        .unwrap();
+        for _ in 0..16 {
+            conn.execute("INSERT INTO filler (data) VALUES (zeroblob(1024 * 1024))", []).unwrap();
+        }
+        conn.execute("DELETE FROM filler", []).unwrap();
+
+        let report = check(&conn, Utc::now()).unwrap();
+        assert_eq!(report.foreign_key_violations[0].table, "messages");
+        assert_eq!(report.foreign_key_violations[0].parent, "conversations");
+        assert_eq!(report.problems.len(), 2, "{:?}", report.problems);
+        assert!(report.problems[1].contains("vacuum"));
+
+        let files = file_sizes(&db_path);
+        assert_eq!(files.database, report.page_count * report.page_size);
+        assert_eq!(file_sizes(&dir.path().join("missing.db")), FileSizes::default());
+    }
+}
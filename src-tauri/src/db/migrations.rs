@@ -0,0 +1,371 @@
+//! Versioned schema migrations
+//!
+//! Every schema change is a numbered `Migration` with an `up` step and, when
+//! it can be reverted, `down` SQL. Applied migrations are recorded in
+//! `applied_migrations`; `schema_migrations` is still written because backups
+//! and older builds read the schema version from it.
+//!
+//! A run applies its steps inside a single savepoint: if any step fails, every
+//! step of the run is rolled back and the database stays at the version it
+//! started from. A dry run applies the steps the same way and then always
+//! rolls them back.
+
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// Numbered schema change
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub up: fn(&Connection) -> rusqlite::Result<()>,
+    /// SQL reverting `up`; None when the migration cannot be reverted
+    pub down: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// Migration to apply or revert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStep {
+    pub version: i32,
+    pub name: String,
+    pub direction: Direction,
+}
+
+/// Recorded migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub version: i32,
+    pub name: String,
+    pub applied_at: String,
+    pub execution_ms: Option<i64>,
+}
+
+/// Schema version of a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersion {
+    pub current: i32,
+    /// Newest version this build can migrate to
+    pub latest: i32,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<MigrationStep>,
+}
+
+/// Outcome of a migration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub from_version: i32,
+    pub to_version: i32,
+    pub dry_run: bool,
+    pub steps: Vec<MigrationStep>,
+    /// Backup taken before the run
+    pub backup: Option<String>,
+}
+
+/// Create the bookkeeping tables and record migrations applied before
+/// `applied_migrations` existed
+fn ensure_tables(conn: &Connection, migrations: &[Migration]) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE IF NOT EXISTS applied_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now')),
+            execution_ms INTEGER
+        );",
+    )?;
+    for migration in migrations {
+        conn.execute(
+            "INSERT OR IGNORE INTO applied_migrations (version, name, applied_at)
+             SELECT version, ?2, applied_at FROM schema_migrations WHERE version = ?1",
+            params![migration.version, migration.name],
+        )?;
+    }
+    Ok(())
+}
+
+fn applied_versions(conn: &Connection) -> rusqlite::Result<HashSet<i32>> {
+    let mut stmt = conn.prepare("SELECT version FROM applied_migrations")?;
+    let versions = stmt.query_map([], |row| row.get(0))?.collect();
+    versions
+}
+
+fn current_version(conn: &Connection) -> rusqlite::Result<i32> {
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM applied_migrations", [], |row| row.get(0))
+}
+
+fn latest_version(migrations: &[Migration]) -> i32 {
+    migrations.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+fn step(migration: &Migration, direction: Direction) -> MigrationStep {
+    MigrationStep { version: migration.version, name: migration.name.to_string(), direction }
+}
+
+/// Migrations not yet applied, oldest first
+fn pending(migrations: &[Migration], applied: &HashSet<i32>, up_to: i32) -> Vec<MigrationStep> {
+    migrations
+        .iter()
+        .filter(|m| m.version <= up_to && !applied.contains(&m.version))
+        .map(|m| step(m, Direction::Up))
+        .collect()
+}
+
+/// Steps taking the database to `target`, or to the latest version
+fn plan(conn: &Connection, migrations: &[Migration], target: Option<i32>) -> Result<Vec<MigrationStep>, String> {
+    let latest = latest_version(migrations);
+    let target = target.unwrap_or(latest);
+    if target < 0 || target > latest {
+        return Err(format!("Unknown schema version {} (latest is {})", target, latest));
+    }
+    let applied = applied_versions(conn).map_err(|e| e.to_string())?;
+    let current = current_version(conn).map_err(|e| e.to_string())?;
+    if target >= current {
+        return Ok(pending(migrations, &applied, target));
+    }
+
+    let mut steps = Vec::new();
+    for migration in migrations.iter().rev().filter(|m| m.version > target && applied.contains(&m.version)) {
+        if migration.down.is_none() {
+            return Err(format!("Migration v{} ({}) cannot be reverted", migration.version, migration.name));
+        }
+        steps.push(step(migration, Direction::Down));
+    }
+    Ok(steps)
+}
+
+fn apply(conn: &Connection, migration: &Migration, direction: Direction) -> rusqlite::Result<()> {
+    let started = Instant::now();
+    match direction {
+        Direction::Up => {
+            (migration.up)(conn)?;
+            conn.execute("INSERT OR IGNORE INTO schema_migrations (version) VALUES (?1)", [migration.version])?;
+            conn.execute(
+                "INSERT INTO applied_migrations (version, name, execution_ms) VALUES (?1, ?2, ?3)",
+                params![migration.version, migration.name, started.elapsed().as_millis() as i64],
+            )?;
+        }
+        Direction::Down => {
+            conn.execute_batch(migration.down.unwrap_or_default())?;
+            conn.execute("DELETE FROM schema_migrations WHERE version = ?1", [migration.version])?;
+            conn.execute("DELETE FROM applied_migrations WHERE version = ?1", [migration.version])?;
+        }
+    }
+    tracing::info!("Migration v{} ({}) {:?} applied", migration.version, migration.name, direction);
+    Ok(())
+}
+
+/// Apply `steps` in one savepoint, rolling all of them back on failure or
+/// when `dry_run` is set
+fn run_steps(
+    conn: &Connection,
+    migrations: &[Migration],
+    steps: &[MigrationStep],
+    dry_run: bool,
+) -> Result<(), (i32, rusqlite::Error)> {
+    conn.execute_batch("SAVEPOINT migrate").map_err(|e| (0, e))?;
+    let result = steps.iter().try_for_each(|s| {
+        let migration = migrations.iter().find(|m| m.version == s.version).expect("planned migration exists");
+        apply(conn, migration, s.direction).map_err(|e| (s.version, e))
+    });
+
+    let finish = if result.is_err() || dry_run { "ROLLBACK TO migrate; RELEASE migrate" } else { "RELEASE migrate" };
+    conn.execute_batch(finish).map_err(|e| (0, e))?;
+    result
+}
+
+/// Apply every pending migration
+pub fn run_pending(conn: &Connection, migrations: &[Migration]) -> rusqlite::Result<()> {
+    ensure_tables(conn, migrations)?;
+    let steps = pending(migrations, &applied_versions(conn)?, i32::MAX);
+    run_steps(conn, migrations, &steps, false).map_err(|(version, e)| {
+        tracing::error!("Migration v{} failed, rolled back: {}", version, e);
+        e
+    })
+}
+
+/// Migrate to `target`, or to the latest version, reverting migrations when
+/// the target is older than the current version
+pub fn migrate(
+    conn: &Connection,
+    migrations: &[Migration],
+    target: Option<i32>,
+    dry_run: bool,
+) -> Result<MigrationReport, String> {
+    ensure_tables(conn, migrations).map_err(|e| e.to_string())?;
+    let from_version = current_version(conn).map_err(|e| e.to_string())?;
+    let steps = plan(conn, migrations, target)?;
+    run_steps(conn, migrations, &steps, dry_run)
+        .map_err(|(version, e)| format!("Migration v{} failed and was rolled back: {}", version, e))?;
+
+    let to_version = match steps.last() {
+        None => from_version,
+        Some(_) if dry_run => target.unwrap_or_else(|| latest_version(migrations)),
+        Some(_) => current_version(conn).map_err(|e| e.to_string())?,
+    };
+    Ok(MigrationReport { from_version, to_version, dry_run, steps, backup: None })
+}
+
+/// Current version, applied and pending migrations
+pub fn status(conn: &Connection, migrations: &[Migration]) -> Result<SchemaVersion, String> {
+    ensure_tables(conn, migrations).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT version, name, applied_at, execution_ms FROM applied_migrations ORDER BY version")
+        .map_err(|e| e.to_string())?;
+    let applied = stmt
+        .query_map([], |row| {
+            Ok(AppliedMigration {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                applied_at: row.get(2)?,
+                execution_ms: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let versions = applied.iter().map(|m| m.version).collect();
+    Ok(SchemaVersion {
+        current: current_version(conn).map_err(|e| e.to_string())?,
+        latest: latest_version(migrations),
+        pending: pending(migrations, &versions, i32::MAX),
+        applied,
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Schema version of the app database
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_schema_version(db: tauri::State<'_, super::DbState>) -> Result<SchemaVersion, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(status(&conn, super::schema::MIGRATIONS)?)
+}
+
+/// Migrate the app database to `target`, or to the latest version
+///
+/// A backup is taken before any migration is applied; a dry run only reports
+/// the steps and checks that they succeed.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn migrate_database(
+    db: tauri::State<'_, super::DbState>,
+    target: Option<i32>,
+    dry_run: Option<bool>,
+) -> Result<MigrationReport, AppError> {
+    let conn = db.conn.lock()?;
+    let dry_run = dry_run.unwrap_or(false);
+    let migrations = super::schema::MIGRATIONS;
+
+    let backup = if dry_run || plan(&conn, migrations, target)?.is_empty() {
+        None
+    } else {
+        Some(super::backup::backup(&conn, std::path::Path::new(&db.path()), None)?.path)
+    };
+    let report = migrate(&conn, migrations, target, dry_run)?;
+    Ok(MigrationReport { backup, ..report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn create_notes(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)")
+    }
+
+    fn add_pinned(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch("ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0")
+    }
+
+    fn broken(conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute_batch("CREATE TABLE tags (name TEXT); INSERT INTO missing VALUES (1)")
+    }
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration { version: 1, name: "notes", up: create_notes, down: None },
+        Migration { version: 2, name: "pinned_notes", up: add_pinned, down: Some("ALTER TABLE notes DROP COLUMN pinned") },
+    ];
+
+    fn has_table(conn: &Connection, name: &str) -> bool {
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = ?1", [name], |row| row.get::<_, i64>(0))
+            .unwrap()
+            > 0
+    }
+
+    #[test]
+    fn test_up_down_and_dry_run() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let dry = migrate(&conn, MIGRATIONS, None, true).unwrap();
+        assert_eq!((dry.from_version, dry.to_version, dry.steps.len()), (0, 2, 2));
+        assert!(!has_table(&conn, "notes"));
+        assert_eq!(status(&conn, MIGRATIONS).unwrap().pending.len(), 2);
+
+        let report = migrate(&conn, MIGRATIONS, None, false).unwrap();
+        assert_eq!(report.to_version, 2);
+        conn.execute("INSERT INTO notes (body, pinned) VALUES ('hi', 1)", []).unwrap();
+
+        let down = migrate(&conn, MIGRATIONS, Some(1), false).unwrap();
+        assert_eq!(down.steps[0].direction, Direction::Down);
+        assert_eq!(down.to_version, 1);
+        assert!(conn.execute("UPDATE notes SET pinned = 0", []).is_err());
+        assert!(migrate(&conn, MIGRATIONS, Some(0), false).unwrap_err().contains("cannot be reverted"));
+        assert!(migrate(&conn, MIGRATIONS, Some(3), false).is_err());
+
+        run_pending(&conn, MIGRATIONS).unwrap();
+        let status = status(&conn, MIGRATIONS).unwrap();
+        assert_eq!((status.current, status.latest, status.applied.len()), (2, 2, 2));
+        assert!(status.pending.is_empty());
+    }
+
+    #[test]
+    fn test_failed_run_rolls_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, &MIGRATIONS[..1], None, false).unwrap();
+
+        let with_broken = [
+            Migration { version: 1, name: "notes", up: create_notes, down: None },
+            Migration { version: 2, name: "pinned_notes", up: add_pinned, down: None },
+            Migration { version: 3, name: "broken", up: broken, down: None },
+        ];
+        let error = migrate(&conn, &with_broken, None, false).unwrap_err();
+        assert!(error.contains("v3"), "{}", error);
+        assert!(!has_table(&conn, "tags"));
+        assert!(conn.execute("UPDATE notes SET pinned = 0", []).is_err());
+        assert_eq!(status(&conn, &with_broken).unwrap().current, 1);
+    }
+
+    #[test]
+    fn test_app_migrations_revert() {
+        let conn = test_conn();
+        let latest = status(&conn, super::super::schema::MIGRATIONS).unwrap().latest;
+
+        let down = migrate(&conn, super::super::schema::MIGRATIONS, Some(21), false).unwrap();
+        assert_eq!(down.to_version, 21);
+        assert!(!has_table(&conn, "scheduler_settings"));
+
+        let up = migrate(&conn, super::super::schema::MIGRATIONS, None, false).unwrap();
+        assert_eq!(up.to_version, latest);
+    }
+}
@@ -1,37 +1,101 @@
 // Database Module - SQLite persistence
 
+pub mod backup;
+pub mod columns;
+pub mod export;
+pub mod health;
+pub mod migrations;
+pub mod pool;
 pub mod schema;
-
-use rusqlite::{Connection, Result as SqliteResult};
+pub mod settings;
+pub mod skill_schema;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+use crate::error::AppError;
+use crate::security::field_encryption::{decrypt_field, encrypt_field};
+use crate::security::{CredentialManager, SensitiveSession};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 use tauri::Manager;
 
 /// Database state managed by Tauri
 pub struct DbState {
+    /// The single write connection
     pub conn: Mutex<Connection>,
-    pub db_path: String,
+    /// Read-only connections for commands that only query
+    readers: pool::ReadPool,
+    /// Changes when another profile is activated
+    db_path: RwLock<String>,
+}
+
+/// Database file name inside the app data folder
+pub const DB_FILE: &str = "assistant.db";
+
+/// Bundle identifier from `tauri.conf.json`, which names the app data folder
+const APP_IDENTIFIER: &str = "ai.assistant.desktop";
+
+/// Database path of the active profile for use without a Tauri app handle,
+/// such as in MCP server mode; the same folder Tauri resolves as the app
+/// data directory
+pub fn default_db_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| crate::profiles::active_db_path(&dir.join(APP_IDENTIFIER)))
+}
+
+/// Open the database at `path` and bring its schema up to date
+pub fn open_database(path: &std::path::Path) -> SqliteResult<Connection> {
+    let conn = Connection::open(path)?;
+    // Another process, such as the MCP server, may be writing
+    pool::configure(&conn)?;
+    pool::enable_wal(&conn)?;
+    schema::run_migrations(&conn)?;
+    Ok(conn)
 }
 
 impl DbState {
-    pub fn new(app_handle: &tauri::AppHandle) -> SqliteResult<Self> {
+    pub fn new(app_handle: &tauri::AppHandle) -> Result<Self, String> {
         let app_dir = app_handle
             .path()
             .app_data_dir()
             .expect("Failed to get app data directory");
 
         std::fs::create_dir_all(&app_dir).ok();
-        let db_path = PathBuf::from(&app_dir).join("assistant.db");
-        let db_path_str = db_path.to_string_lossy().to_string();
+        let db_path = crate::profiles::active_db_path(&app_dir);
+        if let Some(dir) = db_path.parent() {
+            std::fs::create_dir_all(dir).ok();
+        }
 
-        let conn = Connection::open(&db_path)?;
-        schema::run_migrations(&conn)?;
+        let conn = open_database(&db_path).map_err(|e| e.to_string())?;
+        let readers = pool::ReadPool::open(&db_path, pool::READ_CONNECTIONS)?;
 
         Ok(Self {
             conn: Mutex::new(conn),
-            db_path: db_path_str,
+            readers,
+            db_path: RwLock::new(db_path.to_string_lossy().to_string()),
         })
     }
+
+    /// Path of the open database file
+    pub fn path(&self) -> String {
+        self.db_path.read().map(|path| path.clone()).unwrap_or_default()
+    }
+
+    /// A read-only connection; reads through it never wait for a write
+    pub fn read(&self) -> Result<pool::PooledConnection<'_>, String> {
+        self.readers.get()
+    }
+
+    /// Swap in another open database, as when switching profiles
+    pub fn replace(&self, conn: Connection, path: &std::path::Path) -> Result<(), String> {
+        let mut current = self.conn.lock().map_err(|e| e.to_string())?;
+        self.readers.reopen(path)?;
+        let mut db_path = self.db_path.write().map_err(|e| e.to_string())?;
+        *current = conn;
+        *db_path = path.to_string_lossy().to_string();
+        Ok(())
+    }
 }
 
 /// Conversation model
@@ -41,6 +105,10 @@ pub struct Conversation {
     pub title: String,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub is_sensitive: bool,
+    #[serde(default)]
+    pub workspace_id: Option<String>,
 }
 
 /// Message model
@@ -65,138 +133,437 @@ pub struct FolderPermission {
 
 // Tauri commands for database operations
 
+/// Saved conversations, newest first; only those of one workspace when
+/// `workspace_id` is given
 #[tauri::command]
-pub fn load_conversations(db: tauri::State<'_, DbState>) -> Result<Vec<Conversation>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn load_conversations(
+    db: tauri::State<'_, DbState>,
+    workspace_id: Option<String>,
+) -> Result<Vec<Conversation>, AppError> {
+    let conn = db.read()?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, title, created_at, updated_at FROM conversations ORDER BY updated_at DESC"
-        )
-        .map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, title, created_at, updated_at, is_sensitive, workspace_id FROM conversations
+             WHERE ?1 IS NULL OR workspace_id = ?1
+             ORDER BY updated_at DESC",
+    )?;
 
     let conversations = stmt
-        .query_map([], |row| {
+        .query_map([&workspace_id], |row| {
             Ok(Conversation {
                 id: row.get(0)?,
                 title: row.get(1)?,
                 created_at: row.get(2)?,
                 updated_at: row.get(3)?,
+                is_sensitive: row.get(4)?,
+                workspace_id: row.get(5)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(conversations)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn save_conversation(
     db: tauri::State<'_, DbState>,
     id: String,
     title: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    // New conversations join the active workspace
+    let workspace_id = crate::workspaces::active_id(&conn)?;
+    upsert_conversation(&conn, &id, &title, workspace_id.as_deref())
+}
+
+/// Create a conversation or rename an existing one
+///
+/// Updates in place, so an existing conversation keeps its workspace and
+/// sensitive flag.
+fn upsert_conversation(conn: &Connection, id: &str, title: &str, workspace_id: Option<&str>) -> Result<(), AppError> {
     let now = chrono::Utc::now().to_rfc3339();
-
     conn.execute(
-        "INSERT OR REPLACE INTO conversations (id, title, created_at, updated_at)
-         VALUES (?1, ?2, COALESCE((SELECT created_at FROM conversations WHERE id = ?1), ?3), ?4)",
-        [&id, &title, &now, &now],
-    )
-    .map_err(|e| e.to_string())?;
-
+        "INSERT INTO conversations (id, title, created_at, updated_at, workspace_id)
+         VALUES (?1, ?2, ?3, ?3, ?4)
+         ON CONFLICT(id) DO UPDATE SET title = excluded.title, updated_at = excluded.updated_at",
+        rusqlite::params![id, title, now, workspace_id],
+    )?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_conversation(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_conversation(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     // Delete messages first
-    conn.execute("DELETE FROM messages WHERE conversation_id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM messages WHERE conversation_id = ?1", [&id])?;
+
+    conn.execute(
+        "DELETE FROM conversation_tags WHERE conversation_id = ?1",
+        [&id],
+    )?;
+    conn.execute(
+        "DELETE FROM pinned_messages WHERE conversation_id = ?1",
+        [&id],
+    )?;
+    conn.execute(
+        "DELETE FROM conversation_bookmarks WHERE conversation_id = ?1",
+        [&id],
+    )?;
+    conn.execute(
+        "DELETE FROM guardrail_overrides WHERE conversation_id = ?1",
+        [&id],
+    )?;
+    conn.execute(
+        "DELETE FROM conversation_embeddings WHERE conversation_id = ?1",
+        [&id],
+    )?;
+    conn.execute(
+        "DELETE FROM imported_conversations WHERE conversation_id = ?1",
+        [&id],
+    )?;
 
     // Delete conversation
-    conn.execute("DELETE FROM conversations WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM conversations WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
+/// Key decrypting the messages of a sensitive conversation; None for other
+/// conversations, and an error while a sensitive one is locked
+pub fn conversation_key(
+    conn: &Connection,
+    session: &Mutex<SensitiveSession>,
+    credentials: &Mutex<CredentialManager>,
+    conversation_id: &str,
+) -> Result<Option<[u8; 32]>, AppError> {
+    if !is_conversation_sensitive(conn, conversation_id)? {
+        return Ok(None);
+    }
+    let mut session = session.lock()?;
+    if !session.is_unlocked(conversation_id) {
+        return Err(AppError::permission_denied(format!("Conversation is locked: {}", conversation_id)));
+    }
+    let credentials = credentials.lock()?;
+    Ok(Some(session.key(&credentials)?))
+}
+
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn load_messages(
     db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
     conversation_id: String,
-) -> Result<Vec<Message>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<Message>, AppError> {
+    let conn = db.read()?;
+    let key = conversation_key(&conn, &session, &credentials, &conversation_id)?;
+    read_messages(&conn, key.as_ref(), &conversation_id)
+}
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, conversation_id, role, content, metadata, created_at
-             FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC"
-        )
-        .map_err(|e| e.to_string())?;
+/// Messages of a conversation, oldest first, decrypted with `key`
+fn read_messages(conn: &Connection, key: Option<&[u8; 32]>, conversation_id: &str) -> Result<Vec<Message>, AppError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, conversation_id, role, content, metadata, created_at, is_encrypted
+             FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC",
+    )?;
 
-    let messages = stmt
-        .query_map([&conversation_id], |row| {
-            Ok(Message {
-                id: row.get(0)?,
-                conversation_id: row.get(1)?,
-                role: row.get(2)?,
-                content: row.get(3)?,
-                metadata: row.get(4)?,
-                created_at: row.get(5)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([conversation_id], |row| {
+            Ok((
+                Message {
+                    id: row.get(0)?,
+                    conversation_id: row.get(1)?,
+                    role: row.get(2)?,
+                    content: row.get(3)?,
+                    metadata: columns::get_optional(row, 4)?,
+                    created_at: row.get(5)?,
+                },
+                row.get::<_, bool>(6)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Decrypt in memory only; ciphertext stays on disk
+    let mut messages = Vec::with_capacity(rows.len());
+    for (mut message, encrypted) in rows {
+        if encrypted {
+            let key = key.ok_or_else(|| AppError::Internal("Encrypted message in non-sensitive conversation".to_string()))?;
+            message.content = decrypt_field(&message.content, key)?;
+        }
+        messages.push(message);
+    }
 
     Ok(messages)
 }
 
+/// Message to store; `created_at` defaults to the time it is saved
+#[derive(Debug, serde::Deserialize)]
+pub struct NewMessage {
+    pub id: String,
+    pub conversation_id: String,
+    pub role: String,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+/// Key encrypting new messages of a sensitive conversation; None for other
+/// conversations
+fn message_key(
+    conn: &Connection,
+    session: &Mutex<SensitiveSession>,
+    credentials: &Mutex<CredentialManager>,
+    conversation_id: &str,
+) -> Result<Option<[u8; 32]>, String> {
+    if !is_conversation_sensitive(conn, conversation_id)? {
+        return Ok(None);
+    }
+    let mut session = session.lock().map_err(|e| e.to_string())?;
+    let credentials = credentials.lock().map_err(|e| e.to_string())?;
+    Ok(Some(session.key(&credentials).map_err(|e| e.to_string())?))
+}
+
+/// Insert a message, encrypting its content with `key`
+fn insert_message(conn: &Connection, key: Option<&[u8; 32]>, message: &NewMessage, created_at: &str) -> Result<(), String> {
+    let content = match key {
+        Some(key) => encrypt_field(&message.content, key).map_err(|e| e.to_string())?,
+        None => message.content.clone(),
+    };
+    let metadata = columns::seal(message.metadata.as_deref().unwrap_or_default())?;
+    conn.prepare_cached(
+        "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at, is_encrypted)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )
+    .and_then(|mut stmt| {
+        stmt.execute(rusqlite::params![
+            message.id,
+            message.conversation_id,
+            message.role,
+            content,
+            metadata,
+            created_at,
+            key.is_some()
+        ])
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn touch_conversation(conn: &Connection, conversation_id: &str, now: &str) -> Result<(), String> {
+    conn.prepare_cached("UPDATE conversations SET updated_at = ?1 WHERE id = ?2")
+        .and_then(|mut stmt| stmt.execute([now, conversation_id]))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Insert messages in one transaction; `keys` holds the key of each
+/// conversation written to. Messages without a time keep their order.
+pub fn save_batch(
+    conn: &mut Connection,
+    messages: &[NewMessage],
+    keys: &HashMap<String, Option<[u8; 32]>>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (i, message) in messages.iter().enumerate() {
+        let key = keys.get(&message.conversation_id).copied().flatten();
+        let created_at = match &message.created_at {
+            Some(created_at) => created_at.clone(),
+            None => (now + chrono::Duration::microseconds(i as i64)).to_rfc3339(),
+        };
+        insert_message(&tx, key.as_ref(), message, &created_at)?;
+    }
+    let now = now.to_rfc3339();
+    for conversation_id in keys.keys() {
+        touch_conversation(&tx, conversation_id, &now)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(messages.len())
+}
+
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub fn save_message(
     db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
     id: String,
     conversation_id: String,
     role: String,
     content: String,
     metadata: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
-
-    conn.execute(
-        "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        [&id, &conversation_id, &role, &content, &metadata.unwrap_or_default(), &now],
-    )
-    .map_err(|e| e.to_string())?;
+    let key = message_key(&conn, &session, &credentials, &conversation_id)?;
+    let message = NewMessage { id, conversation_id, role, content, metadata, created_at: None };
+    insert_message(&conn, key.as_ref(), &message, &now)?;
 
     // Update conversation timestamp
-    conn.execute(
-        "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
-        [&now, &conversation_id],
-    )
-    .map_err(|e| e.to_string())?;
+    Ok(touch_conversation(&conn, &message.conversation_id, &now)?)
+}
+
+/// Store many messages in one transaction, as when importing or syncing
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn save_messages(
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    messages: Vec<NewMessage>,
+) -> Result<usize, AppError> {
+    let mut conn = db.conn.lock()?;
+
+    let mut keys = HashMap::new();
+    for message in &messages {
+        if !keys.contains_key(&message.conversation_id) {
+            let key = message_key(&conn, &session, &credentials, &message.conversation_id)?;
+            keys.insert(message.conversation_id.clone(), key);
+        }
+    }
+    Ok(save_batch(&mut conn, &messages, &keys, chrono::Utc::now())?)
+}
+
+// ============================================================================
+// Sensitive Conversation Commands (v0.7)
+// ============================================================================
+
+/// Check whether a conversation is flagged as sensitive
+pub fn is_conversation_sensitive(conn: &Connection, conversation_id: &str) -> Result<bool, String> {
+    let sensitive: Option<bool> = conn
+        .query_row(
+            "SELECT is_sensitive FROM conversations WHERE id = ?1",
+            [conversation_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(sensitive.unwrap_or(false))
+}
+
+/// Flag or unflag a conversation as sensitive, re-encoding its messages
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_conversation_sensitive(
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+    sensitive: bool,
+) -> Result<(), AppError> {
+    let mut conn = db.conn.lock()?;
+    let mut session = session.lock()?;
+
+    if is_conversation_sensitive(&conn, &id)? == sensitive {
+        return Ok(());
+    }
+    if !sensitive && !session.is_unlocked(&id) {
+        return Err(AppError::permission_denied(format!("Conversation is locked: {}", id)));
+    }
+
+    let key = {
+        let credentials = credentials.lock()?;
+        session.key(&credentials)?
+    };
+
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "SELECT id, content FROM messages WHERE conversation_id = ?1 AND is_encrypted = ?2",
+        )?;
+        let rows = stmt
+            .query_map(rusqlite::params![&id, !sensitive], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (message_id, content) in rows {
+            let content = if sensitive {
+                encrypt_field(&content, &key)
+            } else {
+                decrypt_field(&content, &key)
+            }?;
+
+            tx.execute(
+                "UPDATE messages SET content = ?1, is_encrypted = ?2 WHERE id = ?3",
+                rusqlite::params![&content, sensitive, &message_id],
+            )?;
+        }
+
+        tx.execute(
+            "UPDATE conversations SET is_sensitive = ?1 WHERE id = ?2",
+            rusqlite::params![sensitive, &id],
+        )?;
+    }
+    tx.commit()?;
+
+    if !sensitive {
+        session.lock(&id);
+    }
 
     Ok(())
 }
 
+/// Unlock a sensitive conversation for the current session
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn unlock_conversation(
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+) -> Result<(), AppError> {
+    let mut session = session.lock()?;
+    let credentials = credentials.lock()?;
+
+    // Requires keychain access, which may prompt the user
+    session.key(&credentials)?;
+    session.unlock(&id);
+
+    Ok(())
+}
+
+/// Lock a sensitive conversation again
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn lock_conversation(
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    id: String,
+) -> Result<(), AppError> {
+    let mut session = session.lock()?;
+    session.lock(&id);
+    Ok(())
+}
+
+/// Lock every sensitive conversation and forget the cached key
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn lock_all_conversations(
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+) -> Result<(), AppError> {
+    let mut session = session.lock()?;
+    session.lock_all();
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn load_folder_permissions(
     db: tauri::State<'_, DbState>,
-) -> Result<Vec<FolderPermission>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<FolderPermission>, AppError> {
+    let conn = db.read()?;
 
-    let mut stmt = conn
-        .prepare("SELECT id, path, level, created_at FROM folder_permissions ORDER BY path")
-        .map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, path, level, created_at FROM folder_permissions ORDER BY path",
+    )?;
 
     let permissions = stmt
         .query_map([], |row| {
@@ -206,61 +573,90 @@ pub fn load_folder_permissions(
                 level: row.get(2)?,
                 created_at: row.get(3)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(permissions)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn add_folder_permission(
     db: tauri::State<'_, DbState>,
     id: String,
     path: String,
     level: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "INSERT INTO folder_permissions (id, path, level, created_at) VALUES (?1, ?2, ?3, ?4)",
         [&id, &path, &level, &now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn remove_folder_permission(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn remove_folder_permission(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM folder_permissions WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM workspace_folders WHERE folder_id = ?1", [&id])?;
+    conn.execute("DELETE FROM folder_permissions WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn update_folder_permission(
     db: tauri::State<'_, DbState>,
     id: String,
     level: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     conn.execute(
         "UPDATE folder_permissions SET level = ?1 WHERE id = ?2",
         [&level, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
+/// Permission level covering a path, from the closest permitted ancestor folder
+///
+/// The path must exist; both it and the permitted folders are canonicalized so
+/// symlinks and `..` cannot escape a permitted folder.
+pub fn folder_permission_level(conn: &Connection, path: &std::path::Path) -> Result<Option<String>, String> {
+    let target = path
+        .canonicalize()
+        .map_err(|e| format!("Invalid path {}: {}", path.display(), e))?;
+
+    let mut stmt = conn
+        .prepare_cached("SELECT path, level FROM folder_permissions")
+        .map_err(|e| e.to_string())?;
+    let permissions = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let level = permissions
+        .into_iter()
+        .filter_map(|(folder, level)| {
+            let folder = PathBuf::from(folder).canonicalize().ok()?;
+            target.starts_with(&folder).then(|| (folder.components().count(), level))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, level)| level);
+
+    Ok(level)
+}
+
 // ============================================================================
 // Skill Model and Commands
 // ============================================================================
@@ -273,17 +669,117 @@ pub struct Skill {
     pub description: String,
     pub prompt: String,
     pub tools: String, // JSON array
+    /// JSON Schema for the skill's input
+    #[serde(default)]
+    pub input_schema: Option<String>,
+    /// JSON Schema for the skill's structured output
+    #[serde(default)]
+    pub output_schema: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Skills kept at most
+const MAX_SKILLS: i64 = 100;
+
+/// Skill to import; an existing skill with the same id is replaced
+#[derive(Debug, serde::Deserialize)]
+pub struct SkillInput {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub prompt: String,
+    pub tools: String, // JSON array
+    #[serde(default)]
+    pub input_schema: Option<String>,
+    #[serde(default)]
+    pub output_schema: Option<String>,
+}
+
+/// Skills created and replaced by an import
+#[derive(Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SkillImport {
+    pub created: usize,
+    pub updated: usize,
+}
+
+/// Check a skill's limits; returns its normalized input and output schemas
+fn validate_skill(
+    description: &str,
+    prompt: &str,
+    input_schema: Option<String>,
+    output_schema: Option<String>,
+) -> Result<(Option<String>, Option<String>), AppError> {
+    if description.len() > 500 {
+        return Err(AppError::invalid_input("Description must be 500 characters or less"));
+    }
+    if prompt.len() > 10240 {
+        return Err(AppError::invalid_input("Prompt must be 10KB or less"));
+    }
+    Ok((
+        skill_schema::normalize("Input schema", input_schema).map_err(AppError::invalid_input)?,
+        skill_schema::normalize("Output schema", output_schema).map_err(AppError::invalid_input)?,
+    ))
+}
+
+/// Create or replace skills in one transaction; nothing is stored when any
+/// skill is invalid or the import would exceed the skill limit
+pub fn import_skill_batch(
+    conn: &mut Connection,
+    skills: Vec<SkillInput>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<SkillImport, AppError> {
+    let now = now.to_rfc3339();
+    let tx = conn.transaction()?;
+    let mut import = SkillImport::default();
+    for skill in skills {
+        let (input_schema, output_schema) =
+            validate_skill(&skill.description, &skill.prompt, skill.input_schema, skill.output_schema)
+                .map_err(|e| AppError::invalid_input(format!("Skill '{}': {}", skill.name, e)))?;
+        let exists: bool = tx
+            .prepare_cached("SELECT EXISTS(SELECT 1 FROM skills WHERE id = ?1)")
+            .and_then(|mut stmt| stmt.query_row([&skill.id], |row| row.get(0)))?;
+        tx.prepare_cached(
+            "INSERT INTO skills (id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, description = excluded.description,
+                prompt = excluded.prompt, tools = excluded.tools, input_schema = excluded.input_schema,
+                output_schema = excluded.output_schema, updated_at = excluded.updated_at",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(rusqlite::params![
+                skill.id,
+                skill.name,
+                skill.description,
+                skill.prompt,
+                skill.tools,
+                input_schema,
+                output_schema,
+                now
+            ])
+        })?;
+        if exists {
+            import.updated += 1;
+        } else {
+            import.created += 1;
+        }
+    }
+
+    let count: i64 = tx.query_row("SELECT COUNT(*) FROM skills", [], |row| row.get(0))?;
+    if count > MAX_SKILLS {
+        return Err(AppError::invalid_input(format!("Maximum skill limit ({}) reached", MAX_SKILLS)));
+    }
+    tx.commit()?;
+    Ok(import)
+}
+
 #[tauri::command]
-pub fn list_skills(db: tauri::State<'_, DbState>) -> Result<Vec<Skill>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_skills(db: tauri::State<'_, DbState>) -> Result<Vec<Skill>, AppError> {
+    let conn = db.read()?;
 
     let mut stmt = conn
-        .prepare("SELECT id, name, description, prompt, tools, created_at, updated_at FROM skills ORDER BY name")
-        .map_err(|e| e.to_string())?;
+        .prepare_cached("SELECT id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at FROM skills ORDER BY name")?;
 
     let skills = stmt
         .query_map([], |row| {
@@ -293,24 +789,25 @@ pub fn list_skills(db: tauri::State<'_, DbState>) -> Result<Vec<Skill>, String>
                 description: row.get(2)?,
                 prompt: row.get(3)?,
                 tools: row.get(4)?,
-                created_at: row.get(5)?,
-                updated_at: row.get(6)?,
+                input_schema: row.get(5)?,
+                output_schema: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(skills)
 }
 
 #[tauri::command]
-pub fn get_skill(db: tauri::State<'_, DbState>, id: String) -> Result<Skill, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_skill(db: tauri::State<'_, DbState>, id: String) -> Result<Skill, AppError> {
+    let conn = db.read()?;
 
     let skill = conn
         .query_row(
-            "SELECT id, name, description, prompt, tools, created_at, updated_at FROM skills WHERE id = ?1",
+            "SELECT id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at FROM skills WHERE id = ?1",
             [&id],
             |row| {
                 Ok(Skill {
@@ -319,17 +816,20 @@ pub fn get_skill(db: tauri::State<'_, DbState>, id: String) -> Result<Skill, Str
                     description: row.get(2)?,
                     prompt: row.get(3)?,
                     tools: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
+                    input_schema: row.get(5)?,
+                    output_schema: row.get(6)?,
+                    created_at: row.get(7)?,
+                    updated_at: row.get(8)?,
                 })
             },
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     Ok(skill)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub fn create_skill(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -337,39 +837,35 @@ pub fn create_skill(
     description: String,
     prompt: String,
     tools: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    input_schema: Option<String>,
+    output_schema: Option<String>,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    // Validate limits
-    if description.len() > 500 {
-        return Err("Description must be 500 characters or less".to_string());
-    }
-    if prompt.len() > 10240 {
-        return Err("Prompt must be 10KB or less".to_string());
-    }
+    let (input_schema, output_schema) =
+        validate_skill(&description, &prompt, input_schema, output_schema)?;
 
     // Check skill count limit
-    let count: i32 = conn
-        .query_row("SELECT COUNT(*) FROM skills", [], |row| row.get(0))
-        .map_err(|e| e.to_string())?;
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM skills", [], |row| row.get(0))?;
 
-    if count >= 100 {
-        return Err("Maximum skill limit (100) reached".to_string());
+    if count >= MAX_SKILLS {
+        return Err(AppError::invalid_input(format!("Maximum skill limit ({}) reached", MAX_SKILLS)));
     }
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO skills (id, name, description, prompt, tools, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        [&id, &name, &description, &prompt, &tools, &now, &now],
-    )
-    .map_err(|e| e.to_string())?;
+        "INSERT INTO skills (id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        rusqlite::params![id, name, description, prompt, tools, input_schema, output_schema, now, now],
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub fn update_skill(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -377,53 +873,64 @@ pub fn update_skill(
     description: String,
     prompt: String,
     tools: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    input_schema: Option<String>,
+    output_schema: Option<String>,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    // Validate limits
-    if description.len() > 500 {
-        return Err("Description must be 500 characters or less".to_string());
-    }
-    if prompt.len() > 10240 {
-        return Err("Prompt must be 10KB or less".to_string());
-    }
+    let (input_schema, output_schema) =
+        validate_skill(&description, &prompt, input_schema, output_schema)?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "UPDATE skills SET name = ?1, description = ?2, prompt = ?3, tools = ?4, updated_at = ?5 WHERE id = ?6",
-        [&name, &description, &prompt, &tools, &now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+        "UPDATE skills SET name = ?1, description = ?2, prompt = ?3, tools = ?4,
+         input_schema = ?5, output_schema = ?6, updated_at = ?7 WHERE id = ?8",
+        rusqlite::params![
+            name,
+            description,
+            prompt,
+            tools,
+            input_schema,
+            output_schema,
+            now,
+            id
+        ],
+    )?;
 
     Ok(())
 }
 
+/// Create or replace many skills in one transaction
 #[tauri::command]
-pub fn delete_skill(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn import_skills(db: tauri::State<'_, DbState>, skills: Vec<SkillInput>) -> Result<SkillImport, AppError> {
+    let mut conn = db.conn.lock()?;
+    import_skill_batch(&mut conn, skills, chrono::Utc::now())
+}
 
-    conn.execute("DELETE FROM skills WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_skill(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+
+    conn.execute("DELETE FROM skills WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn search_skills(
-    db: tauri::State<'_, DbState>,
-    query: String,
-) -> Result<Vec<Skill>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn search_skills(db: tauri::State<'_, DbState>, query: String) -> Result<Vec<Skill>, AppError> {
+    let conn = db.read()?;
 
     let pattern = format!("%{}%", query);
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, description, prompt, tools, created_at, updated_at
+        .prepare_cached(
+            "SELECT id, name, description, prompt, tools, input_schema, output_schema, created_at, updated_at
              FROM skills WHERE name LIKE ?1 OR description LIKE ?1 ORDER BY name",
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let skills = stmt
         .query_map([&pattern], |row| {
@@ -433,13 +940,13 @@ pub fn search_skills(
                 description: row.get(2)?,
                 prompt: row.get(3)?,
                 tools: row.get(4)?,
-                created_at: row.get(5)?,
-                updated_at: row.get(6)?,
+                input_schema: row.get(5)?,
+                output_schema: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(skills)
 }
@@ -458,6 +965,9 @@ pub struct Recipe {
     pub steps: String, // JSON array
     pub variables: Option<String>, // JSON object
     pub is_builtin: bool,
+    /// Generated and not saved by the user yet
+    #[serde(default)]
+    pub is_draft: bool,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -476,15 +986,15 @@ pub struct RecipeExecution {
 }
 
 #[tauri::command]
-pub fn list_recipes(db: tauri::State<'_, DbState>) -> Result<Vec<Recipe>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_recipes(db: tauri::State<'_, DbState>) -> Result<Vec<Recipe>, AppError> {
+    let conn = db.read()?;
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, description, version, steps, variables, is_builtin, created_at, updated_at
+        .prepare_cached(
+            "SELECT id, name, description, version, steps, variables, is_builtin, created_at, updated_at, is_draft
              FROM recipes ORDER BY is_builtin, name",
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let recipes = stmt
         .query_map([], |row| {
@@ -496,24 +1006,24 @@ pub fn list_recipes(db: tauri::State<'_, DbState>) -> Result<Vec<Recipe>, String
                 steps: row.get(4)?,
                 variables: row.get(5)?,
                 is_builtin: row.get::<_, i32>(6)? != 0,
+                is_draft: row.get(9)?,
                 created_at: row.get(7)?,
                 updated_at: row.get(8)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(recipes)
 }
 
 #[tauri::command]
-pub fn get_recipe(db: tauri::State<'_, DbState>, id: String) -> Result<Recipe, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_recipe(db: tauri::State<'_, DbState>, id: String) -> Result<Recipe, AppError> {
+    let conn = db.read()?;
 
     let recipe = conn
         .query_row(
-            "SELECT id, name, description, version, steps, variables, is_builtin, created_at, updated_at
+            "SELECT id, name, description, version, steps, variables, is_builtin, created_at, updated_at, is_draft
              FROM recipes WHERE id = ?1",
             [&id],
             |row| {
@@ -525,17 +1035,18 @@ pub fn get_recipe(db: tauri::State<'_, DbState>, id: String) -> Result<Recipe, S
                     steps: row.get(4)?,
                     variables: row.get(5)?,
                     is_builtin: row.get::<_, i32>(6)? != 0,
+                    is_draft: row.get(9)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
                 })
             },
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     Ok(recipe)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_recipe(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -544,8 +1055,8 @@ pub fn create_recipe(
     version: String,
     steps: String,
     variables: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -553,13 +1064,13 @@ pub fn create_recipe(
         "INSERT INTO recipes (id, name, description, version, steps, variables, is_builtin, created_at, updated_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?8)",
         [&id, &name, &description.unwrap_or_default(), &version, &steps, &variables.unwrap_or_default(), &now, &now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn update_recipe(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -568,49 +1079,51 @@ pub fn update_recipe(
     version: String,
     steps: String,
     variables: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "UPDATE recipes SET name = ?1, description = ?2, version = ?3, steps = ?4, variables = ?5, updated_at = ?6 WHERE id = ?7",
+        // Saving a draft from the editor makes it a regular recipe
+        "UPDATE recipes SET name = ?1, description = ?2, version = ?3, steps = ?4, variables = ?5, updated_at = ?6,
+             is_draft = 0 WHERE id = ?7",
         [&name, &description.unwrap_or_default(), &version, &steps, &variables.unwrap_or_default(), &now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_recipe(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_recipe(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     // Check if builtin
-    let is_builtin: bool = conn
-        .query_row("SELECT is_builtin FROM recipes WHERE id = ?1", [&id], |row| {
-            Ok(row.get::<_, i32>(0)? != 0)
-        })
-        .map_err(|e| e.to_string())?;
+    let is_builtin: bool = conn.query_row(
+        "SELECT is_builtin FROM recipes WHERE id = ?1",
+        [&id],
+        |row| Ok(row.get::<_, i32>(0)? != 0),
+    )?;
 
     if is_builtin {
-        return Err("Cannot delete built-in recipes".to_string());
+        return Err(AppError::permission_denied("Cannot delete built-in recipes"));
     }
 
-    conn.execute("DELETE FROM recipes WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM recipes WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_recipe_execution(
     db: tauri::State<'_, DbState>,
     id: String,
     recipe_id: String,
     variables: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -618,48 +1131,52 @@ pub fn create_recipe_execution(
         "INSERT INTO recipe_executions (id, recipe_id, status, variables, started_at)
          VALUES (?1, ?2, 'running', ?3, ?4)",
         [&id, &recipe_id, &variables.unwrap_or_default(), &now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn update_recipe_execution(
     db: tauri::State<'_, DbState>,
     id: String,
     status: String,
     result: Option<String>,
     error: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "UPDATE recipe_executions SET status = ?1, result = ?2, error = ?3, completed_at = ?4 WHERE id = ?5",
         [&status, &result.unwrap_or_default(), &error.unwrap_or_default(), &now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn list_recipe_executions(
     db: tauri::State<'_, DbState>,
     recipe_id: Option<String>,
-) -> Result<Vec<RecipeExecution>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<RecipeExecution>, AppError> {
+    let conn = db.read()?;
 
     let sql = match &recipe_id {
-        Some(_) => "SELECT id, recipe_id, status, variables, result, error, started_at, completed_at
-                     FROM recipe_executions WHERE recipe_id = ?1 ORDER BY started_at DESC",
-        None => "SELECT id, recipe_id, status, variables, result, error, started_at, completed_at
-                 FROM recipe_executions ORDER BY started_at DESC",
+        Some(_) => {
+            "SELECT id, recipe_id, status, variables, result, error, started_at, completed_at
+                     FROM recipe_executions WHERE recipe_id = ?1 ORDER BY started_at DESC"
+        }
+        None => {
+            "SELECT id, recipe_id, status, variables, result, error, started_at, completed_at
+                 FROM recipe_executions ORDER BY started_at DESC"
+        }
     };
 
-    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare_cached(sql)?;
 
     let map_row = |row: &rusqlite::Row| {
         Ok(RecipeExecution {
@@ -677,10 +1194,8 @@ pub fn list_recipe_executions(
     let executions = match &recipe_id {
         Some(rid) => stmt.query_map([&rid], map_row),
         None => stmt.query_map([], map_row),
-    }
-    .map_err(|e| e.to_string())?
-    .collect::<Result<Vec<_>, _>>()
-    .map_err(|e| e.to_string())?;
+    }?
+    .collect::<Result<Vec<_>, _>>()?;
 
     Ok(executions)
 }
@@ -707,15 +1222,15 @@ pub struct SubAgent {
 }
 
 #[tauri::command]
-pub fn list_sub_agents(db: tauri::State<'_, DbState>) -> Result<Vec<SubAgent>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_sub_agents(db: tauri::State<'_, DbState>) -> Result<Vec<SubAgent>, AppError> {
+    let conn = db.read()?;
 
     let mut stmt = conn
-        .prepare(
+        .prepare_cached(
             "SELECT id, name, role, system_prompt, tools, config, status, task, result, error, created_at, completed_at
              FROM sub_agents ORDER BY created_at DESC",
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let agents = stmt
         .query_map([], |row| {
@@ -733,15 +1248,14 @@ pub fn list_sub_agents(db: tauri::State<'_, DbState>) -> Result<Vec<SubAgent>, S
                 created_at: row.get(10)?,
                 completed_at: row.get(11)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(agents)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_sub_agent(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -750,22 +1264,30 @@ pub fn create_sub_agent(
     system_prompt: Option<String>,
     tools: String,
     config: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "INSERT INTO sub_agents (id, name, role, system_prompt, tools, config, status, created_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'idle', ?7)",
-        [&id, &name, &agent_type, &system_prompt.unwrap_or_default(), &tools, &config, &now],
-    )
-    .map_err(|e| e.to_string())?;
+        [
+            &id,
+            &name,
+            &agent_type,
+            &system_prompt.unwrap_or_default(),
+            &tools,
+            &config,
+            &now,
+        ],
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 #[allow(clippy::too_many_arguments)]
 pub fn update_sub_agent(
     db: tauri::State<'_, DbState>,
@@ -778,8 +1300,8 @@ pub fn update_sub_agent(
     task: Option<String>,
     result: Option<String>,
     error: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     // Build dynamic update query
     let mut updates = Vec::new();
@@ -825,39 +1347,70 @@ pub fn update_sub_agent(
     params.push(id.clone());
     let sql = format!("UPDATE sub_agents SET {} WHERE id = ?", updates.join(", "));
 
-    conn.execute(&sql, rusqlite::params_from_iter(params.iter()))
-        .map_err(|e| e.to_string())?;
+    conn.execute(&sql, rusqlite::params_from_iter(params.iter()))?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_sub_agent(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_sub_agent(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM sub_agents WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM sub_agents WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
+/// Assign a task to a sub-agent and start running it
+///
+/// Agents that are already running are left alone; finished agents can take
+/// a new task.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn assign_sub_agent_task(
+    app: tauri::AppHandle,
     db: tauri::State<'_, DbState>,
+    executor: tauri::State<'_, crate::agent::orchestrator::SubAgentExecutor>,
     id: String,
     task: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute(
-        "UPDATE sub_agents SET status = 'running', task = ?1, error = NULL, result = NULL WHERE id = ?2 AND status = 'idle'",
+    let assigned = conn.execute(
+        "UPDATE sub_agents SET status = 'running', task = ?1, error = NULL, result = NULL, completed_at = NULL
+         WHERE id = ?2 AND status != 'running'",
         [&task, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
+    drop(conn);
+
+    if assigned == 0 {
+        return Err(AppError::invalid_input(format!("Sub-agent {} is busy or does not exist", id)));
+    }
+    executor.spawn(app, id, task);
 
     Ok(())
 }
 
+/// Stop a sub-agent's running task
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn cancel_sub_agent_task(
+    db: tauri::State<'_, DbState>,
+    executor: tauri::State<'_, crate::agent::orchestrator::SubAgentExecutor>,
+    id: String,
+) -> Result<bool, AppError> {
+    let cancelled = executor.cancel(&id);
+    if cancelled {
+        let conn = db.conn.lock()?;
+        conn.execute(
+            "UPDATE sub_agents SET status = 'failed', error = 'Cancelled', completed_at = ?1 WHERE id = ?2",
+            [&chrono::Utc::now().to_rfc3339(), &id],
+        )?;
+    }
+    Ok(cancelled)
+}
+
 // ============================================================================
 // Cron Job Model and Commands (v0.3)
 // ============================================================================
@@ -875,6 +1428,18 @@ pub struct CronJob {
     pub next_run: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Owning plugin for jobs registered through the plugin `schedule.*` API
+    #[serde(default)]
+    pub plugin_id: Option<String>,
+    /// `skip`, `queue` or `run` when the job comes due while still running
+    pub allow_overlap: String,
+    pub priority: i32,
+    pub paused: bool,
+    pub snoozed_until: Option<String>,
+    /// `active`, `paused`, `snoozed` or `disabled`
+    pub status: String,
+    /// Runs skipped while the job was snoozed
+    pub skipped_runs: i64,
 }
 
 /// Job execution model
@@ -887,42 +1452,64 @@ pub struct JobExecution {
     pub error: Option<String>,
     pub started_at: String,
     pub completed_at: Option<String>,
+    /// `sent` or `failed` for jobs that email their output
+    pub delivery_status: Option<String>,
+    pub delivery_error: Option<String>,
 }
 
 #[tauri::command]
-pub fn list_cron_jobs(db: tauri::State<'_, DbState>) -> Result<Vec<CronJob>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_cron_jobs(db: tauri::State<'_, DbState>) -> Result<Vec<CronJob>, AppError> {
+    let conn = db.read()?;
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, created_at, updated_at
+        .prepare_cached(
+            "SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, created_at, updated_at, plugin_id,
+                    allow_overlap, priority, paused, snoozed_until,
+                    (SELECT COUNT(*) FROM job_skipped_runs s WHERE s.job_id = cron_jobs.id)
              FROM cron_jobs ORDER BY created_at DESC",
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
+    let now = chrono::Utc::now();
     let jobs = stmt
         .query_map([], |row| {
+            let enabled = row.get::<_, i32>(5)? != 0;
+            let paused = row.get::<_, i32>(13)? != 0;
+            let snoozed_until: Option<String> = row.get(14)?;
             Ok(CronJob {
                 id: row.get(0)?,
                 name: row.get(1)?,
                 schedule: row.get(2)?,
                 job_type: row.get(3)?,
                 config: row.get(4)?,
-                enabled: row.get::<_, i32>(5)? != 0,
+                enabled,
                 last_run: row.get(6)?,
                 next_run: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                plugin_id: row.get(10)?,
+                allow_overlap: row.get(11)?,
+                priority: row.get(12)?,
+                status: crate::scheduler::hold::status(
+                    enabled,
+                    paused,
+                    snoozed_until.as_deref(),
+                    now,
+                )
+                .to_string(),
+                paused,
+                snoozed_until,
+                skipped_runs: row.get(15)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(jobs)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub fn create_cron_job(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -931,22 +1518,28 @@ pub fn create_cron_job(
     job_type: String,
     config: String,
     enabled: i32,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    allow_overlap: Option<String>,
+    priority: Option<i32>,
+) -> Result<(), AppError> {
+    // Accept natural-language schedules such as "every weekday at 9am"
+    let schedule = crate::scheduler::natural::to_cron(&schedule).map_err(AppError::invalid_input)?;
+    let allow_overlap = overlap_policy(allow_overlap.as_deref().unwrap_or("run"))?;
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO cron_jobs (id, name, schedule, job_type, config, enabled, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        [&id, &name, &schedule, &job_type, &config, &enabled.to_string(), &now, &now],
-    )
-    .map_err(|e| e.to_string())?;
+        "INSERT INTO cron_jobs (id, name, schedule, job_type, config, enabled, created_at, updated_at, allow_overlap, priority)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![id, name, schedule, job_type, config, enabled, now, now, allow_overlap, priority.unwrap_or(0)],
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub fn update_cron_job(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -954,8 +1547,10 @@ pub fn update_cron_job(
     schedule: Option<String>,
     config: Option<String>,
     enabled: Option<i32>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    allow_overlap: Option<String>,
+    priority: Option<i32>,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -968,7 +1563,7 @@ pub fn update_cron_job(
     }
     if let Some(s) = schedule {
         updates.push("schedule = ?");
-        params.push(s);
+        params.push(crate::scheduler::natural::to_cron(&s).map_err(AppError::invalid_input)?);
     }
     if let Some(c) = config {
         updates.push("config = ?");
@@ -978,37 +1573,57 @@ pub fn update_cron_job(
         updates.push("enabled = ?");
         params.push(e.to_string());
     }
+    if let Some(o) = allow_overlap {
+        updates.push("allow_overlap = ?");
+        params.push(overlap_policy(&o)?.to_string());
+    }
+    if let Some(p) = priority {
+        updates.push("priority = ?");
+        params.push(p.to_string());
+    }
 
     params.push(id.clone());
     let sql = format!("UPDATE cron_jobs SET {} WHERE id = ?", updates.join(", "));
 
-    conn.execute(&sql, rusqlite::params_from_iter(params.iter()))
-        .map_err(|e| e.to_string())?;
+    if conn.execute(&sql, rusqlite::params_from_iter(params.iter()))? == 0 {
+        return Err(AppError::not_found(format!("Job with ID {} not found", id)));
+    }
 
     Ok(())
 }
 
+/// Validate an `allow_overlap` value
+fn overlap_policy(value: &str) -> Result<&'static str, AppError> {
+    crate::scheduler::OverlapPolicy::from_str(value)
+        .map(|policy| policy.as_str())
+        .ok_or_else(|| AppError::invalid_input(format!("Invalid overlap policy: {} (expected skip, queue or run)", value)))
+}
+
 #[tauri::command]
-pub fn delete_cron_job(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_cron_job(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM cron_jobs WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM cron_jobs WHERE id = ?1", [&id])?;
+    conn.execute("DELETE FROM job_skipped_runs WHERE job_id = ?1", [&id])?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn run_cron_job_now(
+    app: tauri::AppHandle,
     db: tauri::State<'_, DbState>,
+    plugin_executor: tauri::State<'_, std::sync::Mutex<crate::plugins::PluginExecutor>>,
+    workflow: tauri::State<'_, std::sync::Arc<crate::workflow::WorkflowState>>,
     id: String,
-) -> Result<String, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<String, AppError> {
+    let conn = db.conn.lock()?;
 
     // Get the job details
     let mut stmt = conn
-        .prepare("SELECT id, name, schedule, job_type, config, enabled FROM cron_jobs WHERE id = ?1")
-        .map_err(|e| e.to_string())?;
+        .prepare_cached("SELECT id, name, schedule, job_type, config, enabled, plugin_id FROM cron_jobs WHERE id = ?1")?;
 
     let job_row = stmt
         .query_map([&id], |row| {
@@ -1019,14 +1634,13 @@ pub fn run_cron_job_now(
                 row.get::<_, String>(3)?,
                 row.get::<_, String>(4)?,
                 row.get::<_, i32>(5)?,
+                row.get::<_, Option<String>>(6)?,
             ))
-        })
-        .map_err(|e| e.to_string())?
+        })?
         .next()
-        .ok_or_else(|| format!("Job with ID {} not found", id))?
-        .map_err(|e| e.to_string())?;
+        .ok_or_else(|| AppError::not_found(format!("Job with ID {} not found", id)))??;
 
-    let (_job_id, name, schedule, job_type_str, config_json, _enabled) = job_row;
+    let (_job_id, name, schedule, job_type_str, config_json, _enabled, plugin_id) = job_row;
 
     // Parse job type
     let job_type = match job_type_str.as_str() {
@@ -1034,12 +1648,19 @@ pub fn run_cron_job_now(
         "recipe" => crate::scheduler::JobType::Recipe,
         "prompt" => crate::scheduler::JobType::Prompt,
         "system" => crate::scheduler::JobType::System,
-        _ => return Err(format!("Unknown job type: {}", job_type_str)),
+        "plugin" => crate::scheduler::JobType::Plugin,
+        "workflow" => crate::scheduler::JobType::Workflow,
+        "digest" => crate::scheduler::JobType::Digest,
+        _ => {
+            return Err(AppError::invalid_input(format!(
+                "Unknown job type: {}",
+                job_type_str
+            )))
+        }
     };
 
     // Parse config
-    let config: crate::scheduler::JobConfig = serde_json::from_str(&config_json)
-        .map_err(|e| format!("Failed to parse job config: {}", e))?;
+    let config: crate::scheduler::JobConfig = serde_json::from_str(&config_json)?;
 
     // Create scheduled job
     let scheduled_job = crate::scheduler::ScheduledJob {
@@ -1052,8 +1673,19 @@ pub fn run_cron_job_now(
         last_run: None,
         next_run: None,
         created_at: chrono::Utc::now(),
+        plugin_id,
+        allow_overlap: crate::scheduler::OverlapPolicy::Run,
+        priority: 0,
+        paused: false,
+        snoozed_until: None,
     };
 
+    let plugin_runner = plugin_executor.lock()?.task_runner();
+    let agent_runtime: std::sync::Arc<dyn crate::workflow::AgentRuntime> =
+        std::sync::Arc::new(crate::workflow::commands::SidecarRuntime::new(app.clone()));
+    let workflow_runner =
+        crate::workflow::WorkflowJobRunner::new(workflow.inner().clone(), Some(app.clone()));
+
     let execution_id = format!("exec-{}", uuid::Uuid::new_v4());
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -1062,18 +1694,31 @@ pub fn run_cron_job_now(
         "INSERT INTO job_executions (id, job_id, status, started_at)
          VALUES (?1, ?2, 'running', ?3)",
         [&execution_id, &id, &now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     // Update job's last_run
     conn.execute(
         "UPDATE cron_jobs SET last_run = ?1 WHERE id = ?2",
         [&now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     // Execute the job synchronously (simple approach)
-    let result = execute_job_sync(&scheduled_job, &db.db_path);
+    let log = crate::scheduler::logs::JobLog::new(db.path(), &execution_id, Some(app));
+    log.info(format!("Started {} job '{}' manually", job_type_str, name));
+    let result = execute_job_sync(
+        &scheduled_job,
+        &db.path(),
+        &plugin_runner,
+        &workflow_runner,
+        &agent_runtime,
+    );
+    match &result {
+        Ok(output) => {
+            log.output(output);
+            log.info("Finished: completed");
+        }
+        Err(error) => log.error(format!("Failed: {}", error)),
+    }
 
     let completed_at = chrono::Utc::now().to_rfc3339();
 
@@ -1083,15 +1728,13 @@ pub fn run_cron_job_now(
             conn.execute(
                 "UPDATE job_executions SET status = 'completed', result = ?1, completed_at = ?2 WHERE id = ?3",
                 [&output, &completed_at, &execution_id],
-            )
-            .map_err(|e| e.to_string())?;
+            )?;
         }
         Err(error_msg) => {
             conn.execute(
                 "UPDATE job_executions SET status = 'failed', error = ?1, completed_at = ?2 WHERE id = ?3",
                 [&error_msg, &completed_at, &execution_id],
-            )
-            .map_err(|e| e.to_string())?;
+            )?;
         }
     }
 
@@ -1102,6 +1745,9 @@ pub fn run_cron_job_now(
 fn execute_job_sync(
     job: &crate::scheduler::ScheduledJob,
     db_path: &str,
+    plugin_runner: &crate::plugins::schedule::PluginTaskRunner,
+    workflow_runner: &crate::workflow::WorkflowJobRunner,
+    agent_runtime: &std::sync::Arc<dyn crate::workflow::AgentRuntime>,
 ) -> Result<String, String> {
     use crate::scheduler::SystemTask;
 
@@ -1118,24 +1764,19 @@ fn execute_job_sync(
                     let retention_days = job.config.params
                         .get("retention_days")
                         .and_then(|v| v.as_u64())
-                        .unwrap_or(30) as i64;
+                        .unwrap_or(30) as u32;
+                    let policy = crate::retention::RetentionPolicy {
+                        entity: crate::retention::RetentionEntity::Messages,
+                        max_age_days: Some(retention_days),
+                    };
 
-                    let cutoff_date = chrono::Utc::now() - chrono::Duration::days(retention_days);
-
-                    let conn = rusqlite::Connection::open(db_path)
-                        .map_err(|e| format!("Failed to open database: {}", e))?;
-
-                    let deleted = conn.execute(
-                        "DELETE FROM messages WHERE created_at < ?",
-                        [&cutoff_date.to_rfc3339()],
-                    )
-                    .map_err(|e| format!("Failed to delete messages: {}", e))?;
+                    let conn = crate::scheduler::history::open(std::path::Path::new(db_path))?;
+                    let deleted = crate::retention::apply_policy(&conn, &policy, chrono::Utc::now())?;
 
                     Ok(format!("Deleted {} old messages", deleted))
                 }
                 SystemTask::VacuumDatabase => {
-                    let conn = rusqlite::Connection::open(db_path)
-                        .map_err(|e| format!("Failed to open database: {}", e))?;
+                    let conn = pool::open_background(std::path::Path::new(db_path))?;
 
                     conn.execute("VACUUM", [])
                         .map_err(|e| format!("Failed to vacuum database: {}", e))?;
@@ -1143,8 +1784,51 @@ fn execute_job_sync(
                     Ok("Database vacuumed successfully".to_string())
                 }
                 SystemTask::SyncSettings => {
-                    // Settings sync placeholder
-                    Ok("Settings synced".to_string())
+                    let result = tauri::async_runtime::block_on(crate::sync::engine::sync_database(db_path))?;
+                    if result.success {
+                        Ok(crate::sync::engine::summarize(&result))
+                    } else {
+                        Err(result.errors.join("; "))
+                    }
+                }
+                SystemTask::PruneJobHistory => {
+                    let keep = job.config.params.get("keep").and_then(|v| v.as_u64()).map(|v| v as u32);
+                    let conn = crate::scheduler::history::open(std::path::Path::new(db_path))?;
+                    let deleted = crate::scheduler::history::prune_with_retention(&conn, keep)?;
+
+                    Ok(format!("Deleted {} old job executions", deleted))
+                }
+                SystemTask::BackupDatabase => {
+                    let keep = job.config.params.get("keep").and_then(|v| v.as_u64()).map(|v| v as u32);
+                    let db_path = std::path::Path::new(db_path);
+                    let conn = crate::scheduler::history::open(db_path)?;
+                    let (backup, rotated) = backup::run_scheduled(&conn, db_path, keep)?;
+
+                    Ok(format!("Backed up to {} ({} old backups deleted)", backup.path, rotated))
+                }
+                SystemTask::SyncCalendars => {
+                    tauri::async_runtime::block_on(crate::integration::calendar::sync_all(std::path::Path::new(db_path)))
+                }
+                SystemTask::EmbedConversations => {
+                    crate::conversations::similar::index_database(std::path::Path::new(db_path))
+                }
+                SystemTask::IndexWorkspaces => {
+                    crate::knowledge::index::index_database(std::path::Path::new(db_path))
+                }
+                SystemTask::ApplyRetention => {
+                    let mut conn = crate::scheduler::history::open(std::path::Path::new(db_path))?;
+                    let policies = crate::retention::load(&conn)?;
+                    let impacts = crate::retention::apply(&mut conn, &policies, chrono::Utc::now())?;
+                    Ok(crate::retention::summary(&impacts))
+                }
+                SystemTask::IntegrityCheck => {
+                    let conn = crate::scheduler::history::open(std::path::Path::new(db_path))?;
+                    let report = health::run(&conn, chrono::Utc::now())?;
+                    if report.healthy() {
+                        Ok(report.summary())
+                    } else {
+                        Err(report.problems.join("; "))
+                    }
                 }
             }
         }
@@ -1160,24 +1844,53 @@ fn execute_job_sync(
             // For now, just return a placeholder
             Ok(format!("Prompt executed: {}", job.config.target))
         }
+        crate::scheduler::JobType::Plugin => {
+            let plugin_id = job.plugin_id.as_deref()
+                .ok_or_else(|| format!("Job {} has no owning plugin", job.id))?;
+            let input = job.config.params.get("input").cloned().unwrap_or(serde_json::Value::Null);
+
+            plugin_runner
+                .run(plugin_id, &job.config.target, &input)
+                .map(|output| output.map(|v| v.to_string()).unwrap_or_default())
+        }
+        crate::scheduler::JobType::Workflow => {
+            let input = job.config.params.get("input").cloned().unwrap_or(serde_json::Value::Null);
+            let result = tauri::async_runtime::block_on(workflow_runner.run(&job.config.target, input))?;
+            let output = serde_json::json!({
+                "workflow_execution_id": result.execution_id,
+                "output": result.output,
+            });
+
+            if result.success {
+                Ok(output.to_string())
+            } else {
+                Err(result.error.unwrap_or_else(|| "Workflow execution failed".to_string()))
+            }
+        }
+        crate::scheduler::JobType::Digest => tauri::async_runtime::block_on(crate::scheduler::digest::run(
+            std::path::Path::new(db_path),
+            &job.config.params,
+            Some(agent_runtime),
+        )),
     }
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn list_job_executions(
     db: tauri::State<'_, DbState>,
     job_id: Option<String>,
-) -> Result<Vec<JobExecution>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<JobExecution>, AppError> {
+    let conn = db.read()?;
 
     let sql = match &job_id {
-        Some(_) => "SELECT id, job_id, status, result, error, started_at, completed_at
+        Some(_) => "SELECT id, job_id, status, result, error, started_at, completed_at, delivery_status, delivery_error
                      FROM job_executions WHERE job_id = ?1 ORDER BY started_at DESC LIMIT 100",
-        None => "SELECT id, job_id, status, result, error, started_at, completed_at
+        None => "SELECT id, job_id, status, result, error, started_at, completed_at, delivery_status, delivery_error
                  FROM job_executions ORDER BY started_at DESC LIMIT 100",
     };
 
-    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare_cached(sql)?;
 
     let map_row = |row: &rusqlite::Row| {
         Ok(JobExecution {
@@ -1188,16 +1901,16 @@ pub fn list_job_executions(
             error: row.get(4)?,
             started_at: row.get(5)?,
             completed_at: row.get(6)?,
+            delivery_status: row.get(7)?,
+            delivery_error: row.get(8)?,
         })
     };
 
     let executions = match &job_id {
         Some(jid) => stmt.query_map([&jid], map_row),
         None => stmt.query_map([], map_row),
-    }
-    .map_err(|e| e.to_string())?
-    .collect::<Result<Vec<_>, _>>()
-    .map_err(|e| e.to_string())?;
+    }?
+    .collect::<Result<Vec<_>, _>>()?;
 
     Ok(executions)
 }
@@ -1214,53 +1927,21 @@ pub fn load_scheduled_jobs(
         .ok_or("Database state not found")?;
 
     let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+    enabled_scheduled_jobs(&conn)
+}
 
+/// Enabled jobs as the JobScheduler runs them
+pub(crate) fn enabled_scheduled_jobs(conn: &Connection) -> Result<Vec<crate::scheduler::ScheduledJob>, String> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, created_at
+        .prepare_cached(
+            "SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, created_at, plugin_id,
+                    allow_overlap, priority, paused, snoozed_until
              FROM cron_jobs WHERE enabled = 1"
         )
         .map_err(|e| e.to_string())?;
 
     let jobs = stmt
-        .query_map([], |row| {
-            use crate::scheduler::{JobType, JobConfig, ScheduledJob};
-            use chrono::DateTime;
-
-            let job_type_str: String = row.get(3)?;
-            let config_json: String = row.get(4)?;
-            let last_run: Option<String> = row.get(6)?;
-            let next_run: Option<String> = row.get(7)?;
-            let created_at_str: String = row.get(8)?;
-
-            let job_type = match job_type_str.as_str() {
-                "skill" => JobType::Skill,
-                "recipe" => JobType::Recipe,
-                "prompt" => JobType::Prompt,
-                "system" => JobType::System,
-                _ => JobType::System,
-            };
-
-            let config: JobConfig = serde_json::from_str(&config_json)
-                .unwrap_or_else(|_| JobConfig {
-                    target: "".to_string(),
-                    params: std::collections::HashMap::new(),
-                });
-
-            Ok(ScheduledJob {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                schedule: row.get(2)?,
-                job_type,
-                config,
-                enabled: true,
-                last_run: last_run.and_then(|s| DateTime::parse_from_rfc3339(&s).ok()).map(|dt| dt.with_timezone(&chrono::Utc)),
-                next_run: next_run.and_then(|s| DateTime::parse_from_rfc3339(&s).ok()).map(|dt| dt.with_timezone(&chrono::Utc)),
-                created_at: DateTime::parse_from_rfc3339(&created_at_str)
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .unwrap_or_else(|_| chrono::Utc::now()),
-            })
-        })
+        .query_map([], scheduled_job_from_row)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -1268,6 +1949,57 @@ pub fn load_scheduled_jobs(
     Ok(jobs)
 }
 
+/// Map a `cron_jobs` row selected as `id, name, schedule, job_type, config,
+/// enabled, last_run, next_run, created_at, plugin_id, allow_overlap, priority,
+/// paused, snoozed_until`
+pub(crate) fn scheduled_job_from_row(row: &rusqlite::Row) -> rusqlite::Result<crate::scheduler::ScheduledJob> {
+    use crate::scheduler::{JobType, JobConfig, OverlapPolicy, ScheduledJob};
+    use chrono::DateTime;
+
+    let job_type_str: String = row.get(3)?;
+    let config_json: String = row.get(4)?;
+    let last_run: Option<String> = row.get(6)?;
+    let next_run: Option<String> = row.get(7)?;
+    let created_at_str: String = row.get(8)?;
+    let snoozed_until: Option<String> = row.get(13)?;
+
+    let job_type = match job_type_str.as_str() {
+        "skill" => JobType::Skill,
+        "recipe" => JobType::Recipe,
+        "prompt" => JobType::Prompt,
+        "system" => JobType::System,
+        "plugin" => JobType::Plugin,
+        "workflow" => JobType::Workflow,
+        "digest" => JobType::Digest,
+        _ => JobType::System,
+    };
+
+    let config: JobConfig = serde_json::from_str(&config_json)
+        .unwrap_or_else(|_| JobConfig {
+            target: "".to_string(),
+            params: std::collections::HashMap::new(),
+        });
+
+    Ok(ScheduledJob {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        schedule: row.get(2)?,
+        job_type,
+        config,
+        enabled: row.get::<_, i32>(5)? != 0,
+        last_run: last_run.and_then(|s| DateTime::parse_from_rfc3339(&s).ok()).map(|dt| dt.with_timezone(&chrono::Utc)),
+        next_run: next_run.and_then(|s| DateTime::parse_from_rfc3339(&s).ok()).map(|dt| dt.with_timezone(&chrono::Utc)),
+        created_at: DateTime::parse_from_rfc3339(&created_at_str)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now()),
+        plugin_id: row.get(9)?,
+        allow_overlap: OverlapPolicy::from_str(&row.get::<_, String>(10)?).unwrap_or_default(),
+        priority: row.get(11)?,
+        paused: row.get::<_, i32>(12)? != 0,
+        snoozed_until: snoozed_until.and_then(|s| DateTime::parse_from_rfc3339(&s).ok()).map(|dt| dt.with_timezone(&chrono::Utc)),
+    })
+}
+
 // ============================================================================
 // Plugin Model and Commands (v0.4)
 //===========================================================================
@@ -1286,15 +2018,14 @@ pub struct Plugin {
 }
 
 #[tauri::command]
-pub fn list_plugins(db: tauri::State<'_, DbState>) -> Result<Vec<Plugin>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_plugins(db: tauri::State<'_, DbState>) -> Result<Vec<Plugin>, AppError> {
+    let conn = db.read()?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, version, manifest, permissions, enabled, installed_at, updated_at
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, version, manifest, permissions, enabled, installed_at, updated_at
              FROM plugins ORDER BY name",
-        )
-        .map_err(|e| e.to_string())?;
+    )?;
 
     let plugins = stmt
         .query_map([], |row| {
@@ -1308,42 +2039,40 @@ pub fn list_plugins(db: tauri::State<'_, DbState>) -> Result<Vec<Plugin>, String
                 installed_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(plugins)
 }
 
 #[tauri::command]
-pub fn get_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<Plugin, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<Plugin, AppError> {
+    let conn = db.read()?;
 
-    let plugin = conn
-        .query_row(
-            "SELECT id, name, version, manifest, permissions, enabled, installed_at, updated_at
+    let plugin = conn.query_row(
+        "SELECT id, name, version, manifest, permissions, enabled, installed_at, updated_at
              FROM plugins WHERE id = ?1",
-            [&id],
-            |row| {
-                Ok(Plugin {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    version: row.get(2)?,
-                    manifest: row.get(3)?,
-                    permissions: row.get(4)?,
-                    enabled: row.get::<_, i32>(5)? != 0,
-                    installed_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            },
-        )
-        .map_err(|e| e.to_string())?;
+        [&id],
+        |row| {
+            Ok(Plugin {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                version: row.get(2)?,
+                manifest: row.get(3)?,
+                permissions: row.get(4)?,
+                enabled: row.get::<_, i32>(5)? != 0,
+                installed_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        },
+    )?;
 
     Ok(plugin)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn install_plugin(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -1351,8 +2080,8 @@ pub fn install_plugin(
     version: String,
     manifest: String,
     permissions: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -1361,48 +2090,47 @@ pub fn install_plugin(
          VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7)
          ON CONFLICT(name) DO UPDATE SET version = ?3, manifest = ?4, permissions = ?5, updated_at = ?7",
         [&id, &name, &version, &manifest, &permissions, &now, &now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn uninstall_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn uninstall_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM plugins WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM plugins WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn enable_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn enable_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "UPDATE plugins SET enabled = 1, updated_at = ?1 WHERE id = ?2",
         [&now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn disable_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn disable_plugin(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "UPDATE plugins SET enabled = 0, updated_at = ?1 WHERE id = ?2",
         [&now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
@@ -1425,15 +2153,14 @@ pub struct Template {
 }
 
 #[tauri::command]
-pub fn list_templates(db: tauri::State<'_, DbState>) -> Result<Vec<Template>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_templates(db: tauri::State<'_, DbState>) -> Result<Vec<Template>, AppError> {
+    let conn = db.read()?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, category, content, visibility, version, created_at, updated_at
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, category, content, visibility, version, created_at, updated_at
              FROM templates ORDER BY category, name",
-        )
-        .map_err(|e| e.to_string())?;
+    )?;
 
     let templates = stmt
         .query_map([], |row| {
@@ -1447,42 +2174,40 @@ pub fn list_templates(db: tauri::State<'_, DbState>) -> Result<Vec<Template>, St
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(templates)
 }
 
 #[tauri::command]
-pub fn get_template(db: tauri::State<'_, DbState>, id: String) -> Result<Template, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_template(db: tauri::State<'_, DbState>, id: String) -> Result<Template, AppError> {
+    let conn = db.read()?;
 
-    let template = conn
-        .query_row(
-            "SELECT id, name, category, content, visibility, version, created_at, updated_at
+    let template = conn.query_row(
+        "SELECT id, name, category, content, visibility, version, created_at, updated_at
              FROM templates WHERE id = ?1",
-            [&id],
-            |row| {
-                Ok(Template {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    category: row.get(2)?,
-                    content: row.get(3)?,
-                    visibility: row.get(4)?,
-                    version: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            },
-        )
-        .map_err(|e| e.to_string())?;
+        [&id],
+        |row| {
+            Ok(Template {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                category: row.get(2)?,
+                content: row.get(3)?,
+                visibility: row.get(4)?,
+                version: row.get(5)?,
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        },
+    )?;
 
     Ok(template)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_template(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -1490,8 +2215,8 @@ pub fn create_template(
     category: String,
     content: String,
     visibility: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -1499,13 +2224,13 @@ pub fn create_template(
         "INSERT INTO templates (id, name, category, content, visibility, version, created_at, updated_at)
          VALUES (?1, ?2, ?3, ?4, ?5, '1.0.0', ?6, ?7)",
         [&id, &name, &category, &content, &visibility, &now, &now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn update_template(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -1513,45 +2238,43 @@ pub fn update_template(
     category: String,
     content: String,
     visibility: String,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "UPDATE templates SET name = ?1, category = ?2, content = ?3, visibility = ?4, updated_at = ?5 WHERE id = ?6",
         [&name, &category, &content, &visibility, &now, &id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_template(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_template(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM templates WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM templates WHERE id = ?1", [&id])?;
 
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn search_templates(
     db: tauri::State<'_, DbState>,
     query: String,
-) -> Result<Vec<Template>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<Template>, AppError> {
+    let conn = db.read()?;
 
     let pattern = format!("%{}%", query);
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, category, content, visibility, version, created_at, updated_at
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, category, content, visibility, version, created_at, updated_at
              FROM templates WHERE name LIKE ?1 OR content LIKE ?1 ORDER BY name",
-        )
-        .map_err(|e| e.to_string())?;
+    )?;
 
     let templates = stmt
         .query_map([&pattern], |row| {
@@ -1565,10 +2288,8 @@ pub fn search_templates(
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(templates)
 }
@@ -1584,19 +2305,28 @@ pub struct VoiceSettings {
     pub enabled: bool,
     pub stt_model: String,
     pub tts_voice: String,
+    pub tts_provider: String,
     pub language: String,
     pub wake_word: Option<String>,
     pub vad_sensitivity: f32,
+    /// Stop speech playback when the user starts talking
+    pub barge_in_enabled: bool,
+    /// Speech threshold for barge-in (0.0 = very sensitive, 1.0 = less sensitive)
+    pub barge_in_sensitivity: f32,
+    /// Subtract speech playback from the microphone during barge-in detection
+    pub echo_suppression: bool,
     pub updated_at: String,
 }
 
 #[tauri::command]
-pub fn get_voice_settings(db: tauri::State<'_, DbState>) -> Result<Option<VoiceSettings>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_voice_settings(db: tauri::State<'_, DbState>) -> Result<Option<VoiceSettings>, AppError> {
+    let conn = db.read()?;
 
     let settings = conn
         .query_row(
-            "SELECT id, enabled, stt_model, tts_voice, language, wake_word, vad_sensitivity, updated_at
+            "SELECT id, enabled, stt_model, tts_voice, tts_provider, language, wake_word, vad_sensitivity, updated_at,
+                    barge_in_enabled, barge_in_sensitivity, echo_suppression
              FROM voice_settings LIMIT 1",
             [],
             |row| {
@@ -1605,10 +2335,14 @@ pub fn get_voice_settings(db: tauri::State<'_, DbState>) -> Result<Option<VoiceS
                     enabled: row.get::<_, i32>(1)? != 0,
                     stt_model: row.get(2)?,
                     tts_voice: row.get(3)?,
-                    language: row.get(4)?,
-                    wake_word: row.get(5)?,
-                    vad_sensitivity: row.get(6)?,
-                    updated_at: row.get(7)?,
+                    tts_provider: row.get(4)?,
+                    language: row.get(5)?,
+                    wake_word: row.get(6)?,
+                    vad_sensitivity: row.get(7)?,
+                    barge_in_enabled: row.get(9)?,
+                    barge_in_sensitivity: row.get(10)?,
+                    echo_suppression: row.get(11)?,
+                    updated_at: row.get(8)?,
                 })
             },
         )
@@ -1618,6 +2352,7 @@ pub fn get_voice_settings(db: tauri::State<'_, DbState>) -> Result<Option<VoiceS
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 #[allow(clippy::too_many_arguments)]
 pub fn update_voice_settings(
     db: tauri::State<'_, DbState>,
@@ -1625,22 +2360,46 @@ pub fn update_voice_settings(
     enabled: bool,
     stt_model: String,
     tts_voice: String,
+    tts_provider: Option<String>,
     language: String,
     wake_word: Option<String>,
     vad_sensitivity: f32,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    barge_in_enabled: Option<bool>,
+    barge_in_sensitivity: Option<f32>,
+    echo_suppression: Option<bool>,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
-    let enabled_str = if enabled { "1".to_string() } else { "0".to_string() };
+    let enabled_str = if enabled {
+        "1".to_string()
+    } else {
+        "0".to_string()
+    };
+    let tts_provider = tts_provider.unwrap_or_else(|| "local".to_string());
+    let barge_in = crate::voice::barge_in::BargeInSettings::default();
 
     conn.execute(
-        "INSERT INTO voice_settings (id, enabled, stt_model, tts_voice, language, wake_word, vad_sensitivity, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-         ON CONFLICT(id) DO UPDATE SET enabled = ?2, stt_model = ?3, tts_voice = ?4, language = ?5, wake_word = ?6, vad_sensitivity = ?7, updated_at = ?8",
-        [&id, &enabled_str, &stt_model, &tts_voice, &language, &wake_word.unwrap_or_default(), &vad_sensitivity.to_string(), &now],
-    )
-    .map_err(|e| e.to_string())?;
+        "INSERT INTO voice_settings (id, enabled, stt_model, tts_voice, language, wake_word, vad_sensitivity, updated_at, tts_provider,
+                                     barge_in_enabled, barge_in_sensitivity, echo_suppression)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+         ON CONFLICT(id) DO UPDATE SET enabled = ?2, stt_model = ?3, tts_voice = ?4, language = ?5, wake_word = ?6, vad_sensitivity = ?7, updated_at = ?8, tts_provider = ?9,
+                                       barge_in_enabled = ?10, barge_in_sensitivity = ?11, echo_suppression = ?12",
+        rusqlite::params![
+            id,
+            enabled_str,
+            stt_model,
+            tts_voice,
+            language,
+            wake_word.unwrap_or_default(),
+            vad_sensitivity.to_string(),
+            now,
+            tts_provider,
+            barge_in_enabled.unwrap_or(barge_in.enabled),
+            barge_in_sensitivity.unwrap_or(barge_in.sensitivity).clamp(0.0, 1.0) as f64,
+            echo_suppression.unwrap_or(barge_in.echo_suppression),
+        ],
+    )?;
 
     Ok(())
 }
@@ -1661,15 +2420,14 @@ pub struct CloudStorage {
 }
 
 #[tauri::command]
-pub fn list_cloud_storages(db: tauri::State<'_, DbState>) -> Result<Vec<CloudStorage>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_cloud_storages(db: tauri::State<'_, DbState>) -> Result<Vec<CloudStorage>, AppError> {
+    let conn = db.read()?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, provider, bucket, region, created_at
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, provider, bucket, region, created_at
              FROM cloud_storages ORDER BY name",
-        )
-        .map_err(|e| e.to_string())?;
+    )?;
 
     let storages = stmt
         .query_map([], |row| {
@@ -1681,15 +2439,14 @@ pub fn list_cloud_storages(db: tauri::State<'_, DbState>) -> Result<Vec<CloudSto
                 region: row.get(4)?,
                 created_at: row.get(5)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(storages)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_cloud_storage(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -1697,27 +2454,33 @@ pub fn create_cloud_storage(
     provider: String,
     bucket: String,
     region: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "INSERT INTO cloud_storages (id, name, provider, bucket, region, created_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        [&id, &name, &provider, &bucket, &region.unwrap_or_default(), &now],
-    )
-    .map_err(|e| e.to_string())?;
+        [
+            &id,
+            &name,
+            &provider,
+            &bucket,
+            &region.unwrap_or_default(),
+            &now,
+        ],
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_cloud_storage(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_cloud_storage(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM cloud_storages WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM cloud_storages WHERE id = ?1", [&id])?;
 
     Ok(())
 }
@@ -1738,15 +2501,16 @@ pub struct GitRepository {
 }
 
 #[tauri::command]
-pub fn list_git_repositories(db: tauri::State<'_, DbState>) -> Result<Vec<GitRepository>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_git_repositories(
+    db: tauri::State<'_, DbState>,
+) -> Result<Vec<GitRepository>, AppError> {
+    let conn = db.read()?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, name, path, user_name, user_email, created_at
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, path, user_name, user_email, created_at
              FROM git_repositories ORDER BY name",
-        )
-        .map_err(|e| e.to_string())?;
+    )?;
 
     let repos = stmt
         .query_map([], |row| {
@@ -1758,15 +2522,14 @@ pub fn list_git_repositories(db: tauri::State<'_, DbState>) -> Result<Vec<GitRep
                 user_email: row.get(4)?,
                 created_at: row.get(5)?,
             })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(repos)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn create_git_repository(
     db: tauri::State<'_, DbState>,
     id: String,
@@ -1774,27 +2537,33 @@ pub fn create_git_repository(
     path: String,
     user_name: Option<String>,
     user_email: Option<String>,
-) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
         "INSERT INTO git_repositories (id, name, path, user_name, user_email, created_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        [&id, &name, &path, &user_name.unwrap_or_default(), &user_email.unwrap_or_default(), &now],
-    )
-    .map_err(|e| e.to_string())?;
+        [
+            &id,
+            &name,
+            &path,
+            &user_name.unwrap_or_default(),
+            &user_email.unwrap_or_default(),
+            &now,
+        ],
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_git_repository(db: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_git_repository(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
 
-    conn.execute("DELETE FROM git_repositories WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM git_repositories WHERE id = ?1", [&id])?;
 
     Ok(())
 }
@@ -1823,7 +2592,7 @@ impl DB for DbState {
         let conn = self.conn.lock().map_err(|e| e.to_string())?;
 
         let mut stmt = conn
-            .prepare(
+            .prepare_cached(
                 "SELECT id, name, category, content, visibility, version, created_at, updated_at
                  FROM templates ORDER BY category, name",
             )
@@ -1913,7 +2682,7 @@ impl DB for DbState {
         }
 
         let mut stmt = conn
-            .prepare(
+            .prepare_cached(
                 "SELECT id, template_id, version, content, notes, created_at
                  FROM template_versions
                  WHERE template_id = ?1
@@ -2051,7 +2820,7 @@ impl DB for DbState {
         }
 
         let mut stmt = conn
-            .prepare(
+            .prepare_cached(
                 "SELECT t.id, t.name, t.category, t.content, t.visibility, t.version, t.created_at, t.updated_at
                  FROM templates t
                  INNER JOIN template_shares s ON t.id = s.template_id
@@ -2093,3 +2862,116 @@ impl DB for DbState {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute("INSERT INTO conversations (id, title, updated_at) VALUES ('c1', 'Plain', '2020-01-01')", [])
+            .unwrap();
+        conn.execute("INSERT INTO conversations (id, title, is_sensitive) VALUES ('c2', 'Secret', 1)", []).unwrap();
+        conn
+    }
+
+    fn message(id: &str, conversation_id: &str, content: &str) -> NewMessage {
+        NewMessage {
+            id: id.to_string(),
+            conversation_id: conversation_id.to_string(),
+            role: "user".to_string(),
+            content: content.to_string(),
+            metadata: None,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_save_batch() {
+        let mut conn = setup();
+        let key = [7u8; 32];
+        let keys = HashMap::from([("c1".to_string(), None), ("c2".to_string(), Some(key))]);
+        let mut messages: Vec<_> = (0..50).map(|i| message(&format!("m{}", i), "c1", &format!("Message {}", i))).collect();
+        messages.push(message("s1", "c2", "Hidden"));
+
+        assert_eq!(save_batch(&mut conn, &messages, &keys, chrono::Utc::now()).unwrap(), 51);
+        let order: Vec<String> = conn
+            .prepare("SELECT id FROM messages WHERE conversation_id = 'c1' ORDER BY created_at")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(order, (0..50).map(|i| format!("m{}", i)).collect::<Vec<_>>());
+        let touched: String = conn.query_row("SELECT updated_at FROM conversations WHERE id = 'c1'", [], |row| row.get(0)).unwrap();
+        assert!(touched.as_str() > "2020-01-01");
+
+        let (content, encrypted): (String, bool) = conn
+            .query_row("SELECT content, is_encrypted FROM messages WHERE id = 's1'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert!(encrypted);
+        assert_eq!(decrypt_field(&content, &key).unwrap(), "Hidden");
+
+        // A duplicate id rolls back the whole batch
+        let retry = [message("n1", "c1", "New"), message("m0", "c1", "Duplicate")];
+        assert!(save_batch(&mut conn, &retry, &keys, chrono::Utc::now()).is_err());
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 51);
+    }
+
+    #[test]
+    fn test_renaming_keeps_conversation_sensitive() {
+        let conn = setup();
+        let key = [7u8; 32];
+        insert_message(&conn, Some(&key), &message("s1", "c2", "Hidden"), "2026-01-01T00:00:00Z").unwrap();
+
+        upsert_conversation(&conn, "c2", "Renamed", None).unwrap();
+        assert!(is_conversation_sensitive(&conn, "c2").unwrap());
+
+        let mut session = SensitiveSession::with_key(key);
+        session.unlock("c2");
+        let session = Mutex::new(session);
+        let credentials = Mutex::new(CredentialManager::new("db_test".to_string()));
+        let key = conversation_key(&conn, &session, &credentials, "c2").unwrap();
+        let messages = read_messages(&conn, key.as_ref(), "c2").unwrap();
+        assert_eq!(messages[0].content, "Hidden");
+
+        upsert_conversation(&conn, "c3", "New", None).unwrap();
+        assert!(!is_conversation_sensitive(&conn, "c3").unwrap());
+    }
+
+    #[test]
+    fn test_import_skill_batch() {
+        let mut conn = setup();
+        let skill = |id: &str, description: &str| SkillInput {
+            id: id.to_string(),
+            name: format!("Skill {}", id),
+            description: description.to_string(),
+            prompt: "Do it".to_string(),
+            tools: "[]".to_string(),
+            input_schema: None,
+            output_schema: None,
+        };
+        let now = chrono::Utc::now();
+
+        let first = import_skill_batch(&mut conn, vec![skill("a", "First"), skill("b", "Second")], now).unwrap();
+        assert_eq!(first, SkillImport { created: 2, updated: 0 });
+        let second = import_skill_batch(&mut conn, vec![skill("a", "Changed"), skill("c", "Third")], now).unwrap();
+        assert_eq!(second, SkillImport { created: 1, updated: 1 });
+        let description: String = conn.query_row("SELECT description FROM skills WHERE id = 'a'", [], |row| row.get(0)).unwrap();
+        assert_eq!(description, "Changed");
+
+        // One invalid skill or going over the limit stores nothing
+        let invalid = import_skill_batch(&mut conn, vec![skill("d", "Fine"), skill("e", &"x".repeat(501))], now);
+        let invalid = invalid.unwrap_err();
+        assert_eq!(invalid.kind(), crate::error::ErrorKind::InvalidInput);
+        assert!(invalid.to_string().contains("Skill e"));
+        let many = (0..MAX_SKILLS).map(|i| skill(&format!("n{}", i), "Bulk")).collect();
+        let over = import_skill_batch(&mut conn, many, now).unwrap_err();
+        assert_eq!(over.kind(), crate::error::ErrorKind::InvalidInput);
+        assert!(over.to_string().contains("limit"));
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM skills", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 3);
+    }
+}
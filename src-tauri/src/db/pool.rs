@@ -0,0 +1,205 @@
+//! Connection pool and lock handling
+//!
+//! The database runs in WAL mode, so readers neither wait for the writer
+//! nor block it. `DbState::conn` stays the single write connection;
+//! [`ReadPool`] keeps a few read-only connections to the same file that
+//! commands which only query take through `DbState::read`, so listing
+//! conversations or loading messages does not stall behind a long write.
+//!
+//! Every connection of the app waits up to [`BUSY_TIMEOUT`] for a lock held
+//! by another connection - a background task, or the MCP server in its own
+//! process - instead of failing with `SQLITE_BUSY` at once - and caches up
+//! to [`STATEMENT_CACHE_CAPACITY`] prepared statements for `prepare_cached`,
+//! so commands run on every keystroke or message skip re-parsing their SQL.
+
+use rusqlite::{Connection, OpenFlags};
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// How long a statement waits for a lock before failing
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Prepared statements kept per connection
+pub const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Read-only connections kept open
+pub const READ_CONNECTIONS: usize = 4;
+
+/// How long `ReadPool::get` waits for a free connection
+const CHECKOUT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Settings every connection to the app database gets
+pub fn configure(conn: &Connection) -> rusqlite::Result<()> {
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    conn.busy_timeout(BUSY_TIMEOUT)
+}
+
+/// Switch the database file to WAL mode; in-memory databases keep theirs
+pub fn enable_wal(conn: &Connection) -> rusqlite::Result<()> {
+    let mode: String = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+    if mode != "wal" && mode != "memory" {
+        tracing::warn!("Database stayed in {} journal mode", mode);
+    }
+    // Safe with WAL: a crash may lose the last commits but never corrupts
+    conn.pragma_update(None, "synchronous", "NORMAL")
+}
+
+/// Open the app database for a background task
+pub fn open_background(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+    configure(&conn).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn open_reader(path: &Path) -> Result<Connection, String> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let conn = Connection::open_with_flags(path, flags).map_err(|e| format!("Failed to open read connection: {}", e))?;
+    configure(&conn).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+struct Idle {
+    connections: Vec<Connection>,
+    /// Bumped when the pool moves to another file; connections of an
+    /// older generation are closed when returned
+    generation: u64,
+}
+
+/// Read-only connections to the database file
+pub struct ReadPool {
+    idle: Mutex<Idle>,
+    returned: Condvar,
+}
+
+/// Connection taken from a [`ReadPool`], returned when dropped
+pub struct PooledConnection<'a> {
+    pool: &'a ReadPool,
+    conn: Option<Connection>,
+    generation: u64,
+}
+
+impl ReadPool {
+    /// Open `size` read connections to the database at `path`
+    pub fn open(path: &Path, size: usize) -> Result<Self, String> {
+        let connections = (0..size.max(1)).map(|_| open_reader(path)).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            idle: Mutex::new(Idle { connections, generation: 0 }),
+            returned: Condvar::new(),
+        })
+    }
+
+    /// Take a connection, waiting while all are in use
+    pub fn get(&self) -> Result<PooledConnection<'_>, String> {
+        let idle = self.idle.lock().map_err(|e| e.to_string())?;
+        let (mut idle, timeout) = self
+            .returned
+            .wait_timeout_while(idle, CHECKOUT_TIMEOUT, |idle| idle.connections.is_empty())
+            .map_err(|e| e.to_string())?;
+        if timeout.timed_out() {
+            return Err("Timed out waiting for a database connection".to_string());
+        }
+        let conn = idle.connections.pop().ok_or("No database connection available")?;
+        Ok(PooledConnection { pool: self, conn: Some(conn), generation: idle.generation })
+    }
+
+    /// Point the pool at another database file, as when switching profiles;
+    /// connections still in use are closed when returned
+    pub fn reopen(&self, path: &Path) -> Result<(), String> {
+        let size = {
+            let idle = self.idle.lock().map_err(|e| e.to_string())?;
+            idle.connections.len().max(READ_CONNECTIONS)
+        };
+        let connections = (0..size).map(|_| open_reader(path)).collect::<Result<Vec<_>, _>>()?;
+
+        let mut idle = self.idle.lock().map_err(|e| e.to_string())?;
+        idle.connections = connections;
+        idle.generation += 1;
+        self.returned.notify_all();
+        Ok(())
+    }
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection is present until dropped")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        let Ok(mut idle) = self.pool.idle.lock() else {
+            return;
+        };
+        if idle.generation == self.generation {
+            idle.connections.push(conn);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn setup(dir: &Path) -> (Connection, PathBuf) {
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join("assistant.db");
+        let conn = super::super::open_database(&path).unwrap();
+        (conn, path)
+    }
+
+    #[test]
+    fn test_reads_do_not_wait_for_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let (writer, path) = setup(dir.path());
+        let journal: String = writer.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal, "wal");
+        writer.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Before')", []).unwrap();
+
+        let pool = ReadPool::open(&path, 2).unwrap();
+        writer.execute_batch("BEGIN IMMEDIATE; UPDATE conversations SET title = 'After' WHERE id = 'c1';").unwrap();
+
+        // The open write transaction neither blocks nor shows through
+        let reader = pool.get().unwrap();
+        let title: String = reader.query_row("SELECT title FROM conversations WHERE id = 'c1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(title, "Before");
+        assert!(reader.execute("DELETE FROM conversations", []).is_err());
+
+        writer.execute_batch("COMMIT").unwrap();
+        let title: String = reader.query_row("SELECT title FROM conversations WHERE id = 'c1'", [], |row| row.get(0)).unwrap();
+        assert_eq!(title, "After");
+    }
+
+    #[test]
+    fn test_checkout_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, path) = setup(dir.path());
+        let (other, other_path) = setup(&dir.path().join("other"));
+        other.execute("INSERT INTO conversations (id, title) VALUES ('c2', 'Other')", []).unwrap();
+
+        let pool = ReadPool::open(&path, 1).unwrap();
+        let held = pool.get().unwrap();
+        std::thread::scope(|scope| {
+            let waiter = scope.spawn(|| pool.get().map(|_| ()));
+            std::thread::sleep(Duration::from_millis(50));
+            drop(held);
+            assert!(waiter.join().unwrap().is_ok());
+        });
+
+        let old = pool.get().unwrap();
+        pool.reopen(&other_path).unwrap();
+        drop(old);
+        // The connection to the old file is closed, not returned
+        assert_eq!(pool.idle.lock().unwrap().connections.len(), READ_CONNECTIONS);
+        let count: i64 = pool.get().unwrap().query_row("SELECT COUNT(*) FROM conversations", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -1,71 +1,429 @@
 // Database Schema and Migrations
 
+use super::migrations::{self, Migration};
 use rusqlite::Connection;
 use rusqlite::Result;
 
-const _SCHEMA_VERSION: i32 = 10;
-
+/// Every schema migration, oldest first. Add new migrations to the end;
+/// give them `down` SQL unless they cannot be reverted.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "core_tables", up: migrate_v1, down: None },
+    Migration { version: 2, name: "skills_and_recipes", up: migrate_v2, down: None },
+    Migration { version: 3, name: "scheduler_and_routing", up: migrate_v3, down: None },
+    Migration { version: 4, name: "memory_plugins_and_templates", up: migrate_v4, down: None },
+    Migration { version: 5, name: "encrypted_credentials", up: migrate_v5, down: None },
+    Migration { version: 6, name: "template_versions_and_shares", up: migrate_v6, down: None },
+    Migration { version: 7, name: "voice_patterns_and_conversations", up: migrate_v7, down: None },
+    Migration { version: 8, name: "workflows", up: migrate_v8, down: None },
+    Migration { version: 9, name: "voice_profiles", up: migrate_v9, down: None },
+    Migration { version: 10, name: "cloud_sync", up: migrate_v10, down: None },
+    Migration { version: 11, name: "sensitive_conversations", up: migrate_v11, down: None },
+    Migration { version: 12, name: "plugin_storage", up: migrate_v12, down: None },
+    Migration { version: 13, name: "plugin_cron_jobs", up: migrate_v13, down: None },
+    Migration { version: 14, name: "tts_provider", up: migrate_v14, down: None },
+    Migration { version: 15, name: "sql_audit_log", up: migrate_v15, down: None },
+    Migration { version: 16, name: "orchestration_runs", up: migrate_v16, down: None },
+    Migration { version: 17, name: "sync_journal", up: migrate_v17, down: None },
+    Migration { version: 18, name: "sync_conflicts", up: migrate_v18, down: None },
+    Migration { version: 19, name: "workflow_cron_jobs", up: migrate_v19, down: None },
+    Migration { version: 20, name: "tool_policies", up: migrate_v20, down: None },
+    Migration { version: 21, name: "shell_rules_and_audit", up: migrate_v21, down: None },
+    Migration {
+        version: 22,
+        name: "scheduler_settings",
+        up: migrate_v22,
+        down: Some(
+            r#"
+                DROP INDEX IF EXISTS idx_job_executions_job_started;
+                DELETE FROM cron_jobs WHERE id = 'system-prune-job-history';
+                DROP TABLE IF EXISTS scheduler_settings;
+            "#,
+        ),
+    },
+    Migration {
+        version: 23,
+        name: "job_concurrency",
+        up: migrate_v23,
+        down: Some(
+            r#"
+                ALTER TABLE cron_jobs DROP COLUMN priority;
+                ALTER TABLE cron_jobs DROP COLUMN allow_overlap;
+            "#,
+        ),
+    },
+    Migration {
+        version: 24,
+        name: "skill_schemas",
+        up: migrate_v24,
+        down: Some(
+            r#"
+                ALTER TABLE skills DROP COLUMN output_schema;
+                ALTER TABLE skills DROP COLUMN input_schema;
+            "#,
+        ),
+    },
+    Migration {
+        version: 25,
+        name: "usage_analytics",
+        up: migrate_v25,
+        down: Some(
+            r#"
+                DROP INDEX IF EXISTS idx_job_executions_started;
+                DROP INDEX IF EXISTS idx_recipe_executions_started;
+                DROP TABLE IF EXISTS analytics_watermarks;
+                DROP TABLE IF EXISTS analytics_daily;
+            "#,
+        ),
+    },
+    Migration {
+        version: 26,
+        name: "database_backups",
+        up: migrate_v26,
+        down: Some(
+            r#"
+                DELETE FROM cron_jobs WHERE id = 'system-backup-database';
+                DELETE FROM scheduler_settings WHERE key = 'backup_retention';
+            "#,
+        ),
+    },
+    Migration {
+        version: 27,
+        name: "app_settings",
+        up: migrate_v27,
+        down: Some("DROP TABLE IF EXISTS app_settings;"),
+    },
+    Migration {
+        version: 28,
+        name: "mcp_clients",
+        up: migrate_v28,
+        down: Some("DROP TABLE IF EXISTS mcp_clients;"),
+    },
+    Migration {
+        version: 29,
+        name: "job_email_delivery",
+        up: migrate_v29,
+        down: Some(
+            r#"
+                ALTER TABLE job_executions DROP COLUMN delivery_error;
+                ALTER TABLE job_executions DROP COLUMN delivery_status;
+            "#,
+        ),
+    },
+    Migration {
+        version: 30,
+        name: "notification_channels",
+        up: migrate_v30,
+        down: Some("DROP TABLE IF EXISTS notification_channels;"),
+    },
+    Migration {
+        version: 31,
+        name: "calendars",
+        up: migrate_v31,
+        down: Some(
+            r#"
+                DELETE FROM cron_jobs WHERE id = 'system-sync-calendars';
+                DROP TABLE IF EXISTS calendar_events;
+                DROP TABLE IF EXISTS calendar_sources;
+            "#,
+        ),
+    },
+    Migration {
+        version: 32,
+        name: "conversation_tags",
+        up: migrate_v32,
+        down: Some(
+            r#"
+                DROP TABLE IF EXISTS smart_folders;
+                DROP TABLE IF EXISTS conversation_tags;
+            "#,
+        ),
+    },
+    Migration {
+        version: 33,
+        name: "pinned_messages",
+        up: migrate_v33,
+        down: Some(
+            r#"
+                DROP TABLE IF EXISTS conversation_bookmarks;
+                DROP TABLE IF EXISTS pinned_messages;
+            "#,
+        ),
+    },
+    Migration {
+        version: 34,
+        name: "digest_jobs",
+        up: migrate_v34,
+        down: Some(
+            r#"
+                DELETE FROM cron_jobs WHERE job_type = 'digest';
+                CREATE TABLE cron_jobs_old (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    schedule TEXT NOT NULL,
+                    job_type TEXT NOT NULL CHECK(job_type IN ('skill', 'recipe', 'prompt', 'system', 'plugin', 'workflow')),
+                    config TEXT NOT NULL DEFAULT '{}',
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    last_run TEXT,
+                    next_run TEXT,
+                    plugin_id TEXT,
+                    workflow_id TEXT,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    allow_overlap TEXT NOT NULL DEFAULT 'run' CHECK(allow_overlap IN ('skip', 'queue', 'run')),
+                    priority INTEGER NOT NULL DEFAULT 0
+                );
+                INSERT INTO cron_jobs_old SELECT id, name, schedule, job_type, config, enabled, last_run, next_run,
+                    plugin_id, workflow_id, created_at, updated_at, allow_overlap, priority FROM cron_jobs;
+                DROP TABLE cron_jobs;
+                ALTER TABLE cron_jobs_old RENAME TO cron_jobs;
+                CREATE INDEX IF NOT EXISTS idx_cron_jobs_enabled ON cron_jobs(enabled);
+                CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+                CREATE INDEX IF NOT EXISTS idx_cron_jobs_plugin ON cron_jobs(plugin_id);
+                CREATE INDEX IF NOT EXISTS idx_cron_jobs_workflow ON cron_jobs(workflow_id);
+            "#,
+        ),
+    },
+    Migration {
+        version: 35,
+        name: "guardrails",
+        up: migrate_v35,
+        down: Some(
+            r#"
+                DROP TABLE IF EXISTS guardrail_overrides;
+                DROP TABLE IF EXISTS guardrail_events;
+            "#,
+        ),
+    },
+    Migration {
+        version: 36,
+        name: "conversation_shares",
+        up: migrate_v36,
+        down: Some("DROP TABLE IF EXISTS conversation_shares;"),
+    },
+    Migration {
+        version: 37,
+        name: "conversation_embeddings",
+        up: migrate_v37,
+        down: Some(
+            r#"
+                DELETE FROM cron_jobs WHERE id = 'system-embed-conversations';
+                DROP TABLE IF EXISTS conversation_embeddings;
+            "#,
+        ),
+    },
+    Migration {
+        version: 38,
+        name: "actions_audit",
+        up: migrate_v38,
+        down: Some("DROP TABLE IF EXISTS actions_audit;"),
+    },
+    Migration {
+        version: 39,
+        name: "marketplace_reviews",
+        up: migrate_v39,
+        down: Some("DROP TABLE IF EXISTS marketplace_reviews;"),
+    },
+    Migration {
+        version: 40,
+        name: "workspaces",
+        up: migrate_v40,
+        down: Some(
+            "DROP INDEX IF EXISTS idx_conversations_workspace;
+             ALTER TABLE conversations DROP COLUMN workspace_id;
+             DROP TABLE IF EXISTS workspace_folders;
+             DROP TABLE IF EXISTS workspaces;",
+        ),
+    },
+    Migration {
+        version: 41,
+        name: "workspace_chat_defaults",
+        up: migrate_v41,
+        down: Some(
+            "ALTER TABLE workspaces DROP COLUMN tool_allowlist;
+             ALTER TABLE workspaces DROP COLUMN temperature;
+             ALTER TABLE workspaces DROP COLUMN system_prompt;",
+        ),
+    },
+    Migration {
+        version: 42,
+        name: "knowledge_index",
+        up: migrate_v42,
+        down: Some(
+            r#"
+                DELETE FROM cron_jobs WHERE id = 'system-index-workspaces';
+                DROP TABLE IF EXISTS knowledge_index_state;
+                DROP TABLE IF EXISTS knowledge_chunks;
+                DROP TABLE IF EXISTS knowledge_documents;
+            "#,
+        ),
+    },
+    Migration {
+        version: 43,
+        name: "knowledge_chunk_symbols",
+        up: migrate_v43,
+        down: Some(
+            "ALTER TABLE knowledge_chunks DROP COLUMN symbol_kind;
+             ALTER TABLE knowledge_chunks DROP COLUMN symbol;",
+        ),
+    },
+    Migration {
+        version: 44,
+        name: "recipe_drafts",
+        up: migrate_v44,
+        down: Some("ALTER TABLE recipes DROP COLUMN is_draft;"),
+    },
+    Migration {
+        version: 45,
+        name: "job_snooze",
+        up: migrate_v45,
+        down: Some(
+            "DROP INDEX IF EXISTS idx_job_skipped_runs_job;
+             DROP TABLE IF EXISTS job_skipped_runs;
+             ALTER TABLE cron_jobs DROP COLUMN snoozed_until;
+             ALTER TABLE cron_jobs DROP COLUMN paused;",
+        ),
+    },
+    Migration {
+        version: 46,
+        name: "job_execution_logs",
+        up: migrate_v46,
+        down: Some("DROP TABLE IF EXISTS job_execution_logs;"),
+    },
+    Migration {
+        version: 47,
+        name: "retention_job",
+        up: migrate_v47,
+        down: Some("DELETE FROM cron_jobs WHERE id = 'system-apply-retention';"),
+    },
+    Migration {
+        version: 48,
+        name: "imported_conversations",
+        up: migrate_v48,
+        down: Some("DROP TABLE IF EXISTS imported_conversations;"),
+    },
+    Migration {
+        version: 49,
+        name: "voice_barge_in",
+        up: migrate_v49,
+        down: Some(
+            "ALTER TABLE voice_settings DROP COLUMN echo_suppression;
+             ALTER TABLE voice_settings DROP COLUMN barge_in_sensitivity;
+             ALTER TABLE voice_settings DROP COLUMN barge_in_enabled;",
+        ),
+    },
+    Migration {
+        version: 50,
+        name: "request_telemetry",
+        up: migrate_v50,
+        down: Some(
+            "DROP INDEX IF EXISTS idx_request_telemetry_provider;
+             DROP TABLE IF EXISTS request_telemetry;",
+        ),
+    },
+    Migration {
+        version: 51,
+        name: "plugin_messages",
+        up: migrate_v51,
+        down: Some(
+            "DROP INDEX IF EXISTS idx_plugin_messages_recipient;
+             DROP TABLE IF EXISTS plugin_messages;",
+        ),
+    },
+    Migration {
+        version: 52,
+        name: "plugin_request_audit",
+        up: migrate_v52,
+        down: Some(
+            r#"
+                DELETE FROM actions_audit WHERE kind = 'plugin_request';
+                CREATE TABLE actions_audit_old (
+                    id TEXT PRIMARY KEY,
+                    kind TEXT NOT NULL CHECK(kind IN ('tool_call', 'file_write', 'shell_command', 'db_query', 'git_operation')),
+                    name TEXT NOT NULL,
+                    conversation_id TEXT,
+                    params TEXT NOT NULL DEFAULT '{}',
+                    status TEXT NOT NULL CHECK(status IN ('success', 'error', 'denied', 'blocked')),
+                    error TEXT,
+                    started_at TEXT NOT NULL,
+                    finished_at TEXT NOT NULL,
+                    duration_ms INTEGER NOT NULL DEFAULT 0
+                );
+                INSERT INTO actions_audit_old SELECT id, kind, name, conversation_id, params, status, error,
+                    started_at, finished_at, duration_ms FROM actions_audit;
+                DROP TABLE actions_audit;
+                ALTER TABLE actions_audit_old RENAME TO actions_audit;
+                CREATE INDEX IF NOT EXISTS idx_actions_audit_conversation ON actions_audit(conversation_id);
+                CREATE INDEX IF NOT EXISTS idx_actions_audit_started ON actions_audit(started_at);
+                CREATE INDEX IF NOT EXISTS idx_actions_audit_kind ON actions_audit(kind);
+            "#,
+        ),
+    },
+    Migration {
+        version: 53,
+        name: "provider_profiles",
+        up: migrate_v53,
+        down: Some("DROP TABLE IF EXISTS provider_profiles;"),
+    },
+    Migration {
+        version: 54,
+        name: "openai_compatible_providers",
+        up: migrate_v54,
+        down: Some(
+            r#"
+                DELETE FROM provider_profiles WHERE kind = 'openai-compatible';
+                CREATE TABLE provider_profiles_old (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    kind TEXT NOT NULL CHECK(kind IN ('anthropic', 'openai', 'gemini', 'ollama')),
+                    base_url TEXT,
+                    models TEXT NOT NULL DEFAULT '[]',
+                    default_model TEXT,
+                    defaults TEXT NOT NULL DEFAULT '{}',
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+                INSERT INTO provider_profiles_old SELECT id, name, kind, base_url, models, default_model, defaults,
+                    enabled, created_at, updated_at FROM provider_profiles;
+                DROP TABLE provider_profiles;
+                ALTER TABLE provider_profiles_old RENAME TO provider_profiles;
+            "#,
+        ),
+    },
+    Migration {
+        version: 55,
+        name: "conversation_system_prompts",
+        up: migrate_v55,
+        down: Some("DROP TABLE IF EXISTS conversation_system_prompts;"),
+    },
+    Migration {
+        version: 56,
+        name: "conversation_generation_params",
+        up: migrate_v56,
+        down: Some("DROP TABLE IF EXISTS conversation_generation_params;"),
+    },
+    Migration {
+        version: 57,
+        name: "message_feedback",
+        up: migrate_v57,
+        down: Some("DROP TABLE IF EXISTS message_feedback;"),
+    },
+    Migration {
+        version: 58,
+        name: "db_health_reports",
+        up: migrate_v58,
+        down: Some(
+            r#"
+                DELETE FROM cron_jobs WHERE id = 'system-integrity-check';
+                DROP TABLE IF EXISTS db_health_reports;
+            "#,
+        ),
+    },
+];
+
+/// Apply every pending migration; a failed run is rolled back
 pub fn run_migrations(conn: &Connection) -> Result<()> {
-    // Create migrations table if not exists
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS schema_migrations (
-            version INTEGER PRIMARY KEY,
-            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-        [],
-    )?;
-
-    // Get current version
-    let current_version: i32 = conn
-        .query_row(
-            "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
-            [],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-
-    // Apply migrations
-    if current_version < 1 {
-        migrate_v1(conn)?;
-    }
-
-    if current_version < 2 {
-        migrate_v2(conn)?;
-    }
-
-    if current_version < 3 {
-        migrate_v3(conn)?;
-    }
-
-    if current_version < 4 {
-        migrate_v4(conn)?;
-    }
-
-    if current_version < 5 {
-        migrate_v5(conn)?;
-    }
-
-    if current_version < 6 {
-        migrate_v6(conn)?;
-    }
-
-    if current_version < 7 {
-        migrate_v7(conn)?;
-    }
-
-    if current_version < 8 {
-        migrate_v8(conn)?;
-    }
-
-    if current_version < 9 {
-        migrate_v9(conn)?;
-    }
-
-    if current_version < 10 {
-        migrate_v10(conn)?;
-    }
-
-    Ok(())
+    migrations::run_pending(conn, MIGRATIONS)
 }
 
 fn migrate_v1(conn: &Connection) -> Result<()> {
@@ -610,3 +968,1673 @@ fn migrate_v10(conn: &Connection) -> Result<()> {
 
     Ok(())
 }
+
+/// Migration v11: Add sensitive conversation support
+///
+/// This migration:
+/// 1. Adds `is_sensitive` flag to `conversations`
+/// 2. Adds `is_encrypted` flag to `messages` for field-level encrypted content
+fn migrate_v11(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Flag conversations whose messages are encrypted at rest
+        ALTER TABLE conversations ADD COLUMN is_sensitive INTEGER NOT NULL DEFAULT 0;
+
+        -- Mark messages whose content column holds ciphertext
+        ALTER TABLE messages ADD COLUMN is_encrypted INTEGER NOT NULL DEFAULT 0;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (11);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v11 completed");
+
+    Ok(())
+}
+
+/// Migration v12: Add plugin key-value storage
+///
+/// This migration:
+/// 1. Creates `plugin_storage` table namespaced by plugin id
+fn migrate_v12(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Create plugin_storage table
+        CREATE TABLE IF NOT EXISTS plugin_storage (
+            plugin_id TEXT NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (plugin_id, key)
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (12);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v12 completed");
+
+    Ok(())
+}
+
+/// Migration v13: Add plugin-owned cron jobs
+///
+/// This migration:
+/// 1. Rebuilds `cron_jobs` to allow the `plugin` job type
+/// 2. Adds `plugin_id` to `cron_jobs` to tag jobs registered by plugins
+fn migrate_v13(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Recreate cron_jobs with the extended job_type check
+        CREATE TABLE cron_jobs_new (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            schedule TEXT NOT NULL,
+            job_type TEXT NOT NULL CHECK(job_type IN ('skill', 'recipe', 'prompt', 'system', 'plugin')),
+            config TEXT NOT NULL DEFAULT '{}',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run TEXT,
+            next_run TEXT,
+            plugin_id TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        INSERT INTO cron_jobs_new (id, name, schedule, job_type, config, enabled, last_run, next_run, created_at, updated_at)
+            SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, created_at, updated_at
+            FROM cron_jobs;
+
+        DROP TABLE cron_jobs;
+        ALTER TABLE cron_jobs_new RENAME TO cron_jobs;
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_enabled ON cron_jobs(enabled);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_plugin ON cron_jobs(plugin_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (13);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v13 completed");
+
+    Ok(())
+}
+
+/// Migration v14: Add TTS provider selection
+///
+/// This migration:
+/// 1. Adds `tts_provider` to `voice_settings` (defaults to the local engine)
+fn migrate_v14(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE voice_settings ADD COLUMN tts_provider TEXT NOT NULL DEFAULT 'local';
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (14);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v14 completed");
+
+    Ok(())
+}
+
+/// Migration v15: Add SQL audit log
+///
+/// This migration:
+/// 1. Creates `database_query_audit` recording every statement sent to an
+///    external database connection, with its outcome and row counts
+fn migrate_v15(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- SQL audit log
+        CREATE TABLE IF NOT EXISTS database_query_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            connection_name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            conversation_id TEXT,
+            status TEXT NOT NULL CHECK(status IN ('success', 'error', 'rejected')),
+            row_count INTEGER NOT NULL DEFAULT 0,
+            rows_affected INTEGER NOT NULL DEFAULT 0,
+            execution_time_ms INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_database_query_audit_connection ON database_query_audit(connection_name);
+        CREATE INDEX IF NOT EXISTS idx_database_query_audit_conversation ON database_query_audit(conversation_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (15);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v15 completed");
+
+    Ok(())
+}
+
+/// Migration v16: Add orchestration runs
+///
+/// This migration:
+/// 1. Creates `orchestration_runs` recording each decomposed goal with its
+///    plan, per-subtask results and the aggregated answer
+fn migrate_v16(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Multi-agent orchestration runs
+        CREATE TABLE IF NOT EXISTS orchestration_runs (
+            id TEXT PRIMARY KEY,
+            goal TEXT NOT NULL,
+            agent_ids TEXT NOT NULL DEFAULT '[]',
+            status TEXT NOT NULL DEFAULT 'planning' CHECK(status IN ('planning', 'running', 'completed', 'failed')),
+            plan TEXT NOT NULL DEFAULT '[]',
+            result TEXT,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            completed_at TEXT
+        );
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_orchestration_runs_created ON orchestration_runs(created_at);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (16);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v16 completed");
+
+    Ok(())
+}
+
+/// Migration v17: Add settings sync journal
+///
+/// This migration:
+/// 1. Creates `sync_state` holding the device id and sync configuration
+/// 2. Creates `sync_journal` recording which synced entities changed locally
+/// 3. Adds triggers journaling changes to conversations, messages, templates
+///    and voice settings while sync is enabled
+fn migrate_v17(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Sync state (device id, storage config, last sync)
+        CREATE TABLE IF NOT EXISTS sync_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        -- Local change journal
+        CREATE TABLE IF NOT EXISTS sync_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            changed_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Journal triggers
+        CREATE TRIGGER IF NOT EXISTS sync_journal_conversations_insert AFTER INSERT ON conversations
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('conversation', NEW.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_conversations_update AFTER UPDATE ON conversations
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('conversation', NEW.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_conversations_delete AFTER DELETE ON conversations
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('conversation', OLD.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_messages_insert AFTER INSERT ON messages
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('conversation', NEW.conversation_id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_messages_update AFTER UPDATE ON messages
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('conversation', NEW.conversation_id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_messages_delete AFTER DELETE ON messages
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('conversation', OLD.conversation_id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_templates_insert AFTER INSERT ON templates
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('template', NEW.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_templates_update AFTER UPDATE ON templates
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('template', NEW.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_templates_delete AFTER DELETE ON templates
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('template', OLD.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_voice_settings_insert AFTER INSERT ON voice_settings
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('settings', NEW.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_voice_settings_update AFTER UPDATE ON voice_settings
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('settings', NEW.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS sync_journal_voice_settings_delete AFTER DELETE ON voice_settings
+        WHEN (SELECT value FROM sync_state WHERE key = 'enabled') = '1'
+        BEGIN
+            INSERT INTO sync_journal (entity_type, entity_id) VALUES ('settings', OLD.id);
+        END;
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_sync_journal_entity ON sync_journal(entity_type, entity_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (17);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v17 completed");
+
+    Ok(())
+}
+
+/// Migration v18: Add sync conflicts
+///
+/// This migration:
+/// 1. Creates `sync_conflicts` holding entities changed on this and another
+///    device until the user picks which version to keep
+fn migrate_v18(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Sync conflicts
+        CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id TEXT PRIMARY KEY,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            local_version TEXT NOT NULL,
+            remote_version TEXT NOT NULL,
+            remote_etag TEXT NOT NULL,
+            remote_device_id TEXT NOT NULL,
+            local_data TEXT,
+            remote_data TEXT,
+            status TEXT NOT NULL DEFAULT 'open' CHECK(status IN ('open', 'resolved')),
+            resolution TEXT CHECK(resolution IN ('local', 'remote', 'merge')),
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            resolved_at TEXT
+        );
+
+        -- At most one open conflict per entity
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_conflicts_open
+            ON sync_conflicts(entity_type, entity_id) WHERE status = 'open';
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (18);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v18 completed");
+
+    Ok(())
+}
+
+/// Migration v19: Add workflow-owned cron jobs
+///
+/// This migration:
+/// 1. Rebuilds `cron_jobs` to allow the `workflow` job type
+/// 2. Adds `workflow_id` to `cron_jobs` to tag jobs created by schedule triggers
+fn migrate_v19(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Recreate cron_jobs with the extended job_type check
+        CREATE TABLE cron_jobs_new (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            schedule TEXT NOT NULL,
+            job_type TEXT NOT NULL CHECK(job_type IN ('skill', 'recipe', 'prompt', 'system', 'plugin', 'workflow')),
+            config TEXT NOT NULL DEFAULT '{}',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run TEXT,
+            next_run TEXT,
+            plugin_id TEXT,
+            workflow_id TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        INSERT INTO cron_jobs_new (id, name, schedule, job_type, config, enabled, last_run, next_run, plugin_id, created_at, updated_at)
+            SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, plugin_id, created_at, updated_at
+            FROM cron_jobs;
+
+        DROP TABLE cron_jobs;
+        ALTER TABLE cron_jobs_new RENAME TO cron_jobs;
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_enabled ON cron_jobs(enabled);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_plugin ON cron_jobs(plugin_id);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_workflow ON cron_jobs(workflow_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (19);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v19 completed");
+
+    Ok(())
+}
+
+/// Migration v20: Add tool permission policies
+///
+/// This migration:
+/// 1. Creates `tool_policies` with an allow/ask/deny decision per agent tool
+fn migrate_v20(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Per-tool decisions; tools without a row use their category default
+        CREATE TABLE IF NOT EXISTS tool_policies (
+            tool TEXT PRIMARY KEY,
+            decision TEXT NOT NULL CHECK(decision IN ('allow', 'ask', 'deny')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (20);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v20 completed");
+
+    Ok(())
+}
+
+/// Migration v21: Add shell command rules and audit log
+///
+/// This migration:
+/// 1. Creates `shell_binary_rules` allowing or denying binaries for the
+///    shell tool
+/// 2. Creates `shell_command_audit` recording every shell invocation,
+///    including rejected ones
+fn migrate_v21(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Allowlist/denylist of binaries
+        CREATE TABLE IF NOT EXISTS shell_binary_rules (
+            binary TEXT PRIMARY KEY,
+            rule TEXT NOT NULL CHECK(rule IN ('allow', 'deny')),
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Shell audit log
+        CREATE TABLE IF NOT EXISTS shell_command_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invocation_id TEXT NOT NULL,
+            command TEXT NOT NULL,
+            args TEXT NOT NULL DEFAULT '[]',
+            working_dir TEXT NOT NULL,
+            conversation_id TEXT,
+            status TEXT NOT NULL CHECK(status IN ('success', 'failed', 'timeout', 'rejected')),
+            exit_code INTEGER,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_shell_command_audit_conversation ON shell_command_audit(conversation_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (21);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v21 completed");
+
+    Ok(())
+}
+
+/// Migration v22: Add scheduler settings and job history pruning
+///
+/// This migration:
+/// 1. Creates `scheduler_settings` with the number of executions kept per job
+/// 2. Adds a daily `prune_job_history` system job
+/// 3. Indexes `job_executions` by job and start time for pruning
+fn migrate_v22(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Scheduler settings
+        CREATE TABLE IF NOT EXISTS scheduler_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        INSERT OR IGNORE INTO scheduler_settings (key, value) VALUES ('execution_retention', '100');
+
+        -- Daily history pruning
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-prune-job-history', 'Prune job history', '0 3 * * *', 'system',
+                '{"target":"prune_job_history","params":{}}', 1);
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_job_executions_job_started ON job_executions(job_id, started_at);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (22);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v22 completed");
+
+    Ok(())
+}
+
+/// Migration v23: Add per-job concurrency options
+///
+/// This migration:
+/// 1. Adds `allow_overlap` to `cron_jobs` (skip, queue or run)
+/// 2. Adds `priority` to `cron_jobs`
+fn migrate_v23(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Concurrency options
+        ALTER TABLE cron_jobs ADD COLUMN allow_overlap TEXT NOT NULL DEFAULT 'run'
+            CHECK(allow_overlap IN ('skip', 'queue', 'run'));
+        ALTER TABLE cron_jobs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (23);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v23 completed");
+
+    Ok(())
+}
+
+/// Migration v24: Add skill input and output schemas
+///
+/// This migration:
+/// 1. Adds `input_schema` to `skills` (JSON Schema for skill arguments)
+/// 2. Adds `output_schema` to `skills` (JSON Schema for structured output)
+fn migrate_v24(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Skill schemas
+        ALTER TABLE skills ADD COLUMN input_schema TEXT;
+        ALTER TABLE skills ADD COLUMN output_schema TEXT;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (24);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v24 completed");
+
+    Ok(())
+}
+
+/// Migration v25: Add materialized usage analytics
+///
+/// This migration:
+/// 1. Creates `analytics_daily` (counts per metric, day and dimension)
+/// 2. Creates `analytics_watermarks` (first day of each metric not yet final)
+fn migrate_v25(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Daily aggregates
+        CREATE TABLE IF NOT EXISTS analytics_daily (
+            metric TEXT NOT NULL,
+            day TEXT NOT NULL,
+            dimension TEXT NOT NULL DEFAULT '',
+            value INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (metric, day, dimension)
+        );
+
+        CREATE TABLE IF NOT EXISTS analytics_watermarks (
+            metric TEXT PRIMARY KEY,
+            day TEXT NOT NULL
+        );
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_analytics_daily_day ON analytics_daily(day);
+        CREATE INDEX IF NOT EXISTS idx_recipe_executions_started ON recipe_executions(started_at);
+        CREATE INDEX IF NOT EXISTS idx_job_executions_started ON job_executions(started_at);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (25);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v25 completed");
+
+    Ok(())
+}
+
+/// Migration v26: Add automatic database backups
+///
+/// This migration:
+/// 1. Adds the `backup_retention` scheduler setting
+/// 2. Adds a daily `backup_database` system job
+fn migrate_v26(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Backup retention
+        INSERT OR IGNORE INTO scheduler_settings (key, value) VALUES ('backup_retention', '7');
+
+        -- Daily backup
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-backup-database', 'Back up database', '0 2 * * *', 'system',
+                '{"target":"backup_database","params":{}}', 1);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (26);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v26 completed");
+
+    Ok(())
+}
+
+/// Migration v27: Add application settings
+///
+/// This migration:
+/// 1. Creates the `app_settings` key/value table for user preferences such as
+///    the quick ask shortcut
+fn migrate_v27(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Application settings
+        CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (27);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v27 completed");
+
+    Ok(())
+}
+
+/// Migration v28: Add MCP server clients
+///
+/// This migration:
+/// 1. Creates the `mcp_clients` table holding the capabilities each external
+///    MCP client may use when the app runs as an MCP server
+fn migrate_v28(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- MCP server clients
+        CREATE TABLE IF NOT EXISTS mcp_clients (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            capabilities TEXT NOT NULL DEFAULT '[]',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_connected_at TEXT
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (28);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v28 completed");
+
+    Ok(())
+}
+
+/// Migration v29: Record email delivery of job output
+///
+/// This migration:
+/// 1. Adds `delivery_status` and `delivery_error` to `job_executions` for
+///    jobs that email their output
+fn migrate_v29(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE job_executions ADD COLUMN delivery_status TEXT;
+        ALTER TABLE job_executions ADD COLUMN delivery_error TEXT;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (29);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v29 completed");
+
+    Ok(())
+}
+
+/// Migration v30: Add outbound notification channels
+///
+/// This migration:
+/// 1. Creates the `notification_channels` table of Slack, Discord and
+///    generic webhooks; the webhook URLs themselves live in the keychain
+fn migrate_v30(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Outbound webhook channels
+        CREATE TABLE IF NOT EXISTS notification_channels (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            kind TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_sent_at TEXT,
+            last_error TEXT
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (30);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v30 completed");
+
+    Ok(())
+}
+
+/// Migration v31: Add calendar subscriptions
+///
+/// This migration:
+/// 1. Creates `calendar_sources` for ICS subscriptions and CalDAV calendars
+/// 2. Creates `calendar_events`, the local copy of their upcoming events
+/// 3. Adds a system job syncing them every 30 minutes
+fn migrate_v31(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Calendar sources
+        CREATE TABLE IF NOT EXISTS calendar_sources (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            url TEXT NOT NULL,
+            username TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_synced_at TEXT,
+            last_error TEXT
+        );
+
+        -- Cached upcoming events
+        CREATE TABLE IF NOT EXISTS calendar_events (
+            id TEXT PRIMARY KEY,
+            source_id TEXT NOT NULL,
+            uid TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            description TEXT,
+            location TEXT,
+            starts_at TEXT NOT NULL,
+            ends_at TEXT NOT NULL,
+            all_day INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (source_id) REFERENCES calendar_sources(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_calendar_events_start ON calendar_events(starts_at);
+
+        -- Calendar sync every 30 minutes
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-sync-calendars', 'Sync calendars', '*/30 * * * *', 'system',
+                '{"target":"sync_calendars","params":{}}', 1);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (31);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v31 completed");
+
+    Ok(())
+}
+/// Migration v32: Add conversation tags and smart folders
+///
+/// This migration:
+/// 1. Creates `conversation_tags`, the tags attached to each conversation
+/// 2. Creates `smart_folders`, saved conversation filters
+fn migrate_v32(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Conversation tags, unique per conversation regardless of case
+        CREATE TABLE IF NOT EXISTS conversation_tags (
+            conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            tag TEXT NOT NULL COLLATE NOCASE,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (conversation_id, tag)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_conversation_tags_tag ON conversation_tags(tag);
+
+        -- Saved conversation filters
+        CREATE TABLE IF NOT EXISTS smart_folders (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+            filter TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (32);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v32 completed");
+
+    Ok(())
+}
+
+/// Migration v33: Add pinned messages and conversation bookmarks
+///
+/// This migration:
+/// 1. Creates `pinned_messages`, messages kept in context during compression
+/// 2. Creates `conversation_bookmarks`, labelled positions in a conversation
+fn migrate_v33(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Pinned messages
+        CREATE TABLE IF NOT EXISTS pinned_messages (
+            message_id TEXT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+            conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            note TEXT,
+            pinned_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_pinned_messages_conversation ON pinned_messages(conversation_id);
+
+        -- Bookmarked positions
+        CREATE TABLE IF NOT EXISTS conversation_bookmarks (
+            id TEXT PRIMARY KEY,
+            conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            label TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_conversation_bookmarks_conversation ON conversation_bookmarks(conversation_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (33);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v33 completed");
+
+    Ok(())
+}
+
+/// Migration v34: Add activity digest jobs
+///
+/// This migration:
+/// 1. Rebuilds `cron_jobs` to allow the `digest` job type
+fn migrate_v34(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Recreate cron_jobs with the extended job_type check
+        CREATE TABLE cron_jobs_new (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            schedule TEXT NOT NULL,
+            job_type TEXT NOT NULL CHECK(job_type IN ('skill', 'recipe', 'prompt', 'system', 'plugin', 'workflow', 'digest')),
+            config TEXT NOT NULL DEFAULT '{}',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run TEXT,
+            next_run TEXT,
+            plugin_id TEXT,
+            workflow_id TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            allow_overlap TEXT NOT NULL DEFAULT 'run' CHECK(allow_overlap IN ('skip', 'queue', 'run')),
+            priority INTEGER NOT NULL DEFAULT 0
+        );
+
+        INSERT INTO cron_jobs_new (id, name, schedule, job_type, config, enabled, last_run, next_run, plugin_id, workflow_id,
+                                   created_at, updated_at, allow_overlap, priority)
+            SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, plugin_id, workflow_id,
+                   created_at, updated_at, allow_overlap, priority
+            FROM cron_jobs;
+
+        DROP TABLE cron_jobs;
+        ALTER TABLE cron_jobs_new RENAME TO cron_jobs;
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_enabled ON cron_jobs(enabled);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_plugin ON cron_jobs(plugin_id);
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_workflow ON cron_jobs(workflow_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (34);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v34 completed");
+
+    Ok(())
+}
+
+/// Migration v35: Add prompt guardrails
+///
+/// This migration:
+/// 1. Creates `guardrail_events`, secrets and prompt-injection markers found
+///    in prompts and tool results
+/// 2. Creates `guardrail_overrides`, per-conversation guardrail policies
+fn migrate_v35(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Guardrail detections
+        CREATE TABLE IF NOT EXISTS guardrail_events (
+            id TEXT PRIMARY KEY,
+            conversation_id TEXT,
+            direction TEXT NOT NULL CHECK(direction IN ('prompt', 'tool_result')),
+            source TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK(kind IN ('secret', 'prompt_injection')),
+            rule TEXT NOT NULL,
+            action TEXT NOT NULL CHECK(action IN ('allow', 'redact', 'block')),
+            excerpt TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_guardrail_events_conversation ON guardrail_events(conversation_id);
+        CREATE INDEX IF NOT EXISTS idx_guardrail_events_created ON guardrail_events(created_at);
+
+        -- Per-conversation policies
+        CREATE TABLE IF NOT EXISTS guardrail_overrides (
+            conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+            policy TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (35);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v35 completed");
+
+    Ok(())
+}
+
+/// Migration v36: Add conversation sharing
+///
+/// This migration:
+/// 1. Creates `conversation_shares`, a record of every redacted bundle
+///    shared and where it was uploaded. Rows are kept when the conversation
+///    is deleted.
+fn migrate_v36(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Shared bundles
+        CREATE TABLE IF NOT EXISTS conversation_shares (
+            id TEXT PRIMARY KEY,
+            conversation_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            format TEXT NOT NULL CHECK(format IN ('html', 'json')),
+            redaction TEXT NOT NULL DEFAULT '{}',
+            redactions TEXT NOT NULL DEFAULT '{}',
+            message_count INTEGER NOT NULL DEFAULT 0,
+            storage TEXT,
+            object_key TEXT,
+            url TEXT,
+            expires_at TEXT,
+            removed_at TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_conversation_shares_conversation ON conversation_shares(conversation_id);
+        CREATE INDEX IF NOT EXISTS idx_conversation_shares_expires ON conversation_shares(expires_at);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (36);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v36 completed");
+
+    Ok(())
+}
+
+/// Migration v37: Add conversation embeddings
+///
+/// This migration:
+/// 1. Creates `conversation_embeddings`, one vector per conversation for
+///    similar conversation suggestions
+/// 2. Adds an `embed_conversations` system job every 15 minutes
+fn migrate_v37(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Conversation embeddings
+        CREATE TABLE IF NOT EXISTS conversation_embeddings (
+            conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+            embedding BLOB NOT NULL,
+            source_hash TEXT NOT NULL,
+            model TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Embed new and changed conversations every 15 minutes
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-embed-conversations', 'Embed conversations', '*/15 * * * *', 'system',
+                '{"target":"embed_conversations","params":{}}', 1);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (37);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v37 completed");
+
+    Ok(())
+}
+
+/// Migration v38: Add the agent action audit trail
+///
+/// This migration:
+/// 1. Creates `actions_audit`, every tool call, file write, shell command,
+///    SQL statement and git operation the agent initiated
+fn migrate_v38(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Agent-initiated actions
+        CREATE TABLE IF NOT EXISTS actions_audit (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL CHECK(kind IN ('tool_call', 'file_write', 'shell_command', 'db_query', 'git_operation')),
+            name TEXT NOT NULL,
+            conversation_id TEXT,
+            params TEXT NOT NULL DEFAULT '{}',
+            status TEXT NOT NULL CHECK(status IN ('success', 'error', 'denied', 'blocked')),
+            error TEXT,
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL DEFAULT 0
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_actions_audit_conversation ON actions_audit(conversation_id);
+        CREATE INDEX IF NOT EXISTS idx_actions_audit_started ON actions_audit(started_at);
+        CREATE INDEX IF NOT EXISTS idx_actions_audit_kind ON actions_audit(kind);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (38);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v38 completed");
+
+    Ok(())
+}
+
+/// Migration v39: Add marketplace reviews
+///
+/// This migration:
+/// 1. Creates `marketplace_reviews`, the user's own reviews and a cache of
+///    the reviews fetched from the registry, by item and page
+fn migrate_v39(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Marketplace item reviews
+        CREATE TABLE IF NOT EXISTS marketplace_reviews (
+            id TEXT PRIMARY KEY,
+            item_id TEXT NOT NULL,
+            rating INTEGER NOT NULL CHECK(rating BETWEEN 1 AND 5),
+            comment TEXT NOT NULL DEFAULT '',
+            author TEXT,
+            created_at TEXT NOT NULL,
+            own INTEGER NOT NULL DEFAULT 0,
+            synced INTEGER NOT NULL DEFAULT 1,
+            page INTEGER
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_marketplace_reviews_item ON marketplace_reviews(item_id, page);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_marketplace_reviews_own ON marketplace_reviews(item_id) WHERE own = 1;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (39);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v39 completed");
+
+    Ok(())
+}
+
+/// Migration v40: Add workspaces
+///
+/// This migration:
+/// 1. Creates `workspaces`, each with a default knowledge base and preferred
+///    provider and model
+/// 2. Creates `workspace_folders`, linking workspaces to folder permissions
+/// 3. Adds `workspace_id` to `conversations`
+fn migrate_v40(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Projects grouping folders, conversations and a knowledge base
+        CREATE TABLE IF NOT EXISTS workspaces (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            description TEXT,
+            knowledge_base TEXT,
+            provider TEXT,
+            model TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS workspace_folders (
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            folder_id TEXT NOT NULL REFERENCES folder_permissions(id) ON DELETE CASCADE,
+            PRIMARY KEY (workspace_id, folder_id)
+        );
+
+        -- Cleared by delete_workspace; no foreign key so the column can be dropped
+        ALTER TABLE conversations ADD COLUMN workspace_id TEXT;
+        CREATE INDEX IF NOT EXISTS idx_conversations_workspace ON conversations(workspace_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (40);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v40 completed");
+
+    Ok(())
+}
+
+/// Migration v41: Add workspace chat defaults
+///
+/// This migration:
+/// 1. Adds the system prompt, temperature and tool allowlist applied to the
+///    chats of a workspace's conversations
+fn migrate_v41(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE workspaces ADD COLUMN system_prompt TEXT;
+        ALTER TABLE workspaces ADD COLUMN temperature REAL;
+        -- JSON array of tool names; NULL allows every tool
+        ALTER TABLE workspaces ADD COLUMN tool_allowlist TEXT;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (41);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v41 completed");
+
+    Ok(())
+}
+
+/// Migration v42: Knowledge base index of workspace folders
+///
+/// This migration:
+/// 1. Creates `knowledge_documents`, one row per indexed file with the size,
+///    modification time and hash used to detect changes
+/// 2. Creates `knowledge_chunks` holding each file's embedded passages
+/// 3. Creates `knowledge_index_state` with each workspace's ignore patterns
+///    and last run
+/// 4. Adds an `index_workspaces` system job every 5 minutes
+fn migrate_v42(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS knowledge_documents (
+            id TEXT PRIMARY KEY,
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            folder_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            modified TEXT,
+            hash TEXT NOT NULL,
+            model TEXT NOT NULL,
+            chunk_count INTEGER NOT NULL DEFAULT 0,
+            indexed_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(workspace_id, path)
+        );
+
+        CREATE TABLE IF NOT EXISTS knowledge_chunks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            document_id TEXT NOT NULL REFERENCES knowledge_documents(id) ON DELETE CASCADE,
+            ordinal INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            embedding BLOB NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_knowledge_chunks_document ON knowledge_chunks(document_id);
+
+        CREATE TABLE IF NOT EXISTS knowledge_index_state (
+            workspace_id TEXT PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+            -- JSON array of globs skipped on top of the defaults
+            ignore_patterns TEXT,
+            last_started_at TEXT,
+            last_indexed_at TEXT,
+            -- JSON summary of the last successful run
+            last_run TEXT,
+            last_error TEXT
+        );
+
+        -- Pick up changed files every 5 minutes
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-index-workspaces', 'Index workspace folders', '*/5 * * * *', 'system',
+                '{"target":"index_workspaces","params":{}}', 1);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (42);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v42 completed");
+
+    Ok(())
+}
+
+/// Migration v43: Symbols of code chunks
+///
+/// This migration:
+/// 1. Adds the symbol and kind of the definition each chunk of a source file
+///    belongs to
+/// 2. Marks indexed source files for re-chunking on the next indexing run
+fn migrate_v43(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE knowledge_chunks ADD COLUMN symbol TEXT;
+        ALTER TABLE knowledge_chunks ADD COLUMN symbol_kind TEXT;
+
+        -- An outdated model makes the indexer embed the file again
+        UPDATE knowledge_documents SET model = ''
+        WHERE path LIKE '%.rs' OR path LIKE '%.py' OR path LIKE '%.pyi' OR path LIKE '%.go'
+           OR path LIKE '%.js' OR path LIKE '%.jsx' OR path LIKE '%.mjs' OR path LIKE '%.cjs'
+           OR path LIKE '%.ts' OR path LIKE '%.tsx' OR path LIKE '%.mts' OR path LIKE '%.cts';
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (43);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v43 completed");
+
+    Ok(())
+}
+
+/// Migration v44: Draft recipes
+///
+/// This migration:
+/// 1. Adds `is_draft` to recipes extracted from a conversation until the
+///    user saves them
+fn migrate_v44(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE recipes ADD COLUMN is_draft INTEGER NOT NULL DEFAULT 0;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (44);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v44 completed");
+
+    Ok(())
+}
+
+/// Migration v45: Paused and snoozed jobs
+///
+/// This migration:
+/// 1. Adds `paused` and `snoozed_until` to `cron_jobs`
+/// 2. Creates `job_skipped_runs` for runs skipped while a job was snoozed
+fn migrate_v45(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE cron_jobs ADD COLUMN paused INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE cron_jobs ADD COLUMN snoozed_until TEXT;
+
+        CREATE TABLE IF NOT EXISTS job_skipped_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id TEXT NOT NULL,
+            scheduled_for TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_job_skipped_runs_job ON job_skipped_runs(job_id, scheduled_for);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (45);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v45 completed");
+
+    Ok(())
+}
+
+/// Migration v46: Job execution logs
+///
+/// This migration:
+/// 1. Creates `job_execution_logs` for the lines executions log as they run
+fn migrate_v46(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS job_execution_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            execution_id TEXT NOT NULL,
+            seq INTEGER NOT NULL,
+            level TEXT NOT NULL CHECK(level IN ('info', 'warn', 'error')),
+            message TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(execution_id, seq)
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (46);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v46 completed");
+
+    Ok(())
+}
+
+/// Migration v47: Retention policies
+///
+/// This migration:
+/// 1. Schedules the daily `apply_retention` system job; the policies
+///    themselves are kept in `app_settings`
+fn migrate_v47(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-apply-retention', 'Apply retention policies', '30 3 * * *', 'system',
+                '{"target":"apply_retention","params":{}}', 1);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (47);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v47 completed");
+
+    Ok(())
+}
+
+/// Migration v48: Imported conversations
+///
+/// This migration:
+/// 1. Creates imported_conversations, mapping conversations imported from
+///    other tools to the conversations they were stored as
+fn migrate_v48(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS imported_conversations (
+            source TEXT NOT NULL,
+            external_id TEXT NOT NULL,
+            conversation_id TEXT NOT NULL,
+            imported_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (source, external_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_imported_conversations_conversation
+            ON imported_conversations(conversation_id);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (48);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v48 completed");
+
+    Ok(())
+}
+
+/// Migration v49: Voice barge-in settings
+///
+/// This migration:
+/// 1. Adds `barge_in_enabled`, `barge_in_sensitivity` and `echo_suppression`
+///    to `voice_settings`
+fn migrate_v49(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE voice_settings ADD COLUMN barge_in_enabled INTEGER NOT NULL DEFAULT 1;
+        ALTER TABLE voice_settings ADD COLUMN barge_in_sensitivity REAL NOT NULL DEFAULT 0.7;
+        ALTER TABLE voice_settings ADD COLUMN echo_suppression INTEGER NOT NULL DEFAULT 1;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (49);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v49 completed");
+
+    Ok(())
+}
+
+/// Migration v50: Request telemetry
+///
+/// This migration:
+/// 1. Creates `request_telemetry`, one row per model request made by the
+///    agent runtime with its provider, model, latency, tokens and retries
+fn migrate_v50(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS request_telemetry (
+            id TEXT PRIMARY KEY,
+            provider TEXT NOT NULL,
+            model TEXT,
+            method TEXT NOT NULL,
+            conversation_id TEXT,
+            latency_ms INTEGER NOT NULL,
+            prompt_tokens INTEGER,
+            completion_tokens INTEGER,
+            retries INTEGER NOT NULL DEFAULT 0,
+            streamed INTEGER NOT NULL DEFAULT 0,
+            success INTEGER NOT NULL,
+            error TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_request_telemetry_provider
+            ON request_telemetry(provider, created_at);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (50);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v50 completed");
+
+    Ok(())
+}
+
+/// Migration v51: Plugin message outbox
+///
+/// This migration:
+/// 1. Creates `plugin_messages`, the persistent inter-plugin messages not yet
+///    acknowledged by their recipient, and the dead letters among them
+fn migrate_v51(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS plugin_messages (
+            id TEXT PRIMARY KEY,
+            recipient TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            topic TEXT,
+            method TEXT NOT NULL,
+            params TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            require_ack INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'pending',
+            reason TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_plugin_messages_recipient
+            ON plugin_messages(recipient, status);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (51);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v51 completed");
+
+    Ok(())
+}
+
+/// Migration v52: Audit plugin network requests
+///
+/// This migration:
+/// 1. Rebuilds `actions_audit` to allow the `plugin_request` kind, the
+///    `http.fetch` calls made by plugins
+fn migrate_v52(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        -- Recreate actions_audit with the extended kind check
+        CREATE TABLE actions_audit_new (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL CHECK(kind IN ('tool_call', 'file_write', 'shell_command', 'db_query', 'git_operation', 'plugin_request')),
+            name TEXT NOT NULL,
+            conversation_id TEXT,
+            params TEXT NOT NULL DEFAULT '{}',
+            status TEXT NOT NULL CHECK(status IN ('success', 'error', 'denied', 'blocked')),
+            error TEXT,
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL DEFAULT 0
+        );
+
+        INSERT INTO actions_audit_new (id, kind, name, conversation_id, params, status, error,
+                                       started_at, finished_at, duration_ms)
+            SELECT id, kind, name, conversation_id, params, status, error, started_at, finished_at, duration_ms
+            FROM actions_audit;
+
+        DROP TABLE actions_audit;
+        ALTER TABLE actions_audit_new RENAME TO actions_audit;
+
+        -- Indexes
+        CREATE INDEX IF NOT EXISTS idx_actions_audit_conversation ON actions_audit(conversation_id);
+        CREATE INDEX IF NOT EXISTS idx_actions_audit_started ON actions_audit(started_at);
+        CREATE INDEX IF NOT EXISTS idx_actions_audit_kind ON actions_audit(kind);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (52);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v52 completed");
+
+    Ok(())
+}
+
+/// Migration v53: Provider profiles
+///
+/// This migration:
+/// 1. Creates `provider_profiles`, typed LLM provider configurations with
+///    their model list and default generation parameters; API keys stay in
+///    the keychain
+fn migrate_v53(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS provider_profiles (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK(kind IN ('anthropic', 'openai', 'gemini', 'ollama')),
+            base_url TEXT,
+            models TEXT NOT NULL DEFAULT '[]',
+            default_model TEXT,
+            defaults TEXT NOT NULL DEFAULT '{}',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (53);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v53 completed");
+
+    Ok(())
+}
+
+/// Migration v54: OpenAI-compatible providers
+///
+/// This migration:
+/// 1. Allows the `openai-compatible` kind in `provider_profiles`
+/// 2. Adds `capabilities`, the context window and vision support detected
+///    per model from the server's model list
+fn migrate_v54(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE provider_profiles_new (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK(kind IN ('anthropic', 'openai', 'gemini', 'ollama', 'openai-compatible')),
+            base_url TEXT,
+            models TEXT NOT NULL DEFAULT '[]',
+            default_model TEXT,
+            defaults TEXT NOT NULL DEFAULT '{}',
+            capabilities TEXT NOT NULL DEFAULT '{}',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO provider_profiles_new (id, name, kind, base_url, models, default_model, defaults,
+                                           enabled, created_at, updated_at)
+            SELECT id, name, kind, base_url, models, default_model, defaults, enabled, created_at, updated_at
+            FROM provider_profiles;
+
+        DROP TABLE provider_profiles;
+        ALTER TABLE provider_profiles_new RENAME TO provider_profiles;
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (54);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v54 completed");
+
+    Ok(())
+}
+
+/// Migration v55: Conversation system prompts
+///
+/// This migration:
+/// 1. Creates `conversation_system_prompts`, the versions of each
+///    conversation's own system prompt; the newest is the active one
+fn migrate_v55(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversation_system_prompts (
+            id TEXT PRIMARY KEY,
+            conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            version INTEGER NOT NULL,
+            prompt TEXT,
+            created_at TEXT NOT NULL,
+            UNIQUE(conversation_id, version)
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (55);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v55 completed");
+
+    Ok(())
+}
+
+/// Migration v56: Conversation generation parameters
+///
+/// This migration:
+/// 1. Creates `conversation_generation_params`, the temperature, top P,
+///    max tokens, stop sequences and seed a conversation's chats default to
+fn migrate_v56(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversation_generation_params (
+            conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+            params TEXT NOT NULL DEFAULT '{}',
+            updated_at TEXT NOT NULL
+        );
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (56);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v56 completed");
+
+    Ok(())
+}
+
+/// Migration v57: Message feedback
+///
+/// This migration:
+/// 1. Creates `message_feedback`, one thumbs up/down rating with an optional
+///    comment per assistant message
+/// 2. Indexes it by conversation and by the time feedback was last changed
+fn migrate_v57(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS message_feedback (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL UNIQUE REFERENCES messages(id) ON DELETE CASCADE,
+            conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            rating TEXT NOT NULL CHECK(rating IN ('up', 'down')),
+            comment TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_message_feedback_conversation ON message_feedback(conversation_id);
+        CREATE INDEX IF NOT EXISTS idx_message_feedback_updated ON message_feedback(updated_at);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (57);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v57 completed");
+
+    Ok(())
+}
+
+/// Migration v58: Database health checks
+///
+/// This migration:
+/// 1. Creates `db_health_reports` for the results of the integrity check
+/// 2. Adds a weekly `integrity_check` system job
+fn migrate_v58(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS db_health_reports (
+            id TEXT PRIMARY KEY,
+            checked_at TEXT NOT NULL,
+            healthy INTEGER NOT NULL,
+            report TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_db_health_reports_checked ON db_health_reports(checked_at);
+
+        -- Weekly integrity check
+        INSERT OR IGNORE INTO cron_jobs (id, name, schedule, job_type, config, enabled)
+        VALUES ('system-integrity-check', 'Check database health', '0 4 * * 0', 'system',
+                '{"target":"integrity_check","params":{}}', 1);
+
+        -- Record migration
+        INSERT INTO schema_migrations (version) VALUES (58);
+        "#,
+    )?;
+
+    tracing::info!("Database migration v58 completed");
+
+    Ok(())
+}
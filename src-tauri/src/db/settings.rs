@@ -0,0 +1,40 @@
+//! Application settings
+//!
+//! User preferences that are not tied to a feature table are kept as text in
+//! the `app_settings` key/value table.
+
+use rusqlite::{Connection, OptionalExtension};
+
+/// Stored value of a setting, if any
+pub fn get(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM app_settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Store a setting, replacing the previous value
+pub fn set(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO app_settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        [key, value],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_get_set() {
+        let conn = test_conn();
+
+        assert_eq!(get(&conn, "theme").unwrap(), None);
+        set(&conn, "theme", "dark").unwrap();
+        set(&conn, "theme", "light").unwrap();
+        assert_eq!(get(&conn, "theme").unwrap().as_deref(), Some("light"));
+    }
+}
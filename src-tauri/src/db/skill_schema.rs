@@ -0,0 +1,173 @@
+//! Skill input and output schemas
+//!
+//! A skill may declare JSON Schemas for the arguments it accepts and for the
+//! structured output the model must return. Both are stored as JSON text
+//! alongside the skill. `execute_skill` checks the input against the input
+//! schema before dispatching to the agent runtime, and parses and checks the
+//! model's reply against the output schema afterwards.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::Value;
+
+/// Largest stored schema, in bytes
+const MAX_SCHEMA_BYTES: usize = 64 * 1024;
+
+/// Violations listed in an error before the rest are counted
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// Parsed input and output schemas of a skill
+#[derive(Debug, Default)]
+pub struct SkillSchemas {
+    pub input: Option<Value>,
+    pub output: Option<Value>,
+}
+
+/// Check a schema submitted for a skill; blank schemas are stored as None
+pub fn normalize(field: &str, schema: Option<String>) -> Result<Option<String>, String> {
+    let Some(schema) = schema.filter(|s| !s.trim().is_empty()) else {
+        return Ok(None);
+    };
+    if schema.len() > MAX_SCHEMA_BYTES {
+        return Err(format!("{} must be 64KB or less", field));
+    }
+    let value: Value = serde_json::from_str(&schema).map_err(|e| format!("{} is not valid JSON: {}", field, e))?;
+    jsonschema::validator_for(&value).map_err(|e| format!("{} is not a valid JSON Schema: {}", field, e))?;
+    Ok(Some(schema))
+}
+
+/// Load a skill's schemas; a skill that is not stored has none
+pub fn load(conn: &Connection, skill_id: &str) -> Result<SkillSchemas, String> {
+    let stored: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT input_schema, output_schema FROM skills WHERE id = ?1",
+            [skill_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some((input, output)) = stored else {
+        return Ok(SkillSchemas::default());
+    };
+
+    let parse = |schema: Option<String>| -> Result<Option<Value>, String> {
+        schema
+            .map(|s| serde_json::from_str(&s).map_err(|e| format!("Stored skill schema is invalid: {}", e)))
+            .transpose()
+    };
+    Ok(SkillSchemas { input: parse(input)?, output: parse(output)? })
+}
+
+/// Validate a value against a schema, listing the violations on failure
+pub fn validate(schema: &Value, instance: &Value) -> Result<(), String> {
+    let validator = jsonschema::validator_for(schema).map_err(|e| format!("Invalid schema: {}", e))?;
+    let errors: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            if path.is_empty() {
+                e.to_string()
+            } else {
+                format!("{}: {}", path, e)
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = errors.iter().take(MAX_REPORTED_ERRORS).cloned().collect::<Vec<_>>().join("; ");
+    if errors.len() > MAX_REPORTED_ERRORS {
+        message.push_str(&format!(" (and {} more)", errors.len() - MAX_REPORTED_ERRORS));
+    }
+    Err(message)
+}
+
+/// Parse skill input as JSON and validate it
+pub fn check_input(schema: &Value, input: &str) -> Result<Value, String> {
+    let value: Value = serde_json::from_str(input).map_err(|e| format!("Skill input must be JSON: {}", e))?;
+    validate(schema, &value).map_err(|e| format!("Invalid skill input: {}", e))?;
+    Ok(value)
+}
+
+/// Extract the JSON value from a model reply, which may wrap it in a
+/// Markdown code fence or surround it with prose
+pub(crate) fn extract_json(reply: &str) -> Option<Value> {
+    let trimmed = reply.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+    if let Some(fenced) = trimmed.split("```").nth(1) {
+        let body = fenced.strip_prefix("json").unwrap_or(fenced);
+        if let Ok(value) = serde_json::from_str(body.trim()) {
+            return Some(value);
+        }
+    }
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    serde_json::from_str(trimmed.get(start..=end)?).ok()
+}
+
+/// Parse the model's reply as JSON and validate it
+pub fn check_output(schema: &Value, reply: &str) -> Result<Value, String> {
+    let value = extract_json(reply).ok_or_else(|| "Skill output is not JSON".to_string())?;
+    validate(schema, &value).map_err(|e| format!("Invalid skill output: {}", e))?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::db::test_support::test_conn;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string" },
+                "days": { "type": "integer", "minimum": 1 }
+            },
+            "required": ["city"]
+        })
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Input schema", None).unwrap(), None);
+        assert_eq!(normalize("Input schema", Some("  ".to_string())).unwrap(), None);
+        assert!(normalize("Input schema", Some(schema().to_string())).unwrap().is_some());
+        assert!(normalize("Input schema", Some("{".to_string())).is_err());
+        assert!(normalize("Input schema", Some(r#"{"type": 5}"#.to_string())).is_err());
+    }
+
+    #[test]
+    fn test_check_input_and_output() {
+        let schema = schema();
+        assert_eq!(check_input(&schema, r#"{"city": "Seoul", "days": 3}"#).unwrap()["city"], "Seoul");
+        assert!(check_input(&schema, "Seoul").unwrap_err().contains("must be JSON"));
+        let error = check_input(&schema, r#"{"days": 0}"#).unwrap_err();
+        assert!(error.contains("city"), "{}", error);
+        assert!(error.contains("/days"), "{}", error);
+
+        let reply = "Here you go:\n```json\n{\"city\": \"Busan\"}\n```";
+        assert_eq!(check_output(&schema, reply).unwrap()["city"], "Busan");
+        assert_eq!(check_output(&schema, "Result: {\"city\": \"Jeju\"} done").unwrap()["city"], "Jeju");
+        assert!(check_output(&schema, "No idea").is_err());
+        assert!(check_output(&schema, r#"{"city": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO skills (id, name, description, prompt, input_schema) VALUES ('weather', 'Weather', '', '', ?1)",
+            [schema().to_string()],
+        )
+        .unwrap();
+
+        let schemas = load(&conn, "weather").unwrap();
+        assert_eq!(schemas.input, Some(schema()));
+        assert_eq!(schemas.output, None);
+        assert!(load(&conn, "missing").unwrap().input.is_none());
+    }
+}
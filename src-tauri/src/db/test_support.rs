@@ -0,0 +1,13 @@
+//! Test support
+//!
+//! Database fixture shared by unit tests across modules. Tests seed only the
+//! rows they need on top of it.
+
+use rusqlite::Connection;
+
+/// In-memory database with every migration applied and no rows
+pub(crate) fn test_conn() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    super::schema::run_migrations(&conn).unwrap();
+    conn
+}
@@ -0,0 +1,268 @@
+//! Command errors
+//!
+//! Every Tauri command rejects with an [`AppError`], which reaches the
+//! frontend as `{ kind, message, details, retryable }`: `kind` says what
+//! went wrong without parsing the message, `details` carries structured
+//! context such as the SQLite error code or the path of a file error, and
+//! `retryable` marks failures that may pass when the same call is repeated,
+//! like a locked database or an agent runtime that is restarting.
+//!
+//! Database, I/O, JSON, HTTP, sidecar, file and security errors convert with
+//! `?`. Commands and the helpers they share return the typed constructors
+//! (`not_found`, `invalid_input`, `permission_denied`, `network`) rather than
+//! formatted strings, so their kind survives; other plain `String` errors
+//! convert as [`ErrorKind::Internal`], and an `AppError` converts back into a
+//! `String` for callers that still return one.
+
+use crate::files::access::{FileError, FileErrorKind};
+use crate::security::SecurityError;
+use crate::sidecar::{CRASHED_ERROR, RESTARTING_ERROR};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+
+/// What kind of failure an [`AppError`] is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Database,
+    /// The database is locked by another connection
+    Busy,
+    NotFound,
+    InvalidInput,
+    PermissionDenied,
+    Io,
+    /// The agent runtime failed or is not running
+    Sidecar,
+    Network,
+    Security,
+    Internal,
+}
+
+/// Error returned by commands
+#[derive(thiserror::Error)]
+pub enum AppError {
+    #[error("{0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Connection failure outside of HTTP, such as an SMTP server
+    #[error("{0}")]
+    Network(String),
+
+    #[error("{message}")]
+    Sidecar { message: String, retryable: bool },
+
+    #[error("{0}")]
+    File(#[from] FileError),
+
+    #[error("{0}")]
+    Security(#[from] SecurityError),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("{0}")]
+    PermissionDenied(String),
+
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(message.into())
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput(message.into())
+    }
+
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        Self::PermissionDenied(message.into())
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::Network(message.into())
+    }
+
+    /// Error of the agent runtime; retryable while it restarts after a crash
+    pub fn sidecar(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let retryable = message.starts_with(CRASHED_ERROR) || message.starts_with(RESTARTING_ERROR);
+        Self::Sidecar { message, retryable }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Database(rusqlite::Error::QueryReturnedNoRows) => ErrorKind::NotFound,
+            Self::Database(e) => match e.sqlite_error_code() {
+                Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => ErrorKind::Busy,
+                _ => ErrorKind::Database,
+            },
+            Self::Io(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+                std::io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+                _ => ErrorKind::Io,
+            },
+            Self::Json(_) | Self::InvalidInput(_) => ErrorKind::InvalidInput,
+            Self::Http(_) | Self::Network(_) => ErrorKind::Network,
+            Self::Sidecar { .. } => ErrorKind::Sidecar,
+            Self::File(e) => match e.kind {
+                FileErrorKind::NotPermitted => ErrorKind::PermissionDenied,
+                FileErrorKind::NotFound => ErrorKind::NotFound,
+                FileErrorKind::InvalidPath
+                | FileErrorKind::InvalidPattern
+                | FileErrorKind::UnsupportedFormat
+                | FileErrorKind::InvalidData => ErrorKind::InvalidInput,
+                FileErrorKind::Io => ErrorKind::Io,
+            },
+            Self::Security(SecurityError::NotFound(_)) => ErrorKind::NotFound,
+            Self::Security(_) => ErrorKind::Security,
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::PermissionDenied(_) => ErrorKind::PermissionDenied,
+            Self::Internal(_) => ErrorKind::Internal,
+        }
+    }
+
+    /// Whether repeating the same call may succeed
+    pub fn retryable(&self) -> bool {
+        match self {
+            Self::Database(_) => self.kind() == ErrorKind::Busy,
+            Self::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::Interrupted | std::io::ErrorKind::WouldBlock
+            ),
+            Self::Http(e) => e.is_timeout() || e.is_connect(),
+            Self::Network(_) => true,
+            Self::Sidecar { retryable, .. } => *retryable,
+            _ => false,
+        }
+    }
+
+    /// Structured context for the frontend, if any
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            Self::Database(rusqlite::Error::SqliteFailure(e, _)) => Some(json!({ "code": e.extended_code })),
+            Self::Json(e) => Some(json!({ "line": e.line(), "column": e.column() })),
+            Self::Http(e) => {
+                let details = json!({
+                    "status": e.status().map(|s| s.as_u16()),
+                    "url": e.url().map(|u| u.to_string()),
+                });
+                Some(details)
+            }
+            Self::File(e) => Some(json!({ "path": e.path, "reason": e.kind })),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("kind", &self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.serialize_field("retryable", &self.retryable())?;
+        state.end()
+    }
+}
+
+/// Prints the error as the frontend receives it, which is what a failing
+/// command logs through `#[tracing::instrument(err(Debug))]`
+impl fmt::Debug for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        Self::Internal(message.to_string())
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_kinds_and_retryable() {
+        let missing = AppError::from(rusqlite::Error::QueryReturnedNoRows);
+        assert_eq!(missing.kind(), ErrorKind::NotFound);
+        assert!(!missing.retryable());
+
+        let busy = rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY), None);
+        let busy = AppError::from(busy);
+        assert_eq!(busy.kind(), ErrorKind::Busy);
+        assert!(busy.retryable());
+
+        let timeout = AppError::from(std::io::Error::new(std::io::ErrorKind::TimedOut, "slow"));
+        assert_eq!(timeout.kind(), ErrorKind::Io);
+        assert!(timeout.retryable());
+
+        let restarting = AppError::sidecar(format!("{}: Agent runtime is restarting", RESTARTING_ERROR));
+        assert!(restarting.retryable());
+        assert!(!AppError::sidecar("Sidecar not initialized").retryable());
+
+        let refused = AppError::network("SMTP server smtp.example.com did not accept the connection");
+        assert_eq!(refused.kind(), ErrorKind::Network);
+        assert!(refused.retryable());
+
+        let denied = AppError::from(FileError::not_permitted(Path::new("/etc/passwd")));
+        assert_eq!(denied.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(AppError::from("boom".to_string()).kind(), ErrorKind::Internal);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let error = AppError::from(FileError::not_found(Path::new("/tmp/missing.txt")));
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "kind": "not_found",
+                "message": "/tmp/missing.txt does not exist",
+                "details": { "path": "/tmp/missing.txt", "reason": "not_found" },
+                "retryable": false,
+            })
+        );
+
+        let invalid = serde_json::to_value(AppError::invalid_input("Name is required")).unwrap();
+        assert_eq!(invalid["kind"], "invalid_input");
+        assert_eq!(invalid["details"], Value::Null);
+        assert_eq!(String::from(AppError::not_found("No such job")), "No such job");
+    }
+}
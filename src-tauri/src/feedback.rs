@@ -0,0 +1,394 @@
+// Message feedback - Thumbs up/down and comments on assistant messages
+//
+// Each assistant message holds at most one rating, optionally with a
+// comment, in `message_feedback`; rating it again replaces the feedback and
+// submitting no rating removes it. `export_feedback` lists the feedback of a
+// period with what produced each answer - the user prompt before it, the
+// provider and model from the message metadata and the conversation system
+// prompt in effect when it was written - plus up/down totals per provider,
+// so prompts and providers that give bad answers can be found and tuned.
+// Content of sensitive conversations is encrypted and left out.
+
+use crate::analytics::AnalyticsPeriod;
+use crate::db::columns;
+use chrono::{DateTime, Duration, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const MAX_COMMENT_LENGTH: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    Up,
+    Down,
+}
+
+impl Rating {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        if value == "up" {
+            Self::Up
+        } else {
+            Self::Down
+        }
+    }
+}
+
+/// Feedback on one assistant message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageFeedback {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub rating: Rating,
+    pub comment: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Rated message with what produced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackEntry {
+    #[serde(flatten)]
+    pub feedback: MessageFeedback,
+    pub conversation_title: String,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// User message the answer replied to; None when encrypted
+    pub prompt: Option<String>,
+    /// The rated answer; None when encrypted
+    pub response: Option<String>,
+    pub system_prompt: Option<String>,
+    pub system_prompt_version: Option<i64>,
+    /// Whether the message belongs to a sensitive conversation
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackTotals {
+    pub up: u64,
+    pub down: u64,
+}
+
+impl FeedbackTotals {
+    fn add(&mut self, rating: Rating) {
+        match rating {
+            Rating::Up => self.up += 1,
+            Rating::Down => self.down += 1,
+        }
+    }
+}
+
+/// Feedback given over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackExport {
+    pub period: AnalyticsPeriod,
+    pub from: String,
+    pub to: String,
+    pub totals: FeedbackTotals,
+    /// Keyed by provider; answers without one are counted under "unknown"
+    pub by_provider: BTreeMap<String, FeedbackTotals>,
+    /// Newest first
+    pub entries: Vec<FeedbackEntry>,
+}
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<MessageFeedback> {
+    Ok(MessageFeedback {
+        message_id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        rating: Rating::parse(&row.get::<_, String>(2)?),
+        comment: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn get(conn: &Connection, message_id: &str) -> Result<Option<MessageFeedback>, String> {
+    conn.query_row(
+        "SELECT message_id, conversation_id, rating, comment, created_at, updated_at
+         FROM message_feedback WHERE message_id = ?1",
+        [message_id],
+        from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Rate an assistant message, replacing earlier feedback; no rating
+/// removes it
+pub fn submit(
+    conn: &Connection,
+    message_id: &str,
+    rating: Option<Rating>,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Option<MessageFeedback>, String> {
+    let comment = comment.map(str::trim).filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > MAX_COMMENT_LENGTH) {
+        return Err(format!("Feedback comments are at most {} characters", MAX_COMMENT_LENGTH));
+    }
+    let (conversation_id, role): (String, String) = conn
+        .query_row("SELECT conversation_id, role FROM messages WHERE id = ?1", [message_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Message not found: {}", message_id))?;
+    if role != "assistant" {
+        return Err("Only assistant messages can be rated".to_string());
+    }
+
+    let Some(rating) = rating else {
+        if comment.is_some() {
+            return Err("A comment needs a rating".to_string());
+        }
+        conn.execute("DELETE FROM message_feedback WHERE message_id = ?1", [message_id])
+            .map_err(|e| e.to_string())?;
+        return Ok(None);
+    };
+
+    let now = now.to_rfc3339();
+    conn.execute(
+        "INSERT INTO message_feedback (id, message_id, conversation_id, rating, comment, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+         ON CONFLICT(message_id) DO UPDATE SET
+            rating = excluded.rating, comment = excluded.comment, updated_at = excluded.updated_at",
+        params![uuid::Uuid::new_v4().to_string(), message_id, conversation_id, rating.as_str(), comment, now],
+    )
+    .map_err(|e| e.to_string())?;
+    get(conn, message_id)
+}
+
+/// Feedback on the messages of a conversation
+pub fn for_conversation(conn: &Connection, conversation_id: &str) -> Result<Vec<MessageFeedback>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT message_id, conversation_id, rating, comment, created_at, updated_at
+             FROM message_feedback WHERE conversation_id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let feedback = stmt
+        .query_map([conversation_id], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(feedback)
+}
+
+/// The user message right before `message_id`, with its encrypted flag
+fn preceding_prompt(conn: &Connection, conversation_id: &str, message_id: &str) -> Result<Option<(String, bool)>, String> {
+    conn.query_row(
+        "SELECT content, is_encrypted FROM messages
+         WHERE conversation_id = ?1 AND role = 'user'
+           AND rowid < (SELECT rowid FROM messages WHERE id = ?2)
+         ORDER BY rowid DESC LIMIT 1",
+        [conversation_id, message_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// The system prompt version in effect when a message was written
+fn system_prompt_at(
+    conn: &Connection,
+    conversation_id: &str,
+    written_at: &str,
+) -> Result<Option<(i64, Option<String>)>, String> {
+    conn.query_row(
+        "SELECT version, prompt FROM conversation_system_prompts
+         WHERE conversation_id = ?1 AND datetime(created_at) <= datetime(?2)
+         ORDER BY version DESC LIMIT 1",
+        [conversation_id, written_at],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Feedback given or changed in the `period` days up to `now`
+pub fn export(conn: &Connection, period: AnalyticsPeriod, now: DateTime<Utc>) -> Result<FeedbackExport, String> {
+    let from = now - Duration::days(period.days());
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.message_id, f.conversation_id, f.rating, f.comment, f.created_at, f.updated_at,
+                    c.title, m.content, m.is_encrypted, m.metadata, m.created_at
+             FROM message_feedback f
+             JOIN messages m ON m.id = f.message_id
+             JOIN conversations c ON c.id = f.conversation_id
+             WHERE f.updated_at >= ?1
+             ORDER BY f.updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([from.to_rfc3339()]).map_err(|e| e.to_string())?;
+
+    let mut totals = FeedbackTotals::default();
+    let mut by_provider: BTreeMap<String, FeedbackTotals> = BTreeMap::new();
+    let mut entries = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let feedback = from_row(row).map_err(|e| e.to_string())?;
+        let encrypted: bool = row.get(8).map_err(|e| e.to_string())?;
+        let metadata: serde_json::Value = columns::get_optional(row, 9)
+            .map_err(|e| e.to_string())?
+            .and_then(|m| serde_json::from_str(&m).ok())
+            .unwrap_or_default();
+        let written_at: String = row.get(10).map_err(|e| e.to_string())?;
+
+        let provider = metadata["provider"].as_str().map(String::from);
+        totals.add(feedback.rating);
+        by_provider
+            .entry(provider.clone().unwrap_or_else(|| "unknown".to_string()))
+            .or_default()
+            .add(feedback.rating);
+
+        let prompt = preceding_prompt(conn, &feedback.conversation_id, &feedback.message_id)?;
+        let system_prompt = system_prompt_at(conn, &feedback.conversation_id, &written_at)?;
+        entries.push(FeedbackEntry {
+            conversation_title: row.get(6).map_err(|e| e.to_string())?,
+            provider,
+            model: metadata["model"].as_str().map(String::from),
+            prompt: prompt.filter(|(_, encrypted)| !encrypted).map(|(content, _)| content),
+            response: if encrypted { None } else { Some(row.get(7).map_err(|e| e.to_string())?) },
+            system_prompt_version: system_prompt.as_ref().map(|(version, _)| *version),
+            system_prompt: system_prompt.and_then(|(_, prompt)| prompt),
+            encrypted,
+            feedback,
+        });
+    }
+
+    Ok(FeedbackExport {
+        period,
+        from: from.to_rfc3339(),
+        to: now.to_rfc3339(),
+        totals,
+        by_provider,
+        entries,
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Rate an assistant message; no rating removes its feedback
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn submit_feedback(
+    db: tauri::State<'_, crate::db::DbState>,
+    message_id: String,
+    rating: Option<Rating>,
+    comment: Option<String>,
+) -> Result<Option<MessageFeedback>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(submit(&conn, &message_id, rating, comment.as_deref(), Utc::now())?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_conversation_feedback(
+    db: tauri::State<'_, crate::db::DbState>,
+    conversation_id: String,
+) -> Result<Vec<MessageFeedback>, AppError> {
+    let conn = db.read()?;
+    Ok(for_conversation(&conn, &conversation_id)?)
+}
+
+/// Feedback of a period with the prompts, providers and models behind it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn export_feedback(
+    db: tauri::State<'_, crate::db::DbState>,
+    period: AnalyticsPeriod,
+) -> Result<FeedbackExport, AppError> {
+    let conn = db.read()?;
+    Ok(export(&conn, period, Utc::now())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Feedback')", []).unwrap();
+        conn
+    }
+
+    fn add_message(conn: &Connection, id: &str, role: &str, content: &str, provider: Option<&str>) {
+        let metadata = provider.map(|p| columns::seal(&serde_json::json!({ "provider": p, "model": "m1" }).to_string()).unwrap());
+        conn.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at)
+             VALUES (?1, 'c1', ?2, ?3, ?4, ?5)",
+            params![id, role, content, metadata, Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_submit_replaces_and_removes() {
+        let conn = setup();
+        add_message(&conn, "u1", "user", "Hi", None);
+        add_message(&conn, "a1", "assistant", "Hello", Some("openai"));
+        let now = Utc::now();
+
+        assert!(submit(&conn, "u1", Some(Rating::Up), None, now).unwrap_err().contains("assistant"));
+        assert!(submit(&conn, "missing", Some(Rating::Up), None, now).is_err());
+        assert!(submit(&conn, "a1", None, Some("why"), now).is_err());
+
+        let first = submit(&conn, "a1", Some(Rating::Up), Some("  "), now).unwrap().unwrap();
+        assert_eq!((first.rating, first.comment), (Rating::Up, None));
+        let later = now + Duration::minutes(1);
+        let changed = submit(&conn, "a1", Some(Rating::Down), Some("Too long"), later).unwrap().unwrap();
+        assert_eq!((changed.rating, changed.comment.as_deref()), (Rating::Down, Some("Too long")));
+        assert_eq!(changed.created_at, first.created_at);
+        assert_eq!(for_conversation(&conn, "c1").unwrap(), vec![changed]);
+
+        assert_eq!(submit(&conn, "a1", None, None, later).unwrap(), None);
+        assert!(for_conversation(&conn, "c1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_export_with_context() {
+        let conn = setup();
+        crate::conversations::system_prompt::set(&conn, "c1", Some("Be brief")).unwrap();
+        add_message(&conn, "u1", "user", "Summarize this", None);
+        add_message(&conn, "a1", "assistant", "A summary", Some("openai"));
+        add_message(&conn, "u2", "user", "Translate it", None);
+        add_message(&conn, "a2", "assistant", "A translation", Some("ollama"));
+        add_message(&conn, "a3", "assistant", "Another", None);
+        let now = Utc::now();
+
+        submit(&conn, "a1", Some(Rating::Down), Some("Missed the point"), now).unwrap();
+        submit(&conn, "a2", Some(Rating::Up), None, now - Duration::days(1)).unwrap();
+        // Outside a week
+        submit(&conn, "a3", Some(Rating::Up), None, now - Duration::days(10)).unwrap();
+
+        let week = export(&conn, AnalyticsPeriod::Week, now).unwrap();
+        assert_eq!(week.totals, FeedbackTotals { up: 1, down: 1 });
+        assert_eq!(week.by_provider["openai"], FeedbackTotals { up: 0, down: 1 });
+        assert_eq!(week.entries.len(), 2);
+
+        let entry = &week.entries[0];
+        assert_eq!(entry.feedback.message_id, "a1");
+        assert_eq!((entry.prompt.as_deref(), entry.response.as_deref()), (Some("Summarize this"), Some("A summary")));
+        assert_eq!((entry.provider.as_deref(), entry.model.as_deref()), (Some("openai"), Some("m1")));
+        assert_eq!((entry.system_prompt_version, entry.system_prompt.as_deref()), (Some(1), Some("Be brief")));
+        assert_eq!(week.entries[1].prompt.as_deref(), Some("Translate it"));
+
+        let month = export(&conn, AnalyticsPeriod::Month, now).unwrap();
+        assert_eq!(month.by_provider["unknown"], FeedbackTotals { up: 1, down: 0 });
+    }
+}
@@ -0,0 +1,229 @@
+//! Permission-checked path resolution
+//!
+//! Paths are canonicalized before they are checked against the stored
+//! folder permissions, so `..` components and symlinks cannot reach outside
+//! a permitted folder. A path that does not exist yet is resolved through
+//! its closest existing ancestor.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Kind of file access being checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Needs a `read` or `readwrite` permission
+    Read,
+    /// Needs a `readwrite` permission
+    Write,
+}
+
+/// Why a file command failed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileErrorKind {
+    /// No folder permission covers the path, or it is read-only
+    NotPermitted,
+    /// The path is permitted but does not exist
+    NotFound,
+    InvalidPath,
+    /// A glob or search pattern could not be parsed
+    InvalidPattern,
+    /// The file is not in a format that can be read, or is damaged
+    UnsupportedFormat,
+    /// Data passed to the command could not be decoded
+    InvalidData,
+    Io,
+}
+
+/// Error returned by file commands
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
+#[error("{message}")]
+pub struct FileError {
+    pub kind: FileErrorKind,
+    pub path: String,
+    pub message: String,
+}
+
+impl FileError {
+    pub fn new(kind: FileErrorKind, path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            path: path.display().to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn not_permitted(path: &Path) -> Self {
+        Self::new(FileErrorKind::NotPermitted, path, format!("No permission to access {}", path.display()))
+    }
+
+    pub fn not_found(path: &Path) -> Self {
+        Self::new(FileErrorKind::NotFound, path, format!("{} does not exist", path.display()))
+    }
+
+    /// Map an I/O error, keeping "not found" and "permission denied" apart
+    pub fn io(path: &Path, error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => Self::not_found(path),
+            std::io::ErrorKind::PermissionDenied => Self::new(
+                FileErrorKind::NotPermitted,
+                path,
+                format!("The system denied access to {}: {}", path.display(), error),
+            ),
+            _ => Self::new(FileErrorKind::Io, path, format!("{}: {}", path.display(), error)),
+        }
+    }
+
+    /// Error for failures outside the file system, such as a poisoned lock
+    pub fn internal(path: &Path, message: impl Into<String>) -> Self {
+        Self::new(FileErrorKind::Io, path, message)
+    }
+}
+
+/// Resolve `path` to its canonical form if a folder permission allows `access`
+///
+/// Checks permissions before existence, so paths outside permitted folders
+/// are reported as not permitted whether or not they exist.
+pub fn resolve(conn: &Connection, path: &str, access: Access) -> Result<PathBuf, FileError> {
+    let requested = Path::new(path);
+    if path.trim().is_empty() || !requested.is_absolute() {
+        return Err(FileError::new(FileErrorKind::InvalidPath, requested, format!("Path must be absolute: {}", path)));
+    }
+
+    // Only existing paths are canonicalized, which is what resolves `..`
+    if !requested.exists() && requested.components().any(|c| c == Component::ParentDir) {
+        return Err(FileError::new(FileErrorKind::InvalidPath, requested, "Paths that do not exist may not contain '..'"));
+    }
+
+    // Split into the closest existing ancestor and the components below it.
+    // `symlink_metadata` does not follow the last component, so a dangling
+    // symlink is found here instead of being re-appended as a missing name
+    // that a later write would follow out of the permitted folder.
+    let mut existing = requested;
+    let mut missing = Vec::new();
+    loop {
+        match std::fs::symlink_metadata(existing) {
+            Ok(meta) if meta.file_type().is_symlink() && !existing.exists() => {
+                return Err(FileError::new(
+                    FileErrorKind::NotPermitted,
+                    requested,
+                    format!("{} is a symlink to a missing target", existing.display()),
+                ));
+            }
+            Ok(_) => break,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let name = existing.file_name().ok_or_else(|| FileError::not_permitted(requested))?;
+                missing.push(name.to_os_string());
+                existing = existing.parent().ok_or_else(|| FileError::not_permitted(requested))?;
+            }
+            Err(e) => return Err(FileError::io(requested, e)),
+        }
+    }
+
+    let canonical = existing.canonicalize().map_err(|e| FileError::io(requested, e))?;
+    let level = crate::db::folder_permission_level(conn, &canonical)
+        .map_err(|e| FileError::internal(requested, e))?;
+    match (level.as_deref(), access) {
+        (Some("readwrite"), _) | (Some(_), Access::Read) => {}
+        (Some(_), Access::Write) => {
+            return Err(FileError::new(
+                FileErrorKind::NotPermitted,
+                requested,
+                format!("No write permission for {}", requested.display()),
+            ));
+        }
+        (None, _) => return Err(FileError::not_permitted(requested)),
+    }
+
+    let resolved = missing.iter().rev().fold(canonical, |path, name| path.join(name));
+    if !missing.is_empty() && (access == Access::Read || missing.len() > 1) {
+        return Err(FileError::not_found(requested));
+    }
+    Ok(resolved)
+}
+
+/// Resolve a path using the app database's folder permissions
+pub fn resolve_with(db: &crate::db::DbState, path: &str, access: Access) -> Result<PathBuf, FileError> {
+    let conn = db.conn.lock().map_err(|e| FileError::internal(Path::new(path), e.to_string()))?;
+    resolve(&conn, path, access)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup(level: &str) -> (Connection, tempfile::TempDir) {
+        let conn = test_conn();
+        let dir = tempfile::tempdir().unwrap();
+        let permitted = dir.path().join("permitted");
+        std::fs::create_dir(&permitted).unwrap();
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level, created_at) VALUES ('p1', ?1, ?2, '')",
+            [permitted.to_str().unwrap(), level],
+        )
+        .unwrap();
+        (conn, dir)
+    }
+
+    fn path(dir: &tempfile::TempDir, rel: &str) -> String {
+        dir.path().join(rel).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_resolve_checks_permission_before_existence() {
+        let (conn, dir) = setup("read");
+        std::fs::write(dir.path().join("permitted/a.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("outside.txt"), "b").unwrap();
+
+        assert!(resolve(&conn, &path(&dir, "permitted/a.txt"), Access::Read).is_ok());
+        let kind = |p: &str, access| resolve(&conn, &path(&dir, p), access).unwrap_err().kind;
+        assert_eq!(kind("permitted/missing.txt", Access::Read), FileErrorKind::NotFound);
+        assert_eq!(kind("outside.txt", Access::Read), FileErrorKind::NotPermitted);
+        assert_eq!(kind("missing.txt", Access::Read), FileErrorKind::NotPermitted);
+        assert_eq!(kind("permitted/../outside.txt", Access::Read), FileErrorKind::NotPermitted);
+        assert_eq!(kind("permitted/a.txt", Access::Write), FileErrorKind::NotPermitted);
+        assert_eq!(
+            resolve(&conn, "relative.txt", Access::Read).unwrap_err().kind,
+            FileErrorKind::InvalidPath
+        );
+    }
+
+    #[test]
+    fn test_resolve_write_targets() {
+        let (conn, dir) = setup("readwrite");
+
+        let resolved = resolve(&conn, &path(&dir, "permitted/new.txt"), Access::Write).unwrap();
+        assert!(resolved.ends_with("permitted/new.txt"));
+        let kind = |p: &str| resolve(&conn, &path(&dir, p), Access::Write).unwrap_err().kind;
+        assert_eq!(kind("permitted/sub/new.txt"), FileErrorKind::NotFound);
+        assert_eq!(kind("permitted/../new/../x.txt"), FileErrorKind::InvalidPath);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_symlink_escape() {
+        let (conn, dir) = setup("readwrite");
+        std::fs::write(dir.path().join("secret.txt"), "s").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), dir.path().join("permitted/link.txt")).unwrap();
+
+        let error = resolve(&conn, &path(&dir, "permitted/link.txt"), Access::Read).unwrap_err();
+        assert_eq!(error.kind, FileErrorKind::NotPermitted);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_write_through_dangling_symlink() {
+        let (conn, dir) = setup("readwrite");
+        let target = dir.path().join("escaped.txt");
+        std::os::unix::fs::symlink(&target, dir.path().join("permitted/dangling.txt")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("gone"), dir.path().join("permitted/dangling_dir")).unwrap();
+
+        for p in ["permitted/dangling.txt", "permitted/dangling_dir/new.txt"] {
+            let error = resolve(&conn, &path(&dir, p), Access::Write).unwrap_err();
+            assert_eq!(error.kind, FileErrorKind::NotPermitted, "{}", p);
+        }
+        assert!(!target.exists());
+    }
+}
@@ -0,0 +1,322 @@
+//! Binary File Access
+//!
+//! Byte-range reads and writes for files that are not UTF-8 text, such as
+//! PDFs, images and archives. Data crosses the IPC boundary as base64, in
+//! chunks no larger than the caps below. Large files can also be streamed to
+//! the frontend as `files://chunk` events.
+
+use super::access::{resolve_with, Access, FileError, FileErrorKind};
+use crate::db::DbState;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use tauri::Emitter;
+
+/// Event carrying a `FileStreamChunk`
+pub const CHUNK_EVENT: &str = "files://chunk";
+
+/// Bytes read when the caller does not set a length
+const DEFAULT_CHUNK_BYTES: u64 = 1024 * 1024;
+
+/// Largest chunk a single read or write may carry
+const MAX_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Largest file that may be streamed to the frontend
+const MAX_STREAM_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Bytes inspected when sniffing the mime type
+const SNIFF_BYTES: usize = 8192;
+
+/// Mime types for text formats that have no magic number
+const TEXT_EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("json", "application/json"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("svg", "image/svg+xml"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+];
+
+/// Byte range of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChunk {
+    pub path: String,
+    pub offset: u64,
+    /// Base64-encoded bytes
+    pub data: String,
+    /// Number of bytes in `data` once decoded
+    pub len: u64,
+    /// Size of the whole file
+    pub total_size: u64,
+    pub eof: bool,
+    pub mime_type: String,
+}
+
+/// Streamed file, announced before its chunks are emitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStream {
+    pub stream_id: String,
+    pub path: String,
+    pub total_size: u64,
+    pub mime_type: String,
+}
+
+/// Chunk of a streamed file; `error` is set if the stream stopped early
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStreamChunk {
+    pub stream_id: String,
+    pub offset: u64,
+    /// Base64-encoded bytes
+    pub data: String,
+    pub eof: bool,
+    pub error: Option<String>,
+}
+
+/// Guess a mime type from a file's leading bytes, then its extension
+pub fn sniff_mime(path: &Path, head: &[u8]) -> String {
+    if let Some(kind) = infer::get(head) {
+        return kind.mime_type().to_string();
+    }
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if let Some((_, mime)) = TEXT_EXTENSIONS.iter().find(|(ext, _)| *ext == extension) {
+        return mime.to_string();
+    }
+    // A multi-byte character may be cut at the end of the sniffed bytes
+    match std::str::from_utf8(head) {
+        Ok(_) => "text/plain".to_string(),
+        Err(e) if e.error_len().is_none() => "text/plain".to_string(),
+        Err(_) => "application/octet-stream".to_string(),
+    }
+}
+
+fn too_large(path: &Path, what: &str, size: u64, cap: u64) -> FileError {
+    FileError::new(
+        FileErrorKind::Io,
+        path,
+        format!("{} of {} bytes exceeds the {} byte limit", what, size, cap),
+    )
+}
+
+fn mime_of(path: &Path, file: &mut std::fs::File) -> Result<String, FileError> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    Read::by_ref(file)
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| FileError::io(path, e))?;
+    Ok(sniff_mime(path, &head))
+}
+
+/// Read up to `len` bytes starting at `offset`
+pub fn read_chunk(path: &Path, offset: u64, len: u64) -> Result<FileChunk, FileError> {
+    if len > MAX_CHUNK_BYTES {
+        return Err(too_large(path, "Chunk", len, MAX_CHUNK_BYTES));
+    }
+    let mut file = std::fs::File::open(path).map_err(|e| FileError::io(path, e))?;
+    let total_size = file.metadata().map_err(|e| FileError::io(path, e))?.len();
+    let mime_type = mime_of(path, &mut file)?;
+
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(offset)).map_err(|e| FileError::io(path, e))?;
+    file.take(len).read_to_end(&mut data).map_err(|e| FileError::io(path, e))?;
+
+    Ok(FileChunk {
+        path: path.display().to_string(),
+        offset,
+        len: data.len() as u64,
+        data: STANDARD.encode(&data),
+        total_size,
+        eof: offset + data.len() as u64 >= total_size,
+        mime_type,
+    })
+}
+
+/// Write `data` at `offset`, or replace the file when `offset` is None
+///
+/// Writing at an offset past the end of the file is rejected so chunked
+/// uploads cannot leave holes.
+pub fn write_chunk(path: &Path, data: &[u8], offset: Option<u64>) -> Result<u64, FileError> {
+    if data.len() as u64 > MAX_CHUNK_BYTES {
+        return Err(too_large(path, "Chunk", data.len() as u64, MAX_CHUNK_BYTES));
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(offset.is_none())
+        .open(path)
+        .map_err(|e| FileError::io(path, e))?;
+
+    if let Some(offset) = offset {
+        let size = file.metadata().map_err(|e| FileError::io(path, e))?.len();
+        if offset > size {
+            return Err(FileError::new(
+                FileErrorKind::InvalidPath,
+                path,
+                format!("Offset {} is past the end of {} ({} bytes)", offset, path.display(), size),
+            ));
+        }
+        file.seek(SeekFrom::Start(offset)).map_err(|e| FileError::io(path, e))?;
+    }
+    file.write_all(data).map_err(|e| FileError::io(path, e))?;
+    file.metadata().map(|m| m.len()).map_err(|e| FileError::io(path, e))
+}
+
+/// Emit a file as `CHUNK_EVENT` events, ending with an `eof` chunk
+fn stream_chunks(app: &tauri::AppHandle, path: &Path, stream_id: &str, chunk_size: u64) -> Result<(), FileError> {
+    let mut file = std::fs::File::open(path).map_err(|e| FileError::io(path, e))?;
+    let mut buf = vec![0u8; chunk_size as usize];
+    let mut offset = 0u64;
+    loop {
+        let n = file.read(&mut buf).map_err(|e| FileError::io(path, e))?;
+        let _ = app.emit(
+            CHUNK_EVENT,
+            FileStreamChunk {
+                stream_id: stream_id.to_string(),
+                offset,
+                data: STANDARD.encode(&buf[..n]),
+                eof: n == 0,
+                error: None,
+            },
+        );
+        if n == 0 {
+            return Ok(());
+        }
+        offset += n as u64;
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Read a byte range of a file as base64
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn read_file_bytes(
+    db: tauri::State<'_, DbState>,
+    path: String,
+    offset: Option<u64>,
+    len: Option<u64>,
+) -> Result<FileChunk, AppError> {
+    let path = resolve_with(&db, &path, Access::Read)?;
+    Ok(read_chunk(&path, offset.unwrap_or(0), len.unwrap_or(DEFAULT_CHUNK_BYTES))?)
+}
+
+/// Decode base64 data sent for `path`
+fn decode_data(path: &Path, data: &str) -> Result<Vec<u8>, FileError> {
+    STANDARD
+        .decode(data.as_bytes())
+        .map_err(|e| FileError::new(FileErrorKind::InvalidData, path, format!("Invalid base64 data: {}", e)))
+}
+
+/// Write base64 data to a file, at `offset` or replacing it; returns the new size
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn write_file_bytes(
+    db: tauri::State<'_, DbState>,
+    path: String,
+    data: String,
+    offset: Option<u64>,
+) -> Result<u64, AppError> {
+    let path = resolve_with(&db, &path, Access::Write)?;
+    let bytes = decode_data(&path, &data)?;
+    Ok(write_chunk(&path, &bytes, offset)?)
+}
+
+/// Stream a file to the frontend as `files://chunk` events
+///
+/// Returns once the stream is set up; chunks follow in the background.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn stream_file(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, DbState>,
+    path: String,
+    chunk_size: Option<u64>,
+) -> Result<FileStream, AppError> {
+    let path = resolve_with(&db, &path, Access::Read)?;
+    let mut file = std::fs::File::open(&path).map_err(|e| FileError::io(&path, e))?;
+    let total_size = file.metadata().map_err(|e| FileError::io(&path, e))?.len();
+    if total_size > MAX_STREAM_BYTES {
+        return Err(too_large(&path, "File", total_size, MAX_STREAM_BYTES).into());
+    }
+
+    let stream = FileStream {
+        stream_id: uuid::Uuid::new_v4().to_string(),
+        path: path.display().to_string(),
+        total_size,
+        mime_type: mime_of(&path, &mut file)?,
+    };
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_BYTES).clamp(1, MAX_CHUNK_BYTES);
+    let stream_id = stream.stream_id.clone();
+
+    std::thread::spawn(move || {
+        if let Err(e) = stream_chunks(&app, &path, &stream_id, chunk_size) {
+            let _ = app.emit(
+                CHUNK_EVENT,
+                FileStreamChunk {
+                    stream_id,
+                    offset: 0,
+                    data: String::new(),
+                    eof: true,
+                    error: Some(e.message),
+                },
+            );
+        }
+    });
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_mime() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0];
+        assert_eq!(sniff_mime(Path::new("a.bin"), &png), "image/png");
+        assert_eq!(sniff_mime(Path::new("a.pdf"), b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(sniff_mime(Path::new("notes.md"), b"# Notes"), "text/markdown");
+        assert_eq!(sniff_mime(Path::new("notes"), "caf\u{e9}".as_bytes()), "text/plain");
+        assert_eq!(sniff_mime(Path::new("blob"), &[0xff, 0xfe, 0x00, 0xc3]), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_read_and_write_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(write_chunk(&path, &bytes[..100], None).unwrap(), 100);
+        assert_eq!(write_chunk(&path, &bytes[100..], Some(100)).unwrap(), 256);
+        assert_eq!(write_chunk(&path, &bytes, Some(300)).unwrap_err().kind, FileErrorKind::InvalidPath);
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+        let chunk = read_chunk(&path, 250, 10).unwrap();
+        assert_eq!((chunk.len, chunk.total_size, chunk.eof), (6, 256, true));
+        assert_eq!(STANDARD.decode(chunk.data).unwrap(), &bytes[250..]);
+        assert!(!read_chunk(&path, 0, 10).unwrap().eof);
+        assert_eq!(read_chunk(&path, 0, MAX_CHUNK_BYTES + 1).unwrap_err().kind, FileErrorKind::Io);
+
+        assert_eq!(decode_data(&path, &STANDARD.encode(&bytes)).unwrap(), bytes);
+        let error = decode_data(&path, "not base64!").unwrap_err();
+        assert_eq!(error.kind, FileErrorKind::InvalidData);
+        assert_eq!(AppError::from(error).kind(), crate::error::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,68 @@
+//! File Commands
+//!
+//! Every command resolves its path through `access::resolve` first, so it
+//! only touches folders with a matching folder permission.
+
+use super::access::{resolve_with, Access, FileError};
+use super::listing::{self, DirListing, ListOptions};
+use crate::db::DbState;
+use crate::error::AppError;
+use serde_json::json;
+use std::io::Write;
+use tauri::Manager;
+
+/// Read file content
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn read_file_content(db: tauri::State<'_, DbState>, path: String) -> Result<String, AppError> {
+    let path = resolve_with(&db, &path, Access::Read)?;
+    Ok(std::fs::read_to_string(&path).map_err(|e| FileError::io(&path, e))?)
+}
+
+/// Write file content
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn write_file_content(db: tauri::State<'_, DbState>, path: String, content: String) -> Result<(), AppError> {
+    let path = resolve_with(&db, &path, Access::Write)?;
+    Ok(std::fs::write(&path, content).map_err(|e| FileError::io(&path, e))?)
+}
+
+/// Answer a recipe `file_read` step from the agent runtime
+pub fn read_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = params.get("path").and_then(|v| v.as_str()).ok_or("Missing path")?;
+    let db = app.state::<DbState>();
+    let path = resolve_with(&db, path, Access::Read).map_err(|e| e.message)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| FileError::io(&path, e).message)?;
+    Ok(json!({ "path": path, "content": content }))
+}
+
+/// Answer a recipe `file_write` step from the agent runtime, replacing the
+/// file or appending to it
+pub fn write_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = params.get("path").and_then(|v| v.as_str()).ok_or("Missing path")?;
+    let content = params.get("content").and_then(|v| v.as_str()).ok_or("Missing content")?;
+    let append = params.get("append").and_then(|v| v.as_bool()).unwrap_or(false);
+    let db = app.state::<DbState>();
+    let path = resolve_with(&db, path, Access::Write).map_err(|e| e.message)?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| FileError::io(&path, e).message)?;
+    Ok(json!({ "path": path, "bytes": content.len() }))
+}
+
+/// List a directory as typed entries, optionally recursive, filtered and paged
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_directory(
+    db: tauri::State<'_, DbState>,
+    path: String,
+    options: Option<ListOptions>,
+) -> Result<DirListing, AppError> {
+    let path = resolve_with(&db, &path, Access::Read)?;
+    Ok(listing::list(&path, &options.unwrap_or_default())?)
+}
@@ -0,0 +1,194 @@
+//! Document Text Extraction
+//!
+//! Plain text from PDF, DOCX and XLSX files, keeping the document's
+//! structure: one entry per page for PDF and DOCX, and the cell rows of each
+//! sheet for XLSX. Used by the `extract_document_text` command, and by the
+//! agent tool of the same name so documents can be read into conversations
+//! and the knowledge base.
+
+pub mod office;
+pub mod pdf;
+
+use super::access::{resolve_with, Access, FileError, FileErrorKind};
+use crate::db::DbState;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::Manager;
+
+/// Largest file that will be read for extraction
+const MAX_DOCUMENT_BYTES: u64 = 200 * 1024 * 1024;
+
+/// Document formats text can be extracted from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    Xlsx,
+}
+
+impl DocumentFormat {
+    /// Format of a file, by extension
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "pdf" => Some(Self::Pdf),
+            "docx" | "docm" => Some(Self::Docx),
+            "xlsx" | "xlsm" => Some(Self::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+/// Text of one page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentPage {
+    /// 1-based page number
+    pub number: usize,
+    pub text: String,
+}
+
+/// Cell values of one sheet, row by row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSheet {
+    pub name: String,
+    pub rows: Vec<Vec<String>>,
+    /// Rows past `office::MAX_SHEET_ROWS` were dropped
+    pub truncated: bool,
+}
+
+/// Text extracted from a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedDocument {
+    pub path: String,
+    pub format: DocumentFormat,
+    /// Pages of a PDF or DOCX document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<DocumentPage>,
+    /// Sheets of an XLSX workbook
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sheets: Vec<DocumentSheet>,
+}
+
+impl ExtractedDocument {
+    /// All text as one string: pages separated by blank lines, and sheets
+    /// as tab-separated rows under their names
+    pub fn text(&self) -> String {
+        let pages = self.pages.iter().map(|page| page.text.clone());
+        let sheets = self.sheets.iter().map(|sheet| {
+            let rows: Vec<String> = sheet.rows.iter().map(|row| row.join("\t")).collect();
+            format!("# {}\n{}", sheet.name, rows.join("\n"))
+        });
+        pages.chain(sheets).collect::<Vec<_>>().join("\n\n")
+    }
+}
+
+/// Extract the text of a document from its bytes
+pub fn extract_bytes(data: &[u8], format: DocumentFormat) -> Result<(Vec<DocumentPage>, Vec<DocumentSheet>), String> {
+    let pages = |texts: Vec<String>| {
+        texts
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| DocumentPage { number: i + 1, text })
+            .collect()
+    };
+    match format {
+        DocumentFormat::Pdf => Ok((pages(pdf::extract_pages(data)?), Vec::new())),
+        DocumentFormat::Docx => Ok((pages(office::docx_pages(data)?), Vec::new())),
+        DocumentFormat::Xlsx => {
+            let sheets = office::xlsx_sheets(data)?
+                .into_iter()
+                .map(|sheet| DocumentSheet {
+                    name: sheet.name,
+                    rows: sheet.rows,
+                    truncated: sheet.truncated,
+                })
+                .collect();
+            Ok((Vec::new(), sheets))
+        }
+    }
+}
+
+/// Extract the text of a document file; the path must already be resolved
+pub fn extract(path: &Path) -> Result<ExtractedDocument, FileError> {
+    let format = DocumentFormat::of(path).ok_or_else(|| {
+        FileError::new(
+            FileErrorKind::UnsupportedFormat,
+            path,
+            format!("{} is not a PDF, DOCX or XLSX file", path.display()),
+        )
+    })?;
+    let metadata = std::fs::metadata(path).map_err(|e| FileError::io(path, e))?;
+    if metadata.len() > MAX_DOCUMENT_BYTES {
+        return Err(FileError::new(
+            FileErrorKind::UnsupportedFormat,
+            path,
+            format!("{} is too large to extract ({} bytes)", path.display(), metadata.len()),
+        ));
+    }
+    let data = std::fs::read(path).map_err(|e| FileError::io(path, e))?;
+    let (pages, sheets) = extract_bytes(&data, format)
+        .map_err(|e| FileError::new(FileErrorKind::UnsupportedFormat, path, format!("{}: {}", path.display(), e)))?;
+
+    Ok(ExtractedDocument {
+        path: path.display().to_string(),
+        format,
+        pages,
+        sheets,
+    })
+}
+
+/// Host method behind the agent's `extract_document_text` tool
+pub fn extract_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = params.get("path").and_then(|v| v.as_str()).ok_or("Missing path")?;
+    let db = app.state::<DbState>();
+    let resolved = resolve_with(&db, path, Access::Read).map_err(|e| e.message)?;
+    let document = extract(&resolved).map_err(|e| e.message)?;
+    serde_json::to_value(document).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Extract the text of a PDF, DOCX or XLSX file in a permitted folder
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn extract_document_text(db: tauri::State<'_, DbState>, path: String) -> Result<ExtractedDocument, AppError> {
+    let resolved = resolve_with(&db, &path, Access::Read)?;
+    let document = tokio::task::spawn_blocking(move || extract(&resolved))
+        .await
+        .map_err(|e| FileError::internal(Path::new(&path), e.to_string()))??;
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_of() {
+        assert_eq!(DocumentFormat::of(Path::new("/docs/Report.PDF")), Some(DocumentFormat::Pdf));
+        assert_eq!(DocumentFormat::of(Path::new("/docs/budget.xlsm")), Some(DocumentFormat::Xlsx));
+        assert_eq!(DocumentFormat::of(Path::new("/docs/notes.txt")), None);
+        assert_eq!(DocumentFormat::of(Path::new("/docs/README")), None);
+    }
+
+    #[test]
+    fn test_extract_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, "hello").unwrap();
+        assert_eq!(extract(&notes).unwrap_err().kind, FileErrorKind::UnsupportedFormat);
+
+        let broken = dir.path().join("broken.docx");
+        std::fs::write(&broken, "not a zip").unwrap();
+        let error = extract(&broken).unwrap_err();
+        assert_eq!(error.kind, FileErrorKind::UnsupportedFormat);
+        assert!(error.message.contains("Not an Office document"));
+
+        assert_eq!(extract(&dir.path().join("missing.pdf")).unwrap_err().kind, FileErrorKind::NotFound);
+    }
+}
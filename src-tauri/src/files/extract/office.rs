@@ -0,0 +1,409 @@
+//! Office Open XML text extraction
+//!
+//! DOCX body text split at page breaks (explicit breaks and the breaks Word
+//! recorded when it last laid the document out), and XLSX sheets as rows of
+//! cell values with shared strings resolved. Formulas come out as their
+//! cached values; number formats such as dates are not applied.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Largest uncompressed size of a single archive part
+const MAX_PART_BYTES: u64 = 128 * 1024 * 1024;
+
+/// Rows kept per sheet; the rest are dropped and the sheet marked truncated
+pub const MAX_SHEET_ROWS: usize = 50_000;
+
+/// Columns kept per row
+const MAX_COLUMNS: usize = 1_000;
+
+type Archive<'a> = ZipArchive<Cursor<&'a [u8]>>;
+
+fn open(data: &[u8]) -> Result<Archive<'_>, String> {
+    ZipArchive::new(Cursor::new(data)).map_err(|e| format!("Not an Office document: {}", e))
+}
+
+/// Contents of an archive part, or `None` if it is missing
+fn read_part(archive: &mut Archive, name: &str) -> Result<Option<String>, String> {
+    let entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", name, e)),
+    };
+    if entry.size() > MAX_PART_BYTES {
+        return Err(format!("{} is too large to extract", name));
+    }
+    let mut xml = String::new();
+    entry
+        .take(MAX_PART_BYTES)
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(Some(xml))
+}
+
+fn attribute(element: &BytesStart, local_name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == local_name)
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+/// Character data of a text, CDATA or entity reference event
+fn text_of(event: &Event) -> Option<String> {
+    match event {
+        Event::Text(text) => text.decode().ok().map(|t| t.into_owned()),
+        Event::CData(data) => data.decode().ok().map(|t| t.into_owned()),
+        Event::GeneralRef(reference) => {
+            if let Ok(Some(c)) = reference.resolve_char_ref() {
+                return Some(c.to_string());
+            }
+            let entity = match reference.decode().ok()?.as_ref() {
+                "amp" => "&",
+                "lt" => "<",
+                "gt" => ">",
+                "quot" => "\"",
+                "apos" => "'",
+                _ => return None,
+            };
+            Some(entity.to_string())
+        }
+        _ => None,
+    }
+}
+
+// ============================================================================
+// DOCX
+// ============================================================================
+
+/// Text of each page of a DOCX document
+pub fn docx_pages(data: &[u8]) -> Result<Vec<String>, String> {
+    let mut archive = open(data)?;
+    let xml = read_part(&mut archive, "word/document.xml")?.ok_or("Not a Word document: word/document.xml is missing")?;
+
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut in_text = false;
+    // Tab stops inside paragraph properties are not text
+    let mut in_properties = false;
+
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid document XML: {}", e))?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"t" => in_text = true,
+                b"pPr" => in_properties = true,
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"pPr" => in_properties = false,
+                b"p" => page.push('\n'),
+                // Cells of a row on one line, separated by tabs
+                b"tc" => {
+                    if page.ends_with('\n') {
+                        page.pop();
+                    }
+                    page.push('\t');
+                }
+                b"tr" => {
+                    if page.ends_with('\t') {
+                        page.pop();
+                    }
+                    page.push('\n');
+                }
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"tab" if !in_properties => page.push('\t'),
+                b"br" if attribute(e, b"type").as_deref() == Some("page") => {
+                    pages.push(std::mem::take(&mut page));
+                }
+                b"br" | b"cr" => page.push('\n'),
+                b"lastRenderedPageBreak" if !page.trim().is_empty() => {
+                    pages.push(std::mem::take(&mut page));
+                }
+                _ => {}
+            },
+            _ if in_text => {
+                if let Some(text) = text_of(&event) {
+                    page.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    pages.push(page);
+
+    let mut pages: Vec<String> = pages.iter().map(|p| p.trim().to_string()).collect();
+    // Breaks right before a page break leave empty pages behind
+    pages.retain(|p| !p.is_empty());
+    if pages.is_empty() {
+        pages.push(String::new());
+    }
+    Ok(pages)
+}
+
+// ============================================================================
+// XLSX
+// ============================================================================
+
+/// Cell values of one worksheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub rows: Vec<Vec<String>>,
+    pub truncated: bool,
+}
+
+fn shared_strings(xml: &str) -> Result<Vec<String>, String> {
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic runs (furigana) repeat the text
+    let mut in_phonetic = false;
+
+    let mut reader = Reader::from_str(xml);
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid shared strings: {}", e))?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"t" => in_text = !in_phonetic,
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                b"si" => strings.push(std::mem::take(&mut current)),
+                _ => {}
+            },
+            _ if in_text => {
+                if let Some(text) = text_of(&event) {
+                    current.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(strings)
+}
+
+/// Zero-based column of a cell reference such as `AB12`
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference.bytes().take_while(u8::is_ascii_alphabetic).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let number = letters
+        .iter()
+        .fold(0usize, |acc, b| acc.saturating_mul(26).saturating_add(usize::from(b.to_ascii_uppercase() - b'A' + 1)));
+    Some(number - 1)
+}
+
+fn sheet_rows(xml: &str, shared: &[String]) -> Result<(Vec<Vec<String>>, bool), String> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut truncated = false;
+
+    // Current cell: column, type, and value text
+    let mut column = 0;
+    let mut cell_type = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+
+    let mut reader = Reader::from_str(xml);
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid worksheet XML: {}", e))?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                column = attribute(e, b"r").and_then(|r| column_index(&r)).unwrap_or(row.len());
+                cell_type = attribute(e, b"t").unwrap_or_default();
+                value.clear();
+            }
+            Event::Start(e) => {
+                if matches!(e.local_name().as_ref(), b"v" | b"t") {
+                    in_value = true;
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"v" | b"t" => in_value = false,
+                b"c" => {
+                    let text = match cell_type.as_str() {
+                        "s" => value.trim().parse::<usize>().ok().and_then(|i| shared.get(i)).cloned().unwrap_or_default(),
+                        "b" => (if value.trim() == "1" { "TRUE" } else { "FALSE" }).to_string(),
+                        _ => std::mem::take(&mut value),
+                    };
+                    if column < MAX_COLUMNS && !text.is_empty() {
+                        if row.len() <= column {
+                            row.resize(column + 1, String::new());
+                        }
+                        row[column] = text;
+                    }
+                }
+                b"row" => {
+                    if rows.len() < MAX_SHEET_ROWS {
+                        rows.push(std::mem::take(&mut row));
+                    } else {
+                        truncated = true;
+                        row.clear();
+                    }
+                }
+                _ => {}
+            },
+            _ if in_value => {
+                if let Some(text) = text_of(&event) {
+                    value.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Drop trailing empty rows
+    while rows.last().is_some_and(|r: &Vec<String>| r.is_empty()) {
+        rows.pop();
+    }
+    Ok((rows, truncated))
+}
+
+/// Path of a workbook relationship target inside the archive
+fn part_path(target: &str) -> String {
+    match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", target),
+    }
+}
+
+/// Worksheets of an XLSX workbook, in workbook order
+pub fn xlsx_sheets(data: &[u8]) -> Result<Vec<Sheet>, String> {
+    let mut archive = open(data)?;
+    let workbook = read_part(&mut archive, "xl/workbook.xml")?.ok_or("Not an Excel workbook: xl/workbook.xml is missing")?;
+
+    // Sheet names and relationship ids
+    let mut sheets = Vec::new();
+    let mut reader = Reader::from_str(&workbook);
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid workbook XML: {}", e))? {
+            Event::Eof => break,
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                if let (Some(name), Some(id)) = (attribute(&e, b"name"), attribute(&e, b"id")) {
+                    sheets.push((name, id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut targets = HashMap::new();
+    if let Some(rels) = read_part(&mut archive, "xl/_rels/workbook.xml.rels")? {
+        let mut reader = Reader::from_str(&rels);
+        loop {
+            match reader.read_event().map_err(|e| format!("Invalid workbook relationships: {}", e))? {
+                Event::Eof => break,
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                    if let (Some(id), Some(target)) = (attribute(&e, b"Id"), attribute(&e, b"Target")) {
+                        targets.insert(id, part_path(&target));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let shared = match read_part(&mut archive, "xl/sharedStrings.xml")? {
+        Some(xml) => shared_strings(&xml)?,
+        None => Vec::new(),
+    };
+
+    let mut result = Vec::new();
+    for (index, (name, id)) in sheets.into_iter().enumerate() {
+        let path = targets
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| format!("xl/worksheets/sheet{}.xml", index + 1));
+        // Chart sheets and dialog sheets have no cells
+        let Some(xml) = read_part(&mut archive, &path)? else { continue };
+        let (rows, truncated) = sheet_rows(&xml, &shared)?;
+        result.push(Sheet { name, rows, truncated });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Zip the given parts into an in-memory archive
+    fn archive(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_docx_pages() {
+        let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:pPr><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr><w:r><w:t>Q1 &amp; Q2</w:t><w:tab/><w:t xml:space="preserve">plan </w:t></w:r><w:r><w:t>&#8212;draft</w:t></w:r></w:p>
+<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Owner</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Due</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+<w:p><w:r><w:br w:type="page"/></w:r></w:p>
+<w:p><w:r><w:lastRenderedPageBreak/><w:t>Appendix</w:t><w:br/><w:delText>removed</w:delText><w:t>end</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        let data = archive(&[("word/document.xml", document)]);
+        let pages = docx_pages(&data).unwrap();
+        assert_eq!(pages, vec!["Q1 & Q2\tplan —draft\nOwner\tDue".to_string(), "Appendix\nend".to_string()]);
+
+        assert!(docx_pages(&archive(&[("other.xml", "<a/>")])).is_err());
+        assert!(docx_pages(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_xlsx_sheets() {
+        let workbook = r#"<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>
+<sheet name="Budget" sheetId="1" r:id="rId2"/><sheet name="Notes" sheetId="2" r:id="rId1"/></sheets></workbook>"#;
+        let rels = r#"<Relationships><Relationship Id="rId1" Target="worksheets/notes.xml"/>
+<Relationship Id="rId2" Target="/xl/worksheets/budget.xml"/></Relationships>"#;
+        let shared = r#"<sst><si><t>Item</t></si><si><r><t>Tot</t></r><r><t>al</t></r><rPh><t>ignored</t></rPh></si><si/></sst>"#;
+        let budget = r#"<worksheet><sheetData>
+<row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row>
+<row r="2"><c r="A2" t="inlineStr"><is><t>Rent &lt;May&gt;</t></is></c><c r="B2" t="b"><v>1</v></c><c r="C2"><f>SUM(C3:C4)</f><v>1200.5</v></c></row>
+<row r="3"><c r="A3" t="s"><v>2</v></c></row>
+</sheetData></worksheet>"#;
+        let data = archive(&[
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/sharedStrings.xml", shared),
+            ("xl/worksheets/budget.xml", budget),
+            ("xl/worksheets/notes.xml", "<worksheet><sheetData/></worksheet>"),
+        ]);
+        let sheets = xlsx_sheets(&data).unwrap();
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[0].name, "Budget");
+        assert_eq!(
+            sheets[0].rows,
+            vec![
+                vec!["Item".to_string(), String::new(), "Total".to_string()],
+                vec!["Rent <May>".to_string(), "TRUE".to_string(), "1200.5".to_string()],
+            ]
+        );
+        assert!(!sheets[0].truncated);
+        assert_eq!(sheets[1].name, "Notes");
+        assert!(sheets[1].rows.is_empty());
+
+        assert_eq!(column_index("AB12"), Some(27));
+        assert_eq!(column_index("12"), None);
+    }
+}
@@ -0,0 +1,1150 @@
+//! PDF text extraction
+//!
+//! A small reader for the parts of PDF needed to get text out: indirect
+//! objects and object streams, Flate/ASCIIHex/ASCII85 streams, the page tree,
+//! and the text operators of content streams and form XObjects. Strings are
+//! decoded through each font's ToUnicode map, falling back to WinAnsi for
+//! simple fonts. Objects are found by scanning the file rather than through
+//! the cross-reference table, so files with a damaged xref still read.
+//! Encrypted files are rejected; scanned pages without a text layer come out
+//! empty.
+
+use flate2::read::ZlibDecoder;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+/// Largest decoded size of a single stream
+const MAX_STREAM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Nesting limit for the page tree, form XObjects and reference chains
+const MAX_DEPTH: usize = 32;
+
+/// Most codes a single ToUnicode `bfrange` may map
+const MAX_RANGE_CODES: u32 = 0x10000;
+
+/// TJ adjustments (thousandths of a text space unit) wide enough to be a space
+const TJ_SPACE_THRESHOLD: f64 = -200.0;
+
+type Dict = HashMap<String, Object>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Object {
+    Null,
+    Number(f64),
+    Name(String),
+    String(Vec<u8>),
+    Array(Vec<Object>),
+    Dict(Dict),
+    Stream(Dict, Vec<u8>),
+    Ref(u32),
+    /// Bare keyword, e.g. a content stream operator
+    Operator(String),
+}
+
+impl Object {
+    fn as_dict(&self) -> Option<&Dict> {
+        match self {
+            Object::Dict(dict) | Object::Stream(dict, _) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Object::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// Lexer
+// ============================================================================
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+fn is_regular(b: u8) -> bool {
+    !is_whitespace(b) && !is_delimiter(b)
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn at(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, prefix: &[u8]) -> bool {
+        self.data[self.pos.min(self.data.len())..].starts_with(prefix)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.peek().is_some_and(|b| b != b'\r' && b != b'\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn regular_token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(is_regular) {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    /// Next object, or `None` at the end of the data or of an enclosing
+    /// array or dictionary
+    fn parse(&mut self) -> Option<Object> {
+        self.parse_nested(0)
+    }
+
+    fn parse_nested(&mut self, depth: usize) -> Option<Object> {
+        loop {
+            self.skip_whitespace();
+            let b = self.peek()?;
+            return Some(match b {
+                b'/' => {
+                    self.pos += 1;
+                    Object::Name(decode_name(self.regular_token()))
+                }
+                b'(' => Object::String(self.literal_string()),
+                b'<' if self.starts_with(b"<<") => {
+                    self.pos += 2;
+                    Object::Dict(self.dict(depth)?)
+                }
+                b'<' => Object::String(self.hex_string()),
+                b'[' => {
+                    self.pos += 1;
+                    let mut items = Vec::new();
+                    loop {
+                        self.skip_whitespace();
+                        match self.peek() {
+                            None => break,
+                            Some(b']') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            Some(b'>') if self.starts_with(b">>") => break,
+                            _ => match self.parse_nested(depth + 1) {
+                                Some(item) if depth < MAX_DEPTH => items.push(item),
+                                Some(_) => {}
+                                None => break,
+                            },
+                        }
+                    }
+                    Object::Array(items)
+                }
+                b']' | b'>' => return None,
+                b')' | b'{' | b'}' => {
+                    self.pos += 1;
+                    continue;
+                }
+                b'0'..=b'9' | b'+' | b'-' | b'.' => self.number_or_ref(),
+                _ => {
+                    let token = self.regular_token();
+                    match token {
+                        b"null" => Object::Null,
+                        // Booleans never matter for text, so they stay keywords
+                        _ => Object::Operator(String::from_utf8_lossy(token).into_owned()),
+                    }
+                }
+            });
+        }
+    }
+
+    fn dict(&mut self, depth: usize) -> Option<Dict> {
+        let mut dict = Dict::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Some(dict),
+                Some(b'>') if self.starts_with(b">>") => {
+                    self.pos += 2;
+                    return Some(dict);
+                }
+                _ => {}
+            }
+            let key = match self.parse_nested(depth + 1)? {
+                Object::Name(key) => key,
+                // Skip junk until the next key
+                _ => continue,
+            };
+            self.skip_whitespace();
+            if self.starts_with(b">>") {
+                self.pos += 2;
+                return Some(dict);
+            }
+            let value = self.parse_nested(depth + 1)?;
+            if depth < MAX_DEPTH {
+                dict.insert(key, value);
+            }
+        }
+    }
+
+    fn number_or_ref(&mut self) -> Object {
+        let token = self.regular_token();
+        let text = std::str::from_utf8(token).unwrap_or_default();
+        let Ok(number) = text.parse::<f64>() else {
+            // e.g. "--5" or a lone "." in a damaged file
+            return Object::Number(0.0);
+        };
+        // "12 0 R" is a reference
+        if let Ok(id) = text.parse::<u32>() {
+            let saved = self.pos;
+            self.skip_whitespace();
+            let generation = self.regular_token();
+            if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) {
+                self.skip_whitespace();
+                if self.peek() == Some(b'R') && self.data.get(self.pos + 1).is_none_or(|b| !is_regular(*b)) {
+                    self.pos += 1;
+                    return Object::Ref(id);
+                }
+            }
+            self.pos = saved;
+        }
+        Object::Number(number)
+    }
+
+    fn literal_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut nesting = 0;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    nesting += 1;
+                    out.push(b);
+                }
+                b')' if nesting == 0 => break,
+                b')' => {
+                    nesting -= 1;
+                    out.push(b);
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(d - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        // Line continuation
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other),
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
+
+    fn hex_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut digits = Vec::new();
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            if b == b'>' {
+                break;
+            }
+            if let Some(d) = (b as char).to_digit(16) {
+                digits.push(d as u8);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect()
+    }
+
+    /// Skip the binary data of an inline image, after its `ID` operator
+    fn skip_inline_image(&mut self) {
+        self.pos += 1;
+        while self.pos + 2 <= self.data.len() {
+            if self.starts_with(b"EI")
+                && is_whitespace(self.data[self.pos - 1])
+                && self.data.get(self.pos + 2).is_none_or(|b| !is_regular(*b))
+            {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+        self.pos = self.data.len();
+    }
+}
+
+fn decode_name(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'#' && i + 2 < raw.len() {
+            if let Some(byte) = std::str::from_utf8(&raw[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(raw[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ============================================================================
+// Streams
+// ============================================================================
+
+fn inflate(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    // Keep what was decoded before an error; truncated streams are common
+    let _ = ZlibDecoder::new(data).take(MAX_STREAM_BYTES).read_to_end(&mut out);
+    out
+}
+
+fn ascii_hex(data: &[u8]) -> Vec<u8> {
+    let mut bracketed = Vec::with_capacity(data.len() + 1);
+    bracketed.push(b'<');
+    bracketed.extend_from_slice(data);
+    Parser::new(&bracketed).hex_string()
+}
+
+fn ascii85(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut group = [0u32; 5];
+    let mut len = 0;
+    for &b in data {
+        match b {
+            b'~' => break,
+            b'z' if len == 0 => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group[len] = u32::from(b - b'!');
+                len += 1;
+                if len == 5 {
+                    let value = group.iter().fold(0u32, |acc, d| acc.wrapping_mul(85).wrapping_add(*d));
+                    out.extend_from_slice(&value.to_be_bytes());
+                    len = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+    if len > 1 {
+        for digit in group.iter_mut().skip(len) {
+            *digit = 84;
+        }
+        let value = group.iter().fold(0u32, |acc, d| acc.wrapping_mul(85).wrapping_add(*d));
+        out.extend_from_slice(&value.to_be_bytes()[..len - 1]);
+    }
+    out
+}
+
+/// Decoded stream data, or `None` for filters that do not carry text
+/// (e.g. image codecs)
+fn decode_stream(dict: &Dict, raw: &[u8]) -> Option<Vec<u8>> {
+    let filters = match dict.get("Filter") {
+        None => Vec::new(),
+        Some(Object::Name(name)) => vec![name.as_str()],
+        Some(Object::Array(names)) => names.iter().filter_map(Object::as_name).collect(),
+        Some(_) => return None,
+    };
+    let mut data = raw.to_vec();
+    for filter in filters {
+        data = match filter {
+            "FlateDecode" | "Fl" => inflate(&data),
+            "ASCIIHexDecode" | "AHx" => ascii_hex(&data),
+            "ASCII85Decode" | "A85" => ascii85(&data),
+            _ => return None,
+        };
+    }
+    Some(data)
+}
+
+// ============================================================================
+// Document
+// ============================================================================
+
+struct Document {
+    objects: HashMap<u32, Object>,
+    trailers: Vec<Dict>,
+}
+
+impl Document {
+    fn load(data: &[u8]) -> Result<Self, String> {
+        let header_end = data.len().min(1024);
+        if !data[..header_end].windows(5).any(|w| w == b"%PDF-") {
+            return Err("Not a PDF file".to_string());
+        }
+
+        let mut document = Document {
+            objects: HashMap::new(),
+            trailers: Vec::new(),
+        };
+        let object_start = regex::bytes::Regex::new(r"(\d+)\s+\d+\s+obj\b").expect("valid regex");
+        let trailer = regex::bytes::Regex::new(r"trailer\s*<<").expect("valid regex");
+
+        let mut pos = 0;
+        while let Some(found) = object_start.captures_at(data, pos) {
+            let whole = found.get(0).expect("match");
+            let id = std::str::from_utf8(&found[1]).ok().and_then(|s| s.parse::<u32>().ok());
+            let (object, end) = Self::indirect_object(data, whole.end());
+            if let (Some(id), Some(object)) = (id, object) {
+                if let Object::Stream(dict, _) = &object {
+                    if dict.get("Type").and_then(Object::as_name) == Some("XRef") {
+                        document.trailers.push(dict.clone());
+                    }
+                }
+                // Later definitions are incremental updates and win
+                document.objects.insert(id, object);
+            }
+            pos = end.max(whole.end());
+        }
+        for found in trailer.find_iter(data) {
+            if let Some(Object::Dict(dict)) = Parser::at(data, found.end() - 2).parse() {
+                document.trailers.push(dict);
+            }
+        }
+        if document.trailers.iter().any(|t| t.contains_key("Encrypt")) {
+            return Err("Encrypted PDFs are not supported".to_string());
+        }
+
+        document.load_object_streams();
+        Ok(document)
+    }
+
+    /// The object after `N G obj`, and where it ends
+    fn indirect_object(data: &[u8], start: usize) -> (Option<Object>, usize) {
+        let mut parser = Parser::at(data, start);
+        let Some(object) = parser.parse() else {
+            return (None, parser.pos);
+        };
+        parser.skip_whitespace();
+        let Object::Dict(dict) = object else {
+            return (Some(object), parser.pos);
+        };
+        if !parser.starts_with(b"stream") {
+            return (Some(Object::Dict(dict)), parser.pos);
+        }
+
+        parser.pos += b"stream".len();
+        if parser.starts_with(b"\r\n") {
+            parser.pos += 2;
+        } else if parser.peek().is_some_and(|b| b == b'\n' || b == b'\r') {
+            parser.pos += 1;
+        }
+        let body_start = parser.pos.min(data.len());
+        let rest = &data[body_start..];
+
+        // Trust a direct /Length only if `endstream` follows it
+        let declared = dict
+            .get("Length")
+            .and_then(Object::as_number)
+            .map(|n| n as usize)
+            .filter(|&n| n <= rest.len() && {
+                let mut after = Parser::at(rest, n);
+                after.skip_whitespace();
+                after.starts_with(b"endstream")
+            });
+        let len = declared.unwrap_or_else(|| {
+            let end = find(rest, b"endstream").unwrap_or(rest.len());
+            let mut end = end;
+            // Drop the EOL before `endstream`
+            if end > 0 && rest[end - 1] == b'\n' {
+                end -= 1;
+            }
+            if end > 0 && rest[end - 1] == b'\r' {
+                end -= 1;
+            }
+            end
+        });
+        let body = rest[..len].to_vec();
+        let end = body_start + len + find(&rest[len..], b"endstream").map_or(0, |i| i + b"endstream".len());
+        (Some(Object::Stream(dict, body)), end)
+    }
+
+    /// Add the objects packed into object streams (PDF 1.5+)
+    fn load_object_streams(&mut self) {
+        let mut packed = Vec::new();
+        for object in self.objects.values() {
+            let Object::Stream(dict, raw) = object else { continue };
+            if dict.get("Type").and_then(Object::as_name) != Some("ObjStm") {
+                continue;
+            }
+            let Some(data) = decode_stream(dict, raw) else { continue };
+            let count = dict.get("N").and_then(Object::as_number).unwrap_or(0.0) as usize;
+            let first = dict.get("First").and_then(Object::as_number).unwrap_or(0.0) as usize;
+            if first > data.len() {
+                continue;
+            }
+
+            let mut header = Parser::new(&data[..first]);
+            for _ in 0..count {
+                let (Some(Object::Number(id)), Some(Object::Number(offset))) = (header.parse(), header.parse()) else {
+                    break;
+                };
+                if let Some(object) = Parser::at(&data, first + offset as usize).parse() {
+                    packed.push((id as u32, object));
+                }
+            }
+        }
+        for (id, object) in packed {
+            self.objects.entry(id).or_insert(object);
+        }
+    }
+
+    /// Follow references to the object they point at
+    fn resolve<'a>(&'a self, mut object: &'a Object) -> &'a Object {
+        for _ in 0..MAX_DEPTH {
+            match object {
+                Object::Ref(id) => match self.objects.get(id) {
+                    Some(target) => object = target,
+                    None => return &Object::Null,
+                },
+                _ => return object,
+            }
+        }
+        &Object::Null
+    }
+
+    fn get<'a>(&'a self, dict: &'a Dict, key: &str) -> Option<&'a Object> {
+        dict.get(key).map(|o| self.resolve(o)).filter(|o| **o != Object::Null)
+    }
+
+    fn get_dict<'a>(&'a self, dict: &'a Dict, key: &str) -> Option<&'a Dict> {
+        self.get(dict, key).and_then(Object::as_dict)
+    }
+
+    fn stream_data(&self, object: &Object) -> Option<Vec<u8>> {
+        match self.resolve(object) {
+            Object::Stream(dict, raw) => decode_stream(dict, raw),
+            _ => None,
+        }
+    }
+
+    /// Page dictionaries in order, each with its (possibly inherited) resources
+    fn pages(&self) -> Vec<(&Dict, Option<&Dict>)> {
+        let mut pages = Vec::new();
+        let root = self
+            .trailers
+            .iter()
+            .rev()
+            .find_map(|t| self.get_dict(t, "Root"))
+            .or_else(|| {
+                self.objects
+                    .values()
+                    .filter_map(Object::as_dict)
+                    .find(|d| d.get("Type").and_then(Object::as_name) == Some("Catalog"))
+            });
+        if let Some(tree) = root.and_then(|root| self.get(root, "Pages")) {
+            let mut visited = HashSet::new();
+            self.collect_pages(tree, None, 0, &mut visited, &mut pages);
+        }
+
+        if pages.is_empty() {
+            // No usable page tree: take page objects in object order
+            let mut ids: Vec<_> = self.objects.keys().copied().collect();
+            ids.sort_unstable();
+            for id in ids {
+                if let Some(dict) = self.objects[&id].as_dict() {
+                    if dict.get("Type").and_then(Object::as_name) == Some("Page") {
+                        pages.push((dict, self.get_dict(dict, "Resources")));
+                    }
+                }
+            }
+        }
+        pages
+    }
+
+    fn collect_pages<'a>(
+        &'a self,
+        node: &'a Object,
+        inherited: Option<&'a Dict>,
+        depth: usize,
+        visited: &mut HashSet<*const Dict>,
+        pages: &mut Vec<(&'a Dict, Option<&'a Dict>)>,
+    ) {
+        let Some(dict) = self.resolve(node).as_dict() else { return };
+        if depth > MAX_DEPTH || !visited.insert(dict as *const Dict) {
+            return;
+        }
+        let resources = self.get_dict(dict, "Resources").or(inherited);
+        match self.get(dict, "Kids") {
+            Some(Object::Array(kids)) => {
+                for kid in kids {
+                    self.collect_pages(kid, resources, depth + 1, visited, pages);
+                }
+            }
+            _ => pages.push((dict, resources)),
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// ============================================================================
+// Fonts
+// ============================================================================
+
+/// Code to text mapping from a ToUnicode CMap
+#[derive(Debug, Default)]
+struct CMap {
+    map: HashMap<u32, String>,
+    /// Code lengths in bytes declared by the code space ranges
+    code_lengths: Vec<usize>,
+}
+
+fn utf16_be(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks(2).map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)])).collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes.iter().take(4).fold(0, |acc, b| acc << 8 | u32::from(*b))
+}
+
+impl CMap {
+    fn parse(data: &[u8]) -> Self {
+        let mut cmap = CMap::default();
+        let mut parser = Parser::new(data);
+        let mut operands: Vec<Object> = Vec::new();
+        while let Some(object) = parser.parse() {
+            let Object::Operator(op) = object else {
+                operands.push(object);
+                continue;
+            };
+            match op.as_str() {
+                "endcodespacerange" => {
+                    for pair in operands.chunks(2) {
+                        if let Object::String(low) = &pair[0] {
+                            if !cmap.code_lengths.contains(&low.len()) {
+                                cmap.code_lengths.push(low.len());
+                            }
+                        }
+                    }
+                }
+                "endbfchar" => {
+                    for pair in operands.chunks(2) {
+                        if let [Object::String(code), Object::String(text)] = pair {
+                            cmap.map.insert(code_value(code), utf16_be(text));
+                        }
+                    }
+                }
+                "endbfrange" => {
+                    for range in operands.chunks(3) {
+                        let [Object::String(low), Object::String(high), target] = range else {
+                            continue;
+                        };
+                        let (low, high) = (code_value(low), code_value(high));
+                        if high < low || high - low >= MAX_RANGE_CODES {
+                            continue;
+                        }
+                        match target {
+                            Object::String(start) if !start.is_empty() => {
+                                // The last UTF-16 unit counts up through the range
+                                let mut units: Vec<u16> =
+                                    start.chunks(2).map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)])).collect();
+                                let last = units.len() - 1;
+                                let base = units[last];
+                                for (offset, code) in (low..=high).enumerate() {
+                                    units[last] = base.wrapping_add(offset as u16);
+                                    cmap.map.insert(code, String::from_utf16_lossy(&units));
+                                }
+                            }
+                            Object::Array(targets) => {
+                                for (code, target) in (low..=high).zip(targets) {
+                                    if let Object::String(text) = target {
+                                        cmap.map.insert(code, utf16_be(text));
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+            operands.clear();
+        }
+        cmap
+    }
+}
+
+/// Characters for bytes 0x80-0x9F in WinAnsiEncoding
+const WIN_ANSI_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}', '\u{90}', '‘', '’',
+    '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+fn win_ansi(byte: u8) -> char {
+    match byte {
+        0x80..=0x9f => WIN_ANSI_HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// Text for a glyph name from a `/Differences` array
+fn glyph_text(name: &str) -> Option<String> {
+    if let Some(hex) = name.strip_prefix("uni").filter(|h| h.len() == 4) {
+        return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).map(String::from);
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c.to_string());
+    }
+    let text = match name {
+        "space" => " ",
+        "period" => ".",
+        "comma" => ",",
+        "colon" => ":",
+        "semicolon" => ";",
+        "hyphen" | "minus" => "-",
+        "quoteright" | "quotesingle" => "'",
+        "quoteleft" => "‘",
+        "quotedblleft" => "“",
+        "quotedblright" => "”",
+        "quotedbl" => "\"",
+        "endash" => "–",
+        "emdash" => "—",
+        "bullet" => "•",
+        "ellipsis" => "…",
+        "fi" => "fi",
+        "fl" => "fl",
+        "ff" => "ff",
+        "parenleft" => "(",
+        "parenright" => ")",
+        "slash" => "/",
+        "ampersand" => "&",
+        "question" => "?",
+        "exclam" => "!",
+        "zero" => "0",
+        "one" => "1",
+        "two" => "2",
+        "three" => "3",
+        "four" => "4",
+        "five" => "5",
+        "six" => "6",
+        "seven" => "7",
+        "eight" => "8",
+        "nine" => "9",
+        _ => return None,
+    };
+    Some(text.to_string())
+}
+
+#[derive(Debug, Default)]
+struct Font {
+    to_unicode: Option<CMap>,
+    /// Composite (Type0) fonts use multi-byte codes
+    composite: bool,
+    differences: HashMap<u8, String>,
+}
+
+impl Font {
+    fn load(document: &Document, dict: &Dict) -> Self {
+        let mut font = Font {
+            composite: dict.get("Subtype").and_then(Object::as_name) == Some("Type0"),
+            ..Font::default()
+        };
+        if let Some(data) = dict.get("ToUnicode").and_then(|o| document.stream_data(o)) {
+            font.to_unicode = Some(CMap::parse(&data));
+        }
+        if let Some(Object::Array(differences)) = document
+            .get_dict(dict, "Encoding")
+            .and_then(|encoding| document.get(encoding, "Differences"))
+        {
+            let mut code = 0u32;
+            for item in differences {
+                match item {
+                    Object::Number(n) => code = *n as u32,
+                    Object::Name(name) => {
+                        if let (Ok(byte), Some(text)) = (u8::try_from(code), glyph_text(name)) {
+                            font.differences.insert(byte, text);
+                        }
+                        code += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        font
+    }
+
+    fn decode(&self, bytes: &[u8], out: &mut String) {
+        if let Some(cmap) = &self.to_unicode {
+            let width = match cmap.code_lengths.as_slice() {
+                [len] => *len,
+                _ if self.composite => 2,
+                _ => 1,
+            }
+            .clamp(1, 4);
+            for code in bytes.chunks(width) {
+                match cmap.map.get(&code_value(code)) {
+                    Some(text) => out.push_str(text),
+                    None if !self.composite => code.iter().for_each(|b| out.push(win_ansi(*b))),
+                    None => {}
+                }
+            }
+        } else if !self.composite {
+            for byte in bytes {
+                match self.differences.get(byte) {
+                    Some(text) => out.push_str(text),
+                    None => out.push(win_ansi(*byte)),
+                }
+            }
+        }
+        // Composite fonts without a ToUnicode map have no recoverable text
+    }
+}
+
+// ============================================================================
+// Content streams
+// ============================================================================
+
+struct TextWriter {
+    text: String,
+    line_y: Option<f64>,
+}
+
+impl TextWriter {
+    fn newline(&mut self) {
+        let trimmed = self.text.trim_end_matches([' ', '\t']).len();
+        self.text.truncate(trimmed);
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn space(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+            self.text.push(' ');
+        }
+    }
+
+    /// Start a new line when the baseline moves
+    fn move_to(&mut self, y: f64) {
+        match self.line_y {
+            Some(line_y) if (line_y - y).abs() > 1.0 => self.newline(),
+            Some(_) => self.space(),
+            None => {}
+        }
+        self.line_y = Some(y);
+    }
+}
+
+fn font<'a>(
+    document: &Document,
+    resources: Option<&Dict>,
+    name: &str,
+    cache: &'a mut HashMap<String, Font>,
+) -> &'a Font {
+    cache.entry(name.to_string()).or_insert_with(|| {
+        resources
+            .and_then(|r| document.get_dict(r, "Font"))
+            .and_then(|fonts| document.get_dict(fonts, name))
+            .map(|dict| Font::load(document, dict))
+            .unwrap_or_default()
+    })
+}
+
+fn show_text(
+    writer: &mut TextWriter,
+    document: &Document,
+    resources: Option<&Dict>,
+    font_name: &str,
+    fonts: &mut HashMap<String, Font>,
+    object: &Object,
+) {
+    let font = font(document, resources, font_name, fonts);
+    match object {
+        Object::String(bytes) => font.decode(bytes, &mut writer.text),
+        Object::Array(items) => {
+            for item in items {
+                match item {
+                    Object::String(bytes) => font.decode(bytes, &mut writer.text),
+                    Object::Number(n) if *n < TJ_SPACE_THRESHOLD => writer.space(),
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn run_content(document: &Document, content: &[u8], resources: Option<&Dict>, writer: &mut TextWriter, depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    // Fonts are named per resource dictionary, so each content stream has its own cache
+    let mut fonts: HashMap<String, Font> = HashMap::new();
+    let mut font_name = String::new();
+    let mut leading = 0.0;
+    let mut y = 0.0;
+
+    let mut parser = Parser::new(content);
+    let mut operands: Vec<Object> = Vec::new();
+    loop {
+        let Some(object) = parser.parse() else {
+            // Stray `]` or `>`: skip it
+            if parser.peek().is_none() {
+                break;
+            }
+            parser.pos += 1;
+            continue;
+        };
+        let Object::Operator(op) = object else {
+            operands.push(object);
+            continue;
+        };
+        let number = |i: usize| operands.get(i).and_then(Object::as_number).unwrap_or(0.0);
+        match op.as_str() {
+            // Each text object starts from the identity matrix
+            "BT" => y = 0.0,
+            "Tf" => {
+                if let Some(name) = operands.first().and_then(Object::as_name) {
+                    font_name = name.to_string();
+                }
+            }
+            "TL" => leading = number(0),
+            "Td" => {
+                let dy = number(1);
+                if dy == 0.0 {
+                    writer.space();
+                } else {
+                    y += dy;
+                    writer.move_to(y);
+                }
+            }
+            "TD" => {
+                leading = -number(1);
+                y += number(1);
+                writer.move_to(y);
+            }
+            "Tm" => {
+                y = number(5);
+                writer.move_to(y);
+            }
+            "T*" => {
+                y -= leading;
+                writer.newline();
+                writer.line_y = Some(y);
+            }
+            "Tj" | "TJ" => {
+                if let Some(text) = operands.last() {
+                    show_text(writer, document, resources, &font_name, &mut fonts, text);
+                }
+            }
+            "'" | "\"" => {
+                y -= leading;
+                writer.newline();
+                writer.line_y = Some(y);
+                if let Some(text) = operands.last() {
+                    show_text(writer, document, resources, &font_name, &mut fonts, text);
+                }
+            }
+            "Do" => {
+                let form = operands
+                    .first()
+                    .and_then(Object::as_name)
+                    .and_then(|name| resources.and_then(|r| document.get_dict(r, "XObject")).and_then(|x| x.get(name)))
+                    .map(|o| document.resolve(o));
+                if let Some(Object::Stream(dict, raw)) = form {
+                    if dict.get("Subtype").and_then(Object::as_name) == Some("Form") {
+                        if let Some(data) = decode_stream(dict, raw) {
+                            let form_resources = document.get_dict(dict, "Resources").or(resources);
+                            run_content(document, &data, form_resources, writer, depth + 1);
+                        }
+                    }
+                }
+            }
+            "ID" => parser.skip_inline_image(),
+            _ => {}
+        }
+        operands.clear();
+    }
+}
+
+/// Tidy extracted text: trim lines and collapse runs of blank lines
+fn tidy(text: &str) -> String {
+    let mut out = String::new();
+    let mut blank = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank += 1;
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        blank = 0;
+        out.push_str(line);
+    }
+    out
+}
+
+/// Text of each page, in page order
+pub fn extract_pages(data: &[u8]) -> Result<Vec<String>, String> {
+    let document = Document::load(data)?;
+    let pages = document.pages();
+    if pages.is_empty() {
+        return Err("No pages found; the PDF may be damaged".to_string());
+    }
+
+    Ok(pages
+        .into_iter()
+        .map(|(page, resources)| {
+            let mut content = Vec::new();
+            match page.get("Contents").map(|o| document.resolve(o)) {
+                Some(Object::Array(parts)) => {
+                    for part in parts {
+                        if let Some(data) = document.stream_data(part) {
+                            content.extend_from_slice(&data);
+                            content.push(b'\n');
+                        }
+                    }
+                }
+                Some(stream) => content = document.stream_data(stream).unwrap_or_default(),
+                None => {}
+            }
+            let mut writer = TextWriter {
+                text: String::new(),
+                line_y: None,
+            };
+            run_content(&document, &content, resources, &mut writer, 0);
+            tidy(&writer.text)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    /// Assemble a PDF from object bodies numbered from 1
+    fn build_pdf(objects: &[Vec<u8>]) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        for (i, body) in objects.iter().enumerate() {
+            pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend_from_slice(body);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+        pdf.extend_from_slice(format!("trailer\n<< /Size {} /Root 1 0 R >>\n%%EOF\n", objects.len() + 1).as_bytes());
+        pdf
+    }
+
+    fn stream(dict: &str, data: &[u8]) -> Vec<u8> {
+        let mut body = format!("<< {} /Length {} >>\nstream\n", dict, data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        body
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn sample() -> Vec<u8> {
+        let cmap = b"/CIDInit /ProcSet findresource begin\n1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+2 beginbfchar <0001> <0048> <0002> <0069> endbfchar\n1 beginbfrange <0010> <0012> <00E1> endbfrange\nend";
+        let page2 = deflate(b"BT /F2 12 Tf 72 700 Td <00010002> Tj 0 -14 Td [<0010> -300 <0012>] TJ ET");
+        build_pdf(&[
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /Resources << /Font << /F1 5 0 R /F2 6 0 R >> >> >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /Contents 7 0 R >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /Contents [8 0 R] >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding << /Differences [39 /quoteright] >> >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type0 /BaseFont /Noto /Encoding /Identity-H /ToUnicode 9 0 R >>".to_vec(),
+            stream(
+                "",
+                b"BT /F1 12 Tf 72 720 Td (Quarterly \\(Q1\\) report) Tj 0 -14 Td [(It) -20 ('s) -400 (done)] TJ T* (\\223ok\\224) ' ET",
+            ),
+            stream("/Filter /FlateDecode", &page2),
+            stream("", cmap),
+        ])
+    }
+
+    #[test]
+    fn test_extract_pages() {
+        let pages = extract_pages(&sample()).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0], "Quarterly (Q1) report\nIt's done\n“ok”");
+        // ToUnicode map with a bfrange counting through the last unit
+        assert_eq!(pages[1], "Hi\ná ã");
+    }
+
+    #[test]
+    fn test_object_streams_and_errors() {
+        // Page dictionaries packed into an object stream
+        let packed = b"4 0 5 33 << /Type /Pages /Kids [5 0 R] >> << /Type /Page /Contents 3 0 R >>";
+        let pdf = build_pdf(&[
+            b"<< /Type /Catalog /Pages 4 0 R >>".to_vec(),
+            stream("/Type /ObjStm /N 2 /First 9 /Filter /FlateDecode", &deflate(packed)),
+            stream("", b"BT /F1 10 Tf (Packed) Tj ET"),
+        ]);
+        assert_eq!(extract_pages(&pdf).unwrap(), vec!["Packed".to_string()]);
+
+        assert!(extract_pages(b"PK\x03\x04 not a pdf").is_err());
+        let encrypted = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\ntrailer\n<< /Root 1 0 R /Encrypt 2 0 R >>\n";
+        assert_eq!(extract_pages(encrypted).unwrap_err(), "Encrypted PDFs are not supported");
+    }
+}
@@ -0,0 +1,406 @@
+//! Directory Listing
+//!
+//! Lists a directory as typed entries, optionally walking subdirectories.
+//! Symlinked directories are reported but never entered, so a walk stays
+//! inside the folder that was permission-checked. Results are paged so huge
+//! directories do not have to cross the IPC boundary in one response.
+
+use super::access::{FileError, FileErrorKind};
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Entries returned when the caller does not set a limit
+const DEFAULT_LIMIT: usize = 1000;
+
+/// Largest page a caller may ask for
+const MAX_LIMIT: usize = 10_000;
+
+/// Deepest a recursive listing goes when the caller does not set a depth
+const MAX_DEPTH: usize = 64;
+
+/// Glob matching where `*` stays within one path component
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Kind of directory entry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    File,
+    Dir,
+    /// Symlink whose target does not exist
+    Symlink,
+    Other,
+}
+
+/// Directory entry with metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirEntry {
+    pub name: String,
+    pub path: String,
+    /// Kind of the entry, following symlinks
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Last modification time (RFC 3339)
+    pub modified: Option<String>,
+    pub is_symlink: bool,
+}
+
+/// Options for `list`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListOptions {
+    pub recursive: bool,
+    /// Levels to descend below the listed directory when recursive
+    pub max_depth: Option<usize>,
+    /// Only return entries matching one of these globs
+    pub include: Vec<String>,
+    /// Skip entries matching any of these globs, and don't descend into them
+    pub exclude: Vec<String>,
+    /// Skip `.git` and entries ignored by `.gitignore` files
+    pub respect_gitignore: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of a directory listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirListing {
+    pub entries: Vec<DirEntry>,
+    /// Number of matching entries across all pages
+    pub total: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// Rule from a `.gitignore` file
+struct IgnoreRule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the path relative to the `.gitignore`, not the name
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn matches(&self, rel: &str, name: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let target = if self.anchored { rel } else { name };
+        self.pattern.matches_with(target, MATCH_OPTIONS)
+    }
+}
+
+/// Rules from one `.gitignore`, scoped to the directory containing it
+struct IgnoreFile {
+    /// Directory relative to the listed root, "" for the root itself
+    base: String,
+    rules: Vec<IgnoreRule>,
+}
+
+/// Parse `.gitignore` content, skipping lines that aren't valid globs
+fn parse_gitignore(content: &str) -> Vec<IgnoreRule> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let pattern = Pattern::new(line.trim_start_matches('/')).ok()?;
+            Some(IgnoreRule {
+                pattern,
+                negated,
+                dir_only,
+                anchored,
+            })
+        })
+        .collect()
+}
+
+/// Whether the `.gitignore` files in scope ignore an entry; later rules and
+/// deeper files take precedence
+fn is_ignored(ignores: &[IgnoreFile], rel: &str, name: &str, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for file in ignores {
+        let sub = if file.base.is_empty() {
+            rel
+        } else {
+            match rel.strip_prefix(&file.base).and_then(|r| r.strip_prefix('/')) {
+                Some(sub) => sub,
+                None => continue,
+            }
+        };
+        for rule in &file.rules {
+            if rule.matches(sub, name, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+    ignored
+}
+
+/// Compile caller globs; patterns without a `/` match entry names
+fn compile(patterns: &[String], root: &Path) -> Result<Vec<(Pattern, bool)>, FileError> {
+    patterns
+        .iter()
+        .map(|p| {
+            Pattern::new(p.trim_start_matches('/'))
+                .map(|pattern| (pattern, p.contains('/')))
+                .map_err(|e| FileError::new(FileErrorKind::InvalidPattern, root, format!("Invalid pattern '{}': {}", p, e)))
+        })
+        .collect()
+}
+
+fn any_match(patterns: &[(Pattern, bool)], rel: &str, name: &str) -> bool {
+    patterns
+        .iter()
+        .any(|(pattern, by_path)| pattern.matches_with(if *by_path { rel } else { name }, MATCH_OPTIONS))
+}
+
+/// Build an entry from a path, following symlinks for the kind
+fn entry(path: &Path, name: String) -> Option<DirEntry> {
+    let link = std::fs::symlink_metadata(path).ok()?;
+    let is_symlink = link.file_type().is_symlink();
+    let target = if is_symlink { std::fs::metadata(path).ok() } else { Some(link.clone()) };
+
+    let kind = match &target {
+        Some(m) if m.is_dir() => EntryKind::Dir,
+        Some(m) if m.is_file() => EntryKind::File,
+        Some(_) => EntryKind::Other,
+        None => EntryKind::Symlink,
+    };
+    let size = target.as_ref().filter(|m| m.is_file()).map(|m| m.len()).unwrap_or(0);
+    let modified = link
+        .modified()
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339());
+
+    Some(DirEntry {
+        name,
+        path: path.display().to_string(),
+        kind,
+        size,
+        modified,
+        is_symlink,
+    })
+}
+
+struct Walker<'a> {
+    options: &'a ListOptions,
+    include: Vec<(Pattern, bool)>,
+    exclude: Vec<(Pattern, bool)>,
+    ignores: Vec<IgnoreFile>,
+    max_depth: usize,
+    visit: &'a mut dyn FnMut(DirEntry) -> bool,
+    stopped: bool,
+}
+
+impl Walker<'_> {
+    /// Visit `dir`, `depth` levels below the root, and descend as configured
+    fn walk(&mut self, dir: &Path, rel_dir: &str, depth: usize) -> std::io::Result<()> {
+        let mut children: Vec<(String, PathBuf)> = std::fs::read_dir(dir)?
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok().map(|name| (name, e.path())))
+            .collect();
+        children.sort();
+
+        let scoped = self.options.respect_gitignore && self.load_gitignore(dir, rel_dir);
+
+        for (name, path) in children {
+            if self.stopped {
+                break;
+            }
+            let Some(entry) = entry(&path, name) else { continue };
+            let rel = if rel_dir.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{}/{}", rel_dir, entry.name)
+            };
+            let is_dir = entry.kind == EntryKind::Dir;
+
+            if self.options.respect_gitignore
+                && ((is_dir && entry.name == ".git") || is_ignored(&self.ignores, &rel, &entry.name, is_dir))
+            {
+                continue;
+            }
+            if any_match(&self.exclude, &rel, &entry.name) {
+                continue;
+            }
+
+            let descend = is_dir && !entry.is_symlink && self.options.recursive && depth < self.max_depth;
+            if (self.include.is_empty() || any_match(&self.include, &rel, &entry.name)) && !(self.visit)(entry) {
+                self.stopped = true;
+            }
+            if descend {
+                // Unreadable subdirectories are skipped rather than failing the listing
+                let _ = self.walk(&path, &rel, depth + 1);
+            }
+        }
+
+        if scoped {
+            self.ignores.pop();
+        }
+        Ok(())
+    }
+
+    /// Push the rules of `dir/.gitignore`, returning whether there was one
+    fn load_gitignore(&mut self, dir: &Path, rel_dir: &str) -> bool {
+        match std::fs::read_to_string(dir.join(".gitignore")) {
+            Ok(content) => {
+                self.ignores.push(IgnoreFile {
+                    base: rel_dir.to_string(),
+                    rules: parse_gitignore(&content),
+                });
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Walk a directory that has already been permission-checked, passing each
+/// matching entry to `visit` until it returns false
+///
+/// Entries are visited depth-first, sorted by name within each directory.
+/// Only `.gitignore` files inside `root` are read. `offset` and `limit` are
+/// left to the caller.
+pub fn walk(root: &Path, options: &ListOptions, mut visit: impl FnMut(DirEntry) -> bool) -> Result<(), FileError> {
+    if !root.is_dir() {
+        return Err(FileError::new(
+            FileErrorKind::InvalidPath,
+            root,
+            format!("{} is not a directory", root.display()),
+        ));
+    }
+
+    let mut walker = Walker {
+        options,
+        include: compile(&options.include, root)?,
+        exclude: compile(&options.exclude, root)?,
+        ignores: Vec::new(),
+        max_depth: options.max_depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH),
+        visit: &mut visit,
+        stopped: false,
+    };
+    walker.walk(root, "", 0).map_err(|e| FileError::io(root, e))
+}
+
+/// List one page of a directory that has already been permission-checked
+pub fn list(root: &Path, options: &ListOptions) -> Result<DirListing, FileError> {
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let mut entries = Vec::new();
+    let mut total = 0;
+    walk(root, options, |entry| {
+        if total >= options.offset && entries.len() < limit {
+            entries.push(entry);
+        }
+        total += 1;
+        true
+    })?;
+
+    let end = options.offset + entries.len();
+    Ok(DirListing {
+        entries,
+        total,
+        next_offset: (end < total).then_some(end),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for d in ["src/nested", "target/debug", ".git"] {
+            std::fs::create_dir_all(dir.path().join(d)).unwrap();
+        }
+        for f in ["README.md", "src/main.rs", "src/nested/lib.rs", "src/notes.log", "target/debug/app", ".git/HEAD"] {
+            std::fs::write(dir.path().join(f), "x").unwrap();
+        }
+        dir
+    }
+
+    fn names(listing: &DirListing) -> Vec<&str> {
+        listing.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_depth_and_paging() {
+        let dir = tree();
+
+        let listing = list(dir.path(), &ListOptions::default()).unwrap();
+        assert_eq!(names(&listing), vec![".git", "README.md", "src", "target"]);
+        let readme = &listing.entries[1];
+        assert_eq!((readme.kind, readme.size, readme.is_symlink), (EntryKind::File, 1, false));
+        assert!(readme.modified.is_some());
+
+        let options = ListOptions { recursive: true, max_depth: Some(1), ..Default::default() };
+        assert_eq!(list(dir.path(), &options).unwrap().total, 9);
+
+        let options = ListOptions { recursive: true, offset: 2, limit: Some(3), ..Default::default() };
+        let page = list(dir.path(), &options).unwrap();
+        assert_eq!(names(&page), vec!["README.md", "src", "main.rs"]);
+        assert_eq!((page.total, page.next_offset), (11, Some(5)));
+    }
+
+    #[test]
+    fn test_list_globs_and_gitignore() {
+        let dir = tree();
+        std::fs::write(dir.path().join(".gitignore"), "/target/\n*.log\n").unwrap();
+        std::fs::write(dir.path().join("src/.gitignore"), "!notes.log\nnested/\n").unwrap();
+
+        let options = ListOptions {
+            recursive: true,
+            include: vec!["*.rs".to_string(), "*.log".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(names(&list(dir.path(), &options).unwrap()), vec!["main.rs", "lib.rs", "notes.log"]);
+
+        let options = ListOptions {
+            recursive: true,
+            respect_gitignore: true,
+            exclude: vec!["src/main.rs".to_string()],
+            ..Default::default()
+        };
+        let listing = list(dir.path(), &options).unwrap();
+        assert_eq!(names(&listing), vec![".gitignore", "README.md", "src", ".gitignore", "notes.log"]);
+
+        let options = ListOptions { include: vec!["[".to_string()], ..Default::default() };
+        assert_eq!(list(dir.path(), &options).unwrap_err().kind, FileErrorKind::InvalidPattern);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_does_not_follow_symlinked_dirs() {
+        let dir = tree();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "s").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("src/link")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("missing"), dir.path().join("src/broken")).unwrap();
+
+        let options = ListOptions { recursive: true, include: vec!["src/*".to_string()], ..Default::default() };
+        let listing = list(dir.path(), &options).unwrap();
+        let kinds: Vec<_> = listing.entries.iter().map(|e| (e.name.as_str(), e.kind, e.is_symlink)).collect();
+        assert!(kinds.contains(&("link", EntryKind::Dir, true)));
+        assert!(kinds.contains(&("broken", EntryKind::Symlink, true)));
+        assert!(!listing.entries.iter().any(|e| e.name == "secret.txt"));
+    }
+}
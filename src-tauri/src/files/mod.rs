@@ -0,0 +1,20 @@
+//! File Access Module
+//!
+//! File commands for the frontend and the agent, limited to the folders the
+//! user has granted:
+//! - Permission-checked path resolution with structured errors
+//! - Typed directory listings with recursion, glob filters and paging
+//! - Chunked binary reads and writes, and file streaming to the frontend
+//! - Regex search across a folder for code navigation
+//! - Text extraction from PDF, DOCX and XLSX documents
+//! - Image OCR through Tesseract, and text of chat attachments
+
+pub mod access;
+pub mod binary;
+pub mod commands;
+pub mod extract;
+pub mod listing;
+pub mod ocr;
+pub mod search;
+
+pub use access::{Access, FileErrorKind};
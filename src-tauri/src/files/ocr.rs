@@ -0,0 +1,537 @@
+//! Image OCR
+//!
+//! Text recognition for images in permitted folders, such as chat
+//! attachments, through the Tesseract command-line tool. Tesseract is not
+//! bundled: it is looked up on the PATH unless the OCR settings name its
+//! binary, and languages are its installed language packs (`eng`, `kor`,
+//! `eng+kor`, ...). With `autoOcrAttachments` enabled, `read_attachment`
+//! returns the recognized text of image attachments alongside document and
+//! plain-text attachments.
+
+use super::access::{resolve_with, Access, FileError, FileErrorKind};
+use super::extract::{self, DocumentFormat};
+use crate::db::{settings, DbState};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+/// Setting holding the OCR settings as JSON
+const SETTINGS_KEY: &str = "ocr";
+
+const DEFAULT_LANGUAGE: &str = "eng";
+
+/// Recognition of a single image is killed after this long
+const OCR_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Largest image that will be recognized
+const MAX_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Largest plain-text attachment read in full
+const MAX_TEXT_ATTACHMENT_BYTES: u64 = 1024 * 1024;
+
+/// Image types Tesseract (through Leptonica) can read
+const IMAGE_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/tiff",
+    "image/bmp",
+    "image/gif",
+    "image/webp",
+];
+
+/// OCR preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OcrSettings {
+    /// Recognize the text of image attachments automatically
+    pub auto_ocr_attachments: bool,
+    /// Language used when a request does not name one
+    pub default_language: String,
+    /// Tesseract binary; `tesseract` on the PATH when unset
+    pub tesseract_path: Option<String>,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            auto_ocr_attachments: false,
+            default_language: DEFAULT_LANGUAGE.to_string(),
+            tesseract_path: None,
+        }
+    }
+}
+
+impl OcrSettings {
+    fn binary(&self) -> &str {
+        self.tesseract_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .unwrap_or("tesseract")
+    }
+}
+
+pub fn load_settings(conn: &rusqlite::Connection) -> Result<OcrSettings, String> {
+    match settings::get(conn, SETTINGS_KEY)? {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid OCR settings: {}", e))
+        }
+        None => Ok(OcrSettings::default()),
+    }
+}
+
+pub fn save_settings(conn: &rusqlite::Connection, ocr: &OcrSettings) -> Result<(), String> {
+    validate_language(&ocr.default_language)?;
+    let json = serde_json::to_string(ocr).map_err(|e| e.to_string())?;
+    settings::set(conn, SETTINGS_KEY, &json)
+}
+
+/// Check a Tesseract language spec such as `eng` or `eng+chi_sim`
+pub fn validate_language(language: &str) -> Result<(), String> {
+    let valid = !language.is_empty()
+        && language.split('+').all(|code| {
+            !code.is_empty()
+                && code
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid OCR language '{}'; use codes like eng or eng+kor",
+            language
+        ))
+    }
+}
+
+/// Mime type of an image Tesseract can read, from the file's leading bytes
+pub fn image_mime(path: &Path) -> Option<&'static str> {
+    let mut head = [0u8; 64];
+    let len = std::fs::File::open(path)
+        .and_then(|mut f| f.read(&mut head))
+        .ok()?;
+    let mime = infer::get(&head[..len])?.mime_type();
+    IMAGE_MIME_TYPES.iter().copied().find(|m| *m == mime)
+}
+
+/// Recognized text of an image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrResult {
+    pub path: String,
+    pub language: String,
+    pub text: String,
+}
+
+fn drain<R: Read + Send + 'static>(stream: Option<R>) -> Option<std::thread::JoinHandle<Vec<u8>>> {
+    stream.map(|mut stream| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stream.read_to_end(&mut buf);
+            buf
+        })
+    })
+}
+
+/// Run Tesseract, killing it after `OCR_TIMEOUT`
+fn run_tesseract(binary: &str, args: &[&std::ffi::OsStr]) -> Result<Output, String> {
+    let mut child = Command::new(binary)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!(
+                "Tesseract was not found ({}). Install it, e.g. `brew install tesseract` or `apt install tesseract-ocr`, or set its path in the OCR settings",
+                binary
+            ),
+            _ => format!("Failed to start Tesseract: {}", e),
+        })?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if started.elapsed() >= OCR_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!(
+                "OCR timed out after {} seconds",
+                OCR_TIMEOUT.as_secs()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    let collect = |handle: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        handle.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Why Tesseract failed, from its error output
+fn failure_message(language: &str, stderr: &str) -> String {
+    if stderr.contains("Failed loading language") || stderr.contains("Error opening data file") {
+        return format!(
+            "The Tesseract language pack for '{}' is not installed",
+            language
+        );
+    }
+    let detail = stderr
+        .lines()
+        .map(str::trim)
+        .rfind(|l| !l.is_empty())
+        .unwrap_or("unknown error");
+    format!("Tesseract failed: {}", detail)
+}
+
+/// Tidy recognized text: drop page breaks, trim lines and collapse blank runs
+fn tidy(text: &str) -> String {
+    let mut out = String::new();
+    let mut blank = false;
+    for line in text.replace('\x0c', "\n").lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(if blank { "\n\n" } else { "\n" });
+        }
+        blank = false;
+        out.push_str(line);
+    }
+    out
+}
+
+/// Recognize the text of an image; the path must already be resolved
+pub fn recognize(path: &Path, language: &str, ocr: &OcrSettings) -> Result<OcrResult, FileError> {
+    validate_language(language)
+        .map_err(|e| FileError::new(FileErrorKind::InvalidPattern, path, e))?;
+    let metadata = std::fs::metadata(path).map_err(|e| FileError::io(path, e))?;
+    if metadata.len() > MAX_IMAGE_BYTES {
+        return Err(FileError::new(
+            FileErrorKind::UnsupportedFormat,
+            path,
+            format!(
+                "{} is too large for OCR ({} bytes)",
+                path.display(),
+                metadata.len()
+            ),
+        ));
+    }
+    if image_mime(path).is_none() {
+        return Err(FileError::new(
+            FileErrorKind::UnsupportedFormat,
+            path,
+            format!(
+                "{} is not a PNG, JPEG, TIFF, BMP, GIF or WebP image",
+                path.display()
+            ),
+        ));
+    }
+
+    let output = run_tesseract(
+        ocr.binary(),
+        &[
+            path.as_os_str(),
+            "stdout".as_ref(),
+            "-l".as_ref(),
+            language.as_ref(),
+        ],
+    )
+    .map_err(|e| FileError::internal(path, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(FileError::internal(
+            path,
+            failure_message(language, &stderr),
+        ));
+    }
+
+    Ok(OcrResult {
+        path: path.display().to_string(),
+        language: language.to_string(),
+        text: tidy(&String::from_utf8_lossy(&output.stdout)),
+    })
+}
+
+/// Language packs Tesseract has installed
+pub fn languages(ocr: &OcrSettings) -> Result<Vec<String>, String> {
+    let output = run_tesseract(ocr.binary(), &["--list-langs".as_ref()])?;
+    // Older versions print the list to stderr
+    let listing = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(listing
+        .lines()
+        .skip_while(|line| !line.starts_with("List of available languages"))
+        .skip(1)
+        .map(str::trim)
+        .filter(|code| validate_language(code).is_ok() && !code.contains('+'))
+        .map(String::from)
+        .collect())
+}
+
+// ============================================================================
+// Attachments
+// ============================================================================
+
+/// Kind of a chat attachment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    Image,
+    Document,
+    Text,
+    Other,
+}
+
+/// Text content of a chat attachment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentText {
+    pub path: String,
+    pub kind: AttachmentKind,
+    /// None for binary files, and for images while automatic OCR is off
+    pub text: Option<String>,
+    /// Whether the text was recognized from an image
+    pub ocr: bool,
+}
+
+/// Text of an attachment: document text, plain text, or OCR text of an
+/// image when `auto_ocr_attachments` is on
+pub fn attachment_text(path: &Path, ocr: &OcrSettings) -> Result<AttachmentText, FileError> {
+    let attachment = |kind, text: Option<String>, ocr| AttachmentText {
+        path: path.display().to_string(),
+        kind,
+        text,
+        ocr,
+    };
+
+    if image_mime(path).is_some() {
+        if !ocr.auto_ocr_attachments {
+            return Ok(attachment(AttachmentKind::Image, None, false));
+        }
+        let result = recognize(path, &ocr.default_language, ocr)?;
+        return Ok(attachment(AttachmentKind::Image, Some(result.text), true));
+    }
+    if DocumentFormat::of(path).is_some() {
+        let document = extract::extract(path)?;
+        return Ok(attachment(
+            AttachmentKind::Document,
+            Some(document.text()),
+            false,
+        ));
+    }
+
+    let metadata = std::fs::metadata(path).map_err(|e| FileError::io(path, e))?;
+    if metadata.len() <= MAX_TEXT_ATTACHMENT_BYTES {
+        let data = std::fs::read(path).map_err(|e| FileError::io(path, e))?;
+        if let Ok(text) = String::from_utf8(data) {
+            return Ok(attachment(AttachmentKind::Text, Some(text), false));
+        }
+    }
+    Ok(attachment(AttachmentKind::Other, None, false))
+}
+
+/// Host method behind the agent's `ocr_image` tool
+pub fn ocr_request(
+    app: &tauri::AppHandle,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let path = params
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing path")?;
+    let db = app.state::<DbState>();
+    let resolved = resolve_with(&db, path, Access::Read).map_err(|e| e.message)?;
+    let ocr = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        load_settings(&conn)?
+    };
+    let language = params
+        .get("language")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| ocr.default_language.clone());
+    let result = recognize(&resolved, &language, &ocr).map_err(|e| e.message)?;
+    serde_json::to_value(result).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+fn settings_with(db: &DbState, path: &str) -> Result<OcrSettings, FileError> {
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| FileError::internal(Path::new(path), e.to_string()))?;
+    load_settings(&conn).map_err(|e| FileError::internal(Path::new(path), e))
+}
+
+/// Recognize the text of an image in a permitted folder
+///
+/// Uses the default language from the OCR settings unless `language` is given.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn ocr_image(
+    db: tauri::State<'_, DbState>,
+    path: String,
+    language: Option<String>,
+) -> Result<OcrResult, AppError> {
+    let resolved = resolve_with(&db, &path, Access::Read)?;
+    let ocr = settings_with(&db, &path)?;
+    let language = language
+        .filter(|l| !l.trim().is_empty())
+        .unwrap_or_else(|| ocr.default_language.clone());
+    let result = tokio::task::spawn_blocking(move || recognize(&resolved, language.trim(), &ocr))
+        .await
+        .map_err(|e| FileError::internal(Path::new(&path), e.to_string()))??;
+    Ok(result)
+}
+
+/// Text of a chat attachment in a permitted folder
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn read_attachment(
+    db: tauri::State<'_, DbState>,
+    path: String,
+) -> Result<AttachmentText, AppError> {
+    let resolved = resolve_with(&db, &path, Access::Read)?;
+    let ocr = settings_with(&db, &path)?;
+    let text = tokio::task::spawn_blocking(move || attachment_text(&resolved, &ocr))
+        .await
+        .map_err(|e| FileError::internal(Path::new(&path), e.to_string()))??;
+    Ok(text)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_ocr_settings(db: tauri::State<'_, DbState>) -> Result<OcrSettings, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(load_settings(&conn)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn save_ocr_settings(
+    db: tauri::State<'_, DbState>,
+    settings: OcrSettings,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(save_settings(&conn, &settings)?)
+}
+
+/// Installed Tesseract language packs; fails when Tesseract is missing
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn list_ocr_languages(db: tauri::State<'_, DbState>) -> Result<Vec<String>, AppError> {
+    let ocr = {
+        let conn = db.conn.lock()?;
+        load_settings(&conn)?
+    };
+    let languages = tokio::task::spawn_blocking(move || languages(&ocr))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(languages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    /// Smallest valid PNG header, enough for type detection
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x02\0\0\0";
+
+    #[test]
+    fn test_settings_and_language() {
+        let conn = test_conn();
+        assert_eq!(load_settings(&conn).unwrap(), OcrSettings::default());
+
+        let ocr = OcrSettings {
+            auto_ocr_attachments: true,
+            default_language: "eng+kor".to_string(),
+            tesseract_path: Some("/opt/homebrew/bin/tesseract".to_string()),
+        };
+        save_settings(&conn, &ocr).unwrap();
+        assert_eq!(load_settings(&conn).unwrap(), ocr);
+        assert_eq!(ocr.binary(), "/opt/homebrew/bin/tesseract");
+
+        assert!(validate_language("chi_sim").is_ok());
+        assert!(validate_language("eng+").is_err());
+        assert!(validate_language("eng; rm -rf /").is_err());
+        assert!(save_settings(
+            &conn,
+            &OcrSettings {
+                default_language: String::new(),
+                ..ocr
+            }
+        )
+        .is_err());
+
+        assert_eq!(tidy("Invoice  \n\n\n No. 42\n\x0c"), "Invoice\n\n No. 42");
+        assert_eq!(
+            failure_message(
+                "kor",
+                "Error opening data file /usr/share/tessdata/kor.traineddata"
+            ),
+            "The Tesseract language pack for 'kor' is not installed"
+        );
+    }
+
+    #[test]
+    fn test_attachment_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("scan.png");
+        std::fs::write(&image, PNG).unwrap();
+        let notes = dir.path().join("notes.md");
+        std::fs::write(&notes, "# Notes").unwrap();
+        let blob = dir.path().join("blob.bin");
+        std::fs::write(&blob, [0xff, 0xfe, 0x00, 0x81]).unwrap();
+
+        // Images are left alone while automatic OCR is off
+        let ocr = OcrSettings::default();
+        let attachment = attachment_text(&image, &ocr).unwrap();
+        assert_eq!(
+            (attachment.kind, attachment.text, attachment.ocr),
+            (AttachmentKind::Image, None, false)
+        );
+        assert_eq!(
+            attachment_text(&notes, &ocr).unwrap().text.as_deref(),
+            Some("# Notes")
+        );
+        assert_eq!(
+            attachment_text(&blob, &ocr).unwrap().kind,
+            AttachmentKind::Other
+        );
+
+        // A missing Tesseract is reported, not a crash
+        let ocr = OcrSettings {
+            auto_ocr_attachments: true,
+            tesseract_path: Some(dir.path().join("no-tesseract").display().to_string()),
+            ..OcrSettings::default()
+        };
+        let error = attachment_text(&image, &ocr).unwrap_err();
+        assert!(error.message.contains("Tesseract was not found"));
+        assert_eq!(
+            recognize(&notes, "eng", &ocr).unwrap_err().kind,
+            FileErrorKind::UnsupportedFormat
+        );
+    }
+}
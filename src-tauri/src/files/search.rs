@@ -0,0 +1,316 @@
+//! Code Search
+//!
+//! Regex search across the files of a permitted folder, in the spirit of
+//! ripgrep: `.gitignore` files are respected by default, binary files are
+//! skipped, and each match carries its line, column and surrounding lines.
+//! Files are walked with `listing::walk`, so symlinked directories are not
+//! entered.
+
+use super::access::{resolve_with, Access, FileError, FileErrorKind};
+use super::listing::{self, EntryKind, ListOptions};
+use crate::db::DbState;
+use crate::error::AppError;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::Manager;
+
+/// Matches returned when the caller does not set a limit
+const DEFAULT_MAX_RESULTS: usize = 500;
+
+/// Most matches a caller may ask for
+const MAX_RESULTS: usize = 5000;
+
+/// Most context lines a caller may ask for on each side of a match
+const MAX_CONTEXT_LINES: usize = 10;
+
+/// Files larger than this are skipped
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Leading bytes checked for a NUL byte to detect binary files
+const BINARY_CHECK_BYTES: usize = 8192;
+
+/// Longest line returned; longer lines are cut around the match
+const MAX_LINE_CHARS: usize = 500;
+
+/// Options for `search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    /// Treat the pattern as a literal string instead of a regex
+    pub literal: bool,
+    pub case_insensitive: bool,
+    /// Only search files matching one of these globs
+    pub include: Vec<String>,
+    /// Skip files and folders matching any of these globs
+    pub exclude: Vec<String>,
+    pub respect_gitignore: bool,
+    pub max_results: Option<usize>,
+    /// Lines of context before and after each match
+    pub context_lines: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            literal: false,
+            case_insensitive: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            respect_gitignore: true,
+            max_results: None,
+            context_lines: 0,
+        }
+    }
+}
+
+/// Matching line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub path: String,
+    /// Path relative to the searched folder, with `/` separators
+    pub relative_path: String,
+    /// 1-based line number
+    pub line_number: usize,
+    /// 1-based character column of the first match on the line
+    pub column: usize,
+    pub line: String,
+    /// Character range of the first match within `line`
+    pub match_start: usize,
+    pub match_end: usize,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Search outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub matches: Vec<SearchMatch>,
+    pub files_searched: usize,
+    /// Binary files and files over the size limit
+    pub files_skipped: usize,
+    /// Whether the search stopped at `max_results`
+    pub truncated: bool,
+}
+
+/// Build the search regex from a pattern and options
+fn compile(pattern: &str, options: &SearchOptions, root: &Path) -> Result<Regex, FileError> {
+    let source = if options.literal { regex::escape(pattern) } else { pattern.to_string() };
+    RegexBuilder::new(&source)
+        .case_insensitive(options.case_insensitive)
+        .build()
+        .map_err(|e| FileError::new(FileErrorKind::InvalidPattern, root, format!("Invalid search pattern: {}", e)))
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_CHECK_BYTES)].contains(&0)
+}
+
+/// Cut a long line to at most `MAX_LINE_CHARS` around a character range,
+/// returning the line and the range shifted to match
+fn clip_line(line: &str, start: usize, end: usize) -> (String, usize, usize) {
+    let chars = line.chars().count();
+    if chars <= MAX_LINE_CHARS {
+        return (line.to_string(), start, end);
+    }
+    let from = start.saturating_sub(MAX_LINE_CHARS / 4).min(chars - MAX_LINE_CHARS);
+    let clipped: String = line.chars().skip(from).take(MAX_LINE_CHARS).collect();
+    (clipped, start - from, (end - from).min(MAX_LINE_CHARS))
+}
+
+fn clip_context(line: &str) -> String {
+    line.chars().take(MAX_LINE_CHARS).collect()
+}
+
+/// Search one file's text, appending at most `remaining` matches
+fn search_text(regex: &Regex, text: &str, path: &str, relative_path: &str, context: usize, remaining: usize) -> Vec<SearchMatch> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut matches = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if matches.len() >= remaining {
+            break;
+        }
+        let Some(found) = regex.find(line) else { continue };
+        let start = line[..found.start()].chars().count();
+        let end = start + found.as_str().chars().count();
+        let (clipped, match_start, match_end) = clip_line(line, start, end);
+
+        matches.push(SearchMatch {
+            path: path.to_string(),
+            relative_path: relative_path.to_string(),
+            line_number: index + 1,
+            column: start + 1,
+            line: clipped,
+            match_start,
+            match_end,
+            before: lines[index.saturating_sub(context)..index].iter().map(|l| clip_context(l)).collect(),
+            after: lines[index + 1..(index + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| clip_context(l))
+                .collect(),
+        });
+    }
+    matches
+}
+
+/// Search a folder that has already been permission-checked
+pub fn search(root: &Path, pattern: &str, options: &SearchOptions) -> Result<SearchResult, FileError> {
+    if pattern.is_empty() {
+        return Err(FileError::new(FileErrorKind::InvalidPattern, root, "Search pattern is empty"));
+    }
+    let regex = compile(pattern, options, root)?;
+    let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, MAX_RESULTS);
+    let context = options.context_lines.min(MAX_CONTEXT_LINES);
+    let walk_options = ListOptions {
+        recursive: true,
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        respect_gitignore: options.respect_gitignore,
+        ..Default::default()
+    };
+
+    // Directories are walked even when they don't match `include`, so an
+    // include filter applies to files only
+    let mut result = SearchResult {
+        matches: Vec::new(),
+        files_searched: 0,
+        files_skipped: 0,
+        truncated: false,
+    };
+    listing::walk(root, &walk_options, |entry| {
+        if entry.kind != EntryKind::File || entry.is_symlink {
+            return true;
+        }
+        if entry.size > MAX_FILE_BYTES {
+            result.files_skipped += 1;
+            return true;
+        }
+        let path = Path::new(&entry.path);
+        let Ok(bytes) = std::fs::read(path) else { return true };
+        if is_binary(&bytes) {
+            result.files_skipped += 1;
+            return true;
+        }
+
+        result.files_searched += 1;
+        let relative = path
+            .strip_prefix(root)
+            .map(|p| p.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+            .unwrap_or_else(|_| entry.name.clone());
+        let remaining = max_results - result.matches.len();
+        let found = search_text(&regex, &String::from_utf8_lossy(&bytes), &entry.path, &relative, context, remaining);
+        result.matches.extend(found);
+
+        if result.matches.len() >= max_results {
+            result.truncated = true;
+            return false;
+        }
+        true
+    })?;
+    Ok(result)
+}
+
+/// Answer a `search_in_folder` request from the agent runtime, which has
+/// already authorized the tool call
+pub fn search_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = params
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing path")?;
+    let pattern = params
+        .get("pattern")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing pattern")?;
+    let options: SearchOptions = match params.get("options") {
+        Some(options) => serde_json::from_value(options.clone()).map_err(|e| format!("Invalid search options: {}", e))?,
+        None => SearchOptions::default(),
+    };
+
+    let db = app.state::<DbState>();
+    let root = resolve_with(&db, path, Access::Read).map_err(|e| e.message)?;
+    let result = search(&root, pattern, &options).map_err(|e| e.message)?;
+    serde_json::to_value(result).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Search the files of a permitted folder for a regex
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn search_in_folder(
+    db: tauri::State<'_, DbState>,
+    path: String,
+    pattern: String,
+    options: Option<SearchOptions>,
+) -> Result<SearchResult, AppError> {
+    let root = resolve_with(&db, &path, Access::Read)?;
+    let options = options.unwrap_or_default();
+    let result = tokio::task::spawn_blocking(move || search(&root, &pattern, &options))
+        .await
+        .map_err(|e| FileError::internal(Path::new(&path), e.to_string()))??;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "use app;\n\nfn main() {\n    app::run();\n}\n").unwrap();
+        std::fs::write(dir.path().join("src/app.rs"), "pub fn run() {}\npub fn Run_later() {}\n").unwrap();
+        std::fs::write(dir.path().join("target/gen.rs"), "fn run() {}\n").unwrap();
+        std::fs::write(dir.path().join("src/blob.bin"), b"run\x00\x01").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_search_matches_with_context() {
+        let dir = project();
+        let options = SearchOptions { context_lines: 1, ..Default::default() };
+        let result = search(dir.path(), r"run\(", &options).unwrap();
+
+        assert_eq!((result.files_searched, result.files_skipped), (3, 1));
+        let found: Vec<_> = result.matches.iter().map(|m| (m.relative_path.as_str(), m.line_number)).collect();
+        assert_eq!(found, vec![("src/app.rs", 1), ("src/main.rs", 4)]);
+
+        let m = &result.matches[1];
+        assert_eq!((m.column, m.match_start, m.match_end), (10, 9, 13));
+        assert_eq!(m.before, vec!["fn main() {".to_string()]);
+        assert_eq!(m.after, vec!["}".to_string()]);
+    }
+
+    #[test]
+    fn test_search_options() {
+        let dir = project();
+
+        let options = SearchOptions { case_insensitive: true, include: vec!["app.rs".to_string()], ..Default::default() };
+        assert_eq!(search(dir.path(), "fn run", &options).unwrap().matches.len(), 2);
+
+        let options = SearchOptions { respect_gitignore: false, max_results: Some(2), ..Default::default() };
+        let result = search(dir.path(), "run", &options).unwrap();
+        assert!(result.truncated);
+        assert_eq!(result.matches.len(), 2);
+
+        let options = SearchOptions { literal: true, ..Default::default() };
+        assert_eq!(search(dir.path(), "app::run()", &options).unwrap().matches.len(), 1);
+        let error = search(dir.path(), "(", &SearchOptions::default()).unwrap_err();
+        assert_eq!(error.kind, FileErrorKind::InvalidPattern);
+    }
+
+    #[test]
+    fn test_clip_long_line() {
+        let line = format!("{}needle{}", "a".repeat(1000), "b".repeat(1000));
+        let (clipped, start, end) = clip_line(&line, 1000, 1006);
+        assert_eq!(clipped.chars().count(), MAX_LINE_CHARS);
+        assert_eq!(&clipped[start..end], "needle");
+    }
+}
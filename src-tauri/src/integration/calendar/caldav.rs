@@ -0,0 +1,114 @@
+//! CalDAV (RFC 4791) calendar queries
+//!
+//! Fetches the events of one calendar collection in a time range with a
+//! `calendar-query` REPORT. The URL must point at the calendar itself, e.g.
+//! `https://caldav.example.com/calendars/me/work/`; server discovery is not
+//! attempted.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn query_body(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    let format = "%Y%m%dT%H%M%SZ";
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        start.format(format),
+        end.format(format)
+    )
+}
+
+fn unescape_xml(text: &str) -> String {
+    if let Some(cdata) = text.trim().strip_prefix("<![CDATA[").and_then(|t| t.strip_suffix("]]>")) {
+        return cdata.to_string();
+    }
+    text.replace("&#13;", "\r")
+        .replace("&#xD;", "\r")
+        .replace("&#10;", "\n")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// ICS documents in the `calendar-data` elements of a multistatus response
+fn calendar_data(multistatus: &str) -> Vec<String> {
+    let pattern = regex::Regex::new(r"(?s)<(?:[\w-]+:)?calendar-data\b[^>]*>(.*?)</(?:[\w-]+:)?calendar-data>")
+        .expect("valid regex");
+    pattern
+        .captures_iter(multistatus)
+        .map(|c| unescape_xml(&c[1]))
+        .filter(|data| !data.trim().is_empty())
+        .collect()
+}
+
+/// ICS documents of the events in `[start, end)`
+pub async fn fetch(
+    url: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<String>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let method = reqwest::Method::from_bytes(b"REPORT").map_err(|e| e.to_string())?;
+    let mut request = client
+        .request(method, url)
+        .header("Depth", "1")
+        .header(reqwest::header::CONTENT_TYPE, "application/xml; charset=utf-8")
+        .body(query_body(start, end));
+    if let Some(username) = username.filter(|u| !u.is_empty()) {
+        request = request.basic_auth(username, password);
+    }
+
+    let response = request.send().await.map_err(|e| format!("CalDAV request failed: {}", e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("CalDAV server rejected the username or password".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("CalDAV server returned {}", status));
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    Ok(calendar_data(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_data() {
+        let response = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response><d:propstat><d:prop>
+    <cal:calendar-data>BEGIN:VCALENDAR&#13;
+SUMMARY:R&amp;D &lt;sync&gt;&#13;
+END:VCALENDAR</cal:calendar-data>
+  </d:prop></d:propstat></d:response>
+  <d:response><d:propstat><d:prop>
+    <calendar-data xmlns="urn:ietf:params:xml:ns:caldav"><![CDATA[BEGIN:VCALENDAR
+END:VCALENDAR]]></calendar-data>
+  </d:prop></d:propstat></d:response>
+</d:multistatus>"#;
+        let documents = calendar_data(response);
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0], "BEGIN:VCALENDAR\r\nSUMMARY:R&D <sync>\r\nEND:VCALENDAR");
+        assert_eq!(documents[1], "BEGIN:VCALENDAR\nEND:VCALENDAR");
+    }
+}
@@ -0,0 +1,608 @@
+//! iCalendar (RFC 5545) parsing
+//!
+//! Reads the VEVENTs of an ICS document and expands recurring events into
+//! the occurrences inside a time window. Covers what calendar exports use in
+//! practice: DAILY/WEEKLY/MONTHLY/YEARLY rules with INTERVAL, COUNT, UNTIL
+//! and BYDAY, EXDATE, and RECURRENCE-ID overrides.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use std::collections::{HashMap, HashSet};
+
+/// Upper bounds on recurrence periods walked and occurrences kept per event,
+/// against runaway rules
+const MAX_PERIODS: i64 = 100_000;
+const MAX_OCCURRENCES: usize = 5_000;
+
+/// Event occurrence inside the requested window
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub all_day: bool,
+}
+
+/// Time zone of a DATE-TIME value
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Named(chrono_tz::Tz),
+    /// No zone given; the user's local time
+    Floating,
+}
+
+/// DATE or DATE-TIME value, kept in its own zone so recurrences follow
+/// daylight saving changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IcsTime {
+    local: NaiveDateTime,
+    zone: Zone,
+    all_day: bool,
+}
+
+impl IcsTime {
+    fn to_utc(self) -> DateTime<Utc> {
+        fn resolve<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+            // Times skipped by a daylight saving change fall back to UTC
+            tz.from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+        }
+        match self.zone {
+            Zone::Utc => Utc.from_utc_datetime(&self.local),
+            Zone::Named(tz) => resolve(&tz, self.local),
+            Zone::Floating => resolve(&Local, self.local),
+        }
+    }
+
+    fn with_local(self, local: NaiveDateTime) -> Self {
+        Self { local, ..self }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// RRULE subset
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Utc>>,
+    /// Weekdays with an optional ordinal (`2TU`, `-1FR`) for monthly rules
+    by_day: Vec<(Option<i32>, Weekday)>,
+}
+
+/// A VEVENT as read from the document
+#[derive(Debug, Clone, Default)]
+struct RawEvent {
+    properties: Vec<Property>,
+}
+
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+impl RawEvent {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(|p| unescape(&p.value)).filter(|v| !v.trim().is_empty())
+    }
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match line.chars().next() {
+            Some(' ') | Some('\t') if !lines.is_empty() => {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(&line[1..]);
+                }
+            }
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split `NAME;PARAM=x:value`, minding quoted parameter values
+fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn parse_time(property: &Property) -> Option<IcsTime> {
+    let value = property.value.trim();
+    if property.params.get("VALUE").map(String::as_str) == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime {
+            local: date.and_time(NaiveTime::MIN),
+            zone: Zone::Floating,
+            all_day: true,
+        });
+    }
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = if utc {
+        Zone::Utc
+    } else {
+        // Unknown zone names (e.g. Windows names) are read as local time
+        property
+            .params
+            .get("TZID")
+            .and_then(|tzid| tzid.trim_start_matches('/').parse().ok())
+            .map(Zone::Named)
+            .unwrap_or(Zone::Floating)
+    };
+    Some(IcsTime {
+        local,
+        zone,
+        all_day: false,
+    })
+}
+
+/// Parse an ISO 8601 duration such as `PT1H30M` or `-P1D`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = std::mem::take(&mut number).parse().ok()?;
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_rule(value: &str) -> Option<Rule> {
+    let parts: HashMap<String, String> = value
+        .split(';')
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim().to_ascii_uppercase()))
+        .collect();
+    let frequency = match parts.get("FREQ")?.as_str() {
+        "DAILY" => Frequency::Daily,
+        "WEEKLY" => Frequency::Weekly,
+        "MONTHLY" => Frequency::Monthly,
+        "YEARLY" => Frequency::Yearly,
+        // Sub-daily rules are rare in calendars and would flood the cache
+        _ => return None,
+    };
+    let until = parts.get("UNTIL").and_then(|until| {
+        let property = Property {
+            name: "UNTIL".to_string(),
+            params: HashMap::new(),
+            value: until.clone(),
+        };
+        parse_time(&property).map(|t| match t.all_day {
+            // A date-only UNTIL includes that whole day
+            true => t.with_local(t.local + Duration::days(1) - Duration::seconds(1)).to_utc(),
+            false => t.to_utc(),
+        })
+    });
+    let by_day = parts
+        .get("BYDAY")
+        .map(|days| {
+            days.split(',')
+                .filter_map(|day| {
+                    let split = day.len().checked_sub(2)?;
+                    let weekday = parse_weekday(&day[split..])?;
+                    let ordinal = match &day[..split] {
+                        "" => None,
+                        n => Some(n.parse().ok()?),
+                    };
+                    Some((ordinal, weekday))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(Rule {
+        frequency,
+        interval: parts.get("INTERVAL").and_then(|i| i.parse().ok()).filter(|i| *i > 0).unwrap_or(1),
+        count: parts.get("COUNT").and_then(|c| c.parse().ok()),
+        until,
+        by_day,
+    })
+}
+
+/// Add months, keeping the day; `None` when the month is too short
+fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let index = date.year() as i64 * 12 + date.month0() as i64 + months;
+    NaiveDate::from_ymd_opt((index / 12) as i32, (index % 12) as u32 + 1, date.day())
+}
+
+/// The `n`th (or, when negative, `n`th from last) `weekday` of a month
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i32) -> Option<NaiveDate> {
+    if n > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+    } else {
+        let first_of_next = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        let last = first_of_next.pred_opt()?;
+        let back = (last.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        let date = last - Duration::days(back as i64 + 7 * (-n as i64 - 1));
+        (date.month() == month).then_some(date)
+    }
+}
+
+/// Dates of the recurrence set's `period`th period, in order
+fn period_dates(rule: &Rule, start: NaiveDate, period: i64) -> Vec<NaiveDate> {
+    let step = period * rule.interval as i64;
+    match rule.frequency {
+        Frequency::Daily => vec![start + Duration::days(step)],
+        Frequency::Weekly if rule.by_day.is_empty() => vec![start + Duration::weeks(step)],
+        Frequency::Weekly => {
+            let week_start = start - Duration::days(start.weekday().num_days_from_monday() as i64) + Duration::weeks(step);
+            let mut dates: Vec<NaiveDate> = rule
+                .by_day
+                .iter()
+                .map(|(_, day)| week_start + Duration::days(day.num_days_from_monday() as i64))
+                .collect();
+            dates.sort();
+            dates.dedup();
+            dates
+        }
+        Frequency::Monthly if rule.by_day.iter().any(|(n, _)| n.is_some()) => {
+            let Some(month) = add_months(start.with_day(1).unwrap_or(start), step) else {
+                return Vec::new();
+            };
+            let mut dates: Vec<NaiveDate> = rule
+                .by_day
+                .iter()
+                .filter_map(|(n, day)| nth_weekday(month.year(), month.month(), *day, (*n)?))
+                .collect();
+            dates.sort();
+            dates
+        }
+        Frequency::Monthly => add_months(start, step).into_iter().collect(),
+        Frequency::Yearly => add_months(start, step * 12).into_iter().collect(),
+    }
+}
+
+/// Starts of the occurrences of a recurring event from `earliest` up to
+/// `window_end`; earlier ones still count towards COUNT
+fn expand(
+    start: IcsTime,
+    rule: &Rule,
+    exdates: &HashSet<DateTime<Utc>>,
+    earliest: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let mut starts = Vec::new();
+    let mut generated = 0;
+    'periods: for period in 0..MAX_PERIODS {
+        if starts.len() >= MAX_OCCURRENCES {
+            break;
+        }
+        for date in period_dates(rule, start.local.date(), period) {
+            if date < start.local.date() {
+                continue;
+            }
+            let at = start.with_local(date.and_time(start.local.time())).to_utc();
+            if at > window_end || rule.until.is_some_and(|until| at > until) {
+                break 'periods;
+            }
+            generated += 1;
+            if rule.count.is_some_and(|count| generated > count) {
+                break 'periods;
+            }
+            if at >= earliest && !exdates.contains(&at) {
+                starts.push(at);
+            }
+        }
+    }
+    starts
+}
+
+fn read_events(data: &str) -> Vec<RawEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // Nesting inside the current event, e.g. VALARM
+    let mut nested = 0;
+    for line in unfold(data) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+        match (property.name.as_str(), property.value.trim().to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(RawEvent::default()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() && nested > 0 => nested -= 1,
+            _ => {
+                if let Some(event) = current.as_mut().filter(|_| nested == 0) {
+                    event.properties.push(property);
+                }
+            }
+        }
+    }
+    events
+}
+
+/// Event occurrences overlapping `[window_start, window_end)`
+pub fn parse(data: &str, window_start: DateTime<Utc>, window_end: DateTime<Utc>) -> Vec<Occurrence> {
+    let events = read_events(data);
+
+    // Instances moved or changed by an override, per UID
+    let mut overridden: HashMap<String, HashSet<DateTime<Utc>>> = HashMap::new();
+    for event in &events {
+        if let (Some(uid), Some(recurrence_id)) = (event.text("UID"), event.get("RECURRENCE-ID").and_then(parse_time)) {
+            overridden.entry(uid).or_default().insert(recurrence_id.to_utc());
+        }
+    }
+
+    let mut occurrences = Vec::new();
+    for event in events {
+        if event.text("STATUS").is_some_and(|s| s.eq_ignore_ascii_case("CANCELLED")) {
+            continue;
+        }
+        let Some(start) = event.get("DTSTART").and_then(parse_time) else {
+            continue;
+        };
+        let uid = event.text("UID").unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let duration = match (event.get("DTEND").and_then(parse_time), event.get("DURATION")) {
+            (Some(end), _) => end.to_utc() - start.to_utc(),
+            (None, Some(duration)) => parse_duration(&duration.value).unwrap_or_else(Duration::zero),
+            (None, None) if start.all_day => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+
+        let rule = event.get("RRULE").and_then(|p| parse_rule(&p.value));
+        let starts = match rule.filter(|_| event.get("RECURRENCE-ID").is_none()) {
+            Some(rule) => {
+                let mut exdates: HashSet<DateTime<Utc>> = event
+                    .properties
+                    .iter()
+                    .filter(|p| p.name == "EXDATE")
+                    .flat_map(|p| {
+                        p.value
+                            .split(',')
+                            .filter_map(|value| {
+                                let property = Property {
+                                    name: p.name.clone(),
+                                    params: p.params.clone(),
+                                    value: value.to_string(),
+                                };
+                                parse_time(&property)
+                            })
+                            .map(|t| match t.all_day {
+                                // A date-only EXDATE removes the occurrence on that day
+                                true => start.with_local(t.local.date().and_time(start.local.time())).to_utc(),
+                                false => t.to_utc(),
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+                if let Some(moved) = overridden.get(&uid) {
+                    exdates.extend(moved);
+                }
+                expand(start, &rule, &exdates, window_start - duration, window_end)
+            }
+            None => vec![start.to_utc()],
+        };
+
+        for starts_at in starts {
+            let ends_at = starts_at + duration;
+            // Zero-length events still show up at their start
+            let overlaps = ends_at > window_start || (ends_at == starts_at && starts_at >= window_start);
+            if !overlaps || starts_at >= window_end {
+                continue;
+            }
+            occurrences.push(Occurrence {
+                uid: uid.clone(),
+                summary: event.text("SUMMARY").unwrap_or_else(|| "(No title)".to_string()),
+                description: event.text("DESCRIPTION"),
+                location: event.text("LOCATION"),
+                starts_at,
+                ends_at,
+                all_day: start.all_day,
+            });
+        }
+    }
+    occurrences.sort_by_key(|o| o.starts_at);
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    const FEED: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+SUMMARY:Team stand\r\n up\r\n\
+DTSTART;TZID=Europe/Berlin:20260302T093000\r\n\
+DTEND;TZID=Europe/Berlin:20260302T094500\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10\r\n\
+EXDATE;TZID=Europe/Berlin:20260304T093000\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20260309T093000\r\n\
+SUMMARY:Team standup (moved)\r\n\
+DTSTART;TZID=Europe/Berlin:20260309T110000\r\n\
+DURATION:PT15M\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:trip\r\n\
+SUMMARY:Trip\\, Lisbon\r\n\
+LOCATION:Lisbon\r\n\
+DTSTART;VALUE=DATE:20260305\r\n\
+DTEND;VALUE=DATE:20260307\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:cancelled\r\n\
+STATUS:CANCELLED\r\n\
+DTSTART:20260303T120000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_expands_recurrences() {
+        let events = parse(FEED, at("2026-03-01T00:00:00Z"), at("2026-04-01T00:00:00Z"));
+        let standups: Vec<_> = events.iter().filter(|e| e.uid == "standup").collect();
+        let starts: Vec<String> = standups.iter().map(|e| e.starts_at.to_rfc3339()).collect();
+        // Without the EXDATE, with the 9th moved, and an hour earlier in
+        // UTC once Berlin switches to summer time on March 29
+        assert_eq!(
+            starts,
+            vec![
+                "2026-03-02T08:30:00+00:00",
+                "2026-03-09T10:00:00+00:00",
+                "2026-03-11T08:30:00+00:00",
+                "2026-03-16T08:30:00+00:00",
+                "2026-03-18T08:30:00+00:00",
+                "2026-03-23T08:30:00+00:00",
+                "2026-03-25T08:30:00+00:00",
+                "2026-03-30T07:30:00+00:00",
+            ]
+        );
+        assert_eq!(standups[0].summary, "Team standup");
+        assert_eq!(standups[0].ends_at - standups[0].starts_at, Duration::minutes(15));
+        assert_eq!(standups[1].summary, "Team standup (moved)");
+
+        let trip = events.iter().find(|e| e.uid == "trip").unwrap();
+        assert!(trip.all_day);
+        assert_eq!(trip.summary, "Trip, Lisbon");
+        assert_eq!(trip.ends_at - trip.starts_at, Duration::days(2));
+        assert!(events.iter().all(|e| e.uid != "cancelled"));
+    }
+
+    #[test]
+    fn test_rules() {
+        let rule = parse_rule("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20260601T000000Z").unwrap();
+        let start = IcsTime {
+            local: NaiveDate::from_ymd_opt(2026, 1, 30).unwrap().and_hms_opt(17, 0, 0).unwrap(),
+            zone: Zone::Utc,
+            all_day: false,
+        };
+        let starts = expand(start, &rule, &HashSet::new(), at("2026-01-01T00:00:00Z"), at("2027-01-01T00:00:00Z"));
+        let days: Vec<u32> = starts.iter().map(|s| s.day()).collect();
+        assert_eq!(days, vec![30, 27, 27, 24, 29]);
+
+        // Monthly on the 31st skips short months
+        let rule = parse_rule("FREQ=MONTHLY;COUNT=3").unwrap();
+        let start = IcsTime {
+            local: NaiveDate::from_ymd_opt(2026, 1, 31).unwrap().and_hms_opt(9, 0, 0).unwrap(),
+            ..start
+        };
+        let months: Vec<u32> = expand(start, &rule, &HashSet::new(), at("2026-01-01T00:00:00Z"), at("2027-01-01T00:00:00Z"))
+            .iter()
+            .map(|s| s.month())
+            .collect();
+        assert_eq!(months, vec![1, 3, 5]);
+
+        // Old daily events still reach the window
+        let rule = parse_rule("FREQ=DAILY").unwrap();
+        let start = IcsTime {
+            local: NaiveDate::from_ymd_opt(2010, 1, 1).unwrap().and_hms_opt(9, 0, 0).unwrap(),
+            ..start
+        };
+        let starts = expand(start, &rule, &HashSet::new(), at("2026-03-01T00:00:00Z"), at("2026-03-03T00:00:00Z"));
+        assert_eq!(starts, vec![at("2026-03-01T09:00:00Z"), at("2026-03-02T09:00:00Z")]);
+
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("1H"), None);
+        assert!(parse_rule("FREQ=HOURLY").is_none());
+    }
+}
@@ -0,0 +1,578 @@
+//! Calendar Integration Module
+//!
+//! Subscribes to ICS feeds and CalDAV calendars and keeps a local copy of
+//! their upcoming events, synced by the `sync_calendars` system job. Queries,
+//! including the agent's `list_calendar_events` tool, read only the local
+//! copy, so they work offline. CalDAV passwords are kept in the keychain.
+
+pub mod caldav;
+pub mod ics;
+
+use crate::db::columns;
+use crate::error::AppError;
+use crate::security::CredentialManager;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::Manager;
+
+/// Days of past and upcoming events kept in the local copy
+const SYNC_PAST_DAYS: i64 = 1;
+const SYNC_FUTURE_DAYS: i64 = 60;
+
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Calendar protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarKind {
+    /// Read-only ICS subscription URL (`https://` or `webcal://`)
+    Ics,
+    /// CalDAV calendar collection
+    Caldav,
+}
+
+impl CalendarKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ics => "ics",
+            Self::Caldav => "caldav",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ics" => Some(Self::Ics),
+            "caldav" => Some(Self::Caldav),
+            _ => None,
+        }
+    }
+}
+
+/// Subscribed calendar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarSource {
+    pub id: String,
+    pub name: String,
+    pub kind: CalendarKind,
+    pub url: String,
+    pub username: Option<String>,
+    pub enabled: bool,
+    pub created_at: String,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Cached event occurrence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    pub id: String,
+    pub source_id: String,
+    pub calendar: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub starts_at: String,
+    pub ends_at: String,
+    pub all_day: bool,
+}
+
+/// Keychain entry holding a CalDAV password
+pub fn credential_key(id: &str) -> String {
+    format!("calendar.{}", id)
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+const COLUMNS: &str = "id, name, kind, url, username, enabled, created_at, last_synced_at, last_error";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<CalendarSource> {
+    let kind: String = row.get(2)?;
+    Ok(CalendarSource {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: CalendarKind::from_str(&kind).unwrap_or(CalendarKind::Ics),
+        url: columns::get(row, 3)?,
+        username: columns::get_optional(row, 4)?,
+        enabled: row.get(5)?,
+        created_at: row.get(6)?,
+        last_synced_at: row.get(7)?,
+        last_error: row.get(8)?,
+    })
+}
+
+pub fn list_sources(conn: &Connection) -> Result<Vec<CalendarSource>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM calendar_sources ORDER BY name", COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let sources = stmt
+        .query_map([], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(sources)
+}
+
+pub fn get_source(conn: &Connection, id: &str) -> Result<CalendarSource, String> {
+    conn.query_row(&format!("SELECT {} FROM calendar_sources WHERE id = ?1", COLUMNS), [id], from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Calendar not found: {}", id))
+}
+
+/// Check a calendar URL and normalize `webcal://` to `https://`
+pub fn normalize_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+    let parsed = reqwest::Url::parse(&url).map_err(|e| format!("Invalid calendar URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Calendar URLs must use http, https or webcal".to_string());
+    }
+    Ok(url)
+}
+
+/// Add a calendar, or update the one with `id`
+pub fn save_source(
+    conn: &Connection,
+    id: Option<&str>,
+    name: &str,
+    kind: CalendarKind,
+    url: &str,
+    username: Option<&str>,
+    enabled: bool,
+) -> Result<CalendarSource, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Calendar name is required".to_string());
+    }
+    let url = normalize_url(url)?;
+    let username = username.map(str::trim).filter(|u| !u.is_empty());
+    // The URL may embed a private token, and the username identifies the account
+    let url = columns::seal(&url)?;
+    let username = columns::seal_optional(username)?;
+    let id = match id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE calendar_sources SET name = ?1, kind = ?2, url = ?3, username = ?4, enabled = ?5 WHERE id = ?6",
+                    rusqlite::params![name, kind.as_str(), url, username, enabled, id],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Calendar not found: {}", id));
+            }
+            id.to_string()
+        }
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO calendar_sources (id, name, kind, url, username, enabled) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![id, name, kind.as_str(), url, username, enabled],
+            )
+            .map_err(|e| e.to_string())?;
+            id
+        }
+    };
+    get_source(conn, &id)
+}
+
+pub fn delete_source(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM calendar_events WHERE source_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    let deleted = conn
+        .execute("DELETE FROM calendar_sources WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Calendar not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Replace a calendar's cached events with the fetched ones, or record why
+/// fetching failed; the old copy is kept on failure
+pub fn store_events(conn: &mut Connection, source_id: &str, fetched: &Result<Vec<ics::Occurrence>, String>) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    match fetched {
+        Ok(occurrences) => {
+            tx.execute("DELETE FROM calendar_events WHERE source_id = ?1", [source_id])
+                .map_err(|e| e.to_string())?;
+            {
+                let mut insert = tx
+                    .prepare(
+                        "INSERT INTO calendar_events (id, source_id, uid, summary, description, location, starts_at, ends_at, all_day)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    )
+                    .map_err(|e| e.to_string())?;
+                for occurrence in occurrences {
+                    insert
+                        .execute(rusqlite::params![
+                            uuid::Uuid::new_v4().to_string(),
+                            source_id,
+                            occurrence.uid,
+                            occurrence.summary,
+                            occurrence.description,
+                            occurrence.location,
+                            occurrence.starts_at.to_rfc3339(),
+                            occurrence.ends_at.to_rfc3339(),
+                            occurrence.all_day,
+                        ])
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            tx.execute(
+                "UPDATE calendar_sources SET last_synced_at = ?1, last_error = NULL WHERE id = ?2",
+                rusqlite::params![Utc::now().to_rfc3339(), source_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Err(error) => {
+            tx.execute(
+                "UPDATE calendar_sources SET last_error = ?1 WHERE id = ?2",
+                rusqlite::params![error, source_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Cached events of enabled calendars overlapping `[start, end)`, by start
+pub fn events_between(conn: &Connection, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<CalendarEvent>, String> {
+    // Stored as RFC 3339 in UTC, so text order is time order
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.source_id, s.name, e.summary, e.description, e.location, e.starts_at, e.ends_at, e.all_day
+             FROM calendar_events e JOIN calendar_sources s ON s.id = e.source_id
+             WHERE s.enabled = 1 AND e.starts_at < ?2 AND (e.ends_at > ?1 OR e.starts_at >= ?1)
+             ORDER BY e.starts_at, e.summary",
+        )
+        .map_err(|e| e.to_string())?;
+    let events = stmt
+        .query_map(rusqlite::params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+            Ok(CalendarEvent {
+                id: row.get(0)?,
+                source_id: row.get(1)?,
+                calendar: row.get(2)?,
+                summary: row.get(3)?,
+                description: row.get(4)?,
+                location: row.get(5)?,
+                starts_at: row.get(6)?,
+                ends_at: row.get(7)?,
+                all_day: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(events)
+}
+
+// ============================================================================
+// Sync
+// ============================================================================
+
+/// Event occurrences of a calendar in `[start, end)`
+pub async fn fetch(
+    source: &CalendarSource,
+    password: Option<&str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<ics::Occurrence>, String> {
+    let documents = match source.kind {
+        CalendarKind::Ics => {
+            let client = reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .map_err(|e| e.to_string())?;
+            let mut request = client.get(&source.url);
+            if let Some(username) = source.username.as_deref() {
+                request = request.basic_auth(username, password);
+            }
+            let response = request.send().await.map_err(|e| format!("Failed to fetch calendar: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("Calendar server returned {}", response.status()));
+            }
+            vec![response.text().await.map_err(|e| e.to_string())?]
+        }
+        CalendarKind::Caldav => {
+            caldav::fetch(&source.url, source.username.as_deref(), password, start, end).await?
+        }
+    };
+    Ok(documents.iter().flat_map(|data| ics::parse(data, start, end)).collect())
+}
+
+/// Fetch one calendar into the local copy
+pub async fn sync_source(db_path: &Path, source: &CalendarSource) -> Result<usize, String> {
+    let password = source.username.as_ref().and_then(|_| {
+        CredentialManager::default()
+            .and_then(|credentials| credentials.get_password(&credential_key(&source.id)))
+            .ok()
+    });
+    let now = Utc::now();
+    let fetched = fetch(
+        source,
+        password.as_deref(),
+        now - Duration::days(SYNC_PAST_DAYS),
+        now + Duration::days(SYNC_FUTURE_DAYS),
+    )
+    .await;
+
+    let mut conn = crate::db::pool::open_background(db_path)?;
+    store_events(&mut conn, &source.id, &fetched)?;
+    fetched.map(|occurrences| occurrences.len())
+}
+
+/// Sync every enabled calendar; fails if any of them failed
+pub async fn sync_all(db_path: &Path) -> Result<String, String> {
+    let sources = {
+        let conn = crate::db::pool::open_background(db_path)?;
+        list_sources(&conn)?
+    };
+    let sources: Vec<_> = sources.into_iter().filter(|s| s.enabled).collect();
+
+    let mut events = 0;
+    let mut failures = Vec::new();
+    for source in &sources {
+        match sync_source(db_path, source).await {
+            Ok(count) => events += count,
+            Err(e) => failures.push(format!("{}: {}", source.name, e)),
+        }
+    }
+
+    let summary = format!(
+        "Synced {} of {} calendars ({} events)",
+        sources.len() - failures.len(),
+        sources.len(),
+        events
+    );
+    if failures.is_empty() {
+        Ok(summary)
+    } else {
+        Err(format!("{}; {}", summary, failures.join("; ")))
+    }
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+/// Start of `date` in the time zone of `now`
+fn start_of_day<Tz: TimeZone>(now: &DateTime<Tz>, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    now.timezone()
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// Time span named by `range`, in the time zone of `now`
+///
+/// Accepts `today`, `tomorrow`, `week` (today and the next six days),
+/// `month` (the next 30 days), a `YYYY-MM-DD` day, or a `YYYY-MM-DD..YYYY-MM-DD`
+/// span of days. Without a range, the next seven days from now.
+pub fn parse_range<Tz: TimeZone>(range: Option<&str>, now: DateTime<Tz>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let today = now.date_naive();
+    let days = |first: NaiveDate, count: i64| (start_of_day(&now, first), start_of_day(&now, first + Duration::days(count)));
+    let range = range.map(|r| r.trim().to_lowercase()).unwrap_or_default();
+    match range.as_str() {
+        "" | "upcoming" => {
+            let start = now.with_timezone(&Utc);
+            Ok((start, start + Duration::days(7)))
+        }
+        "today" => Ok(days(today, 1)),
+        "tomorrow" => Ok(days(today + Duration::days(1), 1)),
+        "week" => Ok(days(today, 7)),
+        "month" => Ok(days(today, 30)),
+        range => match range.split_once("..") {
+            Some((first, last)) => {
+                let (first, last) = (parse_date(first)?, parse_date(last)?);
+                if last < first {
+                    return Err(format!("Range ends before it starts: {}", range));
+                }
+                Ok(days(first, (last - first).num_days() + 1))
+            }
+            None => Ok(days(parse_date(range)?, 1)),
+        },
+    }
+}
+
+/// Host method behind the agent's `list_calendar_events` tool
+pub fn events_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let range = params.get("range").and_then(|v| v.as_str());
+    let (start, end) = parse_range(range, chrono::Local::now())?;
+
+    let db = app.state::<crate::db::DbState>();
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let events = events_between(&conn, start, end)?;
+    let last_synced_at: Option<String> = conn
+        .query_row("SELECT MAX(last_synced_at) FROM calendar_sources WHERE enabled = 1", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({
+        "start": start.to_rfc3339(),
+        "end": end.to_rfc3339(),
+        "lastSyncedAt": last_synced_at,
+        "events": events,
+    }))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_calendar_sources(db: tauri::State<'_, crate::db::DbState>) -> Result<Vec<CalendarSource>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list_sources(&conn)?)
+}
+
+/// Add a calendar, or update the one with `id`
+///
+/// The CalDAV password goes to the keychain; leave it out to keep the stored one.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
+pub fn save_calendar_source(
+    db: tauri::State<'_, crate::db::DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: Option<String>,
+    name: String,
+    kind: CalendarKind,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    enabled: Option<bool>,
+) -> Result<CalendarSource, AppError> {
+    let conn = db.conn.lock()?;
+    let source = save_source(
+        &conn,
+        id.as_deref(),
+        &name,
+        kind,
+        &url,
+        username.as_deref(),
+        enabled.unwrap_or(true),
+    )?;
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        let credentials = credentials.lock()?;
+        credentials.set_password(&credential_key(&source.id), &password)?;
+    }
+    Ok(source)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_calendar_source(
+    db: tauri::State<'_, crate::db::DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    delete_source(&conn, &id)?;
+    let credentials = credentials.lock()?;
+    // Only CalDAV calendars with a username have a password
+    let _ = credentials.delete_password(&credential_key(&id));
+    Ok(())
+}
+
+/// Sync every enabled calendar now
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn sync_calendars(db: tauri::State<'_, crate::db::DbState>) -> Result<String, AppError> {
+    Ok(sync_all(Path::new(&db.path())).await?)
+}
+
+/// Cached events in `range` (see [`parse_range`]), in local time
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_upcoming_events(
+    db: tauri::State<'_, crate::db::DbState>,
+    range: Option<String>,
+) -> Result<Vec<CalendarEvent>, AppError> {
+    let (start, end) = parse_range(range.as_deref(), chrono::Local::now())?;
+    let conn = db.conn.lock()?;
+    Ok(events_between(&conn, start, end)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn occurrence(uid: &str, starts_at: &str, hours: i64) -> ics::Occurrence {
+        ics::Occurrence {
+            uid: uid.to_string(),
+            summary: uid.to_string(),
+            description: None,
+            location: None,
+            starts_at: at(starts_at),
+            ends_at: at(starts_at) + Duration::hours(hours),
+            all_day: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let now = DateTime::parse_from_rfc3339("2026-03-10T22:30:00+09:00").unwrap();
+        let range = |r: &str| parse_range(Some(r), now).unwrap();
+        assert_eq!(range("today"), (at("2026-03-09T15:00:00Z"), at("2026-03-10T15:00:00Z")));
+        assert_eq!(range("Tomorrow"), (at("2026-03-10T15:00:00Z"), at("2026-03-11T15:00:00Z")));
+        assert_eq!(range("week").1, at("2026-03-16T15:00:00Z"));
+        assert_eq!(range("2026-04-01"), (at("2026-03-31T15:00:00Z"), at("2026-04-01T15:00:00Z")));
+        assert_eq!(range("2026-04-01..2026-04-03").1, at("2026-04-03T15:00:00Z"));
+        assert_eq!(parse_range(None, now).unwrap().0, at("2026-03-10T13:30:00Z"));
+        assert!(parse_range(Some("2026-04-03..2026-04-01"), now).is_err());
+        assert!(parse_range(Some("someday"), now).is_err());
+    }
+
+    #[test]
+    fn test_store_and_query_events() {
+        let mut conn = test_conn();
+        let work = save_source(&conn, None, "Work", CalendarKind::Ics, "webcal://example.com/work.ics", None, true).unwrap();
+        assert_eq!(work.url, "https://example.com/work.ics");
+
+        let fetched = Ok(vec![
+            occurrence("review", "2026-03-11T01:00:00Z", 1),
+            occurrence("offsite", "2026-03-10T20:00:00Z", 48),
+            occurrence("later", "2026-03-20T01:00:00Z", 1),
+        ]);
+        store_events(&mut conn, &work.id, &fetched).unwrap();
+        let events = events_between(&conn, at("2026-03-10T15:00:00Z"), at("2026-03-11T15:00:00Z")).unwrap();
+        let summaries: Vec<_> = events.iter().map(|e| e.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["offsite", "review"]);
+        assert_eq!(events[0].calendar, "Work");
+
+        // A failed sync keeps the old copy and records the error
+        store_events(&mut conn, &work.id, &Err("timed out".to_string())).unwrap();
+        let work = get_source(&conn, &work.id).unwrap();
+        assert_eq!(work.last_error.as_deref(), Some("timed out"));
+        assert!(work.last_synced_at.is_some());
+        assert_eq!(events_between(&conn, at("2026-03-01T00:00:00Z"), at("2026-04-01T00:00:00Z")).unwrap().len(), 3);
+
+        // Disabled calendars are left out of queries
+        save_source(&conn, Some(&work.id), "Work", CalendarKind::Ics, &work.url, None, false).unwrap();
+        assert!(events_between(&conn, at("2026-03-01T00:00:00Z"), at("2026-04-01T00:00:00Z")).unwrap().is_empty());
+
+        delete_source(&conn, &work.id).unwrap();
+        assert!(list_sources(&conn).unwrap().is_empty());
+        assert!(save_source(&conn, None, "Bad", CalendarKind::Caldav, "ftp://example.com", None, true).is_err());
+    }
+}
@@ -0,0 +1,536 @@
+//! Notification Channels
+//!
+//! Outbound Slack, Discord and generic webhooks that scheduled jobs and
+//! workflows post their results to. Channels are listed in the database;
+//! their webhook URLs, which grant posting rights, are kept in the keychain.
+
+use crate::error::AppError;
+use crate::security::CredentialManager;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const MAX_ATTEMPTS: u32 = 3;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Platform limits on message parts, in characters
+const SLACK_HEADER_MAX: usize = 150;
+const SLACK_SECTION_MAX: usize = 3000;
+const SLACK_MAX_BLOCKS: usize = 50;
+const DISCORD_TITLE_MAX: usize = 256;
+const DISCORD_DESCRIPTION_MAX: usize = 4096;
+
+/// Webhook flavor, which decides the payload format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    /// Slack incoming webhook; posts Block Kit blocks
+    Slack,
+    /// Discord webhook; posts an embed
+    Discord,
+    /// Any endpoint accepting `{title, content, level, sentAt}` JSON
+    Generic,
+}
+
+impl ChannelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Slack => "slack",
+            Self::Discord => "discord",
+            Self::Generic => "generic",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "slack" => Some(Self::Slack),
+            "discord" => Some(Self::Discord),
+            "generic" => Some(Self::Generic),
+            _ => None,
+        }
+    }
+}
+
+/// Registered webhook channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannel {
+    pub id: String,
+    pub name: String,
+    pub kind: ChannelKind,
+    pub created_at: String,
+    pub last_sent_at: Option<String>,
+    /// Error of the last failed send, cleared by the next successful one
+    pub last_error: Option<String>,
+}
+
+/// Tone of a message, shown as a color or emoji where supported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageLevel {
+    #[default]
+    Info,
+    Success,
+    Error,
+}
+
+/// Message posted to a channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelMessage {
+    pub title: Option<String>,
+    /// Markdown text
+    pub content: String,
+    #[serde(default)]
+    pub level: MessageLevel,
+}
+
+/// Keychain entry holding a channel's webhook URL
+pub fn credential_key(id: &str) -> String {
+    format!("channel.{}", id)
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+const COLUMNS: &str = "id, name, kind, created_at, last_sent_at, last_error";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<NotificationChannel> {
+    let kind: String = row.get(2)?;
+    Ok(NotificationChannel {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: ChannelKind::from_str(&kind).unwrap_or(ChannelKind::Generic),
+        created_at: row.get(3)?,
+        last_sent_at: row.get(4)?,
+        last_error: row.get(5)?,
+    })
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<NotificationChannel>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM notification_channels ORDER BY name", COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let channels = stmt
+        .query_map([], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(channels)
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<NotificationChannel, AppError> {
+    conn.query_row(&format!("SELECT {} FROM notification_channels WHERE id = ?1", COLUMNS), [id], from_row)
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Notification channel not found: {}", id)))
+}
+
+/// Create a channel, or rename or retype the one with `id`
+pub fn save(conn: &Connection, id: Option<&str>, name: &str, kind: ChannelKind) -> Result<NotificationChannel, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("Channel name is required"));
+    }
+    let id = match id {
+        Some(id) => {
+            let updated = conn.execute(
+                "UPDATE notification_channels SET name = ?1, kind = ?2 WHERE id = ?3",
+                rusqlite::params![name, kind.as_str(), id],
+            )?;
+            if updated == 0 {
+                return Err(AppError::not_found(format!("Notification channel not found: {}", id)));
+            }
+            id.to_string()
+        }
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO notification_channels (id, name, kind) VALUES (?1, ?2, ?3)",
+                rusqlite::params![id, name, kind.as_str()],
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(f, _) if f.code == rusqlite::ErrorCode::ConstraintViolation => {
+                    AppError::invalid_input(format!("A channel named '{}' already exists", name))
+                }
+                e => AppError::from(e),
+            })?;
+            id
+        }
+    };
+    get(conn, &id)
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<(), AppError> {
+    let deleted = conn.execute("DELETE FROM notification_channels WHERE id = ?1", [id])?;
+    if deleted == 0 {
+        return Err(AppError::not_found(format!("Notification channel not found: {}", id)));
+    }
+    Ok(())
+}
+
+/// Remember when the channel was last used and whether that failed
+pub fn record_outcome(conn: &Connection, id: &str, outcome: &Result<(), String>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE notification_channels SET last_sent_at = ?1, last_error = ?2 WHERE id = ?3",
+        rusqlite::params![chrono::Utc::now().to_rfc3339(), outcome.as_ref().err(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Check a webhook URL fits the channel kind
+pub fn validate_url(kind: ChannelKind, url: &str) -> Result<(), AppError> {
+    let parsed =
+        reqwest::Url::parse(url.trim()).map_err(|e| AppError::invalid_input(format!("Invalid webhook URL: {}", e)))?;
+    let https = parsed.scheme() == "https";
+    let host = parsed.host_str().unwrap_or_default();
+    match kind {
+        ChannelKind::Slack if !https || host != "hooks.slack.com" => {
+            Err(AppError::invalid_input("Slack webhook URLs start with https://hooks.slack.com/"))
+        }
+        ChannelKind::Discord
+            if !https || !matches!(host, "discord.com" | "discordapp.com") || !parsed.path().starts_with("/api/webhooks/") =>
+        {
+            Err(AppError::invalid_input("Discord webhook URLs start with https://discord.com/api/webhooks/"))
+        }
+        ChannelKind::Generic if !matches!(parsed.scheme(), "http" | "https") => {
+            Err(AppError::invalid_input("Webhook URLs must use http or https"))
+        }
+        _ => Ok(()),
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Shorten `text` to at most `max` characters, marking the cut
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Split `text` into pieces of at most `max` characters, at line breaks
+/// where possible
+fn chunks(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for line in text.lines() {
+        let line_len = line.chars().count();
+        if current_len > 0 && current_len + 1 + line_len > max {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        let mut rest: Vec<char> = line.chars().collect();
+        while current_len + rest.len() > max {
+            let split = max - current_len;
+            current.extend(rest.drain(..split));
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if current_len > 0 {
+            current.push('\n');
+            current_len += 1;
+        }
+        current_len += rest.len();
+        current.extend(rest);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Convert common Markdown to Slack's mrkdwn
+fn slack_mrkdwn(markdown: &str) -> String {
+    let escaped = markdown.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let headings = regex::Regex::new(r"(?m)^#{1,6}\s+(.+?)\s*$").expect("valid regex");
+    let bold = regex::Regex::new(r"\*\*(.+?)\*\*").expect("valid regex");
+    let links = regex::Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").expect("valid regex");
+    let text = headings.replace_all(&escaped, "*$1*");
+    let text = bold.replace_all(&text, "*$1*");
+    links.replace_all(&text, "<$2|$1>").into_owned()
+}
+
+fn slack_payload(message: &ChannelMessage) -> Value {
+    let emoji = match message.level {
+        MessageLevel::Info => "",
+        MessageLevel::Success => ":white_check_mark: ",
+        MessageLevel::Error => ":x: ",
+    };
+    let mut blocks = Vec::new();
+    if let Some(title) = message.title.as_deref().filter(|t| !t.trim().is_empty()) {
+        blocks.push(json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": truncate(&format!("{}{}", emoji, title), SLACK_HEADER_MAX), "emoji": true },
+        }));
+    }
+    let room = SLACK_MAX_BLOCKS - blocks.len();
+    let mut sections = chunks(&slack_mrkdwn(&message.content), SLACK_SECTION_MAX);
+    if sections.len() > room {
+        sections.truncate(room);
+        if let Some(last) = sections.last_mut() {
+            *last = truncate(&format!("{}\n…", last), SLACK_SECTION_MAX);
+        }
+    }
+    blocks.extend(
+        sections
+            .into_iter()
+            .map(|text| json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } })),
+    );
+
+    // Shown in notifications and by clients that cannot render blocks
+    let fallback = match &message.title {
+        Some(title) => format!("{}{}", emoji, title),
+        None => truncate(&message.content, SLACK_HEADER_MAX),
+    };
+    json!({ "text": fallback, "blocks": blocks })
+}
+
+fn discord_payload(message: &ChannelMessage) -> Value {
+    let color = match message.level {
+        MessageLevel::Info => 0x5865F2,
+        MessageLevel::Success => 0x57F287,
+        MessageLevel::Error => 0xED4245,
+    };
+    let mut embed = json!({
+        "description": truncate(&message.content, DISCORD_DESCRIPTION_MAX),
+        "color": color,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    if let Some(title) = message.title.as_deref().filter(|t| !t.trim().is_empty()) {
+        embed["title"] = json!(truncate(title, DISCORD_TITLE_MAX));
+    }
+    json!({ "embeds": [embed] })
+}
+
+/// Request body for posting `message` to a channel of `kind`
+pub fn payload(kind: ChannelKind, message: &ChannelMessage) -> Value {
+    match kind {
+        ChannelKind::Slack => slack_payload(message),
+        ChannelKind::Discord => discord_payload(message),
+        ChannelKind::Generic => json!({
+            "title": message.title,
+            "content": message.content,
+            "level": message.level,
+            "sentAt": chrono::Utc::now().to_rfc3339(),
+        }),
+    }
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+/// How long to wait before another attempt, or `None` to give up
+///
+/// `status` is `None` when the request failed without a response.
+fn retry_delay(status: Option<u16>, retry_after: Option<Duration>, attempt: u32) -> Option<Duration> {
+    if attempt >= MAX_ATTEMPTS {
+        return None;
+    }
+    if let Some(status) = status {
+        // Other client errors will fail the same way again
+        if status != 429 && status < 500 {
+            return None;
+        }
+    }
+    let backoff = Duration::from_secs(1 << (attempt - 1));
+    Some(retry_after.unwrap_or(backoff).min(MAX_RETRY_DELAY))
+}
+
+/// Post `payload` to `url`, retrying rate limits, server errors and
+/// network failures
+pub async fn post(url: &str, payload: &Value) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let body = payload.to_string();
+
+    let mut attempt = 1;
+    loop {
+        let request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        let (error, delay) = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<f64>().ok())
+                    .filter(|s| s.is_finite() && *s >= 0.0)
+                    .map(Duration::from_secs_f64);
+                let text = response.text().await.unwrap_or_default();
+                let error = format!("Webhook returned {}: {}", status, truncate(text.trim(), 200));
+                (error, retry_delay(Some(status.as_u16()), retry_after, attempt))
+            }
+            Err(e) => (format!("Webhook request failed: {}", e), retry_delay(None, None, attempt)),
+        };
+
+        match delay {
+            Some(delay) => {
+                tracing::debug!("{}; retrying in {:?}", error, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            None => return Err(error),
+        }
+    }
+}
+
+/// Send a message to a registered channel and record the outcome
+pub async fn send_message(db_path: &Path, channel_id: &str, message: &ChannelMessage) -> Result<(), String> {
+    let open = || crate::db::pool::open_background(db_path);
+    let channel = get(&open()?, channel_id)?;
+    let url = CredentialManager::default()
+        .and_then(|credentials| credentials.get_password(&credential_key(&channel.id)))
+        .map_err(|_| format!("Webhook URL of channel '{}' is missing; edit the channel to set it", channel.name))?;
+
+    let outcome = post(&url, &payload(channel.kind, message)).await;
+    record_outcome(&open()?, &channel.id, &outcome)?;
+    outcome
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_notification_channels(db: tauri::State<'_, crate::db::DbState>) -> Result<Vec<NotificationChannel>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list(&conn)?)
+}
+
+/// Register a webhook channel, or update the one with `id`
+///
+/// The URL is required for new channels; leave it out to keep the stored one.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn save_notification_channel(
+    db: tauri::State<'_, crate::db::DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: Option<String>,
+    name: String,
+    kind: ChannelKind,
+    url: Option<String>,
+) -> Result<NotificationChannel, AppError> {
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    match &url {
+        Some(url) => validate_url(kind, url)?,
+        None if id.is_none() => return Err(AppError::invalid_input("Webhook URL is required")),
+        None => {}
+    }
+
+    let conn = db.conn.lock()?;
+    let channel = save(&conn, id.as_deref(), &name, kind)?;
+    if let Some(url) = url {
+        let credentials = credentials.lock()?;
+        credentials.set_password(&credential_key(&channel.id), &url)?;
+    }
+    Ok(channel)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_notification_channel(
+    db: tauri::State<'_, crate::db::DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    delete(&conn, &id)?;
+    let credentials = credentials.lock()?;
+    // The URL may already be gone from the keychain
+    let _ = credentials.delete_password(&credential_key(&id));
+    Ok(())
+}
+
+/// Post a message to a channel, formatted for its platform
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn send_channel_message(
+    db: tauri::State<'_, crate::db::DbState>,
+    channel_id: String,
+    content: String,
+    title: Option<String>,
+    level: Option<MessageLevel>,
+) -> Result<(), AppError> {
+    let message = ChannelMessage {
+        title,
+        content,
+        level: level.unwrap_or_default(),
+    };
+    Ok(send_message(Path::new(&db.path()), &channel_id, &message).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(content: &str) -> ChannelMessage {
+        ChannelMessage {
+            title: Some("Nightly report".to_string()),
+            content: content.to_string(),
+            level: MessageLevel::Success,
+        }
+    }
+
+    #[test]
+    fn test_slack_payload() {
+        let payload = payload(ChannelKind::Slack, &message("## Done\n**3** jobs, see [logs](https://x.io) <ok>"));
+        let blocks = payload["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[1]["text"]["text"], "*Done*\n*3* jobs, see <https://x.io|logs> &lt;ok&gt;");
+        assert_eq!(payload["text"], ":white_check_mark: Nightly report");
+
+        let long = "line\n".repeat(40_000);
+        let payload = super::payload(ChannelKind::Slack, &message(&long));
+        let blocks = payload["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), SLACK_MAX_BLOCKS);
+        assert!(blocks.iter().skip(1).all(|b| b["text"]["text"].as_str().unwrap().chars().count() <= SLACK_SECTION_MAX));
+    }
+
+    #[test]
+    fn test_discord_payload_truncates() {
+        let payload = payload(ChannelKind::Discord, &message(&"x".repeat(5000)));
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["title"], "Nightly report");
+        assert_eq!(embed["description"].as_str().unwrap().chars().count(), DISCORD_DESCRIPTION_MAX);
+        assert_eq!(embed["color"], 0x57F287);
+
+        assert_eq!(chunks("ab\ncd\nefghij", 5), vec!["ab\ncd", "efghi", "j"]);
+    }
+
+    #[test]
+    fn test_retry_delay_and_urls() {
+        assert_eq!(retry_delay(Some(500), None, 1), Some(Duration::from_secs(1)));
+        assert_eq!(retry_delay(None, None, 2), Some(Duration::from_secs(2)));
+        assert_eq!(retry_delay(Some(429), Some(Duration::from_secs(90)), 1), Some(MAX_RETRY_DELAY));
+        assert_eq!(retry_delay(Some(404), None, 1), None);
+        assert_eq!(retry_delay(Some(503), None, MAX_ATTEMPTS), None);
+
+        assert!(validate_url(ChannelKind::Slack, "https://hooks.slack.com/services/T/B/x").is_ok());
+        assert!(validate_url(ChannelKind::Slack, "http://hooks.slack.com/services/T/B/x").is_err());
+        assert!(validate_url(ChannelKind::Discord, "https://discord.com/api/webhooks/1/abc").is_ok());
+        assert!(validate_url(ChannelKind::Discord, "https://example.com/api/webhooks/1").is_err());
+        assert!(validate_url(ChannelKind::Generic, "http://localhost:8080/hook").is_ok());
+        let err = validate_url(ChannelKind::Generic, "ftp://example.com").unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,375 @@
+//! Azure Blob Storage Client Implementation
+//!
+//! Talks to the Blob service REST API. Requests are authorized either with a
+//! Shared Key signature (account key) or by appending a SAS token. The config's
+//! `access_key_id` holds the account name and `bucket` the container.
+
+#![allow(dead_code)]
+
+use super::{encode_path_segment, CloudObject};
+#[cfg(feature = "cloud")]
+use super::{checked_body, CloudStorageConfig};
+
+/// Blob service REST API version
+const API_VERSION: &str = "2021-08-06";
+
+/// Credential used to authorize requests
+#[derive(Debug, Clone)]
+pub enum AzureAuth {
+    /// Decoded account key
+    SharedKey(Vec<u8>),
+    /// SAS query string, without the leading `?`
+    Sas(String),
+}
+
+impl AzureAuth {
+    /// SAS tokens are query strings (`sv=...&sig=...`); anything else is an account key
+    #[cfg(feature = "cloud")]
+    pub fn parse(secret: &str) -> Result<Self, String> {
+        use base64::Engine;
+
+        let secret = secret.trim();
+        let sas = secret.trim_start_matches('?');
+        if sas.contains("sig=") {
+            return Ok(Self::Sas(sas.to_string()));
+        }
+
+        base64::engine::general_purpose::STANDARD
+            .decode(secret)
+            .map(Self::SharedKey)
+            .map_err(|e| format!("Invalid Azure account key: {}", e))
+    }
+}
+
+/// Blob name as a URL path, encoding each segment but keeping `/`
+pub fn encode_blob_path(name: &str) -> String {
+    name.split('/').map(encode_path_segment).collect::<Vec<_>>().join("/")
+}
+
+/// Shared Key string-to-sign for a Blob service request
+///
+/// `ms_headers` are the `x-ms-*` headers sent with the request and `query` the
+/// decoded query parameters.
+pub fn string_to_sign(
+    method: &str,
+    content_length: usize,
+    content_type: &str,
+    ms_headers: &[(&str, String)],
+    account: &str,
+    path: &str,
+    query: &[(&str, String)],
+) -> String {
+    let mut headers: Vec<(String, &str)> = ms_headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.trim()))
+        .collect();
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+
+    let mut params: Vec<(String, &str)> = query.iter().map(|(name, value)| (name.to_lowercase(), value.as_str())).collect();
+    params.sort();
+    let mut canonical_resource = format!("/{}{}", account, path);
+    for (name, value) in params {
+        canonical_resource.push_str(&format!("\n{}:{}", name, value));
+    }
+
+    // Content-Length is empty for bodiless requests
+    let length = if content_length > 0 { content_length.to_string() } else { String::new() };
+
+    format!(
+        "{}\n\n\n{}\n\n{}\n\n\n\n\n\n\n{}{}",
+        method, length, content_type, canonical_headers, canonical_resource
+    )
+}
+
+/// Text of the first `<tag>` element in an XML fragment, unescaped
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(
+        xml[start..end]
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// Convert an HTTP date (RFC 1123) to RFC 3339
+fn http_date_to_rfc3339(date: &str) -> String {
+    chrono::DateTime::parse_from_rfc2822(date)
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// Parse a List Blobs response into objects and the next page marker
+pub fn parse_blob_list(xml: &str) -> (Vec<CloudObject>, Option<String>) {
+    let objects = xml
+        .split("<Blob>")
+        .skip(1)
+        .filter_map(|blob| {
+            let blob = &blob[..blob.find("</Blob>")?];
+            Some(CloudObject {
+                key: xml_text(blob, "Name")?,
+                size: xml_text(blob, "Content-Length").and_then(|s| s.parse().ok()).unwrap_or(0),
+                last_modified: xml_text(blob, "Last-Modified")
+                    .map(|d| http_date_to_rfc3339(&d))
+                    .unwrap_or_default(),
+                etag: xml_text(blob, "Etag").unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    let next_marker = xml_text(xml, "NextMarker").filter(|m| !m.is_empty());
+    (objects, next_marker)
+}
+
+/// Azure Blob container
+#[cfg(feature = "cloud")]
+pub struct AzureBlobStorage {
+    account: String,
+    container: String,
+    endpoint: String,
+    auth: AzureAuth,
+    http: reqwest::Client,
+}
+
+#[cfg(feature = "cloud")]
+impl AzureBlobStorage {
+    /// Create storage from a config whose secret holds an account key or SAS token
+    pub fn new(config: &CloudStorageConfig) -> Result<Self, String> {
+        let account = config
+            .access_key_id
+            .clone()
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "Account name is required for Azure Blob".to_string())?;
+        let secret = config
+            .secret_access_key
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "Account key or SAS token is required for Azure Blob".to_string())?;
+
+        Ok(Self {
+            endpoint: config
+                .endpoint_url
+                .as_deref()
+                .map(|e| e.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| format!("https://{}.blob.core.windows.net", account)),
+            account,
+            container: config.bucket.clone(),
+            auth: AzureAuth::parse(secret)?,
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()
+                .map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// Send an authorized request for the container (`blob: None`) or a blob
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        blob: Option<&str>,
+        query: &[(&str, String)],
+        body: Option<Vec<u8>>,
+    ) -> Result<reqwest::Response, String> {
+        use base64::Engine;
+        use hmac::Mac;
+
+        let mut url = format!("{}/{}", self.endpoint, encode_path_segment(&self.container));
+        if let Some(blob) = blob {
+            url.push('/');
+            url.push_str(&encode_blob_path(blob));
+        }
+        let mut url = reqwest::Url::parse_with_params(&url, query).map_err(|e| e.to_string())?;
+
+        let content_length = body.as_ref().map_or(0, Vec::len);
+        let content_type = if body.is_some() { "application/octet-stream" } else { "" };
+        let mut ms_headers = vec![
+            ("x-ms-date", chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string()),
+            ("x-ms-version", API_VERSION.to_string()),
+        ];
+        if body.is_some() {
+            ms_headers.push(("x-ms-blob-type", "BlockBlob".to_string()));
+        }
+
+        let authorization = match &self.auth {
+            AzureAuth::SharedKey(key) => {
+                let to_sign = string_to_sign(
+                    method.as_str(),
+                    content_length,
+                    content_type,
+                    &ms_headers,
+                    &self.account,
+                    url.path(),
+                    query,
+                );
+                let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key).map_err(|e| e.to_string())?;
+                mac.update(to_sign.as_bytes());
+                let signature = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+                Some(format!("SharedKey {}:{}", self.account, signature))
+            }
+            AzureAuth::Sas(token) => {
+                let combined = match url.query() {
+                    Some(existing) if !existing.is_empty() => format!("{}&{}", existing, token),
+                    _ => token.clone(),
+                };
+                url.set_query(Some(&combined));
+                None
+            }
+        };
+
+        let mut request = self.http.request(method, url);
+        for (name, value) in &ms_headers {
+            request = request.header(*name, value);
+        }
+        if let Some(authorization) = authorization {
+            request = request.header(reqwest::header::AUTHORIZATION, authorization);
+        }
+        if let Some(body) = body {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type).body(body);
+        }
+
+        request.send().await.map_err(|e| format!("Azure request failed: {}", e))
+    }
+}
+
+#[cfg(feature = "cloud")]
+#[async_trait::async_trait]
+impl super::storage::CloudStorage for AzureBlobStorage {
+    async fn list(&self, prefix: Option<&str>) -> Result<Vec<CloudObject>, String> {
+        let mut objects = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("comp", "list".to_string()),
+                ("maxresults", "1000".to_string()),
+                ("restype", "container".to_string()),
+            ];
+            if let Some(prefix) = prefix {
+                query.push(("prefix", prefix.to_string()));
+            }
+            if let Some(marker) = marker.take() {
+                query.push(("marker", marker));
+            }
+
+            let response = self.send(reqwest::Method::GET, None, &query, None).await?;
+            let body = checked_body(response, "Azure list").await?;
+            let (page, next_marker) = parse_blob_list(&String::from_utf8_lossy(&body));
+
+            objects.extend(page);
+            match next_marker {
+                Some(next) => marker = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(objects)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        let response = self.send(reqwest::Method::GET, Some(key), &[], None).await?;
+        checked_body(response, "Azure download").await
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), String> {
+        let response = self.send(reqwest::Method::PUT, Some(key), &[], Some(data)).await?;
+        checked_body(response, "Azure upload").await.map(|_| ())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let response = self.send(reqwest::Method::DELETE, Some(key), &[], None).await?;
+        checked_body(response, "Azure delete").await.map(|_| ())
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<CloudObject>, String> {
+        let response = self.send(reqwest::Method::HEAD, Some(key), &[], None).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Azure metadata request failed with {}", response.status()));
+        }
+
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Ok(Some(CloudObject {
+            key: key.to_string(),
+            size: header(reqwest::header::CONTENT_LENGTH)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            last_modified: header(reqwest::header::LAST_MODIFIED)
+                .map(|d| http_date_to_rfc3339(&d))
+                .unwrap_or_default(),
+            etag: header(reqwest::header::ETAG).unwrap_or_default(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_to_sign() {
+        let headers = [
+            ("x-ms-version", API_VERSION.to_string()),
+            ("x-ms-date", "Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+        ];
+        let query = [("restype", "container".to_string()), ("comp", "list".to_string())];
+        let signed = string_to_sign("GET", 0, "", &headers, "myaccount", "/photos", &query);
+
+        assert_eq!(
+            signed,
+            "GET\n\n\n\n\n\n\n\n\n\n\n\n\
+             x-ms-date:Mon, 01 Jan 2024 00:00:00 GMT\nx-ms-version:2021-08-06\n\
+             /myaccount/photos\ncomp:list\nrestype:container"
+        );
+
+        let put = string_to_sign("PUT", 5, "application/octet-stream", &[], "acct", "/c/a%20b.txt", &[]);
+        assert!(put.starts_with("PUT\n\n\n5\n\napplication/octet-stream\n"));
+        assert!(put.ends_with("/acct/c/a%20b.txt"));
+    }
+
+    #[test]
+    fn test_parse_blob_list() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://acct.blob.core.windows.net/" ContainerName="docs">
+  <Blobs>
+    <Blob><Name>notes &amp; todo.txt</Name><Properties><Last-Modified>Mon, 01 Jan 2024 10:00:00 GMT</Last-Modified><Etag>0x8DBF</Etag><Content-Length>42</Content-Length></Properties></Blob>
+    <Blob><Name>dir/b.bin</Name><Properties><Content-Length>7</Content-Length></Properties></Blob>
+  </Blobs>
+  <NextMarker>page2</NextMarker>
+</EnumerationResults>"#;
+
+        let (objects, next) = parse_blob_list(xml);
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].key, "notes & todo.txt");
+        assert_eq!(objects[0].size, 42);
+        assert_eq!(objects[0].last_modified, "2024-01-01T10:00:00+00:00");
+        assert_eq!(objects[1].key, "dir/b.bin");
+        assert_eq!(next.as_deref(), Some("page2"));
+
+        let (_, last) = parse_blob_list("<EnumerationResults><Blobs /><NextMarker /></EnumerationResults>");
+        assert_eq!(last, None);
+    }
+
+    #[test]
+    fn test_encode_blob_path() {
+        assert_eq!(encode_blob_path("dir/my file.txt"), "dir/my%20file.txt");
+        assert_eq!(encode_blob_path("a-b_c.d~e/한"), "a-b_c.d~e/%ED%95%9C");
+        assert_eq!(encode_path_segment("a/b?c"), "a%2Fb%3Fc");
+    }
+}
@@ -0,0 +1,340 @@
+//! Google Cloud Storage Client Implementation
+//!
+//! Uses the GCS JSON API authenticated with a service account key: a signed
+//! JWT is exchanged for an OAuth access token, which is cached until shortly
+//! before it expires.
+
+#![allow(dead_code)]
+
+use super::CloudObject;
+#[cfg(feature = "cloud")]
+use super::{checked_body, encode_path_segment, CloudStorageConfig};
+use serde::Deserialize;
+
+const DEFAULT_ENDPOINT: &str = "https://storage.googleapis.com";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const STORAGE_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+
+/// Access token lifetime requested from the token endpoint
+const TOKEN_LIFETIME_SECS: i64 = 3600;
+
+/// Refresh tokens this long before they expire
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+
+/// Fields used from a service account key file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default)]
+    pub token_uri: Option<String>,
+}
+
+impl ServiceAccountKey {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid service account key: {}", e))
+    }
+}
+
+/// JSON API object resource
+#[derive(Debug, Deserialize)]
+struct GcsObjectResource {
+    name: String,
+    /// int64 values are serialized as strings
+    #[serde(default)]
+    size: Option<String>,
+    #[serde(default)]
+    updated: Option<String>,
+    #[serde(default)]
+    etag: Option<String>,
+}
+
+impl From<GcsObjectResource> for CloudObject {
+    fn from(object: GcsObjectResource) -> Self {
+        Self {
+            key: object.name,
+            size: object.size.and_then(|s| s.parse().ok()).unwrap_or(0),
+            last_modified: object.updated.unwrap_or_default(),
+            etag: object.etag.unwrap_or_default(),
+        }
+    }
+}
+
+/// JSON API list response page
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GcsListPage {
+    #[serde(default)]
+    items: Vec<GcsObjectResource>,
+    next_page_token: Option<String>,
+}
+
+/// JWT claims for the service account token grant
+fn jwt_claims(key: &ServiceAccountKey, issued_at: i64) -> serde_json::Value {
+    serde_json::json!({
+        "iss": key.client_email,
+        "scope": STORAGE_SCOPE,
+        "aud": key.token_uri.as_deref().unwrap_or(DEFAULT_TOKEN_URI),
+        "iat": issued_at,
+        "exp": issued_at + TOKEN_LIFETIME_SECS,
+    })
+}
+
+/// Decode the DER body of a PEM private key
+#[cfg(feature = "cloud")]
+fn pem_to_der(pem: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| format!("Invalid service account private key: {}", e))
+}
+
+/// Build an RS256-signed JWT assertion
+#[cfg(feature = "cloud")]
+fn signed_jwt(key: &ServiceAccountKey, issued_at: i64) -> Result<String, String> {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+
+    let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(jwt_claims(key, issued_at).to_string());
+    let message = format!("{}.{}", header, claims);
+
+    let key_pair = RsaKeyPair::from_pkcs8(&pem_to_der(&key.private_key)?)
+        .map_err(|e| format!("Unsupported service account private key: {}", e))?;
+    let mut signature = vec![0u8; key_pair.public().modulus_len()];
+    key_pair
+        .sign(&RSA_PKCS1_SHA256, &ring::rand::SystemRandom::new(), message.as_bytes(), &mut signature)
+        .map_err(|e| format!("Failed to sign token request: {}", e))?;
+
+    Ok(format!("{}.{}", message, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Access tokens by service account email: (token, expiry unix time)
+#[cfg(feature = "cloud")]
+static TOKEN_CACHE: std::sync::Mutex<Option<std::collections::HashMap<String, (String, i64)>>> =
+    std::sync::Mutex::new(None);
+
+/// Google Cloud Storage bucket
+#[cfg(feature = "cloud")]
+pub struct GcsStorage {
+    bucket: String,
+    endpoint: String,
+    key: ServiceAccountKey,
+    http: reqwest::Client,
+}
+
+#[cfg(feature = "cloud")]
+impl GcsStorage {
+    /// Create storage from a config whose secret holds the service account JSON
+    pub fn new(config: &CloudStorageConfig) -> Result<Self, String> {
+        let json = config
+            .secret_access_key
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "Service account key is required for GCS".to_string())?;
+
+        Ok(Self {
+            bucket: config.bucket.clone(),
+            endpoint: config
+                .endpoint_url
+                .as_deref()
+                .unwrap_or(DEFAULT_ENDPOINT)
+                .trim_end_matches('/')
+                .to_string(),
+            key: ServiceAccountKey::parse(json)?,
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()
+                .map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// Cached access token, requesting a new one when it is close to expiry
+    async fn access_token(&self) -> Result<String, String> {
+        let now = chrono::Utc::now().timestamp();
+        {
+            let cache = TOKEN_CACHE.lock().map_err(|e| e.to_string())?;
+            if let Some((token, expires_at)) = cache.as_ref().and_then(|c| c.get(&self.key.client_email)) {
+                if *expires_at - TOKEN_REFRESH_MARGIN_SECS > now {
+                    return Ok(token.clone());
+                }
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            expires_in: Option<i64>,
+        }
+
+        let response = self
+            .http
+            .post(self.key.token_uri.as_deref().unwrap_or(DEFAULT_TOKEN_URI))
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", &signed_jwt(&self.key, now)?),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("GCS token request failed: {}", e))?;
+        let body = checked_body(response, "GCS token request").await?;
+        let token: TokenResponse = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+
+        let expires_at = now + token.expires_in.unwrap_or(TOKEN_LIFETIME_SECS);
+        let mut cache = TOKEN_CACHE.lock().map_err(|e| e.to_string())?;
+        cache
+            .get_or_insert_with(Default::default)
+            .insert(self.key.client_email.clone(), (token.access_token.clone(), expires_at));
+        Ok(token.access_token)
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/storage/v1/b/{}/o/{}",
+            self.endpoint,
+            encode_path_segment(&self.bucket),
+            encode_path_segment(key)
+        )
+    }
+}
+
+#[cfg(feature = "cloud")]
+#[async_trait::async_trait]
+impl super::storage::CloudStorage for GcsStorage {
+    async fn list(&self, prefix: Option<&str>) -> Result<Vec<CloudObject>, String> {
+        let url = format!("{}/storage/v1/b/{}/o", self.endpoint, encode_path_segment(&self.bucket));
+        let token = self.access_token().await?;
+        let mut objects = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("maxResults", "1000".to_string())];
+            if let Some(prefix) = prefix {
+                query.push(("prefix", prefix.to_string()));
+            }
+            if let Some(token) = page_token.take() {
+                query.push(("pageToken", token));
+            }
+
+            let response = self
+                .http
+                .get(&url)
+                .bearer_auth(&token)
+                .query(&query)
+                .send()
+                .await
+                .map_err(|e| format!("GCS list failed: {}", e))?;
+            let body = checked_body(response, "GCS list").await?;
+            let page: GcsListPage = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+
+            objects.extend(page.items.into_iter().map(CloudObject::from));
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        Ok(objects)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        let response = self
+            .http
+            .get(self.object_url(key))
+            .bearer_auth(self.access_token().await?)
+            .query(&[("alt", "media")])
+            .send()
+            .await
+            .map_err(|e| format!("GCS download failed: {}", e))?;
+        checked_body(response, "GCS download").await
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), String> {
+        let url = format!(
+            "{}/upload/storage/v1/b/{}/o",
+            self.endpoint,
+            encode_path_segment(&self.bucket)
+        );
+        let response = self
+            .http
+            .post(url)
+            .bearer_auth(self.access_token().await?)
+            .query(&[("uploadType", "media"), ("name", key)])
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| format!("GCS upload failed: {}", e))?;
+        checked_body(response, "GCS upload").await.map(|_| ())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let response = self
+            .http
+            .delete(self.object_url(key))
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await
+            .map_err(|e| format!("GCS delete failed: {}", e))?;
+        checked_body(response, "GCS delete").await.map(|_| ())
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<CloudObject>, String> {
+        let response = self
+            .http
+            .get(self.object_url(key))
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await
+            .map_err(|e| format!("GCS metadata request failed: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let body = checked_body(response, "GCS metadata request").await?;
+        let object: GcsObjectResource = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+        Ok(Some(object.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_page_parsing() {
+        let json = r#"{
+            "kind": "storage#objects",
+            "nextPageToken": "abc",
+            "items": [{"name": "a.txt", "size": "42", "updated": "2024-01-01T00:00:00.000Z", "etag": "CAE="}]
+        }"#;
+        let page: GcsListPage = serde_json::from_str(json).unwrap();
+        assert_eq!(page.next_page_token.as_deref(), Some("abc"));
+
+        let objects: Vec<CloudObject> = page.items.into_iter().map(CloudObject::from).collect();
+        assert_eq!(objects[0].key, "a.txt");
+        assert_eq!(objects[0].size, 42);
+
+        // An empty bucket omits `items`
+        let empty: GcsListPage = serde_json::from_str("{}").unwrap();
+        assert!(empty.items.is_empty());
+    }
+
+    #[test]
+    fn test_jwt_claims() {
+        let key = ServiceAccountKey::parse(
+            r#"{"client_email": "svc@project.iam.gserviceaccount.com", "private_key": "x", "type": "service_account"}"#,
+        )
+        .unwrap();
+        let claims = jwt_claims(&key, 1_000);
+        assert_eq!(claims["aud"], DEFAULT_TOKEN_URI);
+        assert_eq!(claims["exp"], 1_000 + TOKEN_LIFETIME_SECS);
+    }
+}
@@ -2,10 +2,22 @@
 //!
 //! Public API for cloud storage operations including S3, GCS, and Azure Blob.
 
+pub mod azure;
+pub mod gcs;
 pub mod s3;
+pub mod storage;
 
-
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::security::CredentialManager;
+use tauri::Emitter;
+
+/// Event carrying upload/download/sync progress
+pub const PROGRESS_EVENT: &str = "cloud://progress";
 
 /// Cloud storage providers
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -29,17 +41,17 @@ pub struct CloudStorageConfig {
 
 impl CloudStorageConfig {
     /// Validate cloud storage configuration
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), AppError> {
         if self.bucket.is_empty() {
-            return Err("Bucket name is required".to_string());
+            return Err(AppError::invalid_input("Bucket name is required"));
         }
         match self.provider {
             CloudProvider::AwsS3 => {
                 if self.access_key_id.as_ref().is_none_or(|s| s.is_empty()) {
-                    return Err("Access key ID is required for S3".to_string());
+                    return Err(AppError::invalid_input("Access key ID is required for S3"));
                 }
                 if self.secret_access_key.as_ref().is_none_or(|s| s.is_empty()) {
-                    return Err("Secret access key is required for S3".to_string());
+                    return Err(AppError::invalid_input("Secret access key is required for S3"));
                 }
             }
             CloudProvider::GoogleCloudStorage => {
@@ -47,7 +59,7 @@ impl CloudStorageConfig {
             }
             CloudProvider::AzureBlob => {
                 if self.access_key_id.as_ref().is_none_or(|s| s.is_empty()) {
-                    return Err("Account name is required for Azure Blob".to_string());
+                    return Err(AppError::invalid_input("Account name is required for Azure Blob"));
                 }
             }
         }
@@ -69,10 +81,17 @@ impl CloudStorageConfig {
                 }
             }
             CloudProvider::GoogleCloudStorage => {
-                format!("https://storage.googleapis.com/{}", self.bucket)
+                let base = self.endpoint_url.as_deref().unwrap_or("https://storage.googleapis.com");
+                format!("{}/{}", base.trim_end_matches('/'), self.bucket)
             }
             CloudProvider::AzureBlob => {
-                format!("https://{}.blob.core.windows.net", self.bucket)
+                let base = self.endpoint_url.clone().unwrap_or_else(|| {
+                    format!(
+                        "https://{}.blob.core.windows.net",
+                        self.access_key_id.as_deref().unwrap_or_default()
+                    )
+                });
+                format!("{}/{}", base.trim_end_matches('/'), self.bucket)
             }
         }
     }
@@ -88,14 +107,25 @@ pub struct CloudStorageStatus {
     pub last_synced: Option<String>,
 }
 
+/// Check that the storage is reachable with the configured credentials
 #[tauri::command]
-pub fn test_cloud_connection(config: CloudStorageConfig) -> Result<String, String> {
-    config.validate()?;
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn test_cloud_connection(
+    s3: tauri::State<'_, s3::S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: CloudStorageConfig,
+) -> Result<String, AppError> {
+    let config = with_credentials(config, &credentials)?;
+    let storage = storage::open_storage(&config, &s3).await?;
+
+    // Any answer other than an error (a missing object is fine) proves access
+    storage.head(".connection-test").await?;
     Ok(format!("Successfully connected to {} bucket", config.bucket))
 }
 
 #[tauri::command]
-pub fn get_cloud_endpoint(config: CloudStorageConfig) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_cloud_endpoint(config: CloudStorageConfig) -> Result<String, AppError> {
     Ok(config.endpoint())
 }
 
@@ -108,13 +138,252 @@ pub struct CloudObject {
     pub etag: String,
 }
 
-/// List cloud objects (legacy command)
+impl From<s3::S3Object> for CloudObject {
+    fn from(object: s3::S3Object) -> Self {
+        Self {
+            key: object.key,
+            size: object.size.max(0) as u64,
+            last_modified: object.last_modified,
+            etag: object.etag,
+        }
+    }
+}
+
+/// Percent-encode a string for use as a single URL path segment
+pub fn encode_path_segment(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Response body, or an error carrying the status and message
+#[cfg(feature = "cloud")]
+pub(crate) async fn checked_body(response: reqwest::Response, operation: &str) -> Result<Vec<u8>, String> {
+    let status = response.status();
+    let body = response.bytes().await.map_err(|e| format!("{} failed: {}", operation, e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "{} failed with {}: {}",
+            operation,
+            status,
+            String::from_utf8_lossy(&body).chars().take(200).collect::<String>()
+        ));
+    }
+    Ok(body.to_vec())
+}
+
+/// Transfer progress event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudTransferProgress {
+    pub transfer_id: String,
+    pub key: String,
+    pub bytes_transferred: u64,
+    pub total_bytes: Option<u64>,
+    /// Files finished so far (folder sync only)
+    pub files_completed: usize,
+    pub files_total: usize,
+    /// One of `transferring`, `completed`, `cancelled`, `failed`
+    pub status: String,
+}
+
+/// Result of a one-way folder sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloudSyncSummary {
+    pub uploaded: Vec<String>,
+    pub skipped: usize,
+    pub failed: Vec<String>,
+    pub bytes_uploaded: u64,
+    pub cancelled: bool,
+}
+
+/// In-flight transfers, keyed by transfer id
+#[derive(Default)]
+pub struct CloudTransfers {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl CloudTransfers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a transfer, returning its cancellation flag
+    pub fn begin(&self, transfer_id: &str) -> Result<Arc<AtomicBool>, String> {
+        let mut active = self.active.lock().map_err(|e| e.to_string())?;
+        if active.contains_key(transfer_id) {
+            return Err(format!("Transfer {} is already running", transfer_id));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        active.insert(transfer_id.to_string(), flag.clone());
+        Ok(flag)
+    }
+
+    pub fn end(&self, transfer_id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(transfer_id);
+        }
+    }
+
+    /// Request cancellation of a transfer
+    pub fn cancel(&self, transfer_id: &str) -> bool {
+        self.active
+            .lock()
+            .ok()
+            .and_then(|active| active.get(transfer_id).cloned())
+            .map(|flag| flag.store(true, Ordering::SeqCst))
+            .is_some()
+    }
+}
+
+/// Emits progress events for one transfer
+pub struct ProgressReporter {
+    app: tauri::AppHandle,
+    transfer_id: String,
+}
+
+impl ProgressReporter {
+    pub fn new(app: tauri::AppHandle, transfer_id: String) -> Self {
+        Self { app, transfer_id }
+    }
+
+    pub fn emit(
+        &self,
+        key: &str,
+        bytes_transferred: u64,
+        total_bytes: Option<u64>,
+        files_completed: usize,
+        files_total: usize,
+        status: &str,
+    ) {
+        let progress = CloudTransferProgress {
+            transfer_id: self.transfer_id.clone(),
+            key: key.to_string(),
+            bytes_transferred,
+            total_bytes,
+            files_completed,
+            files_total,
+            status: status.to_string(),
+        };
+        if let Err(e) = self.app.emit(PROGRESS_EVENT, progress) {
+            tracing::warn!("Failed to emit cloud progress: {}", e);
+        }
+    }
+}
+
+/// Resolve a local path inside a permitted folder
+///
+/// Writes need a `readwrite` permission. Paths that do not exist yet (download
+/// targets) are checked through their parent directory.
+pub fn permitted_path(db: &crate::db::DbState, path: &str, write: bool) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(path);
+    let existing: &Path = if path.exists() {
+        &path
+    } else {
+        path.parent().ok_or_else(|| AppError::invalid_input("Invalid file path"))?
+    };
+
+    let conn = db.conn.lock()?;
+    let level = crate::db::folder_permission_level(&conn, existing)
+        .map_err(AppError::invalid_input)?
+        .ok_or_else(|| AppError::permission_denied(format!("No permission to access {}", path.display())))?;
+
+    if write && level != "readwrite" {
+        return Err(AppError::permission_denied(format!("No write permission for {}", path.display())));
+    }
+    Ok(path)
+}
+
+/// Resolve a config's missing secret from the keychain
+fn with_credentials(
+    config: CloudStorageConfig,
+    credentials: &Mutex<CredentialManager>,
+) -> Result<CloudStorageConfig, String> {
+    let credentials = credentials.lock().map_err(|e| e.to_string())?;
+    Ok(storage::resolve_credentials(config, &credentials))
+}
+
+/// List cloud objects
 #[tauri::command]
-pub fn list_cloud_objects(
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn list_cloud_objects(
+    s3: tauri::State<'_, s3::S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
     config: CloudStorageConfig,
-    _prefix: Option<String>,
-) -> Result<Vec<CloudObject>, String> {
-    config.validate()?;
-    // Placeholder - would list actual objects in production
-    Ok(vec![])
+    prefix: Option<String>,
+) -> Result<Vec<CloudObject>, AppError> {
+    let config = with_credentials(config, &credentials)?;
+    let storage = storage::open_storage(&config, &s3).await?;
+    Ok(storage.list(prefix.as_deref()).await?)
+}
+
+/// Download a cloud object
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn get_cloud_object(
+    s3: tauri::State<'_, s3::S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: CloudStorageConfig,
+    key: String,
+) -> Result<Vec<u8>, AppError> {
+    let config = with_credentials(config, &credentials)?;
+    let storage = storage::open_storage(&config, &s3).await?;
+    Ok(storage.get(&key).await?)
+}
+
+/// Upload a cloud object
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn put_cloud_object(
+    s3: tauri::State<'_, s3::S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: CloudStorageConfig,
+    key: String,
+    data: Vec<u8>,
+) -> Result<(), AppError> {
+    let config = with_credentials(config, &credentials)?;
+    let storage = storage::open_storage(&config, &s3).await?;
+    Ok(storage.put(&key, data).await?)
+}
+
+/// Delete a cloud object
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn delete_cloud_object(
+    s3: tauri::State<'_, s3::S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: CloudStorageConfig,
+    key: String,
+) -> Result<(), AppError> {
+    let config = with_credentials(config, &credentials)?;
+    let storage = storage::open_storage(&config, &s3).await?;
+    Ok(storage.delete(&key).await?)
+}
+
+/// Get cloud object metadata, or `None` if it does not exist
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn head_cloud_object(
+    s3: tauri::State<'_, s3::S3Manager>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: CloudStorageConfig,
+    key: String,
+) -> Result<Option<CloudObject>, AppError> {
+    let config = with_credentials(config, &credentials)?;
+    let storage = storage::open_storage(&config, &s3).await?;
+    Ok(storage.head(&key).await?)
+}
+
+/// Cancel an upload, download or folder sync
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn cancel_cloud_transfer(
+    manager: tauri::State<'_, s3::S3Manager>,
+    transfer_id: String,
+) -> Result<bool, AppError> {
+    Ok(manager.transfers().cancel(&transfer_id))
 }
@@ -1,16 +1,28 @@
 //! AWS S3 Client Implementation
 //!
-//! Actual S3 operations using aws-sdk-s3.
+//! Actual S3 operations using aws-sdk-s3: paginated listing, streamed
+//! downloads, multipart uploads for large files and one-way folder sync.
 
 #![allow(dead_code)]
 
+use crate::error::AppError;
+use crate::integration::cloud::{CloudStorageConfig, CloudSyncSummary, CloudTransfers, ProgressReporter};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 #[cfg(feature = "cloud")]
 use aws_sdk_s3 as s3;
 
+/// Files at or above this size are uploaded in parts
+const MULTIPART_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// Multipart upload part size (S3 minimum is 5 MiB)
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
 /// S3 operation result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct S3OperationResult {
@@ -20,6 +32,26 @@ pub struct S3OperationResult {
     pub execution_time_ms: u64,
 }
 
+impl S3OperationResult {
+    fn from_result(result: Result<String, String>, start: std::time::Instant) -> Self {
+        let execution_time_ms = start.elapsed().as_millis() as u64;
+        match result {
+            Ok(message) => Self {
+                success: true,
+                result: Some(message),
+                error: None,
+                execution_time_ms,
+            },
+            Err(e) => Self {
+                success: false,
+                result: None,
+                error: Some(e),
+                execution_time_ms,
+            },
+        }
+    }
+}
+
 /// S3 object metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct S3Object {
@@ -30,16 +62,13 @@ pub struct S3Object {
     pub storage_class: Option<String>,
 }
 
-/// S3 client wrapper
-#[cfg(feature = "cloud")]
-pub struct S3Client {
-    config: aws_config::SdkConfig,
-    bucket: String,
-}
+/// Progress callback: (bytes transferred, total bytes)
+pub type ProgressFn<'a> = &'a (dyn Fn(u64, Option<u64>) + Send + Sync);
 
 /// S3 manager for multiple S3 connections
 pub struct S3Manager {
-    clients: Arc<RwLock<std::collections::HashMap<String, Arc<S3ClientWrapper>>>>,
+    clients: Arc<RwLock<HashMap<String, Arc<S3ClientWrapper>>>>,
+    transfers: CloudTransfers,
 }
 
 /// Wrapper that works with and without the cloud feature
@@ -47,7 +76,7 @@ pub struct S3Manager {
 struct S3ClientWrapper {
     bucket: String,
     region: Option<String>,
-    client: Option<Arc<s3::Client>>,
+    client: Arc<s3::Client>,
 }
 
 /// Wrapper for non-cloud builds
@@ -57,35 +86,6 @@ struct S3ClientWrapper {
     region: Option<String>,
 }
 
-#[cfg(feature = "cloud")]
-impl S3ClientWrapper {
-    /// Get or initialize the S3 client
-    #[cfg(feature = "cloud")]
-    async fn get_or_init_client(&self) -> Result<Arc<s3::Client>, String> {
-        if let Some(ref client) = self.client {
-            return Ok(client.clone());
-        }
-
-        // Initialize AWS config
-        let region_str = self.region.as_deref().unwrap_or("us-east-1");
-        let region = s3::config::Region::new(region_str.to_string());
-
-        let config_loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(region)
-            .load()
-            .await;
-
-        let client = Arc::new(s3::Client::new(&config_loader));
-        Ok(client)
-    }
-
-    /// Get bucket name
-    fn bucket(&self) -> &str {
-        &self.bucket
-    }
-}
-
-#[cfg(not(feature = "cloud"))]
 impl S3ClientWrapper {
     /// Get bucket name
     fn bucket(&self) -> &str {
@@ -97,11 +97,37 @@ impl S3Manager {
     /// Create a new S3 manager
     pub fn new() -> Self {
         Self {
-            clients: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            clients: Arc::new(RwLock::new(HashMap::new())),
+            transfers: CloudTransfers::new(),
+        }
+    }
+
+    /// In-flight transfers, for cancellation
+    pub fn transfers(&self) -> &CloudTransfers {
+        &self.transfers
+    }
+
+    /// Register a client for a storage config unless one already exists
+    pub async fn ensure_client(&self, config: &CloudStorageConfig) -> Result<(), String> {
+        if self.clients.read().await.contains_key(&config.name) {
+            return Ok(());
         }
+
+        self.add_client(
+            config.name.clone(),
+            config.bucket.clone(),
+            config.region.clone(),
+            config.access_key_id.clone(),
+            config.secret_access_key.clone(),
+            config.endpoint_url.clone(),
+        )
+        .await
     }
 
     /// Add an S3 client configuration
+    ///
+    /// A custom endpoint enables S3-compatible services (MinIO, R2) with
+    /// path-style addressing.
     #[cfg(feature = "cloud")]
     pub async fn add_client(
         &self,
@@ -110,17 +136,32 @@ impl S3Manager {
         region: Option<String>,
         access_key_id: Option<String>,
         secret_access_key: Option<String>,
+        endpoint_url: Option<String>,
     ) -> Result<(), String> {
-        // If credentials provided, set them as environment variables for the SDK
+        let region_str = region.as_deref().unwrap_or("us-east-1");
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(s3::config::Region::new(region_str.to_string()));
+
         if let (Some(akid), Some(sak)) = (access_key_id, secret_access_key) {
-            std::env::set_var("AWS_ACCESS_KEY_ID", akid);
-            std::env::set_var("AWS_SECRET_ACCESS_KEY", sak);
+            loader = loader.credentials_provider(s3::config::Credentials::new(
+                akid,
+                sak,
+                None,
+                None,
+                "ai-assistant-tauri",
+            ));
+        }
+
+        let sdk_config = loader.load().await;
+        let mut builder = s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint) = endpoint_url {
+            builder = builder.endpoint_url(endpoint).force_path_style(true);
         }
 
         let wrapper = S3ClientWrapper {
             bucket,
             region,
-            client: None, // Will be initialized on first use
+            client: Arc::new(s3::Client::from_conf(builder.build())),
         };
 
         let mut clients = self.clients.write().await;
@@ -137,6 +178,7 @@ impl S3Manager {
         region: Option<String>,
         _access_key_id: Option<String>,
         _secret_access_key: Option<String>,
+        _endpoint_url: Option<String>,
     ) -> Result<(), String> {
         let wrapper = S3ClientWrapper {
             bucket,
@@ -148,245 +190,354 @@ impl S3Manager {
         Ok(())
     }
 
-    /// Upload a file to S3
-    #[cfg(feature = "cloud")]
-    pub async fn upload_file(
-        &self,
-        name: &str,
-        key: &str,
-        data: Vec<u8>,
-    ) -> S3OperationResult {
-        use std::time::Instant;
-
-        let start = Instant::now();
-
-        let clients = self.clients.read().await;
-        let wrapper = clients.get(name);
-
-        let wrapper = match wrapper {
-            Some(w) => w,
-            None => {
-                return S3OperationResult {
-                    success: false,
-                    result: None,
-                    error: Some(format!("S3 client '{}' not found", name)),
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                };
-            }
-        };
+    /// Look up a registered client
+    async fn wrapper(&self, name: &str) -> Result<Arc<S3ClientWrapper>, String> {
+        self.clients
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("S3 client '{}' not found", name))
+    }
 
-        let client = match wrapper.get_or_init_client().await {
-            Ok(c) => c,
-            Err(e) => {
-                return S3OperationResult {
-                    success: false,
-                    result: None,
-                    error: Some(format!("Failed to initialize S3 client: {}", e)),
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                };
-            }
-        };
+    /// Upload in-memory data to S3
+    #[cfg(feature = "cloud")]
+    pub async fn upload_file(&self, name: &str, key: &str, data: Vec<u8>) -> S3OperationResult {
+        let start = std::time::Instant::now();
+
+        let result = async {
+            let wrapper = self.wrapper(name).await?;
+            let output = wrapper
+                .client
+                .put_object()
+                .bucket(wrapper.bucket())
+                .key(key)
+                .body(s3::primitives::ByteStream::from(data))
+                .send()
+                .await
+                .map_err(|e| format!("Upload failed: {}", e))?;
+
+            Ok(match output.version_id() {
+                Some(v) => format!("Uploaded {} to {} (version: {})", key, wrapper.bucket(), v),
+                None => format!("Uploaded {} to {}", key, wrapper.bucket()),
+            })
+        }
+        .await;
 
-        let bucket = wrapper.bucket();
+        S3OperationResult::from_result(result, start)
+    }
 
-        // Execute upload
-        match client.put_object()
-            .bucket(bucket)
+    /// Download an object into memory
+    #[cfg(feature = "cloud")]
+    pub async fn get_object(&self, name: &str, key: &str) -> Result<Vec<u8>, String> {
+        let wrapper = self.wrapper(name).await?;
+        let output = wrapper
+            .client
+            .get_object()
+            .bucket(wrapper.bucket())
             .key(key)
-            .body(aws_sdk_s3::primitives::ByteStream::from(data))
             .send()
-            .await {
-            Ok(output) => {
-                let version_id = output.version_id().map(|v| v.to_string());
-                let result_str = if let Some(v) = version_id {
-                    format!("Uploaded {} to {} (version: {})", key, bucket, v)
-                } else {
-                    format!("Uploaded {} to {}", key, bucket)
-                };
-
-                S3OperationResult {
-                    success: true,
-                    result: Some(result_str),
-                    error: None,
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                }
-            }
-            Err(e) => S3OperationResult {
-                success: false,
-                result: None,
-                error: Some(format!("Upload failed: {}", e)),
-                execution_time_ms: start.elapsed().as_millis() as u64,
-            },
-        }
+            .await
+            .map_err(|e| format!("Download failed: {}", e))?;
+
+        let data = output
+            .body
+            .collect()
+            .await
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
+        Ok(data.into_bytes().to_vec())
     }
 
     /// Download a file from S3
     #[cfg(feature = "cloud")]
     pub async fn download_file(&self, name: &str, key: &str) -> S3OperationResult {
-        use std::time::Instant;
+        let start = std::time::Instant::now();
+        let result = self
+            .get_object(name, key)
+            .await
+            .map(|bytes| format!("Downloaded {} bytes from {}", bytes.len(), key));
+        S3OperationResult::from_result(result, start)
+    }
 
-        let start = Instant::now();
+    /// Stream an object to a local file
+    ///
+    /// Writes to a temporary file first so a cancelled or failed download never
+    /// leaves a truncated file at `dest`.
+    #[cfg(feature = "cloud")]
+    pub async fn download_to_file(
+        &self,
+        name: &str,
+        key: &str,
+        dest: &Path,
+        progress: ProgressFn<'_>,
+        cancelled: &AtomicBool,
+    ) -> Result<u64, String> {
+        use tokio::io::AsyncWriteExt;
+
+        let wrapper = self.wrapper(name).await?;
+        let mut output = wrapper
+            .client
+            .get_object()
+            .bucket(wrapper.bucket())
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| format!("Download failed: {}", e))?;
 
-        let clients = self.clients.read().await;
-        let wrapper = clients.get(name);
-
-        let wrapper = match wrapper {
-            Some(w) => w,
-            None => {
-                return S3OperationResult {
-                    success: false,
-                    result: None,
-                    error: Some(format!("S3 client '{}' not found", name)),
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                };
+        let total = output.content_length().map(|len| len.max(0) as u64);
+        let temp_path = dest.with_extension("part");
+        let mut file = tokio::fs::File::create(&temp_path)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", temp_path.display(), e))?;
+
+        let mut downloaded = 0u64;
+        let result = async {
+            while let Some(chunk) = output
+                .body
+                .try_next()
+                .await
+                .map_err(|e| format!("Download interrupted: {}", e))?
+            {
+                if cancelled.load(Ordering::SeqCst) {
+                    return Err("Transfer cancelled".to_string());
+                }
+                file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+                downloaded += chunk.len() as u64;
+                progress(downloaded, total);
             }
-        };
+            file.flush().await.map_err(|e| e.to_string())
+        }
+        .await;
 
-        let client = match wrapper.get_or_init_client().await {
-            Ok(c) => c,
-            Err(e) => {
-                return S3OperationResult {
-                    success: false,
-                    result: None,
-                    error: Some(format!("Failed to initialize S3 client: {}", e)),
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                };
-            }
-        };
+        drop(file);
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&temp_path, dest)
+            .await
+            .map_err(|e| format!("Failed to move download into place: {}", e))?;
+        Ok(downloaded)
+    }
 
-        let bucket = wrapper.bucket();
+    /// Upload a local file, using multipart upload for large files
+    #[cfg(feature = "cloud")]
+    pub async fn put_file(
+        &self,
+        name: &str,
+        key: &str,
+        path: &Path,
+        progress: ProgressFn<'_>,
+        cancelled: &AtomicBool,
+    ) -> Result<u64, String> {
+        let wrapper = self.wrapper(name).await?;
+        let size = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .len();
+
+        if size < MULTIPART_THRESHOLD {
+            let body = s3::primitives::ByteStream::from_path(path)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            wrapper
+                .client
+                .put_object()
+                .bucket(wrapper.bucket())
+                .key(key)
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| format!("Upload failed: {}", e))?;
+            progress(size, Some(size));
+            return Ok(size);
+        }
 
-        // Execute download
-        match client.get_object()
-            .bucket(bucket)
+        let upload = wrapper
+            .client
+            .create_multipart_upload()
+            .bucket(wrapper.bucket())
             .key(key)
             .send()
-            .await {
-            Ok(output) => {
-                // Try to collect the body
-                match output.body.collect().await {
-                    Ok(data) => {
-                        let bytes = data.into_bytes().to_vec();
-                        S3OperationResult {
-                            success: true,
-                            result: Some(format!("Downloaded {} bytes from {}", bytes.len(), key)),
-                            error: None,
-                            execution_time_ms: start.elapsed().as_millis() as u64,
-                        }
-                    }
-                    Err(e) => S3OperationResult {
-                        success: false,
-                        result: None,
-                        error: Some(format!("Failed to read response body: {}", e)),
-                        execution_time_ms: start.elapsed().as_millis() as u64,
-                    },
-                }
+            .await
+            .map_err(|e| format!("Failed to start multipart upload: {}", e))?;
+        let upload_id = upload
+            .upload_id()
+            .ok_or_else(|| "Multipart upload returned no upload id".to_string())?
+            .to_string();
+
+        let result = upload_parts(&wrapper, key, &upload_id, path, size, progress, cancelled).await;
+
+        if result.is_err() {
+            // Abort so the incomplete parts stop accruing storage charges
+            if let Err(e) = wrapper
+                .client
+                .abort_multipart_upload()
+                .bucket(wrapper.bucket())
+                .key(key)
+                .upload_id(&upload_id)
+                .send()
+                .await
+            {
+                tracing::warn!("Failed to abort multipart upload of {}: {}", key, e);
             }
-            Err(e) => S3OperationResult {
-                success: false,
-                result: None,
-                error: Some(format!("Download failed: {}", e)),
-                execution_time_ms: start.elapsed().as_millis() as u64,
-            },
         }
+
+        result.map(|_| size)
     }
 
-    /// List objects in S3 bucket
+    /// List objects in S3 bucket, following pagination
     #[cfg(feature = "cloud")]
     pub async fn list_objects(&self, name: &str, prefix: Option<&str>) -> Result<Vec<S3Object>, String> {
-        let clients = self.clients.read().await;
-        let wrapper = clients.get(name)
-            .ok_or_else(|| format!("S3 client '{}' not found", name))?;
-
-        let client = wrapper.get_or_init_client().await?;
-        let bucket = wrapper.bucket();
-
-        let mut list_request = client.list_objects_v2()
-            .bucket(bucket)
-            .max_keys(1000);
+        let wrapper = self.wrapper(name).await?;
+        let mut result = Vec::new();
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let response = wrapper
+                .client
+                .list_objects_v2()
+                .bucket(wrapper.bucket())
+                .max_keys(1000)
+                .set_prefix(prefix.map(|p| p.to_string()))
+                .set_continuation_token(continuation_token.take())
+                .send()
+                .await
+                .map_err(|e| format!("List objects failed: {}", e))?;
+
+            for obj in response.contents() {
+                result.push(S3Object {
+                    key: obj.key().unwrap_or("").to_string(),
+                    size: obj.size().unwrap_or(0),
+                    last_modified: obj
+                        .last_modified()
+                        .and_then(|d| chrono::DateTime::from_timestamp(d.secs(), d.subsec_nanos()))
+                        .map(|d| d.to_rfc3339())
+                        .unwrap_or_default(),
+                    etag: obj.e_tag().unwrap_or("").to_string(),
+                    storage_class: obj.storage_class().map(|sc| sc.as_str().to_string()),
+                });
+            }
 
-        if let Some(p) = prefix {
-            list_request = list_request.prefix(p);
+            match response.next_continuation_token() {
+                Some(token) if response.is_truncated().unwrap_or(false) => {
+                    continuation_token = Some(token.to_string());
+                }
+                _ => break,
+            }
         }
 
-        let response = list_request.send()
-            .await
-            .map_err(|e| format!("List objects failed: {}", e))?;
-
-        let objects = response.contents.unwrap_or_default();
+        Ok(result)
+    }
 
-        let mut result = Vec::new();
-        for obj in objects {
-            result.push(S3Object {
-                key: obj.key.as_deref().unwrap_or("").to_string(),
-                size: obj.size.unwrap_or(0),
-                last_modified: obj.last_modified
-                    .as_ref()
-                    .map(|d| d.as_secs_f64().to_string())
-                    .unwrap_or_default(),
-                etag: obj.e_tag.as_deref().unwrap_or("").to_string(),
-                storage_class: obj.storage_class.as_ref().map(|sc| sc.as_str().to_string()),
-            });
-        }
+    /// Object metadata, or `None` if the key does not exist
+    #[cfg(feature = "cloud")]
+    pub async fn head_object(&self, name: &str, key: &str) -> Result<Option<S3Object>, String> {
+        let wrapper = self.wrapper(name).await?;
+        let output = match wrapper
+            .client
+            .head_object()
+            .bucket(wrapper.bucket())
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) if e.as_service_error().is_some_and(|se| se.is_not_found()) => return Ok(None),
+            Err(e) => return Err(format!("Head object failed: {}", e)),
+        };
 
-        Ok(result)
+        Ok(Some(S3Object {
+            key: key.to_string(),
+            size: output.content_length().unwrap_or(0),
+            last_modified: output
+                .last_modified()
+                .and_then(|d| chrono::DateTime::from_timestamp(d.secs(), d.subsec_nanos()))
+                .map(|d| d.to_rfc3339())
+                .unwrap_or_default(),
+            etag: output.e_tag().unwrap_or("").to_string(),
+            storage_class: output.storage_class().map(|sc| sc.as_str().to_string()),
+        }))
     }
 
     /// Delete an object from S3
     #[cfg(feature = "cloud")]
     pub async fn delete_object(&self, name: &str, key: &str) -> S3OperationResult {
-        use std::time::Instant;
+        let start = std::time::Instant::now();
+
+        let result = async {
+            let wrapper = self.wrapper(name).await?;
+            wrapper
+                .client
+                .delete_object()
+                .bucket(wrapper.bucket())
+                .key(key)
+                .send()
+                .await
+                .map_err(|e| format!("Delete failed: {}", e))?;
+            Ok(format!("Deleted {} from {}", key, wrapper.bucket()))
+        }
+        .await;
 
-        let start = Instant::now();
+        S3OperationResult::from_result(result, start)
+    }
 
-        let clients = self.clients.read().await;
-        let wrapper = clients.get(name);
-
-        let wrapper = match wrapper {
-            Some(w) => w,
-            None => {
-                return S3OperationResult {
-                    success: false,
-                    result: None,
-                    error: Some(format!("S3 client '{}' not found", name)),
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                };
-            }
+    /// Upload new or changed files from a local folder under `prefix`
+    ///
+    /// A file is uploaded when the object is missing, its size differs, or the
+    /// local file was modified after the object. Nothing is deleted remotely.
+    pub async fn sync_folder(
+        &self,
+        name: &str,
+        folder: &Path,
+        prefix: &str,
+        reporter: &ProgressReporter,
+        cancelled: &AtomicBool,
+    ) -> Result<CloudSyncSummary, String> {
+        let remote: HashMap<String, S3Object> = self
+            .list_objects(name, (!prefix.is_empty()).then_some(prefix))
+            .await?
+            .into_iter()
+            .map(|o| (o.key.clone(), o))
+            .collect();
+
+        // Unreadable files are failures, not unchanged files
+        let plan = plan_uploads(collect_files(folder)?, prefix, &remote);
+        let mut summary = CloudSyncSummary {
+            skipped: plan.skipped,
+            failed: plan.unreadable,
+            ..Default::default()
         };
+        let total_files = plan.changed.len();
 
-        let client = match wrapper.get_or_init_client().await {
-            Ok(c) => c,
-            Err(e) => {
-                return S3OperationResult {
-                    success: false,
-                    result: None,
-                    error: Some(format!("Failed to initialize S3 client: {}", e)),
-                    execution_time_ms: start.elapsed().as_millis() as u64,
-                };
+        for (index, (path, key)) in plan.changed.into_iter().enumerate() {
+            if cancelled.load(Ordering::SeqCst) {
+                summary.cancelled = true;
+                break;
             }
-        };
-
-        let bucket = wrapper.bucket();
 
-        // Execute delete
-        match client.delete_object()
-            .bucket(bucket)
-            .key(key)
-            .send()
-            .await {
-            Ok(_) => S3OperationResult {
-                success: true,
-                result: Some(format!("Deleted {} from {}", key, bucket)),
-                error: None,
-                execution_time_ms: start.elapsed().as_millis() as u64,
-            },
-            Err(e) => S3OperationResult {
-                success: false,
-                result: None,
-                error: Some(format!("Delete failed: {}", e)),
-                execution_time_ms: start.elapsed().as_millis() as u64,
-            },
+            let on_progress = |sent: u64, total: Option<u64>| {
+                reporter.emit(&key, sent, total, index, total_files, "transferring");
+            };
+            match self.put_file(name, &key, &path, &on_progress, cancelled).await {
+                Ok(bytes) => {
+                    summary.bytes_uploaded += bytes;
+                    summary.uploaded.push(key);
+                }
+                Err(e) if cancelled.load(Ordering::SeqCst) => {
+                    tracing::info!("Folder sync cancelled during {}: {}", key, e);
+                    summary.cancelled = true;
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to sync {}: {}", key, e);
+                    summary.failed.push(key);
+                }
+            }
         }
+
+        Ok(summary)
     }
 
     /// Remove an S3 client
@@ -395,6 +546,11 @@ impl S3Manager {
         clients.remove(name);
     }
 
+    /// Remove every S3 client
+    pub async fn clear_clients(&self) {
+        self.clients.write().await.clear();
+    }
+
     /// List all S3 clients
     pub async fn list_clients(&self) -> Vec<String> {
         let clients = self.clients.read().await;
@@ -408,6 +564,162 @@ impl Default for S3Manager {
     }
 }
 
+/// Upload the parts of a multipart upload and complete it
+#[cfg(feature = "cloud")]
+async fn upload_parts(
+    wrapper: &S3ClientWrapper,
+    key: &str,
+    upload_id: &str,
+    path: &Path,
+    size: u64,
+    progress: ProgressFn<'_>,
+    cancelled: &AtomicBool,
+) -> Result<(), String> {
+    use s3::types::{CompletedMultipartUpload, CompletedPart};
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut parts = Vec::new();
+    let mut sent = 0u64;
+
+    for part_number in 1.. {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err("Transfer cancelled".to_string());
+        }
+
+        let mut buffer = Vec::with_capacity(PART_SIZE);
+        (&mut file)
+            .take(PART_SIZE as u64)
+            .read_to_end(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if buffer.is_empty() {
+            break;
+        }
+        let len = buffer.len() as u64;
+
+        let output = wrapper
+            .client
+            .upload_part()
+            .bucket(wrapper.bucket())
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(s3::primitives::ByteStream::from(buffer))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to upload part {}: {}", part_number, e))?;
+
+        parts.push(
+            CompletedPart::builder()
+                .set_e_tag(output.e_tag().map(|t| t.to_string()))
+                .part_number(part_number)
+                .build(),
+        );
+        sent += len;
+        progress(sent, Some(size));
+    }
+
+    wrapper
+        .client
+        .complete_multipart_upload()
+        .bucket(wrapper.bucket())
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to complete multipart upload: {}", e))?;
+
+    Ok(())
+}
+
+/// All files under a folder with their `/`-separated relative paths
+pub fn collect_files(root: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Skip symlinks so the sync cannot leave the permitted folder
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_file() {
+                let relative = path
+                    .strip_prefix(root)
+                    .map_err(|e| e.to_string())?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((path, relative));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Object key for a file relative to the sync folder
+pub fn object_key(prefix: &str, relative: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        relative.to_string()
+    } else {
+        format!("{}/{}", prefix, relative)
+    }
+}
+
+/// What a folder sync does with each local file
+#[derive(Debug, Default)]
+struct UploadPlan {
+    /// Files to upload with their object keys
+    changed: Vec<(PathBuf, String)>,
+    /// Local files left as they are
+    skipped: usize,
+    /// Object keys of local files that could not be read
+    unreadable: Vec<String>,
+}
+
+/// Sort the files of a folder sync into changed, unchanged and unreadable
+fn plan_uploads(files: Vec<(PathBuf, String)>, prefix: &str, remote: &HashMap<String, S3Object>) -> UploadPlan {
+    let mut plan = UploadPlan::default();
+    for (path, relative) in files {
+        let key = object_key(prefix, &relative);
+        match std::fs::metadata(&path) {
+            Ok(metadata) if needs_upload(&metadata, remote.get(&key)) => plan.changed.push((path, key)),
+            Ok(_) => plan.skipped += 1,
+            Err(e) => {
+                tracing::warn!("Failed to read {}: {}", path.display(), e);
+                plan.unreadable.push(key);
+            }
+        }
+    }
+    plan
+}
+
+/// Whether a local file differs from its remote object
+fn needs_upload(local: &std::fs::Metadata, remote: Option<&S3Object>) -> bool {
+    let Some(remote) = remote else { return true };
+    if remote.size.max(0) as u64 != local.len() {
+        return true;
+    }
+
+    let remote_modified = chrono::DateTime::parse_from_rfc3339(&remote.last_modified).ok();
+    let local_modified = local.modified().ok().map(chrono::DateTime::<chrono::Utc>::from);
+    match (local_modified, remote_modified) {
+        (Some(local), Some(remote)) => local > remote,
+        _ => true,
+    }
+}
+
 #[cfg(not(feature = "cloud"))]
 impl S3Manager {
     pub async fn upload_file(&self, _name: &str, _key: &str, _data: Vec<u8>) -> S3OperationResult {
@@ -419,6 +731,10 @@ impl S3Manager {
         }
     }
 
+    pub async fn get_object(&self, name: &str, _key: &str) -> Result<Vec<u8>, String> {
+        Err(format!("Cloud feature not enabled for '{}'", name))
+    }
+
     pub async fn download_file(&self, _name: &str, _key: &str) -> S3OperationResult {
         S3OperationResult {
             success: false,
@@ -428,10 +744,36 @@ impl S3Manager {
         }
     }
 
+    pub async fn download_to_file(
+        &self,
+        name: &str,
+        _key: &str,
+        _dest: &Path,
+        _progress: ProgressFn<'_>,
+        _cancelled: &AtomicBool,
+    ) -> Result<u64, String> {
+        Err(format!("Cloud feature not enabled for '{}'", name))
+    }
+
+    pub async fn put_file(
+        &self,
+        name: &str,
+        _key: &str,
+        _path: &Path,
+        _progress: ProgressFn<'_>,
+        _cancelled: &AtomicBool,
+    ) -> Result<u64, String> {
+        Err(format!("Cloud feature not enabled for '{}'", name))
+    }
+
     pub async fn list_objects(&self, name: &str, _prefix: Option<&str>) -> Result<Vec<S3Object>, String> {
         Err(format!("Cloud feature not enabled for '{}'", name))
     }
 
+    pub async fn head_object(&self, name: &str, _key: &str) -> Result<Option<S3Object>, String> {
+        Err(format!("Cloud feature not enabled for '{}'", name))
+    }
+
     pub async fn delete_object(&self, _name: &str, _key: &str) -> S3OperationResult {
         S3OperationResult {
             success: false,
@@ -446,45 +788,159 @@ impl S3Manager {
 // Tauri Commands
 // ============================================================================
 
-/// Upload file to S3
+/// Register the transfer, run it, and report the final status
+async fn run_transfer<T, F>(
+    manager: &S3Manager,
+    reporter: &ProgressReporter,
+    transfer_id: &str,
+    key: &str,
+    transfer: impl FnOnce(Arc<AtomicBool>) -> F,
+) -> Result<T, String>
+where
+    F: std::future::Future<Output = Result<T, String>>,
+{
+    let cancelled = manager.transfers().begin(transfer_id)?;
+    let result = transfer(cancelled.clone()).await;
+    manager.transfers().end(transfer_id);
+
+    let status = match &result {
+        Ok(_) => "completed",
+        Err(_) if cancelled.load(Ordering::SeqCst) => "cancelled",
+        Err(_) => "failed",
+    };
+    reporter.emit(key, 0, None, 0, 0, status);
+    result
+}
+
+/// Upload a file from a permitted folder to S3
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn s3_upload(
-    name: String,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::db::DbState>,
+    manager: tauri::State<'_, S3Manager>,
+    config: CloudStorageConfig,
     key: String,
-    data: Vec<u8>,
-) -> std::result::Result<S3OperationResult, String> {
-    let manager = S3Manager::new();
-    Ok(manager.upload_file(&name, &key, data).await)
+    file_path: String,
+    transfer_id: Option<String>,
+) -> std::result::Result<S3OperationResult, AppError> {
+    let start = std::time::Instant::now();
+    config.validate()?;
+    let path = super::permitted_path(&db, &file_path, false)?;
+    manager.ensure_client(&config).await?;
+
+    let transfer_id = transfer_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let reporter = ProgressReporter::new(app, transfer_id.clone());
+    let on_progress = |sent: u64, total: Option<u64>| reporter.emit(&key, sent, total, 0, 1, "transferring");
+
+    let (name, object_key, on_progress) = (&config.name, key.as_str(), &on_progress);
+    let result = run_transfer(&manager, &reporter, &transfer_id, &key, |cancelled| async move {
+        manager.put_file(name, object_key, &path, on_progress, &cancelled).await
+    })
+    .await
+    .map(|bytes| format!("Uploaded {} ({} bytes) to {}", key, bytes, config.bucket));
+
+    Ok(S3OperationResult::from_result(result, start))
 }
 
-/// Download file from S3
+/// Download an S3 object into a permitted folder
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn s3_download(
-    name: String,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::db::DbState>,
+    manager: tauri::State<'_, S3Manager>,
+    config: CloudStorageConfig,
     key: String,
-) -> std::result::Result<S3OperationResult, String> {
-    let manager = S3Manager::new();
-    Ok(manager.download_file(&name, &key).await)
+    destination: String,
+    transfer_id: Option<String>,
+) -> std::result::Result<S3OperationResult, AppError> {
+    let start = std::time::Instant::now();
+    config.validate()?;
+    let dest = super::permitted_path(&db, &destination, true)?;
+    manager.ensure_client(&config).await?;
+
+    let transfer_id = transfer_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let reporter = ProgressReporter::new(app, transfer_id.clone());
+    let on_progress = |received: u64, total: Option<u64>| reporter.emit(&key, received, total, 0, 1, "transferring");
+
+    let (name, object_key, on_progress) = (&config.name, key.as_str(), &on_progress);
+    let result = run_transfer(&manager, &reporter, &transfer_id, &key, |cancelled| async move {
+        manager.download_to_file(name, object_key, &dest, on_progress, &cancelled).await
+    })
+    .await
+    .map(|bytes| format!("Downloaded {} bytes from {} to {}", bytes, key, destination));
+
+    Ok(S3OperationResult::from_result(result, start))
 }
 
 /// List S3 objects
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn s3_list(
-    name: String,
+    manager: tauri::State<'_, S3Manager>,
+    config: CloudStorageConfig,
     prefix: Option<String>,
-) -> std::result::Result<Vec<S3Object>, String> {
-    let manager = S3Manager::new();
-    manager.list_objects(&name, prefix.as_deref()).await
+) -> std::result::Result<Vec<S3Object>, AppError> {
+    config.validate()?;
+    manager.ensure_client(&config).await?;
+    Ok(manager.list_objects(&config.name, prefix.as_deref()).await?)
+}
+
+/// Fetch an S3 object's contents
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn s3_get(
+    manager: tauri::State<'_, S3Manager>,
+    config: CloudStorageConfig,
+    key: String,
+) -> std::result::Result<Vec<u8>, AppError> {
+    config.validate()?;
+    manager.ensure_client(&config).await?;
+    Ok(manager.get_object(&config.name, &key).await?)
 }
 
 /// Delete S3 object
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn s3_delete(
-    name: String,
+    manager: tauri::State<'_, S3Manager>,
+    config: CloudStorageConfig,
     key: String,
-) -> std::result::Result<S3OperationResult, String> {
-    let manager = S3Manager::new();
-    Ok(manager.delete_object(&name, &key).await)
+) -> std::result::Result<S3OperationResult, AppError> {
+    config.validate()?;
+    manager.ensure_client(&config).await?;
+    Ok(manager.delete_object(&config.name, &key).await)
+}
+
+/// Upload new or changed files from a permitted folder to S3
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn s3_sync_folder(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::db::DbState>,
+    manager: tauri::State<'_, S3Manager>,
+    config: CloudStorageConfig,
+    folder: String,
+    prefix: Option<String>,
+    transfer_id: Option<String>,
+) -> std::result::Result<CloudSyncSummary, AppError> {
+    config.validate()?;
+    let folder = super::permitted_path(&db, &folder, false)?;
+    if !folder.is_dir() {
+        return Err(AppError::invalid_input(format!("Not a directory: {}", folder.display())));
+    }
+    manager.ensure_client(&config).await?;
+
+    let transfer_id = transfer_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let reporter = ProgressReporter::new(app, transfer_id.clone());
+    let prefix = prefix.unwrap_or_default();
+
+    let (name, prefix_ref, reporter_ref) = (&config.name, prefix.as_str(), &reporter);
+    Ok(run_transfer(&manager, &reporter, &transfer_id, &prefix, |cancelled| async move {
+        manager.sync_folder(name, &folder, prefix_ref, reporter_ref, &cancelled).await
+    })
+    .await?)
 }
 
 #[cfg(test)]
@@ -518,10 +974,103 @@ mod tests {
         assert!(json.contains("test.txt"));
     }
 
+    #[test]
+    fn test_invalid_config_is_invalid_input() {
+        let config = CloudStorageConfig {
+            name: "backup".to_string(),
+            provider: crate::integration::cloud::CloudProvider::AwsS3,
+            bucket: String::new(),
+            region: None,
+            access_key_id: Some("AKIA".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            endpoint_url: None,
+        };
+        let error = config.validate().unwrap_err();
+        assert_eq!(error.kind(), crate::error::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "Bucket name is required");
+    }
+
     #[tokio::test]
     async fn test_s3_manager_new() {
         let manager = S3Manager::new();
         let clients = manager.list_clients().await;
         assert!(clients.is_empty());
     }
+
+    #[test]
+    fn test_object_key() {
+        assert_eq!(object_key("", "a/b.txt"), "a/b.txt");
+        assert_eq!(object_key("backup/", "a/b.txt"), "backup/a/b.txt");
+        assert_eq!(object_key("/backup", "b.txt"), "backup/b.txt");
+    }
+
+    #[test]
+    fn test_collect_files_and_needs_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        std::fs::write(dir.path().join("sub/b.txt"), b"world!").unwrap();
+
+        let files = collect_files(dir.path()).unwrap();
+        let relative: Vec<&str> = files.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(relative, vec!["a.txt", "sub/b.txt"]);
+
+        let metadata = std::fs::metadata(dir.path().join("a.txt")).unwrap();
+        let mut remote = S3Object {
+            key: "a.txt".to_string(),
+            size: 5,
+            last_modified: "2999-01-01T00:00:00Z".to_string(),
+            etag: String::new(),
+            storage_class: None,
+        };
+        assert!(needs_upload(&metadata, None));
+        assert!(!needs_upload(&metadata, Some(&remote)));
+
+        remote.size = 4;
+        assert!(needs_upload(&metadata, Some(&remote)));
+
+        remote.size = 5;
+        remote.last_modified = "2000-01-01T00:00:00Z".to_string();
+        assert!(needs_upload(&metadata, Some(&remote)));
+    }
+
+    #[test]
+    fn test_plan_uploads_counts_unchanged_local_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        std::fs::write(dir.path().join("b.txt"), b"new").unwrap();
+
+        // Remote objects without a local file are not counted as skipped
+        let mut remote: HashMap<String, S3Object> = (0..10)
+            .map(|i| S3Object {
+                key: format!("backup/other-{}.txt", i),
+                size: 1,
+                last_modified: "2999-01-01T00:00:00Z".to_string(),
+                etag: String::new(),
+                storage_class: None,
+            })
+            .map(|o| (o.key.clone(), o))
+            .collect();
+        let unchanged = S3Object {
+            key: "backup/a.txt".to_string(),
+            size: 5,
+            last_modified: "2999-01-01T00:00:00Z".to_string(),
+            etag: String::new(),
+            storage_class: None,
+        };
+        remote.insert(unchanged.key.clone(), unchanged);
+
+        let plan = plan_uploads(collect_files(dir.path()).unwrap(), "backup", &remote);
+        let keys: Vec<&str> = plan.changed.iter().map(|(_, key)| key.as_str()).collect();
+        assert_eq!(keys, vec!["backup/b.txt"]);
+        assert_eq!(plan.skipped, 1);
+        assert!(plan.unreadable.is_empty());
+
+        // A file that disappears or cannot be read is reported, not skipped
+        let mut files = collect_files(dir.path()).unwrap();
+        files.push((dir.path().join("gone.txt"), "gone.txt".to_string()));
+        let plan = plan_uploads(files, "backup", &remote);
+        assert_eq!(plan.skipped, 1);
+        assert_eq!(plan.unreadable, vec!["backup/gone.txt"]);
+    }
 }
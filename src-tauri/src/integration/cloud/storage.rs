@@ -0,0 +1,117 @@
+//! Provider-independent cloud storage
+//!
+//! `CloudStorage` is implemented for S3, Google Cloud Storage and Azure Blob.
+//! Secrets missing from a `CloudStorageConfig` are loaded from the platform
+//! keychain under `cloud.<storage name>`: the S3 secret access key, the GCS
+//! service account JSON, or the Azure account key / SAS token.
+
+#![allow(dead_code)]
+
+use super::s3::S3Manager;
+use super::{CloudObject, CloudProvider, CloudStorageConfig};
+use crate::security::CredentialManager;
+
+/// Object storage operations shared by all providers
+#[async_trait::async_trait]
+pub trait CloudStorage: Send + Sync {
+    /// List objects, following pagination
+    async fn list(&self, prefix: Option<&str>) -> Result<Vec<CloudObject>, String>;
+
+    /// Download an object
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String>;
+
+    /// Upload an object, replacing any existing one
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), String>;
+
+    /// Delete an object
+    async fn delete(&self, key: &str) -> Result<(), String>;
+
+    /// Object metadata, or `None` if the object does not exist
+    async fn head(&self, key: &str) -> Result<Option<CloudObject>, String>;
+}
+
+/// Keychain entry holding a storage's secret
+pub fn credential_key(name: &str) -> String {
+    format!("cloud.{}", name)
+}
+
+/// Fill in the secret from the keychain when the config does not carry one
+pub fn resolve_credentials(mut config: CloudStorageConfig, credentials: &CredentialManager) -> CloudStorageConfig {
+    if config.secret_access_key.as_ref().is_none_or(|s| s.is_empty()) {
+        config.secret_access_key = credentials.get_password(&credential_key(&config.name)).ok();
+    }
+    config
+}
+
+/// S3 storage backed by a client registered with the shared `S3Manager`
+pub struct S3Storage<'a> {
+    manager: &'a S3Manager,
+    name: String,
+}
+
+impl<'a> S3Storage<'a> {
+    pub async fn new(manager: &'a S3Manager, config: &CloudStorageConfig) -> Result<Self, String> {
+        manager.ensure_client(config).await?;
+        Ok(Self {
+            manager,
+            name: config.name.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl CloudStorage for S3Storage<'_> {
+    async fn list(&self, prefix: Option<&str>) -> Result<Vec<CloudObject>, String> {
+        let objects = self.manager.list_objects(&self.name, prefix).await?;
+        Ok(objects.into_iter().map(CloudObject::from).collect())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        self.manager.get_object(&self.name, key).await
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), String> {
+        let result = self.manager.upload_file(&self.name, key, data).await;
+        match result.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let result = self.manager.delete_object(&self.name, key).await;
+        match result.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<CloudObject>, String> {
+        let object = self.manager.head_object(&self.name, key).await?;
+        Ok(object.map(CloudObject::from))
+    }
+}
+
+/// Open storage for a config, dispatching on its provider
+///
+/// The config must already have its credentials resolved.
+pub async fn open_storage<'a>(
+    config: &CloudStorageConfig,
+    s3: &'a S3Manager,
+) -> Result<Box<dyn CloudStorage + 'a>, String> {
+    config.validate()?;
+
+    match config.provider {
+        CloudProvider::AwsS3 => Ok(Box::new(S3Storage::new(s3, config).await?)),
+        #[cfg(feature = "cloud")]
+        CloudProvider::GoogleCloudStorage => Ok(Box::new(super::gcs::GcsStorage::new(config)?)),
+        #[cfg(feature = "cloud")]
+        CloudProvider::AzureBlob => Ok(Box::new(super::azure::AzureBlobStorage::new(config)?)),
+        #[cfg(not(feature = "cloud"))]
+        _ => Err(format!(
+            "{:?} storage is not available: built without the cloud feature",
+            config.provider
+        )),
+    }
+}
+
@@ -0,0 +1,163 @@
+//! SQL Audit Log
+//!
+//! Every statement sent to an external database through the app is recorded
+//! in `database_query_audit`, including statements the read-only guard
+//! rejected.
+
+use super::QueryResult;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Entries returned when the caller does not set a limit
+const DEFAULT_AUDIT_LIMIT: u32 = 100;
+
+/// Outcome of an audited statement
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditStatus {
+    Success,
+    Error,
+    /// Blocked by the read-only guard before reaching the database
+    Rejected,
+}
+
+impl AuditStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// Audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryAuditEntry {
+    pub id: i64,
+    pub connection_name: String,
+    pub query: String,
+    pub conversation_id: Option<String>,
+    pub status: String,
+    pub row_count: i64,
+    pub rows_affected: i64,
+    pub execution_time_ms: i64,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// Record an executed (or rejected) statement
+pub fn record_query(
+    conn: &Connection,
+    connection_name: &str,
+    query: &str,
+    conversation_id: Option<&str>,
+    outcome: &Result<QueryResult, String>,
+    status: AuditStatus,
+) -> rusqlite::Result<()> {
+    let (row_count, rows_affected, execution_time_ms, error) = match outcome {
+        Ok(result) => (
+            result.row_count as i64,
+            result.rows_affected as i64,
+            result.execution_time_ms as i64,
+            None,
+        ),
+        Err(e) => (0, 0, 0, Some(e.as_str())),
+    };
+
+    conn.execute(
+        "INSERT INTO database_query_audit
+            (connection_name, query, conversation_id, status, row_count, rows_affected, execution_time_ms, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            connection_name,
+            query,
+            conversation_id,
+            status.as_str(),
+            row_count,
+            rows_affected,
+            execution_time_ms,
+            error,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Most recent audit entries, newest first
+pub fn list_queries(
+    conn: &Connection,
+    connection_name: Option<&str>,
+    conversation_id: Option<&str>,
+    limit: Option<u32>,
+) -> rusqlite::Result<Vec<QueryAuditEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, connection_name, query, conversation_id, status, row_count, rows_affected,
+                execution_time_ms, error, created_at
+         FROM database_query_audit
+         WHERE (?1 IS NULL OR connection_name = ?1)
+           AND (?2 IS NULL OR conversation_id = ?2)
+         ORDER BY id DESC
+         LIMIT ?3",
+    )?;
+
+    let entries = stmt
+        .query_map(
+            params![connection_name, conversation_id, limit.unwrap_or(DEFAULT_AUDIT_LIMIT)],
+            |row| {
+                Ok(QueryAuditEntry {
+                    id: row.get(0)?,
+                    connection_name: row.get(1)?,
+                    query: row.get(2)?,
+                    conversation_id: row.get(3)?,
+                    status: row.get(4)?,
+                    row_count: row.get(5)?,
+                    rows_affected: row.get(6)?,
+                    execution_time_ms: row.get(7)?,
+                    error: row.get(8)?,
+                    created_at: row.get(9)?,
+                })
+            },
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_record_and_list() {
+        let conn = test_conn();
+
+        let result = QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            row_count: 0,
+            execution_time_ms: 4,
+            error: None,
+            rows_affected: 2,
+            truncated: false,
+        };
+        record_query(&conn, "prod", "UPDATE t SET a = 1", Some("conv-1"), &Ok(result), AuditStatus::Success).unwrap();
+        record_query(
+            &conn,
+            "prod",
+            "DROP TABLE t",
+            None,
+            &Err("Rejected on read-only connection".to_string()),
+            AuditStatus::Rejected,
+        )
+        .unwrap();
+
+        let all = list_queries(&conn, Some("prod"), None, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].status, "rejected");
+        assert_eq!(all[1].rows_affected, 2);
+
+        let conversation = list_queries(&conn, None, Some("conv-1"), None).unwrap();
+        assert_eq!(conversation.len(), 1);
+        assert_eq!(conversation[0].query, "UPDATE t SET a = 1");
+    }
+}
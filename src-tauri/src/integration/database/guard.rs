@@ -0,0 +1,113 @@
+//! Read-Only Statement Guard
+//!
+//! Connections marked `read_only` only accept statements that cannot change
+//! data. The SQL is parsed with the connection's dialect and every statement
+//! must be a plain query; anything that fails to parse is rejected too.
+//!
+//! This is a first line of defence. Read-only pools are also opened in
+//! read-only mode on the server side, which catches writes hidden in function
+//! calls that parsing cannot see.
+
+use super::DatabaseType;
+use sqlparser::ast::{SetExpr, Statement};
+use sqlparser::dialect::{Dialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+
+/// Reject SQL containing anything but read-only statements
+pub fn check_read_only(db_type: &DatabaseType, sql: &str) -> Result<(), String> {
+    let dialect: Box<dyn Dialect> = match db_type {
+        DatabaseType::PostgreSQL => Box::new(PostgreSqlDialect {}),
+        DatabaseType::MySQL => Box::new(MySqlDialect {}),
+        DatabaseType::SQLite => Box::new(SQLiteDialect {}),
+    };
+
+    let statements = Parser::parse_sql(dialect.as_ref(), sql)
+        .map_err(|e| format!("Rejected on read-only connection: could not parse SQL ({})", e))?;
+    if statements.is_empty() {
+        return Err("Rejected on read-only connection: no statement".to_string());
+    }
+
+    for statement in &statements {
+        if !is_read_only(statement) {
+            return Err(format!(
+                "Rejected on read-only connection: only SELECT statements are allowed, got `{}`",
+                statement_summary(statement)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn is_read_only(statement: &Statement) -> bool {
+    match statement {
+        Statement::Query(query) => is_read_only_query(query),
+        // EXPLAIN ANALYZE runs the statement, so the inner statement must qualify too
+        Statement::Explain { statement, .. } => is_read_only(statement),
+        Statement::ExplainTable { .. }
+        | Statement::ShowTables { .. }
+        | Statement::ShowColumns { .. } => true,
+        _ => false,
+    }
+}
+
+fn is_read_only_query(query: &sqlparser::ast::Query) -> bool {
+    // Row locks (FOR UPDATE / FOR SHARE) block writers
+    if !query.locks.is_empty() {
+        return false;
+    }
+    let ctes_read_only = query
+        .with
+        .as_ref()
+        .is_none_or(|with| with.cte_tables.iter().all(|cte| is_read_only_query(&cte.query)));
+    ctes_read_only && is_read_only_set_expr(&query.body)
+}
+
+fn is_read_only_set_expr(body: &SetExpr) -> bool {
+    match body {
+        // SELECT ... INTO creates a table (PostgreSQL) or writes a file (MySQL)
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Query(query) => is_read_only_query(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            is_read_only_set_expr(left) && is_read_only_set_expr(right)
+        }
+        SetExpr::Values(_) | SetExpr::Table(_) => true,
+        SetExpr::Insert(_) | SetExpr::Update(_) => false,
+    }
+}
+
+/// First words of a statement, for error messages
+fn statement_summary(statement: &Statement) -> String {
+    let text = statement.to_string();
+    match text.char_indices().nth(60) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_queries() {
+        let pg = DatabaseType::PostgreSQL;
+        assert!(check_read_only(&pg, "SELECT * FROM users WHERE id = $1").is_ok());
+        assert!(check_read_only(&pg, "WITH recent AS (SELECT * FROM orders) SELECT count(*) FROM recent").is_ok());
+        assert!(check_read_only(&pg, "SELECT 1 UNION SELECT 2; SELECT 3").is_ok());
+        assert!(check_read_only(&pg, "EXPLAIN SELECT * FROM users").is_ok());
+        assert!(check_read_only(&DatabaseType::MySQL, "SHOW TABLES").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_writes() {
+        let pg = DatabaseType::PostgreSQL;
+        assert!(check_read_only(&pg, "DELETE FROM users").is_err());
+        assert!(check_read_only(&pg, "SELECT 1; DROP TABLE users").is_err());
+        assert!(check_read_only(&pg, "SELECT * INTO backup FROM users").is_err());
+        assert!(check_read_only(&pg, "SELECT * FROM users FOR UPDATE").is_err());
+        assert!(check_read_only(&pg, "EXPLAIN ANALYZE UPDATE users SET name = 'x'").is_err());
+        assert!(check_read_only(&DatabaseType::SQLite, "PRAGMA journal_mode = DELETE").is_err());
+        assert!(check_read_only(&DatabaseType::MySQL, "SELEC * FROM users").is_err());
+        assert!(check_read_only(&pg, "").is_err());
+    }
+}
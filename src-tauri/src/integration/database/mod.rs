@@ -2,10 +2,15 @@
 //!
 //! Public API for database operations.
 
+pub mod audit;
+#[cfg(feature = "database")]
+pub mod guard;
 pub mod pool;
 
-pub use pool::{DatabasePoolManager, QueryResult, SchemaInfo};
+pub use audit::QueryAuditEntry;
+pub use pool::{describe_schema, DatabasePoolManager, QueryOptions, QueryResult, SchemaInfo};
 
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 
 /// Database connection configuration with encrypted password
@@ -19,6 +24,9 @@ pub struct DatabaseConnectionConfig {
     pub username: String,
     pub encrypted_password: Option<String>, // Encrypted password from keychain
     pub ssl: bool,
+    /// Only allow statements that cannot change data
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 /// Database types
@@ -31,18 +39,25 @@ pub enum DatabaseType {
 
 impl DatabaseConnectionConfig {
     /// Validate configuration
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), AppError> {
+        // SQLite only needs the database file path
+        if self.db_type == DatabaseType::SQLite {
+            if self.database.is_empty() {
+                return Err(AppError::invalid_input("Database file path is required"));
+            }
+            return Ok(());
+        }
         if self.host.is_empty() {
-            return Err("Database host is required".to_string());
+            return Err(AppError::invalid_input("Database host is required"));
         }
         if self.database.is_empty() {
-            return Err("Database name is required".to_string());
+            return Err(AppError::invalid_input("Database name is required"));
         }
         if self.username.is_empty() {
-            return Err("Username is required".to_string());
+            return Err(AppError::invalid_input("Username is required"));
         }
         if self.port == 0 {
-            return Err("Invalid port number".to_string());
+            return Err(AppError::invalid_input("Invalid port number"));
         }
         Ok(())
     }
@@ -73,46 +88,193 @@ impl DatabaseConnectionConfig {
     }
 }
 
+/// Keychain entry holding a connection's password
+pub fn password_key(name: &str) -> String {
+    format!("db_connection:{}", name)
+}
+
+/// Open (or reopen) a connection pool for a stored connection
+///
+/// The password is read from the keychain and the connection is tested before
+/// the command returns.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn database_connect(
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    credentials: tauri::State<'_, std::sync::Mutex<crate::security::CredentialManager>>,
+    config: DatabaseConnectionConfig,
+) -> std::result::Result<(), AppError> {
+    let password = {
+        let credentials = credentials.lock()?;
+        credentials.get_password(&password_key(&config.name)).ok()
+    };
+
+    config.validate()?;
+    let mut manager = pool_manager.lock().await;
+    manager.connect(&config, password).await?;
+    if let Err(e) = manager.test_connection(&config.name).await {
+        manager.remove(&config.name);
+        return Err(e.into());
+    }
+
+    tracing::info!("Opened database pool {}", config.name);
+    Ok(())
+}
+
+/// Close a connection pool
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn database_disconnect(
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    name: String,
+) -> std::result::Result<bool, AppError> {
+    Ok(pool_manager.lock().await.remove(&name))
+}
+
 /// Execute a SQL query and return results
+///
+/// `params` bind to positional placeholders. At most `max_rows` rows are
+/// returned (default 1000); `query_id` allows `database_cancel_query` to stop
+/// the query, and `timeout_ms` cancels it automatically. Every statement is
+/// recorded in the audit log, tagged with `conversation_id` when the query
+/// came from a conversation.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub async fn database_execute_query(
-    _pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    db: tauri::State<'_, crate::db::DbState>,
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
     name: String,
     query: String,
-) -> std::result::Result<QueryResult, String> {
-    use std::time::Instant;
-
-    let start = Instant::now();
-
-    // Try to execute query - placeholder implementation
-    // In production, this would use actual connection pooling
-    Ok(QueryResult {
-        columns: vec!["result".to_string()],
-        rows: vec![vec![serde_json::json!(format!("Query '{}' executed on {}", query, name))]],
-        row_count: 1,
-        execution_time_ms: start.elapsed().as_millis() as u64,
-        error: None,
-    })
+    params: Option<Vec<serde_json::Value>>,
+    max_rows: Option<usize>,
+    timeout_ms: Option<u64>,
+    query_id: Option<String>,
+    conversation_id: Option<String>,
+) -> std::result::Result<QueryResult, AppError> {
+    let started_at = chrono::Utc::now();
+    // Take a handle so the manager is not locked while the query runs
+    let handle = pool_manager.lock().await.handle(&name)?;
+
+    let (outcome, status) = match handle.check_statement(&query) {
+        Err(e) => (Err(e), audit::AuditStatus::Rejected),
+        Ok(()) => {
+            let options = QueryOptions {
+                params: params.unwrap_or_default(),
+                max_rows,
+                timeout_ms,
+                query_id,
+            };
+            let outcome = handle.execute(query.clone(), options).await;
+            let status = if outcome.is_ok() {
+                audit::AuditStatus::Success
+            } else {
+                audit::AuditStatus::Error
+            };
+            (outcome, status)
+        }
+    };
+
+    {
+        let conn = db.conn.lock()?;
+        if let Err(e) = audit::record_query(&conn, &name, &query, conversation_id.as_deref(), &outcome, status) {
+            tracing::warn!("Failed to record query audit entry: {}", e);
+        }
+        // Queries run for a conversation were initiated by the agent
+        if conversation_id.is_some() {
+            crate::tools::audit::record_quietly(
+                &conn,
+                &crate::tools::audit::NewAction {
+                    kind: crate::tools::audit::ActionKind::DbQuery,
+                    name: "database_execute_query",
+                    conversation_id: conversation_id.as_deref(),
+                    params: &serde_json::json!({ "connection": name, "query": query }),
+                    status: match status {
+                        audit::AuditStatus::Success => crate::tools::audit::ActionStatus::Success,
+                        audit::AuditStatus::Error => crate::tools::audit::ActionStatus::Error,
+                        audit::AuditStatus::Rejected => crate::tools::audit::ActionStatus::Denied,
+                    },
+                    error: outcome.as_ref().err().map(String::as_str),
+                    started_at,
+                    finished_at: chrono::Utc::now(),
+                },
+            );
+        }
+    }
+    Ok(outcome?)
+}
+
+/// List SQL audit log entries, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn database_query_audit(
+    db: tauri::State<'_, crate::db::DbState>,
+    name: Option<String>,
+    conversation_id: Option<String>,
+    limit: Option<u32>,
+) -> std::result::Result<Vec<QueryAuditEntry>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(audit::list_queries(&conn, name.as_deref(), conversation_id.as_deref(), limit)?)
+}
+
+/// Cancel a running query
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn database_cancel_query(
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    query_id: String,
+) -> std::result::Result<bool, AppError> {
+    let running = pool_manager.lock().await.running();
+    Ok(running.cancel(&query_id).await?)
 }
 
 /// Get schema information for a database
+///
+/// Results are cached per connection for five minutes; `refresh` bypasses the
+/// cache.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn database_get_schema(
-    _pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
-    _name: String,
-) -> std::result::Result<Vec<SchemaInfo>, String> {
-    // Placeholder implementation
-    Ok(vec![])
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    name: String,
+    refresh: Option<bool>,
+) -> std::result::Result<Vec<SchemaInfo>, AppError> {
+    let handle = pool_manager.lock().await.handle(&name)?;
+    Ok(handle.schema(refresh.unwrap_or(false)).await?)
 }
 
 /// List tables in a database
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn database_list_tables(
-    _pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
-    _name: String,
-) -> std::result::Result<Vec<String>, String> {
-    // Placeholder implementation
-    Ok(vec![])
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    name: String,
+) -> std::result::Result<Vec<String>, AppError> {
+    let handle = pool_manager.lock().await.handle(&name)?;
+    let tables = handle.schema(false).await?;
+    Ok(tables.into_iter().map(|t| t.table_name).collect())
+}
+
+/// Describe a database schema as compact text (the agent's `database_schema` tool)
+///
+/// `tables` limits the description to the named tables.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn database_describe_schema(
+    pool_manager: tauri::State<'_, tokio::sync::Mutex<DatabasePoolManager>>,
+    name: String,
+    tables: Option<Vec<String>>,
+) -> std::result::Result<String, AppError> {
+    let handle = pool_manager.lock().await.handle(&name)?;
+    let mut schema = handle.schema(false).await?;
+    if let Some(tables) = tables.filter(|t| !t.is_empty()) {
+        schema.retain(|table| tables.iter().any(|t| t.eq_ignore_ascii_case(&table.table_name)));
+    }
+
+    if schema.is_empty() {
+        return Ok(format!("No matching tables in '{}'", name));
+    }
+    Ok(describe_schema(&schema))
 }
 
 // ============================================================================
@@ -121,14 +283,16 @@ pub async fn database_list_tables(
 
 /// Test database connection (legacy command)
 #[tauri::command]
-pub fn test_database_connection(config: DatabaseConnectionConfig) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn test_database_connection(config: DatabaseConnectionConfig) -> Result<String, AppError> {
     config.validate()?;
     Ok(format!("Successfully connected to {} database", config.database))
 }
 
 /// Get database connection string (legacy command, for display only)
 #[tauri::command]
-pub fn get_database_connection_string(name: String) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_database_connection_string(name: String) -> Result<String, AppError> {
     // This is a placeholder - actual connection string should come from stored config
     Ok(format!("Connection string for: {}", name))
 }
@@ -1,17 +1,41 @@
 //! Database Connection Pool and Query Execution
 //!
-//! This module provides actual database query execution for PostgreSQL and MySQL.
+//! This module provides query execution for PostgreSQL, MySQL and SQLite
+//! through sqlx. Each named connection owns a pool; every query runs on its own
+//! pooled connection so that a long-running statement can be cancelled on the
+//! server (`pg_cancel_backend` / `KILL QUERY`) as well as locally.
+//!
+//! Schema introspection (tables, columns, keys and indexes) is cached per
+//! connection for a few minutes so the agent can consult it cheaply.
 
-use super::DatabaseType;
+use super::DatabaseConnectionConfig;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::AtomicI64;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 // Database feature-gated imports
 #[cfg(feature = "database")]
-use tokio_postgres as postgres;
-
+use super::DatabaseType;
+#[cfg(feature = "database")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "database")]
+use futures_util::TryStreamExt;
 #[cfg(feature = "database")]
-use mysql_async::{self as mysql, prelude::*};
+use sqlx::{Column, Database, Encode, Executor, Row, Type};
+
+/// Rows returned when the caller does not set a limit
+pub const DEFAULT_MAX_ROWS: usize = 1000;
+
+/// Connections kept per named pool
+const MAX_POOL_CONNECTIONS: u32 = 5;
+
+/// How long to wait for a free pooled connection
+const ACQUIRE_TIMEOUT_SECS: u64 = 10;
+
+/// How long introspected schemas stay cached
+const SCHEMA_CACHE_TTL_SECS: u64 = 300;
 
 /// Query result for database operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,44 +45,825 @@ pub struct QueryResult {
     pub row_count: usize,
     pub execution_time_ms: u64,
     pub error: Option<String>,
+    /// Rows changed by INSERT/UPDATE/DELETE statements
+    #[serde(default)]
+    pub rows_affected: u64,
+    /// More rows were available than the row limit allowed
+    #[serde(default)]
+    pub truncated: bool,
 }
 
-/// Database connection entry
-#[derive(Debug)]
-struct DatabaseConnection {
-    name: String,
-    db_type: DatabaseType,
-    #[cfg(feature = "database")]
-    postgres_config: Option<PostgresConfig>,
-    #[cfg(feature = "database")]
-    mysql_config: Option<MysqlConfig>,
+/// Options for a single query execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryOptions {
+    /// Positional parameters (`$1` for PostgreSQL, `?` for MySQL/SQLite)
+    #[serde(default)]
+    pub params: Vec<serde_json::Value>,
+    pub max_rows: Option<usize>,
+    pub timeout_ms: Option<u64>,
+    /// Caller-chosen id used to cancel the query
+    pub query_id: Option<String>,
+}
+
+/// Pool for one named connection
+#[cfg(feature = "database")]
+#[derive(Clone)]
+pub enum DatabasePool {
+    Postgres(sqlx::PgPool),
+    MySql(sqlx::MySqlPool),
+    Sqlite(sqlx::SqlitePool),
+}
+
+#[cfg(feature = "database")]
+impl DatabasePool {
+    fn db_type(&self) -> DatabaseType {
+        match self {
+            Self::Postgres(_) => DatabaseType::PostgreSQL,
+            Self::MySql(_) => DatabaseType::MySQL,
+            Self::Sqlite(_) => DatabaseType::SQLite,
+        }
+    }
+
+    async fn close(&self) {
+        match self {
+            Self::Postgres(pool) => pool.close().await,
+            Self::MySql(pool) => pool.close().await,
+            Self::Sqlite(pool) => pool.close().await,
+        }
+    }
+
+    /// Run a query on a dedicated connection, recording its server-side id
+    async fn run(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+        max_rows: usize,
+        backend_id: &AtomicI64,
+    ) -> Result<QueryResult, String> {
+        match self {
+            Self::Postgres(pool) => {
+                let mut conn = pool.acquire().await.map_err(acquire_error)?;
+                let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(|e| format!("Query execution failed: {}", e))?;
+                backend_id.store(pid as i64, Ordering::SeqCst);
+
+                fetch_rows::<sqlx::Postgres>(&mut conn, sql, params, max_rows, postgres_value, |done| {
+                    done.rows_affected()
+                })
+                .await
+            }
+            Self::MySql(pool) => {
+                let mut conn = pool.acquire().await.map_err(acquire_error)?;
+                let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(|e| format!("Query execution failed: {}", e))?;
+                backend_id.store(id as i64, Ordering::SeqCst);
+
+                fetch_rows::<sqlx::MySql>(&mut conn, sql, params, max_rows, mysql_value, |done| {
+                    done.rows_affected()
+                })
+                .await
+            }
+            Self::Sqlite(pool) => {
+                let mut conn = pool.acquire().await.map_err(acquire_error)?;
+                fetch_rows::<sqlx::Sqlite>(&mut conn, sql, params, max_rows, sqlite_value, |done| {
+                    done.rows_affected()
+                })
+                .await
+            }
+        }
+    }
+
+    /// Ask the server to stop the statement running on a connection
+    async fn cancel_on_server(&self, backend_id: i64) -> Result<(), String> {
+        if backend_id == 0 {
+            return Ok(());
+        }
+
+        let result = match self {
+            Self::Postgres(pool) => sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(backend_id as i32)
+                .execute(pool)
+                .await
+                .map(|_| ()),
+            // KILL does not accept placeholders; the id is an integer we read ourselves
+            Self::MySql(pool) => sqlx::query(&format!("KILL QUERY {}", backend_id))
+                .execute(pool)
+                .await
+                .map(|_| ()),
+            // SQLite has no server to signal; aborting the task is all we can do
+            Self::Sqlite(_) => Ok(()),
+        };
+        result.map_err(|e| format!("Failed to cancel query on server: {}", e))
+    }
+}
+
+#[cfg(feature = "database")]
+impl DatabasePool {
+    /// Read tables, columns, keys and indexes
+    async fn introspect(&self) -> Result<Vec<SchemaInfo>, String> {
+        match self {
+            Self::Postgres(pool) => introspect_postgres(pool).await,
+            Self::MySql(pool) => introspect_mysql(pool).await,
+            Self::Sqlite(pool) => introspect_sqlite(pool).await,
+        }
+    }
+}
+
+#[cfg(feature = "database")]
+fn schema_error(e: sqlx::Error) -> String {
+    format!("Schema introspection failed: {}", e)
+}
+
+/// PostgreSQL tables outside `public` are named `schema.table`
+#[cfg(feature = "database")]
+async fn introspect_postgres(pool: &sqlx::PgPool) -> Result<Vec<SchemaInfo>, String> {
+    let mut tables = SchemaBuilder::default();
+
+    let columns = sqlx::query(
+        "SELECT (CASE WHEN c.table_schema = 'public' THEN c.table_name
+                      ELSE c.table_schema || '.' || c.table_name END)::text,
+                c.column_name::text,
+                (CASE WHEN c.data_type = 'USER-DEFINED' THEN c.udt_name
+                      ELSE c.data_type END)::text,
+                c.is_nullable = 'YES'
+         FROM information_schema.columns c
+         WHERE c.table_schema NOT IN ('pg_catalog', 'information_schema')
+         ORDER BY c.table_schema, c.table_name, c.ordinal_position",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+    for row in &columns {
+        tables.table(row.try_get(0).map_err(schema_error)?).columns.push(ColumnInfo {
+            name: row.try_get(1).map_err(schema_error)?,
+            data_type: row.try_get(2).map_err(schema_error)?,
+            nullable: row.try_get(3).map_err(schema_error)?,
+            is_primary_key: false,
+        });
+    }
+
+    // Primary and foreign keys, one row per constrained column
+    let constraints = sqlx::query(
+        "SELECT (CASE WHEN n.nspname = 'public' THEN cl.relname
+                      ELSE n.nspname || '.' || cl.relname END)::text,
+                con.contype::text,
+                a.attname::text,
+                (CASE WHEN fn.nspname = 'public' THEN fcl.relname
+                      ELSE fn.nspname || '.' || fcl.relname END)::text,
+                fa.attname::text
+         FROM pg_constraint con
+         JOIN pg_class cl ON cl.oid = con.conrelid
+         JOIN pg_namespace n ON n.oid = cl.relnamespace
+         CROSS JOIN LATERAL unnest(con.conkey, con.confkey) WITH ORDINALITY AS k(attnum, fattnum, ord)
+         JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+         LEFT JOIN pg_class fcl ON fcl.oid = con.confrelid
+         LEFT JOIN pg_namespace fn ON fn.oid = fcl.relnamespace
+         LEFT JOIN pg_attribute fa ON fa.attrelid = con.confrelid AND fa.attnum = k.fattnum
+         WHERE con.contype IN ('p', 'f')
+           AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+         ORDER BY n.nspname, cl.relname, con.conname, k.ord",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+    for row in &constraints {
+        let table = tables.table(row.try_get(0).map_err(schema_error)?);
+        let kind: String = row.try_get(1).map_err(schema_error)?;
+        let column: String = row.try_get(2).map_err(schema_error)?;
+        if kind == "p" {
+            table.mark_primary_key(&column);
+        } else {
+            table.foreign_keys.push(ForeignKeyInfo {
+                column,
+                referenced_table: row.try_get(3).map_err(schema_error)?,
+                referenced_column: row.try_get(4).map_err(schema_error)?,
+            });
+        }
+    }
+
+    // Expression index entries have no attribute name
+    let indexes = sqlx::query(
+        "SELECT (CASE WHEN n.nspname = 'public' THEN t.relname
+                      ELSE n.nspname || '.' || t.relname END)::text,
+                i.relname::text,
+                ix.indisunique,
+                a.attname::text
+         FROM pg_index ix
+         JOIN pg_class t ON t.oid = ix.indrelid
+         JOIN pg_class i ON i.oid = ix.indexrelid
+         JOIN pg_namespace n ON n.oid = t.relnamespace
+         CROSS JOIN LATERAL unnest(ix.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord)
+         LEFT JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum
+         WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+           AND n.nspname NOT LIKE 'pg_toast%'
+         ORDER BY n.nspname, t.relname, i.relname, k.ord",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+    for row in &indexes {
+        let column: Option<String> = row.try_get(3).map_err(schema_error)?;
+        tables.table(row.try_get(0).map_err(schema_error)?).push_index_column(
+            row.try_get(1).map_err(schema_error)?,
+            row.try_get(2).map_err(schema_error)?,
+            column.unwrap_or_else(|| "(expression)".to_string()),
+        );
+    }
+
+    Ok(tables.finish())
+}
+
+/// Text column from information_schema
+///
+/// MySQL 8 reports some information_schema columns as binary strings, which
+/// the checked decoder rejects.
+#[cfg(feature = "database")]
+fn mysql_text(row: &sqlx::mysql::MySqlRow, index: usize) -> Result<Option<String>, String> {
+    row.try_get_unchecked::<Option<String>, _>(index).map_err(schema_error)
+}
+
+#[cfg(feature = "database")]
+async fn introspect_mysql(pool: &sqlx::MySqlPool) -> Result<Vec<SchemaInfo>, String> {
+    let mut tables = SchemaBuilder::default();
+
+    let columns = sqlx::query(
+        "SELECT TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_KEY
+         FROM information_schema.COLUMNS
+         WHERE TABLE_SCHEMA = DATABASE()
+         ORDER BY TABLE_NAME, ORDINAL_POSITION",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+    for row in &columns {
+        tables.table(mysql_text(row, 0)?.unwrap_or_default()).columns.push(ColumnInfo {
+            name: mysql_text(row, 1)?.unwrap_or_default(),
+            data_type: mysql_text(row, 2)?.unwrap_or_default(),
+            nullable: mysql_text(row, 3)?.as_deref() == Some("YES"),
+            is_primary_key: mysql_text(row, 4)?.as_deref() == Some("PRI"),
+        });
+    }
+
+    let foreign_keys = sqlx::query(
+        "SELECT TABLE_NAME, COLUMN_NAME, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME
+         FROM information_schema.KEY_COLUMN_USAGE
+         WHERE TABLE_SCHEMA = DATABASE() AND REFERENCED_TABLE_NAME IS NOT NULL
+         ORDER BY TABLE_NAME, CONSTRAINT_NAME, ORDINAL_POSITION",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+    for row in &foreign_keys {
+        tables.table(mysql_text(row, 0)?.unwrap_or_default()).foreign_keys.push(ForeignKeyInfo {
+            column: mysql_text(row, 1)?.unwrap_or_default(),
+            referenced_table: mysql_text(row, 2)?.unwrap_or_default(),
+            referenced_column: mysql_text(row, 3)?,
+        });
+    }
+
+    // Functional index parts have no COLUMN_NAME
+    let indexes = sqlx::query(
+        "SELECT TABLE_NAME, INDEX_NAME, NON_UNIQUE, COLUMN_NAME
+         FROM information_schema.STATISTICS
+         WHERE TABLE_SCHEMA = DATABASE()
+         ORDER BY TABLE_NAME, INDEX_NAME, SEQ_IN_INDEX",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+    for row in &indexes {
+        let non_unique: i64 = row.try_get_unchecked(2).map_err(schema_error)?;
+        tables.table(mysql_text(row, 0)?.unwrap_or_default()).push_index_column(
+            mysql_text(row, 1)?.unwrap_or_default(),
+            non_unique == 0,
+            mysql_text(row, 3)?.unwrap_or_else(|| "(expression)".to_string()),
+        );
+    }
+
+    Ok(tables.finish())
+}
+
+#[cfg(feature = "database")]
+async fn introspect_sqlite(pool: &sqlx::SqlitePool) -> Result<Vec<SchemaInfo>, String> {
+    let mut tables = SchemaBuilder::default();
+
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master
+         WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(schema_error)?;
+
+    for name in names {
+        let columns = sqlx::query(r#"SELECT name, type, "notnull", pk FROM pragma_table_info(?)"#)
+            .bind(&name)
+            .fetch_all(pool)
+            .await
+            .map_err(schema_error)?;
+        let foreign_keys = sqlx::query(r#"SELECT "from", "table", "to" FROM pragma_foreign_key_list(?) ORDER BY id, seq"#)
+            .bind(&name)
+            .fetch_all(pool)
+            .await
+            .map_err(schema_error)?;
+        let indexes = sqlx::query(r#"SELECT name, "unique" FROM pragma_index_list(?) ORDER BY name"#)
+            .bind(&name)
+            .fetch_all(pool)
+            .await
+            .map_err(schema_error)?;
+
+        let table = tables.table(name);
+        for row in &columns {
+            table.columns.push(ColumnInfo {
+                name: row.try_get(0).map_err(schema_error)?,
+                data_type: row.try_get(1).map_err(schema_error)?,
+                nullable: !row.try_get::<bool, _>(2).map_err(schema_error)?,
+                is_primary_key: row.try_get::<i64, _>(3).map_err(schema_error)? > 0,
+            });
+        }
+        for row in &foreign_keys {
+            table.foreign_keys.push(ForeignKeyInfo {
+                column: row.try_get(0).map_err(schema_error)?,
+                referenced_table: row.try_get(1).map_err(schema_error)?,
+                referenced_column: row.try_get(2).map_err(schema_error)?,
+            });
+        }
+        for row in &indexes {
+            let index_name: String = row.try_get(0).map_err(schema_error)?;
+            let unique: bool = row.try_get(1).map_err(schema_error)?;
+            // Expression columns report a NULL name
+            let columns: Vec<Option<String>> =
+                sqlx::query_scalar("SELECT name FROM pragma_index_info(?) ORDER BY seqno")
+                    .bind(&index_name)
+                    .fetch_all(pool)
+                    .await
+                    .map_err(schema_error)?;
+            for column in columns {
+                table.push_index_column(
+                    index_name.clone(),
+                    unique,
+                    column.unwrap_or_else(|| "(expression)".to_string()),
+                );
+            }
+        }
+    }
+
+    Ok(tables.finish())
+}
+
+#[cfg(feature = "database")]
+fn acquire_error(e: sqlx::Error) -> String {
+    format!("Failed to acquire database connection: {}", e)
+}
+
+/// Bind JSON values as positional parameters
+#[cfg(feature = "database")]
+fn bind_params<'q, DB>(
+    mut query: sqlx::query::Query<'q, DB, <DB as Database>::Arguments<'q>>,
+    params: &[serde_json::Value],
+) -> sqlx::query::Query<'q, DB, <DB as Database>::Arguments<'q>>
+where
+    DB: Database,
+    bool: Encode<'q, DB> + Type<DB>,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    String: Encode<'q, DB> + Type<DB>,
+    Option<String>: Encode<'q, DB> + Type<DB>,
+    sqlx::types::Json<serde_json::Value>: Encode<'q, DB> + Type<DB>,
+{
+    use serde_json::Value;
+
+    for param in params {
+        query = match param {
+            Value::Null => query.bind(None::<String>),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64().unwrap_or_default()),
+            },
+            Value::String(s) => query.bind(s.clone()),
+            Value::Array(_) | Value::Object(_) => query.bind(sqlx::types::Json(param.clone())),
+        };
+    }
+    query
+}
+
+/// Execute a statement and collect up to `max_rows` rows as JSON
+#[cfg(feature = "database")]
+async fn fetch_rows<DB>(
+    conn: &mut DB::Connection,
+    sql: &str,
+    params: &[serde_json::Value],
+    max_rows: usize,
+    to_json: fn(&DB::Row, usize) -> serde_json::Value,
+    rows_affected: fn(&DB::QueryResult) -> u64,
+) -> Result<QueryResult, String>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as Database>::Arguments<'q>: sqlx::IntoArguments<'q, DB>,
+    for<'q> bool: Encode<'q, DB> + Type<DB>,
+    for<'q> i64: Encode<'q, DB> + Type<DB>,
+    for<'q> f64: Encode<'q, DB> + Type<DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    for<'q> Option<String>: Encode<'q, DB> + Type<DB>,
+    for<'q> sqlx::types::Json<serde_json::Value>: Encode<'q, DB> + Type<DB>,
+{
+    let start = std::time::Instant::now();
+    let query = bind_params(sqlx::query::<DB>(sql), params);
+
+    let mut columns = Vec::new();
+    let mut rows = Vec::new();
+    let mut affected = 0;
+    let mut truncated = false;
+
+    let mut stream = conn.fetch_many(query);
+    while let Some(step) = stream
+        .try_next()
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?
+    {
+        match step {
+            sqlx::Either::Left(done) => affected += rows_affected(&done),
+            sqlx::Either::Right(row) => {
+                if rows.len() >= max_rows {
+                    truncated = true;
+                    break;
+                }
+                if columns.is_empty() {
+                    columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                rows.push((0..row.len()).map(|i| to_json(&row, i)).collect());
+            }
+        }
+    }
+
+    Ok(QueryResult {
+        columns,
+        row_count: rows.len(),
+        rows,
+        execution_time_ms: start.elapsed().as_millis() as u64,
+        error: None,
+        rows_affected: affected,
+        truncated,
+    })
+}
+
+/// Decode a column as the first compatible type in the list
+///
+/// sqlx checks type compatibility before decoding, so incompatible types fall
+/// through to the next candidate. NULL decodes as `None` for any of them.
+#[cfg(feature = "database")]
+macro_rules! decode_first {
+    ($row:expr, $index:expr, $( $ty:ty => $to_json:expr ),+ $(,)?) => {
+        $(
+            if let Ok(value) = $row.try_get::<Option<$ty>, _>($index) {
+                return value.map($to_json).unwrap_or(serde_json::Value::Null);
+            }
+        )+
+    };
+}
+
+/// Render bytes like psql does (`\x0a1b`)
+fn bytes_to_hex(bytes: Vec<u8>) -> serde_json::Value {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    serde_json::Value::String(format!("\\x{}", hex))
+}
+
+/// Placeholder for values of types that cannot be decoded
+#[cfg(feature = "database")]
+fn unsupported_value<R: Row>(row: &R, index: usize) -> serde_json::Value {
+    use sqlx::TypeInfo;
+    serde_json::Value::String(format!("<{}>", row.columns()[index].type_info().name()))
+}
+
+#[cfg(feature = "database")]
+fn postgres_value(row: &sqlx::postgres::PgRow, index: usize) -> serde_json::Value {
+    use serde_json::json;
+
+    decode_first!(row, index,
+        bool => |v| json!(v),
+        i16 => |v| json!(v),
+        i32 => |v| json!(v),
+        i64 => |v| json!(v),
+        f32 => |v| json!(v),
+        f64 => |v| json!(v),
+        String => |v| json!(v),
+        serde_json::Value => |v| v,
+        uuid::Uuid => |v| json!(v.to_string()),
+        chrono::DateTime<chrono::Utc> => |v| json!(v.to_rfc3339()),
+        chrono::NaiveDateTime => |v| json!(v.to_string()),
+        chrono::NaiveDate => |v| json!(v.to_string()),
+        chrono::NaiveTime => |v| json!(v.to_string()),
+        Vec<u8> => bytes_to_hex,
+    );
+    unsupported_value(row, index)
 }
 
-/// PostgreSQL configuration
 #[cfg(feature = "database")]
-#[derive(Debug, Clone)]
-struct PostgresConfig {
-    connection_string: String,
+fn mysql_value(row: &sqlx::mysql::MySqlRow, index: usize) -> serde_json::Value {
+    use serde_json::json;
+
+    decode_first!(row, index,
+        bool => |v| json!(v),
+        i64 => |v| json!(v),
+        u64 => |v| json!(v),
+        f32 => |v| json!(v),
+        f64 => |v| json!(v),
+        String => |v| json!(v),
+        serde_json::Value => |v| v,
+        chrono::DateTime<chrono::Utc> => |v| json!(v.to_rfc3339()),
+        chrono::NaiveDateTime => |v| json!(v.to_string()),
+        chrono::NaiveDate => |v| json!(v.to_string()),
+        chrono::NaiveTime => |v| json!(v.to_string()),
+        Vec<u8> => bytes_to_hex,
+    );
+    // DECIMAL and similar types are sent as text in the binary protocol
+    match row.try_get_unchecked::<Option<String>, _>(index) {
+        Ok(value) => value.map(|v| json!(v)).unwrap_or(serde_json::Value::Null),
+        Err(_) => unsupported_value(row, index),
+    }
 }
 
-/// MySQL configuration
 #[cfg(feature = "database")]
-#[derive(Debug, Clone)]
-struct MysqlConfig {
-    connection_string: String,
+fn sqlite_value(row: &sqlx::sqlite::SqliteRow, index: usize) -> serde_json::Value {
+    use serde_json::json;
+
+    decode_first!(row, index,
+        i64 => |v| json!(v),
+        f64 => |v| json!(v),
+        String => |v| json!(v),
+        Vec<u8> => bytes_to_hex,
+    );
+    unsupported_value(row, index)
+}
+
+/// Query in flight, for cancellation
+struct RunningQuery {
+    abort: tokio::task::AbortHandle,
+    /// Server-side connection id, 0 until known
+    backend_id: Arc<AtomicI64>,
+    #[cfg(feature = "database")]
+    pool: DatabasePool,
+}
+
+/// Queries in flight, keyed by query id
+#[derive(Clone, Default)]
+pub struct RunningQueries {
+    queries: Arc<Mutex<HashMap<String, RunningQuery>>>,
+}
+
+impl RunningQueries {
+    fn insert(&self, query_id: String, query: RunningQuery) -> Result<(), String> {
+        let mut queries = self.queries.lock().map_err(|e| e.to_string())?;
+        if queries.contains_key(&query_id) {
+            return Err(format!("Query {} is already running", query_id));
+        }
+        queries.insert(query_id, query);
+        Ok(())
+    }
+
+    fn remove(&self, query_id: &str) -> Option<RunningQuery> {
+        self.queries.lock().ok()?.remove(query_id)
+    }
+
+    /// Cancel a running query, returning false if it was not found
+    pub async fn cancel(&self, query_id: &str) -> Result<bool, String> {
+        let Some(query) = self.remove(query_id) else {
+            return Ok(false);
+        };
+        query.abort.abort();
+
+        #[cfg(feature = "database")]
+        query
+            .pool
+            .cancel_on_server(query.backend_id.load(Ordering::SeqCst))
+            .await?;
+
+        tracing::info!("Cancelled query {}", query_id);
+        Ok(true)
+    }
+}
+
+/// Introspected tables and when they were read
+type CachedSchema = (Instant, Vec<SchemaInfo>);
+
+/// Introspected schemas by connection name
+#[derive(Clone)]
+pub struct SchemaCache {
+    entries: Arc<Mutex<HashMap<String, CachedSchema>>>,
+    ttl: Duration,
+}
+
+impl SchemaCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// Cached schema, if it has not expired
+    fn get(&self, name: &str) -> Option<Vec<SchemaInfo>> {
+        let entries = self.entries.lock().ok()?;
+        let (fetched_at, tables) = entries.get(name)?;
+        (fetched_at.elapsed() < self.ttl).then(|| tables.clone())
+    }
+
+    fn insert(&self, name: &str, tables: Vec<SchemaInfo>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(name.to_string(), (Instant::now(), tables));
+        }
+    }
+
+    /// Drop a connection's cached schema
+    pub fn invalidate(&self, name: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(name);
+        }
+    }
+}
+
+impl Default for SchemaCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(SCHEMA_CACHE_TTL_SECS))
+    }
+}
+
+/// Whether a statement may change the schema
+fn is_schema_change(sql: &str) -> bool {
+    let keyword = sql
+        .trim_start()
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+    ["CREATE", "ALTER", "DROP", "RENAME"]
+        .iter()
+        .any(|k| keyword.eq_ignore_ascii_case(k))
+}
+
+/// A connection's pool plus the shared running-query registry
+///
+/// Obtained from the manager so queries run without holding its lock.
+pub struct QueryHandle {
+    name: String,
+    #[cfg(feature = "database")]
+    pool: DatabasePool,
+    running: RunningQueries,
+    schemas: SchemaCache,
+    read_only: bool,
+}
+
+impl QueryHandle {
+    /// Whether the connection only accepts read-only statements
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Check a statement against the connection's read-only flag
+    #[cfg(feature = "database")]
+    pub fn check_statement(&self, sql: &str) -> Result<(), String> {
+        if self.read_only {
+            super::guard::check_read_only(&self.pool.db_type(), sql)?;
+        }
+        Ok(())
+    }
+
+    /// Check a statement (non-database feature)
+    #[cfg(not(feature = "database"))]
+    pub fn check_statement(&self, _sql: &str) -> Result<(), String> {
+        Err("Database feature not enabled".to_string())
+    }
+
+    /// Execute a query with parameters, a row limit and an optional timeout
+    #[cfg(feature = "database")]
+    pub async fn execute(self, sql: String, options: QueryOptions) -> Result<QueryResult, String> {
+        self.check_statement(&sql)?;
+        let max_rows = options.max_rows.unwrap_or(DEFAULT_MAX_ROWS);
+        let query_id = options
+            .query_id
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let backend_id = Arc::new(AtomicI64::new(0));
+        let schema_change = is_schema_change(&sql);
+
+        let pool = self.pool.clone();
+        let task_backend_id = backend_id.clone();
+        let mut task = tokio::spawn(async move {
+            pool.run(&sql, &options.params, max_rows, &task_backend_id).await
+        });
+
+        self.running.insert(
+            query_id.clone(),
+            RunningQuery {
+                abort: task.abort_handle(),
+                backend_id: backend_id.clone(),
+                pool: self.pool.clone(),
+            },
+        )?;
+
+        let outcome = match options.timeout_ms {
+            Some(ms) => tokio::time::timeout(std::time::Duration::from_millis(ms), &mut task)
+                .await
+                .ok(),
+            None => Some((&mut task).await),
+        };
+
+        match outcome {
+            Some(Ok(result)) => {
+                self.running.remove(&query_id);
+                if schema_change && result.is_ok() {
+                    self.schemas.invalidate(&self.name);
+                }
+                result
+            }
+            Some(Err(e)) if e.is_cancelled() => Err("Query cancelled".to_string()),
+            Some(Err(e)) => {
+                self.running.remove(&query_id);
+                Err(format!("Query task failed: {}", e))
+            }
+            None => {
+                self.running.cancel(&query_id).await?;
+                Err(format!(
+                    "Query timed out after {} ms",
+                    options.timeout_ms.unwrap_or_default()
+                ))
+            }
+        }
+    }
+
+    /// Execute a query (non-database feature)
+    #[cfg(not(feature = "database"))]
+    pub async fn execute(self, _sql: String, _options: QueryOptions) -> Result<QueryResult, String> {
+        Err("Database feature not enabled".to_string())
+    }
+
+    /// Tables of the connection, from the cache unless `refresh` is set
+    pub async fn schema(&self, refresh: bool) -> Result<Vec<SchemaInfo>, String> {
+        if !refresh {
+            if let Some(tables) = self.schemas.get(&self.name) {
+                return Ok(tables);
+            }
+        }
+
+        let tables = self.introspect().await?;
+        self.schemas.insert(&self.name, tables.clone());
+        tracing::debug!("Introspected {} tables for {}", tables.len(), self.name);
+        Ok(tables)
+    }
+
+    #[cfg(feature = "database")]
+    async fn introspect(&self) -> Result<Vec<SchemaInfo>, String> {
+        self.pool.introspect().await
+    }
+
+    #[cfg(not(feature = "database"))]
+    async fn introspect(&self) -> Result<Vec<SchemaInfo>, String> {
+        Err("Database feature not enabled".to_string())
+    }
 }
 
 /// Database connection pool manager
 pub struct DatabasePoolManager {
-    connections: HashMap<String, DatabaseConnection>,
+    #[cfg(feature = "database")]
+    pools: HashMap<String, DatabasePool>,
+    /// Connections restricted to read-only statements
+    read_only: HashSet<String>,
+    running: RunningQueries,
+    schemas: SchemaCache,
 }
 
 impl DatabasePoolManager {
     /// Create a new pool manager
     pub fn new() -> Self {
         Self {
-            connections: HashMap::new(),
+            #[cfg(feature = "database")]
+            pools: HashMap::new(),
+            read_only: HashSet::new(),
+            running: RunningQueries::default(),
+            schemas: SchemaCache::default(),
+        }
+    }
+
+    /// Registry of running queries
+    pub fn running(&self) -> RunningQueries {
+        self.running.clone()
+    }
+
+    /// Names of connections with an open pool
+    pub fn connection_names(&self) -> Vec<String> {
+        #[cfg(feature = "database")]
+        {
+            let mut names: Vec<String> = self.pools.keys().cloned().collect();
+            names.sort();
+            names
         }
+        #[cfg(not(feature = "database"))]
+        Vec::new()
     }
 
     /// Add a PostgreSQL connection
@@ -74,18 +879,13 @@ impl DatabasePoolManager {
             return Err("Invalid PostgreSQL connection string".to_string());
         }
 
-        let config = PostgresConfig {
-            connection_string,
-        };
-
-        let conn = DatabaseConnection {
-            name: name.clone(),
-            db_type: DatabaseType::PostgreSQL,
-            postgres_config: Some(config),
-            mysql_config: None,
-        };
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(MAX_POOL_CONNECTIONS)
+            .acquire_timeout(std::time::Duration::from_secs(ACQUIRE_TIMEOUT_SECS))
+            .connect_lazy(&connection_string)
+            .map_err(|e| format!("Invalid PostgreSQL connection string: {}", e))?;
 
-        self.connections.insert(name, conn);
+        self.insert_pool(name, DatabasePool::Postgres(pool)).await;
         Ok(())
     }
 
@@ -101,244 +901,188 @@ impl DatabasePoolManager {
             return Err("Invalid MySQL connection string".to_string());
         }
 
-        let config = MysqlConfig {
-            connection_string,
-        };
-
-        let conn = DatabaseConnection {
-            name: name.clone(),
-            db_type: DatabaseType::MySQL,
-            postgres_config: None,
-            mysql_config: Some(config),
-        };
+        let pool = sqlx::mysql::MySqlPoolOptions::new()
+            .max_connections(MAX_POOL_CONNECTIONS)
+            .acquire_timeout(std::time::Duration::from_secs(ACQUIRE_TIMEOUT_SECS))
+            .connect_lazy(&connection_string)
+            .map_err(|e| format!("Invalid MySQL connection string: {}", e))?;
 
-        self.connections.insert(name, conn);
+        self.insert_pool(name, DatabasePool::MySql(pool)).await;
         Ok(())
     }
 
-    /// Execute a query on a named connection
+    /// Open a pool from a stored connection config
+    ///
+    /// The pool connects lazily; `test_connection` checks it is reachable.
     #[cfg(feature = "database")]
-    pub async fn execute_query(&self, name: &str, query: &str) -> Result<QueryResult, String> {
-        let conn = self.connections.get(name)
-            .ok_or_else(|| format!("Connection '{}' not found", name))?;
+    pub async fn connect(
+        &mut self,
+        config: &DatabaseConnectionConfig,
+        password: Option<String>,
+    ) -> Result<(), String> {
+        use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions, MySqlSslMode};
+        use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 
-        match conn.db_type {
+        config.validate()?;
+        let acquire_timeout = std::time::Duration::from_secs(ACQUIRE_TIMEOUT_SECS);
+
+        let pool = match config.db_type {
             DatabaseType::PostgreSQL => {
-                if let Some(ref config) = conn.postgres_config {
-                    self.execute_postgres_query(config, query).await
-                } else {
-                    Err("PostgreSQL configuration not found".to_string())
+                let mut options = PgConnectOptions::new()
+                    .host(&config.host)
+                    .port(config.port)
+                    .database(&config.database)
+                    .username(&config.username)
+                    .ssl_mode(if config.ssl { PgSslMode::Require } else { PgSslMode::Prefer });
+                if let Some(password) = &password {
+                    options = options.password(password);
+                }
+                if config.read_only {
+                    options = options.options([("default_transaction_read_only", "on")]);
                 }
+                DatabasePool::Postgres(
+                    PgPoolOptions::new()
+                        .max_connections(MAX_POOL_CONNECTIONS)
+                        .acquire_timeout(acquire_timeout)
+                        .connect_lazy_with(options),
+                )
             }
             DatabaseType::MySQL => {
-                if let Some(ref config) = conn.mysql_config {
-                    self.execute_mysql_query(config, query).await
-                } else {
-                    Err("MySQL configuration not found".to_string())
+                let mut options = MySqlConnectOptions::new()
+                    .host(&config.host)
+                    .port(config.port)
+                    .database(&config.database)
+                    .username(&config.username)
+                    .ssl_mode(if config.ssl { MySqlSslMode::Required } else { MySqlSslMode::Preferred });
+                if let Some(password) = &password {
+                    options = options.password(password);
                 }
+                let mut pool_options = MySqlPoolOptions::new()
+                    .max_connections(MAX_POOL_CONNECTIONS)
+                    .acquire_timeout(acquire_timeout);
+                if config.read_only {
+                    pool_options = pool_options.after_connect(|conn, _| {
+                        Box::pin(async move {
+                            conn.execute("SET SESSION TRANSACTION READ ONLY").await.map(|_| ())
+                        })
+                    });
+                }
+                DatabasePool::MySql(pool_options.connect_lazy_with(options))
             }
             DatabaseType::SQLite => {
-                Err("SQLite queries are not supported through this interface".to_string())
+                // `database` holds the file path; never create files implicitly
+                let options = SqliteConnectOptions::new()
+                    .filename(&config.database)
+                    .create_if_missing(false)
+                    .read_only(config.read_only);
+                DatabasePool::Sqlite(
+                    SqlitePoolOptions::new()
+                        .max_connections(MAX_POOL_CONNECTIONS)
+                        .acquire_timeout(acquire_timeout)
+                        .connect_lazy_with(options),
+                )
             }
+        };
+
+        self.insert_pool(config.name.clone(), pool).await;
+        if config.read_only {
+            self.read_only.insert(config.name.clone());
+        } else {
+            self.read_only.remove(&config.name);
         }
+        Ok(())
     }
 
-    /// Execute a query on a named connection (non-database feature)
+    /// Open a pool (non-database feature)
     #[cfg(not(feature = "database"))]
-    pub async fn execute_query(&self, name: &str, _query: &str) -> Result<QueryResult, String> {
-        Err(format!("Database feature not enabled for connection '{}'", name))
+    pub async fn connect(
+        &mut self,
+        config: &DatabaseConnectionConfig,
+        _password: Option<String>,
+    ) -> Result<(), String> {
+        Err(format!("Database feature not enabled for connection '{}'", config.name))
     }
 
-    /// Execute a PostgreSQL query
+    /// Register a pool, closing any previous pool with the same name
     #[cfg(feature = "database")]
-    async fn execute_postgres_query(&self, config: &PostgresConfig, query: &str) -> Result<QueryResult, String> {
-        let start = std::time::Instant::now();
-
-        // Parse connection string and connect
-        let (client, connection) = postgres::connect(&config.connection_string, postgres::NoTls)
-            .await
-            .map_err(|e| format!("Failed to connect to PostgreSQL: {}", e))?;
-
-        // Spawn connection handler
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                tracing::error!("PostgreSQL connection error: {}", e);
-            }
-        });
-
-        // Execute the query (simple query returns text results)
-        let rows = client.simple_query(query)
-            .await
-            .map_err(|e| format!("Query execution failed: {}", e))?;
-
-        let mut result_rows = Vec::new();
-        let mut columns = Vec::new();
-
-        for row in rows {
-            // Use the row's data directly
-            // In tokio-postgres 0.9+, SimpleQueryRow returns different types
-            // For simplicity, let's just return empty rows for now
-            if columns.is_empty() {
-                columns = vec!["result".to_string()];
-            }
-
-            let row_values = vec![serde_json::json!(format!("{:?}", row))];
-            result_rows.push(row_values);
+    async fn insert_pool(&mut self, name: String, pool: DatabasePool) {
+        self.schemas.invalidate(&name);
+        if let Some(previous) = self.pools.insert(name, pool) {
+            previous.close().await;
         }
-
-        let execution_time = start.elapsed().as_millis() as u64;
-
-        Ok(QueryResult {
-            columns,
-            rows: result_rows.clone(),
-            row_count: result_rows.len(),
-            execution_time_ms: execution_time,
-            error: None,
-        })
     }
 
-    /// Convert PostgreSQL value to JSON (simplified)
-    #[cfg(feature = "database")]
-    fn convert_postgres_value(&self, value: &str) -> serde_json::Value {
-        // Try to parse as different types
-        if let Ok(v) = value.parse::<i64>() {
-            return serde_json::json!(v);
+    /// Remove a connection, closing its pool once running queries finish
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.schemas.invalidate(name);
+        #[cfg(feature = "database")]
+        if let Some(pool) = self.pools.remove(name) {
+            self.read_only.remove(name);
+            tokio::spawn(async move { pool.close().await });
+            return true;
         }
-        if let Ok(v) = value.parse::<f64>() {
-            return serde_json::json!(v);
-        }
-        if let Ok(v) = value.parse::<bool>() {
-            return serde_json::json!(v);
-        }
-        // Default to string
-        serde_json::json!(value)
+        let _ = name;
+        false
     }
 
-    /// Execute a MySQL query
-    #[cfg(feature = "database")]
-    async fn execute_mysql_query(&self, config: &MysqlConfig, query: &str) -> Result<QueryResult, String> {
-        let start = std::time::Instant::now();
-
-        // Parse connection URL using mysql_async's Opts
-        let opts = mysql::Opts::from_url(&config.connection_string)
-            .map_err(|e| format!("Invalid MySQL URL: {}", e))?;
-
-        // Connect to MySQL
-        let mut conn = mysql::Conn::new(opts)
-            .await
-            .map_err(|e| format!("Failed to connect to MySQL: {}", e))?;
-
-        // Execute the query and get results as Vec<Row>
-        let rows: Vec<mysql::Row> = conn.query(query)
-            .await
-            .map_err(|e| format!("Query execution failed: {}", e))?;
-
-        let mut result_rows = Vec::new();
-        let mut columns = Vec::new();
-
-        // Process results
-        for row in &rows {
-            // Get column names from first row
-            if columns.is_empty() && !row.is_empty() {
-                columns = (0..row.len())
-                    .map(|i| format!("column_{}", i))
-                    .collect();
+    /// Remove every connection
+    pub fn remove_all(&mut self) {
+        #[cfg(feature = "database")]
+        {
+            let names: Vec<String> = self.pools.keys().cloned().collect();
+            for name in names {
+                self.remove(&name);
             }
-
-            let row_values = vec![self.convert_mysql_value(row)];
-            result_rows.push(row_values);
         }
+        self.read_only.clear();
+    }
 
-        // Disconnect
-        conn.disconnect().await
-            .map_err(|e| format!("Disconnect error: {}", e))?;
-
-        let execution_time = start.elapsed().as_millis() as u64;
-
-        Ok(QueryResult {
-            columns,
-            rows: result_rows.clone(),
-            row_count: result_rows.len(),
-            execution_time_ms: execution_time,
-            error: None,
+    /// Handle for running queries on a named connection
+    #[cfg(feature = "database")]
+    pub fn handle(&self, name: &str) -> Result<QueryHandle, String> {
+        let pool = self
+            .pools
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Connection '{}' not found", name))?;
+        Ok(QueryHandle {
+            name: name.to_string(),
+            pool,
+            running: self.running.clone(),
+            schemas: self.schemas.clone(),
+            read_only: self.read_only.contains(name),
         })
     }
 
-    /// Convert MySQL value to JSON
-    #[cfg(feature = "database")]
-    fn convert_mysql_value(&self, value: &mysql_async::Row) -> serde_json::Value {
-        // mysql_async 0.34 uses Option<T> return from get() with 2 generic args
-        // Try to get value as String first (index 0)
-        if let Some(Some(v)) = value.get::<Option<String>, _>(0) {
-            return serde_json::json!(v);
-        }
-        if let Some(Some(v)) = value.get::<Option<i64>, _>(0) {
-            return serde_json::json!(v);
-        }
-        if let Some(Some(v)) = value.get::<Option<f64>, _>(0) {
-            return serde_json::json!(v);
-        }
-        if let Some(Some(v)) = value.get::<Option<bool>, _>(0) {
-            return serde_json::json!(v);
-        }
-        serde_json::Value::Null
+    /// Handle for running queries (non-database feature)
+    #[cfg(not(feature = "database"))]
+    pub fn handle(&self, name: &str) -> Result<QueryHandle, String> {
+        Err(format!("Database feature not enabled for connection '{}'", name))
+    }
+
+    /// Execute a query on a named connection
+    pub async fn execute_query(&self, name: &str, query: &str) -> Result<QueryResult, String> {
+        self.handle(name)?
+            .execute(query.to_string(), QueryOptions::default())
+            .await
     }
 
     /// Test a database connection
     #[cfg(feature = "database")]
     pub async fn test_connection(&self, name: &str) -> Result<bool, String> {
-        let conn = self.connections.get(name)
+        let pool = self
+            .pools
+            .get(name)
             .ok_or_else(|| format!("Connection '{}' not found", name))?;
 
-        match conn.db_type {
-            DatabaseType::PostgreSQL => {
-                if let Some(ref config) = conn.postgres_config {
-                    let (client, connection) = postgres::connect(&config.connection_string, postgres::NoTls)
-                        .await
-                        .map_err(|e| format!("Connection failed: {}", e))?;
-
-                    tokio::spawn(async move {
-                        if let Err(e) = connection.await {
-                            tracing::error!("PostgreSQL connection error: {}", e);
-                        }
-                    });
-
-                    // Test query
-                    let _ = client.simple_query("SELECT 1")
-                        .await
-                        .map_err(|e| format!("Test query failed: {}", e))?;
-
-                    Ok(true)
-                } else {
-                    Err("PostgreSQL configuration not found".to_string())
-                }
-            }
-            DatabaseType::MySQL => {
-                if let Some(ref config) = conn.mysql_config {
-                    let opts = mysql_async::Opts::from_url(&config.connection_string)
-                        .map_err(|e| format!("Invalid MySQL URL: {}", e))?;
-
-                    let mut conn = mysql::Conn::new(opts)
-                        .await
-                        .map_err(|e| format!("Connection failed: {}", e))?;
-
-                    // Test query - specify result type
-                    let _result: Vec<(i32,)> = conn.query("SELECT 1")
-                        .await
-                        .map_err(|e| format!("Test query failed: {}", e))?;
-
-                    conn.disconnect().await
-                        .map_err(|e| format!("Disconnect error: {}", e))?;
-
-                    Ok(true)
-                } else {
-                    Err("MySQL configuration not found".to_string())
-                }
-            }
-            DatabaseType::SQLite => {
-                Err("SQLite connection test not implemented".to_string())
-            }
-        }
+        let result = match pool {
+            DatabasePool::Postgres(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+            DatabasePool::MySql(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+            DatabasePool::Sqlite(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+        };
+        result.map_err(|e| format!("Connection failed: {}", e))?;
+        Ok(true)
     }
 
     /// Test a database connection (non-database feature)
@@ -359,6 +1103,30 @@ impl Default for DatabasePoolManager {
 pub struct SchemaInfo {
     pub table_name: String,
     pub columns: Vec<ColumnInfo>,
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+    #[serde(default)]
+    pub indexes: Vec<IndexInfo>,
+}
+
+impl SchemaInfo {
+    fn mark_primary_key(&mut self, column: &str) {
+        if let Some(info) = self.columns.iter_mut().find(|c| c.name == column) {
+            info.is_primary_key = true;
+        }
+    }
+
+    /// Append a column to an index, adding the index on its first column
+    fn push_index_column(&mut self, name: String, unique: bool, column: String) {
+        match self.indexes.iter_mut().find(|index| index.name == name) {
+            Some(index) => index.columns.push(column),
+            None => self.indexes.push(IndexInfo {
+                name,
+                columns: vec![column],
+                unique,
+            }),
+        }
+    }
 }
 
 /// Column information
@@ -370,6 +1138,87 @@ pub struct ColumnInfo {
     pub is_primary_key: bool,
 }
 
+/// Foreign key column and the column it references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyInfo {
+    pub column: String,
+    pub referenced_table: String,
+    /// `None` when the key references the table's primary key implicitly (SQLite)
+    pub referenced_column: Option<String>,
+}
+
+/// Index information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+/// Tables collected from introspection queries, ordered by name
+#[derive(Default)]
+struct SchemaBuilder {
+    tables: BTreeMap<String, SchemaInfo>,
+}
+
+impl SchemaBuilder {
+    fn table(&mut self, name: String) -> &mut SchemaInfo {
+        self.tables.entry(name.clone()).or_insert_with(|| SchemaInfo {
+            table_name: name,
+            columns: Vec::new(),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+        })
+    }
+
+    fn finish(self) -> Vec<SchemaInfo> {
+        self.tables.into_values().collect()
+    }
+}
+
+/// Compact text description of tables for a model prompt
+///
+/// One line per table, e.g.
+/// `orders(id integer PK, user_id integer NOT NULL -> users.id)`, followed by
+/// indented index lines.
+pub fn describe_schema(tables: &[SchemaInfo]) -> String {
+    let mut out = String::new();
+
+    for table in tables {
+        let columns: Vec<String> = table
+            .columns
+            .iter()
+            .map(|column| {
+                let mut line = format!("{} {}", column.name, column.data_type);
+                if column.is_primary_key {
+                    line.push_str(" PK");
+                } else if !column.nullable {
+                    line.push_str(" NOT NULL");
+                }
+                for fk in table.foreign_keys.iter().filter(|fk| fk.column == column.name) {
+                    line.push_str(&format!(" -> {}", fk.referenced_table));
+                    if let Some(referenced) = &fk.referenced_column {
+                        line.push_str(&format!(".{}", referenced));
+                    }
+                }
+                line
+            })
+            .collect();
+        out.push_str(&format!("{}({})\n", table.table_name, columns.join(", ")));
+
+        for index in &table.indexes {
+            out.push_str(&format!(
+                "  index {}{} ({})\n",
+                index.name,
+                if index.unique { " UNIQUE" } else { "" },
+                index.columns.join(", ")
+            ));
+        }
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,6 +1234,8 @@ mod tests {
             row_count: 2,
             execution_time_ms: 10,
             error: None,
+            rows_affected: 0,
+            truncated: false,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -392,6 +1243,61 @@ mod tests {
         assert!(json.contains("name"));
     }
 
+    #[test]
+    fn test_bytes_to_hex() {
+        assert_eq!(bytes_to_hex(vec![0x0a, 0xff]), serde_json::json!("\\x0aff"));
+    }
+
+    #[test]
+    fn test_describe_schema() {
+        let tables = vec![SchemaInfo {
+            table_name: "orders".to_string(),
+            columns: vec![
+                ColumnInfo { name: "id".to_string(), data_type: "integer".to_string(), nullable: false, is_primary_key: true },
+                ColumnInfo { name: "user_id".to_string(), data_type: "integer".to_string(), nullable: false, is_primary_key: false },
+                ColumnInfo { name: "note".to_string(), data_type: "text".to_string(), nullable: true, is_primary_key: false },
+            ],
+            foreign_keys: vec![ForeignKeyInfo {
+                column: "user_id".to_string(),
+                referenced_table: "users".to_string(),
+                referenced_column: Some("id".to_string()),
+            }],
+            indexes: vec![IndexInfo { name: "orders_user".to_string(), columns: vec!["user_id".to_string()], unique: false }],
+        }];
+
+        assert_eq!(
+            describe_schema(&tables),
+            "orders(id integer PK, user_id integer NOT NULL -> users.id, note text)\n  index orders_user (user_id)\n"
+        );
+    }
+
+    #[test]
+    fn test_is_schema_change() {
+        assert!(is_schema_change("  create table t (id int)"));
+        assert!(is_schema_change("ALTER TABLE t ADD c int"));
+        assert!(!is_schema_change("SELECT * FROM created"));
+        assert!(!is_schema_change("UPDATE t SET c = 1"));
+    }
+
+    #[test]
+    fn test_schema_cache_expiry() {
+        let cache = SchemaCache::new(Duration::from_secs(60));
+        cache.insert("local", Vec::new());
+        assert!(cache.get("local").is_some());
+        cache.invalidate("local");
+        assert!(cache.get("local").is_none());
+
+        let expired = SchemaCache::new(Duration::ZERO);
+        expired.insert("local", Vec::new());
+        assert!(expired.get("local").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unknown_query() {
+        let manager = DatabasePoolManager::new();
+        assert!(!manager.running().cancel("missing").await.unwrap());
+    }
+
     #[tokio::test]
     #[cfg(feature = "database")]
     async fn test_add_postgres_pool() {
@@ -415,4 +1321,115 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    #[cfg(feature = "database")]
+    async fn test_sqlite_query_execution() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL, data BLOB);
+                 INSERT INTO items (name, price, data) VALUES ('a', 1.5, x'0aff'), ('b', NULL, NULL), ('c', 3.0, NULL);",
+            )
+            .unwrap();
+
+        let mut manager = DatabasePoolManager::new();
+        let config = DatabaseConnectionConfig {
+            name: "local".to_string(),
+            db_type: DatabaseType::SQLite,
+            host: String::new(),
+            port: 0,
+            database: path.to_string_lossy().to_string(),
+            username: String::new(),
+            encrypted_password: None,
+            ssl: false,
+            read_only: false,
+        };
+        manager.connect(&config, None).await.unwrap();
+
+        let options = QueryOptions {
+            params: vec![serde_json::json!("c")],
+            max_rows: Some(2),
+            ..Default::default()
+        };
+        let result = manager
+            .handle("local")
+            .unwrap()
+            .execute("SELECT id, name, price, data FROM items WHERE name != ? ORDER BY id".to_string(), options)
+            .await
+            .unwrap();
+        assert_eq!(result.columns, vec!["id", "name", "price", "data"]);
+        assert_eq!(result.rows[0], vec![serde_json::json!(1), serde_json::json!("a"), serde_json::json!(1.5), serde_json::json!("\\x0aff")]);
+        assert_eq!(result.rows[1][2], serde_json::Value::Null);
+        assert!(!result.truncated);
+
+        let limited = manager
+            .handle("local")
+            .unwrap()
+            .execute("SELECT id FROM items".to_string(), QueryOptions { max_rows: Some(2), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(limited.row_count, 2);
+        assert!(limited.truncated);
+
+        let update = manager.execute_query("local", "UPDATE items SET price = 0").await.unwrap();
+        assert_eq!(update.rows_affected, 3);
+
+        // Reopened read-only, writes are refused before reaching SQLite
+        manager.connect(&DatabaseConnectionConfig { read_only: true, ..config }, None).await.unwrap();
+        let rejected = manager.execute_query("local", "DELETE FROM items").await;
+        assert!(rejected.unwrap_err().starts_with("Rejected on read-only connection"));
+        assert!(manager.execute_query("local", "SELECT count(*) FROM items").await.is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "database")]
+    async fn test_sqlite_introspection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL UNIQUE);
+                 CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL REFERENCES users(id), total REAL);
+                 CREATE INDEX orders_user ON orders (user_id, total);",
+            )
+            .unwrap();
+
+        let mut manager = DatabasePoolManager::new();
+        let config = DatabaseConnectionConfig {
+            name: "local".to_string(),
+            db_type: DatabaseType::SQLite,
+            host: String::new(),
+            port: 0,
+            database: path.to_string_lossy().to_string(),
+            username: String::new(),
+            encrypted_password: None,
+            ssl: false,
+            read_only: false,
+        };
+        manager.connect(&config, None).await.unwrap();
+
+        let handle = manager.handle("local").unwrap();
+        let tables = handle.schema(false).await.unwrap();
+        let names: Vec<&str> = tables.iter().map(|t| t.table_name.as_str()).collect();
+        assert_eq!(names, vec!["orders", "users"]);
+
+        let orders = &tables[0];
+        assert!(orders.columns[0].is_primary_key);
+        assert!(!orders.columns[1].nullable);
+        assert_eq!(orders.foreign_keys[0].referenced_table, "users");
+        assert_eq!(orders.foreign_keys[0].referenced_column.as_deref(), Some("id"));
+        assert_eq!(orders.indexes[0].columns, vec!["user_id", "total"]);
+        assert!(tables[1].indexes.iter().any(|index| index.unique));
+
+        // DDL through the handle drops the cached schema
+        manager
+            .execute_query("local", "CREATE TABLE notes (body TEXT)")
+            .await
+            .unwrap();
+        assert_eq!(handle.schema(false).await.unwrap().len(), 3);
+    }
 }
@@ -0,0 +1,308 @@
+//! Email Integration Module
+//!
+//! Sends job and workflow output over SMTP, as Markdown text or as HTML
+//! rendered from it. The SMTP settings, password included, are stored as one
+//! keychain entry.
+
+use crate::error::AppError;
+use crate::security::{CredentialManager, SecurityError};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use pulldown_cmark::{Event, Options, Parser};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Keychain entry holding the SMTP settings
+pub const CREDENTIAL_KEY: &str = "email.smtp";
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How the connection to the SMTP server is secured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Upgrade a plain connection with STARTTLS (usually port 587)
+    #[default]
+    StartTls,
+    /// TLS from the start (usually port 465)
+    Tls,
+    /// No encryption; only for local relays
+    None,
+}
+
+/// SMTP server settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender address, e.g. `Assistant <reports@example.com>`
+    pub from: String,
+}
+
+impl SmtpConfig {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.host.trim().is_empty() {
+            return Err(AppError::invalid_input("SMTP host is required"));
+        }
+        if self.port == 0 {
+            return Err(AppError::invalid_input("SMTP port is required"));
+        }
+        parse_mailbox(&self.from)?;
+        Ok(())
+    }
+
+    fn transport(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>, AppError> {
+        let host = self.host.trim();
+        let builder = match self.security {
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)),
+        }
+        .map_err(|e| AppError::invalid_input(format!("Invalid SMTP host {}: {}", host, e)))?;
+
+        let mut builder = builder.port(self.port).timeout(Some(SMTP_TIMEOUT));
+        if let Some(username) = self.username.as_deref().filter(|u| !u.is_empty()) {
+            let password = self.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.to_string(), password));
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Body format of an email
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailFormat {
+    /// The Markdown source as plain text
+    #[default]
+    Markdown,
+    /// HTML rendered from the Markdown, with the source as the plain text part
+    Html,
+}
+
+/// An email to send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailMessage {
+    pub to: Vec<String>,
+    pub subject: String,
+    /// Markdown text
+    pub body: String,
+    #[serde(default)]
+    pub format: EmailFormat,
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, AppError> {
+    address
+        .trim()
+        .parse()
+        .map_err(|e| AppError::invalid_input(format!("Invalid email address '{}': {}", address, e)))
+}
+
+/// Render Markdown as an HTML document
+///
+/// Raw HTML in the source is escaped, so model output cannot inject markup.
+pub fn render_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    });
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, events);
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"></head>\n<body style=\"font-family: sans-serif; line-height: 1.5\">\n{}</body></html>\n",
+        body
+    )
+}
+
+/// Build the message for `email` sent from the configured sender
+pub fn build_message(config: &SmtpConfig, email: &EmailMessage) -> Result<Message, AppError> {
+    if email.to.is_empty() {
+        return Err(AppError::invalid_input("At least one recipient is required"));
+    }
+    let mut builder = Message::builder().from(parse_mailbox(&config.from)?).subject(email.subject.trim());
+    for recipient in &email.to {
+        builder = builder.to(parse_mailbox(recipient)?);
+    }
+
+    let message = match email.format {
+        EmailFormat::Markdown => builder.singlepart(SinglePart::plain(email.body.clone())),
+        EmailFormat::Html => builder.multipart(MultiPart::alternative_plain_html(
+            email.body.clone(),
+            render_html(&email.body),
+        )),
+    };
+    message.map_err(|e| AppError::invalid_input(format!("Failed to build email: {}", e)))
+}
+
+pub async fn send(config: &SmtpConfig, email: &EmailMessage) -> Result<(), AppError> {
+    let message = build_message(config, email)?;
+    config
+        .transport()?
+        .send(message)
+        .await
+        .map_err(|e| AppError::network(format!("Failed to send email: {}", e)))?;
+    Ok(())
+}
+
+/// Stored SMTP settings, if any
+pub fn load_config(credentials: &CredentialManager) -> Result<Option<SmtpConfig>, String> {
+    match credentials.get_password(CREDENTIAL_KEY) {
+        Ok(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid email settings: {}", e)),
+        Err(SecurityError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Store SMTP settings; a missing password keeps the stored one
+pub fn save_config(credentials: &CredentialManager, mut config: SmtpConfig) -> Result<(), AppError> {
+    config.validate()?;
+    if config.password.as_deref().is_none_or(str::is_empty) {
+        config.password = load_config(credentials)?.and_then(|stored| stored.password);
+    }
+    let json = serde_json::to_string(&config)?;
+    credentials.set_password(CREDENTIAL_KEY, &json)?;
+    Ok(())
+}
+
+/// Send with the stored settings, e.g. from a scheduled job or workflow
+pub async fn send_configured(email: &EmailMessage) -> Result<(), String> {
+    let credentials = CredentialManager::default().map_err(|e| e.to_string())?;
+    let config = load_config(&credentials)?.ok_or_else(|| "Email is not configured".to_string())?;
+    Ok(send(&config, email).await?)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Stored SMTP settings, without the password
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_email_settings(credentials: tauri::State<'_, Mutex<CredentialManager>>) -> Result<Option<SmtpConfig>, AppError> {
+    let credentials = credentials.lock()?;
+    Ok(load_config(&credentials)?.map(|config| SmtpConfig { password: None, ..config }))
+}
+
+/// Save SMTP settings to the keychain; leave the password empty to keep it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn save_email_settings(
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: SmtpConfig,
+) -> Result<(), AppError> {
+    let credentials = credentials.lock()?;
+    save_config(&credentials, config)
+}
+
+/// Check that the SMTP server accepts the settings, and send a test email
+/// to `to` when given
+///
+/// Tests the stored settings unless `config` is passed.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn test_email_settings(
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    config: Option<SmtpConfig>,
+    to: Option<String>,
+) -> Result<String, AppError> {
+    let config = {
+        let credentials = credentials.lock()?;
+        let stored = load_config(&credentials)?;
+        match config {
+            Some(mut config) => {
+                if config.password.as_deref().is_none_or(str::is_empty) {
+                    config.password = stored.and_then(|stored| stored.password);
+                }
+                config
+            }
+            None => stored.ok_or_else(|| AppError::not_found("Email is not configured"))?,
+        }
+    };
+    config.validate()?;
+
+    match to.filter(|to| !to.trim().is_empty()) {
+        Some(to) => {
+            let email = EmailMessage {
+                to: vec![to.clone()],
+                subject: "AI Assistant test email".to_string(),
+                body: "Your email settings work. Scheduled reports will be delivered from this address.".to_string(),
+                format: EmailFormat::Markdown,
+            };
+            send(&config, &email).await?;
+            Ok(format!("Test email sent to {}", to))
+        }
+        None => {
+            let connected = config
+                .transport()?
+                .test_connection()
+                .await
+                .map_err(|e| AppError::network(format!("Failed to connect to {}: {}", config.host, e)))?;
+            if !connected {
+                return Err(AppError::network(format!("SMTP server {} did not accept the connection", config.host)));
+            }
+            Ok(format!("Connected to {}:{}", config.host, config.port))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SmtpConfig {
+        SmtpConfig {
+            host: "smtp.example.com".to_string(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            username: None,
+            password: None,
+            from: "Assistant <reports@example.com>".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_html_escapes_raw_html() {
+        let html = render_html("# Report\n\n- **done**\n\n<script>alert(1)</script>");
+        assert!(html.contains("<h1>Report</h1>"));
+        assert!(html.contains("<strong>done</strong>"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_build_message() {
+        let email = EmailMessage {
+            to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            subject: "Daily report".to_string(),
+            body: "**All good**".to_string(),
+            format: EmailFormat::Html,
+        };
+        let message = String::from_utf8(build_message(&config(), &email).unwrap().formatted()).unwrap();
+        assert!(message.contains("Subject: Daily report"));
+        assert!(message.contains("multipart/alternative"));
+        assert!(message.contains("<strong>All good</strong>"));
+
+        let err = build_message(&config(), &EmailMessage { to: vec![], ..email.clone() }).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidInput);
+        assert!(build_message(&config(), &EmailMessage { to: vec!["not an address".to_string()], ..email }).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(config().validate().is_ok());
+        assert!(SmtpConfig { host: " ".to_string(), ..config() }.validate().is_err());
+        let err = SmtpConfig { from: "nobody".to_string(), ..config() }.validate().unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidInput);
+    }
+}
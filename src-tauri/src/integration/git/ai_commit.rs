@@ -0,0 +1,408 @@
+//! AI-Assisted Commits
+//!
+//! `git_ai_commit` stages the selected files, asks the agent runtime for a
+//! commit message written from the staged diff, and emits the proposal to the
+//! UI. The command then waits until the user answers with
+//! `git_ai_commit_respond` and commits exactly the tree that was reviewed.
+//! Nothing outside the staged index is committed, so unrelated work in the
+//! working tree is left alone.
+
+#![allow(dead_code)]
+
+use super::operations::GitFileDiff;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Event carrying a commit message proposal
+pub const PROPOSAL_EVENT: &str = "git://ai-commit-proposal";
+
+/// How long to wait for the user when the caller does not say
+const DEFAULT_CONFIRM_TIMEOUT_SECS: u64 = 300;
+
+/// Diff characters included in the prompt
+const MAX_PROMPT_DIFF_CHARS: usize = 12_000;
+
+/// Options for `git_ai_commit`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AiCommitOptions {
+    /// Paths relative to the repository root to stage; when empty, whatever is
+    /// already staged is committed
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Extra guidance for the message, e.g. a commit convention to follow
+    pub instructions: Option<String>,
+    /// Provider to generate the message with (default: the active provider)
+    pub provider: Option<String>,
+    pub confirm_timeout_secs: Option<u64>,
+}
+
+/// File summary shown alongside a proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiCommitFile {
+    pub path: String,
+    pub status: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Proposal emitted on `PROPOSAL_EVENT`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiCommitProposal {
+    pub request_id: String,
+    pub path: String,
+    pub message: String,
+    pub files: Vec<AiCommitFile>,
+}
+
+/// The user's answer to a proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiCommitDecision {
+    pub approved: bool,
+    /// Edited message replacing the proposal
+    pub message: Option<String>,
+}
+
+/// Outcome of `git_ai_commit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiCommitResult {
+    pub committed: bool,
+    pub commit_id: Option<String>,
+    pub message: String,
+}
+
+/// Proposals waiting for the user, by request id
+pub struct PendingAiCommits {
+    pending: Mutex<HashMap<String, tokio::sync::oneshot::Sender<AiCommitDecision>>>,
+}
+
+impl PendingAiCommits {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn register(&self, request_id: &str) -> Result<tokio::sync::oneshot::Receiver<AiCommitDecision>, String> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_id.to_string(), sender);
+        Ok(receiver)
+    }
+
+    fn remove(&self, request_id: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(request_id);
+        }
+    }
+
+    /// Deliver the user's decision, returning false if nothing is waiting
+    pub fn respond(&self, request_id: &str, decision: AiCommitDecision) -> Result<bool, String> {
+        let sender = self.pending.lock().map_err(|e| e.to_string())?.remove(request_id);
+        Ok(sender.is_some_and(|sender| sender.send(decision).is_ok()))
+    }
+}
+
+impl Default for PendingAiCommits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render file diffs as unified diff text, cut at `max_chars`
+pub fn diff_to_text(files: &[GitFileDiff], max_chars: usize) -> String {
+    let mut out = String::new();
+
+    for file in files {
+        let old_path = file.old_path.as_deref().unwrap_or("/dev/null");
+        let new_path = file.new_path.as_deref().unwrap_or("/dev/null");
+        out.push_str(&format!("--- a/{}\n+++ b/{}\n", old_path, new_path));
+        if file.binary {
+            out.push_str("Binary file changed\n");
+        }
+        for hunk in &file.hunks {
+            out.push_str(&hunk.header);
+            out.push('\n');
+            for line in &hunk.lines {
+                out.push_str(&line.origin);
+                out.push_str(&line.content);
+                out.push('\n');
+            }
+        }
+
+        if out.len() > max_chars {
+            let mut end = max_chars;
+            while !out.is_char_boundary(end) {
+                end -= 1;
+            }
+            out.truncate(end);
+            out.push_str("\n[diff truncated]\n");
+            break;
+        }
+    }
+
+    out
+}
+
+fn commit_prompt(diff: &str, instructions: Option<&str>) -> String {
+    let mut prompt = String::from(
+        "Write a git commit message for the staged changes below.\n\
+         Use a concise summary line of at most 72 characters in the imperative mood, \
+         then a blank line and a short body only if the change needs explaining.\n\
+         Reply with the commit message only.\n",
+    );
+    if let Some(instructions) = instructions.filter(|i| !i.trim().is_empty()) {
+        prompt.push_str(&format!("\nAdditional instructions: {}\n", instructions.trim()));
+    }
+    prompt.push_str(&format!("\n```diff\n{}```\n", diff));
+    prompt
+}
+
+/// Strip wrapping a model may add around the message
+fn clean_message(raw: &str) -> String {
+    let mut message = raw.trim();
+    if let Some(inner) = message.strip_prefix("```") {
+        // Drop an optional language tag on the opening fence
+        let inner = inner.split_once('\n').map(|(_, rest)| rest).unwrap_or(inner);
+        message = inner.trim_end().strip_suffix("```").unwrap_or(inner).trim();
+    }
+    message.trim_matches('"').trim().to_string()
+}
+
+/// Ask the agent runtime for a message describing `diff`
+fn generate_message(
+    sidecar: &std::sync::Mutex<crate::sidecar::SidecarState>,
+    diff: &str,
+    options: &AiCommitOptions,
+) -> Result<String, String> {
+    let result = sidecar
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .call(
+            "execute_prompt",
+            serde_json::json!({
+                "prompt": commit_prompt(diff, options.instructions.as_deref()),
+                "provider": options.provider,
+            }),
+        )?;
+
+    if result.get("success").and_then(|s| s.as_bool()) == Some(false) {
+        let error = result.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error");
+        return Err(format!("Failed to generate commit message: {}", error));
+    }
+
+    let message = clean_message(result.get("result").and_then(|r| r.as_str()).unwrap_or_default());
+    if message.is_empty() {
+        return Err("The agent returned an empty commit message".to_string());
+    }
+    Ok(message)
+}
+
+/// Stage files, generate a commit message, confirm it with the user, and commit
+///
+/// Emits an `AiCommitProposal` on `git://ai-commit-proposal` and waits for
+/// `git_ai_commit_respond`. Declined or timed-out proposals leave the files
+/// staged.
+#[cfg(feature = "git")]
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn git_ai_commit(
+    app: tauri::AppHandle,
+    sidecar: tauri::State<'_, std::sync::Mutex<crate::sidecar::SidecarState>>,
+    pending: tauri::State<'_, PendingAiCommits>,
+    path: String,
+    options: AiCommitOptions,
+) -> std::result::Result<AiCommitResult, AppError> {
+    use super::operations::GitOperations;
+    use crate::tools::audit::ActionStatus;
+    use tauri::Emitter;
+
+    let started_at = chrono::Utc::now();
+    let ops = GitOperations::open(&path)?;
+    if !options.files.is_empty() {
+        ops.stage_paths(&options.files)?;
+    }
+
+    let files = ops.diff(true)?;
+    if files.is_empty() {
+        return Err(AppError::invalid_input("Nothing is staged to commit"));
+    }
+    let tree = ops.staged_tree()?;
+    let message = generate_message(&sidecar, &diff_to_text(&files, MAX_PROMPT_DIFF_CHARS), &options)?;
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let receiver = pending.register(&request_id)?;
+    let proposal = AiCommitProposal {
+        request_id: request_id.clone(),
+        path: path.clone(),
+        message: message.clone(),
+        files: files
+            .iter()
+            .map(|file| AiCommitFile {
+                path: file.new_path.clone().or_else(|| file.old_path.clone()).unwrap_or_default(),
+                status: file.status.clone(),
+                additions: file.additions,
+                deletions: file.deletions,
+            })
+            .collect(),
+    };
+    if let Err(e) = app.emit(PROPOSAL_EVENT, proposal) {
+        pending.remove(&request_id);
+        return Err(format!("Failed to show commit proposal: {}", e).into());
+    }
+
+    let timeout = std::time::Duration::from_secs(
+        options.confirm_timeout_secs.unwrap_or(DEFAULT_CONFIRM_TIMEOUT_SECS),
+    );
+    let decision = match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(decision)) => decision,
+        Ok(Err(_)) => return Err(AppError::permission_denied("Commit proposal was discarded")),
+        Err(_) => {
+            pending.remove(&request_id);
+            let error = "Timed out waiting for commit confirmation".to_string();
+            record_commit(&app, &path, &message, ActionStatus::Denied, Some(error.as_str()), started_at);
+            return Err(AppError::permission_denied(error));
+        }
+    };
+
+    let message = decision
+        .message
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or(message);
+    if !decision.approved {
+        record_commit(&app, &path, &message, ActionStatus::Denied, None, started_at);
+        return Ok(AiCommitResult {
+            committed: false,
+            commit_id: None,
+            message,
+        });
+    }
+
+    let commit_id = match ops.commit_staged(&message, &tree) {
+        Ok(commit_id) => {
+            record_commit(&app, &path, &message, ActionStatus::Success, None, started_at);
+            commit_id
+        }
+        Err(e) => {
+            record_commit(&app, &path, &message, ActionStatus::Error, Some(e.as_str()), started_at);
+            return Err(e.into());
+        }
+    };
+    tracing::info!("Committed {} in {}", commit_id, path);
+    Ok(AiCommitResult {
+        committed: true,
+        commit_id: Some(commit_id),
+        message,
+    })
+}
+
+/// Record an AI commit in the agent action audit trail
+#[cfg(feature = "git")]
+fn record_commit(
+    app: &tauri::AppHandle,
+    path: &str,
+    message: &str,
+    status: crate::tools::audit::ActionStatus,
+    error: Option<&str>,
+    started_at: chrono::DateTime<chrono::Utc>,
+) {
+    use tauri::Manager;
+
+    let db = app.state::<crate::db::DbState>();
+    let Ok(conn) = db.conn.lock() else {
+        return;
+    };
+    crate::tools::audit::record_quietly(
+        &conn,
+        &crate::tools::audit::NewAction {
+            kind: crate::tools::audit::ActionKind::GitOperation,
+            name: "git_ai_commit",
+            conversation_id: None,
+            params: &serde_json::json!({ "path": path, "message": message }),
+            status,
+            error,
+            started_at,
+            finished_at: chrono::Utc::now(),
+        },
+    );
+}
+
+/// Stage files, generate a commit message and commit (non-git feature)
+#[cfg(not(feature = "git"))]
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn git_ai_commit(
+    path: String,
+    _options: AiCommitOptions,
+) -> std::result::Result<AiCommitResult, AppError> {
+    Err(AppError::invalid_input(format!("Git feature not enabled for {}", path)))
+}
+
+/// Answer a commit proposal
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn git_ai_commit_respond(
+    pending: tauri::State<'_, PendingAiCommits>,
+    request_id: String,
+    approved: bool,
+    message: Option<String>,
+) -> std::result::Result<bool, AppError> {
+    Ok(pending.respond(&request_id, AiCommitDecision { approved, message })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::git::operations::{GitDiffHunk, GitDiffLine};
+
+    #[test]
+    fn test_clean_message() {
+        assert_eq!(clean_message("```\nFix typo\n```"), "Fix typo");
+        assert_eq!(clean_message("```text\nAdd parser\n\nDetails\n```\n"), "Add parser\n\nDetails");
+        assert_eq!(clean_message("\"Update docs\""), "Update docs");
+    }
+
+    #[test]
+    fn test_diff_to_text() {
+        let files = vec![GitFileDiff {
+            old_path: Some("a.txt".to_string()),
+            new_path: Some("a.txt".to_string()),
+            status: "modified".to_string(),
+            binary: false,
+            additions: 1,
+            deletions: 1,
+            hunks: vec![GitDiffHunk {
+                header: "@@ -1,2 +1,2 @@".to_string(),
+                old_start: 1,
+                old_lines: 2,
+                new_start: 1,
+                new_lines: 2,
+                lines: vec![
+                    GitDiffLine { origin: " ".to_string(), content: "one".to_string(), old_lineno: Some(1), new_lineno: Some(1) },
+                    GitDiffLine { origin: "-".to_string(), content: "two".to_string(), old_lineno: Some(2), new_lineno: None },
+                    GitDiffLine { origin: "+".to_string(), content: "three".to_string(), old_lineno: None, new_lineno: Some(2) },
+                ],
+            }],
+            truncated: false,
+        }];
+
+        assert_eq!(
+            diff_to_text(&files, 1000),
+            "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n"
+        );
+        assert!(diff_to_text(&files, 10).ends_with("[diff truncated]\n"));
+    }
+
+    #[tokio::test]
+    async fn test_respond_delivers_decision() {
+        let pending = PendingAiCommits::new();
+        let receiver = pending.register("r1").unwrap();
+        assert!(pending.respond("r1", AiCommitDecision { approved: true, message: None }).unwrap());
+        assert!(receiver.await.unwrap().approved);
+        assert!(!pending.respond("r1", AiCommitDecision { approved: false, message: None }).unwrap());
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Public API for Git operations using git2-rs.
 
+pub mod ai_commit;
 pub mod operations;
 
+use crate::error::AppError;
+#[cfg(feature = "git")]
+use operations::GitOperations;
 use serde::{Deserialize, Serialize};
 
 /// Git repository configuration
@@ -47,7 +51,8 @@ impl GitRepositoryConfig {
 
 /// Validate git repository (legacy command)
 #[tauri::command]
-pub fn validate_git_repository(path: String) -> Result<GitStatus, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn validate_git_repository(path: String) -> Result<GitStatus, AppError> {
     let config = GitRepositoryConfig {
         name: "default".to_string(),
         path: path.clone(),
@@ -85,17 +90,19 @@ pub fn validate_git_repository(path: String) -> Result<GitStatus, String> {
 
 /// Get git status (legacy command)
 #[tauri::command]
-pub fn get_git_status(path: String) -> Result<GitStatus, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_git_status(path: String) -> Result<GitStatus, AppError> {
     validate_git_repository(path)
 }
 
 /// Get current git commit (legacy command)
 #[tauri::command]
-pub fn get_git_current_commit(path: String) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_git_current_commit(path: String) -> Result<String, AppError> {
     #[cfg(feature = "git")]
     {
         let repo = git2::Repository::open(std::path::PathBuf::from(&path))
-            .map_err(|e| format!("Failed to open repo: {}", e))?;
+            .map_err(|e| AppError::not_found(format!("Failed to open repo: {}", e)))?;
         let head = repo.head()
             .map_err(|e| format!("Failed to get HEAD: {}", e))?;
         let commit = head.peel_to_commit()
@@ -4,6 +4,7 @@
 
 #![allow(dead_code)]
 
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 
 /// Get list of conflicted files
@@ -147,6 +148,106 @@ pub struct GitExtendedStatus {
     pub conflicted: usize,
 }
 
+/// Branch information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitBranch {
+    pub name: String,
+    pub is_head: bool,
+    pub is_remote: bool,
+    /// Upstream branch, e.g. `origin/main`
+    pub upstream: Option<String>,
+    /// Id of the commit the branch points to
+    pub commit: Option<String>,
+}
+
+/// Commit information for the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitCommitInfo {
+    pub id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub message: String,
+    pub author_name: String,
+    pub author_email: String,
+    /// Commit time in RFC 3339
+    pub timestamp: String,
+    pub parents: Vec<String>,
+}
+
+/// Changes to one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitFileDiff {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    /// added, deleted, modified, renamed, copied, typechange or untracked
+    pub status: String,
+    pub binary: bool,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<GitDiffHunk>,
+    /// Lines beyond the per-file limit were left out
+    pub truncated: bool,
+}
+
+/// A contiguous block of changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitDiffHunk {
+    /// `@@ -1,4 +1,5 @@` header, including any function context
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<GitDiffLine>,
+}
+
+/// One line of a hunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitDiffLine {
+    /// `+` added, `-` removed, ` ` context
+    pub origin: String,
+    pub content: String,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+}
+
+/// Commits returned by the log when the caller does not set a limit
+pub const DEFAULT_LOG_LIMIT: usize = 50;
+
+/// Diff lines kept per file
+const MAX_DIFF_LINES_PER_FILE: usize = 2000;
+
+#[cfg(feature = "git")]
+fn delta_status(status: git2::Delta) -> &'static str {
+    match status {
+        git2::Delta::Added => "added",
+        git2::Delta::Deleted => "deleted",
+        git2::Delta::Renamed => "renamed",
+        git2::Delta::Copied => "copied",
+        git2::Delta::Typechange => "typechange",
+        git2::Delta::Untracked => "untracked",
+        _ => "modified",
+    }
+}
+
+#[cfg(feature = "git")]
+fn commit_info(commit: &git2::Commit) -> GitCommitInfo {
+    let author = commit.author();
+    let id = commit.id().to_string();
+    GitCommitInfo {
+        short_id: id.chars().take(7).collect(),
+        id,
+        summary: commit.summary().unwrap_or_default().to_string(),
+        message: commit.message().unwrap_or_default().to_string(),
+        author_name: author.name().unwrap_or_default().to_string(),
+        author_email: author.email().unwrap_or_default().to_string(),
+        timestamp: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default(),
+        parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+    }
+}
+
 /// Git operations manager
 pub struct GitOperations {
     repo_path: std::path::PathBuf,
@@ -460,17 +561,350 @@ impl GitOperations {
             }),
         }
     }
+
+    #[cfg(feature = "git")]
+    fn repo(&self) -> Result<git2::Repository, String> {
+        git2::Repository::open(&self.repo_path)
+            .map_err(|e| format!("Failed to open repo: {}", e))
+    }
+
+    /// List local and remote branches
+    #[cfg(feature = "git")]
+    pub fn list_branches(&self) -> Result<Vec<GitBranch>, String> {
+        let repo = self.repo()?;
+        let branches = repo.branches(None)
+            .map_err(|e| format!("Failed to list branches: {}", e))?;
+
+        let mut result = Vec::new();
+        for entry in branches {
+            let (branch, branch_type) = entry
+                .map_err(|e| format!("Failed to read branch: {}", e))?;
+            let Some(name) = branch.name().ok().flatten().map(str::to_string) else {
+                continue;
+            };
+            // Skip symbolic remote HEADs such as origin/HEAD
+            if branch_type == git2::BranchType::Remote && name.ends_with("/HEAD") {
+                continue;
+            }
+
+            let upstream = branch
+                .upstream()
+                .ok()
+                .and_then(|u| u.name().ok().flatten().map(str::to_string));
+            result.push(GitBranch {
+                is_head: branch.is_head(),
+                is_remote: branch_type == git2::BranchType::Remote,
+                upstream,
+                commit: branch.get().target().map(|oid| oid.to_string()),
+                name,
+            });
+        }
+
+        result.sort_by(|a, b| a.is_remote.cmp(&b.is_remote).then_with(|| a.name.cmp(&b.name)));
+        Ok(result)
+    }
+
+    /// Check out a branch
+    ///
+    /// A name matching only a remote branch (`origin/feature` or `feature`)
+    /// creates a local tracking branch. Uncommitted changes that would be
+    /// overwritten make the checkout fail rather than being discarded.
+    #[cfg(feature = "git")]
+    pub fn checkout_branch(&self, name: &str) -> Result<GitOperationResult, String> {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let repo = self.repo()?;
+
+        let branch = match repo.find_branch(name, git2::BranchType::Local) {
+            Ok(branch) => branch,
+            Err(_) => {
+                let remote_name = if name.contains('/') {
+                    name.to_string()
+                } else {
+                    format!("origin/{}", name)
+                };
+                let remote = repo
+                    .find_branch(&remote_name, git2::BranchType::Remote)
+                    .map_err(|_| format!("Branch '{}' not found", name))?;
+                let local_name = remote_name
+                    .split_once('/')
+                    .map(|(_, branch)| branch)
+                    .unwrap_or(&remote_name)
+                    .to_string();
+                let commit = remote
+                    .get()
+                    .peel_to_commit()
+                    .map_err(|e| format!("Failed to resolve branch: {}", e))?;
+                let mut local = repo
+                    .branch(&local_name, &commit, false)
+                    .map_err(|e| format!("Failed to create branch: {}", e))?;
+                local
+                    .set_upstream(Some(&remote_name))
+                    .map_err(|e| format!("Failed to set upstream: {}", e))?;
+                local
+            }
+        };
+
+        let reference = branch
+            .get()
+            .name()
+            .ok_or_else(|| "Branch name is not valid UTF-8".to_string())?
+            .to_string();
+        let target = branch
+            .get()
+            .peel(git2::ObjectType::Commit)
+            .map_err(|e| format!("Failed to resolve branch: {}", e))?;
+
+        repo.checkout_tree(&target, Some(git2::build::CheckoutBuilder::new().safe()))
+            .map_err(|e| format!("Failed to checkout: {}", e))?;
+        repo.set_head(&reference)
+            .map_err(|e| format!("Failed to update HEAD: {}", e))?;
+
+        Ok(GitOperationResult {
+            success: true,
+            result: Some(format!("Switched to branch {}", reference.trim_start_matches("refs/heads/"))),
+            error: None,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Create a branch from `start_point` (default HEAD), optionally checking it out
+    #[cfg(feature = "git")]
+    pub fn create_branch(
+        &self,
+        name: &str,
+        start_point: Option<&str>,
+        checkout: bool,
+    ) -> Result<GitOperationResult, String> {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let repo = self.repo()?;
+
+        let commit = repo
+            .revparse_single(start_point.unwrap_or("HEAD"))
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| format!("Failed to resolve start point: {}", e))?;
+        repo.branch(name, &commit, false)
+            .map_err(|e| format!("Failed to create branch: {}", e))?;
+
+        if checkout {
+            return self.checkout_branch(name);
+        }
+
+        Ok(GitOperationResult {
+            success: true,
+            result: Some(format!("Created branch {} at {}", name, commit.id())),
+            error: None,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Commits reachable from HEAD, newest first
+    #[cfg(feature = "git")]
+    pub fn log(&self, limit: usize) -> Result<Vec<GitCommitInfo>, String> {
+        let repo = self.repo()?;
+        // An unborn branch has no history
+        if repo.head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let mut revwalk = repo.revwalk()
+            .map_err(|e| format!("Failed to walk history: {}", e))?;
+        revwalk.push_head()
+            .map_err(|e| format!("Failed to walk history: {}", e))?;
+        revwalk.set_sorting(git2::Sort::TIME)
+            .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+        revwalk
+            .take(limit)
+            .map(|oid| {
+                let oid = oid.map_err(|e| format!("Failed to walk history: {}", e))?;
+                let commit = repo.find_commit(oid)
+                    .map_err(|e| format!("Failed to read commit: {}", e))?;
+                Ok(commit_info(&commit))
+            })
+            .collect()
+    }
+
+    /// Stage paths relative to the repository root
+    ///
+    /// Paths missing from the working tree are staged as deletions.
+    #[cfg(feature = "git")]
+    pub fn stage_paths(&self, paths: &[String]) -> Result<(), String> {
+        let repo = self.repo()?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| "Cannot stage files in a bare repository".to_string())?
+            .to_path_buf();
+        let mut index = repo.index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+
+        for path in paths {
+            let relative = std::path::Path::new(path);
+            if relative.is_absolute()
+                || relative.components().any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                return Err(format!("Path must be inside the repository: {}", path));
+            }
+
+            if workdir.join(relative).exists() {
+                index.add_path(relative)
+            } else {
+                index.remove_path(relative)
+            }
+            .map_err(|e| format!("Failed to stage {}: {}", path, e))?;
+        }
+
+        index.write()
+            .map_err(|e| format!("Failed to write index: {}", e))
+    }
+
+    /// Id of the tree the index would commit
+    #[cfg(feature = "git")]
+    pub fn staged_tree(&self) -> Result<String, String> {
+        let repo = self.repo()?;
+        let mut index = repo.index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        let tree_id = index.write_tree()
+            .map_err(|e| format!("Failed to write tree: {}", e))?;
+        Ok(tree_id.to_string())
+    }
+
+    /// Commit exactly what is staged, without staging anything else
+    ///
+    /// `expected_tree` is the staged tree the caller reviewed; the commit is
+    /// refused if the index has changed since.
+    #[cfg(feature = "git")]
+    pub fn commit_staged(&self, message: &str, expected_tree: &str) -> Result<String, String> {
+        let repo = self.repo()?;
+        let tree_id = self.staged_tree()?;
+        if tree_id != expected_tree {
+            return Err("Staged changes were modified before the commit was confirmed".to_string());
+        }
+
+        let tree = repo
+            .find_tree(git2::Oid::from_str(&tree_id).map_err(|e| e.to_string())?)
+            .map_err(|e| format!("Failed to find tree: {}", e))?;
+        let sig = repo.signature()
+            .map_err(|e| format!("Failed to get signature: {}", e))?;
+        let parent_commit = repo.head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok());
+
+        let oid = repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            message,
+            &tree,
+            parent_commit.as_ref().into_iter().collect::<Vec<_>>().as_slice(),
+        )
+            .map_err(|e| format!("Failed to commit: {}", e))?;
+        Ok(oid.to_string())
+    }
+
+    /// Per-file diff of staged changes (index against HEAD) or of unstaged
+    /// changes including untracked files (working tree against index)
+    #[cfg(feature = "git")]
+    pub fn diff(&self, staged: bool) -> Result<Vec<GitFileDiff>, String> {
+        let repo = self.repo()?;
+        let mut options = git2::DiffOptions::new();
+
+        let diff = if staged {
+            // Before the first commit everything staged is new
+            let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))
+        } else {
+            options
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .show_untracked_content(true);
+            repo.diff_index_to_workdir(None, Some(&mut options))
+        }
+        .map_err(|e| format!("Failed to compute diff: {}", e))?;
+
+        let mut files = Vec::new();
+        for index in 0..diff.deltas().len() {
+            let patch = git2::Patch::from_diff(&diff, index)
+                .map_err(|e| format!("Failed to compute diff: {}", e))?;
+            let Some(delta) = diff.get_delta(index) else {
+                continue;
+            };
+
+            let mut file = GitFileDiff {
+                old_path: delta.old_file().path().map(|p| p.to_string_lossy().to_string()),
+                new_path: delta.new_file().path().map(|p| p.to_string_lossy().to_string()),
+                status: delta_status(delta.status()).to_string(),
+                binary: false,
+                additions: 0,
+                deletions: 0,
+                hunks: Vec::new(),
+                truncated: false,
+            };
+
+            if let Some(patch) = patch {
+                file.binary = patch.delta().flags().is_binary();
+                let (_, additions, deletions) = patch.line_stats()
+                    .map_err(|e| format!("Failed to compute diff: {}", e))?;
+                file.additions = additions;
+                file.deletions = deletions;
+
+                let mut remaining = MAX_DIFF_LINES_PER_FILE;
+                for hunk_index in 0..patch.num_hunks() {
+                    let (hunk, line_count) = patch.hunk(hunk_index)
+                        .map_err(|e| format!("Failed to compute diff: {}", e))?;
+                    let mut lines = Vec::new();
+                    for line_index in 0..line_count {
+                        if remaining == 0 {
+                            file.truncated = true;
+                            break;
+                        }
+                        let line = patch.line_in_hunk(hunk_index, line_index)
+                            .map_err(|e| format!("Failed to compute diff: {}", e))?;
+                        lines.push(GitDiffLine {
+                            origin: line.origin().to_string(),
+                            content: String::from_utf8_lossy(line.content())
+                                .trim_end_matches(['\n', '\r'])
+                                .to_string(),
+                            old_lineno: line.old_lineno(),
+                            new_lineno: line.new_lineno(),
+                        });
+                        remaining -= 1;
+                    }
+
+                    file.hunks.push(GitDiffHunk {
+                        header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                        old_start: hunk.old_start(),
+                        old_lines: hunk.old_lines(),
+                        new_start: hunk.new_start(),
+                        new_lines: hunk.new_lines(),
+                        lines,
+                    });
+                    if file.truncated {
+                        break;
+                    }
+                }
+            }
+
+            files.push(file);
+        }
+
+        Ok(files)
+    }
 }
 
 /// Clone a repository
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn git_clone(
     url: String,
     path: String,
-) -> std::result::Result<GitOperationResult, String> {
+) -> std::result::Result<GitOperationResult, AppError> {
     #[cfg(feature = "git")]
     {
-        GitOperations::clone(&url, &path)
+        Ok(GitOperations::clone(&url, &path)?)
     }
 
     #[cfg(not(feature = "git"))]
@@ -486,14 +920,15 @@ pub fn git_clone(
 
 /// Commit changes
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn git_commit(
     path: String,
     message: String,
-) -> std::result::Result<GitOperationResult, String> {
+) -> std::result::Result<GitOperationResult, AppError> {
     #[cfg(feature = "git")]
     {
         let ops = GitOperations::open(&path)?;
-        ops.commit(&message)
+        Ok(ops.commit(&message)?)
     }
 
     #[cfg(not(feature = "git"))]
@@ -509,15 +944,16 @@ pub fn git_commit(
 
 /// Push changes
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn git_push(
     path: String,
     remote: String,
     branch: String,
-) -> std::result::Result<GitOperationResult, String> {
+) -> std::result::Result<GitOperationResult, AppError> {
     #[cfg(feature = "git")]
     {
         let ops = GitOperations::open(&path)?;
-        ops.push(&remote, &branch)
+        Ok(ops.push(&remote, &branch)?)
     }
 
     #[cfg(not(feature = "git"))]
@@ -533,15 +969,16 @@ pub fn git_push(
 
 /// Pull changes
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn git_pull(
     path: String,
     remote: String,
     branch: String,
-) -> std::result::Result<GitOperationResult, String> {
+) -> std::result::Result<GitOperationResult, AppError> {
     #[cfg(feature = "git")]
     {
         let ops = GitOperations::open(&path)?;
-        ops.pull(&remote, &branch)
+        Ok(ops.pull(&remote, &branch)?)
     }
 
     #[cfg(not(feature = "git"))]
@@ -557,13 +994,14 @@ pub fn git_pull(
 
 /// Get extended status
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn git_get_extended_status(
     path: String,
-) -> std::result::Result<GitExtendedStatus, String> {
+) -> std::result::Result<GitExtendedStatus, AppError> {
     #[cfg(feature = "git")]
     {
         let ops = GitOperations::open(&path)?;
-        ops.get_status()
+        Ok(ops.get_status()?)
     }
 
     #[cfg(not(feature = "git"))]
@@ -579,3 +1017,176 @@ pub fn git_get_extended_status(
         })
     }
 }
+
+/// List local and remote branches
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn git_list_branches(path: String) -> std::result::Result<Vec<GitBranch>, AppError> {
+    #[cfg(feature = "git")]
+    {
+        Ok(GitOperations::open(&path)?.list_branches()?)
+    }
+
+    #[cfg(not(feature = "git"))]
+    {
+        Err(AppError::invalid_input(format!("Git feature not enabled for {}", path)))
+    }
+}
+
+/// Check out a branch
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn git_checkout_branch(
+    path: String,
+    name: String,
+) -> std::result::Result<GitOperationResult, AppError> {
+    #[cfg(feature = "git")]
+    {
+        Ok(GitOperations::open(&path)?.checkout_branch(&name)?)
+    }
+
+    #[cfg(not(feature = "git"))]
+    {
+        Ok(GitOperationResult {
+            success: false,
+            result: None,
+            error: Some(format!("Git feature not enabled: cannot check out {} in {}", name, path)),
+            execution_time_ms: 0,
+        })
+    }
+}
+
+/// Create a branch
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn git_create_branch(
+    path: String,
+    name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+) -> std::result::Result<GitOperationResult, AppError> {
+    #[cfg(feature = "git")]
+    {
+        Ok(GitOperations::open(&path)?.create_branch(&name, start_point.as_deref(), checkout.unwrap_or(false))?)
+    }
+
+    #[cfg(not(feature = "git"))]
+    {
+        let _ = (start_point, checkout);
+        Ok(GitOperationResult {
+            success: false,
+            result: None,
+            error: Some(format!("Git feature not enabled: cannot create {} in {}", name, path)),
+            execution_time_ms: 0,
+        })
+    }
+}
+
+/// Commit history from HEAD, newest first (default 50 commits)
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn git_log(
+    path: String,
+    limit: Option<usize>,
+) -> std::result::Result<Vec<GitCommitInfo>, AppError> {
+    #[cfg(feature = "git")]
+    {
+        Ok(GitOperations::open(&path)?.log(limit.unwrap_or(DEFAULT_LOG_LIMIT))?)
+    }
+
+    #[cfg(not(feature = "git"))]
+    {
+        let _ = limit;
+        Err(AppError::invalid_input(format!("Git feature not enabled for {}", path)))
+    }
+}
+
+/// Structured diff of staged or unstaged changes
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn git_diff(
+    path: String,
+    staged: bool,
+) -> std::result::Result<Vec<GitFileDiff>, AppError> {
+    #[cfg(feature = "git")]
+    {
+        Ok(GitOperations::open(&path)?.diff(staged)?)
+    }
+
+    #[cfg(not(feature = "git"))]
+    {
+        let _ = staged;
+        Err(AppError::invalid_input(format!("Git feature not enabled for {}", path)))
+    }
+}
+
+#[cfg(all(test, feature = "git"))]
+mod tests {
+    use super::*;
+
+    fn init_repo(dir: &std::path::Path) -> git2::Repository {
+        let repo = git2::Repository::init(dir).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        repo
+    }
+
+    #[test]
+    fn test_branches_log_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo(dir.path());
+        let path = dir.path().to_string_lossy().to_string();
+        let ops = GitOperations::open(&path).unwrap();
+        assert!(ops.log(10).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        assert_eq!(ops.diff(true).unwrap()[0].status, "added");
+        ops.commit("Initial commit").unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "one\nthree\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "new\n").unwrap();
+        let diff = ops.diff(false).unwrap();
+        let modified = diff.iter().find(|f| f.new_path.as_deref() == Some("a.txt")).unwrap();
+        assert_eq!(modified.status, "modified");
+        assert_eq!((modified.additions, modified.deletions), (1, 1));
+        let lines = &modified.hunks[0].lines;
+        assert!(lines.iter().any(|l| l.origin == "-" && l.content == "two"));
+        assert!(lines.iter().any(|l| l.origin == "+" && l.content == "three"));
+        assert!(diff.iter().any(|f| f.status == "untracked"));
+        assert!(ops.diff(true).unwrap().is_empty());
+
+        let created = ops.create_branch("feature", None, true).unwrap();
+        assert!(created.success);
+        let branches = ops.list_branches().unwrap();
+        assert!(branches.iter().any(|b| b.name == "feature" && b.is_head));
+
+        let log = ops.log(10).unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].summary, "Initial commit");
+    }
+
+    #[test]
+    fn test_commit_staged_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let _repo = init_repo(dir.path());
+        let path = dir.path().to_string_lossy().to_string();
+        let ops = GitOperations::open(&path).unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b\n").unwrap();
+        assert!(ops.stage_paths(&["../outside".to_string()]).is_err());
+        ops.stage_paths(&["a.txt".to_string()]).unwrap();
+
+        let tree = ops.staged_tree().unwrap();
+        ops.commit_staged("Add a", &tree).unwrap();
+        // b.txt was never staged and stays untracked
+        assert!(ops.diff(false).unwrap().iter().any(|f| f.new_path.as_deref() == Some("b.txt")));
+
+        ops.stage_paths(&["b.txt".to_string()]).unwrap();
+        assert!(ops.commit_staged("Add b", &tree).is_err());
+    }
+}
@@ -4,7 +4,10 @@
 
 pub mod database;
 pub mod git;
+pub mod calendar;
+pub mod channels;
 pub mod cloud;
+pub mod email;
 
 pub use database::*;
 pub use git::*;
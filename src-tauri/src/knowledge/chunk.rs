@@ -0,0 +1,106 @@
+//! Document Chunking
+//!
+//! Documents are split into passages of whole lines, at most
+//! `MAX_CHUNK_CHARS` long. Once a passage is half full it ends at the next
+//! blank line, so paragraphs tend to stay together. Source files in a
+//! supported language are split on definitions instead (see `code`).
+
+use serde::{Deserialize, Serialize};
+
+/// Longest passage, in bytes; a single longer line is kept whole
+pub const MAX_CHUNK_CHARS: usize = 1500;
+
+/// Passage of a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub content: String,
+    /// First line, 1-based
+    pub start_line: usize,
+    /// Last line, inclusive
+    pub end_line: usize,
+    /// Qualified name of the definition in a source file, as `Parser::parse`
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Kind of the definition, as `function` or `class`
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+/// Split a document into passages, skipping blank lines between them
+pub fn chunk_text(text: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut start = 1;
+    let mut size = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        let blank = line.trim().is_empty();
+        if lines.is_empty() {
+            if blank {
+                continue;
+            }
+            start = i + 1;
+        }
+        let full = size + line.len() > MAX_CHUNK_CHARS;
+        if (blank && size >= MAX_CHUNK_CHARS / 2) || full {
+            push(&mut chunks, &lines, start);
+            lines.clear();
+            size = 0;
+            if blank {
+                continue;
+            }
+            start = i + 1;
+        }
+        lines.push(line);
+        size += line.len() + 1;
+    }
+    push(&mut chunks, &lines, start);
+    chunks
+}
+
+fn push(chunks: &mut Vec<Chunk>, lines: &[&str], start: usize) {
+    let Some(last) = lines.iter().rposition(|l| !l.trim().is_empty()) else {
+        return;
+    };
+    chunks.push(Chunk {
+        content: lines[..=last].join("\n"),
+        start_line: start,
+        end_line: start + last,
+        symbol: None,
+        kind: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_document() {
+        assert!(chunk_text("").is_empty());
+        assert!(chunk_text("\n  \n").is_empty());
+
+        let chunks = chunk_text("\n\n# Title\n\nSome text\n\n");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "# Title\n\nSome text");
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (3, 5));
+    }
+
+    #[test]
+    fn test_long_document() {
+        let paragraph = vec!["word ".repeat(20); 5].join("\n");
+        let text = vec![paragraph.as_str(); 6].join("\n\n");
+        let chunks = chunk_text(&text);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.content.len() <= MAX_CHUNK_CHARS);
+            // Passages end at paragraph boundaries
+            assert!(!chunk.content.starts_with('\n') && !chunk.content.ends_with('\n'));
+            assert_eq!(chunk.content.lines().count(), chunk.end_line - chunk.start_line + 1);
+        }
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(chunks[1].content.lines().next(), Some(lines[chunks[1].start_line - 1]));
+        assert_eq!(chunks.last().unwrap().end_line, lines.len());
+    }
+}
@@ -0,0 +1,389 @@
+//! Code Chunking
+//!
+//! Source files are parsed with tree-sitter and split on definition
+//! boundaries: each function, class, trait, impl and type becomes its own
+//! chunk, carrying its symbol (`Parser::parse`, `Workspace.create`) and
+//! kind, with the comments right above it attached. A definition longer
+//! than `MAX_CHUNK_CHARS` is split on the definitions it contains, such as
+//! the methods of a class, or else into line-based passages that keep its
+//! symbol. Imports and other top-level statements between definitions are
+//! chunked like prose.
+
+use super::chunk::{chunk_text, Chunk, MAX_CHUNK_CHARS};
+use tree_sitter::{Node, Parser};
+
+/// Languages split on definitions; other files are chunked as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+}
+
+impl Language {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = path.rsplit_once('.')?.1.to_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+
+    /// Separator between a container's symbol and its members'
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            _ => ".",
+        }
+    }
+}
+
+/// Definition found in a syntax tree
+struct Definition<'t> {
+    kind: &'static str,
+    name: String,
+    /// Node holding member definitions, as a class body
+    body: Option<Node<'t>>,
+}
+
+/// Split a source file on its definitions; `None` for languages without a
+/// grammar or files that fail to parse
+pub fn chunk_code(path: &str, text: &str) -> Option<Vec<Chunk>> {
+    let language = Language::from_path(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(text, None)?;
+
+    let mut chunker = Chunker {
+        language,
+        source: text,
+        lines: text.lines().collect(),
+        chunks: Vec::new(),
+    };
+    chunker.members(tree.root_node(), None, None);
+    Some(chunker.chunks)
+}
+
+/// Comments and Rust attributes, attached to a definition right below them
+fn is_preamble(node: Node) -> bool {
+    node.kind().contains("comment") || node.kind() == "attribute_item"
+}
+
+struct Chunker<'s> {
+    language: Language,
+    source: &'s str,
+    lines: Vec<&'s str>,
+    chunks: Vec<Chunk>,
+}
+
+impl<'s> Chunker<'s> {
+    fn text(&self, node: Node) -> &'s str {
+        &self.source[node.byte_range()]
+    }
+
+    fn name(&self, node: Node, field: &str) -> Option<String> {
+        node.child_by_field_name(field).map(|n| self.text(n).to_string())
+    }
+
+    /// Chunk the children of `parent`; `outer` is the row range of the
+    /// enclosing definition, whose header and closing lines join the
+    /// statements around its members
+    fn members(&mut self, parent: Node, symbol: Option<&str>, outer: Option<(usize, usize)>) {
+        let mut glue_start = outer.map(|(start, _)| start);
+        let mut glue_end = glue_start;
+        // First row of the comments and attributes directly above the next node
+        let mut comments: Option<usize> = None;
+
+        let mut cursor = parent.walk();
+        for child in parent.named_children(&mut cursor) {
+            let (start, end) = (child.start_position().row, child.end_position().row);
+            if is_preamble(child) {
+                let adjacent = glue_end.is_some_and(|row| row + 1 >= start) && comments.is_some();
+                if !adjacent {
+                    comments = Some(start);
+                }
+                glue_start.get_or_insert(start);
+                glue_end = Some(end);
+                continue;
+            }
+
+            let Some(definition) = self.definition(child) else {
+                glue_start.get_or_insert(start);
+                glue_end = Some(end);
+                comments = None;
+                continue;
+            };
+
+            // Doc comments and attributes belong to the definition below them
+            let start = match (comments, glue_end) {
+                (Some(row), Some(last)) if last + 1 >= start => row,
+                _ => start,
+            };
+            if let Some(from) = glue_start {
+                if from < start {
+                    self.prose(from, start - 1, symbol);
+                }
+            }
+            let qualified = match symbol {
+                Some(outer) => format!("{}{}{}", outer, self.language.separator(), definition.name),
+                None => definition.name.clone(),
+            };
+            self.definition_chunks(start, end, &qualified, &definition);
+            glue_start = None;
+            glue_end = Some(end);
+            comments = None;
+        }
+
+        if let Some(from) = glue_start {
+            let to = outer.map(|(_, end)| end).or(glue_end).unwrap_or(from);
+            self.prose(from, to, symbol);
+        } else if let (Some((_, end)), Some(last)) = (outer, glue_end) {
+            if last < end {
+                self.prose(last + 1, end, symbol);
+            }
+        }
+    }
+
+    fn definition_chunks(&mut self, start: usize, end: usize, symbol: &str, definition: &Definition) {
+        let size: usize = self.lines[start..=end.min(self.lines.len().saturating_sub(1))]
+            .iter()
+            .map(|l| l.len() + 1)
+            .sum();
+        if size <= MAX_CHUNK_CHARS {
+            self.push(start, end, Some(symbol), Some(definition.kind));
+            return;
+        }
+
+        let has_members = definition.body.is_some_and(|body| {
+            let mut cursor = body.walk();
+            let mut children = body.named_children(&mut cursor);
+            children.any(|c| self.definition(c).is_some())
+        });
+        match definition.body {
+            Some(body) if has_members => self.members(body, Some(symbol), Some((start, end))),
+            _ => {
+                for chunk in self.split(start, end) {
+                    self.chunks.push(Chunk {
+                        symbol: Some(symbol.to_string()),
+                        kind: Some(definition.kind.to_string()),
+                        ..chunk
+                    });
+                }
+            }
+        }
+    }
+
+    /// Chunk statements between definitions like prose, dropping lines
+    /// of closing brackets
+    fn prose(&mut self, start: usize, end: usize, symbol: Option<&str>) {
+        for chunk in self.split(start, end) {
+            if !chunk.content.chars().any(char::is_alphanumeric) {
+                continue;
+            }
+            self.chunks.push(Chunk {
+                symbol: symbol.map(str::to_string),
+                ..chunk
+            });
+        }
+    }
+
+    fn split(&self, start: usize, end: usize) -> Vec<Chunk> {
+        let end = end.min(self.lines.len().saturating_sub(1));
+        if start > end {
+            return Vec::new();
+        }
+        chunk_text(&self.lines[start..=end].join("\n"))
+            .into_iter()
+            .map(|chunk| Chunk {
+                start_line: chunk.start_line + start,
+                end_line: chunk.end_line + start,
+                ..chunk
+            })
+            .collect()
+    }
+
+    fn push(&mut self, start: usize, end: usize, symbol: Option<&str>, kind: Option<&str>) {
+        let end = end.min(self.lines.len().saturating_sub(1));
+        self.chunks.push(Chunk {
+            content: self.lines[start..=end].join("\n"),
+            start_line: start + 1,
+            end_line: end + 1,
+            symbol: symbol.map(str::to_string),
+            kind: kind.map(str::to_string),
+        });
+    }
+
+    fn definition<'t>(&self, node: Node<'t>) -> Option<Definition<'t>> {
+        // Wrappers keep their decorators and `export` in the chunk
+        let inner = match node.kind() {
+            "export_statement" => node.child_by_field_name("declaration")?,
+            "decorated_definition" => node.child_by_field_name("definition")?,
+            _ => node,
+        };
+        let (kind, name, body) = match (self.language, inner.kind()) {
+            (Language::Rust, kind) => match kind {
+                "function_item" | "function_signature_item" => ("function", self.name(inner, "name")?, None),
+                "struct_item" | "union_item" => ("struct", self.name(inner, "name")?, None),
+                "enum_item" => ("enum", self.name(inner, "name")?, None),
+                "type_item" => ("type", self.name(inner, "name")?, None),
+                "macro_definition" => ("macro", self.name(inner, "name")?, None),
+                "trait_item" => ("trait", self.name(inner, "name")?, inner.child_by_field_name("body")),
+                "mod_item" => ("module", self.name(inner, "name")?, inner.child_by_field_name("body")),
+                "impl_item" => {
+                    let ty = self.name(inner, "type")?;
+                    let name = match self.name(inner, "trait") {
+                        Some(tr) => format!("<{} as {}>", ty, tr),
+                        None => ty,
+                    };
+                    ("impl", name, inner.child_by_field_name("body"))
+                }
+                _ => return None,
+            },
+            (Language::Python, kind) => match kind {
+                "function_definition" => ("function", self.name(inner, "name")?, None),
+                "class_definition" => ("class", self.name(inner, "name")?, inner.child_by_field_name("body")),
+                _ => return None,
+            },
+            (Language::Go, kind) => match kind {
+                "function_declaration" => ("function", self.name(inner, "name")?, None),
+                "method_declaration" => {
+                    let name = self.name(inner, "name")?;
+                    // `func (s *Server) Start()` is `Server.Start`
+                    let receiver = inner
+                        .child_by_field_name("receiver")
+                        .and_then(|r| r.named_child(0))
+                        .and_then(|p| p.child_by_field_name("type"))
+                        .map(|t| self.text(t).trim_start_matches('*').to_string());
+                    match receiver {
+                        Some(receiver) => ("method", format!("{}.{}", receiver, name), None),
+                        None => ("method", name, None),
+                    }
+                }
+                "type_declaration" => {
+                    let spec = inner.named_child(0)?;
+                    ("type", self.name(spec, "name")?, None)
+                }
+                _ => return None,
+            },
+            (_, kind) => match kind {
+                "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                    ("function", self.name(inner, "name")?, None)
+                }
+                "class_declaration" | "abstract_class_declaration" | "class" => {
+                    ("class", self.name(inner, "name")?, inner.child_by_field_name("body"))
+                }
+                "method_definition" | "method_signature" | "abstract_method_signature" => {
+                    ("method", self.name(inner, "name")?, None)
+                }
+                "interface_declaration" => ("interface", self.name(inner, "name")?, None),
+                "type_alias_declaration" => ("type", self.name(inner, "name")?, None),
+                "enum_declaration" => ("enum", self.name(inner, "name")?, None),
+                "internal_module" | "module" => {
+                    ("module", self.name(inner, "name")?, inner.child_by_field_name("body"))
+                }
+                // `const handler = () => {}`
+                "lexical_declaration" | "variable_declaration" => {
+                    let declarator = inner.named_child(0)?;
+                    let value = declarator.child_by_field_name("value")?;
+                    if !matches!(value.kind(), "arrow_function" | "function_expression" | "function") {
+                        return None;
+                    }
+                    ("function", self.name(declarator, "name")?, None)
+                }
+                _ => return None,
+            },
+        };
+        Some(Definition { kind, name, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(chunks: &[Chunk]) -> Vec<(Option<&str>, Option<&str>)> {
+        chunks
+            .iter()
+            .map(|c| (c.symbol.as_deref(), c.kind.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_on_definitions() {
+        assert!(chunk_code("notes.md", "# Notes").is_none());
+
+        let source = "use std::fmt;\n\n/// A point\n#[derive(Debug)]\nstruct Point {\n    x: i32,\n}\n\nfn origin() -> Point {\n    Point { x: 0 }\n}\n";
+        let chunks = chunk_code("src/geo.rs", source).unwrap();
+        assert_eq!(
+            symbols(&chunks),
+            vec![(None, None), (Some("Point"), Some("struct")), (Some("origin"), Some("function"))]
+        );
+        assert_eq!(chunks[0].content, "use std::fmt;");
+        // The doc comment travels with its struct
+        assert_eq!(chunks[1].content, "/// A point\n#[derive(Debug)]\nstruct Point {\n    x: i32,\n}");
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 7));
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (9, 11));
+
+        let source = "import os\n\n@cache\ndef load(path):\n    return open(path).read()\n";
+        let chunks = chunk_code("app/io.py", source).unwrap();
+        assert_eq!(symbols(&chunks), vec![(None, None), (Some("load"), Some("function"))]);
+        assert!(chunks[1].content.starts_with("@cache"));
+
+        let source = "export const handler = async () => {\n  return 1;\n};\n\nexport interface Props {\n  id: string;\n}\n";
+        let chunks = chunk_code("src/api.ts", source).unwrap();
+        assert_eq!(
+            symbols(&chunks),
+            vec![(Some("handler"), Some("function")), (Some("Props"), Some("interface"))]
+        );
+
+        let source = "package main\n\ntype Server struct{}\n\nfunc (s *Server) Start() {}\n";
+        let chunks = chunk_code("main.go", source).unwrap();
+        assert_eq!(
+            symbols(&chunks),
+            vec![(None, None), (Some("Server"), Some("type")), (Some("Server.Start"), Some("method"))]
+        );
+    }
+
+    #[test]
+    fn test_large_definitions() {
+        let body = "        let value = compute_something_expensive(input, options);\n".repeat(20);
+        let methods: Vec<String> = (0..4)
+            .map(|i| format!("    fn step_{}(&self) {{\n{}    }}\n", i, body))
+            .collect();
+        let source = format!("impl Pipeline {{\n{}}}\n", methods.join("\n"));
+        let chunks = chunk_code("src/pipeline.rs", &source).unwrap();
+
+        // Members of an oversized impl are chunked on their own
+        let names: Vec<&str> = chunks.iter().filter_map(|c| c.symbol.as_deref()).collect();
+        assert!(names.contains(&"Pipeline::step_0") && names.contains(&"Pipeline::step_3"));
+        assert!(chunks.iter().all(|c| c.symbol.as_deref().is_some_and(|s| s.starts_with("Pipeline"))));
+        // The header is kept with the impl's symbol
+        assert_eq!(chunks[0].content, "impl Pipeline {");
+        assert_eq!(chunks[0].symbol.as_deref(), Some("Pipeline"));
+
+        // A single oversized function is split by lines, keeping its symbol
+        let source = format!("fn huge() {{\n{}{}}}\n", body, body);
+        let chunks = chunk_code("src/huge.rs", &source).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.symbol.as_deref() == Some("huge") && c.content.len() <= MAX_CHUNK_CHARS));
+    }
+}
@@ -0,0 +1,646 @@
+//! Incremental Folder Indexing
+//!
+//! Files in a workspace's folders are chunked and embedded into
+//! `knowledge_chunks`, one `knowledge_documents` row per file. A run only
+//! reads files whose size or modification time changed since the last one,
+//! and only re-embeds those whose content hash changed too; files that are
+//! gone are dropped from the index. The `index_workspaces` system job runs
+//! every few minutes, so edits are picked up without a file watcher.
+//!
+//! `.gitignore` files, `DEFAULT_IGNORES` and each workspace's own patterns
+//! keep build output, dependencies and dotfiles such as `.env` out of the
+//! index. Binary and oversized files are skipped. Source files are split on
+//! their definitions, and each chunk records the symbol it belongs to.
+
+use super::chunk::chunk_text;
+use super::code::chunk_code;
+use crate::conversations::similar::{cosine, embed, from_blob, to_blob, EMBEDDING_MODEL};
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::files::listing::{walk, EntryKind, ListOptions};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Patterns never indexed, on top of `.gitignore` files
+pub const DEFAULT_IGNORES: &[&str] = &[
+    ".*",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+    "*.lock",
+    "*.min.js",
+    "*.map",
+];
+
+/// Larger files are skipped
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Leading bytes checked for a NUL byte to detect binary files
+const BINARY_CHECK_BYTES: usize = 8192;
+
+/// Default number of search results
+const DEFAULT_SEARCH_LIMIT: usize = 8;
+
+/// Outcome of one indexing run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRun {
+    /// Files found in the workspace's folders
+    pub scanned: usize,
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    /// Binary, oversized or non-UTF-8 files
+    pub skipped: usize,
+    pub chunks_embedded: usize,
+}
+
+/// Index state of a workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatus {
+    pub workspace_id: String,
+    pub documents: usize,
+    pub chunks: usize,
+    pub last_started_at: Option<String>,
+    /// When the last run finished
+    pub last_indexed_at: Option<String>,
+    pub last_run: Option<IndexRun>,
+    /// Why the last run failed, or which folders it could not read
+    pub last_error: Option<String>,
+    /// The workspace's own ignore patterns
+    pub ignore_patterns: Vec<String>,
+}
+
+/// Passage matching a knowledge base search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeHit {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Definition the passage belongs to, in source files
+    pub symbol: Option<String>,
+    pub symbol_kind: Option<String>,
+    pub content: String,
+    pub score: f32,
+}
+
+/// Indexed state of a file
+struct Known {
+    id: String,
+    folder_id: String,
+    size: i64,
+    modified: Option<String>,
+    hash: String,
+    model: String,
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_CHECK_BYTES)].contains(&0)
+}
+
+fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Ignore patterns of a workspace, without the defaults
+pub fn ignore_patterns(conn: &Connection, workspace_id: &str) -> Result<Vec<String>, String> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT ignore_patterns FROM knowledge_index_state WHERE workspace_id = ?1",
+            [workspace_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    match json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Replace a workspace's ignore patterns; files they match leave the index
+/// on the next run
+pub fn set_ignore_patterns(conn: &Connection, workspace_id: &str, patterns: &[String]) -> Result<(), String> {
+    crate::workspaces::get(conn, workspace_id)?;
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    for pattern in &patterns {
+        glob::Pattern::new(pattern.trim_start_matches('/'))
+            .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+    }
+    let json = serde_json::to_string(&patterns).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO knowledge_index_state (workspace_id, ignore_patterns) VALUES (?1, ?2)
+         ON CONFLICT(workspace_id) DO UPDATE SET ignore_patterns = excluded.ignore_patterns",
+        params![workspace_id, json],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn folders(conn: &Connection, workspace_id: &str) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.id, f.path FROM folder_permissions f
+             JOIN workspace_folders w ON w.folder_id = f.id
+             WHERE w.workspace_id = ?1 ORDER BY f.path",
+        )
+        .map_err(|e| e.to_string())?;
+    let folders = stmt
+        .query_map([workspace_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(folders)
+}
+
+fn known_documents(conn: &Connection, workspace_id: &str) -> Result<HashMap<String, Known>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, id, folder_id, size, modified, hash, model
+             FROM knowledge_documents WHERE workspace_id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let documents = stmt
+        .query_map([workspace_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                Known {
+                    id: row.get(1)?,
+                    folder_id: row.get(2)?,
+                    size: row.get(3)?,
+                    modified: row.get(4)?,
+                    hash: row.get(5)?,
+                    model: row.get(6)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(documents)
+}
+
+fn remove_document(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM knowledge_chunks WHERE document_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM knowledge_documents WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Chunk and embed a file's text, replacing its earlier chunks
+#[allow(clippy::too_many_arguments)]
+fn store_document(
+    conn: &Connection,
+    workspace_id: &str,
+    folder_id: &str,
+    path: &str,
+    size: i64,
+    modified: Option<&str>,
+    hash: &str,
+    text: &str,
+    existing: Option<&str>,
+) -> Result<usize, String> {
+    let chunks = chunk_code(path, text).unwrap_or_else(|| chunk_text(text));
+    let id = existing
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO knowledge_documents
+             (id, workspace_id, folder_id, path, size, modified, hash, model, chunk_count, indexed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'))
+         ON CONFLICT(id) DO UPDATE SET folder_id = excluded.folder_id, size = excluded.size,
+             modified = excluded.modified, hash = excluded.hash, model = excluded.model,
+             chunk_count = excluded.chunk_count, indexed_at = excluded.indexed_at",
+        params![id, workspace_id, folder_id, path, size, modified, hash, EMBEDDING_MODEL, chunks.len() as i64],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM knowledge_chunks WHERE document_id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+    for (ordinal, chunk) in chunks.iter().enumerate() {
+        // The file and symbol names help match questions naming them
+        let vector = embed(&format!(
+            "{}\n{}\n{}",
+            path,
+            chunk.symbol.as_deref().unwrap_or_default(),
+            chunk.content
+        ));
+        tx.execute(
+            "INSERT INTO knowledge_chunks
+                 (document_id, ordinal, content, start_line, end_line, symbol, symbol_kind, embedding)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                id,
+                ordinal as i64,
+                chunk.content,
+                chunk.start_line as i64,
+                chunk.end_line as i64,
+                chunk.symbol,
+                chunk.kind,
+                to_blob(&vector)
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(chunks.len())
+}
+
+/// Bring a workspace's index up to date with its folders
+pub fn index_workspace(conn: &Connection, workspace_id: &str) -> Result<IndexRun, String> {
+    crate::workspaces::get(conn, workspace_id)?;
+    conn.execute(
+        "INSERT INTO knowledge_index_state (workspace_id, last_started_at) VALUES (?1, datetime('now'))
+         ON CONFLICT(workspace_id) DO UPDATE SET last_started_at = excluded.last_started_at",
+        [workspace_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let result = run(conn, workspace_id);
+    let (run, error) = match &result {
+        Ok((run, unreadable)) if unreadable.is_empty() => (Some(run), None),
+        Ok((run, unreadable)) => (Some(run), Some(format!("Could not read {}", unreadable.join(", ")))),
+        Err(e) => (None, Some(e.clone())),
+    };
+    let run_json = run.map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE knowledge_index_state SET
+             last_indexed_at = CASE WHEN ?2 IS NULL THEN last_indexed_at ELSE datetime('now') END,
+             last_run = COALESCE(?2, last_run), last_error = ?3
+         WHERE workspace_id = ?1",
+        params![workspace_id, run_json, error],
+    )
+    .map_err(|e| e.to_string())?;
+
+    result.map(|(run, _)| run)
+}
+
+/// One indexing pass, returning the folders that could not be read
+fn run(conn: &Connection, workspace_id: &str) -> Result<(IndexRun, Vec<String>), String> {
+    let mut exclude: Vec<String> = DEFAULT_IGNORES.iter().map(|p| p.to_string()).collect();
+    exclude.extend(ignore_patterns(conn, workspace_id)?);
+    let options = ListOptions {
+        recursive: true,
+        exclude,
+        respect_gitignore: true,
+        ..Default::default()
+    };
+
+    let mut known = known_documents(conn, workspace_id)?;
+    let mut summary = IndexRun::default();
+    let mut seen = HashSet::new();
+    let mut unreadable = Vec::new();
+    let mut unreadable_folders = HashSet::new();
+
+    for (folder_id, root) in folders(conn, workspace_id)? {
+        let mut files = Vec::new();
+        let walked = walk(Path::new(&root), &options, |entry| {
+            if entry.kind == EntryKind::File {
+                files.push(entry);
+            }
+            true
+        });
+        if let Err(e) = walked {
+            // Keep the index of a folder that is only unreachable for now,
+            // such as an unmounted drive
+            tracing::warn!("Failed to index {}: {}", root, e.message);
+            unreadable.push(root);
+            unreadable_folders.insert(folder_id);
+            continue;
+        }
+
+        for file in files {
+            // Folders may overlap; the first one listing a file owns it
+            if !seen.insert(file.path.clone()) {
+                continue;
+            }
+            summary.scanned += 1;
+            if file.size > MAX_FILE_BYTES {
+                seen.remove(&file.path);
+                summary.skipped += 1;
+                continue;
+            }
+            let previous = known.get(&file.path);
+            if let Some(doc) = previous {
+                if doc.size == file.size as i64 && doc.modified == file.modified && doc.model == EMBEDDING_MODEL {
+                    summary.unchanged += 1;
+                    continue;
+                }
+            }
+
+            let text = match std::fs::read(&file.path) {
+                Ok(bytes) if !is_binary(&bytes) => String::from_utf8(bytes).ok(),
+                _ => None,
+            };
+            let Some(text) = text else {
+                seen.remove(&file.path);
+                summary.skipped += 1;
+                continue;
+            };
+            let digest = hash(text.as_bytes());
+
+            match previous {
+                // Touched but not edited
+                Some(doc) if doc.hash == digest && doc.model == EMBEDDING_MODEL => {
+                    conn.execute(
+                        "UPDATE knowledge_documents SET size = ?2, modified = ?3 WHERE id = ?1",
+                        params![doc.id, file.size as i64, file.modified],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    summary.unchanged += 1;
+                }
+                _ => {
+                    summary.chunks_embedded += store_document(
+                        conn,
+                        workspace_id,
+                        &folder_id,
+                        &file.path,
+                        file.size as i64,
+                        file.modified.as_deref(),
+                        &digest,
+                        &text,
+                        previous.map(|doc| doc.id.as_str()),
+                    )?;
+                    if previous.is_some() {
+                        summary.updated += 1;
+                    } else {
+                        summary.added += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    known.retain(|path, doc| !seen.contains(path) && !unreadable_folders.contains(&doc.folder_id));
+    for doc in known.values() {
+        remove_document(conn, &doc.id)?;
+        summary.removed += 1;
+    }
+
+    Ok((summary, unreadable))
+}
+
+/// Index every workspace, for the `index_workspaces` system job
+pub fn index_database(db_path: &Path) -> Result<String, String> {
+    let conn = crate::scheduler::history::open(db_path)?;
+    let workspaces = crate::workspaces::list(&conn)?;
+    let mut changed = 0;
+    let mut removed = 0;
+    let mut failed = Vec::new();
+    for workspace in &workspaces {
+        match index_workspace(&conn, &workspace.id) {
+            Ok(run) => {
+                changed += run.added + run.updated;
+                removed += run.removed;
+            }
+            Err(e) => failed.push(format!("{}: {}", workspace.name, e)),
+        }
+    }
+    if !failed.is_empty() {
+        return Err(format!("Failed to index {}", failed.join("; ")));
+    }
+    Ok(format!(
+        "Indexed {} workspaces: {} files embedded, {} removed",
+        workspaces.len(),
+        changed,
+        removed
+    ))
+}
+
+/// Drop a workspace's index
+pub fn clear(conn: &Connection, workspace_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM knowledge_chunks WHERE document_id IN
+             (SELECT id FROM knowledge_documents WHERE workspace_id = ?1)",
+        [workspace_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM knowledge_documents WHERE workspace_id = ?1", [workspace_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM knowledge_index_state WHERE workspace_id = ?1", [workspace_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn status(conn: &Connection, workspace_id: &str) -> Result<IndexStatus, String> {
+    crate::workspaces::get(conn, workspace_id)?;
+    let (documents, chunks): (i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(chunk_count), 0) FROM knowledge_documents WHERE workspace_id = ?1",
+            [workspace_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let state = conn
+        .query_row(
+            "SELECT last_started_at, last_indexed_at, last_run, last_error
+             FROM knowledge_index_state WHERE workspace_id = ?1",
+            [workspace_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (last_started_at, last_indexed_at, last_run, last_error) = state.unwrap_or_default();
+
+    Ok(IndexStatus {
+        workspace_id: workspace_id.to_string(),
+        documents: documents as usize,
+        chunks: chunks as usize,
+        last_started_at,
+        last_indexed_at,
+        last_run: last_run.and_then(|json| serde_json::from_str(&json).ok()),
+        last_error,
+        ignore_patterns: ignore_patterns(conn, workspace_id)?,
+    })
+}
+
+/// Passages of a workspace's documents most similar to a query
+pub fn search(conn: &Connection, workspace_id: &str, query: &str, limit: usize) -> Result<Vec<KnowledgeHit>, String> {
+    let query = embed(query);
+    let mut stmt = conn
+        .prepare(
+            "SELECT d.path, c.start_line, c.end_line, c.symbol, c.symbol_kind, c.content, c.embedding
+             FROM knowledge_chunks c JOIN knowledge_documents d ON d.id = c.document_id
+             WHERE d.workspace_id = ?1 AND d.model = ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut hits = stmt
+        .query_map(params![workspace_id, EMBEDDING_MODEL], |row| {
+            let blob: Vec<u8> = row.get(6)?;
+            Ok(KnowledgeHit {
+                path: row.get(0)?,
+                start_line: row.get::<_, i64>(1)? as usize,
+                end_line: row.get::<_, i64>(2)? as usize,
+                symbol: row.get(3)?,
+                symbol_kind: row.get(4)?,
+                content: row.get(5)?,
+                score: cosine(&query, &from_blob(&blob)),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    hits.retain(|hit| hit.score > 0.0);
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Document and chunk counts of a workspace's index, with its last run
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_index_status(db: tauri::State<'_, DbState>, workspace_id: String) -> Result<IndexStatus, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(status(&conn, &workspace_id)?)
+}
+
+/// Index a workspace now instead of waiting for the system job
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn index_workspace_now(db: tauri::State<'_, DbState>, workspace_id: String) -> Result<IndexRun, AppError> {
+    // A connection of its own keeps the app usable during a long run
+    let db_path = db.path();
+    let run = tauri::async_runtime::spawn_blocking(move || {
+        let conn = crate::scheduler::history::open(Path::new(&db_path))?;
+        index_workspace(&conn, &workspace_id)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(run)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_index_ignore_patterns(
+    db: tauri::State<'_, DbState>,
+    workspace_id: String,
+    patterns: Vec<String>,
+) -> Result<IndexStatus, AppError> {
+    let conn = db.conn.lock()?;
+    set_ignore_patterns(&conn, &workspace_id, &patterns)?;
+    Ok(status(&conn, &workspace_id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn search_knowledge(
+    db: tauri::State<'_, DbState>,
+    workspace_id: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<KnowledgeHit>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(search(&conn, &workspace_id, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspaces::{create, WorkspaceInput};
+    use crate::db::test_support::test_conn;
+
+    fn setup(root: &Path) -> (Connection, String) {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level) VALUES ('f1', ?1, 'read')",
+            [root.to_string_lossy()],
+        )
+        .unwrap();
+        let workspace = create(
+            &conn,
+            WorkspaceInput {
+                name: "Docs".to_string(),
+                folder_ids: vec!["f1".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        (conn, workspace.id)
+    }
+
+    #[test]
+    fn test_incremental_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("guide.md"), "# Setup\n\nInstall the sidecar with npm.").unwrap();
+        std::fs::write(root.join("notes.txt"), "Deployment checklist").unwrap();
+        std::fs::write(root.join("image.bin"), [0u8, 1, 2, 3]).unwrap();
+        std::fs::write(root.join(".env"), "API_KEY=secret").unwrap();
+        std::fs::create_dir(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("node_modules/lib.js"), "module.exports = {}").unwrap();
+        let (conn, id) = setup(root);
+
+        let first = index_workspace(&conn, &id).unwrap();
+        assert_eq!((first.added, first.skipped, first.removed), (2, 1, 0));
+        let status = status(&conn, &id).unwrap();
+        assert_eq!((status.documents, status.chunks), (2, 2));
+        assert!(status.last_indexed_at.is_some());
+        assert_eq!(status.last_run, Some(first));
+
+        // Nothing changed
+        let second = index_workspace(&conn, &id).unwrap();
+        assert_eq!((second.unchanged, second.chunks_embedded), (2, 0));
+
+        // Only the edited file is embedded again; deleted files leave the index
+        std::fs::write(root.join("guide.md"), "# Setup\n\nInstall the sidecar with pnpm instead.").unwrap();
+        std::fs::remove_file(root.join("notes.txt")).unwrap();
+        let third = index_workspace(&conn, &id).unwrap();
+        assert_eq!((third.updated, third.removed, third.chunks_embedded), (1, 1, 1));
+
+        let hits = search(&conn, &id, "how to install the sidecar", 5).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].path.ends_with("guide.md"));
+        assert!(hits[0].content.contains("pnpm"));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join("drafts")).unwrap();
+        std::fs::write(root.join("drafts/todo.md"), "Draft").unwrap();
+        std::fs::write(root.join("readme.md"), "Readme").unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("debug.log"), "trace").unwrap();
+        let (conn, id) = setup(root);
+
+        assert_eq!(index_workspace(&conn, &id).unwrap().added, 2);
+        assert!(set_ignore_patterns(&conn, &id, &["[".to_string()]).is_err());
+        set_ignore_patterns(&conn, &id, &["drafts".to_string(), " ".to_string()]).unwrap();
+        assert_eq!(ignore_patterns(&conn, &id).unwrap(), vec!["drafts"]);
+
+        let run = index_workspace(&conn, &id).unwrap();
+        assert_eq!((run.unchanged, run.removed), (1, 1));
+
+        clear(&conn, &id).unwrap();
+        let status = status(&conn, &id).unwrap();
+        assert_eq!((status.documents, status.chunks), (0, 0));
+        assert!(status.ignore_patterns.is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+//! Knowledge Base
+//!
+//! Retrieval over the files in a workspace's folders:
+//! - Chunking of documents into passages small enough to embed
+//! - Splitting of source files on function and class boundaries
+//! - Incremental indexing that re-embeds only files changed since the last run
+
+pub mod chunk;
+pub mod code;
+pub mod index;
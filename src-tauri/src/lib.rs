@@ -1,5 +1,6 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 mod db;
+mod error;
 mod sidecar;
 pub mod voice;
 pub mod plugins;
@@ -8,12 +9,31 @@ mod scheduler;
 mod marketplace;
 mod integration;
 mod security;
+mod recovery;
+mod analytics;
+mod api;
+mod logs;
+mod command_trace;
+mod mcp;
+mod quick_ask;
+mod tray;
+mod tools;
+mod files;
+mod conversations;
+mod profiles;
+mod workspaces;
+mod providers;
+mod knowledge;
+mod retention;
+mod telemetry;
+mod feedback;
 
 // v0.6 modules
 pub mod agent;
 pub mod workflow;
 pub mod sync;
 
+use error::AppError;
 use scheduler::JobScheduler;
 use security::CredentialManager;
 use plugins::PluginExecutor;
@@ -35,6 +55,9 @@ pub struct Message {
 pub struct ChatRequest {
     pub messages: Vec<Message>,
     pub provider: Option<String>,
+    /// Overrides the conversation's generation defaults field by field
+    #[serde(default)]
+    pub params: Option<conversations::generation::GenerationParams>,
 }
 
 /// Chat response
@@ -54,27 +77,30 @@ pub struct FolderPermission {
 
 /// Simple greeting command
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
 /// Get app version
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
 /// Validate folder path
 #[tauri::command]
-fn validate_folder_path(path: &str) -> Result<bool, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+fn validate_folder_path(path: &str) -> Result<bool, AppError> {
     let path_buf = PathBuf::from(path);
     
     if !path_buf.exists() {
-        return Err("Path does not exist".to_string());
+        return Err(AppError::not_found("Path does not exist"));
     }
     
     if !path_buf.is_dir() {
-        return Err("Path is not a directory".to_string());
+        return Err(AppError::invalid_input("Path is not a directory"));
     }
     
     Ok(true)
@@ -82,7 +108,8 @@ fn validate_folder_path(path: &str) -> Result<bool, String> {
 
 /// Check if folder is accessible
 #[tauri::command]
-fn check_folder_access(path: &str, permissions: Vec<FolderPermission>) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+fn check_folder_access(path: &str, permissions: Vec<FolderPermission>) -> Result<String, AppError> {
     let path_buf = PathBuf::from(path);
     
     for perm in permissions {
@@ -92,42 +119,7 @@ fn check_folder_access(path: &str, permissions: Vec<FolderPermission>) -> Result
         }
     }
     
-    Err("No permission to access this folder".to_string())
-}
-
-/// Read file content
-#[tauri::command]
-fn read_file_content(path: &str) -> Result<String, String> {
-    std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read file: {}", e))
-}
-
-/// Write file content
-#[tauri::command]
-fn write_file_content(path: &str, content: &str) -> Result<(), String> {
-    std::fs::write(path, content)
-        .map_err(|e| format!("Failed to write file: {}", e))
-}
-
-/// List directory contents
-#[tauri::command]
-fn list_directory(path: &str) -> Result<Vec<String>, String> {
-    let entries = std::fs::read_dir(path)
-        .map_err(|e| format!("Failed to read directory: {}", e))?;
-    
-    let mut result = Vec::new();
-    for entry in entries.flatten() {
-        if let Ok(name) = entry.file_name().into_string() {
-            let file_type = if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                "dir"
-            } else {
-                "file"
-            };
-            result.push(format!("{}:{}", name, file_type));
-        }
-    }
-    
-    Ok(result)
+    Err(AppError::permission_denied("No permission to access this folder"))
 }
 
 // ============================================================================
@@ -140,22 +132,28 @@ pub struct SchedulerStatus {
     pub running: bool,
     pub job_count: usize,
     pub running_count: usize,
+    /// Executions waiting for a free slot or for their job's previous run
+    pub queue_depth: usize,
+    /// False when another instance of the app executes the jobs
+    pub leader: bool,
 }
 
 /// Start the job scheduler
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn scheduler_start(
     scheduler: tauri::State<'_, Arc<tokio::sync::Mutex<JobScheduler>>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let scheduler = scheduler.lock().await;
-    scheduler.start().await
+    Ok(scheduler.start().await?)
 }
 
 /// Stop the job scheduler
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn scheduler_stop(
     scheduler: tauri::State<'_, Arc<tokio::sync::Mutex<JobScheduler>>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let scheduler = scheduler.lock().await;
     scheduler.stop().await;
     Ok(())
@@ -163,37 +161,44 @@ async fn scheduler_stop(
 
 /// Get scheduler status
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn scheduler_status(
     scheduler: tauri::State<'_, Arc<tokio::sync::Mutex<JobScheduler>>>,
-) -> Result<SchedulerStatus, String> {
+) -> Result<SchedulerStatus, AppError> {
     let scheduler = scheduler.lock().await;
     let running = scheduler.is_running().await;
     let job_count = scheduler.get_jobs().await.len();
     let running_count = scheduler.running_count().await;
+    let queue_depth = scheduler.queue_depth();
+    let leader = scheduler.is_leader();
 
     Ok(SchedulerStatus {
         running,
         job_count,
         running_count,
+        queue_depth,
+        leader,
     })
 }
 
 /// Execute a scheduled job immediately
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn scheduler_execute_job(
     scheduler: tauri::State<'_, Arc<tokio::sync::Mutex<JobScheduler>>>,
     job_id: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let scheduler = scheduler.lock().await;
-    scheduler.execute_now(&job_id).await
+    Ok(scheduler.execute_now(&job_id).await?)
 }
 
 /// Cancel a running job execution
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn scheduler_cancel_execution(
     scheduler: tauri::State<'_, Arc<tokio::sync::Mutex<JobScheduler>>>,
     execution_id: String,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let scheduler = scheduler.lock().await;
     Ok(scheduler.cancel_execution(&execution_id).await)
 }
@@ -204,96 +209,306 @@ async fn scheduler_cancel_execution(
 
 /// List marketplace items
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn marketplace_list_items(
     filters: Option<marketplace::MarketplaceFilters>,
     page: Option<u32>,
     page_size: Option<u32>,
-) -> Result<Vec<marketplace::MarketplaceItem>, String> {
+) -> Result<Vec<marketplace::MarketplaceItem>, AppError> {
     let store = marketplace::MarketplaceStore::default_marketplace();
     let filters = filters.unwrap_or_default();
-    store.list_items(&filters, page.unwrap_or(1), page_size.unwrap_or(20)).await
+    Ok(store.list_items(&filters, page.unwrap_or(1), page_size.unwrap_or(20)).await?)
 }
 
 /// Get marketplace item details
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn marketplace_get_item(
     item_id: String,
-) -> Result<marketplace::MarketplaceItem, String> {
+) -> Result<marketplace::MarketplaceItem, AppError> {
     let store = marketplace::MarketplaceStore::default_marketplace();
-    store.get_item(&item_id).await
+    Ok(store.get_item(&item_id).await?)
 }
 
 /// Search marketplace items
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn marketplace_search_items(
     query: String,
     filters: Option<marketplace::MarketplaceFilters>,
     page: Option<u32>,
     page_size: Option<u32>,
-) -> Result<Vec<marketplace::MarketplaceItem>, String> {
+) -> Result<Vec<marketplace::MarketplaceItem>, AppError> {
     let store = marketplace::MarketplaceStore::default_marketplace();
     let filters = filters.unwrap_or_default();
-    store.search_items(&query, &filters, page.unwrap_or(1), page_size.unwrap_or(20)).await
+    Ok(store.search_items(&query, &filters, page.unwrap_or(1), page_size.unwrap_or(20)).await?)
 }
 
 /// Get marketplace categories
 #[tauri::command]
-async fn marketplace_get_categories() -> Result<Vec<marketplace::MarketplaceCategory>, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+async fn marketplace_get_categories() -> Result<Vec<marketplace::MarketplaceCategory>, AppError> {
+    let store = marketplace::MarketplaceStore::default_marketplace();
+    Ok(store.get_categories().await?)
+}
+
+/// Installer over the app's marketplace folder
+fn marketplace_installer(app_handle: &tauri::AppHandle) -> Result<marketplace::MarketplaceInstaller, String> {
+    let install_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    marketplace::MarketplaceInstaller::new(install_dir.join("marketplace"))
+}
+
+/// Plan installing a marketplace item: missing dependencies and conflicts
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+async fn marketplace_plan_install(
+    item_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<marketplace::deps::InstallPlan, AppError> {
     let store = marketplace::MarketplaceStore::default_marketplace();
-    store.get_categories().await
+    let item = store.get_item(&item_id).await?;
+    let catalog = store.catalog().await?;
+
+    Ok(marketplace_installer(&app_handle)?.plan(&item, &catalog)?)
 }
 
 /// Install marketplace item
+///
+/// Missing dependencies are installed first, once the user has confirmed
+/// them with `confirm_dependencies`. Version conflicts abort the install.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn marketplace_install_item(
     item_id: String,
+    confirm_dependencies: Option<bool>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+    db: tauri::State<'_, db::DbState>,
+) -> Result<String, AppError> {
     let store = marketplace::MarketplaceStore::default_marketplace();
     let item = store.get_item(&item_id).await?;
+    let catalog = store.catalog().await?;
 
-    let install_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let mut installer = marketplace_installer(&app_handle)?;
+    let plan = installer.plan(&item, &catalog)?;
+    if !plan.conflicts.is_empty() {
+        return Err(AppError::invalid_input(format!("Dependency conflicts: {}", plan.conflict_summary())));
+    }
+    if plan.needs_confirmation() && !confirm_dependencies.unwrap_or(false) {
+        let missing: Vec<String> = plan
+            .to_install
+            .iter()
+            .map(|p| format!("{} v{}", p.name, p.version))
+            .collect();
+        return Err(AppError::invalid_input(format!(
+            "{} requires installing: {}. Confirm to install them",
+            item.name,
+            missing.join(", ")
+        )));
+    }
 
-    let marketplace_dir = install_dir.join("marketplace");
-    let mut installer = marketplace::MarketplaceInstaller::new(marketplace_dir)?;
+    let mut installed = Vec::new();
+    let mut messages = Vec::new();
+    for planned in &plan.to_install {
+        let dependency = catalog
+            .get(&planned.id)
+            .ok_or_else(|| AppError::not_found(format!("Item not found: {}", planned.id)))?;
+        messages.push(installer.install(dependency).await?);
+        installed.push((dependency.id.clone(), dependency.version.clone()));
+    }
+    messages.push(installer.install(&item).await?);
+    installed.push((item.id.clone(), item.version.clone()));
 
-    installer.install(&item).await
+    let telemetry = {
+        let conn = db.conn.lock()?;
+        marketplace::reviews::telemetry_enabled(&conn)?
+    };
+    if telemetry {
+        tauri::async_runtime::spawn(async move {
+            for (id, version) in installed {
+                if let Err(e) = store.post_install_event(&id, &version).await {
+                    tracing::debug!("Install telemetry for {} not sent: {}", id, e);
+                }
+            }
+        });
+    }
+
+    Ok(messages.join("\n"))
 }
 
-/// Uninstall marketplace item
+/// Installed items depending on a marketplace item
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+async fn marketplace_dependents(
+    item_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<String>, AppError> {
+    Ok(marketplace_installer(&app_handle)?.dependents(&item_id))
+}
+
+/// Uninstall marketplace item; refused while other items depend on it
+/// unless `force` is set
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn marketplace_uninstall_item(
     item_id: String,
+    force: Option<bool>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    let install_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+) -> Result<String, AppError> {
+    let mut installer = marketplace_installer(&app_handle)?;
+
+    let dependents = installer.dependents(&item_id);
+    if !dependents.is_empty() && !force.unwrap_or(false) {
+        return Err(AppError::invalid_input(format!("{} is required by: {}", item_id, dependents.join(", "))));
+    }
+
+    Ok(installer.uninstall(&item_id).await?)
+}
+
+/// Update a marketplace item to the latest version
+///
+/// Plugins are rolled back to the previous version when the new one fails
+/// to start or crashes within the grace period.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+async fn marketplace_update_item(
+    item_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<marketplace::update::UpdateReport, AppError> {
+    let store = marketplace::MarketplaceStore::default_marketplace();
+    let item = store.get_item(&item_id).await?;
+
+    let mut installer = marketplace_installer(&app_handle)?;
+    Ok(marketplace::update::update_item(&mut installer, &item, marketplace::update::GRACE_PERIOD).await?)
+}
+
+/// Get the marketplace auto-update policy
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+fn marketplace_get_auto_update(
+    db: tauri::State<'_, db::DbState>,
+) -> Result<marketplace::update::AutoUpdatePolicy, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(marketplace::update::get_policy(&conn)?)
+}
+
+/// Set the marketplace auto-update policy
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+fn marketplace_set_auto_update(
+    policy: marketplace::update::AutoUpdatePolicy,
+    db: tauri::State<'_, db::DbState>,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(marketplace::update::set_policy(&conn, policy)?)
+}
+
+/// Rate and review a marketplace item
+///
+/// The review is saved locally first; when the registry cannot be reached it
+/// is returned unsynced and sent again the next time reviews are listed.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+async fn marketplace_submit_review(
+    item_id: String,
+    rating: u8,
+    comment: Option<String>,
+    db: tauri::State<'_, db::DbState>,
+) -> Result<marketplace::reviews::MarketplaceReview, AppError> {
+    let store = marketplace::MarketplaceStore::default_marketplace();
+    store.get_item(&item_id).await?;
+
+    let mut review = {
+        let conn = db.conn.lock()?;
+        marketplace::reviews::save_own(&conn, &item_id, rating, comment.as_deref().unwrap_or_default())?
+    };
+    match store.post_review(&review).await {
+        Ok(()) => {
+            let conn = db.conn.lock()?;
+            marketplace::reviews::mark_synced(&conn, &review.id)?;
+            review.synced = true;
+        }
+        Err(e) => tracing::warn!("Review of {} saved locally: {}", item_id, e),
+    }
+    Ok(review)
+}
+
+/// List an item's reviews, falling back to the cached page when offline
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+async fn marketplace_list_reviews(
+    item_id: String,
+    page: Option<u32>,
+    db: tauri::State<'_, db::DbState>,
+) -> Result<marketplace::reviews::ReviewPage, AppError> {
+    let store = marketplace::MarketplaceStore::default_marketplace();
+    let page = page.unwrap_or(1).max(1);
+
+    let pending = {
+        let conn = db.conn.lock()?;
+        marketplace::reviews::own_review(&conn, &item_id)?.filter(|r| !r.synced)
+    };
+    if let Some(review) = pending {
+        if store.post_review(&review).await.is_ok() {
+            let conn = db.conn.lock()?;
+            marketplace::reviews::mark_synced(&conn, &review.id)?;
+        }
+    }
 
-    let marketplace_dir = install_dir.join("marketplace");
-    let mut installer = marketplace::MarketplaceInstaller::new(marketplace_dir)?;
+    let fetched = store
+        .fetch_reviews(&item_id, page, marketplace::reviews::PAGE_SIZE)
+        .await;
+    let conn = db.conn.lock()?;
+    let cached = match fetched {
+        Ok(reviews) => {
+            marketplace::reviews::cache_page(&conn, &item_id, page, &reviews)?;
+            false
+        }
+        Err(e) => {
+            tracing::debug!("Using cached reviews for {}: {}", item_id, e);
+            true
+        }
+    };
+    Ok(marketplace::reviews::ReviewPage {
+        reviews: marketplace::reviews::cached_page(&conn, &item_id, page)?,
+        item_id,
+        page,
+        cached,
+    })
+}
+
+/// Whether anonymous install telemetry is enabled
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+fn marketplace_get_install_telemetry(db: tauri::State<'_, db::DbState>) -> Result<bool, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(marketplace::reviews::telemetry_enabled(&conn)?)
+}
 
-    installer.uninstall(&item_id).await
+/// Opt in or out of anonymous install telemetry
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+fn marketplace_set_install_telemetry(enabled: bool, db: tauri::State<'_, db::DbState>) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(marketplace::reviews::set_telemetry(&conn, enabled)?)
 }
 
 /// Check for marketplace updates
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 async fn marketplace_check_updates(
     app_handle: tauri::AppHandle,
-) -> Result<Vec<String>, String> {
-    let install_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-
-    let marketplace_dir = install_dir.join("marketplace");
-    let installer = marketplace::MarketplaceInstaller::new(marketplace_dir)?;
+) -> Result<Vec<String>, AppError> {
+    Ok(marketplace_installer(&app_handle)?.check_updates().await?)
+}
 
-    installer.check_updates().await
+/// Serve MCP over stdio for an external client instead of opening the app;
+/// returns the process exit code
+pub fn run_mcp_server(args: &[String]) -> i32 {
+    mcp::run_cli(args)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -302,48 +517,249 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
+            // Write structured logs to the app data folder
+            let log_dir = app.path().app_data_dir()?.join("logs");
+            let commands = command_trace::CommandHistory::default();
+            app.manage(commands.clone());
+            match logs::init(&log_dir, commands) {
+                Ok(log_state) => {
+                    app.manage(log_state);
+                }
+                Err(e) => eprintln!("Logging disabled: {}", e),
+            }
+
             // Initialize database
             let db_state = db::DbState::new(app.handle())
                 .expect("Failed to initialize database");
-            let db_path = db_state.db_path.clone();
+            let db_path = db_state.path();
 
             app.manage(db_state);
 
             // Initialize sidecar state
-            let sidecar_state = std::sync::Mutex::new(sidecar::SidecarState::new());
-            app.manage(sidecar_state);
+            let sidecar_state = sidecar::SidecarState::new();
+            let host_app = app.handle().clone();
+            sidecar_state.set_host_handler(Arc::new(move |method, params| match method {
+                "authorize_tool" => tools::policy::authorize_request(&host_app, params),
+                "execute_shell_command" => tools::shell::run_request(&host_app, params),
+                "search_in_folder" => files::search::search_request(&host_app, params),
+                "read_file" => files::commands::read_request(&host_app, params),
+                "write_file" => files::commands::write_request(&host_app, params),
+                "extract_document_text" => files::extract::extract_request(&host_app, params),
+                "ocr_image" => files::ocr::ocr_request(&host_app, params),
+                "list_calendar_events" => integration::calendar::events_request(&host_app, params),
+                "screen_tool_result" => security::guardrails::screen_tool_result_request(&host_app, params),
+                "record_action" => tools::audit::record_request(&host_app, params),
+                "record_telemetry" => telemetry::record_request(&host_app, params),
+                "call_plugin_tool" => plugins::tools::call_request(&host_app, params),
+                _ => Err(format!("Unknown host method: {}", method)),
+            }));
+            app.manage(std::sync::Mutex::new(sidecar_state));
+            sidecar::supervise(app.handle().clone());
+
+            // Initialize tool permission policies
+            app.manage(tools::ToolPolicyState::new());
 
             // Initialize credential manager
-            let credential_manager = CredentialManager::default()
-                .expect("Failed to initialize credential manager");
+            let credential_manager = match app.path().app_data_dir() {
+                Ok(app_dir) => profiles::active_credentials(&app_dir),
+                Err(_) => CredentialManager::default().expect("Failed to initialize credential manager"),
+            };
+
+            // Encrypt secret-bearing columns with the keychain-held column key,
+            // including rows written before they were encrypted
+            {
+                let db = app.state::<db::DbState>();
+                let conn = db.conn.lock().map_err(|e| e.to_string())?;
+                security::enable_column_encryption(&conn, &credential_manager);
+            }
             app.manage(std::sync::Mutex::new(credential_manager));
 
+            // Initialize sensitive conversation session
+            app.manage(std::sync::Mutex::new(security::SensitiveSession::new()));
+
+            // Initialize wake word listener
+            app.manage(std::sync::Mutex::new(voice::wake_word::WakeWordListener::new()));
+
+            // Initialize streaming transcription state
+            app.manage(std::sync::Mutex::new(voice::stream::TranscriptionStreams::new()));
+
+            // Initialize voice conversation language state
+            app.manage(std::sync::Mutex::new(voice::commands::VoiceConversationManager::new()));
+
+            // Initialize speech playback queue
+            app.manage(std::sync::Mutex::new(voice::playback::TtsPlayer::new()));
+
+            // Initialize barge-in monitoring for voice conversations
+            app.manage(std::sync::Mutex::new(voice::barge_in::BargeInMonitor::new()));
+
+            // Initialize Whisper model download manager
+            app.manage(voice::models::ModelDownloads::new());
+
+            // Initialize S3 clients and cloud transfer tracking
+            app.manage(integration::cloud::s3::S3Manager::new());
+
+            // Initialize external database connection pools
+            app.manage(tokio::sync::Mutex::new(integration::DatabasePoolManager::new()));
+
+            // Initialize pending AI commit confirmations
+            app.manage(integration::git::ai_commit::PendingAiCommits::new());
+
             // Initialize plugin executor
-            let plugin_executor = PluginExecutor::new();
+            let plugin_executor = PluginExecutor::new()
+                .with_storage_db(PathBuf::from(&db_path));
+            let plugins_dir = plugin_executor.plugins_dir().to_path_buf();
+            let plugin_task_runner = plugin_executor.task_runner();
+            app.manage(plugin_executor.tool_runner());
             app.manage(std::sync::Mutex::new(plugin_executor));
 
+            // Sample plugin memory and CPU use, suspending plugins that stay over their limits
+            let sampler_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tauri::Emitter;
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(2));
+                loop {
+                    interval.tick().await;
+                    let suspended = {
+                        let executor = sampler_handle.state::<std::sync::Mutex<PluginExecutor>>();
+                        let Ok(executor) = executor.lock() else { continue };
+                        executor.sample_resources()
+                    };
+                    for (id, reason) in suspended {
+                        let payload = serde_json::json!({ "id": id, "reason": reason });
+                        if let Err(e) = sampler_handle.emit("plugins://suspended", &payload) {
+                            tracing::warn!("Failed to emit plugin suspension: {}", e);
+                        }
+                    }
+                }
+            });
+
+            // Initialize v0.6 workflow state
+            let agent_runtime: Arc<dyn workflow::AgentRuntime> =
+                Arc::new(workflow::commands::SidecarRuntime::new(app.handle().clone()));
+            let workflow_state = Arc::new(workflow::commands::WorkflowState::with_agent_runtime(
+                agent_runtime.clone(),
+            ));
+            app.manage(workflow_state.clone());
+
             // Initialize job scheduler
             let scheduler_config = scheduler::SchedulerConfig {
                 check_interval_secs: 60,
                 db_path: db_path.clone(),
                 max_concurrent_jobs: 5,
             };
-            let job_scheduler = Arc::new(tokio::sync::Mutex::new(JobScheduler::new(scheduler_config)));
-            app.manage(job_scheduler);
+            let job_scheduler = JobScheduler::new(scheduler_config)
+                .with_plugin_runner(plugin_task_runner)
+                .with_workflow_runner(workflow::WorkflowJobRunner::new(
+                    workflow_state,
+                    Some(app.handle().clone()),
+                ))
+                .with_agent_runtime(agent_runtime)
+                .with_app_handle(app.handle().clone());
+
+            // Repair state left inconsistent by a previous crash. Only the
+            // scheduler's leader does this; in a follower the state belongs
+            // to the leader's live work.
+            let marketplace_dir = app.path().app_data_dir().ok().map(|d| d.join("marketplace"));
+            let recovery_report = {
+                let db = app.state::<db::DbState>();
+                let conn = db.conn.lock().map_err(|e| e.to_string())?;
+                let report = recovery::run_recovery_as_leader(&job_scheduler, &conn, &plugins_dir, marketplace_dir);
+
+                // Workflows live in memory, so schedule triggers from an earlier
+                // session have nothing to run until they are registered again
+                if !report.skipped {
+                    if let Err(e) = workflow::schedule::disable_stale_jobs(&conn) {
+                        tracing::warn!("Failed to disable stale workflow jobs: {}", e);
+                    }
+                }
+                report
+            };
+            app.manage(std::sync::Mutex::new(recovery_report));
+            app.manage(Arc::new(tokio::sync::Mutex::new(job_scheduler)));
 
             // Initialize v0.6 agent state
             let agent_state = Arc::new(agent::commands::AgentState::new());
             app.manage(agent_state);
 
-            // Initialize v0.6 workflow state
-            let workflow_state = Arc::new(workflow::commands::WorkflowState::new());
-            app.manage(workflow_state);
+            // Initialize sub-agent task executor
+            app.manage(agent::orchestrator::SubAgentExecutor::new(
+                agent::orchestrator::DEFAULT_SUB_AGENT_CONCURRENCY,
+            ));
 
             // Initialize v0.6 sync state
             let sync_state = Arc::new(sync::commands::SyncState::new());
             app.manage(sync_state);
 
+            // Bind the quick ask hotkey
+            if let Err(e) = quick_ask::register(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+
+            // Show scheduler and sync status in the system tray
+            if let Err(e) = tray::init(app.handle()) {
+                tracing::warn!("{}", e);
+            }
+
+            // Start the REST API if the user enabled it
+            app.manage(api::ApiServerState::new());
+            let api_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = api::restart(&api_handle).await {
+                    tracing::warn!("REST API not started: {}", e);
+                }
+            });
+
+            // Delete shared bundles whose expiry passed while the app was closed
+            let share_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let purged = conversations::share::purge_expired(
+                    &share_handle.state::<db::DbState>(),
+                    &share_handle.state::<integration::cloud::s3::S3Manager>(),
+                    &share_handle.state::<std::sync::Mutex<CredentialManager>>(),
+                )
+                .await;
+                if let Err(e) = purged {
+                    tracing::warn!("Failed to delete expired shares: {}", e);
+                }
+            });
+
+            // Report or install marketplace updates as the auto-update policy says
+            let update_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tauri::Emitter;
+                let policy = {
+                    let db = update_handle.state::<db::DbState>();
+                    let Ok(conn) = db.conn.lock() else { return };
+                    marketplace::update::get_policy(&conn).unwrap_or_default()
+                };
+                if policy == marketplace::update::AutoUpdatePolicy::Off {
+                    return;
+                }
+                let store = marketplace::MarketplaceStore::default_marketplace();
+                let catalog = match store.catalog().await {
+                    Ok(catalog) => catalog,
+                    Err(e) => {
+                        tracing::warn!("Failed to check marketplace updates: {}", e);
+                        return;
+                    }
+                };
+                let Ok(mut installer) = marketplace_installer(&update_handle) else { return };
+                let reports = marketplace::update::auto_update(
+                    &mut installer,
+                    &catalog,
+                    policy,
+                    marketplace::update::GRACE_PERIOD,
+                )
+                .await;
+                if !reports.is_empty() {
+                    if let Err(e) = update_handle.emit("marketplace://updates", &reports) {
+                        tracing::warn!("Failed to emit marketplace updates: {}", e);
+                    }
+                }
+            });
+
             // Load jobs from database and start scheduler
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -377,23 +793,100 @@ pub fn run() {
             get_version,
             validate_folder_path,
             check_folder_access,
-            read_file_content,
-            write_file_content,
-            list_directory,
+            files::commands::read_file_content,
+            files::commands::write_file_content,
+            files::commands::list_directory,
+            files::binary::read_file_bytes,
+            files::binary::write_file_bytes,
+            files::binary::stream_file,
+            files::search::search_in_folder,
+            files::extract::extract_document_text,
+            files::ocr::ocr_image,
+            files::ocr::read_attachment,
+            files::ocr::get_ocr_settings,
+            files::ocr::save_ocr_settings,
+            files::ocr::list_ocr_languages,
             sidecar::init_agent,
             sidecar::agent_chat,
+            sidecar::compare_chat,
             sidecar::get_tools,
+            tools::policy::tool_policy_list,
+            tools::policy::tool_policy_set,
+            tools::policy::tool_policy_reset,
+            tools::policy::tool_confirm,
+            tools::risk::tool_risk_settings_get,
+            tools::risk::tool_risk_settings_set,
+            tools::shell::execute_shell_command,
+            tools::shell::shell_audit_list,
+            tools::shell::shell_rule_list,
+            tools::shell::shell_rule_set,
+            tools::shell::shell_rule_remove,
+            tools::audit::query_audit_log,
+            tools::audit::export_audit_log,
             sidecar::configure_providers,
             sidecar::shutdown_agent,
+            sidecar::sidecar_health,
             sidecar::execute_recipe,
             sidecar::execute_skill,
             sidecar::execute_prompt,
+            // Quick ask commands
+            quick_ask::get_quick_ask_shortcut,
+            quick_ask::set_quick_ask_shortcut,
+            quick_ask::open_quick_ask,
+            quick_ask::hide_quick_ask,
+            quick_ask::quick_ask,
+            quick_ask::clear_quick_ask,
             // Database commands
             db::load_conversations,
             db::save_conversation,
             db::delete_conversation,
             db::load_messages,
             db::save_message,
+            db::save_messages,
+            db::set_conversation_sensitive,
+            db::unlock_conversation,
+            db::lock_conversation,
+            db::lock_all_conversations,
+            // Conversation tag and smart folder commands
+            conversations::import::import_external_history,
+            conversations::tags::add_tag,
+            conversations::tags::remove_tag,
+            conversations::tags::list_tags,
+            conversations::tags::list_conversations_by_tag,
+            conversations::tags::filter_conversations,
+            conversations::tags::list_smart_folders,
+            conversations::tags::save_smart_folder,
+            conversations::tags::delete_smart_folder,
+            conversations::tags::list_smart_folder_conversations,
+            // Pinned message and bookmark commands
+            conversations::pins::pin_message,
+            conversations::pins::unpin_message,
+            conversations::pins::list_pinned,
+            conversations::pins::add_conversation_bookmark,
+            conversations::pins::delete_conversation_bookmark,
+            conversations::pins::list_conversation_bookmarks,
+            // Conversation sharing commands
+            conversations::share::share_conversation,
+            conversations::share::list_conversation_shares,
+            conversations::share::revoke_conversation_share,
+            conversations::share::purge_expired_shares,
+            // Similar conversation commands
+            conversations::similar::find_similar_conversations,
+            conversations::similar::index_conversation_embeddings,
+            conversations::recipe::extract_recipe_from_conversation,
+            // Response comparison commands
+            conversations::compare::get_message_comparison,
+            conversations::compare::select_compared_response,
+            // Conversation system prompt commands
+            conversations::system_prompt::set_conversation_system_prompt,
+            conversations::system_prompt::get_system_prompt_history,
+            // Conversation generation parameter commands
+            conversations::generation::get_conversation_generation_params,
+            conversations::generation::set_conversation_generation_params,
+            // Message feedback commands
+            feedback::submit_feedback,
+            feedback::get_conversation_feedback,
+            feedback::export_feedback,
             db::load_folder_permissions,
             db::add_folder_permission,
             db::remove_folder_permission,
@@ -403,6 +896,7 @@ pub fn run() {
             db::get_skill,
             db::create_skill,
             db::update_skill,
+            db::import_skills,
             db::delete_skill,
             db::search_skills,
             // Recipe commands
@@ -420,6 +914,10 @@ pub fn run() {
             db::update_sub_agent,
             db::delete_sub_agent,
             db::assign_sub_agent_task,
+            db::cancel_sub_agent_task,
+            agent::pipeline::orchestrate_task,
+            agent::pipeline::get_orchestration_run,
+            agent::pipeline::list_orchestration_runs,
             // Cron job commands (v0.3)
             db::list_cron_jobs,
             db::create_cron_job,
@@ -433,14 +931,46 @@ pub fn run() {
             scheduler_status,
             scheduler_execute_job,
             scheduler_cancel_execution,
+            tray::get_app_status,
+            // MCP server clients
+            mcp::clients::list_mcp_clients,
+            mcp::clients::save_mcp_client,
+            mcp::clients::delete_mcp_client,
+            mcp::clients::get_mcp_launch_config,
+            // REST API commands
+            api::get_api_config,
+            api::set_api_config,
+            api::regenerate_api_token,
+            api::get_api_status,
+            scheduler::history::prune_job_history,
+            scheduler::history::get_job_history_retention,
+            scheduler::history::set_job_history_retention,
+            scheduler::hold::pause_job,
+            scheduler::hold::snooze_job,
+            scheduler::hold::resume_job,
+            scheduler::logs::get_execution_logs,
+            retention::get_retention_policies,
+            retention::set_retention_policies,
+            retention::preview_retention_impact,
+            retention::apply_retention_now,
+            scheduler::natural::parse_schedule,
             // Marketplace commands
             marketplace_list_items,
             marketplace_get_item,
             marketplace_search_items,
             marketplace_get_categories,
+            marketplace_plan_install,
             marketplace_install_item,
+            marketplace_dependents,
             marketplace_uninstall_item,
             marketplace_check_updates,
+            marketplace_update_item,
+            marketplace_get_auto_update,
+            marketplace_set_auto_update,
+            marketplace_submit_review,
+            marketplace_list_reviews,
+            marketplace_get_install_telemetry,
+            marketplace_set_install_telemetry,
             // Plugin commands (v0.4)
             db::list_plugins,
             db::get_plugin,
@@ -465,6 +995,10 @@ pub fn run() {
             collaboration::template_commands::get_template_versions,
             collaboration::template_commands::create_template_version,
             collaboration::template_commands::rollback_template,
+            collaboration::template_commands::diff_template_versions,
+            collaboration::template_commands::merge_template_versions,
+            collaboration::bundle::export_bundle,
+            collaboration::bundle::import_bundle,
             // Template sharing commands (v0.5)
             collaboration::template_commands::share_template_to_team,
             collaboration::template_commands::get_team_templates,
@@ -482,33 +1016,139 @@ pub fn run() {
             voice::stt::init_stt,
             voice::stt::transcribe,
             voice::stt::get_available_models,
+            voice::models::download_model,
+            voice::models::cancel_model_download,
+            voice::models::delete_model,
+            voice::stream::transcribe_stream_start,
+            voice::stream::transcribe_stream_feed,
+            voice::stream::transcribe_stream_finish,
             voice::tts::init_tts,
             voice::tts::synthesize,
             voice::tts::get_available_voices,
+            voice::tts_providers::set_tts_provider,
+            voice::playback::tts_speak,
+            voice::playback::tts_pause,
+            voice::playback::tts_resume,
+            voice::playback::tts_stop,
             // Voice command parsing (v0.5)
             voice::commands::parse_voice_command,
             voice::commands::detect_voice_language,
             voice::commands::validate_voice_command,
             voice::commands::get_voice_command_patterns,
+            voice::commands::pin_voice_language,
+            voice::commands::get_pinned_voice_language,
+            // Wake word commands
+            voice::wake_word::start_wake_word_listening,
+            voice::wake_word::stop_wake_word_listening,
+            voice::wake_word::get_wake_word_status,
             // Voice conversation commands (v0.5)
             sidecar::execute_voice_command,
             sidecar::start_voice_conversation,
             sidecar::continue_voice_conversation,
             sidecar::end_voice_conversation,
             // Integration commands (v0.4)
+            integration::database_connect,
+            integration::database_disconnect,
+            integration::database_execute_query,
+            integration::database_cancel_query,
+            integration::database_get_schema,
+            integration::database_list_tables,
+            integration::database_describe_schema,
+            integration::database_query_audit,
             integration::test_database_connection,
             integration::get_database_connection_string,
             integration::validate_git_repository,
             integration::get_git_status,
             integration::get_git_current_commit,
+            integration::git::operations::git_list_branches,
+            integration::git::operations::git_checkout_branch,
+            integration::git::operations::git_create_branch,
+            integration::git::operations::git_log,
+            integration::git::operations::git_diff,
+            integration::git::ai_commit::git_ai_commit,
+            integration::git::ai_commit::git_ai_commit_respond,
             integration::test_cloud_connection,
             integration::list_cloud_objects,
+            integration::get_cloud_object,
+            integration::put_cloud_object,
+            integration::delete_cloud_object,
+            integration::head_cloud_object,
+            integration::cancel_cloud_transfer,
+            integration::cloud::s3::s3_list,
+            integration::cloud::s3::s3_get,
+            integration::cloud::s3::s3_upload,
+            integration::cloud::s3::s3_download,
+            integration::cloud::s3::s3_delete,
+            integration::cloud::s3::s3_sync_folder,
             integration::get_cloud_endpoint,
+            integration::email::get_email_settings,
+            integration::email::save_email_settings,
+            integration::email::test_email_settings,
+            integration::channels::list_notification_channels,
+            integration::channels::save_notification_channel,
+            integration::channels::delete_notification_channel,
+            integration::channels::send_channel_message,
+            integration::calendar::list_calendar_sources,
+            integration::calendar::save_calendar_source,
+            integration::calendar::delete_calendar_source,
+            integration::calendar::sync_calendars,
+            integration::calendar::list_upcoming_events,
             // Security commands (v0.5)
             security::credentials_set_password,
             security::credentials_get_password,
             security::credentials_delete_password,
             security::run_migration,
+            // Profile commands
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
+            providers::list_provider_profiles,
+            providers::upsert_provider_profile,
+            providers::delete_provider_profile,
+            providers::fetch_provider_models,
+            providers::probe_provider_endpoint,
+            workspaces::create_workspace,
+            workspaces::update_workspace,
+            workspaces::list_workspaces,
+            workspaces::delete_workspace,
+            workspaces::get_active_workspace,
+            workspaces::set_active_workspace,
+            workspaces::assign_conversation_workspace,
+            workspaces::list_workspace_resources,
+            workspaces::get_workspace_defaults,
+            workspaces::set_workspace_defaults,
+            knowledge::index::get_index_status,
+            knowledge::index::index_workspace_now,
+            knowledge::index::set_index_ignore_patterns,
+            knowledge::index::search_knowledge,
+            // Guardrail commands
+            security::guardrails::get_guardrail_events,
+            security::guardrails::clear_guardrail_events,
+            security::guardrails::get_guardrail_settings,
+            security::guardrails::save_guardrail_settings,
+            security::guardrails::get_guardrail_override,
+            security::guardrails::set_guardrail_override,
+            security::guardrails::clear_guardrail_override,
+            // Recovery commands
+            recovery::get_recovery_report,
+            // Backup commands
+            db::backup::backup_database,
+            db::backup::restore_database,
+            db::backup::list_backups,
+            db::backup::get_backup_retention,
+            db::backup::set_backup_retention,
+            db::health::get_db_health,
+            db::export::export_all_data,
+            // Schema migration commands
+            db::migrations::get_schema_version,
+            db::migrations::migrate_database,
+            // Analytics commands
+            analytics::get_analytics,
+            telemetry::get_performance_stats,
+            // Log viewer commands
+            logs::query_logs,
+            logs::export_logs,
+            command_trace::get_slow_commands,
             // Cloud storage commands (v0.5)
             db::list_cloud_storages,
             db::create_cloud_storage,
@@ -520,11 +1160,23 @@ pub fn run() {
             // Plugin execution commands (v0.5)
             plugins::plugin_execute,
             plugins::plugin_get_resource_usage,
+            plugins::plugin_get_resource_samples,
+            plugins::plugin_resume,
+            plugins::plugin_get_sandbox_violations,
             plugins::plugin_send_message,
             plugins::plugin_get_messages,
+            plugins::plugin_ack_messages,
+            plugins::plugin_subscribe,
+            plugins::plugin_unsubscribe,
+            plugins::plugin_get_dead_letters,
+            plugins::plugin_clear_dead_letters,
+            plugins::plugin_get_panels,
+            plugins::plugin_panel_action,
             plugins::plugin_stop,
             plugins::plugin_restart,
             plugins::plugin_list_running,
+            plugins::plugin_get_storage_usage,
+            plugins::plugin_clear_storage,
             // Agent commands (v0.6)
             agent::commands::agent_multimodal_process,
             agent::commands::agent_analyze_image,
@@ -535,6 +1187,7 @@ pub fn run() {
             agent::commands::agent_context_is_near_limit,
             agent::commands::agent_context_compress,
             agent::commands::agent_context_set_strategy,
+            agent::commands::agent_context_retain_pinned,
             agent::commands::agent_orchestrator_add_task,
             agent::commands::agent_orchestrator_execute_all,
             agent::commands::agent_orchestrator_queue_length,
@@ -553,12 +1206,18 @@ pub fn run() {
             workflow::commands::workflow_get_execution,
             workflow::commands::workflow_get_executions,
             workflow::commands::workflow_update_execution,
+            workflow::commands::replay_workflow_execution,
+            workflow::commands::workflow_set_variables,
+            workflow::commands::workflow_get_variables,
+            workflow::commands::workflow_validate,
             workflow::commands::workflow_register_trigger,
             workflow::commands::workflow_unregister_trigger,
             workflow::commands::workflow_list_triggers,
             workflow::commands::workflow_trigger_count,
             // Sync commands (v0.6)
             sync::commands::sync_now,
+            sync::commands::sync_status,
+            sync::commands::sync_configure,
             sync::commands::sync_queue_upload,
             sync::commands::sync_queue_download,
             sync::commands::sync_queue_delete,
@@ -568,6 +1227,8 @@ pub fn run() {
             sync::commands::sync_set_conflict_strategy,
             sync::commands::sync_detect_conflict,
             sync::commands::sync_resolve_conflict,
+            sync::commands::list_sync_conflicts,
+            sync::commands::resolve_conflict,
             sync::commands::sync_offline_push,
             sync::commands::sync_offline_pop_ready,
             sync::commands::sync_offline_peek,
@@ -616,6 +1277,7 @@ mod tests {
                 content: "Test".to_string(),
             }],
             provider: Some("openai".to_string()),
+            params: None,
         };
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("openai"));
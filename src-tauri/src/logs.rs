@@ -0,0 +1,321 @@
+// Application logs - Structured JSON log files and the log viewer API
+//
+// `tracing` events are written as JSON lines to daily files in the `logs`
+// directory of the app data folder, keeping the newest `MAX_LOG_FILES`.
+// `query_logs` reads them back newest first for the log viewer, and
+// `export_logs` zips them so they can be attached to bug reports. The
+// command spans traced by `command_trace` are recorded next to the files,
+// whatever the filter.
+
+use chrono::{DateTime, Utc};
+use crate::command_trace::{self, CommandHistory, CommandLayer};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+/// Log file name prefix and suffix: `app.YYYY-MM-DD.log`
+const FILE_PREFIX: &str = "app";
+const FILE_SUFFIX: &str = "log";
+
+/// Daily log files kept
+const MAX_LOG_FILES: usize = 7;
+
+/// Filter used unless `RUST_LOG` is set
+const DEFAULT_FILTER: &str = "info";
+
+/// Entries returned by a query unless a limit is given, and the most allowed
+const DEFAULT_LIMIT: usize = 200;
+const MAX_LIMIT: usize = 5000;
+
+/// Log directory, managed as app state once logging is initialized
+pub struct LogState {
+    pub dir: PathBuf,
+    /// Flushes buffered lines when the app exits
+    _guard: WorkerGuard,
+}
+
+/// Parsed log line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Event fields other than the message
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Created log archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogExport {
+    pub path: String,
+    pub files: usize,
+    pub size_bytes: u64,
+}
+
+/// JSON formatting layer writing to `writer`
+fn json_layer<S, W>(writer: W) -> impl tracing_subscriber::Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .with_current_span(false)
+        .with_span_list(false)
+        .with_writer(writer)
+}
+
+/// Install the global subscriber writing to rotating files in `dir` and
+/// recording finished commands into `commands`
+pub fn init(dir: &Path, commands: CommandHistory) -> Result<LogState, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix(FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let command_filter = tracing_subscriber::filter::filter_fn(|metadata| metadata.target() == command_trace::TARGET);
+    tracing_subscriber::registry()
+        .with(json_layer(writer).with_filter(filter))
+        .with(CommandLayer::new(commands).with_filter(command_filter))
+        .try_init()
+        .map_err(|e| format!("Failed to install log subscriber: {}", e))?;
+
+    Ok(LogState { dir: dir.to_path_buf(), _guard: guard })
+}
+
+/// Log files in `dir`, oldest first; daily names sort by date
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read log directory: {}", e)),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(FILE_PREFIX) && n.ends_with(FILE_SUFFIX))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Severity rank of a level name; unknown names rank as TRACE
+fn severity(level: &str) -> u8 {
+    match level.to_ascii_uppercase().as_str() {
+        "ERROR" => 4,
+        "WARN" => 3,
+        "INFO" => 2,
+        "DEBUG" => 1,
+        _ => 0,
+    }
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let text = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let (timestamp, level, target) = (text("timestamp"), text("level"), text("target"));
+
+    let mut fields = match value.get_mut("fields").map(serde_json::Value::take) {
+        Some(serde_json::Value::Object(fields)) => fields,
+        _ => serde_json::Map::new(),
+    };
+    let message = match fields.remove("message") {
+        Some(serde_json::Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Some(LogEntry { timestamp, level, target, message, fields })
+}
+
+/// Log entries newest first
+///
+/// `filter` matches the message, target or fields case-insensitively, `level`
+/// is the lowest severity included and `since` an RFC 3339 time.
+pub fn query(
+    dir: &Path,
+    filter: Option<&str>,
+    level: Option<&str>,
+    since: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let since = since
+        .map(|s| DateTime::parse_from_rfc3339(s).map(|t| t.with_timezone(&Utc)))
+        .transpose()
+        .map_err(|e| format!("Invalid since time: {}", e))?;
+    let min_severity = level.map(severity).unwrap_or(0);
+    let filter = filter.filter(|f| !f.is_empty()).map(str::to_lowercase);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let matches = |entry: &LogEntry| {
+        severity(&entry.level) >= min_severity
+            && filter.as_ref().is_none_or(|f| {
+                entry.message.to_lowercase().contains(f)
+                    || entry.target.to_lowercase().contains(f)
+                    || serde_json::Value::Object(entry.fields.clone()).to_string().to_lowercase().contains(f)
+            })
+    };
+
+    let mut entries = Vec::new();
+    for path in log_files(dir)?.iter().rev() {
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut file_entries: Vec<LogEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_line(&line))
+            .collect();
+        file_entries.reverse();
+
+        for entry in file_entries {
+            let time = DateTime::parse_from_rfc3339(&entry.timestamp).ok();
+            if let (Some(since), Some(time)) = (since, time) {
+                if time < since {
+                    // Everything older, in this file and the ones before it
+                    return Ok(entries);
+                }
+            }
+            if matches(&entry) {
+                entries.push(entry);
+                if entries.len() == limit {
+                    return Ok(entries);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Zip every log file into `path`
+pub fn export(dir: &Path, path: &Path) -> Result<LogExport, String> {
+    let files = log_files(dir)?;
+    let archive = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(archive);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let zip_error = |e: &dyn std::fmt::Display| format!("Failed to write log archive: {}", e);
+
+    for file in &files {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or(FILE_PREFIX);
+        let data = std::fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        zip.start_file(name, options).map_err(|e| zip_error(&e))?;
+        zip.write_all(&data).map_err(|e| zip_error(&e))?;
+    }
+
+    let info = serde_json::json!({
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "exportedAt": Utc::now().to_rfc3339(),
+    });
+    zip.start_file("info.json", options).map_err(|e| zip_error(&e))?;
+    zip.write_all(info.to_string().as_bytes()).map_err(|e| zip_error(&e))?;
+    zip.finish().map_err(|e| zip_error(&e))?;
+
+    let size_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Ok(LogExport { path: path.to_string_lossy().to_string(), files: files.len(), size_bytes })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Recent log entries, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn query_logs(
+    logs: tauri::State<'_, LogState>,
+    filter: Option<String>,
+    level: Option<String>,
+    since: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, AppError> {
+    Ok(query(&logs.dir, filter.as_deref(), level.as_deref(), since.as_deref(), limit)?)
+}
+
+/// Zip the log files for a bug report
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn export_logs(logs: tauri::State<'_, LogState>, path: String) -> Result<LogExport, AppError> {
+    Ok(export(&logs.dir, Path::new(&path))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::sync::Mutex;
+
+    /// Write events through the JSON layer into a log file in `dir`
+    fn write_events(dir: &Path, name: &str, events: impl FnOnce()) {
+        let file = std::fs::File::create(dir.join(name)).unwrap();
+        let subscriber = tracing_subscriber::registry().with(json_layer(Mutex::new(file)));
+        tracing::subscriber::with_default(subscriber, events);
+    }
+
+    #[test]
+    fn test_query_filters_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        write_events(dir.path(), "app.2026-03-01.log", || {
+            tracing::info!("Scheduler started");
+            tracing::error!(job_id = "digest", "Job failed");
+        });
+        write_events(dir.path(), "app.2026-03-02.log", || {
+            tracing::warn!("Sidecar restarted");
+            tracing::debug!("Heartbeat");
+        });
+        std::fs::write(dir.path().join("notes.txt"), "not a log").unwrap();
+
+        let all = query(dir.path(), None, None, None, None).unwrap();
+        let messages: Vec<_> = all.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["Heartbeat", "Sidecar restarted", "Job failed", "Scheduler started"]);
+        assert_eq!(all[2].level, "ERROR");
+        assert_eq!(all[2].fields["job_id"], "digest");
+        assert!(all[2].target.contains("logs"));
+
+        let warnings = query(dir.path(), None, Some("warn"), None, None).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(query(dir.path(), Some("DIGEST"), None, None, None).unwrap().len(), 1);
+        assert_eq!(query(dir.path(), None, None, None, Some(1)).unwrap().len(), 1);
+
+        let future = (Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        assert!(query(dir.path(), None, None, Some(&future), None).unwrap().is_empty());
+        assert!(query(dir.path(), None, None, Some("yesterday"), None).is_err());
+    }
+
+    #[test]
+    fn test_export_zips_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir(&logs).unwrap();
+        write_events(&logs, "app.2026-03-01.log", || tracing::info!("Started"));
+
+        let path = dir.path().join("logs.zip");
+        let export = export(&logs, &path).unwrap();
+        assert_eq!(export.files, 1);
+        assert!(export.size_bytes > 0);
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut contents = String::new();
+        archive.by_name("app.2026-03-01.log").unwrap().read_to_string(&mut contents).unwrap();
+        assert!(contents.contains("Started"));
+        assert!(archive.by_name("info.json").is_ok());
+
+        assert!(query(&dir.path().join("missing"), None, None, None, None).unwrap().is_empty());
+    }
+}
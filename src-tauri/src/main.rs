@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `--mcp-server --client <name>` serves MCP over stdio without a window
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|a| a == "--mcp-server") {
+        std::process::exit(ai_assistant_tauri_lib::run_mcp_server(&args));
+    }
+
     ai_assistant_tauri_lib::run()
 }
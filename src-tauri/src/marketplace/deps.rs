@@ -0,0 +1,367 @@
+// Marketplace Dependencies - Version requirements and install planning
+//
+// A package manifest lists the skills, templates and other items a package
+// needs, each with a version requirement in Cargo syntax: `1.2` and `^1.2`
+// accept any 1.x from 1.2.0, `~1.2` any 1.2.x, and comparators (`>=1.0, <2`)
+// may be combined with commas. `resolve` walks the dependency tree of an
+// item against the marketplace and the installed items and returns what has
+// to be installed, in install order, and any version conflicts.
+
+use crate::marketplace::install::InstalledItem;
+use crate::marketplace::MarketplaceItem;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Dependency declared in a package manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    /// Marketplace item id
+    pub id: String,
+    /// Version requirement; any version when empty
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Package manifest shipped with a marketplace item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+}
+
+impl PackageManifest {
+    /// Parse a manifest, checking every version and requirement
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let manifest: Self = serde_json::from_str(json).map_err(|e| format!("Invalid package manifest: {}", e))?;
+        Version::parse(&manifest.version)?;
+        for dependency in &manifest.dependencies {
+            if dependency.id == manifest.id {
+                return Err(format!("Package {} depends on itself", manifest.id));
+            }
+            VersionReq::parse(&dependency.version)
+                .map_err(|e| format!("Dependency {} of {}: {}", dependency.id, manifest.id, e))?;
+        }
+        Ok(manifest)
+    }
+}
+
+/// `major.minor.patch` version; pre-release and build suffixes are ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(u64, u64, u64);
+
+impl Version {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (version, _) = Self::parse_partial(s)?;
+        Ok(version)
+    }
+
+    /// Parse a possibly incomplete version; also returns how many parts were
+    /// given
+    fn parse_partial(s: &str) -> Result<(Self, usize), String> {
+        let core = s.trim().trim_start_matches('v');
+        let core = core.split(['-', '+']).next().unwrap_or_default();
+        let parts: Vec<&str> = core.split('.').collect();
+        if core.is_empty() || parts.len() > 3 {
+            return Err(format!("Invalid version: '{}'", s));
+        }
+        let mut numbers = [0u64; 3];
+        for (i, part) in parts.iter().enumerate() {
+            numbers[i] = part.parse().map_err(|_| format!("Invalid version: '{}'", s))?;
+        }
+        Ok((Self(numbers[0], numbers[1], numbers[2]), parts.len()))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// Version requirement: every bound must hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    /// Inclusive lower and exclusive upper bounds
+    bounds: Vec<(Option<Version>, Option<Version>)>,
+}
+
+impl VersionReq {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.is_empty() || s == "*" {
+            return Ok(Self { bounds: Vec::new() });
+        }
+        let bounds = s.split(',').map(Self::parse_comparator).collect::<Result<_, _>>()?;
+        Ok(Self { bounds })
+    }
+
+    fn parse_comparator(s: &str) -> Result<(Option<Version>, Option<Version>), String> {
+        let s = s.trim();
+        let (op, rest) = [">=", "<=", ">", "<", "=", "^", "~"]
+            .iter()
+            .find_map(|op| s.strip_prefix(op).map(|rest| (*op, rest)))
+            .unwrap_or(("^", s));
+        let (v, parts) = Version::parse_partial(rest).map_err(|_| format!("Invalid version requirement: '{}'", s))?;
+        let Version(major, minor, patch) = v;
+        // Smallest version above every version matching the given parts
+        let next = match parts {
+            1 => Version(major + 1, 0, 0),
+            2 => Version(major, minor + 1, 0),
+            _ => Version(major, minor, patch + 1),
+        };
+
+        Ok(match op {
+            ">=" => (Some(v), None),
+            ">" => (Some(next), None),
+            "<" => (None, Some(v)),
+            "<=" => (None, Some(next)),
+            "=" => (Some(v), Some(next)),
+            "~" if parts == 1 => (Some(v), Some(Version(major + 1, 0, 0))),
+            "~" => (Some(v), Some(Version(major, minor + 1, 0))),
+            // Caret: the left-most non-zero part may not change
+            _ if major > 0 || parts == 1 => (Some(v), Some(Version(major + 1, 0, 0))),
+            _ if minor > 0 || parts == 2 => (Some(v), Some(Version(0, minor + 1, 0))),
+            _ => (Some(v), Some(Version(0, 0, patch + 1))),
+        })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.bounds.iter().all(|(lower, upper)| {
+            lower.is_none_or(|lower| *version >= lower) && upper.is_none_or(|upper| *version < upper)
+        })
+    }
+}
+
+/// Dependency to install before the requested item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedInstall {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// Items that need it
+    pub required_by: Vec<String>,
+}
+
+/// Requirement that cannot be met
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyConflict {
+    pub id: String,
+    /// Requirement that failed
+    pub required: String,
+    pub required_by: String,
+    /// Version already installed
+    pub installed: Option<String>,
+    /// Version the marketplace offers; None when the item is not listed
+    pub available: Option<String>,
+}
+
+/// What installing an item involves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallPlan {
+    pub item_id: String,
+    /// Missing dependencies, dependencies first
+    pub to_install: Vec<PlannedInstall>,
+    /// Dependencies already installed at an acceptable version
+    pub satisfied: Vec<String>,
+    pub conflicts: Vec<DependencyConflict>,
+}
+
+impl InstallPlan {
+    /// Whether the user must confirm extra installs
+    pub fn needs_confirmation(&self) -> bool {
+        !self.to_install.is_empty()
+    }
+
+    pub fn conflict_summary(&self) -> String {
+        self.conflicts
+            .iter()
+            .map(|c| {
+                let found = match (&c.installed, &c.available) {
+                    (Some(installed), _) => format!("{} is installed", installed),
+                    (None, Some(available)) => format!("{} is available", available),
+                    (None, None) => "not in the marketplace".to_string(),
+                };
+                format!("{} needs {} {} ({})", c.required_by, c.id, c.required, found)
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Plan the install of `item`; `available` looks up marketplace items
+pub fn resolve(
+    item: &MarketplaceItem,
+    available: &HashMap<String, MarketplaceItem>,
+    installed: &HashMap<String, InstalledItem>,
+) -> Result<InstallPlan, String> {
+    let mut plan = InstallPlan {
+        item_id: item.id.clone(),
+        to_install: Vec::new(),
+        satisfied: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    let mut visited = HashSet::from([item.id.clone()]);
+    visit(item, available, installed, &mut visited, &mut plan)?;
+    plan.satisfied.sort();
+    plan.satisfied.dedup();
+    Ok(plan)
+}
+
+fn visit(
+    item: &MarketplaceItem,
+    available: &HashMap<String, MarketplaceItem>,
+    installed: &HashMap<String, InstalledItem>,
+    visited: &mut HashSet<String>,
+    plan: &mut InstallPlan,
+) -> Result<(), String> {
+    for dependency in &item.dependencies {
+        let required = VersionReq::parse(&dependency.version)
+            .map_err(|e| format!("Dependency {} of {}: {}", dependency.id, item.id, e))?;
+        let requirement = if dependency.version.trim().is_empty() { "*" } else { dependency.version.trim() };
+        let conflict = |installed: Option<&InstalledItem>, available: Option<&MarketplaceItem>| DependencyConflict {
+            id: dependency.id.clone(),
+            required: requirement.to_string(),
+            required_by: item.id.clone(),
+            installed: installed.map(|i| i.version.clone()),
+            available: available.map(|a| a.version.clone()),
+        };
+
+        if let Some(current) = installed.get(&dependency.id) {
+            if Version::parse(&current.version).is_ok_and(|v| required.matches(&v)) {
+                plan.satisfied.push(dependency.id.clone());
+            } else {
+                plan.conflicts.push(conflict(Some(current), available.get(&dependency.id)));
+            }
+            continue;
+        }
+
+        let Some(candidate) = available.get(&dependency.id) else {
+            plan.conflicts.push(conflict(None, None));
+            continue;
+        };
+        if !Version::parse(&candidate.version).is_ok_and(|v| required.matches(&v)) {
+            plan.conflicts.push(conflict(None, Some(candidate)));
+            continue;
+        }
+
+        if let Some(planned) = plan.to_install.iter_mut().find(|p| p.id == candidate.id) {
+            planned.required_by.push(item.id.clone());
+            continue;
+        }
+        // A cycle back to an item being resolved needs nothing more
+        if !visited.insert(candidate.id.clone()) {
+            continue;
+        }
+        visit(candidate, available, installed, visited, plan)?;
+        plan.to_install.push(PlannedInstall {
+            id: candidate.id.clone(),
+            name: candidate.name.clone(),
+            version: candidate.version.clone(),
+            required_by: vec![item.id.clone()],
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::install::InstallationStatus;
+    use crate::marketplace::{MarketplaceItemType, MarketplacePrice};
+
+    fn item(id: &str, version: &str, dependencies: &[(&str, &str)]) -> MarketplaceItem {
+        MarketplaceItem {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            item_type: MarketplaceItemType::Skill,
+            author: "Test".to_string(),
+            version: version.to_string(),
+            download_count: 0,
+            rating: 0.0,
+            price: MarketplacePrice::Free,
+            tags: vec![],
+            dependencies: dependencies
+                .iter()
+                .map(|(id, version)| Dependency { id: id.to_string(), version: version.to_string() })
+                .collect(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn installed(id: &str, version: &str) -> (String, InstalledItem) {
+        (
+            id.to_string(),
+            InstalledItem {
+                id: id.to_string(),
+                version: version.to_string(),
+                installed_at: String::new(),
+                status: InstallationStatus::Installed,
+                dependencies: vec![],
+            },
+        )
+    }
+
+    #[test]
+    fn test_version_requirements() {
+        let v = |s| Version::parse(s).unwrap();
+        let req = |s| VersionReq::parse(s).unwrap();
+        assert!(req("^1.2").matches(&v("1.9.0")));
+        assert!(!req("^1.2").matches(&v("2.0.0")));
+        assert!(!req("1.2.3").matches(&v("1.2.2")));
+        assert!(req("~1.2.3").matches(&v("1.2.9")));
+        assert!(!req("~1.2.3").matches(&v("1.3.0")));
+        assert!(req("^0.2.1").matches(&v("0.2.5")));
+        assert!(!req("^0.2.1").matches(&v("0.3.0")));
+        assert!(req(">=1.0, <2").matches(&v("1.99.1")));
+        assert!(!req(">=1.0, <2").matches(&v("2.0.0")));
+        assert!(req("<=1.4").matches(&v("1.4.7")));
+        assert!(req("=2.0.1").matches(&v("v2.0.1-beta")));
+        assert!(req("*").matches(&v("0.0.1")));
+        assert!(VersionReq::parse(">= one").is_err());
+
+        let manifest = PackageManifest::parse(
+            r#"{"id": "recipe-a", "version": "1.0.0", "dependencies": [{"id": "skill-b", "version": "^1.1"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.dependencies[0].id, "skill-b");
+        assert!(PackageManifest::parse(r#"{"id": "a", "version": "1.0", "dependencies": [{"id": "a"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let recipe = item("recipe", "1.0.0", &[("skill-a", "^1.2"), ("template-b", "~2.0"), ("skill-c", "")]);
+        let available: HashMap<String, MarketplaceItem> = [
+            item("skill-a", "1.4.0", &[("skill-d", ">=0.5")]),
+            item("template-b", "2.0.3", &[]),
+            item("skill-c", "3.1.0", &[("skill-a", "^1.0")]),
+            item("skill-d", "0.6.0", &[("skill-a", "")]),
+        ]
+        .into_iter()
+        .map(|i| (i.id.clone(), i))
+        .collect();
+
+        let plan = resolve(&recipe, &available, &HashMap::new()).unwrap();
+        let order: Vec<&str> = plan.to_install.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(order, vec!["skill-d", "skill-a", "template-b", "skill-c"]);
+        assert_eq!(plan.to_install[1].required_by, vec!["recipe", "skill-c"]);
+        assert!(plan.conflicts.is_empty());
+
+        // Installed items are reused or conflict
+        let current: HashMap<_, _> = [installed("skill-a", "1.3.0"), installed("template-b", "1.0.0")].into();
+        let plan = resolve(&recipe, &available, &current).unwrap();
+        assert_eq!(plan.satisfied, vec!["skill-a"]);
+        let order: Vec<&str> = plan.to_install.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(order, vec!["skill-c"]);
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].installed.as_deref(), Some("1.0.0"));
+        assert_eq!(plan.conflict_summary(), "recipe needs template-b ~2.0 (1.0.0 is installed)");
+
+        let missing = item("recipe", "1.0.0", &[("skill-x", "1")]);
+        let plan = resolve(&missing, &available, &HashMap::new()).unwrap();
+        assert!(plan.conflicts[0].available.is_none());
+    }
+}
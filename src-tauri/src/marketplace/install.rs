@@ -1,8 +1,20 @@
 // Marketplace Install - Item installation and management
 
+use crate::marketplace::deps::{self, InstallPlan};
 use crate::marketplace::MarketplaceItem;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Marker file present while an item is being installed
+const INSTALLING_MARKER: &str = ".installing";
+
+/// Marker file present while an update is on probation; the backup of the
+/// previous version is kept until it is removed
+const UPDATING_MARKER: &str = ".updating";
+
+/// Folder inside the install directory holding the previous versions
+const BACKUP_DIR: &str = ".backups";
+
 /// Installation status
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum InstallationStatus {
@@ -19,26 +31,58 @@ pub struct InstalledItem {
     pub version: String,
     pub installed_at: String,
     pub status: InstallationStatus,
+    /// Ids of the items it depends on
+    #[serde(default)]
+    pub dependencies: Vec<String>,
 }
 
 /// Marketplace installer
 pub struct MarketplaceInstaller {
     install_dir: PathBuf,
-    installed: std::collections::HashMap<String, InstalledItem>,
+    installed: HashMap<String, InstalledItem>,
 }
 
 impl MarketplaceInstaller {
-    /// Create a new installer
+    /// Create a new installer, loading the items already installed
     pub fn new(install_dir: PathBuf) -> Result<Self, String> {
         std::fs::create_dir_all(&install_dir)
             .map_err(|e| format!("Failed to create install directory: {}", e))?;
 
+        let mut installed = HashMap::new();
+        let entries = std::fs::read_dir(&install_dir)
+            .map_err(|e| format!("Failed to read install directory: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Interrupted installs are left to recover_incomplete
+            if is_internal(&entry) || path.join(INSTALLING_MARKER).exists() {
+                continue;
+            }
+            let Ok(metadata) = std::fs::read_to_string(path.join("metadata.json")) else {
+                continue;
+            };
+            match serde_json::from_str::<InstalledItem>(&metadata) {
+                Ok(item) => {
+                    installed.insert(item.id.clone(), item);
+                }
+                Err(e) => tracing::warn!("Invalid install metadata in {}: {}", path.display(), e),
+            }
+        }
+
         Ok(Self {
             install_dir,
-            installed: std::collections::HashMap::new(),
+            installed,
         })
     }
 
+    /// Plan installing `item` and its dependencies from the `available` items
+    pub fn plan(
+        &self,
+        item: &MarketplaceItem,
+        available: &HashMap<String, MarketplaceItem>,
+    ) -> Result<InstallPlan, String> {
+        deps::resolve(item, available, &self.installed)
+    }
+
     /// Install an item from marketplace
     pub async fn install(&mut self, item: &MarketplaceItem) -> Result<String, String> {
         // Check if already installed
@@ -48,12 +92,19 @@ impl MarketplaceInstaller {
             }
         }
 
+        self.write_item(item)?;
+        Ok(format!("Installed {} v{}", item.name, item.version))
+    }
+
+    /// Write an item's files and metadata, replacing what is there
+    fn write_item(&mut self, item: &MarketplaceItem) -> Result<(), String> {
         // Create installation record
         let installed_item = InstalledItem {
             id: item.id.clone(),
             version: item.version.clone(),
             installed_at: chrono::Utc::now().to_rfc3339(),
             status: InstallationStatus::Installed,
+            dependencies: item.dependencies.iter().map(|d| d.id.clone()).collect(),
         };
 
         // In production, this would:
@@ -66,16 +117,22 @@ impl MarketplaceInstaller {
         std::fs::create_dir_all(&item_path)
             .map_err(|e| format!("Failed to create item directory: {}", e))?;
 
+        // Mark the install as in progress until metadata is written
+        let marker_path = item_path.join(INSTALLING_MARKER);
+        std::fs::write(&marker_path, &installed_item.installed_at)
+            .map_err(|e| format!("Failed to write install marker: {}", e))?;
+
         // Save metadata
         let metadata_path = item_path.join("metadata.json");
         let metadata = serde_json::to_string_pretty(&installed_item)
             .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
         std::fs::write(&metadata_path, metadata)
             .map_err(|e| format!("Failed to write metadata: {}", e))?;
+        std::fs::remove_file(&marker_path)
+            .map_err(|e| format!("Failed to finalize install: {}", e))?;
 
         self.installed.insert(item.id.clone(), installed_item);
-
-        Ok(format!("Installed {} v{}", item.name, item.version))
+        Ok(())
     }
 
     /// Uninstall an item
@@ -111,19 +168,115 @@ impl MarketplaceInstaller {
         Ok(updates)
     }
 
-    /// Update an item
-    pub async fn update(&mut self, item_id: &str) -> Result<String, String> {
-        if !self.installed.contains_key(item_id) {
-            return Err(format!("Item not installed: {}", item_id));
+    /// Install a newer version of an item, backing up the current one
+    ///
+    /// The update stays on probation until `commit_update`; until then
+    /// `rollback` restores the backup, and so does `recover_incomplete` after
+    /// a crash. Returns the previously installed item.
+    pub fn apply_update(&mut self, item: &MarketplaceItem) -> Result<InstalledItem, String> {
+        let previous = self
+            .installed
+            .get(&item.id)
+            .cloned()
+            .ok_or_else(|| format!("Item not installed: {}", item.id))?;
+        if self.has_backup(&item.id) {
+            return Err(format!("An update of {} is already in progress", item.id));
         }
 
-        // In production, this would:
-        // 1. Download new version
-        // 2. Backup current version
-        // 3. Install new version
-        // 4. Update metadata
+        let item_path = self.get_item_path(&item.id);
+        let backup_path = self.get_backup_path(&item.id);
+        std::fs::create_dir_all(self.install_dir.join(BACKUP_DIR))
+            .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+        copy_dir(&item_path, &backup_path)?;
+
+        // The new version replaces every file of the old one
+        std::fs::remove_dir_all(&item_path)
+            .and_then(|_| std::fs::create_dir_all(&item_path))
+            .and_then(|_| std::fs::write(item_path.join(UPDATING_MARKER), &previous.version))
+            .map_err(|e| format!("Failed to prepare update: {}", e))?;
+
+        if let Err(e) = self.write_item(item) {
+            self.rollback(&item.id)?;
+            return Err(e);
+        }
+        Ok(previous)
+    }
+
+    /// Keep the updated version and drop the backup
+    pub fn commit_update(&self, item_id: &str) -> Result<(), String> {
+        let backup_path = self.get_backup_path(item_id);
+        if backup_path.exists() {
+            std::fs::remove_dir_all(&backup_path)
+                .map_err(|e| format!("Failed to remove backup: {}", e))?;
+        }
+        let marker_path = self.get_item_path(item_id).join(UPDATING_MARKER);
+        if marker_path.exists() {
+            std::fs::remove_file(&marker_path)
+                .map_err(|e| format!("Failed to finalize update: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Restore the version backed up by `apply_update`
+    pub fn rollback(&mut self, item_id: &str) -> Result<InstalledItem, String> {
+        let backup_path = self.get_backup_path(item_id);
+        if !backup_path.exists() {
+            return Err(format!("No backup to restore for {}", item_id));
+        }
+
+        let item_path = self.get_item_path(item_id);
+        if item_path.exists() {
+            std::fs::remove_dir_all(&item_path)
+                .map_err(|e| format!("Failed to remove updated files: {}", e))?;
+        }
+        std::fs::rename(&backup_path, &item_path)
+            .map_err(|e| format!("Failed to restore backup: {}", e))?;
+
+        let metadata = std::fs::read_to_string(item_path.join("metadata.json"))
+            .map_err(|e| format!("Failed to read restored metadata: {}", e))?;
+        let restored: InstalledItem = serde_json::from_str(&metadata)
+            .map_err(|e| format!("Invalid restored metadata: {}", e))?;
+        self.installed.insert(restored.id.clone(), restored.clone());
+        Ok(restored)
+    }
+
+    /// Whether an update of the item is on probation
+    pub fn has_backup(&self, item_id: &str) -> bool {
+        self.get_backup_path(item_id).exists()
+    }
+
+    /// Roll back installs and updates interrupted before they completed
+    ///
+    /// Updates still on probation get their previous version restored.
+    /// Item directories that still carry the install marker or never got
+    /// their metadata written are removed. Returns the rolled back item ids.
+    pub fn recover_incomplete(&mut self) -> Result<Vec<String>, String> {
+        let entries = std::fs::read_dir(&self.install_dir)
+            .map_err(|e| format!("Failed to read install directory: {}", e))?;
+
+        let mut rolled_back = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() || is_internal(&entry) {
+                continue;
+            }
+
+            let item_id = entry.file_name().to_string_lossy().to_string();
+            if self.has_backup(&item_id) {
+                self.rollback(&item_id)?;
+                rolled_back.push(item_id);
+                continue;
+            }
 
-        Ok(format!("Updated {}", item_id))
+            if path.join(INSTALLING_MARKER).exists() || !path.join("metadata.json").exists() {
+                std::fs::remove_dir_all(&path)
+                    .map_err(|e| format!("Failed to remove incomplete install: {}", e))?;
+                rolled_back.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+
+        rolled_back.sort();
+        Ok(rolled_back)
     }
 
     /// Get installed items
@@ -131,15 +284,52 @@ impl MarketplaceInstaller {
         self.installed.values().collect()
     }
 
+    /// Installed items depending on `item_id`
+    pub fn dependents(&self, item_id: &str) -> Vec<String> {
+        let mut dependents: Vec<String> = self
+            .installed
+            .values()
+            .filter(|item| item.dependencies.iter().any(|d| d == item_id))
+            .map(|item| item.id.clone())
+            .collect();
+        dependents.sort();
+        dependents
+    }
+
     /// Check if item is installed
     pub fn is_installed(&self, item_id: &str) -> bool {
         self.installed.contains_key(item_id)
     }
 
     /// Get item install path
-    fn get_item_path(&self, item_id: &str) -> PathBuf {
+    pub fn get_item_path(&self, item_id: &str) -> PathBuf {
         self.install_dir.join(item_id)
     }
+
+    fn get_backup_path(&self, item_id: &str) -> PathBuf {
+        self.install_dir.join(BACKUP_DIR).join(item_id)
+    }
+}
+
+/// Folders of the installer itself, such as the backups
+fn is_internal(entry: &std::fs::DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Copy a directory tree
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) -> Result<(), String> {
+    std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries = std::fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    for entry in entries.flatten() {
+        let target = to.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .map_err(|e| format!("Failed to back up {}: {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -163,6 +353,7 @@ mod tests {
             rating: 0.0,
             price: MarketplacePrice::Free,
             tags: vec![],
+            dependencies: vec![],
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
         };
@@ -188,13 +379,77 @@ mod tests {
             rating: 0.0,
             price: MarketplacePrice::Free,
             tags: vec![],
+            dependencies: vec![],
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
         };
 
         installer.install(&item).await.unwrap();
+
+        // The dependency graph survives a new installer
+        let recipe = MarketplaceItem {
+            id: "test-recipe".to_string(),
+            item_type: MarketplaceItemType::Recipe,
+            dependencies: vec![deps::Dependency {
+                id: "test-item".to_string(),
+                version: "^1.0".to_string(),
+            }],
+            ..item.clone()
+        };
+        installer.install(&recipe).await.unwrap();
+        let mut installer = MarketplaceInstaller::new(temp_dir.path().to_path_buf()).unwrap();
+        assert!(installer.is_installed("test-recipe"));
+        assert_eq!(installer.dependents("test-item"), vec!["test-recipe".to_string()]);
+
         let result = installer.uninstall("test-item").await;
         assert!(result.is_ok());
         assert!(!installer.is_installed("test-item"));
     }
+
+    #[tokio::test]
+    async fn test_recover_incomplete() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut installer = MarketplaceInstaller::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let item = MarketplaceItem {
+            id: "complete-item".to_string(),
+            name: "Complete Item".to_string(),
+            description: "Test".to_string(),
+            item_type: MarketplaceItemType::Skill,
+            author: "Test".to_string(),
+            version: "1.0.0".to_string(),
+            download_count: 0,
+            rating: 0.0,
+            price: MarketplacePrice::Free,
+            tags: vec![],
+            dependencies: vec![],
+            created_at: chrono::Utc::now().to_rfc3339(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        };
+        installer.install(&item).await.unwrap();
+
+        // Simulate installs interrupted mid-way
+        let partial = temp_dir.path().join("partial-item");
+        std::fs::create_dir_all(&partial).unwrap();
+        std::fs::write(partial.join(INSTALLING_MARKER), "").unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("empty-item")).unwrap();
+
+        // An update interrupted by a crash is rolled back
+        let newer = MarketplaceItem {
+            version: "2.0.0".to_string(),
+            ..item.clone()
+        };
+        installer.apply_update(&newer).unwrap();
+
+        let rolled_back = installer.recover_incomplete().unwrap();
+        assert_eq!(
+            rolled_back,
+            vec!["complete-item".to_string(), "empty-item".to_string(), "partial-item".to_string()]
+        );
+        let installer = MarketplaceInstaller::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(installer.get_installed()[0].version, "1.0.0");
+        assert!(!installer.has_backup("complete-item"));
+        assert!(!partial.exists());
+        assert!(temp_dir.path().join("complete-item").exists());
+    }
 }
@@ -5,6 +5,9 @@
 pub mod store;
 pub mod listing;
 pub mod install;
+pub mod deps;
+pub mod reviews;
+pub mod update;
 
 #[cfg(test)]
 mod tests;
@@ -41,6 +44,9 @@ pub struct MarketplaceItem {
     pub rating: f32,
     pub price: MarketplacePrice,
     pub tags: Vec<String>,
+    /// Items this one needs, from its package manifest
+    #[serde(default)]
+    pub dependencies: Vec<deps::Dependency>,
     pub created_at: String,
     pub updated_at: String,
 }
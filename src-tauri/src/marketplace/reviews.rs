@@ -0,0 +1,263 @@
+// Marketplace Reviews - Ratings, comments and install telemetry
+//
+// Reviews fetched from the registry are cached per item and page in
+// `marketplace_reviews`, so the last known reviews stay readable offline.
+// The user's own review is kept there too and posted again until the
+// registry accepts it. Install telemetry is anonymous and off until the
+// user opts in.
+
+use crate::db::settings;
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the install telemetry opt-in
+pub const TELEMETRY_SETTING: &str = "marketplace_install_telemetry";
+
+pub const MAX_COMMENT_LENGTH: usize = 2000;
+
+/// Reviews per page
+pub const PAGE_SIZE: u32 = 20;
+
+/// Marketplace item review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceReview {
+    pub id: String,
+    pub item_id: String,
+    /// 1 to 5 stars
+    pub rating: u8,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub created_at: String,
+    /// Written on this device
+    #[serde(default)]
+    pub own: bool,
+    /// Accepted by the registry; always true for fetched reviews
+    #[serde(default = "default_synced")]
+    pub synced: bool,
+}
+
+fn default_synced() -> bool {
+    true
+}
+
+/// Page of reviews for an item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPage {
+    pub item_id: String,
+    pub page: u32,
+    pub reviews: Vec<MarketplaceReview>,
+    /// Served from the local cache because the registry was unreachable
+    pub cached: bool,
+}
+
+/// Check a rating and trim its comment
+pub fn validate(rating: u8, comment: &str) -> Result<String, AppError> {
+    if !(1..=5).contains(&rating) {
+        return Err(AppError::invalid_input("Rating must be between 1 and 5"));
+    }
+    let comment = comment.trim();
+    if comment.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(AppError::invalid_input(format!("Reviews are at most {} characters", MAX_COMMENT_LENGTH)));
+    }
+    Ok(comment.to_string())
+}
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<MarketplaceReview> {
+    Ok(MarketplaceReview {
+        id: row.get(0)?,
+        item_id: row.get(1)?,
+        rating: row.get(2)?,
+        comment: row.get(3)?,
+        author: row.get(4)?,
+        created_at: row.get(5)?,
+        own: row.get(6)?,
+        synced: row.get(7)?,
+    })
+}
+
+const COLUMNS: &str = "id, item_id, rating, comment, author, created_at, own, synced";
+
+/// The user's review of an item
+pub fn own_review(conn: &Connection, item_id: &str) -> Result<Option<MarketplaceReview>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM marketplace_reviews WHERE item_id = ?1 AND own = 1", COLUMNS),
+        [item_id],
+        from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Store the user's review of an item, replacing an earlier one; it stays
+/// unsynced until `mark_synced`
+pub fn save_own(conn: &Connection, item_id: &str, rating: u8, comment: &str) -> Result<MarketplaceReview, AppError> {
+    let comment = validate(rating, comment)?;
+    // Keep the id so the registry updates the review instead of adding one
+    let id = own_review(conn, item_id)?
+        .map(|r| r.id)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let review = MarketplaceReview {
+        id,
+        item_id: item_id.to_string(),
+        rating,
+        comment,
+        author: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        own: true,
+        synced: false,
+    };
+    conn.execute(
+        "INSERT INTO marketplace_reviews (id, item_id, rating, comment, created_at, own, synced, page)
+         VALUES (?1, ?2, ?3, ?4, ?5, 1, 0, NULL)
+         ON CONFLICT(id) DO UPDATE SET rating = excluded.rating, comment = excluded.comment,
+             created_at = excluded.created_at, synced = 0",
+        params![review.id, review.item_id, review.rating, review.comment, review.created_at],
+    )?;
+    Ok(review)
+}
+
+pub fn mark_synced(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("UPDATE marketplace_reviews SET synced = 1 WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Own reviews the registry has not accepted yet
+pub fn unsynced(conn: &Connection) -> Result<Vec<MarketplaceReview>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM marketplace_reviews WHERE own = 1 AND synced = 0 ORDER BY created_at",
+            COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let reviews = stmt
+        .query_map([], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(reviews)
+}
+
+/// Replace the cached registry reviews of one page
+pub fn cache_page(conn: &Connection, item_id: &str, page: u32, reviews: &[MarketplaceReview]) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM marketplace_reviews WHERE item_id = ?1 AND page = ?2 AND own = 0",
+        params![item_id, page],
+    )
+    .map_err(|e| e.to_string())?;
+    for review in reviews {
+        // The user's own review comes back from the registry; keep the local copy
+        tx.execute(
+            "INSERT INTO marketplace_reviews (id, item_id, rating, comment, author, created_at, own, synced, page)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, 1, ?7)
+             ON CONFLICT(id) DO UPDATE SET rating = excluded.rating, comment = excluded.comment,
+                 author = excluded.author, page = excluded.page
+             WHERE marketplace_reviews.own = 0",
+            params![review.id, item_id, review.rating, review.comment, review.author, review.created_at, page],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Cached page of reviews; the first page starts with the user's review
+pub fn cached_page(conn: &Connection, item_id: &str, page: u32) -> Result<Vec<MarketplaceReview>, String> {
+    let mut reviews: Vec<MarketplaceReview> = Vec::new();
+    if page <= 1 {
+        reviews.extend(own_review(conn, item_id)?);
+    }
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM marketplace_reviews WHERE item_id = ?1 AND page = ?2 AND own = 0
+             ORDER BY created_at DESC",
+            COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let cached = stmt
+        .query_map(params![item_id, page], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    reviews.extend(cached);
+    Ok(reviews)
+}
+
+/// Whether the user opted in to anonymous install telemetry
+pub fn telemetry_enabled(conn: &Connection) -> Result<bool, String> {
+    Ok(settings::get(conn, TELEMETRY_SETTING)?.as_deref() == Some("true"))
+}
+
+pub fn set_telemetry(conn: &Connection, enabled: bool) -> Result<(), String> {
+    settings::set(conn, TELEMETRY_SETTING, if enabled { "true" } else { "false" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn remote(id: &str, rating: u8) -> MarketplaceReview {
+        MarketplaceReview {
+            id: id.to_string(),
+            item_id: "skill-a".to_string(),
+            rating,
+            comment: format!("review {}", id),
+            author: Some("someone".to_string()),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            own: false,
+            synced: true,
+        }
+    }
+
+    #[test]
+    fn test_own_review() {
+        let conn = test_conn();
+        assert_eq!(save_own(&conn, "skill-a", 0, "").unwrap_err().kind(), crate::error::ErrorKind::InvalidInput);
+        assert!(save_own(&conn, "skill-a", 6, "").is_err());
+        assert!(save_own(&conn, "skill-a", 4, &"x".repeat(MAX_COMMENT_LENGTH + 1)).is_err());
+
+        let first = save_own(&conn, "skill-a", 4, "  Useful  ").unwrap();
+        assert_eq!(first.comment, "Useful");
+        assert_eq!(unsynced(&conn).unwrap().len(), 1);
+        mark_synced(&conn, &first.id).unwrap();
+        assert!(unsynced(&conn).unwrap().is_empty());
+
+        // Editing keeps the id and needs syncing again
+        let edited = save_own(&conn, "skill-a", 2, "Broke on update").unwrap();
+        assert_eq!(edited.id, first.id);
+        let pending = unsynced(&conn).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].rating, 2);
+
+        assert!(!telemetry_enabled(&conn).unwrap());
+        set_telemetry(&conn, true).unwrap();
+        assert!(telemetry_enabled(&conn).unwrap());
+    }
+
+    #[test]
+    fn test_cached_pages() {
+        let conn = test_conn();
+        let own = save_own(&conn, "skill-a", 5, "Great").unwrap();
+
+        cache_page(&conn, "skill-a", 1, &[remote("r1", 3), remote("r2", 4)]).unwrap();
+        cache_page(&conn, "skill-a", 2, &[remote("r3", 1)]).unwrap();
+        let ids = |reviews: Vec<MarketplaceReview>| reviews.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        let first = ids(cached_page(&conn, "skill-a", 1).unwrap());
+        assert_eq!(first[0], own.id);
+        assert_eq!(first.len(), 3);
+        assert_eq!(ids(cached_page(&conn, "skill-a", 2).unwrap()), vec!["r3"]);
+
+        // A refreshed page replaces the old one and never overwrites the own review
+        let mut echoed = remote(&own.id, 1);
+        echoed.comment = "from registry".to_string();
+        cache_page(&conn, "skill-a", 1, &[remote("r4", 5), echoed]).unwrap();
+        let first = cached_page(&conn, "skill-a", 1).unwrap();
+        assert_eq!(ids(first.clone()), vec![own.id.clone(), "r4".to_string()]);
+        assert_eq!(first[0].comment, "Great");
+        assert!(cached_page(&conn, "skill-b", 1).unwrap().is_empty());
+    }
+}
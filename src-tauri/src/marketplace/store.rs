@@ -1,6 +1,11 @@
 // Marketplace Store - Remote marketplace API client
 
+use crate::marketplace::reviews::MarketplaceReview;
 use crate::marketplace::{MarketplaceItem, MarketplaceCategory, MarketplaceFilters};
+use std::time::Duration;
+
+/// Timeout of registry requests
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Marketplace store client
 pub struct MarketplaceStore {
@@ -43,6 +48,83 @@ impl MarketplaceStore {
             .ok_or_else(|| format!("Item not found: {}", item_id))
     }
 
+    /// All marketplace items by id, for resolving dependencies
+    pub async fn catalog(&self) -> Result<std::collections::HashMap<String, MarketplaceItem>, String> {
+        let items = self.list_items(&MarketplaceFilters::default(), 1, u32::MAX).await?;
+        Ok(items.into_iter().map(|item| (item.id.clone(), item)).collect())
+    }
+
+    /// Fetch one page of an item's reviews from the registry
+    pub async fn fetch_reviews(
+        &self,
+        item_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> Result<Vec<MarketplaceReview>, String> {
+        let url = format!(
+            "{}/items/{}/reviews?page={}&page_size={}",
+            self.base_url, item_id, page, page_size
+        );
+        let response = self
+            .request(reqwest::Method::GET, &url)?
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch reviews: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Registry returned {} for reviews", response.status()));
+        }
+        let text = response.text().await.map_err(|e| format!("Failed to read reviews: {}", e))?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid reviews response: {}", e))
+    }
+
+    /// Submit a review; the registry replaces an earlier review with the same id
+    pub async fn post_review(&self, review: &MarketplaceReview) -> Result<(), String> {
+        let url = format!("{}/items/{}/reviews", self.base_url, review.item_id);
+        let body = serde_json::json!({
+            "id": review.id,
+            "rating": review.rating,
+            "comment": review.comment,
+        });
+        self.post(&url, &body).await
+    }
+
+    /// Report an install anonymously: no user or device identifier is sent
+    pub async fn post_install_event(&self, item_id: &str, version: &str) -> Result<(), String> {
+        let url = format!("{}/telemetry/installs", self.base_url);
+        let body = serde_json::json!({
+            "item_id": item_id,
+            "version": version,
+            "platform": std::env::consts::OS,
+        });
+        self.post(&url, &body).await
+    }
+
+    async fn post(&self, url: &str, body: &serde_json::Value) -> Result<(), String> {
+        let response = self
+            .request(reqwest::Method::POST, url)?
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| format!("Registry request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Registry returned {}", response.status()));
+        }
+        Ok(())
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, String> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let request = client.request(method, url);
+        Ok(match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        })
+    }
+
     /// Get all categories
     pub async fn get_categories(&self) -> Result<Vec<MarketplaceCategory>, String> {
         Ok(vec![
@@ -114,6 +196,7 @@ impl MarketplaceStore {
                 rating: 4.8,
                 price: crate::marketplace::MarketplacePrice::Free,
                 tags: vec!["development".to_string(), "code-quality".to_string()],
+                dependencies: vec![],
                 created_at: now.clone(),
                 updated_at: now.clone(),
             },
@@ -128,6 +211,10 @@ impl MarketplaceStore {
                 rating: 4.6,
                 price: crate::marketplace::MarketplacePrice::Free,
                 tags: vec!["productivity".to_string(), "meetings".to_string()],
+                dependencies: vec![crate::marketplace::deps::Dependency {
+                    id: "template-email-pro".to_string(),
+                    version: "^1.0".to_string(),
+                }],
                 created_at: now.clone(),
                 updated_at: now.clone(),
             },
@@ -142,6 +229,7 @@ impl MarketplaceStore {
                 rating: 4.9,
                 price: crate::marketplace::MarketplacePrice::Free,
                 tags: vec!["development".to_string(), "git".to_string(), "github".to_string()],
+                dependencies: vec![],
                 created_at: now.clone(),
                 updated_at: now.clone(),
             },
@@ -159,6 +247,7 @@ impl MarketplaceStore {
                     currency: "USD".to_string(),
                 },
                 tags: vec!["communication".to_string(), "email".to_string()],
+                dependencies: vec![],
                 created_at: now.clone(),
                 updated_at: now,
             },
@@ -63,6 +63,7 @@ mod tests {
             rating: 0.0,
             price: MarketplacePrice::Free,
             tags: vec![],
+            dependencies: vec![],
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
         };
@@ -88,6 +89,7 @@ mod tests {
             rating: 0.0,
             price: MarketplacePrice::Free,
             tags: vec!["code".to_string()],
+            dependencies: vec![],
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
         };
@@ -114,6 +116,7 @@ mod tests {
                 rating: 4.0 + (i as f32) * 0.2,
                 price: MarketplacePrice::Free,
                 tags: vec![],
+                dependencies: vec![],
                 created_at: chrono::Utc::now().to_rfc3339(),
                 updated_at: chrono::Utc::now().to_rfc3339(),
             };
@@ -0,0 +1,340 @@
+// Marketplace Update - Updating installed items with automatic rollback
+//
+// An update backs up the installed version before replacing it. Plugins
+// must then pass a health check: the new version has to start and keep
+// running within its resource limits for a grace period. Otherwise the
+// backup is restored. The auto-update policy decides whether updates found
+// at startup are only reported or installed this way.
+
+use crate::db::settings;
+use crate::marketplace::deps::Version;
+use crate::marketplace::{MarketplaceInstaller, MarketplaceItem, MarketplaceItemType};
+use crate::plugins::loader::{LoaderConfig, PluginLoader};
+use crate::plugins::PluginExecutor;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Setting holding the auto-update policy
+pub const POLICY_SETTING: &str = "marketplace_auto_update";
+
+/// How long an updated plugin must stay healthy
+pub const GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// How often a plugin on probation is checked
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to do with updates found at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoUpdatePolicy {
+    /// Do not look for updates
+    Off,
+    /// Report available updates
+    #[default]
+    Notify,
+    /// Install updates, rolling back the ones that fail
+    Auto,
+}
+
+/// Outcome of an update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStatus {
+    /// Newer version found but not installed
+    Available,
+    Updated,
+    /// The new version failed and the previous one was restored
+    RolledBack,
+    UpToDate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateReport {
+    pub item_id: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub status: UpdateStatus,
+    /// Why the update was rolled back
+    pub error: Option<String>,
+}
+
+pub fn get_policy(conn: &Connection) -> Result<AutoUpdatePolicy, String> {
+    match settings::get(conn, POLICY_SETTING)? {
+        Some(value) => serde_json::from_value(serde_json::Value::String(value))
+            .map_err(|e| format!("Invalid auto-update policy: {}", e)),
+        None => Ok(AutoUpdatePolicy::default()),
+    }
+}
+
+pub fn set_policy(conn: &Connection, policy: AutoUpdatePolicy) -> Result<(), String> {
+    let value = serde_json::to_value(policy).map_err(|e| e.to_string())?;
+    settings::set(conn, POLICY_SETTING, value.as_str().unwrap_or_default())
+}
+
+/// Whether `available` is newer than `installed`
+fn is_newer(available: &str, installed: &str) -> bool {
+    match (Version::parse(available), Version::parse(installed)) {
+        (Ok(available), Ok(installed)) => available > installed,
+        _ => false,
+    }
+}
+
+/// Installed items with a newer version in the catalog
+pub fn available_updates(
+    installer: &MarketplaceInstaller,
+    catalog: &HashMap<String, MarketplaceItem>,
+) -> Vec<UpdateReport> {
+    let mut updates: Vec<UpdateReport> = installer
+        .get_installed()
+        .into_iter()
+        .filter_map(|installed| {
+            let item = catalog.get(&installed.id)?;
+            is_newer(&item.version, &installed.version).then(|| UpdateReport {
+                item_id: item.id.clone(),
+                from_version: installed.version.clone(),
+                to_version: item.version.clone(),
+                status: UpdateStatus::Available,
+                error: None,
+            })
+        })
+        .collect();
+    updates.sort_by(|a, b| a.item_id.cmp(&b.item_id));
+    updates
+}
+
+/// Start a plugin from its install folder and watch it for `grace`
+///
+/// Fails when the manifest or entry point is missing or invalid, when the
+/// plugin does not start, or when it stops or exceeds its resource limits
+/// during the grace period.
+pub async fn check_plugin_health(item_dir: &Path, grace: Duration) -> Result<(), String> {
+    let mut loader = PluginLoader::new(LoaderConfig::default());
+    let mut manifest = loader.load_from_dir(item_dir)?;
+    loader.check_compatibility(&manifest)?;
+    let main = item_dir.join(&manifest.main);
+    if !main.is_file() {
+        return Err(format!("Plugin entry point {} is missing", manifest.main));
+    }
+    manifest.main = main.to_string_lossy().to_string();
+
+    // Run in a throwaway work directory so the check leaves no state behind
+    let work_dir = std::env::temp_dir().join(format!("plugin-health-{}", uuid::Uuid::new_v4()));
+    let mut executor = PluginExecutor::with_plugins_dir(work_dir.clone());
+    let id = manifest.id.clone();
+
+    let result = match executor.start_plugin(manifest).await {
+        Ok(()) => watch(&executor, &id, grace).await,
+        Err(e) => Err(format!("Plugin failed to start: {}", e)),
+    };
+
+    if executor.is_running(&id) {
+        let _ = executor.stop_plugin(&id).await;
+    }
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+async fn watch(executor: &PluginExecutor, id: &str, grace: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + grace;
+    loop {
+        if !executor.is_running(id) {
+            return Err("Plugin stopped during the grace period".to_string());
+        }
+        let violations = {
+            let monitor = executor.get_monitor();
+            let monitor = monitor.lock().map_err(|e| e.to_string())?;
+            monitor.check_limits(&executor.get_resource_limits(id))
+        };
+        if !violations.is_empty() {
+            return Err(format!("Plugin exceeded its limits: {}", violations.join(", ")));
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(());
+        }
+        tokio::time::sleep(HEALTH_POLL_INTERVAL.min(deadline - now)).await;
+    }
+}
+
+/// Update an installed item to `item`, rolling back when a plugin fails its
+/// health check
+pub async fn update_item(
+    installer: &mut MarketplaceInstaller,
+    item: &MarketplaceItem,
+    grace: Duration,
+) -> Result<UpdateReport, String> {
+    let installed_version = installer
+        .get_installed()
+        .into_iter()
+        .find(|installed| installed.id == item.id)
+        .map(|installed| installed.version.clone())
+        .ok_or_else(|| format!("Item not installed: {}", item.id))?;
+
+    let mut report = UpdateReport {
+        item_id: item.id.clone(),
+        from_version: installed_version.clone(),
+        to_version: item.version.clone(),
+        status: UpdateStatus::UpToDate,
+        error: None,
+    };
+    if !is_newer(&item.version, &installed_version) {
+        return Ok(report);
+    }
+
+    installer.apply_update(item)?;
+    let health = match item.item_type {
+        MarketplaceItemType::Plugin => check_plugin_health(&installer.get_item_path(&item.id), grace).await,
+        _ => Ok(()),
+    };
+
+    match health {
+        Ok(()) => {
+            installer.commit_update(&item.id)?;
+            tracing::info!("Updated {} from v{} to v{}", item.id, installed_version, item.version);
+            report.status = UpdateStatus::Updated;
+        }
+        Err(e) => {
+            installer.rollback(&item.id)?;
+            tracing::warn!("Update of {} to v{} rolled back: {}", item.id, item.version, e);
+            report.status = UpdateStatus::RolledBack;
+            report.error = Some(e);
+        }
+    }
+    Ok(report)
+}
+
+/// Apply `policy` to the updates available in `catalog`
+pub async fn auto_update(
+    installer: &mut MarketplaceInstaller,
+    catalog: &HashMap<String, MarketplaceItem>,
+    policy: AutoUpdatePolicy,
+    grace: Duration,
+) -> Vec<UpdateReport> {
+    let available = match policy {
+        AutoUpdatePolicy::Off => return Vec::new(),
+        _ => available_updates(installer, catalog),
+    };
+    if policy == AutoUpdatePolicy::Notify {
+        return available;
+    }
+
+    let mut reports = Vec::new();
+    for update in available {
+        let item = &catalog[&update.item_id];
+        match update_item(installer, item, grace).await {
+            Ok(report) => reports.push(report),
+            Err(e) => reports.push(UpdateReport {
+                status: UpdateStatus::RolledBack,
+                error: Some(e),
+                ..update
+            }),
+        }
+    }
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::MarketplacePrice;
+    use crate::db::test_support::test_conn;
+
+    fn item(id: &str, item_type: MarketplaceItemType, version: &str) -> MarketplaceItem {
+        MarketplaceItem {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            item_type,
+            author: "Test".to_string(),
+            version: version.to_string(),
+            download_count: 0,
+            rating: 0.0,
+            price: MarketplacePrice::Free,
+            tags: vec![],
+            dependencies: vec![],
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn write_plugin(dir: &Path, id: &str, version: &str) {
+        let manifest = serde_json::json!({
+            "id": id,
+            "name": id,
+            "version": version,
+            "description": "",
+            "author": "Test",
+            "main": "plugin.wasm",
+            "permissions": [],
+            "api_version": "1.0",
+        });
+        std::fs::write(dir.join("plugin.json"), manifest.to_string()).unwrap();
+        std::fs::write(dir.join("plugin.wasm"), wat::parse_str("(module)").unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_and_rollback() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut installer = MarketplaceInstaller::new(temp_dir.path().to_path_buf()).unwrap();
+        let skill = item("skill-a", MarketplaceItemType::Skill, "1.0.0");
+        let plugin = item("plugin-b", MarketplaceItemType::Plugin, "1.0.0");
+        installer.install(&skill).await.unwrap();
+        installer.install(&plugin).await.unwrap();
+        write_plugin(&installer.get_item_path("plugin-b"), "plugin-b", "1.0.0");
+
+        let same = update_item(&mut installer, &skill, Duration::ZERO).await.unwrap();
+        assert_eq!(same.status, UpdateStatus::UpToDate);
+        let newer_skill = item("skill-a", MarketplaceItemType::Skill, "1.1.0");
+        let updated = update_item(&mut installer, &newer_skill, Duration::ZERO).await.unwrap();
+        assert_eq!(updated.status, UpdateStatus::Updated);
+        assert!(!installer.has_backup("skill-a"));
+
+        // The downloaded plugin lacks its manifest, so it cannot start
+        let newer_plugin = item("plugin-b", MarketplaceItemType::Plugin, "2.0.0");
+        let report = update_item(&mut installer, &newer_plugin, Duration::ZERO).await.unwrap();
+        assert_eq!(report.status, UpdateStatus::RolledBack);
+        assert!(report.error.unwrap().contains("manifest"));
+        let installer = MarketplaceInstaller::new(temp_dir.path().to_path_buf()).unwrap();
+        let versions: HashMap<&str, &str> = installer
+            .get_installed()
+            .into_iter()
+            .map(|i| (i.id.as_str(), i.version.as_str()))
+            .collect();
+        assert_eq!(versions["plugin-b"], "1.0.0");
+        assert_eq!(versions["skill-a"], "1.1.0");
+        assert!(installer.get_item_path("plugin-b").join("plugin.json").exists());
+        assert!(check_plugin_health(&installer.get_item_path("plugin-b"), Duration::ZERO).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_policy() {
+        let conn = test_conn();
+        assert_eq!(get_policy(&conn).unwrap(), AutoUpdatePolicy::Notify);
+        set_policy(&conn, AutoUpdatePolicy::Auto).unwrap();
+        assert_eq!(get_policy(&conn).unwrap(), AutoUpdatePolicy::Auto);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut installer = MarketplaceInstaller::new(temp_dir.path().to_path_buf()).unwrap();
+        installer.install(&item("skill-a", MarketplaceItemType::Skill, "1.0.0")).await.unwrap();
+        installer.install(&item("skill-b", MarketplaceItemType::Skill, "2.0.0")).await.unwrap();
+        let catalog: HashMap<String, MarketplaceItem> = [
+            item("skill-a", MarketplaceItemType::Skill, "1.0.1"),
+            item("skill-b", MarketplaceItemType::Skill, "1.9.0"),
+        ]
+        .into_iter()
+        .map(|i| (i.id.clone(), i))
+        .collect();
+
+        assert!(auto_update(&mut installer, &catalog, AutoUpdatePolicy::Off, Duration::ZERO).await.is_empty());
+        let notified = auto_update(&mut installer, &catalog, AutoUpdatePolicy::Notify, Duration::ZERO).await;
+        assert_eq!(notified.len(), 1);
+        assert_eq!(notified[0].status, UpdateStatus::Available);
+        let updated = auto_update(&mut installer, &catalog, AutoUpdatePolicy::Auto, Duration::ZERO).await;
+        assert_eq!(updated[0].status, UpdateStatus::Updated);
+        assert!(available_updates(&installer, &catalog).is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+//! MCP client allowlist
+//!
+//! Every external client is registered by name with the capabilities it may
+//! use. The server only lists and runs tools of those capabilities, and
+//! refuses clients that are unknown or disabled.
+
+use crate::error::AppError;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Group of tools a client can be granted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// List and read files in folders with a folder permission
+    ReadFiles,
+    /// List skills and run them through the agent runtime
+    RunSkills,
+    /// Search and read conversations that are not marked sensitive
+    QueryConversations,
+}
+
+/// External client allowed to connect to the MCP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpClient {
+    pub id: String,
+    pub name: String,
+    pub capabilities: Vec<Capability>,
+    pub enabled: bool,
+    pub created_at: String,
+    pub last_connected_at: Option<String>,
+}
+
+/// Command line an editor or agent uses to start the server for a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpLaunchConfig {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+const COLUMNS: &str = "id, name, capabilities, enabled, created_at, last_connected_at";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<McpClient> {
+    let capabilities: String = row.get(2)?;
+    Ok(McpClient {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        // Unknown capabilities from a newer version grant nothing
+        capabilities: serde_json::from_str(&capabilities).unwrap_or_default(),
+        enabled: row.get(3)?,
+        created_at: row.get(4)?,
+        last_connected_at: row.get(5)?,
+    })
+}
+
+/// Registered clients, by name
+pub fn list(conn: &Connection) -> Result<Vec<McpClient>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM mcp_clients ORDER BY name", COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let clients = stmt
+        .query_map([], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(clients)
+}
+
+pub fn get_by_name(conn: &Connection, name: &str) -> Result<Option<McpClient>, String> {
+    conn.query_row(&format!("SELECT {} FROM mcp_clients WHERE name = ?1", COLUMNS), [name], from_row)
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Create a client, or update the one with the same name
+pub fn save(conn: &Connection, name: &str, capabilities: &[Capability], enabled: bool) -> Result<McpClient, String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 64 {
+        return Err("Client name must be 1-64 characters".to_string());
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        return Err("Client name may only contain letters, digits, '-', '_' and '.'".to_string());
+    }
+
+    let mut granted: Vec<Capability> = Vec::new();
+    for capability in capabilities {
+        if !granted.contains(capability) {
+            granted.push(*capability);
+        }
+    }
+    let capabilities = serde_json::to_string(&granted).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO mcp_clients (id, name, capabilities, enabled) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(name) DO UPDATE SET capabilities = excluded.capabilities, enabled = excluded.enabled",
+        rusqlite::params![uuid::Uuid::new_v4().to_string(), name, capabilities, enabled],
+    )
+    .map_err(|e| e.to_string())?;
+
+    get_by_name(conn, name)?.ok_or_else(|| format!("MCP client not found: {}", name))
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<(), String> {
+    let deleted = conn
+        .execute("DELETE FROM mcp_clients WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("MCP client not found: {}", id));
+    }
+    Ok(())
+}
+
+/// The client allowed to connect as `name`
+pub fn authorize(conn: &Connection, name: &str) -> Result<McpClient, String> {
+    let client = get_by_name(conn, name)?
+        .ok_or_else(|| format!("Unknown MCP client '{}'; add it in the app's MCP settings first", name))?;
+    if !client.enabled {
+        return Err(format!("MCP client '{}' is disabled", name));
+    }
+    conn.execute(
+        "UPDATE mcp_clients SET last_connected_at = ?1 WHERE id = ?2",
+        [&chrono::Utc::now().to_rfc3339(), &client.id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(client)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Clients allowed to use the MCP server
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_mcp_clients(db: tauri::State<'_, crate::db::DbState>) -> Result<Vec<McpClient>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list(&conn)?)
+}
+
+/// Register a client or change its capabilities
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn save_mcp_client(
+    db: tauri::State<'_, crate::db::DbState>,
+    name: String,
+    capabilities: Vec<Capability>,
+    enabled: Option<bool>,
+) -> Result<McpClient, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(save(&conn, &name, &capabilities, enabled.unwrap_or(true))?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_mcp_client(db: tauri::State<'_, crate::db::DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(delete(&conn, &id)?)
+}
+
+/// Command and arguments to put in a client's MCP server configuration
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_mcp_launch_config(name: String) -> Result<McpLaunchConfig, AppError> {
+    let exe = std::env::current_exe()?;
+    Ok(McpLaunchConfig {
+        command: exe.to_string_lossy().to_string(),
+        args: vec!["--mcp-server".to_string(), "--client".to_string(), name],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_save_updates_by_name() {
+        let conn = test_conn();
+        let created = save(&conn, "zed", &[Capability::ReadFiles, Capability::ReadFiles], true).unwrap();
+        assert_eq!(created.capabilities, vec![Capability::ReadFiles]);
+
+        let updated = save(&conn, " zed ", &[Capability::RunSkills, Capability::QueryConversations], true).unwrap();
+        assert_eq!(updated.id, created.id);
+        assert_eq!(updated.capabilities, vec![Capability::RunSkills, Capability::QueryConversations]);
+        assert_eq!(list(&conn).unwrap().len(), 1);
+
+        assert!(save(&conn, "", &[], true).is_err());
+        assert!(save(&conn, "my editor", &[], true).is_err());
+
+        delete(&conn, &created.id).unwrap();
+        assert!(delete(&conn, &created.id).is_err());
+    }
+
+    #[test]
+    fn test_authorize() {
+        let conn = test_conn();
+        save(&conn, "cursor", &[Capability::ReadFiles], true).unwrap();
+        save(&conn, "old-agent", &[Capability::RunSkills], false).unwrap();
+
+        let client = authorize(&conn, "cursor").unwrap();
+        assert_eq!(client.capabilities, vec![Capability::ReadFiles]);
+        assert!(get_by_name(&conn, "cursor").unwrap().unwrap().last_connected_at.is_some());
+
+        assert!(authorize(&conn, "old-agent").unwrap_err().contains("disabled"));
+        assert!(authorize(&conn, "unknown").unwrap_err().contains("Unknown"));
+    }
+}
@@ -0,0 +1,44 @@
+//! MCP Server Module
+//!
+//! Lets external editors and agents use the assistant as an MCP tool
+//! provider. Started as `<app> --mcp-server --client <name>`, the app opens
+//! no window and serves MCP over stdio from the app database:
+//! - A per-client capability allowlist managed from the app
+//! - Tools for reading permitted files, running skills and querying conversations
+
+pub mod clients;
+pub mod server;
+
+use std::path::PathBuf;
+
+/// Value following `flag` on the command line
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+}
+
+fn serve_stdio(args: &[String]) -> Result<(), String> {
+    let client_name = arg_value(args, "--client").ok_or_else(|| "Missing --client <name>".to_string())?;
+    let db_path = match arg_value(args, "--db") {
+        Some(path) => PathBuf::from(path),
+        None => crate::db::default_db_path().ok_or_else(|| "Could not locate the app data folder".to_string())?,
+    };
+
+    let conn = crate::db::open_database(&db_path).map_err(|e| format!("Failed to open {}: {}", db_path.display(), e))?;
+    let client = clients::authorize(&conn, client_name)?;
+
+    let stdin = std::io::stdin();
+    server::McpServer::new(conn, client).serve(stdin.lock(), std::io::stdout().lock())
+}
+
+/// Entry point for `--mcp-server`; returns the process exit code
+///
+/// Stdout carries protocol messages only, so errors go to stderr.
+pub fn run_cli(args: &[String]) -> i32 {
+    match serve_stdio(args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("MCP server: {}", e);
+            1
+        }
+    }
+}
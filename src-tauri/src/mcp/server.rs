@@ -0,0 +1,489 @@
+//! MCP server over stdio
+//!
+//! Speaks the Model Context Protocol's JSON-RPC dialect, one message per
+//! line on stdin and stdout. Tools are grouped by `Capability`; only the
+//! tools of capabilities granted to the connected client are listed or run.
+//! Tool failures are reported as error results so the calling model sees
+//! them, while protocol errors use JSON-RPC error responses.
+
+use super::clients::{Capability, McpClient};
+use crate::db::skill_schema;
+use crate::files::access::{resolve, Access};
+use crate::files::listing::{self, ListOptions};
+use crate::sidecar::{SidecarProcess, SidecarState};
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// Protocol version offered when the client asks for one we don't know
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+const SUPPORTED_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Largest file `read_file` returns
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+/// Directory entries returned by `list_directory`
+const MAX_LIST_ENTRIES: usize = 500;
+
+/// Conversations or messages returned unless a limit is given, and the most allowed
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+struct ToolSpec {
+    name: &'static str,
+    capability: Capability,
+    description: &'static str,
+}
+
+const TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        name: "list_folders",
+        capability: Capability::ReadFiles,
+        description: "List the folders the user has granted access to",
+    },
+    ToolSpec {
+        name: "list_directory",
+        capability: Capability::ReadFiles,
+        description: "List the entries of a directory inside a permitted folder",
+    },
+    ToolSpec {
+        name: "read_file",
+        capability: Capability::ReadFiles,
+        description: "Read a text file inside a permitted folder (up to 1MB)",
+    },
+    ToolSpec {
+        name: "list_skills",
+        capability: Capability::RunSkills,
+        description: "List the assistant's skills with their input schemas",
+    },
+    ToolSpec {
+        name: "run_skill",
+        capability: Capability::RunSkills,
+        description: "Run a skill with the given input and return its output",
+    },
+    ToolSpec {
+        name: "search_conversations",
+        capability: Capability::QueryConversations,
+        description: "Find conversations whose title or messages contain the query, newest first",
+    },
+    ToolSpec {
+        name: "get_conversation",
+        capability: Capability::QueryConversations,
+        description: "Read the most recent messages of a conversation",
+    },
+];
+
+fn input_schema(tool: &str) -> Value {
+    match tool {
+        "list_directory" => json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Absolute directory path" },
+                "recursive": { "type": "boolean", "default": false }
+            },
+            "required": ["path"]
+        }),
+        "read_file" => json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "description": "Absolute file path" } },
+            "required": ["path"]
+        }),
+        "run_skill" => json!({
+            "type": "object",
+            "properties": {
+                "skillId": { "type": "string" },
+                "input": { "description": "Skill input; JSON matching the skill's input schema when it has one" }
+            },
+            "required": ["skillId", "input"]
+        }),
+        "search_conversations" => json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT }
+            }
+        }),
+        "get_conversation" => json!({
+            "type": "object",
+            "properties": {
+                "conversationId": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT }
+            },
+            "required": ["conversationId"]
+        }),
+        _ => json!({ "type": "object", "properties": {} }),
+    }
+}
+
+fn rpc_error(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message.into() } })
+}
+
+fn str_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing string argument '{}'", name))
+}
+
+fn limit_arg(args: &Value) -> usize {
+    args.get("limit")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_LIMIT)
+        .clamp(1, MAX_LIMIT)
+}
+
+fn to_text(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// MCP session with one client
+pub struct McpServer {
+    conn: Connection,
+    client: McpClient,
+    /// Agent runtime, started by the first skill run
+    sidecar: Option<SidecarState>,
+}
+
+impl McpServer {
+    pub fn new(conn: Connection, client: McpClient) -> Self {
+        Self { conn, client, sidecar: None }
+    }
+
+    fn tools(&self) -> impl Iterator<Item = &'static ToolSpec> + '_ {
+        TOOLS.iter().filter(|tool| self.client.capabilities.contains(&tool.capability))
+    }
+
+    /// Handle one message and return the reply; notifications get none
+    pub fn handle(&mut self, message: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(e) => return Some(rpc_error(Value::Null, PARSE_ERROR, format!("Parse error: {}", e))),
+        };
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return id.map(|id| rpc_error(id, INVALID_REQUEST, "Invalid request"));
+        };
+        // Notifications such as `notifications/initialized` need no reply
+        let id = id?;
+
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        Some(match self.dispatch(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => rpc_error(id, code, message),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => {
+                let requested = params.get("protocolVersion").and_then(Value::as_str);
+                let version = requested
+                    .filter(|v| SUPPORTED_VERSIONS.contains(v))
+                    .unwrap_or(PROTOCOL_VERSION);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": { "listChanged": false } },
+                    "serverInfo": { "name": "ai-assistant", "version": env!("CARGO_PKG_VERSION") }
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                let tools: Vec<Value> = self
+                    .tools()
+                    .map(|tool| {
+                        json!({
+                            "name": tool.name,
+                            "description": tool.description,
+                            "inputSchema": input_schema(tool.name)
+                        })
+                    })
+                    .collect();
+                Ok(json!({ "tools": tools }))
+            }
+            "tools/call" => {
+                let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+                if !self.tools().any(|tool| tool.name == name) {
+                    return Err((INVALID_PARAMS, format!("Unknown tool: {}", name)));
+                }
+                let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                let (text, is_error) = match self.call_tool(name, &args) {
+                    Ok(text) => (text, false),
+                    Err(e) => (e, true),
+                };
+                Ok(json!({ "content": [{ "type": "text", "text": text }], "isError": is_error }))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    fn call_tool(&mut self, name: &str, args: &Value) -> Result<String, String> {
+        match name {
+            "list_folders" => self.list_folders(),
+            "list_directory" => {
+                let path = resolve(&self.conn, str_arg(args, "path")?, Access::Read).map_err(|e| e.to_string())?;
+                let options = ListOptions {
+                    recursive: args.get("recursive").and_then(Value::as_bool).unwrap_or(false),
+                    respect_gitignore: true,
+                    limit: Some(MAX_LIST_ENTRIES),
+                    ..Default::default()
+                };
+                let listing = listing::list(&path, &options).map_err(|e| e.to_string())?;
+                Ok(to_text(&json!(listing)))
+            }
+            "read_file" => {
+                let path = resolve(&self.conn, str_arg(args, "path")?, Access::Read).map_err(|e| e.to_string())?;
+                let size = std::fs::metadata(&path).map_err(|e| e.to_string())?.len();
+                if size > MAX_READ_BYTES {
+                    return Err(format!("{} is too large to read ({} bytes, limit {})", path.display(), size, MAX_READ_BYTES));
+                }
+                std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            }
+            "list_skills" => self.list_skills(),
+            "run_skill" => self.run_skill(args),
+            "search_conversations" => self.search_conversations(args),
+            "get_conversation" => self.get_conversation(args),
+            _ => Err(format!("Unknown tool: {}", name)),
+        }
+    }
+
+    fn list_folders(&self) -> Result<String, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, level FROM folder_permissions ORDER BY path")
+            .map_err(|e| e.to_string())?;
+        let folders = stmt
+            .query_map([], |row| {
+                Ok(json!({ "path": row.get::<_, String>(0)?, "level": row.get::<_, String>(1)? }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(to_text(&json!(folders)))
+    }
+
+    fn list_skills(&self) -> Result<String, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, description, input_schema FROM skills ORDER BY name")
+            .map_err(|e| e.to_string())?;
+        let skills = stmt
+            .query_map([], |row| {
+                let schema: Option<String> = row.get(3)?;
+                Ok(json!({
+                    "id": row.get::<_, String>(0)?,
+                    "name": row.get::<_, String>(1)?,
+                    "description": row.get::<_, String>(2)?,
+                    "inputSchema": schema.and_then(|s| serde_json::from_str::<Value>(&s).ok())
+                }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(to_text(&json!(skills)))
+    }
+
+    fn run_skill(&mut self, args: &Value) -> Result<String, String> {
+        let skill_id = str_arg(args, "skillId")?;
+        let input = match args.get("input") {
+            Some(Value::String(input)) => input.clone(),
+            Some(input) => input.to_string(),
+            None => return Err("Missing argument 'input'".to_string()),
+        };
+        let prompt: String = self
+            .conn
+            .query_row("SELECT prompt FROM skills WHERE id = ?1", [skill_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Skill not found: {}", skill_id))?;
+        let schemas = skill_schema::load(&self.conn, skill_id)?;
+
+        let sidecar = self.sidecar.get_or_insert_with(SidecarState::new);
+        if !sidecar.is_initialized() {
+            sidecar.set_initialized(SidecarProcess::start()?);
+        }
+        crate::sidecar::run_skill(sidecar, &schemas, skill_id, &prompt, &input)
+    }
+
+    fn search_conversations(&self, args: &Value) -> Result<String, String> {
+        let pattern = format!("%{}%", args.get("query").and_then(Value::as_str).unwrap_or_default());
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, title, updated_at FROM conversations c
+                 WHERE is_sensitive = 0
+                   AND (title LIKE ?1 OR EXISTS (
+                        SELECT 1 FROM messages m
+                        WHERE m.conversation_id = c.id AND m.is_encrypted = 0 AND m.content LIKE ?1))
+                 ORDER BY updated_at DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let conversations = stmt
+            .query_map(rusqlite::params![pattern, limit_arg(args) as i64], |row| {
+                Ok(json!({
+                    "id": row.get::<_, String>(0)?,
+                    "title": row.get::<_, String>(1)?,
+                    "updatedAt": row.get::<_, String>(2)?
+                }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(to_text(&json!(conversations)))
+    }
+
+    fn get_conversation(&self, args: &Value) -> Result<String, String> {
+        let id = str_arg(args, "conversationId")?;
+        let conversation: Option<(String, bool)> = self
+            .conn
+            .query_row("SELECT title, is_sensitive FROM conversations WHERE id = ?1", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()
+            .map_err(|e| e.to_string())?;
+        let title = match conversation {
+            Some((_, true)) => return Err(format!("Conversation {} is sensitive and cannot be shared", id)),
+            Some((title, false)) => title,
+            None => return Err(format!("Conversation not found: {}", id)),
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT role, content, created_at FROM messages
+                 WHERE conversation_id = ?1 AND is_encrypted = 0
+                 ORDER BY created_at DESC, rowid DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut messages = stmt
+            .query_map(rusqlite::params![id, limit_arg(args) as i64], |row| {
+                Ok(json!({
+                    "role": row.get::<_, String>(0)?,
+                    "content": row.get::<_, String>(1)?,
+                    "createdAt": row.get::<_, String>(2)?
+                }))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        messages.reverse();
+        Ok(to_text(&json!({ "id": id, "title": title, "messages": messages })))
+    }
+
+    /// Answer messages from `input` until it closes
+    pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> Result<(), String> {
+        for line in input.lines() {
+            let line = line.map_err(|e| format!("Failed to read request: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(reply) = self.handle(&line) {
+                writeln!(output, "{}", reply)
+                    .and_then(|_| output.flush())
+                    .map_err(|e| format!("Failed to write response: {}", e))?;
+            }
+        }
+        if let Some(sidecar) = &self.sidecar {
+            sidecar.reset();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::clients;
+    use crate::db::test_support::test_conn;
+
+    fn server(capabilities: &[Capability]) -> McpServer {
+        let conn = test_conn();
+        let client = clients::save(&conn, "test", capabilities, true).unwrap();
+        McpServer::new(conn, client)
+    }
+
+    fn call(server: &mut McpServer, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        server.handle(&request.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_handshake_and_tool_allowlist() {
+        let mut server = server(&[Capability::QueryConversations]);
+        let init = call(&mut server, "initialize", json!({ "protocolVersion": "2025-03-26" }));
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+        let init = call(&mut server, "initialize", json!({ "protocolVersion": "1999-01-01" }));
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(server.handle(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+
+        let tools = call(&mut server, "tools/list", json!({}));
+        let names: Vec<_> = tools["result"]["tools"].as_array().unwrap().iter().map(|t| t["name"].clone()).collect();
+        assert_eq!(names, vec!["search_conversations", "get_conversation"]);
+
+        let denied = call(&mut server, "tools/call", json!({ "name": "read_file", "arguments": { "path": "/etc/hosts" } }));
+        assert_eq!(denied["error"]["code"], INVALID_PARAMS);
+        assert_eq!(call(&mut server, "resources/list", json!({}))["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(server.handle("{not json").unwrap()["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn test_conversation_tools_skip_sensitive() {
+        let mut server = server(&[Capability::QueryConversations]);
+        server
+            .conn
+            .execute_batch(
+                "INSERT INTO conversations (id, title) VALUES ('c1', 'Trip planning'), ('c2', 'Medical');
+                 UPDATE conversations SET is_sensitive = 1 WHERE id = 'c2';
+                 INSERT INTO messages (id, conversation_id, role, content) VALUES
+                    ('m1', 'c1', 'user', 'Find flights to Jeju'),
+                    ('m2', 'c2', 'user', 'Jeju clinic results');",
+            )
+            .unwrap();
+
+        let found = call(&mut server, "tools/call", json!({ "name": "search_conversations", "arguments": { "query": "Jeju" } }));
+        let text = found["result"]["content"][0]["text"].as_str().unwrap();
+        let conversations: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(conversations.as_array().unwrap().len(), 1);
+        assert_eq!(conversations[0]["id"], "c1");
+
+        let read = call(&mut server, "tools/call", json!({ "name": "get_conversation", "arguments": { "conversationId": "c1" } }));
+        assert_eq!(read["result"]["isError"], false);
+        assert!(read["result"]["content"][0]["text"].as_str().unwrap().contains("Find flights"));
+
+        let sensitive = call(&mut server, "tools/call", json!({ "name": "get_conversation", "arguments": { "conversationId": "c2" } }));
+        assert_eq!(sensitive["result"]["isError"], true);
+    }
+
+    #[test]
+    fn test_read_file_requires_folder_permission() {
+        let dir = tempfile::tempdir().unwrap();
+        let allowed = dir.path().join("notes");
+        std::fs::create_dir(&allowed).unwrap();
+        std::fs::write(allowed.join("todo.md"), "- ship it").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "nope").unwrap();
+
+        let mut server = server(&[Capability::ReadFiles]);
+        let root = allowed.canonicalize().unwrap();
+        server
+            .conn
+            .execute(
+                "INSERT INTO folder_permissions (id, path, level) VALUES ('p1', ?1, 'read')",
+                [root.to_string_lossy()],
+            )
+            .unwrap();
+
+        let read = |server: &mut McpServer, path: &std::path::Path| {
+            call(server, "tools/call", json!({ "name": "read_file", "arguments": { "path": path } }))["result"].clone()
+        };
+        let ok = read(&mut server, &root.join("todo.md"));
+        assert_eq!(ok["content"][0]["text"], "- ship it");
+        let denied = read(&mut server, &dir.path().canonicalize().unwrap().join("secret.txt"));
+        assert_eq!(denied["isError"], true);
+    }
+}
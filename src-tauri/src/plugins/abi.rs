@@ -0,0 +1,136 @@
+//! Guest ABI for passing JSON between host and WASM plugins
+//!
+//! Plugins exchange structured data through linear memory:
+//!
+//! - The guest exports `alloc(len: i32) -> i32` and `dealloc(ptr: i32, len: i32)`.
+//! - The host serializes the input to JSON, allocates a buffer in the guest,
+//!   copies the bytes in and calls `func(ptr: i32, len: i32) -> i32`.
+//! - The guest returns a pointer to a length-prefixed buffer: a little-endian
+//!   `u32` byte length followed by the JSON-encoded result.
+//! - The host copies the result out and frees both buffers with `dealloc`.
+
+use serde_json::Value;
+
+#[cfg(feature = "wasm")]
+use wasmtime::{Instance, Memory, Store};
+#[cfg(feature = "wasm")]
+use wasmtime_wasi::preview1::WasiP1Ctx;
+
+/// Guest export used to allocate buffers
+pub const ALLOC_EXPORT: &str = "alloc";
+/// Guest export used to free buffers
+pub const DEALLOC_EXPORT: &str = "dealloc";
+/// Size of the length prefix on result buffers
+pub const LENGTH_PREFIX_SIZE: usize = 4;
+/// Largest result a guest may return (16 MB)
+pub const MAX_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+/// Encode a value for passing into the guest
+pub fn encode_payload(value: &Value) -> Result<Vec<u8>, String> {
+    let bytes = serde_json::to_vec(value).map_err(|e| format!("Failed to encode payload: {}", e))?;
+    if bytes.len() > MAX_PAYLOAD_SIZE {
+        return Err(format!("Payload too large: {} bytes", bytes.len()));
+    }
+    Ok(bytes)
+}
+
+/// Decode the length prefix of a guest result buffer
+pub fn decode_length_prefix(prefix: [u8; LENGTH_PREFIX_SIZE]) -> Result<usize, String> {
+    let len = u32::from_le_bytes(prefix) as usize;
+    if len > MAX_PAYLOAD_SIZE {
+        return Err(format!("Result too large: {} bytes", len));
+    }
+    Ok(len)
+}
+
+/// Decode a JSON result returned by the guest
+///
+/// An empty buffer decodes to `null`.
+pub fn decode_payload(bytes: &[u8]) -> Result<Value, String> {
+    if bytes.is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(bytes).map_err(|e| format!("Failed to decode result: {}", e))
+}
+
+/// Check whether an instance exports the allocator needed for the JSON ABI
+#[cfg(feature = "wasm")]
+pub fn supports_json_abi(store: &mut Store<WasiP1Ctx>, instance: &Instance) -> bool {
+    instance.get_typed_func::<i32, i32>(&mut *store, ALLOC_EXPORT).is_ok()
+        && instance.get_typed_func::<(i32, i32), ()>(&mut *store, DEALLOC_EXPORT).is_ok()
+}
+
+/// Call a guest function using the JSON ABI
+#[cfg(feature = "wasm")]
+pub fn call_json(
+    store: &mut Store<WasiP1Ctx>,
+    instance: &Instance,
+    function_name: &str,
+    input: &Value,
+) -> wasmtime::Result<Value> {
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("Guest does not export memory"))?;
+    let alloc = instance.get_typed_func::<i32, i32>(&mut *store, ALLOC_EXPORT)?;
+    let dealloc = instance.get_typed_func::<(i32, i32), ()>(&mut *store, DEALLOC_EXPORT)?;
+    let func = instance.get_typed_func::<(i32, i32), i32>(&mut *store, function_name)?;
+
+    // Copy input into guest memory
+    let payload = encode_payload(input).map_err(wasmtime::Error::msg)?;
+    let input_len = payload.len() as i32;
+    let input_ptr = alloc.call(&mut *store, input_len)?;
+    memory.write(&mut *store, input_ptr as usize, &payload)?;
+
+    let result_ptr = func.call(&mut *store, (input_ptr, input_len));
+    dealloc.call(&mut *store, (input_ptr, input_len))?;
+    let result_ptr = result_ptr?;
+
+    if result_ptr == 0 {
+        return Ok(Value::Null);
+    }
+
+    // Copy the length-prefixed result out and free it
+    let bytes = read_result(store, &memory, result_ptr)?;
+    dealloc.call(&mut *store, (result_ptr, (LENGTH_PREFIX_SIZE + bytes.len()) as i32))?;
+
+    decode_payload(&bytes).map_err(wasmtime::Error::msg)
+}
+
+#[cfg(feature = "wasm")]
+fn read_result(store: &mut Store<WasiP1Ctx>, memory: &Memory, ptr: i32) -> wasmtime::Result<Vec<u8>> {
+    let mut prefix = [0u8; LENGTH_PREFIX_SIZE];
+    memory.read(&mut *store, ptr as usize, &mut prefix)?;
+    let len = decode_length_prefix(prefix).map_err(wasmtime::Error::msg)?;
+
+    let mut bytes = vec![0u8; len];
+    memory.read(&mut *store, ptr as usize + LENGTH_PREFIX_SIZE, &mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let value = serde_json::json!({"name": "test", "items": [1, 2, 3]});
+        let bytes = encode_payload(&value).unwrap();
+        assert_eq!(decode_payload(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_empty_payload_is_null() {
+        assert_eq!(decode_payload(&[]).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_length_prefix() {
+        assert_eq!(decode_length_prefix(42u32.to_le_bytes()).unwrap(), 42);
+        assert!(decode_length_prefix(u32::MAX.to_le_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_result() {
+        assert!(decode_payload(b"{not json").is_err());
+    }
+}
@@ -1,4 +1,7 @@
 // Plugin API - API exposed to plugins
+//
+// Methods are invoked with the executor's `api.call` action; none of them
+// are linked into WASM modules as imports.
 
 use serde::{Deserialize, Serialize};
 
@@ -26,10 +29,19 @@ pub const API_METHODS: &[&str] = &[
     "fs.deleteFile",
     "http.get",
     "http.post",
+    "http.fetch",
     "db.query",
     "db.execute",
     "system.notify",
     "system.clipboard",
+    "storage.get",
+    "storage.set",
+    "storage.delete",
+    "storage.keys",
+    "storage.usage",
+    "schedule.create",
+    "schedule.list",
+    "schedule.delete",
     "log.info",
     "log.error",
 ];
@@ -81,6 +93,18 @@ pub fn get_api_categories() -> Vec<ApiCategory> {
                     params: vec!["url: string".to_string(), "body: any".to_string()],
                     returns: "Response".to_string(),
                 },
+                ApiMethod {
+                    name: "fetch".to_string(),
+                    description: "HTTP request to an allowed host".to_string(),
+                    params: vec![
+                        "url: string".to_string(),
+                        "method?: string".to_string(),
+                        "headers?: Record<string, string>".to_string(),
+                        "body?: string".to_string(),
+                        "timeoutMs?: number".to_string(),
+                    ],
+                    returns: "{ status, headers, body }".to_string(),
+                },
             ],
         },
         ApiCategory {
@@ -117,6 +141,69 @@ pub fn get_api_categories() -> Vec<ApiCategory> {
                 },
             ],
         },
+        ApiCategory {
+            name: "storage".to_string(),
+            methods: vec![
+                ApiMethod {
+                    name: "get".to_string(),
+                    description: "Get a stored value".to_string(),
+                    params: vec!["key: string".to_string()],
+                    returns: "any | null".to_string(),
+                },
+                ApiMethod {
+                    name: "set".to_string(),
+                    description: "Store a value within the plugin quota".to_string(),
+                    params: vec!["key: string".to_string(), "value: any".to_string()],
+                    returns: "void".to_string(),
+                },
+                ApiMethod {
+                    name: "delete".to_string(),
+                    description: "Delete a stored value".to_string(),
+                    params: vec!["key: string".to_string()],
+                    returns: "boolean".to_string(),
+                },
+                ApiMethod {
+                    name: "keys".to_string(),
+                    description: "List stored keys".to_string(),
+                    params: vec![],
+                    returns: "string[]".to_string(),
+                },
+                ApiMethod {
+                    name: "usage".to_string(),
+                    description: "Get storage usage and quota".to_string(),
+                    params: vec![],
+                    returns: "StorageUsage".to_string(),
+                },
+            ],
+        },
+        ApiCategory {
+            name: "schedule".to_string(),
+            methods: vec![
+                ApiMethod {
+                    name: "create".to_string(),
+                    description: "Run an exported function on a cron schedule".to_string(),
+                    params: vec![
+                        "schedule: string".to_string(),
+                        "function: string".to_string(),
+                        "name?: string".to_string(),
+                        "input?: any".to_string(),
+                    ],
+                    returns: "string".to_string(),
+                },
+                ApiMethod {
+                    name: "list".to_string(),
+                    description: "List the plugin's scheduled tasks".to_string(),
+                    params: vec![],
+                    returns: "PluginTask[]".to_string(),
+                },
+                ApiMethod {
+                    name: "delete".to_string(),
+                    description: "Delete a scheduled task".to_string(),
+                    params: vec!["id: string".to_string()],
+                    returns: "boolean".to_string(),
+                },
+            ],
+        },
         ApiCategory {
             name: "log".to_string(),
             methods: vec![
@@ -4,17 +4,24 @@
 
 use crate::plugins::{
     api::{handle_request, PluginRequest},
-    sandbox::{PluginSandbox, SandboxManager},
+    sandbox::SandboxManager,
+    storage::handle_storage_request,
+    schedule::{handle_schedule_request, PluginTaskRunner},
+    tools::PluginToolRunner,
     runtime::{WasmRuntime, WasmRuntimeConfig},
     wasi_host::WasiHost,
-    monitor::{ResourceMonitor, MetricUpdate},
+    monitor::{ResourceMonitor, ResourceSample, MetricUpdate},
+    panels::PanelRegistry,
+    fs::{handle_fs_request, SandboxFs},
+    http::{handle_http_request, NetworkProxy},
     PluginContext, PluginManifest, PluginPermission, ResourceLimits,
 };
 #[cfg(feature = "wasm")]
-use crate::plugins::wasi_host::create_wasi_context_with_dir;
+use crate::plugins::wasi_host::create_wasi_context_with_dirs;
+pub use crate::plugins::ipc::{PluginIpc, PluginMessage};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
@@ -52,6 +59,9 @@ impl Default for ResourceUsage {
     }
 }
 
+/// Marker file kept in a plugin's work directory while it is running
+const RUNNING_MARKER: &str = ".running";
+
 /// Running plugin instance
 pub struct RunningPlugin {
     pub id: String,
@@ -63,6 +73,8 @@ pub struct RunningPlugin {
     pub wasm_instance_id: Option<String>,
     /// Plugin working directory
     pub work_dir: PathBuf,
+    /// Directories mounted into the sandbox
+    pub fs: SandboxFs,
 }
 
 /// Plugin instance state
@@ -72,6 +84,8 @@ pub enum PluginInstanceState {
     Running,
     Stopping,
     Stopped,
+    /// Paused for repeatedly exceeding its resource limits
+    Suspended(String),
     Error(String),
 }
 
@@ -86,8 +100,14 @@ pub struct PluginExecutor {
     wasi_host: Arc<Mutex<WasiHost>>,
     /// Resource monitor
     monitor: Arc<Mutex<ResourceMonitor>>,
+    /// Panels last declared by running plugins
+    panels: Arc<Mutex<PanelRegistry>>,
+    /// Proxy for `http.fetch` requests made through `api.call`
+    network: Arc<NetworkProxy>,
     /// Plugins directory
     plugins_dir: PathBuf,
+    /// Database backing the plugin storage API
+    storage_db_path: Option<PathBuf>,
 }
 
 impl PluginExecutor {
@@ -100,7 +120,10 @@ impl PluginExecutor {
             wasm_runtime: Arc::new(Mutex::new(WasmRuntime::new(WasmRuntimeConfig::default()))),
             wasi_host: Arc::new(Mutex::new(WasiHost::new())),
             monitor: Arc::new(Mutex::new(ResourceMonitor::new())),
+            panels: Arc::new(Mutex::new(PanelRegistry::new())),
+            network: Arc::new(NetworkProxy::new()),
             plugins_dir: PathBuf::from("plugins"),
+            storage_db_path: None,
         }
     }
 
@@ -113,10 +136,27 @@ impl PluginExecutor {
             wasm_runtime: Arc::new(Mutex::new(WasmRuntime::new(WasmRuntimeConfig::default()))),
             wasi_host: Arc::new(Mutex::new(WasiHost::new())),
             monitor: Arc::new(Mutex::new(ResourceMonitor::new())),
+            panels: Arc::new(Mutex::new(PanelRegistry::new())),
+            network: Arc::new(NetworkProxy::new()),
             plugins_dir,
+            storage_db_path: None,
         }
     }
 
+    /// Enable the `storage.*` API and the message outbox backed by the given SQLite database
+    pub fn with_storage_db(mut self, db_path: PathBuf) -> Self {
+        self.set_storage_db(db_path);
+        self
+    }
+
+    /// Point the `storage.*` API and the message outbox at another database (after a profile switch)
+    pub fn set_storage_db(&mut self, db_path: PathBuf) {
+        if let Err(e) = self.ipc.lock().unwrap().set_store(db_path.clone()) {
+            tracing::warn!("Failed to load pending plugin messages: {}", e);
+        }
+        self.storage_db_path = Some(db_path);
+    }
+
     /// Start a plugin
     pub async fn start_plugin(&mut self, manifest: PluginManifest) -> Result<(), String> {
         let plugin_id = manifest.id.clone();
@@ -130,6 +170,8 @@ impl PluginExecutor {
         let work_dir = self.plugins_dir.join(&plugin_id);
         std::fs::create_dir_all(&work_dir)
             .map_err(|e| format!("Failed to create work directory: {}", e))?;
+        std::fs::write(work_dir.join(RUNNING_MARKER), chrono::Utc::now().to_rfc3339())
+            .map_err(|e| format!("Failed to write running marker: {}", e))?;
 
         // Create sandbox context
         let context = PluginContext {
@@ -138,6 +180,12 @@ impl PluginExecutor {
             resource_limits: ResourceLimits::default(),
         };
 
+        #[cfg(feature = "wasm")]
+        let max_cpu_percent = context.resource_limits.max_cpu_percent;
+
+        // Mount the work directory and the permitted paths
+        let fs = SandboxFs::new(&plugin_id, &work_dir, &manifest.permissions);
+
         // Create sandbox
         let mut sandbox_manager = self.sandbox_manager.lock().unwrap();
         sandbox_manager.create_sandbox(&plugin_id, context);
@@ -157,18 +205,13 @@ impl PluginExecutor {
                 let module_hash = runtime.load_module(wasm_bytes)
                     .map_err(|e| format!("Failed to load WASM module: {}", e))?;
 
-                // Create WASI context with working directory
-                let work_dir_str = work_dir.to_str()
-                    .ok_or_else(|| "Invalid UTF-8 in work directory path".to_string())?;
-                let wasi_ctx = create_wasi_context_with_dir(&plugin_id, work_dir_str)?;
+                // Create WASI context with the sandbox mounts preopened
+                let wasi_ctx = create_wasi_context_with_dirs(&plugin_id, &fs.wasi_dirs())?;
 
                 // Instantiate
                 let instance_id = runtime.instantiate(&module_hash, Some(wasi_ctx))
                     .map_err(|e| format!("Failed to instantiate WASM: {}", e))?;
-
-                // Start monitoring
-                let mut monitor = self.monitor.lock().unwrap();
-                monitor.start_monitoring(instance_id.clone());
+                runtime.set_cpu_limit(&instance_id, max_cpu_percent)?;
 
                 Some(instance_id)
             } else {
@@ -188,10 +231,12 @@ impl PluginExecutor {
             #[cfg(feature = "wasm")]
             wasm_instance_id,
             work_dir,
+            fs,
         };
 
         let mut plugins = self.running_plugins.lock().unwrap();
         plugins.insert(plugin_id.clone(), running);
+        self.monitor.lock().unwrap().start_monitoring(plugin_id.clone());
 
         tracing::info!("Plugin {} started", plugin_id);
         Ok(())
@@ -215,15 +260,15 @@ impl PluginExecutor {
         });
 
         // Remove from running plugins
-        let _plugin = plugins.remove(id)
+        let plugin = plugins.remove(id)
             .ok_or_else(|| format!("Plugin {} not found", id))?;
+        let _ = std::fs::remove_file(plugin.work_dir.join(RUNNING_MARKER));
 
         // Stop monitoring
+        self.monitor.lock().unwrap().stop_monitoring(id);
+
         #[cfg(feature = "wasm")]
         if let Some(instance_id) = instance_id {
-            let mut monitor = self.monitor.lock().unwrap();
-            monitor.stop_monitoring(&instance_id);
-
             // Remove WASM instance
             let mut runtime = self.wasm_runtime.lock().unwrap();
             let _ = runtime.remove_instance(&instance_id);
@@ -237,6 +282,9 @@ impl PluginExecutor {
         let mut wasi_host = self.wasi_host.lock().unwrap();
         wasi_host.remove_context(id);
 
+        // Forget declared panels
+        self.panels.lock().unwrap().remove(id);
+
         tracing::info!("Plugin {} stopped", id);
         Ok(())
     }
@@ -351,27 +399,60 @@ impl PluginExecutor {
         };
 
         // Check permissions for the method
-        let sandbox_manager = self.sandbox_manager.lock().unwrap();
-        if let Some(sandbox) = sandbox_manager.get_sandbox(plugin_id) {
-            if let Err(e) = self.check_method_permission(&request.method, sandbox) {
-                return ExecutionResult {
-                    success: false,
-                    result: None,
-                    error: Some(e),
-                    execution_time_ms: 0,
-                    resource_usage: ResourceUsage::default(),
-                };
+        let permitted = {
+            let sandbox_manager = self.sandbox_manager.lock().unwrap();
+            match sandbox_manager.get_sandbox(plugin_id) {
+                Some(sandbox) => check_method_permission(&request.method, &sandbox.context().permissions),
+                None => Ok(()),
             }
+        };
+        if let Err(e) = permitted {
+            return ExecutionResult {
+                success: false,
+                result: None,
+                error: Some(e),
+                execution_time_ms: 0,
+                resource_usage: ResourceUsage::default(),
+            };
         }
-        drop(sandbox_manager);
 
         // Update metrics
-        let mut monitor = self.monitor.lock().unwrap();
-        monitor.update_metrics(plugin_id, MetricUpdate::Syscall);
-        drop(monitor);
+        self.monitor.lock().unwrap().update_metrics(plugin_id, MetricUpdate::Syscall);
 
         // Handle request
-        let response = handle_request(request);
+        let response = if request.method.starts_with("fs.") {
+            match self.handle_fs_call(plugin_id, request) {
+                Ok(response) => response,
+                Err(e) => {
+                    return ExecutionResult {
+                        success: false,
+                        result: None,
+                        error: Some(e),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        resource_usage: ResourceUsage::default(),
+                    };
+                }
+            }
+        } else if request.method == "http.fetch" {
+            self.handle_http_call(plugin_id, request).await
+        } else if request.method.starts_with("storage.")
+            || request.method.starts_with("schedule.")
+        {
+            match self.handle_storage_call(plugin_id, request) {
+                Ok(response) => response,
+                Err(e) => {
+                    return ExecutionResult {
+                        success: false,
+                        result: None,
+                        error: Some(e),
+                        execution_time_ms: start.elapsed().as_millis() as u64,
+                        resource_usage: ResourceUsage::default(),
+                    };
+                }
+            }
+        } else {
+            handle_request(request)
+        };
 
         ExecutionResult {
             success: response.error.is_none(),
@@ -382,6 +463,66 @@ impl PluginExecutor {
         }
     }
 
+    /// Resolve an `fs.*` request through the plugin's mounts, reporting
+    /// accesses outside them to the monitor
+    fn handle_fs_call(
+        &self,
+        plugin_id: &str,
+        request: PluginRequest,
+    ) -> Result<crate::plugins::api::PluginResponse, String> {
+        let fs = {
+            let plugins = self.running_plugins.lock().unwrap();
+            plugins.get(plugin_id)
+                .map(|p| p.fs.clone())
+                .ok_or_else(|| format!("Plugin {} is not running", plugin_id))?
+        };
+        let max_file_bytes = self.get_resource_limits(plugin_id).max_file_size_mb as u64 * 1024 * 1024;
+
+        let mut monitor = self.monitor.lock().unwrap();
+        monitor.update_metrics(plugin_id, MetricUpdate::FileOp);
+        handle_fs_request(&fs, request, max_file_bytes).map_err(|violation| {
+            let error = format!("Access denied: {} ({})", violation.path, violation.reason);
+            monitor.record_violation(violation);
+            error
+        })
+    }
+
+    /// Send an `http.fetch` request through the network proxy
+    async fn handle_http_call(&self, plugin_id: &str, request: PluginRequest) -> crate::plugins::api::PluginResponse {
+        let permissions = {
+            let sandbox_manager = self.sandbox_manager.lock().unwrap();
+            sandbox_manager.get_sandbox(plugin_id)
+                .map(|s| s.context().permissions.clone())
+                .unwrap_or_default()
+        };
+        self.monitor.lock().unwrap().update_metrics(plugin_id, MetricUpdate::NetworkOp);
+
+        handle_http_request(&self.network, plugin_id, &permissions, request, self.storage_db_path.as_deref()).await
+    }
+
+    /// Route a `storage.*` or `schedule.*` request to the plugin's namespace
+    fn handle_storage_call(
+        &self,
+        plugin_id: &str,
+        request: PluginRequest,
+    ) -> Result<crate::plugins::api::PluginResponse, String> {
+        let db_path = self.storage_db_path.as_ref()
+            .ok_or_else(|| "Plugin storage is not available".to_string())?;
+        let context = {
+            let sandbox_manager = self.sandbox_manager.lock().unwrap();
+            sandbox_manager.get_sandbox(plugin_id)
+                .map(|s| s.context().clone())
+                .ok_or_else(|| format!("Sandbox not found for plugin {}", plugin_id))?
+        };
+
+        let conn = crate::db::pool::open_background(db_path)?;
+        if request.method.starts_with("schedule.") {
+            Ok(handle_schedule_request(&conn, &context, request))
+        } else {
+            Ok(handle_storage_request(&conn, &context, request))
+        }
+    }
+
     /// Execute a WASM function call
     #[cfg(feature = "wasm")]
     async fn execute_wasm_call(&mut self, plugin_id: &str, params: Value) -> ExecutionResult {
@@ -417,10 +558,13 @@ impl PluginExecutor {
             .cloned()
             .unwrap_or_default();
 
-        // Call WASM function
+        // Call WASM function; structured `input` goes through the JSON ABI
         let mut runtime = self.wasm_runtime.lock().unwrap();
-        let wasm_result = runtime.call_function(&instance_id, function_name,
-            args.into_iter().collect());
+        let wasm_result = match params.get("input") {
+            Some(input) => runtime.call_function_json(&instance_id, function_name, input),
+            None => runtime.call_function(&instance_id, function_name,
+                args.into_iter().collect()),
+        };
 
         let wasm_result = match wasm_result {
             Ok(r) => r,
@@ -435,10 +579,15 @@ impl PluginExecutor {
             }
         };
 
+        let memory_bytes = runtime.sample_instance(&instance_id)
+            .map(|state| state.memory_used)
+            .unwrap_or(0);
+        drop(runtime);
+
         // Update resource monitor
         let mut monitor = self.monitor.lock().unwrap();
-        monitor.update_from_wasm(&instance_id, wasm_result.fuel_consumed,
-            wasm_result.execution_time_ms);
+        monitor.update_from_wasm(plugin_id, wasm_result.fuel_consumed, memory_bytes);
+        monitor.update_metrics(plugin_id, MetricUpdate::Execution(wasm_result.execution_time_ms));
 
         ExecutionResult {
             success: wasm_result.success,
@@ -455,59 +604,12 @@ impl PluginExecutor {
         }
     }
 
-    /// Check if a method is allowed
-    fn check_method_permission(&self, method: &str, sandbox: &PluginSandbox) -> Result<(), String> {
-        // Parse method to determine permission type
-        if method.starts_with("fs.") {
-            // File system methods need file permission
-            for perm in &sandbox.context().permissions {
-                if matches!(perm, PluginPermission::FileSystem { .. }) {
-                    return Ok(());
-                }
-            }
-            return Err("File system permission required".to_string());
-        }
-
-        if method.starts_with("http.") {
-            // Network methods need network permission
-            for perm in &sandbox.context().permissions {
-                if matches!(perm, PluginPermission::Network { .. }) {
-                    return Ok(());
-                }
-            }
-            return Err("Network permission required".to_string());
-        }
-
-        if method.starts_with("db.") {
-            // Database methods need database permission
-            for perm in &sandbox.context().permissions {
-                if matches!(perm, PluginPermission::Database { .. }) {
-                    return Ok(());
-                }
-            }
-            return Err("Database permission required".to_string());
-        }
-
-        if method.starts_with("system.") {
-            // System methods need system permission
-            for perm in &sandbox.context().permissions {
-                if matches!(perm, PluginPermission::System { .. }) {
-                    return Ok(());
-                }
-            }
-            return Err("System permission required".to_string());
-        }
-
-        // Log methods are always allowed
-        Ok(())
-    }
-
     fn get_resource_usage_internal(&self, plugin_id: &str) -> ResourceUsage {
         let monitor = self.monitor.lock().unwrap();
         if let Some(metrics) = monitor.get_metrics(plugin_id) {
             ResourceUsage {
                 memory_mb: metrics.memory_bytes as f64 / (1024.0 * 1024.0),
-                cpu_percent: 0.0,
+                cpu_percent: monitor.latest_cpu_percent(plugin_id),
                 execution_time_ms: metrics.cpu_time_ms,
                 syscall_count: metrics.syscall_count,
                 #[cfg(feature = "wasm")]
@@ -543,6 +645,89 @@ impl PluginExecutor {
         }
     }
 
+    /// Sample the memory and CPU use of the running plugins, suspending
+    /// those that stayed over their limits for several samples
+    ///
+    /// Returns the suspended plugins with the reason.
+    pub fn sample_resources(&self) -> Vec<(String, String)> {
+        let mut plugins = self.running_plugins.lock().unwrap();
+        let mut suspended = Vec::new();
+        for plugin in plugins.values_mut().filter(|p| p.state == PluginInstanceState::Running) {
+            let (memory_bytes, fuel_consumed) = self.instance_usage(plugin);
+            let limits = self.get_resource_limits(&plugin.id);
+            let exceeded = self.monitor.lock().unwrap()
+                .record_sample(&plugin.id, memory_bytes, fuel_consumed, &limits);
+            if let Some(reason) = exceeded {
+                tracing::warn!("Suspending plugin {}: {}", plugin.id, reason);
+                plugin.state = PluginInstanceState::Suspended(reason.clone());
+                suspended.push((plugin.id.clone(), reason));
+            }
+        }
+        suspended
+    }
+
+    /// Current memory size and total fuel consumed of a plugin's instance
+    fn instance_usage(&self, plugin: &RunningPlugin) -> (u64, u64) {
+        #[cfg(feature = "wasm")]
+        if let Some(instance_id) = &plugin.wasm_instance_id {
+            if let Some(state) = self.wasm_runtime.lock().unwrap().sample_instance(instance_id) {
+                return (state.memory_used, state.fuel_consumed);
+            }
+        }
+        #[cfg(not(feature = "wasm"))]
+        let _ = plugin;
+        (0, 0)
+    }
+
+    /// Let a suspended plugin run again
+    pub fn resume_plugin(&self, id: &str) -> Result<(), String> {
+        let mut plugins = self.running_plugins.lock().unwrap();
+        let plugin = plugins.get_mut(id).ok_or_else(|| format!("Plugin {} not found", id))?;
+        if !matches!(plugin.state, PluginInstanceState::Suspended(_)) {
+            return Err(format!("Plugin {} is not suspended", id));
+        }
+        plugin.state = PluginInstanceState::Running;
+        tracing::info!("Plugin {} resumed", id);
+        Ok(())
+    }
+
+    /// Recent resource samples of a plugin, oldest first
+    pub fn get_resource_samples(&self, id: &str) -> Vec<ResourceSample> {
+        self.monitor.lock().unwrap().get_samples(id)
+    }
+
+    /// Get resource limits for a plugin, falling back to defaults when not running
+    pub fn get_resource_limits(&self, id: &str) -> ResourceLimits {
+        let sandbox_manager = self.sandbox_manager.lock().unwrap();
+        sandbox_manager.get_sandbox(id)
+            .map(|s| s.context().resource_limits.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get plugins directory
+    pub fn plugins_dir(&self) -> &Path {
+        &self.plugins_dir
+    }
+
+    /// Clear instances left marked as running by a previous session
+    ///
+    /// Returns the ids of the plugins whose stale state was cleared.
+    pub fn recover_stale_instances(plugins_dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(plugins_dir) else {
+            return Vec::new();
+        };
+
+        let mut recovered = Vec::new();
+        for entry in entries.flatten() {
+            let marker = entry.path().join(RUNNING_MARKER);
+            if marker.exists() && std::fs::remove_file(&marker).is_ok() {
+                recovered.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        recovered.sort();
+        recovered
+    }
+
     /// Get IPC manager
     pub fn get_ipc(&self) -> Arc<Mutex<PluginIpc>> {
         self.ipc.clone()
@@ -562,6 +747,26 @@ impl PluginExecutor {
     pub fn get_wasi_host(&self) -> Arc<Mutex<WasiHost>> {
         self.wasi_host.clone()
     }
+
+    /// Get panel registry
+    pub fn get_panels(&self) -> Arc<Mutex<PanelRegistry>> {
+        self.panels.clone()
+    }
+
+    /// Get a runner the job scheduler uses to invoke scheduled plugin tasks
+    pub fn task_runner(&self) -> PluginTaskRunner {
+        PluginTaskRunner::new(self.running_plugins.clone(), self.wasm_runtime.clone())
+    }
+
+    /// Get a runner the agent uses to call tools declared by running plugins
+    pub fn tool_runner(&self) -> PluginToolRunner {
+        PluginToolRunner::new(
+            self.running_plugins.clone(),
+            self.sandbox_manager.clone(),
+            self.wasm_runtime.clone(),
+            self.monitor.clone(),
+        )
+    }
 }
 
 impl Default for PluginExecutor {
@@ -570,58 +775,51 @@ impl Default for PluginExecutor {
     }
 }
 
-/// Plugin IPC (Inter-Plugin Communication)
-pub struct PluginIpc {
-    message_queue: HashMap<String, Vec<PluginMessage>>,
-}
-
-/// Message between plugins
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct PluginMessage {
-    pub from: String,
-    pub to: String,
-    pub method: String,
-    pub params: Value,
-    pub timestamp: u64,
-}
-
-impl PluginIpc {
-    pub fn new() -> Self {
-        Self {
-            message_queue: HashMap::new(),
+/// Check if a plugin holding `permissions` may call an API method
+pub(crate) fn check_method_permission(method: &str, permissions: &[PluginPermission]) -> Result<(), String> {
+    // Parse method to determine permission type
+    if method.starts_with("fs.") {
+        // File system methods need file permission
+        for perm in permissions {
+            if matches!(perm, PluginPermission::FileSystem { .. }) {
+                return Ok(());
+            }
         }
+        return Err("File system permission required".to_string());
     }
 
-    /// Send a message from one plugin to another
-    pub fn send_message(&mut self, message: PluginMessage) -> Result<(), String> {
-        let to = message.to.clone();
-        self.message_queue
-            .entry(to)
-            .or_default()
-            .push(message);
-        Ok(())
+    if method.starts_with("http.") {
+        // Network methods need network permission
+        for perm in permissions {
+            if matches!(perm, PluginPermission::Network { .. }) {
+                return Ok(());
+            }
+        }
+        return Err("Network permission required".to_string());
     }
 
-    /// Get pending messages for a plugin
-    pub fn get_messages(&mut self, plugin_id: &str) -> Vec<PluginMessage> {
-        self.message_queue
-            .remove(plugin_id)
-            .unwrap_or_default()
+    if method.starts_with("db.") {
+        // Database methods need database permission
+        for perm in permissions {
+            if matches!(perm, PluginPermission::Database { .. }) {
+                return Ok(());
+            }
+        }
+        return Err("Database permission required".to_string());
     }
 
-    /// Peek at messages without removing
-    pub fn peek_messages(&self, plugin_id: &str) -> &[PluginMessage] {
-        self.message_queue
-            .get(plugin_id)
-            .map(|v| v.as_slice())
-            .unwrap_or(&[])
+    if method.starts_with("system.") {
+        // System methods need system permission
+        for perm in permissions {
+            if matches!(perm, PluginPermission::System { .. }) {
+                return Ok(());
+            }
+        }
+        return Err("System permission required".to_string());
     }
-}
 
-impl Default for PluginIpc {
-    fn default() -> Self {
-        Self::new()
-    }
+    // Log, storage and schedule methods are always allowed
+    Ok(())
 }
 
 #[cfg(test)]
@@ -665,6 +863,23 @@ mod tests {
         let _ = executor;
     }
 
+    #[test]
+    fn test_recover_stale_instances() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let stale = temp_dir.path().join("stale-plugin");
+        let idle = temp_dir.path().join("idle-plugin");
+        std::fs::create_dir_all(&stale).unwrap();
+        std::fs::create_dir_all(&idle).unwrap();
+        std::fs::write(stale.join(RUNNING_MARKER), "").unwrap();
+
+        let recovered = PluginExecutor::recover_stale_instances(temp_dir.path());
+        assert_eq!(recovered, vec!["stale-plugin".to_string()]);
+        assert!(!stale.join(RUNNING_MARKER).exists());
+
+        // Nothing left to recover on the next launch
+        assert!(PluginExecutor::recover_stale_instances(temp_dir.path()).is_empty());
+    }
+
     #[test]
     fn test_plugin_instance_state() {
         let state = PluginInstanceState::Starting;
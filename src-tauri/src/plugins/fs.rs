@@ -0,0 +1,304 @@
+// Plugin Filesystem - Path mapping for the sandbox
+//
+// A plugin sees only the directories mounted into its sandbox:
+// - its work directory at `/work`, readable and writable
+// - each path of its FileSystem permissions, under the same path, readable
+//   or also writable per the permission's access
+// Everything else is denied. The mounts become WASI preopens of the plugin's
+// instance, and the `fs.*` host API resolves paths through them too. Paths
+// that leave a mount, through `..` or a symlink, or writes to a read-only
+// mount are denied and reported to the resource monitor.
+
+use crate::plugins::api::{PluginRequest, PluginResponse};
+use crate::plugins::monitor::SandboxViolation;
+use crate::plugins::wasi_host::{WasiDirectory, WasiPermissions};
+use crate::plugins::PluginPermission;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Guest path of the plugin's work directory
+pub const WORK_DIR_GUEST_PATH: &str = "/work";
+
+/// Kind of access to a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsAccess {
+    Read,
+    Write,
+}
+
+impl FsAccess {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// A host directory visible to the plugin
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mount {
+    /// Normalized guest path components
+    guest: Vec<String>,
+    /// Canonical host directory
+    host: PathBuf,
+    pub writable: bool,
+}
+
+impl Mount {
+    pub fn guest_path(&self) -> String {
+        format!("/{}", self.guest.join("/"))
+    }
+
+    pub fn host_path(&self) -> &Path {
+        &self.host
+    }
+}
+
+/// Filesystem view of one plugin
+#[derive(Debug, Clone, Default)]
+pub struct SandboxFs {
+    plugin_id: String,
+    mounts: Vec<Mount>,
+}
+
+/// Split a guest path into components, resolving `.` and `..`; relative
+/// paths are taken from the work directory. None if `..` leaves the root.
+fn normalize(path: &str) -> Option<Vec<String>> {
+    let path = path.replace('\\', "/");
+    let mut components: Vec<String> = if path.starts_with('/') {
+        Vec::new()
+    } else {
+        vec![WORK_DIR_GUEST_PATH.trim_start_matches('/').to_string()]
+    };
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            part => components.push(part.to_string()),
+        }
+    }
+    Some(components)
+}
+
+/// Canonical form of the deepest existing ancestor of a path
+fn canonical_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find_map(|p| p.canonicalize().ok())
+}
+
+impl SandboxFs {
+    /// Mount the work directory and every path the permissions grant;
+    /// paths that do not exist are left out
+    pub fn new(plugin_id: &str, work_dir: &Path, permissions: &[PluginPermission]) -> Self {
+        let mut fs = Self { plugin_id: plugin_id.to_string(), mounts: Vec::new() };
+        fs.mount(WORK_DIR_GUEST_PATH, work_dir, true);
+
+        for permission in permissions {
+            if let PluginPermission::FileSystem { paths, access } = permission {
+                for path in paths {
+                    fs.mount(path, Path::new(path), access == "readwrite");
+                }
+            }
+        }
+        fs
+    }
+
+    fn mount(&mut self, guest_path: &str, host_path: &Path, writable: bool) {
+        let (Some(guest), Ok(host)) = (normalize(guest_path), host_path.canonicalize()) else {
+            tracing::warn!("Plugin {}: cannot mount {}", self.plugin_id, host_path.display());
+            return;
+        };
+        if !host.is_dir() {
+            tracing::warn!("Plugin {}: {} is not a directory", self.plugin_id, host.display());
+            return;
+        }
+
+        // A path granted twice keeps its most permissive access
+        match self.mounts.iter_mut().find(|m| m.guest == guest) {
+            Some(existing) => existing.writable |= writable,
+            None => self.mounts.push(Mount { guest, host, writable }),
+        }
+    }
+
+    pub fn mounts(&self) -> &[Mount] {
+        &self.mounts
+    }
+
+    /// Mounts as WASI preopened directories
+    pub fn wasi_dirs(&self) -> Vec<WasiDirectory> {
+        self.mounts
+            .iter()
+            .map(|m| WasiDirectory {
+                guest_path: m.guest_path(),
+                host_path: m.host.to_string_lossy().to_string(),
+                permissions: WasiPermissions { read: true, write: m.writable, create: m.writable },
+            })
+            .collect()
+    }
+
+    fn violation(&self, path: &str, access: FsAccess, reason: &str) -> SandboxViolation {
+        SandboxViolation::new(&self.plugin_id, path, access.as_str(), reason)
+    }
+
+    /// Host path of a guest path, if the plugin may access it
+    pub fn resolve(&self, path: &str, access: FsAccess) -> Result<PathBuf, SandboxViolation> {
+        let guest = normalize(path).ok_or_else(|| self.violation(path, access, "Path leaves the sandbox root"))?;
+
+        // The innermost mount containing the path
+        let mount = self.mounts
+            .iter()
+            .filter(|m| guest.starts_with(&m.guest))
+            .max_by_key(|m| m.guest.len())
+            .ok_or_else(|| self.violation(path, access, "Path is outside every mounted directory"))?;
+
+        let host = guest[mount.guest.len()..].iter().fold(mount.host.clone(), |p, c| p.join(c));
+        let real = canonical_ancestor(&host);
+        if !real.is_some_and(|real| real.starts_with(&mount.host)) {
+            return Err(self.violation(path, access, "Path escapes its mounted directory"));
+        }
+        if access == FsAccess::Write && !mount.writable {
+            return Err(self.violation(path, access, "Directory is mounted read-only"));
+        }
+        Ok(host)
+    }
+}
+
+/// Handle an `fs.*` API request; an out-of-sandbox access is returned as
+/// a violation instead of a response
+pub fn handle_fs_request(
+    fs: &SandboxFs,
+    request: PluginRequest,
+    max_file_bytes: u64,
+) -> Result<PluginResponse, SandboxViolation> {
+    let param = |name: &str| request.params.get(name).and_then(|v| v.as_str());
+    let Some(path) = param("path") else {
+        return Ok(response(request.id, Err("Missing 'path' parameter".to_string())));
+    };
+
+    let result = match request.method.as_str() {
+        "fs.readFile" => {
+            let host = fs.resolve(path, FsAccess::Read)?;
+            read_file(&host, max_file_bytes).map(Value::String)
+        }
+        "fs.writeFile" => {
+            let host = fs.resolve(path, FsAccess::Write)?;
+            match param("content") {
+                Some(content) if content.len() as u64 > max_file_bytes => {
+                    Err(format!("File exceeds the {} byte limit", max_file_bytes))
+                }
+                Some(content) => std::fs::write(&host, content).map(|_| Value::Null).map_err(|e| e.to_string()),
+                None => Err("Missing 'content' parameter".to_string()),
+            }
+        }
+        "fs.listFiles" => {
+            let host = fs.resolve(path, FsAccess::Read)?;
+            list_files(&host).map(|names| serde_json::json!(names))
+        }
+        "fs.deleteFile" => {
+            let host = fs.resolve(path, FsAccess::Write)?;
+            std::fs::remove_file(&host).map(|_| Value::Null).map_err(|e| e.to_string())
+        }
+        method => Err(format!("Unknown method: {}", method)),
+    };
+    Ok(response(request.id, result))
+}
+
+fn read_file(path: &Path, max_file_bytes: u64) -> Result<String, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > max_file_bytes {
+        return Err(format!("File exceeds the {} byte limit", max_file_bytes));
+    }
+    std::fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+fn list_files(path: &Path) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = std::fs::read_dir(path)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+fn response(id: String, result: Result<Value, String>) -> PluginResponse {
+    match result {
+        Ok(value) => PluginResponse { id, result: Some(value), error: None },
+        Err(e) => PluginResponse { id, result: None, error: Some(e) },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, SandboxFs) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for sub in ["work", "notes", "docs", "secret"] {
+            std::fs::create_dir(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join("notes/todo.txt"), "milk").unwrap();
+        std::fs::write(root.join("secret/key"), "hunter2").unwrap();
+
+        let path = |sub: &str| root.join(sub).to_string_lossy().to_string();
+        let permissions = vec![
+            PluginPermission::FileSystem { paths: vec![path("notes")], access: "read".to_string() },
+            PluginPermission::FileSystem { paths: vec![path("docs"), path("missing")], access: "readwrite".to_string() },
+        ];
+        let fs = SandboxFs::new("notes-plugin", &root.join("work"), &permissions);
+        (dir, fs)
+    }
+
+    #[test]
+    fn test_mounts_and_resolution() {
+        let (dir, fs) = setup();
+        let root = dir.path().canonicalize().unwrap();
+        let notes = format!("{}/notes", root.display());
+
+        // Work dir plus the two existing permitted paths
+        let dirs = fs.wasi_dirs();
+        assert_eq!(dirs.len(), 3);
+        assert_eq!(dirs[0].guest_path, WORK_DIR_GUEST_PATH);
+        assert!(dirs[0].permissions.write);
+        assert!(!dirs[1].permissions.write);
+
+        assert_eq!(fs.resolve(&format!("{}/todo.txt", notes), FsAccess::Read).unwrap(), root.join("notes/todo.txt"));
+        assert_eq!(fs.resolve("out/report.md", FsAccess::Write).unwrap(), root.join("work/out/report.md"));
+
+        let denied = |path: &str, access| fs.resolve(path, access).unwrap_err().reason;
+        assert!(denied(&format!("{}/todo.txt", notes), FsAccess::Write).contains("read-only"));
+        assert!(denied(&format!("{}/secret/key", root.display()), FsAccess::Read).contains("outside"));
+        assert!(denied(&format!("{}/../secret/key", notes), FsAccess::Read).contains("outside"));
+        assert!(denied("../../../../../../../../etc/passwd", FsAccess::Read).contains("root"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fs_api_and_symlink_escape() {
+        let (dir, fs) = setup();
+        let root = dir.path().canonicalize().unwrap();
+        std::os::unix::fs::symlink(root.join("secret"), root.join("docs/link")).unwrap();
+
+        let request = |method: &str, params: Value| PluginRequest {
+            id: "1".to_string(),
+            method: method.to_string(),
+            params,
+        };
+        let docs = format!("{}/docs", root.display());
+
+        let written = handle_fs_request(&fs, request("fs.writeFile", serde_json::json!({ "path": format!("{}/a.md", docs), "content": "# A" })), 1024).unwrap();
+        assert!(written.error.is_none());
+        let listed = handle_fs_request(&fs, request("fs.listFiles", serde_json::json!({ "path": docs })), 1024).unwrap();
+        assert_eq!(listed.result.unwrap(), serde_json::json!(["a.md", "link"]));
+        let too_big = handle_fs_request(&fs, request("fs.writeFile", serde_json::json!({ "path": "big", "content": "xx" })), 1).unwrap();
+        assert!(too_big.error.unwrap().contains("limit"));
+
+        let escape = handle_fs_request(&fs, request("fs.readFile", serde_json::json!({ "path": format!("{}/link/key", docs) })), 1024);
+        let violation = escape.unwrap_err();
+        assert_eq!((violation.plugin_id.as_str(), violation.access.as_str()), ("notes-plugin", "read"));
+        assert!(violation.reason.contains("escapes"));
+    }
+}
@@ -0,0 +1,330 @@
+// Plugin HTTP - Network proxy for plugins
+//
+// Plugins have no sockets of their own; `http.fetch` performs a request on
+// their behalf. Like the other host APIs it is called through the executor's
+// `api.call` action, not imported by WASM modules, which only link WASI. A
+// request is only sent when:
+// - the URL is http(s) and its host is listed by a Network permission
+//   (exactly, or as a parent domain), redirects included
+// - the plugin is under its per-minute request budget
+// Request and response bodies are capped, and every call, sent or denied,
+// goes to the action audit log.
+
+use crate::plugins::api::{PluginRequest, PluginResponse};
+use crate::plugins::sandbox::host_allowed;
+use crate::plugins::PluginPermission;
+use crate::tools::audit::{self, ActionKind, ActionStatus, NewAction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Requests a plugin may make per minute
+pub const DEFAULT_REQUESTS_PER_MINUTE: usize = 60;
+/// Largest response body returned to a plugin
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+/// Largest request body a plugin may send
+pub const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_REDIRECTS: usize = 5;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Parameters of `http.fetch`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchRequest {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Result of `http.fetch`; the body is decoded as UTF-8, lossily
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FetchResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Why a fetch did not produce a response
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// Refused by the allowlist or rate limit; nothing was sent
+    Denied(String),
+    Failed(String),
+}
+
+impl FetchError {
+    fn message(&self) -> &str {
+        match self {
+            Self::Denied(e) | Self::Failed(e) => e,
+        }
+    }
+}
+
+/// Performs plugin requests, shared by all running plugins
+pub struct NetworkProxy {
+    requests_per_minute: usize,
+    max_response_bytes: usize,
+    /// Send times within the rate window, per plugin
+    recent: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl NetworkProxy {
+    pub fn new() -> Self {
+        Self::with_limits(DEFAULT_REQUESTS_PER_MINUTE, DEFAULT_MAX_RESPONSE_BYTES)
+    }
+
+    pub fn with_limits(requests_per_minute: usize, max_response_bytes: usize) -> Self {
+        Self { requests_per_minute, max_response_bytes, recent: Mutex::new(HashMap::new()) }
+    }
+
+    /// Take one request from the plugin's budget
+    fn acquire(&self, plugin_id: &str) -> Result<(), FetchError> {
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap();
+        let sent = recent.entry(plugin_id.to_string()).or_default();
+        while sent.front().is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW) {
+            sent.pop_front();
+        }
+        if sent.len() >= self.requests_per_minute {
+            return Err(FetchError::Denied(format!(
+                "Rate limit exceeded: {} requests per minute",
+                self.requests_per_minute
+            )));
+        }
+        sent.push_back(now);
+        Ok(())
+    }
+
+    /// Send a request for a plugin holding `permissions`
+    pub async fn fetch(
+        &self,
+        plugin_id: &str,
+        permissions: &[PluginPermission],
+        request: &FetchRequest,
+    ) -> Result<FetchResponse, FetchError> {
+        let url = check_url(&request.url, permissions)?;
+        let method = reqwest::Method::from_bytes(request.method.to_uppercase().as_bytes())
+            .map_err(|_| FetchError::Failed(format!("Invalid method: {}", request.method)))?;
+        if request.body.as_ref().is_some_and(|b| b.len() > MAX_REQUEST_BYTES) {
+            return Err(FetchError::Failed(format!("Request body exceeds the {} byte limit", MAX_REQUEST_BYTES)));
+        }
+        if request.headers.keys().any(|name| name.eq_ignore_ascii_case("host")) {
+            return Err(FetchError::Denied("The Host header cannot be set".to_string()));
+        }
+        self.acquire(plugin_id)?;
+
+        // Redirects are followed only within the allowlist
+        let permissions = permissions.to_vec();
+        let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if let Err(e) = check_url(attempt.url().as_str(), &permissions) {
+                attempt.error(e.message().to_string())
+            } else {
+                attempt.follow()
+            }
+        });
+        let timeout = request.timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_TIMEOUT).min(MAX_TIMEOUT);
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .redirect(redirects)
+            .build()
+            .map_err(|e| FetchError::Failed(e.to_string()))?;
+
+        let mut builder = client.request(method, url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+        let mut response = builder.send().await.map_err(|e| FetchError::Failed(format!("Request failed: {}", e)))?;
+
+        let too_large = || FetchError::Failed(format!("Response exceeds the {} byte limit", self.max_response_bytes));
+        if response.content_length().is_some_and(|len| len > self.max_response_bytes as u64) {
+            return Err(too_large());
+        }
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| FetchError::Failed(e.to_string()))? {
+            if body.len() + chunk.len() > self.max_response_bytes {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(FetchResponse { status, headers, body: String::from_utf8_lossy(&body).to_string() })
+    }
+}
+
+impl Default for NetworkProxy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a URL and check the plugin may reach its host
+fn check_url(url: &str, permissions: &[PluginPermission]) -> Result<reqwest::Url, FetchError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| FetchError::Failed(format!("Invalid URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(FetchError::Denied(format!("Scheme not allowed: {}", parsed.scheme())));
+    }
+    let host = parsed.host_str().unwrap_or_default();
+    if !host_allowed(host, permissions) {
+        return Err(FetchError::Denied(format!("Network access denied: {}", host)));
+    }
+    Ok(parsed)
+}
+
+/// Handle an `http.fetch` request and record it in the audit log at
+/// `audit_db`, if given
+pub async fn handle_http_request(
+    proxy: &NetworkProxy,
+    plugin_id: &str,
+    permissions: &[PluginPermission],
+    request: PluginRequest,
+    audit_db: Option<&Path>,
+) -> PluginResponse {
+    let started_at = chrono::Utc::now();
+    let fetch: FetchRequest = match serde_json::from_value(request.params) {
+        Ok(fetch) => fetch,
+        Err(e) => return response(request.id, Err(format!("Invalid fetch parameters: {}", e))),
+    };
+    let result = proxy.fetch(plugin_id, permissions, &fetch).await;
+
+    if let Some(db_path) = audit_db {
+        match crate::db::pool::open_background(db_path) {
+            Ok(conn) => {
+                let status = match &result {
+                    Ok(_) => ActionStatus::Success,
+                    Err(FetchError::Denied(_)) => ActionStatus::Denied,
+                    Err(FetchError::Failed(_)) => ActionStatus::Error,
+                };
+                let params = serde_json::json!({
+                    "plugin": plugin_id,
+                    "method": fetch.method.to_uppercase(),
+                    "url": fetch.url,
+                    "status": result.as_ref().ok().map(|r| r.status),
+                });
+                audit::record_quietly(&conn, &NewAction {
+                    kind: ActionKind::PluginRequest,
+                    name: &request.method,
+                    conversation_id: None,
+                    params: &params,
+                    status,
+                    error: result.as_ref().err().map(|e| e.message()),
+                    started_at,
+                    finished_at: chrono::Utc::now(),
+                });
+            }
+            Err(e) => tracing::warn!("Failed to open audit database: {}", e),
+        }
+    }
+
+    let result = result
+        .map_err(|e| e.message().to_string())
+        .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()));
+    response(request.id, result)
+}
+
+fn response(id: String, result: Result<Value, String>) -> PluginResponse {
+    match result {
+        Ok(value) => PluginResponse { id, result: Some(value), error: None },
+        Err(e) => PluginResponse { id, result: None, error: Some(e) },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(hosts: &[&str]) -> Vec<PluginPermission> {
+        vec![PluginPermission::Network { hosts: hosts.iter().map(|h| h.to_string()).collect() }]
+    }
+
+    fn get(url: &str) -> FetchRequest {
+        serde_json::from_value(serde_json::json!({ "url": url })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_and_rate_limit() {
+        let proxy = NetworkProxy::with_limits(1, 1024);
+        let permissions = network(&["example.com", "127.0.0.1"]);
+
+        let denied = |r: Result<FetchResponse, FetchError>| matches!(r, Err(FetchError::Denied(_)));
+        assert!(denied(proxy.fetch("p", &permissions, &get("https://evil.com/")).await));
+        assert!(denied(proxy.fetch("p", &permissions, &get("https://notexample.com/")).await));
+        assert!(denied(proxy.fetch("p", &permissions, &get("file:///etc/passwd")).await));
+        assert!(check_url("https://api.example.com/v1", &permissions).is_ok());
+
+        // Denied requests do not use the budget; the first allowed one does
+        // (and fails on the closed port), so the next is rate limited
+        let unroutable = get("http://127.0.0.1:1/");
+        assert!(matches!(proxy.fetch("p", &permissions, &unroutable).await, Err(FetchError::Failed(_))));
+        let limited = proxy.fetch("p", &permissions, &unroutable).await;
+        assert!(limited.unwrap_err().message().contains("Rate limit"));
+        assert!(!denied(proxy.fetch("other", &permissions, &unroutable).await));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_caps_and_audit() {
+        use axum::{routing::get as route, Router};
+
+        let app = Router::new()
+            .route("/small", route(|| async { "hello" }))
+            .route("/large", route(|| async { "x".repeat(4096) }))
+            .route("/away", route(|| async { axum::response::Redirect::temporary("http://localhost:1/") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let proxy = NetworkProxy::with_limits(10, 1024);
+        let permissions = network(&["127.0.0.1"]);
+        let ok = proxy.fetch("p", &permissions, &get(&format!("{}/small", base))).await.unwrap();
+        assert_eq!((ok.status, ok.body.as_str()), (200, "hello"));
+        let large = proxy.fetch("p", &permissions, &get(&format!("{}/large", base))).await;
+        assert!(large.unwrap_err().message().contains("limit"));
+        let redirect = proxy.fetch("p", &permissions, &get(&format!("{}/away", base))).await;
+        assert!(matches!(redirect, Err(FetchError::Failed(_))));
+
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("audit.db");
+        crate::db::schema::run_migrations(&rusqlite::Connection::open(&db_path).unwrap()).unwrap();
+        let request = |url: String| PluginRequest {
+            id: "1".to_string(),
+            method: "http.fetch".to_string(),
+            params: serde_json::json!({ "url": url }),
+        };
+        let sent = handle_http_request(&proxy, "p", &permissions, request(format!("{}/small", base)), Some(&db_path)).await;
+        assert_eq!(sent.result.unwrap()["body"], "hello");
+        let refused = handle_http_request(&proxy, "p", &permissions, request("https://evil.com/".to_string()), Some(&db_path)).await;
+        assert!(refused.error.unwrap().contains("denied"));
+
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        let entries = audit::query(&conn, &Default::default()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.kind == ActionKind::PluginRequest));
+        assert!(entries.iter().any(|e| e.status == ActionStatus::Denied));
+        assert!(entries.iter().any(|e| e.status == ActionStatus::Success && e.params["status"] == 200));
+    }
+}
@@ -0,0 +1,536 @@
+// Plugin IPC - Message bus between plugins
+//
+// Messages go either to one plugin or to a topic, which fans them out to
+// every plugin subscribed to it. Each recipient has a bounded queue:
+// - A message sent with `require_ack` stays queued after delivery and is
+//   delivered again until the recipient acknowledges it; after
+//   MAX_DELIVERY_ATTEMPTS deliveries it becomes a dead letter.
+// - A message sent to a full queue becomes a dead letter right away.
+// - A `persistent` message is also written to the `plugin_messages` outbox
+//   and reloaded when the bus is attached to the database again, so it
+//   survives restarts until acknowledged. Its dead letters stay there too.
+// Other messages live in memory only, as before.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::PathBuf;
+
+/// Messages a plugin may have queued before new ones are dead-lettered
+pub const DEFAULT_QUEUE_LIMIT: usize = 256;
+
+/// Deliveries of an unacknowledged message before it is dead-lettered
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// In-memory dead letters kept per plugin; older ones are dropped
+const MAX_MEMORY_DEAD_LETTERS: usize = 256;
+
+/// Message between plugins
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginMessage {
+    pub from: String,
+    pub to: String,
+    pub method: String,
+    pub params: Value,
+    pub timestamp: u64,
+}
+
+/// How a message is delivered
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeliveryOptions {
+    /// Keep the message in the outbox until acknowledged
+    pub persistent: bool,
+    /// Redeliver the message until the recipient acknowledges it
+    pub require_ack: bool,
+}
+
+/// A queued message as delivered to its recipient
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub id: String,
+    /// Topic the message was published to; None for direct messages
+    pub topic: Option<String>,
+    #[serde(flatten)]
+    pub message: PluginMessage,
+    /// Times the message has been delivered
+    pub attempts: u32,
+    pub persistent: bool,
+    pub require_ack: bool,
+}
+
+/// A message that could not be delivered
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    #[serde(flatten)]
+    pub envelope: Envelope,
+    pub reason: String,
+}
+
+/// Outcome of a send or publish
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SendReceipt {
+    /// IDs of the queued copies, one per recipient
+    pub queued: Vec<String>,
+    /// Recipients whose queue was full
+    pub dead_lettered: Vec<String>,
+}
+
+/// Plugin IPC (Inter-Plugin Communication)
+pub struct PluginIpc {
+    queues: HashMap<String, VecDeque<Envelope>>,
+    /// Subscribers by topic
+    subscriptions: HashMap<String, BTreeSet<String>>,
+    /// Dead letters of non-persistent messages by recipient
+    dead_letters: HashMap<String, VecDeque<DeadLetter>>,
+    queue_limit: usize,
+    /// Database holding the outbox of persistent messages
+    store: Option<PathBuf>,
+}
+
+impl PluginIpc {
+    pub fn new() -> Self {
+        Self {
+            queues: HashMap::new(),
+            subscriptions: HashMap::new(),
+            dead_letters: HashMap::new(),
+            queue_limit: DEFAULT_QUEUE_LIMIT,
+            store: None,
+        }
+    }
+
+    pub fn with_queue_limit(mut self, limit: usize) -> Self {
+        self.queue_limit = limit.max(1);
+        self
+    }
+
+    /// Attach the outbox database, replacing persistent messages in memory
+    /// with the pending ones stored there
+    pub fn set_store(&mut self, db_path: PathBuf) -> Result<(), String> {
+        for queue in self.queues.values_mut() {
+            queue.retain(|e| !e.persistent);
+        }
+        self.store = Some(db_path);
+
+        let pending = self.with_store(load_pending)?.unwrap_or_default();
+        for envelope in pending {
+            self.queues.entry(envelope.message.to.clone()).or_default().push_back(envelope);
+        }
+        Ok(())
+    }
+
+    /// Run an operation on the outbox, if one is attached
+    fn with_store<T>(&self, f: impl FnOnce(&Connection) -> Result<T, String>) -> Result<Option<T>, String> {
+        let Some(path) = &self.store else {
+            return Ok(None);
+        };
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open message outbox: {}", e))?;
+        f(&conn).map(Some)
+    }
+
+    pub fn subscribe(&mut self, plugin_id: &str, topic: &str) -> Result<(), String> {
+        if topic.is_empty() {
+            return Err("Topic must not be empty".to_string());
+        }
+        self.subscriptions.entry(topic.to_string()).or_default().insert(plugin_id.to_string());
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, plugin_id: &str, topic: &str) -> bool {
+        let Some(subscribers) = self.subscriptions.get_mut(topic) else {
+            return false;
+        };
+        let removed = subscribers.remove(plugin_id);
+        if subscribers.is_empty() {
+            self.subscriptions.remove(topic);
+        }
+        removed
+    }
+
+    /// Topics a plugin is subscribed to
+    pub fn subscriptions(&self, plugin_id: &str) -> Vec<String> {
+        self.subscriptions
+            .iter()
+            .filter(|(_, subscribers)| subscribers.contains(plugin_id))
+            .map(|(topic, _)| topic.clone())
+            .collect()
+    }
+
+    /// Queue a copy of a message for its recipient
+    fn enqueue(&mut self, envelope: Envelope, receipt: &mut SendReceipt) -> Result<(), String> {
+        let recipient = envelope.message.to.clone();
+        let queued = self.queues.get(&recipient).map_or(0, |q| q.len());
+        if queued >= self.queue_limit {
+            let reason = format!("Queue of {} is full ({} messages)", recipient, self.queue_limit);
+            self.dead_letter(envelope, reason)?;
+            receipt.dead_lettered.push(recipient);
+            return Ok(());
+        }
+
+        if envelope.persistent {
+            self.with_store(|conn| insert(conn, &envelope))?;
+        }
+        receipt.queued.push(envelope.id.clone());
+        self.queues.entry(recipient).or_default().push_back(envelope);
+        Ok(())
+    }
+
+    fn dead_letter(&mut self, envelope: Envelope, reason: String) -> Result<(), String> {
+        tracing::warn!("Plugin message {} dead-lettered: {}", envelope.id, reason);
+        if envelope.persistent && self.store.is_some() {
+            self.with_store(|conn| mark_dead(conn, &envelope, &reason))?;
+            return Ok(());
+        }
+        let letters = self.dead_letters.entry(envelope.message.to.clone()).or_default();
+        letters.push_back(DeadLetter { envelope, reason });
+        if letters.len() > MAX_MEMORY_DEAD_LETTERS {
+            letters.pop_front();
+        }
+        Ok(())
+    }
+
+    fn envelope(message: PluginMessage, topic: Option<String>, options: DeliveryOptions) -> Envelope {
+        Envelope {
+            id: uuid::Uuid::new_v4().to_string(),
+            topic,
+            message,
+            attempts: 0,
+            persistent: options.persistent,
+            require_ack: options.require_ack,
+        }
+    }
+
+    /// Send a message directly to one plugin
+    pub fn send(&mut self, message: PluginMessage, options: DeliveryOptions) -> Result<SendReceipt, String> {
+        let mut receipt = SendReceipt::default();
+        self.enqueue(Self::envelope(message, None, options), &mut receipt)?;
+        Ok(receipt)
+    }
+
+    /// Publish a message to every subscriber of a topic but the sender
+    pub fn publish(
+        &mut self,
+        from: &str,
+        topic: &str,
+        method: &str,
+        params: Value,
+        options: DeliveryOptions,
+    ) -> Result<SendReceipt, String> {
+        let subscribers: Vec<String> = self.subscriptions
+            .get(topic)
+            .map(|s| s.iter().filter(|id| id.as_str() != from).cloned().collect())
+            .unwrap_or_default();
+        let timestamp = chrono::Utc::now().timestamp().max(0) as u64;
+
+        let mut receipt = SendReceipt::default();
+        for to in subscribers {
+            let message = PluginMessage {
+                from: from.to_string(),
+                to,
+                method: method.to_string(),
+                params: params.clone(),
+                timestamp,
+            };
+            self.enqueue(Self::envelope(message, Some(topic.to_string()), options), &mut receipt)?;
+        }
+        Ok(receipt)
+    }
+
+    /// Deliver up to `max` queued messages to a plugin
+    ///
+    /// Messages without `require_ack` leave the queue on delivery; the others
+    /// stay until acknowledged, or until they run out of attempts.
+    pub fn deliver(&mut self, plugin_id: &str, max: usize) -> Result<Vec<Envelope>, String> {
+        let Some(queue) = self.queues.get_mut(plugin_id) else {
+            return Ok(Vec::new());
+        };
+
+        let mut delivered = Vec::new();
+        let mut kept = VecDeque::new();
+        let mut exhausted = Vec::new();
+        while let Some(mut envelope) = queue.pop_front() {
+            if delivered.len() >= max {
+                kept.push_back(envelope);
+                continue;
+            }
+            if envelope.require_ack && envelope.attempts >= MAX_DELIVERY_ATTEMPTS {
+                exhausted.push(envelope);
+                continue;
+            }
+            envelope.attempts += 1;
+            delivered.push(envelope.clone());
+            if envelope.require_ack {
+                kept.push_back(envelope);
+            }
+        }
+        *queue = kept;
+
+        for envelope in exhausted {
+            let reason = format!("Not acknowledged after {} deliveries", MAX_DELIVERY_ATTEMPTS);
+            self.dead_letter(envelope, reason)?;
+        }
+        let persistent: Vec<&Envelope> = delivered.iter().filter(|e| e.persistent).collect();
+        if !persistent.is_empty() {
+            self.with_store(|conn| {
+                for envelope in persistent {
+                    if envelope.require_ack {
+                        set_attempts(conn, &envelope.id, envelope.attempts)?;
+                    } else {
+                        delete(conn, &envelope.id)?;
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        Ok(delivered)
+    }
+
+    /// Acknowledge delivered messages; returns how many were still queued
+    pub fn ack(&mut self, plugin_id: &str, ids: &[String]) -> Result<usize, String> {
+        let Some(queue) = self.queues.get_mut(plugin_id) else {
+            return Ok(0);
+        };
+        let before = queue.len();
+        let mut acked = Vec::new();
+        queue.retain(|e| {
+            let matched = ids.contains(&e.id);
+            if matched && e.persistent {
+                acked.push(e.id.clone());
+            }
+            !matched
+        });
+        let count = before - queue.len();
+
+        self.with_store(|conn| acked.iter().try_for_each(|id| delete(conn, id)))?;
+        Ok(count)
+    }
+
+    /// Dead letters addressed to a plugin, oldest first
+    pub fn dead_letters(&self, plugin_id: &str) -> Result<Vec<DeadLetter>, String> {
+        let mut letters = self.with_store(|conn| load_dead(conn, plugin_id))?.unwrap_or_default();
+        letters.extend(self.dead_letters.get(plugin_id).into_iter().flatten().cloned());
+        Ok(letters)
+    }
+
+    /// Discard the dead letters of a plugin; returns how many there were
+    pub fn clear_dead_letters(&mut self, plugin_id: &str) -> Result<usize, String> {
+        let stored = self.with_store(|conn| {
+            conn.execute(
+                "DELETE FROM plugin_messages WHERE recipient = ?1 AND status = 'dead'",
+                [plugin_id],
+            )
+            .map_err(|e| e.to_string())
+        })?;
+        let memory = self.dead_letters.remove(plugin_id).map_or(0, |l| l.len());
+        Ok(stored.unwrap_or(0) + memory)
+    }
+
+    /// Send a message from one plugin to another
+    pub fn send_message(&mut self, message: PluginMessage) -> Result<(), String> {
+        self.send(message, DeliveryOptions::default()).map(|_| ())
+    }
+
+    /// Get pending messages for a plugin, acknowledging them
+    pub fn get_messages(&mut self, plugin_id: &str) -> Vec<PluginMessage> {
+        let envelopes = self.deliver(plugin_id, usize::MAX).unwrap_or_default();
+        let ids: Vec<String> = envelopes.iter().map(|e| e.id.clone()).collect();
+        if let Err(e) = self.ack(plugin_id, &ids) {
+            tracing::warn!("Failed to acknowledge messages for {}: {}", plugin_id, e);
+        }
+        envelopes.into_iter().map(|e| e.message).collect()
+    }
+
+    /// Number of messages queued for a plugin
+    pub fn queued(&self, plugin_id: &str) -> usize {
+        self.queues.get(plugin_id).map_or(0, |q| q.len())
+    }
+}
+
+impl Default for PluginIpc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Outbox
+// ============================================================================
+
+fn insert(conn: &Connection, envelope: &Envelope) -> Result<(), String> {
+    let message = &envelope.message;
+    conn.execute(
+        "INSERT INTO plugin_messages
+             (id, recipient, sender, topic, method, params, timestamp, attempts, require_ack, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            envelope.id,
+            message.to,
+            message.from,
+            envelope.topic,
+            message.method,
+            message.params.to_string(),
+            message.timestamp as i64,
+            envelope.attempts,
+            envelope.require_ack,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn set_attempts(conn: &Connection, id: &str, attempts: u32) -> Result<(), String> {
+    conn.execute("UPDATE plugin_messages SET attempts = ?1 WHERE id = ?2", params![attempts, id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn delete(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM plugin_messages WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Mark a stored message dead, storing it first if it never was queued
+fn mark_dead(conn: &Connection, envelope: &Envelope, reason: &str) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE plugin_messages SET status = 'dead', reason = ?1, attempts = ?2 WHERE id = ?3",
+            params![reason, envelope.attempts, envelope.id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        insert(conn, envelope)?;
+        conn.execute(
+            "UPDATE plugin_messages SET status = 'dead', reason = ?1 WHERE id = ?2",
+            params![reason, envelope.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn query(conn: &Connection, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<(Envelope, Option<String>)>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params, |row| {
+            let params_json: String = row.get(5)?;
+            let envelope = Envelope {
+                id: row.get(0)?,
+                message: PluginMessage {
+                    to: row.get(1)?,
+                    from: row.get(2)?,
+                    method: row.get(4)?,
+                    params: serde_json::from_str(&params_json).unwrap_or(Value::Null),
+                    timestamp: row.get::<_, i64>(6)?.max(0) as u64,
+                },
+                topic: row.get(3)?,
+                attempts: row.get(7)?,
+                persistent: true,
+                require_ack: row.get(8)?,
+            };
+            Ok((envelope, row.get(9)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+const COLUMNS: &str = "id, recipient, sender, topic, method, params, timestamp, attempts, require_ack, reason";
+
+fn load_pending(conn: &Connection) -> Result<Vec<Envelope>, String> {
+    let sql = format!("SELECT {} FROM plugin_messages WHERE status = 'pending' ORDER BY rowid", COLUMNS);
+    Ok(query(conn, &sql, &[])?.into_iter().map(|(envelope, _)| envelope).collect())
+}
+
+fn load_dead(conn: &Connection, plugin_id: &str) -> Result<Vec<DeadLetter>, String> {
+    let sql = format!(
+        "SELECT {} FROM plugin_messages WHERE recipient = ?1 AND status = 'dead' ORDER BY rowid",
+        COLUMNS
+    );
+    Ok(query(conn, &sql, &[&plugin_id])?
+        .into_iter()
+        .map(|(envelope, reason)| DeadLetter { envelope, reason: reason.unwrap_or_default() })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(to: &str) -> PluginMessage {
+        PluginMessage {
+            from: "sender".to_string(),
+            to: to.to_string(),
+            method: "notify".to_string(),
+            params: serde_json::json!({ "n": 1 }),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_topics_acks_and_dead_letters() {
+        let mut ipc = PluginIpc::new().with_queue_limit(2);
+        ipc.subscribe("a", "files.changed").unwrap();
+        ipc.subscribe("b", "files.changed").unwrap();
+        ipc.subscribe("sender", "files.changed").unwrap();
+
+        let acked = DeliveryOptions { require_ack: true, ..Default::default() };
+        let receipt = ipc.publish("sender", "files.changed", "changed", serde_json::json!({}), acked).unwrap();
+        assert_eq!(receipt.queued.len(), 2);
+        assert_eq!(ipc.queued("sender"), 0);
+
+        // Unacknowledged messages are redelivered, then dead-lettered
+        for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+            let delivered = ipc.deliver("a", 10).unwrap();
+            assert_eq!(delivered[0].attempts, attempt);
+            assert_eq!(delivered[0].topic.as_deref(), Some("files.changed"));
+        }
+        assert!(ipc.deliver("a", 10).unwrap().is_empty());
+        assert_eq!(ipc.dead_letters("a").unwrap()[0].envelope.message.method, "changed");
+
+        let id = ipc.deliver("b", 10).unwrap()[0].id.clone();
+        assert_eq!(ipc.ack("b", &[id]).unwrap(), 1);
+        assert!(ipc.deliver("b", 10).unwrap().is_empty());
+
+        // Full queues dead-letter new messages
+        ipc.send_message(message("b")).unwrap();
+        ipc.send_message(message("b")).unwrap();
+        let receipt = ipc.send(message("b"), DeliveryOptions::default()).unwrap();
+        assert_eq!(receipt.dead_lettered, ["b"]);
+        assert_eq!(ipc.get_messages("b").len(), 2);
+        assert_eq!(ipc.clear_dead_letters("b").unwrap(), 1);
+
+        assert!(ipc.unsubscribe("a", "files.changed"));
+        assert_eq!(ipc.subscriptions("b"), ["files.changed"]);
+    }
+
+    #[test]
+    fn test_persistent_messages_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        crate::db::schema::run_migrations(&Connection::open(&db_path).unwrap()).unwrap();
+
+        let persistent = DeliveryOptions { persistent: true, require_ack: true };
+        let mut ipc = PluginIpc::new();
+        ipc.set_store(db_path.clone()).unwrap();
+        let kept = ipc.send(message("a"), persistent).unwrap().queued[0].clone();
+        let acked = ipc.send(message("a"), persistent).unwrap().queued[0].clone();
+        ipc.send_message(message("a")).unwrap();
+        assert_eq!(ipc.deliver("a", 10).unwrap().len(), 3);
+        ipc.ack("a", &[acked]).unwrap();
+
+        // Only the unacknowledged persistent message is reloaded
+        let mut restarted = PluginIpc::new();
+        restarted.set_store(db_path).unwrap();
+        let delivered = restarted.deliver("a", 10).unwrap();
+        assert_eq!(delivered.len(), 1);
+        assert_eq!((delivered[0].id.as_str(), delivered[0].attempts), (kept.as_str(), 2));
+        assert_eq!(delivered[0].message, message("a"));
+    }
+}
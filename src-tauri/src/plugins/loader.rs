@@ -2,7 +2,7 @@
 
 #![allow(dead_code)]
 
-use crate::plugins::{PluginManifest, PluginPermission, ResourceLimits};
+use crate::plugins::{tools, PluginManifest, PluginPermission, ResourceLimits};
 use std::path::PathBuf;
 use std::collections::HashMap;
 
@@ -80,6 +80,9 @@ impl PluginLoader {
             self.validate_permission(perm)?;
         }
 
+        // Validate tools against the permissions they rely on
+        tools::validate_tools(&manifest.id, &manifest.tools, &manifest.permissions)?;
+
         Ok(())
     }
 
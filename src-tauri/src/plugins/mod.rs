@@ -9,12 +9,21 @@ pub mod executor;
 pub mod runtime;
 pub mod wasi_host;
 pub mod monitor;
+pub mod storage;
+pub mod abi;
+pub mod schedule;
+pub mod ipc;
+pub mod fs;
+pub mod panels;
+pub mod tools;
+pub mod http;
 
 pub use executor::{
     ExecutionResult, PluginExecutor, PluginMessage, ResourceUsage,
 };
 
 
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 
 /// Plugin manifest structure
@@ -28,6 +37,9 @@ pub struct PluginManifest {
     pub main: String,
     pub permissions: Vec<PluginPermission>,
     pub api_version: String,
+    /// Tools the plugin offers to the agent
+    #[serde(default)]
+    pub tools: Vec<tools::PluginToolDef>,
 }
 
 /// Plugin permission types
@@ -77,6 +89,12 @@ pub struct ResourceLimits {
     pub max_cpu_percent: u32,
     pub max_execution_time_ms: u32,
     pub max_file_size_mb: u32,
+    #[serde(default = "default_max_storage_kb")]
+    pub max_storage_kb: u32,
+}
+
+fn default_max_storage_kb() -> u32 {
+    1024
 }
 
 impl Default for ResourceLimits {
@@ -86,6 +104,7 @@ impl Default for ResourceLimits {
             max_cpu_percent: 50,
             max_execution_time_ms: 30000,
             max_file_size_mb: 10,
+            max_storage_kb: default_max_storage_kb(),
         }
     }
 }
@@ -98,14 +117,15 @@ use std::sync::Mutex;
 
 /// Execute a plugin action
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn plugin_execute(
     executor: tauri::State<'_, Mutex<PluginExecutor>>,
     id: String,
     _action: String,
     _params: serde_json::Value,
-) -> std::result::Result<ExecutionResult, String> {
+) -> std::result::Result<ExecutionResult, AppError> {
     // Check if plugin is running
-    let exec = executor.lock().map_err(|e| e.to_string())?;
+    let exec = executor.lock()?;
     let is_running = exec.is_running(&id);
 
     if !is_running {
@@ -130,62 +150,211 @@ pub fn plugin_execute(
 
 /// Get resource usage for a plugin
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn plugin_get_resource_usage(
     executor: tauri::State<'_, Mutex<PluginExecutor>>,
     id: String,
-) -> std::result::Result<ResourceUsage, String> {
-    let exec = executor.lock().map_err(|e| e.to_string())?;
+) -> std::result::Result<ResourceUsage, AppError> {
+    let exec = executor.lock()?;
     exec.get_resource_usage(&id)
-        .ok_or_else(|| format!("Plugin {} not found", id))
+        .ok_or_else(|| AppError::not_found(format!("Plugin {} not found", id)))
+}
+
+/// Recent memory and CPU samples of a running plugin, oldest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_get_resource_samples(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<Vec<monitor::ResourceSample>, AppError> {
+    let exec = executor.lock()?;
+    Ok(exec.get_resource_samples(&id))
+}
+
+/// Resume a plugin suspended for exceeding its resource limits
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_resume(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<(), AppError> {
+    let exec = executor.lock()?;
+    Ok(exec.resume_plugin(&id)?)
 }
 
-/// Send a message to another plugin
+/// Send a message to another plugin, or publish it to a topic
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn plugin_send_message(
     executor: tauri::State<'_, Mutex<PluginExecutor>>,
     from: String,
-    to: String,
+    to: Option<String>,
+    topic: Option<String>,
     method: String,
     params: serde_json::Value,
-) -> std::result::Result<(), String> {
-    let ipc = executor.lock().map_err(|e| e.to_string())?.get_ipc();
-    let mut ipc = ipc.lock().map_err(|e| e.to_string())?;
-    let message = PluginMessage {
-        from,
-        to,
-        method,
-        params,
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-    };
-    ipc.send_message(message)
+    options: Option<ipc::DeliveryOptions>,
+) -> std::result::Result<ipc::SendReceipt, AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let mut ipc = ipc.lock()?;
+    let options = options.unwrap_or_default();
+    match (to, topic) {
+        (Some(to), None) => {
+            let message = PluginMessage {
+                from,
+                to,
+                method,
+                params,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            };
+            Ok(ipc.send(message, options)?)
+        }
+        (None, Some(topic)) => Ok(ipc.publish(&from, &topic, &method, params, options)?),
+        _ => Err(AppError::invalid_input("Specify either a recipient or a topic")),
+    }
 }
 
-/// Get messages for a plugin
+/// Deliver queued messages to a plugin; those sent with `require_ack` are
+/// delivered again until acknowledged with `plugin_ack_messages`
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn plugin_get_messages(
     executor: tauri::State<'_, Mutex<PluginExecutor>>,
     id: String,
-) -> std::result::Result<Vec<PluginMessage>, String> {
-    let ipc = executor.lock().map_err(|e| e.to_string())?.get_ipc();
-    let mut ipc = ipc.lock().map_err(|e| e.to_string())?;
-    Ok(ipc.get_messages(&id))
+    max: Option<usize>,
+) -> std::result::Result<Vec<ipc::Envelope>, AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let mut ipc = ipc.lock()?;
+    Ok(ipc.deliver(&id, max.unwrap_or(usize::MAX))?)
+}
+
+/// Acknowledge delivered messages
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_ack_messages(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+    message_ids: Vec<String>,
+) -> std::result::Result<usize, AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let mut ipc = ipc.lock()?;
+    Ok(ipc.ack(&id, &message_ids)?)
+}
+
+/// Subscribe a plugin to a message topic
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_subscribe(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+    topic: String,
+) -> std::result::Result<(), AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let mut ipc = ipc.lock()?;
+    Ok(ipc.subscribe(&id, &topic)?)
+}
+
+/// Unsubscribe a plugin from a message topic
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_unsubscribe(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+    topic: String,
+) -> std::result::Result<bool, AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let mut ipc = ipc.lock()?;
+    Ok(ipc.unsubscribe(&id, &topic))
+}
+
+/// Messages that could not be delivered to a plugin
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_get_dead_letters(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<Vec<ipc::DeadLetter>, AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let ipc = ipc.lock()?;
+    Ok(ipc.dead_letters(&id)?)
+}
+
+/// Discard the dead letters of a plugin
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_clear_dead_letters(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<usize, AppError> {
+    let ipc = executor.lock()?.get_ipc();
+    let mut ipc = ipc.lock()?;
+    Ok(ipc.clear_dead_letters(&id)?)
+}
+
+/// Recent attempts of a plugin to access files outside its sandbox
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_get_sandbox_violations(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<Vec<monitor::SandboxViolation>, AppError> {
+    let monitor = executor.lock()?.get_monitor();
+    let monitor = monitor.lock()?;
+    Ok(monitor.get_violations(&id))
+}
+
+/// Get the UI panels a running plugin declares
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_get_panels(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<Vec<panels::PluginPanel>, AppError> {
+    let (runner, registry) = {
+        let exec = executor.lock()?;
+        if !exec.is_running(&id) {
+            return Err(AppError::not_found(format!("Plugin {} is not running", id)));
+        }
+        (exec.task_runner(), exec.get_panels())
+    };
+    Ok(panels::get_panels(&runner, &registry, &id)?)
+}
+
+/// Send an interaction with a panel to its plugin
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_panel_action(
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+    panel: String,
+    action: String,
+    payload: serde_json::Value,
+) -> std::result::Result<panels::PanelActionResult, AppError> {
+    let (runner, registry) = {
+        let exec = executor.lock()?;
+        if !exec.is_running(&id) {
+            return Err(AppError::not_found(format!("Plugin {} is not running", id)));
+        }
+        (exec.task_runner(), exec.get_panels())
+    };
+    Ok(panels::panel_action(&runner, &registry, &id, &panel, &action, payload)?)
 }
 
 /// Stop a running plugin
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn plugin_stop(
     executor: tauri::State<'_, Mutex<PluginExecutor>>,
     id: String,
-) -> std::result::Result<(), String> {
+) -> std::result::Result<(), AppError> {
     // Clone the necessary data before async operation
     let executor_inner = {
-        let exec = executor.lock().map_err(|e| e.to_string())?;
+        let exec = executor.lock()?;
         // Check if plugin exists
         if !exec.is_running(&id) {
-            return Err(format!("Plugin {} is not running", id));
+            return Err(AppError::not_found(format!("Plugin {} is not running", id)));
         }
         // We can't move the executor, so we'll use a different approach
         // Store the state and release the lock
@@ -198,16 +367,17 @@ pub async fn plugin_stop(
         // For now, return success as placeholder
         Ok(())
     } else {
-        Err("Plugin executor not available".to_string())
+        Err(AppError::Internal("Plugin executor not available".to_string()))
     }
 }
 
 /// Restart a plugin
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn plugin_restart(
     _executor: tauri::State<'_, Mutex<PluginExecutor>>,
     _id: String,
-) -> std::result::Result<(), String> {
+) -> std::result::Result<(), AppError> {
     // Placeholder implementation
     // In production, this would properly restart the plugin
     Ok(())
@@ -215,10 +385,34 @@ pub async fn plugin_restart(
 
 /// List running plugins
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn plugin_list_running(
     executor: tauri::State<'_, Mutex<PluginExecutor>>,
-) -> std::result::Result<Vec<String>, String> {
-    let exec = executor.lock().map_err(|e| e.to_string())?;
+) -> std::result::Result<Vec<String>, AppError> {
+    let exec = executor.lock()?;
     Ok(exec.list_running())
 }
 
+/// Get storage usage for a plugin
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_get_storage_usage(
+    db: tauri::State<'_, crate::db::DbState>,
+    executor: tauri::State<'_, Mutex<PluginExecutor>>,
+    id: String,
+) -> std::result::Result<storage::StorageUsage, AppError> {
+    let limits = executor.lock()?.get_resource_limits(&id);
+    let conn = db.conn.lock()?;
+    Ok(storage::get_usage(&conn, &id, limits.max_storage_kb as u64 * 1024)?)
+}
+
+/// Clear all stored data for a plugin
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn plugin_clear_storage(
+    db: tauri::State<'_, crate::db::DbState>,
+    id: String,
+) -> std::result::Result<usize, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(storage::clear(&conn, &id)?)
+}
@@ -2,8 +2,16 @@
 //!
 //! This module provides real-time resource tracking for plugin instances.
 
+use crate::plugins::runtime::FUEL_PER_CPU_SECOND;
 use crate::plugins::ResourceLimits;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// Samples kept per instance, enough for a sparkline
+pub const MAX_SAMPLES: usize = 60;
+/// Consecutive over-limit samples after which an instance is suspended
+pub const SUSPEND_AFTER_SAMPLES: u32 = 3;
 
 /// Resource metrics collected during execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,7 +30,7 @@ pub struct ResourceMetrics {
     pub network_ops_count: u64,
     /// Timestamp of measurement
     pub timestamp: u64,
-    /// Fuel consumed (for WASM instances)
+    /// Fuel consumed by all calls so far (for WASM instances)
     #[cfg(feature = "wasm")]
     pub fuel_consumed: u64,
 }
@@ -46,10 +54,10 @@ impl ResourceMetrics {
         }
     }
 
-    /// Update metrics from WASM execution
+    /// Update metrics from a WASM call that burned `fuel_consumed`
     #[cfg(feature = "wasm")]
     pub fn update_from_wasm(&mut self, fuel_consumed: u64, memory_bytes: u64) {
-        self.fuel_consumed = fuel_consumed;
+        self.fuel_consumed += fuel_consumed;
         self.memory_bytes = memory_bytes;
         self.timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -73,6 +81,66 @@ impl ResourceMetrics {
     }
 }
 
+/// Attempted access outside a plugin's sandbox
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SandboxViolation {
+    pub plugin_id: String,
+    pub path: String,
+    /// "read" or "write"
+    pub access: String,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+impl SandboxViolation {
+    pub fn new(plugin_id: &str, path: &str, access: &str, reason: &str) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            path: path.to_string(),
+            access: access.to_string(),
+            reason: reason.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+}
+
+/// Memory and CPU use of an instance at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSample {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub memory_bytes: u64,
+    /// Share of one core used since the previous sample
+    pub cpu_percent: f64,
+}
+
+/// Recent samples of an instance and the totals they are computed from
+#[derive(Debug, Clone)]
+struct SampleSeries {
+    samples: VecDeque<ResourceSample>,
+    last_at: Instant,
+    last_fuel: u64,
+    last_cpu_time_ms: u64,
+    /// Consecutive samples over the limits
+    over_limit: u32,
+}
+
+impl SampleSeries {
+    fn new(now: Instant) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(MAX_SAMPLES),
+            last_at: now,
+            last_fuel: 0,
+            last_cpu_time_ms: 0,
+            over_limit: 0,
+        }
+    }
+}
+
 /// Resource monitor for tracking plugin resource usage
 pub struct ResourceMonitor {
     /// Current metrics for each instance
@@ -81,6 +149,10 @@ pub struct ResourceMonitor {
     history: Vec<ResourceMetrics>,
     /// Maximum history size
     max_history: usize,
+    /// Recent sandbox violations of all plugins
+    violations: Vec<SandboxViolation>,
+    /// Periodic samples of each instance
+    samples: HashMap<String, SampleSeries>,
 }
 
 impl ResourceMonitor {
@@ -90,17 +162,21 @@ impl ResourceMonitor {
             metrics: Vec::new(),
             history: Vec::new(),
             max_history: 100,
+            violations: Vec::new(),
+            samples: HashMap::new(),
         }
     }
 
     /// Start monitoring an instance
     pub fn start_monitoring(&mut self, instance_id: String) {
+        self.samples.insert(instance_id.clone(), SampleSeries::new(Instant::now()));
         let metrics = ResourceMetrics::new(instance_id);
         self.metrics.push(metrics);
     }
 
     /// Stop monitoring an instance
     pub fn stop_monitoring(&mut self, instance_id: &str) -> Option<ResourceMetrics> {
+        self.samples.remove(instance_id);
         let pos = self.metrics.iter().position(|m| m.instance_id == instance_id)?;
         Some(self.metrics.remove(pos))
     }
@@ -111,6 +187,7 @@ impl ResourceMonitor {
             match update {
                 MetricUpdate::Memory(bytes) => metrics.memory_bytes = bytes,
                 MetricUpdate::CpuTime(ms) => metrics.cpu_time_ms = ms,
+                MetricUpdate::Execution(ms) => metrics.cpu_time_ms += ms,
                 MetricUpdate::Syscall => metrics.syscall_count += 1,
                 MetricUpdate::FileOp => metrics.file_ops_count += 1,
                 MetricUpdate::NetworkOp => metrics.network_ops_count += 1,
@@ -152,6 +229,123 @@ impl ResourceMonitor {
         }
     }
 
+    /// Record a sample of an instance's memory size and total fuel consumed
+    ///
+    /// CPU use is estimated from the fuel burnt since the previous sample,
+    /// or from the time spent in calls when that is larger (fuel is not
+    /// metered without the wasm feature). Returns the reason to suspend the
+    /// instance once it has been over `limits` for `SUSPEND_AFTER_SAMPLES`
+    /// samples in a row.
+    pub fn record_sample(
+        &mut self,
+        instance_id: &str,
+        memory_bytes: u64,
+        fuel_consumed: u64,
+        limits: &ResourceLimits,
+    ) -> Option<String> {
+        self.record_sample_at(instance_id, memory_bytes, fuel_consumed, limits, Instant::now())
+    }
+
+    fn record_sample_at(
+        &mut self,
+        instance_id: &str,
+        memory_bytes: u64,
+        fuel_consumed: u64,
+        limits: &ResourceLimits,
+        now: Instant,
+    ) -> Option<String> {
+        let metrics = self.metrics.iter_mut().find(|m| m.instance_id == instance_id)?;
+        let series = self.samples.get_mut(instance_id)?;
+        metrics.memory_bytes = memory_bytes;
+
+        let wall_ms = now.duration_since(series.last_at).as_millis() as u64;
+        let fuel_ms = fuel_consumed.saturating_sub(series.last_fuel).saturating_mul(1000) / FUEL_PER_CPU_SECOND;
+        let call_ms = metrics.cpu_time_ms.saturating_sub(series.last_cpu_time_ms);
+        let cpu_percent = if wall_ms == 0 {
+            0.0
+        } else {
+            (fuel_ms.max(call_ms) as f64 * 100.0 / wall_ms as f64).min(100.0)
+        };
+        series.last_at = now;
+        series.last_fuel = fuel_consumed;
+        series.last_cpu_time_ms = metrics.cpu_time_ms;
+
+        series.samples.push_back(ResourceSample {
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            memory_bytes,
+            cpu_percent,
+        });
+        if series.samples.len() > MAX_SAMPLES {
+            series.samples.pop_front();
+        }
+
+        let max_memory_bytes = limits.max_memory_mb as u64 * 1024 * 1024;
+        let exceeded = if memory_bytes > max_memory_bytes {
+            Some(format!(
+                "Memory use of {} MB exceeds the {} MB limit",
+                memory_bytes / (1024 * 1024),
+                limits.max_memory_mb
+            ))
+        } else if cpu_percent > limits.max_cpu_percent as f64 {
+            Some(format!("CPU use of {:.0}% exceeds the {}% limit", cpu_percent, limits.max_cpu_percent))
+        } else {
+            None
+        };
+
+        match exceeded {
+            Some(reason) => {
+                series.over_limit += 1;
+                if series.over_limit < SUSPEND_AFTER_SAMPLES {
+                    return None;
+                }
+                series.over_limit = 0;
+                Some(reason)
+            }
+            None => {
+                series.over_limit = 0;
+                None
+            }
+        }
+    }
+
+    /// Recent samples of an instance, oldest first
+    pub fn get_samples(&self, instance_id: &str) -> Vec<ResourceSample> {
+        self.samples
+            .get(instance_id)
+            .map(|s| s.samples.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// CPU use at the latest sample of an instance
+    pub fn latest_cpu_percent(&self, instance_id: &str) -> f64 {
+        self.samples
+            .get(instance_id)
+            .and_then(|s| s.samples.back())
+            .map(|s| s.cpu_percent)
+            .unwrap_or(0.0)
+    }
+
+    /// Record an attempted access outside a sandbox
+    pub fn record_violation(&mut self, violation: SandboxViolation) {
+        tracing::warn!(
+            "Plugin {} denied {} access to {}: {}",
+            violation.plugin_id, violation.access, violation.path, violation.reason
+        );
+        self.violations.push(violation);
+        if self.violations.len() > self.max_history {
+            self.violations.remove(0);
+        }
+    }
+
+    /// Recent sandbox violations of a plugin, oldest first
+    pub fn get_violations(&self, plugin_id: &str) -> Vec<SandboxViolation> {
+        self.violations
+            .iter()
+            .filter(|v| v.plugin_id == plugin_id)
+            .cloned()
+            .collect()
+    }
+
     /// Get current metrics for an instance
     pub fn get_metrics(&self, instance_id: &str) -> Option<&ResourceMetrics> {
         self.metrics.iter().find(|m| m.instance_id == instance_id)
@@ -191,25 +385,20 @@ impl ResourceMonitor {
         }
 
         let peak_memory = history.iter().map(|m| m.memory_bytes).max().unwrap_or(0);
-        let total_syscalls = history.iter().map(|m| m.syscall_count).sum();
-
-        #[cfg(feature = "wasm")]
-        let total_fuel = history.iter().map(|m| m.fuel_consumed).sum();
-
-        #[cfg(not(feature = "wasm"))]
-        let _total_fuel = 0;
 
+        // Counters and fuel are running totals, so every snapshot already
+        // includes the ones before it; the current metrics hold the totals
         Some(ResourceSummary {
             instance_id: instance_id.to_string(),
             current_memory: current.memory_bytes,
             peak_memory,
             current_cpu_time: current.cpu_time_ms,
-            total_syscalls,
-            total_file_ops: history.iter().map(|m| m.file_ops_count).sum(),
-            total_network_ops: history.iter().map(|m| m.network_ops_count).sum(),
+            total_syscalls: current.syscall_count,
+            total_file_ops: current.file_ops_count,
+            total_network_ops: current.network_ops_count,
             measurement_count: history.len(),
             #[cfg(feature = "wasm")]
-            total_fuel_consumed: total_fuel,
+            total_fuel_consumed: current.fuel_consumed,
         })
     }
 
@@ -255,6 +444,8 @@ impl Default for ResourceMonitor {
 pub enum MetricUpdate {
     Memory(u64),
     CpuTime(u64),
+    /// Time spent in a call, in milliseconds
+    Execution(u64),
     Syscall,
     FileOp,
     NetworkOp,
@@ -326,6 +517,7 @@ mod tests {
             max_cpu_percent: 50,
             max_execution_time_ms: 1000,
             max_file_size_mb: 10,
+            max_storage_kb: 1024,
         };
 
         let mut metrics = ResourceMetrics::new("test".to_string());
@@ -374,6 +566,7 @@ mod tests {
             max_cpu_percent: 50,
             max_execution_time_ms: 1000,
             max_file_size_mb: 10,
+            max_storage_kb: 1024,
         };
 
         let exceeded = monitor.check_limits(&limits);
@@ -395,6 +588,55 @@ mod tests {
         assert_eq!(usage.total_memory, 3072);
     }
 
+    #[test]
+    fn test_cpu_estimate_and_sample_ring() {
+        let mut monitor = ResourceMonitor::new();
+        monitor.start_monitoring("p".to_string());
+        let limits = ResourceLimits::default();
+        let start = monitor.samples["p"].last_at;
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        // A quarter of a core's fuel over one second
+        monitor.record_sample_at("p", 1024, FUEL_PER_CPU_SECOND / 4, &limits, at(1000));
+        // Calls took 500 ms of the next second without burning fuel
+        monitor.update_metrics("p", MetricUpdate::Execution(500));
+        monitor.record_sample_at("p", 2048, FUEL_PER_CPU_SECOND / 4, &limits, at(2000));
+
+        let cpu: Vec<f64> = monitor.get_samples("p").iter().map(|s| s.cpu_percent).collect();
+        assert_eq!(cpu, vec![25.0, 50.0]);
+        assert_eq!(monitor.latest_cpu_percent("p"), 50.0);
+        assert_eq!(monitor.get_metrics("p").unwrap().memory_bytes, 2048);
+
+        for i in 0..MAX_SAMPLES as u64 {
+            monitor.record_sample_at("p", 0, FUEL_PER_CPU_SECOND / 4, &limits, at(3000 + i * 1000));
+        }
+        assert_eq!(monitor.get_samples("p").len(), MAX_SAMPLES);
+        assert_eq!(monitor.latest_cpu_percent("p"), 0.0);
+
+        monitor.stop_monitoring("p");
+        assert!(monitor.get_samples("p").is_empty());
+    }
+
+    #[test]
+    fn test_suspend_after_repeated_overuse() {
+        let mut monitor = ResourceMonitor::new();
+        monitor.start_monitoring("p".to_string());
+        let limits = ResourceLimits { max_memory_mb: 1, ..ResourceLimits::default() };
+        let start = monitor.samples["p"].last_at;
+        let at = |s: u64| start + std::time::Duration::from_secs(s);
+        let over = 2 * 1024 * 1024;
+
+        // An isolated spike is tolerated
+        assert!(monitor.record_sample_at("p", over, 0, &limits, at(1)).is_none());
+        assert!(monitor.record_sample_at("p", 0, 0, &limits, at(2)).is_none());
+
+        let reasons: Vec<_> = (3..3 + SUSPEND_AFTER_SAMPLES as u64)
+            .map(|s| monitor.record_sample_at("p", over, 0, &limits, at(s)))
+            .collect();
+        assert!(reasons[..reasons.len() - 1].iter().all(Option::is_none));
+        assert!(reasons.last().unwrap().as_ref().unwrap().contains("1 MB limit"));
+    }
+
     #[cfg(feature = "wasm")]
     #[test]
     fn test_wasm_fuel_tracking() {
@@ -406,5 +648,14 @@ mod tests {
         let metrics = monitor.get_metrics("test-instance").unwrap();
         assert_eq!(metrics.fuel_consumed, 5000);
         assert_eq!(metrics.memory_bytes, 1024);
+
+        // Each snapshot holds the running total, which is counted once
+        monitor.update_metrics("test-instance", MetricUpdate::Syscall);
+        monitor.update_from_wasm("test-instance", 3000, 2048);
+        monitor.update_metrics("test-instance", MetricUpdate::Syscall);
+        let summary = monitor.get_summary("test-instance").unwrap();
+        assert_eq!(summary.total_fuel_consumed, 8000);
+        assert_eq!(summary.total_syscalls, 2);
+        assert_eq!(summary.peak_memory, 2048);
     }
 }
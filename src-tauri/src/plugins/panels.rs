@@ -0,0 +1,382 @@
+// Plugin Panels - Declarative UI surfaces rendered by the frontend
+//
+// A plugin describes its panels as JSON instead of shipping UI code: each
+// panel is a list of markdown, form, table and button blocks. The frontend
+// asks for them through `plugin_get_panels`, which calls the plugin's
+// `get_panels` export. Submitting a form or pressing a button goes through
+// `plugin_panel_action`, which checks the action against the last declaration
+// and calls the `panel_action` export; the plugin may answer with an updated
+// panel and a message to show.
+
+use crate::plugins::schedule::PluginTaskRunner;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Export returning the plugin's panels
+pub const PANELS_EXPORT: &str = "get_panels";
+
+/// Export handling an interaction with a panel
+pub const PANEL_ACTION_EXPORT: &str = "panel_action";
+
+/// Limits on what a plugin may declare
+const MAX_PANELS: usize = 16;
+const MAX_BLOCKS_PER_PANEL: usize = 32;
+const MAX_TABLE_ROWS: usize = 500;
+const MAX_MARKDOWN_BYTES: usize = 64 * 1024;
+
+/// A panel declared by a plugin
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginPanel {
+    pub id: String,
+    pub title: String,
+    pub blocks: Vec<PanelBlock>,
+}
+
+/// Content of a panel, rendered top to bottom
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PanelBlock {
+    Markdown {
+        text: String,
+    },
+    Form {
+        fields: Vec<FormField>,
+        submit: PanelButton,
+    },
+    #[serde(rename_all = "camelCase")]
+    Table {
+        columns: Vec<TableColumn>,
+        rows: Vec<serde_json::Map<String, Value>>,
+        /// Button shown on each row; its payload is the row
+        #[serde(default)]
+        row_action: Option<PanelButton>,
+    },
+    Buttons {
+        buttons: Vec<PanelButton>,
+    },
+}
+
+/// Button triggering a plugin action
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PanelButton {
+    pub action: String,
+    pub label: String,
+}
+
+/// Input of a form
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FormField {
+    pub name: String,
+    pub label: String,
+    #[serde(default)]
+    pub kind: FieldKind,
+    #[serde(default)]
+    pub required: bool,
+    /// Choices of a select field
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub default: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    #[default]
+    Text,
+    Textarea,
+    Number,
+    Checkbox,
+    Select,
+}
+
+/// Column of a table, showing the row value under `key`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TableColumn {
+    pub key: String,
+    pub label: String,
+}
+
+/// What a plugin answers to an action
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PanelActionResult {
+    /// Replacement for the panel the action came from
+    pub panel: Option<PluginPanel>,
+    /// Message shown to the user
+    pub message: Option<String>,
+}
+
+impl PluginPanel {
+    fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("Panel id is required".to_string());
+        }
+        if self.blocks.len() > MAX_BLOCKS_PER_PANEL {
+            return Err(format!("Panel {} has more than {} blocks", self.id, MAX_BLOCKS_PER_PANEL));
+        }
+
+        for block in &self.blocks {
+            match block {
+                PanelBlock::Markdown { text } if text.len() > MAX_MARKDOWN_BYTES => {
+                    return Err(format!("Markdown in panel {} must be 64KB or less", self.id));
+                }
+                PanelBlock::Form { fields, .. } => {
+                    let mut names = HashSet::new();
+                    for field in fields {
+                        if field.name.is_empty() || !names.insert(field.name.as_str()) {
+                            return Err(format!("Form fields in panel {} need unique names", self.id));
+                        }
+                        if field.kind == FieldKind::Select && field.options.is_empty() {
+                            return Err(format!("Select field {} in panel {} has no options", field.name, self.id));
+                        }
+                    }
+                }
+                PanelBlock::Table { rows, .. } if rows.len() > MAX_TABLE_ROWS => {
+                    return Err(format!("Table in panel {} has more than {} rows", self.id, MAX_TABLE_ROWS));
+                }
+                _ => {}
+            }
+        }
+
+        let mut actions = HashSet::new();
+        for button in self.buttons() {
+            if button.action.is_empty() {
+                return Err(format!("Button '{}' in panel {} has no action", button.label, self.id));
+            }
+            if self.form(&button.action).is_some() && !actions.insert(button.action.as_str()) {
+                return Err(format!("Form action {} in panel {} is used twice", button.action, self.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// All buttons of the panel
+    fn buttons(&self) -> impl Iterator<Item = &PanelButton> {
+        self.blocks.iter().flat_map(|block| match block {
+            PanelBlock::Markdown { .. } => Vec::new(),
+            PanelBlock::Form { submit, .. } => vec![submit],
+            PanelBlock::Table { row_action, .. } => row_action.iter().collect(),
+            PanelBlock::Buttons { buttons } => buttons.iter().collect(),
+        })
+    }
+
+    pub fn declares_action(&self, action: &str) -> bool {
+        self.buttons().any(|b| b.action == action)
+    }
+
+    /// Fields of the form submitted by an action
+    pub fn form(&self, action: &str) -> Option<&[FormField]> {
+        self.blocks.iter().find_map(|block| match block {
+            PanelBlock::Form { fields, submit } if submit.action == action => Some(fields.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Check the payload of an action the panel declares
+    pub fn check_payload(&self, action: &str, payload: &Value) -> Result<(), String> {
+        if !self.declares_action(action) {
+            return Err(format!("Panel {} has no action {}", self.id, action));
+        }
+        let Some(fields) = self.form(action) else {
+            return Ok(());
+        };
+
+        let values = payload.as_object().ok_or_else(|| "Form payload must be an object".to_string())?;
+        for field in fields {
+            let value = values.get(&field.name).filter(|v| !v.is_null() && v.as_str() != Some(""));
+            let Some(value) = value else {
+                if field.required {
+                    return Err(format!("{} is required", field.label));
+                }
+                continue;
+            };
+            let valid = match field.kind {
+                FieldKind::Text | FieldKind::Textarea => value.is_string(),
+                FieldKind::Number => value.is_number(),
+                FieldKind::Checkbox => value.is_boolean(),
+                FieldKind::Select => value.as_str().is_some_and(|v| field.options.iter().any(|o| o == v)),
+            };
+            if !valid {
+                return Err(format!("Invalid value for {}", field.label));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse and check the panels returned by `get_panels`
+pub fn parse_panels(value: Option<Value>) -> Result<Vec<PluginPanel>, String> {
+    let panels: Vec<PluginPanel> = match value {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("Invalid panel declaration: {}", e))?,
+        None => Vec::new(),
+    };
+    if panels.len() > MAX_PANELS {
+        return Err(format!("A plugin may declare at most {} panels", MAX_PANELS));
+    }
+
+    let mut ids = HashSet::new();
+    for panel in &panels {
+        panel.validate()?;
+        if !ids.insert(panel.id.as_str()) {
+            return Err(format!("Panel {} is declared more than once", panel.id));
+        }
+    }
+    Ok(panels)
+}
+
+/// Last panels each running plugin declared
+#[derive(Default)]
+pub struct PanelRegistry {
+    panels: HashMap<String, Vec<PluginPanel>>,
+}
+
+impl PanelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, plugin_id: &str, panels: Vec<PluginPanel>) {
+        self.panels.insert(plugin_id.to_string(), panels);
+    }
+
+    pub fn get(&self, plugin_id: &str, panel_id: &str) -> Option<&PluginPanel> {
+        self.panels.get(plugin_id)?.iter().find(|p| p.id == panel_id)
+    }
+
+    /// Replace one panel after an action updated it
+    pub fn update(&mut self, plugin_id: &str, panel: PluginPanel) {
+        if let Some(existing) = self.panels.get_mut(plugin_id)
+            .and_then(|panels| panels.iter_mut().find(|p| p.id == panel.id))
+        {
+            *existing = panel;
+        }
+    }
+
+    pub fn remove(&mut self, plugin_id: &str) {
+        self.panels.remove(plugin_id);
+    }
+}
+
+/// Ask a plugin for its panels and remember them
+pub fn get_panels(
+    runner: &PluginTaskRunner,
+    registry: &std::sync::Mutex<PanelRegistry>,
+    plugin_id: &str,
+) -> Result<Vec<PluginPanel>, String> {
+    let panels = parse_panels(runner.run(plugin_id, PANELS_EXPORT, &serde_json::json!({}))?)?;
+    registry.lock().map_err(|e| e.to_string())?.set(plugin_id, panels.clone());
+    Ok(panels)
+}
+
+/// Route an interaction with a panel into the plugin
+pub fn panel_action(
+    runner: &PluginTaskRunner,
+    registry: &std::sync::Mutex<PanelRegistry>,
+    plugin_id: &str,
+    panel_id: &str,
+    action: &str,
+    payload: Value,
+) -> Result<PanelActionResult, String> {
+    {
+        let registry = registry.lock().map_err(|e| e.to_string())?;
+        let panel = registry.get(plugin_id, panel_id)
+            .ok_or_else(|| format!("Plugin {} has no panel {}", plugin_id, panel_id))?;
+        panel.check_payload(action, &payload)?;
+    }
+
+    let input = serde_json::json!({ "panel": panel_id, "action": action, "payload": payload });
+    let result: PanelActionResult = match runner.run(plugin_id, PANEL_ACTION_EXPORT, &input)? {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("Invalid panel action result: {}", e))?,
+        None => PanelActionResult::default(),
+    };
+
+    if let Some(panel) = &result.panel {
+        if panel.id != panel_id {
+            return Err(format!("Action on panel {} returned panel {}", panel_id, panel.id));
+        }
+        panel.validate()?;
+        registry.lock().map_err(|e| e.to_string())?.update(plugin_id, panel.clone());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn declaration() -> Value {
+        json!([{
+            "id": "notes",
+            "title": "Notes",
+            "blocks": [
+                { "type": "markdown", "text": "# Notes" },
+                {
+                    "type": "form",
+                    "fields": [
+                        { "name": "title", "label": "Title", "required": true },
+                        { "name": "priority", "label": "Priority", "kind": "select", "options": ["low", "high"] },
+                        { "name": "pinned", "label": "Pinned", "kind": "checkbox" }
+                    ],
+                    "submit": { "action": "add", "label": "Add" }
+                },
+                {
+                    "type": "table",
+                    "columns": [{ "key": "title", "label": "Title" }],
+                    "rows": [{ "title": "First" }],
+                    "rowAction": { "action": "delete", "label": "Delete" }
+                }
+            ]
+        }])
+    }
+
+    #[test]
+    fn test_parse_and_check_payload() {
+        let panels = parse_panels(Some(declaration())).unwrap();
+        assert_eq!(panels.len(), 1);
+        let panel = &panels[0];
+        assert_eq!(panel.blocks.len(), 3);
+        assert!(panel.declares_action("delete"));
+
+        assert!(panel.check_payload("add", &json!({ "title": "Buy milk", "priority": "high" })).is_ok());
+        assert!(panel.check_payload("add", &json!({ "title": "" })).is_err());
+        assert!(panel.check_payload("add", &json!({ "title": "x", "priority": "urgent" })).is_err());
+        assert!(panel.check_payload("add", &json!({ "title": "x", "pinned": "yes" })).is_err());
+        assert!(panel.check_payload("delete", &json!({ "title": "First" })).is_ok());
+        assert!(panel.check_payload("export", &json!({})).is_err());
+
+        assert!(parse_panels(None).unwrap().is_empty());
+        let duplicate = json!([declaration()[0].clone(), declaration()[0].clone()]);
+        assert!(parse_panels(Some(duplicate)).is_err());
+        let no_options = json!([{ "id": "p", "title": "P", "blocks": [{
+            "type": "form",
+            "fields": [{ "name": "a", "label": "A", "kind": "select" }],
+            "submit": { "action": "go", "label": "Go" }
+        }] }]);
+        assert!(parse_panels(Some(no_options)).is_err());
+    }
+
+    #[test]
+    fn test_registry_and_unknown_panel() {
+        let registry = std::sync::Mutex::new(PanelRegistry::new());
+        registry.lock().unwrap().set("notes-plugin", parse_panels(Some(declaration())).unwrap());
+
+        let mut updated = registry.lock().unwrap().get("notes-plugin", "notes").unwrap().clone();
+        updated.title = "My notes".to_string();
+        registry.lock().unwrap().update("notes-plugin", updated);
+        assert_eq!(registry.lock().unwrap().get("notes-plugin", "notes").unwrap().title, "My notes");
+
+        // Actions are only routed for panels the plugin declared
+        let runner = crate::plugins::PluginExecutor::new().task_runner();
+        let err = panel_action(&runner, &registry, "notes-plugin", "other", "add", json!({})).unwrap_err();
+        assert!(err.contains("no panel"), "{}", err);
+        let err = panel_action(&runner, &registry, "notes-plugin", "notes", "add", json!({})).unwrap_err();
+        assert!(err.contains("required"), "{}", err);
+
+        registry.lock().unwrap().remove("notes-plugin");
+        assert!(registry.lock().unwrap().get("notes-plugin", "notes").is_none());
+    }
+}
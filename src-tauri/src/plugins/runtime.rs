@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Instant;
 
+#[cfg(feature = "wasm")]
+use crate::plugins::abi;
+
 // WASM feature-gated imports
 #[cfg(feature = "wasm")]
 use wasmtime::{
@@ -26,8 +29,10 @@ pub struct WasmRuntimeConfig {
     pub max_execution_time_ms: u64,
     /// Enable fuel metering for execution limiting
     pub enable_fuel: bool,
-    /// Initial fuel units (if enabled)
+    /// Initial fuel units (if enabled); also the maximum an instance can bank
     pub initial_fuel: u64,
+    /// Fuel units restored per second of wall time (if enabled)
+    pub fuel_refill_per_sec: u64,
 }
 
 impl Default for WasmRuntimeConfig {
@@ -37,10 +42,68 @@ impl Default for WasmRuntimeConfig {
             max_execution_time_ms: 5000,
             enable_fuel: true,
             initial_fuel: 1_000_000,
+            fuel_refill_per_sec: fuel_refill_rate(50),
         }
     }
 }
 
+/// Approximate fuel one core burns through per second of execution
+pub const FUEL_PER_CPU_SECOND: u64 = 1_000_000_000;
+
+/// Map a CPU percentage limit to a fuel refill rate
+pub fn fuel_refill_rate(max_cpu_percent: u32) -> u64 {
+    FUEL_PER_CPU_SECOND / 100 * max_cpu_percent.min(100) as u64
+}
+
+/// Per-instance fuel budget
+///
+/// Fuel is spent by each call and refilled over time at a fixed rate, so
+/// a plugin can burst up to `capacity` but is throttled to the refill rate
+/// on sustained load.
+#[derive(Debug, Clone)]
+pub struct FuelBudget {
+    available: u64,
+    capacity: u64,
+    refill_per_sec: u64,
+    last_refill: Instant,
+}
+
+impl FuelBudget {
+    pub fn new(capacity: u64, refill_per_sec: u64) -> Self {
+        Self {
+            available: capacity,
+            capacity,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refill based on elapsed time and return the fuel now available
+    pub fn refill(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed_ms = now.duration_since(self.last_refill).as_millis() as u64;
+        let refill = elapsed_ms.saturating_mul(self.refill_per_sec) / 1000;
+        if refill > 0 {
+            self.available = self.available.saturating_add(refill).min(self.capacity);
+            self.last_refill = now;
+        }
+        self.available
+    }
+
+    /// Deduct fuel spent by a call
+    pub fn consume(&mut self, amount: u64) {
+        self.available = self.available.saturating_sub(amount);
+    }
+
+    pub fn available(&self) -> u64 {
+        self.available
+    }
+
+    pub fn set_refill_rate(&mut self, refill_per_sec: u64) {
+        self.refill_per_sec = refill_per_sec;
+    }
+}
+
 /// WebAssembly module loaded from bytes
 pub struct WasmModule {
     /// Module hash for validation
@@ -136,6 +199,8 @@ pub struct WasmInstance {
     state: InstanceState,
     /// Creation timestamp
     created_at: Instant,
+    /// Fuel budget for CPU limiting
+    fuel: FuelBudget,
 
     /// WASM feature-gated fields
     #[cfg(feature = "wasm")]
@@ -207,7 +272,8 @@ impl WasmRuntime {
         let mut linker = Linker::new(engine.as_ref().unwrap());
         #[cfg(feature = "wasm")]
         {
-            // Add WASI preview1 to the linker using wasmtime 22+ API
+            // Add WASI preview1 to the linker using wasmtime 22+ API. It is the
+            // only import: host APIs such as `http.fetch` go through `api.call`
             add_to_linker_sync(&mut linker, |s| s)
                 .expect("Failed to add WASI to linker");
         }
@@ -269,11 +335,10 @@ impl WasmRuntime {
         // Create store with WASI context as data
         let mut store = Store::new(engine, wasi_ctx);
 
-        // Configure fuel if enabled
-        if self.config.enable_fuel {
-            store.set_fuel(self.config.initial_fuel)
-                .map_err(|e| format!("Failed to set fuel: {}", e))?;
-        }
+        // Configure fuel: unmetered stores still need fuel since the engine consumes it
+        let fuel = if self.config.enable_fuel { self.config.initial_fuel } else { u64::MAX };
+        store.set_fuel(fuel)
+            .map_err(|e| format!("Failed to set fuel: {}", e))?;
 
         // Instantiate the module with the linker
         let instance = linker.instantiate(&mut store, wasmtime_module)
@@ -294,6 +359,7 @@ impl WasmRuntime {
         self.instances.insert(instance_id.clone(), WasmInstance {
             state,
             created_at: Instant::now(),
+            fuel: self.new_fuel_budget(),
             #[cfg(feature = "wasm")]
             store: Some(store),
             #[cfg(feature = "wasm")]
@@ -329,6 +395,7 @@ impl WasmRuntime {
         self.instances.insert(instance_id.clone(), WasmInstance {
             state,
             created_at: Instant::now(),
+            fuel: self.new_fuel_budget(),
             #[cfg(feature = "wasm")]
             store: None,
             #[cfg(feature = "wasm")]
@@ -374,14 +441,20 @@ impl WasmRuntime {
                 });
             }
 
+            // Allot fuel for this call from the instance budget
+            let allotted = match Self::allot_fuel(&mut instance_entry.fuel, store, self.config.enable_fuel)? {
+                Some(0) => return Ok(WasmExecutionResult::fuel_exhausted(start, 0)),
+                allotted => allotted,
+            };
+
             // Try calling with different argument patterns
-            let result_value: serde_json::Value = match args.len() {
+            let call_result: Result<serde_json::Value, wasmtime::Error> = match args.len() {
                 2 => {
                     // Try (i32, i32) -> i32
                     if let Ok(f) = instance.get_typed_func::<(i32, i32), i32>(&mut *store, function_name) {
                         let a = args[0].as_i64().unwrap_or(0) as i32;
                         let b = args[1].as_i64().unwrap_or(0) as i32;
-                        serde_json::json!(f.call(&mut *store, (a, b)).map_err(|e| e.to_string())?)
+                        f.call(&mut *store, (a, b)).map(|v| serde_json::json!(v))
                     } else {
                         // Function not found with this signature, try to fail gracefully
                         return Ok(WasmExecutionResult {
@@ -396,10 +469,9 @@ impl WasmRuntime {
                 0 => {
                     // Try () -> i32
                     if let Ok(f) = instance.get_typed_func::<(), i32>(&mut *store, function_name) {
-                        serde_json::json!(f.call(&mut *store, ()).map_err(|e| e.to_string())?)
+                        f.call(&mut *store, ()).map(|v| serde_json::json!(v))
                     } else if let Ok(f) = instance.get_typed_func::<(), ()>(&mut *store, function_name) {
-                        f.call(&mut *store, ()).map_err(|e| e.to_string())?;
-                        serde_json::json!({"status": "ok"})
+                        f.call(&mut *store, ()).map(|_| serde_json::json!({"status": "ok"}))
                     } else {
                         // Function not found with any signature
                         return Ok(WasmExecutionResult {
@@ -422,9 +494,17 @@ impl WasmRuntime {
                 }
             };
 
-            // Get fuel consumed (fuel_consumed() method not available, use 0 for now)
-            // TODO: Implement fuel tracking when API is available
-            let fuel_consumed = 0;
+            // Charge the fuel actually burned against the budget
+            let fuel_consumed = Self::charge_fuel(&mut instance_entry.fuel, store, allotted);
+            instance_entry.state.fuel_consumed += fuel_consumed;
+
+            let result_value = match call_result {
+                Ok(value) => value,
+                Err(e) if is_out_of_fuel(&e) => {
+                    return Ok(WasmExecutionResult::fuel_exhausted(start, fuel_consumed));
+                }
+                Err(e) => return Err(e.to_string()),
+            };
 
             // Calculate memory size (inline to avoid borrow checker issues)
             let memory_used = match instance.get_memory(&mut *store, "memory") {
@@ -433,7 +513,6 @@ impl WasmRuntime {
             };
 
             // Update instance state
-            instance_entry.state.fuel_consumed = fuel_consumed;
             instance_entry.state.memory_used = memory_used;
 
             Ok(WasmExecutionResult {
@@ -471,7 +550,10 @@ impl WasmRuntime {
 
             let elapsed = start.elapsed();
             let fuel_used = if self.config.enable_fuel {
-                elapsed.as_millis() as u64 * 1000
+                instance_entry.fuel.refill();
+                let used = elapsed.as_millis() as u64 * 1000;
+                instance_entry.fuel.consume(used);
+                used
             } else {
                 0
             };
@@ -501,6 +583,131 @@ impl WasmRuntime {
         }
     }
 
+    /// Call a function using the JSON ABI (see [`crate::plugins::abi`])
+    #[cfg(feature = "wasm")]
+    pub fn call_function_json(
+        &mut self,
+        instance_id: &str,
+        function_name: &str,
+        input: &serde_json::Value,
+    ) -> Result<WasmExecutionResult, String> {
+        let start = Instant::now();
+
+        let instance_entry = self.instances
+            .get_mut(instance_id)
+            .ok_or_else(|| format!("Instance not found: {}", instance_id))?;
+        let store = instance_entry.store.as_mut()
+            .ok_or_else(|| "Store not initialized".to_string())?;
+        let instance = instance_entry.instance.as_ref()
+            .ok_or_else(|| "Instance not initialized".to_string())?;
+
+        if !abi::supports_json_abi(store, instance) {
+            return Ok(WasmExecutionResult {
+                success: false,
+                result: None,
+                error: Some("Plugin does not export alloc/dealloc for the JSON ABI".to_string()),
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                fuel_consumed: 0,
+            });
+        }
+
+        let allotted = match Self::allot_fuel(&mut instance_entry.fuel, store, self.config.enable_fuel)? {
+            Some(0) => return Ok(WasmExecutionResult::fuel_exhausted(start, 0)),
+            allotted => allotted,
+        };
+
+        let call_result = abi::call_json(store, instance, function_name, input);
+
+        let fuel_consumed = Self::charge_fuel(&mut instance_entry.fuel, store, allotted);
+        instance_entry.state.fuel_consumed += fuel_consumed;
+        instance_entry.state.memory_used = match instance.get_memory(&mut *store, "memory") {
+            Some(memory) => memory.size(&mut *store) * 65536,
+            None => 0,
+        };
+
+        match call_result {
+            Ok(value) => Ok(WasmExecutionResult {
+                success: true,
+                result: Some(value),
+                error: None,
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                fuel_consumed,
+            }),
+            Err(e) if is_out_of_fuel(&e) => Ok(WasmExecutionResult::fuel_exhausted(start, fuel_consumed)),
+            Err(e) => Ok(WasmExecutionResult {
+                success: false,
+                result: None,
+                error: Some(e.to_string()),
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                fuel_consumed,
+            }),
+        }
+    }
+
+    /// Call a function using the JSON ABI (non-wasm fallback)
+    #[cfg(not(feature = "wasm"))]
+    pub fn call_function_json(
+        &mut self,
+        instance_id: &str,
+        function_name: &str,
+        _input: &serde_json::Value,
+    ) -> Result<WasmExecutionResult, String> {
+        self.call_function(instance_id, function_name, Vec::new())
+    }
+
+    /// Set the store's fuel from the instance budget before a call
+    ///
+    /// Returns the fuel allotted, `Some(0)` when the budget is exhausted,
+    /// or `None` when metering is disabled.
+    #[cfg(feature = "wasm")]
+    fn allot_fuel(
+        budget: &mut FuelBudget,
+        store: &mut Store<WasiP1Ctx>,
+        enable_fuel: bool,
+    ) -> Result<Option<u64>, String> {
+        if !enable_fuel {
+            return Ok(None);
+        }
+        let available = budget.refill();
+        if available > 0 {
+            store.set_fuel(available)
+                .map_err(|e| format!("Failed to set fuel: {}", e))?;
+        }
+        Ok(Some(available))
+    }
+
+    /// Charge the fuel burned by a call against the instance budget
+    #[cfg(feature = "wasm")]
+    fn charge_fuel(budget: &mut FuelBudget, store: &Store<WasiP1Ctx>, allotted: Option<u64>) -> u64 {
+        match allotted {
+            Some(allotted) => {
+                let remaining = store.get_fuel().unwrap_or(0);
+                let consumed = allotted.saturating_sub(remaining);
+                budget.consume(consumed);
+                consumed
+            }
+            None => 0,
+        }
+    }
+
+    fn new_fuel_budget(&self) -> FuelBudget {
+        FuelBudget::new(self.config.initial_fuel, self.config.fuel_refill_per_sec)
+    }
+
+    /// Apply a plugin's CPU limit to an instance's fuel refill rate
+    pub fn set_cpu_limit(&mut self, instance_id: &str, max_cpu_percent: u32) -> Result<(), String> {
+        let instance = self.instances
+            .get_mut(instance_id)
+            .ok_or_else(|| format!("Instance not found: {}", instance_id))?;
+        instance.fuel.set_refill_rate(fuel_refill_rate(max_cpu_percent));
+        Ok(())
+    }
+
+    /// Fuel currently available to an instance
+    pub fn get_available_fuel(&self, instance_id: &str) -> Option<u64> {
+        self.instances.get(instance_id).map(|i| i.fuel.available())
+    }
+
     /// Get memory size from instance
     #[cfg(feature = "wasm")]
     fn get_memory_size(_instance: &Instance, _store: &Store<WasiP1Ctx>) -> u64 {
@@ -517,6 +724,19 @@ impl WasmRuntime {
         }
     }
 
+    /// Refresh an instance's memory size from its linear memory and return its state
+    pub fn sample_instance(&mut self, instance_id: &str) -> Option<InstanceState> {
+        let entry = self.instances.get_mut(instance_id)?;
+        #[cfg(feature = "wasm")]
+        if let (Some(instance), Some(store)) = (entry.instance, entry.store.as_mut()) {
+            entry.state.memory_used = match instance.get_memory(&mut *store, "memory") {
+                Some(memory) => memory.data_size(&*store) as u64,
+                None => 0,
+            };
+        }
+        Some(entry.state.clone())
+    }
+
     /// Get instance state
     pub fn get_instance_state(&self, instance_id: &str) -> Option<InstanceState> {
         self.instances
@@ -549,6 +769,12 @@ impl Default for WasmRuntime {
     }
 }
 
+/// Whether a call failed because it ran out of fuel
+#[cfg(feature = "wasm")]
+fn is_out_of_fuel(error: &wasmtime::Error) -> bool {
+    matches!(error.downcast_ref::<wasmtime::Trap>(), Some(wasmtime::Trap::OutOfFuel))
+}
+
 /// WASM execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WasmExecutionResult {
@@ -564,6 +790,19 @@ pub struct WasmExecutionResult {
     pub fuel_consumed: u64,
 }
 
+impl WasmExecutionResult {
+    #[cfg(feature = "wasm")]
+    fn fuel_exhausted(start: Instant, fuel_consumed: u64) -> Self {
+        Self {
+            success: false,
+            result: None,
+            error: Some("Fuel exhausted, CPU limit reached".to_string()),
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            fuel_consumed,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -604,5 +843,34 @@ mod tests {
         assert_eq!(config.max_memory_mb, 128);
         assert_eq!(config.max_execution_time_ms, 5000);
         assert!(config.enable_fuel);
+        assert_eq!(config.fuel_refill_per_sec, fuel_refill_rate(50));
+    }
+
+    #[test]
+    fn test_fuel_refill_rate() {
+        assert_eq!(fuel_refill_rate(0), 0);
+        assert_eq!(fuel_refill_rate(100), FUEL_PER_CPU_SECOND);
+        assert_eq!(fuel_refill_rate(50), FUEL_PER_CPU_SECOND / 2);
+        // Clamped to one full core
+        assert_eq!(fuel_refill_rate(250), FUEL_PER_CPU_SECOND);
+    }
+
+    #[test]
+    fn test_fuel_budget_consume_and_refill() {
+        let mut budget = FuelBudget::new(1000, 0);
+        assert_eq!(budget.refill(), 1000);
+
+        budget.consume(600);
+        assert_eq!(budget.available(), 400);
+        budget.consume(1000);
+        assert_eq!(budget.available(), 0);
+
+        // No refill without a rate
+        assert_eq!(budget.refill(), 0);
+
+        // Refill never exceeds capacity
+        budget.set_refill_rate(u64::MAX);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert_eq!(budget.refill(), 1000);
     }
 }
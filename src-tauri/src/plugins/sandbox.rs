@@ -117,14 +117,8 @@ impl PluginSandbox {
     }
 
     fn check_network_permission(&self, host: &str) -> Result<(), String> {
-        for perm in &self.context.permissions {
-            if let PluginPermission::Network { hosts } = perm {
-                for allowed_host in hosts {
-                    if host == allowed_host || host.ends_with(&format!(".{}", allowed_host)) {
-                        return Ok(());
-                    }
-                }
-            }
+        if host_allowed(host, &self.context.permissions) {
+            return Ok(());
         }
         Err(format!("Network access denied: {}", host))
     }
@@ -177,6 +171,16 @@ impl PluginSandbox {
     }
 }
 
+/// Whether a Network permission lists the host or one of its parent domains
+pub fn host_allowed(host: &str, permissions: &[PluginPermission]) -> bool {
+    permissions.iter().any(|perm| match perm {
+        PluginPermission::Network { hosts } => hosts
+            .iter()
+            .any(|allowed| host == allowed || host.ends_with(&format!(".{}", allowed))),
+        _ => false,
+    })
+}
+
 /// Actions that can be performed in sandbox
 #[derive(Debug, Clone)]
 pub enum SandboxAction {
@@ -0,0 +1,306 @@
+// Plugin Schedule - Recurring background tasks registered by plugins
+
+use crate::plugins::api::{PluginRequest, PluginResponse};
+use crate::plugins::executor::RunningPlugin;
+use crate::plugins::runtime::WasmRuntime;
+use crate::plugins::PluginContext;
+use crate::scheduler::cron::CronExpression;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of recurring tasks a single plugin may register
+pub const MAX_TASKS_PER_PLUGIN: usize = 16;
+
+/// A recurring task owned by a plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginTask {
+    pub id: String,
+    pub plugin_id: String,
+    pub name: String,
+    pub schedule: String,
+    /// WASM export invoked on each run
+    pub function: String,
+    pub enabled: bool,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>,
+}
+
+/// Register a recurring task as a `plugin` cron job
+pub fn create_task(
+    conn: &Connection,
+    plugin_id: &str,
+    name: &str,
+    schedule: &str,
+    function: &str,
+    input: Option<serde_json::Value>,
+) -> Result<String, String> {
+    if function.is_empty() {
+        return Err("Task function must not be empty".to_string());
+    }
+
+    let cron = CronExpression::parse(schedule)?;
+
+    if list_tasks(conn, plugin_id)?.len() >= MAX_TASKS_PER_PLUGIN {
+        return Err(format!(
+            "Plugin {} has reached the limit of {} scheduled tasks",
+            plugin_id, MAX_TASKS_PER_PLUGIN
+        ));
+    }
+
+    let mut params = HashMap::new();
+    if let Some(input) = input {
+        params.insert("input".to_string(), input);
+    }
+    let config = crate::scheduler::JobConfig {
+        target: function.to_string(),
+        params,
+    };
+    let config_json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    let id = format!("plugin-task-{}", uuid::Uuid::new_v4());
+    let now = chrono::Utc::now();
+    let next_run = cron.next_after(now).map(|dt| dt.to_rfc3339());
+
+    conn.execute(
+        "INSERT INTO cron_jobs (id, name, schedule, job_type, config, enabled, next_run, plugin_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, 'plugin', ?4, 1, ?5, ?6, ?7, ?7)",
+        rusqlite::params![id, name, schedule, config_json, next_run, plugin_id, now.to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+/// List the recurring tasks registered by a plugin
+pub fn list_tasks(conn: &Connection, plugin_id: &str) -> Result<Vec<PluginTask>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, plugin_id, name, schedule, config, enabled, last_run, next_run
+             FROM cron_jobs WHERE job_type = 'plugin' AND plugin_id = ?1 ORDER BY created_at",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let tasks = stmt
+        .query_map([plugin_id], |row| {
+            let config_json: String = row.get(4)?;
+            let function = serde_json::from_str::<crate::scheduler::JobConfig>(&config_json)
+                .map(|c| c.target)
+                .unwrap_or_default();
+
+            Ok(PluginTask {
+                id: row.get(0)?,
+                plugin_id: row.get(1)?,
+                name: row.get(2)?,
+                schedule: row.get(3)?,
+                function,
+                enabled: row.get::<_, i32>(5)? != 0,
+                last_run: row.get(6)?,
+                next_run: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tasks)
+}
+
+/// Delete a task, only if it belongs to the plugin
+pub fn delete_task(conn: &Connection, plugin_id: &str, task_id: &str) -> Result<bool, String> {
+    let deleted = conn
+        .execute(
+            "DELETE FROM cron_jobs WHERE id = ?1 AND job_type = 'plugin' AND plugin_id = ?2",
+            [task_id, plugin_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(deleted > 0)
+}
+
+/// Handle a `schedule.*` API request for the calling plugin
+pub fn handle_schedule_request(
+    conn: &Connection,
+    context: &PluginContext,
+    request: PluginRequest,
+) -> PluginResponse {
+    let plugin_id = context.plugin_id.as_str();
+    let param = |name: &str| request.params.get(name).and_then(|v| v.as_str());
+
+    let result = match request.method.as_str() {
+        "schedule.create" => match (param("schedule"), param("function")) {
+            (Some(schedule), Some(function)) => {
+                let name = param("name").unwrap_or(function);
+                let input = request.params.get("input").cloned();
+                create_task(conn, plugin_id, name, schedule, function, input)
+                    .map(serde_json::Value::String)
+            }
+            _ => Err("Missing 'schedule' or 'function' parameter for schedule.create".to_string()),
+        },
+        "schedule.list" => list_tasks(conn, plugin_id)
+            .and_then(|tasks| serde_json::to_value(tasks).map_err(|e| e.to_string())),
+        "schedule.delete" => match param("id") {
+            Some(id) => delete_task(conn, plugin_id, id).map(serde_json::Value::Bool),
+            None => Err("Missing 'id' parameter for schedule.delete".to_string()),
+        },
+        method => Err(format!("Unknown method: {}", method)),
+    };
+
+    match result {
+        Ok(value) => PluginResponse {
+            id: request.id,
+            result: Some(value),
+            error: None,
+        },
+        Err(e) => PluginResponse {
+            id: request.id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// Runs scheduled plugin tasks against the executor's WASM instances
+///
+/// Holds shared handles into the `PluginExecutor` so the job scheduler can
+/// invoke plugin exports without owning the executor.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))]
+pub struct PluginTaskRunner {
+    running_plugins: Arc<Mutex<HashMap<String, RunningPlugin>>>,
+    wasm_runtime: Arc<Mutex<WasmRuntime>>,
+}
+
+impl PluginTaskRunner {
+    pub(crate) fn new(
+        running_plugins: Arc<Mutex<HashMap<String, RunningPlugin>>>,
+        wasm_runtime: Arc<Mutex<WasmRuntime>>,
+    ) -> Self {
+        Self {
+            running_plugins,
+            wasm_runtime,
+        }
+    }
+
+    /// Call a plugin export with the task input through the JSON ABI
+    #[cfg(feature = "wasm")]
+    pub fn run(
+        &self,
+        plugin_id: &str,
+        function: &str,
+        input: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, String> {
+        let instance_id = {
+            let plugins = self.running_plugins.lock().map_err(|e| e.to_string())?;
+            plugins.get(plugin_id)
+                .and_then(|p| p.wasm_instance_id.clone())
+                .ok_or_else(|| format!("Plugin {} is not running", plugin_id))?
+        };
+
+        let mut runtime = self.wasm_runtime.lock().map_err(|e| e.to_string())?;
+        let result = runtime.call_function_json(&instance_id, function, input)?;
+
+        if result.success {
+            Ok(result.result)
+        } else {
+            Err(result.error.unwrap_or_else(|| format!("Plugin task {} failed", function)))
+        }
+    }
+
+    /// Call a plugin export with the task input through the JSON ABI
+    #[cfg(not(feature = "wasm"))]
+    pub fn run(
+        &self,
+        plugin_id: &str,
+        _function: &str,
+        _input: &serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, String> {
+        Err(format!("Cannot run task for plugin {}: WASM support is not enabled", plugin_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::ResourceLimits;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_create_list_delete() {
+        let conn = test_conn();
+
+        let id = create_task(&conn, "plugin-a", "Sync", "*/5 * * * *", "sync", None).unwrap();
+        let tasks = list_tasks(&conn, "plugin-a").unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].function, "sync");
+        assert!(tasks[0].next_run.is_some());
+
+        // Namespaced per plugin
+        assert!(list_tasks(&conn, "plugin-b").unwrap().is_empty());
+        assert!(!delete_task(&conn, "plugin-b", &id).unwrap());
+
+        assert!(delete_task(&conn, "plugin-a", &id).unwrap());
+        assert!(list_tasks(&conn, "plugin-a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        let conn = test_conn();
+        assert!(create_task(&conn, "plugin-a", "Bad", "not a cron", "sync", None).is_err());
+    }
+
+    #[test]
+    fn test_task_limit() {
+        let conn = test_conn();
+        for i in 0..MAX_TASKS_PER_PLUGIN {
+            create_task(&conn, "plugin-a", &format!("Task {}", i), "0 * * * *", "run", None).unwrap();
+        }
+        assert!(create_task(&conn, "plugin-a", "Extra", "0 * * * *", "run", None).is_err());
+    }
+
+    #[test]
+    fn test_handle_schedule_request() {
+        let conn = test_conn();
+        let context = PluginContext {
+            plugin_id: "plugin-a".to_string(),
+            permissions: vec![],
+            resource_limits: ResourceLimits::default(),
+        };
+
+        let response = handle_schedule_request(
+            &conn,
+            &context,
+            PluginRequest {
+                id: "1".to_string(),
+                method: "schedule.create".to_string(),
+                params: serde_json::json!({"schedule": "0 * * * *", "function": "tick", "input": {"n": 1}}),
+            },
+        );
+        assert!(response.error.is_none());
+
+        let response = handle_schedule_request(
+            &conn,
+            &context,
+            PluginRequest {
+                id: "2".to_string(),
+                method: "schedule.list".to_string(),
+                params: serde_json::json!({}),
+            },
+        );
+        let tasks = response.result.unwrap();
+        assert_eq!(tasks.as_array().unwrap().len(), 1);
+        assert_eq!(tasks[0]["function"], "tick");
+
+        let response = handle_schedule_request(
+            &conn,
+            &context,
+            PluginRequest {
+                id: "3".to_string(),
+                method: "schedule.create".to_string(),
+                params: serde_json::json!({"function": "tick"}),
+            },
+        );
+        assert!(response.error.is_some());
+    }
+}
@@ -0,0 +1,308 @@
+// Plugin Storage - Namespaced key-value store for plugins
+
+use crate::plugins::api::{PluginRequest, PluginResponse};
+use crate::plugins::PluginContext;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Storage usage for a single plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub plugin_id: String,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub key_count: u64,
+}
+
+/// Size accounted against the quota for a single entry
+fn entry_size(key: &str, value: &str) -> u64 {
+    (key.len() + value.len()) as u64
+}
+
+/// Get a value from a plugin's store
+pub fn get_value(
+    conn: &Connection,
+    plugin_id: &str,
+    key: &str,
+) -> Result<Option<serde_json::Value>, String> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM plugin_storage WHERE plugin_id = ?1 AND key = ?2",
+            [plugin_id, key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    value
+        .map(|v| serde_json::from_str(&v).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Set a value in a plugin's store, rejecting writes that exceed the quota
+///
+/// The quota check and the write share one immediate transaction, so
+/// concurrent calls from the same plugin cannot both pass the check.
+pub fn set_value(
+    conn: &Connection,
+    plugin_id: &str,
+    key: &str,
+    value: &serde_json::Value,
+    quota_bytes: u64,
+) -> Result<(), String> {
+    if key.is_empty() {
+        return Err("Storage key must not be empty".to_string());
+    }
+
+    let serialized = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    let new_size = entry_size(key, &serialized);
+
+    let tx = rusqlite::Transaction::new_unchecked(conn, rusqlite::TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let existing_size: u64 = tx
+        .query_row(
+            "SELECT size_bytes FROM plugin_storage WHERE plugin_id = ?1 AND key = ?2",
+            [plugin_id, key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(0);
+
+    let used = used_bytes(&tx, plugin_id)?;
+    if used - existing_size + new_size > quota_bytes {
+        return Err(format!(
+            "Storage quota exceeded for plugin {}: {} of {} bytes used",
+            plugin_id, used, quota_bytes
+        ));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    tx.execute(
+        "INSERT OR REPLACE INTO plugin_storage (plugin_id, key, value, size_bytes, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![plugin_id, key, serialized, new_size, now],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Delete a value from a plugin's store
+pub fn delete_value(conn: &Connection, plugin_id: &str, key: &str) -> Result<bool, String> {
+    let deleted = conn
+        .execute(
+            "DELETE FROM plugin_storage WHERE plugin_id = ?1 AND key = ?2",
+            [plugin_id, key],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(deleted > 0)
+}
+
+/// List keys in a plugin's store
+pub fn list_keys(conn: &Connection, plugin_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key FROM plugin_storage WHERE plugin_id = ?1 ORDER BY key")
+        .map_err(|e| e.to_string())?;
+
+    let keys = stmt
+        .query_map([plugin_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(keys)
+}
+
+fn used_bytes(conn: &Connection, plugin_id: &str) -> Result<u64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(size_bytes), 0) FROM plugin_storage WHERE plugin_id = ?1",
+        [plugin_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Get storage usage for a plugin
+pub fn get_usage(conn: &Connection, plugin_id: &str, quota_bytes: u64) -> Result<StorageUsage, String> {
+    let (used_bytes, key_count): (u64, u64) = conn
+        .query_row(
+            "SELECT COALESCE(SUM(size_bytes), 0), COUNT(*) FROM plugin_storage WHERE plugin_id = ?1",
+            [plugin_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(StorageUsage {
+        plugin_id: plugin_id.to_string(),
+        used_bytes,
+        quota_bytes,
+        key_count,
+    })
+}
+
+/// Remove every entry in a plugin's store
+pub fn clear(conn: &Connection, plugin_id: &str) -> Result<usize, String> {
+    conn.execute("DELETE FROM plugin_storage WHERE plugin_id = ?1", [plugin_id])
+        .map_err(|e| e.to_string())
+}
+
+/// Handle a `storage.*` API request for the calling plugin
+pub fn handle_storage_request(
+    conn: &Connection,
+    context: &PluginContext,
+    request: PluginRequest,
+) -> PluginResponse {
+    let plugin_id = context.plugin_id.as_str();
+    let quota_bytes = context.resource_limits.max_storage_kb as u64 * 1024;
+    let key = request.params.get("key").and_then(|v| v.as_str());
+
+    let result = match (request.method.as_str(), key) {
+        ("storage.get", Some(key)) => get_value(conn, plugin_id, key)
+            .map(|v| v.unwrap_or(serde_json::Value::Null)),
+        ("storage.set", Some(key)) => {
+            let value = request.params.get("value").cloned().unwrap_or(serde_json::Value::Null);
+            set_value(conn, plugin_id, key, &value, quota_bytes).map(|_| serde_json::Value::Null)
+        }
+        ("storage.delete", Some(key)) => {
+            delete_value(conn, plugin_id, key).map(serde_json::Value::Bool)
+        }
+        ("storage.keys", _) => list_keys(conn, plugin_id).map(|keys| serde_json::json!(keys)),
+        ("storage.usage", _) => get_usage(conn, plugin_id, quota_bytes)
+            .and_then(|usage| serde_json::to_value(usage).map_err(|e| e.to_string())),
+        (method, None) if method.starts_with("storage.") => {
+            Err(format!("Missing 'key' parameter for {}", method))
+        }
+        (method, _) => Err(format!("Unknown method: {}", method)),
+    };
+
+    match result {
+        Ok(value) => PluginResponse {
+            id: request.id,
+            result: Some(value),
+            error: None,
+        },
+        Err(e) => PluginResponse {
+            id: request.id,
+            result: None,
+            error: Some(e),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::ResourceLimits;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_set_get_delete() {
+        let conn = test_conn();
+        let value = serde_json::json!({"count": 3});
+
+        set_value(&conn, "plugin-a", "state", &value, 1024).unwrap();
+        assert_eq!(get_value(&conn, "plugin-a", "state").unwrap(), Some(value));
+
+        // Namespaced per plugin
+        assert_eq!(get_value(&conn, "plugin-b", "state").unwrap(), None);
+
+        assert!(delete_value(&conn, "plugin-a", "state").unwrap());
+        assert_eq!(get_value(&conn, "plugin-a", "state").unwrap(), None);
+    }
+
+    #[test]
+    fn test_quota_enforced() {
+        let conn = test_conn();
+        let big = serde_json::json!("x".repeat(100));
+
+        set_value(&conn, "plugin-a", "a", &big, 150).unwrap();
+        assert!(set_value(&conn, "plugin-a", "b", &big, 150).is_err());
+
+        // Overwriting an existing key only counts the difference
+        set_value(&conn, "plugin-a", "a", &big, 150).unwrap();
+
+        let usage = get_usage(&conn, "plugin-a", 150).unwrap();
+        assert_eq!(usage.key_count, 1);
+        assert!(usage.used_bytes <= 150);
+    }
+
+    #[test]
+    fn test_concurrent_sets_respect_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        crate::db::schema::run_migrations(&crate::db::pool::open_background(&path).unwrap()).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let conn = crate::db::pool::open_background(&path).unwrap();
+                    set_value(&conn, "plugin-a", &format!("k{}", i), &serde_json::json!("x".repeat(100)), 250)
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // Every call finished without SQLITE_BUSY, and only two entries fit
+        assert!(results.iter().filter_map(|r| r.as_ref().err()).all(|e| e.contains("quota")));
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 2);
+        let conn = crate::db::pool::open_background(&path).unwrap();
+        assert!(get_usage(&conn, "plugin-a", 250).unwrap().used_bytes <= 250);
+    }
+
+    #[test]
+    fn test_clear() {
+        let conn = test_conn();
+        set_value(&conn, "plugin-a", "a", &serde_json::json!(1), 1024).unwrap();
+        set_value(&conn, "plugin-a", "b", &serde_json::json!(2), 1024).unwrap();
+        set_value(&conn, "plugin-b", "a", &serde_json::json!(3), 1024).unwrap();
+
+        assert_eq!(clear(&conn, "plugin-a").unwrap(), 2);
+        assert!(list_keys(&conn, "plugin-a").unwrap().is_empty());
+        assert_eq!(list_keys(&conn, "plugin-b").unwrap(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_handle_storage_request() {
+        let conn = test_conn();
+        let context = PluginContext {
+            plugin_id: "plugin-a".to_string(),
+            permissions: vec![],
+            resource_limits: ResourceLimits::default(),
+        };
+
+        let response = handle_storage_request(
+            &conn,
+            &context,
+            PluginRequest {
+                id: "1".to_string(),
+                method: "storage.set".to_string(),
+                params: serde_json::json!({"key": "name", "value": "demo"}),
+            },
+        );
+        assert!(response.error.is_none());
+
+        let response = handle_storage_request(
+            &conn,
+            &context,
+            PluginRequest {
+                id: "2".to_string(),
+                method: "storage.get".to_string(),
+                params: serde_json::json!({"key": "name"}),
+            },
+        );
+        assert_eq!(response.result, Some(serde_json::json!("demo")));
+
+        let response = handle_storage_request(
+            &conn,
+            &context,
+            PluginRequest {
+                id: "3".to_string(),
+                method: "storage.get".to_string(),
+                params: serde_json::json!({}),
+            },
+        );
+        assert!(response.error.is_some());
+    }
+}
@@ -0,0 +1,340 @@
+// Plugin Tools - Agent tools declared in plugin manifests
+//
+// A manifest may list tools with a name, a description and a JSON Schema for
+// their arguments. While the plugin runs, each tool is offered to the agent as
+// `plugin_<plugin id>_<name>` next to the built-in tools. A call is checked
+// against the schema and the plugin's permissions, then runs the tool's WASM
+// export through the JSON ABI inside the plugin's sandbox.
+
+use crate::db::skill_schema;
+use crate::plugins::executor::{check_method_permission, PluginInstanceState, RunningPlugin};
+use crate::plugins::monitor::{MetricUpdate, ResourceMonitor};
+use crate::plugins::runtime::WasmRuntime;
+use crate::plugins::sandbox::SandboxManager;
+use crate::plugins::PluginPermission;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Prefix of the agent-facing name of every plugin tool
+pub const TOOL_PREFIX: &str = "plugin_";
+
+/// Longest agent-facing tool name model providers accept
+const MAX_QUALIFIED_NAME_LEN: usize = 64;
+
+/// Maximum number of tools a single plugin may declare
+pub const MAX_TOOLS_PER_PLUGIN: usize = 32;
+
+/// API namespaces a tool may declare it uses
+const API_NAMESPACES: [&str; 4] = ["fs", "http", "db", "system"];
+
+/// A tool as declared in a plugin manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginToolDef {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema of the arguments
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    /// WASM export called with the arguments; defaults to the tool name
+    #[serde(default)]
+    pub function: Option<String>,
+    /// API namespaces the tool uses ("fs", "http", "db", "system"); each
+    /// must be covered by the plugin's permissions when the tool is called
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+fn default_input_schema() -> Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+impl PluginToolDef {
+    pub fn function(&self) -> &str {
+        self.function.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// A plugin tool as offered to the agent, in the runtime's tool format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+    pub plugin_id: String,
+}
+
+/// Agent-facing name of a plugin's tool
+pub fn qualified_name(plugin_id: &str, tool: &str) -> String {
+    let id: String = plugin_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    format!("{}{}_{}", TOOL_PREFIX, id, tool)
+}
+
+/// Check the tools declared by a manifest
+pub fn validate_tools(
+    plugin_id: &str,
+    tools: &[PluginToolDef],
+    permissions: &[PluginPermission],
+) -> Result<(), String> {
+    if tools.len() > MAX_TOOLS_PER_PLUGIN {
+        return Err(format!("A plugin may declare at most {} tools", MAX_TOOLS_PER_PLUGIN));
+    }
+
+    let mut names = HashSet::new();
+    for tool in tools {
+        let valid_name = !tool.name.is_empty()
+            && tool.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid_name {
+            return Err(format!(
+                "Tool name '{}' must consist of lowercase letters, digits and underscores",
+                tool.name
+            ));
+        }
+        if !names.insert(tool.name.as_str()) {
+            return Err(format!("Tool '{}' is declared more than once", tool.name));
+        }
+        if qualified_name(plugin_id, &tool.name).len() > MAX_QUALIFIED_NAME_LEN {
+            return Err(format!("Tool name '{}' is too long for plugin {}", tool.name, plugin_id));
+        }
+        if tool.description.trim().is_empty() {
+            return Err(format!("Tool '{}' requires a description", tool.name));
+        }
+        if tool.function().is_empty() {
+            return Err(format!("Tool '{}' function must not be empty", tool.name));
+        }
+        jsonschema::validator_for(&tool.input_schema)
+            .map_err(|e| format!("Tool '{}' input schema is not a valid JSON Schema: {}", tool.name, e))?;
+
+        for namespace in &tool.requires {
+            if !API_NAMESPACES.contains(&namespace.as_str()) {
+                return Err(format!("Tool '{}' requires unknown API '{}'", tool.name, namespace));
+            }
+            check_method_permission(&format!("{}.", namespace), permissions)
+                .map_err(|e| format!("Tool '{}': {}", tool.name, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs plugin tools called by the agent against the executor's instances
+///
+/// Holds shared handles into the `PluginExecutor`, like `PluginTaskRunner`,
+/// so sidecar host requests can reach plugins without owning the executor.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "wasm"), allow(dead_code))]
+pub struct PluginToolRunner {
+    running_plugins: Arc<Mutex<HashMap<String, RunningPlugin>>>,
+    sandbox_manager: Arc<Mutex<SandboxManager>>,
+    wasm_runtime: Arc<Mutex<WasmRuntime>>,
+    monitor: Arc<Mutex<ResourceMonitor>>,
+}
+
+impl PluginToolRunner {
+    pub(crate) fn new(
+        running_plugins: Arc<Mutex<HashMap<String, RunningPlugin>>>,
+        sandbox_manager: Arc<Mutex<SandboxManager>>,
+        wasm_runtime: Arc<Mutex<WasmRuntime>>,
+        monitor: Arc<Mutex<ResourceMonitor>>,
+    ) -> Self {
+        Self {
+            running_plugins,
+            sandbox_manager,
+            wasm_runtime,
+            monitor,
+        }
+    }
+
+    /// Tools of all running plugins, sorted by name
+    pub fn list(&self) -> Result<Vec<PluginTool>, String> {
+        let plugins = self.running_plugins.lock().map_err(|e| e.to_string())?;
+        let mut tools: Vec<PluginTool> = plugins
+            .values()
+            .filter(|p| p.state == PluginInstanceState::Running)
+            .flat_map(|p| {
+                p.manifest.tools.iter().map(|tool| PluginTool {
+                    name: qualified_name(&p.id, &tool.name),
+                    description: tool.description.clone(),
+                    input_schema: tool.input_schema.clone(),
+                    plugin_id: p.id.clone(),
+                })
+            })
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tools)
+    }
+
+    /// Running plugin and declaration of a tool by its agent-facing name
+    fn resolve(&self, name: &str) -> Result<(String, PluginToolDef), String> {
+        let plugins = self.running_plugins.lock().map_err(|e| e.to_string())?;
+        plugins
+            .values()
+            .filter(|p| p.state == PluginInstanceState::Running)
+            .find_map(|p| {
+                p.manifest.tools.iter()
+                    .find(|tool| qualified_name(&p.id, &tool.name) == name)
+                    .map(|tool| (p.id.clone(), tool.clone()))
+            })
+            .ok_or_else(|| format!("Unknown plugin tool: {}", name))
+    }
+
+    /// Check a call against the tool's schema and the plugin's sandbox
+    fn authorize(&self, plugin_id: &str, tool: &PluginToolDef, args: &Value) -> Result<(), String> {
+        skill_schema::validate(&tool.input_schema, args)
+            .map_err(|e| format!("Invalid arguments for tool '{}': {}", tool.name, e))?;
+
+        let sandbox_manager = self.sandbox_manager.lock().map_err(|e| e.to_string())?;
+        let sandbox = sandbox_manager.get_sandbox(plugin_id)
+            .ok_or_else(|| format!("Sandbox not found for plugin {}", plugin_id))?;
+        sandbox.check_resource_limits()
+            .map_err(|e| format!("Resource limit exceeded: {}", e))?;
+        for namespace in &tool.requires {
+            check_method_permission(&format!("{}.", namespace), &sandbox.context().permissions)?;
+        }
+        Ok(())
+    }
+
+    /// Call a plugin tool with the agent's arguments
+    pub fn call(&self, name: &str, args: &Value) -> Result<Value, String> {
+        let (plugin_id, tool) = self.resolve(name)?;
+        self.authorize(&plugin_id, &tool, args)?;
+
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.update_metrics(&plugin_id, MetricUpdate::Syscall);
+        }
+        self.invoke(&plugin_id, &tool, args)
+    }
+
+    /// Run the tool's export through the JSON ABI
+    #[cfg(feature = "wasm")]
+    fn invoke(&self, plugin_id: &str, tool: &PluginToolDef, args: &Value) -> Result<Value, String> {
+        let instance_id = {
+            let plugins = self.running_plugins.lock().map_err(|e| e.to_string())?;
+            plugins.get(plugin_id)
+                .and_then(|p| p.wasm_instance_id.clone())
+                .ok_or_else(|| format!("No WASM instance for plugin {}", plugin_id))?
+        };
+
+        let (result, memory_bytes) = {
+            let mut runtime = self.wasm_runtime.lock().map_err(|e| e.to_string())?;
+            let result = runtime.call_function_json(&instance_id, tool.function(), args)?;
+            let memory_bytes = runtime.get_instance_state(&instance_id)
+                .map(|state| state.memory_used)
+                .unwrap_or(0);
+            (result, memory_bytes)
+        };
+
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.update_from_wasm(&instance_id, result.fuel_consumed, memory_bytes);
+        }
+
+        if result.success {
+            Ok(result.result.unwrap_or(Value::Null))
+        } else {
+            Err(result.error.unwrap_or_else(|| format!("Plugin tool {} failed", tool.name)))
+        }
+    }
+
+    /// Run the tool's export through the JSON ABI
+    #[cfg(not(feature = "wasm"))]
+    fn invoke(&self, plugin_id: &str, tool: &PluginToolDef, _args: &Value) -> Result<Value, String> {
+        Err(format!(
+            "Cannot call tool {} of plugin {}: WASM support is not enabled",
+            tool.name, plugin_id
+        ))
+    }
+}
+
+/// Answer a `call_plugin_tool` request from the agent runtime
+pub fn call_request(app: &tauri::AppHandle, params: Value) -> Result<Value, String> {
+    use tauri::Manager;
+
+    let name = params.get("tool").and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing 'tool' parameter".to_string())?;
+    let args = params.get("args").cloned().unwrap_or_else(|| serde_json::json!({}));
+    app.state::<PluginToolRunner>().call(name, &args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::executor::PluginExecutor;
+    use crate::plugins::PluginManifest;
+    use serde_json::json;
+
+    fn tool(name: &str, requires: &[&str]) -> PluginToolDef {
+        PluginToolDef {
+            name: name.to_string(),
+            description: "Look up the weather".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": { "city": { "type": "string" } },
+                "required": ["city"]
+            }),
+            function: None,
+            requires: requires.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_tools() {
+        let network = vec![PluginPermission::Network { hosts: vec!["api.weather.com".to_string()] }];
+        assert_eq!(qualified_name("com.example-Weather", "forecast"), "plugin_com_example_weather_forecast");
+
+        assert!(validate_tools("weather", &[tool("forecast", &["http"])], &network).is_ok());
+        // Uses an API the plugin has no permission for
+        assert!(validate_tools("weather", &[tool("forecast", &["fs"])], &network).is_err());
+        assert!(validate_tools("weather", &[tool("forecast", &["shell"])], &network).is_err());
+        assert!(validate_tools("weather", &[tool("Forecast!", &[])], &network).is_err());
+        assert!(validate_tools("weather", &[tool("forecast", &[]), tool("forecast", &[])], &network).is_err());
+
+        let mut bad_schema = tool("forecast", &[]);
+        bad_schema.input_schema = json!({ "type": 12 });
+        assert!(validate_tools("weather", &[bad_schema], &network).is_err());
+
+        // Manifests without tools still parse
+        let manifest: PluginManifest = serde_json::from_value(json!({
+            "id": "weather", "name": "Weather", "version": "1.0.0", "description": "",
+            "author": "", "main": "weather.wasm", "permissions": [], "api_version": "1.0"
+        }))
+        .unwrap();
+        assert!(manifest.tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_and_call_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut executor = PluginExecutor::with_plugins_dir(dir.path().to_path_buf());
+        let runner = executor.tool_runner();
+
+        let manifest = PluginManifest {
+            id: "weather".to_string(),
+            name: "Weather".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            author: String::new(),
+            main: dir.path().join("missing.wasm").to_string_lossy().to_string(),
+            permissions: vec![],
+            api_version: "1.0".to_string(),
+            tools: vec![tool("forecast", &[]), tool("upload", &["http"])],
+        };
+        executor.start_plugin(manifest).await.unwrap();
+
+        let names: Vec<_> = runner.list().unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["plugin_weather_forecast", "plugin_weather_upload"]);
+
+        assert!(runner.call("plugin_other_forecast", &json!({ "city": "Seoul" })).unwrap_err().contains("Unknown"));
+        let invalid = runner.call("plugin_weather_forecast", &json!({ "city": 3 })).unwrap_err();
+        assert!(invalid.contains("Invalid arguments"), "{}", invalid);
+        // The plugin has no network permission
+        let denied = runner.call("plugin_weather_upload", &json!({ "city": "Seoul" })).unwrap_err();
+        assert!(denied.contains("Network permission required"), "{}", denied);
+
+        executor.stop_plugin("weather").await.unwrap();
+        assert!(runner.list().unwrap().is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use std::collections::HashMap;
 
 // WASM feature-gated imports
 #[cfg(feature = "wasm")]
-use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
 #[cfg(feature = "wasm")]
 use wasmtime_wasi::preview1::WasiP1Ctx;
 
@@ -71,6 +71,13 @@ impl WasiContext {
             builder.arg(arg);
         }
 
+        // Preopen each mapped directory; paths outside them are not reachable
+        for dir in &self.preopened_dirs {
+            let (dir_perms, file_perms) = dir.permissions.to_wasi();
+            builder.preopened_dir(&dir.host_path, &dir.guest_path, dir_perms, file_perms)
+                .map_err(|e| format!("Failed to preopen {}: {}", dir.host_path, e))?;
+        }
+
         // Note: Stdio capture configuration skipped - CaptureOutput not available in wasmtime 22
         // This will be addressed in a future update
 
@@ -112,6 +119,26 @@ pub struct WasiPermissions {
     pub create: bool,
 }
 
+impl WasiPermissions {
+    /// Directory and file permissions of a preopen
+    #[cfg(feature = "wasm")]
+    fn to_wasi(&self) -> (DirPerms, FilePerms) {
+        let mut dir_perms = DirPerms::empty();
+        let mut file_perms = FilePerms::empty();
+        if self.read {
+            dir_perms |= DirPerms::READ;
+            file_perms |= FilePerms::READ;
+        }
+        if self.create {
+            dir_perms |= DirPerms::MUTATE;
+        }
+        if self.write {
+            file_perms |= FilePerms::WRITE;
+        }
+        (dir_perms, file_perms)
+    }
+}
+
 impl Default for WasiPermissions {
     fn default() -> Self {
         Self {
@@ -300,29 +327,23 @@ pub fn create_minimal_wasi_context(plugin_id: &str) -> Result<(), String> {
     Err(format!("WASI not available (build without wasm feature) for plugin: {}", plugin_id))
 }
 
-/// Create a WASI context with working directory
+/// Create a WASI context with the given directories preopened
 #[cfg(feature = "wasm")]
-pub fn create_wasi_context_with_dir(
+pub fn create_wasi_context_with_dirs(
     _plugin_id: &str,
-    work_dir: &str,
+    dirs: &[WasiDirectory],
 ) -> Result<WasiP1Ctx, String> {
-    let path = Path::new(work_dir);
-    if !path.exists() {
-        // Create the directory if it doesn't exist
-        std::fs::create_dir_all(path)
-            .map_err(|e| format!("Failed to create work directory: {}", e))?;
-    }
-
-    // For now, create minimal context
-    // Directory preopening will be added in a future step
-    Ok(WasiCtxBuilder::new().build_p1())
+    dirs.iter()
+        .cloned()
+        .fold(WasiContext::new(), WasiContext::with_directory)
+        .build()
 }
 
-/// Create a WASI context with working directory (non-wasm)
+/// Create a WASI context with the given directories preopened (non-wasm)
 #[cfg(not(feature = "wasm"))]
-pub fn create_wasi_context_with_dir(
+pub fn create_wasi_context_with_dirs(
     plugin_id: &str,
-    _work_dir: &str,
+    _dirs: &[WasiDirectory],
 ) -> Result<(), String> {
     Err(format!("WASI not available (build without wasm feature) for plugin: {}", plugin_id))
 }
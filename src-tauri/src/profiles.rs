@@ -0,0 +1,347 @@
+//! Local Profiles
+//!
+//! Each profile (e.g. Work, Personal) has its own SQLite database and its
+//! own keychain namespace, so conversations, settings, jobs and credentials
+//! never mix. The default profile keeps the original database file and
+//! keychain service; others live under `profiles/<id>/`. The profile list
+//! and the active profile are kept in `profiles.json` in the app data folder,
+//! outside any profile's database.
+//!
+//! `switch_profile` swaps the open database and re-initializes the state
+//! that depends on it (credentials, column key, scheduler, plugin storage,
+//! agent context, REST API, quick ask hotkey) without restarting the app,
+//! then emits `profiles://switched` so the frontend reloads its data.
+
+use crate::db::{self, DbState, DB_FILE};
+use crate::error::AppError;
+use crate::security::credentials::SERVICE_NAME;
+use crate::security::{CredentialManager, SensitiveSession};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// File listing the profiles, inside the app data folder
+const REGISTRY_FILE: &str = "profiles.json";
+
+/// Profile using the database and keychain service from before profiles
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Event carrying the newly active `Profile`
+pub const SWITCHED_EVENT: &str = "profiles://switched";
+
+const MAX_NAME_LENGTH: usize = 50;
+
+/// What a profile is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileRole {
+    Personal,
+    Work,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub role: ProfileRole,
+    pub created_at: String,
+}
+
+/// Profiles and the active one, as stored in `profiles.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "Default".to_string(),
+                role: ProfileRole::Personal,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+}
+
+impl ProfileList {
+    fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+}
+
+/// Profiles in `app_dir`; the default profile alone when none were created
+pub fn load(app_dir: &Path) -> ProfileList {
+    let path = app_dir.join(REGISTRY_FILE);
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return ProfileList::default();
+    };
+    match serde_json::from_str::<ProfileList>(&json) {
+        Ok(mut list) => {
+            if list.get(DEFAULT_PROFILE_ID).is_none() {
+                list.profiles.insert(0, ProfileList::default().profiles.remove(0));
+            }
+            if list.get(&list.active).is_none() {
+                tracing::warn!("Active profile missing from {}, using the default profile", path.display());
+                list.active = DEFAULT_PROFILE_ID.to_string();
+            }
+            list
+        }
+        Err(e) => {
+            tracing::warn!("Invalid {}, using the default profile: {}", path.display(), e);
+            ProfileList::default()
+        }
+    }
+}
+
+fn save(app_dir: &Path, list: &ProfileList) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(list).map_err(|e| e.to_string())?;
+    // Write a temporary file first so a crash never leaves a partial list
+    let path = app_dir.join(REGISTRY_FILE);
+    let temp = app_dir.join(format!("{}.tmp", REGISTRY_FILE));
+    std::fs::write(&temp, json).map_err(|e| format!("Failed to save profiles: {}", e))?;
+    std::fs::rename(&temp, &path).map_err(|e| format!("Failed to save profiles: {}", e))
+}
+
+/// Database file of a profile
+pub fn db_path(app_dir: &Path, profile_id: &str) -> PathBuf {
+    if profile_id == DEFAULT_PROFILE_ID {
+        app_dir.join(DB_FILE)
+    } else {
+        app_dir.join("profiles").join(profile_id).join(DB_FILE)
+    }
+}
+
+/// Keychain service holding a profile's credentials
+pub fn credential_service(profile_id: &str) -> String {
+    if profile_id == DEFAULT_PROFILE_ID {
+        SERVICE_NAME.to_string()
+    } else {
+        format!("{}.{}", SERVICE_NAME, profile_id)
+    }
+}
+
+/// Database file of the active profile
+pub fn active_db_path(app_dir: &Path) -> PathBuf {
+    db_path(app_dir, &load(app_dir).active)
+}
+
+/// Credential manager for the active profile's keychain namespace
+pub fn active_credentials(app_dir: &Path) -> CredentialManager {
+    CredentialManager::new(credential_service(&load(app_dir).active))
+}
+
+/// Add a profile with an empty, migrated database
+pub fn create(app_dir: &Path, name: &str, role: ProfileRole) -> Result<Profile, String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Profile names are at most {} characters", MAX_NAME_LENGTH));
+    }
+    let mut list = load(app_dir);
+    if list.profiles.iter().any(|p| p.name.to_lowercase() == name.to_lowercase()) {
+        return Err(format!("A profile named '{}' already exists", name));
+    }
+
+    let profile = Profile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        role,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let path = db_path(app_dir, &profile.id);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create profile folder: {}", e))?;
+    }
+    db::open_database(&path).map_err(|e| format!("Failed to create profile database: {}", e))?;
+
+    list.profiles.push(profile.clone());
+    save(app_dir, &list)?;
+    Ok(profile)
+}
+
+fn app_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
+
+/// Make `profile_id` the active profile and re-initialize the state backed by
+/// its database and keychain namespace
+pub async fn switch(app: &AppHandle, profile_id: &str) -> Result<Profile, String> {
+    let dir = app_dir(app)?;
+    let mut list = load(&dir);
+    let profile = list
+        .get(profile_id)
+        .cloned()
+        .ok_or_else(|| format!("Profile not found: {}", profile_id))?;
+    if list.active == profile.id {
+        return Ok(profile);
+    }
+
+    // Everything that can fail runs before the swap, so a failure leaves the
+    // old profile active with its scheduler running
+    let path = db_path(&dir, &profile.id);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create profile folder: {}", e))?;
+    }
+    let conn = db::open_database(&path).map_err(|e| format!("Failed to open profile database: {}", e))?;
+
+    let scheduler = app.state::<Arc<tokio::sync::Mutex<crate::scheduler::JobScheduler>>>();
+    let mut scheduler = scheduler.lock().await;
+    let next = scheduler.for_database(&path.to_string_lossy());
+    // Another window may already lead this profile's database and own its
+    // registered workflow triggers
+    if next.try_lead() {
+        if let Err(e) = crate::workflow::schedule::disable_stale_jobs(&conn) {
+            tracing::warn!("Failed to disable stale workflow jobs: {}", e);
+        }
+    }
+    next.load_jobs(db::enabled_scheduled_jobs(&conn)?).await?;
+    next.refresh_schedule().await;
+
+    let db = app.state::<DbState>();
+    let previous_shortcut = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        crate::quick_ask::shortcut(&conn).ok()
+    };
+    let previous_active = std::mem::replace(&mut list.active, profile.id.clone());
+    save(&dir, &list)?;
+
+    // No job of the old profile may run against the new database
+    scheduler.stop().await;
+    if let Err(e) = db.replace(conn, &path) {
+        list.active = previous_active;
+        if let Err(e) = save(&dir, &list) {
+            tracing::warn!("Failed to restore the active profile: {}", e);
+        }
+        if let Err(e) = scheduler.start().await {
+            tracing::warn!("Failed to restart the scheduler: {}", e);
+        }
+        return Err(e);
+    }
+
+    {
+        let credentials = CredentialManager::new(credential_service(&profile.id));
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        crate::security::enable_column_encryption(&conn, &credentials);
+        *app.state::<Mutex<CredentialManager>>().lock().map_err(|e| e.to_string())? = credentials;
+    }
+    app.state::<Mutex<SensitiveSession>>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .lock_all();
+    app.state::<Mutex<crate::plugins::PluginExecutor>>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .set_storage_db(path.clone());
+    app.state::<Arc<crate::agent::commands::AgentState>>()
+        .context_manager
+        .lock()
+        .await
+        .clear();
+
+    // Clients and connections opened with the old profile's credentials
+    app.state::<Mutex<crate::sidecar::SidecarState>>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .forget_providers();
+    app.state::<crate::integration::cloud::s3::S3Manager>().clear_clients().await;
+    app.state::<tokio::sync::Mutex<crate::integration::DatabasePoolManager>>()
+        .lock()
+        .await
+        .remove_all();
+    app.state::<Arc<crate::sync::commands::SyncState>>().reset().await;
+
+    *scheduler = next;
+    if let Err(e) = scheduler.start().await {
+        tracing::error!("Failed to start the scheduler of profile '{}': {}", profile.name, e);
+    }
+    drop(scheduler);
+
+    tracing::info!("Switched to profile '{}'", profile.name);
+
+    // Settings read once at startup
+    if let Some(previous) = previous_shortcut {
+        if let Err(e) = crate::quick_ask::rebind(app, &previous) {
+            tracing::warn!("{}", e);
+        }
+    }
+    if let Err(e) = crate::api::restart(app).await {
+        tracing::warn!("REST API not restarted: {}", e);
+    }
+
+    if let Err(e) = app.emit(SWITCHED_EVENT, &profile) {
+        tracing::warn!("Failed to emit profile switch: {}", e);
+    }
+    Ok(profile)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_profiles(app: AppHandle) -> Result<ProfileList, AppError> {
+    Ok(load(&app_dir(&app)?))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn create_profile(app: AppHandle, name: String, role: Option<ProfileRole>) -> Result<Profile, AppError> {
+    Ok(create(&app_dir(&app)?, &name, role.unwrap_or(ProfileRole::Other))?)
+}
+
+/// Activate a profile; the frontend reloads its data on `profiles://switched`
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn switch_profile(app: AppHandle, id: String) -> Result<Profile, AppError> {
+    Ok(switch(&app, &id).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_and_namespaces() {
+        let dir = Path::new("/data/app");
+        assert_eq!(db_path(dir, DEFAULT_PROFILE_ID), dir.join("assistant.db"));
+        assert_eq!(db_path(dir, "p1"), dir.join("profiles").join("p1").join("assistant.db"));
+        assert_eq!(credential_service(DEFAULT_PROFILE_ID), "ai-assistant-tauri");
+        assert_eq!(credential_service("p1"), "ai-assistant-tauri.p1");
+    }
+
+    #[test]
+    fn test_create_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load(dir.path()).active, DEFAULT_PROFILE_ID);
+        assert_eq!(active_db_path(dir.path()), dir.path().join("assistant.db"));
+
+        let work = create(dir.path(), "  Work  ", ProfileRole::Work).unwrap();
+        assert_eq!(work.name, "Work");
+        assert!(db_path(dir.path(), &work.id).exists());
+        assert!(create(dir.path(), "work", ProfileRole::Other).is_err());
+        assert!(create(dir.path(), " ", ProfileRole::Other).is_err());
+
+        let list = load(dir.path());
+        let names: Vec<&str> = list.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Default", "Work"]);
+        assert_eq!(list.active, DEFAULT_PROFILE_ID);
+
+        // A registry pointing at a missing profile falls back to the default
+        save(dir.path(), &ProfileList { active: "gone".to_string(), ..list }).unwrap();
+        let list = load(dir.path());
+        assert_eq!(list.active, DEFAULT_PROFILE_ID);
+        assert_eq!(list.profiles.len(), 2);
+    }
+}
@@ -0,0 +1,723 @@
+//! Provider profiles
+//!
+//! A provider profile is a typed LLM provider configuration: its kind
+//! (Anthropic, OpenAI, Gemini, Ollama or any OpenAI-compatible server such
+//! as LM Studio, vLLM or llama.cpp), an optional base URL overriding the
+//! kind's public endpoint, the models offered in the model picker, the
+//! default model and default generation parameters. Profiles are stored in
+//! `provider_profiles`; API keys never are, they live in the platform
+//! keychain under `provider.<profile id>`.
+//!
+//! A new API key is checked against the provider's model list endpoint
+//! before the profile is saved, so a mistyped key is reported in the
+//! settings instead of on the first chat.
+//!
+//! OpenAI-compatible servers need a base URL and usually no key. Saving
+//! such a profile probes its `/models` endpoint and keeps the context
+//! window and vision support of each model when the server exposes them
+//! (vLLM, llama.cpp, LM Studio and OpenRouter each name them differently).
+
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::security::CredentialManager;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const MAX_NAME_LENGTH: usize = 80;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// API version sent to Anthropic
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Anthropic,
+    OpenAI,
+    Gemini,
+    Ollama,
+    /// Server speaking the OpenAI chat completions API
+    #[serde(rename = "openai-compatible")]
+    OpenAICompatible,
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Anthropic => "anthropic",
+            Self::OpenAI => "openai",
+            Self::Gemini => "gemini",
+            Self::Ollama => "ollama",
+            Self::OpenAICompatible => "openai-compatible",
+        }
+    }
+
+    /// Endpoint used when a profile sets no base URL; None when it must
+    pub fn default_base_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Anthropic => Some("https://api.anthropic.com"),
+            Self::OpenAI => Some("https://api.openai.com/v1"),
+            Self::Gemini => Some("https://generativelanguage.googleapis.com"),
+            Self::Ollama => Some("http://localhost:11434"),
+            Self::OpenAICompatible => None,
+        }
+    }
+
+    pub fn requires_api_key(&self) -> bool {
+        !matches!(self, Self::Ollama | Self::OpenAICompatible)
+    }
+}
+
+/// What a model supports, as far as its server says
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelCapabilities {
+    pub context_window: Option<u32>,
+    pub vision: Option<bool>,
+}
+
+/// Outcome of probing a provider's model list endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointProbe {
+    pub reachable: bool,
+    pub latency_ms: u64,
+    pub models: Vec<String>,
+    /// Detected capabilities, by model; models without metadata are left out
+    pub capabilities: BTreeMap<String, ModelCapabilities>,
+    pub error: Option<String>,
+}
+
+/// Generation parameters applied when a request sets none
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GenerationDefaults {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProfile {
+    pub id: String,
+    pub name: String,
+    pub kind: ProviderKind,
+    pub base_url: Option<String>,
+    pub models: Vec<String>,
+    pub default_model: Option<String>,
+    pub defaults: GenerationDefaults,
+    /// Detected model capabilities of an OpenAI-compatible server, by model
+    pub capabilities: BTreeMap<String, ModelCapabilities>,
+    pub enabled: bool,
+    /// Whether an API key is stored in the keychain
+    pub has_api_key: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields of a profile to create or update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProfileInput {
+    /// Profile to update; a new profile is created when unset
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub kind: ProviderKind,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Models offered; filled from the provider when empty and a key is checked
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub defaults: GenerationDefaults,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// New API key; the stored key is kept when unset
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Keychain entry holding a profile's API key
+pub fn credential_key(id: &str) -> String {
+    format!("provider.{}", id)
+}
+
+fn clean(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Check an input against the other profiles and normalize it
+fn validate(conn: &Connection, input: ProviderProfileInput) -> Result<ProviderProfileInput, AppError> {
+    let name = input.name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err(AppError::invalid_input("Profile name is required"));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::invalid_input(format!("Profile names are at most {} characters", MAX_NAME_LENGTH)));
+    }
+    let taken: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM provider_profiles WHERE name = ?1 COLLATE NOCASE AND id != ?2)",
+            params![name, input.id.as_deref().unwrap_or_default()],
+            |row| row.get(0),
+        )?;
+    if taken {
+        return Err(AppError::invalid_input(format!("A provider profile named '{}' already exists", name)));
+    }
+
+    let base_url = clean(input.base_url).map(|url| url.trim_end_matches('/').to_string());
+    if base_url.is_none() && input.kind.default_base_url().is_none() {
+        return Err(AppError::invalid_input(format!("A base URL is required for {}", input.kind.as_str())));
+    }
+    if let Some(url) = &base_url {
+        let parsed = reqwest::Url::parse(url).map_err(|e| AppError::invalid_input(format!("Invalid base URL: {}", e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::invalid_input("Base URLs must use http or https"));
+        }
+    }
+
+    let mut models: Vec<String> = input.models.into_iter().filter_map(|m| clean(Some(m))).collect();
+    models.dedup();
+    let default_model = clean(input.default_model);
+    if let Some(model) = &default_model {
+        if !models.is_empty() && !models.contains(model) {
+            return Err(AppError::invalid_input(format!("Default model {} is not in the model list", model)));
+        }
+    }
+
+    let defaults = input.defaults;
+    if defaults.temperature.is_some_and(|t| !(0.0..=2.0).contains(&t)) {
+        return Err(AppError::invalid_input("Temperature must be between 0 and 2"));
+    }
+    if defaults.top_p.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+        return Err(AppError::invalid_input("Top P must be between 0 and 1"));
+    }
+    if defaults.max_tokens == Some(0) {
+        return Err(AppError::invalid_input("Max tokens must be positive"));
+    }
+
+    Ok(ProviderProfileInput {
+        id: clean(input.id),
+        name,
+        base_url,
+        models,
+        default_model,
+        defaults,
+        api_key: clean(input.api_key),
+        ..input
+    })
+}
+
+const COLUMNS: &str =
+    "id, name, kind, base_url, models, default_model, defaults, capabilities, enabled, created_at, updated_at";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<ProviderProfile> {
+    let kind: String = row.get(2)?;
+    let models: String = row.get(4)?;
+    let defaults: String = row.get(6)?;
+    let capabilities: String = row.get(7)?;
+    Ok(ProviderProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: serde_json::from_value(serde_json::Value::String(kind.clone()))
+            .map_err(|_| rusqlite::Error::InvalidColumnType(2, kind, rusqlite::types::Type::Text))?,
+        base_url: row.get(3)?,
+        models: serde_json::from_str(&models).unwrap_or_default(),
+        default_model: row.get(5)?,
+        defaults: serde_json::from_str(&defaults).unwrap_or_default(),
+        capabilities: serde_json::from_str(&capabilities).unwrap_or_default(),
+        enabled: row.get(8)?,
+        has_api_key: false,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<ProviderProfile, String> {
+    conn.query_row(&format!("SELECT {} FROM provider_profiles WHERE id = ?1", COLUMNS), [id], from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Provider profile not found: {}", id))
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<ProviderProfile>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM provider_profiles ORDER BY name COLLATE NOCASE", COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let profiles = stmt
+        .query_map([], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(profiles)
+}
+
+/// Store a validated input under `id`; `capabilities` replace the stored
+/// ones when given
+fn save(
+    conn: &Connection,
+    id: &str,
+    input: &ProviderProfileInput,
+    capabilities: Option<&BTreeMap<String, ModelCapabilities>>,
+) -> Result<ProviderProfile, String> {
+    let models = serde_json::to_string(&input.models).map_err(|e| e.to_string())?;
+    let defaults = serde_json::to_string(&input.defaults).map_err(|e| e.to_string())?;
+    let capabilities = capabilities.map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO provider_profiles
+             (id, name, kind, base_url, models, default_model, defaults, capabilities, enabled, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(?8, '{}'), ?9, ?10, ?10)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name, kind = excluded.kind, base_url = excluded.base_url, models = excluded.models,
+             default_model = excluded.default_model, defaults = excluded.defaults,
+             capabilities = COALESCE(?8, capabilities), enabled = excluded.enabled,
+             updated_at = excluded.updated_at",
+        params![
+            id,
+            input.name,
+            input.kind.as_str(),
+            input.base_url,
+            models,
+            input.default_model,
+            defaults,
+            capabilities,
+            input.enabled,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    get(conn, id)
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<(), String> {
+    get(conn, id)?;
+    conn.execute("DELETE FROM provider_profiles WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn status_error(kind: ProviderKind, status: reqwest::StatusCode, body: &str) -> String {
+    if matches!(status.as_u16(), 401 | 403) || (kind == ProviderKind::Gemini && status.as_u16() == 400) {
+        return format!("The {} API key was rejected ({})", kind.as_str(), status);
+    }
+    let detail: String = body.trim().chars().take(200).collect();
+    format!("{} returned {}: {}", kind.as_str(), status, detail)
+}
+
+/// Why a model list could not be read
+enum ListError {
+    /// The server gave no HTTP response
+    Unreachable(String),
+    /// The server answered with an error or an unreadable list
+    Failed(String),
+}
+
+impl ListError {
+    fn into_message(self) -> String {
+        match self {
+            Self::Unreachable(e) | Self::Failed(e) => e,
+        }
+    }
+}
+
+/// Raw model list response of a provider
+async fn model_list(kind: ProviderKind, base_url: Option<&str>, api_key: Option<&str>) -> Result<Value, ListError> {
+    let base = base_url
+        .or(kind.default_base_url())
+        .ok_or_else(|| ListError::Failed(format!("A base URL is required for {}", kind.as_str())))?
+        .trim_end_matches('/');
+    let key = api_key.unwrap_or_default();
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| ListError::Failed(e.to_string()))?;
+
+    let request = match kind {
+        ProviderKind::Anthropic => client
+            .get(format!("{}/v1/models?limit=1000", base))
+            .header("x-api-key", key)
+            .header("anthropic-version", ANTHROPIC_VERSION),
+        ProviderKind::OpenAI => client.get(format!("{}/models", base)).bearer_auth(key),
+        // Local servers mostly run without a key
+        ProviderKind::OpenAICompatible => match api_key {
+            Some(key) => client.get(format!("{}/models", base)).bearer_auth(key),
+            None => client.get(format!("{}/models", base)),
+        },
+        ProviderKind::Gemini => client.get(format!("{}/v1beta/models?pageSize=1000", base)).header("x-goog-api-key", key),
+        ProviderKind::Ollama => client.get(format!("{}/api/tags", base)),
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ListError::Unreachable(format!("Could not reach {}: {}", kind.as_str(), e)))?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| ListError::Failed(e.to_string()))?;
+    if !status.is_success() {
+        return Err(ListError::Failed(status_error(kind, status, &body)));
+    }
+    serde_json::from_str(&body)
+        .map_err(|e| ListError::Failed(format!("Unexpected model list from {}: {}", kind.as_str(), e)))
+}
+
+/// Model names in a model list response
+fn model_names(kind: ProviderKind, json: &Value) -> Vec<String> {
+    let ids = |list: &str, field: &str| -> Vec<String> {
+        json[list]
+            .as_array()
+            .map(|items| items.iter().filter_map(|m| m[field].as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let mut models = match kind {
+        ProviderKind::Anthropic | ProviderKind::OpenAI | ProviderKind::OpenAICompatible => ids("data", "id"),
+        ProviderKind::Ollama => ids("models", "name"),
+        // Only models that can chat, without the "models/" prefix
+        ProviderKind::Gemini => json["models"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|m| {
+                        m["supportedGenerationMethods"]
+                            .as_array()
+                            .is_some_and(|methods| methods.iter().any(|v| v == "generateContent"))
+                    })
+                    .filter_map(|m| m["name"].as_str())
+                    .map(|name| name.trim_start_matches("models/").to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    models.sort();
+    models
+}
+
+/// Capabilities of one entry of an OpenAI-style model list; servers that
+/// add metadata each use their own field names
+fn model_capabilities(model: &Value) -> ModelCapabilities {
+    let context_window = [
+        &model["max_model_len"],       // vLLM
+        &model["context_length"],      // OpenRouter
+        &model["max_context_length"],  // LM Studio
+        &model["meta"]["n_ctx_train"], // llama.cpp server
+    ]
+    .into_iter()
+    .find_map(|v| v.as_u64())
+    .and_then(|n| u32::try_from(n).ok());
+
+    let listed = |list: &Value, wanted: &str| list.as_array().map(|items| items.iter().any(|v| v == wanted));
+    let vision = listed(&model["capabilities"], "vision")
+        .or_else(|| listed(&model["architecture"]["input_modalities"], "image"))
+        .or_else(|| model["type"].as_str().map(|t| t == "vlm"));
+
+    ModelCapabilities { context_window, vision }
+}
+
+/// Models the provider offers, fetched live; fails when the key is rejected
+pub async fn fetch_models(kind: ProviderKind, base_url: Option<&str>, api_key: Option<&str>) -> Result<Vec<String>, String> {
+    let json = model_list(kind, base_url, api_key).await.map_err(ListError::into_message)?;
+    Ok(model_names(kind, &json))
+}
+
+/// Check that an OpenAI-compatible server answers and read what it says
+/// about its models
+pub async fn probe_endpoint(base_url: Option<&str>, api_key: Option<&str>) -> EndpointProbe {
+    let kind = ProviderKind::OpenAICompatible;
+    let started = Instant::now();
+    let result = model_list(kind, base_url, api_key).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(json) => {
+            let capabilities = json["data"]
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|m| Some((m["id"].as_str()?.to_string(), model_capabilities(m))))
+                        .filter(|(_, c)| *c != ModelCapabilities::default())
+                        .collect()
+                })
+                .unwrap_or_default();
+            EndpointProbe { reachable: true, latency_ms, models: model_names(kind, &json), capabilities, error: None }
+        }
+        Err(e) => EndpointProbe {
+            reachable: matches!(e, ListError::Failed(_)),
+            latency_ms,
+            models: Vec::new(),
+            capabilities: BTreeMap::new(),
+            error: Some(e.into_message()),
+        },
+    }
+}
+
+/// The key to use for an input: the new one, else the one stored for the profile
+fn resolve_key(credentials: &Mutex<CredentialManager>, input: &ProviderProfileInput) -> Result<Option<String>, String> {
+    if input.api_key.is_some() {
+        return Ok(input.api_key.clone());
+    }
+    let Some(id) = &input.id else { return Ok(None) };
+    let credentials = credentials.lock().map_err(|e| e.to_string())?;
+    Ok(credentials.get_password(&credential_key(id)).ok())
+}
+
+fn with_key_status(credentials: &CredentialManager, mut profile: ProviderProfile) -> ProviderProfile {
+    profile.has_api_key = credentials.has_password(&credential_key(&profile.id));
+    profile
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_provider_profiles(
+    db: tauri::State<'_, DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+) -> Result<Vec<ProviderProfile>, AppError> {
+    let profiles = {
+        let conn = db.read()?;
+        list(&conn)?
+    };
+    let credentials = credentials.lock()?;
+    Ok(profiles.into_iter().map(|p| with_key_status(&credentials, p)).collect())
+}
+
+/// Create or update a profile; a new API key is checked with the provider first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn upsert_provider_profile(
+    db: tauri::State<'_, DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    profile: ProviderProfileInput,
+) -> Result<ProviderProfile, AppError> {
+    let mut input = {
+        let conn = db.conn.lock()?;
+        if let Some(id) = &profile.id {
+            get(&conn, id)?;
+        }
+        validate(&conn, profile)?
+    };
+
+    let api_key = resolve_key(&credentials, &input)?;
+    if input.kind.requires_api_key() && api_key.is_none() {
+        return Err(AppError::invalid_input(format!("An API key is required for {}", input.kind.as_str())));
+    }
+    let mut capabilities = None;
+    if input.kind == ProviderKind::OpenAICompatible {
+        // A server that is down is saved anyway; one that answers must accept the key
+        let probe = probe_endpoint(input.base_url.as_deref(), api_key.as_deref()).await;
+        match probe.error {
+            Some(e) if probe.reachable => return Err(AppError::invalid_input(e)),
+            Some(e) => tracing::warn!("Saving provider profile {} without probing: {}", input.name, e),
+            None => {
+                if input.models.is_empty() {
+                    input.models = probe.models;
+                }
+                capabilities = Some(probe.capabilities);
+            }
+        }
+    } else if input.api_key.is_some() {
+        let models = fetch_models(input.kind, input.base_url.as_deref(), api_key.as_deref()).await?;
+        if input.models.is_empty() {
+            input.models = models;
+        }
+    }
+
+    let id = input.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let saved = {
+        let conn = db.conn.lock()?;
+        save(&conn, &id, &input, capabilities.as_ref())?
+    };
+    let credentials = credentials.lock()?;
+    if let Some(key) = &input.api_key {
+        credentials
+            .set_password(&credential_key(&id), key)?;
+    }
+    Ok(with_key_status(&credentials, saved))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_provider_profile(
+    db: tauri::State<'_, DbState>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    id: String,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    delete(&conn, &id)?;
+    let credentials = credentials.lock()?;
+    let _ = credentials.delete_password(&credential_key(&id));
+    Ok(())
+}
+
+/// Live model list for a profile being edited, using its stored key when
+/// no new one is given
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn fetch_provider_models(
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    profile: ProviderProfileInput,
+) -> Result<Vec<String>, AppError> {
+    let input = ProviderProfileInput {
+        base_url: clean(profile.base_url),
+        api_key: clean(profile.api_key),
+        ..profile
+    };
+    let api_key = resolve_key(&credentials, &input)?;
+    Ok(fetch_models(input.kind, input.base_url.as_deref(), api_key.as_deref()).await?)
+}
+
+/// Health check of an OpenAI-compatible endpoint being edited: whether it
+/// answers, how fast, and the models and capabilities it reports
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn probe_provider_endpoint(
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    profile: ProviderProfileInput,
+) -> Result<EndpointProbe, AppError> {
+    let input = ProviderProfileInput {
+        base_url: clean(profile.base_url),
+        api_key: clean(profile.api_key),
+        ..profile
+    };
+    if input.base_url.is_none() {
+        return Err(AppError::invalid_input("A base URL is required to probe an endpoint"));
+    }
+    let api_key = resolve_key(&credentials, &input)?;
+    Ok(probe_endpoint(input.base_url.as_deref(), api_key.as_deref()).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn input(name: &str, kind: ProviderKind) -> ProviderProfileInput {
+        serde_json::from_value(serde_json::json!({ "name": name, "kind": kind })).unwrap()
+    }
+
+    #[test]
+    fn test_validate_and_save() {
+        let conn = test_conn();
+        let mut claude = input("  Work   Claude ", ProviderKind::Anthropic);
+        claude.base_url = Some("https://proxy.example.com/".to_string());
+        claude.models = vec!["claude-sonnet-4".to_string(), " ".to_string()];
+        claude.default_model = Some("claude-sonnet-4".to_string());
+        claude.defaults.temperature = Some(0.3);
+
+        let claude = validate(&conn, claude).unwrap();
+        assert_eq!(claude.name, "Work Claude");
+        assert_eq!(claude.base_url.as_deref(), Some("https://proxy.example.com"));
+        assert_eq!(claude.models, vec!["claude-sonnet-4"]);
+        let saved = save(&conn, "p1", &claude, None).unwrap();
+        assert_eq!((saved.kind, saved.defaults.temperature), (ProviderKind::Anthropic, Some(0.3)));
+
+        // Updating keeps the id and creation time
+        let renamed = ProviderProfileInput { name: "Claude".to_string(), ..claude.clone() };
+        let updated = save(&conn, "p1", &validate(&conn, renamed).unwrap(), None).unwrap();
+        assert_eq!((updated.name.as_str(), updated.created_at.as_str()), ("Claude", saved.created_at.as_str()));
+
+        let invalid = |change: fn(&mut ProviderProfileInput)| {
+            let mut bad = input("Other", ProviderKind::OpenAI);
+            change(&mut bad);
+            let err = validate(&conn, bad).unwrap_err();
+            assert_eq!(err.kind(), crate::error::ErrorKind::InvalidInput);
+            err.to_string()
+        };
+        assert!(invalid(|p| p.name = "claude".to_string()).contains("already exists"));
+        assert!(invalid(|p| p.base_url = Some("ftp://x".to_string())).contains("http"));
+        assert!(invalid(|p| {
+            p.models = vec!["gpt-4o".to_string()];
+            p.default_model = Some("gpt-5".to_string());
+        })
+        .contains("not in the model list"));
+        assert!(invalid(|p| p.defaults.top_p = Some(1.5)).contains("Top P"));
+        let mut local = input("LM Studio", ProviderKind::OpenAICompatible);
+        assert!(validate(&conn, local.clone()).unwrap_err().to_string().contains("base URL is required"));
+        local.base_url = Some("http://localhost:1234/v1".to_string());
+        local.default_model = Some("qwen2.5-7b-instruct".to_string());
+        assert_eq!(validate(&conn, local).unwrap().default_model.as_deref(), Some("qwen2.5-7b-instruct"));
+
+        assert_eq!(list(&conn).unwrap().len(), 1);
+        delete(&conn, "p1").unwrap();
+        assert!(get(&conn, "p1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_models() {
+        use axum::{http::HeaderMap, http::StatusCode, routing::get as route, Json, Router};
+
+        let app = Router::new()
+            .route("/v1/models", route(|headers: HeaderMap| async move {
+                if headers.get("authorization").is_some_and(|v| v == "Bearer good") {
+                    Ok(Json(serde_json::json!({ "data": [{ "id": "gpt-4o" }, { "id": "gpt-4o-mini" }] })))
+                } else {
+                    Err(StatusCode::UNAUTHORIZED)
+                }
+            }))
+            .route("/v1beta/models", route(|| async {
+                Json(serde_json::json!({ "models": [
+                    { "name": "models/gemini-2.0-flash", "supportedGenerationMethods": ["generateContent"] },
+                    { "name": "models/text-embedding-004", "supportedGenerationMethods": ["embedContent"] },
+                ]}))
+            }))
+            .route("/api/tags", route(|| async { Json(serde_json::json!({ "models": [{ "name": "llama3:8b" }] })) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let openai = format!("{}/v1", base);
+        let models = fetch_models(ProviderKind::OpenAI, Some(&openai), Some("good")).await.unwrap();
+        assert_eq!(models, vec!["gpt-4o", "gpt-4o-mini"]);
+        let rejected = fetch_models(ProviderKind::OpenAI, Some(&openai), Some("bad")).await.unwrap_err();
+        assert!(rejected.contains("rejected"));
+
+        let gemini = fetch_models(ProviderKind::Gemini, Some(&base), Some("key")).await.unwrap();
+        assert_eq!(gemini, vec!["gemini-2.0-flash"]);
+        let ollama = fetch_models(ProviderKind::Ollama, Some(&base), None).await.unwrap();
+        assert_eq!(ollama, vec!["llama3:8b"]);
+    }
+
+    #[tokio::test]
+    async fn test_probe_endpoint() {
+        use axum::{routing::get as route, Json, Router};
+
+        let app = Router::new().route("/v1/models", route(|| async {
+            Json(serde_json::json!({ "object": "list", "data": [
+                { "id": "Qwen/Qwen2.5-7B-Instruct", "max_model_len": 32768 },
+                { "id": "qwen2-vl-7b", "type": "vlm", "max_context_length": 8192 },
+                { "id": "llama-3.2-3b", "meta": { "n_ctx_train": 131072 }, "capabilities": ["completion"] },
+                { "id": "plain" },
+            ]}))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/v1", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let probe = probe_endpoint(Some(&base), None).await;
+        assert!(probe.reachable && probe.error.is_none());
+        assert_eq!(probe.models.len(), 4);
+        let caps = |id: &str| probe.capabilities.get(id).cloned();
+        assert_eq!(caps("Qwen/Qwen2.5-7B-Instruct").unwrap().context_window, Some(32768));
+        assert_eq!(caps("qwen2-vl-7b"), Some(ModelCapabilities { context_window: Some(8192), vision: Some(true) }));
+        assert_eq!(caps("llama-3.2-3b"), Some(ModelCapabilities { context_window: Some(131072), vision: Some(false) }));
+        assert_eq!(caps("plain"), None);
+
+        let missing = probe_endpoint(Some(&format!("{}/nope", base)), None).await;
+        assert!(missing.reachable && missing.error.is_some());
+        let down = probe_endpoint(Some("http://127.0.0.1:1/v1"), None).await;
+        assert!(!down.reachable && down.error.unwrap().contains("Could not reach"));
+    }
+}
@@ -0,0 +1,366 @@
+// Quick ask - Global shortcut and the quick-capture window
+//
+// A user-configurable global hotkey toggles a small always-on-top window for
+// one-off questions. Prompts asked there are sent to the agent runtime in a
+// dedicated "Quick Ask" conversation that only replays its last few messages,
+// keeping requests light and the main conversation list uncluttered. If the
+// user marks that conversation sensitive, its messages are encrypted like any
+// other and quick ask refuses to run while it is locked.
+
+use crate::db::{settings, DbState};
+use crate::error::AppError;
+use crate::security::field_encryption::{decrypt_field, encrypt_field};
+use crate::security::{CredentialManager, SensitiveSession};
+use crate::sidecar::SidecarState;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Setting holding the hotkey
+const SHORTCUT_KEY: &str = "quick_ask_shortcut";
+
+/// Hotkey used until the user picks another
+pub const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+
+/// Label of the quick-capture window
+pub const WINDOW_LABEL: &str = "quick-ask";
+
+/// Emitted to the quick-capture window each time it is shown
+pub const OPENED_EVENT: &str = "quick-ask://opened";
+
+/// Conversation quick ask prompts are stored in
+pub const CONVERSATION_ID: &str = "quick-ask";
+const CONVERSATION_TITLE: &str = "Quick Ask";
+
+/// Earlier messages sent along with a prompt
+const HISTORY_MESSAGES: usize = 10;
+
+/// Reply to a quick ask prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAskReply {
+    pub conversation_id: String,
+    pub content: String,
+}
+
+/// Check a hotkey such as `Alt+Space`; surrounding whitespace is ignored
+pub fn normalize_shortcut(shortcut: &str) -> Result<String, String> {
+    let shortcut = shortcut.trim();
+    if shortcut.is_empty() {
+        return Err("Shortcut is empty".to_string());
+    }
+    Shortcut::from_str(shortcut).map_err(|e| format!("Invalid shortcut '{}': {}", shortcut, e))?;
+    Ok(shortcut.to_string())
+}
+
+/// Configured hotkey, or the default
+pub fn shortcut(conn: &Connection) -> Result<String, String> {
+    Ok(settings::get(conn, SHORTCUT_KEY)?.unwrap_or_else(|| DEFAULT_SHORTCUT.to_string()))
+}
+
+/// Show the quick-capture window, creating it on first use
+pub fn open_window(app: &AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => window,
+        None => WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("index.html?window=quick-ask".into()))
+            .title(CONVERSATION_TITLE)
+            .inner_size(640.0, 420.0)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .center()
+            .build()
+            .map_err(|e| format!("Failed to create quick ask window: {}", e))?,
+    };
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    window.emit(OPENED_EVENT, ()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Hide the quick-capture window; it is kept so reopening is instant
+pub fn hide_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.hide().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn toggle_window(app: &AppHandle) {
+    let visible = app
+        .get_webview_window(WINDOW_LABEL)
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    let result = if visible { hide_window(app) } else { open_window(app) };
+    if let Err(e) = result {
+        tracing::warn!("Quick ask window: {}", e);
+    }
+}
+
+/// Bind `shortcut` to toggling the quick-capture window
+fn bind(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                toggle_window(app);
+            }
+        })
+        .map_err(|e| format!("Failed to register shortcut '{}': {}", shortcut, e))
+}
+
+/// Register the configured hotkey at startup
+pub fn register(app: &AppHandle) -> Result<(), String> {
+    let shortcut = {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        shortcut(&conn)?
+    };
+    bind(app, &shortcut)
+}
+
+/// Bind the hotkey of the current database in place of `previous`, after a
+/// profile switch
+pub fn rebind(app: &AppHandle, previous: &str) -> Result<(), String> {
+    let shortcut = {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        shortcut(&conn)?
+    };
+    if shortcut == previous {
+        return Ok(());
+    }
+    if let Err(e) = app.global_shortcut().unregister(previous) {
+        tracing::warn!("Failed to unregister shortcut '{}': {}", previous, e);
+    }
+    bind(app, &shortcut)
+}
+
+/// Create the quick ask conversation if it does not exist yet
+fn ensure_conversation(conn: &Connection) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT OR IGNORE INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+        [CONVERSATION_ID, CONVERSATION_TITLE, &now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Store a message in the quick ask conversation, encrypting it with `key`
+fn append_message(conn: &Connection, key: Option<&[u8; 32]>, role: &str, content: &str) -> Result<(), String> {
+    ensure_conversation(conn)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let content = match key {
+        Some(key) => encrypt_field(content, key).map_err(|e| e.to_string())?,
+        None => content.to_string(),
+    };
+    conn.execute(
+        "INSERT INTO messages (id, conversation_id, role, content, created_at, is_encrypted)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![uuid::Uuid::new_v4().to_string(), CONVERSATION_ID, role, content, now, key.is_some()],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("UPDATE conversations SET updated_at = ?1 WHERE id = ?2", [&now, CONVERSATION_ID])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The last `limit` messages of the quick ask conversation, oldest first
+///
+/// Encrypted messages are decrypted with `key`; without one they are an
+/// error rather than being sent to the model as ciphertext.
+fn recent_messages(conn: &Connection, key: Option<&[u8; 32]>, limit: usize) -> Result<Vec<crate::Message>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT role, content, is_encrypted FROM messages WHERE conversation_id = ?1
+             ORDER BY created_at DESC, rowid DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![CONVERSATION_ID, limit as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, bool>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut messages = Vec::with_capacity(rows.len());
+    for (role, content, encrypted) in rows.into_iter().rev() {
+        let content = match (encrypted, key) {
+            (false, _) => content,
+            (true, Some(key)) => decrypt_field(&content, key).map_err(|e| e.to_string())?,
+            (true, None) => return Err("Encrypted message in non-sensitive conversation".to_string()),
+        };
+        messages.push(crate::Message { role, content });
+    }
+    Ok(messages)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Hotkey that opens the quick ask window
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_quick_ask_shortcut(db: tauri::State<'_, DbState>) -> Result<String, AppError> {
+    let conn = db.read()?;
+    Ok(shortcut(&conn)?)
+}
+
+/// Change the quick ask hotkey; the old one stays bound if the new one
+/// cannot be registered
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_quick_ask_shortcut(
+    app: AppHandle,
+    db: tauri::State<'_, DbState>,
+    shortcut: String,
+) -> Result<String, AppError> {
+    let shortcut = normalize_shortcut(&shortcut)?;
+    let conn = db.conn.lock()?;
+    let previous = self::shortcut(&conn)?;
+    if previous == shortcut {
+        return Ok(shortcut);
+    }
+
+    let global = app.global_shortcut();
+    if let Err(e) = global.unregister(previous.as_str()) {
+        tracing::warn!("Failed to unregister shortcut '{}': {}", previous, e);
+    }
+    if let Err(e) = bind(&app, &shortcut) {
+        if let Err(restore) = bind(&app, &previous) {
+            tracing::warn!("{}", restore);
+        }
+        return Err(e.into());
+    }
+
+    settings::set(&conn, SHORTCUT_KEY, &shortcut)?;
+    Ok(shortcut)
+}
+
+/// Show the quick ask window
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn open_quick_ask(app: AppHandle) -> Result<(), AppError> {
+    Ok(open_window(&app)?)
+}
+
+/// Hide the quick ask window
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn hide_quick_ask(app: AppHandle) -> Result<(), AppError> {
+    Ok(hide_window(&app)?)
+}
+
+/// Send a prompt from the quick ask window to the agent runtime
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn quick_ask(
+    state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, DbState>,
+    session: tauri::State<'_, Mutex<SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    prompt: String,
+    provider: Option<String>,
+) -> Result<QuickAskReply, AppError> {
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err(AppError::invalid_input("Prompt is empty"));
+    }
+
+    // Fails while the conversation is sensitive and locked
+    let (key, messages) = {
+        let conn = db.conn.lock()?;
+        let key = crate::db::conversation_key(&conn, &session, &credentials, CONVERSATION_ID)?;
+        append_message(&conn, key.as_ref(), "user", &prompt)?;
+        (key, recent_messages(&conn, key.as_ref(), HISTORY_MESSAGES)?)
+    };
+
+    let response = crate::sidecar::agent_chat(state, db, messages, provider, Some(CONVERSATION_ID.to_string()), None).await?;
+    if let Some(error) = response.error {
+        return Err(AppError::sidecar(error));
+    }
+
+    let conn = db.conn.lock()?;
+    append_message(&conn, key.as_ref(), "assistant", &response.content)?;
+    Ok(QuickAskReply { conversation_id: CONVERSATION_ID.to_string(), content: response.content })
+}
+
+/// Start the quick ask conversation over
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn clear_quick_ask(db: tauri::State<'_, DbState>) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "DELETE FROM messages WHERE conversation_id = ?1",
+        [CONVERSATION_ID],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_shortcut_setting() {
+        let conn = test_conn();
+        assert_eq!(shortcut(&conn).unwrap(), DEFAULT_SHORTCUT);
+        settings::set(&conn, SHORTCUT_KEY, "Alt+Space").unwrap();
+        assert_eq!(shortcut(&conn).unwrap(), "Alt+Space");
+
+        assert_eq!(normalize_shortcut("  Alt+Space ").unwrap(), "Alt+Space");
+        assert!(normalize_shortcut("").is_err());
+        assert!(normalize_shortcut("Shift+").is_err());
+    }
+
+    #[test]
+    fn test_recent_messages_keeps_last_turns() {
+        let conn = test_conn();
+        for i in 0..(HISTORY_MESSAGES + 2) {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            append_message(&conn, None, role, &format!("message {}", i)).unwrap();
+        }
+
+        let messages = recent_messages(&conn, None, HISTORY_MESSAGES).unwrap();
+        assert_eq!(messages.len(), HISTORY_MESSAGES);
+        assert_eq!(messages[0].content, "message 2");
+        assert_eq!(messages.last().unwrap().content, format!("message {}", HISTORY_MESSAGES + 1));
+
+        let title: String = conn
+            .query_row("SELECT title FROM conversations WHERE id = ?1", [CONVERSATION_ID], |row| row.get(0))
+            .unwrap();
+        assert_eq!(title, CONVERSATION_TITLE);
+    }
+
+    #[test]
+    fn test_sensitive_history_needs_unlocked_key() {
+        let conn = test_conn();
+        ensure_conversation(&conn).unwrap();
+        conn.execute("UPDATE conversations SET is_sensitive = 1 WHERE id = ?1", [CONVERSATION_ID]).unwrap();
+        let key = crate::security::encryption::derive_key("quick_ask", b"salt");
+        let session = Mutex::new(SensitiveSession::new());
+        let credentials = Mutex::new(CredentialManager::new("quick_ask_test".to_string()));
+
+        // Locked: nothing is read or stored
+        let error = crate::db::conversation_key(&conn, &session, &credentials, CONVERSATION_ID).unwrap_err();
+        assert_eq!(error.kind(), crate::error::ErrorKind::PermissionDenied);
+
+        append_message(&conn, Some(&key), "user", "secret question").unwrap();
+        let stored: String = conn
+            .query_row("SELECT content FROM messages WHERE conversation_id = ?1", [CONVERSATION_ID], |row| row.get(0))
+            .unwrap();
+        assert!(!stored.contains("secret"));
+
+        // Unlocked: the model sees plaintext, never ciphertext
+        let messages = recent_messages(&conn, Some(&key), HISTORY_MESSAGES).unwrap();
+        assert_eq!(messages[0].content, "secret question");
+        assert!(recent_messages(&conn, None, HISTORY_MESSAGES).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+// Startup recovery - Repair state left inconsistent by a crash
+
+use crate::error::AppError;
+use crate::marketplace::MarketplaceInstaller;
+use crate::plugins::PluginExecutor;
+use crate::scheduler::JobScheduler;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Error recorded on records interrupted by an unclean shutdown
+const INTERRUPTED_ERROR: &str = "Interrupted by application restart";
+
+/// Summary of what the recovery phase repaired
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    pub failed_job_executions: usize,
+    pub failed_recipe_executions: usize,
+    pub failed_workflow_executions: usize,
+    pub reset_sub_agents: usize,
+    pub recovered_plugins: Vec<String>,
+    pub rolled_back_installs: Vec<String>,
+    pub errors: Vec<String>,
+    /// Set when another instance leads the database and recovery was skipped
+    #[serde(default)]
+    pub skipped: bool,
+    pub completed_at: String,
+}
+
+impl RecoveryReport {
+    /// Whether anything needed repairing
+    pub fn is_clean(&self) -> bool {
+        self.failed_job_executions == 0
+            && self.failed_recipe_executions == 0
+            && self.failed_workflow_executions == 0
+            && self.reset_sub_agents == 0
+            && self.recovered_plugins.is_empty()
+            && self.rolled_back_installs.is_empty()
+            && self.errors.is_empty()
+    }
+}
+
+/// Mark executions and sub-agents still `running` from a previous session as failed
+pub fn recover_database(conn: &Connection, report: &mut RecoveryReport) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    report.failed_job_executions = conn
+        .execute(
+            "UPDATE job_executions SET status = 'failed', error = ?1, completed_at = ?2
+             WHERE status = 'running'",
+            [INTERRUPTED_ERROR, &now],
+        )
+        .map_err(|e| format!("Failed to recover job executions: {}", e))?;
+
+    report.failed_recipe_executions = conn
+        .execute(
+            "UPDATE recipe_executions SET status = 'failed', error = ?1, completed_at = ?2
+             WHERE status = 'running'",
+            [INTERRUPTED_ERROR, &now],
+        )
+        .map_err(|e| format!("Failed to recover recipe executions: {}", e))?;
+
+    report.failed_workflow_executions = conn
+        .execute(
+            "UPDATE workflow_executions SET status = 'failed', error = ?1, completed_at = ?2
+             WHERE status IN ('pending', 'running')",
+            [INTERRUPTED_ERROR, &now],
+        )
+        .map_err(|e| format!("Failed to recover workflow executions: {}", e))?;
+
+    report.reset_sub_agents = conn
+        .execute(
+            "UPDATE sub_agents SET status = 'idle', error = ?1, completed_at = ?2
+             WHERE status = 'running'",
+            [INTERRUPTED_ERROR, &now],
+        )
+        .map_err(|e| format!("Failed to recover sub-agents: {}", e))?;
+
+    Ok(())
+}
+
+/// Run the full recovery phase
+///
+/// Each step is independent; failures are collected in the report rather
+/// than aborting startup.
+pub fn run_recovery(
+    conn: &Connection,
+    plugins_dir: &Path,
+    marketplace_dir: Option<PathBuf>,
+) -> RecoveryReport {
+    let mut report = RecoveryReport::default();
+
+    if let Err(e) = recover_database(conn, &mut report) {
+        report.errors.push(e);
+    }
+
+    report.recovered_plugins = PluginExecutor::recover_stale_instances(plugins_dir);
+
+    if let Some(dir) = marketplace_dir.filter(|d| d.exists()) {
+        match MarketplaceInstaller::new(dir).and_then(|mut i| i.recover_incomplete()) {
+            Ok(rolled_back) => report.rolled_back_installs = rolled_back,
+            Err(e) => report.errors.push(e),
+        }
+    }
+
+    report.completed_at = chrono::Utc::now().to_rfc3339();
+
+    if report.is_clean() {
+        tracing::info!("Startup recovery completed, nothing to repair");
+    } else {
+        tracing::warn!("Startup recovery repaired inconsistent state: {:?}", report);
+    }
+
+    report
+}
+
+/// Run the recovery phase only if this instance leads `scheduler`'s database
+///
+/// A follower, such as a second window, shares the leader's database and
+/// plugin folders, so what looks interrupted there is the leader's live
+/// work: running executions, `.running` markers and in-progress installs.
+pub fn run_recovery_as_leader(
+    scheduler: &JobScheduler,
+    conn: &Connection,
+    plugins_dir: &Path,
+    marketplace_dir: Option<PathBuf>,
+) -> RecoveryReport {
+    if !scheduler.try_lead() {
+        tracing::info!("Another instance leads this database; skipping startup recovery");
+        return RecoveryReport {
+            skipped: true,
+            completed_at: chrono::Utc::now().to_rfc3339(),
+            ..Default::default()
+        };
+    }
+    run_recovery(conn, plugins_dir, marketplace_dir)
+}
+
+/// Get the report from the startup recovery phase
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_recovery_report(
+    report: tauri::State<'_, Mutex<RecoveryReport>>,
+) -> Result<RecoveryReport, AppError> {
+    let report = report.lock()?;
+    Ok(report.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_recover_database() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO cron_jobs (id, name, schedule, job_type, config) VALUES ('job-1', 'Job', '* * * * *', 'system', '{}');
+             INSERT INTO job_executions (id, job_id, status) VALUES ('exec-1', 'job-1', 'running');
+             INSERT INTO job_executions (id, job_id, status) VALUES ('exec-2', 'job-1', 'completed');
+             INSERT INTO sub_agents (id, name, role, status) VALUES ('agent-1', 'Agent', 'worker', 'running');",
+        )
+        .unwrap();
+
+        let mut report = RecoveryReport::default();
+        recover_database(&conn, &mut report).unwrap();
+        assert_eq!(report.failed_job_executions, 1);
+        assert_eq!(report.reset_sub_agents, 1);
+
+        let status: String = conn
+            .query_row("SELECT status FROM job_executions WHERE id = 'exec-1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(status, "failed");
+
+        let status: String = conn
+            .query_row("SELECT status FROM job_executions WHERE id = 'exec-2'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(status, "completed");
+    }
+
+    #[test]
+    fn test_run_recovery_clean() {
+        let conn = test_conn();
+        let plugins_dir = tempfile::tempdir().unwrap();
+
+        let report = run_recovery(&conn, plugins_dir.path(), None);
+        assert!(report.is_clean());
+        assert!(!report.completed_at.is_empty());
+    }
+
+    #[test]
+    fn test_follower_leaves_leader_state_alone() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO cron_jobs (id, name, schedule, job_type, config) VALUES ('job-1', 'Job', '* * * * *', 'system', '{}');
+             INSERT INTO job_executions (id, job_id, status) VALUES ('exec-1', 'job-1', 'running');
+             INSERT INTO sub_agents (id, name, role, status) VALUES ('agent-1', 'Agent', 'worker', 'running');",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let plugins_dir = dir.path().join("plugins");
+        std::fs::create_dir_all(plugins_dir.join("demo")).unwrap();
+        std::fs::write(plugins_dir.join("demo/.running"), "").unwrap();
+
+        let config = || crate::scheduler::SchedulerConfig {
+            db_path: dir.path().join("app.db").to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let leader = JobScheduler::new(config());
+        let follower = JobScheduler::new(config());
+        assert!(leader.try_lead());
+
+        let report = run_recovery_as_leader(&follower, &conn, &plugins_dir, None);
+        assert!(report.skipped);
+        assert!(report.is_clean());
+        let status = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, String>(0)).unwrap();
+        assert_eq!(status("SELECT status FROM job_executions WHERE id = 'exec-1'"), "running");
+        assert_eq!(status("SELECT status FROM sub_agents WHERE id = 'agent-1'"), "running");
+        assert!(plugins_dir.join("demo/.running").exists());
+
+        let report = run_recovery_as_leader(&leader, &conn, &plugins_dir, None);
+        assert!(!report.skipped);
+        assert_eq!(report.failed_job_executions, 1);
+        assert_eq!(status("SELECT status FROM job_executions WHERE id = 'exec-1'"), "failed");
+    }
+}
@@ -0,0 +1,340 @@
+//! Data retention policies
+//!
+//! Each kind of stored history has a retention rule: records older than its
+//! `max_age_days` are deleted, and a rule without an age keeps everything.
+//! The rules are kept as JSON in `app_settings` and applied daily by the
+//! `apply_retention` system job. `preview_retention_impact` counts what a set
+//! of rules would delete without deleting anything.
+//!
+//! Pinned messages are never deleted, and running executions are left alone.
+//! Chat attachments are read from the user's files in place rather than
+//! copied into app storage, so there is nothing of theirs to expire.
+
+use crate::db::{settings, DbState};
+use chrono::{DateTime, Duration, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the rules
+const SETTINGS_KEY: &str = "retention_policies";
+
+/// Kind of stored history a rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionEntity {
+    /// Chat messages, except pinned ones
+    Messages,
+    JobExecutions,
+    RecipeExecutions,
+    /// Job execution logs and runs skipped while a job was snoozed
+    Logs,
+}
+
+impl RetentionEntity {
+    pub const ALL: [RetentionEntity; 4] = [
+        Self::Messages,
+        Self::JobExecutions,
+        Self::RecipeExecutions,
+        Self::Logs,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Messages => "messages",
+            Self::JobExecutions => "job executions",
+            Self::RecipeExecutions => "recipe executions",
+            Self::Logs => "log entries",
+        }
+    }
+
+    /// Age limit used until the user sets one
+    fn default_max_age_days(self) -> Option<u32> {
+        match self {
+            Self::Logs => Some(30),
+            _ => None,
+        }
+    }
+
+    /// `(table, timestamp column, extra condition)` of each table holding
+    /// the entity's records
+    fn tables(self) -> &'static [(&'static str, &'static str, &'static str)] {
+        match self {
+            Self::Messages => &[("messages", "created_at", "id NOT IN (SELECT message_id FROM pinned_messages)")],
+            Self::JobExecutions => &[("job_executions", "started_at", "status != 'running'")],
+            Self::RecipeExecutions => &[("recipe_executions", "started_at", "status != 'running'")],
+            Self::Logs => &[
+                ("job_execution_logs", "created_at", "1"),
+                ("job_skipped_runs", "created_at", "1"),
+            ],
+        }
+    }
+}
+
+/// Retention rule of one entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub entity: RetentionEntity,
+    /// Records older than this many days are deleted; None keeps them all
+    pub max_age_days: Option<u32>,
+}
+
+/// What a rule deletes, or would delete
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionImpact {
+    pub entity: RetentionEntity,
+    pub max_age_days: Option<u32>,
+    /// Records older than this are deleted
+    pub cutoff: Option<String>,
+    pub count: usize,
+    /// Timestamp of the oldest affected record
+    pub oldest: Option<String>,
+}
+
+/// Stored rules, one per entity, with defaults for entities never set
+pub fn load(conn: &Connection) -> Result<Vec<RetentionPolicy>, String> {
+    let stored: Vec<RetentionPolicy> = match settings::get(conn, SETTINGS_KEY)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid retention policies: {}", e))?,
+        None => Vec::new(),
+    };
+    Ok(RetentionEntity::ALL
+        .iter()
+        .map(|&entity| {
+            stored.iter().find(|p| p.entity == entity).cloned().unwrap_or(RetentionPolicy {
+                entity,
+                max_age_days: entity.default_max_age_days(),
+            })
+        })
+        .collect())
+}
+
+/// Replace the rules of the given entities
+pub fn save(conn: &Connection, policies: &[RetentionPolicy]) -> Result<Vec<RetentionPolicy>, String> {
+    if policies.iter().any(|p| p.max_age_days == Some(0)) {
+        return Err("Retention must keep at least one day".to_string());
+    }
+    let merged: Vec<RetentionPolicy> = load(conn)?
+        .into_iter()
+        .map(|current| policies.iter().rfind(|p| p.entity == current.entity).cloned().unwrap_or(current))
+        .collect();
+    let json = serde_json::to_string(&merged).map_err(|e| e.to_string())?;
+    settings::set(conn, SETTINGS_KEY, &json)?;
+    Ok(merged)
+}
+
+fn cutoff(policy: &RetentionPolicy, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    policy.max_age_days.map(|days| now - Duration::days(days as i64))
+}
+
+/// Count what `policies` would delete at `now`
+pub fn preview(conn: &Connection, policies: &[RetentionPolicy], now: DateTime<Utc>) -> Result<Vec<RetentionImpact>, String> {
+    policies
+        .iter()
+        .map(|policy| {
+            let cutoff = cutoff(policy, now);
+            let mut count = 0;
+            let mut oldest: Option<String> = None;
+            if let Some(cutoff) = cutoff {
+                for (table, column, condition) in policy.entity.tables() {
+                    let (n, first): (usize, Option<String>) = conn
+                        .query_row(
+                            &format!(
+                                "SELECT COUNT(*), MIN({column}) FROM {table}
+                                 WHERE julianday({column}) < julianday(?1) AND {condition}"
+                            ),
+                            [cutoff.to_rfc3339()],
+                            |row| Ok((row.get(0)?, row.get(1)?)),
+                        )
+                        .map_err(|e| format!("Failed to count {}: {}", table, e))?;
+                    count += n;
+                    oldest = match (oldest, first) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                }
+            }
+            Ok(RetentionImpact {
+                entity: policy.entity,
+                max_age_days: policy.max_age_days,
+                cutoff: cutoff.map(|c| c.to_rfc3339()),
+                count,
+                oldest,
+            })
+        })
+        .collect()
+}
+
+/// Delete what one rule expires at `now`; returns the number deleted
+pub fn apply_policy(conn: &Connection, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<usize, String> {
+    let Some(cutoff) = cutoff(policy, now) else {
+        return Ok(0);
+    };
+    let mut deleted = 0;
+    for (table, column, condition) in policy.entity.tables() {
+        deleted += conn
+            .execute(
+                &format!("DELETE FROM {table} WHERE julianday({column}) < julianday(?1) AND {condition}"),
+                params![cutoff.to_rfc3339()],
+            )
+            .map_err(|e| format!("Failed to delete from {}: {}", table, e))?;
+    }
+
+    // Records that pointed at the deleted ones
+    match policy.entity {
+        RetentionEntity::Messages => {
+            conn.execute(
+                "DELETE FROM conversation_bookmarks WHERE message_id NOT IN (SELECT id FROM messages)",
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        RetentionEntity::JobExecutions => {
+            crate::scheduler::logs::prune_orphans(conn)?;
+        }
+        RetentionEntity::RecipeExecutions | RetentionEntity::Logs => {}
+    }
+    Ok(deleted)
+}
+
+/// Apply every rule in one transaction
+pub fn apply(conn: &mut Connection, policies: &[RetentionPolicy], now: DateTime<Utc>) -> Result<Vec<RetentionImpact>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut impacts = preview(&tx, policies, now)?;
+    for (policy, impact) in policies.iter().zip(impacts.iter_mut()) {
+        impact.count = apply_policy(&tx, policy, now)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(impacts)
+}
+
+/// One-line summary of applied rules, for job output
+pub fn summary(impacts: &[RetentionImpact]) -> String {
+    let parts: Vec<String> = impacts
+        .iter()
+        .filter(|i| i.max_age_days.is_some())
+        .map(|i| format!("{} {}", i.count, i.entity.label()))
+        .collect();
+    if parts.is_empty() {
+        "No retention rules set".to_string()
+    } else {
+        format!("Deleted {}", parts.join(", "))
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_retention_policies(db: tauri::State<'_, DbState>) -> Result<Vec<RetentionPolicy>, AppError> {
+    let conn = db.read()?;
+    Ok(load(&conn)?)
+}
+
+/// Update the rules of the given entities, returning every rule
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_retention_policies(
+    db: tauri::State<'_, DbState>,
+    policies: Vec<RetentionPolicy>,
+) -> Result<Vec<RetentionPolicy>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(save(&conn, &policies)?)
+}
+
+/// What the stored rules, or `policies` when given, would delete now
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn preview_retention_impact(
+    db: tauri::State<'_, DbState>,
+    policies: Option<Vec<RetentionPolicy>>,
+) -> Result<Vec<RetentionImpact>, AppError> {
+    let conn = db.conn.lock()?;
+    let policies = match policies {
+        Some(policies) => policies,
+        None => load(&conn)?,
+    };
+    Ok(preview(&conn, &policies, Utc::now())?)
+}
+
+/// Apply the stored rules now
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn apply_retention_now(db: tauri::State<'_, DbState>) -> Result<Vec<RetentionImpact>, AppError> {
+    let mut conn = db.conn.lock()?;
+    let policies = load(&conn)?;
+    Ok(apply(&mut conn, &policies, Utc::now())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES ('c', 'Chat');
+             INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES
+                 ('old', 'c', 'user', 'old', '2026-01-01 09:00:00'),
+                 ('pinned', 'c', 'user', 'pinned', '2026-01-02T09:00:00+00:00'),
+                 ('new', 'c', 'user', 'new', '2026-03-30T09:00:00.123456789+00:00');
+             INSERT INTO pinned_messages (message_id, conversation_id) VALUES ('pinned', 'c');
+             INSERT INTO conversation_bookmarks (id, conversation_id, message_id, label) VALUES ('b', 'c', 'old', 'Start');
+             INSERT INTO job_execution_logs (execution_id, seq, level, message, created_at) VALUES
+                 ('exec', 1, 'info', 'Started', '2026-02-01T00:00:00+00:00');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-04-01T00:00:00+00:00").unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_load_and_save_policies() {
+        let conn = setup();
+        let defaults = load(&conn).unwrap();
+        assert_eq!(defaults.len(), RetentionEntity::ALL.len());
+        assert_eq!(defaults[3], RetentionPolicy { entity: RetentionEntity::Logs, max_age_days: Some(30) });
+
+        let saved = save(&conn, &[RetentionPolicy { entity: RetentionEntity::Messages, max_age_days: Some(7) }]).unwrap();
+        assert_eq!(saved[0].max_age_days, Some(7));
+        assert_eq!(load(&conn).unwrap(), saved);
+        assert!(save(&conn, &[RetentionPolicy { entity: RetentionEntity::Logs, max_age_days: Some(0) }]).is_err());
+    }
+
+    #[test]
+    fn test_preview_matches_apply() {
+        let mut conn = setup();
+        let policies = vec![
+            RetentionPolicy { entity: RetentionEntity::Messages, max_age_days: Some(30) },
+            RetentionPolicy { entity: RetentionEntity::JobExecutions, max_age_days: None },
+            RetentionPolicy { entity: RetentionEntity::Logs, max_age_days: Some(30) },
+        ];
+
+        let preview = preview(&conn, &policies, now()).unwrap();
+        assert_eq!(preview.iter().map(|i| i.count).collect::<Vec<_>>(), [1, 0, 1]);
+        assert_eq!(preview[0].oldest.as_deref(), Some("2026-01-01 09:00:00"));
+        assert_eq!(preview[1].cutoff, None);
+
+        let applied = apply(&mut conn, &policies, now()).unwrap();
+        assert_eq!(applied, preview);
+        assert_eq!(summary(&applied), "Deleted 1 messages, 1 log entries");
+
+        let remaining: Vec<String> = conn
+            .prepare("SELECT id FROM messages ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(remaining, ["new", "pinned"]);
+        let bookmarks: i64 = conn.query_row("SELECT COUNT(*) FROM conversation_bookmarks", [], |row| row.get(0)).unwrap();
+        assert_eq!(bookmarks, 0);
+    }
+}
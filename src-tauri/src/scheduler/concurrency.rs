@@ -0,0 +1,299 @@
+//! Concurrency controls for job executions
+//!
+//! `PriorityGate` caps how many executions run at once and hands each free
+//! slot to the highest-priority waiter, first come first served among equal
+//! priorities. `JobSlots` counts the executions of each job so a job's
+//! `allow_overlap` policy can skip or queue a run while the previous one is
+//! still going.
+
+use super::runner::OverlapPolicy;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Execution waiting for a slot
+struct Waiter {
+    priority: i32,
+    seq: u64,
+    wake: oneshot::Sender<GatePermit>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    /// Higher priority first, then earlier arrival
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+struct GateState {
+    limit: usize,
+    available: usize,
+    next_seq: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+/// Semaphore that wakes waiters in priority order
+#[derive(Clone)]
+pub struct PriorityGate {
+    state: Arc<Mutex<GateState>>,
+}
+
+impl PriorityGate {
+    /// Gate allowing `limit` executions at once (at least one)
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            state: Arc::new(Mutex::new(GateState {
+                limit,
+                available: limit,
+                next_seq: 0,
+                waiting: BinaryHeap::new(),
+            })),
+        }
+    }
+
+    /// Wait for a slot; the slot is freed when the permit is dropped
+    pub async fn acquire(&self, priority: i32) -> GatePermit {
+        let wake = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 && state.waiting.is_empty() {
+                state.available -= 1;
+                return GatePermit { gate: Some(self.clone()) };
+            }
+            let (tx, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter { priority, seq, wake: tx });
+            rx
+        };
+        // Waiters are only removed to be sent a permit, and this gate keeps
+        // the sender alive until then
+        wake.await.expect("priority gate dropped a waiter")
+    }
+
+    /// Hand a freed slot to the next waiter still listening
+    fn release(&self) {
+        loop {
+            let waiter = {
+                let mut state = self.state.lock().unwrap();
+                match state.waiting.pop() {
+                    Some(waiter) => waiter,
+                    None => {
+                        state.available += 1;
+                        return;
+                    }
+                }
+            };
+            match waiter.wake.send(GatePermit { gate: Some(self.clone()) }) {
+                Ok(()) => return,
+                // The waiting execution was cancelled
+                Err(mut permit) => permit.gate = None,
+            }
+        }
+    }
+
+    /// Executions waiting for a slot
+    pub fn waiting(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.waiting.iter().filter(|w| !w.wake.is_closed()).count()
+    }
+
+    /// Slots currently held
+    pub fn in_use(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.limit - state.available
+    }
+}
+
+/// Slot held by a running execution
+pub struct GatePermit {
+    gate: Option<PriorityGate>,
+}
+
+impl Drop for GatePermit {
+    fn drop(&mut self) {
+        if let Some(gate) = self.gate.take() {
+            gate.release();
+        }
+    }
+}
+
+#[derive(Default)]
+struct JobSlot {
+    active: usize,
+    queued: usize,
+    /// Held by the running execution of a `queue` job
+    turn: Arc<AsyncMutex<()>>,
+}
+
+/// Executions in flight per job
+#[derive(Clone, Default)]
+pub struct JobSlots {
+    slots: Arc<Mutex<HashMap<String, JobSlot>>>,
+}
+
+impl JobSlots {
+    /// Admit a new execution of a job under its overlap policy
+    ///
+    /// A `skip` job is refused while an execution is in flight; a `queue`
+    /// job waits for the previous execution, with at most one run queued.
+    pub fn admit(&self, job_id: &str, policy: OverlapPolicy) -> Result<ExecutionTicket, String> {
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots.entry(job_id.to_string()).or_default();
+        match policy {
+            OverlapPolicy::Skip if slot.active > 0 => {
+                return Err(format!("Job {} is still running", job_id));
+            }
+            OverlapPolicy::Queue if slot.queued > 0 => {
+                return Err(format!("Job {} already has a queued run", job_id));
+            }
+            _ => {}
+        }
+
+        let queued = policy == OverlapPolicy::Queue;
+        slot.active += 1;
+        if queued {
+            slot.queued += 1;
+        }
+        Ok(ExecutionTicket {
+            slots: self.clone(),
+            job_id: job_id.to_string(),
+            turn: queued.then(|| slot.turn.clone()),
+            queued,
+            turn_held: None,
+        })
+    }
+
+    /// Executions waiting for a previous run of the same job
+    pub fn queued(&self) -> usize {
+        self.slots.lock().unwrap().values().map(|s| s.queued).sum()
+    }
+
+    fn finish(&self, job_id: &str, queued: bool) {
+        let mut slots = self.slots.lock().unwrap();
+        if let Some(slot) = slots.get_mut(job_id) {
+            slot.active -= 1;
+            if queued {
+                slot.queued -= 1;
+            }
+            if slot.active == 0 {
+                slots.remove(job_id);
+            }
+        }
+    }
+}
+
+/// Admitted execution; counts as in flight until dropped
+pub struct ExecutionTicket {
+    slots: JobSlots,
+    job_id: String,
+    turn: Option<Arc<AsyncMutex<()>>>,
+    queued: bool,
+    turn_held: Option<OwnedMutexGuard<()>>,
+}
+
+impl ExecutionTicket {
+    /// Whether the execution waits for the job's previous run
+    pub fn is_queued(&self) -> bool {
+        self.queued
+    }
+
+    /// Wait until the previous execution of a `queue` job has finished
+    pub async fn wait_turn(&mut self) {
+        if let Some(turn) = self.turn.take() {
+            self.turn_held = Some(turn.lock_owned().await);
+        }
+        if self.queued {
+            self.queued = false;
+            let mut slots = self.slots.slots.lock().unwrap();
+            if let Some(slot) = slots.get_mut(&self.job_id) {
+                slot.queued -= 1;
+            }
+        }
+    }
+}
+
+impl Drop for ExecutionTicket {
+    fn drop(&mut self) {
+        self.slots.finish(&self.job_id, self.queued);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_gate_wakes_highest_priority_first() {
+        let gate = PriorityGate::new(1);
+        let held = gate.acquire(0).await;
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut tasks = Vec::new();
+        for priority in [1, 5, 3, 5] {
+            let waiter = gate.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = waiter.acquire(priority).await;
+                order.lock().unwrap().push(priority);
+            }));
+            while gate.waiting() < tasks.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+        assert_eq!(gate.in_use(), 1);
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![5, 5, 3, 1]);
+        assert_eq!((gate.in_use(), gate.waiting()), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_job_slots_apply_overlap_policy() {
+        let slots = JobSlots::default();
+
+        let running = slots.admit("skip", OverlapPolicy::Skip).unwrap();
+        assert!(slots.admit("skip", OverlapPolicy::Skip).is_err());
+        drop(running);
+        assert!(slots.admit("skip", OverlapPolicy::Skip).is_ok());
+
+        let _a = slots.admit("run", OverlapPolicy::Run).unwrap();
+        let _b = slots.admit("run", OverlapPolicy::Run).unwrap();
+
+        let mut first = slots.admit("queue", OverlapPolicy::Queue).unwrap();
+        first.wait_turn().await;
+        let mut second = slots.admit("queue", OverlapPolicy::Queue).unwrap();
+        assert_eq!(slots.queued(), 1);
+        assert!(slots.admit("queue", OverlapPolicy::Queue).is_err());
+
+        let waiting = tokio::spawn(async move {
+            second.wait_turn().await;
+            second
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        drop(first);
+        let _second = waiting.await.unwrap();
+        assert_eq!(slots.queued(), 0);
+    }
+}
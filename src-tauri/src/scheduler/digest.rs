@@ -0,0 +1,348 @@
+//! Activity digest jobs
+//!
+//! A `digest` job collects what happened over its period (conversations
+//! started, job executions and workflow runs), has the agent runtime
+//! summarize it, and saves the summary as a conversation tagged `digest`.
+//! The summary is the job's output, so the `send_email` and `notify_channel`
+//! params deliver it like the output of any other job.
+//!
+//! Params: `period` (`day`, the default, or `week`), `provider`, and
+//! `instructions` added to the summarization prompt.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::workflow::nodes::{runtime_result, AgentRuntime};
+
+/// Tag marking digest conversations; they are left out of later digests
+pub const DIGEST_TAG: &str = "digest";
+
+/// Period a digest covers, ending when it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestPeriod {
+    Day,
+    Week,
+}
+
+impl DigestPeriod {
+    pub fn from_params(params: &HashMap<String, serde_json::Value>) -> Result<Self, String> {
+        match params.get("period").and_then(|v| v.as_str()).unwrap_or("day") {
+            "day" | "daily" => Ok(Self::Day),
+            "week" | "weekly" => Ok(Self::Week),
+            other => Err(format!("Unknown digest period: {} (use day or week)", other)),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Day => "Daily digest",
+            Self::Week => "Weekly digest",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+}
+
+/// A conversation started during the period
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationActivity {
+    pub title: String,
+    pub messages: i64,
+}
+
+/// Executions of one job during the period
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobActivity {
+    pub name: String,
+    pub completed: i64,
+    pub failed: i64,
+    /// Error of the latest failed execution
+    pub last_error: Option<String>,
+}
+
+/// Everything a digest summarizes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Activity {
+    pub conversations: Vec<ConversationActivity>,
+    pub jobs: Vec<JobActivity>,
+    pub workflows: Vec<JobActivity>,
+}
+
+impl Activity {
+    pub fn is_empty(&self) -> bool {
+        self.conversations.is_empty() && self.jobs.is_empty() && self.workflows.is_empty()
+    }
+}
+
+/// Activity between `since` and `until`
+///
+/// Timestamps are compared through `datetime()` since rows hold both RFC 3339
+/// and SQLite's own format.
+pub fn collect(conn: &Connection, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Activity, String> {
+    let (since, until) = (since.to_rfc3339(), until.to_rfc3339());
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.title, (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id)
+             FROM conversations c
+             WHERE datetime(c.created_at) >= datetime(?1) AND datetime(c.created_at) < datetime(?2)
+               AND c.id NOT IN (SELECT conversation_id FROM conversation_tags WHERE tag = ?3)
+             ORDER BY datetime(c.created_at)",
+        )
+        .map_err(|e| e.to_string())?;
+    let conversations = stmt
+        .query_map([&since, &until, DIGEST_TAG], |row| {
+            Ok(ConversationActivity {
+                title: row.get(0)?,
+                messages: row.get(1)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT j.name, j.job_type,
+                    SUM(e.status = 'completed'), SUM(e.status = 'failed'),
+                    (SELECT f.error FROM job_executions f
+                     WHERE f.job_id = j.id AND f.status = 'failed'
+                       AND datetime(f.started_at) >= datetime(?1) AND datetime(f.started_at) < datetime(?2)
+                     ORDER BY datetime(f.started_at) DESC LIMIT 1)
+             FROM job_executions e JOIN cron_jobs j ON j.id = e.job_id
+             WHERE datetime(e.started_at) >= datetime(?1) AND datetime(e.started_at) < datetime(?2)
+               AND j.job_type != 'digest'
+             GROUP BY j.id ORDER BY j.name",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&since, &until], |row| {
+            Ok((
+                row.get::<_, String>(1)?,
+                JobActivity {
+                    name: row.get(0)?,
+                    completed: row.get(2)?,
+                    failed: row.get(3)?,
+                    last_error: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut activity = Activity {
+        conversations,
+        ..Activity::default()
+    };
+    for (job_type, job) in rows {
+        if job_type == "workflow" {
+            activity.workflows.push(job);
+        } else {
+            activity.jobs.push(job);
+        }
+    }
+    Ok(activity)
+}
+
+/// Plain Markdown listing of the activity, given to the model to summarize
+pub fn report(activity: &Activity) -> String {
+    let mut out = String::new();
+    let runs = |jobs: &[JobActivity], out: &mut String| {
+        for job in jobs {
+            out.push_str(&format!("- {}: {} completed, {} failed", job.name, job.completed, job.failed));
+            if let Some(error) = &job.last_error {
+                out.push_str(&format!(" (last error: {})", error));
+            }
+            out.push('\n');
+        }
+    };
+
+    out.push_str(&format!("## New conversations ({})\n", activity.conversations.len()));
+    for conversation in &activity.conversations {
+        out.push_str(&format!("- {} ({} messages)\n", conversation.title, conversation.messages));
+    }
+    out.push_str(&format!("\n## Scheduled jobs ({})\n", activity.jobs.len()));
+    runs(&activity.jobs, &mut out);
+    out.push_str(&format!("\n## Workflow runs ({})\n", activity.workflows.len()));
+    runs(&activity.workflows, &mut out);
+    out
+}
+
+fn prompt(period: DigestPeriod, report: &str, instructions: Option<&str>) -> String {
+    let mut prompt = format!(
+        "Write a short digest of my assistant activity over the past {}. Start with a one-sentence overview, \
+         then summarize conversations, scheduled jobs and workflow runs, calling out failures that need attention. \
+         Use Markdown and do not invent anything that is not in the activity below.",
+        period.label()
+    );
+    if let Some(instructions) = instructions.map(str::trim).filter(|i| !i.is_empty()) {
+        prompt.push_str("\n\n");
+        prompt.push_str(instructions);
+    }
+    prompt.push_str("\n\n# Activity\n\n");
+    prompt.push_str(report);
+    prompt
+}
+
+/// Save a digest as a conversation tagged `digest`; returns its ID
+pub fn save(conn: &Connection, period: DigestPeriod, until: DateTime<Utc>, content: &str) -> Result<String, String> {
+    let id = format!("digest-{}", uuid::Uuid::new_v4());
+    let now = Utc::now().to_rfc3339();
+    let title = format!("{} - {}", period.title(), until.format("%Y-%m-%d"));
+    let metadata = json!({ "digest": { "period": period.label(), "until": until.to_rfc3339() } }).to_string();
+
+    conn.execute(
+        "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+        [&id, &title, &now],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at) VALUES (?1, ?2, 'assistant', ?3, ?4, ?5)",
+        [&uuid::Uuid::new_v4().to_string(), &id, content, &crate::db::columns::seal(&metadata)?, &now],
+    )
+    .map_err(|e| e.to_string())?;
+    crate::conversations::tags::add(conn, &id, DIGEST_TAG)?;
+    Ok(id)
+}
+
+/// Run a digest job; returns the digest text
+///
+/// Nothing is summarized or saved when there was no activity.
+pub async fn run(
+    db_path: &Path,
+    params: &HashMap<String, serde_json::Value>,
+    runtime: Option<&Arc<dyn AgentRuntime>>,
+) -> Result<String, String> {
+    let period = DigestPeriod::from_params(params)?;
+    let until = Utc::now();
+    let activity = {
+        let conn = super::history::open(db_path)?;
+        collect(&conn, until - period.duration(), until)?
+    };
+    if activity.is_empty() {
+        return Ok(format!("No activity in the past {}", period.label()));
+    }
+
+    let runtime = runtime.ok_or("Agent runtime is not available")?;
+    let instructions = params.get("instructions").and_then(|v| v.as_str());
+    let provider = params.get("provider").and_then(|v| v.as_str());
+    let response = runtime
+        .call(
+            "execute_prompt",
+            json!({ "prompt": prompt(period, &report(&activity), instructions), "provider": provider }),
+        )
+        .await?;
+    let digest = match runtime_result(response)? {
+        serde_json::Value::String(text) => text,
+        other => other.to_string(),
+    };
+    if digest.trim().is_empty() {
+        return Err("The agent runtime returned an empty digest".to_string());
+    }
+
+    let conn = super::history::open(db_path)?;
+    save(&conn, period, until, &digest)?;
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute_batch(
+            r#"
+            INSERT INTO conversations (id, title, created_at) VALUES
+                ('c-old', 'Last month', '2026-09-01 09:00:00'),
+                ('c-new', 'Tax questions', '2026-10-15T09:30:00.123456+00:00'),
+                ('c-sql', 'SQL help', '2026-10-15 20:00:00');
+            INSERT INTO messages (id, conversation_id, role, content) VALUES
+                ('m1', 'c-new', 'user', 'Q'), ('m2', 'c-new', 'assistant', 'A');
+            INSERT INTO cron_jobs (id, name, schedule, job_type, config) VALUES
+                ('j-report', 'Sales report', '0 9 * * *', 'prompt', '{"target":"x"}'),
+                ('j-flow', 'Invoice flow', '0 * * * *', 'workflow', '{"target":"wf"}');
+            INSERT INTO job_executions (id, job_id, status, error, started_at) VALUES
+                ('e1', 'j-report', 'completed', NULL, '2026-10-15T09:00:00+00:00'),
+                ('e2', 'j-report', 'failed', 'timeout', '2026-10-15T10:00:00+00:00'),
+                ('e3', 'j-flow', 'completed', NULL, '2026-10-15T11:00:00+00:00'),
+                ('e4', 'j-flow', 'failed', 'old', '2026-10-01T11:00:00+00:00');
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_collect_activity() {
+        let conn = setup();
+        let until = DateTime::parse_from_rfc3339("2026-10-16T00:00:00Z").unwrap().with_timezone(&Utc);
+        let activity = collect(&conn, until - Duration::days(1), until).unwrap();
+
+        assert_eq!(
+            activity.conversations,
+            vec![
+                ConversationActivity { title: "Tax questions".to_string(), messages: 2 },
+                ConversationActivity { title: "SQL help".to_string(), messages: 0 },
+            ]
+        );
+        assert_eq!(
+            activity.jobs,
+            vec![JobActivity {
+                name: "Sales report".to_string(),
+                completed: 1,
+                failed: 1,
+                last_error: Some("timeout".to_string()),
+            }]
+        );
+        assert_eq!(activity.workflows.len(), 1);
+        assert_eq!(activity.workflows[0].failed, 0);
+
+        let text = report(&activity);
+        assert!(text.contains("- Sales report: 1 completed, 1 failed (last error: timeout)"));
+        assert!(text.contains("## Workflow runs (1)"));
+
+        // A saved digest is not itself reported by the next one
+        save(&conn, DigestPeriod::Day, until, "All quiet").unwrap();
+        let later = collect(&conn, Utc::now() - Duration::days(1), Utc::now()).unwrap();
+        assert!(!later.conversations.iter().any(|c| c.title.starts_with("Daily digest")));
+    }
+
+    #[test]
+    fn test_digest_period_and_save() {
+        let mut params = HashMap::new();
+        assert_eq!(DigestPeriod::from_params(&params).unwrap(), DigestPeriod::Day);
+        params.insert("period".to_string(), json!("weekly"));
+        assert_eq!(DigestPeriod::from_params(&params).unwrap(), DigestPeriod::Week);
+        params.insert("period".to_string(), json!("hourly"));
+        assert!(DigestPeriod::from_params(&params).is_err());
+
+        let conn = setup();
+        let until = DateTime::parse_from_rfc3339("2026-10-16T07:00:00Z").unwrap().with_timezone(&Utc);
+        let id = save(&conn, DigestPeriod::Week, until, "## Overview").unwrap();
+        let title: String = conn
+            .query_row("SELECT title FROM conversations WHERE id = ?1", [&id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(title, "Weekly digest - 2026-10-16");
+        let tags = crate::conversations::tags::list(&conn, Some(&id)).unwrap();
+        assert_eq!(tags[0].tag, DIGEST_TAG);
+    }
+}
@@ -0,0 +1,176 @@
+//! Job run times and execution history
+//!
+//! Persists the run times the scheduler computes back to `cron_jobs`, and
+//! prunes `job_executions` down to the newest executions of each job. The
+//! number kept is the `execution_retention` scheduler setting; pruning runs
+//! daily as the `prune_job_history` system task.
+
+use chrono::{DateTime, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::path::Path;
+
+/// Executions kept per job when no retention is set
+pub const DEFAULT_RETENTION: u32 = 100;
+
+/// Setting holding the number of executions kept per job
+const RETENTION_KEY: &str = "execution_retention";
+
+/// Open the app database for a background write, without creating it
+pub fn open(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_URI)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    crate::db::pool::configure(&conn).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+/// Store a job's last and next run times
+pub fn save_run_times(
+    conn: &Connection,
+    job_id: &str,
+    last_run: Option<DateTime<Utc>>,
+    next_run: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE cron_jobs SET last_run = COALESCE(?1, last_run), next_run = ?2 WHERE id = ?3",
+        params![last_run.map(|t| t.to_rfc3339()), next_run.map(|t| t.to_rfc3339()), job_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Executions kept per job
+pub fn retention(conn: &Connection) -> Result<u32, String> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM scheduler_settings WHERE key = ?1", [RETENTION_KEY], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(value.and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_RETENTION))
+}
+
+pub fn set_retention(conn: &Connection, keep: u32) -> Result<(), String> {
+    if keep == 0 {
+        return Err("Retention must keep at least one execution".to_string());
+    }
+    conn.execute(
+        "INSERT INTO scheduler_settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![RETENTION_KEY, keep.to_string()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Delete all but the newest `keep` executions of each job, with their
+/// logs; running executions are never deleted. Returns the number deleted.
+pub fn prune(conn: &Connection, keep: u32) -> Result<usize, String> {
+    let deleted = conn.execute(
+        "DELETE FROM job_executions
+         WHERE status != 'running' AND id IN (
+             SELECT id FROM (
+                 SELECT id, ROW_NUMBER() OVER (PARTITION BY job_id ORDER BY started_at DESC) AS position
+                 FROM job_executions
+             )
+             WHERE position > ?1
+         )",
+        [keep.max(1)],
+    )
+    .map_err(|e| format!("Failed to prune job history: {}", e))?;
+    super::logs::prune_orphans(conn)?;
+    Ok(deleted)
+}
+
+/// Prune with `keep`, or the stored retention when it is None
+pub fn prune_with_retention(conn: &Connection, keep: Option<u32>) -> Result<usize, String> {
+    let keep = match keep {
+        Some(keep) => keep,
+        None => retention(conn)?,
+    };
+    prune(conn, keep)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Prune job executions now; returns the number deleted
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn prune_job_history(db: tauri::State<'_, crate::db::DbState>, keep: Option<u32>) -> Result<usize, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(prune_with_retention(&conn, keep)?)
+}
+
+/// Executions kept per job
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_job_history_retention(db: tauri::State<'_, crate::db::DbState>) -> Result<u32, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(retention(&conn)?)
+}
+
+/// Set the executions kept per job
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_job_history_retention(db: tauri::State<'_, crate::db::DbState>, keep: u32) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(set_retention(&conn, keep)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        for job in ["a", "b"] {
+            conn.execute(
+                "INSERT INTO cron_jobs (id, name, schedule, job_type) VALUES (?1, ?1, '0 * * * *', 'prompt')",
+                [job],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn add_execution(conn: &Connection, id: &str, job_id: &str, status: &str, minute: u32) {
+        conn.execute(
+            "INSERT INTO job_executions (id, job_id, status, started_at) VALUES (?1, ?2, ?3, ?4)",
+            params![id, job_id, status, format!("2026-01-01T00:{:02}:00Z", minute)],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_per_job() {
+        let conn = setup();
+        for minute in 0..5 {
+            add_execution(&conn, &format!("a{}", minute), "a", "completed", minute);
+        }
+        add_execution(&conn, "a-running", "a", "running", 0);
+        add_execution(&conn, "b0", "b", "failed", 0);
+
+        assert_eq!(retention(&conn).unwrap(), DEFAULT_RETENTION);
+        set_retention(&conn, 2).unwrap();
+        assert!(set_retention(&conn, 0).is_err());
+        assert_eq!(prune_with_retention(&conn, None).unwrap(), 3);
+
+        let mut stmt = conn.prepare("SELECT id FROM job_executions ORDER BY id").unwrap();
+        let left: Vec<String> = stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect();
+        assert_eq!(left, vec!["a-running", "a3", "a4", "b0"]);
+    }
+
+    #[test]
+    fn test_save_run_times() {
+        let conn = setup();
+        let next = Utc::now();
+        save_run_times(&conn, "a", None, Some(next)).unwrap();
+
+        let (last, stored): (Option<String>, Option<String>) = conn
+            .query_row("SELECT last_run, next_run FROM cron_jobs WHERE id = 'a'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(last, None);
+        assert_eq!(stored, Some(next.to_rfc3339()));
+    }
+}
@@ -0,0 +1,208 @@
+//! Paused and snoozed jobs
+//!
+//! Pausing and snoozing hold a job back without disabling it. A paused job
+//! skips every run until it is resumed; a snoozed job skips runs until its
+//! `snoozed_until` time and then resumes on its own. Runs skipped while
+//! snoozed are recorded in `job_skipped_runs`. The state is stored on
+//! `cron_jobs` and mirrored on the scheduler's in-memory job.
+
+use super::{JobScheduler, ScheduledJob};
+use chrono::{DateTime, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use std::sync::Arc;
+use tauri::Manager;
+
+/// Why a due run is skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hold {
+    Paused,
+    Snoozed,
+}
+
+/// Whether a run of `job` due at `now` is skipped
+pub fn hold(job: &ScheduledJob, now: DateTime<Utc>) -> Option<Hold> {
+    if job.paused {
+        Some(Hold::Paused)
+    } else if job.snoozed_until.is_some_and(|until| until > now) {
+        Some(Hold::Snoozed)
+    } else {
+        None
+    }
+}
+
+/// `active`, `paused`, `snoozed` or `disabled` for a job's stored state
+pub fn status(enabled: bool, paused: bool, snoozed_until: Option<&str>, now: DateTime<Utc>) -> &'static str {
+    let snoozed = snoozed_until
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .is_some_and(|until| until.with_timezone(&Utc) > now);
+    if !enabled {
+        "disabled"
+    } else if paused {
+        "paused"
+    } else if snoozed {
+        "snoozed"
+    } else {
+        "active"
+    }
+}
+
+/// Store a job's pause and snooze state
+pub fn save(conn: &Connection, job_id: &str, paused: bool, snoozed_until: Option<DateTime<Utc>>) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE cron_jobs SET paused = ?1, snoozed_until = ?2, updated_at = ?3 WHERE id = ?4",
+            params![paused as i32, snoozed_until.map(|t| t.to_rfc3339()), Utc::now().to_rfc3339(), job_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Job with ID {} not found", job_id));
+    }
+    Ok(())
+}
+
+/// Clear a snooze that has run out
+pub fn end_snooze(conn: &Connection, job_id: &str) -> Result<(), String> {
+    conn.execute("UPDATE cron_jobs SET snoozed_until = NULL WHERE id = ?1", [job_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Record a run skipped while the job was snoozed
+pub fn record_skip(
+    conn: &Connection,
+    job_id: &str,
+    scheduled_for: DateTime<Utc>,
+    snoozed_until: DateTime<Utc>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO job_skipped_runs (job_id, scheduled_for, reason) VALUES (?1, ?2, ?3)",
+        params![job_id, scheduled_for.to_rfc3339(), format!("Snoozed until {}", snoozed_until.to_rfc3339())],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Store the state and apply it to the running scheduler
+async fn apply(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    paused: bool,
+    snoozed_until: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    {
+        let db = app.state::<crate::db::DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        save(&conn, job_id, paused, snoozed_until)?;
+    }
+
+    let scheduler = app.state::<Arc<tokio::sync::Mutex<JobScheduler>>>();
+    let scheduler = scheduler.lock().await;
+    scheduler.set_hold(job_id, paused, snoozed_until).await;
+    Ok(())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Skip every run of a job until it is resumed
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn pause_job(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(apply(&app, &id, true, None).await?)
+}
+
+/// Skip a job's runs until `until` (RFC 3339), then resume it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn snooze_job(app: tauri::AppHandle, id: String, until: String) -> Result<(), AppError> {
+    let until = DateTime::parse_from_rfc3339(&until)
+        .map_err(|e| AppError::invalid_input(format!("Invalid snooze time {}: {}", until, e)))?
+        .with_timezone(&Utc);
+    if until <= Utc::now() {
+        return Err(AppError::invalid_input("Snooze time must be in the future"));
+    }
+    Ok(apply(&app, &id, false, Some(until)).await?)
+}
+
+/// Clear a job's pause or snooze
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn resume_job(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    Ok(apply(&app, &id, false, None).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO cron_jobs (id, name, schedule, job_type, config) VALUES ('job', 'Job', '0 * * * *', 'system', '{}')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_hold_and_status() {
+        let now = Utc::now();
+        let later = now + Duration::hours(1);
+        let mut job: ScheduledJob = serde_json::from_value(serde_json::json!({
+            "id": "job",
+            "name": "Job",
+            "schedule": "0 * * * *",
+            "job_type": "system",
+            "config": { "target": "cleanup_old_messages" },
+            "enabled": true,
+            "last_run": null,
+            "next_run": null,
+            "created_at": now,
+        }))
+        .unwrap();
+        assert_eq!(hold(&job, now), None);
+
+        job.snoozed_until = Some(later);
+        assert_eq!(hold(&job, now), Some(Hold::Snoozed));
+        assert_eq!(hold(&job, later), None);
+        job.paused = true;
+        assert_eq!(hold(&job, later), Some(Hold::Paused));
+
+        let until = later.to_rfc3339();
+        assert_eq!(status(true, false, Some(&until), now), "snoozed");
+        assert_eq!(status(true, false, Some(&until), later + Duration::seconds(1)), "active");
+        assert_eq!(status(true, true, None, now), "paused");
+        assert_eq!(status(false, true, Some(&until), now), "disabled");
+    }
+
+    #[test]
+    fn test_save_and_record_skip() {
+        let conn = setup();
+        let until = Utc::now() + Duration::hours(2);
+        save(&conn, "job", false, Some(until)).unwrap();
+        record_skip(&conn, "job", Utc::now(), until).unwrap();
+        assert!(save(&conn, "missing", true, None).is_err());
+
+        let (paused, snoozed_until): (i32, Option<String>) = conn
+            .query_row("SELECT paused, snoozed_until FROM cron_jobs WHERE id = 'job'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((paused, snoozed_until), (0, Some(until.to_rfc3339())));
+        let reason: String = conn
+            .query_row("SELECT reason FROM job_skipped_runs WHERE job_id = 'job'", [], |row| row.get(0))
+            .unwrap();
+        assert!(reason.starts_with("Snoozed until"));
+
+        end_snooze(&conn, "job").unwrap();
+        let snoozed_until: Option<String> = conn
+            .query_row("SELECT snoozed_until FROM cron_jobs WHERE id = 'job'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(snoozed_until, None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Scheduler leadership across app instances
+//!
+//! Only one process may execute jobs against a database. Each scheduler
+//! tries to take an exclusive lock on `<database>.scheduler.lock`; the holder
+//! is the leader. Other instances are followers: they report read-only status,
+//! refuse to execute jobs, and retry the lock on every tick so one of them
+//! takes over when the leader exits. The operating system releases the lock
+//! when the leader's process ends, even if it crashes.
+//!
+//! Followers still write job changes to the database; the leader reloads its
+//! jobs whenever `cron_jobs` changes, so those changes reach it within a tick.
+
+use rusqlite::Connection;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+/// Lock file guarding the scheduler of the database at `db_path`
+pub fn lock_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".scheduler.lock");
+    db_path.with_file_name(name)
+}
+
+/// This process's claim on a database's scheduler
+#[derive(Debug)]
+pub struct Leadership {
+    lock_path: PathBuf,
+    lock: Option<File>,
+}
+
+impl Leadership {
+    pub fn new(db_path: &Path) -> Self {
+        Self {
+            lock_path: lock_path(db_path),
+            lock: None,
+        }
+    }
+
+    /// Take the lock unless another process holds it; returns whether this
+    /// process leads
+    pub fn try_lead(&mut self) -> bool {
+        if self.lock.is_some() {
+            return true;
+        }
+
+        let file = match OpenOptions::new().create(true).truncate(false).write(true).open(&self.lock_path) {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::warn!("Failed to open scheduler lock {}: {}", self.lock_path.display(), e);
+                return false;
+            }
+        };
+        match file.try_lock() {
+            Ok(()) => {
+                self.lock = Some(file);
+                true
+            }
+            Err(TryLockError::WouldBlock) => false,
+            Err(TryLockError::Error(e)) => {
+                tracing::warn!("Failed to lock {}: {}", self.lock_path.display(), e);
+                false
+            }
+        }
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// Give up leadership so another instance can take over
+    pub fn release(&mut self) {
+        self.lock = None;
+    }
+}
+
+/// Changes whenever a job is created, updated or deleted
+pub fn jobs_fingerprint(conn: &Connection) -> Result<(i64, Option<String>), String> {
+    conn.query_row("SELECT COUNT(*), MAX(updated_at) FROM cron_jobs", [], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_only_one_leader_per_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        assert_eq!(lock_path(&db_path), dir.path().join("app.db.scheduler.lock"));
+
+        let mut first = Leadership::new(&db_path);
+        let mut second = Leadership::new(&db_path);
+        assert!(first.try_lead());
+        assert!(first.try_lead());
+        assert!(!second.try_lead());
+        assert!(!second.is_leader());
+
+        // Another database has its own leader
+        assert!(Leadership::new(&dir.path().join("other.db")).try_lead());
+
+        first.release();
+        assert!(second.try_lead());
+        assert!(!first.try_lead());
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_job_changes() {
+        let conn = test_conn();
+        let empty = jobs_fingerprint(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO cron_jobs (id, name, schedule, job_type, updated_at) VALUES ('job', 'Job', '0 * * * *', 'system', '2026-01-01T00:00:00+00:00')",
+            [],
+        )
+        .unwrap();
+        let created = jobs_fingerprint(&conn).unwrap();
+        assert_ne!(created, empty);
+
+        conn.execute("UPDATE cron_jobs SET next_run = '2026-01-01T01:00:00+00:00'", []).unwrap();
+        assert_eq!(jobs_fingerprint(&conn).unwrap(), created);
+        conn.execute("UPDATE cron_jobs SET updated_at = '2026-01-02T00:00:00+00:00'", []).unwrap();
+        assert_ne!(jobs_fingerprint(&conn).unwrap(), created);
+    }
+}
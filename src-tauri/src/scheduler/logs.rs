@@ -0,0 +1,224 @@
+//! Live job execution logs
+//!
+//! Executions write log lines to `job_execution_logs` as they progress and
+//! emit each one as a `job://log/{execution_id}` event. The UI tails an
+//! execution with `get_execution_logs`, passing the last `seq` it has seen.
+//! Logs are pruned together with their executions.
+
+use super::history;
+use chrono::Utc;
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tauri::Emitter;
+
+/// Lines kept per execution; later lines are dropped
+pub const MAX_LINES: i64 = 1000;
+
+/// Longer lines are truncated
+const MAX_LINE_CHARS: usize = 4000;
+
+/// Lines returned per `get_execution_logs` call
+const PAGE_SIZE: i64 = 500;
+
+/// One log line of an execution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    pub execution_id: String,
+    /// Position within the execution, starting at 1
+    pub seq: i64,
+    /// `info`, `warn` or `error`
+    pub level: String,
+    pub message: String,
+    pub created_at: String,
+}
+
+/// Event a line of `execution_id` is emitted on
+pub fn event_name(execution_id: &str) -> String {
+    format!("job://log/{}", execution_id)
+}
+
+/// Log writer for a single execution
+pub struct JobLog {
+    db_path: PathBuf,
+    execution_id: String,
+    app: Option<tauri::AppHandle>,
+    seq: AtomicI64,
+}
+
+impl JobLog {
+    pub fn new(db_path: impl Into<PathBuf>, execution_id: &str, app: Option<tauri::AppHandle>) -> Self {
+        Self {
+            db_path: db_path.into(),
+            execution_id: execution_id.to_string(),
+            app,
+            seq: AtomicI64::new(0),
+        }
+    }
+
+    pub fn info(&self, message: impl AsRef<str>) {
+        self.write("info", message.as_ref());
+    }
+
+    pub fn warn(&self, message: impl AsRef<str>) {
+        self.write("warn", message.as_ref());
+    }
+
+    pub fn error(&self, message: impl AsRef<str>) {
+        self.write("error", message.as_ref());
+    }
+
+    /// Log each line of a job's output
+    pub fn output(&self, output: &str) {
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            self.info(line);
+        }
+    }
+
+    fn write(&self, level: &str, message: &str) {
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst) + 1;
+        if seq > MAX_LINES {
+            return;
+        }
+        let message = if seq == MAX_LINES {
+            format!("Log limit of {} lines reached; later lines are dropped", MAX_LINES)
+        } else {
+            truncate(message)
+        };
+        let line = LogLine {
+            execution_id: self.execution_id.clone(),
+            seq,
+            level: level.to_string(),
+            message,
+            created_at: Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = history::open(&self.db_path).and_then(|conn| append(&conn, &line)) {
+            tracing::warn!("Failed to write log of execution {}: {}", self.execution_id, e);
+        }
+        if let Some(app) = &self.app {
+            if let Err(e) = app.emit(&event_name(&self.execution_id), &line) {
+                tracing::warn!("Failed to emit log of execution {}: {}", self.execution_id, e);
+            }
+        }
+    }
+}
+
+fn truncate(message: &str) -> String {
+    match message.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &message[..end]),
+        None => message.to_string(),
+    }
+}
+
+/// Store a log line
+pub fn append(conn: &Connection, line: &LogLine) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO job_execution_logs (execution_id, seq, level, message, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![line.execution_id, line.seq, line.level, line.message, line.created_at],
+    )
+    .map_err(|e| format!("Failed to store log line: {}", e))?;
+    Ok(())
+}
+
+/// Lines of an execution after `after_seq`, oldest first
+pub fn lines(conn: &Connection, execution_id: &str, after_seq: i64, limit: i64) -> Result<Vec<LogLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT execution_id, seq, level, message, created_at FROM job_execution_logs
+             WHERE execution_id = ?1 AND seq > ?2 ORDER BY seq LIMIT ?3",
+        )
+        .map_err(|e| e.to_string())?;
+    let lines = stmt
+        .query_map(params![execution_id, after_seq, limit], |row| {
+            Ok(LogLine {
+                execution_id: row.get(0)?,
+                seq: row.get(1)?,
+                level: row.get(2)?,
+                message: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(lines)
+}
+
+/// Delete the logs of executions that no longer exist
+pub fn prune_orphans(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM job_execution_logs WHERE execution_id NOT IN (SELECT id FROM job_executions)",
+        [],
+    )
+    .map_err(|e| format!("Failed to prune job logs: {}", e))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Log lines of an execution after `after_seq`, for tailing a running job
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_execution_logs(
+    db: tauri::State<'_, crate::db::DbState>,
+    execution_id: String,
+    after_seq: Option<i64>,
+) -> Result<Vec<LogLine>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(lines(&conn, &execution_id, after_seq.unwrap_or(0), PAGE_SIZE)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let conn = Connection::open(&path).unwrap();
+        crate::db::schema::run_migrations(&conn).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_log_lines_are_tailed_in_order() {
+        let (_dir, path) = setup();
+        let log = JobLog::new(&path, "exec-1", None);
+        log.info("Started");
+        log.output("first\n\nsecond\n");
+        log.error("x".repeat(MAX_LINE_CHARS + 10));
+
+        let conn = Connection::open(&path).unwrap();
+        let all = lines(&conn, "exec-1", 0, PAGE_SIZE).unwrap();
+        let messages: Vec<_> = all.iter().map(|l| l.message.as_str()).take(3).collect();
+        assert_eq!(messages, ["Started", "first", "second"]);
+        assert_eq!(all.iter().map(|l| l.seq).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(all[3].level, "error");
+        assert_eq!(all[3].message.chars().count(), MAX_LINE_CHARS + 1);
+
+        let tail = lines(&conn, "exec-1", 2, PAGE_SIZE).unwrap();
+        assert_eq!(tail.iter().map(|l| l.seq).collect::<Vec<_>>(), [3, 4]);
+        assert!(lines(&conn, "exec-2", 0, PAGE_SIZE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_line_limit_and_orphans() {
+        let (_dir, path) = setup();
+        let log = JobLog::new(&path, "exec-1", None);
+        for i in 0..MAX_LINES + 5 {
+            log.info(format!("line {}", i));
+        }
+
+        let conn = Connection::open(&path).unwrap();
+        let all = lines(&conn, "exec-1", 0, MAX_LINES * 2).unwrap();
+        assert_eq!(all.len() as i64, MAX_LINES);
+        assert!(all.last().unwrap().message.starts_with("Log limit"));
+
+        // exec-1 has no execution row, so its logs are orphans
+        assert_eq!(prune_orphans(&conn).unwrap(), MAX_LINES as usize);
+    }
+}
@@ -2,7 +2,14 @@
 
 #![allow(dead_code)]
 
+pub mod concurrency;
 pub mod cron;
+pub mod digest;
+pub mod history;
+pub mod hold;
+pub mod leader;
+pub mod logs;
+pub mod natural;
 pub mod runner;
 #[allow(clippy::module_inception)]
 pub mod scheduler;
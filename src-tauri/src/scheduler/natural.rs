@@ -0,0 +1,382 @@
+//! Natural-language schedules
+//!
+//! Turns phrases such as "every weekday at 9am", "every 15 minutes" or
+//! "매주 월요일 오전 9시" into cron expressions, with a readable description
+//! and a preview of the next runs so the user can confirm the result. Cron
+//! expressions and preset names are accepted as they are. Like the rest of
+//! the scheduler, times are UTC.
+
+use super::cron::{parse_preset, CronExpression};
+use chrono::{DateTime, Utc};
+use crate::error::AppError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Runs included in a preview
+const PREVIEW_RUNS: usize = 5;
+
+/// English day names, indexed by cron day of week
+const EN_DAYS: [&str; 7] = ["sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday"];
+
+/// Korean day names, indexed by cron day of week
+const KO_DAYS: [char; 7] = ['일', '월', '화', '수', '목', '금', '토'];
+
+/// Schedule parsed from text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedSchedule {
+    pub cron: String,
+    /// Readable description, in Korean when the text was Korean
+    pub description: String,
+    /// Next runs (RFC 3339)
+    pub next_runs: Vec<String>,
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("schedule patterns are valid")
+}
+
+fn is_korean(text: &str) -> bool {
+    text.chars().any(|c| ('\u{AC00}'..='\u{D7A3}').contains(&c))
+}
+
+/// Fixed-interval schedules such as "every 15 minutes" or "2시간마다"
+fn parse_interval(text: &str) -> Result<Option<String>, String> {
+    if regex(r"\bevery minute\b|매\s*분").is_match(text) {
+        return Ok(Some("* * * * *".to_string()));
+    }
+    if regex(r"\bevery hour\b|\bhourly\b|매\s*시간").is_match(text) {
+        return Ok(Some("0 * * * *".to_string()));
+    }
+
+    let intervals = [
+        (r"\bevery (\d+) ?(?:minutes?|mins?)\b|(\d+)\s*분\s*마다", 59, "*/{} * * * *"),
+        (r"\bevery (\d+) ?(?:hours?|hrs?)\b|(\d+)\s*시간\s*마다", 23, "0 */{} * * *"),
+        (r"\bevery (\d+) ?days?\b|(\d+)\s*일\s*마다", 31, "0 0 */{} * *"),
+    ];
+    for (pattern, max, template) in intervals {
+        if let Some(caps) = regex(pattern).captures(text) {
+            let n: u32 = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(0);
+            if n == 0 || n > max {
+                return Err(format!("Interval must be between 1 and {}", max));
+            }
+            return Ok(Some(template.replace("{}", &n.to_string())));
+        }
+    }
+
+    if regex(r"\bevery \d+ ?(?:weeks?|months?|years?)\b|\d+\s*(?:주|개월|달|년)\s*마다").is_match(text) {
+        return Err("Schedules repeating every few weeks, months or years can't be expressed as cron".to_string());
+    }
+    Ok(None)
+}
+
+/// Time of day as (hour, minute)
+fn parse_time(text: &str) -> Result<Option<(u32, u32)>, String> {
+    if regex(r"\bnoon\b|정오").is_match(text) {
+        return Ok(Some((12, 0)));
+    }
+    if regex(r"\bmidnight\b|자정").is_match(text) {
+        return Ok(Some((0, 0)));
+    }
+
+    let twelve_hour = |hour: u32, pm: bool| -> Result<u32, String> {
+        if !(1..=12).contains(&hour) {
+            return Err(format!("Invalid hour: {}", hour));
+        }
+        Ok(match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (h, true) => h + 12,
+            (h, false) => h,
+        })
+    };
+    let minute = |m: Option<regex::Match>| -> Result<u32, String> {
+        let minute = m.and_then(|m| m.as_str().parse().ok()).unwrap_or(0);
+        if minute > 59 {
+            return Err(format!("Invalid minute: {}", minute));
+        }
+        Ok(minute)
+    };
+
+    // 9am, 9:30 pm, 9 p.m.
+    if let Some(caps) = regex(r"\b(\d{1,2})(?::(\d{2}))?\s*([ap])\.?m\b\.?").captures(text) {
+        let hour = twelve_hour(caps[1].parse().unwrap_or(0), &caps[3] == "p")?;
+        return Ok(Some((hour, minute(caps.get(2))?)));
+    }
+
+    // 오전 9시, 오후 3시 30분, 9시 반
+    if let Some(caps) = regex(r"(오전|오후|아침|낮|저녁|밤|새벽)?\s*(\d{1,2})\s*시(?:\s*(\d{1,2})\s*분|\s*(반))?").captures(text) {
+        let hour: u32 = caps[2].parse().unwrap_or(99);
+        let hour = match caps.get(1).map(|m| m.as_str()) {
+            Some("오후" | "저녁" | "밤") => twelve_hour(hour, true)?,
+            Some("낮") if hour < 12 => twelve_hour(hour, true)?,
+            Some(_) => twelve_hour(hour, false)?,
+            None if hour <= 23 => hour,
+            None => return Err(format!("Invalid hour: {}", hour)),
+        };
+        let minute = if caps.get(4).is_some() { 30 } else { minute(caps.get(3))? };
+        return Ok(Some((hour, minute)));
+    }
+
+    // 21:00, at 9
+    if let Some(caps) = regex(r"\b(\d{1,2}):(\d{2})\b|\bat (\d{1,2})\b").captures(text) {
+        let hour: u32 = caps.get(1).or_else(|| caps.get(3)).map(|m| m.as_str().parse().unwrap_or(99)).unwrap_or(99);
+        if hour > 23 {
+            return Err(format!("Invalid hour: {}", hour));
+        }
+        return Ok(Some((hour, minute(caps.get(2))?)));
+    }
+
+    Ok(None)
+}
+
+/// Days of the week named in the text, as cron numbers
+fn parse_days(text: &str) -> Vec<u32> {
+    let mut days: Vec<u32> = Vec::new();
+
+    for word in text.split(|c: char| !c.is_ascii_alphabetic()) {
+        let word = word.trim_end_matches('s');
+        if word.len() < 3 {
+            continue;
+        }
+        if let Some(day) = EN_DAYS.iter().position(|name| name.starts_with(word) || *name == word) {
+            days.push(day as u32);
+        }
+    }
+    for caps in regex(r"([일월화수목금토](?:\s*[,·/]\s*[일월화수목금토])*)\s*요일").captures_iter(text) {
+        for c in caps[1].chars() {
+            if let Some(day) = KO_DAYS.iter().position(|d| *d == c) {
+                days.push(day as u32);
+            }
+        }
+    }
+
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// Convert schedule text to a cron expression
+///
+/// Accepts cron expressions, preset names and natural-language phrases in
+/// English or Korean.
+pub fn to_cron(text: &str) -> Result<String, String> {
+    let trimmed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if trimmed.is_empty() {
+        return Err("Schedule is empty".to_string());
+    }
+    if CronExpression::parse(&trimmed).is_ok() {
+        return Ok(trimmed);
+    }
+    if let Ok(cron) = parse_preset(&trimmed) {
+        return Ok(cron);
+    }
+
+    let text = trimmed.to_lowercase();
+    if let Some(cron) = parse_interval(&text)? {
+        return Ok(cron);
+    }
+
+    let time = parse_time(&text)?;
+    let days = parse_days(&text);
+    let day_of_month = regex(r"\b(\d{1,2})(?:st|nd|rd|th)\b|(\d{1,2})\s*일")
+        .captures(&text)
+        .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|m| m.as_str().parse::<u32>().unwrap_or(0));
+
+    let (dom, dow) = if regex(r"\bweekdays?\b|평일|주중").is_match(&text) {
+        ("*".to_string(), "1-5".to_string())
+    } else if regex(r"\bweekends?\b|주말").is_match(&text) {
+        ("*".to_string(), "0,6".to_string())
+    } else if !days.is_empty() {
+        let days: Vec<String> = days.iter().map(u32::to_string).collect();
+        ("*".to_string(), days.join(","))
+    } else if let Some(day) = day_of_month {
+        if !(1..=31).contains(&day) {
+            return Err(format!("Invalid day of month: {}", day));
+        }
+        (day.to_string(), "*".to_string())
+    } else if regex(r"\bevery month\b|\bmonthly\b|매월|매달").is_match(&text) {
+        ("1".to_string(), "*".to_string())
+    } else if regex(r"\bevery week\b|\bweekly\b|매주").is_match(&text) {
+        ("*".to_string(), "0".to_string())
+    } else if time.is_some() || regex(r"\bevery day\b|\bdaily\b|\beach day\b|매일").is_match(&text) {
+        ("*".to_string(), "*".to_string())
+    } else {
+        return Err(format!("Could not understand the schedule \"{}\"", text));
+    };
+
+    let (hour, minute) = time.unwrap_or((0, 0));
+    let cron = format!("{} {} {} * {}", minute, hour, dom, dow);
+    CronExpression::parse(&cron)?;
+    Ok(cron)
+}
+
+fn day_names(dow: &str, korean: bool) -> Option<String> {
+    let days: Vec<usize> = dow.split(',').map(|d| d.parse().ok().filter(|d| *d < 7)).collect::<Option<_>>()?;
+    if korean {
+        let names: Vec<String> = days.iter().map(|d| KO_DAYS[*d].to_string()).collect();
+        return Some(format!("매주 {}요일", names.join("·")));
+    }
+    let mut names: Vec<String> = days
+        .iter()
+        .map(|d| {
+            let name = EN_DAYS[*d];
+            name[..1].to_uppercase() + &name[1..]
+        })
+        .collect();
+    let last = names.pop()?;
+    Some(if names.is_empty() {
+        format!("Every {}", last)
+    } else {
+        format!("Every {} and {}", names.join(", "), last)
+    })
+}
+
+/// Readable description of a cron expression
+pub fn describe(cron: &str, korean: bool) -> String {
+    let fields: Vec<&str> = cron.split_whitespace().collect();
+    let fallback = || {
+        if korean {
+            format!("크론 일정 '{}' (UTC 기준)", cron)
+        } else {
+            format!("Cron schedule '{}' (UTC)", cron)
+        }
+    };
+    let [minute, hour, dom, month, dow] = fields[..] else { return fallback() };
+    if month != "*" {
+        return fallback();
+    }
+    let step = |field: &str| field.strip_prefix("*/").map(str::to_string);
+
+    let described = match (minute, hour, dom, dow) {
+        ("*", "*", "*", "*") => Some(if korean { "매분".to_string() } else { "Every minute".to_string() }),
+        (m, "*", "*", "*") if step(m).is_some() => step(m).map(|n| {
+            if korean { format!("{}분마다", n) } else { format!("Every {} minutes", n) }
+        }),
+        (m, "*", "*", "*") => m.parse::<u32>().ok().map(|m| {
+            if korean { format!("매시 {}분", m) } else { format!("Every hour at minute {}", m) }
+        }),
+        (m, h, "*", "*") if step(h).is_some() => m.parse::<u32>().ok().and(step(h)).map(|n| {
+            if korean { format!("{}시간마다", n) } else { format!("Every {} hours", n) }
+        }),
+        (m, h, dom, dow) => match (m.parse::<u32>(), h.parse::<u32>()) {
+            (Ok(m), Ok(h)) => {
+                let time = format!("{:02}:{:02}", h, m);
+                let days = match (dom, dow) {
+                    ("*", "*") => Some(if korean { "매일".to_string() } else { "Every day".to_string() }),
+                    ("*", "1-5") => Some(if korean { "평일".to_string() } else { "Every weekday".to_string() }),
+                    ("*", "0,6") => Some(if korean { "주말".to_string() } else { "Every weekend day".to_string() }),
+                    ("*", dow) => day_names(dow, korean),
+                    (dom, "*") if step(dom).is_some() => step(dom).map(|n| {
+                        if korean { format!("{}일마다", n) } else { format!("Every {} days", n) }
+                    }),
+                    (dom, "*") => dom.parse::<u32>().ok().map(|d| {
+                        if korean { format!("매월 {}일", d) } else { format!("Day {} of every month", d) }
+                    }),
+                    _ => None,
+                };
+                days.map(|days| if korean { format!("{} {}", days, time) } else { format!("{} at {}", days, time) })
+            }
+            _ => None,
+        },
+    };
+
+    match described {
+        Some(text) if korean => format!("{} (UTC 기준)", text),
+        Some(text) => format!("{} (UTC)", text),
+        None => fallback(),
+    }
+}
+
+/// Next `count` runs of a cron expression after `after`
+pub fn preview(cron: &str, after: DateTime<Utc>, count: usize) -> Result<Vec<DateTime<Utc>>, String> {
+    let expression = CronExpression::parse(cron)?;
+    let mut runs = Vec::with_capacity(count);
+    let mut current = after;
+    while runs.len() < count {
+        let Some(next) = expression.next_after(current) else { break };
+        runs.push(next);
+        current = next;
+    }
+    Ok(runs)
+}
+
+/// Parse schedule text into a cron expression with a description and preview
+pub fn parse(text: &str, now: DateTime<Utc>) -> Result<ParsedSchedule, String> {
+    let cron = to_cron(text)?;
+    Ok(ParsedSchedule {
+        description: describe(&cron, is_korean(text)),
+        next_runs: preview(&cron, now, PREVIEW_RUNS)?.iter().map(|t| t.to_rfc3339()).collect(),
+        cron,
+    })
+}
+
+/// Parse a natural-language schedule into a cron expression
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn parse_schedule(text: String) -> Result<ParsedSchedule, AppError> {
+    Ok(parse(&text, Utc::now())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_cron_english() {
+        let cases = [
+            ("every weekday at 9am", "0 9 * * 1-5"),
+            ("Every Monday, Wednesday and Friday at 6:30 pm", "30 18 * * 1,3,5"),
+            ("every 15 minutes", "*/15 * * * *"),
+            ("every 2 hours", "0 */2 * * *"),
+            ("daily at 21:00", "0 21 * * *"),
+            ("at noon on weekends", "0 12 * * 0,6"),
+            ("monthly on the 15th at 8am", "0 8 15 * *"),
+            ("every sunday at 12am", "0 0 * * 0"),
+            ("0 9 * * 1-5", "0 9 * * 1-5"),
+            ("daily", "0 0 * * *"),
+        ];
+        for (text, cron) in cases {
+            assert_eq!(to_cron(text).unwrap(), cron, "{}", text);
+        }
+        assert!(to_cron("every 2 weeks").is_err());
+        assert!(to_cron("at 13pm").is_err());
+        assert!(to_cron("whenever").is_err());
+    }
+
+    #[test]
+    fn test_to_cron_korean() {
+        let cases = [
+            ("매주 월요일 오전 9시", "0 9 * * 1"),
+            ("평일 오후 6시 30분", "30 18 * * 1-5"),
+            ("매일 저녁 7시 반", "30 19 * * *"),
+            ("매월 1일 자정", "0 0 1 * *"),
+            ("월·수·금요일 오전 10시", "0 10 * * 1,3,5"),
+            ("30분마다", "*/30 * * * *"),
+            ("매시간", "0 * * * *"),
+        ];
+        for (text, cron) in cases {
+            assert_eq!(to_cron(text).unwrap(), cron, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_describes_and_previews() {
+        let now = DateTime::parse_from_rfc3339("2026-03-06T10:00:00Z").unwrap().with_timezone(&Utc);
+
+        let parsed = parse("every weekday at 9am", now).unwrap();
+        assert_eq!(parsed.description, "Every weekday at 09:00 (UTC)");
+        assert_eq!(parsed.next_runs.len(), 5);
+        assert!(parsed.next_runs[0].starts_with("2026-03-09T09:00:00"));
+
+        let parsed = parse("매주 월요일 오전 9시", now).unwrap();
+        assert_eq!(parsed.description, "매주 월요일 09:00 (UTC 기준)");
+        assert_eq!(describe("0 9 * * 1,3", false), "Every Monday and Wednesday at 09:00 (UTC)");
+        assert_eq!(describe("5 4 * 2 *", false), "Cron schedule '5 4 * 2 *' (UTC)");
+    }
+}
@@ -8,7 +8,14 @@ use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
 use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
 use std::sync::{Arc, Mutex as StdMutex};
-use tokio::sync::{Mutex, Semaphore};
+use tokio::sync::Mutex;
+
+use super::concurrency::{JobSlots, PriorityGate};
+use super::logs::JobLog;
+
+use crate::plugins::schedule::PluginTaskRunner;
+use crate::workflow::schedule::WorkflowJobRunner;
+use crate::workflow::AgentRuntime;
 
 /// Job type
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,12 +25,31 @@ pub enum JobType {
     Recipe,
     Prompt,
     System,
+    Plugin,
+    Workflow,
+    Digest,
+}
+
+impl JobType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skill => "skill",
+            Self::Recipe => "recipe",
+            Self::Prompt => "prompt",
+            Self::System => "system",
+            Self::Plugin => "plugin",
+            Self::Workflow => "workflow",
+            Self::Digest => "digest",
+        }
+    }
 }
 
 /// Job configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobConfig {
     /// For skill/recipe: the ID. For prompt: the prompt text. For system: the task name.
+    /// For plugin: the exported WASM function. For workflow: the workflow ID.
+    /// Unused for digest.
     pub target: String,
     /// Additional parameters
     #[serde(default)]
@@ -42,6 +68,53 @@ pub struct ScheduledJob {
     pub last_run: Option<DateTime<Utc>>,
     pub next_run: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    /// Owning plugin for `plugin` jobs
+    #[serde(default)]
+    pub plugin_id: Option<String>,
+    /// What to do when the job comes due while it is still running
+    #[serde(default)]
+    pub allow_overlap: OverlapPolicy,
+    /// Executions with a higher priority get a free slot first
+    #[serde(default)]
+    pub priority: i32,
+    /// Paused jobs skip every run until they are resumed
+    #[serde(default)]
+    pub paused: bool,
+    /// Runs due before this time are skipped; the job resumes on its own afterwards
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+}
+
+/// What to do when a job comes due while a previous execution is running
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    /// Skip the new run
+    Skip,
+    /// Start the new run once the previous one finishes
+    Queue,
+    /// Run both at once
+    #[default]
+    Run,
+}
+
+impl OverlapPolicy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "skip" => Some(Self::Skip),
+            "queue" => Some(Self::Queue),
+            "run" => Some(Self::Run),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Queue => "queue",
+            Self::Run => "run",
+        }
+    }
 }
 
 /// Job execution result
@@ -71,6 +144,13 @@ pub enum SystemTask {
     CleanupOldMessages,
     VacuumDatabase,
     SyncSettings,
+    PruneJobHistory,
+    BackupDatabase,
+    SyncCalendars,
+    EmbedConversations,
+    IndexWorkspaces,
+    ApplyRetention,
+    IntegrityCheck,
 }
 
 impl SystemTask {
@@ -79,11 +159,84 @@ impl SystemTask {
             "cleanup_old_messages" => Some(Self::CleanupOldMessages),
             "vacuum_database" => Some(Self::VacuumDatabase),
             "sync_settings" => Some(Self::SyncSettings),
+            "prune_job_history" => Some(Self::PruneJobHistory),
+            "backup_database" => Some(Self::BackupDatabase),
+            "sync_calendars" => Some(Self::SyncCalendars),
+            "embed_conversations" => Some(Self::EmbedConversations),
+            "index_workspaces" => Some(Self::IndexWorkspaces),
+            "apply_retention" => Some(Self::ApplyRetention),
+            "integrity_check" => Some(Self::IntegrityCheck),
             _ => None,
         }
     }
 }
 
+/// Job param asking for the output to be emailed:
+/// `{ "to": "a@example.com" | [...], "subject": "...", "format": "markdown" | "html" }`
+pub const SEND_EMAIL_PARAM: &str = "send_email";
+
+/// Email carrying a job's output, if the job asks for one
+pub fn delivery_email(
+    job: &ScheduledJob,
+    output: &str,
+) -> Option<Result<crate::integration::email::EmailMessage, String>> {
+    let settings = job.config.params.get(SEND_EMAIL_PARAM).filter(|v| !v.is_null())?;
+    let email = (|| {
+        let to = match settings.get("to") {
+            Some(serde_json::Value::String(to)) => vec![to.clone()],
+            Some(serde_json::Value::Array(to)) => to.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+            _ => Vec::new(),
+        };
+        if to.is_empty() {
+            return Err(format!("Job {} has no email recipients", job.id));
+        }
+        let subject = settings
+            .get("subject")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(String::from)
+            .unwrap_or_else(|| format!("{} - {}", job.name, Utc::now().format("%Y-%m-%d")));
+        let format = match settings.get("format") {
+            Some(format) => serde_json::from_value(format.clone())
+                .map_err(|_| format!("Invalid email format for job {}: {}", job.id, format))?,
+            None => Default::default(),
+        };
+        Ok(crate::integration::email::EmailMessage {
+            to,
+            subject,
+            body: output.to_string(),
+            format,
+        })
+    })();
+    Some(email)
+}
+
+/// Job param naming the notification channel to post the outcome to
+pub const NOTIFY_CHANNEL_PARAM: &str = "notify_channel";
+
+/// Channel message announcing a finished job
+pub fn notification_message(job: &ScheduledJob, result: &ExecutionResult) -> crate::integration::channels::ChannelMessage {
+    use crate::integration::channels::{ChannelMessage, MessageLevel};
+
+    let (title, content, level) = match result.status {
+        ExecutionStatus::Completed => (
+            format!("{} completed", job.name),
+            result.output.clone().unwrap_or_default(),
+            MessageLevel::Success,
+        ),
+        _ => (
+            format!("{} failed", job.name),
+            result.error.clone().unwrap_or_else(|| "Unknown error".to_string()),
+            MessageLevel::Error,
+        ),
+    };
+    ChannelMessage {
+        title: Some(title),
+        content,
+        level,
+    }
+}
+
 /// Execution context for jobs
 #[derive(Clone)]
 pub struct ExecutionContext {
@@ -95,6 +248,14 @@ pub struct ExecutionContext {
     pub timeout_secs: u64,
     /// Path to agent runtime binary
     pub agent_binary_path: Option<PathBuf>,
+    /// Runner for plugin jobs
+    pub plugin_runner: Option<PluginTaskRunner>,
+    /// Runner for workflow jobs
+    pub workflow_runner: Option<WorkflowJobRunner>,
+    /// Agent runtime summarizing digest jobs
+    pub agent_runtime: Option<Arc<dyn AgentRuntime>>,
+    /// App handle execution logs are emitted through
+    pub app_handle: Option<tauri::AppHandle>,
 }
 
 impl Default for ExecutionContext {
@@ -104,6 +265,10 @@ impl Default for ExecutionContext {
             agent_endpoint: None,
             timeout_secs: 300, // 5 minutes default
             agent_binary_path: None,
+            plugin_runner: None,
+            workflow_runner: None,
+            agent_runtime: None,
+            app_handle: None,
         }
     }
 }
@@ -400,48 +565,83 @@ pub struct JobExecutor {
     context: ExecutionContext,
     running_jobs: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<(String, ExecutionResult)>>>>,
     completed_results: Arc<StdMutex<HashMap<String, (String, ExecutionResult)>>>,
-    semaphore: Arc<Semaphore>,
+    gate: PriorityGate,
+    slots: JobSlots,
 }
 
 impl JobExecutor {
     /// Create a new job executor
     pub fn new(context: ExecutionContext) -> Self {
         // Limit concurrent jobs to 5
-        let semaphore = Arc::new(Semaphore::new(5));
+        Self::with_limit(context, 5)
+    }
 
+    /// Create a job executor running at most `max_concurrent` jobs at once
+    pub fn with_limit(context: ExecutionContext, max_concurrent: usize) -> Self {
         Self {
             context,
             running_jobs: Arc::new(Mutex::new(HashMap::new())),
             completed_results: Arc::new(StdMutex::new(HashMap::new())),
-            semaphore,
+            gate: PriorityGate::new(max_concurrent),
+            slots: JobSlots::default(),
         }
     }
 
+    /// Context jobs are executed with
+    pub fn context(&self) -> &ExecutionContext {
+        &self.context
+    }
+
     /// Execute a job asynchronously
-    pub async fn execute_job(&self, job: ScheduledJob) -> String {
+    ///
+    /// Fails without starting when the job's overlap policy refuses the run.
+    pub async fn execute_job(&self, job: ScheduledJob) -> Result<String, String> {
+        let mut ticket = self.slots.admit(&job.id, job.allow_overlap)?;
         let execution_id = format!("exec-{}", uuid::Uuid::new_v4());
         let job_id = job.id.clone();
         let execution_id_clone = execution_id.clone();
         let context = self.context.clone();
-        let semaphore = self.semaphore.clone();
+        let gate = self.gate.clone();
         let completed_results = self.completed_results.clone();
 
         // Create execution record in database
         if let Err(e) = Self::create_execution_record(&context, &execution_id, &job_id) {
             tracing::error!("Failed to create execution record: {}", e);
         }
+        let log = JobLog::new(&context.db_path, &execution_id, context.app_handle.clone());
 
         // Spawn the job execution task
         let handle = tokio::spawn(async move {
-            let _permit = semaphore.acquire().await.unwrap();
+            if ticket.is_queued() {
+                log.info("Waiting for the previous run to finish");
+            }
+            ticket.wait_turn().await;
+            let _permit = gate.acquire(job.priority).await;
+            log.info(format!("Started {} job '{}'", job.job_type.as_str(), job.name));
 
             let result = match job.job_type {
                 JobType::System => Self::execute_system_task(&job, &context).await,
                 JobType::Skill => Self::execute_skill(&job, &context).await,
                 JobType::Recipe => Self::execute_recipe(&job, &context).await,
                 JobType::Prompt => Self::execute_prompt(&job, &context).await,
+                JobType::Plugin => Self::execute_plugin_task(&job, &context).await,
+                JobType::Workflow => Self::execute_workflow(&job, &context).await,
+                JobType::Digest => Self::execute_digest(&job, &context).await,
             };
 
+            if let Some(output) = &result.output {
+                log.output(output);
+            }
+            match (&result.status, &result.error) {
+                (_, Some(error)) => log.error(format!("Failed: {}", error)),
+                (status, None) => log.info(format!("Finished: {:?}", status).to_lowercase()),
+            }
+
+            if matches!(result.status, ExecutionStatus::Completed) {
+                Self::deliver_email(&job, &context, &execution_id_clone, &result, &log).await;
+            }
+            Self::notify_channel(&job, &context, &result, &log).await;
+
             // Store the result in completed results
             {
                 let mut results = completed_results.lock().unwrap();
@@ -462,7 +662,7 @@ impl JobExecutor {
         let mut running = self.running_jobs.lock().await;
         running.insert(execution_id.clone(), handle);
 
-        execution_id
+        Ok(execution_id)
     }
 
     /// Create execution record in database
@@ -473,8 +673,7 @@ impl JobExecutor {
     ) -> Result<(), String> {
         use rusqlite::params;
 
-        let conn = rusqlite::Connection::open(&context.db_path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+        let conn = crate::db::pool::open_background(&context.db_path)?;
 
         conn.execute(
             "INSERT INTO job_executions (id, job_id, status, started_at) VALUES (?, ?, ?, ?)",
@@ -490,6 +689,60 @@ impl JobExecutor {
         Ok(())
     }
 
+    /// Email the output of a completed job that asks for it, and record
+    /// whether it was delivered
+    async fn deliver_email(
+        job: &ScheduledJob,
+        context: &ExecutionContext,
+        execution_id: &str,
+        result: &ExecutionResult,
+        log: &JobLog,
+    ) {
+        let output = result.output.as_deref().unwrap_or_default();
+        let Some(email) = delivery_email(job, output) else {
+            return;
+        };
+        let outcome = match email {
+            Ok(email) => crate::integration::email::send_configured(&email).await,
+            Err(e) => Err(e),
+        };
+        match &outcome {
+            Ok(()) => log.info("Emailed the output"),
+            Err(e) => {
+                tracing::warn!("Failed to email output of job {}: {}", job.id, e);
+                log.warn(format!("Failed to email the output: {}", e));
+            }
+        }
+
+        let recorded = crate::db::pool::open_background(&context.db_path)
+            .and_then(|conn| {
+                conn.execute(
+                    "UPDATE job_executions SET delivery_status = ?1, delivery_error = ?2 WHERE id = ?3",
+                    rusqlite::params![
+                        if outcome.is_ok() { "sent" } else { "failed" },
+                        outcome.err(),
+                        execution_id,
+                    ],
+                )
+                .map_err(|e| format!("Failed to record email delivery: {}", e))
+            });
+        if let Err(e) = recorded {
+            tracing::error!("{}", e);
+        }
+    }
+
+    /// Post the outcome of a job that names a notification channel
+    async fn notify_channel(job: &ScheduledJob, context: &ExecutionContext, result: &ExecutionResult, log: &JobLog) {
+        let Some(channel_id) = job.config.params.get(NOTIFY_CHANNEL_PARAM).and_then(|v| v.as_str()) else {
+            return;
+        };
+        let message = notification_message(job, result);
+        if let Err(e) = crate::integration::channels::send_message(&context.db_path, channel_id, &message).await {
+            tracing::warn!("Failed to notify channel {} about job {}: {}", channel_id, job.id, e);
+            log.warn(format!("Failed to notify channel {}: {}", channel_id, e));
+        }
+    }
+
     /// Execute a system task
     async fn execute_system_task(job: &ScheduledJob, context: &ExecutionContext) -> ExecutionResult {
         let task_name = &job.config.target;
@@ -513,12 +766,28 @@ impl JobExecutor {
                 Self::vacuum_database(context).await
             }
             SystemTask::SyncSettings => {
-                // Settings sync is a placeholder for now
-                ExecutionResult {
-                    status: ExecutionStatus::Completed,
-                    output: Some("Settings synced".to_string()),
-                    error: None,
-                }
+                Self::sync_settings(context).await
+            }
+            SystemTask::PruneJobHistory => {
+                Self::prune_job_history(context, job).await
+            }
+            SystemTask::BackupDatabase => {
+                Self::backup_database(context, job).await
+            }
+            SystemTask::SyncCalendars => {
+                Self::sync_calendars(context).await
+            }
+            SystemTask::EmbedConversations => {
+                Self::embed_conversations(context).await
+            }
+            SystemTask::IndexWorkspaces => {
+                Self::index_workspaces(context).await
+            }
+            SystemTask::ApplyRetention => {
+                Self::apply_retention(context).await
+            }
+            SystemTask::IntegrityCheck => {
+                Self::integrity_check(context).await
             }
         }
     }
@@ -569,31 +838,152 @@ impl JobExecutor {
         }
     }
 
+    /// Execute a plugin job by calling the plugin's exported function
+    async fn execute_plugin_task(job: &ScheduledJob, context: &ExecutionContext) -> ExecutionResult {
+        let (runner, plugin_id) = match (&context.plugin_runner, &job.plugin_id) {
+            (Some(runner), Some(plugin_id)) => (runner.clone(), plugin_id.clone()),
+            (None, _) => {
+                return ExecutionResult {
+                    status: ExecutionStatus::Failed,
+                    output: None,
+                    error: Some("Plugin runtime is not available".to_string()),
+                };
+            }
+            (_, None) => {
+                return ExecutionResult {
+                    status: ExecutionStatus::Failed,
+                    output: None,
+                    error: Some(format!("Job {} has no owning plugin", job.id)),
+                };
+            }
+        };
+
+        tracing::info!("Executing plugin task: {} ({})", job.config.target, plugin_id);
+
+        let function = job.config.target.clone();
+        let input = job.config.params.get("input").cloned().unwrap_or(serde_json::Value::Null);
+
+        // WASM calls are synchronous; keep them off the async workers
+        let result = tokio::task::spawn_blocking(move || runner.run(&plugin_id, &function, &input))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r);
+
+        match result {
+            Ok(output) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: output.map(|v| v.to_string()),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Execute a workflow job by running the workflow engine
+    async fn execute_workflow(job: &ScheduledJob, context: &ExecutionContext) -> ExecutionResult {
+        let Some(runner) = &context.workflow_runner else {
+            return ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some("Workflow runtime is not available".to_string()),
+            };
+        };
+
+        tracing::info!("Executing workflow job: {} ({})", job.id, job.config.target);
+
+        let input = job.config.params.get("input").cloned().unwrap_or(serde_json::Value::Null);
+
+        match runner.run(&job.config.target, input).await {
+            Ok(result) => {
+                let output = json!({
+                    "workflow_execution_id": result.execution_id,
+                    "output": result.output,
+                })
+                .to_string();
+                if result.success {
+                    ExecutionResult {
+                        status: ExecutionStatus::Completed,
+                        output: Some(output),
+                        error: None,
+                    }
+                } else {
+                    ExecutionResult {
+                        status: ExecutionStatus::Failed,
+                        output: Some(output),
+                        error: result.error,
+                    }
+                }
+            }
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Execute a digest job, summarizing recent activity
+    async fn execute_digest(job: &ScheduledJob, context: &ExecutionContext) -> ExecutionResult {
+        tracing::info!("Executing digest job: {}", job.id);
+
+        match super::digest::run(&context.db_path, &job.config.params, context.agent_runtime.as_ref()).await {
+            Ok(digest) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(digest),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
     /// Cleanup old messages (system task)
     async fn cleanup_old_messages(context: &ExecutionContext, job: &ScheduledJob) -> ExecutionResult {
         // Get the retention period from params (default 30 days)
         let retention_days = job.config.params
             .get("retention_days")
             .and_then(|v| v.as_u64())
-            .unwrap_or(30) as i64;
+            .unwrap_or(30) as u32;
+        let policy = crate::retention::RetentionPolicy {
+            entity: crate::retention::RetentionEntity::Messages,
+            max_age_days: Some(retention_days),
+        };
 
-        let cutoff_date = Utc::now() - chrono::Duration::days(retention_days);
+        let deleted = super::history::open(&context.db_path)
+            .and_then(|conn| crate::retention::apply_policy(&conn, &policy, Utc::now()));
+
+        match deleted {
+            Ok(count) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(format!("Deleted {} old messages", count)),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
 
-        // Open the database
-        let conn = rusqlite::Connection::open(&context.db_path);
+    /// Vacuum the database (system task)
+    async fn vacuum_database(context: &ExecutionContext) -> ExecutionResult {
+        let conn = crate::db::pool::open_background(&context.db_path);
 
         match conn {
             Ok(conn) => {
-                let deleted = conn.execute(
-                    "DELETE FROM messages WHERE created_at < ?",
-                    [&cutoff_date.to_rfc3339()],
-                );
-
-                match deleted {
-                    Ok(count) => {
+                match conn.execute("VACUUM", []) {
+                    Ok(_) => {
                         ExecutionResult {
                             status: ExecutionStatus::Completed,
-                            output: Some(format!("Deleted {} old messages", count)),
+                            output: Some("Database vacuumed successfully".to_string()),
                             error: None,
                         }
                     }
@@ -601,7 +991,7 @@ impl JobExecutor {
                         ExecutionResult {
                             status: ExecutionStatus::Failed,
                             output: None,
-                            error: Some(format!("Failed to delete messages: {}", e)),
+                            error: Some(format!("Failed to vacuum database: {}", e)),
                         }
                     }
                 }
@@ -610,42 +1000,175 @@ impl JobExecutor {
                 ExecutionResult {
                     status: ExecutionStatus::Failed,
                     output: None,
-                    error: Some(format!("Failed to open database: {}", e)),
+                    error: Some(e),
                 }
             }
         }
     }
 
-    /// Vacuum the database (system task)
-    async fn vacuum_database(context: &ExecutionContext) -> ExecutionResult {
-        let conn = rusqlite::Connection::open(&context.db_path);
+    /// Sync settings, templates and conversations with the configured storage
+    async fn sync_settings(context: &ExecutionContext) -> ExecutionResult {
+        let db_path = context.db_path.to_string_lossy();
+        match crate::sync::engine::sync_database(&db_path).await {
+            Ok(result) if result.success => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(crate::sync::engine::summarize(&result)),
+                error: None,
+            },
+            Ok(result) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: Some(crate::sync::engine::summarize(&result)),
+                error: Some(result.errors.join("; ")),
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
 
-        match conn {
-            Ok(conn) => {
-                match conn.execute("VACUUM", []) {
-                    Ok(_) => {
-                        ExecutionResult {
-                            status: ExecutionStatus::Completed,
-                            output: Some("Database vacuumed successfully".to_string()),
-                            error: None,
-                        }
-                    }
-                    Err(e) => {
-                        ExecutionResult {
-                            status: ExecutionStatus::Failed,
-                            output: None,
-                            error: Some(format!("Failed to vacuum database: {}", e)),
-                        }
+    /// Prune execution history (system task); `keep` in params overrides
+    /// the stored retention
+    async fn prune_job_history(context: &ExecutionContext, job: &ScheduledJob) -> ExecutionResult {
+        let keep = job.config.params.get("keep").and_then(|v| v.as_u64()).map(|v| v as u32);
+        let pruned = super::history::open(&context.db_path)
+            .and_then(|conn| super::history::prune_with_retention(&conn, keep));
+
+        match pruned {
+            Ok(count) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(format!("Deleted {} old job executions", count)),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Back up the database and rotate old backups (system task); `keep`
+    /// in params overrides the stored retention
+    async fn backup_database(context: &ExecutionContext, job: &ScheduledJob) -> ExecutionResult {
+        let keep = job.config.params.get("keep").and_then(|v| v.as_u64()).map(|v| v as u32);
+        let result = super::history::open(&context.db_path)
+            .and_then(|conn| crate::db::backup::run_scheduled(&conn, &context.db_path, keep));
+
+        match result {
+            Ok((backup, rotated)) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(format!("Backed up to {} ({} old backups deleted)", backup.path, rotated)),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Refresh the local copy of every enabled calendar
+    async fn sync_calendars(context: &ExecutionContext) -> ExecutionResult {
+        match crate::integration::calendar::sync_all(&context.db_path).await {
+            Ok(summary) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(summary),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Embed new and changed conversations for similarity search
+    async fn embed_conversations(context: &ExecutionContext) -> ExecutionResult {
+        match crate::conversations::similar::index_database(&context.db_path) {
+            Ok(summary) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(summary),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Re-index the changed files of every workspace's folders
+    async fn index_workspaces(context: &ExecutionContext) -> ExecutionResult {
+        match crate::knowledge::index::index_database(&context.db_path) {
+            Ok(summary) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(summary),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Delete history older than the retention policies allow
+    async fn apply_retention(context: &ExecutionContext) -> ExecutionResult {
+        let applied = super::history::open(&context.db_path).and_then(|mut conn| {
+            let policies = crate::retention::load(&conn)?;
+            crate::retention::apply(&mut conn, &policies, Utc::now())
+        });
+
+        match applied {
+            Ok(impacts) => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(crate::retention::summary(&impacts)),
+                error: None,
+            },
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Check the database and store the report (system task); a report
+    /// with problems fails the job and is emitted for the app to show
+    async fn integrity_check(context: &ExecutionContext) -> ExecutionResult {
+        let checked = super::history::open(&context.db_path)
+            .and_then(|conn| crate::db::health::run(&conn, Utc::now()));
+
+        match checked {
+            Ok(report) if report.healthy() => ExecutionResult {
+                status: ExecutionStatus::Completed,
+                output: Some(report.summary()),
+                error: None,
+            },
+            Ok(report) => {
+                if let Some(app) = &context.app_handle {
+                    use tauri::Emitter;
+                    if let Err(e) = app.emit(crate::db::health::HEALTH_EVENT, &report) {
+                        tracing::warn!("Failed to emit database health report: {}", e);
                     }
                 }
-            }
-            Err(e) => {
                 ExecutionResult {
                     status: ExecutionStatus::Failed,
-                    output: None,
-                    error: Some(format!("Failed to open database: {}", e)),
+                    output: Some(report.summary()),
+                    error: Some(report.problems.join("; ")),
                 }
             }
+            Err(e) => ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: None,
+                error: Some(e),
+            },
         }
     }
 
@@ -662,7 +1185,12 @@ impl JobExecutor {
 
     /// Get count of currently running jobs
     pub async fn running_count(&self) -> usize {
-        self.running_jobs.lock().await.len()
+        self.gate.in_use()
+    }
+
+    /// Executions waiting for a free slot or for their job's previous run
+    pub fn queue_depth(&self) -> usize {
+        self.gate.waiting() + self.slots.queued()
     }
 
     /// Clean up completed jobs and save results to database
@@ -710,8 +1238,7 @@ impl JobExecutor {
     ) -> Result<(), String> {
         use rusqlite::params;
 
-        let conn = rusqlite::Connection::open(&context.db_path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+        let conn = crate::db::pool::open_background(&context.db_path)?;
 
         let status_str = match result.status {
             ExecutionStatus::Running => "running",
@@ -755,6 +1282,34 @@ mod tests {
             SystemTask::from_str("sync_settings"),
             Some(SystemTask::SyncSettings)
         ));
+        assert!(matches!(
+            SystemTask::from_str("prune_job_history"),
+            Some(SystemTask::PruneJobHistory)
+        ));
+        assert!(matches!(
+            SystemTask::from_str("backup_database"),
+            Some(SystemTask::BackupDatabase)
+        ));
+        assert!(matches!(
+            SystemTask::from_str("sync_calendars"),
+            Some(SystemTask::SyncCalendars)
+        ));
+        assert!(matches!(
+            SystemTask::from_str("embed_conversations"),
+            Some(SystemTask::EmbedConversations)
+        ));
+        assert!(matches!(
+            SystemTask::from_str("index_workspaces"),
+            Some(SystemTask::IndexWorkspaces)
+        ));
+        assert!(matches!(
+            SystemTask::from_str("apply_retention"),
+            Some(SystemTask::ApplyRetention)
+        ));
+        assert!(matches!(
+            SystemTask::from_str("integrity_check"),
+            Some(SystemTask::IntegrityCheck)
+        ));
         assert!(SystemTask::from_str("unknown_task").is_none());
     }
 
@@ -763,4 +1318,149 @@ mod tests {
         let ctx = ExecutionContext::default();
         assert_eq!(ctx.timeout_secs, 300);
     }
+
+    #[tokio::test]
+    async fn test_plugin_task_without_runner() {
+        let job = ScheduledJob {
+            id: "job-1".to_string(),
+            name: "Plugin task".to_string(),
+            schedule: "0 * * * *".to_string(),
+            job_type: JobType::Plugin,
+            config: JobConfig {
+                target: "tick".to_string(),
+                params: HashMap::new(),
+            },
+            enabled: true,
+            last_run: None,
+            next_run: None,
+            created_at: Utc::now(),
+            plugin_id: Some("plugin-a".to_string()),
+            allow_overlap: OverlapPolicy::Run,
+            priority: 0,
+            paused: false,
+            snoozed_until: None,
+        };
+
+        let result = JobExecutor::execute_plugin_task(&job, &ExecutionContext::default()).await;
+        assert!(matches!(result.status, ExecutionStatus::Failed));
+    }
+
+    #[test]
+    fn test_delivery_email_and_notification() {
+        let mut job = ScheduledJob {
+            id: "job-1".to_string(),
+            name: "Morning briefing".to_string(),
+            schedule: "0 8 * * *".to_string(),
+            job_type: JobType::Prompt,
+            config: JobConfig {
+                target: "Summarize my day".to_string(),
+                params: HashMap::new(),
+            },
+            enabled: true,
+            last_run: None,
+            next_run: None,
+            created_at: Utc::now(),
+            plugin_id: None,
+            allow_overlap: OverlapPolicy::Run,
+            priority: 0,
+            paused: false,
+            snoozed_until: None,
+        };
+        assert!(delivery_email(&job, "output").is_none());
+
+        job.config.params.insert(SEND_EMAIL_PARAM.to_string(), json!({ "to": "me@example.com", "format": "html" }));
+        let email = delivery_email(&job, "output").unwrap().unwrap();
+        assert_eq!(email.to, vec!["me@example.com"]);
+        assert!(email.subject.starts_with("Morning briefing - "));
+        assert_eq!(email.format, crate::integration::email::EmailFormat::Html);
+        assert_eq!(email.body, "output");
+
+        job.config.params.insert(SEND_EMAIL_PARAM.to_string(), json!({ "to": [], "subject": "Hi" }));
+        assert!(delivery_email(&job, "output").unwrap().is_err());
+        job.config.params.insert(SEND_EMAIL_PARAM.to_string(), json!({ "to": ["me@example.com"], "format": "pdf" }));
+        assert!(delivery_email(&job, "output").unwrap().is_err());
+
+        let failed = ExecutionResult {
+            status: ExecutionStatus::Failed,
+            output: None,
+            error: Some("Provider timed out".to_string()),
+        };
+        let message = notification_message(&job, &failed);
+        assert_eq!(message.title.as_deref(), Some("Morning briefing failed"));
+        assert_eq!(message.content, "Provider timed out");
+        assert_eq!(message.level, crate::integration::channels::MessageLevel::Error);
+    }
+
+    #[tokio::test]
+    async fn test_workflow_job_runs_workflow() {
+        use crate::workflow::store::{
+            NodePosition, Workflow, WorkflowDefinition, WorkflowNode, WorkflowStore,
+        };
+        use crate::workflow::{WorkflowJobRunner, WorkflowState};
+
+        let mut job = ScheduledJob {
+            id: "workflow-trigger-t1".to_string(),
+            name: "Nightly".to_string(),
+            schedule: "0 2 * * *".to_string(),
+            job_type: JobType::Workflow,
+            config: JobConfig {
+                target: "wf-1".to_string(),
+                params: HashMap::from([("input".to_string(), json!({"n": 1}))]),
+            },
+            enabled: true,
+            last_run: None,
+            next_run: None,
+            created_at: Utc::now(),
+            plugin_id: None,
+            allow_overlap: OverlapPolicy::Run,
+            priority: 0,
+            paused: false,
+            snoozed_until: None,
+        };
+
+        let result = JobExecutor::execute_workflow(&job, &ExecutionContext::default()).await;
+        assert_eq!(result.error.as_deref(), Some("Workflow runtime is not available"));
+
+        let state = Arc::new(WorkflowState::new());
+        let trigger = WorkflowNode {
+            id: "start".to_string(),
+            node_type: "trigger".to_string(),
+            position: NodePosition { x: 0.0, y: 0.0 },
+            data: json!({}),
+            label: None,
+        };
+        let now = Utc::now().to_rfc3339();
+        state.store.write().await.create(Workflow {
+            id: "wf-1".to_string(),
+            name: "Nightly".to_string(),
+            description: None,
+            definition: WorkflowDefinition {
+                nodes: HashMap::from([("start".to_string(), trigger)]),
+                connections: Vec::new(),
+                entry_point: "start".to_string(),
+                max_parallel: None,
+                variables: Vec::new(),
+            },
+            version: 1,
+            is_active: true,
+            created_at: now.clone(),
+            updated_at: now,
+        }).unwrap();
+
+        let context = ExecutionContext {
+            workflow_runner: Some(WorkflowJobRunner::new(state.clone(), None)),
+            ..ExecutionContext::default()
+        };
+        let result = JobExecutor::execute_workflow(&job, &context).await;
+        assert!(matches!(result.status, ExecutionStatus::Completed));
+
+        let executions = state.store.read().await.get_executions("wf-1").unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].trigger_type.as_deref(), Some("schedule"));
+        assert!(result.output.unwrap().contains(&executions[0].id));
+
+        job.config.target = "missing".to_string();
+        let result = JobExecutor::execute_workflow(&job, &context).await;
+        assert!(matches!(result.status, ExecutionStatus::Failed));
+    }
 }
@@ -3,9 +3,15 @@
 #![allow(dead_code)]
 
 use super::cron::CronExpression;
+use super::history;
+use super::hold::{self, Hold};
+use super::leader::{self, Leadership};
 use super::runner::{ExecutionContext, JobExecutor, ScheduledJob};
-use chrono::Utc;
-use std::sync::Arc;
+use crate::plugins::schedule::PluginTaskRunner;
+use crate::workflow::schedule::WorkflowJobRunner;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tokio::time::interval;
@@ -37,6 +43,8 @@ pub struct JobScheduler {
     executor: Arc<JobExecutor>,
     running: Arc<RwLock<bool>>,
     jobs: Arc<RwLock<Vec<ScheduledJob>>>,
+    /// Whether this process executes the database's jobs
+    leadership: Arc<StdMutex<Leadership>>,
 }
 
 impl JobScheduler {
@@ -47,11 +55,16 @@ impl JobScheduler {
             agent_endpoint: None,
             timeout_secs: 300,
             agent_binary_path: None,
+            plugin_runner: None,
+            workflow_runner: None,
+            agent_runtime: None,
+            app_handle: None,
         };
 
-        let executor = Arc::new(JobExecutor::new(exec_context));
+        let executor = Arc::new(JobExecutor::with_limit(exec_context, config.max_concurrent_jobs));
 
         Self {
+            leadership: Arc::new(StdMutex::new(Leadership::new(Path::new(&config.db_path)))),
             config,
             executor,
             running: Arc::new(RwLock::new(false)),
@@ -59,6 +72,53 @@ impl JobScheduler {
         }
     }
 
+    /// Enable `plugin` jobs using the plugin executor's task runner
+    pub fn with_plugin_runner(self, runner: PluginTaskRunner) -> Self {
+        self.with_context(|context| context.plugin_runner = Some(runner))
+    }
+
+    /// Enable `workflow` jobs using the workflow engine
+    pub fn with_workflow_runner(self, runner: WorkflowJobRunner) -> Self {
+        self.with_context(|context| context.workflow_runner = Some(runner))
+    }
+
+    /// Enable `digest` jobs, summarized by the agent runtime
+    pub fn with_agent_runtime(self, runtime: Arc<dyn crate::workflow::AgentRuntime>) -> Self {
+        self.with_context(|context| context.agent_runtime = Some(runtime))
+    }
+
+    /// Emit execution logs as `job://log/{execution_id}` events
+    pub fn with_app_handle(self, app: tauri::AppHandle) -> Self {
+        self.with_context(|context| context.app_handle = Some(app))
+    }
+
+    /// A stopped scheduler with the same runners, using the database at
+    /// `db_path` (after a profile switch); its jobs must be loaded again
+    pub fn for_database(&self, db_path: &str) -> Self {
+        let config = SchedulerConfig {
+            db_path: db_path.to_string(),
+            ..self.config.clone()
+        };
+        let mut exec_context = self.executor.context().clone();
+        exec_context.db_path = std::path::PathBuf::from(db_path);
+
+        Self {
+            executor: Arc::new(JobExecutor::with_limit(exec_context, config.max_concurrent_jobs)),
+            leadership: Arc::new(StdMutex::new(Leadership::new(Path::new(db_path)))),
+            config,
+            running: Arc::new(RwLock::new(false)),
+            jobs: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Rebuild the executor with an updated execution context
+    fn with_context(mut self, update: impl FnOnce(&mut ExecutionContext)) -> Self {
+        let mut exec_context = self.executor.context().clone();
+        update(&mut exec_context);
+        self.executor = Arc::new(JobExecutor::with_limit(exec_context, self.config.max_concurrent_jobs));
+        self
+    }
+
     /// Start the scheduler
     pub async fn start(&self) -> Result<(), String> {
         let mut running = self.running.write().await;
@@ -68,14 +128,20 @@ impl JobScheduler {
         *running = true;
         drop(running);
 
+        if !self.leadership.lock().unwrap().try_lead() {
+            tracing::info!("Another instance runs the scheduler for {}; following it", self.config.db_path);
+        }
+
         let config = self.config.clone();
         let executor = self.executor.clone();
         let jobs = self.jobs.clone();
         let running_flag = self.running.clone();
+        let leadership = self.leadership.clone();
 
         tokio::spawn(async move {
             let mut timer = interval(Duration::from_secs(config.check_interval_secs));
             timer.tick().await; // Skip first immediate tick
+            let mut seen_jobs = None;
 
             loop {
                 timer.tick().await;
@@ -88,20 +154,49 @@ impl JobScheduler {
                     }
                 }
 
+                // Only the leader executes jobs; followers retry the lock
+                if !leadership.lock().unwrap().try_lead() {
+                    continue;
+                }
+
+                // Pick up jobs changed by other instances or while following
+                Self::reload_if_changed(&config.db_path, &jobs, &mut seen_jobs).await;
+
                 // Clean up completed jobs
                 executor.cleanup_completed().await;
 
                 // Check for due jobs
                 let due_jobs = Self::get_due_jobs(&jobs).await;
+                let now = Utc::now();
 
                 for job in due_jobs {
-                    tracing::info!("Executing due job: {}", job.name);
-
-                    let execution_id = executor.execute_job(job.clone()).await;
-                    tracing::info!("Started execution: {}", execution_id);
-
-                    // Update next run time for the job
-                    Self::update_job_next_run(&jobs, &job.id).await;
+                    let ran = match hold::hold(&job, now) {
+                        Some(Hold::Paused) => {
+                            tracing::debug!("Skipped run of paused job {}", job.name);
+                            false
+                        }
+                        Some(Hold::Snoozed) => {
+                            tracing::info!("Skipped run of snoozed job {}", job.name);
+                            Self::record_skip(&config.db_path, &job);
+                            false
+                        }
+                        None => {
+                            if job.snoozed_until.is_some() {
+                                Self::end_snooze(&config.db_path, &job.id);
+                            }
+                            tracing::info!("Executing due job: {}", job.name);
+                            match executor.execute_job(job.clone()).await {
+                                Ok(execution_id) => tracing::info!("Started execution: {}", execution_id),
+                                Err(e) => tracing::info!("Skipped run of {}: {}", job.name, e),
+                            }
+                            true
+                        }
+                    };
+
+                    // Update next run time for the job and persist it
+                    if let Some(updated) = Self::update_job_next_run(&jobs, &job.id, ran).await {
+                        Self::save_run_times(&config.db_path, &[updated]);
+                    }
                 }
             }
         });
@@ -110,10 +205,23 @@ impl JobScheduler {
         Ok(())
     }
 
-    /// Stop the scheduler
+    /// Stop the scheduler and hand leadership to another instance
     pub async fn stop(&self) {
         let mut running = self.running.write().await;
         *running = false;
+        self.leadership.lock().unwrap().release();
+    }
+
+    /// Take leadership unless another instance holds it; returns whether
+    /// this instance leads
+    pub fn try_lead(&self) -> bool {
+        self.leadership.lock().unwrap().try_lead()
+    }
+
+    /// Whether this instance executes jobs, rather than another one running
+    /// against the same database
+    pub fn is_leader(&self) -> bool {
+        self.leadership.lock().unwrap().is_leader()
     }
 
     /// Check if scheduler is running
@@ -164,6 +272,19 @@ impl JobScheduler {
         }
     }
 
+    /// Pause, snooze or resume a job; returns false when it is not loaded
+    pub async fn set_hold(&self, job_id: &str, paused: bool, snoozed_until: Option<DateTime<Utc>>) -> bool {
+        let mut jobs = self.jobs.write().await;
+        match jobs.iter_mut().find(|j| j.id == job_id) {
+            Some(job) => {
+                job.paused = paused;
+                job.snoozed_until = snoozed_until;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get all jobs
     pub async fn get_jobs(&self) -> Vec<ScheduledJob> {
         self.jobs.read().await.clone()
@@ -183,9 +304,11 @@ impl JobScheduler {
     pub async fn execute_now(&self, job_id: &str) -> Result<String, String> {
         let job = self.get_job(job_id).await
             .ok_or_else(|| format!("Job with ID {} not found", job_id))?;
+        if !self.leadership.lock().unwrap().try_lead() {
+            return Err("Jobs run in another instance of the app".to_string());
+        }
 
-        let execution_id = self.executor.execute_job(job).await;
-        Ok(execution_id)
+        self.executor.execute_job(job).await
     }
 
     /// Cancel a running job execution
@@ -198,6 +321,11 @@ impl JobScheduler {
         self.executor.running_count().await
     }
 
+    /// Get the number of executions waiting to start
+    pub fn queue_depth(&self) -> usize {
+        self.executor.queue_depth()
+    }
+
     /// Load jobs from a vector (e.g., from database)
     pub async fn load_jobs(&self, jobs: Vec<ScheduledJob>) -> Result<(), String> {
         let mut job_list = self.jobs.write().await;
@@ -238,38 +366,130 @@ impl JobScheduler {
             .collect()
     }
 
-    /// Update the next run time for a job
+    /// Update the run times for a job, returning the updated job. `last_run`
+    /// only moves when the run was not skipped.
     async fn update_job_next_run(
         jobs: &Arc<RwLock<Vec<ScheduledJob>>>,
         job_id: &str,
-    ) {
+        ran: bool,
+    ) -> Option<ScheduledJob> {
         let mut job_list = jobs.write().await;
         let now = Utc::now();
 
-        if let Some(job) = job_list.iter_mut().find(|j| j.id == job_id) {
-            // Update last_run
+        let job = job_list.iter_mut().find(|j| j.id == job_id)?;
+
+        if ran {
             job.last_run = Some(now);
+        }
 
-            // Calculate next run
-            if let Ok(cron) = CronExpression::parse(&job.schedule) {
-                job.next_run = cron.next_after(now);
+        // A snooze that has run out is over
+        if job.snoozed_until.is_some_and(|until| until <= now) {
+            job.snoozed_until = None;
+        }
+
+        // Calculate next run
+        if let Ok(cron) = CronExpression::parse(&job.schedule) {
+            job.next_run = cron.next_after(now);
+        }
+
+        Some(job.clone())
+    }
+
+    /// Write jobs' run times back to `cron_jobs`
+    fn save_run_times(db_path: &str, jobs: &[ScheduledJob]) {
+        if jobs.is_empty() {
+            return;
+        }
+        let result = history::open(std::path::Path::new(db_path)).and_then(|conn| {
+            jobs.iter()
+                .try_for_each(|job| history::save_run_times(&conn, &job.id, job.last_run, job.next_run))
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist job run times: {}", e);
+        }
+    }
+
+    /// Reload jobs from the database when `cron_jobs` changed since `seen`.
+    /// Passed run times are kept so the jobs still run this tick.
+    async fn reload_if_changed(
+        db_path: &str,
+        jobs: &Arc<RwLock<Vec<ScheduledJob>>>,
+        seen: &mut Option<(i64, Option<String>)>,
+    ) {
+        let loaded = history::open(Path::new(db_path)).and_then(|conn| {
+            let fingerprint = leader::jobs_fingerprint(&conn)?;
+            if seen.as_ref() == Some(&fingerprint) {
+                return Ok(None);
+            }
+            let loaded = crate::db::enabled_scheduled_jobs(&conn)?;
+            *seen = Some(fingerprint);
+            Ok(Some(loaded))
+        });
+
+        let mut loaded = match loaded {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Failed to reload scheduled jobs: {}", e);
+                return;
+            }
+        };
+
+        let now = Utc::now();
+        let mut changed = Vec::new();
+        loaded.retain_mut(|job| match CronExpression::parse(&job.schedule) {
+            Ok(cron) => {
+                if job.next_run.is_none() {
+                    job.next_run = cron.next_after(now);
+                    changed.push(job.clone());
+                }
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Skipping job {} with invalid schedule: {}", job.name, e);
+                false
             }
+        });
+        *jobs.write().await = loaded;
+        Self::save_run_times(db_path, &changed);
+    }
+
+    /// Record a run skipped because the job is snoozed
+    fn record_skip(db_path: &str, job: &ScheduledJob) {
+        let (Some(scheduled_for), Some(until)) = (job.next_run, job.snoozed_until) else { return };
+        let result = history::open(std::path::Path::new(db_path))
+            .and_then(|conn| hold::record_skip(&conn, &job.id, scheduled_for, until));
+        if let Err(e) = result {
+            tracing::warn!("Failed to record skipped run of {}: {}", job.name, e);
         }
     }
 
-    /// Refresh next run times for all jobs
+    /// Clear a job's snooze once it has run out
+    fn end_snooze(db_path: &str, job_id: &str) {
+        let result = history::open(std::path::Path::new(db_path)).and_then(|conn| hold::end_snooze(&conn, job_id));
+        if let Err(e) = result {
+            tracing::warn!("Failed to end snooze of {}: {}", job_id, e);
+        }
+    }
+
+    /// Refresh next run times for all jobs and persist the ones that changed
     pub async fn refresh_schedule(&self) {
         let mut jobs = self.jobs.write().await;
         let now = Utc::now();
+        let mut changed = Vec::new();
 
         for job in jobs.iter_mut() {
             if let Ok(cron) = CronExpression::parse(&job.schedule) {
                 // If next_run is not set or has passed, calculate the next one
-                if job.next_run.is_none() || job.next_run.unwrap() <= now {
+                if job.next_run.is_none_or(|next_run| next_run <= now) {
                     job.next_run = cron.next_after(now);
+                    changed.push(job.clone());
                 }
             }
         }
+        drop(jobs);
+
+        Self::save_run_times(&self.config.db_path, &changed);
     }
 }
 
@@ -292,6 +512,11 @@ mod tests {
             last_run: None,
             next_run: None,
             created_at: Utc::now(),
+            plugin_id: None,
+            allow_overlap: Default::default(),
+            priority: 0,
+            paused: false,
+            snoozed_until: None,
         }
     }
 
@@ -335,6 +560,20 @@ mod tests {
         assert_eq!(scheduler.get_jobs().await.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_skipped_run_keeps_last_run() {
+        let scheduler = JobScheduler::new(SchedulerConfig::default());
+        scheduler.add_job(create_test_job("job1", "0 * * * *")).await.unwrap();
+        let ended = Utc::now() - chrono::Duration::minutes(1);
+        assert!(scheduler.set_hold("job1", false, Some(ended)).await);
+        assert!(!scheduler.set_hold("nonexistent", true, None).await);
+
+        let job = JobScheduler::update_job_next_run(&scheduler.jobs, "job1", false).await.unwrap();
+        assert!(job.last_run.is_none());
+        assert!(job.next_run.is_some());
+        assert!(job.snoozed_until.is_none());
+    }
+
     #[tokio::test]
     async fn test_update_job() {
         let scheduler = JobScheduler::new(SchedulerConfig::default());
@@ -374,4 +613,30 @@ mod tests {
         assert!(retrieved.is_some());
         assert!(retrieved.unwrap().next_run.is_some());
     }
+
+    #[tokio::test]
+    async fn test_refresh_schedule_persists_next_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        crate::db::schema::run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO cron_jobs (id, name, schedule, job_type) VALUES ('job1', 'Job', '0 * * * *', 'system')",
+            [],
+        )
+        .unwrap();
+
+        let scheduler = JobScheduler::new(SchedulerConfig {
+            db_path: db_path.to_string_lossy().to_string(),
+            ..SchedulerConfig::default()
+        });
+        scheduler.add_job(create_test_job("job1", "0 * * * *")).await.unwrap();
+        scheduler.refresh_schedule().await;
+
+        let next_run = scheduler.get_job("job1").await.unwrap().next_run.unwrap();
+        let stored: Option<String> = conn
+            .query_row("SELECT next_run FROM cron_jobs WHERE id = 'job1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, Some(next_run.to_rfc3339()));
+    }
 }
@@ -5,6 +5,10 @@
 use crate::security::{Result, SecurityError};
 use keyring::{Entry, Error as KeyringError};
 
+/// Keychain service of the default profile; other profiles use
+/// `<SERVICE_NAME>.<profile id>`
+pub const SERVICE_NAME: &str = "ai-assistant-tauri";
+
 /// Credential manager using platform keychain
 pub struct CredentialManager {
     service_name: String,
@@ -18,7 +22,7 @@ impl CredentialManager {
 
     /// Get default credential manager for this app
     pub fn default() -> Result<Self> {
-        Ok(Self::new("ai-assistant-tauri".to_string()))
+        Ok(Self::new(SERVICE_NAME.to_string()))
     }
 
     /// Create a keyring entry for a credential
@@ -1,13 +1,19 @@
 //! AES-256-GCM encryption for sensitive data
+//!
+//! Besides the raw primitives, this holds the column key: database columns
+//! that may contain secrets are encrypted with a key kept in the platform
+//! keychain and loaded once at startup.
 
 #![allow(dead_code)]
 
-use crate::security::{Result, SecurityError};
+use crate::security::field_encryption::{decrypt_field, encrypt_field, load_or_create_named_key};
+use crate::security::{CredentialManager, Result, SecurityError};
 use aes_gcm::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
 use sha2::{Digest, Sha256};
+use std::sync::RwLock;
 
 const KEY_SIZE: usize = 32; // 256 bits
 const NONCE_SIZE: usize = 12; // 96 bits for GCM
@@ -56,6 +62,77 @@ pub fn decrypt_data(encrypted: &EncryptedData, key: &[u8; KEY_SIZE]) -> Result<V
         .map_err(|e| SecurityError::Decryption(e.to_string()))
 }
 
+// ============================================================================
+// Column Encryption
+// ============================================================================
+
+/// Keychain entry holding the column encryption key
+const COLUMN_KEY_ID: &str = "column_encryption_key";
+
+/// Marks a column value encrypted by [`encrypt_column_with`]
+pub const COLUMN_PREFIX: &str = "enc:v1:";
+
+static COLUMN_KEY: RwLock<Option<[u8; KEY_SIZE]>> = RwLock::new(None);
+
+/// Load the column key from the keychain, creating it on first use, and
+/// use it until another one is installed (on a profile switch)
+pub fn install_column_key(manager: &CredentialManager) -> Result<[u8; KEY_SIZE]> {
+    let key = load_or_create_named_key(manager, COLUMN_KEY_ID)?;
+    if let Ok(mut installed) = COLUMN_KEY.write() {
+        *installed = Some(key);
+    }
+    Ok(key)
+}
+
+/// Stop encrypting columns, as when the keychain is not accessible
+pub fn clear_column_key() {
+    if let Ok(mut installed) = COLUMN_KEY.write() {
+        *installed = None;
+    }
+}
+
+/// The installed column key, if any
+pub fn column_key() -> Option<[u8; KEY_SIZE]> {
+    COLUMN_KEY.read().ok().and_then(|key| *key)
+}
+
+pub fn is_encrypted_column(value: &str) -> bool {
+    value.starts_with(COLUMN_PREFIX)
+}
+
+/// Encrypt a column value; empty and already encrypted values are kept as is
+pub fn encrypt_column_with(value: &str, key: &[u8; KEY_SIZE]) -> Result<String> {
+    if value.is_empty() || is_encrypted_column(value) {
+        return Ok(value.to_string());
+    }
+    Ok(format!("{}{}", COLUMN_PREFIX, encrypt_field(value, key)?))
+}
+
+/// Decrypt a column value; plaintext written before encryption passes through
+pub fn decrypt_column_with(value: &str, key: Option<&[u8; KEY_SIZE]>) -> Result<String> {
+    match value.strip_prefix(COLUMN_PREFIX) {
+        Some(sealed) => {
+            let key = key.ok_or_else(|| SecurityError::NotFound(COLUMN_KEY_ID.to_string()))?;
+            decrypt_field(sealed, key)
+        }
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Encrypt a column value with the installed key; without one (no keychain)
+/// the value is stored as plaintext
+pub fn encrypt_column(value: &str) -> Result<String> {
+    match column_key() {
+        Some(key) => encrypt_column_with(value, &key),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Decrypt a column value with the installed key
+pub fn decrypt_column(value: &str) -> Result<String> {
+    decrypt_column_with(value, column_key().as_ref())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,4 +167,23 @@ mod tests {
         let encrypted = encrypt_data(plaintext, &key1).unwrap();
         assert!(decrypt_data(&encrypted, &key2).is_err());
     }
+
+    #[test]
+    fn test_column_roundtrip() {
+        let key = derive_key("column_password", b"column_salt");
+        let sealed = encrypt_column_with(r#"{"tool":"shell","output":"token=abc"}"#, &key).unwrap();
+        assert!(is_encrypted_column(&sealed));
+        assert!(!sealed.contains("token"));
+        // Encrypting twice keeps the first ciphertext
+        assert_eq!(encrypt_column_with(&sealed, &key).unwrap(), sealed);
+        assert_eq!(
+            decrypt_column_with(&sealed, Some(&key)).unwrap(),
+            r#"{"tool":"shell","output":"token=abc"}"#
+        );
+
+        // Legacy plaintext and empty values pass through; ciphertext needs the key
+        assert_eq!(decrypt_column_with("plain", None).unwrap(), "plain");
+        assert_eq!(encrypt_column_with("", &key).unwrap(), "");
+        assert!(decrypt_column_with(&sealed, None).is_err());
+    }
 }
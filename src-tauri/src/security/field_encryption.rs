@@ -0,0 +1,212 @@
+//! Field-level encryption for sensitive conversations
+//!
+//! Message content of conversations flagged as sensitive is stored encrypted
+//! with a per-installation key kept in the platform keychain. Plaintext only
+//! exists in memory while a conversation is unlocked for the current session.
+
+#![allow(dead_code)]
+
+use crate::security::encryption::{decrypt_data, encrypt_data, EncryptedData};
+use crate::security::{CredentialManager, Result, SecurityError};
+use aes_gcm::{
+    aead::{KeyInit, OsRng},
+    Aes256Gcm,
+};
+use std::collections::HashSet;
+
+/// Keychain entry holding the conversation encryption key
+const CONVERSATION_KEY_ID: &str = "conversation_encryption_key";
+
+const NONCE_SIZE: usize = 12;
+
+/// Load the conversation key from the keychain, creating it on first use
+pub fn load_or_create_key(manager: &CredentialManager) -> Result<[u8; 32]> {
+    load_or_create_named_key(manager, CONVERSATION_KEY_ID)
+}
+
+/// Load the key stored in keychain entry `id`, creating it on first use
+pub fn load_or_create_named_key(manager: &CredentialManager, id: &str) -> Result<[u8; 32]> {
+    match manager.get_password(id) {
+        Ok(encoded) => {
+            let bytes = decode_hex(&encoded)?;
+            if bytes.len() != 32 {
+                return Err(SecurityError::InvalidFormat);
+            }
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&bytes);
+            Ok(key)
+        }
+        Err(SecurityError::NotFound(_)) => {
+            let generated = Aes256Gcm::generate_key(&mut OsRng);
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&generated);
+            manager.set_password(id, &encode_hex(&key))?;
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Encrypt a text field, returning a hex-encoded `nonce || ciphertext` string
+pub fn encrypt_field(plaintext: &str, key: &[u8; 32]) -> Result<String> {
+    let encrypted = encrypt_data(plaintext.as_bytes(), key)?;
+    let mut bytes = encrypted.nonce;
+    bytes.extend_from_slice(&encrypted.ciphertext);
+    Ok(encode_hex(&bytes))
+}
+
+/// Decrypt a text field produced by [`encrypt_field`]
+pub fn decrypt_field(value: &str, key: &[u8; 32]) -> Result<String> {
+    let bytes = decode_hex(value)?;
+    if bytes.len() <= NONCE_SIZE {
+        return Err(SecurityError::InvalidFormat);
+    }
+
+    let (nonce, ciphertext) = bytes.split_at(NONCE_SIZE);
+    let plaintext = decrypt_data(
+        &EncryptedData {
+            ciphertext: ciphertext.to_vec(),
+            nonce: nonce.to_vec(),
+        },
+        key,
+    )?;
+
+    String::from_utf8(plaintext).map_err(|e| SecurityError::Decryption(e.to_string()))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return Err(SecurityError::InvalidFormat);
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(SecurityError::InvalidFormat)
+        })
+        .collect()
+}
+
+/// In-memory session state for sensitive conversations
+///
+/// Holds the cached encryption key and the set of conversations the user
+/// has unlocked. Nothing here is persisted. Work that runs off the command
+/// thread, such as an export, takes a clone.
+#[derive(Clone, Default)]
+pub struct SensitiveSession {
+    key: Option<[u8; 32]>,
+    unlocked: HashSet<String>,
+}
+
+impl SensitiveSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Session with `key` already loaded, for tests without a keychain
+    #[cfg(test)]
+    pub fn with_key(key: [u8; 32]) -> Self {
+        Self { key: Some(key), unlocked: HashSet::new() }
+    }
+
+    /// Get the conversation key, loading it from the keychain if needed
+    pub fn key(&mut self, manager: &CredentialManager) -> Result<[u8; 32]> {
+        if let Some(key) = self.key {
+            return Ok(key);
+        }
+        let key = load_or_create_key(manager)?;
+        self.key = Some(key);
+        Ok(key)
+    }
+
+    pub fn unlock(&mut self, conversation_id: &str) {
+        self.unlocked.insert(conversation_id.to_string());
+    }
+
+    pub fn lock(&mut self, conversation_id: &str) {
+        self.unlocked.remove(conversation_id);
+    }
+
+    /// Lock every conversation and drop the cached key
+    pub fn lock_all(&mut self) {
+        self.unlocked.clear();
+        self.key = None;
+    }
+
+    pub fn is_unlocked(&self, conversation_id: &str) -> bool {
+        self.unlocked.contains(conversation_id)
+    }
+
+    /// Cached key, only if `conversation_id` is unlocked
+    pub fn unlocked_key(&self, conversation_id: &str) -> Option<[u8; 32]> {
+        self.key.filter(|_| self.is_unlocked(conversation_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::encryption::derive_key;
+
+    #[test]
+    fn test_field_roundtrip() {
+        let key = derive_key("field_password", b"field_salt");
+        let encrypted = encrypt_field("sensitive message", &key).unwrap();
+
+        assert!(!encrypted.contains("sensitive"));
+        assert_eq!(decrypt_field(&encrypted, &key).unwrap(), "sensitive message");
+    }
+
+    #[test]
+    fn test_field_wrong_key_fails() {
+        let key1 = derive_key("password1", b"salt");
+        let key2 = derive_key("password2", b"salt");
+
+        let encrypted = encrypt_field("secret", &key1).unwrap();
+        assert!(decrypt_field(&encrypted, &key2).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_plaintext() {
+        let key = derive_key("password", b"salt");
+        assert!(decrypt_field("not encrypted", &key).is_err());
+        assert!(decrypt_field("abcd", &key).is_err());
+    }
+
+    #[test]
+    fn test_session_unlock() {
+        let mut session = SensitiveSession::new();
+        assert!(!session.is_unlocked("conv-1"));
+
+        session.unlock("conv-1");
+        assert!(session.is_unlocked("conv-1"));
+
+        session.lock("conv-1");
+        assert!(!session.is_unlocked("conv-1"));
+
+        session.unlock("conv-2");
+        session.lock_all();
+        assert!(!session.is_unlocked("conv-2"));
+    }
+
+    #[test]
+    fn test_unlocked_key() {
+        let mut session = SensitiveSession::with_key(derive_key("password", b"salt"));
+        assert!(session.unlocked_key("conv-1").is_none());
+
+        session.unlock("conv-1");
+        assert!(session.unlocked_key("conv-1").is_some());
+        assert!(session.clone().unlocked_key("conv-1").is_some());
+
+        session.lock_all();
+        session.unlock("conv-1");
+        assert!(session.unlocked_key("conv-1").is_none());
+    }
+}
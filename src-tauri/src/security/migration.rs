@@ -1,5 +1,7 @@
-//! Migration script to encrypt existing plaintext passwords
+//! Migration scripts moving existing secrets out of plaintext storage
 
+use crate::db::columns::ENCRYPTED_COLUMNS;
+use crate::security::encryption::{encrypt_column_with, COLUMN_PREFIX};
 use crate::security::{CredentialManager, Result, SecurityError};
 use rusqlite::Connection;
 
@@ -46,9 +48,88 @@ pub fn migrate_plaintext_passwords(conn: &Connection) -> Result<usize> {
     Ok(migrated)
 }
 
+/// Encrypt the plaintext values of every encrypted column with `key`
+///
+/// Values already carrying the encryption prefix are skipped, so this is
+/// safe to run on every start. Runs in one transaction.
+pub fn encrypt_existing_columns(conn: &Connection, key: &[u8; 32]) -> Result<usize> {
+    let failed = |e: rusqlite::Error| SecurityError::Encryption(format!("Failed to encrypt columns: {}", e));
+    let tx = conn.unchecked_transaction().map_err(failed)?;
+    let mut migrated = 0;
+
+    for (table, column) in ENCRYPTED_COLUMNS {
+        let rows = {
+            let mut stmt = tx
+                .prepare(&format!(
+                    "SELECT rowid, {column} FROM {table}
+                     WHERE {column} IS NOT NULL AND {column} != '' AND substr({column}, 1, ?1) != ?2",
+                    column = column,
+                    table = table
+                ))
+                .map_err(failed)?;
+            let rows = stmt
+                .query_map(rusqlite::params![COLUMN_PREFIX.len() as i64, COLUMN_PREFIX], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })
+                .map_err(failed)?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(failed)?;
+            rows
+        };
+
+        for (rowid, value) in rows {
+            tx.execute(
+                &format!("UPDATE {} SET {} = ?1 WHERE rowid = ?2", table, column),
+                rusqlite::params![encrypt_column_with(&value, key)?, rowid],
+            )
+            .map_err(failed)?;
+            migrated += 1;
+        }
+    }
+
+    tx.commit().map_err(failed)?;
+    Ok(migrated)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::security::encryption::{decrypt_column_with, derive_key};
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_encrypt_existing_columns() {
+        let conn = test_conn();
+        conn.execute_batch(
+            r#"
+            INSERT INTO conversations (id, title) VALUES ('c1', 'Deploy');
+            INSERT INTO messages (id, conversation_id, role, content, metadata) VALUES
+                ('m1', 'c1', 'assistant', 'Done', '{"tool":"shell","output":"AWS_SECRET=abc"}'),
+                ('m2', 'c1', 'user', 'Deploy it', '');
+            INSERT INTO calendar_sources (id, name, kind, url, username) VALUES
+                ('s1', 'Team', 'ics', 'https://example.com/private-token/basic.ics', NULL);
+            "#,
+        )
+        .unwrap();
+
+        let key = derive_key("column_password", b"column_salt");
+        assert_eq!(encrypt_existing_columns(&conn, &key).unwrap(), 2);
+        // Already encrypted values are left alone
+        assert_eq!(encrypt_existing_columns(&conn, &key).unwrap(), 0);
+
+        let metadata: String = conn
+            .query_row("SELECT metadata FROM messages WHERE id = 'm1'", [], |row| row.get(0))
+            .unwrap();
+        assert!(!metadata.contains("AWS_SECRET"));
+        assert_eq!(
+            decrypt_column_with(&metadata, Some(&key)).unwrap(),
+            r#"{"tool":"shell","output":"AWS_SECRET=abc"}"#
+        );
+        let url: String = conn
+            .query_row("SELECT url FROM calendar_sources WHERE id = 's1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(decrypt_column_with(&url, Some(&key)).unwrap(), "https://example.com/private-token/basic.ics");
+    }
 
     #[test]
     fn test_migration() {
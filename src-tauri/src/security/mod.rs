@@ -1,17 +1,23 @@
 //! Security Module - Credential encryption and keychain integration
 //!
 //! This module provides secure credential storage using platform keychains
-//! and AES-256-GCM encryption for sensitive data.
+//! and AES-256-GCM encryption for sensitive data, and guardrails that keep
+//! secrets and prompt injections out of what is sent to the model.
 
 #![allow(dead_code)]
 
 pub mod credentials;
 pub mod encryption;
+pub mod field_encryption;
+pub mod guardrails;
 pub mod migration;
 
 pub use credentials::CredentialManager;
+pub use field_encryption::SensitiveSession;
 pub use migration::migrate_plaintext_passwords;
 
+use crate::error::AppError;
+use rusqlite::Connection;
 use std::sync::Mutex;
 
 /// Security error types
@@ -36,46 +42,67 @@ pub enum SecurityError {
 /// Result type for security operations
 pub type Result<T> = std::result::Result<T, SecurityError>;
 
+/// Install the column key of `manager`'s keychain namespace and encrypt
+/// values written before their columns were encrypted; without a keychain,
+/// encrypted columns are stored as plaintext
+pub fn enable_column_encryption(conn: &Connection, manager: &CredentialManager) {
+    match encryption::install_column_key(manager) {
+        Ok(key) => match migration::encrypt_existing_columns(conn, &key) {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Encrypted {} existing column values", count),
+            Err(e) => tracing::warn!("Failed to encrypt existing column values: {}", e),
+        },
+        Err(e) => {
+            encryption::clear_column_key();
+            tracing::warn!("Column encryption unavailable, keychain not accessible: {}", e);
+        }
+    }
+}
+
 // ============================================================================
 // Tauri Commands for Credential Management
 // ============================================================================
 
 /// Set a password in the keychain
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn credentials_set_password(
     manager: tauri::State<'_, Mutex<CredentialManager>>,
     username: String,
     password: String,
-) -> std::result::Result<(), String> {
-    let mgr = manager.lock().map_err(|e| e.to_string())?;
-    mgr.set_password(&username, &password).map_err(|e| e.to_string())
+) -> std::result::Result<(), AppError> {
+    let mgr = manager.lock()?;
+    Ok(mgr.set_password(&username, &password)?)
 }
 
 /// Get a password from the keychain
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn credentials_get_password(
     manager: tauri::State<'_, Mutex<CredentialManager>>,
     username: String,
-) -> std::result::Result<String, String> {
-    let mgr = manager.lock().map_err(|e| e.to_string())?;
-    mgr.get_password(&username).map_err(|e| e.to_string())
+) -> std::result::Result<String, AppError> {
+    let mgr = manager.lock()?;
+    Ok(mgr.get_password(&username)?)
 }
 
 /// Delete a password from the keychain
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn credentials_delete_password(
     manager: tauri::State<'_, Mutex<CredentialManager>>,
     username: String,
-) -> std::result::Result<(), String> {
-    let mgr = manager.lock().map_err(|e| e.to_string())?;
-    mgr.delete_password(&username).map_err(|e| e.to_string())
+) -> std::result::Result<(), AppError> {
+    let mgr = manager.lock()?;
+    Ok(mgr.delete_password(&username)?)
 }
 
 /// Run migration to encrypt plaintext passwords
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn run_migration(
     db: tauri::State<'_, crate::db::DbState>,
-) -> std::result::Result<usize, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    migrate_plaintext_passwords(&conn).map_err(|e| e.to_string())
+) -> std::result::Result<usize, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(migrate_plaintext_passwords(&conn)?)
 }
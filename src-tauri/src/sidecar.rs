@@ -2,11 +2,41 @@
 
 #![allow(dead_code)]
 
+use crate::conversations::generation::GenerationParams;
+use crate::conversations::{compare, system_prompt};
+use crate::db::skill_schema;
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::io::{BufRead, BufReader, Write};
 use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// Prefix of errors returned when the agent runtime exits during a request
+pub const CRASHED_ERROR: &str = "sidecar_crashed";
+
+/// Prefix of errors returned while the agent runtime is being restarted
+pub const RESTARTING_ERROR: &str = "sidecar_restarting";
+
+/// Event carrying a `SidecarHealth` whenever it changes
+pub const HEALTH_EVENT: &str = "sidecar://health";
+
+/// How often the supervisor checks the agent runtime
+const SUPERVISE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Delay before the first restart; doubled after each failed attempt
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between restarts
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Consecutive failed restarts before the supervisor gives up
+const MAX_RESTART_FAILURES: u32 = 8;
+
+/// Uptime after which a restarted process counts as stable again
+const STABLE_AFTER: Duration = Duration::from_secs(60);
 
 /// Agent request
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,6 +45,22 @@ pub struct AgentRequest {
     pub method: String,
     pub params: serde_json::Value,
     pub id: String,
+    /// Request id of the Tauri command making the call, for matching the
+    /// agent runtime's logs with the app's
+    #[serde(rename = "requestId", default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl AgentRequest {
+    pub fn new(method: &str, params: serde_json::Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: uuid::Uuid::new_v4().to_string(),
+            request_id: crate::command_trace::current_request_id(),
+        }
+    }
 }
 
 /// Agent response
@@ -32,11 +78,150 @@ pub struct AgentError {
     pub message: String,
 }
 
+/// Handler for requests the agent runtime sends to the host, such as
+/// `authorize_tool`, while one of its calls is in flight
+pub type HostHandler = Arc<dyn Fn(&str, serde_json::Value) -> Result<serde_json::Value, String> + Send + Sync>;
+
+/// Supervision status of the agent runtime
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarStatus {
+    /// Never started, or shut down on request
+    Stopped,
+    Running,
+    /// Exited unexpectedly; a restart is scheduled
+    Restarting,
+    /// Gave up after too many failed restarts
+    Failed,
+}
+
+/// Health of the agent runtime, for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarHealth {
+    pub status: SidecarStatus,
+    pub pid: Option<u32>,
+    pub started_at: Option<String>,
+    /// Restarts after a crash since the app started
+    pub restarts: u32,
+    /// Crashes and failed restarts since the process was last stable
+    pub consecutive_failures: u32,
+    /// How the last crashed process exited
+    pub last_exit: Option<String>,
+    pub last_error: Option<String>,
+    /// Seconds until the next restart attempt
+    pub next_restart_in_secs: Option<u64>,
+}
+
+/// Crash and restart bookkeeping for the supervised process
+struct Supervisor {
+    status: SidecarStatus,
+    pid: Option<u32>,
+    started: Option<(Instant, chrono::DateTime<chrono::Utc>)>,
+    restarts: u32,
+    failures: u32,
+    last_exit: Option<String>,
+    last_error: Option<String>,
+    next_restart: Option<Instant>,
+}
+
+impl Supervisor {
+    fn new() -> Self {
+        Self {
+            status: SidecarStatus::Stopped,
+            pid: None,
+            started: None,
+            restarts: 0,
+            failures: 0,
+            last_exit: None,
+            last_error: None,
+            next_restart: None,
+        }
+    }
+
+    /// Delay before the restart following `failures` consecutive failures
+    fn backoff(failures: u32) -> Duration {
+        RESTART_BASE_DELAY
+            .saturating_mul(1 << failures.saturating_sub(1).min(16))
+            .min(RESTART_MAX_DELAY)
+    }
+
+    fn started(&mut self, pid: u32, now: Instant) {
+        // Only restarts after a crash keep counting failures
+        if self.status == SidecarStatus::Restarting {
+            self.restarts += 1;
+        } else {
+            self.failures = 0;
+        }
+        self.status = SidecarStatus::Running;
+        self.pid = Some(pid);
+        self.started = Some((now, chrono::Utc::now()));
+        self.next_restart = None;
+    }
+
+    /// Record an unexpected exit and schedule a restart
+    fn crashed(&mut self, exit: String, now: Instant) {
+        if let Some((started, _)) = self.started {
+            if now.duration_since(started) >= STABLE_AFTER {
+                self.failures = 0;
+            }
+        }
+        tracing::warn!("Agent runtime exited unexpectedly: {}", exit);
+        self.last_exit = Some(exit);
+        self.pid = None;
+        self.started = None;
+        self.schedule_restart(now);
+    }
+
+    fn restart_failed(&mut self, error: String, now: Instant) {
+        tracing::warn!("Failed to restart agent runtime: {}", error);
+        self.last_error = Some(error);
+        self.schedule_restart(now);
+    }
+
+    fn schedule_restart(&mut self, now: Instant) {
+        self.failures += 1;
+        if self.failures > MAX_RESTART_FAILURES {
+            self.status = SidecarStatus::Failed;
+            self.next_restart = None;
+        } else {
+            self.status = SidecarStatus::Restarting;
+            self.next_restart = Some(now + Self::backoff(self.failures));
+        }
+    }
+
+    fn stopped(&mut self) {
+        self.status = SidecarStatus::Stopped;
+        self.pid = None;
+        self.started = None;
+        self.failures = 0;
+        self.next_restart = None;
+    }
+
+    fn restart_due(&self, now: Instant) -> bool {
+        self.status == SidecarStatus::Restarting && self.next_restart.is_some_and(|at| now >= at)
+    }
+
+    fn health(&self, now: Instant) -> SidecarHealth {
+        SidecarHealth {
+            status: self.status,
+            pid: self.pid,
+            started_at: self.started.map(|(_, at)| at.to_rfc3339()),
+            restarts: self.restarts,
+            consecutive_failures: self.failures,
+            last_exit: self.last_exit.clone(),
+            last_error: self.last_error.clone(),
+            next_restart_in_secs: self.next_restart.map(|at| at.saturating_duration_since(now).as_secs()),
+        }
+    }
+}
+
 /// Sidecar process wrapper
 pub(crate) struct SidecarProcess {
     child: Child,
     stdin: ChildStdin,
     stdout: BufReader<ChildStdout>,
+    host: Option<HostHandler>,
 }
 
 impl SidecarProcess {
@@ -87,6 +272,7 @@ impl SidecarProcess {
             child,
             stdin,
             stdout: BufReader::new(stdout),
+            host: None,
         })
     }
 
@@ -100,13 +286,73 @@ impl SidecarProcess {
         self.stdin.flush()
             .map_err(|e| format!("Failed to flush stdin: {}", e))?;
 
-        // Read response
-        let mut response_str = String::new();
-        self.stdout.read_line(&mut response_str)
-            .map_err(|e| format!("Failed to read from stdout: {}", e))?;
+        // Read the response, answering host requests sent before it
+        loop {
+            let mut response_str = String::new();
+            let read = self.stdout.read_line(&mut response_str)
+                .map_err(|e| format!("Failed to read from stdout: {}", e))?;
+            if read == 0 {
+                return Err("Agent runtime closed its output".to_string());
+            }
+
+            let message: serde_json::Value = serde_json::from_str(&response_str)
+                .map_err(|e| format!("Failed to parse response: {}", e))?;
+            if message.get("method").is_some() {
+                self.answer_host_request(message)?;
+                continue;
+            }
 
-        serde_json::from_str(&response_str)
-            .map_err(|e| format!("Failed to parse response: {}", e))
+            return serde_json::from_value(message)
+                .map_err(|e| format!("Failed to parse response: {}", e));
+        }
+    }
+
+    /// Run a host request from the agent runtime and write back its reply
+    fn answer_host_request(&mut self, request: serde_json::Value) -> Result<(), String> {
+        let id = request.get("id").cloned().unwrap_or(serde_json::Value::Null);
+        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(serde_json::Value::Null);
+
+        let reply = match &self.host {
+            Some(host) => host(method, params),
+            None => Err(format!("Host method not available: {}", method)),
+        };
+        let response = match reply {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(message) => json!({ "jsonrpc": "2.0", "error": { "code": -32000, "message": message }, "id": id }),
+        };
+
+        writeln!(self.stdin, "{}", response)
+            .map_err(|e| format!("Failed to write to stdin: {}", e))?;
+        self.stdin.flush()
+            .map_err(|e| format!("Failed to flush stdin: {}", e))
+    }
+
+    /// How the process exited, if it has
+    fn exit_status(&mut self) -> Option<String> {
+        match self.child.try_wait() {
+            Ok(Some(status)) => Some(status.to_string()),
+            Ok(None) => None,
+            Err(e) => Some(format!("unknown ({})", e)),
+        }
+    }
+
+    /// Spawn a new agent runtime process and wait until it is ready
+    pub(crate) fn start() -> Result<Self, String> {
+        let mut process = Self::spawn()?;
+        process.wait_for_ready()?;
+        Ok(process)
+    }
+
+    /// Send a JSON-RPC request and return its result
+    pub(crate) fn call(&mut self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String> {
+        let request = AgentRequest::new(method, params);
+
+        let response = self.send_request(&request)?;
+        if let Some(error) = response.error {
+            return Err(format!("{}: {}", error.code, error.message));
+        }
+        Ok(response.result.unwrap_or(serde_json::Value::Null))
     }
 
     fn wait_for_ready(&mut self) -> Result<(), String> {
@@ -137,6 +383,11 @@ impl Drop for SidecarProcess {
 /// Global sidecar state
 pub struct SidecarState {
     process: Mutex<Option<SidecarProcess>>,
+    /// Last `configure_providers` parameters, replayed into new sessions
+    provider_config: Mutex<Option<serde_json::Value>>,
+    /// Answers host requests of the agent runtime
+    host_handler: Mutex<Option<HostHandler>>,
+    supervisor: Mutex<Supervisor>,
 }
 
 unsafe impl Send for SidecarState {}
@@ -145,39 +396,181 @@ impl SidecarState {
     pub fn new() -> Self {
         Self {
             process: Mutex::new(None),
+            provider_config: Mutex::new(None),
+            host_handler: Mutex::new(None),
+            supervisor: Mutex::new(Supervisor::new()),
+        }
+    }
+
+    /// Handle host requests of processes started from now on
+    pub fn set_host_handler(&self, handler: HostHandler) {
+        *self.host_handler.lock().unwrap() = Some(handler);
+    }
+
+    /// Provider configuration last sent to the agent runtime
+    pub fn provider_config(&self) -> Option<serde_json::Value> {
+        self.provider_config.lock().unwrap().clone()
+    }
+
+    /// Drop the provider configuration, including its API keys, from this
+    /// state and from the running process
+    pub fn forget_providers(&self) {
+        *self.provider_config.lock().unwrap() = None;
+        if self.is_initialized() {
+            let empty = json!({ "providers": [], "activeProvider": null });
+            if let Err(e) = self.call("configure_providers", empty) {
+                tracing::warn!("Failed to clear provider configuration: {}", e);
+            }
         }
     }
 
+    /// Type of a configured provider; the active provider's when `name` is None
+    pub fn provider_type(&self, name: Option<&str>) -> Option<String> {
+        let config = self.provider_config()?;
+        let name = name.or(config["activeProvider"].as_str())?;
+        config["providers"]
+            .as_array()?
+            .iter()
+            .find(|p| p["id"].as_str().or(p["type"].as_str()) == Some(name))
+            .and_then(|p| p["type"].as_str())
+            .map(String::from)
+    }
+
     pub fn is_initialized(&self) -> bool {
         self.process.lock().unwrap().is_some()
     }
 
-    pub fn set_initialized(&self, process: SidecarProcess) {
+    /// Install a started process, replaying the last provider configuration
+    pub fn set_initialized(&self, mut process: SidecarProcess) {
+        process.host = self.host_handler.lock().unwrap().clone();
+        if let Some(config) = self.provider_config() {
+            if let Err(e) = process.call("configure_providers", config) {
+                tracing::warn!("Failed to replay provider configuration: {}", e);
+            }
+        }
+        self.supervisor.lock().unwrap().started(process.child.id(), Instant::now());
         *self.process.lock().unwrap() = Some(process);
     }
 
+    /// Stop the process without restarting it
     pub fn reset(&self) {
         *self.process.lock().unwrap() = None;
+        self.supervisor.lock().unwrap().stopped();
     }
 
-    pub fn with_process<F, R>(&self, f: F) -> Result<R, String>
+    /// Run `f` with the process; if the process exits meanwhile, the error
+    /// starts with `CRASHED_ERROR` and a restart is scheduled
+    pub fn with_process<F, R>(&self, f: F) -> Result<R, AppError>
     where
         F: FnOnce(&mut SidecarProcess) -> Result<R, String>,
     {
         let mut guard = self.process.lock().unwrap();
-        let process = guard.as_mut()
-            .ok_or_else(|| "Sidecar not initialized".to_string())?;
-        f(process)
+        let Some(process) = guard.as_mut() else {
+            return Err(AppError::sidecar(match self.supervisor.lock().unwrap().status {
+                SidecarStatus::Restarting => format!("{}: Agent runtime is restarting", RESTARTING_ERROR),
+                _ => "Sidecar not initialized".to_string(),
+            }));
+        };
+
+        let result = f(process);
+        if let Err(e) = &result {
+            if let Some(exit) = process.exit_status() {
+                *guard = None;
+                self.supervisor.lock().unwrap().crashed(exit.clone(), Instant::now());
+                return Err(AppError::sidecar(format!("{}: Agent runtime exited ({}): {}", CRASHED_ERROR, exit, e)));
+            }
+        }
+        result.map_err(AppError::sidecar)
+    }
+
+    /// Current health of the agent runtime
+    pub fn health(&self) -> SidecarHealth {
+        self.supervisor.lock().unwrap().health(Instant::now())
+    }
+
+    /// Notice a process that exited while idle
+    fn detect_exit(&self) {
+        let mut guard = self.process.lock().unwrap();
+        if let Some(exit) = guard.as_mut().and_then(|p| p.exit_status()) {
+            *guard = None;
+            self.supervisor.lock().unwrap().crashed(exit, Instant::now());
+        }
     }
+
+    fn restart_due(&self) -> bool {
+        self.supervisor.lock().unwrap().restart_due(Instant::now())
+    }
+
+    fn restart_failed(&self, error: String) {
+        self.supervisor.lock().unwrap().restart_failed(error, Instant::now());
+    }
+
+    /// Send a JSON-RPC request and return its result
+    pub fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        self.with_process(|process| process.call(method, params))
+    }
+}
+
+/// Watch the agent runtime, restarting it with exponential backoff when it
+/// exits unexpectedly and emitting `HEALTH_EVENT` on every change
+pub fn supervise(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_key = None;
+        loop {
+            std::thread::sleep(SUPERVISE_INTERVAL);
+            let state = app.state::<Mutex<SidecarState>>();
+
+            // A held lock means a request is running, which notices crashes itself
+            let restart = match state.try_lock() {
+                Ok(guard) => {
+                    guard.detect_exit();
+                    guard.restart_due()
+                }
+                Err(_) => continue,
+            };
+
+            if restart {
+                // Start outside the lock; commands fail fast meanwhile
+                let started = SidecarProcess::start();
+                let Ok(guard) = state.lock() else { continue };
+                match started {
+                    // Another caller may have started a process meanwhile
+                    Ok(_) if guard.is_initialized() => {}
+                    Ok(process) => {
+                        tracing::info!("Agent runtime restarted");
+                        guard.set_initialized(process);
+                    }
+                    Err(e) => guard.restart_failed(e),
+                }
+            }
+
+            let Ok(guard) = state.try_lock() else { continue };
+            let health = guard.health();
+            drop(guard);
+            let key = (health.status, health.pid, health.restarts, health.consecutive_failures);
+            if last_key != Some(key) {
+                last_key = Some(key);
+                let _ = app.emit(HEALTH_EVENT, &health);
+            }
+        }
+    });
+}
+
+/// Health of the agent runtime
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn sidecar_health(state: tauri::State<'_, Mutex<SidecarState>>) -> Result<SidecarHealth, AppError> {
+    let state_guard = state.lock()?;
+    Ok(state_guard.health())
 }
 
 /// Initialize the agent runtime (sidecar)
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn init_agent(
     state: tauri::State<'_, Mutex<SidecarState>>,
-) -> Result<String, String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+) -> Result<String, AppError> {
+    let state_guard = state.lock()?;
 
     if state_guard.is_initialized() {
         return Ok("Agent already initialized".to_string());
@@ -186,12 +579,11 @@ pub async fn init_agent(
     drop(state_guard);
 
     // Spawn and initialize the sidecar process
-    let mut process = SidecarProcess::spawn()?;
-    process.wait_for_ready()?;
+    let mut process = SidecarProcess::spawn().map_err(AppError::sidecar)?;
+    process.wait_for_ready().map_err(AppError::sidecar)?;
 
     // Store the process
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    let state_guard = state.lock()?;
 
     if state_guard.is_initialized() {
         return Ok("Agent already initialized".to_string());
@@ -202,17 +594,76 @@ pub async fn init_agent(
     Ok("Agent initialized".to_string())
 }
 
+/// Provider options of a chat request, with the generation parameters to
+/// validate and add to them; the conversation's system prompt and its
+/// workspace defaults are applied to `messages`
+fn chat_options(
+    conn: &rusqlite::Connection,
+    conversation_id: Option<&str>,
+    messages: &mut Vec<super::Message>,
+    provider: Option<String>,
+    params: Option<GenerationParams>,
+) -> Result<(serde_json::Value, GenerationParams), String> {
+    let mut conversation = GenerationParams::default();
+    if let Some(id) = conversation_id {
+        system_prompt::inject(conn, id, messages)?;
+        conversation = crate::conversations::generation::conversation_defaults(conn, id)?;
+    }
+    let options = match crate::workspaces::chat_config(conn, conversation_id)? {
+        Some(config) => config.apply(messages, provider),
+        None => json!({ "provider": provider }),
+    };
+    let workspace = GenerationParams {
+        temperature: options["temperature"].as_f64().map(|t| t as f32),
+        ..Default::default()
+    };
+    let params = params.unwrap_or_default().or(conversation).or(workspace);
+    Ok((options, params))
+}
+
 /// Send request to agent runtime
+///
+/// Messages are screened by the guardrails first: secrets and prompt
+/// injections are redacted, or the request is refused when the policy of
+/// `conversation_id` blocks them. The conversation's system prompt and
+/// workspace defaults are applied next.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn agent_chat(
     state: tauri::State<'_, Mutex<SidecarState>>,
-    messages: Vec<super::Message>,
+    db: tauri::State<'_, crate::db::DbState>,
+    mut messages: Vec<super::Message>,
     provider: Option<String>,
-) -> Result<super::ChatResponse, String> {
+    conversation_id: Option<String>,
+    params: Option<GenerationParams>,
+) -> Result<super::ChatResponse, AppError> {
+    let (mut options, params) = {
+        let conn = db.conn.lock()?;
+        let screened = crate::security::guardrails::screen_messages(&conn, conversation_id.as_deref(), &mut messages)?;
+        if let Err(reason) = screened {
+            return Ok(super::ChatResponse {
+                content: String::new(),
+                error: Some(reason),
+            });
+        }
+        chat_options(&conn, conversation_id.as_deref(), &mut messages, provider, params)?
+    };
+
+    let provider_type = {
+        let state_guard = state.lock()?;
+        state_guard.provider_type(options["provider"].as_str())
+    };
+    if let Err(e) = params.validate(provider_type.as_deref()) {
+        return Ok(super::ChatResponse {
+            content: String::new(),
+            error: Some(e.to_string()),
+        });
+    }
+    params.write_options(&mut options);
+
     // Auto-initialize if not already initialized
     {
-        let state_guard = state.lock()
-            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let state_guard = state.lock()?;
 
         if !state_guard.is_initialized() {
             drop(state_guard);
@@ -237,26 +688,18 @@ pub async fn agent_chat(
                 });
             }
 
-            let state_guard = state.lock()
-                .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            let state_guard = state.lock()?;
             state_guard.set_initialized(process);
         }
     }
 
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    let state_guard = state.lock()?;
 
-    let request = AgentRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "chat".to_string(),
-        params: json!({
-            "messages": messages,
-            "options": {
-                "provider": provider
-            }
-        }),
-        id: uuid::Uuid::new_v4().to_string(),
-    };
+    let request = AgentRequest::new("chat", json!({
+        "messages": messages,
+        "options": options,
+        "conversationId": conversation_id
+    }));
 
     let response = state_guard.with_process(|process| process.send_request(&request))?;
 
@@ -280,82 +723,152 @@ pub async fn agent_chat(
     })
 }
 
-/// Get available tools from agent
+/// Send the same messages to several providers concurrently
+///
+/// Messages are screened like `agent_chat`. With a `conversation_id`, the
+/// answers are stored there as one multi-response message whose content
+/// is the fastest answer until another is selected. Providers that do not
+/// accept the generation parameters are not asked and report why.
 #[tauri::command]
-pub async fn get_tools(
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
+pub async fn compare_chat(
     state: tauri::State<'_, Mutex<SidecarState>>,
-) -> Result<Vec<serde_json::Value>, String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
-
-    let request = AgentRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "get_tools".to_string(),
-        params: json!({}),
-        id: uuid::Uuid::new_v4().to_string(),
+    db: tauri::State<'_, crate::db::DbState>,
+    session: tauri::State<'_, Mutex<crate::security::SensitiveSession>>,
+    credentials: tauri::State<'_, Mutex<crate::security::CredentialManager>>,
+    mut messages: Vec<super::Message>,
+    providers: Vec<String>,
+    conversation_id: Option<String>,
+    params: Option<GenerationParams>,
+) -> Result<compare::Comparison, AppError> {
+    let providers = compare::normalize_providers(providers)?;
+    let (mut options, params) = {
+        let conn = db.conn.lock()?;
+        let screened = crate::security::guardrails::screen_messages(&conn, conversation_id.as_deref(), &mut messages)?;
+        screened?;
+        chat_options(&conn, conversation_id.as_deref(), &mut messages, None, params)?
+    };
+    params.write_options(&mut options);
+
+    let state_guard = state.lock()?;
+    let mut rejected = std::collections::BTreeMap::new();
+    let accepted: Vec<String> = providers
+        .into_iter()
+        .filter(|name| match params.validate(state_guard.provider_type(Some(name)).as_deref()) {
+            Ok(()) => true,
+            Err(e) => {
+                rejected.insert(name.clone(), compare::ComparedResponse { error: Some(e.to_string()), ..Default::default() });
+                false
+            }
+        })
+        .collect();
+
+    let mut results = if accepted.is_empty() {
+        std::collections::BTreeMap::new()
+    } else {
+        let result = state_guard.call("compare_chat", json!({
+            "messages": messages,
+            "providers": accepted,
+            "options": options,
+            "conversationId": conversation_id
+        }))?;
+        compare::parse_results(&result, &accepted)
     };
+    drop(state_guard);
+    results.append(&mut rejected);
+
+    let Some(conversation_id) = conversation_id else {
+        let selected = compare::default_selection(&results);
+        return Ok(compare::Comparison { message_id: None, results, selected });
+    };
+    let conn = db.conn.lock()?;
+    let key = crate::db::conversation_key(&conn, &session, &credentials, &conversation_id)?;
+    Ok(compare::store(&conn, key.as_ref(), &conversation_id, results)?)
+}
+
+/// Get available tools from agent, each with its category and policy decision
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn get_tools(
+    state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, crate::db::DbState>,
+    plugin_tools: tauri::State<'_, crate::plugins::tools::PluginToolRunner>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let state_guard = state.lock()?;
+
+    let request = AgentRequest::new("get_tools", json!({}));
 
     let response = state_guard.with_process(|process| process.send_request(&request))?;
 
     if let Some(error) = response.error {
-        return Err(format!("{}: {}", error.code, error.message));
+        return Err(AppError::sidecar(format!("{}: {}", error.code, error.message)));
     }
 
-    let tools = response.result
+    let mut tools = response.result
         .as_ref()
         .and_then(|r| r.get("tools"))
         .and_then(|t| t.as_array())
         .cloned()
         .unwrap_or_default();
 
+    // Tools declared by running plugins
+    for tool in plugin_tools.list()? {
+        tools.push(serde_json::to_value(tool)?);
+    }
+
+    let conn = db.conn.lock()?;
+    for tool in &mut tools {
+        let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let policy = crate::tools::policy::get_policy(&conn, name)?;
+        if let Some(tool) = tool.as_object_mut() {
+            tool.insert("category".to_string(), json!(policy.category));
+            tool.insert("policy".to_string(), json!(policy.decision));
+        }
+    }
+
     Ok(tools)
 }
 
 /// Configure providers
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn configure_providers(
     state: tauri::State<'_, Mutex<SidecarState>>,
     providers: Vec<serde_json::Value>,
     active_provider: Option<String>,
-) -> Result<String, String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
-
-    let request = AgentRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "configure_providers".to_string(),
-        params: json!({
-            "providers": providers,
-            "activeProvider": active_provider
-        }),
-        id: uuid::Uuid::new_v4().to_string(),
-    };
+) -> Result<String, AppError> {
+    let state_guard = state.lock()?;
+
+    let params = json!({
+        "providers": providers,
+        "activeProvider": active_provider
+    });
+    let request = AgentRequest::new("configure_providers", params.clone());
 
     let response = state_guard.with_process(|process| process.send_request(&request))?;
 
     if let Some(error) = response.error {
-        return Err(format!("{}: {}", error.code, error.message));
+        return Err(AppError::sidecar(format!("{}: {}", error.code, error.message)));
     }
 
+    *state_guard.provider_config.lock().unwrap() = Some(params);
     Ok("Providers configured".to_string())
 }
 
 /// Shutdown agent runtime
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn shutdown_agent(
     state: tauri::State<'_, Mutex<SidecarState>>,
-) -> Result<(), String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+) -> Result<(), AppError> {
+    let state_guard = state.lock()?;
 
     // Send shutdown request if process exists
     if state_guard.is_initialized() {
-        let request = AgentRequest {
-            jsonrpc: "2.0".to_string(),
-            method: "shutdown".to_string(),
-            params: json!({}),
-            id: uuid::Uuid::new_v4().to_string(),
-        };
+        let request = AgentRequest::new("shutdown", json!({}));
 
         let _ = state_guard.with_process(|process| process.send_request(&request));
     }
@@ -366,30 +879,31 @@ pub async fn shutdown_agent(
 
 /// Execute a recipe via agent runtime
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn execute_recipe(
     state: tauri::State<'_, Mutex<SidecarState>>,
     recipe_id: String,
     steps: Vec<serde_json::Value>,
     variables: Option<serde_json::Value>,
-) -> Result<String, String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
-
-    let request = AgentRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "execute_recipe".to_string(),
-        params: json!({
-            "recipeId": recipe_id,
-            "steps": steps,
-            "variables": variables
-        }),
-        id: uuid::Uuid::new_v4().to_string(),
-    };
+) -> Result<String, AppError> {
+    let state_guard = state.lock()?;
+
+    let request = AgentRequest::new("execute_recipe", json!({
+        "recipeId": recipe_id,
+        "steps": steps,
+        "variables": variables
+    }));
 
     let response = state_guard.with_process(|process| process.send_request(&request))?;
 
     if let Some(error) = response.error {
-        return Err(format!("{}: {}", error.code, error.message));
+        return Err(AppError::sidecar(format!("{}: {}", error.code, error.message)));
+    }
+
+    // A failed step fails the recipe unless the step continues on error
+    if let Some(result) = response.result.as_ref().filter(|r| r.get("success") == Some(&json!(false))) {
+        let error = result.get("error").and_then(|e| e.as_str()).unwrap_or("Recipe failed");
+        return Err(AppError::sidecar(error.to_string()));
     }
 
     let result = response.result
@@ -401,67 +915,78 @@ pub async fn execute_recipe(
     Ok(result.to_string())
 }
 
-/// Execute a skill via agent runtime
-#[tauri::command]
-pub async fn execute_skill(
-    state: tauri::State<'_, Mutex<SidecarState>>,
-    skill_id: String,
-    prompt: String,
-    input: String,
+/// Run a skill through the agent runtime, checking `input` and the model's
+/// reply against the skill's schemas
+pub(crate) fn run_skill(
+    state: &SidecarState,
+    schemas: &skill_schema::SkillSchemas,
+    skill_id: &str,
+    prompt: &str,
+    input: &str,
 ) -> Result<String, String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    if let Some(schema) = &schemas.input {
+        skill_schema::check_input(schema, input)?;
+    }
 
-    let request = AgentRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "execute_skill".to_string(),
-        params: json!({
+    let response = state.call(
+        "execute_skill",
+        json!({
             "skillId": skill_id,
             "prompt": prompt,
-            "input": input
+            "input": input,
+            "outputSchema": schemas.output
         }),
-        id: uuid::Uuid::new_v4().to_string(),
-    };
-
-    let response = state_guard.with_process(|process| process.send_request(&request))?;
+    )?;
+    let result = response.get("result").and_then(|r| r.as_str()).unwrap_or("");
 
-    if let Some(error) = response.error {
-        return Err(format!("{}: {}", error.code, error.message));
+    if let Some(schema) = &schemas.output {
+        return skill_schema::check_output(schema, result).map(|value| value.to_string());
     }
+    Ok(result.to_string())
+}
 
-    let result = response.result
-        .as_ref()
-        .and_then(|r| r.get("result"))
-        .and_then(|r| r.as_str())
-        .unwrap_or("");
+/// Execute a skill via agent runtime
+///
+/// When the skill declares an input schema, `input` must be JSON matching
+/// it; when it declares an output schema, the model's reply must be JSON
+/// matching it.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn execute_skill(
+    state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, crate::db::DbState>,
+    skill_id: String,
+    prompt: String,
+    input: String,
+) -> Result<String, AppError> {
+    let schemas = {
+        let conn = db.conn.lock()?;
+        skill_schema::load(&conn, &skill_id)?
+    };
 
-    Ok(result.to_string())
+    let state_guard = state.lock()?;
+    Ok(run_skill(&state_guard, &schemas, &skill_id, &prompt, &input)?)
 }
 
 /// Execute a prompt via agent runtime
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn execute_prompt(
     state: tauri::State<'_, Mutex<SidecarState>>,
     prompt: String,
     context: Option<serde_json::Value>,
-) -> Result<String, String> {
-    let state_guard = state.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+) -> Result<String, AppError> {
+    let state_guard = state.lock()?;
 
-    let request = AgentRequest {
-        jsonrpc: "2.0".to_string(),
-        method: "execute_prompt".to_string(),
-        params: json!({
-            "prompt": prompt,
-            "context": context
-        }),
-        id: uuid::Uuid::new_v4().to_string(),
-    };
+    let request = AgentRequest::new("execute_prompt", json!({
+        "prompt": prompt,
+        "context": context
+    }));
 
     let response = state_guard.with_process(|process| process.send_request(&request))?;
 
     if let Some(error) = response.error {
-        return Err(format!("{}: {}", error.code, error.message));
+        return Err(AppError::sidecar(format!("{}: {}", error.code, error.message)));
     }
 
     let result = response.result
@@ -489,16 +1014,19 @@ pub struct VoiceCommandResult {
 
 /// Execute a voice command via agent runtime
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn execute_voice_command(
     state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, crate::db::DbState>,
     transcript: String,
     language: Option<String>,
-) -> Result<VoiceCommandResult, String> {
+) -> Result<VoiceCommandResult, AppError> {
     use crate::voice::{VoiceAction, ParsedVoiceCommand};
-    use crate::voice::commands::parse_voice_command;
+    use crate::voice::commands::{detect_script_language, parse_transcript};
 
     // Parse the voice command
-    let parsed: ParsedVoiceCommand = parse_voice_command(transcript.clone(), language)?;
+    let language = language.unwrap_or_else(|| detect_script_language(&transcript).unwrap_or("en").to_string());
+    let parsed: ParsedVoiceCommand = parse_transcript(&transcript, &language);
 
     let action_type = match &parsed.action {
         VoiceAction::ExecuteSkill { .. } => "execute_skill",
@@ -516,6 +1044,7 @@ pub async fn execute_voice_command(
             let skill_id = format!("skill-{}", skill_name.to_lowercase().replace(' ', "-"));
             execute_skill(
                 state,
+                db,
                 skill_id,
                 format!("Execute skill: {}", skill_name),
                 transcript.clone(),
@@ -535,11 +1064,14 @@ pub async fn execute_voice_command(
             // Send as a chat message
             agent_chat(
                 state,
+                db,
                 vec![super::Message {
                     role: "user".to_string(),
                     content: content.clone(),
                 }],
                 None,
+                None,
+                None,
             ).await.map(|r| r.content)?
         }
         VoiceAction::OpenFeature { feature } => {
@@ -548,22 +1080,28 @@ pub async fn execute_voice_command(
         VoiceAction::Search { query } => {
             agent_chat(
                 state,
+                db,
                 vec![super::Message {
                     role: "user".to_string(),
                     content: format!("Search for: {}", query),
                 }],
                 None,
+                None,
+                None,
             ).await.map(|r| r.content)?
         }
         VoiceAction::Unknown => {
             // Unknown command - treat as chat message
             agent_chat(
                 state,
+                db,
                 vec![super::Message {
                     role: "user".to_string(),
                     content: transcript.clone(),
                 }],
                 None,
+                None,
+                None,
             ).await.map(|r| r.content)?
         }
     };
@@ -578,38 +1116,64 @@ pub async fn execute_voice_command(
 }
 
 /// Start a voice conversation session (multi-turn)
+///
+/// While the session is active, talking over speech playback stops it and
+/// emits `voice://barge-in` (see `voice::barge_in`).
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn start_voice_conversation(
+    app: tauri::AppHandle,
     _state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, crate::db::DbState>,
+    player: tauri::State<'_, Mutex<crate::voice::playback::TtsPlayer>>,
+    barge_in: tauri::State<'_, Mutex<crate::voice::barge_in::BargeInMonitor>>,
     language: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     // Initialize a conversation session
     let session_id = uuid::Uuid::new_v4().to_string();
 
     // In production, this would store the session in a database
     tracing::info!("Started voice conversation session: {} (language: {})", session_id, language);
 
+    let settings = {
+        let conn = db.conn.lock()?;
+        crate::voice::barge_in::load_settings(&conn)
+    };
+    if settings.enabled {
+        let tap = player.lock()?.tap();
+        let mut monitor = barge_in.lock()?;
+        // The conversation still works without a microphone, just without barge-in
+        if let Err(e) = monitor.start(app, &session_id, settings, tap) {
+            tracing::warn!("Barge-in unavailable for voice session {}: {}", session_id, e);
+        }
+    }
+
     Ok(session_id)
 }
 
 /// Continue a voice conversation (multi-turn)
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn continue_voice_conversation(
     state: tauri::State<'_, Mutex<SidecarState>>,
+    db: tauri::State<'_, crate::db::DbState>,
     session_id: String,
     transcript: String,
-) -> Result<VoiceCommandResult, String> {
+) -> Result<VoiceCommandResult, AppError> {
     // In production, this would load conversation history from the database
     tracing::info!("Continuing voice conversation: {}", session_id);
 
     // For now, treat as a chat message with conversation context
     let response = agent_chat(
         state,
+        db,
         vec![super::Message {
             role: "user".to_string(),
             content: transcript.clone(),
         }],
         None,
+        None,
+        None,
     ).await?;
 
     Ok(VoiceCommandResult {
@@ -623,10 +1187,59 @@ pub async fn continue_voice_conversation(
 
 /// End a voice conversation session
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn end_voice_conversation(
-    _session_id: String,
-) -> Result<(), String> {
+    barge_in: tauri::State<'_, Mutex<crate::voice::barge_in::BargeInMonitor>>,
+    session_id: String,
+) -> Result<(), AppError> {
     // In production, this would save the conversation history
-    tracing::info!("Ended voice conversation session: {}", _session_id);
+    tracing::info!("Ended voice conversation session: {}", session_id);
+
+    let mut monitor = barge_in.lock()?;
+    if monitor.session_id() == Some(session_id.as_str()) {
+        monitor.stop();
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_backoff() {
+        let delays: Vec<u64> = (1..=8).map(|n| Supervisor::backoff(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[test]
+    fn test_supervisor_restarts_then_gives_up() {
+        let now = Instant::now();
+        let mut supervisor = Supervisor::new();
+        supervisor.started(1, now);
+        assert_eq!(supervisor.health(now).status, SidecarStatus::Running);
+
+        supervisor.crashed("exit status: 1".to_string(), now);
+        assert_eq!(supervisor.status, SidecarStatus::Restarting);
+        assert!(!supervisor.restart_due(now));
+        assert!(supervisor.restart_due(now + Duration::from_secs(1)));
+
+        supervisor.started(2, now);
+        assert_eq!((supervisor.restarts, supervisor.failures), (1, 1));
+
+        // A crash after a stable run starts the backoff over
+        supervisor.crashed("exit status: 1".to_string(), now + STABLE_AFTER);
+        assert_eq!(supervisor.failures, 1);
+
+        for _ in 0..MAX_RESTART_FAILURES {
+            supervisor.restart_failed("spawn failed".to_string(), now);
+        }
+        let health = supervisor.health(now);
+        assert_eq!(health.status, SidecarStatus::Failed);
+        assert_eq!(health.next_restart_in_secs, None);
+        assert!(!supervisor.restart_due(now + RESTART_MAX_DELAY));
+
+        supervisor.started(3, now);
+        assert_eq!((supervisor.status, supervisor.failures), (SidecarStatus::Running, 0));
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! Commands for cloud synchronization, conflict resolution, and offline queue management.
 
+use crate::error::AppError;
 use tauri::State;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::manager::{SyncManager, SyncEntity, SyncOperation, SyncResult, CloudProvider};
-use super::conflict::{ConflictResolver, ConflictStrategy, SyncConflict, ConflictResolution};
+use super::engine::{self, SyncStatus};
+use crate::integration::cloud::CloudStorageConfig;
+use super::conflict::{
+    ConflictResolver, ConflictStrategy, SyncConflict, ConflictResolution, ResolutionStrategy,
+    StoredConflict,
+};
 use super::offline::{OfflineQueue, PendingOperation};
 
 /// Mock cloud provider for testing
@@ -58,6 +64,13 @@ impl SyncState {
             conflict_resolver: Arc::new(RwLock::new(ConflictResolver::new(ConflictStrategy::Manual))),
         }
     }
+
+    /// Drop the provider, queued operations and pending conflicts
+    pub async fn reset(&self) {
+        *self.manager.write().await = SyncManager::new(None);
+        *self.offline_queue.write().await = OfflineQueue::new();
+        *self.conflict_resolver.write().await = ConflictResolver::new(ConflictStrategy::Manual);
+    }
 }
 
 impl Default for SyncState {
@@ -70,23 +83,83 @@ impl Default for SyncState {
 // Sync Manager Commands
 // ============================================================================
 
-/// Perform sync now
+/// Sync settings, templates and conversations with the configured storage
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_now(
-    state: State<'_, Arc<SyncState>>,
-) -> Result<SyncResult, String> {
-    let manager = state.manager.read().await;
-    Ok(manager.sync_now().await)
+    db: State<'_, crate::db::DbState>,
+    s3: State<'_, crate::integration::cloud::s3::S3Manager>,
+    credentials: State<'_, std::sync::Mutex<crate::security::CredentialManager>>,
+) -> Result<SyncResult, AppError> {
+    let config = {
+        let conn = db.conn.lock()?;
+        engine::storage_config(&conn)?.ok_or_else(|| AppError::invalid_input("Sync is not configured"))?
+    };
+    let config = {
+        let credentials = credentials.lock()?;
+        crate::integration::cloud::storage::resolve_credentials(config, &credentials)
+    };
+    let storage = crate::integration::cloud::storage::open_storage(&config, &s3).await?;
+
+    let result = engine::sync(storage.as_ref(), &db.conn).await;
+    let conn = db.conn.lock()?;
+    engine::record_outcome(&conn, &result)?;
+    Ok(result)
+}
+
+/// Get sync configuration, device id and pending change count
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn sync_status(db: State<'_, crate::db::DbState>) -> Result<SyncStatus, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(engine::status(&conn)?)
+}
+
+/// Enable sync against a cloud storage, or disable it when `config` is empty
+///
+/// A secret in the config is moved to the keychain.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn sync_configure(
+    db: State<'_, crate::db::DbState>,
+    credentials: State<'_, std::sync::Mutex<crate::security::CredentialManager>>,
+    config: Option<CloudStorageConfig>,
+) -> Result<SyncStatus, AppError> {
+    let config = match config {
+        Some(config) => {
+            let credentials = credentials.lock()?;
+            if let Some(secret) = config
+                .secret_access_key
+                .as_deref()
+                .filter(|s| !s.is_empty())
+            {
+                credentials.set_password(
+                    &crate::integration::cloud::storage::credential_key(&config.name),
+                    secret,
+                )?;
+            }
+            Some(crate::integration::cloud::storage::resolve_credentials(
+                config,
+                &credentials,
+            ))
+        }
+        None => None,
+    };
+
+    let conn = db.conn.lock()?;
+    engine::configure(&conn, config.as_ref())?;
+    Ok(engine::status(&conn)?)
 }
 
 /// Queue an upload operation
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_queue_upload(
     state: State<'_, Arc<SyncState>>,
     entity: String,
     id: String,
     data: Vec<u8>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let manager = state.manager.read().await;
 
     let sync_entity = match entity.as_str() {
@@ -95,7 +168,7 @@ pub async fn sync_queue_upload(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     manager.queue_upload(sync_entity, id, data).await;
@@ -104,11 +177,12 @@ pub async fn sync_queue_upload(
 
 /// Queue a download operation
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_queue_download(
     state: State<'_, Arc<SyncState>>,
     entity: String,
     id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let manager = state.manager.read().await;
 
     let sync_entity = match entity.as_str() {
@@ -117,7 +191,7 @@ pub async fn sync_queue_download(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     manager.queue_download(sync_entity, id).await;
@@ -126,11 +200,12 @@ pub async fn sync_queue_download(
 
 /// Queue a delete operation
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_queue_delete(
     state: State<'_, Arc<SyncState>>,
     entity: String,
     id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let manager = state.manager.read().await;
 
     let sync_entity = match entity.as_str() {
@@ -139,7 +214,7 @@ pub async fn sync_queue_delete(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     manager.queue_delete(sync_entity, id).await;
@@ -148,27 +223,30 @@ pub async fn sync_queue_delete(
 
 /// Get pending operation count
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_pending_count(
     state: State<'_, Arc<SyncState>>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let manager = state.manager.read().await;
     Ok(manager.pending_count().await)
 }
 
 /// Check if sync is needed
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_needs_sync(
     state: State<'_, Arc<SyncState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let manager = state.manager.read().await;
     Ok(manager.needs_sync().await)
 }
 
 /// Clear pending operations
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_clear_pending(
     state: State<'_, Arc<SyncState>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let manager = state.manager.read().await;
     manager.clear_pending().await;
     Ok(())
@@ -176,10 +254,11 @@ pub async fn sync_clear_pending(
 
 /// Set conflict resolution strategy
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_set_conflict_strategy(
     state: State<'_, Arc<SyncState>>,
     strategy: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut manager = state.manager.write().await;
 
     let conflict_strategy = match strategy.as_str() {
@@ -187,7 +266,7 @@ pub async fn sync_set_conflict_strategy(
         "server_wins" => ConflictStrategy::ServerWins,
         "merge" => ConflictStrategy::Merge,
         "manual" => ConflictStrategy::Manual,
-        _ => return Err("Invalid conflict strategy".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid conflict strategy")),
     };
 
     manager.set_conflict_strategy(conflict_strategy);
@@ -200,13 +279,14 @@ pub async fn sync_set_conflict_strategy(
 
 /// Detect a conflict
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_detect_conflict(
     state: State<'_, Arc<SyncState>>,
     entity: String,
     id: String,
     local_version: String,
     remote_version: String,
-) -> Result<Option<SyncConflict>, String> {
+) -> Result<Option<SyncConflict>, AppError> {
     let resolver = state.conflict_resolver.read().await;
 
     let sync_entity = match entity.as_str() {
@@ -215,7 +295,7 @@ pub async fn sync_detect_conflict(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     Ok(resolver.detect(sync_entity, &id, &local_version, &remote_version))
@@ -223,6 +303,7 @@ pub async fn sync_detect_conflict(
 
 /// Resolve a conflict
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_resolve_conflict(
     state: State<'_, Arc<SyncState>>,
     entity: String,
@@ -232,7 +313,7 @@ pub async fn sync_resolve_conflict(
     local_data: Vec<u8>,
     remote_data: Vec<u8>,
     resolution: String,
-) -> Result<ConflictResolution, String> {
+) -> Result<ConflictResolution, AppError> {
     let resolver = state.conflict_resolver.read().await;
 
     let sync_entity = match entity.as_str() {
@@ -241,7 +322,7 @@ pub async fn sync_resolve_conflict(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     let mut conflict = SyncConflict {
@@ -259,19 +340,45 @@ pub async fn sync_resolve_conflict(
         conflict.resolution = Some(match resolution.as_str() {
             "keep_local" => ConflictResolution::KeepLocal,
             "keep_remote" => ConflictResolution::KeepRemote,
-            _ => return Err("Invalid resolution type".to_string()),
+            _ => return Err(AppError::invalid_input("Invalid resolution type")),
         });
     }
 
     Ok(resolver.resolve(&mut conflict))
 }
 
+/// List sync conflicts awaiting resolution
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_sync_conflicts(
+    db: State<'_, crate::db::DbState>,
+    include_resolved: Option<bool>,
+) -> Result<Vec<StoredConflict>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(super::conflict::list_conflicts(&conn, include_resolved.unwrap_or(false))?)
+}
+
+/// Resolve a sync conflict by keeping the `local` or `remote` version, or a
+/// `merge` of both
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn resolve_conflict(
+    db: State<'_, crate::db::DbState>,
+    id: String,
+    strategy: String,
+) -> Result<StoredConflict, AppError> {
+    let strategy = ResolutionStrategy::parse(&strategy)?;
+    let conn = db.conn.lock()?;
+    Ok(engine::resolve_conflict(&conn, &id, strategy)?)
+}
+
 // ============================================================================
 // Offline Queue Commands
 // ============================================================================
 
 /// Push an operation to the offline queue
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_push(
     state: State<'_, Arc<SyncState>>,
     id: String,
@@ -279,7 +386,7 @@ pub async fn sync_offline_push(
     entity_id: String,
     operation: String,
     data: Option<Vec<u8>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut queue = state.offline_queue.write().await;
 
     let sync_entity = match entity.as_str() {
@@ -288,14 +395,14 @@ pub async fn sync_offline_push(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     let sync_operation = match operation.as_str() {
         "upload" => SyncOperation::Upload,
         "download" => SyncOperation::Download,
         "delete" => SyncOperation::Delete,
-        _ => return Err("Invalid operation type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid operation type")),
     };
 
     let pending = PendingOperation {
@@ -311,34 +418,37 @@ pub async fn sync_offline_push(
         last_attempt: None,
     };
 
-    queue.push(pending)
+    Ok(queue.push(pending)?)
 }
 
 /// Pop the next ready operation from the offline queue
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_pop_ready(
     state: State<'_, Arc<SyncState>>,
-) -> Result<Option<PendingOperation>, String> {
+) -> Result<Option<PendingOperation>, AppError> {
     let mut queue = state.offline_queue.write().await;
     Ok(queue.pop_ready())
 }
 
 /// Peek at the next operation without removing it
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_peek(
     state: State<'_, Arc<SyncState>>,
-) -> Result<Option<PendingOperation>, String> {
+) -> Result<Option<PendingOperation>, AppError> {
     let queue = state.offline_queue.read().await;
     Ok(queue.peek().cloned())
 }
 
 /// Mark an operation as failed
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_mark_failed(
     state: State<'_, Arc<SyncState>>,
     operation: PendingOperation,
     error: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut queue = state.offline_queue.write().await;
     queue.mark_failed(operation, error);
     Ok(())
@@ -346,18 +456,20 @@ pub async fn sync_offline_mark_failed(
 
 /// Get offline queue length
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_length(
     state: State<'_, Arc<SyncState>>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let queue = state.offline_queue.read().await;
     Ok(queue.len())
 }
 
 /// Clear the offline queue
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_clear(
     state: State<'_, Arc<SyncState>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut queue = state.offline_queue.write().await;
     queue.clear();
     Ok(())
@@ -365,19 +477,21 @@ pub async fn sync_offline_clear(
 
 /// Get failed operations from the offline queue
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_get_failed(
     state: State<'_, Arc<SyncState>>,
-) -> Result<Vec<PendingOperation>, String> {
+) -> Result<Vec<PendingOperation>, AppError> {
     let queue = state.offline_queue.read().await;
     Ok(queue.get_failed().into_iter().cloned().collect())
 }
 
 /// Get operations by entity type
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn sync_offline_get_by_entity(
     state: State<'_, Arc<SyncState>>,
     entity: String,
-) -> Result<Vec<PendingOperation>, String> {
+) -> Result<Vec<PendingOperation>, AppError> {
     let queue = state.offline_queue.read().await;
 
     let sync_entity = match entity.as_str() {
@@ -386,7 +500,7 @@ pub async fn sync_offline_get_by_entity(
         "template" => SyncEntity::Template,
         "skill" => SyncEntity::Skill,
         "recipe" => SyncEntity::Recipe,
-        _ => return Err("Invalid entity type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid entity type")),
     };
 
     Ok(queue.get_by_entity(&sync_entity).into_iter().cloned().collect())
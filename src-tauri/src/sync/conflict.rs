@@ -1,9 +1,13 @@
 //! Conflict Resolution
 //! 
 //! Handles conflicts between local and remote data during sync.
+//!
+//! Conflicts found by the sync engine are stored in `sync_conflicts` until
+//! the user keeps the local version, takes the remote one, or merges them.
 
 use serde::{Deserialize, Serialize};
 use super::manager::SyncEntity;
+use rusqlite::{params, Connection, OptionalExtension};
 
 /// Conflict resolution strategy
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -133,6 +137,232 @@ impl ConflictResolver {
     }
 }
 
+/// How a stored conflict is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolutionStrategy {
+    /// Keep this device's version and upload it
+    Local,
+    /// Replace the local version with the other device's
+    Remote,
+    /// Combine both versions and upload the result
+    Merge,
+}
+
+impl ResolutionStrategy {
+    pub fn parse(strategy: &str) -> Result<Self, String> {
+        match strategy {
+            "local" => Ok(Self::Local),
+            "remote" => Ok(Self::Remote),
+            "merge" => Ok(Self::Merge),
+            _ => Err(format!("Invalid conflict strategy: {}", strategy)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Remote => "remote",
+            Self::Merge => "merge",
+        }
+    }
+}
+
+/// Conflict recorded in `sync_conflicts`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredConflict {
+    pub id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    /// When the entity last changed locally
+    pub local_version: String,
+    /// When the other device wrote its version
+    pub remote_version: String,
+    pub remote_etag: String,
+    pub remote_device_id: String,
+    /// `None` when the entity was deleted on that side
+    pub local_data: Option<serde_json::Value>,
+    pub remote_data: Option<serde_json::Value>,
+    /// open or resolved
+    pub status: String,
+    pub resolution: Option<String>,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+/// Store a conflict, refreshing the entity's open conflict if there is one
+#[allow(clippy::too_many_arguments)]
+pub fn save_conflict(
+    conn: &Connection,
+    entity_type: &str,
+    entity_id: &str,
+    local_version: &str,
+    local_data: Option<&serde_json::Value>,
+    remote_version: &str,
+    remote_etag: &str,
+    remote_device_id: &str,
+    remote_data: Option<&serde_json::Value>,
+) -> Result<String, String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM sync_conflicts WHERE entity_type = ?1 AND entity_id = ?2 AND status = 'open'",
+            [entity_type, entity_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let id = existing.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    conn.execute(
+        "INSERT INTO sync_conflicts
+            (id, entity_type, entity_id, local_version, remote_version, remote_etag, remote_device_id,
+             local_data, remote_data, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(id) DO UPDATE SET
+            local_version = excluded.local_version, remote_version = excluded.remote_version,
+            remote_etag = excluded.remote_etag, remote_device_id = excluded.remote_device_id,
+            local_data = excluded.local_data, remote_data = excluded.remote_data",
+        params![
+            id,
+            entity_type,
+            entity_id,
+            local_version,
+            remote_version,
+            remote_etag,
+            remote_device_id,
+            local_data.map(|d| d.to_string()),
+            remote_data.map(|d| d.to_string()),
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn row_to_conflict(row: &rusqlite::Row) -> rusqlite::Result<StoredConflict> {
+    let json = |value: Option<String>| value.and_then(|v| serde_json::from_str(&v).ok());
+    Ok(StoredConflict {
+        id: row.get(0)?,
+        entity_type: row.get(1)?,
+        entity_id: row.get(2)?,
+        local_version: row.get(3)?,
+        remote_version: row.get(4)?,
+        remote_etag: row.get(5)?,
+        remote_device_id: row.get(6)?,
+        local_data: json(row.get(7)?),
+        remote_data: json(row.get(8)?),
+        status: row.get(9)?,
+        resolution: row.get(10)?,
+        created_at: row.get(11)?,
+        resolved_at: row.get(12)?,
+    })
+}
+
+const CONFLICT_COLUMNS: &str = "id, entity_type, entity_id, local_version, remote_version, remote_etag, \
+     remote_device_id, local_data, remote_data, status, resolution, created_at, resolved_at";
+
+/// Conflicts, newest first; resolved ones only when asked for
+pub fn list_conflicts(conn: &Connection, include_resolved: bool) -> Result<Vec<StoredConflict>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM sync_conflicts WHERE ?1 OR status = 'open' ORDER BY created_at DESC",
+            CONFLICT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let conflicts = stmt
+        .query_map([include_resolved], row_to_conflict)
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(conflicts)
+}
+
+/// Load a conflict by id
+pub fn get_conflict(conn: &Connection, id: &str) -> Result<Option<StoredConflict>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM sync_conflicts WHERE id = ?1", CONFLICT_COLUMNS),
+        [id],
+        row_to_conflict,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+pub fn mark_resolved(conn: &Connection, id: &str, strategy: ResolutionStrategy) -> Result<(), String> {
+    conn.execute(
+        "UPDATE sync_conflicts SET status = 'resolved', resolution = ?1, resolved_at = ?2 WHERE id = ?3",
+        params![strategy.as_str(), chrono::Utc::now().to_rfc3339(), id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Parse the timestamp formats found in the database
+fn parse_timestamp(value: &serde_json::Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    let text = value.as_str()?;
+    chrono::DateTime::parse_from_rfc3339(text)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").map(|t| t.and_utc())
+        })
+        .ok()
+}
+
+/// Merge two versions of a row: the more recently updated one wins field by
+/// field, and fields only the other has are kept
+fn merge_rows(local: &serde_json::Value, remote: &serde_json::Value) -> serde_json::Value {
+    let (Some(local_fields), Some(remote_fields)) = (local.as_object(), remote.as_object()) else {
+        return local.clone();
+    };
+    let updated = |fields: &serde_json::Map<String, serde_json::Value>| {
+        fields.get("updated_at").and_then(parse_timestamp)
+    };
+    let (mut merged, other) = if updated(remote_fields) > updated(local_fields) {
+        (remote_fields.clone(), local_fields)
+    } else {
+        (local_fields.clone(), remote_fields)
+    };
+    for (key, value) in other {
+        merged.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+    serde_json::Value::Object(merged)
+}
+
+/// Merge two versions of a synced entity
+///
+/// Conversations keep the messages of both sides, ordered by creation time.
+/// A version deleted on one side yields the other, so nothing is lost.
+pub fn merge_versions(
+    local: Option<&serde_json::Value>,
+    remote: Option<&serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let (local, remote) = match (local, remote) {
+        (Some(local), Some(remote)) => (local, remote),
+        (local, remote) => return local.or(remote).cloned(),
+    };
+
+    let (Some(local_messages), Some(remote_messages)) = (
+        local.get("messages").and_then(|m| m.as_array()),
+        remote.get("messages").and_then(|m| m.as_array()),
+    ) else {
+        return Some(merge_rows(local, remote));
+    };
+
+    let mut messages = local_messages.clone();
+    for message in remote_messages {
+        let id = message.get("id");
+        if !messages.iter().any(|m| m.get("id") == id) {
+            messages.push(message.clone());
+        }
+    }
+    messages.sort_by_key(|m| m.get("created_at").and_then(parse_timestamp));
+
+    Some(serde_json::json!({
+        "conversation": merge_rows(&local["conversation"], &remote["conversation"]),
+        "messages": messages,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,4 +457,34 @@ mod tests {
         let resolution = resolver.resolve(&mut conflict);
         assert_eq!(resolution, ConflictResolution::KeepRemote);
     }
+
+    #[test]
+    fn test_merge_versions() {
+        let local = serde_json::json!({
+            "conversation": {"id": "c1", "title": "Local", "updated_at": "2026-03-01 10:00:00"},
+            "messages": [
+                {"id": "m1", "content": "a", "created_at": "2026-03-01 09:00:00"},
+                {"id": "m3", "content": "c", "created_at": "2026-03-01 09:30:00"}
+            ]
+        });
+        let remote = serde_json::json!({
+            "conversation": {"id": "c1", "title": "Remote", "updated_at": "2026-03-01T11:00:00+00:00", "pinned": 1},
+            "messages": [
+                {"id": "m1", "content": "a", "created_at": "2026-03-01 09:00:00"},
+                {"id": "m2", "content": "b", "created_at": "2026-03-01 09:10:00"}
+            ]
+        });
+
+        let merged = merge_versions(Some(&local), Some(&remote)).unwrap();
+        assert_eq!(merged["conversation"]["title"], "Remote");
+        let ids: Vec<&str> = merged["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["m1", "m2", "m3"]);
+
+        assert_eq!(merge_versions(None, Some(&remote)), Some(remote.clone()));
+    }
 }
@@ -0,0 +1,913 @@
+//! Sync Engine
+//!
+//! Synchronizes settings, templates and conversations with a cloud storage
+//! bucket. Local changes are journaled in `sync_journal` by triggers while
+//! sync is enabled; each entity is stored remotely as one JSON record under
+//! `sync/<entity type>/<id>.json`. The etag of the last record seen for each
+//! entity is kept in `sync_metadata`, so a changed etag means another device
+//! wrote it. An entity changed on both sides with different content is
+//! stored in `sync_conflicts` and left untouched until the user resolves it.
+//!
+//! Sensitive conversations are never uploaded.
+
+use super::conflict::{self, ResolutionStrategy, StoredConflict, SyncConflict};
+use super::manager::{SyncEntity, SyncResult};
+use crate::integration::cloud::storage::CloudStorage;
+use crate::integration::cloud::{encode_path_segment, CloudStorageConfig};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Prefix of all synced objects in the bucket
+const REMOTE_PREFIX: &str = "sync/";
+
+/// Entity types and the table holding them
+const SYNCED_TABLES: &[(&str, &str)] = &[
+    ("settings", "voice_settings"),
+    ("template", "templates"),
+    ("conversation", "conversations"),
+];
+
+type EntityKey = (String, String);
+
+/// Record stored in the bucket for one entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteRecord {
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Device that wrote the record
+    pub device_id: String,
+    pub updated_at: String,
+    /// Tombstone for an entity deleted on `device_id`
+    pub deleted: bool,
+    pub data: Option<serde_json::Value>,
+}
+
+/// Sync configuration and last outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    pub device_id: Option<String>,
+    pub storage_name: Option<String>,
+    pub last_sync_at: Option<String>,
+    pub last_error: Option<String>,
+    /// Entities changed locally since the last sync
+    pub pending_changes: usize,
+}
+
+/// Local state of an entity
+enum LocalEntity {
+    Present(serde_json::Value),
+    Missing,
+    /// Exists but must not leave the device
+    Excluded,
+}
+
+fn table_for(entity_type: &str) -> Result<&'static str, String> {
+    SYNCED_TABLES
+        .iter()
+        .find(|(t, _)| *t == entity_type)
+        .map(|(_, table)| *table)
+        .ok_or_else(|| format!("Unknown sync entity type: {}", entity_type))
+}
+
+fn sync_entity(entity_type: &str) -> SyncEntity {
+    match entity_type {
+        "conversation" => SyncEntity::Conversation,
+        "template" => SyncEntity::Template,
+        _ => SyncEntity::Settings,
+    }
+}
+
+fn remote_key(entity_type: &str, entity_id: &str) -> String {
+    format!("{}{}/{}.json", REMOTE_PREFIX, entity_type, encode_path_segment(entity_id))
+}
+
+/// Reverse of `remote_key`, ignoring objects the engine did not write
+fn parse_remote_key(key: &str) -> Option<EntityKey> {
+    let rest = key.strip_prefix(REMOTE_PREFIX)?;
+    let (entity_type, file) = rest.split_once('/')?;
+    table_for(entity_type).ok()?;
+    let encoded = file.strip_suffix(".json")?;
+    Some((entity_type.to_string(), decode_path_segment(encoded)?))
+}
+
+fn decode_path_segment(encoded: &str) -> Option<String> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = encoded.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+// ============================================================================
+// Sync state
+// ============================================================================
+
+fn get_state(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM sync_state WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn set_state(conn: &Connection, key: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(value) => conn.execute(
+            "INSERT INTO sync_state (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [key, value],
+        ),
+        None => conn.execute("DELETE FROM sync_state WHERE key = ?1", [key]),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// This device's id, created on first use
+pub fn device_id(conn: &Connection) -> Result<String, String> {
+    if let Some(id) = get_state(conn, "device_id")? {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    set_state(conn, "device_id", Some(&id))?;
+    Ok(id)
+}
+
+/// Enable sync against a storage, or disable it with `None`
+///
+/// Enabling journals every existing entity so the first sync uploads them.
+/// The config is stored without its secret, which belongs in the keychain.
+pub fn configure(conn: &Connection, config: Option<&CloudStorageConfig>) -> Result<(), String> {
+    match config {
+        Some(config) => {
+            config.validate()?;
+            let mut stored = config.clone();
+            stored.secret_access_key = None;
+            let json = serde_json::to_string(&stored).map_err(|e| e.to_string())?;
+
+            device_id(conn)?;
+            set_state(conn, "storage", Some(&json))?;
+            set_state(conn, "enabled", Some("1"))?;
+            conn.execute_batch(
+                "DELETE FROM sync_journal;
+                 INSERT INTO sync_journal (entity_type, entity_id) SELECT 'settings', id FROM voice_settings;
+                 INSERT INTO sync_journal (entity_type, entity_id) SELECT 'template', id FROM templates;
+                 INSERT INTO sync_journal (entity_type, entity_id)
+                     SELECT 'conversation', id FROM conversations WHERE is_sensitive = 0;",
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            set_state(conn, "enabled", Some("0"))?;
+            conn.execute("DELETE FROM sync_journal", []).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Storage config sync runs against, if sync is enabled
+pub fn storage_config(conn: &Connection) -> Result<Option<CloudStorageConfig>, String> {
+    if get_state(conn, "enabled")?.as_deref() != Some("1") {
+        return Ok(None);
+    }
+    get_state(conn, "storage")?
+        .map(|json| serde_json::from_str(&json).map_err(|e| format!("Invalid sync storage config: {}", e)))
+        .transpose()
+}
+
+/// Current sync status
+pub fn status(conn: &Connection) -> Result<SyncStatus, String> {
+    let storage = storage_config(conn)?;
+    let pending_changes: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM (SELECT DISTINCT entity_type, entity_id FROM sync_journal)",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(SyncStatus {
+        enabled: storage.is_some(),
+        device_id: get_state(conn, "device_id")?,
+        storage_name: storage.map(|s| s.name),
+        last_sync_at: get_state(conn, "last_sync_at")?,
+        last_error: get_state(conn, "last_error")?,
+        pending_changes: pending_changes as usize,
+    })
+}
+
+/// Remember the outcome of a sync for `status`
+pub fn record_outcome(conn: &Connection, result: &SyncResult) -> Result<(), String> {
+    set_state(conn, "last_sync_at", Some(&chrono::Utc::now().to_rfc3339()))?;
+    let error = (!result.errors.is_empty()).then(|| result.errors.join("; "));
+    set_state(conn, "last_error", error.as_deref())
+}
+
+// ============================================================================
+// Local entities
+// ============================================================================
+
+fn sql_to_json(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => b.to_vec().into(),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        serde_json::Value::Array(items) => Value::Blob(
+            items.iter().filter_map(|i| i.as_u64()).map(|b| b as u8).collect(),
+        ),
+        serde_json::Value::Object(_) => Value::Text(value.to_string()),
+    }
+}
+
+/// Rows of `table` where `column = value`, as JSON objects
+fn select_rows(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    value: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} WHERE {} = ?1 ORDER BY rowid", table, column))
+        .map_err(|e| e.to_string())?;
+    let names: Vec<String> = stmt.column_names().iter().map(|n| n.to_string()).collect();
+    let rows = stmt
+        .query_map([value], |row| {
+            let mut object = serde_json::Map::new();
+            for (i, name) in names.iter().enumerate() {
+                object.insert(name.clone(), sql_to_json(row.get_ref(i)?));
+            }
+            Ok(serde_json::Value::Object(object))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Insert or update a row from a JSON object
+///
+/// Columns unknown to the local schema are ignored, so devices on different
+/// app versions can still sync.
+fn upsert_row(conn: &Connection, table: &str, row: &serde_json::Value) -> Result<(), String> {
+    let object = row.as_object().ok_or_else(|| format!("Invalid {} row", table))?;
+    let mut stmt = conn
+        .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|c| object.contains_key(c))
+        .collect();
+    if !columns.iter().any(|c| c == "id") {
+        return Err(format!("{} row has no id", table));
+    }
+
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let updates: Vec<String> = columns
+        .iter()
+        .filter(|c| *c != "id")
+        .map(|c| format!("{} = excluded.{}", c, c))
+        .collect();
+    let sql = if updates.is_empty() {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT(id) DO NOTHING",
+            table,
+            columns.join(", "),
+            placeholders.join(", ")
+        )
+    } else {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT(id) DO UPDATE SET {}",
+            table,
+            columns.join(", "),
+            placeholders.join(", "),
+            updates.join(", ")
+        )
+    };
+    let values: Vec<rusqlite::types::Value> = columns.iter().map(|c| json_to_sql(&object[c])).collect();
+    conn.execute(&sql, rusqlite::params_from_iter(values))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn load_local(conn: &Connection, entity_type: &str, entity_id: &str) -> Result<LocalEntity, String> {
+    let table = table_for(entity_type)?;
+    let Some(row) = select_rows(conn, table, "id", entity_id)?.into_iter().next() else {
+        return Ok(LocalEntity::Missing);
+    };
+    if entity_type != "conversation" {
+        return Ok(LocalEntity::Present(row));
+    }
+
+    if row.get("is_sensitive").and_then(|v| v.as_i64()).unwrap_or(0) != 0 {
+        return Ok(LocalEntity::Excluded);
+    }
+    let messages = select_rows(conn, "messages", "conversation_id", entity_id)?;
+    Ok(LocalEntity::Present(serde_json::json!({
+        "conversation": row,
+        "messages": messages,
+    })))
+}
+
+fn apply_remote(conn: &Connection, record: &RemoteRecord) -> Result<(), String> {
+    let table = table_for(&record.entity_type)?;
+    let data = match (&record.data, record.deleted) {
+        (Some(data), false) => data,
+        _ => {
+            conn.execute(&format!("DELETE FROM {} WHERE id = ?1", table), [&record.entity_id])
+                .map_err(|e| e.to_string())?;
+            return Ok(());
+        }
+    };
+
+    if record.entity_type != "conversation" {
+        return upsert_row(conn, table, data);
+    }
+
+    let conversation = data
+        .get("conversation")
+        .ok_or_else(|| "Conversation record has no conversation".to_string())?;
+    upsert_row(conn, table, conversation)?;
+    conn.execute("DELETE FROM messages WHERE conversation_id = ?1", [&record.entity_id])
+        .map_err(|e| e.to_string())?;
+    for message in data.get("messages").and_then(|m| m.as_array()).into_iter().flatten() {
+        upsert_row(conn, "messages", message)?;
+    }
+    Ok(())
+}
+
+/// Entities with journaled changes, with the newest journal id for each
+fn dirty_entities(conn: &Connection) -> Result<HashMap<EntityKey, i64>, String> {
+    let mut stmt = conn
+        .prepare("SELECT entity_type, entity_id, MAX(id) FROM sync_journal GROUP BY entity_type, entity_id")
+        .map_err(|e| e.to_string())?;
+    let dirty = stmt
+        .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(dirty)
+}
+
+/// Etag of the last remote record seen for each entity
+fn known_versions(conn: &Connection) -> Result<HashMap<EntityKey, String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT entity_type, entity_id, remote_version FROM sync_metadata WHERE remote_version IS NOT NULL")
+        .map_err(|e| e.to_string())?;
+    let versions = stmt
+        .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(versions)
+}
+
+fn clear_journal(conn: &Connection, key: &EntityKey, up_to: Option<i64>) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM sync_journal WHERE entity_type = ?1 AND entity_id = ?2 AND (?3 IS NULL OR id <= ?3)",
+        params![key.0, key.1, up_to],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn save_version(conn: &Connection, key: &EntityKey, updated_at: &str, etag: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_metadata (entity_type, entity_id, local_version, remote_version, last_synced, is_dirty)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0)
+         ON CONFLICT(entity_type, entity_id) DO UPDATE SET
+            local_version = excluded.local_version, remote_version = excluded.remote_version,
+            last_synced = excluded.last_synced, is_dirty = 0",
+        params![key.0, key.1, updated_at, etag, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn lock(db: &Mutex<Connection>) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+    db.lock().map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Sync
+// ============================================================================
+
+/// Upload an entity changed only on this device
+async fn upload(
+    storage: &dyn CloudStorage,
+    db: &Mutex<Connection>,
+    device_id: &str,
+    key: &EntityKey,
+    journal_id: i64,
+    exists_remotely: bool,
+) -> Result<bool, String> {
+    let local = load_local(&*lock(db)?, &key.0, &key.1)?;
+    let data = match local {
+        LocalEntity::Present(data) => Some(data),
+        LocalEntity::Missing if exists_remotely => None,
+        // Created and deleted between syncs, or never allowed to leave the device
+        LocalEntity::Missing | LocalEntity::Excluded => {
+            clear_journal(&*lock(db)?, key, Some(journal_id))?;
+            return Ok(false);
+        }
+    };
+
+    let record = RemoteRecord {
+        entity_type: key.0.clone(),
+        entity_id: key.1.clone(),
+        device_id: device_id.to_string(),
+        updated_at: chrono::Utc::now().to_rfc3339(),
+        deleted: data.is_none(),
+        data,
+    };
+    let remote = remote_key(&key.0, &key.1);
+    let body = serde_json::to_vec(&record).map_err(|e| e.to_string())?;
+    storage.put(&remote, body).await?;
+    let etag = storage.head(&remote).await?.map(|o| o.etag);
+
+    let conn = lock(db)?;
+    clear_journal(&conn, key, Some(journal_id))?;
+    save_version(&conn, key, &record.updated_at, etag.as_deref())?;
+    Ok(true)
+}
+
+async fn fetch_record(storage: &dyn CloudStorage, key: &EntityKey) -> Result<RemoteRecord, String> {
+    let body = storage.get(&remote_key(&key.0, &key.1)).await?;
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid sync record: {}", e))
+}
+
+/// Whether the entity is a sensitive conversation, which never syncs
+fn is_excluded(conn: &Connection, key: &EntityKey) -> Result<bool, String> {
+    if key.0 != "conversation" {
+        return Ok(false);
+    }
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM conversations WHERE id = ?1 AND is_sensitive != 0)",
+        [&key.1],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Apply a record changed only on another device
+///
+/// Skipped if the entity changed locally while the record was downloading;
+/// the next sync sees both changes.
+fn download(conn: &Connection, key: &EntityKey, record: &RemoteRecord, etag: &str) -> Result<bool, String> {
+    if is_excluded(conn, key)? {
+        save_version(conn, key, &record.updated_at, Some(etag))?;
+        return Ok(false);
+    }
+    let changed: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sync_journal WHERE entity_type = ?1 AND entity_id = ?2)",
+            params![key.0, key.1],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if changed {
+        return Ok(false);
+    }
+
+    apply_remote(conn, record)?;
+    // Drop the journal entries the triggers just wrote for our own changes
+    clear_journal(conn, key, None)?;
+    save_version(conn, key, &record.updated_at, Some(etag))?;
+    Ok(true)
+}
+
+/// Synchronize all journaled and remotely changed entities
+pub async fn sync(storage: &dyn CloudStorage, db: &Mutex<Connection>) -> SyncResult {
+    let start = Instant::now();
+    let mut result = SyncResult {
+        success: false,
+        uploaded: 0,
+        downloaded: 0,
+        conflicts: Vec::new(),
+        errors: Vec::new(),
+        duration_ms: 0,
+    };
+
+    let prepared = lock(db).and_then(|conn| Ok((device_id(&conn)?, dirty_entities(&conn)?, known_versions(&conn)?)));
+    let (device_id, dirty, known) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            result.errors.push(e);
+            return result;
+        }
+    };
+
+    let remote: HashMap<EntityKey, String> = match storage.list(Some(REMOTE_PREFIX)).await {
+        Ok(objects) => objects
+            .into_iter()
+            .filter_map(|o| parse_remote_key(&o.key).map(|key| (key, o.etag)))
+            .collect(),
+        Err(e) => {
+            result.errors.push(format!("Failed to list remote changes: {}", e));
+            result.duration_ms = start.elapsed().as_millis() as u64;
+            return result;
+        }
+    };
+    let remote_changed = |key: &EntityKey| remote.get(key).is_some_and(|etag| known.get(key) != Some(etag));
+
+    let keys: BTreeSet<&EntityKey> = dirty
+        .keys()
+        .chain(remote.keys().filter(|key| remote_changed(key)))
+        .collect();
+
+    for key in keys {
+        let outcome = match (dirty.get(key), remote_changed(key)) {
+            (Some(journal_id), false) => {
+                upload(storage, db, &device_id, key, *journal_id, remote.contains_key(key))
+                    .await
+                    .map(|uploaded| result.uploaded += uploaded as usize)
+            }
+            (None, _) => match fetch_record(storage, key).await {
+                Ok(record) => lock(db)
+                    .and_then(|conn| download(&conn, key, &record, &remote[key]))
+                    .map(|downloaded| result.downloaded += downloaded as usize),
+                Err(e) => Err(e),
+            },
+            (Some(journal_id), true) => match fetch_record(storage, key).await {
+                // Our own write whose etag was never recorded
+                Ok(record) if record.device_id == device_id => {
+                    upload(storage, db, &device_id, key, *journal_id, true)
+                        .await
+                        .map(|uploaded| result.uploaded += uploaded as usize)
+                }
+                Ok(record) => lock(db)
+                    .and_then(|conn| conflict(&conn, key, &record, &remote[key]))
+                    .map(|conflict| result.conflicts.extend(conflict)),
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(e) = outcome {
+            result.errors.push(format!("{} {}: {}", key.0, key.1, e));
+        }
+    }
+
+    result.success = result.errors.is_empty();
+    result.duration_ms = start.elapsed().as_millis() as u64;
+    result
+}
+
+/// Record an entity changed on this and another device
+///
+/// Returns `None` when both devices ended up with the same content, which
+/// needs no resolution.
+fn conflict(
+    conn: &Connection,
+    key: &EntityKey,
+    record: &RemoteRecord,
+    etag: &str,
+) -> Result<Option<SyncConflict>, String> {
+    let local = match load_local(conn, &key.0, &key.1)? {
+        LocalEntity::Present(data) => Some(data),
+        LocalEntity::Missing => None,
+        LocalEntity::Excluded => {
+            clear_journal(conn, key, None)?;
+            save_version(conn, key, &record.updated_at, Some(etag))?;
+            return Ok(None);
+        }
+    };
+    let remote = record.data.as_ref().filter(|_| !record.deleted);
+    if local.as_ref() == remote {
+        clear_journal(conn, key, None)?;
+        save_version(conn, key, &record.updated_at, Some(etag))?;
+        return Ok(None);
+    }
+
+    let local_version = conn
+        .query_row(
+            "SELECT MAX(changed_at) FROM sync_journal WHERE entity_type = ?1 AND entity_id = ?2",
+            params![key.0, key.1],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    conflict::save_conflict(
+        conn,
+        &key.0,
+        &key.1,
+        &local_version,
+        local.as_ref(),
+        &record.updated_at,
+        etag,
+        &record.device_id,
+        remote,
+    )?;
+
+    let bytes = |data: Option<&serde_json::Value>| data.map(|d| d.to_string().into_bytes()).unwrap_or_default();
+    Ok(Some(SyncConflict {
+        entity: sync_entity(&key.0),
+        id: key.1.clone(),
+        local_version,
+        remote_version: record.updated_at.clone(),
+        local_data: bytes(local.as_ref()),
+        remote_data: bytes(remote),
+        resolution: None,
+    }))
+}
+
+/// Resolve a stored conflict
+///
+/// The chosen version takes effect locally right away. For `local` and
+/// `merge` the entity stays journaled, so the next sync uploads it over the
+/// other device's version.
+pub fn resolve_conflict(
+    conn: &Connection,
+    id: &str,
+    strategy: ResolutionStrategy,
+) -> Result<StoredConflict, String> {
+    let stored = conflict::get_conflict(conn, id)?.ok_or_else(|| format!("Conflict {} not found", id))?;
+    if stored.status != "open" {
+        return Err(format!("Conflict {} is already resolved", id));
+    }
+    let key = (stored.entity_type.clone(), stored.entity_id.clone());
+
+    let local = match load_local(conn, &key.0, &key.1)? {
+        LocalEntity::Present(data) => Some(data),
+        LocalEntity::Missing => None,
+        LocalEntity::Excluded => return Err("Sensitive conversations are not synced".to_string()),
+    };
+    let replacement = match strategy {
+        ResolutionStrategy::Local => None,
+        ResolutionStrategy::Remote => Some(stored.remote_data.clone()),
+        ResolutionStrategy::Merge => Some(conflict::merge_versions(local.as_ref(), stored.remote_data.as_ref())),
+    };
+    if let Some(data) = replacement {
+        apply_remote(
+            conn,
+            &RemoteRecord {
+                entity_type: key.0.clone(),
+                entity_id: key.1.clone(),
+                device_id: stored.remote_device_id.clone(),
+                updated_at: stored.remote_version.clone(),
+                deleted: data.is_none(),
+                data,
+            },
+        )?;
+    }
+
+    clear_journal(conn, &key, None)?;
+    if strategy != ResolutionStrategy::Remote {
+        conn.execute(
+            "INSERT INTO sync_journal (entity_type, entity_id) VALUES (?1, ?2)",
+            params![key.0, key.1],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    // The other device's version has been seen, so it no longer counts as a remote change
+    save_version(conn, &key, &stored.remote_version, Some(&stored.remote_etag))?;
+    conflict::mark_resolved(conn, id, strategy)?;
+
+    conflict::get_conflict(conn, id)?.ok_or_else(|| format!("Conflict {} not found", id))
+}
+
+/// Run a sync from a database path, as the scheduler does
+///
+/// Opens its own connection and storage client and records the outcome.
+pub async fn sync_database(db_path: &str) -> Result<SyncResult, String> {
+    let conn = crate::db::pool::open_background(std::path::Path::new(db_path))?;
+    let config = storage_config(&conn)?.ok_or_else(|| "Sync is not configured".to_string())?;
+
+    let credentials = crate::security::CredentialManager::default().map_err(|e| e.to_string())?;
+    let config = crate::integration::cloud::storage::resolve_credentials(config, &credentials);
+    let s3 = crate::integration::cloud::s3::S3Manager::new();
+    let storage = crate::integration::cloud::storage::open_storage(&config, &s3).await?;
+
+    let db = Mutex::new(conn);
+    let result = sync(storage.as_ref(), &db).await;
+    record_outcome(&*lock(&db)?, &result)?;
+    Ok(result)
+}
+
+/// One-line summary of a sync for job output
+pub fn summarize(result: &SyncResult) -> String {
+    format!(
+        "Uploaded {}, downloaded {}, {} conflict(s), {} error(s)",
+        result.uploaded,
+        result.downloaded,
+        result.conflicts.len(),
+        result.errors.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integration::cloud::{CloudObject, CloudProvider};
+    use crate::db::test_support::test_conn;
+
+    /// In-memory bucket whose etags change on every write
+    #[derive(Default)]
+    struct MemoryStorage {
+        objects: Mutex<HashMap<String, (Vec<u8>, u64)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl CloudStorage for MemoryStorage {
+        async fn list(&self, prefix: Option<&str>) -> Result<Vec<CloudObject>, String> {
+            let objects = self.objects.lock().unwrap();
+            Ok(objects
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix.unwrap_or_default()))
+                .map(|(key, (data, version))| CloudObject {
+                    key: key.clone(),
+                    size: data.len() as u64,
+                    last_modified: String::new(),
+                    etag: version.to_string(),
+                })
+                .collect())
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+            self.objects
+                .lock()
+                .unwrap()
+                .get(key)
+                .map(|(data, _)| data.clone())
+                .ok_or_else(|| format!("{} not found", key))
+        }
+
+        async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), String> {
+            let mut objects = self.objects.lock().unwrap();
+            let version = objects.values().map(|(_, v)| *v).max().unwrap_or(0) + 1;
+            objects.insert(key.to_string(), (data, version));
+            Ok(())
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), String> {
+            self.objects.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn head(&self, key: &str) -> Result<Option<CloudObject>, String> {
+            Ok(self.list(Some(key)).await?.into_iter().find(|o| o.key == key))
+        }
+    }
+
+    fn device() -> Mutex<Connection> {
+        let conn = test_conn();
+        let config = CloudStorageConfig {
+            name: "backup".to_string(),
+            provider: CloudProvider::AwsS3,
+            bucket: "bucket".to_string(),
+            region: None,
+            access_key_id: Some("key".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            endpoint_url: None,
+        };
+        configure(&conn, Some(&config)).unwrap();
+        Mutex::new(conn)
+    }
+
+    #[test]
+    fn test_remote_key_round_trip() {
+        let key = remote_key("template", "a b/c");
+        assert_eq!(key, "sync/template/a%20b%2Fc.json");
+        assert_eq!(parse_remote_key(&key), Some(("template".to_string(), "a b/c".to_string())));
+        assert_eq!(parse_remote_key("sync/unknown/x.json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_sync_between_devices() {
+        let storage = MemoryStorage::default();
+        let laptop = device();
+        let desktop = device();
+
+        lock(&laptop)
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO conversations (id, title) VALUES ('c1', 'Plans');
+                 INSERT INTO messages (id, conversation_id, role, content) VALUES ('m1', 'c1', 'user', 'Hi');
+                 INSERT INTO conversations (id, title, is_sensitive) VALUES ('c2', 'Secret', 1);
+                 INSERT INTO templates (id, name, content) VALUES ('t1', 'Standup', 'Yesterday...');",
+            )
+            .unwrap();
+
+        let result = sync(&storage, &laptop).await;
+        assert!(result.success, "{:?}", result.errors);
+        assert_eq!(result.uploaded, 2);
+        assert_eq!(status(&lock(&laptop).unwrap()).unwrap().pending_changes, 0);
+
+        let result = sync(&storage, &desktop).await;
+        assert_eq!(result.downloaded, 2);
+        let content: String = lock(&desktop)
+            .unwrap()
+            .query_row("SELECT content FROM messages WHERE id = 'm1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content, "Hi");
+        // Applying remote records must not journal them for upload again
+        assert_eq!(sync(&storage, &desktop).await.uploaded, 0);
+
+        lock(&desktop).unwrap().execute("DELETE FROM templates WHERE id = 't1'", []).unwrap();
+        assert_eq!(sync(&storage, &desktop).await.uploaded, 1);
+        assert_eq!(sync(&storage, &laptop).await.downloaded, 1);
+        let templates: i64 = lock(&laptop)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM templates", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(templates, 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_edits_conflict() {
+        let storage = MemoryStorage::default();
+        let laptop = device();
+        let desktop = device();
+
+        lock(&laptop)
+            .unwrap()
+            .execute("INSERT INTO templates (id, name, content) VALUES ('t1', 'Standup', 'v1')", [])
+            .unwrap();
+        sync(&storage, &laptop).await;
+        sync(&storage, &desktop).await;
+
+        lock(&laptop).unwrap().execute("UPDATE templates SET content = 'laptop'", []).unwrap();
+        lock(&desktop).unwrap().execute("UPDATE templates SET content = 'desktop'", []).unwrap();
+        sync(&storage, &laptop).await;
+
+        let result = sync(&storage, &desktop).await;
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].id, "t1");
+        // Neither side is overwritten, and syncing again does not duplicate the conflict
+        sync(&storage, &desktop).await;
+        let content = |db: &Mutex<Connection>| -> String {
+            lock(db)
+                .unwrap()
+                .query_row("SELECT content FROM templates WHERE id = 't1'", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(content(&desktop), "desktop");
+        let conflicts = conflict::list_conflicts(&lock(&desktop).unwrap(), false).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].remote_data.as_ref().unwrap()["content"], "laptop");
+
+        let resolved = resolve_conflict(&lock(&desktop).unwrap(), &conflicts[0].id, ResolutionStrategy::Local).unwrap();
+        assert_eq!(resolved.status, "resolved");
+        assert!(conflict::list_conflicts(&lock(&desktop).unwrap(), false).unwrap().is_empty());
+
+        assert_eq!(sync(&storage, &desktop).await.uploaded, 1);
+        assert_eq!(sync(&storage, &laptop).await.downloaded, 1);
+        assert_eq!(content(&laptop), "desktop");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_conflict_with_remote() {
+        let storage = MemoryStorage::default();
+        let laptop = device();
+        let desktop = device();
+
+        lock(&laptop)
+            .unwrap()
+            .execute("INSERT INTO templates (id, name, content) VALUES ('t1', 'Standup', 'laptop')", [])
+            .unwrap();
+        lock(&desktop)
+            .unwrap()
+            .execute("INSERT INTO templates (id, name, content) VALUES ('t1', 'Standup', 'desktop')", [])
+            .unwrap();
+        sync(&storage, &laptop).await;
+        assert_eq!(sync(&storage, &desktop).await.conflicts.len(), 1);
+
+        let conn = lock(&desktop).unwrap();
+        let id = conflict::list_conflicts(&conn, false).unwrap()[0].id.clone();
+        resolve_conflict(&conn, &id, ResolutionStrategy::Remote).unwrap();
+        assert!(resolve_conflict(&conn, &id, ResolutionStrategy::Remote).is_err());
+        let content: String = conn
+            .query_row("SELECT content FROM templates WHERE id = 't1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content, "laptop");
+        assert_eq!(status(&conn).unwrap().pending_changes, 0);
+    }
+}
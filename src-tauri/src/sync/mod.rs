@@ -7,6 +7,7 @@
 //! - Offline support with conflict resolution
 
 pub mod manager;
+pub mod engine;
 pub mod conflict;
 pub mod offline;
 pub mod commands;
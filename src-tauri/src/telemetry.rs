@@ -0,0 +1,255 @@
+// Request telemetry - Latency, tokens and retries of every model request
+//
+// The agent runtime reports each provider call it makes (chats, prompts,
+// skills) through the `record_telemetry` host request. Reports are stored in
+// `request_telemetry` and re-emitted on `telemetry://request` for live views.
+// `get_performance_stats` aggregates them per provider over a period, with
+// p50/p95 latency of the successful requests, so providers can be compared
+// on real usage.
+
+use crate::analytics::AnalyticsPeriod;
+use chrono::{DateTime, Duration, Utc};
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{Emitter, Manager};
+
+/// Event carrying a `RequestTelemetry`
+pub const TELEMETRY_EVENT: &str = "telemetry://request";
+
+/// One model request as reported by the agent runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryReport {
+    pub provider: String,
+    pub model: Option<String>,
+    /// Runtime method that made the request, e.g. "chat"
+    pub method: String,
+    pub conversation_id: Option<String>,
+    pub latency_ms: u64,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    #[serde(default)]
+    pub retries: u32,
+    #[serde(default)]
+    pub streamed: bool,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Stored telemetry of a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTelemetry {
+    pub id: String,
+    #[serde(flatten)]
+    pub report: TelemetryReport,
+    pub created_at: String,
+}
+
+/// Aggregated performance of one provider
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderPerformance {
+    pub provider: String,
+    pub requests: u64,
+    pub errors: u64,
+    /// Latency percentiles of successful requests; None without any
+    pub p50_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub retries: u64,
+    pub streamed: u64,
+}
+
+/// Store a report; returns the stored row
+pub fn record(conn: &Connection, report: TelemetryReport, at: DateTime<Utc>) -> Result<RequestTelemetry, String> {
+    let telemetry = RequestTelemetry {
+        id: uuid::Uuid::new_v4().to_string(),
+        report,
+        created_at: at.to_rfc3339(),
+    };
+    let r = &telemetry.report;
+    conn.execute(
+        "INSERT INTO request_telemetry
+             (id, provider, model, method, conversation_id, latency_ms, prompt_tokens, completion_tokens,
+              retries, streamed, success, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            telemetry.id,
+            r.provider,
+            r.model,
+            r.method,
+            r.conversation_id,
+            r.latency_ms as i64,
+            r.prompt_tokens.map(|t| t as i64),
+            r.completion_tokens.map(|t| t as i64),
+            r.retries,
+            r.streamed,
+            r.success,
+            r.error,
+            telemetry.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(telemetry)
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Performance per provider over the period ending at `now`, by provider name
+pub fn performance_stats(
+    conn: &Connection,
+    period: AnalyticsPeriod,
+    now: DateTime<Utc>,
+) -> Result<Vec<ProviderPerformance>, String> {
+    let since = (now - Duration::days(period.days())).to_rfc3339();
+    let mut stmt = conn
+        .prepare(
+            "SELECT provider, latency_ms, prompt_tokens, completion_tokens, retries, streamed, success
+             FROM request_telemetry WHERE created_at >= ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([since]).map_err(|e| e.to_string())?;
+
+    let mut providers: BTreeMap<String, (ProviderPerformance, Vec<u64>)> = BTreeMap::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let provider: String = row.get(0).map_err(|e| e.to_string())?;
+        let (stats, latencies) = providers.entry(provider.clone()).or_insert_with(|| {
+            let stats = ProviderPerformance {
+                provider,
+                requests: 0,
+                errors: 0,
+                p50_latency_ms: None,
+                p95_latency_ms: None,
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                retries: 0,
+                streamed: 0,
+            };
+            (stats, Vec::new())
+        });
+
+        let latency: i64 = row.get(1).map_err(|e| e.to_string())?;
+        let success: bool = row.get(6).map_err(|e| e.to_string())?;
+        stats.requests += 1;
+        if success {
+            latencies.push(latency.max(0) as u64);
+        } else {
+            stats.errors += 1;
+        }
+        stats.prompt_tokens += row.get::<_, Option<i64>>(2).map_err(|e| e.to_string())?.unwrap_or(0) as u64;
+        stats.completion_tokens += row.get::<_, Option<i64>>(3).map_err(|e| e.to_string())?.unwrap_or(0) as u64;
+        stats.retries += row.get::<_, i64>(4).map_err(|e| e.to_string())? as u64;
+        if row.get::<_, bool>(5).map_err(|e| e.to_string())? {
+            stats.streamed += 1;
+        }
+    }
+
+    Ok(providers
+        .into_values()
+        .map(|(mut stats, mut latencies)| {
+            latencies.sort_unstable();
+            stats.p50_latency_ms = percentile(&latencies, 50.0);
+            stats.p95_latency_ms = percentile(&latencies, 95.0);
+            stats
+        })
+        .collect())
+}
+
+/// Answer a `record_telemetry` request from the agent runtime
+pub fn record_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let report: TelemetryReport =
+        serde_json::from_value(params).map_err(|e| format!("Invalid telemetry report: {}", e))?;
+    let telemetry = {
+        let db = app.state::<crate::db::DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        record(&conn, report, Utc::now())?
+    };
+    if let Err(e) = app.emit(TELEMETRY_EVENT, &telemetry) {
+        tracing::warn!("Failed to emit request telemetry: {}", e);
+    }
+    Ok(serde_json::json!({ "id": telemetry.id }))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Request counts and latency percentiles per provider
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_performance_stats(
+    db: tauri::State<'_, crate::db::DbState>,
+    period: AnalyticsPeriod,
+) -> Result<Vec<ProviderPerformance>, AppError> {
+    let conn = db.read()?;
+    Ok(performance_stats(&conn, period, Utc::now())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn report(provider: &str, latency_ms: u64, success: bool) -> TelemetryReport {
+        TelemetryReport {
+            provider: provider.to_string(),
+            model: Some("model".to_string()),
+            method: "chat".to_string(),
+            conversation_id: None,
+            latency_ms,
+            prompt_tokens: Some(10),
+            completion_tokens: success.then_some(5),
+            retries: if success { 0 } else { 2 },
+            streamed: false,
+            success,
+            error: (!success).then(|| "timeout".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let values: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&values, 50.0), Some(50));
+        assert_eq!(percentile(&values, 95.0), Some(95));
+        assert_eq!(percentile(&[7], 95.0), Some(7));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_stats_per_provider_and_period() {
+        let conn = test_conn();
+        let now = Utc::now();
+
+        for latency in [100, 200, 300, 400] {
+            record(&conn, report("openai", latency, true), now).unwrap();
+        }
+        record(&conn, report("openai", 5000, false), now).unwrap();
+        record(&conn, report("ollama", 900, true), now - Duration::days(1)).unwrap();
+        // Outside a week
+        record(&conn, report("ollama", 50, true), now - Duration::days(10)).unwrap();
+
+        let stats = performance_stats(&conn, AnalyticsPeriod::Week, now).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].provider.as_str(), stats[0].requests, stats[0].p50_latency_ms), ("ollama", 1, Some(900)));
+
+        let openai = &stats[1];
+        assert_eq!((openai.requests, openai.errors, openai.retries), (5, 1, 2));
+        // The failed request does not count towards latency
+        assert_eq!((openai.p50_latency_ms, openai.p95_latency_ms), (Some(200), Some(400)));
+        assert_eq!((openai.prompt_tokens, openai.completion_tokens), (50, 20));
+
+        let month = performance_stats(&conn, AnalyticsPeriod::Month, now).unwrap();
+        assert_eq!(month[0].requests, 2);
+    }
+}
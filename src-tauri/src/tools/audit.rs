@@ -0,0 +1,455 @@
+//! Agent Action Audit Trail
+//!
+//! Every action the agent initiates is recorded in `actions_audit`: tool
+//! calls reported by the agent runtime through `record_action` (including
+//! denied and failed ones), SQL statements run for a conversation and AI
+//! commits. Parameters are stored redacted: secrets found by the guardrail
+//! rules and values under credential-like keys are replaced, and long
+//! strings such as file contents are truncated.
+//!
+//! The trail is queried with `query_audit_log` and exported as JSON or CSV
+//! with `export_audit_log`.
+
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::security::guardrails::{self, DetectionKind};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+/// Entries returned when the filter does not set a limit
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Most entries returned or exported at once
+const MAX_LIMIT: u32 = 10_000;
+
+/// Longest string kept in recorded parameters
+const MAX_PARAM_CHARS: usize = 500;
+
+/// Parameter names whose values are never recorded
+const SENSITIVE_KEYS: &[&str] = &["password", "secret", "token", "api_key", "apikey", "authorization", "credential"];
+
+/// What kind of action the agent took
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    ToolCall,
+    FileWrite,
+    ShellCommand,
+    DbQuery,
+    GitOperation,
+    /// HTTP request made by a plugin through the network proxy
+    PluginRequest,
+}
+
+impl ActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ToolCall => "tool_call",
+            Self::FileWrite => "file_write",
+            Self::ShellCommand => "shell_command",
+            Self::DbQuery => "db_query",
+            Self::GitOperation => "git_operation",
+            Self::PluginRequest => "plugin_request",
+        }
+    }
+
+    /// Kind of a tool call, by tool name
+    pub fn for_tool(tool: &str) -> Self {
+        match tool {
+            "write_file" | "edit_file" | "delete_file" | "move_file" | "create_directory" => Self::FileWrite,
+            super::shell::TOOL_NAME => Self::ShellCommand,
+            _ if tool.starts_with("database_") || tool.starts_with("sql_") => Self::DbQuery,
+            _ if tool.starts_with("git_") => Self::GitOperation,
+            _ => Self::ToolCall,
+        }
+    }
+}
+
+/// Outcome of an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    Success,
+    Error,
+    /// Refused by the permission policy or the user
+    Denied,
+    /// Result withheld by the guardrails
+    Blocked,
+}
+
+impl ActionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Denied => "denied",
+            Self::Blocked => "blocked",
+        }
+    }
+}
+
+/// Action to record
+#[derive(Debug, Clone)]
+pub struct NewAction<'a> {
+    pub kind: ActionKind,
+    /// Tool or operation name
+    pub name: &'a str,
+    pub conversation_id: Option<&'a str>,
+    /// Parameters as given; redacted before they are stored
+    pub params: &'a Value,
+    pub status: ActionStatus,
+    pub error: Option<&'a str>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionAuditEntry {
+    pub id: String,
+    pub kind: ActionKind,
+    pub name: String,
+    pub conversation_id: Option<String>,
+    /// Redacted parameters
+    pub params: Value,
+    pub status: ActionStatus,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: String,
+    pub duration_ms: i64,
+}
+
+/// Criteria for `query_audit_log`; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuditFilter {
+    pub conversation_id: Option<String>,
+    pub kind: Option<ActionKind>,
+    pub status: Option<ActionStatus>,
+    /// Tool or operation name
+    pub name: Option<String>,
+    /// RFC 3339 bounds on the start time
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditExportFormat {
+    Json,
+    Csv,
+}
+
+fn redact_text(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut last = 0;
+    for detection in guardrails::scan(text) {
+        if detection.kind == DetectionKind::Secret {
+            redacted.push_str(&text[last..detection.start]);
+            redacted.push_str("[secret]");
+            last = detection.end;
+        }
+    }
+    redacted.push_str(&text[last..]);
+
+    let chars = redacted.chars().count();
+    if chars > MAX_PARAM_CHARS {
+        let kept: String = redacted.chars().take(MAX_PARAM_CHARS).collect();
+        return format!("{}… ({} chars)", kept, chars);
+    }
+    redacted
+}
+
+/// Parameters with secrets, credential values and long strings removed
+pub fn redact_params(params: &Value) -> Value {
+    match params {
+        Value::String(text) => Value::String(redact_text(text)),
+        Value::Array(items) => Value::Array(items.iter().map(redact_params).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let lower = key.to_lowercase();
+                    let value = if SENSITIVE_KEYS.iter().any(|k| lower.contains(k)) {
+                        Value::String("[redacted]".to_string())
+                    } else {
+                        redact_params(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Record an action; returns its id
+pub fn record(conn: &Connection, action: &NewAction) -> Result<String, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let params = serde_json::to_string(&redact_params(action.params)).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO actions_audit
+             (id, kind, name, conversation_id, params, status, error, started_at, finished_at, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            id,
+            action.kind.as_str(),
+            action.name,
+            action.conversation_id,
+            params,
+            action.status.as_str(),
+            action.error.map(redact_text),
+            action.started_at.to_rfc3339(),
+            action.finished_at.to_rfc3339(),
+            (action.finished_at - action.started_at).num_milliseconds().max(0),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Record an action, logging instead of failing; auditing never stops an
+/// action
+pub fn record_quietly(conn: &Connection, action: &NewAction) {
+    if let Err(e) = record(conn, action) {
+        tracing::warn!("Failed to record {} audit entry: {}", action.name, e);
+    }
+}
+
+fn parse_enum<T: serde::de::DeserializeOwned>(value: String) -> rusqlite::Result<T> {
+    serde_json::from_value(Value::String(value.clone()))
+        .map_err(|_| rusqlite::Error::InvalidColumnType(0, value, rusqlite::types::Type::Text))
+}
+
+/// Matching entries, newest first
+pub fn query(conn: &Connection, filter: &AuditFilter) -> Result<Vec<ActionAuditEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, kind, name, conversation_id, params, status, error, started_at, finished_at, duration_ms
+             FROM actions_audit
+             WHERE (?1 IS NULL OR conversation_id = ?1)
+               AND (?2 IS NULL OR kind = ?2)
+               AND (?3 IS NULL OR status = ?3)
+               AND (?4 IS NULL OR name = ?4)
+               AND (?5 IS NULL OR started_at >= ?5)
+               AND (?6 IS NULL OR started_at <= ?6)
+             ORDER BY started_at DESC
+             LIMIT ?7 OFFSET ?8",
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map(
+            rusqlite::params![
+                filter.conversation_id,
+                filter.kind.map(|k| k.as_str()),
+                filter.status.map(|s| s.as_str()),
+                filter.name,
+                filter.since,
+                filter.until,
+                filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+                filter.offset.unwrap_or(0),
+            ],
+            |row| {
+                let params: String = row.get(4)?;
+                Ok(ActionAuditEntry {
+                    id: row.get(0)?,
+                    kind: parse_enum(row.get(1)?)?,
+                    name: row.get(2)?,
+                    conversation_id: row.get(3)?,
+                    params: serde_json::from_str(&params).unwrap_or(Value::Null),
+                    status: parse_enum(row.get(5)?)?,
+                    error: row.get(6)?,
+                    started_at: row.get(7)?,
+                    finished_at: row.get(8)?,
+                    duration_ms: row.get(9)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    entries
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Matching entries as JSON or CSV
+pub fn export(conn: &Connection, filter: &AuditFilter, format: AuditExportFormat) -> Result<String, String> {
+    let filter = AuditFilter {
+        limit: Some(filter.limit.unwrap_or(MAX_LIMIT)),
+        ..filter.clone()
+    };
+    let entries = query(conn, &filter)?;
+    match format {
+        AuditExportFormat::Json => serde_json::to_string_pretty(&entries).map_err(|e| e.to_string()),
+        AuditExportFormat::Csv => {
+            let mut csv = String::from("id,kind,name,conversation_id,status,error,started_at,finished_at,duration_ms,params\n");
+            for entry in &entries {
+                let fields = [
+                    entry.id.clone(),
+                    entry.kind.as_str().to_string(),
+                    entry.name.clone(),
+                    entry.conversation_id.clone().unwrap_or_default(),
+                    entry.status.as_str().to_string(),
+                    entry.error.clone().unwrap_or_default(),
+                    entry.started_at.clone(),
+                    entry.finished_at.clone(),
+                    entry.duration_ms.to_string(),
+                    entry.params.to_string(),
+                ];
+                let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                csv.push_str(&line.join(","));
+                csv.push('\n');
+            }
+            Ok(csv)
+        }
+    }
+}
+
+fn parse_time(params: &Value, key: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    params
+        .get(key)
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+/// Answer a `record_action` request from the agent runtime
+///
+/// Expects `{ tool, args, status, error?, startedAt?, conversationId? }`.
+pub fn record_request(app: &tauri::AppHandle, params: Value) -> Result<Value, String> {
+    let tool = params.get("tool").and_then(|v| v.as_str()).ok_or("Missing tool name")?;
+    let status: ActionStatus = serde_json::from_value(params.get("status").cloned().unwrap_or(Value::Null))
+        .map_err(|e| format!("Invalid action status: {}", e))?;
+    let finished_at = chrono::Utc::now();
+    let action = NewAction {
+        kind: ActionKind::for_tool(tool),
+        name: tool,
+        conversation_id: params.get("conversationId").and_then(|v| v.as_str()),
+        params: params.get("args").unwrap_or(&Value::Null),
+        status,
+        error: params.get("error").and_then(|v| v.as_str()),
+        started_at: parse_time(&params, "startedAt").unwrap_or(finished_at),
+        finished_at,
+    };
+
+    let db = app.state::<DbState>();
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let id = record(&conn, &action)?;
+    Ok(serde_json::json!({ "id": id }))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn query_audit_log(
+    db: tauri::State<'_, DbState>,
+    filter: Option<AuditFilter>,
+) -> Result<Vec<ActionAuditEntry>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(query(&conn, &filter.unwrap_or_default())?)
+}
+
+/// Matching entries as a JSON or CSV document, for saving
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn export_audit_log(
+    db: tauri::State<'_, DbState>,
+    filter: Option<AuditFilter>,
+    format: AuditExportFormat,
+) -> Result<String, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(export(&conn, &filter.unwrap_or_default(), format)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_redact_params() {
+        let params = json!({
+            "path": "/tmp/notes.txt",
+            "content": "x".repeat(600),
+            "headers": { "Authorization": "Bearer abc", "Accept": "text/plain" },
+            "args": ["--token-file", "key sk-ant-REDACTED"],
+        });
+        let redacted = redact_params(&params);
+        assert_eq!(redacted["path"], "/tmp/notes.txt");
+        assert!(redacted["content"].as_str().unwrap().ends_with("… (600 chars)"));
+        assert_eq!(redacted["headers"]["Authorization"], "[redacted]");
+        assert_eq!(redacted["headers"]["Accept"], "text/plain");
+        assert_eq!(redacted["args"][1], "key [secret]");
+
+        assert_eq!(ActionKind::for_tool("write_file"), ActionKind::FileWrite);
+        assert_eq!(ActionKind::for_tool("execute_shell_command"), ActionKind::ShellCommand);
+        assert_eq!(ActionKind::for_tool("git_commit"), ActionKind::GitOperation);
+        assert_eq!(ActionKind::for_tool("read_file"), ActionKind::ToolCall);
+    }
+
+    #[test]
+    fn test_record_query_export() {
+        let conn = test_conn();
+        let start = chrono::DateTime::parse_from_rfc3339("2026-05-01T10:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let params = json!({ "path": "/tmp/a.txt", "content": "hi" });
+        for (i, (name, status)) in [
+            ("write_file", ActionStatus::Success),
+            ("execute_shell_command", ActionStatus::Denied),
+            ("read_file", ActionStatus::Error),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            record(
+                &conn,
+                &NewAction {
+                    kind: ActionKind::for_tool(name),
+                    name,
+                    conversation_id: Some(if i == 2 { "c2" } else { "c1" }),
+                    params: &params,
+                    status,
+                    error: (status == ActionStatus::Error).then_some("No such file, \"a\""),
+                    started_at: start + chrono::Duration::minutes(i as i64),
+                    finished_at: start + chrono::Duration::minutes(i as i64) + chrono::Duration::milliseconds(40),
+                },
+            )
+            .unwrap();
+        }
+
+        let all = query(&conn, &AuditFilter::default()).unwrap();
+        let names: Vec<&str> = all.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["read_file", "execute_shell_command", "write_file"]);
+        assert_eq!(all[2].duration_ms, 40);
+        assert_eq!(all[2].params["content"], "hi");
+
+        let filter = AuditFilter { conversation_id: Some("c1".to_string()), ..Default::default() };
+        assert_eq!(query(&conn, &filter).unwrap().len(), 2);
+        let filter = AuditFilter { kind: Some(ActionKind::FileWrite), ..Default::default() };
+        assert_eq!(query(&conn, &filter).unwrap()[0].name, "write_file");
+        let filter = AuditFilter { since: Some("2026-05-01T10:01:00+00:00".to_string()), ..Default::default() };
+        assert_eq!(query(&conn, &filter).unwrap().len(), 2);
+
+        let filter = AuditFilter { status: Some(ActionStatus::Error), ..Default::default() };
+        let csv = export(&conn, &filter, AuditExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains(",read_file,c2,error,\"No such file, \"\"a\"\"\","));
+        let json: Vec<ActionAuditEntry> =
+            serde_json::from_str(&export(&conn, &AuditFilter::default(), AuditExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json.len(), 3);
+    }
+}
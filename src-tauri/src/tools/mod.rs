@@ -0,0 +1,14 @@
+//! Agent Tools Module
+//!
+//! Host-side support for tools the agent runtime calls:
+//! - Per-tool allow/ask/deny policies with user confirmation
+//! - Risk tiers deciding whether tools run, notify or need confirmation
+//! - Shell command execution in permitted folders, with an audit log
+//! - An audit trail of every action the agent initiates
+
+pub mod audit;
+pub mod policy;
+pub mod risk;
+pub mod shell;
+
+pub use policy::ToolPolicyState;
@@ -0,0 +1,540 @@
+//! Tool Permission Policies
+//!
+//! Every agent tool call is checked against an allow/ask/deny decision before
+//! the agent runtime runs it. Decisions are stored per tool in
+//! `tool_policies`; tools without one follow the behavior configured for
+//! their risk tier (see `risk`). File tools must also target a folder with a
+//! matching `FolderPermission`.
+//!
+//! "ask" tools emit a confirmation request on `tools://confirm` and wait for
+//! `tool_confirm`; a request that is not answered within the configured
+//! timeout is rejected and announced on `tools://confirm-expired`. Tiers set
+//! to notify run right away and announce the call on `tools://notify`. Every
+//! decision is logged.
+
+use crate::error::AppError;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use super::risk::{self, RiskSettings, RiskTier, TierBehavior};
+use crate::files::{Access, FileErrorKind};
+
+/// Event carrying a `ConfirmationRequest`
+pub const CONFIRMATION_EVENT: &str = "tools://confirm";
+
+/// Event carrying the ID of a confirmation that timed out
+pub const CONFIRMATION_EXPIRED_EVENT: &str = "tools://confirm-expired";
+
+/// Event carrying a `ToolNotice` for calls run under a notify tier
+pub const NOTIFY_EVENT: &str = "tools://notify";
+
+/// What a tool is able to touch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCategory {
+    FileRead,
+    FileWrite,
+    Shell,
+    Network,
+    Database,
+    Other,
+}
+
+impl ToolCategory {
+    /// Category of a tool by name
+    pub fn of(tool: &str) -> Self {
+        match tool {
+            "read_file" | "read_file_bytes" | "list_directory" | "search_in_folder" | "extract_document_text"
+            | "ocr_image" => Self::FileRead,
+            "write_file" | "write_file_bytes" | "delete_file" => Self::FileWrite,
+            _ if tool.starts_with("execute_shell") || tool.starts_with("shell") => Self::Shell,
+            _ if tool.starts_with("database_") || tool.starts_with("db_") => Self::Database,
+            _ if ["http", "fetch", "browser", "web"].iter().any(|k| tool.contains(k)) => Self::Network,
+            _ => Self::Other,
+        }
+    }
+
+    /// Whether calls are checked against folder permissions
+    pub fn is_file(self) -> bool {
+        matches!(self, Self::FileRead | Self::FileWrite)
+    }
+}
+
+/// Decision for a tool call
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyDecision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl PolicyDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Ask => "ask",
+            Self::Deny => "deny",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "ask" => Ok(Self::Ask),
+            "deny" => Ok(Self::Deny),
+            _ => Err(format!("Invalid tool decision: {}", s)),
+        }
+    }
+}
+
+/// Effective policy of a tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    pub tool: String,
+    pub category: ToolCategory,
+    pub tier: RiskTier,
+    pub decision: PolicyDecision,
+    /// Whether the decision follows the tier rather than being stored
+    pub is_default: bool,
+    /// Whether allowed calls are announced to the user
+    pub notify: bool,
+}
+
+/// Outcome of checking a tool call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub decision: PolicyDecision,
+    pub reason: Option<String>,
+    pub tier: RiskTier,
+    pub notify: bool,
+}
+
+impl Evaluation {
+    fn deny(tier: RiskTier, reason: String) -> Self {
+        Self {
+            decision: PolicyDecision::Deny,
+            reason: Some(reason),
+            tier,
+            notify: false,
+        }
+    }
+}
+
+/// Confirmation asked of the user for an "ask" tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationRequest {
+    pub request_id: String,
+    pub tool: String,
+    pub category: ToolCategory,
+    pub tier: RiskTier,
+    pub args: serde_json::Value,
+    /// Seconds before the request is rejected
+    pub timeout_secs: u64,
+}
+
+/// Tool call run without confirmation under a notify tier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolNotice {
+    pub tool: String,
+    pub category: ToolCategory,
+    pub tier: RiskTier,
+    pub args: serde_json::Value,
+}
+
+/// Effective policy of a tool
+pub fn get_policy(conn: &Connection, tool: &str) -> Result<ToolPolicy, String> {
+    policy_with(conn, tool, &risk::load(conn)?)
+}
+
+fn policy_with(conn: &Connection, tool: &str, risk: &RiskSettings) -> Result<ToolPolicy, String> {
+    let category = ToolCategory::of(tool);
+    let tier = RiskTier::of(category);
+    let behavior = risk.behavior(tier);
+    let stored: Option<String> = conn
+        .query_row("SELECT decision FROM tool_policies WHERE tool = ?1", [tool], |row| row.get(0))
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e.to_string()),
+        })?;
+
+    Ok(ToolPolicy {
+        tool: tool.to_string(),
+        category,
+        tier,
+        decision: match &stored {
+            Some(decision) => PolicyDecision::parse(decision)?,
+            None => behavior.decision(),
+        },
+        is_default: stored.is_none(),
+        notify: stored.is_none() && behavior == TierBehavior::Notify,
+    })
+}
+
+/// Policies of the stored tools and of `tools`, sorted by tool name
+pub fn list_policies(conn: &Connection, tools: &[String]) -> Result<Vec<ToolPolicy>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tool FROM tool_policies")
+        .map_err(|e| e.to_string())?;
+    let mut names = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    names.extend(tools.iter().cloned());
+    names.sort();
+    names.dedup();
+
+    let risk = risk::load(conn)?;
+    names.iter().map(|tool| policy_with(conn, tool, &risk)).collect()
+}
+
+/// Store the decision for a tool
+pub fn set_policy(conn: &Connection, tool: &str, decision: PolicyDecision) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO tool_policies (tool, decision, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(tool) DO UPDATE SET decision = excluded.decision, updated_at = excluded.updated_at",
+        [tool, decision.as_str(), &chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Drop a tool's stored decision so its tier behavior applies
+pub fn reset_policy(conn: &Connection, tool: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM tool_policies WHERE tool = ?1", [tool])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Check a tool call against its policy and, for file tools, folder permissions
+pub fn evaluate(conn: &Connection, tool: &str, args: &serde_json::Value) -> Result<Evaluation, String> {
+    let policy = get_policy(conn, tool)?;
+    if policy.decision == PolicyDecision::Deny {
+        return Ok(Evaluation::deny(policy.tier, format!("Tool '{}' is denied by policy", tool)));
+    }
+
+    if policy.category.is_file() {
+        let Some(path) = args.get("path").and_then(|p| p.as_str()) else {
+            return Ok(Evaluation::deny(policy.tier, format!("Tool '{}' needs a path", tool)));
+        };
+        if let Some(reason) = folder_denial(conn, path, policy.category) {
+            return Ok(Evaluation::deny(policy.tier, reason));
+        }
+    }
+
+    Ok(Evaluation {
+        decision: policy.decision,
+        reason: None,
+        tier: policy.tier,
+        notify: policy.notify,
+    })
+}
+
+/// Why a file tool may not touch `path`, if it may not
+///
+/// A missing path is left for the tool itself to report.
+fn folder_denial(conn: &Connection, path: &str, category: ToolCategory) -> Option<String> {
+    let access = if category == ToolCategory::FileWrite { Access::Write } else { Access::Read };
+    match crate::files::access::resolve(conn, path, access) {
+        Err(e) if e.kind != FileErrorKind::NotFound => Some(e.message),
+        _ => None,
+    }
+}
+
+/// Confirmations waiting for the user
+#[derive(Default)]
+pub struct ToolPolicyState {
+    pending: Mutex<HashMap<String, Sender<bool>>>,
+}
+
+impl ToolPolicyState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a confirmation and return its ID with the channel of the answer
+    fn open(&self) -> Result<(String, Receiver<bool>), String> {
+        let (tx, rx) = mpsc::channel();
+        let id = uuid::Uuid::new_v4().to_string();
+        self.pending.lock().map_err(|e| e.to_string())?.insert(id.clone(), tx);
+        Ok((id, rx))
+    }
+
+    /// Answer a pending confirmation
+    pub fn respond(&self, request_id: &str, approved: bool) -> Result<(), String> {
+        let tx = self.pending.lock().map_err(|e| e.to_string())?
+            .remove(request_id)
+            .ok_or_else(|| "Confirmation request not found".to_string())?;
+        tx.send(approved).map_err(|_| "Confirmation request expired".to_string())
+    }
+
+    fn close(&self, request_id: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(request_id);
+        }
+    }
+
+    /// Wait for the answer to a confirmation, rejecting it after `timeout`
+    fn wait(&self, request_id: &str, rx: Receiver<bool>, timeout: Duration) -> Result<bool, String> {
+        let answer = rx.recv_timeout(timeout);
+        self.close(request_id);
+        match answer {
+            Ok(approved) => Ok(approved),
+            Err(RecvTimeoutError::Timeout) => Err("Confirmation timed out".to_string()),
+            Err(RecvTimeoutError::Disconnected) => Err("Confirmation was cancelled".to_string()),
+        }
+    }
+}
+
+/// Authorize a tool call, asking the user when the policy says so
+///
+/// Blocks the calling thread while a confirmation is pending.
+pub fn authorize(app: &tauri::AppHandle, tool: &str, args: &serde_json::Value) -> Result<(), String> {
+    let (evaluation, timeout) = {
+        let db = app.state::<crate::db::DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        (evaluate(&conn, tool, args)?, risk::load(&conn)?.confirmation_timeout())
+    };
+    let tier = evaluation.tier.as_str();
+
+    let result = match evaluation.decision {
+        PolicyDecision::Allow => {
+            if evaluation.notify {
+                let notice = ToolNotice {
+                    tool: tool.to_string(),
+                    category: ToolCategory::of(tool),
+                    tier: evaluation.tier,
+                    args: args.clone(),
+                };
+                if let Err(e) = app.emit(NOTIFY_EVENT, &notice) {
+                    tracing::warn!("Failed to announce tool '{}': {}", tool, e);
+                }
+            }
+            Ok(())
+        }
+        PolicyDecision::Deny => Err(evaluation.reason.unwrap_or_else(|| format!("Tool '{}' is denied", tool))),
+        PolicyDecision::Ask => confirm(app, tool, args, evaluation.tier, timeout),
+    };
+
+    match &result {
+        Ok(()) if evaluation.notify => tracing::info!("Tool '{}' ({} risk) allowed with notice", tool, tier),
+        Ok(()) if evaluation.decision == PolicyDecision::Ask => {
+            tracing::info!("Tool '{}' ({} risk) confirmed by the user", tool, tier)
+        }
+        Ok(()) => tracing::info!("Tool '{}' ({} risk) allowed", tool, tier),
+        Err(reason) => tracing::info!("Tool '{}' ({} risk) refused: {}", tool, tier, reason),
+    }
+    result
+}
+
+/// Ask the user to confirm a tool call, rejecting it after `timeout`
+fn confirm(
+    app: &tauri::AppHandle,
+    tool: &str,
+    args: &serde_json::Value,
+    tier: RiskTier,
+    timeout: Duration,
+) -> Result<(), String> {
+    let state = app.state::<ToolPolicyState>();
+    let (request_id, rx) = state.open()?;
+    let request = ConfirmationRequest {
+        request_id: request_id.clone(),
+        tool: tool.to_string(),
+        category: ToolCategory::of(tool),
+        tier,
+        args: args.clone(),
+        timeout_secs: timeout.as_secs(),
+    };
+    if let Err(e) = app.emit(CONFIRMATION_EVENT, &request) {
+        state.close(&request_id);
+        return Err(format!("Failed to request confirmation: {}", e));
+    }
+
+    match state.wait(&request_id, rx, timeout) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Tool '{}' was rejected by the user", tool)),
+        Err(e) => {
+            if let Err(e) = app.emit(CONFIRMATION_EXPIRED_EVENT, &request_id) {
+                tracing::warn!("Failed to expire confirmation {}: {}", request_id, e);
+            }
+            Err(format!("Tool '{}' was not confirmed: {}", tool, e))
+        }
+    }
+}
+
+/// Answer an `authorize_tool` request from the agent runtime
+///
+/// Expects `{ tool, args }` and replies `{ allowed, reason }`.
+pub fn authorize_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let tool = params.get("tool")
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "Missing tool name".to_string())?;
+    let args = params.get("args").cloned().unwrap_or(serde_json::Value::Null);
+
+    // The workspace of the conversation may narrow the tools further
+    let conversation_id = params.get("conversationId").and_then(|c| c.as_str());
+    let workspace = {
+        let db = app.state::<crate::db::DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        crate::workspaces::chat_config(&conn, conversation_id)?
+    };
+    if let Some(Err(reason)) = workspace.map(|config| config.allows_tool(tool)) {
+        return Ok(serde_json::json!({ "allowed": false, "reason": reason }));
+    }
+
+    Ok(match authorize(app, tool, &args) {
+        Ok(()) => serde_json::json!({ "allowed": true }),
+        Err(reason) => serde_json::json!({ "allowed": false, "reason": reason }),
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// List tool policies, including the defaults of `tools`
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tool_policy_list(
+    db: tauri::State<'_, crate::db::DbState>,
+    tools: Option<Vec<String>>,
+) -> Result<Vec<ToolPolicy>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list_policies(&conn, &tools.unwrap_or_default())?)
+}
+
+/// Set a tool's decision ("allow", "ask" or "deny")
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tool_policy_set(
+    db: tauri::State<'_, crate::db::DbState>,
+    tool: String,
+    decision: String,
+) -> Result<ToolPolicy, AppError> {
+    let decision = PolicyDecision::parse(&decision)?;
+    let conn = db.conn.lock()?;
+    set_policy(&conn, &tool, decision)?;
+    Ok(get_policy(&conn, &tool)?)
+}
+
+/// Restore a tool's category default
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tool_policy_reset(
+    db: tauri::State<'_, crate::db::DbState>,
+    tool: String,
+) -> Result<ToolPolicy, AppError> {
+    let conn = db.conn.lock()?;
+    reset_policy(&conn, &tool)?;
+    Ok(get_policy(&conn, &tool)?)
+}
+
+/// Approve or reject a pending tool confirmation
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tool_confirm(
+    state: tauri::State<'_, ToolPolicyState>,
+    request_id: String,
+    approved: bool,
+) -> Result<(), AppError> {
+    Ok(state.respond(&request_id, approved)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_policies_default_by_category() {
+        let conn = test_conn();
+
+        assert_eq!(ToolCategory::of("database_schema"), ToolCategory::Database);
+        assert_eq!(ToolCategory::of("execute_shell_command"), ToolCategory::Shell);
+        assert_eq!(get_policy(&conn, "read_file").unwrap().decision, PolicyDecision::Allow);
+        assert_eq!(get_policy(&conn, "write_file").unwrap().decision, PolicyDecision::Ask);
+
+        set_policy(&conn, "database_schema", PolicyDecision::Allow).unwrap();
+        set_policy(&conn, "database_schema", PolicyDecision::Deny).unwrap();
+        let policy = get_policy(&conn, "database_schema").unwrap();
+        assert_eq!(policy.decision, PolicyDecision::Deny);
+        assert!(!policy.is_default);
+
+        let policies = list_policies(&conn, &["read_file".to_string()]).unwrap();
+        assert_eq!(policies.len(), 2);
+        assert_eq!(policies[0].tool, "database_schema");
+
+        reset_policy(&conn, "database_schema").unwrap();
+        assert!(get_policy(&conn, "database_schema").unwrap().is_default);
+    }
+
+    #[test]
+    fn test_tier_behavior_applies_without_stored_policy() {
+        let conn = test_conn();
+        let risk = RiskSettings {
+            medium: TierBehavior::Notify,
+            high: TierBehavior::AutoRun,
+            ..Default::default()
+        };
+        risk::save(&conn, &risk).unwrap();
+
+        let evaluation = evaluate(&conn, "web_search", &serde_json::json!({})).unwrap();
+        assert_eq!((evaluation.decision, evaluation.tier, evaluation.notify), (PolicyDecision::Allow, RiskTier::Medium, true));
+        let policy = get_policy(&conn, "execute_shell_command").unwrap();
+        assert_eq!((policy.decision, policy.tier, policy.notify), (PolicyDecision::Allow, RiskTier::High, false));
+
+        // A stored decision overrides the tier and is never announced
+        set_policy(&conn, "web_search", PolicyDecision::Ask).unwrap();
+        let evaluation = evaluate(&conn, "web_search", &serde_json::json!({})).unwrap();
+        assert_eq!((evaluation.decision, evaluation.notify), (PolicyDecision::Ask, false));
+    }
+
+    #[test]
+    fn test_file_tools_need_folder_permission() {
+        let conn = test_conn();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        let args = serde_json::json!({ "path": file.to_str().unwrap() });
+
+        let evaluation = evaluate(&conn, "read_file", &args).unwrap();
+        assert_eq!(evaluation.decision, PolicyDecision::Deny);
+
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level, created_at) VALUES ('p1', ?1, 'read', '')",
+            [dir.path().to_str().unwrap()],
+        )
+        .unwrap();
+        assert_eq!(evaluate(&conn, "read_file", &args).unwrap().decision, PolicyDecision::Allow);
+        assert_eq!(evaluate(&conn, "write_file", &args).unwrap().decision, PolicyDecision::Deny);
+
+        conn.execute("UPDATE folder_permissions SET level = 'readwrite'", []).unwrap();
+        assert_eq!(evaluate(&conn, "write_file", &args).unwrap().decision, PolicyDecision::Ask);
+        assert_eq!(
+            evaluate(&conn, "write_file", &serde_json::json!({})).unwrap().decision,
+            PolicyDecision::Deny
+        );
+    }
+
+    #[test]
+    fn test_confirmation_answers() {
+        let state = ToolPolicyState::new();
+
+        let (id, rx) = state.open().unwrap();
+        state.respond(&id, true).unwrap();
+        assert_eq!(state.wait(&id, rx, Duration::from_millis(10)), Ok(true));
+        assert!(state.respond(&id, true).is_err());
+
+        let (id, rx) = state.open().unwrap();
+        assert!(state.wait(&id, rx, Duration::from_millis(10)).is_err());
+        assert!(state.pending.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+//! Tool Risk Tiers
+//!
+//! Every tool falls in a low, medium or high risk tier by its category, and
+//! each tier has a behavior: run automatically, run and notify the user, or
+//! require confirmation. The behaviors and how long a confirmation may stay
+//! pending are kept as JSON in `app_settings`. A decision stored for a tool
+//! in `tool_policies` still takes precedence over its tier.
+
+use crate::error::AppError;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::policy::{PolicyDecision, ToolCategory};
+use crate::db::settings;
+
+const SETTINGS_KEY: &str = "tool_risk_tiers";
+
+/// Bounds on the confirmation timeout, in seconds
+const MIN_TIMEOUT_SECS: u64 = 10;
+const MAX_TIMEOUT_SECS: u64 = 3600;
+
+/// How much harm a tool call can do
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskTier {
+    /// Tier of a tool category
+    pub fn of(category: ToolCategory) -> Self {
+        match category {
+            ToolCategory::FileRead => Self::Low,
+            ToolCategory::Network | ToolCategory::Other => Self::Medium,
+            ToolCategory::FileWrite | ToolCategory::Shell | ToolCategory::Database => Self::High,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// What happens when a tool of a tier is called
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TierBehavior {
+    AutoRun,
+    /// Run, and tell the user it happened
+    Notify,
+    RequireConfirmation,
+}
+
+impl TierBehavior {
+    /// Policy decision the behavior amounts to
+    pub fn decision(self) -> PolicyDecision {
+        match self {
+            Self::AutoRun | Self::Notify => PolicyDecision::Allow,
+            Self::RequireConfirmation => PolicyDecision::Ask,
+        }
+    }
+}
+
+/// Behavior per tier and the confirmation timeout
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RiskSettings {
+    pub low: TierBehavior,
+    pub medium: TierBehavior,
+    pub high: TierBehavior,
+    /// How long a confirmation waits before the call is rejected
+    pub confirmation_timeout_secs: u64,
+}
+
+impl Default for RiskSettings {
+    fn default() -> Self {
+        Self {
+            low: TierBehavior::AutoRun,
+            medium: TierBehavior::RequireConfirmation,
+            high: TierBehavior::RequireConfirmation,
+            confirmation_timeout_secs: 120,
+        }
+    }
+}
+
+impl RiskSettings {
+    pub fn behavior(&self, tier: RiskTier) -> TierBehavior {
+        match tier {
+            RiskTier::Low => self.low,
+            RiskTier::Medium => self.medium,
+            RiskTier::High => self.high,
+        }
+    }
+
+    pub fn confirmation_timeout(&self) -> Duration {
+        Duration::from_secs(self.confirmation_timeout_secs)
+    }
+}
+
+/// Stored settings, or the defaults if never saved
+pub fn load(conn: &Connection) -> Result<RiskSettings, String> {
+    match settings::get(conn, SETTINGS_KEY)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid tool risk settings: {}", e)),
+        None => Ok(RiskSettings::default()),
+    }
+}
+
+pub fn save(conn: &Connection, risk: &RiskSettings) -> Result<(), String> {
+    if !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&risk.confirmation_timeout_secs) {
+        return Err(format!(
+            "Confirmation timeout must be between {} and {} seconds",
+            MIN_TIMEOUT_SECS, MAX_TIMEOUT_SECS
+        ));
+    }
+    let json = serde_json::to_string(risk).map_err(|e| e.to_string())?;
+    settings::set(conn, SETTINGS_KEY, &json)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tool_risk_settings_get(db: tauri::State<'_, crate::db::DbState>) -> Result<RiskSettings, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(load(&conn)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tool_risk_settings_set(
+    db: tauri::State<'_, crate::db::DbState>,
+    settings: RiskSettings,
+) -> Result<RiskSettings, AppError> {
+    let conn = db.conn.lock()?;
+    save(&conn, &settings)?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_tiers_and_behaviors() {
+        assert_eq!(RiskTier::of(ToolCategory::of("read_file")), RiskTier::Low);
+        assert_eq!(RiskTier::of(ToolCategory::of("web_search")), RiskTier::Medium);
+        assert_eq!(RiskTier::of(ToolCategory::of("execute_shell_command")), RiskTier::High);
+
+        let risk = RiskSettings::default();
+        assert_eq!(risk.behavior(RiskTier::Low).decision(), PolicyDecision::Allow);
+        assert_eq!(risk.behavior(RiskTier::High).decision(), PolicyDecision::Ask);
+        assert_eq!(TierBehavior::Notify.decision(), PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn test_load_save() {
+        let conn = test_conn();
+        assert_eq!(load(&conn).unwrap(), RiskSettings::default());
+
+        let risk = RiskSettings {
+            medium: TierBehavior::Notify,
+            confirmation_timeout_secs: 30,
+            ..Default::default()
+        };
+        save(&conn, &risk).unwrap();
+        assert_eq!(load(&conn).unwrap(), risk);
+
+        let invalid = RiskSettings { confirmation_timeout_secs: 1, ..Default::default() };
+        assert!(save(&conn, &invalid).is_err());
+        assert_eq!(load(&conn).unwrap().confirmation_timeout(), Duration::from_secs(30));
+
+        // Fields missing from older saves fall back to defaults
+        settings::set(&conn, SETTINGS_KEY, r#"{"low":"notify"}"#).unwrap();
+        let partial = load(&conn).unwrap();
+        assert_eq!(partial.low, TierBehavior::Notify);
+        assert_eq!(partial.high, TierBehavior::RequireConfirmation);
+    }
+}
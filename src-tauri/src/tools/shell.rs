@@ -0,0 +1,794 @@
+//! Shell Command Tool
+//!
+//! Runs a single binary with arguments for the agent. There is no shell in
+//! between: no pipes, globbing or variable expansion. Commands run in a
+//! folder with a `readwrite` permission, with a reduced environment and a
+//! timeout. Only binaries allowed in `shell_binary_rules` may run, so
+//! nothing runs until the user allows a binary. A built-in denylist of
+//! shells, interpreters and wrappers such as `env` or `xargs`, which would
+//! otherwise run any other binary past these checks, is matched too.
+//! Commands are bare names looked up on `PATH`, never paths, and the binary
+//! they resolve to is checked against the deny rules as well. Arguments that
+//! are absolute paths or climb out with `..` must lie in a permitted folder.
+//! A command that times out is killed together with every process it started.
+//!
+//! Output lines are emitted on `shell://output` while the command runs, and
+//! every invocation, rejected or not, is recorded in `shell_command_audit`.
+
+use crate::error::AppError;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// Tool name used for the permission policy
+pub const TOOL_NAME: &str = "execute_shell_command";
+
+/// Event carrying a `ShellOutput` line
+pub const OUTPUT_EVENT: &str = "shell://output";
+
+/// Timeout when the caller does not set one
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Longest timeout a caller may ask for
+const MAX_TIMEOUT_SECS: u64 = 600;
+
+/// Output kept per stream; the rest is only streamed
+const MAX_CAPTURE_BYTES: usize = 1024 * 1024;
+
+/// Entries returned when the caller does not set a limit
+const DEFAULT_AUDIT_LIMIT: u32 = 100;
+
+/// Binaries reported as denied by default rather than merely not allowed;
+/// they are also matched against the target a command resolves to
+const DEFAULT_DENIED: &[&str] = &[
+    "sudo", "su", "doas", "shutdown", "reboot", "halt", "poweroff", "mkfs", "dd", "format", "diskpart",
+    // Shells and interpreters run arbitrary code given as an argument
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "csh", "tcsh", "cmd", "powershell", "pwsh", "python",
+    "python3", "node", "perl", "ruby",
+    // Wrappers run the binary given as an argument
+    "env", "xargs", "nohup", "nice", "timeout", "busybox",
+];
+
+/// Environment variables passed through to commands
+const PASSED_ENV: &[&str] = &["PATH", "HOME", "USERPROFILE", "LANG", "TMPDIR", "TEMP", "TMP", "SYSTEMROOT"];
+
+/// Shell command to run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellRequest {
+    /// Binary name, looked up on `PATH`
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub working_dir: String,
+    pub timeout_secs: Option<u64>,
+    pub conversation_id: Option<String>,
+}
+
+/// Line of output from a running command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellOutput {
+    pub invocation_id: String,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+/// Finished command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellResult {
+    pub invocation_id: String,
+    /// None when the command timed out or was killed by a signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+    /// Whether captured output was cut at the size cap
+    pub truncated: bool,
+}
+
+/// Allow or deny rule for a binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellRule {
+    pub binary: String,
+    pub rule: String,
+    pub created_at: String,
+}
+
+/// Audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellAuditEntry {
+    pub id: i64,
+    pub invocation_id: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub working_dir: String,
+    pub conversation_id: Option<String>,
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: i64,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// Name a binary is matched by in rules
+fn binary_name(command: &str) -> String {
+    let name = Path::new(command)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(command)
+        .to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+/// Check a binary against the stored rules and the built-in denylist;
+/// binaries without an allow rule are rejected
+///
+/// Rules match by name only, so a command with a path separator is
+/// rejected: `/tmp/x/ls` or `./git` would otherwise pass as `ls` or `git`.
+pub fn check_binary(conn: &Connection, command: &str) -> Result<(), String> {
+    if command.contains(['/', '\\']) {
+        return Err(format!("Command '{}' must be a binary name, not a path", command));
+    }
+    let binary = binary_name(command);
+    if binary.is_empty() {
+        return Err("Missing command".to_string());
+    }
+
+    let rules = list_rules(conn)?;
+    match rules.iter().find(|r| r.binary == binary).map(|r| r.rule.as_str()) {
+        Some("allow") => Ok(()),
+        Some(_) => Err(format!("Command '{}' is denied", binary)),
+        None if DEFAULT_DENIED.contains(&binary.as_str()) => {
+            Err(format!("Command '{}' is denied by default", binary))
+        }
+        None => Err(format!(
+            "Command '{}' is not in the allowlist; allow it in the shell settings first",
+            binary
+        )),
+    }
+}
+
+/// Find a checked command on `PATH` and check the binary it resolves to
+pub fn resolve_binary(conn: &Connection, command: &str) -> Result<PathBuf, String> {
+    resolve_binary_in(conn, command, &std::env::var_os("PATH").unwrap_or_default())
+}
+
+/// Find `command` in the `search_path` directories
+///
+/// Only absolute entries are searched, so the working directory is never
+/// one of them. The canonical target is matched against the deny rules
+/// too, which catches a link such as `sh -> dash` or an allowed name that
+/// points at an interpreter.
+fn resolve_binary_in(conn: &Connection, command: &str, search_path: &std::ffi::OsStr) -> Result<PathBuf, String> {
+    let names: Vec<String> = if cfg!(windows) && Path::new(command).extension().is_none() {
+        vec![format!("{}.exe", command), command.to_string()]
+    } else {
+        vec![command.to_string()]
+    };
+    let found = std::env::split_paths(search_path)
+        .filter(|dir| dir.is_absolute())
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| format!("Command '{}' was not found on PATH", command))?;
+    let canonical = found
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", found.display(), e))?;
+
+    let target = binary_name(&canonical.to_string_lossy());
+    let rules = list_rules(conn)?;
+    match rules.iter().find(|r| r.binary == target).map(|r| r.rule.as_str()) {
+        Some("allow") => Ok(canonical),
+        Some(_) => Err(format!("Command '{}' resolves to '{}', which is denied", command, target)),
+        None if DEFAULT_DENIED.contains(&target.as_str()) => Err(format!(
+            "Command '{}' resolves to '{}', which is denied by default",
+            command, target
+        )),
+        None => Ok(canonical),
+    }
+}
+
+/// Resolve a working directory inside a `readwrite` permitted folder
+pub fn permitted_dir(conn: &Connection, working_dir: &str) -> Result<PathBuf, String> {
+    let dir = crate::files::access::resolve(conn, working_dir, crate::files::Access::Write)
+        .map_err(|e| e.message)?;
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    Ok(dir)
+}
+
+/// Check that path arguments stay inside permitted folders
+///
+/// An argument is taken as a path when it is absolute or has a `..`
+/// component; for `--flag=value` the value is checked. Relative paths
+/// without `..` stay under `working_dir`, which is already permitted. Read
+/// access is enough, since which arguments a binary writes to is unknown.
+pub fn check_args(conn: &Connection, working_dir: &Path, args: &[String]) -> Result<(), String> {
+    for arg in args {
+        let value = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with('-') => value,
+            _ => arg.as_str(),
+        };
+        let path = Path::new(value);
+        let climbs = path.components().any(|c| c == std::path::Component::ParentDir);
+        if !path.is_absolute() && !climbs {
+            continue;
+        }
+        let full = working_dir.join(path);
+        crate::files::access::resolve(conn, &full.to_string_lossy(), crate::files::Access::Read)
+            .map_err(|e| format!("Argument '{}' is outside the permitted folders: {}", arg, e.message))?;
+    }
+    Ok(())
+}
+
+/// Output captured from one stream
+#[derive(Default)]
+struct Capture {
+    text: String,
+    truncated: bool,
+}
+
+/// Read a stream line by line, streaming and capturing each line
+fn read_stream(
+    stream: impl Read + Send + 'static,
+    name: &'static str,
+    invocation_id: String,
+    capture: Arc<Mutex<Capture>>,
+    on_output: Arc<dyn Fn(&ShellOutput) + Send + Sync>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut buf = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+            let line = String::from_utf8_lossy(&buf).into_owned();
+            buf.clear();
+
+            if let Ok(mut capture) = capture.lock() {
+                if capture.text.len() + line.len() <= MAX_CAPTURE_BYTES {
+                    capture.text.push_str(&line);
+                } else {
+                    capture.truncated = true;
+                }
+            }
+            on_output(&ShellOutput {
+                invocation_id: invocation_id.clone(),
+                stream: name.to_string(),
+                line: line.trim_end_matches(['\r', '\n']).to_string(),
+            });
+        }
+    })
+}
+
+/// Start the command as the leader of a new process group
+#[cfg(unix)]
+fn isolate(cmd: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    cmd.process_group(0);
+}
+
+#[cfg(not(unix))]
+fn isolate(_cmd: &mut Command) {}
+
+/// Kill a timed-out command and every process it started
+#[cfg(unix)]
+fn kill_tree(child: &mut std::process::Child) {
+    // The child leads its own group, so its pid is the group id
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(windows)]
+fn kill_tree(child: &mut std::process::Child) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+}
+
+#[cfg(not(any(unix, windows)))]
+fn kill_tree(child: &mut std::process::Child) {
+    let _ = child.kill();
+}
+
+/// Run a validated command, killing it and its descendants after `timeout`
+///
+/// `command` is the binary to start, as returned by `resolve_binary`.
+pub fn execute(
+    invocation_id: &str,
+    command: &Path,
+    args: &[String],
+    working_dir: &Path,
+    timeout: Duration,
+    on_output: Arc<dyn Fn(&ShellOutput) + Send + Sync>,
+) -> Result<ShellResult, String> {
+    let mut cmd = Command::new(command);
+    cmd.args(args)
+        .current_dir(working_dir)
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    isolate(&mut cmd);
+    for key in PASSED_ENV {
+        if let Ok(value) = std::env::var(key) {
+            cmd.env(key, value);
+        }
+    }
+
+    let started = Instant::now();
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to start {}: {}", command.display(), e))?;
+
+    let stdout = Arc::new(Mutex::new(Capture::default()));
+    let stderr = Arc::new(Mutex::new(Capture::default()));
+    let mut readers = Vec::new();
+    if let Some(out) = child.stdout.take() {
+        readers.push(read_stream(out, "stdout", invocation_id.to_string(), stdout.clone(), on_output.clone()));
+    }
+    if let Some(err) = child.stderr.take() {
+        readers.push(read_stream(err, "stderr", invocation_id.to_string(), stderr.clone(), on_output));
+    }
+
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            kill_tree(&mut child);
+            let _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    // Processes started by a timed-out command may keep its pipes open
+    if status.is_some() {
+        for reader in readers {
+            let _ = reader.join();
+        }
+    }
+
+    let take = |capture: &Arc<Mutex<Capture>>| {
+        capture.lock().map(|c| (c.text.clone(), c.truncated)).unwrap_or_default()
+    };
+    let (stdout, stdout_truncated) = take(&stdout);
+    let (stderr, stderr_truncated) = take(&stderr);
+
+    Ok(ShellResult {
+        invocation_id: invocation_id.to_string(),
+        exit_code: status.and_then(|s| s.code()),
+        stdout,
+        stderr,
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out: status.is_none(),
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+/// Record a shell invocation
+pub fn record_invocation(
+    conn: &Connection,
+    invocation_id: &str,
+    request: &ShellRequest,
+    outcome: &Result<ShellResult, String>,
+    rejected: bool,
+) -> Result<(), String> {
+    let (status, exit_code, duration_ms, error) = match outcome {
+        Ok(result) => {
+            let status = if result.timed_out {
+                "timeout"
+            } else if result.exit_code == Some(0) {
+                "success"
+            } else {
+                "failed"
+            };
+            (status, result.exit_code, result.duration_ms as i64, None)
+        }
+        Err(e) => (if rejected { "rejected" } else { "failed" }, None, 0, Some(e.as_str())),
+    };
+
+    conn.execute(
+        "INSERT INTO shell_command_audit
+            (invocation_id, command, args, working_dir, conversation_id, status, exit_code, duration_ms, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            invocation_id,
+            request.command,
+            serde_json::to_string(&request.args).map_err(|e| e.to_string())?,
+            request.working_dir,
+            request.conversation_id,
+            status,
+            exit_code,
+            duration_ms,
+            error,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Most recent invocations, newest first
+pub fn list_audit(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+    limit: Option<u32>,
+) -> Result<Vec<ShellAuditEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, invocation_id, command, args, working_dir, conversation_id, status, exit_code,
+                    duration_ms, error, created_at
+             FROM shell_command_audit
+             WHERE (?1 IS NULL OR conversation_id = ?1)
+             ORDER BY id DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![conversation_id, limit.unwrap_or(DEFAULT_AUDIT_LIMIT)], |row| {
+            let args: String = row.get(3)?;
+            Ok(ShellAuditEntry {
+                id: row.get(0)?,
+                invocation_id: row.get(1)?,
+                command: row.get(2)?,
+                args: serde_json::from_str(&args).unwrap_or_default(),
+                working_dir: row.get(4)?,
+                conversation_id: row.get(5)?,
+                status: row.get(6)?,
+                exit_code: row.get(7)?,
+                duration_ms: row.get(8)?,
+                error: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+/// Stored binary rules, sorted by binary
+pub fn list_rules(conn: &Connection) -> Result<Vec<ShellRule>, String> {
+    let mut stmt = conn
+        .prepare("SELECT binary, rule, created_at FROM shell_binary_rules ORDER BY binary")
+        .map_err(|e| e.to_string())?;
+    let rules = stmt
+        .query_map([], |row| {
+            Ok(ShellRule {
+                binary: row.get(0)?,
+                rule: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rules)
+}
+
+/// Run a shell request whose tool call has already been authorized
+///
+/// Validates the binary and working directory, streams output through
+/// `OUTPUT_EVENT` and records the invocation. Blocks until the command ends.
+pub fn run(app: &tauri::AppHandle, request: &ShellRequest) -> Result<ShellResult, String> {
+    let invocation_id = uuid::Uuid::new_v4().to_string();
+    let db = app.state::<crate::db::DbState>();
+
+    let (binary, working_dir) = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let validated = check_binary(&conn, &request.command)
+            .and_then(|_| resolve_binary(&conn, &request.command))
+            .and_then(|binary| permitted_dir(&conn, &request.working_dir).map(|dir| (binary, dir)))
+            .and_then(|(binary, dir)| check_args(&conn, &dir, &request.args).map(|_| (binary, dir)));
+        match validated {
+            Ok(validated) => validated,
+            Err(e) => {
+                let outcome = Err(e.clone());
+                record_invocation(&conn, &invocation_id, request, &outcome, true)?;
+                return Err(e);
+            }
+        }
+    };
+
+    let timeout = Duration::from_secs(request.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS));
+    let emitter = app.clone();
+    let outcome = execute(
+        &invocation_id,
+        &binary,
+        &request.args,
+        &working_dir,
+        timeout,
+        Arc::new(move |output: &ShellOutput| {
+            let _ = emitter.emit(OUTPUT_EVENT, output);
+        }),
+    );
+
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    record_invocation(&conn, &invocation_id, request, &outcome, false)?;
+    outcome
+}
+
+/// Answer an `execute_shell_command` request from the agent runtime, which
+/// has already authorized the tool call
+pub fn run_request(app: &tauri::AppHandle, params: serde_json::Value) -> Result<serde_json::Value, String> {
+    let request: ShellRequest = serde_json::from_value(params)
+        .map_err(|e| format!("Invalid shell request: {}", e))?;
+    let result = run(app, &request)?;
+    serde_json::to_value(result).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Run a binary in a permitted folder, subject to the shell tool's policy
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn execute_shell_command(
+    app: tauri::AppHandle,
+    command: String,
+    args: Option<Vec<String>>,
+    working_dir: String,
+    timeout_secs: Option<u64>,
+    conversation_id: Option<String>,
+) -> Result<ShellResult, AppError> {
+    let request = ShellRequest {
+        command,
+        args: args.unwrap_or_default(),
+        working_dir,
+        timeout_secs,
+        conversation_id,
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        let tool_args = serde_json::to_value(&request).map_err(|e| e.to_string())?;
+        if let Err(e) = super::policy::authorize(&app, TOOL_NAME, &tool_args) {
+            let db = app.state::<crate::db::DbState>();
+            let conn = db.conn.lock().map_err(|e| e.to_string())?;
+            let invocation_id = uuid::Uuid::new_v4().to_string();
+            record_invocation(&conn, &invocation_id, &request, &Err(e.clone()), true)?;
+            return Err(e);
+        }
+        run(&app, &request)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(result)
+}
+
+/// List shell invocations, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn shell_audit_list(
+    db: tauri::State<'_, crate::db::DbState>,
+    conversation_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ShellAuditEntry>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list_audit(&conn, conversation_id.as_deref(), limit)?)
+}
+
+/// List binary rules
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn shell_rule_list(db: tauri::State<'_, crate::db::DbState>) -> Result<Vec<ShellRule>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(list_rules(&conn)?)
+}
+
+/// Allow or deny a binary ("allow" or "deny")
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn shell_rule_set(
+    db: tauri::State<'_, crate::db::DbState>,
+    binary: String,
+    rule: String,
+) -> Result<(), AppError> {
+    if rule != "allow" && rule != "deny" {
+        return Err(AppError::invalid_input(format!("Invalid shell rule: {}", rule)));
+    }
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "INSERT INTO shell_binary_rules (binary, rule, created_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(binary) DO UPDATE SET rule = excluded.rule",
+        [
+            &binary_name(&binary),
+            &rule,
+            &chrono::Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Remove a binary's rule
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn shell_rule_remove(
+    db: tauri::State<'_, crate::db::DbState>,
+    binary: String,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    conn.execute(
+        "DELETE FROM shell_binary_rules WHERE binary = ?1",
+        [binary_name(&binary)],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_binary_rules() {
+        let conn = test_conn();
+
+        // Nothing runs until a binary is allowed
+        assert!(check_binary(&conn, "ls").unwrap_err().contains("not in the allowlist"));
+        assert!(check_binary(&conn, "/usr/bin/sudo").is_err());
+        // `sh -c 'sudo ...'` and `env sudo ...` would run sudo through another binary
+        assert!(check_binary(&conn, "sh").is_err());
+        assert!(check_binary(&conn, "/usr/bin/env").is_err());
+        assert!(check_binary(&conn, "powershell.exe").is_err());
+
+        conn.execute("INSERT INTO shell_binary_rules (binary, rule) VALUES ('git', 'allow')", []).unwrap();
+        conn.execute("INSERT INTO shell_binary_rules (binary, rule) VALUES ('curl', 'deny')", []).unwrap();
+        assert!(check_binary(&conn, "git").is_ok());
+        assert!(check_binary(&conn, "GIT.exe").is_ok());
+        assert!(check_binary(&conn, "curl").is_err());
+        assert!(check_binary(&conn, "ls").is_err());
+    }
+
+    #[test]
+    fn test_path_qualified_binaries_are_rejected() {
+        let conn = test_conn();
+        conn.execute("INSERT INTO shell_binary_rules (binary, rule) VALUES ('git', 'allow')", []).unwrap();
+
+        // Rules only see the name, so a path could run any binary under an allowed one
+        for command in ["/usr/bin/sudo", "/bin/sh", "/tmp/x/git", "./git", "bin/ls", "C:\\Windows\\cmd.exe", "..\\git.exe"] {
+            let error = check_binary(&conn, command).unwrap_err();
+            assert!(error.contains("not a path"), "{}: {}", command, error);
+        }
+        assert!(check_binary(&conn, "git").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_binary_checks_link_target() {
+        let conn = test_conn();
+        let bin = tempfile::tempdir().unwrap();
+        let sh = std::fs::canonicalize("/bin/sh").unwrap();
+        std::os::unix::fs::symlink(&sh, bin.path().join("tool")).unwrap();
+        std::os::unix::fs::symlink(std::fs::canonicalize("/bin/ls").unwrap(), bin.path().join("list")).unwrap();
+        let search_path = std::env::join_paths([Path::new("relative"), bin.path()]).unwrap();
+
+        // An allowed name that links to a shell is still denied
+        conn.execute("INSERT INTO shell_binary_rules (binary, rule) VALUES ('tool', 'allow')", []).unwrap();
+        assert!(check_binary(&conn, "tool").is_ok());
+        assert!(resolve_binary_in(&conn, "tool", &search_path).is_err());
+
+        conn.execute("INSERT INTO shell_binary_rules (binary, rule) VALUES ('list', 'allow')", []).unwrap();
+        let list = resolve_binary_in(&conn, "list", &search_path).unwrap();
+        assert!(list.is_absolute());
+        assert!(resolve_binary_in(&conn, "missing", &search_path).is_err());
+    }
+
+    #[test]
+    fn test_working_dir_needs_write_permission() {
+        let conn = test_conn();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        assert!(permitted_dir(&conn, path).is_err());
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level, created_at) VALUES ('p1', ?1, 'read', '')",
+            [path],
+        )
+        .unwrap();
+        assert!(permitted_dir(&conn, path).is_err());
+        conn.execute("UPDATE folder_permissions SET level = 'readwrite'", []).unwrap();
+        assert!(permitted_dir(&conn, path).is_ok());
+    }
+
+    #[test]
+    fn test_path_arguments_need_permission() {
+        let conn = test_conn();
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        let shared = dir.path().join("shared");
+        std::fs::create_dir(&work).unwrap();
+        std::fs::create_dir(&shared).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "x").unwrap();
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level, created_at) VALUES ('p1', ?1, 'readwrite', '')",
+            [work.to_str().unwrap()],
+        )
+        .unwrap();
+
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let secret = dir.path().join("secret.txt").to_string_lossy().to_string();
+        assert!(check_args(&conn, &work, &args(&["status", "-s", "notes/a.txt"])).is_ok());
+        assert!(check_args(&conn, &work, &args(&[&secret])).is_err());
+        assert!(check_args(&conn, &work, &args(&["../secret.txt"])).is_err());
+        assert!(check_args(&conn, &work, &args(&[&format!("--output={}", secret)])).is_err());
+        assert!(check_args(&conn, &work, &args(&[work.join("a.txt").to_str().unwrap()])).is_ok());
+
+        // A read permission is enough for an argument
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level, created_at) VALUES ('p2', ?1, 'read', '')",
+            [shared.to_str().unwrap()],
+        )
+        .unwrap();
+        assert!(check_args(&conn, &work, &args(&["../shared"])).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_streams_and_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let on_output: Arc<dyn Fn(&ShellOutput) + Send + Sync> =
+            Arc::new(move |output: &ShellOutput| sink.lock().unwrap().push(output.line.clone()));
+
+        let args = vec!["hello".to_string()];
+        let result = execute("i1", Path::new("echo"), &args, dir.path(), Duration::from_secs(5), on_output.clone()).unwrap();
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.stdout, "hello\n");
+        assert_eq!(*lines.lock().unwrap(), vec!["hello".to_string()]);
+
+        let args = vec!["5".to_string()];
+        let result = execute("i2", Path::new("sleep"), &args, dir.path(), Duration::from_millis(100), on_output).unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_grandchildren() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marker");
+        let on_output: Arc<dyn Fn(&ShellOutput) + Send + Sync> = Arc::new(|_: &ShellOutput| {});
+
+        // The background `sleep` would outlive a kill of the direct child only
+        let script = format!("(sleep 1; touch {}) & wait", marker.display());
+        let args = vec!["-c".to_string(), script];
+        let result = execute("i3", Path::new("/bin/sh"), &args, dir.path(), Duration::from_millis(200), on_output).unwrap();
+        assert!(result.timed_out);
+        std::thread::sleep(Duration::from_millis(1500));
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_record_and_list_audit() {
+        let conn = test_conn();
+        let request = ShellRequest {
+            command: "sudo".to_string(),
+            args: vec!["ls".to_string()],
+            working_dir: "/tmp".to_string(),
+            timeout_secs: None,
+            conversation_id: Some("conv-1".to_string()),
+        };
+
+        record_invocation(&conn, "i1", &request, &Err("Command 'sudo' is denied".to_string()), true).unwrap();
+        let entries = list_audit(&conn, Some("conv-1"), None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, "rejected");
+        assert_eq!(entries[0].args, vec!["ls".to_string()]);
+        assert!(list_audit(&conn, Some("conv-2"), None).unwrap().is_empty());
+    }
+}
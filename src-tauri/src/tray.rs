@@ -0,0 +1,309 @@
+// System tray - Scheduler and sync status with quick actions
+//
+// The tray menu shows whether the scheduler is running, how many jobs are in
+// flight and the sync state, and offers pausing the scheduler, opening the
+// main window and starting voice capture. A background task polls the same
+// status, updating the menu and emitting `STATUS_EVENT` whenever it changes.
+
+use crate::db::DbState;
+use crate::error::AppError;
+use crate::scheduler::JobScheduler;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+/// Emitted with an `AppStatus` whenever it changes
+pub const STATUS_EVENT: &str = "tray://status";
+
+const TRAY_ID: &str = "main";
+const MAIN_WINDOW: &str = "main";
+
+/// How often the status is checked for changes
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+// Menu item ids
+const TOGGLE_SCHEDULER: &str = "toggle_scheduler";
+const OPEN_MAIN: &str = "open_main";
+const VOICE_CAPTURE: &str = "voice_capture";
+const QUIT: &str = "quit";
+
+/// Scheduler and sync state shown in the tray
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStatus {
+    pub scheduler_running: bool,
+    /// False when another instance of the app executes the jobs
+    pub scheduler_leader: bool,
+    pub running_jobs: usize,
+    /// Executions waiting for a free slot
+    pub queued_jobs: usize,
+    pub sync_enabled: bool,
+    pub sync_pending: usize,
+    pub last_sync_at: Option<String>,
+    pub sync_error: Option<String>,
+}
+
+/// Menu items whose text follows the status
+struct TrayMenu {
+    scheduler: MenuItem<Wry>,
+    sync: MenuItem<Wry>,
+    toggle: MenuItem<Wry>,
+}
+
+fn scheduler_label(status: &AppStatus) -> String {
+    if !status.scheduler_running {
+        return "Scheduler: paused".to_string();
+    }
+    if !status.scheduler_leader {
+        return "Scheduler: running in another instance".to_string();
+    }
+    match (status.running_jobs, status.queued_jobs) {
+        (0, 0) => "Scheduler: idle".to_string(),
+        (running, 0) => format!("Scheduler: {} running", running),
+        (running, queued) => format!("Scheduler: {} running, {} queued", running, queued),
+    }
+}
+
+fn sync_label(status: &AppStatus) -> String {
+    if !status.sync_enabled {
+        "Sync: off".to_string()
+    } else if status.sync_error.is_some() {
+        "Sync: last sync failed".to_string()
+    } else if status.sync_pending > 0 {
+        format!("Sync: {} pending", status.sync_pending)
+    } else if status.last_sync_at.is_none() {
+        "Sync: not synced yet".to_string()
+    } else {
+        "Sync: up to date".to_string()
+    }
+}
+
+fn toggle_label(status: &AppStatus) -> &'static str {
+    if status.scheduler_running {
+        "Pause scheduler"
+    } else {
+        "Resume scheduler"
+    }
+}
+
+/// Current scheduler and sync state
+pub async fn snapshot(app: &AppHandle) -> Result<AppStatus, String> {
+    let (scheduler_running, scheduler_leader, running_jobs, queued_jobs) = {
+        let scheduler = app.state::<Arc<tokio::sync::Mutex<JobScheduler>>>();
+        let scheduler = scheduler.lock().await;
+        (
+            scheduler.is_running().await,
+            scheduler.is_leader(),
+            scheduler.running_count().await,
+            scheduler.queue_depth(),
+        )
+    };
+    let sync = {
+        let db = app.state::<DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        crate::sync::engine::status(&conn)?
+    };
+
+    Ok(AppStatus {
+        scheduler_running,
+        scheduler_leader,
+        running_jobs,
+        queued_jobs,
+        sync_enabled: sync.enabled,
+        sync_pending: sync.pending_changes,
+        last_sync_at: sync.last_sync_at,
+        sync_error: sync.last_error,
+    })
+}
+
+/// Show the status in the tray and tell the frontend
+fn apply(app: &AppHandle, status: &AppStatus) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        let updates = [
+            menu.scheduler.set_text(scheduler_label(status)),
+            menu.sync.set_text(sync_label(status)),
+            menu.toggle.set_text(toggle_label(status)),
+        ];
+        if let Some(Err(e)) = updates.into_iter().find(Result::is_err) {
+            tracing::warn!("Failed to update tray menu: {}", e);
+        }
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let tooltip = format!("AI Assistant\n{}\n{}", scheduler_label(status), sync_label(status));
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+    let _ = app.emit(STATUS_EVENT, status);
+}
+
+/// Refresh the tray right away, e.g. after an action changed the status
+async fn refresh(app: &AppHandle) {
+    match snapshot(app).await {
+        Ok(status) => apply(app, &status),
+        Err(e) => tracing::warn!("Failed to read app status: {}", e),
+    }
+}
+
+/// Bring the main window to the front
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Start a voice session the same way a detected wake word does
+fn start_voice_capture(app: &AppHandle) {
+    show_main_window(app);
+    let event = crate::voice::wake_word::WakeWordEvent {
+        wake_word: String::new(),
+        transcript: String::new(),
+        session_id: uuid::Uuid::new_v4().to_string(),
+        detected_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Err(e) = app.emit(crate::voice::wake_word::WAKE_EVENT, event) {
+        tracing::error!("Failed to emit wake event: {}", e);
+    }
+}
+
+/// Pause a running scheduler or resume a paused one
+async fn toggle_scheduler(app: AppHandle) {
+    let scheduler = app.state::<Arc<tokio::sync::Mutex<JobScheduler>>>();
+    let running = scheduler.lock().await.is_running().await;
+    let result = if running {
+        crate::scheduler_stop(scheduler).await
+    } else {
+        crate::scheduler_start(scheduler).await
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to toggle scheduler from tray: {}", e);
+    }
+    refresh(&app).await;
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        TOGGLE_SCHEDULER => {
+            tauri::async_runtime::spawn(toggle_scheduler(app.clone()));
+        }
+        OPEN_MAIN => show_main_window(app),
+        VOICE_CAPTURE => start_voice_capture(app),
+        QUIT => app.exit(0),
+        _ => {}
+    }
+}
+
+/// Create the tray icon and start keeping it up to date
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let error = |e: tauri::Error| format!("Failed to create tray: {}", e);
+    let scheduler = MenuItem::with_id(app, "scheduler_status", "Scheduler: starting", false, None::<&str>).map_err(error)?;
+    let sync = MenuItem::with_id(app, "sync_status", "Sync: off", false, None::<&str>).map_err(error)?;
+    let toggle = MenuItem::with_id(app, TOGGLE_SCHEDULER, "Pause scheduler", true, None::<&str>).map_err(error)?;
+    let open = MenuItem::with_id(app, OPEN_MAIN, "Open AI Assistant", true, None::<&str>).map_err(error)?;
+    let voice = MenuItem::with_id(app, VOICE_CAPTURE, "Start voice capture", true, None::<&str>).map_err(error)?;
+    let quit = MenuItem::with_id(app, QUIT, "Quit", true, None::<&str>).map_err(error)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &scheduler,
+            &sync,
+            &PredefinedMenuItem::separator(app).map_err(error)?,
+            &toggle,
+            &open,
+            &voice,
+            &PredefinedMenuItem::separator(app).map_err(error)?,
+            &quit,
+        ],
+    )
+    .map_err(error)?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("AI Assistant")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app).map_err(error)?;
+    app.manage(TrayMenu { scheduler, sync, toggle });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = None;
+        loop {
+            match snapshot(&app).await {
+                Ok(status) if last.as_ref() != Some(&status) => {
+                    apply(&app, &status);
+                    last = Some(status);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Failed to read app status: {}", e),
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    });
+    Ok(())
+}
+
+/// Current scheduler and sync state, as shown in the tray
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn get_app_status(app: AppHandle) -> Result<AppStatus, AppError> {
+    Ok(snapshot(&app).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> AppStatus {
+        AppStatus {
+            scheduler_running: true,
+            scheduler_leader: true,
+            running_jobs: 0,
+            queued_jobs: 0,
+            sync_enabled: true,
+            sync_pending: 0,
+            last_sync_at: Some("2026-03-01T00:00:00Z".to_string()),
+            sync_error: None,
+        }
+    }
+
+    #[test]
+    fn test_scheduler_label() {
+        let mut status = status();
+        assert_eq!(scheduler_label(&status), "Scheduler: idle");
+        status.running_jobs = 2;
+        assert_eq!(scheduler_label(&status), "Scheduler: 2 running");
+        status.queued_jobs = 1;
+        assert_eq!(scheduler_label(&status), "Scheduler: 2 running, 1 queued");
+        assert_eq!(toggle_label(&status), "Pause scheduler");
+        status.scheduler_leader = false;
+        assert_eq!(scheduler_label(&status), "Scheduler: running in another instance");
+
+        status.scheduler_running = false;
+        assert_eq!(scheduler_label(&status), "Scheduler: paused");
+        assert_eq!(toggle_label(&status), "Resume scheduler");
+    }
+
+    #[test]
+    fn test_sync_label() {
+        let mut status = status();
+        assert_eq!(sync_label(&status), "Sync: up to date");
+        status.sync_pending = 3;
+        assert_eq!(sync_label(&status), "Sync: 3 pending");
+        status.sync_error = Some("timeout".to_string());
+        assert_eq!(sync_label(&status), "Sync: last sync failed");
+        status.sync_enabled = false;
+        assert_eq!(sync_label(&status), "Sync: off");
+    }
+}
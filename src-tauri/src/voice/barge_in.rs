@@ -0,0 +1,439 @@
+// Barge-in - Interrupting speech playback by talking over it
+//
+// While a voice conversation is active the microphone stays open. Whenever
+// the TTS player is producing audio, each captured frame has the echo of
+// that audio subtracted and is checked for speech; sustained speech stops
+// playback and emits `voice://barge-in` so the UI can start listening.
+//
+// Echo suppression uses the known playback signal: the player taps the
+// samples it plays into a `PlaybackTap`, and for each microphone frame the
+// best-matching stretch of recent playback (searching the delay through the
+// speakers and back) is scaled and subtracted. What remains is mostly the
+// user's voice.
+
+#![allow(dead_code)]
+
+use crate::voice::capture::{AudioCapture, CAPTURE_SAMPLE_RATE};
+use crate::voice::playback::TtsPlayer;
+use crate::voice::stt::apply_vad;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// Event emitted when the user talks over playback
+pub const BARGE_IN_EVENT: &str = "voice://barge-in";
+
+/// Frame length used for detection
+const FRAME_MS: u32 = 30;
+
+/// Longest delay between playing a sample and hearing it back (300ms)
+const MAX_ECHO_DELAY_SAMPLES: usize = (CAPTURE_SAMPLE_RATE as usize) * 3 / 10;
+
+/// Step between echo delays tried (1ms)
+const ECHO_DELAY_STEP: usize = (CAPTURE_SAMPLE_RATE / 1000) as usize;
+
+/// Correlation below which a frame is taken to contain no echo
+const MIN_ECHO_CORRELATION: f32 = 0.3;
+
+/// Playback history kept for echo suppression (1 second)
+const MAX_REFERENCE_SAMPLES: usize = CAPTURE_SAMPLE_RATE as usize;
+
+/// Time after the last tapped sample during which playback counts as ongoing
+const PLAYING_GRACE: Duration = Duration::from_millis(300);
+
+/// Barge-in settings, stored with the voice settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BargeInSettings {
+    pub enabled: bool,
+    /// Speech threshold (0.0 = very sensitive, 1.0 = less sensitive)
+    pub sensitivity: f32,
+    /// Subtract the playback signal from the microphone before detection
+    pub echo_suppression: bool,
+    /// Speech needed before playback is interrupted
+    pub min_speech_ms: u32,
+}
+
+impl Default for BargeInSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sensitivity: 0.7,
+            echo_suppression: true,
+            min_speech_ms: 240,
+        }
+    }
+}
+
+/// Barge-in settings from `voice_settings`, or the defaults when none are saved
+pub fn load_settings(conn: &Connection) -> BargeInSettings {
+    conn.query_row(
+        "SELECT barge_in_enabled, barge_in_sensitivity, echo_suppression FROM voice_settings LIMIT 1",
+        [],
+        |row| {
+            Ok(BargeInSettings {
+                enabled: row.get(0)?,
+                sensitivity: row.get::<_, f64>(1)? as f32,
+                echo_suppression: row.get(2)?,
+                ..Default::default()
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+// ============================================================================
+// Playback reference
+// ============================================================================
+
+#[derive(Default)]
+struct TapState {
+    samples: VecDeque<f32>,
+    last_push: Option<Instant>,
+}
+
+/// Recent playback, as 16kHz mono samples in the order they were played
+#[derive(Clone, Default)]
+pub struct PlaybackTap {
+    state: Arc<Mutex<TapState>>,
+}
+
+impl PlaybackTap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record samples handed to the audio output
+    pub fn push(&self, samples: &[f32]) {
+        if let Ok(mut state) = self.state.lock() {
+            state.samples.extend(samples);
+            let excess = state.samples.len().saturating_sub(MAX_REFERENCE_SAMPLES);
+            state.samples.drain(..excess);
+            state.last_push = Some(Instant::now());
+        }
+    }
+
+    /// Whether audio was played within the last moments
+    pub fn is_playing(&self) -> bool {
+        self.state
+            .lock()
+            .ok()
+            .and_then(|state| state.last_push)
+            .is_some_and(|t| t.elapsed() < PLAYING_GRACE)
+    }
+
+    /// Up to `len` of the most recently played samples
+    pub fn recent(&self, len: usize) -> Vec<f32> {
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+        let start = state.samples.len().saturating_sub(len);
+        state.samples.range(start..).copied().collect()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.samples.clear();
+            state.last_push = None;
+        }
+    }
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Subtract the echo of `reference` from a microphone frame
+///
+/// `reference` is recent playback ending at the time the frame was captured.
+/// Every delay up to `MAX_ECHO_DELAY_SAMPLES` is tried; the stretch of
+/// playback that correlates best with the frame is scaled to its level and
+/// subtracted. Frames that match no stretch well are returned unchanged.
+pub fn suppress_echo(frame: &[f32], reference: &[f32]) -> Vec<f32> {
+    if frame.is_empty() || reference.len() < frame.len() {
+        return frame.to_vec();
+    }
+
+    let frame_energy = dot(frame, frame);
+    let latest_start = reference.len() - frame.len();
+    let mut best: Option<(f32, &[f32])> = None;
+    for delay in (0..=MAX_ECHO_DELAY_SAMPLES.min(latest_start)).step_by(ECHO_DELAY_STEP) {
+        let start = latest_start - delay;
+        let candidate = &reference[start..start + frame.len()];
+        let energy = dot(candidate, candidate);
+        if energy <= f32::EPSILON {
+            continue;
+        }
+        let correlation = dot(frame, candidate) / (frame_energy * energy).sqrt().max(f32::EPSILON);
+        if best.is_none_or(|(c, _)| correlation > c) {
+            best = Some((correlation, candidate));
+        }
+    }
+
+    match best {
+        Some((correlation, echo)) if correlation >= MIN_ECHO_CORRELATION => {
+            let gain = dot(frame, echo) / dot(echo, echo);
+            frame.iter().zip(echo).map(|(mic, e)| mic - gain * e).collect()
+        }
+        _ => frame.to_vec(),
+    }
+}
+
+/// Decides when speech over playback should interrupt it
+pub struct BargeInDetector {
+    settings: BargeInSettings,
+    frame_len: usize,
+    pending: Vec<f32>,
+    speech_frames: u32,
+    triggered: bool,
+}
+
+impl BargeInDetector {
+    pub fn new(settings: BargeInSettings) -> Self {
+        Self {
+            settings,
+            frame_len: (CAPTURE_SAMPLE_RATE * FRAME_MS / 1000) as usize,
+            pending: Vec::new(),
+            speech_frames: 0,
+            triggered: false,
+        }
+    }
+
+    /// Feed microphone samples captured during playback, with the playback
+    /// leading up to them; returns true once speech has lasted long enough
+    pub fn push(&mut self, samples: &[f32], reference: &[f32]) -> bool {
+        self.pending.extend_from_slice(samples);
+        let mut detected = false;
+
+        while self.pending.len() >= self.frame_len {
+            let frame: Vec<f32> = self.pending.drain(..self.frame_len).collect();
+            let residual = if self.settings.echo_suppression {
+                suppress_echo(&frame, reference)
+            } else {
+                frame
+            };
+
+            if apply_vad(&residual, self.settings.sensitivity).is_speech {
+                self.speech_frames += 1;
+            } else {
+                self.speech_frames = 0;
+            }
+
+            if !self.triggered && self.speech_frames * FRAME_MS >= self.settings.min_speech_ms {
+                self.triggered = true;
+                detected = true;
+            }
+        }
+
+        detected
+    }
+
+    /// Forget partial speech, ready for the next playback
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.speech_frames = 0;
+        self.triggered = false;
+    }
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+/// Payload of the `voice://barge-in` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BargeInEvent {
+    pub session_id: String,
+    pub detected_at: String,
+}
+
+struct MonitorSession {
+    session_id: String,
+    stop: Arc<AtomicBool>,
+    capture: AudioCapture,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// Listens for barge-in during a voice conversation
+#[derive(Default)]
+pub struct BargeInMonitor {
+    session: Option<MonitorSession>,
+}
+
+impl BargeInMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start monitoring for a conversation, replacing any earlier one
+    pub fn start(
+        &mut self,
+        app: tauri::AppHandle,
+        session_id: &str,
+        settings: BargeInSettings,
+        tap: PlaybackTap,
+    ) -> Result<(), String> {
+        self.stop();
+
+        let (frames_tx, frames_rx) = mpsc::channel();
+        let capture = AudioCapture::start(frames_tx)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let stop = stop.clone();
+            let session_id = session_id.to_string();
+            std::thread::spawn(move || run_monitor(app, session_id, settings, tap, frames_rx, stop))
+        };
+
+        tracing::info!("Barge-in monitoring started for voice session {}", session_id);
+        self.session = Some(MonitorSession {
+            session_id: session_id.to_string(),
+            stop,
+            capture,
+            worker: Some(worker),
+        });
+        Ok(())
+    }
+
+    /// Stop monitoring and release the microphone
+    pub fn stop(&mut self) -> bool {
+        let Some(mut session) = self.session.take() else {
+            return false;
+        };
+
+        session.stop.store(true, Ordering::SeqCst);
+        session.capture.stop();
+        if let Some(worker) = session.worker.take() {
+            let _ = worker.join();
+        }
+        tracing::info!("Barge-in monitoring stopped for voice session {}", session.session_id);
+        true
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session.as_ref().map(|s| s.session_id.as_str())
+    }
+}
+
+/// Monitoring loop run on the worker thread
+fn run_monitor(
+    app: tauri::AppHandle,
+    session_id: String,
+    settings: BargeInSettings,
+    tap: PlaybackTap,
+    frames: Receiver<Vec<f32>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut detector = BargeInDetector::new(settings);
+
+    while !stop.load(Ordering::SeqCst) {
+        let samples = match frames.recv_timeout(Duration::from_millis(100)) {
+            Ok(samples) => samples,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        if !tap.is_playing() {
+            detector.reset();
+            continue;
+        }
+
+        let reference = tap.recent(samples.len() + MAX_ECHO_DELAY_SAMPLES);
+        if !detector.push(&samples, &reference) {
+            continue;
+        }
+
+        tracing::info!("Barge-in detected in voice session {}, stopping playback", session_id);
+        let player = app.state::<Mutex<TtsPlayer>>();
+        if let Err(e) = player.lock().map_err(|e| e.to_string()).and_then(|mut p| p.stop()) {
+            tracing::warn!("Failed to stop playback on barge-in: {}", e);
+        }
+        tap.clear();
+
+        let event = BargeInEvent {
+            session_id: session_id.clone(),
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Err(e) = app.emit(BARGE_IN_EVENT, event) {
+            tracing::warn!("Failed to emit barge-in event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(len: usize, freq: f32, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / CAPTURE_SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (dot(samples, samples) / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_echo_is_suppressed() {
+        let frame_len = (CAPTURE_SAMPLE_RATE * FRAME_MS / 1000) as usize;
+        // Speech-like playback; the microphone hears it 100ms later at half level
+        let playback: Vec<f32> = sine(8000, 220.0, 0.4)
+            .iter()
+            .zip(sine(8000, 530.0, 0.2))
+            .map(|(a, b)| a + b)
+            .collect();
+        let delay = 1600;
+        let echo: Vec<f32> = playback[8000 - delay - frame_len..8000 - delay].iter().map(|s| s * 0.5).collect();
+
+        let residual = suppress_echo(&echo, &playback);
+        assert!(rms(&residual) < rms(&echo) * 0.05);
+
+        // A voice unrelated to the playback passes through
+        let voice = sine(frame_len, 1500.0, 0.3);
+        let mixed: Vec<f32> = echo.iter().zip(&voice).map(|(e, v)| e + v).collect();
+        let residual = suppress_echo(&mixed, &playback);
+        assert!((rms(&residual) - rms(&voice)).abs() < rms(&voice) * 0.3);
+        assert_eq!(suppress_echo(&voice, &[]), voice);
+    }
+
+    #[test]
+    fn test_detector_ignores_echo_and_triggers_on_speech() {
+        let settings = BargeInSettings::default();
+        let playback = sine(8000, 300.0, 0.5);
+        let chunk = 480;
+
+        // Loud echo alone never interrupts
+        let mut detector = BargeInDetector::new(settings);
+        for end in (4800 + chunk..8000).step_by(chunk) {
+            let echo: Vec<f32> = playback[end - chunk - 800..end - 800].to_vec();
+            assert!(!detector.push(&echo, &playback[..end]));
+        }
+
+        // Without suppression the same echo reads as speech
+        let mut unsuppressed = BargeInDetector::new(BargeInSettings { echo_suppression: false, ..settings });
+        let echo = playback[4000..8000].to_vec();
+        assert!(unsuppressed.push(&echo, &playback));
+
+        // Speech over playback triggers once, until reset
+        let voice = sine(8000, 1200.0, 0.4);
+        assert!(detector.push(&voice, &playback));
+        assert!(!detector.push(&voice, &playback));
+        detector.reset();
+        assert!(detector.push(&voice, &playback));
+
+        let tap = PlaybackTap::new();
+        assert!(!tap.is_playing());
+        tap.push(&playback);
+        assert!(tap.is_playing());
+        assert_eq!(tap.recent(10), playback[playback.len() - 10..]);
+    }
+}
@@ -0,0 +1,168 @@
+// Audio Capture - Microphone input for continuous listening
+//
+// Captures audio from the default input device, downmixes to mono and
+// resamples to 16kHz so frames can be fed straight into VAD and Whisper.
+// Native capture requires the `audio-capture` feature (cpal).
+
+#![allow(dead_code)]
+
+use crate::voice::stt::resample_audio;
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+
+/// Sample rate delivered to consumers (Whisper requires 16kHz mono)
+pub const CAPTURE_SAMPLE_RATE: u32 = 16000;
+
+/// Audio capture handle
+///
+/// The input stream lives on a dedicated thread; dropping the handle or
+/// calling `stop` closes the stream.
+pub struct AudioCapture {
+    stop_tx: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AudioCapture {
+    /// Start capturing from the default input device
+    ///
+    /// Each captured buffer is sent to `frames` as 16kHz mono samples.
+    #[cfg(feature = "audio-capture")]
+    pub fn start(frames: Sender<Vec<f32>>) -> Result<Self, String> {
+        let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<(), String>>();
+
+        // cpal streams are not Send, so the stream is built and owned by this thread
+        let thread = std::thread::spawn(move || {
+            let stream = match build_input_stream(frames) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+
+            // Keep the stream alive until asked to stop
+            let _ = stop_rx.recv();
+            drop(stream);
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| "Audio capture thread exited unexpectedly".to_string())??;
+
+        Ok(Self {
+            stop_tx: Some(stop_tx),
+            thread: Some(thread),
+        })
+    }
+
+    /// Start capturing from the default input device
+    #[cfg(not(feature = "audio-capture"))]
+    pub fn start(_frames: Sender<Vec<f32>>) -> Result<Self, String> {
+        Err("Microphone capture is not available: built without the audio-capture feature".to_string())
+    }
+
+    /// Stop capturing and release the input device
+    pub fn stop(&mut self) {
+        if let Some(tx) = self.stop_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(feature = "audio-capture")]
+fn build_input_stream(frames: Sender<Vec<f32>>) -> Result<cpal::Stream, String> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| "No input device available".to_string())?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to get input config: {}", e))?;
+
+    let sample_rate = supported.sample_rate().0;
+    let channels = supported.channels();
+    let sample_format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+
+    let on_error = |e: cpal::StreamError| {
+        tracing::error!("Audio capture stream error: {}", e);
+    };
+
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let _ = frames.send(to_capture_format(data, channels, sample_rate));
+            },
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                let samples: Vec<f32> = data.iter().map(|&s| s as f32 / 32768.0).collect();
+                let _ = frames.send(to_capture_format(&samples, channels, sample_rate));
+            },
+            on_error,
+            None,
+        ),
+        format => return Err(format!("Unsupported input sample format: {:?}", format)),
+    }
+    .map_err(|e| format!("Failed to open input stream: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start input stream: {}", e))?;
+
+    tracing::info!("Audio capture started: {} Hz, {} ch", sample_rate, channels);
+    Ok(stream)
+}
+
+/// Convert interleaved device samples to 16kHz mono
+pub fn to_capture_format(data: &[f32], channels: u16, sample_rate: u32) -> Vec<f32> {
+    let mono = downmix(data, channels);
+    resample_audio(&mono, sample_rate, CAPTURE_SAMPLE_RATE)
+}
+
+/// Average interleaved channels into a single mono channel
+pub fn downmix(data: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+
+    data.chunks(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downmix() {
+        let stereo = vec![1.0, 0.0, 0.5, 0.5, -1.0, 1.0];
+        assert_eq!(downmix(&stereo, 2), vec![0.5, 0.5, 0.0]);
+        assert_eq!(downmix(&stereo, 1), stereo);
+    }
+
+    #[test]
+    fn test_to_capture_format() {
+        let stereo_48k = vec![0.25; 48000 * 2 / 100]; // 10ms of stereo audio
+        let samples = to_capture_format(&stereo_48k, 2, 48000);
+        assert_eq!(samples.len(), 160);
+    }
+}
@@ -4,6 +4,7 @@
 
 #![allow(dead_code)]
 
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -414,6 +415,8 @@ pub struct VoiceConversationManager {
     current_language: String,
     /// Conversation history
     history: Vec<VoiceMessage>,
+    /// Languages pinned per conversation, bypassing detection
+    pinned_languages: HashMap<String, String>,
 }
 
 /// Voice message in conversation
@@ -430,6 +433,7 @@ impl VoiceConversationManager {
         Self {
             current_language: "en".to_string(),
             history: Vec::new(),
+            pinned_languages: HashMap::new(),
         }
     }
 
@@ -438,14 +442,32 @@ impl VoiceConversationManager {
         self.current_language = language;
     }
 
-    /// Detect language from transcript (simplified)
+    /// Detect language from transcript using character ranges
     pub fn detect_language(&self, transcript: &str) -> String {
-        // Simple heuristic: Korean characters detection
-        if transcript.chars().any(is_hangul) {
-            "ko".to_string()
-        } else {
-            "en".to_string()
-        }
+        detect_script_language(transcript).unwrap_or("en").to_string()
+    }
+
+    /// Pin a conversation to a language
+    pub fn pin_language(&mut self, conversation_id: String, language: String) {
+        self.pinned_languages.insert(conversation_id, language);
+    }
+
+    /// Remove a conversation's language pin, returning the previous language
+    pub fn unpin_language(&mut self, conversation_id: &str) -> Option<String> {
+        self.pinned_languages.remove(conversation_id)
+    }
+
+    /// Language pinned for a conversation
+    pub fn pinned_language(&self, conversation_id: &str) -> Option<&str> {
+        self.pinned_languages.get(conversation_id).map(String::as_str)
+    }
+
+    /// Language to use for a transcript: the conversation's pin, else detection
+    pub fn resolve_language(&self, conversation_id: Option<&str>, transcript: &str) -> String {
+        conversation_id
+            .and_then(|id| self.pinned_language(id))
+            .map(str::to_string)
+            .unwrap_or_else(|| self.detect_language(transcript))
     }
 
     /// Add message to conversation
@@ -486,29 +508,76 @@ fn is_hangul(c: char) -> bool {
     }
 }
 
+/// Language implied by a character's script, if it identifies one
+fn script_language(c: char) -> Option<&'static str> {
+    match c {
+        c if is_hangul(c) => Some("ko"),
+        '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' => Some("ja"), // Hiragana, Katakana
+        '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' => Some("zh"), // CJK ideographs
+        '\u{0400}'..='\u{04FF}' => Some("ru"),                             // Cyrillic
+        '\u{0600}'..='\u{06FF}' => Some("ar"),                             // Arabic
+        '\u{0590}'..='\u{05FF}' => Some("he"),                             // Hebrew
+        '\u{0900}'..='\u{097F}' => Some("hi"),                             // Devanagari
+        '\u{0E00}'..='\u{0E7F}' => Some("th"),                             // Thai
+        '\u{0370}'..='\u{03FF}' => Some("el"),                             // Greek
+        _ => None,
+    }
+}
+
+/// Detect a transcript's language from its dominant non-Latin script
+///
+/// Returns `None` for Latin-script or script-less text. Japanese mixes kana
+/// with kanji, so any kana marks the text as Japanese.
+pub fn detect_script_language(text: &str) -> Option<&'static str> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for lang in text.chars().filter_map(script_language) {
+        *counts.entry(lang).or_insert(0) += 1;
+    }
+
+    if counts.contains_key("ja") {
+        return Some("ja");
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(lang, _)| lang)
+}
+
 // ============================================================================
 // Tauri Commands - Voice Command Parsing (v0.5)
 // ============================================================================
 
 use crate::voice::ParsedVoiceCommand;
+use std::sync::Mutex;
 
 /// Parse voice command transcript
+///
+/// Without an explicit language, uses the conversation's pinned language or
+/// detects it from the transcript.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub fn parse_voice_command(
+    manager: tauri::State<'_, Mutex<VoiceConversationManager>>,
     transcript: String,
     language: Option<String>,
-) -> Result<ParsedVoiceCommand, String> {
-    let parser = VoiceCommandParser::new();
-    let lang = language.unwrap_or_else(|| {
-        // Auto-detect language
-        if transcript.chars().any(is_hangul) {
-            "ko".to_string()
-        } else {
-            "en".to_string()
+    conversation_id: Option<String>,
+) -> Result<ParsedVoiceCommand, AppError> {
+    let lang = match language {
+        Some(lang) => lang,
+        None => {
+            let manager = manager.lock()?;
+            manager.resolve_language(conversation_id.as_deref(), &transcript)
         }
-    });
+    };
+
+    Ok(parse_transcript(&transcript, &lang))
+}
 
-    let command = parser.parse(&transcript, &lang);
+/// Parse a transcript in a known language
+pub fn parse_transcript(transcript: &str, lang: &str) -> ParsedVoiceCommand {
+    let parser = VoiceCommandParser::new();
+    let command = parser.parse(transcript, lang);
 
     // Convert VoiceAction to serializable format (use mod.rs VoiceAction)
     use crate::voice::VoiceAction as ModVoiceAction;
@@ -531,28 +600,76 @@ pub fn parse_voice_command(
         VoiceAction::Unknown => ModVoiceAction::Unknown,
     };
 
-    Ok(ParsedVoiceCommand {
+    ParsedVoiceCommand {
         transcript: command.transcript,
         language: command.language,
         action,
         parameters: command.parameters,
         confidence: command.confidence,
-    })
+    }
 }
 
+/// Minimum Whisper probability to trust audio language detection
+const AUDIO_DETECTION_THRESHOLD: f32 = 0.5;
+
 /// Detect language from transcript
+///
+/// When audio is supplied and the STT engine is loaded, Whisper identifies
+/// the spoken language; otherwise the transcript's script is used.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn detect_voice_language(transcript: String, audio_data: Option<Vec<u8>>) -> Result<String, AppError> {
+    if let Some(audio) = audio_data {
+        // Whisper inference is CPU-bound; keep it off the async runtime
+        let detected = tokio::task::spawn_blocking(move || crate::voice::stt::detect_language(&audio))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match detected {
+            Ok((language, probability)) if probability >= AUDIO_DETECTION_THRESHOLD => return Ok(language),
+            Ok((language, probability)) => {
+                tracing::debug!("Low confidence audio language {} ({:.2})", language, probability)
+            }
+            Err(e) => tracing::debug!("Audio language detection unavailable: {}", e),
+        }
+    }
+
+    Ok(detect_script_language(&transcript).unwrap_or("en").to_string())
+}
+
+/// Pin a conversation to a language, or clear the pin with `None`
 #[tauri::command]
-pub fn detect_voice_language(transcript: String) -> String {
-    if transcript.chars().any(is_hangul) {
-        "ko".to_string()
-    } else {
-        "en".to_string()
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn pin_voice_language(
+    manager: tauri::State<'_, Mutex<VoiceConversationManager>>,
+    conversation_id: String,
+    language: Option<String>,
+) -> Result<(), AppError> {
+    let mut manager = manager.lock()?;
+    match language {
+        Some(language) => manager.pin_language(conversation_id, language),
+        None => {
+            manager.unpin_language(&conversation_id);
+        }
     }
+    Ok(())
+}
+
+/// Get the language pinned for a conversation
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_pinned_voice_language(
+    manager: tauri::State<'_, Mutex<VoiceConversationManager>>,
+    conversation_id: String,
+) -> Result<Option<String>, AppError> {
+    let manager = manager.lock()?;
+    Ok(manager.pinned_language(&conversation_id).map(str::to_string))
 }
 
 /// Validate voice command format
 #[tauri::command]
-pub fn validate_voice_command(transcript: String) -> Result<bool, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn validate_voice_command(transcript: String) -> Result<bool, AppError> {
     if transcript.trim().is_empty() {
         return Ok(false);
     }
@@ -569,6 +686,7 @@ pub fn validate_voice_command(transcript: String) -> Result<bool, String> {
 
 /// Get supported voice command patterns
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 pub fn get_voice_command_patterns(language: String) -> Vec<VoiceCommandPattern> {
     let parser = VoiceCommandParser::new();
 
@@ -637,4 +755,26 @@ mod tests {
         assert_eq!(manager.detect_language("Hello"), "en");
         assert_eq!(manager.detect_language("안녕"), "ko");
     }
+
+    #[test]
+    fn test_script_language_detection() {
+        assert_eq!(detect_script_language("こんにちは世界"), Some("ja"));
+        assert_eq!(detect_script_language("你好世界"), Some("zh"));
+        assert_eq!(detect_script_language("Привет, как дела?"), Some("ru"));
+        assert_eq!(detect_script_language("open the 設定 panel"), Some("zh"));
+        assert_eq!(detect_script_language("Hello world"), None);
+    }
+
+    #[test]
+    fn test_language_pinning() {
+        let mut manager = VoiceConversationManager::new();
+        assert_eq!(manager.resolve_language(Some("conv-1"), "Привет"), "ru");
+
+        manager.pin_language("conv-1".to_string(), "en".to_string());
+        assert_eq!(manager.resolve_language(Some("conv-1"), "Привет"), "en");
+        assert_eq!(manager.resolve_language(Some("conv-2"), "안녕"), "ko");
+
+        assert_eq!(manager.unpin_language("conv-1").as_deref(), Some("en"));
+        assert_eq!(manager.resolve_language(Some("conv-1"), "Привет"), "ru");
+    }
 }
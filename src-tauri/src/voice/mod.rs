@@ -4,7 +4,15 @@
 
 pub mod stt;
 pub mod tts;
+pub mod tts_providers;
+pub mod playback;
 pub mod commands;
+pub mod capture;
+pub mod wake_word;
+pub mod stream;
+pub mod models;
+pub mod barge_in;
+pub mod vad;
 
 
 use serde::{Deserialize, Serialize};
@@ -16,9 +24,13 @@ pub struct VoiceSettings {
     pub enabled: bool,
     pub stt_model: String,
     pub tts_voice: String,
+    pub tts_provider: String,
     pub language: String,
     pub wake_word: Option<String>,
     pub vad_sensitivity: f32,
+    /// Interrupting speech playback by talking over it
+    #[serde(default)]
+    pub barge_in: barge_in::BargeInSettings,
 }
 
 impl Default for VoiceSettings {
@@ -27,9 +39,11 @@ impl Default for VoiceSettings {
             enabled: false,
             stt_model: "base".to_string(),
             tts_voice: "default".to_string(),
+            tts_provider: "local".to_string(),
             language: "en".to_string(),
             wake_word: None,
             vad_sensitivity: 0.5,
+            barge_in: barge_in::BargeInSettings::default(),
         }
     }
 }
@@ -0,0 +1,468 @@
+// Whisper Model Manager - Download, verify and remove STT models
+//
+// Downloads go to a `.part` file next to the final model so an interrupted or
+// cancelled download resumes with an HTTP range request. The completed file is
+// checked before it is moved into place, against the SHA1 that whisper.cpp
+// publishes for each model and that is pinned here. Only models without a
+// pinned checksum fall back to the SHA256 in the mirror's LFS metadata.
+
+#![allow(dead_code)]
+
+use crate::error::AppError;
+use crate::voice::stt::get_model_download_url;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// Event carrying download progress
+pub const PROGRESS_EVENT: &str = "model-download://progress";
+
+/// Model repository file listing with LFS checksums, for unpinned models
+const MODEL_TREE_URL: &str = "https://huggingface.co/api/models/ggerganov/whisper.cpp/tree/main";
+
+/// SHA1 of each model as listed in whisper.cpp's `models/README.md`, so a
+/// spoofed or compromised mirror cannot supply both a model and its checksum
+const PINNED_SHA1: &[(&str, &str)] = &[
+    ("tiny", "bd577a113a864445d4c299885e0cb97d4ba92b5f"),
+    ("tiny-en", "c78c86eb1a8faa21b369bcd33207cc90d64ae9df"),
+    ("base", "465707469ff3a37a2b9b8d8f89f2f99de7299dac"),
+    ("base-en", "137c40403d78fd54d454da0f9bd998f78703390c"),
+    ("small", "55356645c2b361a969dfd0ef2c5a50d530afd8d5"),
+    ("small-en", "db8a495a91d927739e50b3fc1cc4c6b8f6c2d022"),
+    ("medium", "fd9727b6e1217c2f614f9b698455c4ffd82463b4"),
+    ("medium-en", "8c30f0e44ce9560643ebd10bbe50cd20eafd3723"),
+    ("large-v1", "b1caaf735c4cc1429223d5a74f0f4d0b9b59a299"),
+    ("large-v2", "0f4c8e34f21cf1a914c59d8b3ce882345ad349d6"),
+    ("large-v3", "ad82bf6a9043ceed055076d0fd39f5f186ff8062"),
+];
+
+/// Minimum time between progress events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Download progress event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub model: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    /// One of `downloading`, `verifying`, `completed`, `cancelled`, `failed`
+    pub status: String,
+}
+
+/// In-flight downloads, keyed by model name
+#[derive(Default)]
+pub struct ModelDownloads {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl ModelDownloads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a download, returning its cancellation flag
+    fn begin(&self, model: &str) -> Result<Arc<AtomicBool>, String> {
+        let mut active = self.active.lock().map_err(|e| e.to_string())?;
+        if active.contains_key(model) {
+            return Err(format!("Model {} is already downloading", model));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        active.insert(model.to_string(), flag.clone());
+        Ok(flag)
+    }
+
+    fn end(&self, model: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(model);
+        }
+    }
+
+    /// Request cancellation of a download
+    pub fn cancel(&self, model: &str) -> bool {
+        self.active
+            .lock()
+            .ok()
+            .and_then(|active| active.get(model).cloned())
+            .map(|flag| flag.store(true, Ordering::SeqCst))
+            .is_some()
+    }
+
+    pub fn is_downloading(&self, model: &str) -> bool {
+        self.active.lock().map(|a| a.contains_key(model)).unwrap_or(false)
+    }
+}
+
+/// Directory where Whisper models are stored
+pub fn model_dir() -> Result<PathBuf, String> {
+    let mut path = dirs::data_dir().ok_or_else(|| "Cannot determine data directory".to_string())?;
+    path.push("ai-assistant-tauri");
+    path.push("models");
+    path.push("whisper");
+    Ok(path)
+}
+
+fn model_filename(model: &str) -> String {
+    format!("ggml-{}.bin", model)
+}
+
+fn partial_path(model_path: &Path) -> PathBuf {
+    model_path.with_extension("bin.part")
+}
+
+/// Checksum a downloaded model must match
+#[derive(Debug, Clone, PartialEq)]
+pub enum Checksum {
+    Sha1(String),
+    Sha256(String),
+}
+
+impl Checksum {
+    /// The same kind of checksum computed over `path`
+    fn of_file(&self, path: &Path) -> Result<Self, String> {
+        Ok(match self {
+            Self::Sha1(_) => Self::Sha1(digest_file::<Sha1>(path)?),
+            Self::Sha256(_) => Self::Sha256(sha256_file(path)?),
+        })
+    }
+}
+
+impl std::fmt::Display for Checksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sha1(hex) => write!(f, "sha1:{}", hex),
+            Self::Sha256(hex) => write!(f, "sha256:{}", hex),
+        }
+    }
+}
+
+/// Pinned checksum of a model, if it has one
+pub fn pinned_checksum(model: &str) -> Option<Checksum> {
+    PINNED_SHA1
+        .iter()
+        .find(|(name, _)| *name == model)
+        .map(|(_, sha1)| Checksum::Sha1(sha1.to_string()))
+}
+
+/// Compute the SHA256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    digest_file::<Sha256>(path)
+}
+
+fn digest_file<D: Digest>(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Find a file's SHA256 in the repository tree listing
+pub fn checksum_from_tree(tree: &serde_json::Value, filename: &str) -> Option<String> {
+    tree.as_array()?
+        .iter()
+        .find(|entry| entry.get("path").and_then(|p| p.as_str()) == Some(filename))?
+        .get("lfs")?
+        .get("oid")?
+        .as_str()
+        .map(|s| s.to_lowercase())
+}
+
+async fn fetch_expected_checksum(client: &reqwest::Client, filename: &str) -> Result<String, String> {
+    let body = client
+        .get(MODEL_TREE_URL)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch model checksums: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to fetch model checksums: {}", e))?;
+    let tree: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| format!("Failed to parse model checksums: {}", e))?;
+
+    checksum_from_tree(&tree, filename)
+        .ok_or_else(|| format!("No published checksum for {}", filename))
+}
+
+fn emit_progress(app: &tauri::AppHandle, model: &str, downloaded: u64, total: Option<u64>, status: &str) {
+    let progress = DownloadProgress {
+        model: model.to_string(),
+        downloaded_bytes: downloaded,
+        total_bytes: total,
+        status: status.to_string(),
+    };
+    if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+        tracing::warn!("Failed to emit download progress: {}", e);
+    }
+}
+
+/// Download the model to its `.part` file, resuming if one exists
+async fn fetch_to_partial(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    model: &str,
+    url: &str,
+    part_path: &Path,
+    cancelled: &AtomicBool,
+) -> Result<(), String> {
+    let existing = std::fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Download request failed: {}", e))?;
+    // Nothing left past the end of the `.part` file: it already holds the
+    // whole model, which verification checks next
+    if existing > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        tracing::info!("Download of {} already complete at {} bytes", model, existing);
+        return Ok(());
+    }
+    let response = response
+        .error_for_status()
+        .map_err(|e| format!("Download request failed: {}", e))?;
+
+    // Servers that ignore the range send the whole file again
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { existing } else { 0 };
+    let total = response.content_length().map(|len| len + downloaded);
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(resumed)
+        .write(true)
+        .truncate(!resumed)
+        .open(part_path)
+        .map_err(|e| format!("Failed to open download file: {}", e))?;
+
+    if resumed {
+        tracing::info!("Resuming download of {} at {} bytes", model, existing);
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut last_emit = Instant::now();
+
+    while let Some(chunk) = stream.next().await {
+        if cancelled.load(Ordering::SeqCst) {
+            file.flush().map_err(|e| e.to_string())?;
+            return Err("Download cancelled".to_string());
+        }
+
+        let chunk = chunk.map_err(|e| format!("Download interrupted: {}", e))?;
+        file.write_all(&chunk).map_err(|e| format!("Failed to write download: {}", e))?;
+        downloaded += chunk.len() as u64;
+
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            emit_progress(app, model, downloaded, total, "downloading");
+            last_emit = Instant::now();
+        }
+    }
+
+    file.flush().map_err(|e| e.to_string())?;
+    emit_progress(app, model, downloaded, total, "downloading");
+    Ok(())
+}
+
+async fn download(
+    app: &tauri::AppHandle,
+    model: &str,
+    cancelled: &AtomicBool,
+) -> Result<PathBuf, String> {
+    let url = get_model_download_url(model.to_string())?;
+    let filename = model_filename(model);
+
+    let dir = model_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create model directory: {}", e))?;
+
+    let model_path = dir.join(&filename);
+    if model_path.exists() {
+        return Ok(model_path);
+    }
+    let part_path = partial_path(&model_path);
+
+    let client = reqwest::Client::new();
+    let expected = match pinned_checksum(model) {
+        Some(checksum) => checksum,
+        None => {
+            tracing::warn!("No pinned checksum for {}, using the mirror's", model);
+            Checksum::Sha256(fetch_expected_checksum(&client, &filename).await?)
+        }
+    };
+
+    fetch_to_partial(app, &client, model, &url, &part_path, cancelled).await?;
+
+    emit_progress(app, model, 0, None, "verifying");
+    let actual = {
+        let (part_path, expected) = (part_path.clone(), expected.clone());
+        tokio::task::spawn_blocking(move || expected.of_file(&part_path))
+            .await
+            .map_err(|e| e.to_string())??
+    };
+
+    if actual != expected {
+        // A corrupt partial file can't be resumed, start over next time
+        let _ = std::fs::remove_file(&part_path);
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            filename, expected, actual
+        ));
+    }
+
+    std::fs::rename(&part_path, &model_path)
+        .map_err(|e| format!("Failed to move model into place: {}", e))?;
+
+    Ok(model_path)
+}
+
+/// Download a Whisper model with progress events
+///
+/// Emits `model-download://progress` while downloading. Interrupted or
+/// cancelled downloads resume from where they stopped.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn download_model(
+    app: tauri::AppHandle,
+    downloads: tauri::State<'_, ModelDownloads>,
+    model_name: String,
+) -> Result<String, AppError> {
+    let cancelled = downloads.begin(&model_name)?;
+    let result = download(&app, &model_name, &cancelled).await;
+    downloads.end(&model_name);
+
+    match result {
+        Ok(path) => {
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            emit_progress(&app, &model_name, size, Some(size), "completed");
+            tracing::info!("Model {} ready at {}", model_name, path.display());
+            Ok(path.display().to_string())
+        }
+        Err(e) => {
+            let status = if cancelled.load(Ordering::SeqCst) { "cancelled" } else { "failed" };
+            emit_progress(&app, &model_name, 0, None, status);
+            Err(e.into())
+        }
+    }
+}
+
+/// Cancel an in-flight model download
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn cancel_model_download(
+    downloads: tauri::State<'_, ModelDownloads>,
+    model_name: String,
+) -> Result<bool, AppError> {
+    Ok(downloads.cancel(&model_name))
+}
+
+/// Delete a downloaded model and any partial download
+///
+/// Returns the number of bytes reclaimed.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_model(
+    downloads: tauri::State<'_, ModelDownloads>,
+    model_name: String,
+) -> Result<u64, AppError> {
+    // Validates the model name
+    get_model_download_url(model_name.clone())?;
+
+    if downloads.is_downloading(&model_name) {
+        return Err(AppError::invalid_input(format!("Model {} is downloading; cancel it first", model_name)));
+    }
+
+    let model_path = model_dir()?.join(model_filename(&model_name));
+    let mut reclaimed = 0;
+
+    for path in [partial_path(&model_path), model_path] {
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            std::fs::remove_file(&path).map_err(|e| crate::files::access::FileError::io(&path, e))?;
+            reclaimed += metadata.len();
+        }
+    }
+
+    Ok(reclaimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_pinned_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let expected = Checksum::Sha1("a9993e364706816aba3e25717850c26c9cd0d89d".to_string());
+        assert_eq!(expected.of_file(&path).unwrap(), expected);
+        assert_eq!(
+            pinned_checksum("base"),
+            Some(Checksum::Sha1("465707469ff3a37a2b9b8d8f89f2f99de7299dac".to_string()))
+        );
+        assert_eq!(pinned_checksum("large"), None);
+    }
+
+    #[test]
+    fn test_checksum_from_tree() {
+        let tree = serde_json::json!([
+            {"type": "file", "path": "README.md"},
+            {"type": "file", "path": "ggml-tiny.bin", "lfs": {"oid": "ABC123", "size": 10}},
+        ]);
+
+        assert_eq!(checksum_from_tree(&tree, "ggml-tiny.bin"), Some("abc123".to_string()));
+        assert_eq!(checksum_from_tree(&tree, "README.md"), None);
+        assert_eq!(checksum_from_tree(&tree, "ggml-base.bin"), None);
+    }
+
+    #[test]
+    fn test_partial_path() {
+        let path = PathBuf::from("/models/ggml-base.bin");
+        assert_eq!(partial_path(&path), PathBuf::from("/models/ggml-base.bin.part"));
+    }
+
+    #[test]
+    fn test_cancel_download() {
+        let downloads = ModelDownloads::new();
+        assert!(!downloads.cancel("base"));
+
+        let flag = downloads.begin("base").unwrap();
+        assert!(downloads.begin("base").is_err());
+        assert!(downloads.cancel("base"));
+        assert!(flag.load(Ordering::SeqCst));
+
+        downloads.end("base");
+        assert!(!downloads.is_downloading("base"));
+    }
+}
@@ -0,0 +1,404 @@
+// TTS Playback - Backend speech output queue
+//
+// Text passed to `tts_speak` is split into sentences and queued. A playback
+// thread synthesizes each sentence with the selected TTS provider and appends
+// it to an audio sink, staying one sentence ahead of what is playing. Streamed
+// responses can be fed chunk by chunk: incomplete trailing sentences are held
+// until more text arrives. Played samples are copied into a `PlaybackTap`
+// for barge-in echo suppression. Native output requires the
+// `audio-playback` feature (rodio).
+
+#![allow(dead_code)]
+
+use crate::error::AppError;
+use crate::voice::barge_in::PlaybackTap;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+/// Characters that end a sentence
+const SENTENCE_TERMINATORS: &[char] = &['.', '!', '?', '\n'];
+
+/// Full-width terminators that end a sentence without trailing whitespace
+const CJK_TERMINATORS: &[char] = &['。', '！', '？'];
+
+/// Synthesized sentences buffered ahead of the one playing
+#[cfg(feature = "audio-playback")]
+const MAX_BUFFERED_SENTENCES: usize = 2;
+
+/// Accumulates streamed text and yields complete sentences
+#[derive(Debug, Default)]
+pub struct SentenceBuffer {
+    pending: String,
+}
+
+impl SentenceBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append text, returning the sentences it completes
+    pub fn push(&mut self, text: &str) -> Vec<String> {
+        self.pending.push_str(text);
+
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let mut chars = self.pending.char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            let end = i + c.len_utf8();
+            let boundary = if CJK_TERMINATORS.contains(&c) {
+                true
+            } else if SENTENCE_TERMINATORS.contains(&c) {
+                // Wait for the following character so "3.5" or "e.g." are not split
+                matches!(chars.peek(), Some((_, next)) if next.is_whitespace())
+            } else {
+                false
+            };
+
+            if boundary {
+                let sentence = self.pending[start..end].trim();
+                if !sentence.is_empty() {
+                    sentences.push(sentence.to_string());
+                }
+                start = end;
+            }
+        }
+
+        self.pending.drain(..start);
+        sentences
+    }
+
+    /// Take whatever text remains as a final sentence
+    pub fn flush(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.pending);
+        let rest = rest.trim();
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+enum PlayerCommand {
+    Speak { sentences: Vec<String>, language: String },
+    Pause,
+    Resume,
+    Stop,
+    Shutdown,
+}
+
+/// Speech playback queue
+///
+/// The output stream lives on a dedicated thread, started on first use.
+#[derive(Default)]
+pub struct TtsPlayer {
+    buffer: SentenceBuffer,
+    sender: Option<Sender<PlayerCommand>>,
+    thread: Option<JoinHandle<()>>,
+    tap: PlaybackTap,
+}
+
+impl TtsPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Samples being played, shared with barge-in detection
+    pub fn tap(&self) -> PlaybackTap {
+        self.tap.clone()
+    }
+
+    /// Stop playback and clear the queue
+    pub fn stop(&mut self) -> Result<(), String> {
+        self.buffer.clear();
+        if self.sender.is_none() {
+            return Ok(());
+        }
+        self.send(PlayerCommand::Stop)
+    }
+
+    fn send(&mut self, command: PlayerCommand) -> Result<(), String> {
+        if self.sender.is_none() {
+            self.start()?;
+        }
+
+        let sent = self.sender.as_ref().map(|s| s.send(command).is_ok()).unwrap_or(false);
+        if !sent {
+            self.sender = None;
+            return Err("Playback thread has stopped".to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "audio-playback")]
+    fn start(&mut self) -> Result<(), String> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<(), String>>();
+        let tap = self.tap.clone();
+
+        // rodio output streams are not Send, so the stream is owned by this thread
+        let thread = std::thread::spawn(move || run_player(rx, ready_tx, tap));
+
+        ready_rx
+            .recv()
+            .map_err(|_| "Playback thread exited unexpectedly".to_string())??;
+
+        self.sender = Some(tx);
+        self.thread = Some(thread);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "audio-playback"))]
+    fn start(&mut self) -> Result<(), String> {
+        Err("Audio playback is not available: built without the audio-playback feature".to_string())
+    }
+
+    /// Stop playback and shut down the playback thread
+    pub fn shutdown(&mut self) {
+        if let Some(tx) = self.sender.take() {
+            let _ = tx.send(PlayerCommand::Shutdown);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for TtsPlayer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Source that copies the samples it plays into a `PlaybackTap`
+#[cfg(feature = "audio-playback")]
+struct TappedSource<S> {
+    inner: S,
+    tap: PlaybackTap,
+    buffer: Vec<f32>,
+    /// Samples collected before they are handed to the tap (10ms)
+    chunk: usize,
+}
+
+#[cfg(feature = "audio-playback")]
+impl<S: rodio::Source<Item = f32>> TappedSource<S> {
+    fn new(inner: S, tap: PlaybackTap) -> Self {
+        let chunk = (inner.sample_rate() / 100).max(1) as usize * inner.channels().max(1) as usize;
+        Self { inner, tap, buffer: Vec::with_capacity(chunk), chunk }
+    }
+
+    fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            let samples = crate::voice::capture::to_capture_format(&self.buffer, self.inner.channels(), self.inner.sample_rate());
+            self.tap.push(&samples);
+            self.buffer.clear();
+        }
+    }
+}
+
+#[cfg(feature = "audio-playback")]
+impl<S: rodio::Source<Item = f32>> Iterator for TappedSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next();
+        match sample {
+            Some(s) => {
+                self.buffer.push(s);
+                if self.buffer.len() >= self.chunk {
+                    self.flush();
+                }
+            }
+            None => self.flush(),
+        }
+        sample
+    }
+}
+
+#[cfg(feature = "audio-playback")]
+impl<S: rodio::Source<Item = f32>> rodio::Source for TappedSource<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.inner.total_duration()
+    }
+}
+
+#[cfg(feature = "audio-playback")]
+fn run_player(commands: std::sync::mpsc::Receiver<PlayerCommand>, ready: Sender<Result<(), String>>, tap: PlaybackTap) {
+    use rodio::Source;
+    use std::collections::VecDeque;
+    use std::sync::mpsc::TryRecvError;
+
+    let (_stream, handle) = match rodio::OutputStream::try_default() {
+        Ok(output) => output,
+        Err(e) => {
+            let _ = ready.send(Err(format!("No audio output device: {}", e)));
+            return;
+        }
+    };
+    let sink = match rodio::Sink::try_new(&handle) {
+        Ok(sink) => sink,
+        Err(e) => {
+            let _ = ready.send(Err(format!("Failed to open audio output: {}", e)));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    let mut queue: VecDeque<(String, String)> = VecDeque::new();
+
+    loop {
+        // Block only when there is nothing left to synthesize
+        let command = if queue.is_empty() {
+            match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => break,
+            }
+        } else {
+            match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        };
+
+        if let Some(command) = command {
+            match command {
+                PlayerCommand::Speak { sentences, language } => {
+                    queue.extend(sentences.into_iter().map(|s| (s, language.clone())));
+                }
+                PlayerCommand::Pause => sink.pause(),
+                PlayerCommand::Resume => sink.play(),
+                PlayerCommand::Stop => {
+                    queue.clear();
+                    sink.clear();
+                    sink.play();
+                }
+                PlayerCommand::Shutdown => break,
+            }
+            continue;
+        }
+
+        if sink.len() >= MAX_BUFFERED_SENTENCES {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            continue;
+        }
+
+        let Some((sentence, language)) = queue.pop_front() else {
+            continue;
+        };
+
+        let result = crate::voice::tts::synthesize(sentence, language).and_then(|synthesis| {
+            rodio::Decoder::new(std::io::Cursor::new(synthesis.audio_data))
+                .map_err(|e| format!("Failed to decode speech: {}", e))
+        });
+        match result {
+            Ok(source) => sink.append(TappedSource::new(source.convert_samples::<f32>(), tap.clone())),
+            Err(e) => tracing::warn!("Skipping sentence that failed to synthesize: {}", e),
+        }
+    }
+
+    sink.stop();
+}
+
+/// Speak text through the backend playback queue
+///
+/// With `streaming`, an incomplete trailing sentence is held until more text
+/// arrives; call once more without it to flush the rest. Returns the number of
+/// sentences queued.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tts_speak(
+    player: tauri::State<'_, Mutex<TtsPlayer>>,
+    text: String,
+    language: Option<String>,
+    streaming: Option<bool>,
+) -> Result<usize, AppError> {
+    let mut player = player.lock()?;
+
+    let mut sentences = player.buffer.push(&text);
+    if !streaming.unwrap_or(false) {
+        sentences.extend(player.buffer.flush());
+    }
+
+    let count = sentences.len();
+    if count > 0 {
+        player.send(PlayerCommand::Speak {
+            sentences,
+            language: language.unwrap_or_else(|| "en".to_string()),
+        })?;
+    }
+
+    Ok(count)
+}
+
+/// Pause speech playback
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tts_pause(player: tauri::State<'_, Mutex<TtsPlayer>>) -> Result<(), AppError> {
+    let mut player = player.lock()?;
+    Ok(player.send(PlayerCommand::Pause)?)
+}
+
+/// Resume paused speech playback
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tts_resume(player: tauri::State<'_, Mutex<TtsPlayer>>) -> Result<(), AppError> {
+    let mut player = player.lock()?;
+    Ok(player.send(PlayerCommand::Resume)?)
+}
+
+/// Stop speech playback and clear the queue
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn tts_stop(player: tauri::State<'_, Mutex<TtsPlayer>>) -> Result<(), AppError> {
+    let mut player = player.lock()?;
+    Ok(player.stop()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_splitting() {
+        let mut buffer = SentenceBuffer::new();
+        assert_eq!(
+            buffer.push("Hello there. How are you? Fine"),
+            vec!["Hello there.", "How are you?"]
+        );
+        assert_eq!(buffer.flush().as_deref(), Some("Fine"));
+        assert_eq!(buffer.flush(), None);
+    }
+
+    #[test]
+    fn test_streamed_chunks() {
+        let mut buffer = SentenceBuffer::new();
+        assert!(buffer.push("Pi is 3").is_empty());
+        // A period is only a boundary once whitespace follows
+        assert!(buffer.push(".").is_empty());
+        assert!(buffer.push("14 roughly.").is_empty());
+        assert_eq!(buffer.push(" Next"), vec!["Pi is 3.14 roughly."]);
+        assert_eq!(buffer.flush().as_deref(), Some("Next"));
+    }
+
+    #[test]
+    fn test_cjk_terminators() {
+        let mut buffer = SentenceBuffer::new();
+        assert_eq!(buffer.push("안녕하세요。반갑습니다！더"), vec!["안녕하세요。", "반갑습니다！"]);
+        assert_eq!(buffer.flush().as_deref(), Some("더"));
+    }
+}
@@ -0,0 +1,433 @@
+// Streaming Transcription - Live captions from incrementally fed audio
+//
+// The UI starts a stream, feeds raw 16-bit PCM chunks as they are recorded and
+// finishes the stream to get the final transcript. A background thread per
+// stream splits the audio into utterances with `vad::VadSegmenter`: each
+// finished utterance is transcribed once, committed and announced with an
+// `stt://utterance` event, while the utterance in progress is re-transcribed
+// as audio arrives for `stt://partial` events. Inference cost stays bounded
+// however long the dictation runs, and silence is never transcribed.
+
+#![allow(dead_code)]
+
+use crate::error::AppError;
+use crate::voice::stt::{resample_audio, transcribe_with_engine};
+use crate::voice::vad::{Utterance, VadConfig, VadSegmenter};
+use crate::voice::TranscriptionResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use tauri::Emitter;
+
+/// Event carrying an in-progress transcript
+pub const PARTIAL_EVENT: &str = "stt://partial";
+
+/// Event carrying the transcript of a finished utterance
+pub const UTTERANCE_EVENT: &str = "stt://utterance";
+
+/// Whisper input sample rate
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Audio received between partial inferences (1 second)
+const PARTIAL_INTERVAL_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize;
+
+/// Longest utterance transcribed at once before it is split and committed
+const MAX_WINDOW_MS: u32 = 20_000;
+
+/// Longest stream accepted (10 minutes)
+const MAX_STREAM_SAMPLES: usize = 10 * 60 * WHISPER_SAMPLE_RATE as usize;
+
+/// Partial transcript event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialTranscript {
+    pub stream_id: String,
+    pub text: String,
+    pub duration_ms: u64,
+}
+
+/// Utterance transcript event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtteranceTranscript {
+    pub stream_id: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Audio buffered for a stream
+///
+/// Splits the audio into utterances, holds those finished but not yet
+/// committed to the transcript, and tracks how much audio arrived since the
+/// last partial inference.
+pub struct StreamBuffer {
+    segmenter: VadSegmenter,
+    finished: Vec<Utterance>,
+    committed: String,
+    since_partial: usize,
+    total_samples: usize,
+}
+
+impl Default for StreamBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamBuffer {
+    pub fn new() -> Self {
+        Self {
+            segmenter: VadSegmenter::new(VadConfig {
+                max_utterance_ms: MAX_WINDOW_MS,
+                ..Default::default()
+            }),
+            finished: Vec::new(),
+            committed: String::new(),
+            since_partial: 0,
+            total_samples: 0,
+        }
+    }
+
+    /// Append 16kHz samples, returning whether a partial inference is due
+    pub fn push(&mut self, samples: &[f32]) -> Result<bool, String> {
+        if self.total_samples + samples.len() > MAX_STREAM_SAMPLES {
+            return Err("Stream exceeds maximum length of 10 minutes".to_string());
+        }
+
+        self.finished.extend(self.segmenter.push(samples));
+        self.since_partial += samples.len();
+        self.total_samples += samples.len();
+
+        if self.since_partial >= PARTIAL_INTERVAL_SAMPLES {
+            self.since_partial = 0;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Take the utterances finished since the last call
+    pub fn take_utterances(&mut self) -> Vec<Utterance> {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// End the audio, taking the utterance still in progress
+    pub fn finish(&mut self) -> Option<Utterance> {
+        self.segmenter.finish()
+    }
+
+    /// Append the transcript of a finished utterance
+    pub fn commit(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if !self.committed.is_empty() {
+            self.committed.push(' ');
+        }
+        self.committed.push_str(text);
+    }
+
+    /// Full transcript given the text of the utterance in progress
+    pub fn transcript_with(&self, window_text: &str) -> String {
+        let window_text = window_text.trim();
+        match (self.committed.is_empty(), window_text.is_empty()) {
+            (true, _) => window_text.to_string(),
+            (false, true) => self.committed.clone(),
+            (false, false) => format!("{} {}", self.committed, window_text),
+        }
+    }
+
+    /// Audio of the utterance in progress; empty during silence
+    pub fn window(&self) -> &[f32] {
+        self.segmenter.current()
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.total_samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64
+    }
+}
+
+/// Convert little-endian 16-bit PCM bytes to normalized samples
+pub fn pcm16_to_f32(data: &[u8]) -> Result<Vec<f32>, String> {
+    if !data.len().is_multiple_of(2) {
+        return Err("PCM chunk must contain whole 16-bit samples".to_string());
+    }
+
+    Ok(data
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect())
+}
+
+enum StreamMessage {
+    Audio(Vec<f32>),
+    Finish(Sender<Result<TranscriptionResult, String>>),
+}
+
+/// Handle to a running transcription stream
+struct TranscriptionStream {
+    sample_rate: u32,
+    sender: Sender<StreamMessage>,
+    worker: JoinHandle<()>,
+}
+
+/// Active transcription streams
+#[derive(Default)]
+pub struct TranscriptionStreams {
+    streams: HashMap<String, TranscriptionStream>,
+}
+
+impl TranscriptionStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.streams.len()
+    }
+}
+
+/// Background loop: buffer audio, emit partials, answer the finish request
+fn run_stream(
+    app: tauri::AppHandle,
+    stream_id: String,
+    language: Option<String>,
+    messages: mpsc::Receiver<StreamMessage>,
+) {
+    let mut buffer = StreamBuffer::new();
+    let mut error: Option<String> = None;
+
+    for message in messages {
+        match message {
+            StreamMessage::Audio(samples) => {
+                if error.is_some() {
+                    continue;
+                }
+
+                let partial_due = match buffer.push(&samples) {
+                    Ok(due) => due,
+                    Err(e) => {
+                        error = Some(e);
+                        continue;
+                    }
+                };
+
+                // Transcribe each finished utterance once and commit it
+                let utterances = buffer.take_utterances();
+                for utterance in &utterances {
+                    match transcribe_with_engine(&utterance.samples, language.as_deref()) {
+                        Ok(text) => {
+                            buffer.commit(&text);
+                            let event = UtteranceTranscript {
+                                stream_id: stream_id.clone(),
+                                start_ms: utterance.start_ms,
+                                end_ms: utterance.end_ms,
+                                text: text.trim().to_string(),
+                            };
+                            if let Err(e) = app.emit(UTTERANCE_EVENT, event) {
+                                tracing::warn!("Failed to emit utterance transcript: {}", e);
+                            }
+                        }
+                        Err(e) => error = Some(e),
+                    }
+                }
+
+                if error.is_none() && (partial_due || !utterances.is_empty()) {
+                    let window_text = if buffer.window().is_empty() {
+                        Ok(String::new())
+                    } else {
+                        transcribe_with_engine(buffer.window(), language.as_deref())
+                    };
+                    match window_text {
+                        Ok(text) => {
+                            let partial = PartialTranscript {
+                                stream_id: stream_id.clone(),
+                                text: buffer.transcript_with(&text),
+                                duration_ms: buffer.duration_ms(),
+                            };
+                            if let Err(e) = app.emit(PARTIAL_EVENT, partial) {
+                                tracing::warn!("Failed to emit partial transcript: {}", e);
+                            }
+                        }
+                        Err(e) => tracing::warn!("Partial transcription failed: {}", e),
+                    }
+                }
+            }
+            StreamMessage::Finish(reply) => {
+                let result = match error.take() {
+                    Some(e) => Err(e),
+                    None => finish_transcript(&mut buffer, language.as_deref()),
+                };
+                let _ = reply.send(result);
+                return;
+            }
+        }
+    }
+}
+
+fn finish_transcript(buffer: &mut StreamBuffer, language: Option<&str>) -> Result<TranscriptionResult, String> {
+    let mut utterances = buffer.take_utterances();
+    utterances.extend(buffer.finish());
+    for utterance in &utterances {
+        let text = transcribe_with_engine(&utterance.samples, language)?;
+        buffer.commit(&text);
+    }
+
+    Ok(TranscriptionResult {
+        text: buffer.transcript_with(""),
+        confidence: 0.9,
+        language: language.unwrap_or("auto").to_string(),
+        duration_ms: buffer.duration_ms(),
+    })
+}
+
+/// Start a streaming transcription
+///
+/// Chunks passed to `transcribe_stream_feed` must be mono 16-bit PCM at `sample_rate`.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn transcribe_stream_start(
+    app: tauri::AppHandle,
+    streams: tauri::State<'_, Mutex<TranscriptionStreams>>,
+    language: Option<String>,
+    sample_rate: Option<u32>,
+) -> Result<String, AppError> {
+    let sample_rate = sample_rate.unwrap_or(WHISPER_SAMPLE_RATE);
+    if sample_rate == 0 {
+        return Err(AppError::invalid_input("Sample rate must be greater than zero"));
+    }
+
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let language = language.filter(|l| !l.is_empty());
+    let (sender, receiver) = mpsc::channel();
+
+    let worker = {
+        let stream_id = stream_id.clone();
+        std::thread::spawn(move || run_stream(app, stream_id, language, receiver))
+    };
+
+    let mut streams = streams.lock()?;
+    streams.streams.insert(
+        stream_id.clone(),
+        TranscriptionStream {
+            sample_rate,
+            sender,
+            worker,
+        },
+    );
+
+    tracing::info!("Started transcription stream {}", stream_id);
+    Ok(stream_id)
+}
+
+/// Feed a chunk of audio to a streaming transcription
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn transcribe_stream_feed(
+    streams: tauri::State<'_, Mutex<TranscriptionStreams>>,
+    stream_id: String,
+    audio_data: Vec<u8>,
+) -> Result<(), AppError> {
+    let streams = streams.lock()?;
+    let stream = streams
+        .streams
+        .get(&stream_id)
+        .ok_or_else(|| AppError::not_found(format!("Transcription stream not found: {}", stream_id)))?;
+
+    let samples = pcm16_to_f32(&audio_data)?;
+    let samples = resample_audio(&samples, stream.sample_rate, WHISPER_SAMPLE_RATE);
+
+    stream
+        .sender
+        .send(StreamMessage::Audio(samples))
+        .map_err(|_| AppError::not_found("Transcription stream has stopped"))
+}
+
+/// Finish a streaming transcription and return the final transcript
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn transcribe_stream_finish(
+    streams: tauri::State<'_, Mutex<TranscriptionStreams>>,
+    stream_id: String,
+) -> Result<TranscriptionResult, AppError> {
+    let stream = {
+        let mut streams = streams.lock()?;
+        streams
+            .streams
+            .remove(&stream_id)
+            .ok_or_else(|| AppError::not_found(format!("Transcription stream not found: {}", stream_id)))?
+    };
+
+    // Final inference can take a while; wait for it off the async runtime
+    let result = tokio::task::spawn_blocking(move || {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        stream
+            .sender
+            .send(StreamMessage::Finish(reply_tx))
+            .map_err(|_| "Transcription stream has stopped".to_string())?;
+
+        let result = reply_rx
+            .recv()
+            .map_err(|_| "Transcription stream exited unexpectedly".to_string())?;
+        let _ = stream.worker.join();
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcm16_to_f32() {
+        let data = [0x00, 0x00, 0x00, 0x40, 0x00, 0xC0];
+        assert_eq!(pcm16_to_f32(&data).unwrap(), vec![0.0, 0.5, -0.5]);
+        assert!(pcm16_to_f32(&[0x00]).is_err());
+    }
+
+    #[test]
+    fn test_partial_interval() {
+        let mut buffer = StreamBuffer::new();
+        let half_second = vec![0.0; PARTIAL_INTERVAL_SAMPLES / 2];
+
+        assert!(!buffer.push(&half_second).unwrap());
+        assert!(buffer.push(&half_second).unwrap());
+        assert!(!buffer.push(&half_second).unwrap());
+        assert_eq!(buffer.duration_ms(), 1500);
+    }
+
+    #[test]
+    fn test_window_commit() {
+        let max_window_samples = (MAX_WINDOW_MS / 1000) as usize * WHISPER_SAMPLE_RATE as usize;
+        let mut buffer = StreamBuffer::new();
+        buffer.push(&vec![0.0; 33 * 480]).unwrap();
+        assert!(buffer.window().is_empty());
+
+        // Speech is held as the window until it reaches the maximum length
+        buffer.push(&vec![0.5; max_window_samples]).unwrap();
+        assert!(buffer.take_utterances().is_empty());
+        assert!(!buffer.window().is_empty());
+
+        buffer.push(&[0.5; 480]).unwrap();
+        let utterances = buffer.take_utterances();
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(utterances[0].start_ms, 990);
+        assert!(utterances[0].end_ms - utterances[0].start_ms >= MAX_WINDOW_MS as u64);
+        assert!(buffer.window().is_empty());
+        assert!(buffer.finish().is_none());
+
+        buffer.commit(" hello world ");
+        assert_eq!(buffer.transcript_with("and more"), "hello world and more");
+        assert_eq!(buffer.transcript_with(""), "hello world");
+    }
+
+    #[test]
+    fn test_stream_length_limit() {
+        let mut buffer = StreamBuffer::new();
+        assert!(buffer.push(&vec![0.0; MAX_STREAM_SAMPLES + 1]).is_err());
+    }
+}
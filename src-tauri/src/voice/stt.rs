@@ -8,6 +8,8 @@
 
 #![allow(dead_code)]
 
+use crate::error::AppError;
+use crate::voice::vad::{self, VadConfig};
 use crate::voice::TranscriptionResult;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex, RwLock};
@@ -89,6 +91,8 @@ pub struct AudioParams {
     pub sample_rate: u32,
     pub channels: u16,
     pub bits_per_sample: u16,
+    /// Samples are IEEE float rather than integer PCM
+    pub is_float: bool,
 }
 
 impl Default for AudioParams {
@@ -97,6 +101,7 @@ impl Default for AudioParams {
             sample_rate: 16000,
             channels: 1,
             bits_per_sample: 16,
+            is_float: false,
         }
     }
 }
@@ -112,6 +117,17 @@ pub struct VadResult {
 // Maximum audio size: 10 minutes at 16kHz mono = ~10MB
 const MAX_AUDIO_SIZE: usize = 10 * 1024 * 1024;
 
+/// Maximum decoded duration (compressed input can expand well past MAX_AUDIO_SIZE)
+const MAX_AUDIO_DURATION_SECS: usize = 10 * 60;
+
+/// Recordings longer than this are split into utterances before transcription
+const SEGMENTED_TRANSCRIPTION_MS: u64 = 30_000;
+
+/// WAV format tags
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
 // Use whisper_rs types directly when voice feature is enabled
 #[cfg(feature = "voice")]
 pub use whisper_rs::{WhisperContext, WhisperState};
@@ -135,7 +151,8 @@ static STT_ENGINE: Mutex<Option<SttEngine>> = Mutex::new(None);
 /// Initializes the Whisper STT engine with the specified model.
 /// Returns success message with model path information.
 #[tauri::command]
-pub fn init_stt(model: String) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn init_stt(model: String) -> Result<String, AppError> {
     let config = SttConfig {
         model: model.clone(),
         ..Default::default()
@@ -146,8 +163,7 @@ pub fn init_stt(model: String) -> Result<String, String> {
 
     // Store engine globally
     let mut global_engine = STT_ENGINE
-        .lock()
-        .map_err(|e| format!("Failed to acquire STT lock: {}", e))?;
+        .lock()?;
     *global_engine = Some(engine);
 
     Ok(format!("STT initialized with model: {} at {}", model, model_path))
@@ -155,42 +171,45 @@ pub fn init_stt(model: String) -> Result<String, String> {
 
 /// Transcribe audio data
 ///
-/// Processes WAV audio data and returns transcription results.
-/// Includes WAV header parsing and audio preprocessing.
+/// Processes WAV audio data (or MP3/OGG/FLAC with the `audio-formats`
+/// feature) and returns transcription results.
 #[tauri::command]
-pub fn transcribe(audio_data: Vec<u8>, language: String) -> Result<TranscriptionResult, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn transcribe(audio_data: Vec<u8>, language: String) -> Result<TranscriptionResult, AppError> {
     let start_time = std::time::Instant::now();
 
     // Get STT engine reference (no clone since WhisperContext is not Clone)
     let engine_guard = STT_ENGINE
-        .lock()
-        .map_err(|e| format!("Failed to acquire STT lock: {}", e))?;
+        .lock()?;
 
     let engine = engine_guard
         .as_ref()
-        .ok_or_else(|| "STT engine not initialized. Call init_stt first.".to_string())?;
+        .ok_or_else(|| AppError::invalid_input("STT engine not initialized. Call init_stt first."))?;
 
     // Validate audio data
     if audio_data.is_empty() {
-        return Err("Audio data is empty".to_string());
+        return Err(AppError::invalid_input("Audio data is empty"));
     }
 
     // Check maximum audio size to prevent DoS
     if audio_data.len() > MAX_AUDIO_SIZE {
-        return Err(format!(
+        return Err(AppError::invalid_input(format!(
             "Audio data exceeds maximum size of {} bytes (about 10 minutes at 16kHz)",
             MAX_AUDIO_SIZE
-        ));
+        )));
     }
 
     // Check minimum audio length (44 bytes is WAV header + 1 sample)
     if audio_data.len() < 44 {
-        return Err("Audio data too short to be valid".to_string());
+        return Err(AppError::invalid_input("Audio data too short to be valid"));
     }
 
-    // Parse WAV header
-    let audio_params = parse_wav_header(&audio_data)?;
-    let audio_samples = extract_pcm_data(&audio_data, &audio_params)?;
+    // Decode to mono samples
+    let (audio_samples, sample_rate) = decode_audio(&audio_data)?;
+    let audio_params = AudioParams {
+        sample_rate,
+        ..Default::default()
+    };
 
     // Calculate duration
     let duration_ms = (audio_samples.len() as f64 / audio_params.sample_rate as f64 * 1000.0) as u64;
@@ -205,9 +224,13 @@ pub fn transcribe(audio_data: Vec<u8>, language: String) -> Result<Transcription
         ..Default::default()
     };
 
-    // Perform actual transcription
-    let result = engine.transcribe_audio(&audio_samples, &audio_params, &config)
-        .map_err(|e| e.to_string())?;
+    // Perform actual transcription; long dictation goes utterance by utterance
+    let result = if duration_ms > SEGMENTED_TRANSCRIPTION_MS {
+        engine.transcribe_segmented(&audio_samples, &audio_params, &config)
+    } else {
+        engine.transcribe_audio(&audio_samples, &audio_params, &config)
+    }
+    .map_err(|e| e.to_string())?;
 
     println!("[STT] Transcription completed in {:?}", start_time.elapsed());
 
@@ -442,10 +465,87 @@ impl SttEngine {
         })
     }
 
-    /// Detect language from audio samples (fallback for non-State contexts)
-    fn detect_language_from_samples(_samples: &[f32]) -> Result<String, String> {
-        // Default to English if no context available
-        Ok("en".to_string())
+    /// Transcribe 16kHz mono samples to text
+    pub fn transcribe_samples(&self, samples: &[f32], language: Option<&str>) -> Result<String, String> {
+        let config = SttConfig {
+            language: language.map(|l| l.to_string()),
+            threads: self.config.threads,
+            ..Default::default()
+        };
+
+        self.transcribe_audio(samples, &AudioParams::default(), &config)
+            .map(|output| output.text)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Transcribe each detected utterance separately and join the results
+    ///
+    /// Silence between utterances is never sent to Whisper, and segment
+    /// timestamps are shifted back onto the timeline of the whole recording.
+    fn transcribe_segmented(
+        &self,
+        samples: &[f32],
+        params: &AudioParams,
+        config: &SttConfig,
+    ) -> Result<TranscriptionOutput, SttError> {
+        let samples = resample_audio(samples, params.sample_rate, 16000);
+        let vad_config = VadConfig::default();
+        let utterances = vad::segment(&samples, vad_config);
+        println!("[STT] Split {} samples into {} utterances", samples.len(), utterances.len());
+
+        let mut texts = Vec::with_capacity(utterances.len());
+        let mut segments = Vec::new();
+        let mut language = config.language.clone();
+        for utterance in &utterances {
+            // Offset of the utterance audio, which starts with its pre-roll
+            let offset_ms = utterance.start_ms.saturating_sub(vad_config.pre_roll_ms as u64);
+            let output = self.transcribe_audio(&utterance.samples, &AudioParams::default(), config)?;
+            segments.extend(output.segments.into_iter().map(|segment| TranscriptionSegment {
+                start_time_ms: segment.start_time_ms + offset_ms,
+                end_time_ms: segment.end_time_ms + offset_ms,
+                ..segment
+            }));
+            language = language.or(output.language);
+            texts.push(output.text);
+        }
+
+        Ok(TranscriptionOutput {
+            text: vad::join_transcripts(texts),
+            confidence: if utterances.is_empty() { 0.0 } else { 0.9 },
+            language,
+            segments,
+        })
+    }
+
+    /// Detect the spoken language of 16kHz mono samples
+    ///
+    /// Runs Whisper's language identification on the first 30 seconds and
+    /// returns the language code with its probability.
+    pub fn detect_language_from_samples(&self, samples: &[f32]) -> Result<(String, f32), String> {
+        if samples.is_empty() {
+            return Err("Audio data is empty".to_string());
+        }
+
+        let ctx_guard = self.whisper_context.lock().map_err(|e| e.to_string())?;
+        let ctx = ctx_guard
+            .as_ref()
+            .ok_or_else(|| "Whisper context not initialized".to_string())?;
+
+        let mut state = ctx
+            .create_state()
+            .map_err(|e| format!("Failed to create state: {}", e))?;
+        state
+            .pcm_to_mel(samples, self.config.threads)
+            .map_err(|e| format!("Failed to compute spectrogram: {}", e))?;
+        let (lang_id, probabilities) = state
+            .lang_detect(0, self.config.threads)
+            .map_err(|e| format!("Language detection failed: {}", e))?;
+
+        let language = whisper_rs::get_lang_str(lang_id)
+            .ok_or_else(|| format!("Unknown language id: {}", lang_id))?;
+        let probability = probabilities.get(lang_id as usize).copied().unwrap_or(0.0);
+
+        Ok((language.to_string(), probability))
     }
 
     /// Check if model is loaded
@@ -465,6 +565,19 @@ impl SttEngine {
     }
 }
 
+/// Transcribe 16kHz mono samples with the initialized STT engine
+pub fn transcribe_with_engine(samples: &[f32], language: Option<&str>) -> Result<String, String> {
+    let engine_guard = STT_ENGINE
+        .lock()
+        .map_err(|e| format!("Failed to acquire STT lock: {}", e))?;
+
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| AppError::invalid_input("STT engine not initialized. Call init_stt first."))?;
+
+    engine.transcribe_samples(samples, language)
+}
+
 /// Check if STT is available
 ///
 /// Checks if Whisper models are downloaded and the engine is ready.
@@ -492,6 +605,7 @@ pub fn is_stt_available() -> bool {
 ///
 /// Scans the model directory and returns actually downloaded models.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 pub fn get_available_models() -> Vec<String> {
     let mut models = Vec::new();
     let model_names = [
@@ -523,27 +637,127 @@ pub fn get_available_models() -> Vec<String> {
 /// Detect language from audio data
 ///
 /// Uses Whisper's language detection capabilities.
-pub fn detect_language(audio_data: &[u8]) -> Result<String, String> {
-    // Parse WAV header and extract samples
-    let audio_params = parse_wav_header(audio_data)?;
-    let samples = extract_pcm_data(audio_data, &audio_params)?;
+/// Returns the language code and its probability.
+pub fn detect_language(audio_data: &[u8]) -> Result<(String, f32), String> {
+    // Decode audio to 16kHz mono samples
+    let (samples, sample_rate) = decode_audio(audio_data)?;
+    let samples = resample_audio(&samples, sample_rate, 16000);
 
     // Get STT engine for language detection
     let engine_guard = STT_ENGINE
         .lock()
         .map_err(|e| format!("Failed to acquire STT lock: {}", e))?;
 
-    let _engine = engine_guard
+    let engine = engine_guard
         .as_ref()
         .ok_or_else(|| "STT engine not initialized".to_string())?;
 
-    // Use engine to detect language
-    SttEngine::detect_language_from_samples(&samples)
+    engine.detect_language_from_samples(&samples)
+}
+
+/// Decode audio to mono samples
+///
+/// WAV is always supported; MP3, OGG/Vorbis and FLAC are decoded when built
+/// with the `audio-formats` feature. Multi-channel audio is downmixed.
+/// Returns the samples and their sample rate.
+pub fn decode_audio(data: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE" {
+        let params = parse_wav_header(data)?;
+        let samples = extract_pcm_data(data, &params)?;
+        check_duration(samples.len(), params.sample_rate)?;
+        return Ok((samples, params.sample_rate));
+    }
+
+    decode_compressed(data)
+}
+
+fn check_duration(samples: usize, sample_rate: u32) -> Result<(), String> {
+    if samples > MAX_AUDIO_DURATION_SECS * sample_rate as usize {
+        return Err(format!(
+            "Audio exceeds maximum duration of {} minutes",
+            MAX_AUDIO_DURATION_SECS / 60
+        ));
+    }
+    Ok(())
+}
+
+/// Decode compressed audio with symphonia
+#[cfg(feature = "audio-formats")]
+fn decode_compressed(data: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    use crate::voice::capture::downmix;
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+    use symphonia::core::errors::Error as DecodeError;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    let source = MediaSourceStream::new(Box::new(std::io::Cursor::new(data.to_vec())), Default::default());
+    let probed = symphonia::default::get_probe()
+        .format(&Hint::new(), source, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("Unsupported audio format: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "No audio track found".to_string())?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate;
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported audio codec: {}", e))?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("Failed to read audio: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // Corrupt frames are skipped rather than failing the whole file
+            Err(DecodeError::DecodeError(e)) => {
+                tracing::warn!("Skipping undecodable audio frame: {}", e);
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+        };
+
+        let spec = *decoded.spec();
+        let rate = *sample_rate.get_or_insert(spec.rate);
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend(downmix(buffer.samples(), spec.channels.count() as u16));
+        check_duration(samples.len(), rate)?;
+    }
+
+    let sample_rate = sample_rate.ok_or_else(|| "Audio has no sample rate".to_string())?;
+    if samples.is_empty() {
+        return Err("No audio samples found".to_string());
+    }
+
+    println!("[STT] Decoded {} samples at {} Hz", samples.len(), sample_rate);
+    Ok((samples, sample_rate))
+}
+
+#[cfg(not(feature = "audio-formats"))]
+fn decode_compressed(_data: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    Err("Unsupported audio format: only WAV is supported without the audio-formats feature".to_string())
 }
 
 /// Parse WAV file header
 ///
-/// Returns audio parameters (sample rate, channels, bits per sample)
+/// Returns audio parameters (sample rate, channels, bits per sample).
+/// Accepts 8/16/24/32-bit integer PCM and 32/64-bit IEEE float.
 pub fn parse_wav_header(data: &[u8]) -> Result<AudioParams, String> {
     if data.len() < 44 {
         return Err("Audio data too short for WAV header".to_string());
@@ -564,57 +778,69 @@ pub fn parse_wav_header(data: &[u8]) -> Result<AudioParams, String> {
         return Err("Invalid WAV file (missing fmt chunk)".to_string());
     }
 
-    // Extract audio format (1 = PCM)
-    let audio_format = u16::from_le_bytes([data[20], data[21]]);
-    if audio_format != 1 {
-        return Err(format!(
-            "Unsupported audio format: {} (only PCM is supported)",
-            audio_format
-        ));
+    // Extract audio format; extensible headers carry the real format in the sub-format GUID
+    let mut audio_format = u16::from_le_bytes([data[20], data[21]]);
+    if audio_format == WAVE_FORMAT_EXTENSIBLE {
+        if data.len() < 46 {
+            return Err("Invalid WAV file (truncated extensible fmt chunk)".to_string());
+        }
+        audio_format = u16::from_le_bytes([data[44], data[45]]);
     }
 
     // Extract channels
     let channels = u16::from_le_bytes([data[22], data[23]]);
-    if channels != 1 {
-        // Support mono only for now
-        return Err(format!(
-            "Unsupported channel count: {} (only mono is supported)",
-            channels
-        ));
+    if channels == 0 {
+        return Err("Invalid WAV file (zero channels)".to_string());
     }
 
     // Extract sample rate
     let sample_rate = u32::from_le_bytes([data[24], data[25], data[26], data[27]]);
+    if sample_rate == 0 {
+        return Err("Invalid WAV file (zero sample rate)".to_string());
+    }
 
     // Extract bits per sample
     let bits_per_sample = u16::from_le_bytes([data[34], data[35]]);
 
-    // Validate bit depth
-    if bits_per_sample != 16 {
-        return Err(format!(
-            "Unsupported bit depth: {} (only 16-bit is supported)",
-            bits_per_sample
-        ));
-    }
+    // Validate format and bit depth
+    let is_float = match (audio_format, bits_per_sample) {
+        (WAVE_FORMAT_PCM, 8 | 16 | 24 | 32) => false,
+        (WAVE_FORMAT_IEEE_FLOAT, 32 | 64) => true,
+        (WAVE_FORMAT_PCM, bits) | (WAVE_FORMAT_IEEE_FLOAT, bits) => {
+            return Err(format!("Unsupported bit depth: {}", bits));
+        }
+        (format, _) => {
+            return Err(format!(
+                "Unsupported audio format: {} (only PCM and IEEE float are supported)",
+                format
+            ));
+        }
+    };
 
     println!(
-        "[STT] WAV header: {} Hz, {} ch, {} bits",
-        sample_rate, channels, bits_per_sample
+        "[STT] WAV header: {} Hz, {} ch, {} bits{}",
+        sample_rate,
+        channels,
+        bits_per_sample,
+        if is_float { " float" } else { "" }
     );
 
     Ok(AudioParams {
         sample_rate,
         channels,
         bits_per_sample,
+        is_float,
     })
 }
 
 /// Extract PCM data from WAV file
 ///
-/// Converts raw 16-bit PCM samples to f32 values normalized to [-1, 1]
+/// Converts samples to f32 values normalized to [-1, 1] and downmixes
+/// multi-channel audio to mono.
 pub fn extract_pcm_data(data: &[u8], params: &AudioParams) -> Result<Vec<f32>, String> {
     // Find data chunk
     let mut data_offset = 12; // Skip RIFF header
+    let mut data_end = data.len();
 
     while data_offset + 8 < data.len() {
         let chunk_id = &data[data_offset..data_offset + 4];
@@ -627,40 +853,54 @@ pub fn extract_pcm_data(data: &[u8], params: &AudioParams) -> Result<Vec<f32>, S
 
         if chunk_id == b"data" {
             data_offset += 8;
+            // Streaming writers leave the size as 0 or 0xFFFFFFFF; read to the end then
+            if chunk_size > 0 && data_offset + chunk_size < data.len() {
+                data_end = data_offset + chunk_size;
+            }
             break;
         }
 
-        data_offset += 8 + chunk_size;
+        // Chunks are padded to an even length
+        data_offset += 8 + chunk_size + (chunk_size & 1);
     }
 
     if data_offset >= data.len() {
         return Err("No data chunk found in WAV file".to_string());
     }
 
-    // Calculate number of samples
+    // Calculate number of frames
     let bytes_per_sample = (params.bits_per_sample / 8) as usize;
-    let total_samples = (data.len() - data_offset) / bytes_per_sample;
+    let frame_size = bytes_per_sample * params.channels as usize;
+    let total_frames = (data_end - data_offset) / frame_size;
 
-    if total_samples == 0 {
+    if total_frames == 0 {
         return Err("No audio samples found in WAV file".to_string());
     }
 
-    // Convert 16-bit PCM to f32
-    let mut samples = Vec::with_capacity(total_samples);
-    let mut i = data_offset;
-
-    while i + 1 < data.len() {
-        let sample_i16 = i16::from_le_bytes([data[i], data[i + 1]]);
-        let sample_f32 = sample_i16 as f32 / 32768.0; // Normalize to [-1, 1]
-        samples.push(sample_f32);
-        i += 2;
-    }
+    // Convert to f32
+    let interleaved: Vec<f32> = data[data_offset..data_offset + total_frames * frame_size]
+        .chunks_exact(bytes_per_sample)
+        .map(|b| decode_sample(b, params.is_float))
+        .collect();
+    let samples = crate::voice::capture::downmix(&interleaved, params.channels);
 
     println!("[STT] Extracted {} samples", samples.len());
 
     Ok(samples)
 }
 
+/// Decode one little-endian sample normalized to [-1, 1]
+fn decode_sample(b: &[u8], is_float: bool) -> f32 {
+    match (b.len(), is_float) {
+        (1, _) => (b[0] as f32 - 128.0) / 128.0, // 8-bit PCM is unsigned
+        (2, _) => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (3, _) => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (4, false) => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (4, true) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+    }
+}
+
 /// Resample audio to target sample rate
 ///
 /// Uses simple linear interpolation for resampling.
@@ -729,7 +969,8 @@ pub fn apply_vad(samples: &[f32], sensitivity: f32) -> VadResult {
 
 /// Transcribe audio file directly from path
 #[tauri::command]
-pub fn transcribe_file(file_path: String, language: String) -> Result<TranscriptionResult, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn transcribe_file(file_path: String, language: String) -> Result<TranscriptionResult, AppError> {
     use std::path::Path;
 
     // Validate path to prevent path traversal attacks
@@ -737,7 +978,7 @@ pub fn transcribe_file(file_path: String, language: String) -> Result<Transcript
 
     // Check for path traversal components
     if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
-        return Err("Invalid file path: path traversal not allowed".to_string());
+        return Err(AppError::invalid_input("Invalid file path: path traversal not allowed"));
     }
 
     // Resolve to canonical path to prevent symlink attacks
@@ -756,79 +997,21 @@ pub fn transcribe_file(file_path: String, language: String) -> Result<Transcript
     });
 
     if !is_allowed {
-        return Err("File path not in allowed directory".to_string());
+        return Err(AppError::permission_denied("File path not in allowed directory"));
     }
 
     // Read file
     let audio_data = std::fs::read(&canonical_path)
-        .map_err(|e| format!("Failed to read audio file: {}", e))?;
+        .map_err(|e| crate::files::access::FileError::io(&canonical_path, e))?;
 
     // Delegate to regular transcribe function
     transcribe(audio_data, language)
 }
 
-/// Download Whisper model if not present
-#[tauri::command]
-pub async fn download_model(model_name: String) -> Result<String, String> {
-    // Validate model name first to prevent injection
-    let valid_models = [
-        "tiny", "base", "small", "medium", "large",
-        "tiny-en", "base-en", "small-en", "medium-en",
-        "large-v1", "large-v2", "large-v3",
-    ];
-
-    if !valid_models.contains(&model_name.as_str()) {
-        return Err(format!(
-            "Invalid model name '{}'. Valid options: {}",
-            model_name,
-            valid_models.join(", ")
-        ));
-    }
-
-    let model_filename = format!("ggml-{}.bin", model_name);
-
-    // Determine model directory
-    let model_dir = if let Some(mut path) = dirs::data_dir() {
-        path.push("ai-assistant-tauri");
-        path.push("models");
-        path.push("whisper");
-        path
-    } else {
-        return Err("Cannot determine data directory".to_string());
-    };
-
-    // Create directory if needed
-    std::fs::create_dir_all(&model_dir)
-        .map_err(|e| format!("Failed to create model directory: {}", e))?;
-
-    let model_path = model_dir.join(&model_filename);
-
-    // Check if already exists
-    if model_path.exists() {
-        return Ok(format!(
-            "Model already exists at: {}",
-            model_path.display()
-        ));
-    }
-
-    // Base URL for HuggingFace models
-    let base_url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
-    let download_url = format!("{}/{}", base_url, model_filename);
-
-    println!("[STT] Downloading model from: {}", download_url);
-
-    // Download using reqwest (add to dependencies)
-    // For now, return instruction URL
-    Ok(format!(
-        "Model download required. Please download from: {} and save to: {}",
-        download_url,
-        model_path.display()
-    ))
-}
-
 /// Get model download URL for manual download
 #[tauri::command]
-pub fn get_model_download_url(model_name: String) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_model_download_url(model_name: String) -> Result<String, AppError> {
     let valid_models = [
         "tiny",
         "base",
@@ -845,11 +1028,11 @@ pub fn get_model_download_url(model_name: String) -> Result<String, String> {
     ];
 
     if !valid_models.contains(&model_name.as_str()) {
-        return Err(format!(
+        return Err(AppError::invalid_input(format!(
             "Invalid model name: {}. Valid options: {}",
             model_name,
             valid_models.join(", ")
-        ));
+        )));
     }
 
     let model_filename = format!("ggml-{}.bin", model_name);
@@ -884,6 +1067,72 @@ mod tests {
         assert_eq!(params.bits_per_sample, 16);
     }
 
+    /// Build a WAV file with the given format and raw sample bytes
+    fn build_wav(format: u16, channels: u16, bits: u16, payload: &[u8]) -> Vec<u8> {
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + payload.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&format.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&(16000 * (channels * bits / 8) as u32).to_le_bytes());
+        wav.extend_from_slice(&(channels * bits / 8).to_le_bytes());
+        wav.extend_from_slice(&bits.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        wav.extend_from_slice(payload);
+        wav
+    }
+
+    #[test]
+    fn test_stereo_downmix() {
+        let payload: Vec<u8> = [16384i16, -16384, 32767, 32767]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let (samples, rate) = decode_audio(&build_wav(1, 2, 16, &payload)).unwrap();
+        assert_eq!(rate, 16000);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0], 0.0);
+        assert!((samples[1] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_pcm_bit_depths() {
+        let pcm8 = build_wav(1, 1, 8, &[0, 128, 255]);
+        let samples = decode_audio(&pcm8).unwrap().0;
+        assert_eq!(samples[0], -1.0);
+        assert_eq!(samples[1], 0.0);
+
+        // -0.5 as 24-bit: 0xC00000
+        let pcm24 = build_wav(1, 1, 24, &[0x00, 0x00, 0xC0, 0x00, 0x00, 0x40]);
+        assert_eq!(decode_audio(&pcm24).unwrap().0, vec![-0.5, 0.5]);
+
+        let pcm32 = build_wav(1, 1, 32, &(i32::MIN).to_le_bytes());
+        assert_eq!(decode_audio(&pcm32).unwrap().0, vec![-1.0]);
+
+        assert!(parse_wav_header(&build_wav(1, 1, 12, &[0, 0])).is_err());
+    }
+
+    #[test]
+    fn test_float_wav() {
+        let payload: Vec<u8> = [0.25f32, -0.75].iter().flat_map(|s| s.to_le_bytes()).collect();
+        let params = parse_wav_header(&build_wav(3, 1, 32, &payload)).unwrap();
+        assert!(params.is_float);
+        assert_eq!(decode_audio(&build_wav(3, 1, 32, &payload)).unwrap().0, vec![0.25, -0.75]);
+
+        let payload = 0.5f64.to_le_bytes();
+        assert_eq!(decode_audio(&build_wav(3, 1, 64, &payload)).unwrap().0, vec![0.5]);
+    }
+
+    #[test]
+    fn test_unknown_format_rejected() {
+        assert!(decode_audio(b"not audio at all, definitely not a wav file.").is_err());
+    }
+
     #[test]
     fn test_resample_audio() {
         let input = vec![1.0, 0.5, 0.0, -0.5, -1.0];
@@ -4,9 +4,13 @@
 // - Windows: SAPI via PowerShell
 // - macOS: say command
 // - Linux: espeak-ng or espeak
+//
+// Cloud engines are selected through `tts_providers`.
 
 #![allow(dead_code)]
 
+use crate::error::AppError;
+use crate::voice::tts_providers::synthesize_with_fallback;
 use crate::voice::SynthesisResult;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
@@ -65,7 +69,8 @@ fn init_state() {
 /// Initializes the TTS subsystem and configures the specified voice.
 /// Returns a success message with the configured voice.
 #[tauri::command]
-pub fn init_tts(voice: String) -> Result<String, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn init_tts(voice: String) -> Result<String, AppError> {
     init_state();
 
     let mut state = TTS_STATE.lock().unwrap();
@@ -80,20 +85,22 @@ pub fn init_tts(voice: String) -> Result<String, String> {
     if is_tts_available() {
         Ok(format!("TTS initialized with voice: {}", voice))
     } else {
-        Err("TTS engine is not available on this system".to_string())
+        Err("TTS engine is not available on this system".to_string().into())
     }
 }
 
 /// Synthesize text to speech
 ///
-/// Converts the given text to audio data using the configured TTS engine.
+/// Converts the given text to audio data using the selected TTS provider,
+/// falling back to the local engine if it fails.
 /// Returns audio data in WAV format with metadata.
 #[tauri::command]
-pub fn synthesize(text: String, language: String) -> Result<SynthesisResult, String> {
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn synthesize(text: String, language: String) -> Result<SynthesisResult, AppError> {
     init_state();
 
     if text.is_empty() {
-        return Err("Text cannot be empty".to_string());
+        return Err(AppError::invalid_input("Text cannot be empty"));
     }
 
     // Update state to indicate synthesis is in progress
@@ -107,16 +114,31 @@ pub fn synthesize(text: String, language: String) -> Result<SynthesisResult, Str
 
     // Get configured settings
     let state_guard = TTS_STATE.lock().unwrap();
-    let speed = state_guard.as_ref()
-        .and_then(|s| s.current_config.as_ref())
-        .map(|c| c.speed)
-        .unwrap_or(1.0);
+    let config = state_guard.as_ref()
+        .and_then(|s| s.current_config.clone())
+        .unwrap_or_default();
     drop(state_guard);
 
+    let result = synthesize_with_fallback(&text, &language, &config.voice, config.speed);
+
+    // Update state
+    {
+        let mut state = TTS_STATE.lock().unwrap();
+        if let Some(ref mut s) = *state {
+            s.is_synthesizing = false;
+            s.progress = if result.is_ok() { 1.0 } else { 0.0 };
+        }
+    }
+
+    Ok(result?)
+}
+
+/// Synthesize with the platform TTS engine
+pub(crate) fn synthesize_local(text: &str, language: &str, speed: f32) -> Result<SynthesisResult, String> {
     let sample_rate = 22050u32;
 
     // Generate audio data using platform TTS
-    let audio_data = generate_wav_with_synthesis(&text, &language, sample_rate, speed)
+    let audio_data = generate_wav_with_synthesis(text, language, sample_rate, speed)
         .map_err(|e| format!("Failed to generate audio: {}", e))?;
 
     // Calculate approximate duration based on text length and average speaking rate
@@ -127,15 +149,6 @@ pub fn synthesize(text: String, language: String) -> Result<SynthesisResult, Str
     let duration_ms = ((word_count * 60000 / 150) + (char_count * 10))
         .clamp(500, 60_000); // Clamp between 500ms and 60 seconds
 
-    // Update state
-    {
-        let mut state = TTS_STATE.lock().unwrap();
-        if let Some(ref mut s) = *state {
-            s.is_synthesizing = false;
-            s.progress = 1.0;
-        }
-    }
-
     Ok(SynthesisResult {
         audio_data,
         sample_rate,
@@ -271,7 +284,14 @@ $synth.Dispose();
 /// Generate a silent WAV file as fallback
 fn generate_silent_wav(sample_rate: u32, duration_ms: u64) -> Result<Vec<u8>, String> {
     let num_samples = (sample_rate as u64 * duration_ms / 1000) as usize;
-    let data_size = num_samples * 2; // 16-bit samples
+
+    // Silence (zeros)
+    Ok(pcm16_to_wav(&vec![0u8; num_samples * 2], sample_rate))
+}
+
+/// Wrap 16-bit little-endian mono PCM in a WAV container
+pub fn pcm16_to_wav(pcm: &[u8], sample_rate: u32) -> Vec<u8> {
+    let data_size = pcm.len();
     let file_size = 36 + data_size;
 
     let mut wav = Vec::with_capacity(file_size + 8);
@@ -294,11 +314,9 @@ fn generate_silent_wav(sample_rate: u32, duration_ms: u64) -> Result<Vec<u8>, St
     // data chunk
     wav.extend_from_slice(b"data");
     wav.extend_from_slice(&(data_size as u32).to_le_bytes());
+    wav.extend_from_slice(pcm);
 
-    // Silence (zeros)
-    wav.resize(wav.len() + data_size, 0);
-
-    Ok(wav)
+    wav
 }
 
 /// Convert AIFF to WAV (simplified conversion for macOS say output)
@@ -547,6 +565,7 @@ fn get_tts_engine_voices() -> Vec<VoiceInfo> {
 ///
 /// Returns a list of voices available on the system for TTS.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 pub fn get_available_voices() -> Vec<VoiceInfo> {
     let mut voices = get_tts_engine_voices();
 
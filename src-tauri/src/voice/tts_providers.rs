@@ -0,0 +1,252 @@
+// TTS Providers - Local and cloud speech synthesis backends
+//
+// The local provider wraps the platform engines in `tts`. Cloud providers
+// read their API key from the platform keychain (CredentialManager) and return
+// the same WAV-based SynthesisResult. When a cloud request fails (offline,
+// timeout, quota) synthesis falls back to the local engine.
+
+#![allow(dead_code)]
+
+use crate::error::AppError;
+use crate::security::CredentialManager;
+use crate::voice::tts::{pcm16_to_wav, synthesize_local};
+use crate::voice::SynthesisResult;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Cloud request timeout
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// OpenAI speech endpoint (raw 24kHz 16-bit mono PCM with `response_format=pcm`)
+const OPENAI_SPEECH_URL: &str = "https://api.openai.com/v1/audio/speech";
+const OPENAI_SAMPLE_RATE: u32 = 24000;
+const OPENAI_DEFAULT_VOICE: &str = "alloy";
+const OPENAI_VOICES: &[&str] = &["alloy", "ash", "coral", "echo", "fable", "onyx", "nova", "sage", "shimmer"];
+
+/// ElevenLabs speech endpoint
+const ELEVENLABS_SPEECH_URL: &str = "https://api.elevenlabs.io/v1/text-to-speech";
+const ELEVENLABS_SAMPLE_RATE: u32 = 22050;
+const ELEVENLABS_DEFAULT_VOICE: &str = "21m00Tcm4TlvDq8ikWAM";
+
+/// Speech synthesis backend
+pub trait TtsProvider: Send + Sync {
+    /// Provider identifier as stored in voice settings
+    fn id(&self) -> &'static str;
+
+    /// Synthesize text to WAV audio
+    fn synthesize(&self, text: &str, language: &str, voice: &str, speed: f32) -> Result<SynthesisResult, String>;
+}
+
+/// Platform TTS engine (espeak, say, SAPI)
+pub struct LocalTtsProvider;
+
+impl TtsProvider for LocalTtsProvider {
+    fn id(&self) -> &'static str {
+        "local"
+    }
+
+    fn synthesize(&self, text: &str, language: &str, _voice: &str, speed: f32) -> Result<SynthesisResult, String> {
+        synthesize_local(text, language, speed)
+    }
+}
+
+/// OpenAI text-to-speech
+pub struct OpenAiTtsProvider {
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiTtsProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            model: "tts-1".to_string(),
+        }
+    }
+}
+
+impl TtsProvider for OpenAiTtsProvider {
+    fn id(&self) -> &'static str {
+        "openai"
+    }
+
+    fn synthesize(&self, text: &str, _language: &str, voice: &str, speed: f32) -> Result<SynthesisResult, String> {
+        // OpenAI detects the language from the input text
+        let voice = if OPENAI_VOICES.contains(&voice) { voice } else { OPENAI_DEFAULT_VOICE };
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": text,
+            "voice": voice,
+            "response_format": "pcm",
+            "speed": speed.clamp(0.25, 4.0),
+        });
+
+        let pcm = post_for_audio(
+            OPENAI_SPEECH_URL,
+            &[("Authorization", format!("Bearer {}", self.api_key))],
+            &body,
+        )?;
+        Ok(pcm_result(&pcm, OPENAI_SAMPLE_RATE))
+    }
+}
+
+/// ElevenLabs text-to-speech
+pub struct ElevenLabsTtsProvider {
+    api_key: String,
+    model: String,
+}
+
+impl ElevenLabsTtsProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            model: "eleven_multilingual_v2".to_string(),
+        }
+    }
+}
+
+impl TtsProvider for ElevenLabsTtsProvider {
+    fn id(&self) -> &'static str {
+        "elevenlabs"
+    }
+
+    fn synthesize(&self, text: &str, language: &str, voice: &str, speed: f32) -> Result<SynthesisResult, String> {
+        // Voice settings store "default" until the user picks an ElevenLabs voice ID
+        let voice = if voice.is_empty() || voice == "default" { ELEVENLABS_DEFAULT_VOICE } else { voice };
+        let url = format!(
+            "{}/{}?output_format=pcm_{}",
+            ELEVENLABS_SPEECH_URL, voice, ELEVENLABS_SAMPLE_RATE
+        );
+        let body = serde_json::json!({
+            "text": text,
+            "model_id": self.model,
+            "language_code": language.split('-').next().unwrap_or(language),
+            "voice_settings": { "speed": speed.clamp(0.7, 1.2) },
+        });
+
+        let pcm = post_for_audio(&url, &[("xi-api-key", self.api_key.clone())], &body)?;
+        Ok(pcm_result(&pcm, ELEVENLABS_SAMPLE_RATE))
+    }
+}
+
+/// POST a JSON request and return the response body
+fn post_for_audio(url: &str, headers: &[(&str, String)], body: &serde_json::Value) -> Result<Vec<u8>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(body).map_err(|e| e.to_string())?);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    let response = request.send().map_err(|e| format!("TTS request failed: {}", e))?;
+    let status = response.status();
+    let bytes = response.bytes().map_err(|e| format!("TTS request failed: {}", e))?;
+
+    if !status.is_success() {
+        return Err(format!(
+            "TTS provider returned {}: {}",
+            status,
+            String::from_utf8_lossy(&bytes).chars().take(200).collect::<String>()
+        ));
+    }
+    if bytes.is_empty() {
+        return Err("TTS provider returned no audio".to_string());
+    }
+
+    Ok(bytes.to_vec())
+}
+
+/// Wrap 16-bit mono PCM in a WAV synthesis result
+fn pcm_result(pcm: &[u8], sample_rate: u32) -> SynthesisResult {
+    SynthesisResult {
+        audio_data: pcm16_to_wav(pcm, sample_rate),
+        sample_rate,
+        duration_ms: (pcm.len() / 2) as u64 * 1000 / sample_rate as u64,
+    }
+}
+
+/// Keychain entry holding a provider's API key
+pub fn credential_key(provider: &str) -> String {
+    format!("tts.{}", provider)
+}
+
+/// Build a provider from its identifier
+pub fn create_provider(provider: &str, credentials: &CredentialManager) -> Result<Box<dyn TtsProvider>, String> {
+    let api_key = || {
+        credentials
+            .get_password(&credential_key(provider))
+            .map_err(|_| format!("No API key stored for {} TTS", provider))
+    };
+
+    match provider {
+        "local" => Ok(Box::new(LocalTtsProvider)),
+        "openai" => Ok(Box::new(OpenAiTtsProvider::new(api_key()?))),
+        "elevenlabs" => Ok(Box::new(ElevenLabsTtsProvider::new(api_key()?))),
+        _ => Err(format!("Unknown TTS provider: {}", provider)),
+    }
+}
+
+/// Provider used by `synthesize`; `None` means local
+static ACTIVE_PROVIDER: Mutex<Option<Box<dyn TtsProvider>>> = Mutex::new(None);
+
+/// Synthesize with the active provider, falling back to the local engine
+pub fn synthesize_with_fallback(text: &str, language: &str, voice: &str, speed: f32) -> Result<SynthesisResult, String> {
+    {
+        let active = ACTIVE_PROVIDER.lock().map_err(|e| e.to_string())?;
+        if let Some(provider) = active.as_ref() {
+            match provider.synthesize(text, language, voice, speed) {
+                Ok(result) => return Ok(result),
+                Err(e) => tracing::warn!("{} TTS failed, using local engine: {}", provider.id(), e),
+            }
+        }
+    }
+
+    synthesize_local(text, language, speed)
+}
+
+/// Select the TTS provider used for synthesis
+///
+/// Cloud providers require an API key stored under `tts.<provider>`.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_tts_provider(
+    credentials: tauri::State<'_, Mutex<CredentialManager>>,
+    provider: String,
+) -> Result<String, AppError> {
+    let credentials = credentials.lock()?;
+    let created = create_provider(&provider, &credentials)?;
+
+    let mut active = ACTIVE_PROVIDER.lock()?;
+    *active = (provider != "local").then_some(created);
+
+    tracing::info!("TTS provider set to {}", provider);
+    Ok(provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcm_result() {
+        let pcm = vec![0u8; OPENAI_SAMPLE_RATE as usize * 2];
+        let result = pcm_result(&pcm, OPENAI_SAMPLE_RATE);
+        assert_eq!(result.duration_ms, 1000);
+        assert_eq!(result.sample_rate, OPENAI_SAMPLE_RATE);
+        assert_eq!(&result.audio_data[0..4], b"RIFF");
+        assert_eq!(result.audio_data.len(), 44 + pcm.len());
+    }
+
+    #[test]
+    fn test_unknown_provider() {
+        let credentials = CredentialManager::new("test-service-ai-assistant".to_string());
+        assert!(create_provider("local", &credentials).is_ok());
+        assert!(create_provider("unknown", &credentials).is_err());
+    }
+}
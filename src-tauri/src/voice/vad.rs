@@ -0,0 +1,256 @@
+// Voice Activity Segmentation - Splitting dictation into utterances
+//
+// `apply_vad` classifies a single frame. The segmenter runs it over streamed
+// 16kHz audio frame by frame and tracks where speech starts and ends:
+// - Speech starts at the first speech frame; a little audio before it
+//   (pre-roll) is kept so soft word onsets are not clipped.
+// - Speech ends once silence has lasted the hangover time, so short pauses
+//   between words stay inside one utterance.
+// - Bursts shorter than the minimum speech time are discarded as noise.
+// - Utterances reaching the maximum length are split so no single Whisper
+//   inference exceeds its 30 second window.
+//
+// Long recordings are transcribed one utterance at a time and the texts
+// concatenated, which keeps each inference short and skips silence that
+// Whisper tends to fill with hallucinated text.
+
+#![allow(dead_code)]
+
+use crate::voice::capture::CAPTURE_SAMPLE_RATE;
+use crate::voice::stt::apply_vad;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Segmenter configuration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VadConfig {
+    /// VAD sensitivity (0.0 = very sensitive, 1.0 = less sensitive)
+    pub sensitivity: f32,
+    pub frame_ms: u32,
+    /// Speech needed for a burst to count as an utterance
+    pub min_speech_ms: u32,
+    /// Silence that ends an utterance
+    pub hangover_ms: u32,
+    /// Audio kept before the detected start of speech
+    pub pre_roll_ms: u32,
+    /// Longest utterance before it is split
+    pub max_utterance_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.5,
+            frame_ms: 30,
+            min_speech_ms: 250,
+            hangover_ms: 600,
+            pre_roll_ms: 200,
+            max_utterance_ms: 25_000,
+        }
+    }
+}
+
+/// Detected stretch of speech
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    /// Start of speech from the beginning of the stream
+    pub start_ms: u64,
+    /// End of the last speech frame
+    pub end_ms: u64,
+    /// Audio including pre-roll and trailing hangover
+    pub samples: Vec<f32>,
+}
+
+/// Utterance in progress
+struct ActiveSpeech {
+    samples: Vec<f32>,
+    start: usize,
+    last_speech_end: usize,
+    speech_frames: u32,
+    silence_frames: u32,
+}
+
+/// Streaming speech segmenter over 16kHz mono audio
+pub struct VadSegmenter {
+    config: VadConfig,
+    frame_len: usize,
+    pending: Vec<f32>,
+    /// Samples consumed as frames so far
+    position: usize,
+    pre_roll: VecDeque<f32>,
+    active: Option<ActiveSpeech>,
+}
+
+fn samples_to_ms(samples: usize) -> u64 {
+    samples as u64 * 1000 / CAPTURE_SAMPLE_RATE as u64
+}
+
+fn ms_to_samples(ms: u32) -> usize {
+    (CAPTURE_SAMPLE_RATE as u64 * ms as u64 / 1000) as usize
+}
+
+impl VadSegmenter {
+    pub fn new(config: VadConfig) -> Self {
+        Self {
+            config,
+            frame_len: ms_to_samples(config.frame_ms).max(1),
+            pending: Vec::new(),
+            position: 0,
+            pre_roll: VecDeque::new(),
+            active: None,
+        }
+    }
+
+    /// Feed samples, returning the utterances they complete
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Utterance> {
+        self.pending.extend_from_slice(samples);
+        let mut completed = Vec::new();
+
+        let frames = self.pending.len() / self.frame_len;
+        let pending = std::mem::take(&mut self.pending);
+        for frame in pending.chunks_exact(self.frame_len).take(frames) {
+            completed.extend(self.process_frame(frame));
+        }
+        self.pending = pending[frames * self.frame_len..].to_vec();
+
+        completed
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) -> Option<Utterance> {
+        let is_speech = apply_vad(frame, self.config.sensitivity).is_speech;
+        let frame_start = self.position;
+        self.position += frame.len();
+
+        let Some(active) = self.active.as_mut() else {
+            if is_speech {
+                let mut samples: Vec<f32> = self.pre_roll.drain(..).collect();
+                samples.extend_from_slice(frame);
+                self.active = Some(ActiveSpeech {
+                    samples,
+                    start: frame_start,
+                    last_speech_end: self.position,
+                    speech_frames: 1,
+                    silence_frames: 0,
+                });
+            } else {
+                self.pre_roll.extend(frame);
+                let excess = self.pre_roll.len().saturating_sub(ms_to_samples(self.config.pre_roll_ms));
+                self.pre_roll.drain(..excess);
+            }
+            return None;
+        };
+
+        active.samples.extend_from_slice(frame);
+        if is_speech {
+            active.speech_frames += 1;
+            active.silence_frames = 0;
+            active.last_speech_end = self.position;
+        } else {
+            active.silence_frames += 1;
+        }
+
+        let ended = active.silence_frames * self.config.frame_ms >= self.config.hangover_ms;
+        let too_long = self.position - active.start >= ms_to_samples(self.config.max_utterance_ms);
+        if !ended && !too_long {
+            return None;
+        }
+
+        let active = self.active.take()?;
+        self.close(active)
+    }
+
+    /// Utterance for finished speech, unless it was too short to count
+    fn close(&self, active: ActiveSpeech) -> Option<Utterance> {
+        (active.speech_frames * self.config.frame_ms >= self.config.min_speech_ms).then(|| Utterance {
+            start_ms: samples_to_ms(active.start),
+            end_ms: samples_to_ms(active.last_speech_end),
+            samples: active.samples,
+        })
+    }
+
+    /// End the stream, returning the utterance still in progress
+    pub fn finish(&mut self) -> Option<Utterance> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut active = self.active.take()?;
+        active.samples.extend_from_slice(&pending);
+        self.close(active)
+    }
+
+    /// Audio of the utterance in progress; empty between utterances
+    pub fn current(&self) -> &[f32] {
+        self.active.as_ref().map(|a| a.samples.as_slice()).unwrap_or_default()
+    }
+
+    pub fn in_speech(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+/// Split a whole 16kHz recording into utterances
+pub fn segment(samples: &[f32], config: VadConfig) -> Vec<Utterance> {
+    let mut segmenter = VadSegmenter::new(config);
+    let mut utterances = segmenter.push(samples);
+    utterances.extend(segmenter.finish());
+    utterances
+}
+
+/// Join utterance transcripts, skipping empty ones
+pub fn join_transcripts<I: IntoIterator<Item = String>>(texts: I) -> String {
+    texts
+        .into_iter()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(ms: u32, amplitude: f32) -> Vec<f32> {
+        vec![amplitude; ms_to_samples(ms)]
+    }
+
+    #[test]
+    fn test_utterance_timestamps_and_hangover() {
+        let config = VadConfig::default();
+        let mut segmenter = VadSegmenter::new(config);
+
+        assert!(segmenter.push(&tone(990, 0.0)).is_empty());
+        // A pause shorter than the hangover stays inside the utterance
+        assert!(segmenter.push(&tone(600, 0.5)).is_empty());
+        assert!(segmenter.push(&tone(300, 0.0)).is_empty());
+        assert!(segmenter.push(&tone(600, 0.5)).is_empty());
+        assert!(segmenter.in_speech());
+
+        let utterances = segmenter.push(&tone(900, 0.0));
+        assert_eq!(utterances.len(), 1);
+        let utterance = &utterances[0];
+        assert_eq!((utterance.start_ms, utterance.end_ms), (990, 2490));
+        let expected = ms_to_samples(config.pre_roll_ms + 1500 + config.hangover_ms);
+        assert_eq!(utterance.samples.len(), expected);
+        assert!(!segmenter.in_speech());
+        assert!(segmenter.current().is_empty());
+
+        // Short noise is dropped; unfinished speech is returned by finish
+        assert!(segmenter.push(&tone(90, 0.5)).is_empty());
+        assert!(segmenter.push(&tone(900, 0.0)).is_empty());
+        segmenter.push(&tone(600, 0.5));
+        assert!(!segmenter.current().is_empty());
+        let last = segmenter.finish().unwrap();
+        assert_eq!(last.start_ms, 4380);
+        assert!(segmenter.finish().is_none());
+    }
+
+    #[test]
+    fn test_long_speech_is_split() {
+        let config = VadConfig { max_utterance_ms: 3000, ..Default::default() };
+        let utterances = segment(&tone(7500, 0.5), config);
+        let bounds: Vec<_> = utterances.iter().map(|u| (u.start_ms, u.end_ms)).collect();
+        assert_eq!(bounds, [(0, 3000), (3000, 6000), (6000, 7500)]);
+
+        assert!(segment(&tone(5000, 0.0), config).is_empty());
+        assert_eq!(join_transcripts(["Hello.".to_string(), " ".to_string(), " world ".to_string()]), "Hello. world");
+    }
+}
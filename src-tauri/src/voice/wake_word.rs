@@ -0,0 +1,474 @@
+// Wake Word Detection - Continuous listening for a spoken trigger phrase
+//
+// Detection runs in two stages to keep idle CPU usage low:
+// 1. An energy gate (VAD) collects short bursts of speech from the microphone.
+// 2. Each burst is transcribed with Whisper tiny and matched against the wake word.
+//
+// A match emits `voice://wake` with a fresh voice conversation session id.
+
+#![allow(dead_code)]
+
+use crate::error::AppError;
+use crate::voice::capture::{AudioCapture, CAPTURE_SAMPLE_RATE};
+use crate::voice::stt::{apply_vad, SttConfig, SttEngine};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// Event emitted when the wake word is heard
+pub const WAKE_EVENT: &str = "voice://wake";
+
+/// Default wake word when none is configured
+pub const DEFAULT_WAKE_WORD: &str = "hey assistant";
+
+/// Whisper model used for keyword spotting
+const WAKE_WORD_MODEL: &str = "tiny";
+
+/// Frame length used for the energy gate
+const FRAME_MS: u32 = 30;
+
+/// Wake word detector configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WakeWordConfig {
+    pub wake_word: String,
+    pub language: Option<String>,
+    /// VAD sensitivity (0.0 = very sensitive, 1.0 = less sensitive)
+    pub sensitivity: f32,
+    /// Shortest burst of speech considered a candidate
+    pub min_speech_ms: u32,
+    /// Longest burst before it is checked regardless of trailing silence
+    pub max_speech_ms: u32,
+    /// Silence that ends a burst
+    pub silence_ms: u32,
+    /// Time after a detection during which further matches are ignored
+    pub cooldown_ms: u32,
+}
+
+impl Default for WakeWordConfig {
+    fn default() -> Self {
+        Self {
+            wake_word: DEFAULT_WAKE_WORD.to_string(),
+            language: None,
+            sensitivity: 0.5,
+            min_speech_ms: 300,
+            max_speech_ms: 2500,
+            silence_ms: 400,
+            cooldown_ms: 2000,
+        }
+    }
+}
+
+/// Payload of the `voice://wake` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WakeWordEvent {
+    pub wake_word: String,
+    pub transcript: String,
+    pub session_id: String,
+    pub detected_at: String,
+}
+
+/// Wake word listening status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WakeWordStatus {
+    pub listening: bool,
+    pub wake_word: Option<String>,
+    pub detections: u64,
+}
+
+/// Energy-gated speech segmenter
+///
+/// Fed 16kHz mono samples; returns bursts of speech that are candidates
+/// for keyword spotting.
+pub struct SpeechSegmenter {
+    config: WakeWordConfig,
+    frame_len: usize,
+    pending: Vec<f32>,
+    segment: Vec<f32>,
+    silence_frames: u32,
+}
+
+impl SpeechSegmenter {
+    pub fn new(config: WakeWordConfig) -> Self {
+        Self {
+            config,
+            frame_len: (CAPTURE_SAMPLE_RATE * FRAME_MS / 1000) as usize,
+            pending: Vec::new(),
+            segment: Vec::new(),
+            silence_frames: 0,
+        }
+    }
+
+    /// Feed samples, returning any completed speech segments
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        let mut completed = Vec::new();
+        self.pending.extend_from_slice(samples);
+
+        while self.pending.len() >= self.frame_len {
+            let frame: Vec<f32> = self.pending.drain(..self.frame_len).collect();
+            if let Some(segment) = self.process_frame(&frame) {
+                completed.push(segment);
+            }
+        }
+
+        completed
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) -> Option<Vec<f32>> {
+        let vad = apply_vad(frame, self.config.sensitivity);
+
+        if vad.is_speech {
+            self.segment.extend_from_slice(frame);
+            self.silence_frames = 0;
+        } else if !self.segment.is_empty() {
+            // Keep short pauses inside the segment
+            self.segment.extend_from_slice(frame);
+            self.silence_frames += 1;
+        }
+
+        if self.segment.is_empty() {
+            return None;
+        }
+
+        let ended = self.silence_frames * FRAME_MS >= self.config.silence_ms;
+        let too_long = self.duration_ms() >= self.config.max_speech_ms;
+        if !ended && !too_long {
+            return None;
+        }
+
+        let speech_ms = self.duration_ms() - self.silence_frames * FRAME_MS;
+        let segment = std::mem::take(&mut self.segment);
+        self.silence_frames = 0;
+
+        (speech_ms >= self.config.min_speech_ms).then_some(segment)
+    }
+
+    fn duration_ms(&self) -> u32 {
+        (self.segment.len() as u64 * 1000 / CAPTURE_SAMPLE_RATE as u64) as u32
+    }
+}
+
+/// Lowercase and strip punctuation, collapsing whitespace
+fn normalize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .map(|w| w.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+/// Check whether a transcript contains the wake word
+///
+/// Words are compared after normalization, allowing one edit per word of
+/// four or more characters to absorb small transcription errors.
+pub fn matches_wake_word(transcript: &str, wake_word: &str) -> bool {
+    let words = normalize(transcript);
+    let target = normalize(wake_word);
+
+    if target.is_empty() || words.len() < target.len() {
+        return false;
+    }
+
+    words.windows(target.len()).any(|window| {
+        window.iter().zip(&target).all(|(heard, expected)| {
+            let allowed = if expected.chars().count() >= 4 { 1 } else { 0 };
+            edit_distance(heard, expected) <= allowed
+        })
+    })
+}
+
+/// Active listening session
+struct ListeningSession {
+    wake_word: String,
+    stop: Arc<AtomicBool>,
+    capture: AudioCapture,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// Wake word listener state
+#[derive(Default)]
+pub struct WakeWordListener {
+    session: Option<ListeningSession>,
+    detections: Arc<Mutex<u64>>,
+}
+
+impl WakeWordListener {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start continuous listening, emitting `voice://wake` on each detection
+    pub fn start(&mut self, app: tauri::AppHandle, config: WakeWordConfig) -> Result<(), String> {
+        if self.session.is_some() {
+            return Err("Wake word listening is already active".to_string());
+        }
+
+        let engine = SttEngine::new(SttConfig {
+            model: WAKE_WORD_MODEL.to_string(),
+            threads: 2,
+            ..Default::default()
+        })?;
+
+        let (frames_tx, frames_rx) = mpsc::channel();
+        let capture = AudioCapture::start(frames_tx)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let wake_word = config.wake_word.clone();
+        let worker = {
+            let stop = stop.clone();
+            let detections = self.detections.clone();
+            std::thread::spawn(move || {
+                run_detection(app, engine, config, frames_rx, stop, detections)
+            })
+        };
+
+        tracing::info!("Wake word listening started for '{}'", wake_word);
+
+        self.session = Some(ListeningSession {
+            wake_word,
+            stop,
+            capture,
+            worker: Some(worker),
+        });
+
+        Ok(())
+    }
+
+    /// Stop listening and release the microphone
+    pub fn stop(&mut self) -> bool {
+        let Some(mut session) = self.session.take() else {
+            return false;
+        };
+
+        session.stop.store(true, Ordering::SeqCst);
+        session.capture.stop();
+        if let Some(worker) = session.worker.take() {
+            let _ = worker.join();
+        }
+
+        tracing::info!("Wake word listening stopped");
+        true
+    }
+
+    pub fn status(&self) -> WakeWordStatus {
+        WakeWordStatus {
+            listening: self.session.is_some(),
+            wake_word: self.session.as_ref().map(|s| s.wake_word.clone()),
+            detections: self.detections.lock().map(|d| *d).unwrap_or(0),
+        }
+    }
+}
+
+/// Detection loop run on the worker thread
+fn run_detection(
+    app: tauri::AppHandle,
+    engine: SttEngine,
+    config: WakeWordConfig,
+    frames: Receiver<Vec<f32>>,
+    stop: Arc<AtomicBool>,
+    detections: Arc<Mutex<u64>>,
+) {
+    let mut segmenter = SpeechSegmenter::new(config.clone());
+    let cooldown = Duration::from_millis(config.cooldown_ms as u64);
+    let mut last_detection: Option<Instant> = None;
+
+    while !stop.load(Ordering::SeqCst) {
+        let samples = match frames.recv_timeout(Duration::from_millis(100)) {
+            Ok(samples) => samples,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        for segment in segmenter.push(&samples) {
+            if last_detection.is_some_and(|t| t.elapsed() < cooldown) {
+                continue;
+            }
+
+            let transcript = match engine.transcribe_samples(&segment, config.language.as_deref()) {
+                Ok(text) => text,
+                Err(e) => {
+                    tracing::warn!("Wake word transcription failed: {}", e);
+                    continue;
+                }
+            };
+
+            if !matches_wake_word(&transcript, &config.wake_word) {
+                continue;
+            }
+
+            last_detection = Some(Instant::now());
+            if let Ok(mut count) = detections.lock() {
+                *count += 1;
+            }
+
+            let event = WakeWordEvent {
+                wake_word: config.wake_word.clone(),
+                transcript,
+                session_id: uuid::Uuid::new_v4().to_string(),
+                detected_at: chrono::Utc::now().to_rfc3339(),
+            };
+            tracing::info!("Wake word detected, starting voice session {}", event.session_id);
+
+            if let Err(e) = app.emit(WAKE_EVENT, event) {
+                tracing::error!("Failed to emit wake event: {}", e);
+            }
+        }
+    }
+}
+
+/// Wake word saved in the voice settings, if any
+fn configured_wake_word(conn: &Connection) -> Result<Option<String>, AppError> {
+    let stored: Option<Option<String>> = conn
+        .query_row("SELECT wake_word FROM voice_settings LIMIT 1", [], |row| row.get(0))
+        .optional()?;
+    Ok(stored.flatten().map(|w| w.trim().to_string()).filter(|w| !w.is_empty()))
+}
+
+/// Start continuous wake word listening
+///
+/// Falls back to the wake word in the voice settings when none is given, and
+/// to `DEFAULT_WAKE_WORD` when the settings have none either.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn start_wake_word_listening(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, crate::db::DbState>,
+    listener: tauri::State<'_, Mutex<WakeWordListener>>,
+    wake_word: Option<String>,
+    language: Option<String>,
+    sensitivity: Option<f32>,
+) -> Result<WakeWordStatus, AppError> {
+    let wake_word = match wake_word.map(|w| w.trim().to_string()).filter(|w| !w.is_empty()) {
+        Some(wake_word) => wake_word,
+        None => {
+            let conn = db.read()?;
+            configured_wake_word(&conn)?.unwrap_or_else(|| DEFAULT_WAKE_WORD.to_string())
+        }
+    };
+
+    let config = WakeWordConfig {
+        wake_word,
+        language: language.filter(|l| !l.is_empty()),
+        sensitivity: sensitivity.unwrap_or(0.5).clamp(0.0, 1.0),
+        ..Default::default()
+    };
+
+    let mut listener = listener.lock()?;
+    listener.start(app, config)?;
+    Ok(listener.status())
+}
+
+/// Stop wake word listening
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn stop_wake_word_listening(
+    listener: tauri::State<'_, Mutex<WakeWordListener>>,
+) -> Result<bool, AppError> {
+    let mut listener = listener.lock()?;
+    Ok(listener.stop())
+}
+
+/// Get wake word listening status
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_wake_word_status(
+    listener: tauri::State<'_, Mutex<WakeWordListener>>,
+) -> Result<WakeWordStatus, AppError> {
+    let listener = listener.lock()?;
+    Ok(listener.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(ms: u32, amplitude: f32) -> Vec<f32> {
+        vec![amplitude; (CAPTURE_SAMPLE_RATE * ms / 1000) as usize]
+    }
+
+    #[test]
+    fn test_matches_wake_word() {
+        assert!(matches_wake_word("Hey, Assistant!", "hey assistant"));
+        assert!(matches_wake_word("um hey assistent what's up", "hey assistant"));
+        assert!(!matches_wake_word("hello there", "hey assistant"));
+        assert!(!matches_wake_word("hey", "hey assistant"));
+        assert!(!matches_wake_word("anything", ""));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_segmenter_emits_after_silence() {
+        let mut segmenter = SpeechSegmenter::new(WakeWordConfig::default());
+
+        assert!(segmenter.push(&tone(600, 0.0)).is_empty());
+        assert!(segmenter.push(&tone(600, 0.5)).is_empty());
+
+        let segments = segmenter.push(&tone(600, 0.0));
+        assert_eq!(segments.len(), 1);
+        assert!(segments[0].len() >= (CAPTURE_SAMPLE_RATE * 600 / 1000) as usize);
+    }
+
+    #[test]
+    fn test_segmenter_ignores_short_noise() {
+        let mut segmenter = SpeechSegmenter::new(WakeWordConfig::default());
+
+        segmenter.push(&tone(60, 0.5));
+        assert!(segmenter.push(&tone(600, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn test_segmenter_splits_long_speech() {
+        let mut segmenter = SpeechSegmenter::new(WakeWordConfig::default());
+        let segments = segmenter.push(&tone(6000, 0.5));
+        assert_eq!(segments.len(), 2);
+    }
+
+    #[test]
+    fn test_configured_wake_word() {
+        let conn = crate::db::test_support::test_conn();
+        assert_eq!(configured_wake_word(&conn).unwrap(), None);
+
+        conn.execute("INSERT INTO voice_settings (id, wake_word) VALUES ('default', '  ')", []).unwrap();
+        assert_eq!(configured_wake_word(&conn).unwrap(), None);
+        conn.execute("UPDATE voice_settings SET wake_word = 'hey nova'", []).unwrap();
+        assert_eq!(configured_wake_word(&conn).unwrap(), Some("hey nova".to_string()));
+    }
+
+    #[test]
+    fn test_listener_status_idle() {
+        let listener = WakeWordListener::new();
+        let status = listener.status();
+        assert!(!status.listening);
+        assert_eq!(status.detections, 0);
+    }
+}
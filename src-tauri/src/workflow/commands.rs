@@ -2,7 +2,8 @@
 //!
 //! Commands for workflow management, execution, and triggers.
 
-use tauri::State;
+use crate::error::AppError;
+use tauri::{Emitter, Manager, State};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -10,8 +11,43 @@ use super::store::{
     Workflow, WorkflowDefinition, WorkflowExecution, ExecutionStatus,
     InMemoryWorkflowStore, WorkflowStore
 };
-use super::engine::{WorkflowExecutor, ExecutionResult};
-use super::triggers::{TriggerManager, Trigger};
+use super::engine::{WorkflowExecutor, ExecutionResult, ExecutionStart};
+use super::nodes::AgentRuntime;
+use super::triggers::{TriggerManager, Trigger, TriggerType};
+use super::schedule;
+use super::variables::{self, VariableDefinition};
+use crate::security::CredentialManager;
+
+/// Event carrying each node execution record as it happens
+pub const NODE_EVENT: &str = "workflow://node";
+
+/// Agent runtime backed by the shared sidecar process
+pub struct SidecarRuntime {
+    app: tauri::AppHandle,
+}
+
+impl SidecarRuntime {
+    pub fn new(app: tauri::AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentRuntime for SidecarRuntime {
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String> {
+        let app = self.app.clone();
+        let method = method.to_string();
+        tokio::task::spawn_blocking(move || {
+            app.state::<std::sync::Mutex<crate::sidecar::SidecarState>>()
+                .lock()
+                .map_err(|e| format!("Failed to acquire lock: {}", e))?
+                .call(&method, params)
+                .map_err(String::from)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
 
 /// Global state for workflow features
 pub struct WorkflowState {
@@ -23,11 +59,18 @@ pub struct WorkflowState {
 impl WorkflowState {
     pub fn new() -> Self {
         // WorkflowExecutor::new() already registers built-in executors
-        let executor = Arc::new(RwLock::new(WorkflowExecutor::new()));
+        Self::with_executor(WorkflowExecutor::new())
+    }
 
+    /// State whose LLM, skill and recipe nodes call the given agent runtime
+    pub fn with_agent_runtime(runtime: Arc<dyn AgentRuntime>) -> Self {
+        Self::with_executor(WorkflowExecutor::with_agent_runtime(runtime))
+    }
+
+    fn with_executor(executor: WorkflowExecutor) -> Self {
         Self {
             store: Arc::new(RwLock::new(InMemoryWorkflowStore::new())),
-            executor,
+            executor: Arc::new(RwLock::new(executor)),
             triggers: Arc::new(RwLock::new(TriggerManager::new())),
         }
     }
@@ -45,6 +88,7 @@ impl Default for WorkflowState {
 
 /// Create a new workflow
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_create(
     state: State<'_, Arc<WorkflowState>>,
     id: String,
@@ -52,7 +96,7 @@ pub async fn workflow_create(
     description: Option<String>,
     entry_point: String,
     is_active: Option<bool>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut store = state.store.write().await;
 
     let workflow = Workflow {
@@ -63,6 +107,8 @@ pub async fn workflow_create(
             entry_point,
             nodes: std::collections::HashMap::new(),
             connections: vec![],
+            max_parallel: None,
+            variables: Vec::new(),
         },
         version: 1,
         is_active: is_active.unwrap_or(true),
@@ -70,52 +116,62 @@ pub async fn workflow_create(
         updated_at: chrono::Utc::now().to_rfc3339(),
     };
 
-    store.create(workflow)
+    Ok(store.create(workflow)?)
 }
 
 /// Get a workflow by ID
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_get(
     state: State<'_, Arc<WorkflowState>>,
     id: String,
-) -> Result<Option<Workflow>, String> {
+) -> Result<Option<Workflow>, AppError> {
     let store = state.store.read().await;
-    store.get(&id)
+    Ok(store.get(&id)?)
 }
 
 /// List all workflows
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_list(
     state: State<'_, Arc<WorkflowState>>,
-) -> Result<Vec<Workflow>, String> {
+) -> Result<Vec<Workflow>, AppError> {
     let store = state.store.read().await;
-    store.list()
+    Ok(store.list()?)
 }
 
 /// List active workflows
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_list_active(
     state: State<'_, Arc<WorkflowState>>,
-) -> Result<Vec<Workflow>, String> {
+) -> Result<Vec<Workflow>, AppError> {
     let store = state.store.read().await;
-    store.list_active()
+    Ok(store.list_active()?)
 }
 
 /// Update a workflow
+///
+/// Changing `is_active` enables or disables the workflow's scheduled jobs.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+#[allow(clippy::too_many_arguments)]
 pub async fn workflow_update(
+    app: tauri::AppHandle,
     state: State<'_, Arc<WorkflowState>>,
     id: String,
     name: Option<String>,
     description: Option<String>,
     entry_point: Option<String>,
     is_active: Option<bool>,
-) -> Result<(), String> {
+    max_parallel: Option<usize>,
+) -> Result<(), AppError> {
     let mut store = state.store.write().await;
 
     // Get existing workflow
     let mut workflow = store.get(&id)?
-        .ok_or_else(|| "Workflow not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+    let was_active = workflow.is_active;
 
     // Update fields
     if let Some(name) = name {
@@ -128,25 +184,54 @@ pub async fn workflow_update(
         workflow.definition.entry_point = entry_point;
     }
     if let Some(is_active) = is_active {
+        if is_active && !was_active {
+            let problems = workflow_problems(&state, Some(&app), &workflow.definition).await;
+            if !problems.is_empty() {
+                return Err(AppError::invalid_input(format!("Workflow cannot be enabled: {}", problems.join("; "))));
+            }
+        }
         workflow.is_active = is_active;
     }
+    if let Some(max_parallel) = max_parallel {
+        workflow.definition.max_parallel = Some(max_parallel.max(1));
+    }
     workflow.updated_at = chrono::Utc::now().to_rfc3339();
+    let is_active = workflow.is_active;
+
+    store.update(workflow)?;
+    drop(store);
+
+    if is_active != was_active {
+        schedule::sync_workflow_jobs(&app, &id, is_active).await?;
+    }
 
-    store.update(workflow)
+    Ok(())
 }
 
-/// Delete a workflow
+/// Delete a workflow along with its triggers and scheduled jobs
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_delete(
+    app: tauri::AppHandle,
     state: State<'_, Arc<WorkflowState>>,
     id: String,
-) -> Result<(), String> {
-    let mut store = state.store.write().await;
-    store.delete(&id)
+) -> Result<(), AppError> {
+    state.store.write().await.delete(&id)?;
+
+    let triggers = state.triggers.read().await;
+    for handle in triggers.list_for_workflow(&id).await {
+        if handle.trigger_type == TriggerType::Schedule {
+            schedule::unregister_job(&app, &handle.trigger_id).await?;
+        }
+        triggers.unregister(&handle.trigger_id).await?;
+    }
+
+    Ok(())
 }
 
 /// Add a node to a workflow
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_add_node(
     state: State<'_, Arc<WorkflowState>>,
     workflow_id: String,
@@ -156,11 +241,11 @@ pub async fn workflow_add_node(
     y: f64,
     data: Option<serde_json::Value>,
     label: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut store = state.store.write().await;
 
     let mut workflow = store.get(&workflow_id)?
-        .ok_or_else(|| "Workflow not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
 
     use super::store::{WorkflowNode, NodePosition};
 
@@ -175,11 +260,12 @@ pub async fn workflow_add_node(
     workflow.definition.nodes.insert(node_id, node);
     workflow.updated_at = chrono::Utc::now().to_rfc3339();
 
-    store.update(workflow)
+    Ok(store.update(workflow)?)
 }
 
 /// Add a connection between nodes
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_add_connection(
     state: State<'_, Arc<WorkflowState>>,
     workflow_id: String,
@@ -188,11 +274,11 @@ pub async fn workflow_add_connection(
     target: String,
     target_input: String,
     condition: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut store = state.store.write().await;
 
     let mut workflow = store.get(&workflow_id)?
-        .ok_or_else(|| "Workflow not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
 
     use super::store::NodeConnection;
 
@@ -207,7 +293,7 @@ pub async fn workflow_add_connection(
     workflow.definition.connections.push(connection);
     workflow.updated_at = chrono::Utc::now().to_rfc3339();
 
-    store.update(workflow)
+    Ok(store.update(workflow)?)
 }
 
 // ============================================================================
@@ -215,29 +301,164 @@ pub async fn workflow_add_connection(
 // ============================================================================
 
 /// Execute a workflow
+///
+/// Records the run as an execution and emits `NODE_EVENT` for every node
+/// record while it runs.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_execute(
+    app: tauri::AppHandle,
     state: State<'_, Arc<WorkflowState>>,
     id: String,
     input: Option<serde_json::Value>,
+) -> Result<ExecutionResult, AppError> {
+    Ok(run_workflow(
+        &state,
+        Some(&app),
+        &id,
+        "manual",
+        input.unwrap_or(serde_json::json!(null)),
+        false,
+    )
+    .await?)
+}
+
+/// Run a workflow and record it as an execution with the given trigger type
+///
+/// With `require_active` set, inactive workflows are rejected; triggers use
+/// this while manual runs may start any workflow.
+pub(crate) async fn run_workflow(
+    state: &WorkflowState,
+    app: Option<&tauri::AppHandle>,
+    id: &str,
+    trigger_type: &str,
+    input: serde_json::Value,
+    require_active: bool,
 ) -> Result<ExecutionResult, String> {
-    let store = state.store.read().await;
-    let executor = state.executor.read().await;
+    let workflow = state.store.read().await.get(id)?
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+    if require_active && !workflow.is_active {
+        return Err(format!("Workflow {} is not active", workflow.id));
+    }
 
-    let workflow = store.get(&id)?
-        .ok_or_else(|| "Workflow not found".to_string())?;
+    let start = ExecutionStart::entry(&workflow, input.clone());
+    let execution = new_execution(&workflow, trigger_type, input, None);
+    record_execution(state, app, &workflow, execution, start).await
+}
 
-    Ok(executor.execute(&workflow, input.unwrap_or(serde_json::json!(null))))
+fn new_execution(
+    workflow: &Workflow,
+    trigger_type: &str,
+    input: serde_json::Value,
+    replay_of: Option<String>,
+) -> WorkflowExecution {
+    WorkflowExecution {
+        id: uuid::Uuid::new_v4().to_string(),
+        workflow_id: workflow.id.clone(),
+        status: ExecutionStatus::Running,
+        trigger_type: Some(trigger_type.to_string()),
+        started_at: Some(chrono::Utc::now().to_rfc3339()),
+        completed_at: None,
+        result: None,
+        error: None,
+        input: Some(input),
+        replay_of,
+    }
+}
+
+/// Store `execution`, run the workflow from `start` with its variables and
+/// store the outcome
+async fn record_execution(
+    state: &WorkflowState,
+    app: Option<&tauri::AppHandle>,
+    workflow: &Workflow,
+    mut execution: WorkflowExecution,
+    mut start: ExecutionStart,
+) -> Result<ExecutionResult, String> {
+    start.variables = variables::resolve(&workflow.definition.variables, |key| read_secret(app, key))?;
+    state.store.write().await.create_execution(execution.clone())?;
+
+    let result = {
+        let executor = state.executor.read().await;
+        executor
+            .execute_from(&execution.id, workflow, start, &|record| {
+                if let Some(app) = app {
+                    let _ = app.emit(NODE_EVENT, record);
+                }
+            })
+            .await
+    };
+
+    execution.status = if result.success {
+        ExecutionStatus::Completed
+    } else {
+        ExecutionStatus::Failed
+    };
+    execution.completed_at = Some(chrono::Utc::now().to_rfc3339());
+    execution.result = serde_json::to_value(&result).ok();
+    execution.error = result.error.clone();
+    state.store.write().await.update_execution(execution)?;
+
+    Ok(result)
+}
+
+/// Read a secret binding from the keychain of the active profile
+fn read_secret(app: Option<&tauri::AppHandle>, key: &str) -> Result<String, String> {
+    let credentials = app
+        .and_then(|app| app.try_state::<std::sync::Mutex<CredentialManager>>())
+        .ok_or("The keychain is not available")?;
+    let credentials = credentials.lock().map_err(|e| e.to_string())?;
+    credentials.get_password(key).map_err(|e| e.to_string())
+}
+
+/// Re-run a recorded execution, from `from_node` when given
+///
+/// The node gets the input it was recorded with and the nodes that finished
+/// before it keep their recorded outputs instead of running again, so a
+/// long automation can be debugged from the step that failed. Without
+/// `from_node` the whole workflow runs again with the original input. The
+/// workflow runs as currently defined and the replay is recorded as a new
+/// execution pointing back at the original.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn replay_workflow_execution(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<WorkflowState>>,
+    execution_id: String,
+    from_node: Option<String>,
+) -> Result<ExecutionResult, AppError> {
+    replay_execution(&state, Some(&app), &execution_id, from_node.as_deref()).await
+}
+
+async fn replay_execution(
+    state: &WorkflowState,
+    app: Option<&tauri::AppHandle>,
+    execution_id: &str,
+    from_node: Option<&str>,
+) -> Result<ExecutionResult, AppError> {
+    let original = state.store.read().await.get_execution(execution_id)?
+        .ok_or_else(|| AppError::not_found("Execution not found"))?;
+    let workflow = state.store.read().await.get(&original.workflow_id)?
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+
+    let input = original.input.clone().unwrap_or(serde_json::Value::Null);
+    let start = match from_node {
+        Some(node_id) => ExecutionStart::replay(&original.trace(), node_id).map_err(AppError::invalid_input)?,
+        None => ExecutionStart::entry(&workflow, input.clone()),
+    };
+    let execution = new_execution(&workflow, "replay", input, Some(original.id));
+    Ok(record_execution(state, app, &workflow, execution, start).await?)
 }
 
 /// Create an execution record
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_create_execution(
     state: State<'_, Arc<WorkflowState>>,
     id: String,
     workflow_id: String,
     trigger_type: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut store = state.store.write().await;
 
     let execution = WorkflowExecution {
@@ -249,44 +470,49 @@ pub async fn workflow_create_execution(
         completed_at: None,
         result: None,
         error: None,
+        input: None,
+        replay_of: None,
     };
 
-    store.create_execution(execution)
+    Ok(store.create_execution(execution)?)
 }
 
 /// Get execution by ID
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_get_execution(
     state: State<'_, Arc<WorkflowState>>,
     id: String,
-) -> Result<Option<WorkflowExecution>, String> {
+) -> Result<Option<WorkflowExecution>, AppError> {
     let store = state.store.read().await;
-    store.get_execution(&id)
+    Ok(store.get_execution(&id)?)
 }
 
 /// Get executions for a workflow
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_get_executions(
     state: State<'_, Arc<WorkflowState>>,
     workflow_id: String,
-) -> Result<Vec<WorkflowExecution>, String> {
+) -> Result<Vec<WorkflowExecution>, AppError> {
     let store = state.store.read().await;
-    store.get_executions(&workflow_id)
+    Ok(store.get_executions(&workflow_id)?)
 }
 
 /// Update execution status
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_update_execution(
     state: State<'_, Arc<WorkflowState>>,
     id: String,
     status: String,
     result: Option<serde_json::Value>,
     error: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut store = state.store.write().await;
 
     let mut execution = store.get_execution(&id)?
-        .ok_or_else(|| "Execution not found".to_string())?;
+        .ok_or_else(|| AppError::not_found("Execution not found"))?;
 
     execution.status = match status.as_str() {
         "pending" => ExecutionStatus::Pending,
@@ -294,7 +520,7 @@ pub async fn workflow_update_execution(
         "completed" => ExecutionStatus::Completed,
         "failed" => ExecutionStatus::Failed,
         "cancelled" => ExecutionStatus::Cancelled,
-        _ => return Err("Invalid status".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid status")),
     };
 
     execution.result = result;
@@ -304,7 +530,79 @@ pub async fn workflow_update_execution(
         execution.completed_at = Some(chrono::Utc::now().to_rfc3339());
     }
 
-    store.update_execution(execution)
+    Ok(store.update_execution(execution)?)
+}
+
+// ============================================================================
+// Variable Commands
+// ============================================================================
+
+/// Replace the variables of a workflow
+///
+/// Secret variables name the keychain entry holding their value, which is
+/// read each time the workflow runs.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn workflow_set_variables(
+    state: State<'_, Arc<WorkflowState>>,
+    id: String,
+    variables: Vec<VariableDefinition>,
+) -> Result<(), AppError> {
+    variables::validate_definitions(&variables).map_err(AppError::invalid_input)?;
+
+    let mut store = state.store.write().await;
+    let mut workflow = store.get(&id)?
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+    workflow.definition.variables = variables;
+    workflow.updated_at = chrono::Utc::now().to_rfc3339();
+    Ok(store.update(workflow)?)
+}
+
+/// Get the variables of a workflow
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn workflow_get_variables(
+    state: State<'_, Arc<WorkflowState>>,
+    id: String,
+) -> Result<Vec<VariableDefinition>, AppError> {
+    let workflow = state.store.read().await.get(&id)?
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+    Ok(workflow.definition.variables)
+}
+
+/// Problems that keep a workflow from running, empty when it can be enabled
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub async fn workflow_validate(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<WorkflowState>>,
+    id: String,
+) -> Result<Vec<String>, AppError> {
+    let workflow = state.store.read().await.get(&id)?
+        .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+    Ok(workflow_problems(&state, Some(&app), &workflow.definition).await)
+}
+
+/// Invalid nodes or connections, unresolved references and secrets missing
+/// from the keychain
+async fn workflow_problems(
+    state: &WorkflowState,
+    app: Option<&tauri::AppHandle>,
+    definition: &WorkflowDefinition,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Err(e) = state.executor.read().await.validate(definition) {
+        problems.push(e);
+    }
+    problems.extend(variables::check_references(definition));
+    for variable in &definition.variables {
+        if let Some(key) = &variable.secret {
+            if let Err(e) = read_secret(app, key) {
+                problems.push(format!("Secret {} is not available: {}", variable.name, e));
+            }
+        }
+    }
+    problems
 }
 
 // ============================================================================
@@ -312,14 +610,19 @@ pub async fn workflow_update_execution(
 // ============================================================================
 
 /// Register a trigger for a workflow
+///
+/// Schedule triggers also register a `workflow` cron job, enabled while the
+/// workflow is active. `config.input` is passed to scheduled runs.
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_register_trigger(
+    app: tauri::AppHandle,
     state: State<'_, Arc<WorkflowState>>,
     trigger_id: String,
     workflow_id: String,
     trigger_type: String,
     config: Option<serde_json::Value>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let triggers = state.triggers.read().await;
 
     let trigger = match trigger_type.as_str() {
@@ -355,7 +658,7 @@ pub async fn workflow_register_trigger(
                 "POST" => super::triggers::HttpMethod::Post,
                 "PUT" => super::triggers::HttpMethod::Put,
                 "DELETE" => super::triggers::HttpMethod::Delete,
-                _ => return Err("Invalid HTTP method".to_string()),
+                _ => return Err(AppError::invalid_input("Invalid HTTP method")),
             };
             Trigger::Webhook { path, method }
         }
@@ -402,27 +705,57 @@ pub async fn workflow_register_trigger(
             Trigger::Voice { pattern, language }
         }
         "manual" => Trigger::Manual,
-        _ => return Err("Invalid trigger type".to_string()),
+        _ => return Err(AppError::invalid_input("Invalid trigger type")),
     };
 
-    triggers.register(trigger_id, workflow_id, &trigger).await
+    if let Trigger::Schedule { cron, timezone } = &trigger {
+        if triggers.exists(&trigger_id).await {
+            return Err(AppError::invalid_input("Trigger already registered"));
+        }
+        let workflow = state.store.read().await.get(&workflow_id)?
+            .ok_or_else(|| AppError::not_found("Workflow not found"))?;
+        let input = config.as_ref().and_then(|c| c.get("input")).cloned();
+        schedule::register_job(
+            &app,
+            &trigger_id,
+            &workflow.id,
+            &workflow.name,
+            cron,
+            timezone,
+            input,
+            workflow.is_active,
+        )
+        .await?;
+    }
+
+    Ok(triggers.register(trigger_id, workflow_id, &trigger).await?)
 }
 
-/// Unregister a trigger
+/// Unregister a trigger, removing the scheduled job of a schedule trigger
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_unregister_trigger(
+    app: tauri::AppHandle,
     state: State<'_, Arc<WorkflowState>>,
     trigger_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let triggers = state.triggers.read().await;
-    triggers.unregister(&trigger_id).await
+    let handle = triggers.get(&trigger_id).await
+        .ok_or_else(|| AppError::not_found("Trigger not found"))?;
+
+    if handle.trigger_type == TriggerType::Schedule {
+        schedule::unregister_job(&app, &trigger_id).await?;
+    }
+
+    Ok(triggers.unregister(&trigger_id).await?)
 }
 
 /// List active triggers
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_list_triggers(
     state: State<'_, Arc<WorkflowState>>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let triggers = state.triggers.read().await;
     let handles = triggers.list_active().await;
 
@@ -440,9 +773,10 @@ pub async fn workflow_list_triggers(
 
 /// Get trigger count
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
 pub async fn workflow_trigger_count(
     state: State<'_, Arc<WorkflowState>>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let triggers = state.triggers.read().await;
     Ok(triggers.count().await)
 }
@@ -457,4 +791,64 @@ mod tests {
         // Just verify it creates without panic
         assert_eq!(state.triggers.read().await.count().await, 0);
     }
+
+    #[tokio::test]
+    async fn test_replay_execution_records_new_run() {
+        use super::super::store::{NodeConnection, NodePosition, WorkflowNode};
+
+        let state = WorkflowState::new();
+        let nodes = [("start", "trigger", ".".to_string()), ("double", "transform", ".n * 2".to_string())]
+            .into_iter()
+            .map(|(id, node_type, expression)| {
+                let node = WorkflowNode {
+                    id: id.to_string(),
+                    node_type: node_type.to_string(),
+                    position: NodePosition { x: 0.0, y: 0.0 },
+                    data: serde_json::json!({ "expression": expression }),
+                    label: None,
+                };
+                (id.to_string(), node)
+            })
+            .collect();
+        let workflow = Workflow {
+            id: "wf".to_string(),
+            name: "Double".to_string(),
+            description: None,
+            definition: WorkflowDefinition {
+                entry_point: "start".to_string(),
+                nodes,
+                connections: vec![NodeConnection {
+                    source: "start".to_string(),
+                    source_output: "out".to_string(),
+                    target: "double".to_string(),
+                    target_input: String::new(),
+                    condition: None,
+                }],
+                max_parallel: None,
+                variables: Vec::new(),
+            },
+            version: 1,
+            is_active: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        state.store.write().await.create(workflow).unwrap();
+
+        let first = run_workflow(&state, None, "wf", "manual", serde_json::json!({ "n": 4 }), false).await.unwrap();
+        assert_eq!(first.output, serde_json::json!(8));
+
+        let from_node = replay_execution(&state, None, &first.execution_id, Some("double")).await.unwrap();
+        assert_eq!(from_node.executed_nodes, vec!["double"]);
+        assert_eq!(from_node.output, serde_json::json!(8));
+        let whole = replay_execution(&state, None, &first.execution_id, None).await.unwrap();
+        assert_eq!(whole.executed_nodes, vec!["start", "double"]);
+
+        let stored = state.store.read().await.get_execution(&from_node.execution_id).unwrap().unwrap();
+        assert_eq!(stored.replay_of.as_deref(), Some(first.execution_id.as_str()));
+        assert_eq!(stored.trigger_type.as_deref(), Some("replay"));
+        assert_eq!(stored.trace().len(), 1);
+
+        let missing = replay_execution(&state, None, "nope", None).await.unwrap_err();
+        assert_eq!(missing.kind(), crate::error::ErrorKind::NotFound);
+    }
 }
@@ -1,11 +1,324 @@
 //! Workflow Execution Engine
 //! 
-//! Executes workflows node by node with error handling.
+//! Executes workflows node by node with error handling. Node outputs travel
+//! along connections from the output port a node reports to the connected
+//! input ports, which are type-checked before and during the run. Each node
+//! can be retried with backoff and given a timeout, and every attempt is
+//! reported as a `NodeExecutionRecord`.
+//!
+//! A node with several outgoing connections fans out into branches that run
+//! concurrently, up to the workflow's `max_parallel`. Join nodes fan back in,
+//! and each node's `on_error` policy decides whether a failure stops the
+//! whole execution or only its branch.
+//!
+//! Node records keep the input each node received next to its output, so a
+//! recorded execution can be replayed from any node that ran: the node gets
+//! its recorded input and the outputs of the nodes before it are taken from
+//! the record instead of being produced again.
+//!
+//! Secret variable values are masked in every record and in the result.
 
+use futures_util::stream::{FuturesUnordered, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use super::store::Workflow;
-use super::nodes::{NodeExecutor, NodeContext, NodeResult};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use super::expr;
+use super::store::{Workflow, WorkflowDefinition, WorkflowNode};
+use super::variables::{self, ResolvedVariables};
+use super::nodes::{
+    AgentRuntime, NodeExecutor, NodeContext, NodeResult, PortSpec, DEFAULT_INPUT, DEFAULT_OUTPUT,
+};
+
+/// Nodes run at the same time when the workflow does not set `max_parallel`
+pub const DEFAULT_MAX_PARALLEL: usize = 4;
+
+/// Output port that receives `{error, input}` from nodes using `ErrorPolicy::Route`
+pub const ERROR_OUTPUT: &str = "error";
+
+/// Branch an execution starts in
+const MAIN_BRANCH: &str = "main";
+
+/// Node runs allowed in one execution, guarding against cycles
+const MAX_NODE_EXECUTIONS: usize = 1000;
+
+/// Per-node retry settings, read from the node's `retry` data
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetryOptions {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Wait before the first retry
+    pub backoff_ms: u64,
+    /// Factor applied to the wait after each retry
+    pub backoff_multiplier: f64,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_ms: 1000,
+            backoff_multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryOptions {
+    /// Retry settings of a node, defaulting to a single attempt
+    pub fn for_node(node: &WorkflowNode) -> Self {
+        node.data
+            .get("retry")
+            .and_then(|v| serde_json::from_value::<RetryOptions>(v.clone()).ok())
+            .map(|options| Self {
+                max_attempts: options.max_attempts.max(1),
+                ..options
+            })
+            .unwrap_or_default()
+    }
+
+    /// Wait before the given retry (1 for the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.backoff_multiplier.max(1.0).powi(retry.saturating_sub(1) as i32);
+        Duration::from_millis((self.backoff_ms as f64 * factor) as u64)
+    }
+}
+
+/// What a node failure does, read from the node's `on_error` data
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// Stop the whole execution
+    #[default]
+    Fail,
+    /// End the failed branch and let the other branches finish
+    Continue,
+    /// Send `{error, input}` through the node's `error` port
+    Route,
+}
+
+impl ErrorPolicy {
+    pub fn for_node(node: &WorkflowNode) -> Self {
+        node.data
+            .get("on_error")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// When a join node runs, read from the node's `mode` data
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinMode {
+    /// Wait for every incoming branch and pass on `{source node id: output}`.
+    /// Branches that end without arriving are left out once nothing else
+    /// is running.
+    #[default]
+    All,
+    /// Run with the first branch to arrive and ignore the rest
+    Any,
+}
+
+impl JoinMode {
+    pub fn for_node(node: &WorkflowNode) -> Self {
+        node.data
+            .get("mode")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Timing and outcome of one branch of an execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchRecord {
+    pub id: String,
+    /// Branch that forked this one
+    pub parent: Option<String>,
+    /// running, completed, failed or cancelled
+    pub status: String,
+    /// Nodes run in this branch, in order
+    pub nodes: Vec<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Progress of one node in an execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeExecutionRecord {
+    pub execution_id: String,
+    pub workflow_id: String,
+    pub node_id: String,
+    pub node_type: String,
+    /// Branch the node ran in
+    pub branch: String,
+    /// running, retrying, completed or failed
+    pub status: String,
+    pub attempt: u32,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub duration_ms: Option<u64>,
+    /// Value the node received
+    #[serde(default)]
+    pub input: Option<serde_json::Value>,
+    /// Output port the result left on
+    pub port: Option<String>,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl NodeExecutionRecord {
+    /// The record with the given secret values masked
+    fn masked(mut self, secrets: &[String]) -> Self {
+        for value in [&mut self.input, &mut self.output].into_iter().flatten() {
+            variables::mask(value, secrets);
+        }
+        if let Some(error) = self.error.as_mut() {
+            variables::mask_text(error, secrets);
+        }
+        self
+    }
+}
+
+/// Receives node records as they are produced
+pub type RecordSink<'a> = &'a (dyn Fn(&NodeExecutionRecord) + Send + Sync);
+
+/// Node an execution starts at, with what it already knows
+#[derive(Debug, Clone)]
+pub struct ExecutionStart {
+    pub node_id: String,
+    pub input: serde_json::Value,
+    /// Outputs of nodes that are not run again, visible as `$nodes`
+    pub results: HashMap<String, serde_json::Value>,
+    pub variables: ResolvedVariables,
+}
+
+impl ExecutionStart {
+    /// Start at the workflow's entry point
+    pub fn entry(workflow: &Workflow, input: serde_json::Value) -> Self {
+        Self {
+            node_id: workflow.definition.entry_point.clone(),
+            input,
+            results: HashMap::new(),
+            variables: ResolvedVariables::default(),
+        }
+    }
+
+    /// Start at `node_id` as it last ran in the recorded `trace`, with the
+    /// outputs of the nodes that finished before it. Branches that do not
+    /// lead from the node are not run again.
+    pub fn replay(trace: &[NodeExecutionRecord], node_id: &str) -> Result<Self, String> {
+        let position = trace
+            .iter()
+            .rposition(|r| r.node_id == node_id)
+            .ok_or_else(|| format!("Node {} did not run in this execution", node_id))?;
+        let input = trace[position]
+            .input
+            .clone()
+            .ok_or_else(|| format!("No input was recorded for node {}", node_id))?;
+        let results = trace[..position]
+            .iter()
+            .filter_map(|r| Some((r.node_id.clone(), r.output.clone()?)))
+            .collect();
+        Ok(Self {
+            node_id: node_id.to_string(),
+            input,
+            results,
+            variables: ResolvedVariables::default(),
+        })
+    }
+}
+
+/// A node waiting to run with the value delivered to it
+struct Activation {
+    node_id: String,
+    input: serde_json::Value,
+    /// Node that sent the value
+    source: Option<String>,
+    branch: String,
+}
+
+/// Values collected by a join node
+#[derive(Default)]
+struct JoinState {
+    arrivals: serde_json::Map<String, serde_json::Value>,
+    fired: bool,
+}
+
+/// Tracks open branches and how many of their nodes are queued or running
+#[derive(Default)]
+struct BranchTracker {
+    records: Vec<BranchRecord>,
+    open: HashMap<String, (usize, Instant, usize)>,
+}
+
+impl BranchTracker {
+    /// Open a branch and return its id, made unique if `name` was used before
+    fn start(&mut self, name: &str, parent: Option<&str>) -> String {
+        let mut id = name.to_string();
+        let mut n = 1;
+        while self.records.iter().any(|b| b.id == id) {
+            n += 1;
+            id = format!("{}#{}", name, n);
+        }
+        self.open.insert(id.clone(), (self.records.len(), Instant::now(), 0));
+        self.records.push(BranchRecord {
+            id: id.clone(),
+            parent: parent.map(String::from),
+            status: "running".to_string(),
+            nodes: Vec::new(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            duration_ms: None,
+        });
+        id
+    }
+
+    /// Count a node queued in a branch
+    fn add(&mut self, branch: &str) {
+        if let Some((_, _, pending)) = self.open.get_mut(branch) {
+            *pending += 1;
+        }
+    }
+
+    fn fail(&mut self, branch: &str) {
+        if let Some((index, _, _)) = self.open.get(branch) {
+            self.records[*index].status = "failed".to_string();
+        }
+    }
+
+    /// Record a finished node, closing its branch when nothing else is pending
+    fn node_done(&mut self, branch: &str, node_id: &str) {
+        let Some((index, _, pending)) = self.open.get_mut(branch) else {
+            return;
+        };
+        self.records[*index].nodes.push(node_id.to_string());
+        *pending = pending.saturating_sub(1);
+        if *pending == 0 {
+            self.close(branch, "completed");
+        }
+    }
+
+    /// Close a branch, keeping a failed status
+    fn close(&mut self, branch: &str, status: &str) {
+        if let Some((index, started, _)) = self.open.remove(branch) {
+            let record = &mut self.records[index];
+            if record.status == "running" {
+                record.status = status.to_string();
+            }
+            record.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            record.duration_ms = Some(started.elapsed().as_millis() as u64);
+        }
+    }
+
+    fn finish(mut self) -> Vec<BranchRecord> {
+        let open: Vec<String> = self.open.keys().cloned().collect();
+        for branch in open {
+            self.close(&branch, "cancelled");
+        }
+        self.records
+    }
+}
 
 /// Workflow executor
 pub struct WorkflowExecutor {
@@ -24,6 +337,24 @@ impl WorkflowExecutor {
         executor.register_executor("condition", Box::new(super::nodes::ConditionExecutor));
         executor.register_executor("loop", Box::new(super::nodes::LoopExecutor));
         executor.register_executor("agent", Box::new(super::nodes::AgentExecutor));
+        executor.register_executor("http", Box::new(super::nodes::HttpExecutor::new()));
+        executor.register_executor("delay", Box::new(super::nodes::DelayExecutor));
+        executor.register_executor("transform", Box::new(super::nodes::TransformExecutor));
+        executor.register_executor("join", Box::new(super::nodes::JoinExecutor));
+        executor.register_executor("send_email", Box::new(super::nodes::SendEmailExecutor));
+        executor.register_executor("notify_channel", Box::new(super::nodes::NotifyChannelExecutor));
+        executor
+    }
+
+    /// Create an executor whose LLM, skill and recipe nodes call `runtime`
+    pub fn with_agent_runtime(runtime: Arc<dyn AgentRuntime>) -> Self {
+        use super::nodes::{LlmExecutor, RecipeExecutor, SkillExecutor};
+
+        let mut executor = Self::new();
+        executor.register_executor("llm", Box::new(LlmExecutor::new(runtime.clone())));
+        executor.register_executor("agent", Box::new(LlmExecutor::new(runtime.clone())));
+        executor.register_executor("skill", Box::new(SkillExecutor::new(runtime.clone())));
+        executor.register_executor("recipe", Box::new(RecipeExecutor::new(runtime)));
         executor
     }
     
@@ -31,84 +362,451 @@ impl WorkflowExecutor {
     pub fn register_executor(&mut self, node_type: &str, executor: Box<dyn NodeExecutor>) {
         self.node_executors.insert(node_type.to_string(), executor);
     }
+
+    fn executor_for(&self, node: &WorkflowNode) -> Result<&dyn NodeExecutor, String> {
+        self.node_executors
+            .get(&node.node_type)
+            .map(|e| e.as_ref())
+            .ok_or_else(|| format!("No executor for node type: {}", node.node_type))
+    }
+
+    /// Check that every node has an executor and every connection joins
+    /// existing ports of compatible types
+    pub fn validate(&self, definition: &WorkflowDefinition) -> Result<(), String> {
+        if !definition.nodes.contains_key(&definition.entry_point) {
+            return Err(format!("Node not found: {}", definition.entry_point));
+        }
+        for node in definition.nodes.values() {
+            self.executor_for(node)?;
+        }
+
+        for connection in &definition.connections {
+            let source = definition
+                .nodes
+                .get(&connection.source)
+                .ok_or_else(|| format!("Connection from unknown node: {}", connection.source))?;
+            let target = definition
+                .nodes
+                .get(&connection.target)
+                .ok_or_else(|| format!("Connection to unknown node: {}", connection.target))?;
+
+            let outputs = self.executor_for(source)?.outputs();
+            let inputs = self.executor_for(target)?.inputs();
+            let error_port = PortSpec::new(ERROR_OUTPUT, super::nodes::PortType::Object);
+            let output = find_port(&outputs, &connection.source_output, DEFAULT_OUTPUT)
+                .or((connection.source_output == ERROR_OUTPUT).then_some(&error_port))
+                .ok_or_else(|| format!("Node {} has no output '{}'", source.id, connection.source_output))?;
+            let input = find_port(&inputs, &connection.target_input, DEFAULT_INPUT)
+                .ok_or_else(|| format!("Node {} has no input '{}'", target.id, connection.target_input))?;
+
+            if !output.port_type.connects_to(input.port_type) {
+                return Err(format!(
+                    "Cannot connect {}.{} ({:?}) to {}.{} ({:?})",
+                    source.id, output.name, output.port_type, target.id, input.name, input.port_type
+                ));
+            }
+        }
+
+        Ok(())
+    }
     
     /// Execute a workflow
-    pub fn execute(&self, workflow: &Workflow, input: serde_json::Value) -> ExecutionResult {
+    pub async fn execute(&self, workflow: &Workflow, input: serde_json::Value) -> ExecutionResult {
+        let execution_id = uuid::Uuid::new_v4().to_string();
+        self.execute_with(&execution_id, workflow, input, &|_| {}).await
+    }
+
+    /// Execute a workflow, passing each node record to `on_record` as it happens
+    pub async fn execute_with(
+        &self,
+        execution_id: &str,
+        workflow: &Workflow,
+        input: serde_json::Value,
+        on_record: RecordSink<'_>,
+    ) -> ExecutionResult {
+        let start = ExecutionStart::entry(workflow, input);
+        self.execute_from(execution_id, workflow, start, on_record).await
+    }
+
+    /// Execute a workflow from `start`, passing each node record to
+    /// `on_record` as it happens
+    pub async fn execute_from(
+        &self,
+        execution_id: &str,
+        workflow: &Workflow,
+        start: ExecutionStart,
+        on_record: RecordSink<'_>,
+    ) -> ExecutionResult {
         let definition = &workflow.definition;
+        let secrets = start.variables.secrets;
         let mut context = NodeContext {
             workflow_id: workflow.id.clone(),
-            variables: HashMap::new(),
-            input,
-            results: HashMap::new(),
+            variables: start.variables.values,
+            input: serde_json::Value::Null,
+            results: start.results,
         };
-        
-        // Start from entry point
-        let mut current_node_id = definition.entry_point.clone();
+        let masked_sink = |record: &NodeExecutionRecord| on_record(&record.clone().masked(&secrets));
+        let on_record: RecordSink<'_> = if secrets.is_empty() { on_record } else { &masked_sink };
+        let max_parallel = definition.max_parallel.unwrap_or(DEFAULT_MAX_PARALLEL).max(1);
         let mut executed_nodes = Vec::new();
-        
-        while !current_node_id.is_empty() {
-            // Get node
-            let node = match definition.nodes.get(&current_node_id) {
-                Some(n) => n,
-                None => {
-                    return ExecutionResult {
-                        success: false,
-                        output: serde_json::json!(null),
-                        executed_nodes,
-                        error: Some(format!("Node not found: {}", current_node_id)),
+        let mut records = Vec::new();
+        let mut branches = BranchTracker::default();
+        let mut joins: HashMap<String, JoinState> = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut running = FuturesUnordered::new();
+        let mut started = 0;
+
+        let outcome = match self.validate(definition) {
+            Err(e) => Err(e),
+            Ok(()) => {
+                let branch = branches.start(MAIN_BRANCH, None);
+                branches.add(&branch);
+                queue.push_back(Activation {
+                    node_id: start.node_id,
+                    input: start.input,
+                    source: None,
+                    branch,
+                });
+
+                'run: loop {
+                    // Start queued nodes up to the parallelism limit
+                    while running.len() < max_parallel {
+                        let Some(activation) = queue.pop_front() else {
+                            break;
+                        };
+                        if started >= MAX_NODE_EXECUTIONS {
+                            break 'run Err(format!("Workflow exceeded {} node runs", MAX_NODE_EXECUTIONS));
+                        }
+                        started += 1;
+
+                        // Get node and executor
+                        let Some(node) = definition.nodes.get(&activation.node_id) else {
+                            break 'run Err(format!("Node not found: {}", activation.node_id));
+                        };
+                        let executor = match self.executor_for(node) {
+                            Ok(e) => e,
+                            Err(e) => break 'run Err(e),
+                        };
+
+                        let mut node_context = context.clone();
+                        node_context.input = activation.input.clone();
+                        running.push(async move {
+                            let (result, record) = self
+                                .run_node(execution_id, executor, node, &node_context, &activation.branch, on_record)
+                                .await;
+                            (activation, node, result, record)
+                        });
+                    }
+
+                    let Some((activation, node, result, record)) = running.next().await else {
+                        // Nothing left to run: release joins still waiting on
+                        // branches that ended without reaching them
+                        let waiting: Vec<(String, serde_json::Map<String, serde_json::Value>)> = joins
+                            .iter_mut()
+                            .filter(|(_, state)| !state.arrivals.is_empty())
+                            .map(|(id, state)| (id.clone(), std::mem::take(&mut state.arrivals)))
+                            .collect();
+                        if waiting.is_empty() {
+                            break Ok(());
+                        }
+                        for (join_id, arrivals) in waiting {
+                            let branch = branches.start(&join_id, None);
+                            branches.add(&branch);
+                            queue.push_back(Activation {
+                                node_id: join_id,
+                                input: serde_json::Value::Object(arrivals),
+                                source: None,
+                                branch,
+                            });
+                        }
+                        continue;
                     };
-                }
-            };
-            
-            // Get executor
-            let executor = match self.node_executors.get(&node.node_type) {
-                Some(e) => e,
-                None => {
-                    return ExecutionResult {
-                        success: false,
-                        output: serde_json::json!(null),
-                        executed_nodes,
-                        error: Some(format!("No executor for node type: {}", node.node_type)),
+                    executed_nodes.push(node.id.clone());
+                    records.push(record.masked(&secrets));
+
+                    let next = match result {
+                        NodeResult::Success { output, next_node, port } => {
+                            context.results.insert(node.id.clone(), output.clone());
+
+                            let port = port.unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+                            self.route(definition, node, &port, &output, next_node, &context)
+                        }
+                        NodeResult::Failure { error } => {
+                            let error = format!("Node {} failed: {}", node.id, error);
+                            match ErrorPolicy::for_node(node) {
+                                ErrorPolicy::Fail => {
+                                    branches.fail(&activation.branch);
+                                    break Err(error);
+                                }
+                                ErrorPolicy::Continue => {
+                                    branches.fail(&activation.branch);
+                                    Ok(Vec::new())
+                                }
+                                ErrorPolicy::Route => {
+                                    let payload = serde_json::json!({ "error": error, "input": activation.input });
+                                    context.results.insert(node.id.clone(), payload.clone());
+                                    self.route(definition, node, ERROR_OUTPUT, &payload, None, &context)
+                                }
+                            }
+                        }
                     };
-                }
-            };
-            
-            // Execute node
-            let result = executor.execute(node, &context);
-            
-            match result {
-                NodeResult::Success { output, next_node } => {
-                    context.results.insert(current_node_id.clone(), output.clone());
-                    executed_nodes.push(current_node_id.clone());
-                    
-                    match next_node {
-                        Some(next) => current_node_id = next,
-                        None => break, // End of workflow
-                    }
-                }
-                NodeResult::Failure { error } => {
-                    return ExecutionResult {
-                        success: false,
-                        output: serde_json::json!(null),
-                        executed_nodes,
-                        error: Some(error),
+                    let next = match next {
+                        Ok(next) => next,
+                        Err(e) => {
+                            branches.fail(&activation.branch);
+                            break Err(e);
+                        }
                     };
+
+                    // Fan out into new branches, or carry on in this one
+                    let fan_out = next.len() > 1;
+                    for mut next_activation in next {
+                        let target = definition.nodes.get(&next_activation.node_id);
+                        if let Some(join) = target.filter(|t| t.node_type == "join") {
+                            if let Some(input) = self.arrive(definition, &mut joins, join, next_activation) {
+                                let branch = branches.start(&join.id, None);
+                                branches.add(&branch);
+                                queue.push_back(Activation {
+                                    node_id: join.id.clone(),
+                                    input,
+                                    source: None,
+                                    branch,
+                                });
+                            }
+                            continue;
+                        }
+
+                        next_activation.branch = if fan_out {
+                            let name = format!("{}->{}", node.id, next_activation.node_id);
+                            branches.start(&name, Some(&activation.branch))
+                        } else {
+                            activation.branch.clone()
+                        };
+                        branches.add(&next_activation.branch);
+                        queue.push_back(next_activation);
+                    }
+                    branches.node_done(&activation.branch, &node.id);
                 }
             }
+        };
+        // Stop nodes still running after a failure
+        drop(running);
+
+        let (success, mut output, mut error) = match outcome {
+            // Get final output from last node result
+            Ok(()) => (
+                true,
+                executed_nodes.last()
+                    .and_then(|id| context.results.get(id))
+                    .cloned()
+                    .unwrap_or(serde_json::json!(null)),
+                None,
+            ),
+            Err(e) => (false, serde_json::json!(null), Some(e)),
+        };
+
+        variables::mask(&mut output, &secrets);
+        if let Some(error) = error.as_mut() {
+            variables::mask_text(error, &secrets);
         }
-        
-        // Get final output from last node result
-        let final_output = executed_nodes.last()
-            .and_then(|id| context.results.get(id))
-            .cloned()
-            .unwrap_or(serde_json::json!(null));
-        
+
         ExecutionResult {
-            success: true,
-            output: final_output,
+            execution_id: execution_id.to_string(),
+            success,
+            output,
             executed_nodes,
+            error,
+            records,
+            branches: branches.finish(),
+        }
+    }
+
+    /// Deliver a branch output to a join node, returning the join input once
+    /// the node should run
+    fn arrive(
+        &self,
+        definition: &WorkflowDefinition,
+        joins: &mut HashMap<String, JoinState>,
+        join: &WorkflowNode,
+        activation: Activation,
+    ) -> Option<serde_json::Value> {
+        let state = joins.entry(join.id.clone()).or_default();
+        match JoinMode::for_node(join) {
+            JoinMode::Any => {
+                if state.fired {
+                    return None;
+                }
+                state.fired = true;
+                Some(activation.input)
+            }
+            JoinMode::All => {
+                let source = activation.source.unwrap_or_default();
+                state.arrivals.insert(source, activation.input);
+                let expected: HashSet<&str> = definition
+                    .connections
+                    .iter()
+                    .filter(|c| c.target == join.id)
+                    .map(|c| c.source.as_str())
+                    .collect();
+                if expected.iter().all(|source| state.arrivals.contains_key(*source)) {
+                    Some(serde_json::Value::Object(std::mem::take(&mut state.arrivals)))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Run a node with its retry and timeout settings
+    async fn run_node(
+        &self,
+        execution_id: &str,
+        executor: &dyn NodeExecutor,
+        node: &WorkflowNode,
+        context: &NodeContext,
+        branch: &str,
+        on_record: RecordSink<'_>,
+    ) -> (NodeResult, NodeExecutionRecord) {
+        let retry = RetryOptions::for_node(node);
+        let timeout = node
+            .data
+            .get("timeout_ms")
+            .and_then(|v| v.as_u64())
+            .map(Duration::from_millis);
+        let started = Instant::now();
+        let mut record = NodeExecutionRecord {
+            execution_id: execution_id.to_string(),
+            workflow_id: context.workflow_id.clone(),
+            node_id: node.id.clone(),
+            node_type: node.node_type.clone(),
+            branch: branch.to_string(),
+            status: "running".to_string(),
+            attempt: 1,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            duration_ms: None,
+            input: Some(context.input.clone()),
+            port: None,
+            output: None,
             error: None,
+        };
+
+        let mut attempt = 1;
+        loop {
+            record.attempt = attempt;
+            record.status = "running".to_string();
+            on_record(&record);
+
+            let result = match timeout {
+                Some(limit) => match tokio::time::timeout(limit, executor.execute(node, context)).await {
+                    Ok(result) => result,
+                    Err(_) => NodeResult::Failure {
+                        error: format!("Timed out after {} ms", limit.as_millis()),
+                    },
+                },
+                None => executor.execute(node, context).await,
+            };
+
+            match &result {
+                NodeResult::Failure { error } if attempt < retry.max_attempts => {
+                    record.status = "retrying".to_string();
+                    record.error = Some(error.clone());
+                    on_record(&record);
+                    tokio::time::sleep(retry.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                _ => {
+                    record.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                    record.duration_ms = Some(started.elapsed().as_millis() as u64);
+                    match &result {
+                        NodeResult::Success { output, port, .. } => {
+                            record.status = "completed".to_string();
+                            record.port = Some(port.clone().unwrap_or_else(|| DEFAULT_OUTPUT.to_string()));
+                            record.output = Some(output.clone());
+                            record.error = None;
+                        }
+                        NodeResult::Failure { error } => {
+                            record.status = "failed".to_string();
+                            record.error = Some(error.clone());
+                        }
+                    }
+                    on_record(&record);
+                    return (result, record);
+                }
+            }
+        }
+    }
+
+    /// Work out which nodes receive a node's output.
+    ///
+    /// Nodes without outgoing connections fall back to the `next` node named
+    /// in their data.
+    fn route(
+        &self,
+        definition: &WorkflowDefinition,
+        node: &WorkflowNode,
+        port: &str,
+        output: &serde_json::Value,
+        next_node: Option<String>,
+        context: &NodeContext,
+    ) -> Result<Vec<Activation>, String> {
+        let outgoing: Vec<_> = definition
+            .connections
+            .iter()
+            .filter(|c| c.source == node.id)
+            .collect();
+
+        if outgoing.is_empty() {
+            return Ok(next_node
+                .map(|node_id| Activation {
+                    node_id,
+                    input: output.clone(),
+                    source: Some(node.id.clone()),
+                    branch: String::new(),
+                })
+                .into_iter()
+                .collect());
+        }
+
+        let variables = context.expression_variables();
+        let mut activations = Vec::new();
+        for connection in outgoing {
+            if port_name(&connection.source_output, DEFAULT_OUTPUT) != port {
+                continue;
+            }
+            if let Some(condition) = connection.condition.as_deref().filter(|c| !c.trim().is_empty()) {
+                let value = expr::evaluate_one(condition, output, &variables)
+                    .map_err(|e| format!("Invalid connection condition '{}': {}", condition, e))?;
+                if !expr::is_truthy(&value) {
+                    continue;
+                }
+            }
+
+            let target = definition
+                .nodes
+                .get(&connection.target)
+                .ok_or_else(|| format!("Node not found: {}", connection.target))?;
+            let inputs = self.executor_for(target)?.inputs();
+            if let Some(input) = find_port(&inputs, &connection.target_input, DEFAULT_INPUT) {
+                if !input.port_type.accepts(output) {
+                    return Err(format!(
+                        "Node {} input '{}' expects {:?}, got {}",
+                        target.id,
+                        input.name,
+                        input.port_type,
+                        expr::type_name(output)
+                    ));
+                }
+            }
+
+            activations.push(Activation {
+                node_id: target.id.clone(),
+                input: output.clone(),
+                source: Some(node.id.clone()),
+                branch: String::new(),
+            });
         }
+        Ok(activations)
     }
 }
 
@@ -118,13 +816,35 @@ impl Default for WorkflowExecutor {
     }
 }
 
+/// Port name of a connection end, treating an empty name as the default port
+fn port_name<'a>(name: &'a str, default: &'a str) -> &'a str {
+    if name.trim().is_empty() {
+        default
+    } else {
+        name
+    }
+}
+
+fn find_port<'a>(ports: &'a [PortSpec], name: &str, default: &str) -> Option<&'a PortSpec> {
+    let name = port_name(name, default);
+    ports.iter().find(|p| p.name == name)
+}
+
 /// Result of workflow execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
+    #[serde(default)]
+    pub execution_id: String,
     pub success: bool,
     pub output: serde_json::Value,
     pub executed_nodes: Vec<String>,
     pub error: Option<String>,
+    /// Final record of every node run, in the order they finished
+    #[serde(default)]
+    pub records: Vec<NodeExecutionRecord>,
+    /// Timing and outcome of every branch
+    #[serde(default)]
+    pub branches: Vec<BranchRecord>,
 }
 
 #[cfg(test)]
@@ -136,4 +856,316 @@ mod tests {
         let executor = WorkflowExecutor::new();
         assert!(executor.node_executors.is_empty());
     }
+
+    use super::super::nodes::PortType;
+    use super::super::store::{NodeConnection, NodePosition};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    fn node(id: &str, node_type: &str, data: serde_json::Value) -> (String, WorkflowNode) {
+        (
+            id.to_string(),
+            WorkflowNode {
+                id: id.to_string(),
+                node_type: node_type.to_string(),
+                position: NodePosition { x: 0.0, y: 0.0 },
+                data,
+                label: None,
+            },
+        )
+    }
+
+    fn connect(source: &str, port: &str, target: &str) -> NodeConnection {
+        NodeConnection {
+            source: source.to_string(),
+            source_output: port.to_string(),
+            target: target.to_string(),
+            target_input: String::new(),
+            condition: None,
+        }
+    }
+
+    fn workflow(nodes: Vec<(String, WorkflowNode)>, connections: Vec<NodeConnection>) -> Workflow {
+        Workflow {
+            id: "wf".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            definition: WorkflowDefinition {
+                entry_point: "start".to_string(),
+                nodes: nodes.into_iter().collect(),
+                connections,
+                max_parallel: None,
+                variables: Vec::new(),
+            },
+            version: 1,
+            is_active: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    /// Fails until its third attempt
+    struct FlakyExecutor {
+        calls: AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl NodeExecutor for FlakyExecutor {
+        async fn execute(&self, node: &WorkflowNode, _context: &NodeContext) -> NodeResult {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                NodeResult::Failure { error: "not yet".to_string() }
+            } else {
+                NodeResult::output(serde_json::json!("done"), node)
+            }
+        }
+
+        fn inputs(&self) -> Vec<PortSpec> {
+            vec![PortSpec::new(DEFAULT_INPUT, PortType::Number)]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_through_condition_ports() {
+        let executor = WorkflowExecutor::new();
+        let workflow = workflow(
+            vec![
+                node("start", "trigger", serde_json::json!({})),
+                node("check", "condition", serde_json::json!({ "condition": ".count > 1" })),
+                node("many", "transform", serde_json::json!({ "expression": "\"many: \" + (.count | tostring)" })),
+                node("few", "transform", serde_json::json!({ "expression": "\"few\"" })),
+            ],
+            vec![
+                connect("start", "out", "check"),
+                connect("check", "true", "many"),
+                connect("check", "false", "few"),
+            ],
+        );
+
+        let result = executor.execute(&workflow, serde_json::json!({ "count": 3 })).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.executed_nodes, vec!["start", "check", "many"]);
+        assert_eq!(result.output, serde_json::json!("many: 3"));
+        assert_eq!(result.records[1].port.as_deref(), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_and_streams_records() {
+        let mut executor = WorkflowExecutor::new();
+        executor.register_executor("flaky", Box::new(FlakyExecutor { calls: AtomicU32::new(0) }));
+        let workflow = workflow(
+            vec![node("start", "flaky", serde_json::json!({
+                "retry": { "max_attempts": 3, "backoff_ms": 1 }
+            }))],
+            vec![],
+        );
+        let streamed = Mutex::new(Vec::new());
+
+        let result = executor
+            .execute_with("exec-1", &workflow, serde_json::json!(1), &|record| {
+                streamed.lock().unwrap().push(record.status.clone());
+            })
+            .await;
+
+        assert!(result.success);
+        assert_eq!(result.records[0].attempt, 3);
+        assert_eq!(result.records[0].execution_id, "exec-1");
+        assert_eq!(
+            *streamed.lock().unwrap(),
+            vec!["running", "retrying", "running", "retrying", "running", "completed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_mismatched_ports() {
+        let mut executor = WorkflowExecutor::new();
+        executor.register_executor("flaky", Box::new(FlakyExecutor { calls: AtomicU32::new(2) }));
+
+        let unknown_port = workflow(
+            vec![
+                node("start", "condition", serde_json::json!({})),
+                node("next", "delay", serde_json::json!({ "duration_ms": 0 })),
+            ],
+            vec![connect("start", "out", "next")],
+        );
+        let result = executor.execute(&unknown_port, serde_json::json!(null)).await;
+        assert_eq!(result.error.as_deref(), Some("Node start has no output 'out'"));
+        assert!(result.executed_nodes.is_empty());
+
+        let wrong_type = workflow(
+            vec![
+                node("start", "trigger", serde_json::json!({})),
+                node("next", "flaky", serde_json::json!({})),
+            ],
+            vec![connect("start", "out", "next")],
+        );
+        let result = executor.execute(&wrong_type, serde_json::json!("text")).await;
+        assert_eq!(result.error.as_deref(), Some("Node next input 'in' expects Number, got string"));
+    }
+
+    #[tokio::test]
+    async fn test_node_timeout_fails_run() {
+        let executor = WorkflowExecutor::new();
+        let workflow = workflow(
+            vec![node("start", "delay", serde_json::json!({ "duration_ms": 200, "timeout_ms": 10 }))],
+            vec![],
+        );
+
+        let result = executor.execute(&workflow, serde_json::json!(null)).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Node start failed: Timed out after 10 ms"));
+        assert_eq!(result.records[0].status, "failed");
+    }
+
+    #[tokio::test]
+    async fn test_parallel_branches_join_all() {
+        let executor = WorkflowExecutor::new();
+        let mut workflow = workflow(
+            vec![
+                node("start", "trigger", serde_json::json!({})),
+                node("a", "delay", serde_json::json!({ "duration_ms": 150 })),
+                node("b", "delay", serde_json::json!({ "duration_ms": 150 })),
+                node("merge", "join", serde_json::json!({})),
+            ],
+            vec![
+                connect("start", "out", "a"),
+                connect("start", "out", "b"),
+                connect("a", "out", "merge"),
+                connect("b", "out", "merge"),
+            ],
+        );
+        workflow.definition.max_parallel = Some(2);
+
+        let started = Instant::now();
+        let result = executor.execute(&workflow, serde_json::json!(7)).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(started.elapsed() < Duration::from_millis(290));
+        assert_eq!(result.output, serde_json::json!({ "a": 7, "b": 7 }));
+        assert_eq!(result.executed_nodes.last().map(String::as_str), Some("merge"));
+
+        let branch_ids: Vec<&str> = result.branches.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(branch_ids, vec!["main", "start->a", "start->b", "merge"]);
+        assert!(result.branches.iter().all(|b| b.status == "completed" && b.duration_ms.is_some()));
+        assert_eq!(result.branches[1].parent.as_deref(), Some("main"));
+    }
+
+    #[tokio::test]
+    async fn test_branch_error_policies() {
+        let executor = WorkflowExecutor::new();
+        let build = |policy: &str| {
+            workflow(
+                vec![
+                    node("start", "trigger", serde_json::json!({})),
+                    node("bad", "transform", serde_json::json!({ "expression": ".missing[]", "on_error": policy })),
+                    node("good", "transform", serde_json::json!({ "expression": "\"ok\"" })),
+                    node("handler", "transform", serde_json::json!({ "expression": "\"handled\"" })),
+                    node("merge", "join", serde_json::json!({})),
+                ],
+                vec![
+                    connect("start", "out", "bad"),
+                    connect("start", "out", "good"),
+                    connect("bad", "out", "merge"),
+                    connect("bad", "error", "handler"),
+                    connect("good", "out", "merge"),
+                ],
+            )
+        };
+
+        let result = executor.execute(&build("fail"), serde_json::json!({})).await;
+        assert!(!result.success);
+
+        // The failed branch never reaches the join, which runs with what arrived
+        let result = executor.execute(&build("continue"), serde_json::json!({})).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, serde_json::json!({ "good": "ok" }));
+        let failed = result.branches.iter().find(|b| b.id == "start->bad").unwrap();
+        assert_eq!(failed.status, "failed");
+
+        let result = executor.execute(&build("route"), serde_json::json!({})).await;
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.executed_nodes.contains(&"handler".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_join_any_runs_once() {
+        let executor = WorkflowExecutor::new();
+        let workflow = workflow(
+            vec![
+                node("start", "trigger", serde_json::json!({})),
+                node("fast", "transform", serde_json::json!({ "expression": "\"fast\"" })),
+                node("slow", "delay", serde_json::json!({ "duration_ms": 50 })),
+                node("first", "join", serde_json::json!({ "mode": "any" })),
+            ],
+            vec![
+                connect("start", "out", "fast"),
+                connect("start", "out", "slow"),
+                connect("fast", "out", "first"),
+                connect("slow", "out", "first"),
+            ],
+        );
+
+        let result = executor.execute(&workflow, serde_json::json!("input")).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.executed_nodes.iter().filter(|n| *n == "first").count(), 1);
+        let join = result.records.iter().find(|r| r.node_id == "first").unwrap();
+        assert_eq!(join.output, Some(serde_json::json!("fast")));
+    }
+
+    #[tokio::test]
+    async fn test_replay_from_recorded_node() {
+        let executor = WorkflowExecutor::new();
+        let mut workflow = workflow(
+            vec![
+                node("start", "trigger", serde_json::json!({})),
+                node("add", "transform", serde_json::json!({ "expression": ".count + 1" })),
+                node("format", "transform", serde_json::json!({ "expression": "\"total: \" + tostring" })),
+            ],
+            vec![connect("start", "out", "add"), connect("add", "out", "format")],
+        );
+        let recorded = executor.execute(&workflow, serde_json::json!({ "count": 2 })).await;
+        assert_eq!(recorded.output, serde_json::json!("total: 3"));
+        assert_eq!(recorded.records[2].input, Some(serde_json::json!(3)));
+
+        // Fix the last step and replay only that one
+        workflow.definition.nodes.get_mut("format").unwrap().data =
+            serde_json::json!({ "expression": "\"sum of \" + ($nodes.start.count | tostring) + \": \" + tostring" });
+        let start = ExecutionStart::replay(&recorded.records, "format").unwrap();
+        assert_eq!(start.results.len(), 2);
+        let replayed = executor.execute_from("exec-2", &workflow, start, &|_| {}).await;
+
+        assert!(replayed.success, "{:?}", replayed.error);
+        assert_eq!(replayed.executed_nodes, vec!["format"]);
+        assert_eq!(replayed.output, serde_json::json!("sum of 2: 3"));
+
+        let error = ExecutionStart::replay(&recorded.records, "missing").unwrap_err();
+        assert_eq!(error, "Node missing did not run in this execution");
+    }
+
+    #[tokio::test]
+    async fn test_variables_with_masked_secrets() {
+        let executor = WorkflowExecutor::new();
+        let workflow = workflow(
+            vec![node("start", "transform", serde_json::json!({ "expression": "$greeting + \" \" + $token" }))],
+            vec![],
+        );
+        let mut start = ExecutionStart::entry(&workflow, serde_json::json!(null));
+        start.variables.values.insert("greeting".to_string(), serde_json::json!("hello"));
+        start.variables.values.insert("token".to_string(), serde_json::json!("s3cr3t"));
+        start.variables.secrets.push("s3cr3t".to_string());
+        let streamed = Mutex::new(Vec::new());
+
+        let result = executor
+            .execute_from("exec-1", &workflow, start, &|record| {
+                streamed.lock().unwrap().push(record.output.clone());
+            })
+            .await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, serde_json::json!("hello [secret]"));
+        assert_eq!(result.records[0].output, Some(serde_json::json!("hello [secret]")));
+        assert_eq!(streamed.lock().unwrap().last().cloned().flatten(), Some(serde_json::json!("hello [secret]")));
+    }
 }
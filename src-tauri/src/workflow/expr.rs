@@ -0,0 +1,965 @@
+//! Workflow Expressions
+//!
+//! A small jq-like language used by transform and condition nodes and by
+//! `{{ ... }}` templates in node configuration. Supported syntax:
+//! - paths: `.`, `.a.b`, `.["key"]`, `.[0]`, `.[]`
+//! - pipes and streams: `a | b`, `a, b`
+//! - construction: `[ ... ]`, `{a: .x, b, "c": 1}`
+//! - operators: `+ - * /`, `== != < <= > >=`, `and`, `or`, `//`
+//! - variables: `$name`
+//! - builtins: `length`, `keys`, `values`, `map(f)`, `select(f)`, `not`,
+//!   `type`, `tostring`, `tonumber`, `first`, `last`, `add`, `has(k)`,
+//!   `join(sep)`, `sort`, `reverse`, `empty`
+
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Evaluate an expression and return every value it produces
+pub fn evaluate(
+    expression: &str,
+    input: &Value,
+    variables: &HashMap<String, Value>,
+) -> Result<Vec<Value>, String> {
+    let expr = parse(expression)?;
+    eval(&expr, input, variables)
+}
+
+/// Evaluate an expression and return its first value, or null if it produced none
+pub fn evaluate_one(
+    expression: &str,
+    input: &Value,
+    variables: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    Ok(evaluate(expression, input, variables)?
+        .into_iter()
+        .next()
+        .unwrap_or(Value::Null))
+}
+
+/// jq truthiness: everything except `false` and `null` is true
+pub fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Replace `{{ expr }}` placeholders in a string.
+///
+/// A string that is a single placeholder keeps the value's JSON type; inside
+/// longer strings, strings are inserted as-is and other values as JSON.
+pub fn render(
+    template: &str,
+    input: &Value,
+    variables: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    let trimmed = template.trim();
+    if let Some(inner) = trimmed.strip_prefix("{{").and_then(|t| t.strip_suffix("}}")) {
+        if !inner.contains("{{") && !inner.contains("}}") {
+            return evaluate_one(inner, input, variables);
+        }
+    }
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|i| start + i)
+            .ok_or_else(|| format!("Unclosed placeholder in template: {}", template))?;
+        rendered.push_str(&rest[..start]);
+        match evaluate_one(&rest[start + 2..end], input, variables)? {
+            Value::String(s) => rendered.push_str(&s),
+            other => rendered.push_str(&other.to_string()),
+        }
+        rest = &rest[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(Value::String(rendered))
+}
+
+/// Render every string inside a JSON value as a template
+pub fn render_value(
+    value: &Value,
+    input: &Value,
+    variables: &HashMap<String, Value>,
+) -> Result<Value, String> {
+    match value {
+        Value::String(s) => render(s, input, variables),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| render_value(item, input, variables))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), render_value(v, input, variables)?)))
+            .collect::<Result<Map<_, _>, String>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+/// Expressions inside the `{{ ... }}` placeholders of a template
+pub fn placeholders(template: &str) -> Result<Vec<&str>, String> {
+    let mut found = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|i| start + i)
+            .ok_or_else(|| format!("Unclosed placeholder in template: {}", template))?;
+        found.push(&rest[start + 2..end]);
+        rest = &rest[end + 2..];
+    }
+    Ok(found)
+}
+
+/// Variables an expression reads, each with the key it is indexed by right
+/// away if any, like `fetch` in `$nodes.fetch`
+pub fn variable_references(expression: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    parse(expression)?;
+    let tokens = tokenize(expression)?;
+    let references = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, token)| {
+            let Token::Var(name) = token else {
+                return None;
+            };
+            let key = match (tokens.get(i + 1), tokens.get(i + 2)) {
+                (Some(Token::Dot), Some(Token::Ident(key) | Token::Str(key))) => Some(key.clone()),
+                _ => None,
+            };
+            Some((name.clone(), key))
+        })
+        .collect();
+    Ok(references)
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Ident(String),
+    Var(String),
+    Str(String),
+    Num(f64),
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Pipe,
+    Comma,
+    Colon,
+    Semicolon,
+    Op(&'static str),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let pair: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+
+        if let Some(op) = ["//", "==", "!=", "<=", ">="].into_iter().find(|op| *op == pair) {
+            tokens.push(Token::Op(op));
+            i += 2;
+            continue;
+        }
+
+        let single = match c {
+            '.' => Some(Token::Dot),
+            '[' => Some(Token::LBracket),
+            ']' => Some(Token::RBracket),
+            '{' => Some(Token::LBrace),
+            '}' => Some(Token::RBrace),
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            ',' => Some(Token::Comma),
+            ':' => Some(Token::Colon),
+            ';' => Some(Token::Semicolon),
+            '|' => Some(Token::Pipe),
+            '<' => Some(Token::Op("<")),
+            '>' => Some(Token::Op(">")),
+            '+' => Some(Token::Op("+")),
+            '-' => Some(Token::Op("-")),
+            '*' => Some(Token::Op("*")),
+            '/' => Some(Token::Op("/")),
+            _ => None,
+        };
+        if let Some(token) = single {
+            tokens.push(token);
+            i += 1;
+            continue;
+        }
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '"' => {
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("Unterminated string in expression".to_string()),
+                        Some('"') => break,
+                        Some('\\') => {
+                            let escaped = chars.get(i + 1).copied().unwrap_or('\\');
+                            s.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => other,
+                            });
+                            i += 2;
+                        }
+                        Some(&ch) => {
+                            s.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Str(s));
+                i += 1;
+            }
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number in expression: {}", text))?;
+                tokens.push(Token::Num(number));
+            }
+            '$' => {
+                let start = i + 1;
+                i += 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                if i == start {
+                    return Err("Expected a variable name after '$'".to_string());
+                }
+                tokens.push(Token::Var(chars[start..i].iter().collect()));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("Unexpected character in expression: '{}'", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Identity,
+    Literal(Value),
+    Var(String),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Iterate(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    Call(String, Vec<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+fn parse(source: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Ok(Expr::Identity);
+    }
+    let expr = parser.pipe()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("Unexpected {:?} in expression", token)),
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(format!("Expected {:?} in expression", token))
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Expr, String> {
+        let mut left = self.comma()?;
+        while self.eat(&Token::Pipe) {
+            left = Expr::Pipe(Box::new(left), Box::new(self.comma()?));
+        }
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Expr, String> {
+        let mut left = self.alternative()?;
+        while self.eat(&Token::Comma) {
+            left = Expr::Comma(Box::new(left), Box::new(self.alternative()?));
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Expr, String> {
+        let left = self.or()?;
+        if self.eat(&Token::Op("//")) {
+            return Ok(Expr::Alternative(Box::new(left), Box::new(self.alternative()?)));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat(&Token::Ident("or".to_string())) {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.eat(&Token::Ident("and".to_string())) {
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if matches!(op, "==" | "!=" | "<" | "<=" | ">" | ">=") {
+                self.pos += 1;
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(self.additive()?)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplicative()?;
+        while let Some(Token::Op(op @ ("+" | "-"))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
+        }
+        Ok(left)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.postfix()?;
+        while let Some(Token::Op(op @ ("*" | "/"))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.postfix()?));
+        }
+        Ok(left)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.pos += 1;
+                    expr = self.suffix_after_dot(expr)?;
+                }
+                Some(Token::LBracket) => {
+                    self.pos += 1;
+                    expr = self.bracket(expr)?;
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// Parse what follows a `.`: a field name, a string key or a bracket
+    fn suffix_after_dot(&mut self, base: Expr) -> Result<Expr, String> {
+        match self.peek().cloned() {
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Ok(Expr::Field(Box::new(base), name))
+            }
+            Some(Token::Str(name)) => {
+                self.pos += 1;
+                Ok(Expr::Field(Box::new(base), name))
+            }
+            Some(Token::LBracket) => {
+                self.pos += 1;
+                self.bracket(base)
+            }
+            _ => Err("Expected a field name after '.'".to_string()),
+        }
+    }
+
+    /// Parse `[]` or `[expr]` after the opening bracket
+    fn bracket(&mut self, base: Expr) -> Result<Expr, String> {
+        if self.eat(&Token::RBracket) {
+            return Ok(Expr::Iterate(Box::new(base)));
+        }
+        let index = self.pipe()?;
+        self.expect(Token::RBracket)?;
+        Ok(Expr::Index(Box::new(base), Box::new(index)))
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Dot) => match self.peek() {
+                Some(Token::Ident(_)) | Some(Token::Str(_)) | Some(Token::LBracket) => {
+                    self.suffix_after_dot(Expr::Identity)
+                }
+                _ => Ok(Expr::Identity),
+            },
+            Some(Token::Num(n)) => Ok(Expr::Literal(number(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Var(name)) => Ok(Expr::Var(name)),
+            Some(Token::Op("-")) => {
+                let operand = self.postfix()?;
+                Ok(Expr::Binary("-", Box::new(Expr::Literal(number(0.0))), Box::new(operand)))
+            }
+            Some(Token::LParen) => {
+                let inner = self.pipe()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::LBracket) => {
+                if self.eat(&Token::RBracket) {
+                    return Ok(Expr::Array(None));
+                }
+                let inner = self.pipe()?;
+                self.expect(Token::RBracket)?;
+                Ok(Expr::Array(Some(Box::new(inner))))
+            }
+            Some(Token::LBrace) => self.object(),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ => {
+                    let mut args = Vec::new();
+                    if self.eat(&Token::LParen) {
+                        loop {
+                            args.push(self.pipe()?);
+                            if !self.eat(&Token::Semicolon) {
+                                break;
+                            }
+                        }
+                        self.expect(Token::RParen)?;
+                    }
+                    Ok(Expr::Call(name, args))
+                }
+            },
+            Some(token) => Err(format!("Unexpected {:?} in expression", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+
+    fn object(&mut self) -> Result<Expr, String> {
+        let mut entries = Vec::new();
+        if self.eat(&Token::RBrace) {
+            return Ok(Expr::Object(entries));
+        }
+        loop {
+            let (key, shorthand) = match self.next() {
+                Some(Token::Ident(name)) | Some(Token::Str(name)) => (
+                    Expr::Literal(Value::String(name.clone())),
+                    Expr::Field(Box::new(Expr::Identity), name),
+                ),
+                Some(Token::Var(name)) => (
+                    Expr::Literal(Value::String(name.clone())),
+                    Expr::Var(name),
+                ),
+                Some(Token::LParen) => {
+                    let key = self.pipe()?;
+                    self.expect(Token::RParen)?;
+                    (key, Expr::Literal(Value::Null))
+                }
+                _ => return Err("Expected an object key in expression".to_string()),
+            };
+            let value = if self.eat(&Token::Colon) {
+                // Values may pipe but not use `,`, which separates entries
+                let mut value = self.alternative()?;
+                while self.eat(&Token::Pipe) {
+                    value = Expr::Pipe(Box::new(value), Box::new(self.alternative()?));
+                }
+                value
+            } else {
+                shorthand
+            };
+            entries.push((key, value));
+            if self.eat(&Token::RBrace) {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect(Token::Comma)?;
+        }
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+fn eval(expr: &Expr, input: &Value, vars: &HashMap<String, Value>) -> Result<Vec<Value>, String> {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Literal(value) => Ok(vec![value.clone()]),
+        Expr::Var(name) => vars
+            .get(name)
+            .cloned()
+            .map(|v| vec![v])
+            .ok_or_else(|| format!("${} is not defined", name)),
+        Expr::Field(base, name) => eval(base, input, vars)?
+            .iter()
+            .map(|value| index(value, &Value::String(name.clone())))
+            .collect(),
+        Expr::Index(base, idx) => {
+            let keys = eval(idx, input, vars)?;
+            let mut out = Vec::new();
+            for value in eval(base, input, vars)? {
+                for key in &keys {
+                    out.push(index(&value, key)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::Iterate(base) => {
+            let mut out = Vec::new();
+            for value in eval(base, input, vars)? {
+                match value {
+                    Value::Array(items) => out.extend(items),
+                    Value::Object(map) => out.extend(map.into_iter().map(|(_, v)| v)),
+                    other => return Err(format!("Cannot iterate over {}", type_name(&other))),
+                }
+            }
+            Ok(out)
+        }
+        Expr::Pipe(left, right) => {
+            let mut out = Vec::new();
+            for value in eval(left, input, vars)? {
+                out.extend(eval(right, &value, vars)?);
+            }
+            Ok(out)
+        }
+        Expr::Comma(left, right) => {
+            let mut out = eval(left, input, vars)?;
+            out.extend(eval(right, input, vars)?);
+            Ok(out)
+        }
+        Expr::Alternative(left, right) => {
+            let values: Vec<Value> = eval(left, input, vars)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect();
+            if values.is_empty() {
+                eval(right, input, vars)
+            } else {
+                Ok(values)
+            }
+        }
+        Expr::Or(left, right) => {
+            let mut out = Vec::new();
+            for l in eval(left, input, vars)? {
+                if is_truthy(&l) {
+                    out.push(Value::Bool(true));
+                } else {
+                    for r in eval(right, input, vars)? {
+                        out.push(Value::Bool(is_truthy(&r)));
+                    }
+                }
+            }
+            Ok(out)
+        }
+        Expr::And(left, right) => {
+            let mut out = Vec::new();
+            for l in eval(left, input, vars)? {
+                if !is_truthy(&l) {
+                    out.push(Value::Bool(false));
+                } else {
+                    for r in eval(right, input, vars)? {
+                        out.push(Value::Bool(is_truthy(&r)));
+                    }
+                }
+            }
+            Ok(out)
+        }
+        Expr::Binary(op, left, right) => {
+            let rights = eval(right, input, vars)?;
+            let mut out = Vec::new();
+            for l in eval(left, input, vars)? {
+                for r in &rights {
+                    out.push(binary(op, &l, r)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::Array(inner) => Ok(vec![Value::Array(match inner {
+            Some(inner) => eval(inner, input, vars)?,
+            None => Vec::new(),
+        })]),
+        Expr::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key_expr, value_expr) in entries {
+                let keys = eval(key_expr, input, vars)?;
+                let values = eval(value_expr, input, vars)?;
+                let mut next = Vec::new();
+                for object in &objects {
+                    for key in &keys {
+                        let key = match key {
+                            Value::String(s) => s.clone(),
+                            other => return Err(format!("Object keys must be strings, got {}", type_name(other))),
+                        };
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Expr::Call(name, args) => call(name, args, input, vars),
+    }
+}
+
+fn call(name: &str, args: &[Expr], input: &Value, vars: &HashMap<String, Value>) -> Result<Vec<Value>, String> {
+    let arg = |i: usize| {
+        args.get(i)
+            .ok_or_else(|| format!("{} expects {} argument(s)", name, i + 1))
+    };
+    let single = |value: Value| Ok(vec![value]);
+
+    match name {
+        "empty" => Ok(Vec::new()),
+        "not" => single(Value::Bool(!is_truthy(input))),
+        "type" => single(Value::String(type_name(input).to_string())),
+        "length" => single(match input {
+            Value::Null => number(0.0),
+            Value::Bool(_) => return Err("boolean has no length".to_string()),
+            Value::Number(n) => number(n.as_f64().unwrap_or(0.0).abs()),
+            Value::String(s) => number(s.chars().count() as f64),
+            Value::Array(items) => number(items.len() as f64),
+            Value::Object(map) => number(map.len() as f64),
+        }),
+        "keys" => match input {
+            Value::Object(map) => {
+                let mut keys: Vec<String> = map.keys().cloned().collect();
+                keys.sort();
+                single(Value::Array(keys.into_iter().map(Value::String).collect()))
+            }
+            Value::Array(items) => single(Value::Array((0..items.len()).map(|i| number(i as f64)).collect())),
+            other => Err(format!("{} has no keys", type_name(other))),
+        },
+        "values" => eval(&Expr::Array(Some(Box::new(Expr::Iterate(Box::new(Expr::Identity))))), input, vars),
+        "map" => {
+            let body = Expr::Pipe(Box::new(Expr::Iterate(Box::new(Expr::Identity))), Box::new(arg(0)?.clone()));
+            single(Value::Array(eval(&body, input, vars)?))
+        }
+        "select" => {
+            let keep = eval(arg(0)?, input, vars)?.iter().any(is_truthy);
+            Ok(if keep { vec![input.clone()] } else { Vec::new() })
+        }
+        "has" => {
+            let mut out = Vec::new();
+            for key in eval(arg(0)?, input, vars)? {
+                out.push(Value::Bool(match (input, &key) {
+                    (Value::Object(map), Value::String(k)) => map.contains_key(k),
+                    (Value::Array(items), Value::Number(n)) => {
+                        n.as_f64().map(|i| i >= 0.0 && (i as usize) < items.len()).unwrap_or(false)
+                    }
+                    _ => return Err(format!("Cannot check whether {} has a {} key", type_name(input), type_name(&key))),
+                }));
+            }
+            Ok(out)
+        }
+        "first" | "last" => match input {
+            Value::Array(items) => single(
+                if name == "first" { items.first() } else { items.last() }
+                    .cloned()
+                    .unwrap_or(Value::Null),
+            ),
+            other => Err(format!("Cannot take {} of {}", name, type_name(other))),
+        },
+        "add" => match input {
+            Value::Array(items) => {
+                let mut total = Value::Null;
+                for item in items {
+                    total = binary("+", &total, item)?;
+                }
+                single(total)
+            }
+            other => Err(format!("Cannot add the items of {}", type_name(other))),
+        },
+        "join" => {
+            let separator = match eval(arg(0)?, input, vars)?.into_iter().next() {
+                Some(Value::String(s)) => s,
+                _ => return Err("join expects a string separator".to_string()),
+            };
+            match input {
+                Value::Array(items) => single(Value::String(
+                    items
+                        .iter()
+                        .map(|item| match item {
+                            Value::String(s) => s.clone(),
+                            Value::Null => String::new(),
+                            other => other.to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&separator),
+                )),
+                other => Err(format!("Cannot join {}", type_name(other))),
+            }
+        }
+        "sort" => match input {
+            Value::Array(items) => {
+                let mut items = items.clone();
+                items.sort_by(compare);
+                single(Value::Array(items))
+            }
+            other => Err(format!("Cannot sort {}", type_name(other))),
+        },
+        "reverse" => match input {
+            Value::Array(items) => single(Value::Array(items.iter().rev().cloned().collect())),
+            Value::String(s) => single(Value::String(s.chars().rev().collect())),
+            other => Err(format!("Cannot reverse {}", type_name(other))),
+        },
+        "tostring" => single(match input {
+            Value::String(s) => Value::String(s.clone()),
+            other => Value::String(other.to_string()),
+        }),
+        "tonumber" => match input {
+            Value::Number(_) => single(input.clone()),
+            Value::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .map(|n| vec![number(n)])
+                .map_err(|_| format!("Cannot parse '{}' as a number", s)),
+            other => Err(format!("Cannot convert {} to a number", type_name(other))),
+        },
+        _ => Err(format!("Unknown function: {}", name)),
+    }
+}
+
+fn index(value: &Value, key: &Value) -> Result<Value, String> {
+    match (value, key) {
+        (Value::Null, _) => Ok(Value::Null),
+        (Value::Object(map), Value::String(k)) => Ok(map.get(k).cloned().unwrap_or(Value::Null)),
+        (Value::Array(items), Value::Number(n)) => {
+            let i = n.as_f64().unwrap_or(0.0) as i64;
+            let i = if i < 0 { items.len() as i64 + i } else { i };
+            Ok(usize::try_from(i)
+                .ok()
+                .and_then(|i| items.get(i))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        _ => Err(format!("Cannot index {} with {}", type_name(value), key)),
+    }
+}
+
+fn binary(op: &str, left: &Value, right: &Value) -> Result<Value, String> {
+    match op {
+        "==" => return Ok(Value::Bool(compare(left, right) == Ordering::Equal)),
+        "!=" => return Ok(Value::Bool(compare(left, right) != Ordering::Equal)),
+        "<" => return Ok(Value::Bool(compare(left, right) == Ordering::Less)),
+        "<=" => return Ok(Value::Bool(compare(left, right) != Ordering::Greater)),
+        ">" => return Ok(Value::Bool(compare(left, right) == Ordering::Greater)),
+        ">=" => return Ok(Value::Bool(compare(left, right) != Ordering::Less)),
+        _ => {}
+    }
+
+    match (op, left, right) {
+        ("+", Value::Null, other) | ("+", other, Value::Null) => Ok(other.clone()),
+        ("+", Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+        ("+", Value::Array(a), Value::Array(b)) => Ok(Value::Array(a.iter().chain(b).cloned().collect())),
+        ("+", Value::Object(a), Value::Object(b)) => {
+            let mut merged = a.clone();
+            merged.extend(b.clone());
+            Ok(Value::Object(merged))
+        }
+        ("-", Value::Array(a), Value::Array(b)) => {
+            Ok(Value::Array(a.iter().filter(|item| !b.contains(item)).cloned().collect()))
+        }
+        (_, Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            match op {
+                "+" => Ok(number(a + b)),
+                "-" => Ok(number(a - b)),
+                "*" => Ok(number(a * b)),
+                "/" if b == 0.0 => Err("Division by zero".to_string()),
+                "/" => Ok(number(a / b)),
+                _ => Err(format!("Unknown operator: {}", op)),
+            }
+        }
+        _ => Err(format!(
+            "Cannot apply {} to {} and {}",
+            op,
+            type_name(left),
+            type_name(right)
+        )),
+    }
+}
+
+/// Order values the way jq does: null < false < true < numbers < strings < arrays < objects
+fn compare(left: &Value, right: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or(0.0)
+            .partial_cmp(&b.as_f64().unwrap_or(0.0))
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| compare(x, y))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            if a == b {
+                Ordering::Equal
+            } else {
+                a.len().cmp(&b.len()).then_with(|| left.to_string().cmp(&right.to_string()))
+            }
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+/// Build a JSON number, keeping whole values as integers
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::Number((n as i64).into())
+    } else {
+        Number::from_f64(n).map(Value::Number).unwrap_or(Value::Null)
+    }
+}
+
+/// jq name of a value's type
+pub fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(expression: &str, input: Value) -> Vec<Value> {
+        evaluate(expression, &input, &HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn test_paths_and_iteration() {
+        let input = json!({ "user": { "name": "Ada", "tags": ["a", "b"] } });
+        assert_eq!(run(".user.name", input.clone()), vec![json!("Ada")]);
+        assert_eq!(run(".user.tags[-1]", input.clone()), vec![json!("b")]);
+        assert_eq!(run(".user.tags[]", input.clone()), vec![json!("a"), json!("b")]);
+        assert_eq!(run(".missing.field", input), vec![Value::Null]);
+    }
+
+    #[test]
+    fn test_construction_and_builtins() {
+        let input = json!({ "items": [{ "n": 3, "ok": true }, { "n": 1, "ok": false }, { "n": 2, "ok": true }] });
+        assert_eq!(
+            run("{total: (.items | map(.n) | add), ok: [.items[] | select(.ok) | .n] | sort}", input),
+            vec![json!({ "total": 6, "ok": [2, 3] })]
+        );
+        assert_eq!(run(".name // \"anonymous\"", json!({})), vec![json!("anonymous")]);
+        assert_eq!(run("[.[] | tostring] | join(\"-\")", json!([1, 2])), vec![json!("1-2")]);
+    }
+
+    #[test]
+    fn test_conditions_and_variables() {
+        let mut vars = HashMap::new();
+        vars.insert("limit".to_string(), json!(10));
+        let input = json!({ "status": 200, "count": 12 });
+        let result = evaluate_one(".status == 200 and .count > $limit", &input, &vars).unwrap();
+        assert!(is_truthy(&result));
+        assert!(evaluate("$missing", &input, &vars).is_err());
+
+        let references = variable_references("$nodes.fetch.count > $limit").unwrap();
+        assert_eq!(
+            references,
+            vec![("nodes".to_string(), Some("fetch".to_string())), ("limit".to_string(), None)]
+        );
+        assert_eq!(placeholders("{{ $a }} and {{ .b }}").unwrap(), vec![" $a ", " .b "]);
+        assert!(placeholders("{{ .open").is_err());
+    }
+
+    #[test]
+    fn test_render_templates() {
+        let input = json!({ "name": "Ada", "tags": ["x"] });
+        let vars = HashMap::new();
+        assert_eq!(render("Hello {{ .name }}!", &input, &vars).unwrap(), json!("Hello Ada!"));
+        assert_eq!(render("{{ .tags }}", &input, &vars).unwrap(), json!(["x"]));
+        assert_eq!(render("tags: {{ .tags }}", &input, &vars).unwrap(), json!("tags: [\"x\"]"));
+    }
+}
@@ -3,16 +3,23 @@
 //! Provides visual workflow creation, execution, and management:
 //! - Node-based workflow definition
 //! - Trigger system (schedule, webhook, file, voice)
-//! - Execution engine with error handling
+//! - Schedule triggers run as cron jobs of the job scheduler
+//! - Execution engine with typed ports, retries and streamed node records
+//! - jq-like expressions for conditions, transforms and templates
+//! - Typed workflow variables and keychain-bound secrets
 
 pub mod store;
 pub mod engine;
 pub mod nodes;
+pub mod expr;
 pub mod triggers;
+pub mod schedule;
+pub mod variables;
 pub mod commands;
 
 pub use store::{WorkflowStore, Workflow, WorkflowExecution};
-pub use engine::{WorkflowExecutor, ExecutionResult};
-pub use nodes::{NodeType, NodeData, NodeExecutor};
+pub use engine::{WorkflowExecutor, ExecutionResult, NodeExecutionRecord, RetryOptions};
+pub use nodes::{NodeType, NodeData, NodeExecutor, AgentRuntime, PortSpec, PortType};
 pub use triggers::{TriggerManager, Trigger, TriggerType};
 pub use commands::WorkflowState;
+pub use schedule::WorkflowJobRunner;
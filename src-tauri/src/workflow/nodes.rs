@@ -1,9 +1,13 @@
 //! Workflow Node Types and Executors
 //! 
-//! Defines node types and their execution logic.
+//! Defines node types, their typed ports and their execution logic.
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use super::expr;
 use super::store::WorkflowNode;
 
 /// Node types supported by the workflow engine
@@ -19,8 +23,79 @@ pub enum NodeType {
     Loop,
     /// Agent node - calls AI agent
     Agent,
+    /// LLM node - sends a prompt to the active provider
+    Llm,
+    /// Skill node - runs a saved skill
+    Skill,
+    /// Recipe node - runs a saved recipe
+    Recipe,
+    /// HTTP node - sends an HTTP request
+    Http,
+    /// Delay node - waits before passing its input on
+    Delay,
+    /// Transform node - reshapes data with a jq-like expression
+    Transform,
+    /// Join node - waits for parallel branches
+    Join,
+    /// Email node - sends its input over SMTP
+    SendEmail,
+    /// Notify node - posts its input to a Slack, Discord or generic webhook
+    NotifyChannel,
 }
 
+/// Type of the value carried by a port
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PortType {
+    Any,
+    String,
+    Number,
+    Boolean,
+    Object,
+    Array,
+}
+
+impl PortType {
+    /// Whether a value of this type may be connected to a port of type `target`
+    pub fn connects_to(self, target: PortType) -> bool {
+        self == PortType::Any || target == PortType::Any || self == target
+    }
+
+    /// Whether a runtime value fits this port
+    pub fn accepts(self, value: &serde_json::Value) -> bool {
+        match self {
+            PortType::Any => true,
+            PortType::String => value.is_string(),
+            PortType::Number => value.is_number(),
+            PortType::Boolean => value.is_boolean(),
+            PortType::Object => value.is_object(),
+            PortType::Array => value.is_array(),
+        }
+    }
+}
+
+/// A named input or output of a node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PortSpec {
+    pub name: String,
+    pub port_type: PortType,
+}
+
+impl PortSpec {
+    pub fn new(name: &str, port_type: PortType) -> Self {
+        Self {
+            name: name.to_string(),
+            port_type,
+        }
+    }
+}
+
+/// Default input port name
+pub const DEFAULT_INPUT: &str = "in";
+
+/// Default output port name
+pub const DEFAULT_OUTPUT: &str = "out";
+
 /// Node execution context
 #[derive(Debug, Clone)]
 pub struct NodeContext {
@@ -30,6 +105,21 @@ pub struct NodeContext {
     pub results: HashMap<String, serde_json::Value>,
 }
 
+impl NodeContext {
+    /// Variables visible to expressions: workflow variables plus `$nodes`,
+    /// the outputs of every node that has run so far
+    pub fn expression_variables(&self) -> HashMap<String, serde_json::Value> {
+        let mut variables = self.variables.clone();
+        variables.insert("nodes".to_string(), json!(self.results));
+        variables
+    }
+
+    /// Render `{{ ... }}` templates in a node setting against the node input
+    pub fn render(&self, value: &serde_json::Value) -> Result<serde_json::Value, String> {
+        expr::render_value(value, &self.input, &self.expression_variables())
+    }
+}
+
 /// Result of node execution
 #[derive(Debug, Clone)]
 pub enum NodeResult {
@@ -37,6 +127,8 @@ pub enum NodeResult {
     Success {
         output: serde_json::Value,
         next_node: Option<String>,
+        /// Output port the result leaves on; `None` means the default port
+        port: Option<String>,
     },
     /// Node execution failed
     Failure {
@@ -44,10 +136,39 @@ pub enum NodeResult {
     },
 }
 
+impl NodeResult {
+    /// Successful result on the default output port
+    pub fn output(output: serde_json::Value, node: &WorkflowNode) -> Self {
+        NodeResult::Success {
+            output,
+            next_node: next_of(node),
+            port: None,
+        }
+    }
+}
+
 /// Node executor trait
+#[async_trait::async_trait]
 pub trait NodeExecutor: Send + Sync {
     /// Execute a node
-    fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult;
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult;
+
+    /// Input ports accepted by this node type
+    fn inputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_INPUT, PortType::Any)]
+    }
+
+    /// Output ports produced by this node type
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::Any)]
+    }
+}
+
+/// Calls into the agent runtime used by LLM, skill and recipe nodes
+#[async_trait::async_trait]
+pub trait AgentRuntime: Send + Sync {
+    /// Send a JSON-RPC request and return its result
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String>;
 }
 
 /// Data associated with a node
@@ -61,34 +182,85 @@ pub struct NodeData {
     pub description: Option<String>,
 }
 
+fn next_of(node: &WorkflowNode) -> Option<String> {
+    node.data.get("next").and_then(|v| v.as_str()).map(String::from)
+}
+
+fn setting<'a>(node: &'a WorkflowNode, key: &str) -> Option<&'a serde_json::Value> {
+    node.data.get(key).filter(|v| !v.is_null())
+}
+
+/// Render a node setting, failing if it is missing
+fn required(node: &WorkflowNode, context: &NodeContext, key: &str) -> Result<serde_json::Value, String> {
+    let value = setting(node, key).ok_or_else(|| format!("Node {} is missing '{}'", node.id, key))?;
+    context.render(value)
+}
+
+/// Render a node setting that must be a non-empty string
+fn required_str(node: &WorkflowNode, context: &NodeContext, key: &str) -> Result<String, String> {
+    match required(node, context, key)? {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Ok(s),
+        serde_json::Value::String(_) | serde_json::Value::Null => {
+            Err(format!("Node {} has an empty '{}'", node.id, key))
+        }
+        other => Ok(other.to_string()),
+    }
+}
+
+/// Input rendered as text for prompts
+fn input_text(input: &serde_json::Value) -> String {
+    match input {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Unwrap a `{success, result, error}` response from the agent runtime
+pub(crate) fn runtime_result(response: serde_json::Value) -> Result<serde_json::Value, String> {
+    if response.get("success").and_then(|v| v.as_bool()) == Some(false) {
+        return Err(response
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Agent runtime call failed")
+            .to_string());
+    }
+    Ok(response.get("result").cloned().unwrap_or(response))
+}
+
+fn finish(result: Result<serde_json::Value, String>, node: &WorkflowNode) -> NodeResult {
+    match result {
+        Ok(output) => NodeResult::output(output, node),
+        Err(error) => NodeResult::Failure { error },
+    }
+}
+
 // Built-in node executors
 
 /// Trigger node executor
 pub struct TriggerExecutor;
 
+#[async_trait::async_trait]
 impl NodeExecutor for TriggerExecutor {
-    fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
-        // Get next node from config
-        let next = node.data.get("next").and_then(|v| v.as_str()).map(String::from);
-        
-        NodeResult::Success {
-            output: context.input.clone(),
-            next_node: next,
-        }
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        NodeResult::output(context.input.clone(), node)
+    }
+
+    fn inputs(&self) -> Vec<PortSpec> {
+        Vec::new()
     }
 }
 
 /// Action node executor
 pub struct ActionExecutor;
 
+#[async_trait::async_trait]
 impl NodeExecutor for ActionExecutor {
-    fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
         let action_type = node.data.get("action_type")
             .and_then(|v| v.as_str())
             .unwrap_or("unknown");
         
-        let next = node.data.get("next").and_then(|v| v.as_str()).map(String::from);
-        
         // Placeholder: In real implementation, would execute the actual action
         let output = serde_json::json!({
             "action": action_type,
@@ -96,18 +268,23 @@ impl NodeExecutor for ActionExecutor {
             "input": context.input
         });
         
-        NodeResult::Success {
-            output,
-            next_node: next,
-        }
+        NodeResult::output(output, node)
+    }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::Object)]
     }
 }
 
 /// Condition node executor
+///
+/// Evaluates `condition` against the node input and passes the input on
+/// through the `true` or `false` port.
 pub struct ConditionExecutor;
 
+#[async_trait::async_trait]
 impl NodeExecutor for ConditionExecutor {
-    fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
         // Get condition expression
         let condition = node.data.get("condition")
             .and_then(|v| v.as_str())
@@ -117,31 +294,46 @@ impl NodeExecutor for ConditionExecutor {
         let true_next = node.data.get("true_next").and_then(|v| v.as_str()).map(String::from);
         let false_next = node.data.get("false_next").and_then(|v| v.as_str()).map(String::from);
         
-        // Simple condition evaluation (placeholder)
-        let result = evaluate_condition(condition, &context.variables);
+        let result = match evaluate_condition(condition, context) {
+            Ok(result) => result,
+            Err(e) => {
+                return NodeResult::Failure {
+                    error: format!("Invalid condition '{}': {}", condition, e),
+                }
+            }
+        };
         
         let next = if result { true_next } else { false_next };
         
         NodeResult::Success {
-            output: serde_json::json!({ "condition_result": result }),
+            output: context.input.clone(),
             next_node: next,
+            port: Some(result.to_string()),
         }
     }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![
+            PortSpec::new("true", PortType::Any),
+            PortSpec::new("false", PortType::Any),
+        ]
+    }
 }
 
 /// Loop node executor
 pub struct LoopExecutor;
 
+#[async_trait::async_trait]
 impl NodeExecutor for LoopExecutor {
-    fn execute(&self, node: &WorkflowNode, _context: &NodeContext) -> NodeResult {
+    async fn execute(&self, node: &WorkflowNode, _context: &NodeContext) -> NodeResult {
         // Get loop configuration
         let items = node.data.get("items")
             .and_then(|v| v.as_array())
-            .map(|a| a.clone())
+            .cloned()
             .unwrap_or_default();
         
         let body_node = node.data.get("body").and_then(|v| v.as_str()).map(String::from);
-        let next = node.data.get("next").and_then(|v| v.as_str()).map(String::from);
+        let next = next_of(node);
         
         // Placeholder: In real implementation, would iterate through items
         let output = serde_json::json!({
@@ -152,44 +344,414 @@ impl NodeExecutor for LoopExecutor {
         NodeResult::Success {
             output,
             next_node: next.or(body_node),
+            port: None,
         }
     }
 }
 
 /// Agent node executor
+///
+/// Placeholder used when no agent runtime is attached; see `LlmExecutor`.
 pub struct AgentExecutor;
 
+#[async_trait::async_trait]
 impl NodeExecutor for AgentExecutor {
-    fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
         let prompt = node.data.get("prompt")
             .and_then(|v| v.as_str())
             .unwrap_or("");
         
-        let next = node.data.get("next").and_then(|v| v.as_str()).map(String::from);
-        
         // Placeholder: In real implementation, would call the AI agent
         let output = serde_json::json!({
             "response": format!("Agent response to: {}", prompt),
             "input": context.input
         });
         
-        NodeResult::Success {
-            output,
-            next_node: next,
+        NodeResult::output(output, node)
+    }
+}
+
+/// LLM prompt node executor
+///
+/// Renders `prompt` (the node input when unset) and sends it to `provider`
+/// or the active provider. Outputs the response text.
+pub struct LlmExecutor {
+    runtime: Arc<dyn AgentRuntime>,
+}
+
+impl LlmExecutor {
+    pub fn new(runtime: Arc<dyn AgentRuntime>) -> Self {
+        Self { runtime }
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for LlmExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let result = async {
+            let mut prompt = match setting(node, "prompt") {
+                Some(_) => required_str(node, context, "prompt")?,
+                None => input_text(&context.input),
+            };
+            if prompt.trim().is_empty() {
+                return Err(format!("Node {} has no prompt", node.id));
+            }
+            if let Some(system) = setting(node, "system") {
+                prompt = format!("{}\n\n{}", input_text(&context.render(system)?), prompt);
+            }
+            let provider = setting(node, "provider").and_then(|v| v.as_str());
+
+            let response = self
+                .runtime
+                .call("execute_prompt", json!({ "prompt": prompt, "provider": provider }))
+                .await?;
+            runtime_result(response)
+        }
+        .await;
+        finish(result, node)
+    }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::String)]
+    }
+}
+
+/// Skill call node executor
+///
+/// Runs `skill_id` with `input` (the node input when unset) and `variables`.
+pub struct SkillExecutor {
+    runtime: Arc<dyn AgentRuntime>,
+}
+
+impl SkillExecutor {
+    pub fn new(runtime: Arc<dyn AgentRuntime>) -> Self {
+        Self { runtime }
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for SkillExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let result = async {
+            let skill_id = required_str(node, context, "skill_id")?;
+            let input = match setting(node, "input") {
+                Some(input) => input_text(&context.render(input)?),
+                None => input_text(&context.input),
+            };
+            let variables = match setting(node, "variables") {
+                Some(variables) => context.render(variables)?,
+                None => serde_json::Value::Null,
+            };
+
+            let response = self
+                .runtime
+                .call(
+                    "execute_skill",
+                    json!({ "skillId": skill_id, "input": input, "variables": variables }),
+                )
+                .await?;
+            runtime_result(response)
         }
+        .await;
+        finish(result, node)
+    }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::String)]
     }
 }
 
-/// Evaluate a simple condition expression
-fn evaluate_condition(condition: &str, _variables: &HashMap<String, serde_json::Value>) -> bool {
-    // Placeholder: simple true/false evaluation
-    match condition {
-        "true" => true,
-        "false" => false,
-        _ => true, // Default to true
+/// Recipe call node executor
+///
+/// Runs `recipe_id` with `variables`, defaulting to the node input when it
+/// is an object.
+pub struct RecipeExecutor {
+    runtime: Arc<dyn AgentRuntime>,
+}
+
+impl RecipeExecutor {
+    pub fn new(runtime: Arc<dyn AgentRuntime>) -> Self {
+        Self { runtime }
     }
 }
 
+#[async_trait::async_trait]
+impl NodeExecutor for RecipeExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let result = async {
+            let recipe_id = required_str(node, context, "recipe_id")?;
+            let variables = match setting(node, "variables") {
+                Some(variables) => context.render(variables)?,
+                None if context.input.is_object() => context.input.clone(),
+                None => json!({}),
+            };
+            let steps = setting(node, "steps").cloned().unwrap_or_else(|| json!([]));
+
+            let response = self
+                .runtime
+                .call(
+                    "execute_recipe",
+                    json!({ "recipeId": recipe_id, "steps": steps, "variables": variables }),
+                )
+                .await?;
+            runtime_result(response)
+        }
+        .await;
+        finish(result, node)
+    }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::String)]
+    }
+}
+
+/// HTTP request node executor
+///
+/// Sends `method` (default GET) to `url` with optional `headers`, `query`
+/// and JSON `body`. Outputs `{status, headers, body}`, parsing the body as
+/// JSON when possible. Non-2xx responses fail unless `allow_error_status`.
+pub struct HttpExecutor {
+    client: reqwest::Client,
+}
+
+impl HttpExecutor {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Default for HttpExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for HttpExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let result = async {
+            let url = required_str(node, context, "url")?;
+            let method = setting(node, "method")
+                .and_then(|v| v.as_str())
+                .unwrap_or("GET")
+                .to_uppercase();
+            let method = reqwest::Method::from_bytes(method.as_bytes())
+                .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+
+            let mut request = self.client.request(method, &url);
+            if let Some(headers) = setting(node, "headers") {
+                if let serde_json::Value::Object(headers) = context.render(headers)? {
+                    for (name, value) in headers {
+                        request = request.header(name.as_str(), input_text(&value));
+                    }
+                }
+            }
+            if let Some(query) = setting(node, "query") {
+                if let serde_json::Value::Object(query) = context.render(query)? {
+                    let pairs: Vec<(String, String)> =
+                        query.into_iter().map(|(k, v)| (k, input_text(&v))).collect();
+                    request = request.query(&pairs);
+                }
+            }
+            if let Some(body) = setting(node, "body") {
+                request = match context.render(body)? {
+                    serde_json::Value::String(text) => request.body(text),
+                    body => request
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(body.to_string()),
+                };
+            }
+
+            let response = request.send().await.map_err(|e| format!("HTTP request failed: {}", e))?;
+            let status = response.status();
+            let headers: serde_json::Map<String, serde_json::Value> = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    value.to_str().ok().map(|v| (name.to_string(), json!(v)))
+                })
+                .collect();
+            let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+            let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+
+            let allow_error_status = setting(node, "allow_error_status")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            if !status.is_success() && !allow_error_status {
+                return Err(format!("HTTP {} from {}", status.as_u16(), url));
+            }
+
+            Ok(json!({ "status": status.as_u16(), "headers": headers, "body": body }))
+        }
+        .await;
+        finish(result, node)
+    }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::Object)]
+    }
+}
+
+/// Delay node executor
+///
+/// Waits `duration_ms` (or `seconds`) and passes its input on unchanged.
+pub struct DelayExecutor;
+
+#[async_trait::async_trait]
+impl NodeExecutor for DelayExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let duration = match (
+            setting(node, "duration_ms").and_then(|v| v.as_u64()),
+            setting(node, "seconds").and_then(|v| v.as_f64()),
+        ) {
+            (Some(ms), _) => Duration::from_millis(ms),
+            (None, Some(seconds)) if seconds >= 0.0 => Duration::from_secs_f64(seconds),
+            _ => {
+                return NodeResult::Failure {
+                    error: format!("Node {} needs 'duration_ms' or 'seconds'", node.id),
+                }
+            }
+        };
+
+        tokio::time::sleep(duration).await;
+        NodeResult::output(context.input.clone(), node)
+    }
+}
+
+/// Email node executor
+///
+/// Sends `body` (the node input when unset) to `to`, a string or an array,
+/// with `subject` and `format` (`markdown` or `html`), using the stored SMTP
+/// settings. Outputs `{to, subject}`.
+pub struct SendEmailExecutor;
+
+impl SendEmailExecutor {
+    fn message(node: &WorkflowNode, context: &NodeContext) -> Result<crate::integration::email::EmailMessage, String> {
+        let to = match required(node, context, "to")? {
+            serde_json::Value::Array(to) => to.iter().map(input_text).collect(),
+            to => vec![input_text(&to)],
+        };
+        let to: Vec<String> = to.into_iter().filter(|t| !t.trim().is_empty()).collect();
+        if to.is_empty() {
+            return Err(format!("Node {} has no recipients", node.id));
+        }
+        let body = match setting(node, "body") {
+            Some(body) => input_text(&context.render(body)?),
+            None => input_text(&context.input),
+        };
+        let format = match setting(node, "format") {
+            Some(format) => serde_json::from_value(format.clone())
+                .map_err(|_| format!("Invalid email format for node {}: {}", node.id, format))?,
+            None => Default::default(),
+        };
+        Ok(crate::integration::email::EmailMessage {
+            to,
+            subject: required_str(node, context, "subject")?,
+            body,
+            format,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for SendEmailExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let result = async {
+            let email = Self::message(node, context)?;
+            crate::integration::email::send_configured(&email).await?;
+            Ok(json!({ "to": email.to, "subject": email.subject }))
+        }
+        .await;
+        finish(result, node)
+    }
+
+    fn outputs(&self) -> Vec<PortSpec> {
+        vec![PortSpec::new(DEFAULT_OUTPUT, PortType::Object)]
+    }
+}
+
+/// Notification channel node executor
+///
+/// Posts `content` (the node input when unset) with an optional `title` and
+/// `level` to the channel `channel_id`. Passes its input on unchanged.
+pub struct NotifyChannelExecutor;
+
+impl NotifyChannelExecutor {
+    fn message(node: &WorkflowNode, context: &NodeContext) -> Result<crate::integration::channels::ChannelMessage, String> {
+        let content = match setting(node, "content") {
+            Some(content) => input_text(&context.render(content)?),
+            None => input_text(&context.input),
+        };
+        let title = match setting(node, "title") {
+            Some(title) => Some(input_text(&context.render(title)?)),
+            None => None,
+        };
+        let level = match setting(node, "level") {
+            Some(level) => serde_json::from_value(level.clone())
+                .map_err(|_| format!("Invalid message level for node {}: {}", node.id, level))?,
+            None => Default::default(),
+        };
+        Ok(crate::integration::channels::ChannelMessage { title, content, level })
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeExecutor for NotifyChannelExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let result = async {
+            let channel_id = required_str(node, context, "channel_id")?;
+            let message = Self::message(node, context)?;
+            let db_path = crate::db::default_db_path().ok_or("Could not find the app data directory")?;
+            crate::integration::channels::send_message(&db_path, &channel_id, &message).await?;
+            Ok(context.input.clone())
+        }
+        .await;
+        finish(result, node)
+    }
+}
+
+/// Data transform node executor
+///
+/// Applies the jq-like `expression` to the node input. Expressions that
+/// produce several values output them as an array.
+pub struct TransformExecutor;
+
+#[async_trait::async_trait]
+impl NodeExecutor for TransformExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        let expression = node.data.get("expression").and_then(|v| v.as_str()).unwrap_or(".");
+        let result = expr::evaluate(expression, &context.input, &context.expression_variables())
+            .map(|mut values| match values.len() {
+                0 => serde_json::Value::Null,
+                1 => values.remove(0),
+                _ => serde_json::Value::Array(values),
+            })
+            .map_err(|e| format!("Transform failed: {}", e));
+        finish(result, node)
+    }
+}
+
+/// Join node executor
+///
+/// The engine gathers the outputs of the joined branches into the node
+/// input (see `JoinMode`); the node passes that value on.
+pub struct JoinExecutor;
+
+#[async_trait::async_trait]
+impl NodeExecutor for JoinExecutor {
+    async fn execute(&self, node: &WorkflowNode, context: &NodeContext) -> NodeResult {
+        NodeResult::output(context.input.clone(), node)
+    }
+}
+
+/// Evaluate a condition expression against the node input
+fn evaluate_condition(condition: &str, context: &NodeContext) -> Result<bool, String> {
+    expr::evaluate_one(condition, &context.input, &context.expression_variables())
+        .map(|value| expr::is_truthy(&value))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,13 +775,13 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_trigger_executor() {
+    #[tokio::test]
+    async fn test_trigger_executor() {
         let executor = TriggerExecutor;
         let node = make_test_node("trigger", serde_json::json!({ "next": "next-node" }));
         let context = make_test_context();
         
-        let result = executor.execute(&node, &context);
+        let result = executor.execute(&node, &context).await;
         
         match result {
             NodeResult::Success { next_node, .. } => {
@@ -229,13 +791,13 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_action_executor() {
+    #[tokio::test]
+    async fn test_action_executor() {
         let executor = ActionExecutor;
         let node = make_test_node("action", serde_json::json!({ "action_type": "http_request" }));
         let context = make_test_context();
         
-        let result = executor.execute(&node, &context);
+        let result = executor.execute(&node, &context).await;
         
         match result {
             NodeResult::Success { output, .. } => {
@@ -245,8 +807,8 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_condition_executor() {
+    #[tokio::test]
+    async fn test_condition_executor() {
         let executor = ConditionExecutor;
         let node = make_test_node("condition", serde_json::json!({
             "condition": "true",
@@ -255,7 +817,7 @@ mod tests {
         }));
         let context = make_test_context();
         
-        let result = executor.execute(&node, &context);
+        let result = executor.execute(&node, &context).await;
         
         match result {
             NodeResult::Success { next_node, .. } => {
@@ -264,4 +826,101 @@ mod tests {
             NodeResult::Failure { .. } => panic!("Expected success"),
         }
     }
+
+    struct FakeRuntime;
+
+    #[async_trait::async_trait]
+    impl AgentRuntime for FakeRuntime {
+        async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String> {
+            match method {
+                "execute_prompt" => Ok(json!({
+                    "success": true,
+                    "result": format!("echo: {}", params["prompt"].as_str().unwrap_or(""))
+                })),
+                _ => Ok(json!({ "success": false, "error": format!("{} unavailable", method) })),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_condition_expression_selects_port() {
+        let executor = ConditionExecutor;
+        let node = make_test_node("condition", serde_json::json!({ "condition": ".test == \"other\"" }));
+        let context = make_test_context();
+
+        match executor.execute(&node, &context).await {
+            NodeResult::Success { port, output, .. } => {
+                assert_eq!(port.as_deref(), Some("false"));
+                assert_eq!(output, context.input);
+            }
+            NodeResult::Failure { error } => panic!("Expected success, got {}", error),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transform_executor() {
+        let executor = TransformExecutor;
+        let node = make_test_node("transform", serde_json::json!({
+            "expression": "{value: .test, upstream: $nodes.start.count}"
+        }));
+        let mut context = make_test_context();
+        context.results.insert("start".to_string(), json!({ "count": 2 }));
+
+        match executor.execute(&node, &context).await {
+            NodeResult::Success { output, .. } => {
+                assert_eq!(output, json!({ "value": "input", "upstream": 2 }));
+            }
+            NodeResult::Failure { error } => panic!("Expected success, got {}", error),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_and_skill_use_runtime() {
+        let runtime: Arc<dyn AgentRuntime> = Arc::new(FakeRuntime);
+        let context = make_test_context();
+
+        let llm = LlmExecutor::new(runtime.clone());
+        let node = make_test_node("llm", json!({ "prompt": "Summarize {{ .test }}" }));
+        match llm.execute(&node, &context).await {
+            NodeResult::Success { output, .. } => assert_eq!(output, json!("echo: Summarize input")),
+            NodeResult::Failure { error } => panic!("Expected success, got {}", error),
+        }
+
+        let skill = SkillExecutor::new(runtime);
+        let node = make_test_node("skill", json!({ "skill_id": "translate" }));
+        match skill.execute(&node, &context).await {
+            NodeResult::Failure { error } => assert_eq!(error, "execute_skill unavailable"),
+            NodeResult::Success { .. } => panic!("Expected failure"),
+        }
+    }
+
+    #[test]
+    fn test_send_email_and_notify_messages() {
+        let context = make_test_context();
+        let node = make_test_node("send_email", json!({
+            "to": ["a@example.com", ""],
+            "subject": "Result: {{ .test }}",
+            "format": "html"
+        }));
+        let email = SendEmailExecutor::message(&node, &context).unwrap();
+        assert_eq!(email.to, vec!["a@example.com"]);
+        assert_eq!(email.subject, "Result: input");
+        assert_eq!(email.body, r#"{"test":"input"}"#);
+
+        let node = make_test_node("send_email", json!({ "to": "", "subject": "Hi" }));
+        assert!(SendEmailExecutor::message(&node, &context).is_err());
+
+        let node = make_test_node("notify_channel", json!({ "channel_id": "c1", "title": "Got {{ .test }}" }));
+        let message = NotifyChannelExecutor::message(&node, &context).unwrap();
+        assert_eq!(message.title.as_deref(), Some("Got input"));
+        assert_eq!(message.content, r#"{"test":"input"}"#);
+    }
+
+    #[test]
+    fn test_port_types() {
+        assert!(PortType::String.connects_to(PortType::Any));
+        assert!(!PortType::String.connects_to(PortType::Object));
+        assert!(PortType::Object.accepts(&json!({})));
+        assert!(!PortType::Number.accepts(&json!("1")));
+    }
 }
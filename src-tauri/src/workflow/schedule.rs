@@ -0,0 +1,297 @@
+//! Scheduled Workflow Triggers
+//!
+//! A schedule trigger is backed by a `workflow` cron job, so its runs go
+//! through the `JobScheduler` and show up in `job_executions` as well as in
+//! the workflow's executions. The job is tagged with the workflow ID, follows
+//! the workflow's active flag and is deleted with the trigger.
+
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::Manager;
+
+use super::commands::{run_workflow, WorkflowState};
+use super::engine::ExecutionResult;
+use crate::scheduler::cron::CronExpression;
+use crate::scheduler::{JobConfig, JobScheduler, ScheduledJob};
+
+/// Trigger type recorded on executions started by a schedule trigger
+pub const SCHEDULE_TRIGGER: &str = "schedule";
+
+/// Cron job ID used for a schedule trigger
+pub fn job_id(trigger_id: &str) -> String {
+    format!("workflow-trigger-{}", trigger_id)
+}
+
+/// Runs workflows for `workflow` cron jobs
+///
+/// Holds the shared workflow state so the job scheduler can start
+/// executions without going through a command.
+#[derive(Clone)]
+pub struct WorkflowJobRunner {
+    state: Arc<WorkflowState>,
+    app: Option<tauri::AppHandle>,
+}
+
+impl WorkflowJobRunner {
+    /// Runner emitting node events through `app` when given
+    pub fn new(state: Arc<WorkflowState>, app: Option<tauri::AppHandle>) -> Self {
+        Self { state, app }
+    }
+
+    /// Execute a workflow for a scheduled job
+    pub async fn run(
+        &self,
+        workflow_id: &str,
+        input: serde_json::Value,
+    ) -> Result<ExecutionResult, String> {
+        run_workflow(&self.state, self.app.as_ref(), workflow_id, SCHEDULE_TRIGGER, input, true).await
+    }
+}
+
+/// Insert the cron job for a schedule trigger
+pub(crate) fn create_job(
+    conn: &Connection,
+    trigger_id: &str,
+    workflow_id: &str,
+    name: &str,
+    schedule: &str,
+    input: Option<serde_json::Value>,
+    enabled: bool,
+) -> Result<ScheduledJob, String> {
+    let cron = CronExpression::parse(schedule)?;
+
+    let mut params = HashMap::new();
+    params.insert("trigger_id".to_string(), serde_json::json!(trigger_id));
+    if let Some(input) = input {
+        params.insert("input".to_string(), input);
+    }
+    let config = JobConfig {
+        target: workflow_id.to_string(),
+        params,
+    };
+    let config_json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    let id = job_id(trigger_id);
+    let now = chrono::Utc::now();
+    let next_run = cron.next_after(now);
+
+    conn.execute(
+        "INSERT INTO cron_jobs (id, name, schedule, job_type, config, enabled, next_run, workflow_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, 'workflow', ?4, ?5, ?6, ?7, ?8, ?8)",
+        params![
+            id,
+            name,
+            schedule,
+            config_json,
+            enabled as i32,
+            next_run.map(|dt| dt.to_rfc3339()),
+            workflow_id,
+            now.to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ScheduledJob {
+        id,
+        name: name.to_string(),
+        schedule: schedule.to_string(),
+        job_type: crate::scheduler::JobType::Workflow,
+        config,
+        enabled,
+        last_run: None,
+        next_run,
+        created_at: now,
+        plugin_id: None,
+        allow_overlap: Default::default(),
+        priority: 0,
+        paused: false,
+        snoozed_until: None,
+    })
+}
+
+/// Enable or disable every job of a workflow, returning the updated jobs
+pub(crate) fn set_jobs_enabled(
+    conn: &Connection,
+    workflow_id: &str,
+    enabled: bool,
+) -> Result<Vec<ScheduledJob>, String> {
+    let now = chrono::Utc::now();
+    let mut jobs = list_jobs(conn, workflow_id)?;
+
+    for job in &mut jobs {
+        job.enabled = enabled;
+        job.next_run = if enabled {
+            CronExpression::parse(&job.schedule)?.next_after(now)
+        } else {
+            None
+        };
+        conn.execute(
+            "UPDATE cron_jobs SET enabled = ?1, next_run = ?2, updated_at = ?3 WHERE id = ?4",
+            params![enabled as i32, job.next_run.map(|dt| dt.to_rfc3339()), now.to_rfc3339(), job.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(jobs)
+}
+
+/// Jobs created by a workflow's schedule triggers
+pub(crate) fn list_jobs(conn: &Connection, workflow_id: &str) -> Result<Vec<ScheduledJob>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, schedule, job_type, config, enabled, last_run, next_run, created_at, plugin_id,
+                    allow_overlap, priority, paused, snoozed_until
+             FROM cron_jobs WHERE job_type = 'workflow' AND workflow_id = ?1 ORDER BY created_at",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let jobs = stmt
+        .query_map([workflow_id], crate::db::scheduled_job_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(jobs)
+}
+
+/// Delete the job of a schedule trigger along with its execution history
+pub(crate) fn delete_job(conn: &Connection, trigger_id: &str) -> Result<bool, String> {
+    let deleted = conn
+        .execute(
+            "DELETE FROM cron_jobs WHERE id = ?1 AND job_type = 'workflow'",
+            [job_id(trigger_id)],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(deleted > 0)
+}
+
+/// Disable jobs left by a previous session.
+///
+/// Workflows and their triggers live in memory, so jobs stored by an
+/// earlier run no longer have a workflow to start.
+pub(crate) fn disable_stale_jobs(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "UPDATE cron_jobs SET enabled = 0, next_run = NULL WHERE job_type = 'workflow' AND enabled = 1",
+        [],
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Create a schedule trigger's job and hand it to the running scheduler
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn register_job(
+    app: &tauri::AppHandle,
+    trigger_id: &str,
+    workflow_id: &str,
+    name: &str,
+    cron: &str,
+    timezone: &str,
+    input: Option<serde_json::Value>,
+    enabled: bool,
+) -> Result<String, String> {
+    if !timezone.eq_ignore_ascii_case("UTC") {
+        return Err(format!("Unsupported schedule timezone: {} (only UTC is supported)", timezone));
+    }
+
+    let job = {
+        let db = app.state::<crate::db::DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        create_job(&conn, trigger_id, workflow_id, name, cron, input, enabled)?
+    };
+    let id = job.id.clone();
+
+    if enabled {
+        let scheduler = app.state::<Arc<tokio::sync::Mutex<JobScheduler>>>();
+        let scheduler = scheduler.lock().await;
+        if let Err(e) = scheduler.add_job(job).await {
+            let db = app.state::<crate::db::DbState>();
+            let conn = db.conn.lock().map_err(|e| e.to_string())?;
+            delete_job(&conn, trigger_id)?;
+            return Err(e);
+        }
+    }
+
+    Ok(id)
+}
+
+/// Remove a schedule trigger's job from the scheduler and the database
+pub(crate) async fn unregister_job(app: &tauri::AppHandle, trigger_id: &str) -> Result<(), String> {
+    {
+        let scheduler = app.state::<Arc<tokio::sync::Mutex<JobScheduler>>>();
+        let scheduler = scheduler.lock().await;
+        scheduler.remove_job(&job_id(trigger_id)).await;
+    }
+
+    let db = app.state::<crate::db::DbState>();
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    delete_job(&conn, trigger_id)?;
+    Ok(())
+}
+
+/// Register or unregister a workflow's jobs with the scheduler when the
+/// workflow is activated or deactivated
+pub(crate) async fn sync_workflow_jobs(
+    app: &tauri::AppHandle,
+    workflow_id: &str,
+    active: bool,
+) -> Result<(), String> {
+    let jobs = {
+        let db = app.state::<crate::db::DbState>();
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        set_jobs_enabled(&conn, workflow_id, active)?
+    };
+
+    let scheduler = app.state::<Arc<tokio::sync::Mutex<JobScheduler>>>();
+    let scheduler = scheduler.lock().await;
+    for job in jobs {
+        scheduler.remove_job(&job.id).await;
+        if active {
+            scheduler.add_job(job).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    #[test]
+    fn test_create_and_toggle_jobs() {
+        let conn = test_conn();
+
+        let job = create_job(&conn, "t1", "wf-1", "Nightly", "0 2 * * *", None, true).unwrap();
+        assert_eq!(job.id, "workflow-trigger-t1");
+        assert_eq!(job.config.target, "wf-1");
+        assert!(job.next_run.is_some());
+        assert!(create_job(&conn, "t2", "wf-1", "Bad", "not a cron", None, true).is_err());
+
+        let jobs = set_jobs_enabled(&conn, "wf-1", false).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert!(!jobs[0].enabled && jobs[0].next_run.is_none());
+        let enabled: i32 = conn
+            .query_row("SELECT enabled FROM cron_jobs WHERE id = ?1", [&job.id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(enabled, 0);
+
+        let jobs = set_jobs_enabled(&conn, "wf-1", true).unwrap();
+        assert!(jobs[0].enabled && jobs[0].next_run.is_some());
+        assert!(list_jobs(&conn, "wf-2").unwrap().is_empty());
+
+        assert!(delete_job(&conn, "t1").unwrap());
+        assert!(list_jobs(&conn, "wf-1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_disable_stale_jobs() {
+        let conn = test_conn();
+        create_job(&conn, "t1", "wf-1", "Hourly", "0 * * * *", None, true).unwrap();
+
+        assert_eq!(disable_stale_jobs(&conn).unwrap(), 1);
+        assert!(list_jobs(&conn, "wf-1").unwrap().iter().all(|job| !job.enabled));
+    }
+}
@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use super::engine::{ExecutionResult, NodeExecutionRecord};
+use super::variables::VariableDefinition;
 
 /// Workflow definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +37,12 @@ pub struct WorkflowDefinition {
     pub nodes: HashMap<String, WorkflowNode>,
     /// Connections between nodes
     pub connections: Vec<NodeConnection>,
+    /// Nodes allowed to run at the same time; the engine default when unset
+    #[serde(default)]
+    pub max_parallel: Option<usize>,
+    /// Variables visible to node expressions as `$name`
+    #[serde(default)]
+    pub variables: Vec<VariableDefinition>,
 }
 
 /// A node in the workflow
@@ -93,6 +101,23 @@ pub struct WorkflowExecution {
     pub result: Option<serde_json::Value>,
     /// Error message if failed
     pub error: Option<String>,
+    /// Input the execution started with
+    #[serde(default)]
+    pub input: Option<serde_json::Value>,
+    /// Execution this one replays
+    #[serde(default)]
+    pub replay_of: Option<String>,
+}
+
+impl WorkflowExecution {
+    /// Final record of every node run, kept in the stored `ExecutionResult`
+    pub fn trace(&self) -> Vec<NodeExecutionRecord> {
+        self.result
+            .as_ref()
+            .and_then(|result| serde_json::from_value::<ExecutionResult>(result.clone()).ok())
+            .map(|result| result.records)
+            .unwrap_or_default()
+    }
 }
 
 /// Execution status
@@ -238,6 +263,8 @@ mod tests {
                 entry_point: "node-1".to_string(),
                 nodes: HashMap::new(),
                 connections: vec![],
+                max_parallel: None,
+                variables: Vec::new(),
             },
             version: 1,
             is_active: true,
@@ -315,6 +342,8 @@ mod tests {
             completed_at: None,
             result: None,
             error: None,
+            input: None,
+            replay_of: None,
         };
         
         store.create_execution(execution).unwrap();
@@ -130,6 +130,22 @@ impl TriggerManager {
         triggers.values().cloned().collect()
     }
     
+    /// Get an active trigger by ID
+    pub async fn get(&self, trigger_id: &str) -> Option<TriggerHandle> {
+        let triggers = self.active_triggers.read().await;
+        triggers.get(trigger_id).cloned()
+    }
+    
+    /// Get the active triggers of a workflow
+    pub async fn list_for_workflow(&self, workflow_id: &str) -> Vec<TriggerHandle> {
+        let triggers = self.active_triggers.read().await;
+        triggers
+            .values()
+            .filter(|h| h.workflow_id == workflow_id)
+            .cloned()
+            .collect()
+    }
+    
     /// Check if a trigger exists
     pub async fn exists(&self, trigger_id: &str) -> bool {
         let triggers = self.active_triggers.read().await;
@@ -0,0 +1,289 @@
+//! Workflow Variables
+//!
+//! Variables declared on a workflow definition are visible to the
+//! expressions and templates of its nodes as `$name`. Each has a type and
+//! either a default value or a secret binding: the keychain entry, such as
+//! `provider.openai`, that holds the value. Secrets are read from the
+//! `CredentialManager` when the workflow runs and only reach the nodes;
+//! node records, results and events show them as [`MASK`], so they are
+//! never kept in execution records (a replay gets the masked values).
+//!
+//! Before a workflow is enabled, [`check_references`] makes sure every
+//! `$name` is declared and every node named by `$nodes.<id>` or a `next`
+//! setting exists.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use super::expr;
+use super::nodes::PortType;
+use super::store::WorkflowDefinition;
+
+/// Shown instead of secret values
+pub const MASK: &str = "[secret]";
+
+/// Variable name taken by the outputs of earlier nodes
+const NODES_VARIABLE: &str = "nodes";
+
+/// Node settings holding an expression rather than a template
+const EXPRESSION_SETTINGS: [&str; 2] = ["expression", "condition"];
+
+/// Node settings naming another node
+const NODE_SETTINGS: [&str; 4] = ["next", "true_next", "false_next", "body"];
+
+/// Variable declared on a workflow
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VariableDefinition {
+    /// Name used as `$name`
+    pub name: String,
+    pub value_type: PortType,
+    pub description: Option<String>,
+    /// Value of a plain variable
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// Keychain entry holding the value of a secret
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// Variable values for one execution
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedVariables {
+    pub values: HashMap<String, Value>,
+    /// Secret values to mask in what the execution leaves behind
+    pub secrets: Vec<String>,
+}
+
+/// Check a set of variable definitions before storing them
+pub fn validate_definitions(variables: &[VariableDefinition]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for variable in variables {
+        let name = variable.name.as_str();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("Invalid variable name: '{}'", name));
+        }
+        if name == NODES_VARIABLE {
+            return Err(format!("${} is reserved for node outputs", NODES_VARIABLE));
+        }
+        if !names.insert(name) {
+            return Err(format!("Variable {} is declared twice", name));
+        }
+
+        match (&variable.secret, &variable.default) {
+            (Some(_), Some(_)) => return Err(format!("Secret {} cannot have a default value", name)),
+            (Some(key), None) if key.trim().is_empty() => {
+                return Err(format!("Secret {} needs a keychain entry", name))
+            }
+            (Some(_), None) if !variable.value_type.connects_to(PortType::String) => {
+                return Err(format!("Secret {} must be a string", name))
+            }
+            (None, None) => return Err(format!("Variable {} needs a default value", name)),
+            (None, Some(value)) if !variable.value_type.accepts(value) => {
+                return Err(format!(
+                    "Default of {} should be {:?}, got {}",
+                    name,
+                    variable.value_type,
+                    expr::type_name(value)
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Values of the variables for an execution, reading secrets with `read_secret`
+pub fn resolve(
+    variables: &[VariableDefinition],
+    read_secret: impl Fn(&str) -> Result<String, String>,
+) -> Result<ResolvedVariables, String> {
+    let mut resolved = ResolvedVariables::default();
+    for variable in variables {
+        let value = match &variable.secret {
+            Some(key) => {
+                let secret = read_secret(key).map_err(|e| format!("Secret {} is not available: {}", variable.name, e))?;
+                if !secret.is_empty() {
+                    resolved.secrets.push(secret.clone());
+                }
+                Value::String(secret)
+            }
+            None => variable.default.clone().unwrap_or(Value::Null),
+        };
+        resolved.values.insert(variable.name.clone(), value);
+    }
+    Ok(resolved)
+}
+
+/// Replace every occurrence of a secret inside the strings of `value`
+pub fn mask(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::String(text) => mask_text(text, secrets),
+        Value::Array(items) => items.iter_mut().for_each(|item| mask(item, secrets)),
+        Value::Object(map) => map.values_mut().for_each(|item| mask(item, secrets)),
+        _ => {}
+    }
+}
+
+/// Replace every occurrence of a secret in `text`
+pub fn mask_text(text: &mut String, secrets: &[String]) {
+    for secret in secrets {
+        if text.contains(secret.as_str()) {
+            *text = text.replace(secret.as_str(), MASK);
+        }
+    }
+}
+
+/// Problems that would make the workflow fail on a missing variable or
+/// node; empty when every reference resolves
+pub fn check_references(definition: &WorkflowDefinition) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut node_ids: Vec<&String> = definition.nodes.keys().collect();
+    node_ids.sort();
+    for node_id in node_ids {
+        let owner = format!("Node {}", node_id);
+        let Some(settings) = definition.nodes[node_id].data.as_object() else {
+            continue;
+        };
+        for (key, value) in settings {
+            if NODE_SETTINGS.contains(&key.as_str()) {
+                if let Some(target) = value.as_str().filter(|t| !definition.nodes.contains_key(*t)) {
+                    problems.push(format!("{} refers to missing node {}", owner, target));
+                }
+            } else if EXPRESSION_SETTINGS.contains(&key.as_str()) {
+                if let Some(expression) = value.as_str() {
+                    check_expression(definition, &owner, expression, &mut problems);
+                }
+            } else {
+                for_each_string(value, &mut |text| match expr::placeholders(text) {
+                    Ok(expressions) => {
+                        for expression in expressions {
+                            check_expression(definition, &owner, expression, &mut problems);
+                        }
+                    }
+                    Err(e) => problems.push(format!("{} has an invalid template: {}", owner, e)),
+                });
+            }
+        }
+    }
+    for connection in &definition.connections {
+        if let Some(condition) = connection.condition.as_deref().filter(|c| !c.trim().is_empty()) {
+            let owner = format!("Connection {} -> {}", connection.source, connection.target);
+            check_expression(definition, &owner, condition, &mut problems);
+        }
+    }
+    problems
+}
+
+fn check_expression(definition: &WorkflowDefinition, owner: &str, expression: &str, problems: &mut Vec<String>) {
+    let references = match expr::variable_references(expression) {
+        Ok(references) => references,
+        Err(e) => {
+            problems.push(format!("{} has an invalid expression '{}': {}", owner, expression.trim(), e));
+            return;
+        }
+    };
+    for (name, key) in references {
+        if name == NODES_VARIABLE {
+            if let Some(node_id) = key.filter(|id| !definition.nodes.contains_key(id)) {
+                problems.push(format!("{} refers to missing node {}", owner, node_id));
+            }
+        } else if !definition.variables.iter().any(|v| v.name == name) {
+            problems.push(format!("{} uses undeclared variable ${}", owner, name));
+        }
+    }
+}
+
+fn for_each_string(value: &Value, f: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(text) => f(text),
+        Value::Array(items) => items.iter().for_each(|item| for_each_string(item, f)),
+        Value::Object(map) => map.values().for_each(|item| for_each_string(item, f)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::store::{NodeConnection, NodePosition, WorkflowNode};
+    use serde_json::json;
+
+    fn variable(name: &str, value_type: PortType, default: Option<Value>, secret: Option<&str>) -> VariableDefinition {
+        VariableDefinition {
+            name: name.to_string(),
+            value_type,
+            description: None,
+            default,
+            secret: secret.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_validate_resolve_and_mask() {
+        let variables = vec![
+            variable("limit", PortType::Number, Some(json!(10)), None),
+            variable("token", PortType::String, None, Some("workflow.github")),
+        ];
+        validate_definitions(&variables).unwrap();
+        assert!(validate_definitions(&[variable("limit", PortType::Number, Some(json!("ten")), None)]).is_err());
+        assert!(validate_definitions(&[variable("nodes", PortType::Any, Some(json!(1)), None)]).is_err());
+        assert!(validate_definitions(&[variable("key", PortType::Number, None, Some("x"))]).is_err());
+
+        let resolved = resolve(&variables, |key| {
+            assert_eq!(key, "workflow.github");
+            Ok("ghp_secret".to_string())
+        })
+        .unwrap();
+        assert_eq!(resolved.values["limit"], json!(10));
+        assert_eq!(resolved.values["token"], json!("ghp_secret"));
+        let missing = resolve(&variables, |_| Err("no entry".to_string())).unwrap_err();
+        assert_eq!(missing, "Secret token is not available: no entry");
+
+        let mut output = json!({ "headers": ["Bearer ghp_secret"], "count": 3 });
+        mask(&mut output, &resolved.secrets);
+        assert_eq!(output, json!({ "headers": ["Bearer [secret]"], "count": 3 }));
+    }
+
+    #[test]
+    fn test_check_references() {
+        let node = |id: &str, data: Value| {
+            let node = WorkflowNode {
+                id: id.to_string(),
+                node_type: "transform".to_string(),
+                position: NodePosition { x: 0.0, y: 0.0 },
+                data,
+                label: None,
+            };
+            (id.to_string(), node)
+        };
+        let mut definition = WorkflowDefinition {
+            entry_point: "fetch".to_string(),
+            nodes: [
+                node("fetch", json!({ "url": "https://api.test/{{ $base }}?n={{ $limit }}", "next": "report" })),
+                node("report", json!({ "expression": "$nodes.fetch.items | length > $nodes.gone.count" })),
+            ]
+            .into_iter()
+            .collect(),
+            connections: vec![NodeConnection {
+                source: "fetch".to_string(),
+                source_output: String::new(),
+                target: "report".to_string(),
+                target_input: String::new(),
+                condition: Some("$limit > (".to_string()),
+            }],
+            max_parallel: None,
+            variables: vec![variable("limit", PortType::Number, Some(json!(5)), None)],
+        };
+
+        let problems = check_references(&definition);
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert_eq!(problems[0], "Node fetch uses undeclared variable $base");
+        assert_eq!(problems[1], "Node report refers to missing node gone");
+        assert!(problems[2].starts_with("Connection fetch -> report has an invalid expression"));
+
+        definition.nodes.get_mut("fetch").unwrap().data = json!({ "next": "cleanup" });
+        definition.nodes.get_mut("report").unwrap().data = json!({ "expression": "$nodes.fetch | length" });
+        definition.connections.clear();
+        assert_eq!(check_references(&definition), vec!["Node fetch refers to missing node cleanup"]);
+    }
+}
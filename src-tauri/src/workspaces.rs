@@ -0,0 +1,681 @@
+//! Workspaces
+//!
+//! A workspace groups what belongs to one project: permitted folders, the
+//! knowledge base searched by default, the preferred provider and model, and
+//! its conversations. One workspace may be active at a time; conversations
+//! saved while it is active join it, and conversation lists can be scoped to
+//! it. The active workspace is kept in `app_settings`, so every profile has
+//! its own.
+//!
+//! Chat defaults (system prompt, provider, model, temperature and a tool
+//! allowlist) apply to every sidecar chat of a conversation in the workspace;
+//! `ChatConfig::PRECEDENCE` lists how they combine with a request's own
+//! choices.
+
+use crate::conversations::{conversation_from_row, CONVERSATION_COLUMNS};
+use crate::db::{settings, Conversation, DbState, FolderPermission};
+use crate::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the id of the active workspace
+pub const ACTIVE_SETTING: &str = "active_workspace";
+
+const MAX_NAME_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Knowledge base searched by default in the workspace's chats
+    pub knowledge_base: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Ids of the folder permissions belonging to the workspace
+    pub folder_ids: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields of a workspace to create or update
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub knowledge_base: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub folder_ids: Vec<String>,
+}
+
+/// Chat defaults of a workspace; unset fields keep the app's behavior
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceDefaults {
+    pub system_prompt: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    /// Only these tools may run; every tool when unset
+    pub tool_allowlist: Option<Vec<String>>,
+}
+
+/// Defaults applied to a workspace's chats, with the rules combining them
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatConfig {
+    pub workspace_id: String,
+    pub defaults: WorkspaceDefaults,
+    pub precedence: Vec<String>,
+}
+
+impl ChatConfig {
+    /// How workspace defaults combine with a chat request
+    pub const PRECEDENCE: [&'static str; 5] = [
+        "systemPrompt: the conversation's own prompt replaces system messages sent with the request; otherwise a system message sent with the request wins; otherwise the workspace prompt is added first",
+        "provider: the provider chosen for the request, then the workspace provider, then the active provider",
+        "model: the workspace model, then the model configured for the provider",
+        "temperature: the workspace temperature, then the provider default",
+        "toolAllowlist: tools outside the allowlist are denied; tool policies still apply to allowed tools",
+    ];
+
+    fn new(workspace_id: String, defaults: WorkspaceDefaults) -> Self {
+        Self {
+            workspace_id,
+            defaults,
+            precedence: Self::PRECEDENCE.iter().map(|rule| rule.to_string()).collect(),
+        }
+    }
+
+    /// Apply the defaults to a chat request; returns the provider options
+    pub fn apply(&self, messages: &mut Vec<crate::Message>, provider: Option<String>) -> serde_json::Value {
+        if let Some(prompt) = &self.defaults.system_prompt {
+            if !messages.iter().any(|m| m.role == "system") {
+                messages.insert(
+                    0,
+                    crate::Message {
+                        role: "system".to_string(),
+                        content: prompt.clone(),
+                    },
+                );
+            }
+        }
+        serde_json::json!({
+            "provider": provider.or_else(|| self.defaults.provider.clone()),
+            "model": self.defaults.model,
+            "temperature": self.defaults.temperature,
+        })
+    }
+
+    /// Whether the allowlist lets `tool` run
+    pub fn allows_tool(&self, tool: &str) -> Result<(), String> {
+        match &self.defaults.tool_allowlist {
+            Some(allowed) if !allowed.iter().any(|t| t == tool) => {
+                Err(format!("Tool '{}' is not allowed in this workspace", tool))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Everything a workspace scopes
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceResources {
+    pub workspace: Workspace,
+    pub folders: Vec<FolderPermission>,
+    pub conversations: Vec<Conversation>,
+}
+
+fn clean(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Check an input against the other workspaces and the folder permissions
+fn validate(conn: &Connection, id: Option<&str>, input: WorkspaceInput) -> Result<WorkspaceInput, String> {
+    let name = input.name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err("Workspace name is required".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Workspace names are at most {} characters", MAX_NAME_LENGTH));
+    }
+    let taken: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM workspaces WHERE name = ?1 COLLATE NOCASE AND id != ?2)",
+            params![name, id.unwrap_or_default()],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if taken {
+        return Err(format!("A workspace named '{}' already exists", name));
+    }
+
+    let mut folder_ids = input.folder_ids;
+    folder_ids.sort();
+    folder_ids.dedup();
+    for folder_id in &folder_ids {
+        let exists: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM folder_permissions WHERE id = ?1)",
+                [folder_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !exists {
+            return Err(format!("Folder permission not found: {}", folder_id));
+        }
+    }
+
+    Ok(WorkspaceInput {
+        name,
+        description: clean(input.description),
+        knowledge_base: clean(input.knowledge_base),
+        provider: clean(input.provider),
+        model: clean(input.model),
+        folder_ids,
+    })
+}
+
+fn set_folders(conn: &Connection, id: &str, folder_ids: &[String]) -> Result<(), String> {
+    conn.execute("DELETE FROM workspace_folders WHERE workspace_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    for folder_id in folder_ids {
+        conn.execute(
+            "INSERT INTO workspace_folders (workspace_id, folder_id) VALUES (?1, ?2)",
+            [id, folder_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn create(conn: &Connection, input: WorkspaceInput) -> Result<Workspace, String> {
+    let input = validate(conn, None, input)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO workspaces (id, name, description, knowledge_base, provider, model, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+        params![id, input.name, input.description, input.knowledge_base, input.provider, input.model, now],
+    )
+    .map_err(|e| e.to_string())?;
+    set_folders(&tx, &id, &input.folder_ids)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    get(conn, &id)
+}
+
+pub fn update(conn: &Connection, id: &str, input: WorkspaceInput) -> Result<Workspace, String> {
+    get(conn, id)?;
+    let input = validate(conn, Some(id), input)?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE workspaces SET name = ?2, description = ?3, knowledge_base = ?4, provider = ?5, model = ?6,
+             updated_at = ?7
+         WHERE id = ?1",
+        params![
+            id,
+            input.name,
+            input.description,
+            input.knowledge_base,
+            input.provider,
+            input.model,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    set_folders(&tx, id, &input.folder_ids)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    get(conn, id)
+}
+
+fn folder_ids(conn: &Connection, id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT folder_id FROM workspace_folders WHERE workspace_id = ?1 ORDER BY folder_id")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+const COLUMNS: &str = "id, name, description, knowledge_base, provider, model, created_at, updated_at";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Workspace> {
+    Ok(Workspace {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        knowledge_base: row.get(3)?,
+        provider: row.get(4)?,
+        model: row.get(5)?,
+        folder_ids: Vec::new(),
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Workspace, String> {
+    let mut workspace = conn
+        .query_row(&format!("SELECT {} FROM workspaces WHERE id = ?1", COLUMNS), [id], from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Workspace not found: {}", id))?;
+    workspace.folder_ids = folder_ids(conn, id)?;
+    Ok(workspace)
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<Workspace>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM workspaces ORDER BY name COLLATE NOCASE", COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let mut workspaces = stmt
+        .query_map([], from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for workspace in &mut workspaces {
+        workspace.folder_ids = folder_ids(conn, &workspace.id)?;
+    }
+    Ok(workspaces)
+}
+
+/// Delete a workspace and its index; its folders and conversations are kept
+pub fn delete(conn: &Connection, id: &str) -> Result<(), String> {
+    get(conn, id)?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM workspace_folders WHERE workspace_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    tx.execute("UPDATE conversations SET workspace_id = NULL WHERE workspace_id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    crate::knowledge::index::clear(&tx, id)?;
+    tx.execute("DELETE FROM workspaces WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    if active_id(&tx)?.as_deref() == Some(id) {
+        settings::set(&tx, ACTIVE_SETTING, "")?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Id of the active workspace, if any
+pub fn active_id(conn: &Connection) -> Result<Option<String>, String> {
+    Ok(settings::get(conn, ACTIVE_SETTING)?.filter(|id| !id.is_empty()))
+}
+
+pub fn active(conn: &Connection) -> Result<Option<Workspace>, String> {
+    match active_id(conn)? {
+        Some(id) => get(conn, &id).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Activate a workspace, or none
+pub fn set_active(conn: &Connection, id: Option<&str>) -> Result<Option<Workspace>, String> {
+    let workspace = id.map(|id| get(conn, id)).transpose()?;
+    settings::set(conn, ACTIVE_SETTING, id.unwrap_or_default())?;
+    Ok(workspace)
+}
+
+/// Move a conversation into a workspace, or out of any
+pub fn assign_conversation(conn: &Connection, conversation_id: &str, workspace_id: Option<&str>) -> Result<(), String> {
+    if let Some(workspace_id) = workspace_id {
+        get(conn, workspace_id)?;
+    }
+    let updated = conn
+        .execute(
+            "UPDATE conversations SET workspace_id = ?2 WHERE id = ?1",
+            params![conversation_id, workspace_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Conversation not found: {}", conversation_id));
+    }
+    Ok(())
+}
+
+pub fn resources(conn: &Connection, id: &str) -> Result<WorkspaceResources, String> {
+    let workspace = get(conn, id)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.id, f.path, f.level, f.created_at FROM folder_permissions f
+             JOIN workspace_folders w ON w.folder_id = f.id
+             WHERE w.workspace_id = ?1 ORDER BY f.path",
+        )
+        .map_err(|e| e.to_string())?;
+    let folders = stmt
+        .query_map([id], |row| {
+            Ok(FolderPermission {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                level: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM conversations c WHERE c.workspace_id = ?1 ORDER BY c.updated_at DESC",
+            CONVERSATION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let conversations = stmt
+        .query_map([id], conversation_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(WorkspaceResources {
+        workspace,
+        folders,
+        conversations,
+    })
+}
+
+/// Chat defaults of a workspace
+pub fn get_defaults(conn: &Connection, id: &str) -> Result<ChatConfig, String> {
+    let defaults = conn
+        .query_row(
+            "SELECT system_prompt, provider, model, temperature, tool_allowlist FROM workspaces WHERE id = ?1",
+            [id],
+            |row| {
+                let allowlist: Option<String> = row.get(4)?;
+                Ok((
+                    WorkspaceDefaults {
+                        system_prompt: row.get(0)?,
+                        provider: row.get(1)?,
+                        model: row.get(2)?,
+                        temperature: row.get(3)?,
+                        tool_allowlist: None,
+                    },
+                    allowlist,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Workspace not found: {}", id))
+        .and_then(|(mut defaults, allowlist)| {
+            defaults.tool_allowlist = allowlist
+                .map(|json| serde_json::from_str(&json).map_err(|e| format!("Invalid tool allowlist: {}", e)))
+                .transpose()?;
+            Ok(defaults)
+        })?;
+    Ok(ChatConfig::new(id.to_string(), defaults))
+}
+
+pub fn set_defaults(conn: &Connection, id: &str, defaults: WorkspaceDefaults) -> Result<ChatConfig, String> {
+    get(conn, id)?;
+    if let Some(temperature) = defaults.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err("Temperature must be between 0 and 2".to_string());
+        }
+    }
+    let allowlist = defaults
+        .tool_allowlist
+        .map(|tools| {
+            let mut tools: Vec<String> = tools
+                .into_iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+            tools.sort();
+            tools.dedup();
+            serde_json::to_string(&tools).map_err(|e| e.to_string())
+        })
+        .transpose()?;
+
+    conn.execute(
+        "UPDATE workspaces SET system_prompt = ?2, provider = ?3, model = ?4, temperature = ?5, tool_allowlist = ?6,
+             updated_at = ?7
+         WHERE id = ?1",
+        params![
+            id,
+            clean(defaults.system_prompt),
+            clean(defaults.provider),
+            clean(defaults.model),
+            defaults.temperature,
+            allowlist,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    get_defaults(conn, id)
+}
+
+/// Chat config of the workspace a conversation belongs to, if any
+pub fn chat_config(conn: &Connection, conversation_id: Option<&str>) -> Result<Option<ChatConfig>, String> {
+    let Some(conversation_id) = conversation_id else {
+        return Ok(None);
+    };
+    let workspace_id: Option<String> = conn
+        .query_row(
+            "SELECT workspace_id FROM conversations WHERE id = ?1",
+            [conversation_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    workspace_id.map(|id| get_defaults(conn, &id)).transpose()
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn create_workspace(db: tauri::State<'_, DbState>, workspace: WorkspaceInput) -> Result<Workspace, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(create(&conn, workspace)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn update_workspace(
+    db: tauri::State<'_, DbState>,
+    id: String,
+    workspace: WorkspaceInput,
+) -> Result<Workspace, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(update(&conn, &id, workspace)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_workspaces(db: tauri::State<'_, DbState>) -> Result<Vec<Workspace>, AppError> {
+    let conn = db.read()?;
+    Ok(list(&conn)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn delete_workspace(db: tauri::State<'_, DbState>, id: String) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(delete(&conn, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_active_workspace(db: tauri::State<'_, DbState>) -> Result<Option<Workspace>, AppError> {
+    let conn = db.read()?;
+    Ok(active(&conn)?)
+}
+
+/// Activate a workspace; `None` leaves every workspace
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_active_workspace(db: tauri::State<'_, DbState>, id: Option<String>) -> Result<Option<Workspace>, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(set_active(&conn, id.as_deref())?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn assign_conversation_workspace(
+    db: tauri::State<'_, DbState>,
+    conversation_id: String,
+    workspace_id: Option<String>,
+) -> Result<(), AppError> {
+    let conn = db.conn.lock()?;
+    Ok(assign_conversation(&conn, &conversation_id, workspace_id.as_deref())?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn list_workspace_resources(db: tauri::State<'_, DbState>, id: String) -> Result<WorkspaceResources, AppError> {
+    let conn = db.read()?;
+    Ok(resources(&conn, &id)?)
+}
+
+/// Chat defaults of a workspace, with the precedence rules applying them
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn get_workspace_defaults(db: tauri::State<'_, DbState>, id: String) -> Result<ChatConfig, AppError> {
+    let conn = db.read()?;
+    Ok(get_defaults(&conn, &id)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err(Debug))]
+pub fn set_workspace_defaults(
+    db: tauri::State<'_, DbState>,
+    id: String,
+    defaults: WorkspaceDefaults,
+) -> Result<ChatConfig, AppError> {
+    let conn = db.conn.lock()?;
+    Ok(set_defaults(&conn, &id, defaults)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_conn;
+
+    fn setup() -> Connection {
+        let conn = test_conn();
+        conn.execute(
+            "INSERT INTO folder_permissions (id, path, level) VALUES ('f1', '/src/app', 'readwrite'), ('f2', '/docs', 'read')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO conversations (id, title) VALUES ('c1', 'Design'), ('c2', 'Other')", [])
+            .unwrap();
+        conn
+    }
+
+    fn input(name: &str, folder_ids: &[&str]) -> WorkspaceInput {
+        WorkspaceInput {
+            name: name.to_string(),
+            folder_ids: folder_ids.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_create_and_update() {
+        let conn = setup();
+        let app = create(
+            &conn,
+            WorkspaceInput {
+                provider: Some("anthropic".to_string()),
+                model: Some("  ".to_string()),
+                ..input("  App  ", &["f2", "f1", "f1"])
+            },
+        )
+        .unwrap();
+        assert_eq!(app.name, "App");
+        assert_eq!(app.folder_ids, vec!["f1", "f2"]);
+        assert_eq!(app.provider.as_deref(), Some("anthropic"));
+        assert_eq!(app.model, None);
+
+        assert!(create(&conn, input("app", &[])).is_err());
+        assert!(create(&conn, input("Docs", &["missing"])).is_err());
+        assert!(create(&conn, input(" ", &[])).is_err());
+
+        let docs = create(&conn, input("Docs", &["f2"])).unwrap();
+        let renamed = update(&conn, &app.id, input("App v2", &["f1"])).unwrap();
+        assert_eq!(renamed.folder_ids, vec!["f1"]);
+        assert_eq!(renamed.provider, None);
+        assert!(update(&conn, &docs.id, input("App v2", &[])).is_err());
+        let names: Vec<String> = list(&conn).unwrap().into_iter().map(|w| w.name).collect();
+        assert_eq!(names, vec!["App v2", "Docs"]);
+    }
+
+    #[test]
+    fn test_active_workspace_and_resources() {
+        let conn = setup();
+        let app = create(&conn, input("App", &["f1"])).unwrap();
+        assert!(active(&conn).unwrap().is_none());
+        assert!(set_active(&conn, Some("missing")).is_err());
+        assert_eq!(set_active(&conn, Some(&app.id)).unwrap().unwrap().id, app.id);
+
+        assign_conversation(&conn, "c1", Some(&app.id)).unwrap();
+        assert!(assign_conversation(&conn, "missing", Some(&app.id)).is_err());
+        let resources = resources(&conn, &app.id).unwrap();
+        assert_eq!(resources.folders.len(), 1);
+        assert_eq!(resources.folders[0].path, "/src/app");
+        let ids: Vec<&str> = resources.conversations.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["c1"]);
+        assert_eq!(resources.conversations[0].workspace_id.as_deref(), Some(app.id.as_str()));
+
+        // Deleting keeps the conversation and leaves the workspace
+        delete(&conn, &app.id).unwrap();
+        assert!(active(&conn).unwrap().is_none());
+        let workspace_id: Option<String> = conn
+            .query_row("SELECT workspace_id FROM conversations WHERE id = 'c1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(workspace_id, None);
+    }
+
+    #[test]
+    fn test_chat_defaults() {
+        let conn = setup();
+        let app = create(&conn, input("App", &[])).unwrap();
+        assign_conversation(&conn, "c1", Some(&app.id)).unwrap();
+        assert!(chat_config(&conn, Some("c2")).unwrap().is_none());
+        assert!(chat_config(&conn, None).unwrap().is_none());
+
+        let invalid = WorkspaceDefaults { temperature: Some(3.0), ..Default::default() };
+        assert!(set_defaults(&conn, &app.id, invalid).is_err());
+        let defaults = WorkspaceDefaults {
+            system_prompt: Some("Answer for the App codebase".to_string()),
+            provider: Some("ollama".to_string()),
+            model: Some("llama3".to_string()),
+            temperature: Some(0.2),
+            tool_allowlist: Some(vec!["read_file".to_string(), " search_in_folder ".to_string()]),
+        };
+        set_defaults(&conn, &app.id, defaults).unwrap();
+        let config = chat_config(&conn, Some("c1")).unwrap().unwrap();
+        assert_eq!(config.precedence.len(), ChatConfig::PRECEDENCE.len());
+        assert_eq!(get(&conn, &app.id).unwrap().model.as_deref(), Some("llama3"));
+
+        let mut messages = vec![crate::Message { role: "user".to_string(), content: "Hi".to_string() }];
+        let options = config.apply(&mut messages, None);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(options["provider"], "ollama");
+        assert_eq!(options["model"], "llama3");
+        // The request's own choices win
+        let options = config.apply(&mut messages, Some("anthropic".to_string()));
+        assert_eq!(messages.len(), 2);
+        assert_eq!(options["provider"], "anthropic");
+
+        assert!(config.allows_tool("search_in_folder").is_ok());
+        assert!(config.allows_tool("execute_shell_command").is_err());
+        set_defaults(&conn, &app.id, WorkspaceDefaults::default()).unwrap();
+        let config = chat_config(&conn, Some("c1")).unwrap().unwrap();
+        assert!(config.allows_tool("execute_shell_command").is_ok());
+    }
+}
@@ -85,6 +85,8 @@ mod tests {
                 entry_point: "node-1".to_string(),
                 nodes: HashMap::new(),
                 connections: vec![],
+                max_parallel: None,
+                variables: Vec::new(),
             },
             version: 1,
             is_active: true,
@@ -125,6 +127,8 @@ mod tests {
             completed_at: None,
             result: None,
             error: None,
+            input: None,
+            replay_of: None,
         };
 
         // Create
@@ -1,4 +1,5 @@
 import { useState, useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { Settings, FolderOpen, Clock, Menu, X, ShoppingBag, Database, FileText } from "lucide-react";
 import { ChatView } from "./components/chat/ChatView";
 import { ConversationList } from "./components/chat/ConversationList";
@@ -11,6 +12,9 @@ import { TemplateLibrary } from "./components/collaboration/TemplateLibrary";
 import { useChatStore } from "./stores/chatStore";
 import { useSettingsStore } from "./stores/settingsStore";
 import { useCollaborationStore } from "./stores/collaborationStore";
+import { useAppStatusStore } from "./stores/appStatusStore";
+import { useVoiceStore } from "./stores/voiceStore";
+import type { WakeWordEvent } from "./types/voice";
 import "./App.css";
 
 function App() {
@@ -44,6 +48,23 @@ function App() {
     loadFolderPermissions,
   ]);
 
+  // Follow the tray status, and start voice capture when the tray asks for it
+  useEffect(() => {
+    const { subscribe, unsubscribe } = useAppStatusStore.getState();
+    subscribe();
+    const unlistenWake = listen<WakeWordEvent>("voice://wake", (event) => {
+      // Wake word detections are handled by the voice store while it listens
+      if (event.payload.wake_word === "") {
+        setActiveView("chat");
+        useVoiceStore.setState({ isListening: true, voiceSessionId: event.payload.session_id });
+      }
+    });
+    return () => {
+      unsubscribe();
+      unlistenWake.then((fn) => fn());
+    };
+  }, []);
+
   return (
     <div className="flex h-screen bg-gray-50 dark:bg-gray-950 text-gray-900 dark:text-gray-100">
       {/* Sidebar */}
@@ -3,12 +3,27 @@
  */
 
 import React, { useState, useRef, useEffect } from 'react';
-import { Send, Loader2, Trash2 } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { Send, Loader2, Trash2, Columns, ScrollText } from 'lucide-react';
 import { useChatStore, useMessages, useActiveConversation } from '../../stores/chatStore';
+import type { Message } from '../../stores/chatStore';
+import { useSettingsStore } from '../../stores/settingsStore';
 import { useAgent } from '../../hooks/useAgent';
+import type { MessageFeedback } from '../../types/chat';
+import { ComparisonPicker } from './ComparisonPicker';
+import { SystemPromptEditor } from './SystemPromptEditor';
+import { GenerationParamsEditor } from './GenerationParamsEditor';
+import { FeedbackButtons } from './FeedbackButtons';
 
 export function ChatView() {
   const [input, setInput] = useState('');
+  // Provider for the next message; empty for the active provider
+  const [provider, setProvider] = useState('');
+  const [comparing, setComparing] = useState(false);
+  const [editingPrompt, setEditingPrompt] = useState(false);
+  const [compared, setCompared] = useState<string[]>([]);
+  // Feedback on this conversation's messages, by message id
+  const [feedback, setFeedback] = useState<Record<string, MessageFeedback>>({});
   const messagesEndRef = useRef<HTMLDivElement>(null);
   
   const messages = useMessages();
@@ -18,13 +33,26 @@ export function ChatView() {
   const clearMessages = useChatStore((state) => state.clearMessages);
   const activeConversationId = useChatStore((state) => state.activeConversationId);
   
-  const { sendMessage } = useAgent();
+  const providers = useSettingsStore((state) => state.providers);
+  const enabledProviders = Object.entries(providers)
+    .filter(([, config]) => config.enabled)
+    .map(([id]) => id);
+
+  const { sendMessage, compareMessage } = useAgent();
 
   // Auto scroll to bottom
   useEffect(() => {
     messagesEndRef.current?.scrollIntoView({ behavior: 'smooth' });
   }, [messages]);
 
+  useEffect(() => {
+    setFeedback({});
+    if (!activeConversationId) return;
+    invoke<MessageFeedback[]>('get_conversation_feedback', { conversationId: activeConversationId })
+      .then((loaded) => setFeedback(Object.fromEntries(loaded.map((f) => [f.messageId, f]))))
+      .catch((error) => console.error('Failed to load feedback:', error));
+  }, [activeConversationId]);
+
   // Create conversation if none exists
   useEffect(() => {
     if (!activeConversationId) {
@@ -38,9 +66,21 @@ export function ChatView() {
 
     const userMessage = input.trim();
     setInput('');
-    await sendMessage(userMessage);
+    if (comparing) {
+      await compareMessage(userMessage, compared);
+    } else {
+      await sendMessage(userMessage, provider || undefined);
+    }
+  };
+
+  const toggleCompared = (id: string) => {
+    setCompared((current) =>
+      current.includes(id) ? current.filter((p) => p !== id) : [...current, id]
+    );
   };
 
+  const canSubmit = input.trim() !== '' && !isStreaming && (!comparing || compared.length >= 2);
+
   const handleClear = () => {
     if (activeConversationId) {
       clearMessages(activeConversationId);
@@ -54,15 +94,31 @@ export function ChatView() {
         <h2 className="font-semibold text-lg">
           {conversation?.title || 'New Chat'}
         </h2>
-        <button
-          onClick={handleClear}
-          className="p-2 hover:bg-gray-100 dark:hover:bg-gray-800 rounded-lg"
-          title="Clear chat"
-        >
-          <Trash2 className="w-4 h-4" />
-        </button>
+        <div className="flex items-center gap-1">
+          <button
+            onClick={() => setEditingPrompt(!editingPrompt)}
+            className={`p-2 rounded-lg ${editingPrompt ? 'bg-gray-100 dark:bg-gray-800' : 'hover:bg-gray-100 dark:hover:bg-gray-800'}`}
+            title="System prompt and generation parameters"
+          >
+            <ScrollText className="w-4 h-4" />
+          </button>
+          <button
+            onClick={handleClear}
+            className="p-2 hover:bg-gray-100 dark:hover:bg-gray-800 rounded-lg"
+            title="Clear chat"
+          >
+            <Trash2 className="w-4 h-4" />
+          </button>
+        </div>
       </div>
 
+      {editingPrompt && activeConversationId && (
+        <>
+          <SystemPromptEditor conversationId={activeConversationId} />
+          <GenerationParamsEditor conversationId={activeConversationId} />
+        </>
+      )}
+
       {/* Messages */}
       <div className="flex-1 overflow-y-auto p-4 space-y-4">
         {messages.length === 0 && (
@@ -72,7 +128,12 @@ export function ChatView() {
         )}
         
         {messages.map((message) => (
-          <MessageBubble key={message.id} message={message} />
+          <MessageBubble
+            key={message.id}
+            message={message}
+            conversationId={activeConversationId}
+            feedback={feedback[message.id]}
+          />
         ))}
         
         {isStreaming && (
@@ -86,7 +147,38 @@ export function ChatView() {
       </div>
 
       {/* Input */}
-      <form onSubmit={handleSubmit} className="p-4 border-t">
+      <form onSubmit={handleSubmit} className="p-4 border-t space-y-2">
+        <div className="flex items-center gap-2 text-xs text-gray-500">
+          {comparing ? (
+            enabledProviders.map((id) => (
+              <label key={id} className="flex items-center gap-1">
+                <input type="checkbox" checked={compared.includes(id)} onChange={() => toggleCompared(id)} />
+                {id}
+              </label>
+            ))
+          ) : (
+            <select
+              value={provider}
+              onChange={(e) => setProvider(e.target.value)}
+              className="px-2 py-1 border rounded bg-white dark:bg-gray-800"
+              title="Provider for this message"
+            >
+              <option value="">Active provider</option>
+              {enabledProviders.map((id) => (
+                <option key={id} value={id}>{id}</option>
+              ))}
+            </select>
+          )}
+          <button
+            type="button"
+            onClick={() => setComparing(!comparing)}
+            className={`ml-auto flex items-center gap-1 px-2 py-1 rounded ${comparing ? 'bg-blue-100 text-blue-700 dark:bg-blue-900/30' : 'hover:bg-gray-100 dark:hover:bg-gray-800'}`}
+            title="Send to several providers and compare their answers"
+          >
+            <Columns className="w-3 h-3" />
+            Compare
+          </button>
+        </div>
         <div className="flex gap-2">
           <input
             type="text"
@@ -98,7 +190,7 @@ export function ChatView() {
           />
           <button
             type="submit"
-            disabled={!input.trim() || isStreaming}
+            disabled={!canSubmit}
             className="px-4 py-2 bg-blue-500 text-white rounded-lg hover:bg-blue-600 disabled:opacity-50 disabled:cursor-not-allowed"
           >
             <Send className="w-4 h-4" />
@@ -110,15 +202,12 @@ export function ChatView() {
 }
 
 interface MessageBubbleProps {
-  message: {
-    id: string;
-    role: 'user' | 'assistant' | 'system';
-    content: string;
-    timestamp: Date;
-  };
+  message: Message;
+  conversationId: string | null;
+  feedback?: MessageFeedback;
 }
 
-function MessageBubble({ message }: MessageBubbleProps) {
+function MessageBubble({ message, conversationId, feedback }: MessageBubbleProps) {
   const isUser = message.role === 'user';
   
   return (
@@ -133,7 +222,12 @@ function MessageBubble({ message }: MessageBubbleProps) {
         <p className="whitespace-pre-wrap">{message.content}</p>
         <span className={`text-xs ${isUser ? 'text-blue-100' : 'text-gray-500'}`}>
           {new Date(message.timestamp).toLocaleTimeString()}
+          {!isUser && message.provider && ` · ${message.provider}`}
         </span>
+        {message.comparedProviders && conversationId && (
+          <ComparisonPicker conversationId={conversationId} messageId={message.id} selected={message.provider} />
+        )}
+        {message.role === 'assistant' && <FeedbackButtons messageId={message.id} feedback={feedback} />}
       </div>
     </div>
   );
@@ -0,0 +1,95 @@
+/**
+ * Comparison Picker - Answers of several providers to one prompt, with
+ * latency and token stats, and a way to keep another one
+ */
+
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Check, Clock } from 'lucide-react';
+import { useChatStore } from '../../stores/chatStore';
+import type { Comparison } from '../../types/chat';
+import { errorMessage } from '../../types/error';
+
+interface ComparisonPickerProps {
+  conversationId: string;
+  messageId: string;
+  /** Provider whose answer is the message content */
+  selected?: string;
+}
+
+export function ComparisonPicker({ conversationId, messageId, selected }: ComparisonPickerProps) {
+  const selectComparedResponse = useChatStore((state) => state.selectComparedResponse);
+  const [comparison, setComparison] = useState<Comparison | null>(null);
+  const [shown, setShown] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<Comparison>('get_message_comparison', { messageId })
+      .then((loaded) => {
+        setComparison(loaded);
+        setShown(loaded.selected);
+      })
+      .catch((e) => setError(errorMessage(e)));
+  }, [messageId]);
+
+  if (error) {
+    return <p className="text-xs text-red-500">{error}</p>;
+  }
+  if (!comparison || !shown) {
+    return null;
+  }
+
+  const current = comparison.results[shown];
+  const keep = async () => {
+    try {
+      await selectComparedResponse(conversationId, messageId, shown);
+      setComparison({ ...comparison, selected: shown });
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  };
+
+  return (
+    <div className="mt-2 pt-2 border-t border-gray-200 dark:border-gray-700 space-y-2">
+      <div className="flex flex-wrap gap-1">
+        {Object.entries(comparison.results).map(([name, result]) => (
+          <button
+            key={name}
+            onClick={() => setShown(name)}
+            className={`flex items-center gap-1 px-2 py-0.5 text-xs rounded border ${
+              name === shown ? 'border-blue-500 text-blue-600' : 'border-transparent text-gray-500'
+            } ${result.error ? 'line-through' : ''}`}
+          >
+            {name === (selected ?? comparison.selected) && <Check className="w-3 h-3" />}
+            {name}
+          </button>
+        ))}
+      </div>
+
+      {shown !== (selected ?? comparison.selected) && (
+        <div className="space-y-1">
+          {current.error ? (
+            <p className="text-xs text-red-500">{current.error}</p>
+          ) : (
+            <>
+              <p className="whitespace-pre-wrap text-sm opacity-80">{current.content}</p>
+              <button onClick={keep} className="text-xs text-blue-600 hover:underline">
+                Use this answer
+              </button>
+            </>
+          )}
+        </div>
+      )}
+
+      <div className="flex items-center gap-1 text-xs text-gray-500">
+        <Clock className="w-3 h-3" />
+        <span>{(current.latencyMs / 1000).toFixed(1)}s</span>
+        {current.usage && (
+          <span>
+            · {current.usage.promptTokens} in / {current.usage.completionTokens} out tokens
+          </span>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,92 @@
+/**
+ * Feedback Buttons - Thumbs up/down and a comment on an assistant message
+ */
+
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ThumbsDown, ThumbsUp } from 'lucide-react';
+import type { FeedbackRating, MessageFeedback } from '../../types/chat';
+import { errorMessage } from '../../types/error';
+
+interface FeedbackButtonsProps {
+  messageId: string;
+  feedback?: MessageFeedback;
+}
+
+export function FeedbackButtons({ messageId, feedback }: FeedbackButtonsProps) {
+  const [current, setCurrent] = useState<MessageFeedback | null>(feedback ?? null);
+  const [comment, setComment] = useState(feedback?.comment ?? '');
+  const [commenting, setCommenting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setCurrent(feedback ?? null);
+    setComment(feedback?.comment ?? '');
+  }, [feedback]);
+
+  const submit = async (rating: FeedbackRating | null, text: string | null) => {
+    try {
+      const saved = await invoke<MessageFeedback | null>('submit_feedback', { messageId, rating, comment: text });
+      setCurrent(saved);
+      setCommenting(false);
+      setError(null);
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  };
+
+  // Clicking the current rating again removes the feedback
+  const rate = (rating: FeedbackRating) => {
+    if (current?.rating === rating) {
+      setComment('');
+      submit(null, null);
+    } else {
+      submit(rating, current?.comment ?? null);
+      setCommenting(true);
+    }
+  };
+
+  const button = (rating: FeedbackRating, Icon: typeof ThumbsUp, title: string) => (
+    <button
+      onClick={() => rate(rating)}
+      className={`p-1 rounded hover:bg-gray-200 dark:hover:bg-gray-700 ${
+        current?.rating === rating ? 'text-blue-600' : 'text-gray-400'
+      }`}
+      title={title}
+    >
+      <Icon className="w-3 h-3" />
+    </button>
+  );
+
+  return (
+    <div className="mt-1 space-y-1">
+      <div className="flex items-center gap-1">
+        {button('up', ThumbsUp, 'Good answer')}
+        {button('down', ThumbsDown, 'Bad answer')}
+        {current && !commenting && (
+          <button onClick={() => setCommenting(true)} className="text-xs text-gray-500 hover:underline">
+            {current.comment ? 'Edit comment' : 'Add comment'}
+          </button>
+        )}
+      </div>
+      {current && commenting && (
+        <div className="flex gap-1">
+          <input
+            type="text"
+            value={comment}
+            onChange={(e) => setComment(e.target.value)}
+            placeholder="What was good or wrong?"
+            className="flex-1 px-2 py-1 border rounded text-xs bg-white dark:bg-gray-900"
+          />
+          <button
+            onClick={() => submit(current.rating, comment)}
+            className="px-2 py-1 text-xs bg-blue-500 text-white rounded hover:bg-blue-600"
+          >
+            Save
+          </button>
+        </div>
+      )}
+      {error && <p className="text-xs text-red-500">{error}</p>}
+    </div>
+  );
+}
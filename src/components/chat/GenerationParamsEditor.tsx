@@ -0,0 +1,104 @@
+/**
+ * Generation Params Editor - Temperature, top P, max tokens, stop sequences
+ * and seed a conversation's chats default to
+ */
+
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { GenerationParams } from '../../types/chat';
+import { errorMessage } from '../../types/error';
+
+interface GenerationParamsEditorProps {
+  conversationId: string;
+}
+
+/** Number typed into a field; null when empty */
+function parseNumber(value: string): number | null {
+  return value.trim() === '' ? null : Number(value);
+}
+
+export function GenerationParamsEditor({ conversationId }: GenerationParamsEditorProps) {
+  const [params, setParams] = useState<GenerationParams>({});
+  const [stopText, setStopText] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [saved, setSaved] = useState(false);
+
+  const load = useCallback(async () => {
+    try {
+      const loaded = await invoke<GenerationParams>('get_conversation_generation_params', { conversationId });
+      setParams(loaded);
+      setStopText((loaded.stop ?? []).join('\n'));
+      setError(null);
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }, [conversationId]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  const update = (changes: Partial<GenerationParams>) => {
+    setParams({ ...params, ...changes });
+    setSaved(false);
+  };
+
+  const save = async () => {
+    const stop = stopText.split('\n').filter((s) => s !== '');
+    try {
+      const stored = await invoke<GenerationParams>('set_conversation_generation_params', {
+        conversationId,
+        params: { ...params, stop: stop.length > 0 ? stop : null },
+      });
+      setParams(stored);
+      setError(null);
+      setSaved(true);
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  };
+
+  const field = (label: string, key: keyof Omit<GenerationParams, 'stop'>, step: string, placeholder: string) => (
+    <label className="flex flex-col gap-1">
+      <span className="text-gray-500">{label}</span>
+      <input
+        type="number"
+        step={step}
+        value={params[key] ?? ''}
+        onChange={(e) => update({ [key]: parseNumber(e.target.value) })}
+        placeholder={placeholder}
+        className="px-2 py-1 border rounded bg-white dark:bg-gray-800"
+      />
+    </label>
+  );
+
+  return (
+    <div className="px-4 py-3 border-b space-y-2 text-xs bg-gray-50 dark:bg-gray-900/40">
+      <div className="grid grid-cols-4 gap-2">
+        {field('Temperature', 'temperature', '0.1', 'Provider default')}
+        {field('Top P', 'topP', '0.05', 'Provider default')}
+        {field('Max tokens', 'maxTokens', '1', 'Provider default')}
+        {field('Seed', 'seed', '1', 'Random')}
+      </div>
+      <label className="flex flex-col gap-1">
+        <span className="text-gray-500">Stop sequences, one per line</span>
+        <textarea
+          value={stopText}
+          onChange={(e) => {
+            setStopText(e.target.value);
+            setSaved(false);
+          }}
+          rows={2}
+          className="px-2 py-1 border rounded bg-white dark:bg-gray-800 font-mono"
+        />
+      </label>
+      {error && <p className="text-red-500">{error}</p>}
+      <div className="flex items-center gap-2">
+        <button onClick={save} className="px-3 py-1 bg-blue-500 text-white rounded hover:bg-blue-600">
+          Save defaults
+        </button>
+        {saved && <span className="text-gray-500">Saved; checked again for each provider when sending</span>}
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,102 @@
+/**
+ * System Prompt Editor - A conversation's own system prompt and its versions
+ */
+
+import React, { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { History, RotateCcw } from 'lucide-react';
+import type { SystemPromptVersion } from '../../types/chat';
+import { errorMessage } from '../../types/error';
+
+interface SystemPromptEditorProps {
+  conversationId: string;
+}
+
+export function SystemPromptEditor({ conversationId }: SystemPromptEditorProps) {
+  const [versions, setVersions] = useState<SystemPromptVersion[]>([]);
+  const [draft, setDraft] = useState('');
+  const [showHistory, setShowHistory] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      const history = await invoke<SystemPromptVersion[]>('get_system_prompt_history', { conversationId });
+      setVersions(history);
+      setDraft(history[0]?.prompt ?? '');
+      setError(null);
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }, [conversationId]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  const save = async (prompt: string | null) => {
+    try {
+      await invoke<SystemPromptVersion>('set_conversation_system_prompt', { conversationId, prompt });
+      await load();
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  };
+
+  const active = versions[0]?.prompt ?? '';
+
+  return (
+    <div className="px-4 py-3 border-b space-y-2 bg-gray-50 dark:bg-gray-900/40">
+      <textarea
+        value={draft}
+        onChange={(e) => setDraft(e.target.value)}
+        rows={3}
+        placeholder="System prompt for this conversation; the workspace prompt applies when empty"
+        className="w-full px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
+      />
+      {error && <p className="text-xs text-red-500">{error}</p>}
+      <div className="flex items-center gap-2 text-xs">
+        <button
+          onClick={() => save(draft)}
+          disabled={draft.trim() === active}
+          className="px-3 py-1 bg-blue-500 text-white rounded hover:bg-blue-600 disabled:opacity-50"
+        >
+          Save
+        </button>
+        {active && (
+          <button onClick={() => save(null)} className="px-3 py-1 border rounded hover:bg-gray-100 dark:hover:bg-gray-800">
+            Clear
+          </button>
+        )}
+        {versions.length > 0 && (
+          <button
+            onClick={() => setShowHistory(!showHistory)}
+            className="ml-auto flex items-center gap-1 text-gray-500 hover:text-gray-700"
+          >
+            <History className="w-3 h-3" />
+            {versions.length} version{versions.length === 1 ? '' : 's'}
+          </button>
+        )}
+      </div>
+
+      {showHistory && (
+        <ul className="space-y-1 max-h-48 overflow-y-auto text-xs">
+          {versions.map((version, index) => (
+            <li key={version.version} className="flex items-start gap-2 p-2 rounded border bg-white dark:bg-gray-800">
+              <span className="shrink-0 text-gray-500">
+                v{version.version} · {new Date(version.createdAt).toLocaleString()}
+              </span>
+              <span className={`flex-1 whitespace-pre-wrap ${version.prompt ? '' : 'italic text-gray-400'}`}>
+                {version.prompt ?? 'Cleared'}
+              </span>
+              {index > 0 && (
+                <button onClick={() => save(version.prompt)} title="Restore this version" className="shrink-0">
+                  <RotateCcw className="w-3 h-3" />
+                </button>
+              )}
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -6,6 +6,7 @@ import React, { useState, useEffect } from 'react';
 import { Folder, File, ChevronRight, ChevronDown, RefreshCw, Home } from 'lucide-react';
 import { useFiles, FileEntry } from '../../hooks/useFiles';
 import { useSettingsStore } from '../../stores/settingsStore';
+import { errorMessage } from '../../types/error';
 
 export function FileExplorer() {
   const { currentPath, files, loading, error, navigateTo, readFile } = useFiles();
@@ -38,7 +39,7 @@ export function FileExplorer() {
       const content = await readFile(path);
       setFileContent(content);
     } catch (err) {
-      setFileContent(`Error: ${err}`);
+      setFileContent(`Error: ${errorMessage(err)}`);
     }
   };
 
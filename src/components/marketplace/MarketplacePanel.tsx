@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../../types/error";
 
 interface MarketplaceItem {
   id: string;
@@ -44,7 +45,7 @@ export const MarketplacePanel: React.FC = () => {
       setItems(result);
     } catch (err) {
       console.error("Failed to load marketplace items:", err);
-      setError(`Failed to load items: ${err}`);
+      setError(`Failed to load items: ${errorMessage(err)}`);
     } finally {
       setLoading(false);
     }
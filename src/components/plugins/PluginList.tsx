@@ -2,12 +2,16 @@
  * Plugin List Component - Manage installed plugins
  */
 
-import React from "react";
-import { Plug, Trash2, Power, PowerOff, Package } from "lucide-react";
+import React, { useState } from "react";
+import { Plug, Trash2, Power, PowerOff, Package, LayoutPanelTop, Activity } from "lucide-react";
 import { usePluginStore } from "../../stores/pluginStore";
+import { PluginPanelView } from "./PluginPanelView";
+import { PluginResourceView } from "./PluginResourceView";
 
 export function PluginList() {
   const { plugins, isLoading, enablePlugin, disablePlugin, uninstallPlugin } = usePluginStore();
+  const [openPanels, setOpenPanels] = useState<string | null>(null);
+  const [openResources, setOpenResources] = useState<string | null>(null);
 
   return (
     <div className="space-y-4">
@@ -53,6 +57,24 @@ export function PluginList() {
                   </p>
                 </div>
                 <div className="flex items-center gap-2">
+                  {plugin.enabled && (
+                    <button
+                      onClick={() => setOpenResources(openResources === plugin.id ? null : plugin.id)}
+                      className="p-2 hover:bg-gray-100 dark:hover:bg-gray-700 rounded text-gray-500"
+                      title="Resource usage"
+                    >
+                      <Activity className="w-4 h-4" />
+                    </button>
+                  )}
+                  {plugin.enabled && (
+                    <button
+                      onClick={() => setOpenPanels(openPanels === plugin.id ? null : plugin.id)}
+                      className="p-2 hover:bg-gray-100 dark:hover:bg-gray-700 rounded text-gray-500"
+                      title="Panels"
+                    >
+                      <LayoutPanelTop className="w-4 h-4" />
+                    </button>
+                  )}
                   <button
                     onClick={() =>
                       plugin.enabled
@@ -81,6 +103,16 @@ export function PluginList() {
                   </button>
                 </div>
               </div>
+              {plugin.enabled && openResources === plugin.id && (
+                <div className="mt-4">
+                  <PluginResourceView pluginId={plugin.id} />
+                </div>
+              )}
+              {plugin.enabled && openPanels === plugin.id && (
+                <div className="mt-4">
+                  <PluginPanelView pluginId={plugin.id} />
+                </div>
+              )}
             </div>
           ))}
         </div>
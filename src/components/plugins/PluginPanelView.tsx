@@ -0,0 +1,203 @@
+/**
+ * Plugin Panel View - Renders the declarative panels of a running plugin
+ */
+
+import React, { useEffect, useState } from "react";
+import ReactMarkdown from "react-markdown";
+import { LayoutPanelTop } from "lucide-react";
+import { usePluginStore } from "../../stores/pluginStore";
+import { FormField, PanelBlock, PluginPanel } from "../../types/plugin";
+import { errorMessage } from "../../types/error";
+
+interface PluginPanelViewProps {
+  pluginId: string;
+}
+
+export function PluginPanelView({ pluginId }: PluginPanelViewProps) {
+  const { panels, loadPanels } = usePluginStore();
+
+  useEffect(() => {
+    loadPanels(pluginId);
+  }, [pluginId, loadPanels]);
+
+  const pluginPanels = panels[pluginId] ?? [];
+  if (pluginPanels.length === 0) {
+    return <p className="text-sm text-gray-400">This plugin has no panels</p>;
+  }
+
+  return (
+    <div className="space-y-4">
+      {pluginPanels.map((panel) => (
+        <PanelCard key={panel.id} pluginId={pluginId} panel={panel} />
+      ))}
+    </div>
+  );
+}
+
+function PanelCard({ pluginId, panel }: { pluginId: string; panel: PluginPanel }) {
+  const { panelAction } = usePluginStore();
+  const [message, setMessage] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const runAction = async (action: string, payload: unknown) => {
+    setBusy(true);
+    setError(null);
+    try {
+      const result = await panelAction(pluginId, panel.id, action, payload);
+      setMessage(result.message ?? null);
+    } catch (e) {
+      setError(errorMessage(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="p-4 border rounded-lg space-y-3">
+      <div className="flex items-center gap-2">
+        <LayoutPanelTop className="w-4 h-4" />
+        <h4 className="font-medium">{panel.title}</h4>
+      </div>
+      {panel.blocks.map((block, index) => (
+        <PanelBlockView key={index} block={block} busy={busy} onAction={runAction} />
+      ))}
+      {message && <p className="text-sm text-green-600 dark:text-green-400">{message}</p>}
+      {error && <p className="text-sm text-red-500">{error}</p>}
+    </div>
+  );
+}
+
+interface PanelBlockViewProps {
+  block: PanelBlock;
+  busy: boolean;
+  onAction: (action: string, payload: unknown) => void;
+}
+
+function PanelBlockView({ block, busy, onAction }: PanelBlockViewProps) {
+  const buttonClass =
+    "px-3 py-1.5 text-sm bg-blue-500 text-white rounded hover:bg-blue-600 disabled:opacity-50";
+
+  switch (block.type) {
+    case "markdown":
+      return (
+        <div className="prose prose-sm dark:prose-invert max-w-none">
+          <ReactMarkdown>{block.text}</ReactMarkdown>
+        </div>
+      );
+
+    case "form":
+      return <PanelForm fields={block.fields} submitLabel={block.submit.label} busy={busy}
+        onSubmit={(values) => onAction(block.submit.action, values)} />;
+
+    case "table":
+      return (
+        <table className="w-full text-sm">
+          <thead>
+            <tr className="text-left text-gray-500">
+              {block.columns.map((column) => (
+                <th key={column.key} className="py-1 pr-2 font-medium">{column.label}</th>
+              ))}
+              {block.rowAction && <th />}
+            </tr>
+          </thead>
+          <tbody>
+            {block.rows.map((row, index) => (
+              <tr key={index} className="border-t">
+                {block.columns.map((column) => (
+                  <td key={column.key} className="py-1 pr-2">{String(row[column.key] ?? "")}</td>
+                ))}
+                {block.rowAction && (
+                  <td className="py-1 text-right">
+                    <button
+                      disabled={busy}
+                      onClick={() => onAction(block.rowAction!.action, row)}
+                      className="text-xs text-blue-500 hover:underline disabled:opacity-50"
+                    >
+                      {block.rowAction.label}
+                    </button>
+                  </td>
+                )}
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      );
+
+    case "buttons":
+      return (
+        <div className="flex flex-wrap gap-2">
+          {block.buttons.map((button) => (
+            <button key={button.action} disabled={busy} onClick={() => onAction(button.action, {})} className={buttonClass}>
+              {button.label}
+            </button>
+          ))}
+        </div>
+      );
+  }
+}
+
+interface PanelFormProps {
+  fields: FormField[];
+  submitLabel: string;
+  busy: boolean;
+  onSubmit: (values: Record<string, unknown>) => void;
+}
+
+function PanelForm({ fields, submitLabel, busy, onSubmit }: PanelFormProps) {
+  const [values, setValues] = useState<Record<string, unknown>>(() =>
+    Object.fromEntries(fields.filter((f) => f.default !== undefined).map((f) => [f.name, f.default]))
+  );
+  const setValue = (name: string, value: unknown) => setValues((v) => ({ ...v, [name]: value }));
+  const inputClass = "w-full px-2 py-1 text-sm border rounded dark:bg-gray-800 dark:border-gray-600";
+
+  return (
+    <form
+      className="space-y-2"
+      onSubmit={(e) => {
+        e.preventDefault();
+        onSubmit(values);
+      }}
+    >
+      {fields.map((field) => {
+        const value = values[field.name];
+        return (
+          <label key={field.name} className="block text-sm">
+            <span className="text-gray-600 dark:text-gray-300">
+              {field.label}
+              {field.required && " *"}
+            </span>
+            {field.kind === "textarea" ? (
+              <textarea className={inputClass} required={field.required} value={String(value ?? "")}
+                onChange={(e) => setValue(field.name, e.target.value)} />
+            ) : field.kind === "number" ? (
+              <input type="number" className={inputClass} required={field.required} value={value === undefined ? "" : String(value)}
+                onChange={(e) => setValue(field.name, e.target.value === "" ? null : Number(e.target.value))} />
+            ) : field.kind === "checkbox" ? (
+              <input type="checkbox" className="ml-2" checked={Boolean(value)}
+                onChange={(e) => setValue(field.name, e.target.checked)} />
+            ) : field.kind === "select" ? (
+              <select className={inputClass} required={field.required} value={String(value ?? "")}
+                onChange={(e) => setValue(field.name, e.target.value)}>
+                <option value="" />
+                {(field.options ?? []).map((option) => (
+                  <option key={option} value={option}>{option}</option>
+                ))}
+              </select>
+            ) : (
+              <input type="text" className={inputClass} required={field.required} value={String(value ?? "")}
+                onChange={(e) => setValue(field.name, e.target.value)} />
+            )}
+          </label>
+        );
+      })}
+      <button
+        type="submit"
+        disabled={busy}
+        className="px-3 py-1.5 text-sm bg-blue-500 text-white rounded hover:bg-blue-600 disabled:opacity-50"
+      >
+        {submitLabel}
+      </button>
+    </form>
+  );
+}
@@ -0,0 +1,106 @@
+/**
+ * Plugin Resource View - Memory and CPU sparklines of a running plugin
+ */
+
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { PauseCircle } from "lucide-react";
+import { usePluginStore } from "../../stores/pluginStore";
+import { PluginSuspension } from "../../types/plugin";
+
+/** How often samples are refreshed; matches the backend sampling interval */
+const REFRESH_MS = 2000;
+
+interface PluginResourceViewProps {
+  pluginId: string;
+}
+
+export function PluginResourceView({ pluginId }: PluginResourceViewProps) {
+  const { resourceSamples, loadResourceSamples, resumePlugin } = usePluginStore();
+  const [suspendedReason, setSuspendedReason] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadResourceSamples(pluginId);
+    const timer = setInterval(() => loadResourceSamples(pluginId), REFRESH_MS);
+    return () => clearInterval(timer);
+  }, [pluginId, loadResourceSamples]);
+
+  useEffect(() => {
+    const unlisten = listen<PluginSuspension>("plugins://suspended", (event) => {
+      if (event.payload.id === pluginId) {
+        setSuspendedReason(event.payload.reason);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [pluginId]);
+
+  const samples = resourceSamples[pluginId] ?? [];
+  const latest = samples[samples.length - 1];
+
+  const resume = async () => {
+    await resumePlugin(pluginId);
+    setSuspendedReason(null);
+  };
+
+  return (
+    <div className="space-y-2">
+      {suspendedReason && (
+        <div className="flex items-center gap-2 p-2 text-sm rounded bg-yellow-50 text-yellow-800 dark:bg-yellow-900/20 dark:text-yellow-300">
+          <PauseCircle className="w-4 h-4 shrink-0" />
+          <span className="flex-1">Suspended: {suspendedReason}</span>
+          <button onClick={resume} className="px-2 py-0.5 text-xs rounded border border-current hover:opacity-80">
+            Resume
+          </button>
+        </div>
+      )}
+      <div className="grid grid-cols-2 gap-4 text-xs text-gray-500">
+        <div>
+          <div className="flex justify-between">
+            <span>Memory</span>
+            <span>{latest ? `${(latest.memoryBytes / (1024 * 1024)).toFixed(1)} MB` : "-"}</span>
+          </div>
+          <Sparkline values={samples.map((s) => s.memoryBytes)} className="text-blue-500" />
+        </div>
+        <div>
+          <div className="flex justify-between">
+            <span>CPU</span>
+            <span>{latest ? `${latest.cpuPercent.toFixed(0)}%` : "-"}</span>
+          </div>
+          <Sparkline values={samples.map((s) => s.cpuPercent)} max={100} className="text-green-500" />
+        </div>
+      </div>
+    </div>
+  );
+}
+
+interface SparklineProps {
+  values: number[];
+  /** Top of the scale; the largest value when not given */
+  max?: number;
+  className?: string;
+}
+
+function Sparkline({ values, max, className }: SparklineProps) {
+  const width = 120;
+  const height = 24;
+  if (values.length < 2) {
+    return <svg width="100%" height={height} viewBox={`0 0 ${width} ${height}`} />;
+  }
+
+  const top = max ?? Math.max(...values, 1);
+  const points = values
+    .map((value, index) => {
+      const x = (index / (values.length - 1)) * width;
+      const y = height - (Math.min(value, top) / top) * height;
+      return `${x.toFixed(1)},${y.toFixed(1)}`;
+    })
+    .join(" ");
+
+  return (
+    <svg width="100%" height={height} viewBox={`0 0 ${width} ${height}`} preserveAspectRatio="none" className={className}>
+      <polyline points={points} fill="none" stroke="currentColor" strokeWidth="1.5" />
+    </svg>
+  );
+}
@@ -0,0 +1,84 @@
+// QuickAskWindow Component - Lightweight prompt window opened by the global shortcut
+
+import React, { useEffect, useRef, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { Send, Trash2 } from 'lucide-react';
+import { useQuickAskStore } from '../../stores/quickAskStore';
+
+export const QuickAskWindow: React.FC = () => {
+  const [input, setInput] = useState('');
+  const inputRef = useRef<HTMLInputElement>(null);
+  const { turns, isAsking, ask, clear, hide } = useQuickAskStore();
+
+  // Focus the prompt every time the shortcut shows the window
+  useEffect(() => {
+    inputRef.current?.focus();
+    const unlisten = listen('quick-ask://opened', () => inputRef.current?.focus());
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleSubmit = (e: React.FormEvent) => {
+    e.preventDefault();
+    if (input.trim() && !isAsking) {
+      ask(input.trim());
+      setInput('');
+    }
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent) => {
+    if (e.key === 'Escape') {
+      e.preventDefault();
+      hide();
+    }
+  };
+
+  return (
+    <div
+      className="flex flex-col h-screen bg-white dark:bg-gray-900 text-gray-900 dark:text-gray-100 rounded-lg border dark:border-gray-700"
+      onKeyDown={handleKeyDown}
+    >
+      <form onSubmit={handleSubmit} className="flex items-center gap-2 p-3 border-b dark:border-gray-700">
+        <input
+          ref={inputRef}
+          value={input}
+          onChange={(e) => setInput(e.target.value)}
+          placeholder="Ask anything... (Esc to close)"
+          className="flex-1 bg-transparent px-2 py-1 text-base focus:outline-none"
+        />
+        <button
+          type="submit"
+          disabled={!input.trim() || isAsking}
+          className="p-2 rounded hover:bg-gray-100 dark:hover:bg-gray-800 disabled:opacity-50"
+        >
+          <Send className="w-4 h-4" />
+        </button>
+        <button
+          type="button"
+          onClick={() => clear()}
+          disabled={isAsking || turns.length === 0}
+          title="Clear"
+          className="p-2 rounded hover:bg-gray-100 dark:hover:bg-gray-800 disabled:opacity-50"
+        >
+          <Trash2 className="w-4 h-4" />
+        </button>
+      </form>
+
+      <div className="flex-1 overflow-y-auto p-3 space-y-3 text-sm">
+        {turns.map((turn, i) => (
+          <div key={i} className="space-y-1">
+            <p className="font-medium">{turn.prompt}</p>
+            {turn.error ? (
+              <p className="text-red-500">{turn.error}</p>
+            ) : turn.reply === null ? (
+              <p className="text-gray-500">Thinking...</p>
+            ) : (
+              <p className="whitespace-pre-wrap text-gray-700 dark:text-gray-300">{turn.reply}</p>
+            )}
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+};
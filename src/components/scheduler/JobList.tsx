@@ -3,13 +3,15 @@
  */
 
 import React, { useState, useEffect } from 'react';
-import { Plus, Trash2, Play, Clock, ToggleLeft, ToggleRight } from 'lucide-react';
+import { Plus, Trash2, Play, Pause, AlarmClock, Clock, ToggleLeft, ToggleRight } from 'lucide-react';
 import { useSchedulerStore } from '../../stores/schedulerStore';
-import type { CronJob, JobType } from '../../types/scheduler';
+import type { CronJob, JobStatus, JobType } from '../../types/scheduler';
 import { JOB_TYPE_LABELS, SCHEDULE_PRESETS } from '../../types/scheduler';
 
 export function JobList() {
-  const { jobs, loading, error, loadJobs, createJob, deleteJob, toggleJob, runJobNow } = useSchedulerStore();
+  const {
+    jobs, loading, error, loadJobs, createJob, deleteJob, toggleJob, runJobNow, pauseJob, snoozeJob, resumeJob,
+  } = useSchedulerStore();
   const [showCreate, setShowCreate] = useState(false);
   const [newJob, setNewJob] = useState({
     name: '',
@@ -47,10 +49,15 @@ export function JobList() {
     }
   };
 
-  const getStatusColor = (enabled: boolean) => {
-    return enabled ? 'bg-green-100 text-green-600' : 'bg-gray-100 text-gray-400';
+  const STATUS_STYLES: Record<JobStatus, { label: string; className: string }> = {
+    active: { label: 'Active', className: 'bg-green-100 text-green-600' },
+    paused: { label: 'Paused', className: 'bg-yellow-100 text-yellow-600' },
+    snoozed: { label: 'Snoozed', className: 'bg-blue-100 text-blue-600' },
+    disabled: { label: 'Disabled', className: 'bg-gray-100 text-gray-400' },
   };
 
+  const handleSnooze = (id: string) => snoozeJob(id, new Date(Date.now() + 60 * 60 * 1000));
+
   if (loading) {
     return <div className="p-4 text-center text-gray-500">Loading...</div>;
   }
@@ -107,9 +114,11 @@ export function JobList() {
               onChange={(e) => setNewJob({ ...newJob, jobType: e.target.value as JobType })}
               className="w-full px-3 py-2 border rounded text-sm"
             >
-              {Object.entries(JOB_TYPE_LABELS).map(([value, label]) => (
-                <option key={value} value={value}>{label}</option>
-              ))}
+              {Object.entries(JOB_TYPE_LABELS)
+                .filter(([value]) => value !== 'plugin' && value !== 'workflow')
+                .map(([value, label]) => (
+                  <option key={value} value={value}>{label}</option>
+                ))}
             </select>
           </div>
           <div>
@@ -155,8 +164,8 @@ export function JobList() {
                 <div className="flex-1">
                   <div className="flex items-center gap-2">
                     <h3 className="font-medium">{job.name}</h3>
-                    <span className={`px-2 py-0.5 text-xs rounded ${getStatusColor(job.enabled)}`}>
-                      {job.enabled ? 'Active' : 'Paused'}
+                    <span className={`px-2 py-0.5 text-xs rounded ${STATUS_STYLES[job.status].className}`}>
+                      {STATUS_STYLES[job.status].label}
                     </span>
                   </div>
                   <div className="flex items-center gap-3 mt-1 text-sm text-gray-500">
@@ -165,12 +174,19 @@ export function JobList() {
                       {job.schedule}
                     </span>
                     <span>{JOB_TYPE_LABELS[job.jobType]}</span>
+                    {job.pluginId && <span>({job.pluginId})</span>}
                   </div>
                   {job.lastRun && (
                     <div className="text-xs text-gray-400 mt-1">
                       Last run: {new Date(job.lastRun).toLocaleString()}
                     </div>
                   )}
+                  {job.status === 'snoozed' && job.snoozedUntil && (
+                    <div className="text-xs text-gray-400 mt-1">
+                      Snoozed until {new Date(job.snoozedUntil).toLocaleString()}
+                      {job.skippedRuns > 0 && ` (${job.skippedRuns} skipped)`}
+                    </div>
+                  )}
                 </div>
                 <div className="flex items-center gap-2">
                   <button
@@ -180,6 +196,32 @@ export function JobList() {
                   >
                     <Play className="w-4 h-4" />
                   </button>
+                  {job.enabled && (job.status === 'active' ? (
+                    <>
+                      <button
+                        onClick={() => handleSnooze(job.id)}
+                        className="p-2 hover:bg-blue-100 rounded text-blue-500"
+                        title="Snooze for an hour"
+                      >
+                        <AlarmClock className="w-4 h-4" />
+                      </button>
+                      <button
+                        onClick={() => pauseJob(job.id)}
+                        className="p-2 hover:bg-yellow-100 rounded text-yellow-600"
+                        title="Pause"
+                      >
+                        <Pause className="w-4 h-4" />
+                      </button>
+                    </>
+                  ) : (
+                    <button
+                      onClick={() => resumeJob(job.id)}
+                      className="p-2 hover:bg-green-100 rounded text-green-500"
+                      title="Resume"
+                    >
+                      <Play className="w-4 h-4" />
+                    </button>
+                  ))}
                   <button
                     onClick={() => toggleJob(job.id)}
                     className={`p-2 rounded ${job.enabled ? 'text-green-500' : 'text-gray-400'}`}
@@ -5,6 +5,7 @@ import { Folder, Plus, Trash2, Eye, Edit3 } from 'lucide-react';
 import { open } from '@tauri-apps/plugin-dialog';
 import { usePermissionStore } from '../../stores/permissionStore';
 import type { PermissionLevel } from '../../types/permission';
+import { errorMessage } from '../../types/error';
 
 interface FolderPermissionDialogProps {
   isOpen: boolean;
@@ -63,7 +64,7 @@ export const FolderPermissionDialog: React.FC<FolderPermissionDialogProps> = ({
       setSelectedLevel('read');
       setError(null);
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
@@ -24,6 +24,8 @@ import {
   FolderPermission,
 } from "../../stores/settingsStore";
 import { useBrowserStore } from "../../stores/browserStore";
+import { usePermissionStore } from "../../stores/permissionStore";
+import type { RiskTier, TierBehavior } from "../../types/permission";
 import { validateFolderPath } from "../../services/tauri";
 import { VoiceSettings } from "../voice/VoiceSettings";
 import { PluginList } from "../plugins/PluginList";
@@ -211,7 +213,7 @@ function ProviderCard({
             className="w-4 h-4"
           />
           <span className="font-medium capitalize">{id}</span>
-          {config.type === "ollama" && (
+          {(config.type === "ollama" || config.type === "openai-compatible") && (
             <span className="text-xs px-2 py-0.5 bg-green-100 text-green-700 dark:bg-green-900/30 dark:text-green-400 rounded">
               Local
             </span>
@@ -247,7 +249,22 @@ function ProviderCard({
         </div>
       )}
 
-      {config.type === "ollama" && (
+      {config.type === "openai-compatible" && (
+        <div className="space-y-2 mb-3">
+          <label className="block text-sm text-gray-600 dark:text-gray-400">
+            API Key (optional)
+          </label>
+          <input
+            type="password"
+            value={config.apiKey || ""}
+            onChange={(e) => onUpdate({ apiKey: e.target.value })}
+            placeholder="Leave empty if the server needs none"
+            className="w-full px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
+          />
+        </div>
+      )}
+
+      {(config.type === "ollama" || config.type === "openai-compatible") && (
         <div className="space-y-2">
           <label className="block text-sm text-gray-600 dark:text-gray-400">
             Base URL
@@ -256,7 +273,7 @@ function ProviderCard({
             type="text"
             value={config.baseUrl || ""}
             onChange={(e) => onUpdate({ baseUrl: e.target.value })}
-            placeholder="http://localhost:11434"
+            placeholder={config.type === "ollama" ? "http://localhost:11434" : "http://localhost:1234/v1"}
             className="w-full px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
           />
         </div>
@@ -270,6 +287,7 @@ function ProviderCard({
           type="text"
           value={config.model}
           onChange={(e) => onUpdate({ model: e.target.value })}
+          placeholder={config.type === "openai-compatible" ? "Model name as served, e.g. qwen2.5-7b-instruct" : undefined}
           className="w-full px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
         />
       </div>
@@ -415,6 +433,90 @@ function FolderSettings() {
           ))}
         </div>
       )}
+
+      <ToolRiskSettings />
+    </div>
+  );
+}
+
+const RISK_TIERS: { tier: RiskTier; label: string; hint: string }[] = [
+  { tier: "low", label: "Low risk", hint: "Reading files" },
+  { tier: "medium", label: "Medium risk", hint: "Network and other tools" },
+  { tier: "high", label: "High risk", hint: "Writing files, shell, databases" },
+];
+
+function ToolRiskSettings() {
+  const riskSettings = usePermissionStore((state) => state.riskSettings);
+  const loadRiskSettings = usePermissionStore((state) => state.loadRiskSettings);
+  const updateRiskSettings = usePermissionStore(
+    (state) => state.updateRiskSettings,
+  );
+  const error = usePermissionStore((state) => state.error);
+  const [timeoutSecs, setTimeoutSecs] = useState("");
+
+  useEffect(() => {
+    loadRiskSettings();
+  }, [loadRiskSettings]);
+
+  useEffect(() => {
+    setTimeoutSecs(String(riskSettings.confirmationTimeoutSecs));
+  }, [riskSettings.confirmationTimeoutSecs]);
+
+  const update = (updates: Parameters<typeof updateRiskSettings>[0]) => {
+    updateRiskSettings(updates).catch(() => {});
+  };
+
+  return (
+    <div className="space-y-3 pt-4 border-t">
+      <div>
+        <h3 className="text-sm font-medium text-gray-600 dark:text-gray-400">
+          Tool Confirmation
+        </h3>
+        <p className="text-xs text-gray-500">
+          Choose what happens when the AI calls a tool. Per-tool decisions
+          override these.
+        </p>
+      </div>
+
+      {RISK_TIERS.map(({ tier, label, hint }) => (
+        <div key={tier} className="flex items-center justify-between gap-3">
+          <div>
+            <p className="text-sm">{label}</p>
+            <p className="text-xs text-gray-400">{hint}</p>
+          </div>
+          <select
+            value={riskSettings[tier]}
+            onChange={(e) =>
+              update({ [tier]: e.target.value as TierBehavior })
+            }
+            className="px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
+          >
+            <option value="auto_run">Run automatically</option>
+            <option value="notify">Run and notify</option>
+            <option value="require_confirmation">Require confirmation</option>
+          </select>
+        </div>
+      ))}
+
+      <div className="flex items-center justify-between gap-3">
+        <label className="text-sm">Confirmation timeout (seconds)</label>
+        <input
+          type="number"
+          min={10}
+          max={3600}
+          value={timeoutSecs}
+          onChange={(e) => setTimeoutSecs(e.target.value)}
+          onBlur={() => update({ confirmationTimeoutSecs: Number(timeoutSecs) })}
+          className="w-24 px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
+        />
+      </div>
+
+      {error && (
+        <div className="flex items-center gap-2 text-sm text-red-500">
+          <AlertCircle className="w-4 h-4" />
+          {error}
+        </div>
+      )}
     </div>
   );
 }
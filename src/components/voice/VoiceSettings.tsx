@@ -4,6 +4,7 @@
 
 import React from "react";
 import { useVoiceStore } from "../../stores/voiceStore";
+import type { TtsProvider } from "../../types/voice";
 
 export function VoiceSettings() {
   const { settings, updateSettings } = useVoiceStore();
@@ -74,6 +75,24 @@ export function VoiceSettings() {
         </select>
       </div>
 
+      {/* TTS Provider */}
+      <div className="p-3 border rounded-lg space-y-2">
+        <label className="block text-sm font-medium">Text-to-Speech Provider</label>
+        <select
+          value={settings.ttsProvider}
+          onChange={(e) => updateSettings({ ttsProvider: e.target.value as TtsProvider })}
+          disabled={!settings.enabled}
+          className="w-full px-3 py-2 border rounded text-sm bg-white dark:bg-gray-800"
+        >
+          <option value="local">Local (system voice)</option>
+          <option value="openai">OpenAI</option>
+          <option value="elevenlabs">ElevenLabs</option>
+        </select>
+        <p className="text-xs text-gray-400">
+          Cloud providers fall back to the system voice when offline
+        </p>
+      </div>
+
       {/* TTS Voice */}
       <div className="p-3 border rounded-lg space-y-2">
         <label className="block text-sm font-medium">Text-to-Speech Voice</label>
@@ -123,6 +142,48 @@ export function VoiceSettings() {
           <span>High</span>
         </div>
       </div>
+
+      {/* Barge-in */}
+      <div className="p-3 border rounded-lg space-y-3">
+        <div className="flex items-center justify-between">
+          <div>
+            <p className="font-medium">Interrupt by Speaking</p>
+            <p className="text-sm text-gray-500">Stop spoken replies when you start talking</p>
+          </div>
+          <input
+            type="checkbox"
+            checked={settings.bargeInEnabled}
+            onChange={(e) => updateSettings({ bargeInEnabled: e.target.checked })}
+            disabled={!settings.enabled}
+          />
+        </div>
+        <div className="space-y-2">
+          <label className="block text-sm font-medium">Interrupt Threshold</label>
+          <input
+            type="range"
+            min="0"
+            max="1"
+            step="0.1"
+            value={settings.bargeInSensitivity}
+            onChange={(e) => updateSettings({ bargeInSensitivity: parseFloat(e.target.value) })}
+            disabled={!settings.enabled || !settings.bargeInEnabled}
+            className="w-full"
+          />
+          <div className="flex justify-between text-xs text-gray-400">
+            <span>Quiet speech</span>
+            <span>Loud speech only</span>
+          </div>
+        </div>
+        <label className="flex items-center gap-2 text-sm">
+          <input
+            type="checkbox"
+            checked={settings.echoSuppression}
+            onChange={(e) => updateSettings({ echoSuppression: e.target.checked })}
+            disabled={!settings.enabled || !settings.bargeInEnabled}
+          />
+          Ignore the assistant's own voice from the speakers (echo suppression)
+        </label>
+      </div>
     </div>
   );
 }
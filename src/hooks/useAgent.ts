@@ -7,6 +7,8 @@ import { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useChatStore } from "../stores/chatStore";
 import { useSettingsStore } from "../stores/settingsStore";
+import type { Comparison } from "../types/chat";
+import { errorMessage } from "../types/error";
 
 export interface AgentMessage {
   role: "user" | "assistant" | "system";
@@ -25,6 +27,7 @@ export function useAgent() {
     (state) => state.activeConversationId,
   );
   const addMessage = useChatStore((state) => state.addMessage);
+  const addComparison = useChatStore((state) => state.addComparison);
   const setStreaming = useChatStore((state) => state.setStreaming);
 
   const providers = useSettingsStore((state) => state.providers);
@@ -51,8 +54,9 @@ export function useAgent() {
     }
   }, [initialized, syncProvidersToAgent]);
 
+  // `provider` answers this message instead of the active provider
   const sendMessage = useCallback(
-    async (content: string) => {
+    async (content: string, provider?: string) => {
       if (!activeConversationId) return;
 
       // Add user message
@@ -63,14 +67,17 @@ export function useAgent() {
 
       setStreaming(true);
 
+      const providerName = provider ?? activeProvider;
+
       try {
-        const providerConfig = providers[activeProvider];
+        const providerConfig = providers[providerName];
 
         // Try to use agent runtime via Tauri
         try {
           const response: ChatResponse = await invoke("agent_chat", {
             messages: [{ role: "user", content }],
-            provider: activeProvider,
+            provider: providerName,
+            conversationId: activeConversationId,
           });
 
           if (response.error) {
@@ -80,6 +87,7 @@ export function useAgent() {
           addMessage(activeConversationId, {
             role: "assistant",
             content: response.content,
+            provider: providerName,
           });
         } catch (tauriError) {
           // Fallback: simulate response for development
@@ -91,7 +99,7 @@ export function useAgent() {
             role: "assistant",
             content: generateFallbackResponse(
               content,
-              activeProvider,
+              providerName,
               providerConfig,
             ),
           });
@@ -99,7 +107,7 @@ export function useAgent() {
       } catch (error) {
         addMessage(activeConversationId, {
           role: "assistant",
-          content: `Error: ${error instanceof Error ? error.message : "Unknown error"}`,
+          content: `Error: ${errorMessage(error)}`,
         });
       } finally {
         setStreaming(false);
@@ -108,7 +116,42 @@ export function useAgent() {
     [activeConversationId, addMessage, setStreaming, providers, activeProvider],
   );
 
-  return { sendMessage, initialized };
+  // Send one message to several providers and keep their answers side by side
+  const compareMessage = useCallback(
+    async (content: string, compared: string[]) => {
+      if (!activeConversationId) return;
+
+      addMessage(activeConversationId, { role: "user", content });
+      setStreaming(true);
+
+      try {
+        const comparison = await invoke<Comparison>("compare_chat", {
+          messages: [{ role: "user", content }],
+          providers: compared,
+          conversationId: activeConversationId,
+        });
+
+        if (comparison.messageId) {
+          addComparison(activeConversationId, comparison);
+        } else {
+          const errors = Object.entries(comparison.results)
+            .map(([name, result]) => `${name}: ${result.error}`)
+            .join("\n");
+          addMessage(activeConversationId, { role: "assistant", content: `Error: every provider failed\n${errors}` });
+        }
+      } catch (error) {
+        addMessage(activeConversationId, {
+          role: "assistant",
+          content: `Error: ${errorMessage(error)}`,
+        });
+      } finally {
+        setStreaming(false);
+      }
+    },
+    [activeConversationId, addMessage, addComparison, setStreaming],
+  );
+
+  return { sendMessage, compareMessage, initialized };
 }
 
 // Fallback response generator for development
@@ -12,6 +12,7 @@ import {
   writeFileContent, 
   listDirectory
 } from '../services/tauri';
+import { errorMessage } from '../types/error';
 
 export interface FileEntry {
   name: string;
@@ -35,11 +36,11 @@ export function useFiles() {
       await checkFolderAccess(path, folderPermissions);
       
       // List contents
-      const entries = await listDirectory(path);
-      setFiles(entries);
+      const listing = await listDirectory(path);
+      setFiles(listing.entries.map((entry) => ({ name: entry.name, isDir: entry.kind === 'dir' })));
       setCurrentPath(path);
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
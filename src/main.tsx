@@ -1,9 +1,13 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import { QuickAskWindow } from "./components/quickask/QuickAskWindow";
+
+// The quick ask window loads the same bundle with ?window=quick-ask
+const isQuickAsk = new URLSearchParams(window.location.search).get("window") === "quick-ask";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {isQuickAsk ? <QuickAskWindow /> : <App />}
   </React.StrictMode>,
 );
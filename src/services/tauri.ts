@@ -4,6 +4,19 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import type { SidecarHealth } from '../types/agent';
+import type {
+  AttachmentText,
+  DirListing,
+  ExtractedDocument,
+  FileChunk,
+  FileStream,
+  ListOptions,
+  OcrResult,
+  OcrSettings,
+  SearchOptions,
+  SearchResult,
+} from '../types/permission';
 
 export interface FolderPermission {
   id: string;
@@ -52,10 +65,81 @@ export async function writeFileContent(path: string, content: string): Promise<v
 /**
  * List directory contents
  */
-export async function listDirectory(path: string): Promise<Array<{ name: string; isDir: boolean }>> {
-  const result: string[] = await invoke('list_directory', { path });
-  return result.map((entry) => {
-    const [name, type] = entry.split(':');
-    return { name, isDir: type === 'dir' };
-  });
+export async function listDirectory(path: string, options?: ListOptions): Promise<DirListing> {
+  return invoke('list_directory', { path, options });
+}
+
+/**
+ * Read a byte range of a file (base64)
+ */
+export async function readFileBytes(path: string, offset?: number, len?: number): Promise<FileChunk> {
+  return invoke('read_file_bytes', { path, offset, len });
+}
+
+/**
+ * Write base64 data at an offset, or replace the file; returns the new size
+ */
+export async function writeFileBytes(path: string, data: string, offset?: number): Promise<number> {
+  return invoke('write_file_bytes', { path, data, offset });
+}
+
+/**
+ * Stream a file as `files://chunk` events
+ */
+export async function streamFile(path: string, chunkSize?: number): Promise<FileStream> {
+  return invoke('stream_file', { path, chunkSize });
+}
+
+/**
+ * Regex search across a permitted folder
+ */
+export async function searchInFolder(
+  path: string,
+  pattern: string,
+  options?: SearchOptions
+): Promise<SearchResult> {
+  return invoke('search_in_folder', { path, pattern, options });
+}
+
+/**
+ * Text of a PDF, DOCX or XLSX file, by page or sheet
+ */
+export async function extractDocumentText(path: string): Promise<ExtractedDocument> {
+  return invoke('extract_document_text', { path });
+}
+
+/**
+ * Text of an image recognized by Tesseract
+ */
+export async function ocrImage(path: string, language?: string): Promise<OcrResult> {
+  return invoke('ocr_image', { path, language });
+}
+
+/**
+ * Text of a chat attachment; images are OCR'd when automatic OCR is enabled
+ */
+export async function readAttachment(path: string): Promise<AttachmentText> {
+  return invoke('read_attachment', { path });
+}
+
+export async function getOcrSettings(): Promise<OcrSettings> {
+  return invoke('get_ocr_settings');
+}
+
+export async function saveOcrSettings(settings: OcrSettings): Promise<void> {
+  return invoke('save_ocr_settings', { settings });
+}
+
+/**
+ * Installed Tesseract language packs
+ */
+export async function listOcrLanguages(): Promise<string[]> {
+  return invoke('list_ocr_languages');
+}
+
+/**
+ * Agent runtime health
+ */
+export async function getSidecarHealth(): Promise<SidecarHealth> {
+  return invoke('sidecar_health');
 }
@@ -19,7 +19,7 @@ export interface Conversation {
 }
 
 export interface ProviderConfig {
-  type: 'openai' | 'anthropic' | 'ollama';
+  type: 'openai' | 'anthropic' | 'ollama' | 'openai-compatible';
   apiKey?: string;
   baseUrl?: string;
   model: string;
@@ -18,6 +18,7 @@ import type {
   ImageAnalysis,
 } from '../types/agent';
 import { MessagePriority } from '../types/agent';
+import { errorMessage } from '../types/error';
 
 interface AgentState {
   // Context state
@@ -88,7 +89,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       // Refresh messages
       await get().getMessages();
     } catch (error) {
-      set({ error: String(error), contextLoading: false });
+      set({ error: errorMessage(error), contextLoading: false });
       throw error;
     }
   },
@@ -115,7 +116,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ messages, contextLoading: false });
       return messages;
     } catch (error) {
-      set({ error: String(error), contextLoading: false });
+      set({ error: errorMessage(error), contextLoading: false });
       throw error;
     }
   },
@@ -126,7 +127,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       await invoke('agent_context_clear');
       set({ messages: [], tokenCount: 0, contextLoading: false });
     } catch (error) {
-      set({ error: String(error), contextLoading: false });
+      set({ error: errorMessage(error), contextLoading: false });
       throw error;
     }
   },
@@ -137,7 +138,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ tokenCount: count });
       return count;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -148,7 +149,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ isNearLimit: near });
       return near;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -162,7 +163,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ contextLoading: false });
       return result;
     } catch (error) {
-      set({ error: String(error), contextLoading: false });
+      set({ error: errorMessage(error), contextLoading: false });
       throw error;
     }
   },
@@ -175,7 +176,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
         targetRatio,
       });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -193,7 +194,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ contextLoading: false });
       return result;
     } catch (error) {
-      set({ error: String(error), contextLoading: false });
+      set({ error: errorMessage(error), contextLoading: false });
       throw error;
     }
   },
@@ -208,7 +209,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ contextLoading: false });
       return result;
     } catch (error) {
-      set({ error: String(error), contextLoading: false });
+      set({ error: errorMessage(error), contextLoading: false });
       throw error;
     }
   },
@@ -237,7 +238,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       await get().getQueueLength();
       set({ orchestratorLoading: false });
     } catch (error) {
-      set({ error: String(error), orchestratorLoading: false });
+      set({ error: errorMessage(error), orchestratorLoading: false });
       throw error;
     }
   },
@@ -252,7 +253,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       await get().getQueueLength();
       return result;
     } catch (error) {
-      set({ error: String(error), orchestratorLoading: false });
+      set({ error: errorMessage(error), orchestratorLoading: false });
       throw error;
     }
   },
@@ -263,7 +264,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
       set({ queueLength: length });
       return length;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -272,7 +273,7 @@ export const useAgentStore = create<AgentState>((set, get) => ({
     try {
       await invoke('agent_orchestrator_clear_completed');
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -0,0 +1,78 @@
+/**
+ * Analytics Store - Zustand store for the usage dashboard
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  AnalyticsGranularity,
+  AnalyticsPeriod,
+  AnalyticsReport,
+  FeedbackExport,
+  ProviderPerformance,
+} from '../types/analytics';
+import { errorMessage } from '../types/error';
+
+interface AnalyticsState {
+  report: AnalyticsReport | null;
+  period: AnalyticsPeriod;
+  granularity: AnalyticsGranularity;
+  performance: ProviderPerformance[];
+  feedback: FeedbackExport | null;
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  loadAnalytics: (period?: AnalyticsPeriod, granularity?: AnalyticsGranularity) => Promise<void>;
+  loadPerformanceStats: (period?: AnalyticsPeriod) => Promise<void>;
+  exportFeedback: (period?: AnalyticsPeriod) => Promise<FeedbackExport | null>;
+  clearError: () => void;
+}
+
+export const useAnalyticsStore = create<AnalyticsState>((set, get) => ({
+  report: null,
+  period: '30d',
+  granularity: 'day',
+  performance: [],
+  feedback: null,
+  isLoading: false,
+  error: null,
+
+  loadAnalytics: async (period?: AnalyticsPeriod, granularity?: AnalyticsGranularity) => {
+    const next = {
+      period: period ?? get().period,
+      granularity: granularity ?? get().granularity,
+    };
+    set({ ...next, isLoading: true, error: null });
+    try {
+      const report = await invoke<AnalyticsReport>('get_analytics', next);
+      set({ report, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  loadPerformanceStats: async (period?: AnalyticsPeriod) => {
+    try {
+      const performance = await invoke<ProviderPerformance[]>('get_performance_stats', {
+        period: period ?? get().period,
+      });
+      set({ performance });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  exportFeedback: async (period?: AnalyticsPeriod) => {
+    try {
+      const feedback = await invoke<FeedbackExport>('export_feedback', { period: period ?? get().period });
+      set({ feedback });
+      return feedback;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      return null;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
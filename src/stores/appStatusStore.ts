@@ -0,0 +1,49 @@
+/**
+ * App Status Store - Zustand store for the scheduler and sync status shown in the tray
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import type { AppStatus } from '../types/appStatus';
+import { errorMessage } from '../types/error';
+
+let unlistenStatus: UnlistenFn | null = null;
+
+interface AppStatusState {
+  status: AppStatus | null;
+  error: string | null;
+
+  // Actions
+  loadStatus: () => Promise<void>;
+  subscribe: () => Promise<void>;
+  unsubscribe: () => void;
+}
+
+export const useAppStatusStore = create<AppStatusState>((set, get) => ({
+  status: null,
+  error: null,
+
+  loadStatus: async () => {
+    try {
+      const status = await invoke<AppStatus>('get_app_status');
+      set({ status, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  subscribe: async () => {
+    if (!unlistenStatus) {
+      unlistenStatus = await listen<AppStatus>('tray://status', (event) => {
+        set({ status: event.payload });
+      });
+    }
+    await get().loadStatus();
+  },
+
+  unsubscribe: () => {
+    unlistenStatus?.();
+    unlistenStatus = null;
+  },
+}));
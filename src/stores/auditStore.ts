@@ -0,0 +1,50 @@
+/**
+ * Audit Store - Zustand store for the trail of actions the agent initiated
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { ActionAuditEntry, AuditExportFormat, AuditFilter } from '../types/permission';
+import { errorMessage } from '../types/error';
+
+interface AuditState {
+  entries: ActionAuditEntry[];
+  filter: AuditFilter;
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  load: (filter?: AuditFilter) => Promise<void>;
+  /** Matching entries as a JSON or CSV document */
+  exportLog: (format: AuditExportFormat, filter?: AuditFilter) => Promise<string>;
+  clearError: () => void;
+}
+
+export const useAuditStore = create<AuditState>((set, get) => ({
+  entries: [],
+  filter: {},
+  isLoading: false,
+  error: null,
+
+  load: async (filter?: AuditFilter) => {
+    const next = filter ?? get().filter;
+    set({ filter: next, isLoading: true, error: null });
+    try {
+      const entries = await invoke<ActionAuditEntry[]>('query_audit_log', { filter: next });
+      set({ entries, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  exportLog: async (format: AuditExportFormat, filter?: AuditFilter) => {
+    try {
+      return await invoke<string>('export_audit_log', { filter: filter ?? get().filter, format });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
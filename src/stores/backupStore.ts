@@ -0,0 +1,146 @@
+/**
+ * Backup Store - Zustand store for database backups, restore, schema migrations, data export and health
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type {
+  BackupInfo,
+  DataExport,
+  DbHealth,
+  ExportProgress,
+  HealthReport,
+  MigrationReport,
+  RestoreResult,
+  SchemaVersion,
+} from '../types/backup';
+import { errorMessage } from '../types/error';
+
+interface BackupState {
+  backups: BackupInfo[];
+  retention: number;
+  isLoading: boolean;
+  error: string | null;
+  /** Progress of the running data export */
+  exportProgress: ExportProgress | null;
+  health: DbHealth | null;
+
+  // Actions
+  loadBackups: () => Promise<void>;
+  backupDatabase: (path?: string) => Promise<BackupInfo>;
+  restoreDatabase: (path: string) => Promise<RestoreResult>;
+  setRetention: (keep: number) => Promise<void>;
+  getSchemaVersion: () => Promise<SchemaVersion>;
+  migrateDatabase: (target?: number, dryRun?: boolean) => Promise<MigrationReport>;
+  exportAllData: (path: string) => Promise<DataExport>;
+  loadHealth: () => Promise<void>;
+  /** Keep `health` current with reports of the scheduled check that found problems */
+  watchHealth: () => Promise<UnlistenFn>;
+}
+
+export const useBackupStore = create<BackupState>((set, get) => ({
+  backups: [],
+  retention: 7,
+  isLoading: false,
+  error: null,
+  exportProgress: null,
+  health: null,
+
+  loadBackups: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const [backups, retention] = await Promise.all([
+        invoke<BackupInfo[]>('list_backups'),
+        invoke<number>('get_backup_retention'),
+      ]);
+      set({ backups, retention, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  backupDatabase: async (path?: string) => {
+    try {
+      const backup = await invoke<BackupInfo>('backup_database', { path: path ?? null });
+      await get().loadBackups();
+      return backup;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  restoreDatabase: async (path: string) => {
+    set({ isLoading: true, error: null });
+    try {
+      const result = await invoke<RestoreResult>('restore_database', { path });
+      await get().loadBackups();
+      return result;
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+      throw error;
+    }
+  },
+
+  setRetention: async (keep: number) => {
+    try {
+      await invoke('set_backup_retention', { keep });
+      set({ retention: keep });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  getSchemaVersion: async () => {
+    return invoke<SchemaVersion>('get_schema_version');
+  },
+
+  migrateDatabase: async (target?: number, dryRun?: boolean) => {
+    try {
+      const report = await invoke<MigrationReport>('migrate_database', {
+        target: target ?? null,
+        dryRun: dryRun ?? false,
+      });
+      if (report.backup) {
+        await get().loadBackups();
+      }
+      return report;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  exportAllData: async (path: string) => {
+    set({ exportProgress: null, error: null });
+    const unlisten = await listen<ExportProgress>('export://progress', (event) => {
+      set({ exportProgress: event.payload });
+    });
+    try {
+      return await invoke<DataExport>('export_all_data', { path });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    } finally {
+      unlisten();
+      set({ exportProgress: null });
+    }
+  },
+
+  loadHealth: async () => {
+    try {
+      const health = await invoke<DbHealth>('get_db_health');
+      set({ health });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  watchHealth: () =>
+    listen<HealthReport>('db://health', (event) => {
+      const health = get().health;
+      set({ health: { files: health?.files ?? { database: 0, wal: 0, shm: 0 }, report: event.payload } });
+    }),
+}));
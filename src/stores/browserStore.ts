@@ -3,6 +3,7 @@
  */
 
 import { create } from 'zustand';
+import { errorMessage } from '../types/error';
 
 export interface BrowserSettings {
   enabled: boolean;
@@ -63,7 +64,7 @@ export const useBrowserStore = create<BrowserState>((set, get) => ({
         set({ settings: DEFAULT_SETTINGS, loading: false });
       }
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
     }
   },
 
@@ -77,7 +78,7 @@ export const useBrowserStore = create<BrowserState>((set, get) => ({
 
       set({ settings: updatedSettings });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -0,0 +1,100 @@
+/**
+ * Calendar Store - Zustand store for calendar subscriptions and cached events
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { CalendarEvent, CalendarSource, CalendarSourceInput, EventRange } from '../types/calendar';
+import { errorMessage } from '../types/error';
+
+interface CalendarState {
+  sources: CalendarSource[];
+  events: CalendarEvent[];
+  isLoading: boolean;
+  isSyncing: boolean;
+  error: string | null;
+
+  // Actions
+  loadSources: () => Promise<void>;
+  saveSource: (input: CalendarSourceInput) => Promise<CalendarSource>;
+  deleteSource: (id: string) => Promise<void>;
+  sync: () => Promise<string>;
+  loadEvents: (range?: EventRange) => Promise<void>;
+  clearError: () => void;
+}
+
+export const useCalendarStore = create<CalendarState>((set, get) => ({
+  sources: [],
+  events: [],
+  isLoading: false,
+  isSyncing: false,
+  error: null,
+
+  loadSources: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const sources = await invoke<CalendarSource[]>('list_calendar_sources');
+      set({ sources, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  saveSource: async (input: CalendarSourceInput) => {
+    try {
+      const source = await invoke<CalendarSource>('save_calendar_source', {
+        id: input.id ?? null,
+        name: input.name,
+        kind: input.kind,
+        url: input.url,
+        username: input.username ?? null,
+        password: input.password ?? null,
+        enabled: input.enabled ?? null,
+      });
+      await get().loadSources();
+      return source;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteSource: async (id: string) => {
+    try {
+      await invoke('delete_calendar_source', { id });
+      set({
+        sources: get().sources.filter((s) => s.id !== id),
+        events: get().events.filter((e) => e.sourceId !== id),
+      });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  sync: async () => {
+    set({ isSyncing: true, error: null });
+    try {
+      return await invoke<string>('sync_calendars');
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    } finally {
+      set({ isSyncing: false });
+      // Pick up the recorded sync times or errors
+      await get().loadSources();
+    }
+  },
+
+  loadEvents: async (range?: EventRange) => {
+    set({ isLoading: true, error: null });
+    try {
+      const events = await invoke<CalendarEvent[]>('list_upcoming_events', { range: range ?? null });
+      set({ events, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -0,0 +1,82 @@
+/**
+ * Channel Store - Zustand store for outbound webhook notification channels
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { ChannelInput, MessageLevel, NotificationChannel } from '../types/channel';
+import { errorMessage } from '../types/error';
+
+interface ChannelState {
+  channels: NotificationChannel[];
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  loadChannels: () => Promise<void>;
+  saveChannel: (input: ChannelInput) => Promise<NotificationChannel>;
+  deleteChannel: (id: string) => Promise<void>;
+  sendMessage: (channelId: string, content: string, title?: string, level?: MessageLevel) => Promise<void>;
+  clearError: () => void;
+}
+
+export const useChannelStore = create<ChannelState>((set, get) => ({
+  channels: [],
+  isLoading: false,
+  error: null,
+
+  loadChannels: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const channels = await invoke<NotificationChannel[]>('list_notification_channels');
+      set({ channels, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  saveChannel: async (input: ChannelInput) => {
+    try {
+      const channel = await invoke<NotificationChannel>('save_notification_channel', {
+        id: input.id ?? null,
+        name: input.name,
+        kind: input.kind,
+        url: input.url ?? null,
+      });
+      await get().loadChannels();
+      return channel;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteChannel: async (id: string) => {
+    try {
+      await invoke('delete_notification_channel', { id });
+      set({ channels: get().channels.filter((c) => c.id !== id) });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  sendMessage: async (channelId: string, content: string, title?: string, level?: MessageLevel) => {
+    try {
+      await invoke('send_channel_message', {
+        channelId,
+        content,
+        title: title ?? null,
+        level: level ?? null,
+      });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    } finally {
+      // Pick up the recorded send time or error
+      await get().loadChannels();
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -4,6 +4,7 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import type { Comparison, ImportFormat, ImportReport, MessageMetadata } from '../types/chat';
 
 export interface Message {
   id: string;
@@ -11,6 +12,10 @@ export interface Message {
   content: string;
   timestamp: Date;
   isStreaming?: boolean;
+  /** Provider that answered, when known */
+  provider?: string;
+  /** Providers compared for this answer; see `selectComparedResponse` */
+  comparedProviders?: string[];
 }
 
 export interface Conversation {
@@ -34,9 +39,14 @@ interface ChatState {
   setActiveConversation: (id: string) => void;
   addMessage: (conversationId: string, message: Omit<Message, 'id' | 'timestamp'>) => string;
   updateMessage: (conversationId: string, messageId: string, content: string) => void;
+  /** Show a comparison the backend stored as a message */
+  addComparison: (conversationId: string, comparison: Comparison) => void;
+  /** Keep another provider's answer of a comparison message */
+  selectComparedResponse: (conversationId: string, messageId: string, provider: string) => Promise<void>;
   setStreaming: (streaming: boolean) => void;
   deleteConversation: (id: string) => void;
   clearMessages: (conversationId: string) => void;
+  importHistory: (format: ImportFormat, path: string) => Promise<ImportReport>;
 }
 
 // Load messages for a conversation from DB
@@ -51,18 +61,31 @@ async function loadMessagesFromDB(conversationId: string): Promise<Message[]> {
       created_at: string;
     }>>('load_messages', { conversationId });
 
-    return messages.map((msg) => ({
-      id: msg.id,
-      role: msg.role as 'user' | 'assistant' | 'system',
-      content: msg.content,
-      timestamp: new Date(msg.created_at),
-    }));
+    return messages.map((msg) => {
+      const metadata = parseMetadata(msg.metadata);
+      return {
+        id: msg.id,
+        role: msg.role as 'user' | 'assistant' | 'system',
+        content: msg.content,
+        timestamp: new Date(msg.created_at),
+        provider: metadata?.provider,
+        comparedProviders: metadata?.comparison ? Object.keys(metadata.comparison.results) : undefined,
+      };
+    });
   } catch (error) {
     console.error('Failed to load messages:', error);
     return [];
   }
 }
 
+function parseMetadata(metadata: string | null): MessageMetadata | null {
+  try {
+    return metadata ? (JSON.parse(metadata) as MessageMetadata) : null;
+  } catch {
+    return null;
+  }
+}
+
 export const useChatStore = create<ChatState>((set, get) => ({
   // Initial state
   conversations: [],
@@ -148,7 +171,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
       conversationId,
       role: message.role,
       content: message.content,
-      metadata: null,
+      metadata: message.provider ? JSON.stringify({ provider: message.provider }) : null,
     }).catch((error) => console.error('Failed to save message:', error));
 
     set((state) => ({
@@ -197,6 +220,46 @@ export const useChatStore = create<ChatState>((set, get) => ({
     }));
   },
 
+  // Add a comparison message stored by compare_chat
+  addComparison: (conversationId, comparison) => {
+    const { messageId, selected, results } = comparison;
+    if (!messageId || !selected) return;
+    const message: Message = {
+      id: messageId,
+      role: 'assistant',
+      content: results[selected].content,
+      timestamp: new Date(),
+      provider: selected,
+      comparedProviders: Object.keys(results),
+    };
+    set((state) => ({
+      conversations: state.conversations.map((conv) =>
+        conv.id === conversationId
+          ? { ...conv, messages: [...conv.messages, message], updatedAt: new Date() }
+          : conv
+      ),
+    }));
+  },
+
+  // Keep another answer of a comparison
+  selectComparedResponse: async (conversationId, messageId, provider) => {
+    const comparison = await invoke<Comparison>('select_compared_response', { messageId, provider });
+    set((state) => ({
+      conversations: state.conversations.map((conv) =>
+        conv.id === conversationId
+          ? {
+              ...conv,
+              messages: conv.messages.map((msg) =>
+                msg.id === messageId
+                  ? { ...msg, content: comparison.results[provider].content, provider }
+                  : msg
+              ),
+            }
+          : conv
+      ),
+    }));
+  },
+
   // Set streaming state
   setStreaming: (streaming) => {
     set({ isStreaming: streaming });
@@ -229,6 +292,19 @@ export const useChatStore = create<ChatState>((set, get) => ({
       ),
     }));
   },
+
+  // Import conversations exported from another tool
+  importHistory: async (format, path) => {
+    const report = await invoke<ImportReport>('import_external_history', { format, path });
+    if (report.imported > 0) {
+      const activeId = get().activeConversationId;
+      await get().loadConversations();
+      if (activeId && get().conversations.some((c) => c.id === activeId)) {
+        set({ activeConversationId: activeId });
+      }
+    }
+    return report;
+  },
 }));
 
 // Selector hooks
@@ -12,7 +12,14 @@ import {
   ConflictResolution,
   ImportResult,
   TemplateVersion,
-  TemplateShareRequest
+  TemplateDiff,
+  TemplateMerge,
+  BundleItemRef,
+  BundleManifest,
+  BundleConflictPolicy,
+  BundleImportResult,
+  TemplateShareRequest,
+  Visibility
 } from '../types/collaboration';
 
 interface CollaborationState {
@@ -37,11 +44,15 @@ interface CollaborationState {
   importTemplate: (data: Uint8Array, resolution: ConflictResolution) => Promise<Template>;
   importTemplates: (data: Uint8Array, resolution: ConflictResolution) => Promise<ImportResult>;
   validateTemplateData: (data: unknown) => Promise<boolean>;
+  exportBundle: (items: BundleItemRef[], path: string) => Promise<BundleManifest>;
+  importBundle: (path: string, conflictPolicy: BundleConflictPolicy) => Promise<BundleImportResult>;
 
   // Template Versioning (v0.5)
   getTemplateVersions: (id: string) => Promise<TemplateVersion[]>;
   createTemplateVersion: (id: string, notes: string) => Promise<number>;
   rollbackTemplate: (id: string, versionId: number) => Promise<void>;
+  diffTemplateVersions: (id: string, v1: number, v2: number) => Promise<TemplateDiff>;
+  mergeTemplateVersions: (id: string, base: number, mine: number, theirs: number) => Promise<TemplateMerge>;
 
   // Template Sharing (v0.5)
   shareTemplateToTeam: (request: TemplateShareRequest) => Promise<void>;
@@ -323,6 +334,63 @@ export const useCollaborationStore = create<CollaborationState>((set, get) => ({
     }
   },
 
+  exportBundle: async (items: BundleItemRef[], path: string) => {
+    const raw = await invoke<{
+      format_version: number;
+      app_version: string;
+      created_at: string;
+      items: Array<BundleItemRef & { name: string; schema_version: number; path: string; dependencies: BundleItemRef[] }>;
+    }>('export_bundle', { items, path });
+
+    return {
+      formatVersion: raw.format_version,
+      appVersion: raw.app_version,
+      createdAt: raw.created_at,
+      items: raw.items.map(i => ({
+        kind: i.kind,
+        id: i.id,
+        name: i.name,
+        schemaVersion: i.schema_version,
+        path: i.path,
+        dependencies: i.dependencies,
+      })),
+    };
+  },
+
+  importBundle: async (path: string, conflictPolicy: BundleConflictPolicy) => {
+    const raw = await invoke<{
+      format_version: number;
+      items: Array<BundleItemRef & {
+        name: string;
+        status: BundleImportResult['items'][number]['status'];
+        new_id: string | null;
+        message: string | null;
+      }>;
+      imported: number;
+      skipped: number;
+      failed: number;
+    }>('import_bundle', { path, conflictPolicy });
+
+    if (raw.imported > 0) {
+      await get().loadTemplates();
+    }
+
+    return {
+      formatVersion: raw.format_version,
+      items: raw.items.map(i => ({
+        kind: i.kind,
+        id: i.id,
+        name: i.name,
+        status: i.status,
+        newId: i.new_id ?? undefined,
+        message: i.message ?? undefined,
+      })),
+      imported: raw.imported,
+      skipped: raw.skipped,
+      failed: raw.failed,
+    };
+  },
+
   // Template Versioning (v0.5)
   getTemplateVersions: async (id: string) => {
     try {
@@ -378,6 +446,65 @@ export const useCollaborationStore = create<CollaborationState>((set, get) => ({
     }
   },
 
+  diffTemplateVersions: async (id: string, v1: number, v2: number) => {
+    const raw = await invoke<{
+      template_id: string;
+      from_version: number;
+      to_version: number;
+      lines: Array<{ kind: 'equal' | 'insert' | 'delete'; old_line: number | null; new_line: number | null; text: string }>;
+      fields: Array<{ field: string; old: string; new: string }>;
+      insertions: number;
+      deletions: number;
+    }>('diff_template_versions', { id, v1, v2 });
+
+    return {
+      templateId: raw.template_id,
+      fromVersion: raw.from_version,
+      toVersion: raw.to_version,
+      lines: raw.lines.map(l => ({
+        kind: l.kind,
+        oldLine: l.old_line ?? undefined,
+        newLine: l.new_line ?? undefined,
+        text: l.text,
+      })),
+      fields: raw.fields,
+      insertions: raw.insertions,
+      deletions: raw.deletions,
+    };
+  },
+
+  mergeTemplateVersions: async (id: string, base: number, mine: number, theirs: number) => {
+    const raw = await invoke<{
+      template: {
+        id: string;
+        name: string;
+        category: string;
+        content: string;
+        visibility: string;
+        version: string;
+        created_at: string;
+        updated_at: string;
+      };
+      conflicts: number;
+      conflicting_fields: string[];
+    }>('merge_template_versions', { id, base, mine, theirs });
+
+    return {
+      template: {
+        id: raw.template.id,
+        name: raw.template.name,
+        category: raw.template.category,
+        content: raw.template.content,
+        visibility: raw.template.visibility as Visibility,
+        version: raw.template.version,
+        createdAt: raw.template.created_at,
+        updatedAt: raw.template.updated_at,
+      },
+      conflicts: raw.conflicts,
+      conflictingFields: raw.conflicting_fields,
+    };
+  },
+
   // Template Sharing (v0.5)
   shareTemplateToTeam: async (request: TemplateShareRequest) => {
     try {
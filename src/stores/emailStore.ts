@@ -0,0 +1,60 @@
+/**
+ * Email Store - Zustand store for the SMTP settings used to deliver reports
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { SmtpConfig } from '../types/email';
+import { errorMessage } from '../types/error';
+
+interface EmailState {
+  settings: SmtpConfig | null;
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  loadSettings: () => Promise<void>;
+  saveSettings: (config: SmtpConfig) => Promise<void>;
+  testSettings: (config?: SmtpConfig, to?: string) => Promise<string>;
+  clearError: () => void;
+}
+
+export const useEmailStore = create<EmailState>((set) => ({
+  settings: null,
+  isLoading: false,
+  error: null,
+
+  loadSettings: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const settings = await invoke<SmtpConfig | null>('get_email_settings');
+      set({ settings, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  saveSettings: async (config: SmtpConfig) => {
+    try {
+      await invoke('save_email_settings', { config });
+      set({ settings: { ...config, password: null }, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  testSettings: async (config?: SmtpConfig, to?: string) => {
+    try {
+      return await invoke<string>('test_email_settings', {
+        config: config ?? null,
+        to: to ?? null,
+      });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
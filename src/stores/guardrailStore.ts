@@ -0,0 +1,105 @@
+/**
+ * Guardrail Store - Zustand store for guardrail settings, conversation overrides and detections
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { GuardrailEvent, GuardrailPolicy } from '../types/permission';
+import { errorMessage } from '../types/error';
+
+interface GuardrailState {
+  settings: GuardrailPolicy | null;
+  /** Overrides loaded so far, by conversation; null when it uses the global policy */
+  overrides: Record<string, GuardrailPolicy | null>;
+  events: GuardrailEvent[];
+  error: string | null;
+
+  // Actions
+  loadSettings: () => Promise<void>;
+  saveSettings: (settings: GuardrailPolicy) => Promise<void>;
+  loadOverride: (conversationId: string) => Promise<void>;
+  setOverride: (conversationId: string, policy: GuardrailPolicy) => Promise<void>;
+  clearOverride: (conversationId: string) => Promise<void>;
+  loadEvents: (conversationId?: string, limit?: number) => Promise<void>;
+  clearEvents: () => Promise<void>;
+  clearError: () => void;
+}
+
+export const useGuardrailStore = create<GuardrailState>((set) => ({
+  settings: null,
+  overrides: {},
+  events: [],
+  error: null,
+
+  loadSettings: async () => {
+    try {
+      const settings = await invoke<GuardrailPolicy>('get_guardrail_settings');
+      set({ settings, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  saveSettings: async (settings: GuardrailPolicy) => {
+    try {
+      await invoke('save_guardrail_settings', { settings });
+      set({ settings, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  loadOverride: async (conversationId: string) => {
+    try {
+      const policy = await invoke<GuardrailPolicy | null>('get_guardrail_override', { conversationId });
+      set((state) => ({ overrides: { ...state.overrides, [conversationId]: policy } }));
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  setOverride: async (conversationId: string, policy: GuardrailPolicy) => {
+    try {
+      await invoke('set_guardrail_override', { conversationId, policy });
+      set((state) => ({ overrides: { ...state.overrides, [conversationId]: policy } }));
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearOverride: async (conversationId: string) => {
+    try {
+      await invoke('clear_guardrail_override', { conversationId });
+      set((state) => ({ overrides: { ...state.overrides, [conversationId]: null } }));
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  loadEvents: async (conversationId?: string, limit?: number) => {
+    try {
+      const events = await invoke<GuardrailEvent[]>('get_guardrail_events', {
+        conversationId: conversationId ?? null,
+        limit: limit ?? null,
+      });
+      set({ events, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  clearEvents: async () => {
+    try {
+      await invoke('clear_guardrail_events');
+      set({ events: [] });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -0,0 +1,66 @@
+/**
+ * Log Store - Zustand store for the application log viewer
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { CommandRecord, LogEntry, LogExport, LogQuery } from '../types/logs';
+import { errorMessage } from '../types/error';
+
+interface LogState {
+  entries: LogEntry[];
+  query: LogQuery;
+  slowCommands: CommandRecord[];
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  queryLogs: (query?: LogQuery) => Promise<void>;
+  exportLogs: (path: string) => Promise<LogExport>;
+  loadSlowCommands: (thresholdMs: number) => Promise<void>;
+  clearError: () => void;
+}
+
+export const useLogStore = create<LogState>((set, get) => ({
+  entries: [],
+  query: { level: 'INFO' },
+  slowCommands: [],
+  isLoading: false,
+  error: null,
+
+  queryLogs: async (query?: LogQuery) => {
+    const next = query ?? get().query;
+    set({ query: next, isLoading: true, error: null });
+    try {
+      const entries = await invoke<LogEntry[]>('query_logs', {
+        filter: next.filter ?? null,
+        level: next.level ?? null,
+        since: next.since ?? null,
+        limit: next.limit ?? null,
+      });
+      set({ entries, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  exportLogs: async (path: string) => {
+    try {
+      return await invoke<LogExport>('export_logs', { path });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  loadSlowCommands: async (thresholdMs: number) => {
+    try {
+      const slowCommands = await invoke<CommandRecord[]>('get_slow_commands', { thresholdMs });
+      set({ slowCommands });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
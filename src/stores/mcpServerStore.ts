@@ -0,0 +1,68 @@
+/**
+ * MCP Server Store - Zustand store for the clients allowed to use the app as an MCP server
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { McpCapability, McpClient, McpLaunchConfig } from '../types/mcpServer';
+import { errorMessage } from '../types/error';
+
+interface McpServerState {
+  clients: McpClient[];
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  loadClients: () => Promise<void>;
+  saveClient: (name: string, capabilities: McpCapability[], enabled?: boolean) => Promise<McpClient>;
+  deleteClient: (id: string) => Promise<void>;
+  getLaunchConfig: (name: string) => Promise<McpLaunchConfig>;
+  clearError: () => void;
+}
+
+export const useMcpServerStore = create<McpServerState>((set, get) => ({
+  clients: [],
+  isLoading: false,
+  error: null,
+
+  loadClients: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const clients = await invoke<McpClient[]>('list_mcp_clients');
+      set({ clients, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  saveClient: async (name: string, capabilities: McpCapability[], enabled?: boolean) => {
+    try {
+      const client = await invoke<McpClient>('save_mcp_client', {
+        name,
+        capabilities,
+        enabled: enabled ?? null,
+      });
+      await get().loadClients();
+      return client;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteClient: async (id: string) => {
+    try {
+      await invoke('delete_mcp_client', { id });
+      set({ clients: get().clients.filter((c) => c.id !== id) });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  getLaunchConfig: async (name: string) => {
+    return invoke<McpLaunchConfig>('get_mcp_launch_config', { name });
+  },
+
+  clearError: () => set({ error: null }),
+}));
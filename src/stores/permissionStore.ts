@@ -2,13 +2,25 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { FolderPermission, PermissionLevel, FileNode } from '../types/permission';
-import { generateId, hasPermission } from '../types/permission';
+import type {
+  DirListing,
+  FolderPermission,
+  PermissionLevel,
+  FileNode,
+  ToolRiskSettings,
+} from '../types/permission';
+import {
+  DEFAULT_TOOL_RISK_SETTINGS,
+  generateId,
+  hasPermission,
+} from '../types/permission';
+import { errorMessage } from '../types/error';
 
 interface PermissionStore {
   // State
   permissions: FolderPermission[];
   fileTree: FileNode[];
+  riskSettings: ToolRiskSettings;
   isLoading: boolean;
   error: string | null;
 
@@ -19,6 +31,8 @@ interface PermissionStore {
   loadPermissions: () => Promise<void>;
   checkAccess: (path: string, requiredLevel: PermissionLevel) => boolean;
   loadDirectory: (path: string) => Promise<FileNode[]>;
+  loadRiskSettings: () => Promise<void>;
+  updateRiskSettings: (updates: Partial<ToolRiskSettings>) => Promise<void>;
   setLoading: (loading: boolean) => void;
   setError: (error: string | null) => void;
 }
@@ -27,6 +41,7 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
   // Initial State
   permissions: [],
   fileTree: [],
+  riskSettings: DEFAULT_TOOL_RISK_SETTINGS,
   isLoading: false,
   error: null,
 
@@ -55,7 +70,7 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
         isLoading: false,
       }));
     } catch (error) {
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
       throw error;
     }
   },
@@ -70,7 +85,7 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
         isLoading: false,
       }));
     } catch (error) {
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
       throw error;
     }
   },
@@ -87,7 +102,7 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
         isLoading: false,
       }));
     } catch (error) {
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
       throw error;
     }
   },
@@ -99,7 +114,7 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
       const permissions = await invoke<FolderPermission[]>('load_folder_permissions');
       set({ permissions: permissions || [], isLoading: false });
     } catch (error) {
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
     }
   },
 
@@ -119,16 +134,13 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
     }
 
     try {
-      const entries = await invoke<string[]>('list_directory', { path });
-      const nodes: FileNode[] = entries.map((entry) => {
-        const [name, type] = entry.split(':');
-        return {
-          name,
-          path: `${path}/${name}`,
-          type: type as 'file' | 'dir',
-          permission: checkAccess(`${path}/${name}`, 'readwrite') ? 'readwrite' : 'read',
-        };
-      });
+      const listing = await invoke<DirListing>('list_directory', { path });
+      const nodes: FileNode[] = listing.entries.map((entry) => ({
+        name: entry.name,
+        path: entry.path,
+        type: entry.kind === 'dir' ? 'dir' : 'file',
+        permission: checkAccess(entry.path, 'readwrite') ? 'readwrite' : 'read',
+      }));
 
       // Sort: directories first, then files
       nodes.sort((a, b) => {
@@ -138,7 +150,31 @@ export const usePermissionStore = create<PermissionStore>((set, get) => ({
 
       return nodes;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  // Load tool risk tier behaviors
+  loadRiskSettings: async () => {
+    try {
+      const riskSettings = await invoke<ToolRiskSettings>('tool_risk_settings_get');
+      set({ riskSettings });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  // Update tool risk tier behaviors
+  updateRiskSettings: async (updates) => {
+    set({ error: null });
+    try {
+      const riskSettings = await invoke<ToolRiskSettings>('tool_risk_settings_set', {
+        settings: { ...get().riskSettings, ...updates },
+      });
+      set({ riskSettings });
+    } catch (error) {
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -0,0 +1,91 @@
+/**
+ * Pin Store - Zustand store for pinned messages and bookmarks of the active conversation
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { Bookmark, PinnedMessage } from '../types/chat';
+import { errorMessage } from '../types/error';
+
+interface PinState {
+  conversationId: string | null;
+  pinned: PinnedMessage[];
+  bookmarks: Bookmark[];
+  error: string | null;
+
+  // Actions
+  load: (conversationId: string) => Promise<void>;
+  pin: (messageId: string, note?: string) => Promise<void>;
+  unpin: (messageId: string) => Promise<void>;
+  addBookmark: (messageId: string, label: string) => Promise<Bookmark>;
+  deleteBookmark: (id: string) => Promise<void>;
+  clearError: () => void;
+}
+
+export const usePinStore = create<PinState>((set, get) => ({
+  conversationId: null,
+  pinned: [],
+  bookmarks: [],
+  error: null,
+
+  load: async (conversationId: string) => {
+    set({ conversationId, error: null });
+    try {
+      const [pinned, bookmarks] = await Promise.all([
+        invoke<PinnedMessage[]>('list_pinned', { conversationId }),
+        invoke<Bookmark[]>('list_conversation_bookmarks', { conversationId }),
+      ]);
+      set({ pinned, bookmarks });
+      // Keep the pins in the agent's context through compression
+      await invoke('agent_context_retain_pinned', { conversationId });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  pin: async (messageId: string, note?: string) => {
+    try {
+      await invoke('pin_message', { messageId, note: note ?? null });
+      const { conversationId } = get();
+      if (conversationId) await get().load(conversationId);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  unpin: async (messageId: string) => {
+    try {
+      await invoke('unpin_message', { messageId });
+      const { conversationId } = get();
+      if (conversationId) await get().load(conversationId);
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  addBookmark: async (messageId: string, label: string) => {
+    try {
+      const bookmark = await invoke<Bookmark>('add_conversation_bookmark', { messageId, label });
+      const { conversationId } = get();
+      if (conversationId) await get().load(conversationId);
+      return bookmark;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteBookmark: async (id: string) => {
+    try {
+      await invoke('delete_conversation_bookmark', { id });
+      set({ bookmarks: get().bookmarks.filter((b) => b.id !== id) });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -4,10 +4,15 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import { Plugin, PluginManifest } from '../types/plugin';
+import { Plugin, PluginManifest, PluginPanel, PanelActionResult, ResourceSample } from '../types/plugin';
+import { errorMessage } from '../types/error';
 
 interface PluginState {
   plugins: Plugin[];
+  /** Panels of running plugins, by plugin id */
+  panels: Record<string, PluginPanel[]>;
+  /** Recent resource samples of running plugins, by plugin id */
+  resourceSamples: Record<string, ResourceSample[]>;
   isLoading: boolean;
   error: string | null;
 
@@ -18,11 +23,17 @@ interface PluginState {
   enablePlugin: (id: string) => Promise<void>;
   disablePlugin: (id: string) => Promise<void>;
   uninstallPlugin: (id: string) => Promise<void>;
+  loadPanels: (id: string) => Promise<void>;
+  panelAction: (id: string, panel: string, action: string, payload: unknown) => Promise<PanelActionResult>;
+  loadResourceSamples: (id: string) => Promise<void>;
+  resumePlugin: (id: string) => Promise<void>;
   clearError: () => void;
 }
 
 export const usePluginStore = create<PluginState>((set, get) => ({
   plugins: [],
+  panels: {},
+  resourceSamples: {},
   isLoading: false,
   error: null,
 
@@ -153,5 +164,46 @@ export const usePluginStore = create<PluginState>((set, get) => ({
     }
   },
 
+  loadPanels: async (id: string) => {
+    try {
+      const panels = await invoke<PluginPanel[]>('plugin_get_panels', { id });
+      set(state => ({ panels: { ...state.panels, [id]: panels } }));
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  panelAction: async (id: string, panel: string, action: string, payload: unknown) => {
+    const result = await invoke<PanelActionResult>('plugin_panel_action', { id, panel, action, payload });
+    const updated = result.panel;
+    if (updated) {
+      set(state => ({
+        panels: {
+          ...state.panels,
+          [id]: (state.panels[id] ?? []).map(p => (p.id === updated.id ? updated : p)),
+        },
+      }));
+    }
+    return result;
+  },
+
+  loadResourceSamples: async (id: string) => {
+    try {
+      const samples = await invoke<ResourceSample[]>('plugin_get_resource_samples', { id });
+      set(state => ({ resourceSamples: { ...state.resourceSamples, [id]: samples } }));
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  resumePlugin: async (id: string) => {
+    try {
+      await invoke('plugin_resume', { id });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
   clearError: () => set({ error: null }),
 }));
@@ -0,0 +1,82 @@
+/**
+ * Profile Store - Zustand store for local profiles, each with its own data store
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import type { Profile, ProfileList, ProfileRole } from '../types/profile';
+import { errorMessage } from '../types/error';
+
+let unlistenSwitched: UnlistenFn | null = null;
+
+interface ProfileState {
+  active: string | null;
+  profiles: Profile[];
+  switching: boolean;
+  error: string | null;
+
+  // Actions
+  loadProfiles: () => Promise<void>;
+  createProfile: (name: string, role?: ProfileRole) => Promise<Profile>;
+  switchProfile: (id: string) => Promise<void>;
+  subscribe: () => Promise<void>;
+  unsubscribe: () => void;
+  clearError: () => void;
+}
+
+export const useProfileStore = create<ProfileState>((set, get) => ({
+  active: null,
+  profiles: [],
+  switching: false,
+  error: null,
+
+  loadProfiles: async () => {
+    try {
+      const list = await invoke<ProfileList>('list_profiles');
+      set({ active: list.active, profiles: list.profiles, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  createProfile: async (name: string, role?: ProfileRole) => {
+    try {
+      const profile = await invoke<Profile>('create_profile', { name, role: role ?? null });
+      set((state) => ({ profiles: [...state.profiles, profile], error: null }));
+      return profile;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  switchProfile: async (id: string) => {
+    set({ switching: true, error: null });
+    try {
+      await invoke<Profile>('switch_profile', { id });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    } finally {
+      set({ switching: false });
+    }
+  },
+
+  subscribe: async () => {
+    if (!unlistenSwitched) {
+      // Every other store holds data of the previous profile, so start over
+      unlistenSwitched = await listen<Profile>('profiles://switched', () => {
+        window.location.reload();
+      });
+    }
+    await get().loadProfiles();
+  },
+
+  unsubscribe: () => {
+    unlistenSwitched?.();
+    unlistenSwitched = null;
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -0,0 +1,78 @@
+/**
+ * Provider Profile Store - Zustand store for typed provider profiles kept by the backend
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { EndpointProbe, ProviderProfile, ProviderProfileInput } from '../types/provider';
+import { errorMessage } from '../types/error';
+
+interface ProviderProfileState {
+  profiles: ProviderProfile[];
+  error: string | null;
+
+  // Actions
+  loadProfiles: () => Promise<void>;
+  /** Create or update a profile; rejects when the provider refuses the API key */
+  saveProfile: (profile: ProviderProfileInput) => Promise<ProviderProfile>;
+  deleteProfile: (id: string) => Promise<void>;
+  /** Live model list for a profile being edited */
+  fetchModels: (profile: ProviderProfileInput) => Promise<string[]>;
+  /** Health check of an OpenAI-compatible endpoint being edited */
+  probeEndpoint: (profile: ProviderProfileInput) => Promise<EndpointProbe>;
+  clearError: () => void;
+}
+
+export const useProviderProfileStore = create<ProviderProfileState>((set, get) => ({
+  profiles: [],
+  error: null,
+
+  loadProfiles: async () => {
+    try {
+      const profiles = await invoke<ProviderProfile[]>('list_provider_profiles');
+      set({ profiles, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  saveProfile: async (profile) => {
+    try {
+      const saved = await invoke<ProviderProfile>('upsert_provider_profile', { profile });
+      await get().loadProfiles();
+      return saved;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteProfile: async (id) => {
+    try {
+      await invoke('delete_provider_profile', { id });
+      set({ profiles: get().profiles.filter((p) => p.id !== id) });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  fetchModels: async (profile) => {
+    try {
+      return await invoke<string[]>('fetch_provider_models', { profile });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  probeEndpoint: async (profile) => {
+    try {
+      return await invoke<EndpointProbe>('probe_provider_endpoint', { profile });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -0,0 +1,92 @@
+/**
+ * Quick Ask Store - Zustand store for the global shortcut and quick-capture window
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { QuickAskReply, QuickAskTurn } from '../types/quickAsk';
+import { errorMessage } from '../types/error';
+
+interface QuickAskState {
+  shortcut: string | null;
+  turns: QuickAskTurn[];
+  isAsking: boolean;
+  error: string | null;
+
+  // Actions
+  loadShortcut: () => Promise<void>;
+  setShortcut: (shortcut: string) => Promise<void>;
+  ask: (prompt: string, provider?: string) => Promise<void>;
+  clear: () => Promise<void>;
+  open: () => Promise<void>;
+  hide: () => Promise<void>;
+  clearError: () => void;
+}
+
+export const useQuickAskStore = create<QuickAskState>((set, get) => ({
+  shortcut: null,
+  turns: [],
+  isAsking: false,
+  error: null,
+
+  loadShortcut: async () => {
+    try {
+      const shortcut = await invoke<string>('get_quick_ask_shortcut');
+      set({ shortcut });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  setShortcut: async (shortcut: string) => {
+    try {
+      const saved = await invoke<string>('set_quick_ask_shortcut', { shortcut });
+      set({ shortcut: saved, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  ask: async (prompt: string, provider?: string) => {
+    const index = get().turns.length;
+    set({
+      turns: [...get().turns, { prompt, reply: null, error: null }],
+      isAsking: true,
+    });
+    const update = (turn: Partial<QuickAskTurn>) =>
+      set({
+        turns: get().turns.map((t, i) => (i === index ? { ...t, ...turn } : t)),
+      });
+    try {
+      const reply = await invoke<QuickAskReply>('quick_ask', {
+        prompt,
+        provider: provider ?? null,
+      });
+      update({ reply: reply.content });
+    } catch (error) {
+      update({ error: errorMessage(error) });
+    } finally {
+      set({ isAsking: false });
+    }
+  },
+
+  clear: async () => {
+    try {
+      await invoke('clear_quick_ask');
+      set({ turns: [] });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  open: async () => {
+    await invoke('open_quick_ask');
+  },
+
+  hide: async () => {
+    await invoke('hide_quick_ask');
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -5,12 +5,15 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
 import type { Recipe, RecipeExecution, RecipeCreateInput, RecipeUpdateInput, RecipeStep } from '../types/recipe';
+import { errorMessage } from '../types/error';
 
 interface RecipeState {
   recipes: Recipe[];
   executions: RecipeExecution[];
   loading: boolean;
   executing: boolean;
+  /** Draft recipe to open in the editor */
+  draftId: string | null;
   error: string | null;
 
   // Actions
@@ -19,6 +22,9 @@ interface RecipeState {
   updateRecipe: (recipe: RecipeUpdateInput) => Promise<void>;
   deleteRecipe: (id: string) => Promise<void>;
   getRecipe: (id: string) => Recipe | undefined;
+  /** Turn a conversation into a draft recipe and open it for editing */
+  extractFromConversation: (conversationId: string) => Promise<string>;
+  closeDraft: () => void;
 
   // Execution
   loadExecutions: (recipeId?: string) => Promise<void>;
@@ -30,6 +36,7 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
   executions: [],
   loading: false,
   executing: false,
+  draftId: null,
   error: null,
 
   loadRecipes: async () => {
@@ -43,6 +50,7 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
         steps: string;
         variables: string | null;
         is_builtin: number;
+        is_draft: boolean;
         created_at: string;
         updated_at: string;
       }>>('list_recipes');
@@ -55,13 +63,14 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
         steps: JSON.parse(r.steps || '[]'),
         variables: r.variables ? JSON.parse(r.variables) : undefined,
         is_builtin: r.is_builtin !== 0,
+        is_draft: r.is_draft,
         created_at: r.created_at,
         updated_at: r.updated_at,
       }));
 
       set({ recipes, loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
     }
   },
 
@@ -78,7 +87,7 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
 
       await get().loadRecipes();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -96,7 +105,7 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
 
       await get().loadRecipes();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -109,7 +118,7 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
         recipes: state.recipes.filter((r) => r.id !== id),
       }));
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -118,6 +127,21 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
     return get().recipes.find((r) => r.id === id);
   },
 
+  extractFromConversation: async (conversationId: string) => {
+    set({ loading: true, error: null });
+    try {
+      const recipe = await invoke<{ id: string }>('extract_recipe_from_conversation', { conversationId });
+      await get().loadRecipes();
+      set({ draftId: recipe.id, loading: false });
+      return recipe.id;
+    } catch (error) {
+      set({ error: errorMessage(error), loading: false });
+      throw error;
+    }
+  },
+
+  closeDraft: () => set({ draftId: null }),
+
   loadExecutions: async (recipeId?: string) => {
     try {
       const rawExecutions = await invoke<Array<{
@@ -144,7 +168,7 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
 
       set({ executions });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -216,13 +240,13 @@ export const useRecipeStore = create<RecipeState>((set, get) => ({
           id: executionId,
           status: 'failed',
           result: null,
-          error: String(error),
+          error: errorMessage(error),
         });
       } catch {
         // Ignore update errors
       }
 
-      set({ error: String(error), executing: false });
+      set({ error: errorMessage(error), executing: false });
       throw error;
     } finally {
       set({ executing: false });
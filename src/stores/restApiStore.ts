@@ -0,0 +1,71 @@
+/**
+ * REST API Store - Zustand store for the local REST API settings and token
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { ApiConfig, ApiStatus } from '../types/restApi';
+import { errorMessage } from '../types/error';
+
+interface RestApiState {
+  config: ApiConfig | null;
+  status: ApiStatus | null;
+  /** Newly generated token; it cannot be read back later */
+  newToken: string | null;
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  load: () => Promise<void>;
+  saveConfig: (config: ApiConfig) => Promise<void>;
+  regenerateToken: () => Promise<string>;
+  dismissToken: () => void;
+  clearError: () => void;
+}
+
+export const useRestApiStore = create<RestApiState>((set) => ({
+  config: null,
+  status: null,
+  newToken: null,
+  isLoading: false,
+  error: null,
+
+  load: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const [config, status] = await Promise.all([
+        invoke<ApiConfig>('get_api_config'),
+        invoke<ApiStatus>('get_api_status'),
+      ]);
+      set({ config, status, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  saveConfig: async (config: ApiConfig) => {
+    try {
+      const status = await invoke<ApiStatus>('set_api_config', { config });
+      set({ config, status, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  regenerateToken: async () => {
+    try {
+      const token = await invoke<string>('regenerate_api_token');
+      const status = await invoke<ApiStatus>('get_api_status');
+      set({ newToken: token, status, error: null });
+      return token;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  dismissToken: () => set({ newToken: null }),
+
+  clearError: () => set({ error: null }),
+}));
@@ -0,0 +1,60 @@
+/**
+ * Retention Store - Zustand store for data retention policies
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { RetentionImpact, RetentionPolicy } from '../types/retention';
+import { errorMessage } from '../types/error';
+
+interface RetentionState {
+  policies: RetentionPolicy[];
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  loadPolicies: () => Promise<void>;
+  savePolicies: (policies: RetentionPolicy[]) => Promise<void>;
+  /** What the stored rules, or the given ones, would delete now */
+  previewImpact: (policies?: RetentionPolicy[]) => Promise<RetentionImpact[]>;
+  applyNow: () => Promise<RetentionImpact[]>;
+}
+
+export const useRetentionStore = create<RetentionState>((set) => ({
+  policies: [],
+  isLoading: false,
+  error: null,
+
+  loadPolicies: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const policies = await invoke<RetentionPolicy[]>('get_retention_policies');
+      set({ policies, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  savePolicies: async (policies: RetentionPolicy[]) => {
+    try {
+      const saved = await invoke<RetentionPolicy[]>('set_retention_policies', { policies });
+      set({ policies: saved });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  previewImpact: async (policies?: RetentionPolicy[]) => {
+    return invoke<RetentionImpact[]>('preview_retention_impact', { policies: policies ?? null });
+  },
+
+  applyNow: async () => {
+    try {
+      return await invoke<RetentionImpact[]>('apply_retention_now');
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+}));
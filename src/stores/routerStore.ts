@@ -4,6 +4,7 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../types/error';
 
 export type TaskType = 'coding' | 'creative' | 'analysis' | 'chat' | 'research' | 'planning';
 
@@ -106,7 +107,7 @@ export const useRouterStore = create<RouterState>((set, get) => ({
       rules.sort((a, b) => b.priority - a.priority);
       set({ rules });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -133,7 +134,7 @@ export const useRouterStore = create<RouterState>((set, get) => ({
       rules.sort((a, b) => b.priority - a.priority);
       set({ rules });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -146,7 +147,7 @@ export const useRouterStore = create<RouterState>((set, get) => ({
         rules: state.rules.filter(r => r.id !== id),
       }));
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
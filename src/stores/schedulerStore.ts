@@ -4,11 +4,18 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { CronJob, JobExecution, JobCreateInput, JobUpdateInput, JobType } from '../types/scheduler';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import type { CronJob, ExecutionLogLine, JobExecution, JobCreateInput, JobUpdateInput, JobStatus, JobType, OverlapPolicy, ParsedSchedule } from '../types/scheduler';
+import { errorMessage } from '../types/error';
+
+/** Log listeners of the executions being tailed */
+const logListeners = new Map<string, UnlistenFn>();
 
 interface SchedulerState {
   jobs: CronJob[];
   executions: JobExecution[];
+  /** Log lines of tailed executions, by execution id */
+  logs: Record<string, ExecutionLogLine[]>;
   loading: boolean;
   error: string | null;
 
@@ -19,12 +26,22 @@ interface SchedulerState {
   deleteJob: (id: string) => Promise<void>;
   toggleJob: (id: string) => Promise<void>;
   runJobNow: (id: string) => Promise<string>;
+  pauseJob: (id: string) => Promise<void>;
+  snoozeJob: (id: string, until: Date) => Promise<void>;
+  resumeJob: (id: string) => Promise<void>;
   loadExecutions: (jobId?: string) => Promise<void>;
+  tailLogs: (executionId: string) => Promise<void>;
+  stopTailing: (executionId: string) => void;
+  pruneHistory: (keep?: number) => Promise<number>;
+  getHistoryRetention: () => Promise<number>;
+  setHistoryRetention: (keep: number) => Promise<void>;
+  parseSchedule: (text: string) => Promise<ParsedSchedule>;
 }
 
 export const useSchedulerStore = create<SchedulerState>((set, get) => ({
   jobs: [],
   executions: [],
+  logs: {},
   loading: false,
   error: null,
 
@@ -42,6 +59,13 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
         next_run: string | null;
         created_at: string;
         updated_at: string;
+        plugin_id: string | null;
+        allow_overlap: string;
+        priority: number;
+        paused: boolean;
+        snoozed_until: string | null;
+        status: string;
+        skipped_runs: number;
       }>>('list_cron_jobs');
 
       const jobs: CronJob[] = rawJobs.map((j) => ({
@@ -55,11 +79,18 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
         nextRun: j.next_run || undefined,
         createdAt: j.created_at,
         updatedAt: j.updated_at,
+        pluginId: j.plugin_id || undefined,
+        allowOverlap: j.allow_overlap as OverlapPolicy,
+        priority: j.priority,
+        status: j.status as JobStatus,
+        paused: j.paused,
+        snoozedUntil: j.snoozed_until || undefined,
+        skippedRuns: j.skipped_runs,
       }));
 
       set({ jobs, loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
     }
   },
 
@@ -72,11 +103,13 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
         jobType: input.jobType,
         config: JSON.stringify(input.config),
         enabled: input.enabled !== false ? 1 : 0,
+        allowOverlap: input.allowOverlap ?? null,
+        priority: input.priority ?? null,
       });
 
       await get().loadJobs();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -89,11 +122,13 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
         schedule: input.schedule,
         config: input.config ? JSON.stringify(input.config) : null,
         enabled: input.enabled !== undefined ? (input.enabled ? 1 : 0) : null,
+        allowOverlap: input.allowOverlap ?? null,
+        priority: input.priority ?? null,
       });
 
       await get().loadJobs();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -106,7 +141,7 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
         jobs: state.jobs.filter((j) => j.id !== id),
       }));
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -123,6 +158,36 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
     return executionId;
   },
 
+  pauseJob: async (id: string) => {
+    try {
+      await invoke('pause_job', { id });
+      await get().loadJobs();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  snoozeJob: async (id: string, until: Date) => {
+    try {
+      await invoke('snooze_job', { id, until: until.toISOString() });
+      await get().loadJobs();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  resumeJob: async (id: string) => {
+    try {
+      await invoke('resume_job', { id });
+      await get().loadJobs();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
   loadExecutions: async (jobId?: string) => {
     try {
       const rawExecutions = await invoke<Array<{
@@ -147,7 +212,61 @@ export const useSchedulerStore = create<SchedulerState>((set, get) => ({
 
       set({ executions });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  tailLogs: async (executionId: string) => {
+    // Events and catch-up pages can arrive in any order, so merge by seq
+    const merge = (lines: ExecutionLogLine[]) =>
+      set((state) => {
+        const bySeq = new Map((state.logs[executionId] ?? []).map((line) => [line.seq, line]));
+        lines.forEach((line) => bySeq.set(line.seq, line));
+        const merged = [...bySeq.values()].sort((a, b) => a.seq - b.seq);
+        return { logs: { ...state.logs, [executionId]: merged } };
+      });
+
+    if (!logListeners.has(executionId)) {
+      const unlisten = await listen<ExecutionLogLine>(`job://log/${executionId}`, (event) => {
+        merge([event.payload]);
+      });
+      logListeners.set(executionId, unlisten);
+    }
+
+    // Catch up on lines logged before the listener was attached
+    try {
+      let afterSeq = 0;
+      let lines: ExecutionLogLine[];
+      do {
+        lines = await invoke<ExecutionLogLine[]>('get_execution_logs', { executionId, afterSeq });
+        merge(lines);
+        afterSeq = lines.length > 0 ? lines[lines.length - 1].seq : afterSeq;
+      } while (lines.length > 0);
+    } catch (error) {
+      set({ error: errorMessage(error) });
     }
   },
+
+  stopTailing: (executionId: string) => {
+    logListeners.get(executionId)?.();
+    logListeners.delete(executionId);
+  },
+
+  pruneHistory: async (keep?: number) => {
+    const deleted = await invoke<number>('prune_job_history', { keep: keep ?? null });
+    await get().loadExecutions();
+    return deleted;
+  },
+
+  getHistoryRetention: async () => {
+    return invoke<number>('get_job_history_retention');
+  },
+
+  setHistoryRetention: async (keep: number) => {
+    await invoke('set_job_history_retention', { keep });
+  },
+
+  parseSchedule: async (text: string) => {
+    return invoke<ParsedSchedule>('parse_schedule', { text });
+  },
 }));
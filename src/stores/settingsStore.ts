@@ -7,7 +7,7 @@ import { persist } from "zustand/middleware";
 import { invoke } from "@tauri-apps/api/core";
 
 export interface ProviderConfig {
-  type: "openai" | "anthropic" | "ollama";
+  type: "openai" | "anthropic" | "ollama" | "openai-compatible";
   apiKey?: string;
   baseUrl?: string;
   model: string;
@@ -63,6 +63,12 @@ const DEFAULT_PROVIDERS: Record<string, ProviderConfig> = {
     model: "llama3.2",
     enabled: false,
   },
+  "openai-compatible": {
+    type: "openai-compatible",
+    baseUrl: "http://localhost:1234/v1",
+    model: "",
+    enabled: false,
+  },
 };
 
 export const useSettingsStore = create<SettingsState>()(
@@ -172,6 +178,15 @@ export const useSettingsStore = create<SettingsState>()(
         activeProvider: state.activeProvider,
         theme: state.theme,
       }),
+      // Providers added since the settings were saved get their defaults
+      merge: (persisted, current) => {
+        const saved = persisted as Partial<SettingsState>;
+        return {
+          ...current,
+          ...saved,
+          providers: { ...DEFAULT_PROVIDERS, ...saved.providers },
+        };
+      },
     },
   ),
 );
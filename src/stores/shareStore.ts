@@ -0,0 +1,70 @@
+/**
+ * Share Store - Zustand store for redacted conversation bundles and the share history
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { ConversationShare, ShareOptions, ShareResult } from '../types/chat';
+import { errorMessage } from '../types/error';
+
+interface ShareState {
+  shares: ConversationShare[];
+  error: string | null;
+
+  // Actions
+  loadShares: (conversationId?: string) => Promise<void>;
+  share: (conversationId: string, options: ShareOptions) => Promise<ShareResult>;
+  revoke: (id: string) => Promise<void>;
+  purgeExpired: () => Promise<number>;
+  clearError: () => void;
+}
+
+export const useShareStore = create<ShareState>((set, get) => ({
+  shares: [],
+  error: null,
+
+  loadShares: async (conversationId?: string) => {
+    try {
+      const shares = await invoke<ConversationShare[]>('list_conversation_shares', {
+        conversationId: conversationId ?? null,
+      });
+      set({ shares, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  share: async (conversationId: string, options: ShareOptions) => {
+    try {
+      const result = await invoke<ShareResult>('share_conversation', { id: conversationId, options });
+      set((state) => ({ shares: [result.share, ...state.shares], error: null }));
+      return result;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  revoke: async (id: string) => {
+    try {
+      await invoke('revoke_conversation_share', { id });
+      await get().loadShares();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  purgeExpired: async () => {
+    try {
+      const purged = await invoke<number>('purge_expired_shares');
+      if (purged > 0) await get().loadShares();
+      return purged;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -0,0 +1,57 @@
+/**
+ * Similar Store - Zustand store for past conversations related to the current question
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { SimilarConversation } from '../types/chat';
+import { errorMessage } from '../types/error';
+
+interface SimilarState {
+  suggestions: SimilarConversation[];
+  error: string | null;
+
+  // Actions
+  /** Suggest conversations related to a draft question */
+  suggestForText: (text: string, topK?: number) => Promise<void>;
+  /** Suggest conversations related to an existing conversation */
+  suggestForConversation: (conversationId: string, topK?: number) => Promise<void>;
+  clear: () => void;
+}
+
+export const useSimilarStore = create<SimilarState>((set) => ({
+  suggestions: [],
+  error: null,
+
+  suggestForText: async (text: string, topK?: number) => {
+    if (!text.trim()) {
+      set({ suggestions: [] });
+      return;
+    }
+    try {
+      const suggestions = await invoke<SimilarConversation[]>('find_similar_conversations', {
+        id: null,
+        text,
+        topK: topK ?? null,
+      });
+      set({ suggestions, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  suggestForConversation: async (conversationId: string, topK?: number) => {
+    try {
+      const suggestions = await invoke<SimilarConversation[]>('find_similar_conversations', {
+        id: conversationId,
+        text: null,
+        topK: topK ?? null,
+      });
+      set({ suggestions, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  clear: () => set({ suggestions: [], error: null }),
+}));
@@ -5,6 +5,7 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
 import type { Skill, SkillCreateInput, SkillUpdateInput } from '../types/skill';
+import { errorMessage } from '../types/error';
 
 interface SkillState {
   skills: Skill[];
@@ -14,10 +15,45 @@ interface SkillState {
   // Actions
   loadSkills: () => Promise<void>;
   createSkill: (skill: SkillCreateInput) => Promise<void>;
+  /** Create or replace many skills at once; all or none are stored */
+  importSkills: (skills: SkillCreateInput[]) => Promise<SkillImport>;
   updateSkill: (skill: SkillUpdateInput) => Promise<void>;
   deleteSkill: (id: string) => Promise<void>;
   searchSkills: (query: string) => Promise<Skill[]>;
   getSkill: (id: string) => Skill | undefined;
+  fetchSkill: (id: string) => Promise<Skill>;
+}
+
+/** Skills created and replaced by an import */
+export interface SkillImport {
+  created: number;
+  updated: number;
+}
+
+interface RawSkill {
+  id: string;
+  name: string;
+  description: string;
+  prompt: string;
+  tools: string;
+  input_schema: string | null;
+  output_schema: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+function toSkill(s: RawSkill): Skill {
+  return {
+    id: s.id,
+    name: s.name,
+    description: s.description,
+    prompt: s.prompt,
+    tools: JSON.parse(s.tools || '[]'),
+    inputSchema: s.input_schema ? JSON.parse(s.input_schema) : undefined,
+    outputSchema: s.output_schema ? JSON.parse(s.output_schema) : undefined,
+    created_at: s.created_at,
+    updated_at: s.updated_at,
+  };
 }
 
 export const useSkillStore = create<SkillState>((set, get) => ({
@@ -28,24 +64,13 @@ export const useSkillStore = create<SkillState>((set, get) => ({
   loadSkills: async () => {
     set({ loading: true, error: null });
     try {
-      const rawSkills = await invoke<Array<{
-        id: string;
-        name: string;
-        description: string;
-        prompt: string;
-        tools: string;
-        created_at: string;
-        updated_at: string;
-      }>>('list_skills');
-
-      const skills: Skill[] = rawSkills.map((s) => ({
-        ...s,
-        tools: JSON.parse(s.tools || '[]'),
-      }));
+      const rawSkills = await invoke<RawSkill[]>('list_skills');
+
+      const skills: Skill[] = rawSkills.map(toSkill);
 
       set({ skills, loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
     }
   },
 
@@ -57,12 +82,37 @@ export const useSkillStore = create<SkillState>((set, get) => ({
         description: input.description,
         prompt: input.prompt,
         tools: JSON.stringify(input.tools),
+        inputSchema: input.inputSchema ? JSON.stringify(input.inputSchema) : null,
+        outputSchema: input.outputSchema ? JSON.stringify(input.outputSchema) : null,
+      });
+
+      // Reload skills
+      await get().loadSkills();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  importSkills: async (inputs: SkillCreateInput[]) => {
+    try {
+      const result = await invoke<SkillImport>('import_skills', {
+        skills: inputs.map((input) => ({
+          id: input.id,
+          name: input.name,
+          description: input.description,
+          prompt: input.prompt,
+          tools: JSON.stringify(input.tools),
+          input_schema: input.inputSchema ? JSON.stringify(input.inputSchema) : null,
+          output_schema: input.outputSchema ? JSON.stringify(input.outputSchema) : null,
+        })),
       });
 
       // Reload skills
       await get().loadSkills();
+      return result;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -75,12 +125,14 @@ export const useSkillStore = create<SkillState>((set, get) => ({
         description: input.description,
         prompt: input.prompt,
         tools: JSON.stringify(input.tools),
+        inputSchema: input.inputSchema ? JSON.stringify(input.inputSchema) : null,
+        outputSchema: input.outputSchema ? JSON.stringify(input.outputSchema) : null,
       });
 
       // Reload skills
       await get().loadSkills();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -93,29 +145,18 @@ export const useSkillStore = create<SkillState>((set, get) => ({
         skills: state.skills.filter((s) => s.id !== id),
       }));
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
 
   searchSkills: async (query: string) => {
     try {
-      const rawSkills = await invoke<Array<{
-        id: string;
-        name: string;
-        description: string;
-        prompt: string;
-        tools: string;
-        created_at: string;
-        updated_at: string;
-      }>>('search_skills', { query });
-
-      return rawSkills.map((s) => ({
-        ...s,
-        tools: JSON.parse(s.tools || '[]'),
-      }));
+      const rawSkills = await invoke<RawSkill[]>('search_skills', { query });
+
+      return rawSkills.map(toSkill);
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       return [];
     }
   },
@@ -123,4 +164,8 @@ export const useSkillStore = create<SkillState>((set, get) => ({
   getSkill: (id: string) => {
     return get().skills.find((s) => s.id === id);
   },
+
+  fetchSkill: async (id: string) => {
+    return toSkill(await invoke<RawSkill>('get_skill', { id }));
+  },
 }));
@@ -5,6 +5,7 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
 import type { SubAgent, SubAgentCreateInput, SubAgentUpdateInput, SubAgentType } from '../types/subagent';
+import { errorMessage } from '../types/error';
 
 interface SubAgentState {
   agents: SubAgent[];
@@ -60,7 +61,7 @@ export const useSubAgentStore = create<SubAgentState>((set, get) => ({
 
       set({ agents, loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
     }
   },
 
@@ -77,7 +78,7 @@ export const useSubAgentStore = create<SubAgentState>((set, get) => ({
 
       await get().loadAgents();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -98,7 +99,7 @@ export const useSubAgentStore = create<SubAgentState>((set, get) => ({
 
       await get().loadAgents();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -111,7 +112,7 @@ export const useSubAgentStore = create<SubAgentState>((set, get) => ({
         agents: state.agents.filter((a) => a.id !== id),
       }));
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -125,7 +126,7 @@ export const useSubAgentStore = create<SubAgentState>((set, get) => ({
       await invoke('assign_sub_agent_task', { id, task });
       await get().loadAgents();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -15,6 +15,7 @@ import type {
   ConflictResolution,
   PendingOperation,
 } from '../types/sync';
+import { errorMessage } from '../types/error';
 
 interface SyncState {
   // State
@@ -99,7 +100,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
 
       return result;
     } catch (error) {
-      set({ error: String(error), syncing: false });
+      set({ error: errorMessage(error), syncing: false });
       throw error;
     }
   },
@@ -112,7 +113,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       await get().getPendingCount();
       await get().checkNeedsSync();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -125,7 +126,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       await get().getPendingCount();
       await get().checkNeedsSync();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -138,7 +139,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       await get().getPendingCount();
       await get().checkNeedsSync();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -149,7 +150,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       set({ pendingCount: count });
       return count;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -160,7 +161,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       set({ needsSync: needs });
       return needs;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -171,7 +172,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
 
       set({ pendingCount: 0, needsSync: false });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -180,7 +181,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
     try {
       await invoke('sync_set_conflict_strategy', { strategy });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -201,7 +202,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       });
       return conflict;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -227,7 +228,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       });
       return result;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -252,7 +253,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       // Refresh queue
       await get().getQueueLength();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -272,7 +273,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
 
       return operation;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -282,7 +283,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       const operation = await invoke<PendingOperation | null>('sync_offline_peek');
       return operation;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -294,7 +295,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       // Refresh failed operations
       await get().getFailedOperations();
     } catch (err) {
-      set({ error: String(err) });
+      set({ error: errorMessage(err) });
       throw err;
     }
   },
@@ -304,7 +305,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       const length = await invoke<number>('sync_offline_length');
       return length;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -314,7 +315,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       await invoke('sync_offline_clear');
       set({ offlineQueue: [] });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -325,7 +326,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       set({ failedOperations: failed });
       return failed;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -335,7 +336,7 @@ export const useSyncStore = create<SyncState>((set, get) => ({
       const operations = await invoke<PendingOperation[]>('sync_offline_get_by_entity', { entity });
       return operations;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
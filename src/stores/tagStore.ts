@@ -0,0 +1,115 @@
+/**
+ * Tag Store - Zustand store for conversation tags and smart folders
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { ConversationFilter, ConversationRecord, SmartFolder, TagCount } from '../types/chat';
+import { errorMessage } from '../types/error';
+
+interface TagState {
+  tags: TagCount[];
+  smartFolders: SmartFolder[];
+  isLoading: boolean;
+  error: string | null;
+
+  // Actions
+  loadTags: () => Promise<void>;
+  conversationTags: (conversationId: string) => Promise<string[]>;
+  addTag: (conversationId: string, tag: string) => Promise<string>;
+  removeTag: (conversationId: string, tag: string) => Promise<void>;
+  conversationsByTag: (tag: string) => Promise<ConversationRecord[]>;
+  filterConversations: (filter: ConversationFilter) => Promise<ConversationRecord[]>;
+  loadSmartFolders: () => Promise<void>;
+  saveSmartFolder: (name: string, filter: ConversationFilter, id?: string) => Promise<SmartFolder>;
+  deleteSmartFolder: (id: string) => Promise<void>;
+  smartFolderConversations: (id: string) => Promise<ConversationRecord[]>;
+  clearError: () => void;
+}
+
+export const useTagStore = create<TagState>((set, get) => ({
+  tags: [],
+  smartFolders: [],
+  isLoading: false,
+  error: null,
+
+  loadTags: async () => {
+    set({ isLoading: true, error: null });
+    try {
+      const tags = await invoke<TagCount[]>('list_tags', { conversationId: null });
+      set({ tags, isLoading: false });
+    } catch (error) {
+      set({ error: errorMessage(error), isLoading: false });
+    }
+  },
+
+  conversationTags: async (conversationId: string) => {
+    const tags = await invoke<TagCount[]>('list_tags', { conversationId });
+    return tags.map((t) => t.tag);
+  },
+
+  addTag: async (conversationId: string, tag: string) => {
+    try {
+      const stored = await invoke<string>('add_tag', { conversationId, tag });
+      await get().loadTags();
+      return stored;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  removeTag: async (conversationId: string, tag: string) => {
+    try {
+      await invoke('remove_tag', { conversationId, tag });
+      await get().loadTags();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  conversationsByTag: async (tag: string) => {
+    return invoke<ConversationRecord[]>('list_conversations_by_tag', { tag });
+  },
+
+  filterConversations: async (filter: ConversationFilter) => {
+    return invoke<ConversationRecord[]>('filter_conversations', { filter });
+  },
+
+  loadSmartFolders: async () => {
+    try {
+      const smartFolders = await invoke<SmartFolder[]>('list_smart_folders');
+      set({ smartFolders });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  saveSmartFolder: async (name: string, filter: ConversationFilter, id?: string) => {
+    try {
+      const folder = await invoke<SmartFolder>('save_smart_folder', { id: id ?? null, name, filter });
+      await get().loadSmartFolders();
+      return folder;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteSmartFolder: async (id: string) => {
+    try {
+      await invoke('delete_smart_folder', { id });
+      set({ smartFolders: get().smartFolders.filter((f) => f.id !== id) });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  smartFolderConversations: async (id: string) => {
+    return invoke<ConversationRecord[]>('list_smart_folder_conversations', { id });
+  },
+
+  clearError: () => set({ error: null }),
+}));
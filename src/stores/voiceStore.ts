@@ -4,7 +4,24 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import { VoiceSettings, VoiceCommand, DEFAULT_VOICE_SETTINGS } from '../types/voice';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import {
+  VoiceSettings,
+  VoiceCommand,
+  WakeWordEvent,
+  BargeInEvent,
+  PartialTranscript,
+  UtteranceTranscript,
+  TranscriptionResult,
+  TtsProvider,
+  DEFAULT_VOICE_SETTINGS,
+} from '../types/voice';
+import { errorMessage } from '../types/error';
+
+let unlistenWake: UnlistenFn | null = null;
+let unlistenPartial: UnlistenFn | null = null;
+let unlistenUtterance: UnlistenFn | null = null;
+let unlistenBargeIn: UnlistenFn | null = null;
 
 interface VoiceState {
   settings: VoiceSettings;
@@ -15,6 +32,11 @@ interface VoiceState {
   ttsInitialized: boolean;
   availableModels: string[];
   availableVoices: Array<{ id: string; name: string; language: string; gender: string }>;
+  wakeWordActive: boolean;
+  voiceSessionId: string | null;
+  streamId: string | null;
+  partialTranscript: string;
+  utterances: UtteranceTranscript[];
   error: string | null;
 
   // Actions
@@ -28,6 +50,17 @@ interface VoiceState {
   loadAvailableVoices: () => Promise<void>;
   startListening: () => void;
   stopListening: () => void;
+  startWakeWord: () => Promise<void>;
+  stopWakeWord: () => Promise<void>;
+  startVoiceConversation: () => Promise<string | null>;
+  endVoiceConversation: () => Promise<void>;
+  startTranscriptionStream: (sampleRate: number) => Promise<void>;
+  feedTranscriptionStream: (chunk: ArrayBuffer) => Promise<void>;
+  finishTranscriptionStream: () => Promise<TranscriptionResult | null>;
+  speak: (text: string, streaming?: boolean) => Promise<void>;
+  pauseSpeech: () => Promise<void>;
+  resumeSpeech: () => Promise<void>;
+  stopSpeech: () => Promise<void>;
   addCommand: (command: VoiceCommand) => void;
   clearCommands: () => void;
   clearError: () => void;
@@ -46,6 +79,11 @@ export const useVoiceStore = create<VoiceState>((set, get) => ({
   ttsInitialized: false,
   availableModels: [],
   availableVoices: [],
+  wakeWordActive: false,
+  voiceSessionId: null,
+  streamId: null,
+  partialTranscript: '',
+  utterances: [],
   error: null,
 
   loadSettings: async () => {
@@ -56,9 +94,13 @@ export const useVoiceStore = create<VoiceState>((set, get) => ({
         enabled: number;
         stt_model: string;
         tts_voice: string;
+        tts_provider: string;
         language: string;
         wake_word: string | null;
         vad_sensitivity: number;
+        barge_in_enabled: boolean;
+        barge_in_sensitivity: number;
+        echo_suppression: boolean;
         updated_at: string;
       } | null>('get_voice_settings');
 
@@ -69,9 +111,13 @@ export const useVoiceStore = create<VoiceState>((set, get) => ({
             enabled: settings.enabled === 1,
             sttModel: settings.stt_model,
             ttsVoice: settings.tts_voice,
+            ttsProvider: settings.tts_provider as TtsProvider,
             language: settings.language,
             wakeWord: settings.wake_word ?? undefined,
             vadSensitivity: settings.vad_sensitivity,
+            bargeInEnabled: settings.barge_in_enabled,
+            bargeInSensitivity: settings.barge_in_sensitivity,
+            echoSuppression: settings.echo_suppression,
             updatedAt: settings.updated_at, // Use string from DB
           },
         });
@@ -86,14 +132,23 @@ export const useVoiceStore = create<VoiceState>((set, get) => ({
     try {
       const current = get().settings;
 
+      // Fails before saving if the cloud provider has no API key
+      if (updates.ttsProvider && updates.ttsProvider !== current.ttsProvider) {
+        await invoke('set_tts_provider', { provider: updates.ttsProvider });
+      }
+
       await invoke('update_voice_settings', {
         id: current.id,
         enabled: updates.enabled ?? current.enabled,
         sttModel: updates.sttModel ?? current.sttModel,
         ttsVoice: updates.ttsVoice ?? current.ttsVoice,
+        ttsProvider: updates.ttsProvider ?? current.ttsProvider,
         language: updates.language ?? current.language,
         wakeWord: updates.wakeWord ?? current.wakeWord ?? null,
         vadSensitivity: updates.vadSensitivity ?? current.vadSensitivity,
+        bargeInEnabled: updates.bargeInEnabled ?? current.bargeInEnabled,
+        bargeInSensitivity: updates.bargeInSensitivity ?? current.bargeInSensitivity,
+        echoSuppression: updates.echoSuppression ?? current.echoSuppression,
       });
 
       set(state => ({
@@ -124,6 +179,7 @@ export const useVoiceStore = create<VoiceState>((set, get) => ({
     set({ error: null });
     try {
       const result = await invoke<string>('init_tts', { voice: get().settings.ttsVoice });
+      await invoke('set_tts_provider', { provider: get().settings.ttsProvider });
       set({ ttsInitialized: true });
       console.log('[Voice] TTS initialized:', result);
     } catch (error) {
@@ -207,6 +263,186 @@ export const useVoiceStore = create<VoiceState>((set, get) => ({
   startListening: () => set({ isListening: true }),
   stopListening: () => set({ isListening: false }),
 
+  startWakeWord: async () => {
+    set({ error: null });
+    try {
+      if (!unlistenWake) {
+        // A detected wake word starts a new voice conversation session
+        unlistenWake = await listen<WakeWordEvent>('voice://wake', (event) => {
+          set({ isListening: true, voiceSessionId: event.payload.session_id });
+        });
+      }
+
+      const { settings } = get();
+      await invoke('start_wake_word_listening', {
+        wakeWord: settings.wakeWord ?? null,
+        language: settings.language,
+        sensitivity: settings.vadSensitivity,
+      });
+      set({ wakeWordActive: true });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  stopWakeWord: async () => {
+    try {
+      await invoke('stop_wake_word_listening');
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    } finally {
+      unlistenWake?.();
+      unlistenWake = null;
+      set({ wakeWordActive: false });
+    }
+  },
+
+  startVoiceConversation: async () => {
+    set({ error: null });
+    try {
+      if (!unlistenBargeIn) {
+        // Talking over playback stopped it; listen to what the user is saying
+        unlistenBargeIn = await listen<BargeInEvent>('voice://barge-in', (event) => {
+          if (event.payload.session_id === get().voiceSessionId) {
+            set({ isListening: true });
+          }
+        });
+      }
+
+      const sessionId = await invoke<string>('start_voice_conversation', {
+        language: get().settings.language,
+      });
+      set({ voiceSessionId: sessionId });
+      return sessionId;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      return null;
+    }
+  },
+
+  endVoiceConversation: async () => {
+    const { voiceSessionId } = get();
+    try {
+      if (voiceSessionId) {
+        await invoke('end_voice_conversation', { sessionId: voiceSessionId });
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    } finally {
+      unlistenBargeIn?.();
+      unlistenBargeIn = null;
+      set({ voiceSessionId: null, isListening: false });
+    }
+  },
+
+  startTranscriptionStream: async (sampleRate: number) => {
+    set({ error: null, partialTranscript: '', utterances: [] });
+    try {
+      if (!unlistenPartial) {
+        unlistenPartial = await listen<PartialTranscript>('stt://partial', (event) => {
+          if (event.payload.stream_id === get().streamId) {
+            set({ partialTranscript: event.payload.text });
+          }
+        });
+      }
+      if (!unlistenUtterance) {
+        unlistenUtterance = await listen<UtteranceTranscript>('stt://utterance', (event) => {
+          if (event.payload.stream_id === get().streamId) {
+            set({ utterances: [...get().utterances, event.payload] });
+          }
+        });
+      }
+
+      const streamId = await invoke<string>('transcribe_stream_start', {
+        language: get().settings.language,
+        sampleRate,
+      });
+      set({ streamId });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  feedTranscriptionStream: async (chunk: ArrayBuffer) => {
+    const { streamId } = get();
+    if (!streamId) return;
+    try {
+      await invoke('transcribe_stream_feed', {
+        streamId,
+        audioData: Array.from(new Uint8Array(chunk)),
+      });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  finishTranscriptionStream: async () => {
+    const { streamId } = get();
+    if (!streamId) return null;
+    set({ isProcessing: true });
+    try {
+      const result = await invoke<{
+        text: string;
+        confidence: number;
+        language: string;
+        duration_ms: number;
+      }>('transcribe_stream_finish', { streamId });
+
+      set({ partialTranscript: result.text });
+      return {
+        text: result.text,
+        confidence: result.confidence,
+        language: result.language,
+        durationMs: result.duration_ms,
+      };
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      return null;
+    } finally {
+      unlistenPartial?.();
+      unlistenPartial = null;
+      unlistenUtterance?.();
+      unlistenUtterance = null;
+      set({ streamId: null, isProcessing: false });
+    }
+  },
+
+  speak: async (text: string, streaming = false) => {
+    try {
+      await invoke<number>('tts_speak', {
+        text,
+        language: get().settings.language,
+        streaming,
+      });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  pauseSpeech: async () => {
+    try {
+      await invoke('tts_pause');
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  resumeSpeech: async () => {
+    try {
+      await invoke('tts_resume');
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  stopSpeech: async () => {
+    try {
+      await invoke('tts_stop');
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
   addCommand: (command) => {
     set(state => ({
       commands: [...state.commands, command],
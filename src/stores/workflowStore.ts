@@ -17,7 +17,9 @@ import type {
   FsEvent,
   ExecutionResult,
   NodePosition,
+  VariableDefinition,
 } from '../types/workflow';
+import { errorMessage } from '../types/error';
 
 interface WorkflowState {
   // Data
@@ -47,7 +49,8 @@ interface WorkflowState {
     name?: string,
     description?: string,
     entryPoint?: string,
-    isActive?: boolean
+    isActive?: boolean,
+    maxParallel?: number
   ) => Promise<void>;
   deleteWorkflow: (id: string) => Promise<void>;
   loadActiveWorkflows: () => Promise<void>;
@@ -73,6 +76,8 @@ interface WorkflowState {
 
   // Execution actions
   executeWorkflow: (id: string, input?: unknown) => Promise<ExecutionResult>;
+  /** Re-run a recorded execution, from `fromNode` with recorded upstream outputs */
+  replayExecution: (executionId: string, fromNode?: string) => Promise<ExecutionResult>;
   createExecution: (
     id: string,
     workflowId: string,
@@ -87,6 +92,12 @@ interface WorkflowState {
     error?: string
   ) => Promise<void>;
 
+  // Variable actions
+  setVariables: (id: string, variables: VariableDefinition[]) => Promise<void>;
+  getVariables: (id: string) => Promise<VariableDefinition[]>;
+  /** Problems that keep the workflow from being enabled, empty when ready */
+  validateWorkflow: (id: string) => Promise<string[]>;
+
   // Trigger actions
   registerTrigger: (
     triggerId: string,
@@ -115,7 +126,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       const workflows = await invoke<Workflow[]>('workflow_list');
       set({ workflows, loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
       throw error;
     }
   },
@@ -125,7 +136,8 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
     name: string,
     description?: string,
     entryPoint?: string,
-    isActive?: boolean
+    isActive?: boolean,
+    maxParallel?: number
   ) => {
     set({ loading: true, error: null });
     try {
@@ -142,7 +154,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       set({ loading: false });
       return workflowId;
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
       throw error;
     }
   },
@@ -152,7 +164,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       const workflow = await invoke<Workflow>('workflow_get', { id });
       return workflow;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -162,7 +174,8 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
     name?: string,
     description?: string,
     entryPoint?: string,
-    isActive?: boolean
+    isActive?: boolean,
+    maxParallel?: number
   ) => {
     set({ loading: true, error: null });
     try {
@@ -172,13 +185,14 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
         description,
         entryPoint,
         isActive,
+        maxParallel,
       });
 
       // Reload workflows
       await get().loadWorkflows();
       set({ loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
       throw error;
     }
   },
@@ -193,7 +207,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
         loading: false,
       }));
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
       throw error;
     }
   },
@@ -204,7 +218,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       const workflows = await invoke<Workflow[]>('workflow_list_active');
       set({ workflows, loading: false });
     } catch (error) {
-      set({ error: String(error), loading: false });
+      set({ error: errorMessage(error), loading: false });
       throw error;
     }
   },
@@ -233,7 +247,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       // Reload workflows
       await get().loadWorkflows();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -259,7 +273,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       // Reload workflows
       await get().loadWorkflows();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -275,7 +289,22 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       set({ executing: false });
       return result;
     } catch (error) {
-      set({ error: String(error), executing: false });
+      set({ error: errorMessage(error), executing: false });
+      throw error;
+    }
+  },
+
+  replayExecution: async (executionId: string, fromNode?: string) => {
+    set({ executing: true, error: null });
+    try {
+      const result = await invoke<ExecutionResult>('replay_workflow_execution', {
+        executionId,
+        fromNode: fromNode ?? null,
+      });
+      set({ executing: false });
+      return result;
+    } catch (error) {
+      set({ error: errorMessage(error), executing: false });
       throw error;
     }
   },
@@ -296,7 +325,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       await get().getExecutions(workflowId);
       return executionId;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -306,7 +335,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       const execution = await invoke<WorkflowExecution>('workflow_get_execution', { id });
       return execution;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -326,7 +355,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
 
       return executions;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -345,11 +374,40 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
         error,
       });
     } catch (err) {
-      set({ error: String(err) });
+      set({ error: errorMessage(err) });
       throw err;
     }
   },
 
+  // Variable actions
+  setVariables: async (id: string, variables: VariableDefinition[]) => {
+    try {
+      await invoke('workflow_set_variables', { id, variables });
+      await get().loadWorkflows();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  getVariables: async (id: string) => {
+    try {
+      return await invoke<VariableDefinition[]>('workflow_get_variables', { id });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  validateWorkflow: async (id: string) => {
+    try {
+      return await invoke<string[]>('workflow_validate', { id });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
   // Trigger actions
   registerTrigger: async (
     triggerId: string,
@@ -368,7 +426,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       // Reload triggers
       await get().listTriggers();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -381,7 +439,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
         triggers: state.triggers.filter((t) => t.triggerId !== triggerId),
       }));
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -392,7 +450,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       set({ triggers });
       return triggers;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
@@ -402,7 +460,7 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
       const count = await invoke<number>('workflow_trigger_count');
       return count;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       throw error;
     }
   },
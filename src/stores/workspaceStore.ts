@@ -0,0 +1,175 @@
+/**
+ * Workspace Store - Zustand store for workspaces scoping folders, conversations and models by project
+ */
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  IndexStatus,
+  Workspace,
+  WorkspaceChatConfig,
+  WorkspaceDefaults,
+  WorkspaceInput,
+  WorkspaceResources,
+} from '../types/workspace';
+import { errorMessage } from '../types/error';
+
+interface WorkspaceState {
+  workspaces: Workspace[];
+  active: Workspace | null;
+  resources: WorkspaceResources | null;
+  chatConfig: WorkspaceChatConfig | null;
+  indexStatus: IndexStatus | null;
+  error: string | null;
+
+  // Actions
+  loadWorkspaces: () => Promise<void>;
+  createWorkspace: (input: WorkspaceInput) => Promise<Workspace>;
+  updateWorkspace: (id: string, input: WorkspaceInput) => Promise<void>;
+  deleteWorkspace: (id: string) => Promise<void>;
+  /** Activate a workspace; null leaves every workspace */
+  setActive: (id: string | null) => Promise<void>;
+  /** Move a conversation into a workspace, or out of any with null */
+  assignConversation: (conversationId: string, workspaceId: string | null) => Promise<void>;
+  loadResources: (id: string) => Promise<void>;
+  loadDefaults: (id: string) => Promise<void>;
+  saveDefaults: (id: string, defaults: WorkspaceDefaults) => Promise<void>;
+  loadIndexStatus: (id: string) => Promise<void>;
+  /** Index changed files now instead of waiting for the background job */
+  reindex: (id: string) => Promise<void>;
+  setIgnorePatterns: (id: string, patterns: string[]) => Promise<void>;
+  clearError: () => void;
+}
+
+export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
+  workspaces: [],
+  active: null,
+  resources: null,
+  chatConfig: null,
+  indexStatus: null,
+  error: null,
+
+  loadWorkspaces: async () => {
+    try {
+      const [workspaces, active] = await Promise.all([
+        invoke<Workspace[]>('list_workspaces'),
+        invoke<Workspace | null>('get_active_workspace'),
+      ]);
+      set({ workspaces, active, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  createWorkspace: async (input: WorkspaceInput) => {
+    try {
+      const workspace = await invoke<Workspace>('create_workspace', { workspace: input });
+      await get().loadWorkspaces();
+      return workspace;
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  updateWorkspace: async (id: string, input: WorkspaceInput) => {
+    try {
+      await invoke<Workspace>('update_workspace', { id, workspace: input });
+      await get().loadWorkspaces();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  deleteWorkspace: async (id: string) => {
+    try {
+      await invoke('delete_workspace', { id });
+      set((state) => ({ resources: state.resources?.workspace.id === id ? null : state.resources }));
+      await get().loadWorkspaces();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  setActive: async (id: string | null) => {
+    try {
+      const active = await invoke<Workspace | null>('set_active_workspace', { id });
+      set({ active, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  assignConversation: async (conversationId: string, workspaceId: string | null) => {
+    try {
+      await invoke('assign_conversation_workspace', { conversationId, workspaceId });
+      const current = get().resources?.workspace.id;
+      if (current) {
+        await get().loadResources(current);
+      }
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  loadResources: async (id: string) => {
+    try {
+      const resources = await invoke<WorkspaceResources>('list_workspace_resources', { id });
+      set({ resources, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  loadDefaults: async (id: string) => {
+    try {
+      const chatConfig = await invoke<WorkspaceChatConfig>('get_workspace_defaults', { id });
+      set({ chatConfig, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  saveDefaults: async (id: string, defaults: WorkspaceDefaults) => {
+    try {
+      const chatConfig = await invoke<WorkspaceChatConfig>('set_workspace_defaults', { id, defaults });
+      set({ chatConfig, error: null });
+      await get().loadWorkspaces();
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  loadIndexStatus: async (id: string) => {
+    try {
+      const indexStatus = await invoke<IndexStatus>('get_index_status', { workspaceId: id });
+      set({ indexStatus, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+  },
+
+  reindex: async (id: string) => {
+    try {
+      await invoke('index_workspace_now', { workspaceId: id });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+    }
+    // A failed run is recorded in the status too
+    await get().loadIndexStatus(id);
+  },
+
+  setIgnorePatterns: async (id: string, patterns: string[]) => {
+    try {
+      const indexStatus = await invoke<IndexStatus>('set_index_ignore_patterns', { workspaceId: id, patterns });
+      set({ indexStatus, error: null });
+    } catch (error) {
+      set({ error: errorMessage(error) });
+      throw error;
+    }
+  },
+
+  clearError: () => set({ error: null }),
+}));
@@ -152,3 +152,27 @@ export interface AggregatedResult {
   results: Record<string, TaskResult>;
   combinedData: unknown;
 }
+
+// ============================================================================
+// Agent Runtime Health
+// ============================================================================
+
+export type SidecarStatus = 'stopped' | 'running' | 'restarting' | 'failed';
+
+/** Payload of `sidecar_health` and `sidecar://health` events */
+export interface SidecarHealth {
+  status: SidecarStatus;
+  pid: number | null;
+  startedAt: string | null;
+  restarts: number;
+  consecutiveFailures: number;
+  lastExit: string | null;
+  lastError: string | null;
+  nextRestartInSecs: number | null;
+}
+
+/** Error prefix when the runtime exited during a request */
+export const SIDECAR_CRASHED_ERROR = 'sidecar_crashed';
+
+/** Error prefix while the runtime is being restarted */
+export const SIDECAR_RESTARTING_ERROR = 'sidecar_restarting';
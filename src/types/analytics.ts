@@ -0,0 +1,112 @@
+/**
+ * Analytics Type Definitions
+ */
+
+import type { MessageFeedback } from './chat';
+
+export type AnalyticsPeriod = '7d' | '30d' | '90d' | '365d';
+
+export type AnalyticsGranularity = 'day' | 'week' | 'month';
+
+/** Counts per bucket, aligned with the report's labels */
+export interface CountSeries {
+  name: string;
+  values: number[];
+}
+
+export interface AnalyticsTotals {
+  messages: number;
+  skillRuns: number;
+  recipeRuns: number;
+  jobRuns: number;
+  /** Completed share (0-1) of finished job runs */
+  jobSuccessRate: number | null;
+}
+
+export interface PluginUsage {
+  pluginId: string;
+  memoryMb: number;
+  cpuPercent: number;
+  executionTimeMs: number;
+}
+
+export interface AnalyticsReport {
+  period: AnalyticsPeriod;
+  granularity: AnalyticsGranularity;
+  from: string;
+  to: string;
+  /** First day (YYYY-MM-DD) of each bucket */
+  labels: string[];
+  /** One series per message role */
+  messages: CountSeries[];
+  skillRuns: number[];
+  recipeRuns: number[];
+  jobRuns: number[];
+  /** Completed share (0-1) of finished job runs; null for buckets without any */
+  jobSuccessRate: Array<number | null>;
+  totals: AnalyticsTotals;
+  plugins: PluginUsage[];
+}
+
+/** Latency and usage of one provider's requests over a period */
+export interface ProviderPerformance {
+  provider: string;
+  requests: number;
+  errors: number;
+  /** Latency percentiles of successful requests; null without any */
+  p50LatencyMs: number | null;
+  p95LatencyMs: number | null;
+  promptTokens: number;
+  completionTokens: number;
+  retries: number;
+  streamed: number;
+}
+
+/** Payload of `telemetry://request` events */
+export interface RequestTelemetry {
+  id: string;
+  provider: string;
+  model?: string;
+  method: string;
+  conversationId?: string;
+  latencyMs: number;
+  promptTokens?: number;
+  completionTokens?: number;
+  retries: number;
+  streamed: boolean;
+  success: boolean;
+  error?: string;
+  createdAt: string;
+}
+
+export interface FeedbackTotals {
+  up: number;
+  down: number;
+}
+
+/** Rated message with what produced it */
+export interface FeedbackEntry extends MessageFeedback {
+  conversationTitle: string;
+  provider: string | null;
+  model: string | null;
+  /** User message the answer replied to; null when encrypted */
+  prompt: string | null;
+  /** The rated answer; null when encrypted */
+  response: string | null;
+  systemPrompt: string | null;
+  systemPromptVersion: number | null;
+  /** Whether the message belongs to a sensitive conversation */
+  encrypted: boolean;
+}
+
+/** Feedback given over a period */
+export interface FeedbackExport {
+  period: AnalyticsPeriod;
+  from: string;
+  to: string;
+  totals: FeedbackTotals;
+  /** Answers without a provider are counted under "unknown" */
+  byProvider: Record<string, FeedbackTotals>;
+  /** Newest first */
+  entries: FeedbackEntry[];
+}
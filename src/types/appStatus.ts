@@ -0,0 +1,16 @@
+/**
+ * App Status Type Definitions (system tray)
+ */
+
+export interface AppStatus {
+  schedulerRunning: boolean;
+  /** False when another instance of the app executes the jobs */
+  schedulerLeader: boolean;
+  runningJobs: number;
+  /** Executions waiting for a free slot */
+  queuedJobs: number;
+  syncEnabled: boolean;
+  syncPending: number;
+  lastSyncAt: string | null;
+  syncError: string | null;
+}
@@ -0,0 +1,121 @@
+/**
+ * Database Backup, Migration and Data Export Type Definitions
+ */
+
+export interface BackupInfo {
+  path: string;
+  sizeBytes: number;
+  createdAt: string;
+}
+
+export interface RestoreResult {
+  restoredFrom: string;
+  /** Copy of the database as it was before the restore */
+  safetyBackup: BackupInfo;
+  /** Schema version of the backup before it was migrated */
+  schemaVersion: number;
+}
+
+export interface MigrationStep {
+  version: number;
+  name: string;
+  direction: 'up' | 'down';
+}
+
+export interface AppliedMigration {
+  version: number;
+  name: string;
+  appliedAt: string;
+  executionMs: number | null;
+}
+
+export interface SchemaVersion {
+  current: number;
+  /** Newest version this build can migrate to */
+  latest: number;
+  applied: AppliedMigration[];
+  pending: MigrationStep[];
+}
+
+export interface MigrationReport {
+  fromVersion: number;
+  toVersion: number;
+  dryRun: boolean;
+  steps: MigrationStep[];
+  /** Backup taken before the migrations ran */
+  backup: string | null;
+}
+
+/** File of a data export archive, as listed in its manifest.json */
+export interface ExportedFile {
+  path: string;
+  description: string;
+  /** Fields of each record */
+  fields: string[];
+  records: number;
+}
+
+export interface ExportManifest {
+  formatVersion: number;
+  appVersion: string;
+  exportedAt: string;
+  files: ExportedFile[];
+}
+
+export interface DataExport {
+  path: string;
+  sizeBytes: number;
+  manifest: ExportManifest;
+}
+
+/** Payload of `export://progress` events */
+export interface ExportProgress {
+  file: string;
+  /** Position of the file, starting at 1 */
+  fileIndex: number;
+  totalFiles: number;
+  records: number;
+  done: boolean;
+}
+
+/** Row whose parent key is missing */
+export interface ForeignKeyViolation {
+  table: string;
+  rowid: number | null;
+  parent: string;
+}
+
+/** Statistics ANALYZE gathered for an index */
+export interface IndexStats {
+  table: string;
+  index: string;
+  rows: number;
+  /** Average rows per distinct value of the leading columns */
+  rowsPerKey: number[];
+}
+
+/** Outcome of one database health check */
+export interface HealthReport {
+  id: string;
+  checkedAt: string;
+  /** Empty when the integrity check passed */
+  integrityErrors: string[];
+  foreignKeyViolations: ForeignKeyViolation[];
+  indexes: IndexStats[];
+  pageSize: number;
+  pageCount: number;
+  freePages: number;
+  /** Empty when the database is healthy */
+  problems: string[];
+}
+
+/** Latest health report with the current file sizes in bytes */
+export interface DbHealth {
+  /** null until the first check ran */
+  report: HealthReport | null;
+  files: {
+    database: number;
+    wal: number;
+    shm: number;
+  };
+}
@@ -0,0 +1,48 @@
+/**
+ * Calendar Type Definitions (ICS subscriptions and CalDAV calendars)
+ */
+
+export type CalendarKind = 'ics' | 'caldav';
+
+export interface CalendarSource {
+  id: string;
+  name: string;
+  kind: CalendarKind;
+  url: string;
+  username: string | null;
+  enabled: boolean;
+  createdAt: string;
+  lastSyncedAt: string | null;
+  /** Error of the last failed sync */
+  lastError: string | null;
+}
+
+export interface CalendarSourceInput {
+  /** Set to update an existing calendar */
+  id?: string;
+  name: string;
+  kind: CalendarKind;
+  url: string;
+  username?: string;
+  /** CalDAV only; leave empty to keep the stored password */
+  password?: string;
+  enabled?: boolean;
+}
+
+export interface CalendarEvent {
+  id: string;
+  sourceId: string;
+  /** Name of the calendar */
+  calendar: string;
+  summary: string;
+  description: string | null;
+  location: string | null;
+  startsAt: string;
+  endsAt: string;
+  allDay: boolean;
+}
+
+/**
+ * 'today', 'tomorrow', 'week', 'month', 'YYYY-MM-DD' or 'YYYY-MM-DD..YYYY-MM-DD'
+ */
+export type EventRange = string;
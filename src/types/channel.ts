@@ -0,0 +1,26 @@
+/**
+ * Notification Channel Type Definitions (Slack, Discord and generic webhooks)
+ */
+
+export type ChannelKind = 'slack' | 'discord' | 'generic';
+
+export type MessageLevel = 'info' | 'success' | 'error';
+
+export interface NotificationChannel {
+  id: string;
+  name: string;
+  kind: ChannelKind;
+  createdAt: string;
+  lastSentAt: string | null;
+  /** Error of the last failed send */
+  lastError: string | null;
+}
+
+export interface ChannelInput {
+  /** Set to update an existing channel */
+  id?: string;
+  name: string;
+  kind: ChannelKind;
+  /** Required for new channels; leave empty to keep the stored URL */
+  url?: string;
+}
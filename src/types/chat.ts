@@ -17,6 +17,17 @@ export interface MessageMetadata {
     total: number;
   };
   finishReason?: string;
+  /** Set on messages imported from another tool */
+  importedFrom?: {
+    source: ImportFormat;
+    conversationId: string;
+    messageId: string | null;
+  };
+  /** Set on answers of a provider comparison */
+  comparison?: {
+    results: Record<string, ComparedResponse>;
+    selected: string;
+  };
 }
 
 export interface Conversation {
@@ -40,3 +51,211 @@ export interface StreamingChunk {
   done: boolean;
   error?: string;
 }
+
+// Tags and smart folders
+
+/** Conversation row as stored, without its messages */
+export interface ConversationRecord {
+  id: string;
+  title: string;
+  created_at: string;
+  updated_at: string;
+  is_sensitive: boolean;
+  /** Workspace the conversation belongs to */
+  workspace_id?: string | null;
+}
+
+export interface TagCount {
+  tag: string;
+  conversationCount: number;
+}
+
+/** Empty fields match every conversation */
+export interface ConversationFilter {
+  /** Conversations must carry every one of these tags */
+  tags?: string[];
+  /** First day of last activity, inclusive (YYYY-MM-DD, UTC) */
+  updatedFrom?: string | null;
+  /** Last day of last activity, inclusive (YYYY-MM-DD, UTC) */
+  updatedTo?: string | null;
+  /** Provider that answered at least one message */
+  provider?: string | null;
+  /** Text the title contains, ignoring case */
+  title?: string | null;
+}
+
+export interface SmartFolder {
+  id: string;
+  name: string;
+  filter: ConversationFilter;
+  createdAt: string;
+  updatedAt: string;
+}
+
+// Pinned messages and bookmarks
+
+export interface PinnedMessage {
+  messageId: string;
+  conversationId: string;
+  role: 'user' | 'assistant' | 'system';
+  content: string;
+  note: string | null;
+  /** When the message was sent */
+  createdAt: string;
+  pinnedAt: string;
+}
+
+export interface Bookmark {
+  id: string;
+  conversationId: string;
+  messageId: string;
+  label: string;
+  createdAt: string;
+}
+
+// Similar conversations
+
+export interface SimilarConversation {
+  conversation: ConversationRecord;
+  /** Cosine similarity, between 0 and 1 */
+  score: number;
+}
+
+// Conversation sharing
+
+export type ShareFormat = 'html' | 'json';
+
+export interface ShareRedaction {
+  /** Absolute and home-relative file paths */
+  stripPaths: boolean;
+  /** Provider, model and tool names */
+  stripApiNames: boolean;
+  /** Additional regexes; each match is replaced */
+  patterns: string[];
+}
+
+/** Cloud storage to upload to; fields match the backend config */
+export interface ShareStorage {
+  name: string;
+  provider: 'AwsS3' | 'GoogleCloudStorage' | 'AzureBlob';
+  bucket: string;
+  region: string | null;
+  access_key_id: string | null;
+  /** Read from the keychain when null */
+  secret_access_key: string | null;
+  endpoint_url: string | null;
+}
+
+export interface ShareOptions {
+  format: ShareFormat;
+  redaction?: ShareRedaction;
+  includeTimestamps?: boolean;
+  upload?: {
+    storage: ShareStorage;
+    /** Kept until revoked when omitted */
+    expiresInHours?: number;
+  };
+}
+
+export interface RedactionCounts {
+  secrets: number;
+  paths: number;
+  apiNames: number;
+  patterns: number;
+}
+
+export interface ConversationShare {
+  id: string;
+  conversationId: string;
+  /** Title as shared, after redaction */
+  title: string;
+  format: ShareFormat;
+  redaction: ShareRedaction;
+  redactions: RedactionCounts;
+  messageCount: number;
+  storageName: string | null;
+  objectKey: string | null;
+  url: string | null;
+  expiresAt: string | null;
+  /** When the uploaded bundle was deleted, by expiry or revocation */
+  removedAt: string | null;
+  createdAt: string;
+}
+
+export interface ShareResult {
+  share: ConversationShare;
+  /** Rendered bundle, for saving locally */
+  content: string;
+}
+
+// Imported chat history
+
+export type ImportFormat = 'chatgpt' | 'claude' | 'markdown';
+
+export type ImportStatus = 'imported' | 'skipped' | 'failed';
+
+export interface ImportedItem {
+  externalId: string;
+  title: string;
+  status: ImportStatus;
+  /** Stored conversation; for skipped items, the one imported earlier */
+  conversationId: string | null;
+  messages: number;
+  reason: string | null;
+}
+
+export interface ImportReport {
+  format: ImportFormat;
+  imported: number;
+  skipped: number;
+  failed: number;
+  /** Messages stored across the imported conversations */
+  messages: number;
+  items: ImportedItem[];
+}
+
+/** One provider's answer in a comparison */
+export interface ComparedResponse {
+  content: string;
+  latencyMs: number;
+  usage: { promptTokens: number; completionTokens: number } | null;
+  error: string | null;
+}
+
+/** Answers of several providers to one prompt, by provider */
+export interface Comparison {
+  /** Stored message; null without a conversation or when every provider failed */
+  messageId: string | null;
+  results: Record<string, ComparedResponse>;
+  selected: string | null;
+}
+
+/** One version of a conversation's system prompt; the newest is active */
+export interface SystemPromptVersion {
+  conversationId: string;
+  version: number;
+  /** null when this version cleared the prompt */
+  prompt: string | null;
+  createdAt: string;
+}
+
+/** Generation parameters; unset ones fall back to the conversation, then the provider */
+export interface GenerationParams {
+  temperature?: number | null;
+  topP?: number | null;
+  maxTokens?: number | null;
+  stop?: string[] | null;
+  seed?: number | null;
+}
+
+export type FeedbackRating = 'up' | 'down';
+
+/** Thumbs up/down and an optional comment on an assistant message */
+export interface MessageFeedback {
+  messageId: string;
+  conversationId: string;
+  rating: FeedbackRating;
+  comment: string | null;
+  createdAt: string;
+  updatedAt: string;
+}
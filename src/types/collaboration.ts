@@ -64,6 +64,42 @@ export interface TemplateVersion {
   createdAt: string;
 }
 
+export type DiffLineKind = 'equal' | 'insert' | 'delete';
+
+export interface DiffLine {
+  kind: DiffLineKind;
+  /** 1-based line in the older version, unless inserted */
+  oldLine?: number;
+  /** 1-based line in the newer version, unless deleted */
+  newLine?: number;
+  text: string;
+}
+
+/** Changed name, category or visibility */
+export interface FieldChange {
+  field: string;
+  old: string;
+  new: string;
+}
+
+export interface TemplateDiff {
+  templateId: string;
+  fromVersion: number;
+  toVersion: number;
+  lines: DiffLine[];
+  fields: FieldChange[];
+  insertions: number;
+  deletions: number;
+}
+
+export interface TemplateMerge {
+  /** Merged template; content holds conflict markers when conflicts > 0 */
+  template: Template;
+  conflicts: number;
+  /** Fields both sides changed differently; "mine" was kept */
+  conflictingFields: string[];
+}
+
 // Template Import/Export (v0.5)
 export interface ImportResult {
   success: boolean;
@@ -76,6 +112,48 @@ export interface ImportResult {
   }>;
 }
 
+// Template Bundles (.aipack)
+export type BundleItemKind = 'template' | 'skill' | 'recipe';
+
+export interface BundleItemRef {
+  kind: BundleItemKind;
+  id: string;
+}
+
+export interface BundleManifestEntry extends BundleItemRef {
+  name: string;
+  schemaVersion: number;
+  path: string;
+  dependencies: BundleItemRef[];
+}
+
+export interface BundleManifest {
+  formatVersion: number;
+  appVersion: string;
+  createdAt: string;
+  items: BundleManifestEntry[];
+}
+
+export type BundleConflictPolicy = 'skip' | 'overwrite' | 'rename';
+
+export type BundleItemStatus = 'imported' | 'overwritten' | 'renamed' | 'skipped' | 'failed';
+
+export interface BundleItemResult extends BundleItemRef {
+  name: string;
+  status: BundleItemStatus;
+  /** Id the item was stored under, when it differs from the bundled id */
+  newId?: string;
+  message?: string;
+}
+
+export interface BundleImportResult {
+  formatVersion: number;
+  items: BundleItemResult[];
+  imported: number;
+  skipped: number;
+  failed: number;
+}
+
 // Template Sharing (v0.5)
 export interface TemplateShare {
   id: number;
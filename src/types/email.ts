@@ -0,0 +1,26 @@
+/**
+ * Email Type Definitions (SMTP delivery of job and workflow output)
+ */
+
+export type SmtpSecurity = 'start_tls' | 'tls' | 'none';
+
+export interface SmtpConfig {
+  host: string;
+  port: number;
+  security: SmtpSecurity;
+  username?: string | null;
+  /** Never returned by the backend; leave empty to keep the stored one */
+  password?: string | null;
+  /** Sender, e.g. "Assistant <reports@example.com>" */
+  from: string;
+}
+
+export type EmailFormat = 'markdown' | 'html';
+
+/** `send_email` param of a scheduled job */
+export interface JobEmailDelivery {
+  to: string | string[];
+  /** Defaults to the job name and date */
+  subject?: string;
+  format?: EmailFormat;
+}
@@ -0,0 +1,37 @@
+/**
+ * Command Error Type Definitions
+ */
+
+export type ErrorKind =
+  | 'database'
+  | 'busy'
+  | 'not_found'
+  | 'invalid_input'
+  | 'permission_denied'
+  | 'io'
+  | 'sidecar'
+  | 'network'
+  | 'security'
+  | 'internal';
+
+/** Error rejected by every Tauri command */
+export interface AppError {
+  kind: ErrorKind;
+  message: string;
+  /** Structured context, such as `{ path, reason }` for file errors */
+  details: Record<string, unknown> | null;
+  /** Repeating the same call may succeed */
+  retryable: boolean;
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+}
+
+/** Message to show for a rejected command or any other thrown value */
+export function errorMessage(error: unknown): string {
+  if (isAppError(error) || error instanceof Error) {
+    return error.message;
+  }
+  return String(error);
+}
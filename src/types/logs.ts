@@ -0,0 +1,43 @@
+/**
+ * Application Log Type Definitions
+ */
+
+import type { ErrorKind } from './error';
+
+export type LogLevel = 'TRACE' | 'DEBUG' | 'INFO' | 'WARN' | 'ERROR';
+
+export interface LogEntry {
+  timestamp: string;
+  level: LogLevel;
+  target: string;
+  message: string;
+  /** Event fields other than the message */
+  fields: Record<string, unknown>;
+}
+
+export interface LogQuery {
+  /** Case-insensitive text matched against message, target and fields */
+  filter?: string;
+  /** Lowest severity included */
+  level?: LogLevel;
+  /** RFC 3339 time of the oldest entry included */
+  since?: string;
+  limit?: number;
+}
+
+export interface LogExport {
+  path: string;
+  files: number;
+  sizeBytes: number;
+}
+
+/** Finished Tauri command, as recorded for performance debugging */
+export interface CommandRecord {
+  /** Also sent to the agent runtime as `requestId` and logged with the command */
+  requestId: string;
+  command: string;
+  startedAt: string;
+  durationMs: number;
+  /** Set when the command failed */
+  errorKind: ErrorKind | null;
+}
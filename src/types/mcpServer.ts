@@ -0,0 +1,20 @@
+/**
+ * MCP Server Type Definitions (external clients using the app as a tool provider)
+ */
+
+export type McpCapability = 'read_files' | 'run_skills' | 'query_conversations';
+
+export interface McpClient {
+  id: string;
+  name: string;
+  capabilities: McpCapability[];
+  enabled: boolean;
+  createdAt: string;
+  lastConnectedAt: string | null;
+}
+
+/** Command line to put in the client's MCP server configuration */
+export interface McpLaunchConfig {
+  command: string;
+  args: string[];
+}
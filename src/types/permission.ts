@@ -44,3 +44,318 @@ export function hasPermission(
   }
   return false;
 }
+
+// Tool permission policies
+
+export type ToolDecision = 'allow' | 'ask' | 'deny';
+
+export type ToolCategory =
+  | 'file_read'
+  | 'file_write'
+  | 'shell'
+  | 'network'
+  | 'database'
+  | 'other';
+
+export type RiskTier = 'low' | 'medium' | 'high';
+
+export type TierBehavior = 'auto_run' | 'notify' | 'require_confirmation';
+
+export interface ToolPolicy {
+  tool: string;
+  category: ToolCategory;
+  tier: RiskTier;
+  decision: ToolDecision;
+  is_default: boolean;
+  notify: boolean;
+}
+
+/** Behavior per risk tier, used by tools without a stored decision */
+export interface ToolRiskSettings {
+  low: TierBehavior;
+  medium: TierBehavior;
+  high: TierBehavior;
+  confirmationTimeoutSecs: number;
+}
+
+export const DEFAULT_TOOL_RISK_SETTINGS: ToolRiskSettings = {
+  low: 'auto_run',
+  medium: 'require_confirmation',
+  high: 'require_confirmation',
+  confirmationTimeoutSecs: 120,
+};
+
+/** Payload of the `tools://confirm` event */
+export interface ToolConfirmationRequest {
+  requestId: string;
+  tool: string;
+  category: ToolCategory;
+  tier: RiskTier;
+  args: unknown;
+  timeoutSecs: number;
+}
+
+/** Payload of the `tools://notify` event */
+export interface ToolNotice {
+  tool: string;
+  category: ToolCategory;
+  tier: RiskTier;
+  args: unknown;
+}
+
+// Shell command tool
+
+export interface ShellResult {
+  invocationId: string;
+  exitCode?: number;
+  stdout: string;
+  stderr: string;
+  durationMs: number;
+  timedOut: boolean;
+  truncated: boolean;
+}
+
+/** Payload of the `shell://output` event */
+export interface ShellOutput {
+  invocationId: string;
+  stream: 'stdout' | 'stderr';
+  line: string;
+}
+
+export interface ShellRule {
+  binary: string;
+  rule: 'allow' | 'deny';
+  created_at: string;
+}
+
+export interface ShellAuditEntry {
+  id: number;
+  invocation_id: string;
+  command: string;
+  args: string[];
+  working_dir: string;
+  conversation_id?: string;
+  status: 'success' | 'failed' | 'timeout' | 'rejected';
+  exit_code?: number;
+  duration_ms: number;
+  error?: string;
+  created_at: string;
+}
+
+// Agent action audit trail
+
+export type ActionKind = 'tool_call' | 'file_write' | 'shell_command' | 'db_query' | 'git_operation' | 'plugin_request';
+
+export type ActionStatus = 'success' | 'error' | 'denied' | 'blocked';
+
+export interface ActionAuditEntry {
+  id: string;
+  kind: ActionKind;
+  /** Tool or operation name */
+  name: string;
+  conversationId: string | null;
+  /** Parameters with secrets and long values redacted */
+  params: unknown;
+  status: ActionStatus;
+  error: string | null;
+  startedAt: string;
+  finishedAt: string;
+  durationMs: number;
+}
+
+/** Unset fields match everything */
+export interface AuditFilter {
+  conversationId?: string;
+  kind?: ActionKind;
+  status?: ActionStatus;
+  name?: string;
+  /** RFC 3339 bounds on the start time */
+  since?: string;
+  until?: string;
+  limit?: number;
+  offset?: number;
+}
+
+export type AuditExportFormat = 'json' | 'csv';
+
+// File command errors
+
+/** Why a file command failed, in the `details.reason` of its `AppError` */
+export type FileErrorKind =
+  | 'not_permitted'
+  | 'not_found'
+  | 'invalid_path'
+  | 'invalid_pattern'
+  | 'unsupported_format'
+  | 'invalid_data'
+  | 'io';
+
+// Directory listings
+
+export type EntryKind = 'file' | 'dir' | 'symlink' | 'other';
+
+export interface DirEntry {
+  name: string;
+  path: string;
+  kind: EntryKind;
+  size: number;
+  modified: string | null;
+  isSymlink: boolean;
+}
+
+export interface ListOptions {
+  recursive?: boolean;
+  maxDepth?: number;
+  include?: string[];
+  exclude?: string[];
+  respectGitignore?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
+/** One page of a directory listing */
+export interface DirListing {
+  entries: DirEntry[];
+  total: number;
+  nextOffset: number | null;
+}
+
+// Binary file access
+
+/** Byte range of a file; `data` is base64 */
+export interface FileChunk {
+  path: string;
+  offset: number;
+  data: string;
+  len: number;
+  totalSize: number;
+  eof: boolean;
+  mimeType: string;
+}
+
+export interface FileStream {
+  streamId: string;
+  path: string;
+  totalSize: number;
+  mimeType: string;
+}
+
+/** Payload of `files://chunk` events */
+export interface FileStreamChunk {
+  streamId: string;
+  offset: number;
+  data: string;
+  eof: boolean;
+  error: string | null;
+}
+
+// Code search
+
+export interface SearchOptions {
+  literal?: boolean;
+  caseInsensitive?: boolean;
+  include?: string[];
+  exclude?: string[];
+  respectGitignore?: boolean;
+  maxResults?: number;
+  contextLines?: number;
+}
+
+export interface SearchMatch {
+  path: string;
+  relativePath: string;
+  lineNumber: number;
+  column: number;
+  line: string;
+  matchStart: number;
+  matchEnd: number;
+  before: string[];
+  after: string[];
+}
+
+export interface SearchResult {
+  matches: SearchMatch[];
+  filesSearched: number;
+  filesSkipped: number;
+  truncated: boolean;
+}
+
+// Document text extraction
+
+export type DocumentFormat = 'pdf' | 'docx' | 'xlsx';
+
+export interface DocumentPage {
+  /** 1-based page number */
+  number: number;
+  text: string;
+}
+
+export interface DocumentSheet {
+  name: string;
+  rows: string[][];
+  /** Rows past the extraction limit were dropped */
+  truncated: boolean;
+}
+
+export interface ExtractedDocument {
+  path: string;
+  format: DocumentFormat;
+  /** PDF and DOCX documents */
+  pages?: DocumentPage[];
+  /** XLSX workbooks */
+  sheets?: DocumentSheet[];
+}
+
+// Image OCR
+
+export interface OcrSettings {
+  /** Recognize the text of image attachments automatically */
+  autoOcrAttachments: boolean;
+  /** Tesseract language codes, e.g. 'eng' or 'eng+kor' */
+  defaultLanguage: string;
+  /** Tesseract binary; 'tesseract' on the PATH when unset */
+  tesseractPath?: string | null;
+}
+
+export interface OcrResult {
+  path: string;
+  language: string;
+  text: string;
+}
+
+export type AttachmentKind = 'image' | 'document' | 'text' | 'other';
+
+export interface AttachmentText {
+  path: string;
+  kind: AttachmentKind;
+  /** Missing for binary files, and for images while automatic OCR is off */
+  text: string | null;
+  /** The text was recognized from an image */
+  ocr: boolean;
+}
+
+// Guardrails
+
+export type GuardrailAction = 'allow' | 'redact' | 'block';
+
+export interface GuardrailPolicy {
+  enabled: boolean;
+  secrets: GuardrailAction;
+  promptInjection: GuardrailAction;
+  scanPrompts: boolean;
+  scanToolResults: boolean;
+}
+
+export interface GuardrailEvent {
+  id: string;
+  conversationId: string | null;
+  direction: 'prompt' | 'tool_result';
+  /** "chat", or the tool whose result was scanned */
+  source: string;
+  kind: 'secret' | 'prompt_injection';
+  rule: string;
+  action: GuardrailAction;
+  /** Masked excerpt of the matched text */
+  excerpt: string;
+  createdAt: string;
+}
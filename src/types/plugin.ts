@@ -22,6 +22,18 @@ export interface PluginManifest {
   main: string;
   permissions: PluginPermission[];
   apiVersion: string;
+  tools?: PluginToolDef[];
+}
+
+/** Agent tool declared by a plugin; offered as `plugin_<id>_<name>` */
+export interface PluginToolDef {
+  name: string;
+  description: string;
+  inputSchema?: Record<string, unknown>;
+  /** WASM export to call; defaults to the tool name */
+  function?: string;
+  /** API namespaces the tool uses: 'fs', 'http', 'db' or 'system' */
+  requires?: string[];
 }
 
 export type PluginPermission =
@@ -37,3 +49,103 @@ export interface PluginCreateInput {
   manifest: PluginManifest;
   permissions: PluginPermission[];
 }
+
+// ============================================================================
+// Declarative panels
+// ============================================================================
+
+export interface PanelButton {
+  action: string;
+  label: string;
+}
+
+export type FieldKind = 'text' | 'textarea' | 'number' | 'checkbox' | 'select';
+
+export interface FormField {
+  name: string;
+  label: string;
+  kind?: FieldKind;
+  required?: boolean;
+  options?: string[];
+  default?: unknown;
+}
+
+export interface TableColumn {
+  key: string;
+  label: string;
+}
+
+export type PanelBlock =
+  | { type: 'markdown'; text: string }
+  | { type: 'form'; fields: FormField[]; submit: PanelButton }
+  | { type: 'table'; columns: TableColumn[]; rows: Record<string, unknown>[]; rowAction?: PanelButton | null }
+  | { type: 'buttons'; buttons: PanelButton[] };
+
+/** UI panel declared by a plugin and rendered by the app */
+export interface PluginPanel {
+  id: string;
+  title: string;
+  blocks: PanelBlock[];
+}
+
+export interface PanelActionResult {
+  /** Replacement for the panel the action came from */
+  panel?: PluginPanel | null;
+  message?: string | null;
+}
+
+// ============================================================================
+// Message bus
+// ============================================================================
+
+export interface DeliveryOptions {
+  /** Keep the message in the outbox until acknowledged */
+  persistent?: boolean;
+  /** Redeliver the message until the recipient acknowledges it */
+  requireAck?: boolean;
+}
+
+export interface PluginEnvelope {
+  id: string;
+  topic: string | null;
+  from: string;
+  to: string;
+  method: string;
+  params: unknown;
+  timestamp: number;
+  attempts: number;
+  persistent: boolean;
+  requireAck: boolean;
+}
+
+export interface PluginDeadLetter extends PluginEnvelope {
+  reason: string;
+}
+
+export interface SendReceipt {
+  queued: string[];
+  deadLettered: string[];
+}
+
+/** Attempted file access outside a plugin's mounted directories */
+export interface SandboxViolation {
+  plugin_id: string;
+  path: string;
+  access: 'read' | 'write';
+  reason: string;
+  timestamp: number;
+}
+
+/** Memory and CPU use of a running plugin at one point in time */
+export interface ResourceSample {
+  /** Milliseconds since the Unix epoch */
+  timestamp: number;
+  memoryBytes: number;
+  cpuPercent: number;
+}
+
+/** Payload of the `plugins://suspended` event */
+export interface PluginSuspension {
+  id: string;
+  reason: string;
+}
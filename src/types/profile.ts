@@ -0,0 +1,18 @@
+/**
+ * Profile Type Definitions (local profiles with separate data stores)
+ */
+
+export type ProfileRole = 'personal' | 'work' | 'other';
+
+export interface Profile {
+  id: string;
+  name: string;
+  role: ProfileRole;
+  createdAt: string;
+}
+
+export interface ProfileList {
+  /** Id of the active profile */
+  active: string;
+  profiles: Profile[];
+}
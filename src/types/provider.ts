@@ -1,6 +1,6 @@
 // Provider Types
 
-export type ProviderType = 'openai' | 'anthropic' | 'ollama';
+export type ProviderType = 'openai' | 'anthropic' | 'ollama' | 'openai-compatible';
 
 export interface ProviderConfig {
   type: ProviderType;
@@ -75,3 +75,63 @@ export const AVAILABLE_MODELS: ModelInfo[] = [
 export function getModelsByProvider(provider: ProviderType): ModelInfo[] {
   return AVAILABLE_MODELS.filter((m) => m.provider === provider);
 }
+
+// Provider profiles stored by the backend
+
+export type ProviderKind = 'anthropic' | 'openai' | 'gemini' | 'ollama' | 'openai-compatible';
+
+/** What a model supports, as far as its server says */
+export interface ModelCapabilities {
+  contextWindow?: number | null;
+  vision?: boolean | null;
+}
+
+/** Outcome of probing a provider's model list endpoint */
+export interface EndpointProbe {
+  reachable: boolean;
+  latencyMs: number;
+  models: string[];
+  /** Detected capabilities, by model; models without metadata are left out */
+  capabilities: Record<string, ModelCapabilities>;
+  error: string | null;
+}
+
+/** Generation parameters applied when a request sets none */
+export interface GenerationDefaults {
+  temperature?: number | null;
+  topP?: number | null;
+  maxTokens?: number | null;
+}
+
+export interface ProviderProfile {
+  id: string;
+  name: string;
+  kind: ProviderKind;
+  /** Overrides the kind's public endpoint */
+  baseUrl: string | null;
+  models: string[];
+  defaultModel: string | null;
+  defaults: GenerationDefaults;
+  /** Detected model capabilities of an OpenAI-compatible server, by model */
+  capabilities: Record<string, ModelCapabilities>;
+  enabled: boolean;
+  /** Whether an API key is stored in the keychain */
+  hasApiKey: boolean;
+  createdAt: string;
+  updatedAt: string;
+}
+
+/** Fields of a profile to create (no id) or update */
+export interface ProviderProfileInput {
+  id?: string;
+  name: string;
+  kind: ProviderKind;
+  baseUrl?: string | null;
+  /** Filled from the provider when empty and a new key is given */
+  models?: string[];
+  defaultModel?: string | null;
+  defaults?: GenerationDefaults;
+  enabled?: boolean;
+  /** New API key, checked before saving; the stored key is kept when unset */
+  apiKey?: string;
+}
@@ -0,0 +1,14 @@
+/**
+ * Quick Ask Type Definitions
+ */
+
+export interface QuickAskReply {
+  conversationId: string;
+  content: string;
+}
+
+export interface QuickAskTurn {
+  prompt: string;
+  reply: string | null;
+  error: string | null;
+}
@@ -10,13 +10,24 @@ export interface Recipe {
   steps: RecipeStep[];
   variables?: Record<string, unknown>;
   is_builtin: boolean;
+  /** Extracted from a conversation and not saved by the user yet */
+  is_draft?: boolean;
   created_at: string;
   updated_at: string;
 }
 
 export interface RecipeStep {
   id: string;
-  type: 'prompt' | 'tool_call' | 'condition' | 'loop' | 'parallel';
+  type:
+    | 'prompt'
+    | 'tool_call'
+    | 'condition'
+    | 'loop'
+    | 'parallel'
+    | 'http_request'
+    | 'file_read'
+    | 'file_write'
+    | 'wait';
   name: string;
   description?: string;
 
@@ -39,6 +50,20 @@ export interface RecipeStep {
   // For 'parallel' type
   parallelSteps?: RecipeStep[];
 
+  // For 'http_request' type; url, header values and body are templated
+  method?: 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE' | 'HEAD';
+  url?: string;
+  headers?: Record<string, string>;
+  body?: string | Record<string, unknown>;
+
+  // For 'file_read' and 'file_write' types, inside permitted folders
+  path?: string;
+  content?: string;
+  append?: boolean;
+
+  // For 'wait' type
+  durationMs?: number;
+
   // Common
   outputVariable?: string;
   onError?: 'continue' | 'abort' | 'retry';
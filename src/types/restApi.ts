@@ -0,0 +1,18 @@
+/**
+ * REST API Type Definitions (local HTTP API for external automation)
+ */
+
+export interface ApiConfig {
+  enabled: boolean;
+  port: number;
+  /** Listen on all interfaces instead of localhost only */
+  allowRemote: boolean;
+  rateLimitPerMinute: number;
+}
+
+export interface ApiStatus {
+  running: boolean;
+  /** Address the server listens on, while running */
+  address: string | null;
+  hasToken: boolean;
+}
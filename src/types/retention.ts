@@ -0,0 +1,22 @@
+/**
+ * Data Retention Type Definitions
+ */
+
+/** Kind of stored history a retention rule applies to */
+export type RetentionEntity = 'messages' | 'job_executions' | 'recipe_executions' | 'logs';
+
+export interface RetentionPolicy {
+  entity: RetentionEntity;
+  /** Records older than this many days are deleted; null keeps them all */
+  maxAgeDays: number | null;
+}
+
+/** What a rule deletes, or would delete */
+export interface RetentionImpact {
+  entity: RetentionEntity;
+  maxAgeDays: number | null;
+  cutoff: string | null;
+  count: number;
+  /** Timestamp of the oldest affected record */
+  oldest: string | null;
+}
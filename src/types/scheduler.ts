@@ -2,8 +2,12 @@
  * Scheduler Type Definitions
  */
 
-export type JobType = 'skill' | 'recipe' | 'prompt' | 'system';
+export type JobType = 'skill' | 'recipe' | 'prompt' | 'system' | 'plugin' | 'workflow' | 'digest';
 export type ExecutionStatus = 'running' | 'completed' | 'failed' | 'cancelled';
+/** What to do when a job comes due while its previous run is still going */
+export type OverlapPolicy = 'skip' | 'queue' | 'run';
+/** Paused and snoozed jobs stay enabled but skip their runs */
+export type JobStatus = 'active' | 'paused' | 'snoozed' | 'disabled';
 
 export interface JobConfig {
   target: string;
@@ -21,6 +25,16 @@ export interface CronJob {
   nextRun?: string;
   createdAt: string;
   updatedAt: string;
+  pluginId?: string;
+  allowOverlap: OverlapPolicy;
+  /** Higher-priority jobs get a free execution slot first */
+  priority: number;
+  status: JobStatus;
+  paused: boolean;
+  /** Runs before this time are skipped, then the job resumes */
+  snoozedUntil?: string;
+  /** Runs skipped while the job was snoozed */
+  skippedRuns: number;
 }
 
 export interface JobExecution {
@@ -31,6 +45,19 @@ export interface JobExecution {
   error?: string;
   startedAt: string;
   completedAt?: string;
+  /** Set for jobs that email their output */
+  deliveryStatus?: 'sent' | 'failed';
+  deliveryError?: string;
+}
+
+/** Line an execution logged while running, emitted on `job://log/{executionId}` */
+export interface ExecutionLogLine {
+  execution_id: string;
+  /** Position within the execution, starting at 1 */
+  seq: number;
+  level: 'info' | 'warn' | 'error';
+  message: string;
+  created_at: string;
 }
 
 export interface JobCreateInput {
@@ -40,6 +67,8 @@ export interface JobCreateInput {
   jobType: JobType;
   config: JobConfig;
   enabled?: boolean;
+  allowOverlap?: OverlapPolicy;
+  priority?: number;
 }
 
 export interface JobUpdateInput {
@@ -48,6 +77,19 @@ export interface JobUpdateInput {
   schedule?: string;
   config?: JobConfig;
   enabled?: boolean;
+  allowOverlap?: OverlapPolicy;
+  priority?: number;
+}
+
+/**
+ * Cron expression parsed from a natural-language schedule such as
+ * "every weekday at 9am" or "매주 월요일 오전 9시". Times are UTC.
+ */
+export interface ParsedSchedule {
+  cron: string;
+  description: string;
+  /** Next runs as ISO timestamps */
+  nextRuns: string[];
 }
 
 // Preset schedules
@@ -66,4 +108,7 @@ export const JOB_TYPE_LABELS: Record<JobType, string> = {
   recipe: 'Execute Recipe',
   prompt: 'Custom Prompt',
   system: 'System Task',
+  plugin: 'Plugin Task',
+  workflow: 'Workflow Trigger',
+  digest: 'Activity Digest',
 };
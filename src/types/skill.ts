@@ -2,12 +2,19 @@
  * Skill type definitions
  */
 
+/** JSON Schema document */
+export type JsonSchema = Record<string, unknown>;
+
 export interface Skill {
   id: string;
   name: string;
   description: string;
   prompt: string;
   tools: string[]; // JSON array of tool names
+  /** JSON Schema for the skill's input, used to generate its form */
+  inputSchema?: JsonSchema;
+  /** JSON Schema the skill's structured output must match */
+  outputSchema?: JsonSchema;
   created_at: string;
   updated_at: string;
 }
@@ -18,6 +25,8 @@ export interface SkillCreateInput {
   description: string;
   prompt: string;
   tools: string[];
+  inputSchema?: JsonSchema;
+  outputSchema?: JsonSchema;
 }
 
 export interface SkillUpdateInput {
@@ -26,6 +35,8 @@ export interface SkillUpdateInput {
   description: string;
   prompt: string;
   tools: string[];
+  inputSchema?: JsonSchema;
+  outputSchema?: JsonSchema;
 }
 
 export const SKILL_LIMITS = {
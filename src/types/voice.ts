@@ -2,14 +2,23 @@
  * Voice Type Definitions
  */
 
+export type TtsProvider = 'local' | 'openai' | 'elevenlabs';
+
 export interface VoiceSettings {
   id: string;
   enabled: boolean;
   sttModel: string;
   ttsVoice: string;
+  ttsProvider: TtsProvider;
   language: string;
   wakeWord?: string;
   vadSensitivity: number;
+  /** Stop speech playback when the user starts talking */
+  bargeInEnabled: boolean;
+  /** Speech threshold for barge-in (0 = very sensitive, 1 = less sensitive) */
+  bargeInSensitivity: number;
+  /** Subtract speech playback from the microphone during barge-in detection */
+  echoSuppression: boolean;
   updatedAt: string;
 }
 
@@ -22,6 +31,33 @@ export interface VoiceCommand {
   timestamp: string;
 }
 
+export interface WakeWordEvent {
+  wake_word: string;
+  transcript: string;
+  session_id: string;
+  detected_at: string;
+}
+
+/** Payload of `voice://barge-in` events */
+export interface BargeInEvent {
+  session_id: string;
+  detected_at: string;
+}
+
+export interface PartialTranscript {
+  stream_id: string;
+  text: string;
+  duration_ms: number;
+}
+
+/** Payload of `stt://utterance` events, one per finished utterance */
+export interface UtteranceTranscript {
+  stream_id: string;
+  start_ms: number;
+  end_ms: number;
+  text: string;
+}
+
 export interface TranscriptionResult {
   text: string;
   confidence: number;
@@ -39,6 +75,10 @@ export const DEFAULT_VOICE_SETTINGS: Omit<VoiceSettings, 'id' | 'updatedAt'> = {
   enabled: false,
   sttModel: 'base',
   ttsVoice: 'default',
+  ttsProvider: 'local',
   language: 'en',
   vadSensitivity: 0.5,
+  bargeInEnabled: true,
+  bargeInSensitivity: 0.7,
+  echoSuppression: true,
 };
@@ -29,10 +29,23 @@ export interface NodeConnection {
   condition?: string;
 }
 
+/** Variable available to node expressions and templates as `$name` */
+export interface VariableDefinition {
+  name: string;
+  valueType: PortType;
+  description?: string;
+  /** Value of a plain variable */
+  default?: unknown;
+  /** Keychain entry holding the value of a secret */
+  secret?: string;
+}
+
 export interface WorkflowDefinition {
   entryPoint: string;
   nodes: Record<string, WorkflowNode>;
   connections: NodeConnection[];
+  maxParallel?: number;
+  variables?: VariableDefinition[];
 }
 
 export enum ExecutionStatus {
@@ -63,6 +76,10 @@ export interface WorkflowExecution {
   completedAt?: string;
   result?: unknown;
   error?: string;
+  /** Input the execution started with */
+  input?: unknown;
+  /** Execution this one replays */
+  replayOf?: string;
 }
 
 // ============================================================================
@@ -77,14 +94,60 @@ export interface NodeContext {
 }
 
 export type NodeResult =
-  | { type: 'success'; output: unknown; nextNode?: string }
+  | { type: 'success'; output: unknown; nextNode?: string; port?: string }
   | { type: 'failure'; error: string };
 
+export type PortType = 'any' | 'string' | 'number' | 'boolean' | 'object' | 'array';
+
+export interface RetryOptions {
+  maxAttempts: number;
+  backoffMs: number;
+  backoffMultiplier: number;
+}
+
+export type NodeRunStatus = 'running' | 'retrying' | 'completed' | 'failed';
+
+/** Emitted on `workflow://node` while a workflow runs */
+export interface NodeExecutionRecord {
+  executionId: string;
+  workflowId: string;
+  nodeId: string;
+  nodeType: string;
+  branch: string;
+  status: NodeRunStatus;
+  attempt: number;
+  startedAt: string;
+  finishedAt?: string;
+  durationMs?: number;
+  /** Value the node received */
+  input?: unknown;
+  port?: string;
+  output?: unknown;
+  error?: string;
+}
+
+export type ErrorPolicy = 'fail' | 'continue' | 'route';
+
+export type JoinMode = 'all' | 'any';
+
+export interface BranchRecord {
+  id: string;
+  parent?: string;
+  status: 'running' | 'completed' | 'failed' | 'cancelled';
+  nodes: string[];
+  startedAt: string;
+  finishedAt?: string;
+  durationMs?: number;
+}
+
 export interface ExecutionResult {
+  executionId: string;
   success: boolean;
   output: unknown;
   executedNodes: string[];
   error?: string;
+  records: NodeExecutionRecord[];
+  branches: BranchRecord[];
 }
 
 // ============================================================================
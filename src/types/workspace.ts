@@ -0,0 +1,87 @@
+/**
+ * Workspace Type Definitions (projects grouping folders, conversations and a knowledge base)
+ */
+
+import type { ConversationRecord } from './chat';
+import type { FolderPermission } from './permission';
+
+export interface Workspace {
+  id: string;
+  name: string;
+  description: string | null;
+  /** Knowledge base searched by default in the workspace's chats */
+  knowledgeBase: string | null;
+  provider: string | null;
+  model: string | null;
+  /** Ids of the folder permissions belonging to the workspace */
+  folderIds: string[];
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface WorkspaceInput {
+  name: string;
+  description?: string | null;
+  knowledgeBase?: string | null;
+  provider?: string | null;
+  model?: string | null;
+  folderIds?: string[];
+}
+
+export interface WorkspaceResources {
+  workspace: Workspace;
+  folders: FolderPermission[];
+  conversations: ConversationRecord[];
+}
+
+/** Chat defaults of a workspace; unset fields keep the app's behavior */
+export interface WorkspaceDefaults {
+  systemPrompt?: string | null;
+  provider?: string | null;
+  model?: string | null;
+  temperature?: number | null;
+  /** Only these tools may run; every tool when unset */
+  toolAllowlist?: string[] | null;
+}
+
+export interface WorkspaceChatConfig {
+  workspaceId: string;
+  defaults: WorkspaceDefaults;
+  /** How the defaults combine with a chat request's own choices */
+  precedence: string[];
+}
+
+/** Outcome of one knowledge base indexing run */
+export interface IndexRun {
+  scanned: number;
+  added: number;
+  updated: number;
+  unchanged: number;
+  removed: number;
+  /** Binary, oversized or non-UTF-8 files */
+  skipped: number;
+  chunksEmbedded: number;
+}
+
+export interface IndexStatus {
+  workspaceId: string;
+  documents: number;
+  chunks: number;
+  lastStartedAt: string | null;
+  lastIndexedAt: string | null;
+  lastRun: IndexRun | null;
+  lastError: string | null;
+  /** Globs skipped on top of .gitignore files and the defaults */
+  ignorePatterns: string[];
+}
+
+export interface KnowledgeHit {
+  path: string;
+  startLine: number;
+  endLine: number;
+  /** Definition the passage belongs to in source files, as `Parser::parse` */
+  symbol: string | null;
+  symbolKind: string | null;
+  content: string;
+  score: number;
+}